@@ -0,0 +1,148 @@
+//! Allocation Benchmark for Per-Query Arenas
+//!
+//! Counts heap allocations made while grouping rows, comparing per-row string keys
+//! (the previous hash aggregate strategy) against keys encoded into a reused buffer
+//! and interned in a `QueryArena`, then reports the allocations of a GROUP BY heavy
+//! query run end to end.
+//!
+//! Run with `cargo bench --bench arena_allocation_bench`.
+
+use prism::common::allocator::QueryArena;
+use prism::execution::hash_table::encode_key_value;
+use prism::{Database, PrismDBResult, Value};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// System allocator wrapper that counts allocation calls
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ROWS: usize = 200_000;
+const GROUPS: usize = 1_000;
+/// In-memory tables hold at most 1024 rows, so the SQL query runs on a smaller table
+const TABLE_ROWS: usize = 1_000;
+const TABLE_GROUPS: usize = 100;
+
+/// Run a closure and return its result with the allocations and time it took
+fn measure<T>(f: impl FnOnce() -> T) -> (T, usize, f64) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before, elapsed)
+}
+
+fn sample_rows() -> Vec<[Value; 2]> {
+    (0..ROWS)
+        .map(|i| {
+            [
+                Value::Varchar(format!("region-{}", i % 10)),
+                Value::Integer(((i / 10) % (GROUPS / 10)) as i32),
+            ]
+        })
+        .collect()
+}
+
+/// Previous strategy: a joined string key per row
+fn group_with_string_keys(rows: &[[Value; 2]]) -> usize {
+    let mut groups: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let key = row.iter().map(|v| v.to_string()).collect::<Vec<_>>().join("|");
+        let next = groups.len();
+        groups.entry(key).or_insert(next);
+    }
+    groups.len()
+}
+
+/// Arena strategy: encode into a reused buffer, copy into the arena once per group
+fn group_with_arena_keys(rows: &[[Value; 2]], arena: &QueryArena) -> usize {
+    let mut groups: HashMap<&[u8], usize> = HashMap::new();
+    let mut key = Vec::new();
+    for row in rows {
+        key.clear();
+        for value in row {
+            encode_key_value(value, &mut key);
+        }
+        if !groups.contains_key(key.as_slice()) {
+            let next = groups.len();
+            groups.insert(arena.alloc_bytes(&key), next);
+        }
+    }
+    groups.len()
+}
+
+fn grouping_database() -> PrismDBResult<Database> {
+    let mut db = Database::new_in_memory()?;
+    db.execute("CREATE TABLE events (region VARCHAR, bucket INTEGER, amount DOUBLE)")?;
+    let values: Vec<String> = (0..TABLE_ROWS)
+        .map(|row| format!("('region-{}', {}, {}.5)", row % 10, (row / 10) % 10, row % 97))
+        .collect();
+    db.execute(&format!("INSERT INTO events VALUES {}", values.join(", ")))?;
+    Ok(db)
+}
+
+fn main() -> PrismDBResult<()> {
+    println!("Grouping {} rows into {} groups", ROWS, GROUPS);
+    let rows = sample_rows();
+
+    let (groups, string_allocs, string_ms) = measure(|| group_with_string_keys(black_box(&rows)));
+    assert_eq!(groups, GROUPS);
+    println!(
+        "  string keys: {:>9} allocations {:>8.2} ms",
+        string_allocs, string_ms
+    );
+
+    let arena = QueryArena::new();
+    let (groups, arena_allocs, arena_ms) =
+        measure(|| group_with_arena_keys(black_box(&rows), &arena));
+    assert_eq!(groups, GROUPS);
+    println!(
+        "  arena keys:  {:>9} allocations {:>8.2} ms ({} arena blocks, {} key bytes)",
+        arena_allocs,
+        arena_ms,
+        arena.block_count(),
+        arena.allocated_bytes()
+    );
+    println!(
+        "  {:.1}x fewer allocations",
+        string_allocs as f64 / arena_allocs.max(1) as f64
+    );
+
+    let db = grouping_database()?;
+    let sql = "SELECT region, bucket, COUNT(*), SUM(amount), AVG(amount) \
+               FROM events GROUP BY region, bucket";
+    let (result, query_allocs, query_ms) = measure(|| db.query(sql));
+    assert_eq!(result?.row_count(), TABLE_GROUPS);
+    println!("\n{}", sql);
+    println!(
+        "  end to end: {:>9} allocations {:>8.2} ms ({:.2} per input row)",
+        query_allocs,
+        query_ms,
+        query_allocs as f64 / TABLE_ROWS as f64
+    );
+
+    Ok(())
+}
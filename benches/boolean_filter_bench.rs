@@ -0,0 +1,64 @@
+//! Boolean Column Filter Benchmark
+//!
+//! Filters a table on a bare boolean column (`WHERE active`), which selects
+//! rows straight from the column's data and validity buffers, and on the
+//! equivalent comparison (`WHERE active = true`), which evaluates an
+//! expression per chunk and reads every result back as a value.
+//!
+//! Run with `cargo bench --bench boolean_filter_bench`.
+
+use prism::{Database, PrismDBResult};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ROWS: usize = 200_000;
+const BATCH: usize = 10_000;
+const RUNS: usize = 5;
+
+fn users_database() -> PrismDBResult<Database> {
+    let mut db = Database::new_in_memory()?;
+    db.execute("CREATE TABLE users (id INTEGER, active BOOLEAN)")?;
+    for start in (0..ROWS).step_by(BATCH) {
+        let values: Vec<String> = (start..start + BATCH)
+            .map(|id| {
+                let active = match id % 7 {
+                    0 => "NULL",
+                    1 | 3 | 4 => "true",
+                    _ => "false",
+                };
+                format!("({}, {})", id, active)
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO users VALUES {}", values.join(", ")))?;
+    }
+    Ok(db)
+}
+
+/// Best time of several runs of `sql`, in milliseconds
+fn best_of(db: &Database, sql: &str) -> PrismDBResult<(usize, f64)> {
+    let mut best = f64::MAX;
+    let mut rows = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let result = black_box(db.query(sql)?.collect()?);
+        best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+        rows = result.rows.len();
+    }
+    Ok((rows, best))
+}
+
+fn main() -> PrismDBResult<()> {
+    let db = users_database()?;
+    println!("Filtering {} rows on a boolean column", ROWS);
+
+    for (label, sql) in [
+        ("bare column", "SELECT id FROM users WHERE active"),
+        ("comparison", "SELECT id FROM users WHERE active = true"),
+        ("negated column", "SELECT id FROM users WHERE NOT active"),
+        ("negated comparison", "SELECT id FROM users WHERE active = false"),
+    ] {
+        let (rows, ms) = best_of(&db, sql)?;
+        println!("  {:<20} {:>8} rows  {:>8.2} ms", label, rows, ms);
+    }
+    Ok(())
+}
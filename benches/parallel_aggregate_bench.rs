@@ -0,0 +1,81 @@
+//! Parallel Hash Aggregate Benchmark
+//!
+//! Groups rows into many groups with COUNT/SUM/AVG/MIN/MAX, once on a single
+//! thread and once with every worker thread building its own partial group
+//! table that is merged at the end. The input is a UNION ALL of several
+//! tables, so the aggregate receives one chunk per table to divide up.
+//!
+//! Run with `cargo bench --bench parallel_aggregate_bench`.
+
+use prism::{Database, PrismDBResult};
+use std::hint::black_box;
+use std::time::Instant;
+
+const PARTS: usize = 64;
+const ROWS_PER_PART: usize = 1_000;
+const GROUPS: usize = 2_000;
+const RUNS: usize = 5;
+
+fn sales_database() -> PrismDBResult<Database> {
+    let mut db = Database::new_in_memory()?;
+    for part in 0..PARTS {
+        db.execute(&format!(
+            "CREATE TABLE sales_{} (customer INTEGER, amount INTEGER, price DOUBLE)",
+            part
+        ))?;
+        let values: Vec<String> = (0..ROWS_PER_PART)
+            .map(|i| {
+                let row = part * ROWS_PER_PART + i;
+                format!("({}, {}, {}.25)", (row * 31) % GROUPS, row % 997, row % 89)
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO sales_{} VALUES {}", part, values.join(", ")))?;
+    }
+    Ok(db)
+}
+
+/// Best time of several runs of `sql`, in milliseconds
+fn best_of(db: &Database, sql: &str) -> PrismDBResult<(usize, f64)> {
+    let mut best = f64::MAX;
+    let mut rows = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let result = black_box(db.query(sql)?);
+        best = best.min(start.elapsed().as_secs_f64() * 1000.0);
+        rows = result.row_count();
+    }
+    Ok((rows, best))
+}
+
+fn main() -> PrismDBResult<()> {
+    let mut db = sales_database()?;
+    let parts: Vec<String> = (0..PARTS).map(|part| format!("SELECT * FROM sales_{}", part)).collect();
+    let sql = format!(
+        "SELECT customer, COUNT(*), SUM(amount), AVG(price), MIN(amount), MAX(amount) \
+         FROM ({}) AS sales GROUP BY customer",
+        parts.join(" UNION ALL ")
+    );
+    println!(
+        "Aggregating {} rows into {} groups",
+        PARTS * ROWS_PER_PART,
+        GROUPS
+    );
+
+    db.execute("SET threads = 1")?;
+    let (sequential_rows, sequential_ms) = best_of(&db, &sql)?;
+    println!("  1 thread:   {:>8.2} ms", sequential_ms);
+
+    let threads = num_cpus::get();
+    db.execute(&format!("SET threads = {}", threads))?;
+    let (parallel_rows, parallel_ms) = best_of(&db, &sql)?;
+    assert_eq!(parallel_rows, sequential_rows);
+    assert_eq!(parallel_rows, GROUPS);
+    println!(
+        "  {} threads: {:>8.2} ms ({:.2}x)",
+        threads,
+        parallel_ms,
+        sequential_ms / parallel_ms
+    );
+
+    Ok(())
+}
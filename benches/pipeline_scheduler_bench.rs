@@ -0,0 +1,96 @@
+//! Pipeline Scheduler Benchmark
+//!
+//! Runs a query made of many independent joins, combined with UNION ALL and
+//! aggregated at the end. Every join side is a pipeline of its own, so with
+//! the scheduler the scans and joins of all branches run at once on the
+//! worker pool; on a single thread they run one after the other. Reports
+//! queries per second for both.
+//!
+//! Run with `cargo bench --bench pipeline_scheduler_bench`.
+
+use prism::{Database, PrismDBResult};
+use std::hint::black_box;
+use std::time::Instant;
+
+const BRANCHES: usize = 16;
+const FACT_ROWS: usize = 1_000;
+const DIMENSION_ROWS: usize = 200;
+const RUNS: usize = 10;
+
+fn star_database() -> PrismDBResult<Database> {
+    let mut db = Database::new_in_memory()?;
+    for branch in 0..BRANCHES {
+        db.execute(&format!(
+            "CREATE TABLE facts_{} (key INTEGER, amount INTEGER)",
+            branch
+        ))?;
+        let facts: Vec<String> = (0..FACT_ROWS)
+            .map(|i| format!("({}, {})", (i * 17 + branch) % DIMENSION_ROWS, i % 101))
+            .collect();
+        db.execute(&format!("INSERT INTO facts_{} VALUES {}", branch, facts.join(", ")))?;
+
+        db.execute(&format!(
+            "CREATE TABLE dimension_{} (key INTEGER, category INTEGER)",
+            branch
+        ))?;
+        let dimension: Vec<String> = (0..DIMENSION_ROWS)
+            .map(|i| format!("({}, {})", i, i % 10))
+            .collect();
+        db.execute(&format!(
+            "INSERT INTO dimension_{} VALUES {}",
+            branch,
+            dimension.join(", ")
+        ))?;
+    }
+    Ok(db)
+}
+
+/// Queries per second of `sql`, from the best of several timed runs
+fn throughput(db: &Database, sql: &str) -> PrismDBResult<(usize, f64)> {
+    let mut best = f64::MAX;
+    let mut rows = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let result = black_box(db.query(sql)?);
+        best = best.min(start.elapsed().as_secs_f64());
+        rows = result.row_count();
+    }
+    Ok((rows, 1.0 / best))
+}
+
+fn main() -> PrismDBResult<()> {
+    let mut db = star_database()?;
+    let branches: Vec<String> = (0..BRANCHES)
+        .map(|branch| {
+            format!(
+                "SELECT d.category, f.amount FROM facts_{0} f JOIN dimension_{0} d ON f.key = d.key",
+                branch
+            )
+        })
+        .collect();
+    let sql = format!(
+        "SELECT category, COUNT(*), SUM(amount) FROM ({}) AS joined GROUP BY category",
+        branches.join(" UNION ALL ")
+    );
+    println!(
+        "{} joins of {} x {} rows, combined and aggregated",
+        BRANCHES, FACT_ROWS, DIMENSION_ROWS
+    );
+
+    db.execute("SET threads = 1")?;
+    let (sequential_rows, sequential_qps) = throughput(&db, &sql)?;
+    println!("  1 thread:   {:>8.1} queries/s", sequential_qps);
+
+    let threads = num_cpus::get();
+    db.execute(&format!("SET threads = {}", threads))?;
+    let (scheduled_rows, scheduled_qps) = throughput(&db, &sql)?;
+    assert_eq!(scheduled_rows, sequential_rows);
+    println!(
+        "  {} threads: {:>8.1} queries/s ({:.2}x)",
+        threads,
+        scheduled_qps,
+        scheduled_qps / sequential_qps
+    );
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Memory Benchmark for Dictionary-Encoded String Vectors
+//!
+//! Measures the live heap bytes of a low-cardinality VARCHAR column held as
+//! separate `Value`s, as a plain string `Vector` and as a dictionary-encoded
+//! `Vector`, along with the time to read every value back.
+//!
+//! Run with `cargo bench --bench string_dictionary_bench`.
+
+use prism::{PrismDBResult, Value, Vector};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// System allocator wrapper that tracks the bytes currently allocated
+struct TrackingAllocator;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+const ROWS: usize = 2_048;
+const CATEGORIES: [&str; 8] = [
+    "electronics",
+    "home & garden",
+    "clothing",
+    "sporting goods",
+    "books",
+    "toys",
+    "grocery",
+    "automotive",
+];
+
+/// Build a value and return it with the heap bytes it keeps alive
+fn retained<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    let result = f();
+    (result, LIVE_BYTES.load(Ordering::Relaxed).saturating_sub(before))
+}
+
+/// Read every value of a vector and return the elapsed milliseconds
+fn scan(vector: &Vector) -> PrismDBResult<f64> {
+    let start = Instant::now();
+    for value in vector.iter() {
+        black_box(value?);
+    }
+    Ok(start.elapsed().as_secs_f64() * 1000.0)
+}
+
+fn main() -> PrismDBResult<()> {
+    let values: Vec<Value> = (0..ROWS)
+        .map(|i| Value::Varchar(CATEGORIES[i % CATEGORIES.len()].to_string()))
+        .collect();
+    println!(
+        "{} VARCHAR rows with {} distinct values",
+        ROWS,
+        CATEGORIES.len()
+    );
+
+    let (owned, owned_bytes) = retained(|| values.clone());
+    println!("  Vec<Value>:        {:>8} bytes", owned_bytes);
+    drop(owned);
+
+    let (plain, plain_bytes) = retained(|| -> PrismDBResult<Vector> {
+        let mut vector = Vector::from_values(&values)?;
+        vector.decode_dictionary()?;
+        Ok(vector)
+    });
+    let plain = plain?;
+    assert!(!plain.is_dictionary_encoded());
+    println!(
+        "  plain vector:      {:>8} bytes  scan {:>8.2} ms",
+        plain_bytes,
+        scan(&plain)?
+    );
+    drop(plain);
+
+    let (encoded, encoded_bytes) = retained(|| Vector::from_values(&values));
+    let encoded = encoded?;
+    assert!(encoded.is_dictionary_encoded());
+    println!(
+        "  dictionary vector: {:>8} bytes  scan {:>8.2} ms",
+        encoded_bytes,
+        scan(&encoded)?
+    );
+    println!(
+        "  {:.1}x smaller than the plain vector",
+        plain_bytes as f64 / encoded_bytes.max(1) as f64
+    );
+
+    Ok(())
+}
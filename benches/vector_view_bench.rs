@@ -0,0 +1,118 @@
+//! Benchmark for Selection-Vector Views over DataChunks
+//!
+//! Filters a chunk down to a fraction of its rows and then narrows the result
+//! to a range, once copying the selected rows at every step (eager slicing)
+//! and once keeping each step as a view that is read in place. Reports the
+//! heap bytes allocated while slicing and the time to slice and read back
+//! every value.
+//!
+//! Run with `cargo bench --bench vector_view_bench`.
+
+use prism::{DataChunk, PrismDBResult, Value, Vector};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// System allocator wrapper that counts the bytes allocated
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(new_size.saturating_sub(layout.size()), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ROWS: usize = 2_048;
+const ITERATIONS: usize = 200;
+const REGIONS: [&str; 4] = ["north", "south", "east", "west"];
+
+fn source_chunk() -> PrismDBResult<DataChunk> {
+    let ids: Vec<Value> = (0..ROWS as i64).map(Value::BigInt).collect();
+    let amounts: Vec<Value> = (0..ROWS).map(|i| Value::Double(i as f64 * 0.25)).collect();
+    let regions: Vec<Value> = (0..ROWS)
+        .map(|i| Value::Varchar(REGIONS[i % REGIONS.len()].to_string()))
+        .collect();
+    DataChunk::from_vectors(vec![
+        Vector::from_values(&ids)?,
+        Vector::from_values(&amounts)?,
+        Vector::from_values(&regions)?,
+    ])
+}
+
+/// Filter to every third row, then keep the middle half of the survivors
+fn filter_and_range(chunk: &DataChunk, selected: &[usize], eager: bool) -> PrismDBResult<DataChunk> {
+    let mut filtered = chunk.filter(selected)?;
+    if eager {
+        filtered.flatten();
+    }
+    let mut ranged = filtered.slice_range(filtered.len() / 4, filtered.len() / 2)?;
+    if eager {
+        ranged.flatten();
+    }
+    Ok(ranged)
+}
+
+/// Slice and read the chunk repeatedly, returning bytes allocated by slicing per iteration and elapsed ms
+fn run(chunk: &DataChunk, selected: &[usize], eager: bool) -> PrismDBResult<(usize, f64)> {
+    let mut allocated = 0;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let result = filter_and_range(chunk, selected, eager)?;
+        allocated += ALLOCATED_BYTES.load(Ordering::Relaxed) - before;
+        for vector in result.column_iter() {
+            for value in vector.iter() {
+                black_box(value?);
+            }
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+    Ok((allocated / ITERATIONS, elapsed))
+}
+
+fn main() -> PrismDBResult<()> {
+    let chunk = source_chunk()?;
+    let selected: Vec<usize> = (0..chunk.len()).filter(|i| i % 3 == 0).collect();
+    let result = filter_and_range(&chunk, &selected, false)?;
+    assert_eq!(result.to_rows()?, filter_and_range(&chunk, &selected, true)?.to_rows()?);
+    println!(
+        "{} rows x {} columns filtered to {} rows, {} iterations",
+        ROWS,
+        chunk.column_count(),
+        result.len(),
+        ITERATIONS
+    );
+
+    let (eager_bytes, eager_ms) = run(&chunk, &selected, true)?;
+    println!(
+        "  eager slicing: {:>8} bytes allocated per iteration  {:>8.2} ms",
+        eager_bytes, eager_ms
+    );
+    let (view_bytes, view_ms) = run(&chunk, &selected, false)?;
+    println!(
+        "  views:         {:>8} bytes allocated per iteration  {:>8.2} ms",
+        view_bytes, view_ms
+    );
+    println!(
+        "  {:.1}x fewer bytes allocated with views",
+        eager_bytes as f64 / view_bytes.max(1) as f64
+    );
+
+    Ok(())
+}
@@ -0,0 +1,215 @@
+//! Wide-Table Hash Join Benchmark
+//!
+//! Joins a 24-column fact table against a 12-column dimension table with several
+//! matches per key, comparing the previous row-at-a-time strategy (every row copied
+//! into a `Vec<Value>` and cloned again per match) against the column-oriented
+//! `ParallelHashTable`, which keeps build chunks whole and gathers matched rows
+//! straight into output vectors. Also reports a wide join run end to end.
+//!
+//! Run with `cargo bench --bench wide_join_bench`.
+
+use prism::execution::hash_table::ParallelHashTable;
+use prism::planner::PhysicalJoinType;
+use prism::{DataChunk, Database, PrismDBResult, Value, Vector};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+/// System allocator wrapper that counts allocation calls
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const PROBE_ROWS: usize = 1_000;
+const PROBE_COLUMNS: usize = 24;
+const BUILD_ROWS: usize = 1_000;
+const BUILD_COLUMNS: usize = 12;
+const KEYS: usize = 250;
+const CHUNK_SIZE: usize = 250;
+
+/// Run a closure and return its result with the allocations and time it took
+fn measure<T>(f: impl FnOnce() -> T) -> (T, usize, f64) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before, elapsed)
+}
+
+/// Value of a non-key column: alternating integers and doubles
+fn column_value(row: usize, col: usize) -> Value {
+    if col % 2 == 0 {
+        Value::Integer((row * 31 + col) as i32)
+    } else {
+        Value::Double(row as f64 + col as f64 / 100.0)
+    }
+}
+
+/// Chunks whose first column is the join key and the rest are payload
+fn wide_chunks(rows: usize, columns: usize) -> PrismDBResult<Vec<DataChunk>> {
+    let mut chunks = Vec::new();
+    for start in (0..rows).step_by(CHUNK_SIZE) {
+        let end = (start + CHUNK_SIZE).min(rows);
+        let mut chunk = DataChunk::with_rows(end - start);
+        for col in 0..columns {
+            let values: Vec<Value> = (start..end)
+                .map(|row| match col {
+                    0 => Value::Integer((row % KEYS) as i32),
+                    _ => column_value(row, col),
+                })
+                .collect();
+            chunk.set_vector(col, Vector::from_values(&values)?)?;
+        }
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
+
+fn chunk_rows(chunk: &DataChunk) -> PrismDBResult<Vec<Vec<Value>>> {
+    (0..chunk.len())
+        .map(|row| {
+            (0..chunk.column_count())
+                .map(|col| chunk.get_vector(col).unwrap().get_value(row))
+                .collect()
+        })
+        .collect()
+}
+
+/// The row-at-a-time strategy: materialize rows, clone them into a string-keyed map,
+/// clone again for every match, then transpose the joined rows back into vectors
+fn join_by_rows(probe: &[DataChunk], build: &[DataChunk]) -> PrismDBResult<usize> {
+    let mut build_rows = Vec::new();
+    for chunk in build {
+        build_rows.extend(chunk_rows(chunk)?);
+    }
+    let mut table: HashMap<String, Vec<Vec<Value>>> = HashMap::new();
+    for row in &build_rows {
+        table.entry(row[0].to_string()).or_default().push(row.clone());
+    }
+
+    let mut joined = Vec::new();
+    for chunk in probe {
+        for row in chunk_rows(chunk)? {
+            if let Some(matches) = table.get(&row[0].to_string()) {
+                for build_row in matches {
+                    let mut output = row.clone();
+                    output.extend(build_row.clone());
+                    joined.push(output);
+                }
+            }
+        }
+    }
+
+    let mut output_rows = 0;
+    for batch in joined.chunks(2048) {
+        let mut chunk = DataChunk::with_rows(batch.len());
+        for col in 0..batch[0].len() {
+            let values: Vec<Value> = batch.iter().map(|row| row[col].clone()).collect();
+            chunk.set_vector(col, Vector::from_values(&values)?)?;
+        }
+        output_rows += black_box(chunk).len();
+    }
+    Ok(output_rows)
+}
+
+/// The column-oriented strategy used by the hash join operators
+fn join_by_columns(probe: &[DataChunk], build: Vec<DataChunk>) -> PrismDBResult<usize> {
+    let mut table = ParallelHashTable::new(vec![0]);
+    for chunk in build {
+        table.build_from_chunk(chunk)?;
+    }
+
+    let mut output_rows = 0;
+    for chunk in probe {
+        let matches = table.probe_chunk(chunk, &[0], &PhysicalJoinType::Inner)?;
+        for output in table.gather(chunk, &matches, BUILD_COLUMNS)? {
+            output_rows += black_box(output).len();
+        }
+    }
+    Ok(output_rows)
+}
+
+fn wide_database() -> PrismDBResult<Database> {
+    let mut db = Database::new_in_memory()?;
+    for (table, rows, columns) in [
+        ("facts", PROBE_ROWS, PROBE_COLUMNS),
+        ("dims", BUILD_ROWS, BUILD_COLUMNS),
+    ] {
+        let column_defs: Vec<String> = (0..columns)
+            .map(|col| match col {
+                0 => "k INTEGER".to_string(),
+                c if c % 2 == 0 => format!("c{} INTEGER", c),
+                c => format!("c{} DOUBLE", c),
+            })
+            .collect();
+        db.execute(&format!("CREATE TABLE {} ({})", table, column_defs.join(", ")))?;
+
+        let values: Vec<String> = (0..rows)
+            .map(|row| {
+                let fields: Vec<String> = (0..columns)
+                    .map(|col| match col {
+                        0 => (row % KEYS).to_string(),
+                        _ => column_value(row, col).to_string(),
+                    })
+                    .collect();
+                format!("({})", fields.join(", "))
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO {} VALUES {}", table, values.join(", ")))?;
+    }
+    Ok(db)
+}
+
+fn main() -> PrismDBResult<()> {
+    let expected = PROBE_ROWS * (BUILD_ROWS / KEYS);
+    println!(
+        "Joining {} x {} columns with {} x {} columns on {} keys ({} output rows)",
+        PROBE_ROWS, PROBE_COLUMNS, BUILD_ROWS, BUILD_COLUMNS, KEYS, expected
+    );
+    let probe = wide_chunks(PROBE_ROWS, PROBE_COLUMNS)?;
+    let build = wide_chunks(BUILD_ROWS, BUILD_COLUMNS)?;
+
+    let (rows, row_allocs, row_ms) = measure(|| join_by_rows(&probe, &build));
+    assert_eq!(rows?, expected);
+    println!("  row copies:    {:>9} allocations {:>8.2} ms", row_allocs, row_ms);
+
+    let (rows, column_allocs, column_ms) = measure(|| join_by_columns(&probe, build));
+    assert_eq!(rows?, expected);
+    println!(
+        "  column gather: {:>9} allocations {:>8.2} ms",
+        column_allocs, column_ms
+    );
+
+    let db = wide_database()?;
+    let sql = "SELECT * FROM facts f JOIN dims d ON f.k = d.k";
+    let (result, query_allocs, query_ms) = measure(|| db.query(sql));
+    assert_eq!(result?.row_count(), expected);
+    println!("\n{}", sql);
+    println!(
+        "  end to end:    {:>9} allocations {:>8.2} ms",
+        query_allocs, query_ms
+    );
+
+    Ok(())
+}
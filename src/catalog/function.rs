@@ -4,6 +4,7 @@
 
 use crate::catalog::ObjectMetadata;
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::parser::ast::Expression;
 use crate::types::LogicalType;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -278,3 +279,19 @@ impl Default for FunctionRegistry {
         Self::new()
     }
 }
+
+/// Scalar function defined in SQL with CREATE FUNCTION
+///
+/// Calls are inlined by the binder: the body is bound in place of the call,
+/// with each parameter replaced by its argument.
+#[derive(Debug, Clone)]
+pub struct SqlFunction {
+    /// Function name
+    pub name: String,
+    /// Parameter names and declared types
+    pub parameters: Vec<(String, LogicalType)>,
+    /// Declared return type, if any
+    pub return_type: Option<LogicalType>,
+    /// Expression the call expands to
+    pub body: Expression,
+}
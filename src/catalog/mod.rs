@@ -17,10 +17,13 @@ pub use table::*;
 pub use transaction::*;
 pub use view::*;
 
-use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::sync::{MutexExt, RwLockExt};
 use crate::storage::TableInfo;
+use crate::types::EnumType;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
 /// Main catalog that manages all database metadata
 #[derive(Debug)]
@@ -29,8 +32,16 @@ pub struct Catalog {
     schemas: HashMap<String, Arc<RwLock<Schema>>>,
     /// Default schema name
     default_schema: String,
+    /// Schemas searched in order for unqualified table names; new tables go
+    /// in the first one that exists
+    search_path: Vec<String>,
     /// Catalog metadata
     metadata: CatalogMetadata,
+    /// Last version handed out to a modified table
+    latest_table_version: AtomicU64,
+    /// Version of each table's last modification; never reused, so a dropped
+    /// and recreated table still gets a newer version
+    table_versions: Mutex<HashMap<String, u64>>,
 }
 
 impl Catalog {
@@ -39,7 +50,10 @@ impl Catalog {
         let mut catalog = Self {
             schemas: HashMap::new(),
             default_schema: "main".to_string(),
+            search_path: vec!["main".to_string()],
             metadata: CatalogMetadata::new(),
+            latest_table_version: AtomicU64::new(0),
+            table_versions: Mutex::new(HashMap::new()),
         };
 
         // Create default schema
@@ -62,20 +76,40 @@ impl Catalog {
         Ok(())
     }
 
-    /// Drop a schema
-    pub fn drop_schema(&mut self, name: &str) -> PrismDBResult<()> {
+    /// Drop a schema. With `cascade` the objects in it are dropped first; without
+    /// it a schema that still has objects is kept and the error lists them.
+    pub fn drop_schema(&mut self, name: &str, cascade: bool) -> PrismDBResult<()> {
         if name == self.default_schema {
             return Err(PrismDBError::Catalog(
                 "Cannot drop default schema".to_string(),
             ));
         }
 
-        if !self.schemas.contains_key(name) {
-            return Err(PrismDBError::Catalog(format!(
+        let Some(schema) = self.schemas.get(name) else {
+            return Err(PrismDBError::NotFound(ObjectKind::Schema, format!(
                 "Schema '{}' does not exist",
                 name
             )));
+        };
+
+        let mut schema = schema.write_or_err()?;
+        let objects = schema.list_objects();
+        if !objects.is_empty() {
+            if !cascade {
+                return Err(PrismDBError::Catalog(format!(
+                    "Schema '{}' still has objects ({}); use DROP SCHEMA {} CASCADE to drop them too",
+                    name,
+                    objects.join(", "),
+                    name
+                )));
+            }
+            for table_name in schema.list_tables() {
+                schema.drop_table(&table_name)?;
+                self.bump_table_version(&table_name);
+            }
+            schema.clear();
         }
+        drop(schema);
 
         self.schemas.remove(name);
         Ok(())
@@ -86,7 +120,7 @@ impl Catalog {
         self.schemas
             .get(name)
             .cloned()
-            .ok_or_else(|| PrismDBError::Catalog(format!("Schema '{}' does not exist", name)))
+            .ok_or_else(|| PrismDBError::NotFound(ObjectKind::Schema, format!("Schema '{}' does not exist", name)))
     }
 
     /// Get the default schema
@@ -94,17 +128,117 @@ impl Catalog {
         self.schemas.get(&self.default_schema).unwrap().clone()
     }
 
+    /// Make `name` the default schema, creating it if needed, and reset the
+    /// search path to it
+    pub fn set_default_schema(&mut self, name: &str) {
+        if !self.schemas.contains_key(name) {
+            self.create_schema(name).unwrap();
+        }
+        self.default_schema = name.to_string();
+        self.search_path = vec![name.to_string()];
+    }
+
+    /// Schemas searched for unqualified table names, in order
+    pub fn search_path(&self) -> &[String] {
+        &self.search_path
+    }
+
+    /// Set the schemas searched for unqualified table names; schemas that do
+    /// not exist are skipped, and an empty path falls back to the default schema
+    pub fn set_search_path(&mut self, schemas: Vec<String>) {
+        self.search_path = if schemas.is_empty() {
+            vec![self.default_schema.clone()]
+        } else {
+            schemas
+        };
+    }
+
+    /// Schema that unqualified CREATE TABLE puts new tables in: the first
+    /// schema on the search path that exists
+    pub fn current_schema(&self) -> PrismDBResult<Arc<RwLock<Schema>>> {
+        self.search_path
+            .iter()
+            .find_map(|name| self.schemas.get(name).cloned())
+            .ok_or_else(|| {
+                PrismDBError::Catalog(format!(
+                    "No schema on the search path '{}' exists",
+                    self.search_path.join(", ")
+                ))
+            })
+    }
+
+    /// Schema holding the unqualified table `table_name`: the first schema on
+    /// the search path that has it
+    pub fn table_schema(&self, table_name: &str) -> PrismDBResult<Arc<RwLock<Schema>>> {
+        for schema in self.search_path_schemas() {
+            if schema.read_or_err()?.table_exists(table_name) {
+                return Ok(schema.clone());
+            }
+        }
+        Err(PrismDBError::NotFound(
+            ObjectKind::Table,
+            format!("Table '{}' does not exist", table_name),
+        ))
+    }
+
+    /// Look up an unqualified table along the search path
+    pub fn find_table(&self, table_name: &str) -> PrismDBResult<Arc<RwLock<Table>>> {
+        let schema = self.table_schema(table_name)?;
+        let result = schema.read_or_err()?.get_table(table_name);
+        result
+    }
+
+    /// Schema holding the unqualified index `index_name`: the first schema on
+    /// the search path that has it
+    pub fn index_schema(&self, index_name: &str) -> PrismDBResult<Arc<RwLock<Schema>>> {
+        for schema in self.search_path_schemas() {
+            if schema.read_or_err()?.index_exists(index_name) {
+                return Ok(schema.clone());
+            }
+        }
+        Err(PrismDBError::NotFound(
+            ObjectKind::Index,
+            format!("Index '{}' does not exist", index_name),
+        ))
+    }
+
+    /// Look up an unqualified enum type along the search path
+    pub fn find_type(&self, type_name: &str) -> PrismDBResult<Arc<EnumType>> {
+        for schema in self.search_path_schemas() {
+            if let Ok(enum_type) = schema.read_or_err()?.get_type(type_name) {
+                return Ok(enum_type);
+            }
+        }
+        Err(PrismDBError::NotFound(
+            ObjectKind::Type,
+            format!("Type '{}' does not exist", type_name),
+        ))
+    }
+
+    /// Look up an unqualified SQL function along the search path
+    pub fn find_function(&self, function_name: &str) -> Option<Arc<SqlFunction>> {
+        self.search_path_schemas()
+            .find_map(|schema| schema.read_or_err().ok()?.get_function(function_name))
+    }
+
+    /// Existing schemas on the search path, in order
+    fn search_path_schemas(&self) -> impl Iterator<Item = &Arc<RwLock<Schema>>> {
+        self.search_path.iter().filter_map(|name| self.schemas.get(name))
+    }
+
     /// Create a table
     pub fn create_table(&self, table_info: &TableInfo) -> PrismDBResult<()> {
         let schema = self.get_schema(&table_info.schema_name)?;
-        let result = schema.write().unwrap().create_table(table_info);
+        let result = schema.write_or_err()?.create_table(table_info);
+        self.bump_table_version(&table_info.table_name);
         result
     }
 
     /// Drop a table
     pub fn drop_table(&self, schema_name: &str, table_name: &str) -> PrismDBResult<()> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.write().unwrap().drop_table(table_name);
+        let result = schema.write_or_err()?.drop_table(table_name);
+        self.bump_table_version(table_name);
         result
     }
 
@@ -115,10 +249,17 @@ impl Catalog {
         table_name: &str,
     ) -> PrismDBResult<Arc<RwLock<Table>>> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.read().unwrap().get_table(table_name);
+        let result = schema.read_or_err()?.get_table(table_name);
         result
     }
 
+    /// Get the CREATE TABLE statement for a table
+    pub fn get_table_ddl(&self, schema_name: &str, table_name: &str) -> PrismDBResult<String> {
+        let table = self.get_table(schema_name, table_name)?;
+        let ddl = table.read_or_err()?.get_table_info().to_create_sql();
+        Ok(ddl)
+    }
+
     /// Create a view
     pub fn create_view(
         &self,
@@ -128,38 +269,35 @@ impl Catalog {
         column_names: Vec<String>,
     ) -> PrismDBResult<()> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema
-            .write()
-            .unwrap()
-            .create_view(view_name, query, column_names);
+        let result = schema.write_or_err()?.create_view(view_name, query, column_names);
         result
     }
 
     /// Drop a view
     pub fn drop_view(&self, schema_name: &str, view_name: &str) -> PrismDBResult<()> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.write().unwrap().drop_view(view_name);
+        let result = schema.write_or_err()?.drop_view(view_name);
         result
     }
 
     /// Get a view
     pub fn get_view(&self, schema_name: &str, view_name: &str) -> PrismDBResult<Arc<RwLock<View>>> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.read().unwrap().get_view(view_name);
+        let result = schema.read_or_err()?.get_view(view_name);
         result
     }
 
     /// Create an index
     pub fn create_index(&self, index_info: &IndexInfo) -> PrismDBResult<()> {
         let schema = self.get_schema(&index_info.schema_name)?;
-        let result = schema.write().unwrap().create_index(index_info);
+        let result = schema.write_or_err()?.create_index(index_info);
         result
     }
 
     /// Drop an index
     pub fn drop_index(&self, schema_name: &str, index_name: &str) -> PrismDBResult<()> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.write().unwrap().drop_index(index_name);
+        let result = schema.write_or_err()?.drop_index(index_name);
         result
     }
 
@@ -170,10 +308,57 @@ impl Catalog {
         index_name: &str,
     ) -> PrismDBResult<Arc<RwLock<Index>>> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.read().unwrap().get_index(index_name);
+        let result = schema.read_or_err()?.get_index(index_name);
+        result
+    }
+
+    /// Create an enum type
+    pub fn create_type(&self, schema_name: &str, enum_type: EnumType) -> PrismDBResult<()> {
+        let schema = self.get_schema(schema_name)?;
+        let result = schema.write_or_err()?.create_type(enum_type);
+        result
+    }
+
+    /// Get an enum type
+    pub fn get_type(&self, schema_name: &str, type_name: &str) -> PrismDBResult<Arc<EnumType>> {
+        let schema = self.get_schema(schema_name)?;
+        let result = schema.read_or_err()?.get_type(type_name);
+        result
+    }
+
+    /// Create a SQL function
+    pub fn create_function(
+        &self,
+        schema_name: &str,
+        function: SqlFunction,
+        or_replace: bool,
+    ) -> PrismDBResult<()> {
+        let schema = self.get_schema(schema_name)?;
+        let result = schema.write_or_err()?.create_function(function, or_replace);
+        result
+    }
+
+    /// Drop a SQL function
+    pub fn drop_function(&self, schema_name: &str, function_name: &str) -> PrismDBResult<()> {
+        let schema = self.get_schema(schema_name)?;
+        let result = schema.write_or_err()?.drop_function(function_name);
         result
     }
 
+    /// Get a SQL function, if one of that name exists
+    pub fn get_function(&self, schema_name: &str, function_name: &str) -> Option<Arc<SqlFunction>> {
+        let schema = self.get_schema(schema_name).ok()?;
+        let result = schema.read_or_err().ok()?.get_function(function_name);
+        result
+    }
+
+    /// SQL functions of a schema, by name
+    pub fn list_functions(&self, schema_name: &str) -> PrismDBResult<Vec<Arc<SqlFunction>>> {
+        let schema = self.get_schema(schema_name)?;
+        let result = schema.read_or_err()?.list_functions();
+        Ok(result)
+    }
+
     /// List all schemas
     pub fn list_schemas(&self) -> Vec<String> {
         self.schemas.keys().cloned().collect()
@@ -182,21 +367,21 @@ impl Catalog {
     /// List all tables in a schema
     pub fn list_tables(&self, schema_name: &str) -> PrismDBResult<Vec<String>> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.read().unwrap().list_tables();
+        let result = schema.read_or_err()?.list_tables();
         Ok(result)
     }
 
     /// List all views in a schema
     pub fn list_views(&self, schema_name: &str) -> PrismDBResult<Vec<String>> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.read().unwrap().list_views();
+        let result = schema.read_or_err()?.list_views();
         Ok(result)
     }
 
     /// List all indexes in a schema
     pub fn list_indexes(&self, schema_name: &str) -> PrismDBResult<Vec<String>> {
         let schema = self.get_schema(schema_name)?;
-        let result = schema.read().unwrap().list_indexes();
+        let result = schema.read_or_err()?.list_indexes();
         Ok(result)
     }
 
@@ -205,31 +390,45 @@ impl Catalog {
         &self.metadata
     }
 
+    /// Latest version handed out to a modified table
+    pub fn latest_table_version(&self) -> u64 {
+        self.latest_table_version.load(Ordering::SeqCst)
+    }
+
+    /// Version of the last modification of `table_name` (0 if never modified)
+    pub fn table_version(&self, table_name: &str) -> u64 {
+        let versions = self.table_versions.lock_recovered();
+        versions.get(Self::version_key(table_name)).copied().unwrap_or(0)
+    }
+
+    /// Record that `table_name` was modified, giving it a new version
+    pub fn bump_table_version(&self, table_name: &str) {
+        let version = self.latest_table_version.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut versions = self.table_versions.lock_recovered();
+        versions.insert(Self::version_key(table_name).to_string(), version);
+    }
+
+    /// Versions are tracked by bare table name, without a schema qualifier
+    fn version_key(table_name: &str) -> &str {
+        table_name.rsplit('.').next().unwrap_or(table_name)
+    }
+
     /// Check if a table exists
     pub fn table_exists(&self, schema_name: &str, table_name: &str) -> bool {
-        if let Ok(schema) = self.get_schema(schema_name) {
-            schema.read().unwrap().table_exists(table_name)
-        } else {
-            false
-        }
+        self.get_schema(schema_name)
+            .is_ok_and(|schema| schema.read_or_err().is_ok_and(|schema| schema.table_exists(table_name)))
     }
 
     /// Check if a view exists
     pub fn view_exists(&self, schema_name: &str, view_name: &str) -> bool {
-        if let Ok(schema) = self.get_schema(schema_name) {
-            schema.read().unwrap().view_exists(view_name)
-        } else {
-            false
-        }
+        self.get_schema(schema_name)
+            .is_ok_and(|schema| schema.read_or_err().is_ok_and(|schema| schema.view_exists(view_name)))
     }
 
     /// Check if an index exists
     pub fn index_exists(&self, schema_name: &str, index_name: &str) -> bool {
-        if let Ok(schema) = self.get_schema(schema_name) {
-            schema.read().unwrap().index_exists(index_name)
-        } else {
-            false
-        }
+        self.get_schema(schema_name)
+            .is_ok_and(|schema| schema.read_or_err().is_ok_and(|schema| schema.index_exists(index_name)))
     }
 }
 
@@ -2,8 +2,10 @@
 //!
 //! Provides schema management functionality for organizing database objects.
 
-use crate::catalog::{Index, IndexInfo, ObjectMetadata, Table, TableInfo, View};
-use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::catalog::{Index, IndexInfo, ObjectMetadata, SqlFunction, Table, TableInfo, View};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
+use crate::types::EnumType;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -18,6 +20,10 @@ pub struct Schema {
     views: HashMap<String, Arc<RwLock<View>>>,
     /// Indexes in the schema
     indexes: HashMap<String, Arc<RwLock<Index>>>,
+    /// Enum types created with CREATE TYPE
+    types: HashMap<String, Arc<EnumType>>,
+    /// Scalar functions created with CREATE FUNCTION, keyed by lower-case name
+    functions: HashMap<String, Arc<SqlFunction>>,
     /// Schema metadata
     pub metadata: ObjectMetadata,
 }
@@ -30,6 +36,8 @@ impl Schema {
             tables: HashMap::new(),
             views: HashMap::new(),
             indexes: HashMap::new(),
+            types: HashMap::new(),
+            functions: HashMap::new(),
             metadata: ObjectMetadata::new(),
         }
     }
@@ -58,22 +66,20 @@ impl Schema {
     /// Drop a table
     pub fn drop_table(&mut self, table_name: &str) -> PrismDBResult<()> {
         if !self.tables.contains_key(table_name) {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
                 "Table '{}' does not exist in schema '{}'",
                 table_name, self.name
             )));
         }
 
         // Check for dependent indexes
-        let dependent_indexes: Vec<String> = self
-            .indexes
-            .values()
-            .filter(|index| {
-                let index = index.read().unwrap();
-                index.get_table_name() == table_name
-            })
-            .map(|index| index.read().unwrap().get_name().to_string())
-            .collect();
+        let mut dependent_indexes = Vec::new();
+        for index in self.indexes.values() {
+            let index = index.read_or_err()?;
+            if index.get_table_name() == table_name {
+                dependent_indexes.push(index.get_name().to_string());
+            }
+        }
 
         // Drop dependent indexes
         for index_name in dependent_indexes {
@@ -88,7 +94,7 @@ impl Schema {
     /// Get a table
     pub fn get_table(&self, table_name: &str) -> PrismDBResult<Arc<RwLock<Table>>> {
         self.tables.get(table_name).cloned().ok_or_else(|| {
-            PrismDBError::Catalog(format!(
+            PrismDBError::NotFound(ObjectKind::Table, format!(
                 "Table '{}' does not exist in schema '{}'",
                 table_name, self.name
             ))
@@ -156,7 +162,7 @@ impl Schema {
     /// Drop a view
     pub fn drop_view(&mut self, view_name: &str) -> PrismDBResult<()> {
         if !self.views.contains_key(view_name) {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::View, format!(
                 "View '{}' does not exist in schema '{}'",
                 view_name, self.name
             )));
@@ -170,7 +176,7 @@ impl Schema {
     /// Get a view
     pub fn get_view(&self, view_name: &str) -> PrismDBResult<Arc<RwLock<View>>> {
         self.views.get(view_name).cloned().ok_or_else(|| {
-            PrismDBError::Catalog(format!(
+            PrismDBError::NotFound(ObjectKind::View, format!(
                 "View '{}' does not exist in schema '{}'",
                 view_name, self.name
             ))
@@ -194,7 +200,7 @@ impl Schema {
         data: Vec<crate::types::DataChunk>,
     ) -> PrismDBResult<()> {
         let view_arc = self.get_view(view_name)?;
-        let mut view = view_arc.write().unwrap();
+        let mut view = view_arc.write_or_err()?;
 
         if !view.is_materialized {
             return Err(PrismDBError::Catalog(format!(
@@ -211,7 +217,7 @@ impl Schema {
     /// Mark materialized views that depend on a table as stale
     pub fn mark_dependent_views_stale(&mut self, table_name: &str) -> PrismDBResult<()> {
         for view_arc in self.views.values() {
-            let mut view = view_arc.write().unwrap();
+            let mut view = view_arc.write_or_err()?;
             if view.is_materialized {
                 if let Some(ref metadata) = view.materialized_metadata {
                     if metadata.dependencies.contains(&table_name.to_string()) {
@@ -234,7 +240,7 @@ impl Schema {
 
         // Verify the table exists
         if !self.tables.contains_key(&index_info.table_name) {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
                 "Table '{}' does not exist in schema '{}'",
                 index_info.table_name, self.name
             )));
@@ -250,7 +256,7 @@ impl Schema {
     /// Drop an index
     pub fn drop_index(&mut self, index_name: &str) -> PrismDBResult<()> {
         if !self.indexes.contains_key(index_name) {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Index, format!(
                 "Index '{}' does not exist in schema '{}'",
                 index_name, self.name
             )));
@@ -264,7 +270,7 @@ impl Schema {
     /// Get an index
     pub fn get_index(&self, index_name: &str) -> PrismDBResult<Arc<RwLock<Index>>> {
         self.indexes.get(index_name).cloned().ok_or_else(|| {
-            PrismDBError::Catalog(format!(
+            PrismDBError::NotFound(ObjectKind::Index, format!(
                 "Index '{}' does not exist in schema '{}'",
                 index_name, self.name
             ))
@@ -285,14 +291,75 @@ impl Schema {
     pub fn get_table_indexes(&self, table_name: &str) -> Vec<Arc<RwLock<Index>>> {
         self.indexes
             .values()
-            .filter(|index| {
-                let index = index.read().unwrap();
-                index.get_table_name() == table_name
-            })
+            .filter(|index| index.read_or_err().is_ok_and(|index| index.get_table_name() == table_name))
             .cloned()
             .collect()
     }
 
+    /// Create an enum type
+    pub fn create_type(&mut self, enum_type: EnumType) -> PrismDBResult<()> {
+        if self.types.contains_key(&enum_type.name) {
+            return Err(PrismDBError::Catalog(format!(
+                "Type '{}' already exists in schema '{}'",
+                enum_type.name, self.name
+            )));
+        }
+
+        self.types.insert(enum_type.name.clone(), Arc::new(enum_type));
+        self.metadata.touch();
+        Ok(())
+    }
+
+    /// Get an enum type
+    pub fn get_type(&self, type_name: &str) -> PrismDBResult<Arc<EnumType>> {
+        self.types.get(type_name).cloned().ok_or_else(|| {
+            PrismDBError::NotFound(ObjectKind::Type, format!(
+                "Type '{}' does not exist in schema '{}'",
+                type_name, self.name
+            ))
+        })
+    }
+
+    /// Create a SQL function, replacing an existing one only with `or_replace`
+    pub fn create_function(&mut self, function: SqlFunction, or_replace: bool) -> PrismDBResult<()> {
+        let key = function.name.to_lowercase();
+        if !or_replace && self.functions.contains_key(&key) {
+            return Err(PrismDBError::Catalog(format!(
+                "Function '{}' already exists in schema '{}'",
+                function.name, self.name
+            )));
+        }
+
+        self.functions.insert(key, Arc::new(function));
+        self.metadata.touch();
+        Ok(())
+    }
+
+    /// Drop a SQL function
+    pub fn drop_function(&mut self, function_name: &str) -> PrismDBResult<()> {
+        if self.functions.remove(&function_name.to_lowercase()).is_none() {
+            return Err(PrismDBError::NotFound(ObjectKind::Function, format!(
+                "Function '{}' does not exist in schema '{}'",
+                function_name, self.name
+            )));
+        }
+
+        self.metadata.touch();
+        Ok(())
+    }
+
+    /// Get a SQL function; names are case-insensitive
+    pub fn get_function(&self, function_name: &str) -> Option<Arc<SqlFunction>> {
+        self.functions.get(&function_name.to_lowercase()).cloned()
+    }
+
+    /// SQL functions of the schema, by name
+    pub fn list_functions(&self) -> Vec<Arc<SqlFunction>> {
+        let mut functions: Vec<Arc<SqlFunction>> = self.functions.values().cloned().collect();
+        functions.sort_by_key(|function| function.name.to_lowercase());
+        functions
+    }
+
     /// Get all objects in the schema
     pub fn get_all_objects(&self) -> SchemaObjects {
         SchemaObjects {
@@ -302,6 +369,18 @@ impl Schema {
         }
     }
 
+    /// Every object in the schema, kind first, as in `table events`, sorted by kind
+    /// then name; indexes come with their tables and are not listed
+    pub fn list_objects(&self) -> Vec<String> {
+        let mut objects: Vec<(&str, &String)> = Vec::new();
+        objects.extend(self.tables.keys().map(|name| ("table", name)));
+        objects.extend(self.views.keys().map(|name| ("view", name)));
+        objects.extend(self.types.keys().map(|name| ("type", name)));
+        objects.extend(self.functions.values().map(|function| ("function", &function.name)));
+        objects.sort();
+        objects.into_iter().map(|(kind, name)| format!("{} {}", kind, name)).collect()
+    }
+
     /// Get object count
     pub fn get_object_count(&self) -> usize {
         self.tables.len() + self.views.len() + self.indexes.len()
@@ -312,6 +391,8 @@ impl Schema {
         self.tables.clear();
         self.views.clear();
         self.indexes.clear();
+        self.types.clear();
+        self.functions.clear();
         self.metadata.touch();
     }
 }
@@ -341,7 +422,7 @@ impl Schema {
         let mut total_size_bytes = 0;
 
         for table in self.tables.values() {
-            let table = table.read().unwrap();
+            let table = table.read_recovered();
             total_rows += table.row_count();
             total_size_bytes += table.size_bytes();
         }
@@ -359,7 +440,7 @@ impl Schema {
     pub fn validate(&self) -> PrismDBResult<()> {
         // Check that all indexes reference existing tables
         for index in self.indexes.values() {
-            let index = index.read().unwrap();
+            let index = index.read_or_err()?;
             if !self.tables.contains_key(index.get_table_name()) {
                 return Err(PrismDBError::Catalog(format!(
                     "Index '{}' references non-existent table '{}'",
@@ -371,9 +452,9 @@ impl Schema {
 
         // Check that all index columns exist in their respective tables
         for index in self.indexes.values() {
-            let index = index.read().unwrap();
+            let index = index.read_or_err()?;
             if let Ok(table) = self.get_table(index.get_table_name()) {
-                let table = table.read().unwrap();
+                let table = table.read_or_err()?;
                 for column_name in index.get_column_names() {
                     if !table.has_column(column_name) {
                         return Err(PrismDBError::Catalog(format!(
@@ -2,7 +2,8 @@
 //!
 //! Provides table management functionality including metadata and statistics.
 
-use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use crate::storage::{ColumnData, ColumnInfo, TableData, TableInfo};
 use crate::types::LogicalType;
 use std::collections::HashMap;
@@ -80,12 +81,12 @@ impl Table {
 
     /// Get row count
     pub fn row_count(&self) -> usize {
-        self.data.read().unwrap().row_count()
+        self.data.read_recovered().row_count()
     }
 
     /// Get table size in bytes
     pub fn size_bytes(&self) -> u64 {
-        self.data.read().unwrap().size_bytes()
+        self.data.read_recovered().size_bytes()
     }
 
     /// Add a column
@@ -102,10 +103,10 @@ impl Table {
         self.info.columns.push(column_info.clone());
 
         // Add column to table data
-        self.data.write().unwrap().add_column(&column_info)?;
+        self.data.write_or_err()?.add_column(&column_info)?;
 
         // Update statistics
-        self.statistics.write().unwrap().add_column(&column_info);
+        self.statistics.write_or_err()?.add_column(&column_info);
 
         self.metadata.touch();
         Ok(())
@@ -115,7 +116,7 @@ impl Table {
     pub fn drop_column(&mut self, column_name: &str) -> PrismDBResult<()> {
         // Check if column exists
         let column_index = self.get_column_index(column_name).ok_or_else(|| {
-            PrismDBError::Catalog(format!(
+            PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column '{}' does not exist in table '{}'",
                 column_name, self.info.table_name
             ))
@@ -125,10 +126,10 @@ impl Table {
         self.info.columns.remove(column_index);
 
         // Remove column from table data
-        self.data.write().unwrap().remove_column(column_name)?;
+        self.data.write_or_err()?.remove_column(column_name)?;
 
         // Update statistics
-        self.statistics.write().unwrap().remove_column(column_name);
+        self.statistics.write_or_err()?.remove_column(column_name);
 
         self.metadata.touch();
         Ok(())
@@ -138,7 +139,7 @@ impl Table {
     pub fn rename_column(&mut self, old_name: &str, new_name: &str) -> PrismDBResult<()> {
         // Check if old column exists
         let _column = self.get_column(old_name).ok_or_else(|| {
-            PrismDBError::Catalog(format!(
+            PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column '{}' does not exist in table '{}'",
                 old_name, self.info.table_name
             ))
@@ -161,16 +162,10 @@ impl Table {
         }
 
         // Update column name in table data
-        self.data
-            .write()
-            .unwrap()
-            .rename_column(old_name, new_name)?;
+        self.data.write_or_err()?.rename_column(old_name, new_name)?;
 
         // Update statistics
-        self.statistics
-            .write()
-            .unwrap()
-            .rename_column(old_name, new_name);
+        self.statistics.write_or_err()?.rename_column(old_name, new_name);
 
         self.metadata.touch();
         Ok(())
@@ -178,7 +173,7 @@ impl Table {
 
     /// Get column data
     pub fn get_column_data(&self, column_name: &str) -> PrismDBResult<Arc<ColumnData>> {
-        self.data.read().unwrap().get_column_data(column_name)
+        self.data.read_or_err()?.get_column_data(column_name)
     }
 
     /// Insert data
@@ -196,7 +191,7 @@ impl Table {
             let expected_type = &self.info.columns[i].column_type;
             let actual_type = value.get_type();
             if !self.compatible_types(&actual_type, expected_type) {
-                return Err(PrismDBError::Execution(format!(
+                return Err(PrismDBError::TypeMismatch(format!(
                     "Type mismatch for column '{}': expected {:?}, got {:?}",
                     self.info.columns[i].name, expected_type, actual_type
                 )));
@@ -204,13 +199,10 @@ impl Table {
         }
 
         // Insert into table data
-        self.data.write().unwrap().insert(values)?;
+        self.data.write_or_err()?.insert(values)?;
 
         // Update statistics
-        self.statistics
-            .write()
-            .unwrap()
-            .update_for_insert(0, values);
+        self.statistics.write_or_err()?.update_for_insert(0, values);
 
         Ok(())
     }
@@ -229,8 +221,8 @@ impl Table {
 
     /// Update table statistics
     pub fn update_statistics(&self) -> PrismDBResult<()> {
-        let data = self.data.read().unwrap();
-        let mut stats = self.statistics.write().unwrap();
+        let data = self.data.read_or_err()?;
+        let mut stats = self.statistics.write_or_err()?;
 
         for column_info in &self.info.columns {
             if let Ok(column_data) = data.get_column_data(&column_info.name) {
@@ -244,13 +236,13 @@ impl Table {
     /// Validate table integrity
     pub fn validate(&self) -> PrismDBResult<()> {
         // Check that all columns have data
-        let data = self.data.read().unwrap();
+        let data = self.data.read_or_err()?;
         for column_info in &self.info.columns {
             data.get_column_data(&column_info.name)?;
         }
 
         // Validate statistics
-        let stats = self.statistics.read().unwrap();
+        let stats = self.statistics.read_or_err()?;
         stats.validate()?;
 
         Ok(())
@@ -3,7 +3,8 @@
 //! Provides catalog integration for transaction management.
 
 use crate::catalog::Catalog;
-use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use crate::storage::Transaction;
 use std::sync::{Arc, RwLock};
 
@@ -92,7 +93,7 @@ impl TransactionCatalog {
 
         // Check if schema exists
         if !self.schema_exists(name)? {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Schema, format!(
                 "Schema '{}' does not exist",
                 name
             )));
@@ -116,7 +117,7 @@ impl TransactionCatalog {
         }
 
         // Check base catalog
-        let catalog = self.base_catalog.read().unwrap();
+        let catalog = self.base_catalog.read_or_err()?;
         Ok(catalog.list_schemas().contains(&name.to_string()))
     }
 
@@ -161,7 +162,7 @@ impl TransactionCatalog {
 
         // Check if table exists
         if !self.table_exists(schema_name, table_name)? {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
                 "Table '{}' does not exist",
                 table_name
             )));
@@ -187,7 +188,7 @@ impl TransactionCatalog {
         }
 
         // Check base catalog
-        let catalog = self.base_catalog.read().unwrap();
+        let catalog = self.base_catalog.read_or_err()?;
         Ok(catalog.table_exists(schema_name, table_name))
     }
 
@@ -234,7 +235,7 @@ impl TransactionCatalog {
 
         // Check if view exists
         if !self.view_exists(schema_name, view_name)? {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::View, format!(
                 "View '{}' does not exist",
                 view_name
             )));
@@ -260,7 +261,7 @@ impl TransactionCatalog {
         }
 
         // Check base catalog
-        let catalog = self.base_catalog.read().unwrap();
+        let catalog = self.base_catalog.read_or_err()?;
         Ok(catalog.view_exists(schema_name, view_name))
     }
 
@@ -305,7 +306,7 @@ impl TransactionCatalog {
 
         // Check if index exists
         if !self.index_exists(schema_name, index_name)? {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Index, format!(
                 "Index '{}' does not exist",
                 index_name
             )));
@@ -331,13 +332,13 @@ impl TransactionCatalog {
         }
 
         // Check base catalog
-        let catalog = self.base_catalog.read().unwrap();
+        let catalog = self.base_catalog.read_or_err()?;
         Ok(catalog.index_exists(schema_name, index_name))
     }
 
     /// Commit the transaction changes to the base catalog
     pub fn commit(self) -> PrismDBResult<()> {
-        let mut catalog = self.base_catalog.write().unwrap();
+        let mut catalog = self.base_catalog.write_or_err()?;
 
         // Apply schema changes
         for schema_name in &self.local_changes.created_schemas {
@@ -345,7 +346,7 @@ impl TransactionCatalog {
         }
 
         for schema_name in &self.local_changes.dropped_schemas {
-            catalog.drop_schema(schema_name)?;
+            catalog.drop_schema(schema_name, false)?;
         }
 
         // Apply table changes
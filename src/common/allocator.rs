@@ -1,8 +1,10 @@
 //! Memory allocation utilities
 
-use crate::common::error::Result;
+use crate::common::error::{PrismDBError, Result};
 use std::alloc::{GlobalAlloc, Layout, System};
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
 /// Custom allocator for PrismDB memory management
 pub struct PrismDBAllocator;
@@ -89,6 +91,344 @@ impl Default for ArenaAllocator {
     }
 }
 
+/// Per-query arena for temporary byte data such as hash table and group keys
+///
+/// Memory is bump-allocated from large blocks and released all at once when the arena
+/// is dropped or reset. Allocation takes `&self` so operators on different threads can
+/// share one arena through the `ExecutionContext`; every slice handed out borrows the
+/// arena, so the borrow checker keeps keys from outliving it.
+pub struct QueryArena {
+    state: Mutex<ArenaState>,
+    block_size: usize,
+}
+
+struct ArenaState {
+    /// Blocks are never moved or resized while the arena is shared
+    blocks: Vec<(NonNull<u8>, Layout)>,
+    /// Bytes used in the last block
+    offset: usize,
+    /// Bytes handed out across all blocks
+    allocated: usize,
+}
+
+// SAFETY: the blocks are owned by the arena and only written under the mutex, into
+// regions that have not been handed out yet. Handed-out slices are immutable.
+unsafe impl Send for QueryArena {}
+unsafe impl Sync for QueryArena {}
+
+impl QueryArena {
+    /// Default block size (64KB)
+    pub const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+    pub fn new() -> Self {
+        Self::with_block_size(Self::DEFAULT_BLOCK_SIZE)
+    }
+
+    pub fn with_block_size(block_size: usize) -> Self {
+        Self {
+            state: Mutex::new(ArenaState {
+                blocks: Vec::new(),
+                offset: 0,
+                allocated: 0,
+            }),
+            block_size: block_size.max(1),
+        }
+    }
+
+    /// Copy bytes into the arena
+    pub fn alloc_bytes(&self, bytes: &[u8]) -> &[u8] {
+        if bytes.is_empty() {
+            return &[];
+        }
+
+        let mut state = self.lock();
+        let fits = state
+            .blocks
+            .last()
+            .is_some_and(|(_, layout)| state.offset + bytes.len() <= layout.size());
+        if !fits {
+            // Oversized requests get a block of their own
+            let size = self.block_size.max(bytes.len());
+            let layout = Layout::array::<u8>(size).expect("arena block size overflows isize");
+            // SAFETY: layout has a non-zero size
+            let ptr = NonNull::new(unsafe { std::alloc::alloc(layout) })
+                .unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+            state.blocks.push((ptr, layout));
+            state.offset = 0;
+        }
+
+        let (block, _) = *state.blocks.last().expect("arena has a block");
+        // SAFETY: [offset, offset + len) lies inside the block and has not been handed
+        // out, so nothing else reads or writes it. The block lives until the arena is
+        // dropped or reset, both of which need the shared borrow on `self` to end.
+        let slice = unsafe {
+            let dst = block.as_ptr().add(state.offset);
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+            std::slice::from_raw_parts(dst, bytes.len())
+        };
+        state.offset += bytes.len();
+        state.allocated += bytes.len();
+        slice
+    }
+
+    /// Copy a string into the arena
+    pub fn alloc_str(&self, s: &str) -> &str {
+        // SAFETY: the bytes were copied verbatim from a valid str
+        unsafe { std::str::from_utf8_unchecked(self.alloc_bytes(s.as_bytes())) }
+    }
+
+    /// Bytes handed out since creation or the last reset
+    pub fn allocated_bytes(&self) -> usize {
+        self.lock().allocated
+    }
+
+    /// Number of blocks requested from the system allocator
+    pub fn block_count(&self) -> usize {
+        self.lock().blocks.len()
+    }
+
+    /// Free every block at once
+    pub fn reset(&mut self) {
+        let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+        for (ptr, layout) in state.blocks.drain(..) {
+            // SAFETY: each block was allocated with this layout and `&mut self`
+            // guarantees no slices into it are still alive
+            unsafe { std::alloc::dealloc(ptr.as_ptr(), layout) };
+        }
+        state.offset = 0;
+        state.allocated = 0;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ArenaState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for QueryArena {
+    fn drop(&mut self) {
+        self.reset();
+    }
+}
+
+impl Default for QueryArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for QueryArena {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("QueryArena")
+            .field("blocks", &state.blocks.len())
+            .field("allocated", &state.allocated)
+            .finish()
+    }
+}
+
+/// Memory budget shared by the operators of a query
+///
+/// Operators that buffer data (sorts, aggregate groups, join build sides, set operations)
+/// reserve the bytes they hold through a `MemoryReservation`. A reservation that would
+/// take usage past the limit fails with `PrismDBError::OutOfMemory`, so an oversized
+/// query errors out instead of exhausting the process's memory.
+#[derive(Debug, Default)]
+pub struct MemoryBudget {
+    /// Limit in bytes (None for unlimited)
+    limit: Option<usize>,
+    used: AtomicUsize,
+    peak: AtomicUsize,
+    /// Operator name and peak bytes of each operator reservation, in the order they started
+    operators: Mutex<Vec<(String, usize)>>,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            operators: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Get the limit in bytes
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Get the bytes currently reserved
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Get the most bytes reserved at once
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Take bytes from the budget, failing if that would exceed the limit
+    pub fn reserve(&self, bytes: usize) -> Result<()> {
+        let mut used = self.used.load(Ordering::Relaxed);
+        loop {
+            let next = used.saturating_add(bytes);
+            if self.limit.is_some_and(|limit| next > limit) {
+                return Err(PrismDBError::OutOfMemory);
+            }
+            match self
+                .used
+                .compare_exchange_weak(used, next, Ordering::AcqRel, Ordering::Relaxed)
+            {
+                Ok(_) => {
+                    self.peak.fetch_max(next, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(actual) => used = actual,
+            }
+        }
+    }
+
+    /// Return bytes to the budget
+    pub fn release(&self, bytes: usize) {
+        let _ = self
+            .used
+            .fetch_update(Ordering::AcqRel, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(bytes))
+            });
+    }
+
+    /// Operator name and peak bytes of every reservation made with
+    /// `MemoryReservation::for_operator`, in the order they started
+    ///
+    /// A reservation still held reports the bytes it holds so far.
+    pub fn operator_peaks(&self) -> Vec<(String, usize)> {
+        self.operators.lock().map(|operators| operators.clone()).unwrap_or_default()
+    }
+
+    fn register_operator(&self, operator: &str) -> Option<usize> {
+        let mut operators = self.operators.lock().ok()?;
+        operators.push((operator.to_string(), 0));
+        Some(operators.len() - 1)
+    }
+
+    fn record_operator_peak(&self, slot: usize, bytes: usize) {
+        if let Ok(mut operators) = self.operators.lock() {
+            if let Some((_, peak)) = operators.get_mut(slot) {
+                *peak = (*peak).max(bytes);
+            }
+        }
+    }
+}
+
+/// Bytes one operator holds against a `MemoryBudget`, returned when dropped
+///
+/// Growing takes `&self`, so threads working for the same operator can share one
+/// reservation.
+#[derive(Debug)]
+pub struct MemoryReservation {
+    budget: Arc<MemoryBudget>,
+    size: AtomicUsize,
+    /// Entry of the budget's operator list this reservation reports its peak to
+    slot: Option<usize>,
+}
+
+impl MemoryReservation {
+    pub fn new(budget: Arc<MemoryBudget>) -> Self {
+        Self {
+            budget,
+            size: AtomicUsize::new(0),
+            slot: None,
+        }
+    }
+
+    /// Start a reservation whose peak the budget lists under `operator`
+    pub fn for_operator(budget: Arc<MemoryBudget>, operator: &str) -> Self {
+        let slot = budget.register_operator(operator);
+        Self {
+            budget,
+            size: AtomicUsize::new(0),
+            slot,
+        }
+    }
+
+    /// Reserve additional bytes
+    ///
+    /// Reservations only grow, so the size after growing is the peak so far.
+    pub fn grow(&self, bytes: usize) -> Result<()> {
+        self.budget.reserve(bytes)?;
+        let size = self.size.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if let Some(slot) = self.slot {
+            self.budget.record_operator_peak(slot, size);
+        }
+        Ok(())
+    }
+
+    /// Get the bytes held by this reservation
+    pub fn size(&self) -> usize {
+        self.size.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.budget.release(*self.size.get_mut());
+    }
+}
+
+/// Parse a memory size such as `1GB`, `512 MiB` or `1000000` into bytes
+///
+/// Decimal units (KB, MB, GB, TB) are powers of 1000 and binary units (KiB, MiB, GiB,
+/// TiB) powers of 1024; a bare number is a byte count.
+pub fn parse_memory_size(text: &str) -> Result<usize> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || {
+        PrismDBError::InvalidArgument(format!(
+            "Invalid memory size '{}': expected a number with an optional unit such as KB, MB, GB or GiB",
+            text
+        ))
+    };
+
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" | "byte" | "bytes" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    Ok((number * multiplier as f64) as usize)
+}
+
+/// Format a byte count in the largest unit that divides it evenly, e.g. `1GB` or `64KiB`
+///
+/// The result parses back to the same byte count with `parse_memory_size`.
+pub fn format_memory_size(bytes: usize) -> String {
+    const UNITS: [(&str, usize); 8] = [
+        ("TiB", 1 << 40),
+        ("TB", 1_000_000_000_000),
+        ("GiB", 1 << 30),
+        ("GB", 1_000_000_000),
+        ("MiB", 1 << 20),
+        ("MB", 1_000_000),
+        ("KiB", 1 << 10),
+        ("KB", 1_000),
+    ];
+    UNITS
+        .iter()
+        .find(|(_, size)| bytes > 0 && bytes.is_multiple_of(*size))
+        .map(|(unit, size)| format!("{}{}", bytes / size, unit))
+        .unwrap_or_else(|| format!("{} bytes", bytes))
+}
+
 /// Buffer pool for managing reusable memory buffers
 pub struct BufferPool {
     buffers: Vec<Vec<u8>>,
@@ -160,6 +500,56 @@ mod tests {
         assert_eq!(arena.used_memory(), 0);
     }
 
+    #[test]
+    fn test_query_arena() {
+        let mut arena = QueryArena::with_block_size(16);
+
+        let a = arena.alloc_bytes(b"abcdefgh");
+        let b = arena.alloc_str("ijklmnop");
+        assert_eq!(a, b"abcdefgh");
+        assert_eq!(b, "ijklmnop");
+        assert_eq!(arena.block_count(), 1);
+
+        // Oversized and overflowing requests get new blocks without moving earlier data
+        let big = arena.alloc_bytes(&[7u8; 40]);
+        let c = arena.alloc_bytes(b"q");
+        assert_eq!(big, &[7u8; 40][..]);
+        assert_eq!(c, b"q");
+        assert_eq!(a, b"abcdefgh");
+        assert_eq!(arena.block_count(), 3);
+        assert_eq!(arena.allocated_bytes(), 57);
+
+        assert!(arena.alloc_bytes(&[]).is_empty());
+
+        arena.reset();
+        assert_eq!(arena.block_count(), 0);
+        assert_eq!(arena.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn test_query_arena_shared_across_threads() {
+        let arena = QueryArena::with_block_size(64);
+        let keys: Vec<Vec<&str>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|t| {
+                    let arena = &arena;
+                    scope.spawn(move || {
+                        (0..100)
+                            .map(|i| arena.alloc_str(&format!("{}-{}", t, i)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for (t, thread_keys) in keys.iter().enumerate() {
+            for (i, key) in thread_keys.iter().enumerate() {
+                assert_eq!(*key, format!("{}-{}", t, i));
+            }
+        }
+    }
+
     #[test]
     fn test_buffer_pool() {
         let mut pool = BufferPool::new(4096, 2);
@@ -179,4 +569,59 @@ mod tests {
         pool.release(buffer1);
         assert_eq!(pool.available_count(), 1);
     }
+
+    #[test]
+    fn test_memory_budget() {
+        let budget = Arc::new(MemoryBudget::new(Some(1000)));
+        let reservation = MemoryReservation::new(budget.clone());
+        reservation.grow(600).unwrap();
+        assert!(matches!(reservation.grow(500), Err(PrismDBError::OutOfMemory)));
+        assert_eq!(budget.used(), 600);
+
+        // Reservations share the budget and give their bytes back when dropped
+        let other = MemoryReservation::new(budget.clone());
+        other.grow(400).unwrap();
+        drop(reservation);
+        assert_eq!(budget.used(), 400);
+        assert_eq!(budget.peak(), 1000);
+        drop(other);
+        assert_eq!(budget.used(), 0);
+
+        let unlimited = Arc::new(MemoryBudget::new(None));
+        MemoryReservation::new(unlimited).grow(usize::MAX).unwrap();
+    }
+
+    #[test]
+    fn test_operator_peaks() {
+        let budget = Arc::new(MemoryBudget::new(None));
+        let sort = MemoryReservation::for_operator(budget.clone(), "ORDER_BY");
+        sort.grow(100).unwrap();
+        sort.grow(50).unwrap();
+        let aggregate = MemoryReservation::for_operator(budget.clone(), "HASH_AGGREGATE");
+        drop(sort);
+        aggregate.grow(30).unwrap();
+        MemoryReservation::new(budget.clone()).grow(1000).unwrap();
+
+        assert_eq!(
+            budget.operator_peaks(),
+            [("ORDER_BY".to_string(), 150), ("HASH_AGGREGATE".to_string(), 30)]
+        );
+    }
+
+    #[test]
+    fn test_parse_memory_size() {
+        assert_eq!(parse_memory_size("1GB").unwrap(), 1_000_000_000);
+        assert_eq!(parse_memory_size(" 512 MiB ").unwrap(), 512 * 1024 * 1024);
+        assert_eq!(parse_memory_size("1.5kb").unwrap(), 1_500);
+        assert_eq!(parse_memory_size("4096").unwrap(), 4096);
+        assert!(parse_memory_size("lots").is_err());
+        assert!(parse_memory_size("10 parsecs").is_err());
+
+        assert_eq!(format_memory_size(1_000_000_000), "1GB");
+        assert_eq!(format_memory_size(64 * 1024), "64KiB");
+        assert_eq!(format_memory_size(1_500), "1500 bytes");
+        for bytes in [0, 1_500, 2_000_000, 3 << 30] {
+            assert_eq!(parse_memory_size(&format_memory_size(bytes)).unwrap(), bytes);
+        }
+    }
 }
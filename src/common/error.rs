@@ -1,7 +1,22 @@
 //! Error handling for PrismDB Rust port
+//!
+//! Every error has a stable code, from [`PrismDBError::code`], for callers
+//! to branch on instead of matching message text.
 
 use thiserror::Error;
 
+/// Kind of named object a [`PrismDBError::NotFound`] failed to find
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Schema,
+    Table,
+    View,
+    Index,
+    Column,
+    Function,
+    Type,
+}
+
 /// Main error type for PrismDB operations
 #[derive(Error, Debug)]
 pub enum PrismDBError {
@@ -55,6 +70,91 @@ pub enum PrismDBError {
 
     #[error("Storage error: {0}")]
     Storage(String),
+
+    #[error("Read-only transaction: {0}")]
+    ReadOnly(String),
+
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+
+    /// A row broke a NOT NULL, UNIQUE or PRIMARY KEY constraint
+    #[error("Constraint violation: {0}")]
+    Constraint(String),
+
+    /// A statement named a schema, table, column, ... that doesn't exist
+    #[error("Catalog error: {1}")]
+    NotFound(ObjectKind, String),
+
+    /// Values of types that can't be combined, compared or cast to each other
+    #[error("Type mismatch: {0}")]
+    TypeMismatch(String),
+}
+
+impl PrismDBError {
+    /// Prefix the error message with `context`, keeping the error kind
+    pub fn with_context(self, context: impl std::fmt::Display) -> Self {
+        use PrismDBError::*;
+        match self {
+            InvalidArgument(msg) => InvalidArgument(format!("{}: {}", context, msg)),
+            InvalidValue(msg) => InvalidValue(format!("{}: {}", context, msg)),
+            InvalidType(msg) => InvalidType(format!("{}: {}", context, msg)),
+            Internal(msg) => Internal(format!("{}: {}", context, msg)),
+            Parse(msg) => Parse(format!("{}: {}", context, msg)),
+            Type(msg) => Type(format!("{}: {}", context, msg)),
+            Transaction(msg) => Transaction(format!("{}: {}", context, msg)),
+            Catalog(msg) => Catalog(format!("{}: {}", context, msg)),
+            Execution(msg) => Execution(format!("{}: {}", context, msg)),
+            NotImplemented(msg) => NotImplemented(format!("{}: {}", context, msg)),
+            Serialization(msg) => Serialization(format!("{}: {}", context, msg)),
+            Compression(msg) => Compression(format!("{}: {}", context, msg)),
+            Extension(msg) => Extension(format!("{}: {}", context, msg)),
+            Wal(msg) => Wal(format!("{}: {}", context, msg)),
+            Storage(msg) => Storage(format!("{}: {}", context, msg)),
+            ReadOnly(msg) => ReadOnly(format!("{}: {}", context, msg)),
+            LimitExceeded(msg) => LimitExceeded(format!("{}: {}", context, msg)),
+            Constraint(msg) => Constraint(format!("{}: {}", context, msg)),
+            NotFound(kind, msg) => NotFound(kind, format!("{}: {}", context, msg)),
+            TypeMismatch(msg) => TypeMismatch(format!("{}: {}", context, msg)),
+            OutOfMemory | Io(_) => self,
+        }
+    }
+
+    /// Stable code of the error's kind, such as `PARSE_ERROR` or `TABLE_NOT_FOUND`
+    ///
+    /// Codes never change between releases, unlike messages.
+    pub fn code(&self) -> &'static str {
+        use PrismDBError::*;
+        match self {
+            InvalidArgument(_) => "INVALID_ARGUMENT",
+            InvalidValue(_) => "INVALID_VALUE",
+            InvalidType(_) => "INVALID_TYPE",
+            OutOfMemory => "OUT_OF_MEMORY",
+            Internal(_) => "INTERNAL_ERROR",
+            Io(_) => "IO_ERROR",
+            Parse(_) => "PARSE_ERROR",
+            Type(_) => "TYPE_ERROR",
+            Transaction(_) => "TRANSACTION_ERROR",
+            Catalog(_) => "CATALOG_ERROR",
+            Execution(_) => "EXECUTION_ERROR",
+            NotImplemented(_) => "NOT_IMPLEMENTED",
+            Serialization(_) => "SERIALIZATION_ERROR",
+            Compression(_) => "COMPRESSION_ERROR",
+            Extension(_) => "EXTENSION_ERROR",
+            Wal(_) => "WAL_ERROR",
+            Storage(_) => "STORAGE_ERROR",
+            ReadOnly(_) => "READ_ONLY",
+            LimitExceeded(_) => "LIMIT_EXCEEDED",
+            Constraint(_) => "CONSTRAINT_VIOLATION",
+            NotFound(ObjectKind::Schema, _) => "SCHEMA_NOT_FOUND",
+            NotFound(ObjectKind::Table, _) => "TABLE_NOT_FOUND",
+            NotFound(ObjectKind::View, _) => "VIEW_NOT_FOUND",
+            NotFound(ObjectKind::Index, _) => "INDEX_NOT_FOUND",
+            NotFound(ObjectKind::Column, _) => "COLUMN_NOT_FOUND",
+            NotFound(ObjectKind::Function, _) => "FUNCTION_NOT_FOUND",
+            NotFound(ObjectKind::Type, _) => "TYPE_NOT_FOUND",
+            TypeMismatch(_) => "TYPE_MISMATCH",
+        }
+    }
 }
 
 /// Result type alias for convenience
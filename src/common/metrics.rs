@@ -0,0 +1,180 @@
+//! Database-wide counters for monitoring
+//!
+//! Every counter is an atomic updated with relaxed ordering, so recording a
+//! metric never takes a lock. Counters only grow; read them all at once with
+//! `Database::metrics()` or `SELECT * FROM prism_metrics()`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A counter the database keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// Statements run, including failed ones
+    QueriesExecuted,
+    /// Statements that returned an error
+    QueryErrors,
+    /// Rows read from table storage by scans, before filters
+    RowsScanned,
+    /// UPDATEs and DELETEs that found their rows through an index
+    IndexLookups,
+    /// Pipelines run by the pipeline scheduler
+    PipelinesScheduled,
+    /// SELECTs answered from the query cache
+    QueryCacheHits,
+    /// Cacheable SELECTs that had to be run
+    QueryCacheMisses,
+    /// SELECTs run with a plan from the plan cache
+    PlanCacheHits,
+    /// SELECTs that had to be planned for the plan cache
+    PlanCacheMisses,
+    /// Statements that failed for exceeding the memory limit
+    MemoryLimitErrors,
+    /// Physical plan nodes translated into execution operators
+    OperatorsBuilt,
+}
+
+impl Metric {
+    /// Every metric, in the order they are listed
+    pub const ALL: [Metric; 11] = [
+        Metric::QueriesExecuted,
+        Metric::QueryErrors,
+        Metric::RowsScanned,
+        Metric::IndexLookups,
+        Metric::PipelinesScheduled,
+        Metric::QueryCacheHits,
+        Metric::QueryCacheMisses,
+        Metric::PlanCacheHits,
+        Metric::PlanCacheMisses,
+        Metric::MemoryLimitErrors,
+        Metric::OperatorsBuilt,
+    ];
+
+    /// Name the metric is listed under
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::QueriesExecuted => "queries_executed",
+            Metric::QueryErrors => "query_errors",
+            Metric::RowsScanned => "rows_scanned",
+            Metric::IndexLookups => "index_lookups",
+            Metric::PipelinesScheduled => "pipelines_scheduled",
+            Metric::QueryCacheHits => "query_cache_hits",
+            Metric::QueryCacheMisses => "query_cache_misses",
+            Metric::PlanCacheHits => "plan_cache_hits",
+            Metric::PlanCacheMisses => "plan_cache_misses",
+            Metric::MemoryLimitErrors => "memory_limit_errors",
+            Metric::OperatorsBuilt => "operators_built",
+        }
+    }
+
+    /// What the metric counts
+    pub fn description(self) -> &'static str {
+        match self {
+            Metric::QueriesExecuted => "Statements run, including failed ones",
+            Metric::QueryErrors => "Statements that returned an error",
+            Metric::RowsScanned => "Rows read from table storage by scans, before filters",
+            Metric::IndexLookups => "UPDATEs and DELETEs that found their rows through an index",
+            Metric::PipelinesScheduled => "Pipelines run by the pipeline scheduler",
+            Metric::QueryCacheHits => "SELECTs answered from the query cache",
+            Metric::QueryCacheMisses => "Cacheable SELECTs that had to be run",
+            Metric::PlanCacheHits => "SELECTs run with a plan from the plan cache",
+            Metric::PlanCacheMisses => "SELECTs that had to be planned for the plan cache",
+            Metric::MemoryLimitErrors => "Statements that failed for exceeding the memory limit",
+            Metric::OperatorsBuilt => "Physical plan nodes translated into execution operators",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Live counters, shared by a database and the queries it runs
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    counters: [AtomicU64; Metric::ALL.len()],
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `amount` to a counter
+    pub fn add(&self, metric: Metric, amount: u64) {
+        self.counters[metric.index()].fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Add one to a counter
+    pub fn increment(&self, metric: Metric) {
+        self.add(metric, 1);
+    }
+
+    /// Current value of a counter
+    pub fn get(&self, metric: Metric) -> u64 {
+        self.counters[metric.index()].load(Ordering::Relaxed)
+    }
+
+    /// Values of every counter
+    ///
+    /// Each counter is read on its own, so updates racing with the snapshot
+    /// may be reflected in some counters and not others.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            values: Metric::ALL.map(|metric| self.get(metric)),
+        }
+    }
+}
+
+/// Counter values read at one point in time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    values: [u64; Metric::ALL.len()],
+}
+
+impl MetricsSnapshot {
+    /// Value of a counter
+    pub fn get(&self, metric: Metric) -> u64 {
+        self.values[metric.index()]
+    }
+
+    /// Every metric with its value
+    pub fn iter(&self) -> impl Iterator<Item = (Metric, u64)> + '_ {
+        Metric::ALL.iter().map(|&metric| (metric, self.get(metric)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_order_matches_index() {
+        for (i, metric) in Metric::ALL.iter().enumerate() {
+            assert_eq!(metric.index(), i);
+        }
+    }
+
+    #[test]
+    fn test_counters_add_across_threads() {
+        let registry = std::sync::Arc::new(MetricsRegistry::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let registry = registry.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        registry.increment(Metric::RowsScanned);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        registry.add(Metric::QueryErrors, 3);
+
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot.get(Metric::RowsScanned), 4000);
+        assert_eq!(snapshot.get(Metric::QueryErrors), 3);
+        assert_eq!(snapshot.get(Metric::QueriesExecuted), 0);
+    }
+}
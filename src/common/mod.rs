@@ -4,8 +4,12 @@ pub mod allocator;
 pub mod constants;
 pub mod error;
 pub mod helper;
+pub mod metrics;
+pub mod sync;
 
 pub use allocator::*;
 pub use constants::*;
 pub use error::*;
 pub use helper::*;
+pub use metrics::*;
+pub use sync::*;
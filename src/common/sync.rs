@@ -0,0 +1,67 @@
+//! Lock acquisition that survives poisoning
+//!
+//! A lock is poisoned when a thread panics while holding it. Calling
+//! `unwrap()` on the acquisition then panics every later user too, so one
+//! failed operator would take down unrelated queries. The `*_or_err` methods
+//! turn poisoning into a `PrismDBError::Internal` the caller can return; the
+//! `*_recovered` ones take the guard anyway, for data such as caches and
+//! counters that stay valid whatever a panicking writer left behind.
+
+use crate::common::error::{PrismDBError, PrismDBResult};
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+fn poisoned<T>(_: PoisonError<T>) -> PrismDBError {
+    PrismDBError::Internal("lock poisoned by a thread that panicked while holding it".to_string())
+}
+
+/// Poison-aware acquisition of a `RwLock`
+pub trait RwLockExt<T> {
+    /// Read guard, or an error if the lock is poisoned
+    fn read_or_err(&self) -> PrismDBResult<RwLockReadGuard<'_, T>>;
+
+    /// Write guard, or an error if the lock is poisoned
+    fn write_or_err(&self) -> PrismDBResult<RwLockWriteGuard<'_, T>>;
+
+    /// Read guard, even if the lock is poisoned
+    fn read_recovered(&self) -> RwLockReadGuard<'_, T>;
+
+    /// Write guard, even if the lock is poisoned
+    fn write_recovered(&self) -> RwLockWriteGuard<'_, T>;
+}
+
+impl<T> RwLockExt<T> for RwLock<T> {
+    fn read_or_err(&self) -> PrismDBResult<RwLockReadGuard<'_, T>> {
+        self.read().map_err(poisoned)
+    }
+
+    fn write_or_err(&self) -> PrismDBResult<RwLockWriteGuard<'_, T>> {
+        self.write().map_err(poisoned)
+    }
+
+    fn read_recovered(&self) -> RwLockReadGuard<'_, T> {
+        self.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_recovered(&self) -> RwLockWriteGuard<'_, T> {
+        self.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Poison-aware acquisition of a `Mutex`
+pub trait MutexExt<T> {
+    /// Guard, or an error if the mutex is poisoned
+    fn lock_or_err(&self) -> PrismDBResult<MutexGuard<'_, T>>;
+
+    /// Guard, even if the mutex is poisoned
+    fn lock_recovered(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> MutexExt<T> for Mutex<T> {
+    fn lock_or_err(&self) -> PrismDBResult<MutexGuard<'_, T>> {
+        self.lock().map_err(poisoned)
+    }
+
+    fn lock_recovered(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -3,21 +3,152 @@
 //! This module provides the main Database struct that ties together
 //! all components: catalog, storage, transactions, parser, planner, and executor.
 
-use crate::catalog::Catalog;
-use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::execution::{CollectedResult, ExecutionContext, ExecutionEngine, ExecutionStats};
+use crate::catalog::{Catalog, IndexInfo, IndexOptions, IndexType, SqlFunction};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::metrics::{Metric, MetricsRegistry, MetricsSnapshot};
+use crate::common::sync::{MutexExt, RwLockExt};
+use crate::execution::parallel::ParallelContext;
+use crate::execution::{CollectedResult, ExecutionContext, ExecutionStats, PipelineScheduler};
+use crate::expression::expression::{ConstantExpression, ExpressionRef};
+use crate::expression::function::{type_name, FunctionRegistry};
+use crate::expression::{AggregateState, ApproxCountDistinctState, ArithmeticErrorMode, TypeCoercion};
+use crate::extensions::config::{find_setting, SettingValue, SETTINGS};
 use crate::extensions::{ConfigManager, ExtensionManager, SecretsManager};
-use crate::extensions::csv_reader::CsvReader;
-use crate::extensions::file_reader::FileReader;
+use crate::extensions::csv_reader::{widen_type, CsvOptions, CsvReader};
+use crate::extensions::file_reader::{FileCompression, FileReader, RemoteFile};
+use crate::extensions::file_writer::{CopyFormat, FileWriter};
 use crate::extensions::json_reader::JsonReader;
 use crate::extensions::parquet_reader::ParquetReader;
 use crate::extensions::sqlite_reader::SqliteReader;
-use crate::parser::{tokenizer::Tokenizer, Parser, Statement, SetValue, TableReference, Expression, SelectStatement};
-use crate::planner::{LogicalPlan, QueryOptimizer, QueryPlanner};
-use crate::storage::{BlockManager, TransactionManager};
-use crate::types::{DataChunk, LogicalType, Value};
+use crate::parser::{CopyFromStatement, CopyStatement, CreateIndexStatement, DropIndexStatement, IdentifierCase, ParseErrorMode, SqlParser, DescribeStatement, PragmaStatement, Statement, SetValue, ShowStatement, TableReference, Expression, SelectStatement, TransactionMode, VacuumStatement, AnalyzeStatement};
+use crate::planner::{
+    Column, DataChunkStream, LogicalCreateTable, LogicalDropTable, LogicalPlan, NullOrder, PhysicalColumn,
+    PhysicalInsert, PhysicalPlan, PhysicalValues, QueryOptimizer, QueryPlanner,
+};
+use crate::plan_cache::{PlanCache, PlanCacheStats};
+use crate::query_cache::{QueryCache, QueryCacheStats};
+use crate::storage::{
+    BlockManager, BLOCK_SIZE, ColumnInfo, IsolationLevel, ReadView, StringLengthMode, TableData,
+    TransactionManager, UniqueKeys,
+};
+use crate::types::{Collation, DataChunk, EnumType, LogicalType, OrderedValue, Value, Vector};
+use parquet::file::reader::ChunkReader;
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// The literal value of a named table function argument
+///
+/// A negative number, parsed as a negation, is folded into its literal.
+fn named_literal(name: &str, value: &Expression) -> PrismDBResult<crate::parser::LiteralValue> {
+    use crate::parser::{LiteralValue, UnaryOperator};
+
+    match value {
+        Expression::Literal(literal) => Ok(literal.clone()),
+        Expression::Unary { operator: UnaryOperator::Minus, expression } => match expression.as_ref() {
+            Expression::Literal(LiteralValue::Integer(n)) => Ok(LiteralValue::Integer(-n)),
+            Expression::Literal(LiteralValue::Float(n)) => Ok(LiteralValue::Float(-n)),
+            _ => Err(PrismDBError::InvalidArgument(format!("Argument '{}' must be a constant", name))),
+        },
+        _ => Err(PrismDBError::InvalidArgument(format!("Argument '{}' must be a constant", name))),
+    }
+}
+
+/// Fail if a table function that takes no named arguments was given some
+fn no_named_arguments(function: &str, named_arguments: &[(String, Expression)]) -> PrismDBResult<()> {
+    match named_arguments.first() {
+        Some((name, _)) => Err(PrismDBError::InvalidArgument(format!(
+            "{} has no argument named '{}'",
+            function, name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Compression of a file a table function reads: the `compression => '...'`
+/// argument if one is given, otherwise detected from the file extension
+fn file_compression(url: &str, named_arguments: &[(String, Expression)]) -> PrismDBResult<FileCompression> {
+    for (name, value) in named_arguments {
+        if name == "compression" {
+            return match named_literal(name, value)? {
+                crate::parser::LiteralValue::String(option) => FileCompression::from_option(&option, url),
+                other => Err(PrismDBError::InvalidArgument(format!(
+                    "Invalid value {:?} for option 'compression'",
+                    other
+                ))),
+            };
+        }
+    }
+    Ok(FileCompression::from_path(url))
+}
+
+/// The named arguments of read_csv, besides `compression`
+fn csv_options(named_arguments: &[(String, Expression)]) -> PrismDBResult<CsvOptions> {
+    use crate::parser::LiteralValue;
+
+    let mut options = CsvOptions::default();
+    for (name, value) in named_arguments {
+        let value = &named_literal(name, value)?;
+        let invalid = || {
+            PrismDBError::InvalidArgument(format!("Invalid value {:?} for CSV option '{}'", value, name))
+        };
+        let single_byte = || match value {
+            LiteralValue::String(s) if s.len() == 1 => Ok(s.as_bytes()[0]),
+            LiteralValue::String(s) if s == "\\t" => Ok(b'\t'),
+            _ => Err(invalid()),
+        };
+        match name.as_str() {
+            "compression" => {}
+            "delim" | "sep" | "delimiter" => options.delimiter = single_byte()?,
+            "quote" => options.quote = single_byte()?,
+            "header" => {
+                options.header = match value {
+                    LiteralValue::Boolean(header) => *header,
+                    _ => return Err(invalid()),
+                }
+            }
+            "nullstr" => {
+                options.null_string = match value {
+                    LiteralValue::String(s) => s.clone(),
+                    _ => return Err(invalid()),
+                }
+            }
+            "sample_size" => {
+                options.sample_size = match value {
+                    LiteralValue::Integer(n) if *n > 0 => *n as usize,
+                    // -1 samples the whole file
+                    LiteralValue::Integer(-1) => usize::MAX,
+                    _ => return Err(invalid()),
+                }
+            }
+            "columns" => {
+                // Written like the body of CREATE TABLE: 'id INTEGER, name VARCHAR'
+                let definitions = match value {
+                    LiteralValue::String(s) => s,
+                    _ => return Err(invalid()),
+                };
+                let columns = match crate::parser::parse_sql(&format!("CREATE TABLE t ({})", definitions)) {
+                    Ok(Statement::CreateTable(create)) => create.columns,
+                    _ => return Err(invalid()),
+                };
+                options.columns = Some(columns.into_iter().map(|c| (c.name, c.data_type)).collect());
+            }
+            _ => {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "Unknown CSV option '{}'",
+                    name
+                )))
+            }
+        }
+    }
+    Ok(options)
+}
+
+/// A result column name without the `table.` prefix `SELECT *` gives it
+fn bare_column_name(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
 
 /// Main database instance
 #[derive(Clone)]
@@ -35,8 +166,15 @@ pub struct Database {
     /// Secrets manager
     secrets_manager: Arc<SecretsManager>,
     /// Database configuration
-    #[allow(dead_code)]
     config: DatabaseConfig,
+    /// Transaction opened with BEGIN, until COMMIT or ROLLBACK
+    transaction: Arc<RwLock<Option<uuid::Uuid>>>,
+    /// Results of recent SELECTs, used when the config enables it
+    query_cache: Arc<QueryCache>,
+    /// Plans of recent SELECTs by shape, used when the config enables it
+    plan_cache: Arc<PlanCache>,
+    /// Counters of the work done by every statement run
+    metrics: Arc<MetricsRegistry>,
 }
 
 impl Database {
@@ -56,7 +194,11 @@ impl Database {
             extension_manager,
             config_manager,
             secrets_manager,
+            query_cache: Arc::new(QueryCache::new(config.query_cache_capacity)),
+            plan_cache: Arc::new(PlanCache::new(config.plan_cache_capacity)),
             config,
+            transaction: Arc::new(RwLock::new(None)),
+            metrics: Arc::new(MetricsRegistry::new()),
         })
     }
 
@@ -90,7 +232,11 @@ impl Database {
             extension_manager: Arc::new(ExtensionManager::new()),
             config_manager: Arc::new(ConfigManager::new()),
             secrets_manager: Arc::new(SecretsManager::new()),
+            query_cache: Arc::new(QueryCache::new(config.query_cache_capacity)),
+            plan_cache: Arc::new(PlanCache::new(config.plan_cache_capacity)),
             config,
+            transaction: Arc::new(RwLock::new(None)),
+            metrics: Arc::new(MetricsRegistry::new()),
         })
     }
 
@@ -113,46 +259,257 @@ impl Database {
     }
 
     /// Execute a SQL query and collect results
+    ///
+    /// `sql` may hold several semicolon-separated statements; they run in
+    /// order and the result of the last one is returned.
     pub fn execute_sql_collect(&self, sql: &str) -> PrismDBResult<QueryResult> {
-        // Tokenize the SQL
-        let tokenizer = Tokenizer::new();
-        let tokens = tokenizer.tokenize(sql)?;
+        let statements = self.parse(sql)?;
+        if let [Statement::Select(select)] = statements.as_slice() {
+            if let Some(key) = self.query_cache_key(sql)? {
+                return self.record_statement(self.execute_cached_select(key, select));
+            }
+            if self.config.enable_plan_cache {
+                return self.record_statement(self.execute_with_plan_cache(select));
+            }
+        }
+        Ok(self.execute_statements(&statements)?.pop().unwrap_or_else(QueryResult::empty))
+    }
+
+    /// Execute semicolon-separated statements in order, returning one result
+    /// per statement
+    ///
+    /// Execution stops at the first failing statement; its error names the
+    /// statement's position in the batch.
+    pub fn execute_batch(&self, sql: &str) -> PrismDBResult<Vec<QueryResult>> {
+        self.execute_statements(&self.parse(sql)?)
+    }
+
+    /// Parse semicolon-separated statements, folding unquoted identifiers as configured
+    fn parse(&self, sql: &str) -> PrismDBResult<Vec<Statement>> {
+        SqlParser::new()
+            .with_identifier_case(self.config.identifier_case)
+            .with_error_mode(self.config.parse_error_mode)
+            .parse_multiple(sql)
+    }
+
+    fn execute_statements(&self, statements: &[Statement]) -> PrismDBResult<Vec<QueryResult>> {
+        let count = statements.len();
+
+        statements
+            .iter()
+            .enumerate()
+            .map(|(idx, statement)| {
+                let result = self.record_statement(self.execute_statement(statement));
+                let invalidated = self.invalidate_query_cache(statement);
+                result.and_then(|result| invalidated.map(|()| result)).map_err(|e| {
+                    if count > 1 {
+                        e.with_context(format!("Statement {} of {}", idx + 1, count))
+                    } else {
+                        e
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Count a statement that ran, and whether it failed
+    fn record_statement(&self, result: PrismDBResult<QueryResult>) -> PrismDBResult<QueryResult> {
+        self.metrics.increment(Metric::QueriesExecuted);
+        if let Err(e) = &result {
+            self.metrics.increment(Metric::QueryErrors);
+            if matches!(e, PrismDBError::OutOfMemory) {
+                self.metrics.increment(Metric::MemoryLimitErrors);
+            }
+        }
+        result
+    }
+
+    /// Current values of the database's counters
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
 
-        // Parse the SQL
-        let mut parser = Parser::new(tokens);
-        let statements = parser.parse_statements()?;
+    /// Query cache key of `sql`, or None if its result must not be cached
+    ///
+    /// Inside a transaction a query may see the transaction's own uncommitted
+    /// writes, so the cache is only used outside one.
+    fn query_cache_key(&self, sql: &str) -> PrismDBResult<Option<String>> {
+        if !self.config.enable_query_cache || self.transaction.read_or_err()?.is_some() {
+            return Ok(None);
+        }
+        Ok(QueryCache::normalize(sql, self.config.identifier_case))
+    }
 
-        if statements.is_empty() {
-            return Ok(QueryResult::empty());
+    /// Answer a SELECT from the query cache, or run it and cache its result
+    fn execute_cached_select(&self, key: String, select: &SelectStatement) -> PrismDBResult<QueryResult> {
+        if let Some(result) = self.query_cache.get(&key, &*self.catalog.read_or_err()?) {
+            self.metrics.increment(Metric::QueryCacheHits);
+            return Ok(result);
+        }
+        // Table functions read files, whose changes the cache cannot see
+        if let Some(result) = self.try_execute_table_function(select)? {
+            return Ok(result);
+        }
+        self.metrics.increment(Metric::QueryCacheMisses);
+
+        // Take the version first: a write that lands during execution leaves
+        // its table with a newer version, so the entry is never used
+        let version = self.catalog.read_or_err()?.latest_table_version();
+        let (logical_plan, ctes) = self.plan_statement(&Statement::Select(select.clone()))?;
+        let (result, tables_read) = self.execute_plan_reading(logical_plan, ctes)?;
+        self.query_cache.insert(key, &result, tables_read, version);
+        Ok(result)
+    }
+
+    /// Run a SELECT with the plan cached for its shape, planning and caching
+    /// one first if there is none
+    fn execute_with_plan_cache(&self, select: &SelectStatement) -> PrismDBResult<QueryResult> {
+        // Table functions are answered without a plan
+        if let Some(result) = self.try_execute_table_function(select)? {
+            return Ok(result);
+        }
+
+        let started = Instant::now();
+        let (shape, parameters) = PlanCache::parameterize(select);
+        let parameter_types: Vec<LogicalType> = parameters.iter().map(Value::get_type).collect();
+        let key = PlanCache::key(&shape, &parameter_types);
+        let (physical_plan, columns) = match self.plan_cache.get(&key) {
+            Some(cached) => {
+                self.metrics.increment(Metric::PlanCacheHits);
+                cached
+            }
+            None => {
+                self.metrics.increment(Metric::PlanCacheMisses);
+                let generation = self.plan_cache.generation();
+                let (logical_plan, ctes) = self.plan_statement(&Statement::Select(shape))?;
+                let (physical_plan, columns) = self.optimize_plan(logical_plan, ctes, parameter_types)?;
+                self.plan_cache.insert(key, &physical_plan, &columns, generation);
+                (physical_plan, columns)
+            }
+        };
+
+        let mut context = self.execution_context()?;
+        context.parameter_values = Arc::new(parameters);
+        Ok(self.run_physical_plan(physical_plan, columns, context, started)?.0)
+    }
+
+    /// Give the tables `statement` modified new versions, so cached results
+    /// computed from them are no longer used
+    ///
+    /// Statements whose effects reach beyond one table's rows (DDL, SET,
+    /// COMMIT and ROLLBACK) clear the whole query cache, and the plan cache
+    /// with it.
+    fn invalidate_query_cache(&self, statement: &Statement) -> PrismDBResult<()> {
+        let catalog = self.catalog.read_or_err()?;
+        match statement {
+            Statement::Insert(insert) => catalog.bump_table_version(&insert.table_name),
+            Statement::Update(update) => catalog.bump_table_version(&update.table_name),
+            Statement::Delete(delete) => catalog.bump_table_version(&delete.table_name),
+            Statement::Merge(merge) => catalog.bump_table_version(&merge.table_name),
+            Statement::CopyFrom(copy) => catalog.bump_table_version(&copy.table_name),
+            Statement::Select(_)
+            | Statement::Describe(_)
+            | Statement::Summarize(_)
+            | Statement::Show(_)
+            | Statement::Copy(_)
+            | Statement::Begin(_) => {}
+            _ => {
+                self.query_cache.clear();
+                self.plan_cache.clear();
+            }
         }
+        Ok(())
+    }
 
-        // Execute all statements but return only the last result
-        let mut last_result = QueryResult::empty();
-        for (idx, statement) in statements.iter().enumerate() {
-            let _is_last = idx == statements.len() - 1;
+    /// Hit and miss counts of the query cache
+    pub fn query_cache_stats(&self) -> QueryCacheStats {
+        self.query_cache.stats()
+    }
+
+    /// Hit and miss counts of the plan cache
+    pub fn plan_cache_stats(&self) -> PlanCacheStats {
+        self.plan_cache.stats()
+    }
 
+    /// Execute a single parsed statement
+    fn execute_statement(&self, statement: &Statement) -> PrismDBResult<QueryResult> {
         // Handle special statements that don't require planning/execution
         match statement {
             Statement::Install(install) => {
                 self.extension_manager.install(&install.extension_name)?;
-                last_result = QueryResult::empty();
-                continue;
+                return Ok(QueryResult::empty());
             }
             Statement::Load(load) => {
                 self.extension_manager.load(&load.extension_name)?;
-                last_result = QueryResult::empty();
-                continue;
+                return Ok(QueryResult::empty());
             }
             Statement::Set(set) => {
-                let value_str = match &set.value {
-                    SetValue::String(s) => s.clone(),
-                    SetValue::Number(n) => n.to_string(),
-                    SetValue::Boolean(b) => b.to_string(),
-                    SetValue::Default => "DEFAULT".to_string(),
-                };
-                self.config_manager.set(&set.variable, value_str);
-                last_result = QueryResult::empty();
-                continue;
+                self.apply_setting(&set.variable, &set.value)?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::Pragma(pragma) => {
+                return self.execute_pragma(pragma);
+            }
+            Statement::CreateIndex(create) => {
+                self.create_index(create)?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::DropIndex(drop) => {
+                self.drop_index(drop)?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::CreateType(create) => {
+                let catalog = self.catalog.read_or_err()?;
+                catalog.current_schema()?.write_or_err()?.create_type(EnumType::new(
+                    create.type_name.clone(),
+                    create.values.clone(),
+                ))?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::CreateFunction(create) => {
+                let catalog = self.catalog.read_or_err()?;
+                catalog.current_schema()?.write_or_err()?.create_function(
+                    SqlFunction {
+                        name: create.function_name.clone(),
+                        parameters: create.parameters.clone(),
+                        return_type: create.return_type.clone(),
+                        body: create.body.clone(),
+                    },
+                    create.or_replace,
+                )?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::DropFunction(drop) => {
+                let catalog = self.catalog.read_or_err()?;
+                let schema = catalog.search_path().iter().find(|schema| {
+                    catalog.get_function(schema, &drop.function_name).is_some()
+                });
+                match schema {
+                    Some(schema) => catalog.drop_function(schema, &drop.function_name)?,
+                    None if drop.if_exists => {}
+                    None => {
+                        return Err(PrismDBError::NotFound(ObjectKind::Function, format!(
+                            "Function '{}' does not exist",
+                            drop.function_name
+                        )))
+                    }
+                }
+                return Ok(QueryResult::empty());
+            }
+            Statement::CreateSchema(create) => {
+                let mut catalog = self.catalog.write_or_err()?;
+                if !create.if_not_exists || catalog.get_schema(&create.schema_name).is_err() {
+                    catalog.create_schema(&create.schema_name)?;
+                }
+                return Ok(QueryResult::empty());
+            }
+            Statement::DropSchema(drop) => {
+                let mut catalog = self.catalog.write_or_err()?;
+                if drop.if_exists && catalog.get_schema(&drop.schema_name).is_err() {
+                    return Ok(QueryResult::empty());
+                }
+                catalog.drop_schema(&drop.schema_name, drop.cascade)?;
+                return Ok(QueryResult::empty());
             }
             Statement::CreateSecret(secret) => {
                 self.secrets_manager.create_secret(
@@ -161,14 +518,59 @@ impl Database {
                     secret.options.clone(),
                     secret.or_replace,
                 )?;
-                last_result = QueryResult::empty();
-                continue;
+                return Ok(QueryResult::empty());
+            }
+            Statement::Begin(begin) => {
+                self.begin_transaction(begin.transaction_mode.as_ref())?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::Commit(_) => {
+                self.end_transaction(true)?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::Rollback(_) => {
+                self.end_transaction(false)?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::Describe(DescribeStatement::Table { table_name }) => {
+                return self.describe_table(table_name);
+            }
+            Statement::Describe(DescribeStatement::Query(query)) => {
+                return self.describe_query(query);
+            }
+            Statement::Summarize(summarize) => {
+                return self.summarize(&summarize.query);
+            }
+            Statement::Show(ShowStatement::CreateTable { table }) => {
+                return self.show_create_table(table);
+            }
+            Statement::Show(ShowStatement::Variables) => {
+                return self.show_variables();
+            }
+            Statement::Show(ShowStatement::Variable { name }) => {
+                return self.show_variable(name);
+            }
+            Statement::Show(ShowStatement::Functions) => {
+                return self.show_functions();
+            }
+            Statement::Copy(copy) => {
+                return self.execute_copy(copy);
+            }
+            Statement::CopyFrom(copy) => {
+                return self.execute_copy_from(copy);
+            }
+            Statement::Vacuum(vacuum) => {
+                self.vacuum(vacuum)?;
+                return Ok(QueryResult::empty());
+            }
+            Statement::Analyze(analyze) => {
+                self.analyze(analyze)?;
+                return Ok(QueryResult::empty());
             }
             Statement::Select(select) => {
                 // Check if this is a simple table function call
                 if let Some(result) = self.try_execute_table_function(select)? {
-                    last_result = result;
-                    continue;
+                    return Ok(result);
                 }
             }
             _ => {}
@@ -178,90 +580,1275 @@ impl Database {
         let (logical_plan, ctes) = self.plan_statement(statement)?;
 
         // Execute the plan with CTEs (optimization happens inside execute_plan)
-        last_result = self.execute_plan(logical_plan, ctes)?;
+        let result = self.execute_plan(logical_plan, ctes)?;
+        if let Statement::Delete(delete) = statement {
+            self.auto_vacuum(&delete.table_name)?;
+        }
+        Ok(result)
+    }
+
+    /// BEGIN: open a transaction that later statements run in
+    fn begin_transaction(&self, mode: Option<&TransactionMode>) -> PrismDBResult<()> {
+        let mut transaction = self.transaction.write_or_err()?;
+        if transaction.is_some() {
+            return Err(PrismDBError::Transaction(
+                "Transaction already active".to_string(),
+            ));
+        }
+
+        let isolation_level = match mode {
+            Some(TransactionMode::Serializable) => IsolationLevel::Serializable,
+            Some(TransactionMode::RepeatableRead) => IsolationLevel::RepeatableRead,
+            _ => IsolationLevel::ReadCommitted,
+        };
+        let read_only = matches!(mode, Some(TransactionMode::ReadOnly));
+        let transaction_id = self
+            .transaction_manager
+            .begin_transaction_with_mode(isolation_level, read_only)?;
+        *transaction = Some(transaction_id);
+        Ok(())
+    }
+
+    /// Data of every table in the catalog
+    fn table_data(&self) -> PrismDBResult<Vec<Arc<RwLock<TableData>>>> {
+        let catalog = self.catalog.read_or_err()?;
+        let mut tables = Vec::new();
+        for schema in catalog.list_schemas() {
+            for table in catalog.list_tables(&schema)? {
+                tables.push(catalog.get_table(&schema, &table)?.read_or_err()?.get_data());
+            }
+        }
+        Ok(tables)
+    }
+
+    /// COMMIT or ROLLBACK the transaction opened with BEGIN
+    ///
+    /// A transaction aborted by a serialization failure has already had its
+    /// writes undone: ROLLBACK just ends it and COMMIT reports that it did not
+    /// commit.
+    fn end_transaction(&self, commit: bool) -> PrismDBResult<()> {
+        let transaction_id = self.transaction.write_or_err()?.take().ok_or_else(|| {
+            PrismDBError::Transaction("No active transaction".to_string())
+        })?;
+        if !self.transaction_manager.is_transaction_active(transaction_id) {
+            return if commit {
+                Err(PrismDBError::Transaction(
+                    "Transaction was aborted and has been rolled back".to_string(),
+                ))
+            } else {
+                Ok(())
+            };
+        }
+        if commit {
+            self.transaction_manager.commit_transaction(transaction_id)
+        } else {
+            self.transaction_manager.abort_transaction(transaction_id)
+        }
+    }
+
+    /// SHOW CREATE TABLE: a single `sql` row holding the table's DDL
+    fn show_create_table(&self, table_name: &str) -> PrismDBResult<QueryResult> {
+        let catalog = self
+            .catalog
+            .read()
+            .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
+        let schema_name = catalog.table_schema(table_name)?.read_or_err()?.name.clone();
+        let ddl = catalog.get_table_ddl(&schema_name, table_name)?;
+
+        QueryResult::from_rows(
+            vec![ColumnMetadata {
+                name: "sql".to_string(),
+                data_type: LogicalType::Varchar,
+            }],
+            vec![vec![Value::Varchar(ddl)]],
+        )
+    }
+
+    /// CREATE INDEX: record the index in the catalog and build it over the
+    /// table's rows
+    ///
+    /// Indexes cover a single column and do not enforce uniqueness.
+    fn create_index(&self, create: &CreateIndexStatement) -> PrismDBResult<()> {
+        let column_name = match create.columns.as_slice() {
+            [column] => column,
+            _ => {
+                return Err(PrismDBError::NotImplemented(
+                    "Indexes on more than one column are not supported".to_string(),
+                ))
+            }
+        };
+        if create.unique {
+            return Err(PrismDBError::NotImplemented(
+                "UNIQUE indexes are not supported".to_string(),
+            ));
+        }
+
+        let catalog = self
+            .catalog
+            .read()
+            .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
+        // The index lives in the schema of the table it covers
+        let schema = catalog.table_schema(&create.table_name)?;
+        let schema_name = schema.read_or_err()?.get_name().to_string();
+        if create.if_not_exists && schema.read_or_err()?.index_exists(&create.index_name) {
+            return Ok(());
+        }
+        let table = catalog.get_table(&schema_name, &create.table_name)?;
+        let table = table.read_or_err()?;
+        let column = table.get_table_info().get_column_index(column_name).ok_or_else(|| {
+            PrismDBError::NotFound(ObjectKind::Column, format!(
+                "Column '{}' does not exist in table '{}'",
+                column_name, create.table_name
+            ))
+        })?;
+
+        catalog.create_index(&IndexInfo {
+            index_name: create.index_name.clone(),
+            schema_name: schema_name.clone(),
+            table_name: create.table_name.clone(),
+            column_names: create.columns.clone(),
+            index_type: IndexType::BTree,
+            unique: false,
+            options: IndexOptions::default(),
+        })?;
+        let result = table.get_data().write_or_err()?.create_index(&create.index_name, column);
+        if result.is_err() {
+            catalog.drop_index(&schema_name, &create.index_name)?;
+        }
+        result
+    }
+
+    /// DROP INDEX: remove the index from the catalog and its table
+    fn drop_index(&self, drop: &DropIndexStatement) -> PrismDBResult<()> {
+        let catalog = self
+            .catalog
+            .read()
+            .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
+        let schema = match catalog.index_schema(&drop.index_name) {
+            Ok(schema) => schema,
+            Err(_) if drop.if_exists => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let schema_name = schema.read_or_err()?.get_name().to_string();
+        let index = catalog.get_index(&schema_name, &drop.index_name)?;
+        let table_name = index.read_or_err()?.get_table_name().to_string();
+        catalog.drop_index(&schema_name, &drop.index_name)?;
+        if let Ok(table) = catalog.get_table(&schema_name, &table_name) {
+            table.read_or_err()?.get_data().write_or_err()?.drop_index(&drop.index_name);
+        }
+        Ok(())
+    }
+
+    /// VACUUM: drop the deleted rows from one table's storage, or every table's
+    ///
+    /// Runs outside transactions only, and skips tables with writes that a
+    /// transaction still open in another session may not see, or has not
+    /// committed.
+    fn vacuum(&self, vacuum: &VacuumStatement) -> PrismDBResult<()> {
+        if self.transaction.read_or_err()?.is_some() {
+            return Err(PrismDBError::Transaction(
+                "VACUUM cannot run inside a transaction".to_string(),
+            ));
+        }
+        let tables = match &vacuum.table_name {
+            Some(table_name) => {
+                let catalog = self.catalog.read_or_err()?;
+                let table = catalog.find_table(table_name)?;
+                let data = table.read_or_err()?.get_data();
+                vec![data]
+            }
+            None => self.table_data()?,
+        };
+        for table in &tables {
+            self.vacuum_table(table)?;
+        }
+        Ok(())
+    }
+
+    /// Vacuum `table_name` once a DELETE leaves more of its rows deleted than
+    /// `auto_vacuum_threshold` allows
+    fn auto_vacuum(&self, table_name: &str) -> PrismDBResult<()> {
+        let threshold = self
+            .setting("auto_vacuum_threshold")
+            .and_then(|value| value.as_usize())
+            .unwrap_or(0);
+        if threshold == 0 || self.transaction.read_or_err()?.is_some() {
+            return Ok(());
+        }
+        let table = {
+            let catalog = self.catalog.read_or_err()?;
+            let table = catalog.find_table(table_name)?;
+            let data = table.read_or_err()?.get_data();
+            data
+        };
+        let (physical, live) = {
+            let data = table.read_or_err()?;
+            (data.physical_row_count(), data.row_count())
+        };
+        if (physical - live) * 100 > threshold * physical {
+            self.vacuum_table(&table)?;
+        }
+        Ok(())
+    }
+
+    /// Compact one table unless some reader may still see an older version
+    /// of its rows
+    fn vacuum_table(&self, table: &Arc<RwLock<TableData>>) -> PrismDBResult<()> {
+        self.transaction_manager.settle_tables()?;
+        table
+            .write()
+            .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?
+            .vacuum()?;
+        Ok(())
+    }
+
+    /// ANALYZE: estimate the distinct values in each column of one table, or every table's
+    fn analyze(&self, analyze: &AnalyzeStatement) -> PrismDBResult<()> {
+        let tables = match &analyze.table_name {
+            Some(table_name) => {
+                let catalog = self.catalog.read_or_err()?;
+                let table = catalog.find_table(table_name)?;
+                let data = table.read_or_err()?.get_data();
+                vec![data]
+            }
+            None => self.table_data()?,
+        };
+        for table in &tables {
+            let mut data = table
+                .write()
+                .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?;
+            let mut states = vec![ApproxCountDistinctState::new(); data.column_count()];
+            for row_id in 0..data.physical_row_count() {
+                if data.deleted_rows.get(row_id).copied().unwrap_or(false) {
+                    continue;
+                }
+                for (state, value) in states.iter_mut().zip(data.get_row(row_id)?) {
+                    state.update(&value)?;
+                }
+            }
+            let counts = states
+                .iter()
+                .map(|state| Ok(state.finalize()?.try_as_i64()? as usize))
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            data.set_distinct_counts(&counts);
+        }
+        Ok(())
+    }
+
+    /// SHOW VARIABLES: one row per known setting with its current value
+    fn show_variables(&self) -> PrismDBResult<QueryResult> {
+        let columns = ["name", "value", "description"]
+            .into_iter()
+            .map(|name| ColumnMetadata {
+                name: name.to_string(),
+                data_type: LogicalType::Varchar,
+            })
+            .collect();
+        let rows = SETTINGS
+            .iter()
+            .map(|setting| {
+                vec![
+                    Value::Varchar(setting.name.to_string()),
+                    self.setting(setting.name)
+                        .map(|value| Value::Varchar(value.to_string()))
+                        .unwrap_or(Value::Null),
+                    Value::Varchar(setting.description.to_string()),
+                ]
+            })
+            .collect();
+        QueryResult::from_rows(columns, rows)
+    }
+
+    /// SHOW FUNCTIONS: one row per overload of each built-in function, then
+    /// one per function made with CREATE FUNCTION
+    fn show_functions(&self) -> PrismDBResult<QueryResult> {
+        let columns = ["function_name", "function_type", "signature", "return_type", "schema"]
+            .into_iter()
+            .map(|name| ColumnMetadata {
+                name: name.to_string(),
+                data_type: LogicalType::Varchar,
+            })
+            .collect();
+        let mut rows: Vec<Vec<Value>> = FunctionRegistry::builtin()
+            .functions()
+            .into_iter()
+            .map(|function| {
+                vec![
+                    Value::Varchar(function.name.clone()),
+                    Value::Varchar(function.function_type.name().to_string()),
+                    Value::Varchar(function.signature()),
+                    Value::Varchar(type_name(&function.return_type)),
+                    Value::Null,
+                ]
+            })
+            .collect();
+
+        let catalog = self.catalog.read_or_err()?;
+        let mut schemas = catalog.list_schemas();
+        schemas.sort();
+        for schema in schemas {
+            for function in catalog.list_functions(&schema)? {
+                let types: Vec<String> = function.parameters.iter().map(|(_, t)| t.to_string()).collect();
+                rows.push(vec![
+                    Value::Varchar(function.name.clone()),
+                    Value::Varchar("scalar".to_string()),
+                    Value::Varchar(format!("{}({})", function.name.to_uppercase(), types.join(", "))),
+                    function
+                        .return_type
+                        .as_ref()
+                        .map_or(Value::Null, |return_type| Value::Varchar(return_type.to_string())),
+                    Value::Varchar(schema.clone()),
+                ]);
+            }
+        }
+        QueryResult::from_rows(columns, rows)
+    }
+
+    /// SET name = value, or reset the setting for SET name = DEFAULT
+    fn apply_setting(&self, name: &str, value: &SetValue) -> PrismDBResult<()> {
+        match value {
+            SetValue::String(s) => self.config_manager.set(name, s),
+            SetValue::Number(n) => self.config_manager.set(name, &n.to_string()),
+            SetValue::Boolean(b) => self.config_manager.set(name, &b.to_string()),
+            SetValue::Default => self.config_manager.reset(name),
+        }?;
+        if name.eq_ignore_ascii_case("search_path") {
+            self.catalog.write_or_err()?.set_search_path(self.search_path());
+        }
+        Ok(())
+    }
+
+    /// Schemas searched for unqualified table names, from `SET search_path`
+    /// or else the configured default schema
+    fn search_path(&self) -> Vec<String> {
+        self.setting("search_path")
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default()
+            .split(',')
+            .map(|schema| schema.trim().trim_matches('"').to_string())
+            .filter(|schema| !schema.is_empty())
+            .collect()
+    }
+
+    /// PRAGMA: introspection pragmas, or reading and assigning settings
+    ///
+    /// `PRAGMA threads` reads a setting like SHOW, and `PRAGMA threads = 4`
+    /// or `PRAGMA threads(4)` assigns it like SET.
+    fn execute_pragma(&self, pragma: &PragmaStatement) -> PrismDBResult<QueryResult> {
+        let name = pragma.name.to_lowercase();
+        match (name.as_str(), &pragma.value) {
+            ("table_info", Some(SetValue::String(table))) => self.pragma_table_info(table),
+            ("table_info", _) => Err(PrismDBError::InvalidArgument(
+                "PRAGMA table_info requires a table name, e.g. PRAGMA table_info('t')".to_string(),
+            )),
+            ("database_size", None) => self.pragma_database_size(),
+            ("storage_info", Some(SetValue::String(table))) => self.pragma_storage_info(table),
+            ("storage_info", _) => Err(PrismDBError::InvalidArgument(
+                "PRAGMA storage_info requires a table name, e.g. PRAGMA storage_info('t')".to_string(),
+            )),
+            (name, value) if find_setting(name).is_some() => match value {
+                Some(value) => {
+                    self.apply_setting(name, value)?;
+                    Ok(QueryResult::empty())
+                }
+                None => self.show_variable(name),
+            },
+            _ => Err(PrismDBError::InvalidArgument(format!(
+                "Unrecognized PRAGMA '{}'",
+                pragma.name
+            ))),
+        }
+    }
+
+    /// PRAGMA table_info: one row per column with its position, type,
+    /// NOT NULL flag, default and whether it is in the primary key
+    fn pragma_table_info(&self, table_name: &str) -> PrismDBResult<QueryResult> {
+        let table_info = {
+            let catalog = self.catalog.read_or_err()?;
+            let table = catalog.find_table(table_name)?;
+            let info = table.read_or_err()?.get_table_info();
+            info
+        };
+
+        let columns = [
+            ("cid", LogicalType::Integer),
+            ("name", LogicalType::Varchar),
+            ("type", LogicalType::Varchar),
+            ("notnull", LogicalType::Boolean),
+            ("dflt_value", LogicalType::Varchar),
+            ("pk", LogicalType::Boolean),
+        ]
+        .into_iter()
+        .map(|(name, data_type)| ColumnMetadata {
+            name: name.to_string(),
+            data_type,
+        })
+        .collect();
+        let rows = table_info
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(cid, col)| {
+                vec![
+                    Value::Integer(cid as i32),
+                    Value::Varchar(col.name.clone()),
+                    Value::Varchar(col.type_name()),
+                    Value::Boolean(!col.nullable),
+                    col.default_expression.clone().map_or(Value::Null, Value::Varchar),
+                    Value::Boolean(col.is_primary_key),
+                ]
+            })
+            .collect();
+        QueryResult::from_rows(columns, rows)
+    }
+
+    /// PRAGMA storage_info: one row per column with the compression its
+    /// segments get, their compressed and uncompressed sizes, and their count
+    fn pragma_storage_info(&self, table_name: &str) -> PrismDBResult<QueryResult> {
+        let (table_info, table_data) = {
+            let catalog = self.catalog.read_or_err()?;
+            let table = catalog.find_table(table_name)?;
+            let table = table.read_or_err()?;
+            (table.get_table_info(), table.get_data())
+        };
+
+        let columns = [
+            ("column_name", LogicalType::Varchar),
+            ("column_type", LogicalType::Varchar),
+            ("compression", LogicalType::Varchar),
+            ("compressed_size", LogicalType::BigInt),
+            ("uncompressed_size", LogicalType::BigInt),
+            ("segment_count", LogicalType::BigInt),
+        ]
+        .into_iter()
+        .map(|(name, data_type)| ColumnMetadata {
+            name: name.to_string(),
+            data_type,
+        })
+        .collect();
+        let table_data = table_data.read_or_err()?;
+        let rows = table_info
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, col)| {
+                let column = table_data.get_column(index).ok_or_else(|| {
+                    PrismDBError::Internal(format!("Column {} of {} has no data", col.name, table_name))
+                })?;
+                let storage = column.read_or_err()?.storage_info()?;
+                Ok(vec![
+                    Value::Varchar(col.name.clone()),
+                    Value::Varchar(col.type_name()),
+                    Value::Varchar(storage.compression.name().to_string()),
+                    Value::BigInt(storage.compressed_size as i64),
+                    Value::BigInt(storage.uncompressed_size as i64),
+                    Value::BigInt(storage.segment_count as i64),
+                ])
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        QueryResult::from_rows(columns, rows)
+    }
+
+    /// PRAGMA database_size: storage used by the database file and the memory limit
+    fn pragma_database_size(&self) -> PrismDBResult<QueryResult> {
+        let (database_name, database_size, total_blocks) = match &self.block_manager {
+            Some(block_manager) => {
+                let path = block_manager.get_file_path();
+                let name = path
+                    .file_stem()
+                    .map_or_else(|| path.display().to_string(), |s| s.to_string_lossy().to_string());
+                let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                (name, size, block_manager.get_total_blocks())
+            }
+            None => ("memory".to_string(), 0, 0),
+        };
+        let memory_limit = self
+            .setting("memory_limit")
+            .map_or_else(|| "unlimited".to_string(), |value| value.to_string());
+
+        let columns = [
+            ("database_name", LogicalType::Varchar),
+            ("database_size", LogicalType::BigInt),
+            ("block_size", LogicalType::BigInt),
+            ("total_blocks", LogicalType::BigInt),
+            ("memory_limit", LogicalType::Varchar),
+        ]
+        .into_iter()
+        .map(|(name, data_type)| ColumnMetadata {
+            name: name.to_string(),
+            data_type,
+        })
+        .collect();
+        QueryResult::from_rows(
+            columns,
+            vec![vec![
+                Value::Varchar(database_name),
+                Value::BigInt(database_size as i64),
+                Value::BigInt(BLOCK_SIZE as i64),
+                Value::BigInt(total_blocks as i64),
+                Value::Varchar(memory_limit),
+            ]],
+        )
+    }
+
+    /// SHOW name: a single column named after the setting holding its current value
+    fn show_variable(&self, name: &str) -> PrismDBResult<QueryResult> {
+        let setting = find_setting(name).ok_or_else(|| {
+            PrismDBError::InvalidArgument(format!("Unknown configuration setting '{}'", name))
+        })?;
+        let value = self
+            .setting(setting.name)
+            .map(|value| Value::Varchar(value.to_string()))
+            .unwrap_or(Value::Null);
+        QueryResult::from_rows(
+            vec![ColumnMetadata {
+                name: setting.name.to_string(),
+                data_type: LogicalType::Varchar,
+            }],
+            vec![vec![value]],
+        )
+    }
+
+    /// DESCRIBE: one row per column with its type, nullability, key and default
+    fn describe_table(&self, table_name: &str) -> PrismDBResult<QueryResult> {
+        let table_info = {
+            let catalog = self
+                .catalog
+                .read()
+                .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
+            let table = catalog.find_table(table_name)?;
+            let info = table.read_or_err()?.get_table_info();
+            info
+        };
+
+        let rows = table_info
+            .columns
+            .iter()
+            .map(|col| {
+                let key = if col.is_primary_key {
+                    Value::Varchar("PRI".to_string())
+                } else if col.is_unique {
+                    Value::Varchar("UNI".to_string())
+                } else {
+                    Value::Null
+                };
+                vec![
+                    Value::Varchar(col.name.clone()),
+                    Value::Varchar(col.type_name()),
+                    Value::Varchar(if col.nullable { "YES" } else { "NO" }.to_string()),
+                    key,
+                    col.default_expression.clone().map_or(Value::Null, Value::Varchar),
+                ]
+            })
+            .collect();
+
+        Self::describe_result(rows)
+    }
+
+    /// DESCRIBE SELECT: the query's result columns, planned but not executed
+    fn describe_query(&self, query: &SelectStatement) -> PrismDBResult<QueryResult> {
+        let rows = self
+            .describe_statement(&Statement::Select(query.clone()))?
+            .into_iter()
+            .map(|(name, data_type)| {
+                vec![
+                    Value::Varchar(name),
+                    Value::Varchar(data_type.to_string()),
+                    Value::Varchar("YES".to_string()),
+                    Value::Null,
+                    Value::Null,
+                ]
+            })
+            .collect();
+
+        Self::describe_result(rows)
+    }
+
+    /// SUMMARIZE: profile each column of a query result in a single pass
+    ///
+    /// Returns one row per column with its min, max, distinct count and NULL
+    /// percentage, plus the mean and sample standard deviation of numeric columns.
+    fn summarize(&self, query: &SelectStatement) -> PrismDBResult<QueryResult> {
+        let result = self.execute_statement(&Statement::Select(query.clone()))?;
+        let mut summaries: Vec<ColumnSummary> = result
+            .columns
+            .iter()
+            .map(|column| ColumnSummary::new(column.data_type.is_numeric()))
+            .collect();
+        let rows = result.collect()?.rows;
+        for row in &rows {
+            for (summary, value) in summaries.iter_mut().zip(row) {
+                summary.add(value)?;
+            }
         }
 
-        Ok(last_result)
+        let columns = [
+            ("column_name", LogicalType::Varchar),
+            ("column_type", LogicalType::Varchar),
+            ("min", LogicalType::Varchar),
+            ("max", LogicalType::Varchar),
+            ("approx_unique", LogicalType::BigInt),
+            ("null_percentage", LogicalType::Double),
+            ("avg", LogicalType::Double),
+            ("std", LogicalType::Double),
+        ]
+        .into_iter()
+        .map(|(name, data_type)| ColumnMetadata {
+            name: name.to_string(),
+            data_type,
+        })
+        .collect();
+
+        let text = |value: &Option<Value>| {
+            value.as_ref().map_or(Value::Null, |v| Value::Varchar(format_value(v)))
+        };
+        let summary_rows = result
+            .columns
+            .iter()
+            .zip(&summaries)
+            .map(|(column, summary)| {
+                let mean = summary.numeric.then_some(summary.mean).filter(|_| summary.count > 0);
+                let std = summary.numeric.then_some(summary.count).filter(|&n| n > 1).map(|n| {
+                    (summary.squares / (n - 1) as f64).sqrt()
+                });
+                let null_percentage = (!rows.is_empty())
+                    .then(|| (rows.len() - summary.count) as f64 * 100.0 / rows.len() as f64);
+                vec![
+                    Value::Varchar(bare_column_name(&column.name).to_string()),
+                    Value::Varchar(column.data_type.to_string()),
+                    text(&summary.min),
+                    text(&summary.max),
+                    Value::BigInt(summary.distinct.len() as i64),
+                    null_percentage.map_or(Value::Null, Value::Double),
+                    mean.map_or(Value::Null, Value::Double),
+                    std.map_or(Value::Null, Value::Double),
+                ]
+            })
+            .collect();
+
+        QueryResult::from_rows(columns, summary_rows)
+    }
+
+    /// Result set shared by all DESCRIBE forms
+    fn describe_result(rows: Vec<Vec<Value>>) -> PrismDBResult<QueryResult> {
+        let columns = ["column_name", "column_type", "null", "key", "default"]
+            .iter()
+            .map(|name| ColumnMetadata {
+                name: name.to_string(),
+                data_type: LogicalType::Varchar,
+            })
+            .collect();
+
+        QueryResult::from_rows(columns, rows)
+    }
+
+    /// Get a query's result column names and types without executing it
+    ///
+    /// Only parsing and binding run, so clients can prepare result handling
+    /// ahead of execution.
+    pub fn describe(&self, sql: &str) -> PrismDBResult<Vec<(String, LogicalType)>> {
+        let statement = SqlParser::new()
+            .with_identifier_case(self.config.identifier_case)
+            .with_error_mode(self.config.parse_error_mode)
+            .parse(sql)?;
+        self.describe_statement(&statement)
+    }
+
+    /// Output schema of a statement from its logical plan
+    fn describe_statement(&self, statement: &Statement) -> PrismDBResult<Vec<(String, LogicalType)>> {
+        let (plan, _ctes) = self.plan_statement(statement)?;
+        Ok(plan
+            .schema()
+            .into_iter()
+            .map(|col| (col.name, col.data_type))
+            .collect())
     }
 
     /// Plan a SQL statement and return plan with CTEs
     fn plan_statement(&self, statement: &Statement) -> PrismDBResult<(LogicalPlan, std::collections::HashMap<String, LogicalPlan>)> {
-        let mut planner = QueryPlanner::new_with_catalog(self.catalog.clone());
+        let mut planner = QueryPlanner::new_with_catalog(self.catalog.clone())
+            .with_default_null_order(self.default_null_order())
+            .with_approx_count_distinct_threshold(
+                self.setting("approx_count_distinct_threshold")
+                    .and_then(|value| value.as_usize())
+                    .unwrap_or(0),
+            );
         let plan = planner.plan_statement(statement)?;
         let ctes = planner.get_ctes();
         Ok((plan, ctes))
     }
 
-    /// Execute a logical plan
-    fn execute_plan(&self, plan: LogicalPlan, ctes: std::collections::HashMap<String, LogicalPlan>) -> PrismDBResult<QueryResult> {
-        // Optimize and convert to physical plan with catalog/transaction context and CTEs
-        let mut optimizer = QueryOptimizer::new()
-            .with_context(self.catalog.clone(), self.transaction_manager.clone())
-            .with_ctes(ctes);
-        let physical_plan = optimizer.optimize(plan)?;
+    /// Current value of a setting: the value given with SET, else the database config
+    fn setting(&self, name: &str) -> Option<SettingValue> {
+        self.config_manager.get_value(name).or_else(|| match name {
+            "approx_count_distinct_threshold" => Some(SettingValue::UnsignedInteger(0)),
+            "arithmetic_errors" => Some(SettingValue::String("error".to_string())),
+            "auto_vacuum_threshold" => Some(SettingValue::UnsignedInteger(0)),
+            "default_null_order" => Some(SettingValue::String(
+                self.config.default_null_order.name().to_string(),
+            )),
+            "enable_parallelism" => Some(SettingValue::Boolean(self.config.enable_parallelism)),
+            "grouping_collation" => Some(SettingValue::String("binary".to_string())),
+            "memory_limit" => self.config.memory_limit.map(SettingValue::MemorySize),
+            "preserve_insertion_order" => Some(SettingValue::Boolean(false)),
+            "search_path" => Some(SettingValue::String(self.config.default_schema.clone())),
+            "string_length_limit" => Some(SettingValue::String("ignore".to_string())),
+            "threads" => Some(SettingValue::UnsignedInteger(self.config.threads)),
+            "type_coercion" => Some(SettingValue::String(self.config.type_coercion.name().to_string())),
+            _ => None,
+        })
+    }
+
+    /// NULL ordering for ORDER BY items without NULLS FIRST/LAST
+    fn default_null_order(&self) -> NullOrder {
+        self.setting("default_null_order")
+            .and_then(|value| value.as_str().and_then(NullOrder::from_name))
+            .unwrap_or(self.config.default_null_order)
+    }
+
+    /// Whether division by zero and integer overflow fail the query or yield NULL
+    fn arithmetic_errors(&self) -> ArithmeticErrorMode {
+        self.setting("arithmetic_errors")
+            .and_then(|value| value.as_str().and_then(ArithmeticErrorMode::from_name))
+            .unwrap_or_default()
+    }
+
+    /// What INSERT and UPDATE do with strings longer than their column's declared length
+    fn string_length_limit(&self) -> StringLengthMode {
+        self.setting("string_length_limit")
+            .and_then(|value| value.as_str().and_then(StringLengthMode::from_name))
+            .unwrap_or_default()
+    }
+
+    /// Whether operands and stored values of different types are cast or rejected
+    fn type_coercion(&self) -> TypeCoercion {
+        self.setting("type_coercion")
+            .and_then(|value| value.as_str().and_then(TypeCoercion::from_name))
+            .unwrap_or(self.config.type_coercion)
+    }
+
+    /// Collation strings are grouped by in GROUP BY and DISTINCT
+    fn grouping_collation(&self) -> Collation {
+        self.setting("grouping_collation")
+            .and_then(|value| value.as_str().and_then(|name| Collation::from_name(name).ok()))
+            .unwrap_or_default()
+    }
+
+    /// Operator memory budget (None for unlimited)
+    fn memory_limit(&self) -> Option<usize> {
+        self.setting("memory_limit").and_then(|value| value.as_usize())
+    }
+
+    /// Whether queries with an unordered LIMIT run on one thread
+    fn preserve_insertion_order(&self) -> bool {
+        self.setting("preserve_insertion_order")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Parallel execution settings for a query
+    fn parallel_context(&self) -> ParallelContext {
+        let threads = self
+            .setting("threads")
+            .and_then(|value| value.as_usize())
+            .unwrap_or(self.config.threads)
+            .max(1);
+        let mut parallel_context = ParallelContext::new(threads);
+        parallel_context.parallel_enabled &= self
+            .setting("enable_parallelism")
+            .and_then(|value| value.as_bool())
+            .unwrap_or(self.config.enable_parallelism);
+        parallel_context
+    }
+
+    /// Execute a logical plan
+    fn execute_plan(&self, plan: LogicalPlan, ctes: std::collections::HashMap<String, LogicalPlan>) -> PrismDBResult<QueryResult> {
+        Ok(self.execute_plan_reading(plan, ctes)?.0)
+    }
+
+    /// Execute a logical plan, also returning the names of the tables it read
+    fn execute_plan_reading(
+        &self,
+        plan: LogicalPlan,
+        ctes: std::collections::HashMap<String, LogicalPlan>,
+    ) -> PrismDBResult<(QueryResult, HashSet<String>)> {
+        let started = Instant::now();
+        let (physical_plan, columns, context) = self.prepare_plan(plan, ctes)?;
+        self.run_physical_plan(physical_plan, columns, context, started)
+    }
+
+    /// Run a physical plan to completion, also returning the names of the
+    /// tables it read
+    ///
+    /// The plan runs as a unit: if it fails, the row changes it wrote are
+    /// undone. Inside a transaction, the changes of a plan that succeeds are
+    /// kept for ROLLBACK; outside one, they are committed.
+    fn run_physical_plan(
+        &self,
+        physical_plan: PhysicalPlan,
+        columns: Vec<ColumnMetadata>,
+        context: ExecutionContext,
+        started: Instant,
+    ) -> PrismDBResult<(QueryResult, HashSet<String>)> {
+        let undo = context.statement_undo.clone();
+        let transaction_id = context.transaction_id;
+        let writer = context.writer;
+        let result = self.collect_physical_plan(physical_plan, columns, context, started);
+        if result.is_err() {
+            if let Err(e) = undo.rollback() {
+                log::error!("Failed to roll back a failed statement: {}", e);
+            }
+        } else if let Some(id) = transaction_id {
+            self.transaction_manager.record_changes(id, undo.take()?)?;
+        } else {
+            self.transaction_manager.commit_changes(writer, undo.take()?)?;
+        }
+        result
+    }
+
+    fn collect_physical_plan(
+        &self,
+        physical_plan: PhysicalPlan,
+        columns: Vec<ColumnMetadata>,
+        mut context: ExecutionContext,
+        started: Instant,
+    ) -> PrismDBResult<(QueryResult, HashSet<String>)> {
+        context.limits.deadline = self.config.statement_timeout.map(|timeout| started + timeout);
+        // Which rows an unordered LIMIT keeps depends on the order parallel
+        // workers produce them in
+        if physical_plan.has_unordered_limit() && self.preserve_insertion_order() {
+            context.parallel_context.parallel_enabled = false;
+        }
+        let limits = context.limits.clone();
+        let tables_read = context.tables_read.clone();
+        let memory = context.memory.clone();
+
+        // Execute the physical plan, running independent pipelines concurrently
+        let stream = PipelineScheduler::new(context).execute(physical_plan)?;
+
+        // Collect results
+        let mut total_rows = 0;
+        let mut all_chunks = Vec::new();
+        let mut summaries = self
+            .config
+            .collect_column_summaries
+            .then(|| vec![ColumnProfile::default(); columns.len()]);
+
+        for chunk_result in stream {
+            let chunk = chunk_result?;
+            total_rows += chunk.len();
+            if let Some(max) = self.config.max_result_rows.filter(|&max| total_rows > max) {
+                return Err(PrismDBError::LimitExceeded(format!(
+                    "query returned more than {} rows",
+                    max
+                )));
+            }
+            limits.check_deadline()?;
+            if let Some(summaries) = summaries.as_mut() {
+                for (col_idx, summary) in summaries.iter_mut().enumerate() {
+                    if let Some(vector) = chunk.get_vector(col_idx) {
+                        summary.add(vector, chunk.len())?;
+                    }
+                }
+            }
+            all_chunks.push(chunk);
+        }
+
+        let stats = ExecutionStats {
+            rows_processed: total_rows,
+            execution_time_ms: started.elapsed().as_millis() as u64,
+            memory_used_bytes: memory.peak(),
+            operators_executed: 0,
+            operator_memory: memory.operator_peaks(),
+        };
+        let result = QueryResult {
+            chunks: all_chunks,
+            row_count: total_rows,
+            columns,
+            summaries,
+            stats,
+        };
+        let tables_read = std::mem::take(&mut *tables_read.lock_or_err()?);
+        Ok((result, tables_read))
+    }
+
+    /// Optimize a logical plan, returning the physical plan, its result
+    /// columns and the context to execute it in
+    fn prepare_plan(
+        &self,
+        plan: LogicalPlan,
+        ctes: std::collections::HashMap<String, LogicalPlan>,
+    ) -> PrismDBResult<(PhysicalPlan, Vec<ColumnMetadata>, ExecutionContext)> {
+        let context = self.execution_context()?;
+        let (physical_plan, columns) = self.optimize_plan(plan, ctes, Vec::new())?;
+        Ok((physical_plan, columns, context))
+    }
+
+    /// Optimize a logical plan whose parameters have `parameter_types`,
+    /// returning the physical plan and its result columns
+    fn optimize_plan(
+        &self,
+        plan: LogicalPlan,
+        ctes: std::collections::HashMap<String, LogicalPlan>,
+        parameter_types: Vec<LogicalType>,
+    ) -> PrismDBResult<(PhysicalPlan, Vec<ColumnMetadata>)> {
+        // Optimize and convert to physical plan with catalog/transaction context and CTEs
+        let mut optimizer = QueryOptimizer::new()
+            .with_context(self.catalog.clone(), self.transaction_manager.clone())
+            .with_ctes(ctes)
+            .with_parameter_types(parameter_types);
+        let physical_plan = optimizer.optimize(plan)?;
+
+        // Extract column metadata from physical plan
+        let physical_columns = physical_plan.schema();
+        let columns: Vec<ColumnMetadata> = physical_columns
+            .iter()
+            .map(|col| ColumnMetadata {
+                name: col.name.clone(),
+                data_type: col.data_type.clone(),
+            })
+            .collect();
+        Ok((physical_plan, columns))
+    }
+
+    /// Context to execute a statement's plan in, with the session's
+    /// transaction and settings
+    fn execution_context(&self) -> PrismDBResult<ExecutionContext> {
+        if let Some(transaction_id) = *self.transaction.read_or_err()? {
+            if !self.transaction_manager.is_transaction_active(transaction_id) {
+                return Err(PrismDBError::Transaction(
+                    "Current transaction is aborted; statements are ignored until ROLLBACK".to_string(),
+                ));
+            }
+        }
+
+        let mut context = ExecutionContext::new(self.transaction_manager.clone(), self.catalog.clone());
+        context.transaction_id = *self.transaction.read_or_err()?;
+        if let Some(id) = context.transaction_id {
+            context.writer = id;
+        }
+        context.read_view = self.transaction_manager.read_view(context.writer);
+        context.set_memory_limit(self.memory_limit());
+        context.parallel_context = self.parallel_context();
+        context.thread_limit = Some(context.parallel_context.num_threads);
+        context.arithmetic_errors = self.arithmetic_errors();
+        context.string_length_limit = self.string_length_limit();
+        context.type_coercion = self.type_coercion();
+        context.grouping_collation = self.grouping_collation();
+        context.metrics = self.metrics.clone();
+        context.limits.max_scan_rows = self.config.max_scan_rows;
+        Ok(context)
+    }
+
+    /// Open a server-side cursor over the rows of a query
+    ///
+    /// `params` are bound to the query's `?` placeholders in order. The query
+    /// runs single-threaded and only as far as the cursor's fetches need, so
+    /// a client paging through a large result never holds all of it. The
+    /// result row cap and statement timeout don't apply to cursors; the scan
+    /// budget does.
+    pub fn open_cursor(&self, sql: &str, params: &[Value]) -> PrismDBResult<Cursor> {
+        let statement = SqlParser::new()
+            .with_identifier_case(self.config.identifier_case)
+            .with_error_mode(self.config.parse_error_mode)
+            .with_parameters(params.to_vec())
+            .parse(sql)?;
+        if !matches!(statement, Statement::Select(_)) {
+            return Err(PrismDBError::InvalidArgument(
+                "A cursor can only be opened on a query".to_string(),
+            ));
+        }
+
+        let (logical_plan, ctes) = self.plan_statement(&statement)?;
+        let (physical_plan, columns, mut context) = self.prepare_plan(logical_plan, ctes)?;
+        context.parallel_context.parallel_enabled = false;
+        let stream = PipelineScheduler::new(context).execute(physical_plan)?;
+        self.metrics.increment(Metric::QueriesExecuted);
+
+        Ok(Cursor {
+            stream: Some(stream),
+            pending: None,
+            columns,
+        })
+    }
+
+    /// Try to execute a table function directly (bypassing planner)
+    fn try_execute_table_function(&self, select: &SelectStatement) -> PrismDBResult<Option<QueryResult>> {
+        // Check if this is a simple SELECT * FROM table_function(...) query
+        if let Some(TableReference::TableFunction { name, arguments, named_arguments, .. }) = &select.from {
+            let func_name = name.to_lowercase();
+            match func_name.as_str() {
+                "read_csv" | "read_csv_auto" => {
+                    return Ok(Some(self.execute_read_csv_auto(arguments, named_arguments)?));
+                }
+                "read_parquet" => {
+                    no_named_arguments(name, named_arguments)?;
+                    return Ok(Some(self.execute_read_parquet(arguments)?));
+                }
+                "read_json_auto" => {
+                    return Ok(Some(self.execute_read_json_auto(arguments, named_arguments)?));
+                }
+                "sqlite_scan" => {
+                    no_named_arguments(name, named_arguments)?;
+                    return Ok(Some(self.execute_sqlite_scan(arguments)?));
+                }
+                "prism_metrics" => {
+                    no_named_arguments(name, named_arguments)?;
+                    return Ok(Some(self.execute_prism_metrics(arguments)?));
+                }
+                "prism_transactions" => {
+                    no_named_arguments(name, named_arguments)?;
+                    return Ok(Some(self.execute_prism_transactions(arguments)?));
+                }
+                "prism_functions" => {
+                    no_named_arguments(name, named_arguments)?;
+                    if !arguments.is_empty() {
+                        return Err(PrismDBError::InvalidArgument(
+                            "prism_functions takes no arguments".to_string()
+                        ));
+                    }
+                    return Ok(Some(self.show_functions()?));
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// COPY ... TO: write the rows of a table or query to CSV, Parquet or JSON files
+    ///
+    /// With `PARTITION_BY (...)` the target is a directory holding one
+    /// Hive-style `key=value/...` subdirectory per distinct key combination.
+    /// Returns the number of rows written.
+    fn execute_copy(&self, copy: &CopyStatement) -> PrismDBResult<QueryResult> {
+        let option = |name: &str| copy.options.get(name).and_then(|values| values.first());
+        let format = match option("format") {
+            Some(format) => CopyFormat::parse(format)?,
+            None => CopyFormat::from_path(&copy.target),
+        };
+        let header = match option("header").map(|h| h.to_lowercase()) {
+            None => true,
+            Some(h) => matches!(h.as_str(), "true" | "1" | "on"),
+        };
+        let json_array = match option("array").map(|a| a.to_lowercase()) {
+            None => false,
+            Some(a) => matches!(a.as_str(), "true" | "1" | "on"),
+        };
+        let partition_by = copy.options.get("partition_by").cloned().unwrap_or_default();
+        for name in copy.options.keys() {
+            if !matches!(name.as_str(), "format" | "header" | "array" | "partition_by") {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "Unrecognized COPY option '{}'",
+                    name
+                )));
+            }
+        }
 
-        // Extract column metadata from physical plan
-        let physical_columns = physical_plan.schema();
-        let columns: Vec<ColumnMetadata> = physical_columns
+        let result = self.execute_statement(&Statement::Select(copy.query.as_ref().clone()))?;
+        let columns: Vec<(String, LogicalType)> = result
+            .columns
             .iter()
-            .map(|col| ColumnMetadata {
-                name: col.name.clone(),
-                data_type: col.data_type.clone(),
-            })
+            .map(|c| (bare_column_name(&c.name).to_string(), c.data_type.clone()))
             .collect();
+        let rows = result.collect()?.rows;
+
+        let target = copy.target.strip_prefix("file://").unwrap_or(&copy.target);
+        FileWriter::new(format)
+            .with_partition_by(partition_by)
+            .with_header(header)
+            .with_json_array(json_array)
+            .write(Path::new(target), &columns, &rows)?;
+
+        QueryResult::from_rows(
+            vec![ColumnMetadata {
+                name: "Count".to_string(),
+                data_type: LogicalType::BigInt,
+            }],
+            vec![vec![Value::BigInt(rows.len() as i64)]],
+        )
+    }
 
-        // Create execution context
-        let context = ExecutionContext::new(self.transaction_manager.clone(), self.catalog.clone());
+    /// COPY ... FROM: append the objects of a JSON file to a table
+    ///
+    /// Keys are matched to the table's columns by name, and columns a row has
+    /// no key for are NULL. The rows are checked against the table's unique
+    /// constraints before any is stored. Returns the number of rows loaded.
+    fn execute_copy_from(&self, copy: &CopyFromStatement) -> PrismDBResult<QueryResult> {
+        let format = match copy.options.get("format").and_then(|values| values.first()) {
+            Some(format) => CopyFormat::parse(format)?,
+            None => CopyFormat::from_path(&copy.source),
+        };
+        if format != CopyFormat::Json {
+            return Err(PrismDBError::NotImplemented(
+                "COPY ... FROM reads only FORMAT JSON; use read_csv or read_parquet for other files"
+                    .to_string(),
+            ));
+        }
+        if let Some(name) = copy.options.keys().find(|name| name.as_str() != "format") {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "Unrecognized COPY option '{}'",
+                name
+            )));
+        }
 
-        // Execute the physical plan
-        let mut engine = ExecutionEngine::new(context);
-        let mut stream = engine.execute(physical_plan)?;
+        let table = self.catalog.read_or_err()?.find_table(&copy.table_name)?;
+        let table_data_arc = table.read_or_err()?.get_data();
+        let columns: Vec<ColumnInfo> = table_data_arc.read_or_err()?.info.columns.clone();
 
-        // Collect results
-        let mut total_rows = 0;
-        let mut all_chunks = Vec::new();
+        let source = copy.source.strip_prefix("file://").unwrap_or(&copy.source);
+        let s3_config = self.secrets_manager.get_s3_config(&self.config_manager);
+        let file_data = FileReader::new()?.read_file(source, Some(&s3_config))?;
+        let column_types: Vec<(String, LogicalType)> = columns
+            .iter()
+            .map(|column| (column.name.clone(), column.column_type.clone()))
+            .collect();
+        let string_length_limit = self.string_length_limit();
+        let rows = JsonReader::new(file_data)
+            .with_compression(FileCompression::from_path(source))
+            .read_rows(&column_types)?
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(&columns)
+                    .map(|(value, column)| string_length_limit.apply(column, value))
+                    .collect::<PrismDBResult<Vec<_>>>()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
 
-        while let Some(chunk_result) = stream.next() {
-            let chunk = chunk_result?;
-            total_rows += chunk.len();
-            all_chunks.push(chunk);
+        let transaction_id = *self.transaction.read_or_err()?;
+        if let Some(id) = transaction_id {
+            self.transaction_manager.check_write(id, &table_data_arc)?;
+        }
+        let writer = transaction_id.unwrap_or_else(uuid::Uuid::new_v4);
+        let mut changes = Vec::with_capacity(rows.len());
+        {
+            let mut table_data = table_data_arc.write_or_err()?;
+            let mut unique_keys = UniqueKeys::new(&table_data, &ReadView::latest(writer))?;
+            for row in &rows {
+                table_data.info.check_not_null(row)?;
+                unique_keys.claim(row, &table_data.info)?;
+            }
+            table_data.reserve(rows.len())?;
+            for row in &rows {
+                changes.push((table_data_arc.clone(), table_data.insert_row_as(writer, row)?));
+            }
+        }
+        match transaction_id {
+            Some(id) => self.transaction_manager.record_changes(id, changes)?,
+            None => self.transaction_manager.commit_changes(writer, changes)?,
         }
 
-        Ok(QueryResult {
-            chunks: all_chunks,
-            row_count: total_rows,
-            columns,
-        })
+        QueryResult::from_rows(
+            vec![ColumnMetadata {
+                name: "Count".to_string(),
+                data_type: LogicalType::BigInt,
+            }],
+            vec![vec![Value::BigInt(rows.len() as i64)]],
+        )
     }
 
-    /// Try to execute a table function directly (bypassing planner)
-    fn try_execute_table_function(&self, select: &SelectStatement) -> PrismDBResult<Option<QueryResult>> {
-        // Check if this is a simple SELECT * FROM table_function(...) query
-        if let Some(ref from) = select.from {
-            if let TableReference::TableFunction { name, arguments, .. } = from {
-                let func_name = name.to_lowercase();
-                match func_name.as_str() {
-                    "read_csv_auto" => {
-                        return Ok(Some(self.execute_read_csv_auto(arguments)?));
-                    }
-                    "read_parquet" => {
-                        return Ok(Some(self.execute_read_parquet(arguments)?));
-                    }
-                    "read_json_auto" => {
-                        return Ok(Some(self.execute_read_json_auto(arguments)?));
-                    }
-                    "sqlite_scan" => {
-                        return Ok(Some(self.execute_sqlite_scan(arguments)?));
-                    }
-                    _ => {}
+    /// Read the file a table function names, or every file a glob pattern matches
+    ///
+    /// The files' results are concatenated, so they must have the same columns;
+    /// a column whose type differs between files is widened to cover them all.
+    /// Hive-style `key=value` directories in the matched paths become extra
+    /// columns: BIGINT if every value is an integer, otherwise VARCHAR.
+    fn read_files(
+        &self,
+        url: &str,
+        read: impl Fn(&str) -> PrismDBResult<QueryResult>,
+    ) -> PrismDBResult<QueryResult> {
+        if !FileReader::is_glob(url) {
+            return read(url);
+        }
+        let paths = FileReader::glob_files(url)?;
+        if paths.is_empty() {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "No files found that match the pattern '{}'",
+                url
+            )));
+        }
+
+        let mut columns: Option<Vec<ColumnMetadata>> = None;
+        let mut rows = Vec::new();
+        let mut partitions = Vec::new();
+        for path in &paths {
+            let result = read(path)?;
+            let names = |columns: &[ColumnMetadata]| -> Vec<String> {
+                columns.iter().map(|c| c.name.clone()).collect()
+            };
+            match &columns {
+                Some(expected) if names(expected) != names(&result.columns) => {
+                    return Err(PrismDBError::InvalidArgument(format!(
+                        "File '{}' has columns {:?}, expected {:?}",
+                        path,
+                        names(&result.columns),
+                        names(expected)
+                    )));
                 }
+                Some(_) => {}
+                None => columns = Some(result.columns.clone()),
+            }
+            // Files may infer different types for a column; it takes the wider
+            if let Some(columns) = columns.as_mut() {
+                for (column, other) in columns.iter_mut().zip(&result.columns) {
+                    column.data_type = widen_type(&column.data_type, &other.data_type);
+                }
+            }
+            let file_partitions = FileReader::hive_partitions(path);
+            for row in result.collect()?.rows {
+                rows.push(row);
+                partitions.push(file_partitions.clone());
             }
         }
-        Ok(None)
+        let mut columns = columns.unwrap_or_default();
+        for row in rows.iter_mut() {
+            for (value, column) in row.iter_mut().zip(&columns) {
+                if !matches!(value, Value::Null) && value.get_type() != column.data_type {
+                    *value = value.cast_to(&column.data_type)?;
+                }
+            }
+        }
+
+        // Partition keys every matched file has become columns
+        let keys: Vec<String> = FileReader::hive_partitions(&paths[0])
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| !columns.iter().any(|c| c.name.eq_ignore_ascii_case(key)))
+            .filter(|key| {
+                paths
+                    .iter()
+                    .all(|path| FileReader::hive_partitions(path).iter().any(|(k, _)| k == key))
+            })
+            .collect();
+        for key in &keys {
+            let values: Vec<Option<&String>> = partitions
+                .iter()
+                .map(|p| p.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.as_ref()))
+                .collect();
+            let integers = values.iter().flatten().all(|v| v.parse::<i64>().is_ok());
+            for (row, value) in rows.iter_mut().zip(&values) {
+                row.push(match value {
+                    None => Value::Null,
+                    Some(v) if integers => Value::BigInt(v.parse().unwrap_or_default()),
+                    Some(v) => Value::Varchar((*v).clone()),
+                });
+            }
+            columns.push(ColumnMetadata {
+                name: key.clone(),
+                data_type: if integers { LogicalType::BigInt } else { LogicalType::Varchar },
+            });
+        }
+
+        QueryResult::from_rows(columns, rows)
     }
 
     /// Execute read_csv_auto table function
-    fn execute_read_csv_auto(&self, arguments: &[Expression]) -> PrismDBResult<QueryResult> {
+    fn execute_read_csv_auto(
+        &self,
+        arguments: &[Expression],
+        named_arguments: &[(String, Expression)],
+    ) -> PrismDBResult<QueryResult> {
         // Extract the URL argument
         if arguments.is_empty() {
             return Err(PrismDBError::InvalidArgument(
@@ -285,31 +1872,32 @@ impl Database {
 
         // Get S3 configuration from secrets manager
         let s3_config = self.secrets_manager.get_s3_config(&self.config_manager);
+        let options = csv_options(named_arguments)?;
 
-        // Read the file
-        let file_data = file_reader.read_file(&url, Some(&s3_config))?;
-
-        // Parse CSV
-        let csv_reader = CsvReader::new(file_data);
-        let chunk = csv_reader.read()?;
+        self.read_files(&url, |url| {
+            // Read the file
+            let file_data = file_reader.read_file(url, Some(&s3_config))?;
 
-        // Get column names
-        let column_names = csv_reader.get_column_names()?;
+            // Parse CSV
+            let csv_reader = CsvReader::new(file_data)
+                .with_compression(file_compression(url, named_arguments)?)
+                .with_options(options.clone());
+            let table = csv_reader.read()?;
 
-        // Build column metadata
-        let columns: Vec<ColumnMetadata> = column_names.iter()
-            .map(|name| ColumnMetadata {
-                name: name.clone(),
-                data_type: LogicalType::Varchar, // For now, all VARCHAR
-            })
-            .collect();
+            // Build column metadata
+            let columns: Vec<ColumnMetadata> = table.columns.into_iter()
+                .map(|(name, data_type)| ColumnMetadata { name, data_type })
+                .collect();
 
-        let row_count = chunk.len();
+            let row_count = table.chunks.iter().map(|chunk| chunk.len()).sum();
 
-        Ok(QueryResult {
-            chunks: vec![chunk],
-            row_count,
-            columns,
+            Ok(QueryResult {
+                chunks: table.chunks,
+                row_count,
+                columns,
+                summaries: None,
+                stats: ExecutionStats::default(),
+            })
         })
     }
 
@@ -339,11 +1927,23 @@ impl Database {
         // Get S3 configuration from secrets manager
         let s3_config = self.secrets_manager.get_s3_config(&self.config_manager);
 
-        // Read the file
-        let file_data = file_reader.read_file(&url, Some(&s3_config))?;
+        self.read_files(&url, |url| {
+            // Remote files are read with range requests, fetching only the footer
+            // and column chunks; local files are read whole
+            if FileReader::is_remote(url) {
+                let file = RemoteFile::open(file_reader.clone(), url, Some(&s3_config))?;
+                Self::parquet_result(ParquetReader::from_source(file))
+            } else {
+                let file_data = file_reader.read_file(url, Some(&s3_config))?;
+                Self::parquet_result(ParquetReader::new(file_data))
+            }
+        })
+    }
 
-        // Parse Parquet
-        let parquet_reader = ParquetReader::new(file_data);
+    /// Read a whole Parquet source into a query result
+    fn parquet_result<R: ChunkReader + Clone + 'static>(
+        parquet_reader: ParquetReader<R>,
+    ) -> PrismDBResult<QueryResult> {
         let chunk = parquet_reader.read()?;
 
         // Get column names and types
@@ -365,11 +1965,17 @@ impl Database {
             chunks: vec![chunk],
             row_count,
             columns,
+            summaries: None,
+            stats: ExecutionStats::default(),
         })
     }
 
     /// Execute read_json_auto table function
-    fn execute_read_json_auto(&self, arguments: &[Expression]) -> PrismDBResult<QueryResult> {
+    fn execute_read_json_auto(
+        &self,
+        arguments: &[Expression],
+        named_arguments: &[(String, Expression)],
+    ) -> PrismDBResult<QueryResult> {
         // Extract the URL argument
         if arguments.is_empty() {
             return Err(PrismDBError::InvalidArgument(
@@ -386,6 +1992,10 @@ impl Database {
             }
         };
 
+        if let Some((name, _)) = named_arguments.iter().find(|(name, _)| name != "compression") {
+            return Err(PrismDBError::InvalidArgument(format!("Unknown JSON option '{}'", name)));
+        }
+
         println!("Executing read_json_auto('{}')", url);
 
         // Create file reader
@@ -394,33 +2004,97 @@ impl Database {
         // Get S3 configuration from secrets manager
         let s3_config = self.secrets_manager.get_s3_config(&self.config_manager);
 
-        // Read the file
-        let file_data = file_reader.read_file(&url, Some(&s3_config))?;
+        self.read_files(&url, |url| {
+            // Read the file
+            let file_data = file_reader.read_file(url, Some(&s3_config))?;
+
+            // Parse JSON
+            let json_reader =
+                JsonReader::new(file_data).with_compression(file_compression(url, named_arguments)?);
+            let chunk = json_reader.read()?;
+
+            // Get column names and types
+            let column_names = json_reader.get_column_names()?;
+            let column_types = json_reader.get_column_types()?;
+
+            // Build column metadata
+            let columns: Vec<ColumnMetadata> = column_names.iter()
+                .zip(column_types.iter())
+                .map(|(name, data_type)| ColumnMetadata {
+                    name: name.clone(),
+                    data_type: data_type.clone(),
+                })
+                .collect();
 
-        // Parse JSON
-        let json_reader = JsonReader::new(file_data);
-        let chunk = json_reader.read()?;
+            let row_count = chunk.len();
 
-        // Get column names and types
-        let column_names = json_reader.get_column_names()?;
-        let column_types = json_reader.get_column_types()?;
+            Ok(QueryResult {
+                chunks: vec![chunk],
+                row_count,
+                columns,
+                summaries: None,
+                stats: ExecutionStats::default(),
+            })
+        })
+    }
 
-        // Build column metadata
-        let columns: Vec<ColumnMetadata> = column_names.iter()
-            .zip(column_types.iter())
-            .map(|(name, data_type)| ColumnMetadata {
-                name: name.clone(),
-                data_type: data_type.clone(),
+    /// Execute prism_metrics table function: one row per counter
+    fn execute_prism_metrics(&self, arguments: &[Expression]) -> PrismDBResult<QueryResult> {
+        if !arguments.is_empty() {
+            return Err(PrismDBError::InvalidArgument(
+                "prism_metrics takes no arguments".to_string()
+            ));
+        }
+
+        let columns = vec![
+            ColumnMetadata { name: "metric".to_string(), data_type: LogicalType::Varchar },
+            ColumnMetadata { name: "value".to_string(), data_type: LogicalType::BigInt },
+            ColumnMetadata { name: "description".to_string(), data_type: LogicalType::Varchar },
+        ];
+        let rows = self
+            .metrics()
+            .iter()
+            .map(|(metric, value)| {
+                vec![
+                    Value::Varchar(metric.name().to_string()),
+                    Value::BigInt(value as i64),
+                    Value::Varchar(metric.description().to_string()),
+                ]
             })
             .collect();
+        QueryResult::from_rows(columns, rows)
+    }
 
-        let row_count = chunk.len();
+    /// Execute prism_transactions table function: one row per open transaction
+    fn execute_prism_transactions(&self, arguments: &[Expression]) -> PrismDBResult<QueryResult> {
+        if !arguments.is_empty() {
+            return Err(PrismDBError::InvalidArgument(
+                "prism_transactions takes no arguments".to_string()
+            ));
+        }
 
-        Ok(QueryResult {
-            chunks: vec![chunk],
-            row_count,
-            columns,
-        })
+        let columns = vec![
+            ColumnMetadata { name: "transaction_id".to_string(), data_type: LogicalType::Varchar },
+            ColumnMetadata { name: "state".to_string(), data_type: LogicalType::Varchar },
+            ColumnMetadata { name: "start_time".to_string(), data_type: LogicalType::Timestamp },
+            ColumnMetadata { name: "isolation_level".to_string(), data_type: LogicalType::Varchar },
+            ColumnMetadata { name: "read_only".to_string(), data_type: LogicalType::Boolean },
+        ];
+        let rows = self
+            .transaction_manager
+            .transaction_statuses()
+            .into_iter()
+            .map(|status| {
+                vec![
+                    Value::Varchar(status.id.to_string()),
+                    Value::Varchar(status.state.name().to_string()),
+                    Value::Timestamp(status.start_time as i64 * 1_000_000),
+                    Value::Varchar(status.isolation_level.name().to_string()),
+                    Value::Boolean(status.read_only),
+                ]
+            })
+            .collect();
+        QueryResult::from_rows(columns, rows)
     }
 
     /// Execute sqlite_scan table function
@@ -484,6 +2158,8 @@ impl Database {
             chunks: vec![chunk],
             row_count,
             columns,
+            summaries: None,
+            stats: ExecutionStats::default(),
         })
     }
 
@@ -492,6 +2168,17 @@ impl Database {
         self.catalog.clone()
     }
 
+    /// Open another session on this database
+    ///
+    /// The session shares all tables and settings but runs its own transactions,
+    /// so two sessions can hold transactions open at the same time.
+    pub fn connect(&self) -> Database {
+        Database {
+            transaction: Arc::new(RwLock::new(None)),
+            ..self.clone()
+        }
+    }
+
     /// Execute a SQL statement (convenience wrapper)
     pub fn execute(&mut self, sql: &str) -> PrismDBResult<QueryResult> {
         self.execute_sql_collect(sql)
@@ -502,15 +2189,196 @@ impl Database {
         self.execute_sql_collect(sql)
     }
 
+    /// Create table `name` with the given columns and load `rows` into it,
+    /// without going through SQL
+    ///
+    /// The rows are checked and cast like the rows of an INSERT. If any fails,
+    /// the table is dropped again, so either the table exists with all the
+    /// rows or not at all.
+    pub fn create_table_from_rows(
+        &mut self,
+        name: &str,
+        schema: Vec<(String, LogicalType)>,
+        rows: Vec<Vec<Value>>,
+    ) -> PrismDBResult<()> {
+        if let Some(row) = rows.iter().find(|row| row.len() != schema.len()) {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "Row has {} values but table '{}' has {} columns",
+                row.len(),
+                name,
+                schema.len()
+            )));
+        }
+        let columns: Vec<Column> = schema
+            .into_iter()
+            .map(|(column_name, data_type)| Column::new(column_name, data_type))
+            .collect();
+
+        let create = LogicalCreateTable::new(name.to_string(), columns.clone(), false);
+        self.execute_plan(LogicalPlan::CreateTable(create), Default::default())?;
+        self.query_cache.clear();
+        self.plan_cache.clear();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let loaded = self.insert_values(name, &columns, rows);
+        if loaded.is_err() {
+            self.execute_plan(
+                LogicalPlan::DropTable(LogicalDropTable::new(name.to_string(), true)),
+                Default::default(),
+            )?;
+            self.query_cache.clear();
+            self.plan_cache.clear();
+        }
+        loaded
+    }
+
+    /// Insert `rows` into every column of table `name` through the INSERT operator
+    fn insert_values(&self, name: &str, columns: &[Column], rows: Vec<Vec<Value>>) -> PrismDBResult<()> {
+        let values = rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|value| Ok(Arc::new(ConstantExpression::new(value)?) as ExpressionRef))
+                    .collect::<PrismDBResult<Vec<_>>>()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        let schema = columns
+            .iter()
+            .map(|column| PhysicalColumn::new(column.name.clone(), column.data_type.clone()))
+            .collect();
+        let insert = PhysicalInsert::new(
+            name.to_string(),
+            PhysicalPlan::Values(PhysicalValues::new(values, schema)),
+            Vec::new(),
+        );
+        self.run_physical_plan(PhysicalPlan::Insert(insert), Vec::new(), self.execution_context()?, Instant::now())?;
+        Ok(())
+    }
+
     /// Create a new database with configuration
     pub fn new(config: DatabaseConfig) -> PrismDBResult<Self> {
-        if let Some(ref file_path) = config.file_path {
+        let mut db = if let Some(ref file_path) = config.file_path {
             // Create file-based database
-            Self::open(file_path)
+            Self::open(file_path)?
         } else {
             // Create in-memory database
-            Self::new_in_memory()
+            Self::new_in_memory()?
+        };
+        db.query_cache = Arc::new(QueryCache::new(config.query_cache_capacity));
+        db.plan_cache = Arc::new(PlanCache::new(config.plan_cache_capacity));
+        db.catalog.write_or_err()?.set_default_schema(&config.default_schema);
+        db.config = config;
+        Ok(db)
+    }
+}
+
+/// Running statistics of one column for SUMMARIZE
+struct ColumnSummary {
+    /// Whether the column is numeric, so has a mean and standard deviation
+    numeric: bool,
+    /// Number of non-NULL values
+    count: usize,
+    min: Option<Value>,
+    max: Option<Value>,
+    distinct: HashSet<OrderedValue>,
+    /// Running mean and sum of squared deviations (Welford's algorithm)
+    mean: f64,
+    squares: f64,
+}
+
+impl ColumnSummary {
+    fn new(numeric: bool) -> Self {
+        Self {
+            numeric,
+            count: 0,
+            min: None,
+            max: None,
+            distinct: HashSet::new(),
+            mean: 0.0,
+            squares: 0.0,
+        }
+    }
+
+    fn add(&mut self, value: &Value) -> PrismDBResult<()> {
+        if value.is_null() {
+            return Ok(());
+        }
+        self.count += 1;
+        if self.min.as_ref().is_none_or(|min| value.total_cmp(min).is_lt()) {
+            self.min = Some(value.clone());
+        }
+        if self.max.as_ref().is_none_or(|max| value.total_cmp(max).is_gt()) {
+            self.max = Some(value.clone());
+        }
+        self.distinct.insert(OrderedValue(value.clone()));
+        if self.numeric {
+            let x = value.try_as_f64()?;
+            let delta = x - self.mean;
+            self.mean += delta / self.count as f64;
+            self.squares += delta * (x - self.mean);
+        }
+        Ok(())
+    }
+}
+
+/// Server-side cursor over a query's rows, opened with [`Database::open_cursor`]
+///
+/// Execution state is kept between fetches: each fetch runs the query only
+/// far enough to produce the rows it returns.
+pub struct Cursor {
+    /// Remaining output of the query, None once closed
+    stream: Option<Box<dyn DataChunkStream>>,
+    /// Rows of the last chunk read that the previous fetch didn't return
+    pending: Option<DataChunk>,
+    /// Column metadata
+    pub columns: Vec<ColumnMetadata>,
+}
+
+impl Cursor {
+    /// Fetch the next `n` rows, or fewer at the end of the result
+    ///
+    /// Returns no chunks once every row has been fetched. An error while
+    /// executing the query closes the cursor.
+    pub fn fetch(&mut self, n: usize) -> PrismDBResult<Vec<DataChunk>> {
+        let Some(stream) = self.stream.as_mut() else {
+            return Err(PrismDBError::InvalidArgument("Cursor is closed".to_string()));
+        };
+
+        let mut chunks = Vec::new();
+        let mut remaining = n;
+        while remaining > 0 {
+            let chunk = match self.pending.take().map(Ok).or_else(|| stream.next()) {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(e)) => {
+                    self.close();
+                    return Err(e);
+                }
+                None => break,
+            };
+            if chunk.len() > remaining {
+                self.pending = Some(chunk.slice_range(remaining, chunk.len() - remaining)?);
+                chunks.push(chunk.slice_range(0, remaining)?);
+                break;
+            }
+            remaining -= chunk.len();
+            if !chunk.is_empty() {
+                chunks.push(chunk);
+            }
         }
+        Ok(chunks)
+    }
+
+    /// Stop the query and release its state; later fetches fail
+    pub fn close(&mut self) {
+        self.stream = None;
+        self.pending = None;
+    }
+
+    /// Whether the cursor has been closed
+    pub fn is_closed(&self) -> bool {
+        self.stream.is_none()
     }
 }
 
@@ -521,8 +2389,39 @@ pub struct ColumnMetadata {
     pub data_type: LogicalType,
 }
 
+/// NULL count and range of one column of a query result
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnProfile {
+    /// Rows where the column is NULL
+    pub null_count: usize,
+    /// Smallest non-NULL value, None if every value is NULL
+    pub min: Option<Value>,
+    /// Largest non-NULL value, None if every value is NULL
+    pub max: Option<Value>,
+}
+
+impl ColumnProfile {
+    /// Add the values of a result vector to the profile
+    fn add(&mut self, vector: &Vector, rows: usize) -> PrismDBResult<()> {
+        for row in 0..rows {
+            let value = vector.get_value(row)?;
+            if value.is_null() {
+                self.null_count += 1;
+                continue;
+            }
+            if self.min.as_ref().is_none_or(|min| value.total_cmp(min).is_lt()) {
+                self.min = Some(value.clone());
+            }
+            if self.max.as_ref().is_none_or(|max| value.total_cmp(max).is_gt()) {
+                self.max = Some(value);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Query result containing data chunks
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct QueryResult {
     /// Data chunks containing the results
     chunks: Vec<DataChunk>,
@@ -530,6 +2429,10 @@ pub struct QueryResult {
     row_count: usize,
     /// Column metadata
     pub columns: Vec<ColumnMetadata>,
+    /// Summary of each column, when collected with `collect_column_summaries`
+    summaries: Option<Vec<ColumnProfile>>,
+    /// Time and memory the query took to run
+    stats: ExecutionStats,
 }
 
 impl QueryResult {
@@ -539,7 +2442,30 @@ impl QueryResult {
             chunks: Vec::new(),
             row_count: 0,
             columns: Vec::new(),
+            summaries: None,
+            stats: ExecutionStats::default(),
+        }
+    }
+
+    /// Build a single-chunk result from rows of values
+    fn from_rows(columns: Vec<ColumnMetadata>, rows: Vec<Vec<Value>>) -> PrismDBResult<Self> {
+        let mut vectors: Vec<Vector> = columns
+            .iter()
+            .map(|col| Vector::new(col.data_type.clone(), rows.len().max(1)))
+            .collect();
+        for row in &rows {
+            for (vector, value) in vectors.iter_mut().zip(row) {
+                vector.push(value)?;
+            }
         }
+
+        Ok(QueryResult {
+            chunks: vec![DataChunk::from_vectors(vectors)?],
+            row_count: rows.len(),
+            columns,
+            summaries: None,
+            stats: ExecutionStats::default(),
+        })
     }
 
     /// Get the number of rows in the result
@@ -574,6 +2500,22 @@ impl QueryResult {
         self.row_count == 0
     }
 
+    /// NULL count, minimum and maximum of each column, in column order
+    ///
+    /// Only results of queries run with `collect_column_summaries` set have
+    /// summaries; they are gathered as the rows are collected.
+    pub fn column_summaries(&self) -> Option<&[ColumnProfile]> {
+        self.summaries.as_deref()
+    }
+
+    /// Time the query took and the memory its blocking operators held
+    ///
+    /// Results not produced by running a plan, such as those of SHOW or
+    /// PRAGMA statements, report zeros.
+    pub fn stats(&self) -> &ExecutionStats {
+        &self.stats
+    }
+
     /// Collect all chunks into rows format
     pub fn collect(&self) -> PrismDBResult<CollectedResult> {
         let mut all_rows = Vec::new();
@@ -596,27 +2538,51 @@ impl QueryResult {
             rows: all_rows,
             stats: ExecutionStats {
                 rows_processed: self.row_count,
-                execution_time_ms: 0,
-                memory_used_bytes: 0,
-                operators_executed: 0,
+                ..self.stats.clone()
             },
         })
     }
 
+    /// Rows as a JSON array of objects keyed by column name, in column order
+    ///
+    /// Values are serialized with [`Value::to_json`], so nested values nest.
+    pub fn to_json(&self) -> PrismDBResult<serde_json::Value> {
+        let rows = self.collect()?.rows;
+        Ok(serde_json::Value::Array(
+            rows.iter()
+                .map(|row| {
+                    serde_json::Value::Object(
+                        self.columns
+                            .iter()
+                            .zip(row)
+                            .map(|(column, value)| (column.name.clone(), value.to_json()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
     /// Convert result to a formatted table string with color support
     pub fn to_table_string(&self) -> String {
+        self.to_table_string_with(&TableFormatOptions::default())
+    }
+
+    /// Convert result to a formatted table string using the given display options
+    pub fn to_table_string_with(&self, options: &TableFormatOptions) -> String {
         if self.chunks.is_empty() {
             return String::new();
         }
 
         let mut output = String::new();
 
-        // ANSI color codes
-        const GRAY: &str = "\x1b[90m";      // Dim gray for borders
-        const CYAN: &str = "\x1b[36m";      // Cyan for column names
-        const DIM: &str = "\x1b[2m";        // Dim for types
-        const GREEN: &str = "\x1b[32m";     // Green for values
-        const RESET: &str = "\x1b[0m";      // Reset
+        // ANSI color codes (empty when colors are disabled)
+        let color = |code: &'static str| if options.colors { code } else { "" };
+        let gray = color("\x1b[90m"); // Dim gray for borders
+        let cyan = color("\x1b[36m"); // Cyan for column names
+        let dim = color("\x1b[2m"); // Dim for types and NULLs
+        let green = color("\x1b[32m"); // Green for values
+        let reset = color("\x1b[0m"); // Reset
 
         // Get column information from first chunk
         let first_chunk = &self.chunks[0];
@@ -633,7 +2599,7 @@ impl QueryResult {
         for i in 0..column_count {
             // Use actual column names from metadata if available
             if i < self.columns.len() {
-                column_names.push(self.columns[i].name.clone());
+                column_names.push(options.truncate(&self.columns[i].name));
                 column_types.push(self.columns[i].data_type.clone());
             } else {
                 // Fallback to default names if metadata is missing
@@ -646,141 +2612,217 @@ impl QueryResult {
             }
         }
 
-        // Calculate column widths (accounting for both name and type)
+        // Render every cell up front as (text, is_null)
+        let mut rows: Vec<Vec<(String, bool)>> = Vec::new();
+        for chunk in &self.chunks {
+            for row_idx in 0..chunk.len() {
+                let row = (0..column_count)
+                    .map(|col_idx| {
+                        match chunk.get_vector(col_idx).map(|v| v.get_value(row_idx)) {
+                            Some(Ok(value)) if !value.is_null() => {
+                                (options.truncate(&options.format_value(&value)), false)
+                            }
+                            _ => (options.null_value.clone(), true),
+                        }
+                    })
+                    .collect();
+                rows.push(row);
+            }
+        }
+
+        // Calculate column widths (accounting for name, type and data)
         let mut column_widths: Vec<usize> = column_names.iter()
             .zip(&column_types)
             .map(|(name, col_type)| {
                 let type_name = format_type_name(col_type);
-                name.len().max(type_name.len())
+                name.chars().count().max(type_name.chars().count())
             })
             .collect();
+        for row in &rows {
+            for (col_idx, (text, _)) in row.iter().enumerate() {
+                column_widths[col_idx] = column_widths[col_idx].max(text.chars().count());
+            }
+        }
 
-        // Check data widths
-        for chunk in &self.chunks {
-            for row_idx in 0..chunk.len() {
-                for col_idx in 0..column_count {
-                    if let Some(vector) = chunk.get_vector(col_idx) {
-                        if let Ok(value) = vector.get_value(row_idx) {
-                            let str_len = format_value(&value).len();
-                            column_widths[col_idx] = column_widths[col_idx].max(str_len);
-                        }
-                    }
+        let horizontal_border = |left: char, middle: char, right: char| {
+            let mut line = String::from(gray);
+            line.push(left);
+            for (i, width) in column_widths.iter().enumerate() {
+                line.push_str(&"─".repeat(width + 2));
+                if i < column_widths.len() - 1 {
+                    line.push(middle);
                 }
             }
-        }
+            line.push(right);
+            line.push_str(reset);
+            line.push('\n');
+            line
+        };
 
         // Print header (thin borders with darker color)
-        output.push_str(GRAY);
-        output.push('┌');
-        for (i, width) in column_widths.iter().enumerate() {
-            output.push_str(&"─".repeat(width + 2));
-            if i < column_widths.len() - 1 {
-                output.push('┬');
-            }
-        }
-        output.push_str("┐");
-        output.push_str(RESET);
-        output.push('\n');
+        output.push_str(&horizontal_border('┌', '┬', '┐'));
 
         // Print column names (cyan color)
-        output.push_str(GRAY);
+        output.push_str(gray);
         output.push('│');
-        output.push_str(RESET);
+        output.push_str(reset);
         for (i, (name, width)) in column_names.iter().zip(&column_widths).enumerate() {
-            output.push_str(&format!(" {}{:width$}{} ", CYAN, name, RESET, width = width));
+            output.push_str(&format!(" {}{:width$}{} ", cyan, name, reset, width = width));
             if i < column_names.len() - 1 {
-                output.push_str(GRAY);
+                output.push_str(gray);
                 output.push('│');
-                output.push_str(RESET);
+                output.push_str(reset);
             }
         }
-        output.push_str(GRAY);
-        output.push_str("│");
-        output.push_str(RESET);
+        output.push_str(gray);
+        output.push('│');
+        output.push_str(reset);
         output.push('\n');
 
         // Print column types (dim color)
-        output.push_str(GRAY);
+        output.push_str(gray);
         output.push('│');
-        output.push_str(RESET);
+        output.push_str(reset);
         for (i, (col_type, width)) in column_types.iter().zip(&column_widths).enumerate() {
             let type_name = format_type_name(col_type);
-            output.push_str(&format!(" {}{:width$}{} ", DIM, type_name, RESET, width = width));
+            output.push_str(&format!(" {}{:width$}{} ", dim, type_name, reset, width = width));
             if i < column_types.len() - 1 {
-                output.push_str(GRAY);
+                output.push_str(gray);
                 output.push('│');
-                output.push_str(RESET);
+                output.push_str(reset);
             }
         }
-        output.push_str(GRAY);
-        output.push_str("│");
-        output.push_str(RESET);
+        output.push_str(gray);
+        output.push('│');
+        output.push_str(reset);
         output.push('\n');
 
         // Print separator
-        output.push_str(GRAY);
-        output.push('├');
-        for (i, width) in column_widths.iter().enumerate() {
-            output.push_str(&"─".repeat(width + 2));
-            if i < column_widths.len() - 1 {
-                output.push('┼');
+        output.push_str(&horizontal_border('├', '┼', '┤'));
+
+        // Print rows (green values, dimmed NULLs, numbers right-aligned)
+        for row in &rows {
+            output.push_str(gray);
+            output.push('│');
+            output.push_str(reset);
+            for (col_idx, (text, is_null)) in row.iter().enumerate() {
+                let width = column_widths[col_idx];
+                let value_color = if *is_null { dim } else { green };
+                let cell = if column_types[col_idx].is_numeric() {
+                    format!("{:>width$}", text, width = width)
+                } else {
+                    format!("{:width$}", text, width = width)
+                };
+                output.push_str(&format!(" {}{}{} ", value_color, cell, reset));
+                if col_idx < column_count - 1 {
+                    output.push_str(gray);
+                    output.push('│');
+                    output.push_str(reset);
+                }
             }
+            output.push_str(gray);
+            output.push('│');
+            output.push_str(reset);
+            output.push('\n');
         }
-        output.push_str("┤");
-        output.push_str(RESET);
-        output.push('\n');
 
-        // Print rows (green values)
-        for chunk in &self.chunks {
-            for row_idx in 0..chunk.len() {
-                output.push_str(GRAY);
-                output.push('│');
-                output.push_str(RESET);
-                for col_idx in 0..column_count {
-                    let value_str = if let Some(vector) = chunk.get_vector(col_idx) {
-                        if let Ok(value) = vector.get_value(row_idx) {
-                            format_value(&value)
-                        } else {
-                            "NULL".to_string()
-                        }
-                    } else {
-                        "NULL".to_string()
-                    };
-
-                    output.push_str(&format!(
-                        " {}{:width$}{} ",
-                        GREEN,
-                        value_str,
-                        RESET,
-                        width = column_widths[col_idx]
-                    ));
-                    if col_idx < column_count - 1 {
-                        output.push_str(GRAY);
-                        output.push('│');
-                        output.push_str(RESET);
-                    }
+        // Print footer
+        output.push_str(&horizontal_border('└', '┴', '┘'));
+
+        output
+    }
+}
+
+/// Display options for [`QueryResult::to_table_string_with`]
+#[derive(Debug, Clone)]
+pub struct TableFormatOptions {
+    /// Maximum characters per cell; longer values are cut and end with `…` (None = unlimited)
+    pub max_column_width: Option<usize>,
+    /// Text shown for NULL values, rendered dimmed to set it apart from strings
+    pub null_value: String,
+    /// Emit ANSI color codes
+    pub colors: bool,
+    /// Group digits and fix decimal places of numbers (None = as computed)
+    pub number_format: Option<NumberFormat>,
+}
+
+/// How numbers are written in table output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NumberFormat {
+    /// Decimal places shown for floating point and DECIMAL values, rounding
+    /// or padding with zeros (None = as computed)
+    pub decimal_places: Option<usize>,
+}
+
+impl NumberFormat {
+    /// Write `value` with thousands separators, or None if it isn't a number
+    pub fn format(&self, value: &Value) -> Option<String> {
+        let text = match value {
+            Value::TinyInt(_)
+            | Value::SmallInt(_)
+            | Value::Integer(_)
+            | Value::BigInt(_)
+            | Value::HugeInt { .. } => format_value(value),
+            Value::Float(_) | Value::Double(_) | Value::Decimal { .. } => {
+                let text = format_value(value);
+                match (self.decimal_places, text.parse::<f64>()) {
+                    (Some(places), Ok(number)) if number.is_finite() => format!("{:.*}", places, number),
+                    _ => text,
                 }
-                output.push_str(GRAY);
-                output.push_str("│");
-                output.push_str(RESET);
-                output.push('\n');
             }
+            _ => return None,
+        };
+        Some(group_thousands(&text))
+    }
+}
+
+/// Put a comma between each group of three digits of a number's integer part
+fn group_thousands(text: &str) -> String {
+    let digits_start = text.find(|c: char| c.is_ascii_digit()).unwrap_or(text.len());
+    let digits_end = text[digits_start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(text.len(), |end| digits_start + end);
+    let digits = &text[digits_start..digits_end];
+
+    let mut grouped = String::from(&text[..digits_start]);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
         }
+        grouped.push(digit);
+    }
+    grouped.push_str(&text[digits_end..]);
+    grouped
+}
 
-        // Print footer
-        output.push_str(GRAY);
-        output.push('└');
-        for (i, width) in column_widths.iter().enumerate() {
-            output.push_str(&"─".repeat(width + 2));
-            if i < column_widths.len() - 1 {
-                output.push('┴');
+impl TableFormatOptions {
+    /// Render a non-NULL cell, applying the number format to numbers
+    fn format_value(&self, value: &Value) -> String {
+        self.number_format
+            .and_then(|format| format.format(value))
+            .unwrap_or_else(|| format_value(value))
+    }
+
+    /// Cut `text` to the maximum column width, marking the cut with an ellipsis
+    fn truncate(&self, text: &str) -> String {
+        match self.max_column_width {
+            Some(max) if text.chars().count() > max => {
+                let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+                format!("{}…", kept)
             }
+            _ => text.to_string(),
         }
-        output.push_str("┘");
-        output.push_str(RESET);
-        output.push('\n');
+    }
+}
 
-        output
+impl Default for TableFormatOptions {
+    fn default() -> Self {
+        Self {
+            max_column_width: None,
+            null_value: "NULL".to_string(),
+            colors: true,
+            number_format: None,
+        }
     }
 }
 
@@ -810,7 +2852,7 @@ fn format_type_name(data_type: &LogicalType) -> String {
         LogicalType::Struct(_) => "struct".to_string(),
         LogicalType::Map { .. } => "map".to_string(),
         LogicalType::Union(_) => "union".to_string(),
-        LogicalType::Enum { .. } => "enum".to_string(),
+        LogicalType::Enum(_) => "enum".to_string(),
         LogicalType::Null => "null".to_string(),
         LogicalType::Invalid => "invalid".to_string(),
     }
@@ -830,6 +2872,7 @@ fn format_value(value: &Value) -> String {
         Value::Double(d) => d.to_string(),
         Value::Varchar(s) => s.clone(),
         Value::Char(s) => s.clone(),
+        Value::Enum { code, enum_type } => enum_type.label(*code).to_string(),
         Value::Date(d) => format!("DATE({})", d),
         Value::Time(t) => format!("TIME({})", t),
         Value::Timestamp(ts) => format!("TIMESTAMP({})", ts),
@@ -883,10 +2926,47 @@ pub struct DatabaseConfig {
     pub max_memory: usize,
     /// Number of threads for parallel execution
     pub threads: usize,
+    /// Run table scans across worker threads
+    pub enable_parallelism: bool,
     /// Enable query optimization
     pub enable_optimizer: bool,
     /// Enable write-ahead logging
     pub enable_wal: bool,
+    /// NULL placement for ORDER BY items without NULLS FIRST/LAST
+    pub default_null_order: NullOrder,
+    /// Memory budget for buffering query operators (None for unlimited)
+    pub memory_limit: Option<usize>,
+    /// Reuse the results of repeated SELECTs until a table they read changes
+    pub enable_query_cache: bool,
+    /// Maximum number of results the query cache holds
+    pub query_cache_capacity: usize,
+    /// Reuse the plans of SELECTs that differ only in the literals their
+    /// WHERE clause compares against, until DDL or SET. Cached plans don't
+    /// use those literals to prune zones of a scan.
+    pub enable_plan_cache: bool,
+    /// Maximum number of plans the plan cache holds
+    pub plan_cache_capacity: usize,
+    /// How unquoted table and column names are folded before catalog lookup;
+    /// quoted names always match exactly
+    pub identifier_case: IdentifierCase,
+    /// Fail a statement that would return more than this many rows (None for no cap)
+    pub max_result_rows: Option<usize>,
+    /// Fail a statement whose table scans read more than this many rows in total
+    /// (None for no budget)
+    pub max_scan_rows: Option<usize>,
+    /// Cancel a statement that runs longer than this (None for no timeout)
+    pub statement_timeout: Option<Duration>,
+    /// Schema that unqualified table names resolve to and new tables are
+    /// created in, until SET search_path says otherwise
+    pub default_schema: String,
+    /// How parse errors point at the offending token
+    pub parse_error_mode: ParseErrorMode,
+    /// Whether operands and stored values of different types are cast or
+    /// rejected, until SET type_coercion says otherwise
+    pub type_coercion: TypeCoercion,
+    /// Gather each result column's NULL count, minimum and maximum while
+    /// collecting query results, for `QueryResult::column_summaries`
+    pub collect_column_summaries: bool,
 }
 
 impl DatabaseConfig {
@@ -913,8 +2993,23 @@ impl Default for DatabaseConfig {
             file_path: None,
             max_memory: 1024 * 1024 * 1024, // 1GB
             threads: num_cpus::get(),
+            enable_parallelism: true,
             enable_optimizer: true,
             enable_wal: true,
+            default_null_order: NullOrder::default(),
+            memory_limit: None,
+            enable_query_cache: false,
+            query_cache_capacity: 128,
+            enable_plan_cache: false,
+            plan_cache_capacity: 128,
+            identifier_case: IdentifierCase::default(),
+            max_result_rows: None,
+            max_scan_rows: None,
+            statement_timeout: None,
+            default_schema: "main".to_string(),
+            parse_error_mode: ParseErrorMode::default(),
+            type_coercion: TypeCoercion::default(),
+            collect_column_summaries: false,
         }
     }
 }
@@ -3,12 +3,19 @@
 //! Provides context and resources for query execution.
 
 use crate::catalog::Catalog;
+use crate::common::allocator::{MemoryBudget, MemoryReservation, QueryArena};
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::metrics::{Metric, MetricsRegistry};
+use crate::common::sync::{MutexExt, RwLockExt};
 use crate::execution::parallel::ParallelContext;
-use crate::storage::{Transaction, TransactionManager};
-use crate::types::LogicalType;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use crate::expression::operator::{ArithmeticErrorMode, TypeCoercion};
+use crate::storage::table::StringLengthMode;
+use crate::storage::{ReadView, RowChange, TableChange, TableData, Transaction, TransactionManager};
+use crate::types::{Collation, LogicalType};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
 use uuid::Uuid;
 
 /// Execution context for query execution
@@ -22,8 +29,15 @@ pub struct ExecutionContext {
     pub transaction_id: Option<Uuid>,
     /// Current transaction
     pub transaction: Option<Arc<Transaction>>,
+    /// Who the statement's writes are stamped with until they commit: the
+    /// current transaction, or the statement itself outside one
+    pub writer: Uuid,
+    /// The rows the statement reads
+    pub read_view: ReadView,
     /// Execution parameters
     pub parameters: HashMap<String, ContextValue>,
+    /// Values of the plan's query parameters, by index
+    pub parameter_values: Arc<Vec<crate::types::Value>>,
     /// Execution mode
     pub mode: ExecutionMode,
     /// Memory limit in bytes
@@ -32,6 +46,89 @@ pub struct ExecutionContext {
     pub thread_limit: Option<usize>,
     /// Parallel execution context
     pub parallel_context: ParallelContext,
+    /// Arena for temporary keys, shared by every operator in the query and freed with it
+    pub arena: Arc<QueryArena>,
+    /// Budget the operators of the query reserve buffered data against
+    pub memory: Arc<MemoryBudget>,
+    /// Whether division by zero and integer overflow fail the query or yield NULL
+    pub arithmetic_errors: ArithmeticErrorMode,
+    /// What INSERT and UPDATE do with strings longer than their column's declared length
+    pub string_length_limit: StringLengthMode,
+    /// Whether operands and stored values of different types are cast or rejected
+    pub type_coercion: TypeCoercion,
+    /// Collation strings are grouped by in GROUP BY and DISTINCT
+    pub grouping_collation: Collation,
+    /// Names of the tables the query has read, including from subqueries
+    pub tables_read: Arc<Mutex<HashSet<String>>>,
+    /// Counters the query's operators add their work to
+    pub metrics: Arc<MetricsRegistry>,
+    /// Safety limits the query's operators check at chunk boundaries
+    pub limits: QueryLimits,
+    /// Row changes the statement has written, to undo if it fails
+    pub statement_undo: Arc<StatementUndo>,
+}
+
+/// Row changes a statement has written, so a statement that fails partway
+/// leaves no partial changes
+#[derive(Debug, Default)]
+pub struct StatementUndo {
+    changes: Mutex<Vec<TableChange>>,
+}
+
+impl StatementUndo {
+    /// Record a row change the statement wrote to `table`
+    fn record(&self, table: &Arc<RwLock<TableData>>, change: RowChange) -> PrismDBResult<()> {
+        self.changes.lock_or_err()?.push((table.clone(), change));
+        Ok(())
+    }
+
+    /// Take the statement's row changes once it has succeeded
+    pub fn take(&self) -> PrismDBResult<Vec<TableChange>> {
+        Ok(std::mem::take(&mut *self.changes.lock_or_err()?))
+    }
+
+    /// Undo the statement's row changes, latest first
+    pub fn rollback(&self) -> PrismDBResult<()> {
+        for (table, change) in self.take()?.into_iter().rev() {
+            table.write_or_err()?.undo(&change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Limits that abort a runaway query, for databases embedded in other services
+#[derive(Debug, Clone, Default)]
+pub struct QueryLimits {
+    /// Most rows the query's table scans may read, in total
+    pub max_scan_rows: Option<usize>,
+    /// Instant after which the query is cancelled
+    pub deadline: Option<Instant>,
+    /// Rows read so far, shared by every operator of the query
+    rows_scanned: Arc<AtomicUsize>,
+}
+
+impl QueryLimits {
+    /// Fail if the statement has run past its deadline
+    pub fn check_deadline(&self) -> PrismDBResult<()> {
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(PrismDBError::LimitExceeded(
+                "statement timeout reached".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add `rows` to the rows scanned, failing once the scan budget is spent
+    fn add_scanned(&self, rows: usize) -> PrismDBResult<()> {
+        let total = self.rows_scanned.fetch_add(rows, Ordering::Relaxed) + rows;
+        match self.max_scan_rows {
+            Some(max) if total > max => Err(PrismDBError::LimitExceeded(format!(
+                "query scanned more than {} rows",
+                max
+            ))),
+            _ => self.check_deadline(),
+        }
+    }
 }
 
 /// Execution mode
@@ -49,17 +146,31 @@ impl ExecutionContext {
         catalog: Arc<RwLock<Catalog>>,
     ) -> Self {
         let parallel_context = ParallelContext::from_system();
+        let writer = Uuid::new_v4();
 
         Self {
             transaction_manager,
             catalog,
             transaction_id: None,
             transaction: None,
+            writer,
+            read_view: ReadView::latest(writer),
             parameters: HashMap::new(),
+            parameter_values: Arc::new(Vec::new()),
             mode: ExecutionMode::Parallel, // Enable parallel mode by default
             memory_limit: None,
             thread_limit: None,
             parallel_context,
+            arena: Arc::new(QueryArena::new()),
+            memory: Arc::new(MemoryBudget::new(None)),
+            arithmetic_errors: ArithmeticErrorMode::default(),
+            string_length_limit: StringLengthMode::default(),
+            type_coercion: TypeCoercion::default(),
+            grouping_collation: Collation::default(),
+            tables_read: Arc::new(Mutex::new(HashSet::new())),
+            metrics: Arc::new(MetricsRegistry::new()),
+            limits: QueryLimits::default(),
+            statement_undo: Arc::new(StatementUndo::default()),
         }
     }
 
@@ -76,6 +187,8 @@ impl ExecutionContext {
             crate::storage::transaction::IsolationLevel::ReadCommitted,
         )?);
         self.transaction_id = Some(transaction.id);
+        self.writer = transaction.id;
+        self.read_view = self.transaction_manager.read_view(transaction.id);
         self.transaction = Some(transaction);
         Ok(())
     }
@@ -104,6 +217,59 @@ impl ExecutionContext {
         self.transaction_id
     }
 
+    /// Whether the current transaction is read-only
+    pub fn is_read_only(&self) -> bool {
+        self.transaction_id
+            .is_some_and(|id| self.transaction_manager.is_read_only(id))
+    }
+
+    /// Note that the query read the table `table_name`
+    pub fn record_table_read(&self, table_name: &str) {
+        self.tables_read.lock_recovered().insert(table_name.to_string());
+    }
+
+    /// Count `rows` rows read from table storage, checking the query's limits
+    pub fn record_rows_scanned(&self, rows: usize) -> PrismDBResult<()> {
+        self.metrics.add(Metric::RowsScanned, rows as u64);
+        self.limits.add_scanned(rows)
+    }
+
+    /// Data of `table`, recording the read for the current transaction
+    ///
+    /// Which of its rows the statement sees is up to `read_view`.
+    pub fn table_data(
+        &self,
+        table: Arc<RwLock<TableData>>,
+    ) -> PrismDBResult<Arc<RwLock<TableData>>> {
+        if let Some(id) = self.transaction_id {
+            self.transaction_manager.record_read(id, &table)?;
+        }
+        Ok(table)
+    }
+
+    /// The rows the statement's writes apply to: the latest committed ones
+    /// plus its own
+    pub fn write_view(&self) -> ReadView {
+        ReadView::latest(self.writer)
+    }
+
+    /// Fail with a serialization error if the current transaction may not write `table`
+    pub fn check_write(&self, table: &Arc<RwLock<TableData>>) -> PrismDBResult<()> {
+        match self.transaction_id {
+            Some(id) => self.transaction_manager.check_write(id, table),
+            None => Ok(()),
+        }
+    }
+
+    /// Record a row change the statement wrote to `table`, so it can be
+    /// undone if the statement fails or its transaction rolls back
+    ///
+    /// Operators call this right after each write, so a statement failing
+    /// partway still undoes the rows it already changed.
+    pub fn record_change(&self, table: &Arc<RwLock<TableData>>, change: RowChange) -> PrismDBResult<()> {
+        self.statement_undo.record(table, change)
+    }
+
     /// Get the current transaction
     pub fn get_transaction(&self) -> PrismDBResult<Arc<Transaction>> {
         self.transaction
@@ -126,9 +292,16 @@ impl ExecutionContext {
         self.mode = mode;
     }
 
-    /// Set memory limit
+    /// Set memory limit, starting a fresh budget for the query
     pub fn set_memory_limit(&mut self, limit: Option<usize>) {
         self.memory_limit = limit;
+        self.memory = Arc::new(MemoryBudget::new(limit));
+    }
+
+    /// Start a reservation against the query's memory budget, reported
+    /// under `operator`, the operator's EXPLAIN name
+    pub fn memory_reservation(&self, operator: &str) -> MemoryReservation {
+        MemoryReservation::for_operator(self.memory.clone(), operator)
     }
 
     /// Set thread limit
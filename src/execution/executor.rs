@@ -66,6 +66,7 @@ impl QueryExecutor {
                 execution_time_ms: execution_time.as_millis() as u64,
                 memory_used_bytes: 0,  // TODO: Track memory usage
                 operators_executed: 0, // TODO: Track operator count
+                operator_memory: Vec::new(),
             },
         })
     }
@@ -121,6 +122,7 @@ impl QueryExecutor {
                 execution_time_ms: execution_time.as_millis() as u64,
                 memory_used_bytes: 0,
                 operators_executed: 0,
+                operator_memory: Vec::new(),
             },
         })
     }
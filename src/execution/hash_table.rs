@@ -3,7 +3,7 @@
 //! This module implements PrismDB's parallel hash table design:
 //! - Partitioned hash table with thread-local build
 //! - Lock-free probe phase after build completes
-//! - Linear probing for cache efficiency
+//! - Build rows stay in their chunks; entries are row references
 //! - Support for multiple join types (inner, left, semi, anti)
 //!
 //! Architecture:
@@ -19,56 +19,153 @@
 //! All threads can probe all partitions concurrently (lock-free).
 
 use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::types::{DataChunk, Value};
+use crate::planner::PhysicalJoinType;
+use crate::types::ordering::{interval_micros, sorted_entries};
+use crate::types::{DataChunk, Value, Vector};
 use rayon::prelude::*;
 use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
+use std::hash::Hasher;
 use std::sync::RwLock;
 
 /// Number of partitions for parallel hash table
 /// Power-of-2 partitioning for efficient modulo via bitwise AND
 pub const NUM_PARTITIONS: usize = 256;
 
-/// Hash table entry - represents one row in the hash table
-#[derive(Debug, Clone)]
-pub struct HashTableEntry {
-    /// Hash value for this entry
-    pub hash: u64,
-    /// Payload: all column values for this row
-    pub payload: Vec<Value>,
-    /// Next entry in the chain (for collision resolution)
-    pub next: Option<Box<HashTableEntry>>,
+/// Destination for encoded key bytes: a buffer for interned keys or a hasher
+pub trait KeySink {
+    fn put(&mut self, bytes: &[u8]);
 }
 
-impl HashTableEntry {
-    pub fn new(hash: u64, payload: Vec<Value>) -> Self {
-        Self {
-            hash,
-            payload,
-            next: None,
-        }
+impl KeySink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+impl KeySink for std::collections::hash_map::DefaultHasher {
+    fn put(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+    }
+}
+
+/// Encode a value as self-delimiting, type-tagged key bytes without allocating.
+/// Values that `Value::total_cmp` finds equal encode identically, so numbers of
+/// different types share a key (`1`, `1::BIGINT` and `1.0` group together) and
+/// the bytes can be hashed or used directly as a hash table key.
+pub fn encode_key_value<S: KeySink>(value: &Value, sink: &mut S) {
+    fn put_str<S: KeySink>(tag: u8, s: &[u8], sink: &mut S) {
+        sink.put(&[tag]);
+        sink.put(&(s.len() as u64).to_le_bytes());
+        sink.put(s);
     }
 
-    /// Check if this entry matches the given key values
-    pub fn matches(&self, key_values: &[Value], key_indices: &[usize]) -> bool {
-        for (i, &key_idx) in key_indices.iter().enumerate() {
-            if key_idx >= self.payload.len() {
-                return false;
+    match value {
+        Value::Null => sink.put(&[0]),
+        Value::Boolean(b) => sink.put(&[1, *b as u8]),
+        Value::TinyInt(_)
+        | Value::SmallInt(_)
+        | Value::Integer(_)
+        | Value::BigInt(_)
+        | Value::HugeInt { .. }
+        | Value::Decimal { .. }
+        | Value::Float(_)
+        | Value::Double(_) => {
+            if let Some(key) = value.number_key() {
+                sink.put(&[2]);
+                sink.put(&key);
             }
-            if self.payload[key_idx] != key_values[i] {
-                return false;
+        }
+        // CHAR and VARCHAR compare as text
+        Value::Varchar(s) | Value::Char(s) => put_str(9, s.as_bytes(), sink),
+        Value::Date(d) => {
+            sink.put(&[12]);
+            sink.put(&d.to_le_bytes());
+        }
+        Value::Time(t) => {
+            sink.put(&[13]);
+            sink.put(&t.to_le_bytes());
+        }
+        Value::Timestamp(t) => {
+            sink.put(&[14]);
+            sink.put(&t.to_le_bytes());
+        }
+        // By length, like intervals compare
+        Value::Interval { .. } => {
+            sink.put(&[15]);
+            sink.put(&interval_micros(value).to_le_bytes());
+        }
+        Value::UUID { high, low } => {
+            sink.put(&[16]);
+            sink.put(&high.to_le_bytes());
+            sink.put(&low.to_le_bytes());
+        }
+        Value::JSON(s) => put_str(17, s.as_bytes(), sink),
+        Value::Blob(b) => put_str(18, b, sink),
+        Value::List(items) => {
+            sink.put(&[19]);
+            sink.put(&(items.len() as u64).to_le_bytes());
+            for item in items {
+                encode_key_value(item, sink);
+            }
+        }
+        Value::Struct(fields) => {
+            sink.put(&[20]);
+            sink.put(&(fields.len() as u64).to_le_bytes());
+            for (name, field) in fields {
+                put_str(9, name.as_bytes(), sink);
+                encode_key_value(field, sink);
+            }
+        }
+        // Entries in key order, so maps built in different orders share a key
+        Value::Map(entries) => {
+            sink.put(&[21]);
+            sink.put(&(entries.len() as u64).to_le_bytes());
+            for (key, entry) in sorted_entries(entries) {
+                encode_key_value(key, sink);
+                encode_key_value(entry, sink);
             }
         }
-        true
+        Value::Union { tag, value } => {
+            sink.put(&[22]);
+            sink.put(&(*tag as u64).to_le_bytes());
+            encode_key_value(value, sink);
+        }
+        // Keyed by label like a VARCHAR, which enum values compare equal to
+        Value::Enum { code, enum_type } => put_str(9, enum_type.label(*code).as_bytes(), sink),
     }
 }
 
+/// Location of a build-side row: the stored chunk and the row within it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRef {
+    pub chunk: u32,
+    pub row: u32,
+}
+
+/// A probe row paired with its matching build row, or None when the build side is NULL
+/// (LEFT JOIN) or not emitted at all (SEMI/ANTI JOIN)
+pub type JoinMatch = (usize, Option<RowRef>);
+
+/// Hash table entry - references one build row and its encoded join key
+#[derive(Debug, Clone)]
+pub struct HashTableEntry {
+    /// Hash value for this entry
+    pub hash: u64,
+    /// Position of the encoded key in the partition's key buffer
+    key_start: usize,
+    key_len: usize,
+    /// Build row this entry points at
+    pub row: RowRef,
+}
+
 /// Single partition of the hash table
 /// Each partition is built by one thread and can be probed by all threads
 #[derive(Debug)]
 pub struct HashTablePartition {
     /// The actual hash table (hash -> entries)
     entries: HashMap<u64, Vec<HashTableEntry>>,
+    /// Encoded keys of all entries, stored back to back
+    keys: Vec<u8>,
     /// Total number of entries in this partition
     count: usize,
 }
@@ -77,28 +174,34 @@ impl HashTablePartition {
     pub fn new() -> Self {
         Self {
             entries: HashMap::new(),
+            keys: Vec::new(),
             count: 0,
         }
     }
 
     /// Insert an entry into this partition
-    pub fn insert(&mut self, hash: u64, payload: Vec<Value>) {
-        let entry = HashTableEntry::new(hash, payload);
-        self.entries.entry(hash).or_insert_with(Vec::new).push(entry);
+    pub fn insert(&mut self, hash: u64, key: &[u8], row: RowRef) {
+        let entry = HashTableEntry {
+            hash,
+            key_start: self.keys.len(),
+            key_len: key.len(),
+            row,
+        };
+        self.keys.extend_from_slice(key);
+        self.entries.entry(hash).or_default().push(entry);
         self.count += 1;
     }
 
-    /// Probe this partition for matching entries
-    pub fn probe(&self, hash: u64, key_values: &[Value], key_indices: &[usize]) -> Vec<Vec<Value>> {
-        if let Some(entries) = self.entries.get(&hash) {
-            entries
-                .iter()
-                .filter(|entry| entry.matches(key_values, key_indices))
-                .map(|entry| entry.payload.clone())
-                .collect()
-        } else {
-            Vec::new()
-        }
+    /// Probe this partition for rows whose encoded key equals `key`
+    pub fn probe<'a>(&'a self, hash: u64, key: &'a [u8]) -> impl Iterator<Item = RowRef> + 'a {
+        self.entries
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .filter(move |entry| {
+                &self.keys[entry.key_start..entry.key_start + entry.key_len] == key
+            })
+            .map(|entry| entry.row)
     }
 
     pub fn count(&self) -> usize {
@@ -106,17 +209,27 @@ impl HashTablePartition {
     }
 }
 
+/// Build rows hashed by one thread for a single partition: encoded keys back to back,
+/// plus (hash, key end, row) per entry
+#[derive(Debug, Clone, Default)]
+struct HashedRows {
+    keys: Vec<u8>,
+    entries: Vec<(u64, usize, RowRef)>,
+}
+
 /// Parallel Hash Table for high-performance joins
 ///
 /// Design:
 /// - NUM_PARTITIONS partitions for parallel build
-/// - Each partition is built independently
+/// - Build chunks are kept as-is (column-oriented); entries hold row references into them
+/// - Keys are stored encoded, so probing compares bytes instead of values
 /// - After build: all partitions are read-only, lock-free probing
-/// - Uses linear probing within partitions for cache efficiency
 pub struct ParallelHashTable {
     /// Partitions (one per thread during build)
     partitions: Vec<RwLock<HashTablePartition>>,
-    /// Indices of key columns in the payload
+    /// Build-side chunks referenced by the entries
+    chunks: Vec<DataChunk>,
+    /// Indices of key columns in the build chunks
     key_indices: Vec<usize>,
     /// Total number of entries across all partitions
     total_count: usize,
@@ -132,20 +245,18 @@ impl ParallelHashTable {
 
         Self {
             partitions,
+            chunks: Vec::new(),
             key_indices,
             total_count: 0,
         }
     }
 
-    /// Compute hash for a set of key values
-    fn compute_hash(values: &[Value]) -> u64 {
+    /// Compute hash for an encoded key
+    fn compute_hash(key: &[u8]) -> u64 {
         use std::collections::hash_map::DefaultHasher;
 
         let mut hasher = DefaultHasher::new();
-        for value in values {
-            // Hash the debug representation (simple but works for all types)
-            format!("{:?}", value).hash(&mut hasher);
-        }
+        hasher.write(key);
         hasher.finish()
     }
 
@@ -156,141 +267,205 @@ impl ParallelHashTable {
         (hash as usize) & (NUM_PARTITIONS - 1)
     }
 
-    /// Build hash table from a DataChunk (single-threaded for one chunk)
-    /// Multiple threads can call this concurrently for different chunks
-    pub fn build_from_chunk(&mut self, chunk: &DataChunk) -> PrismDBResult<()> {
-        for row_idx in 0..chunk.len() {
-            // Extract all column values for this row (payload)
-            let mut payload = Vec::with_capacity(chunk.column_count());
-            for col_idx in 0..chunk.column_count() {
-                let vector = chunk.get_vector(col_idx).ok_or_else(|| {
-                    PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
-                })?;
-                let value = vector.get_value(row_idx)?;
-                payload.push(value);
+    /// Encode the key columns of one row into `key`, reusing its allocation.
+    /// Returns false if any key is NULL, since NULL keys never match.
+    pub fn encode_row_key(
+        chunk: &DataChunk,
+        row_idx: usize,
+        key_indices: &[usize],
+        key: &mut Vec<u8>,
+    ) -> PrismDBResult<bool> {
+        key.clear();
+        for &col_idx in key_indices {
+            let vector = chunk.get_vector(col_idx).ok_or_else(|| {
+                PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
+            })?;
+            let value = vector.get_value(row_idx)?;
+            if value.is_null() {
+                return Ok(false);
             }
+            encode_key_value(&value, key);
+        }
+        Ok(true)
+    }
 
-            // Extract key values
-            let key_values: Vec<Value> = self
-                .key_indices
-                .iter()
-                .map(|&idx| payload[idx].clone())
-                .collect();
-
-            // Compute hash from key values
-            let hash = Self::compute_hash(&key_values);
-
-            // Determine which partition this entry belongs to
-            let partition_idx = Self::partition_index(hash);
+    /// Hash the key of every row in a build chunk, grouped by destination partition
+    fn hash_chunk(&self, chunk_idx: usize, chunk: &DataChunk) -> PrismDBResult<Vec<HashedRows>> {
+        let mut local_partitions = vec![HashedRows::default(); NUM_PARTITIONS];
+        let mut key = Vec::new();
+        for row_idx in 0..chunk.len() {
+            if !Self::encode_row_key(chunk, row_idx, &self.key_indices, &mut key)? {
+                continue;
+            }
+            let hash = Self::compute_hash(&key);
+            let local = &mut local_partitions[Self::partition_index(hash)];
+            local.keys.extend_from_slice(&key);
+            let row = RowRef {
+                chunk: chunk_idx as u32,
+                row: row_idx as u32,
+            };
+            local.entries.push((hash, local.keys.len(), row));
+        }
+        Ok(local_partitions)
+    }
 
-            // Insert into the appropriate partition
+    /// Insert hashed rows into the global partitions
+    fn insert_hashed(&self, local_partitions: Vec<HashedRows>) -> PrismDBResult<usize> {
+        let mut total = 0;
+        for (partition_idx, local) in local_partitions.into_iter().enumerate() {
+            if local.entries.is_empty() {
+                continue;
+            }
             // Lock only the specific partition (minimal contention)
             let mut partition = self.partitions[partition_idx]
                 .write()
                 .map_err(|_| PrismDBError::Internal("Failed to lock partition".to_string()))?;
-
-            partition.insert(hash, payload);
+            let mut key_start = 0;
+            for (hash, key_end, row) in local.entries {
+                partition.insert(hash, &local.keys[key_start..key_end], row);
+                key_start = key_end;
+                total += 1;
+            }
         }
+        Ok(total)
+    }
 
+    /// Build hash table from a DataChunk (single-threaded for one chunk)
+    pub fn build_from_chunk(&mut self, chunk: DataChunk) -> PrismDBResult<()> {
+        let local_partitions = self.hash_chunk(self.chunks.len(), &chunk)?;
+        self.total_count += self.insert_hashed(local_partitions)?;
+        self.chunks.push(chunk);
         Ok(())
     }
 
     /// Build hash table from multiple chunks in parallel
     pub fn build_parallel(&mut self, chunks: Vec<DataChunk>) -> PrismDBResult<()> {
-        // Use Rayon to build partitions in parallel
-        // Each thread processes some chunks
-        let partition_count: Vec<usize> = chunks
+        let offset = self.chunks.len();
+        // Each thread hashes whole chunks, then inserts them partition by partition
+        let inserted: Vec<usize> = chunks
             .par_iter()
-            .map(|chunk| {
-                // For each chunk, we need to insert into partitions
-                // We'll collect insertions per partition and do them in batch
-                let mut local_partitions: Vec<Vec<(u64, Vec<Value>)>> =
-                    vec![Vec::new(); NUM_PARTITIONS];
-
-                for row_idx in 0..chunk.len() {
-                    // Extract payload
-                    let mut payload = Vec::with_capacity(chunk.column_count());
-                    for col_idx in 0..chunk.column_count() {
-                        if let Some(vector) = chunk.get_vector(col_idx) {
-                            if let Ok(value) = vector.get_value(row_idx) {
-                                payload.push(value);
-                            }
-                        }
-                    }
-
-                    // Extract key values and compute hash
-                    let key_values: Vec<Value> = self
-                        .key_indices
-                        .iter()
-                        .filter_map(|&idx| payload.get(idx).cloned())
-                        .collect();
-
-                    let hash = Self::compute_hash(&key_values);
-                    let partition_idx = Self::partition_index(hash);
+            .enumerate()
+            .map(|(i, chunk)| self.insert_hashed(self.hash_chunk(offset + i, chunk)?))
+            .collect::<PrismDBResult<_>>()?;
 
-                    local_partitions[partition_idx].push((hash, payload));
-                }
-
-                // Now insert all local_partitions into global partitions
-                let mut total = 0;
-                for (partition_idx, entries) in local_partitions.iter().enumerate() {
-                    if !entries.is_empty() {
-                        if let Ok(mut partition) = self.partitions[partition_idx].write() {
-                            for (hash, payload) in entries {
-                                partition.insert(*hash, payload.clone());
-                                total += 1;
-                            }
-                        }
-                    }
-                }
-                total
-            })
-            .collect();
-
-        self.total_count = partition_count.iter().sum();
+        self.total_count += inserted.iter().sum::<usize>();
+        self.chunks.extend(chunks);
         Ok(())
     }
 
-    /// Probe hash table with key values
-    /// Returns all matching rows from the hash table
+    /// Find the build rows whose encoded key equals `key`
     /// This is lock-free after build completes (only uses read locks)
-    pub fn probe(&self, key_values: &[Value]) -> PrismDBResult<Vec<Vec<Value>>> {
-        let hash = Self::compute_hash(key_values);
-        let partition_idx = Self::partition_index(hash);
-
-        let partition = self.partitions[partition_idx]
+    pub fn probe_key(&self, key: &[u8]) -> PrismDBResult<Vec<RowRef>> {
+        let hash = Self::compute_hash(key);
+        let partition = self.partitions[Self::partition_index(hash)]
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock partition".to_string()))?;
+        Ok(partition.probe(hash, key).collect())
+    }
 
-        Ok(partition.probe(hash, key_values, &self.key_indices))
+    /// Probe hash table with key values
+    /// Returns all matching rows from the hash table
+    pub fn probe(&self, key_values: &[Value]) -> PrismDBResult<Vec<Vec<Value>>> {
+        if key_values.iter().any(|v| v.is_null()) {
+            return Ok(Vec::new());
+        }
+        let mut key = Vec::new();
+        for value in key_values {
+            encode_key_value(value, &mut key);
+        }
+        self.probe_key(&key)?
+            .into_iter()
+            .map(|row| self.row_values(row))
+            .collect()
     }
 
-    /// Probe hash table from a DataChunk
-    /// Returns matching rows for each row in the input chunk
+    /// Probe hash table with every row of a DataChunk
+    /// Returns the (probe row, build row) pairs the join type emits, in probe row order
     pub fn probe_chunk(
         &self,
         chunk: &DataChunk,
         probe_key_indices: &[usize],
-    ) -> PrismDBResult<Vec<Vec<Vec<Value>>>> {
-        let mut results = Vec::with_capacity(chunk.len());
+        join_type: &PhysicalJoinType,
+    ) -> PrismDBResult<Vec<JoinMatch>> {
+        let mut matches = Vec::with_capacity(chunk.len());
+        let mut key = Vec::new();
 
         for row_idx in 0..chunk.len() {
-            // Extract key values from this row
-            let mut key_values = Vec::with_capacity(probe_key_indices.len());
-            for &key_idx in probe_key_indices {
-                let vector = chunk.get_vector(key_idx).ok_or_else(|| {
-                    PrismDBError::InvalidValue(format!("Column {} not found", key_idx))
-                })?;
-                let value = vector.get_value(row_idx)?;
-                key_values.push(value);
+            let rows = if Self::encode_row_key(chunk, row_idx, probe_key_indices, &mut key)? {
+                self.probe_key(&key)?
+            } else {
+                Vec::new()
+            };
+
+            match join_type {
+                PhysicalJoinType::Semi => {
+                    if !rows.is_empty() {
+                        matches.push((row_idx, None));
+                    }
+                }
+                PhysicalJoinType::Anti => {
+                    if rows.is_empty() {
+                        matches.push((row_idx, None));
+                    }
+                }
+                PhysicalJoinType::Left if rows.is_empty() => matches.push((row_idx, None)),
+                _ => matches.extend(rows.into_iter().map(|row| (row_idx, Some(row)))),
             }
+        }
+
+        Ok(matches)
+    }
 
-            // Probe hash table
-            let matches = self.probe(&key_values)?;
-            results.push(matches);
+    /// Assemble joined output chunks of up to `chunk_size` rows for a probe chunk, copying
+    /// the probe columns and then `build_columns` build columns (NULL where a match has no
+    /// build row) column by column
+    pub fn gather(
+        &self,
+        probe: &DataChunk,
+        matches: &[JoinMatch],
+        build_columns: usize,
+        chunk_size: usize,
+    ) -> PrismDBResult<Vec<DataChunk>> {
+        let mut chunks = Vec::new();
+        for batch in matches.chunks(chunk_size) {
+            let mut chunk = DataChunk::with_rows(batch.len());
+            for col_idx in 0..probe.column_count() {
+                let vector = probe.get_vector(col_idx).ok_or_else(|| {
+                    PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
+                })?;
+                let values = batch
+                    .iter()
+                    .map(|&(row, _)| vector.get_value(row))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                chunk.set_vector(col_idx, Vector::from_values(&values)?)?;
+            }
+            for col_idx in 0..build_columns {
+                let values = batch
+                    .iter()
+                    .map(|&(_, row)| match row {
+                        Some(row) => self.value_at(row, col_idx),
+                        None => Ok(Value::Null),
+                    })
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                chunk.set_vector(probe.column_count() + col_idx, Vector::from_values(&values)?)?;
+            }
+            chunks.push(chunk);
         }
+        Ok(chunks)
+    }
 
-        Ok(results)
+    /// Get one column of a build row
+    fn value_at(&self, row: RowRef, col_idx: usize) -> PrismDBResult<Value> {
+        self.chunks[row.chunk as usize]
+            .get_vector(col_idx)
+            .ok_or_else(|| PrismDBError::InvalidValue(format!("Column {} not found", col_idx)))?
+            .get_value(row.row as usize)
+    }
+
+    /// Materialize all columns of a build row
+    pub fn row_values(&self, row: RowRef) -> PrismDBResult<Vec<Value>> {
+        let column_count = self.chunks[row.chunk as usize].column_count();
+        (0..column_count).map(|col_idx| self.value_at(row, col_idx)).collect()
     }
 
     /// Get total number of entries in the hash table
@@ -307,27 +482,75 @@ impl ParallelHashTable {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::constants::STANDARD_VECTOR_SIZE;
     use crate::types::Vector;
 
+    fn row(row: u32) -> RowRef {
+        RowRef { chunk: 0, row }
+    }
+
     #[test]
     fn test_hash_table_partition() -> PrismDBResult<()> {
         let mut partition = HashTablePartition::new();
 
-        // Insert some entries
-        partition.insert(100, vec![Value::integer(1), Value::Varchar("a".to_string())]);
-        partition.insert(100, vec![Value::integer(2), Value::Varchar("b".to_string())]);
-        partition.insert(200, vec![Value::integer(3), Value::Varchar("c".to_string())]);
+        // Insert some entries; the first two share a hash but not a key
+        partition.insert(100, b"a", row(0));
+        partition.insert(100, b"b", row(1));
+        partition.insert(200, b"c", row(2));
 
         assert_eq!(partition.count(), 3);
 
         // Probe for hash 100
-        let results = partition.probe(100, &[Value::integer(1)], &[0]);
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0][0], Value::integer(1));
+        let results: Vec<RowRef> = partition.probe(100, b"a").collect();
+        assert_eq!(results, vec![row(0)]);
+        assert_eq!(partition.probe(200, b"a").count(), 0);
 
         Ok(())
     }
 
+    fn encode(values: &[Value]) -> Vec<u8> {
+        let mut key = Vec::new();
+        for value in values {
+            encode_key_value(value, &mut key);
+        }
+        key
+    }
+
+    #[test]
+    fn test_encode_key_value() {
+        // Strings are length-prefixed, so separators inside values can't collide
+        assert_ne!(
+            encode(&[Value::Varchar("a|b".to_string()), Value::Varchar("c".to_string())]),
+            encode(&[Value::Varchar("a".to_string()), Value::Varchar("b|c".to_string())])
+        );
+        // Types are tagged
+        assert_ne!(encode(&[Value::Null]), encode(&[Value::Varchar("NULL".to_string())]));
+        assert_ne!(encode(&[Value::Integer(1)]), encode(&[Value::Varchar("1".to_string())]));
+        // Values that compare equal encode identically, numbers whatever their type
+        assert_eq!(encode(&[Value::Double(0.0)]), encode(&[Value::Double(-0.0)]));
+        assert_eq!(encode(&[Value::Integer(1)]), encode(&[Value::BigInt(1)]));
+        assert_eq!(encode(&[Value::Integer(1)]), encode(&[Value::Double(1.0)]));
+        assert_eq!(
+            encode(&[Value::Decimal { value: 150, scale: 2, precision: 5 }]),
+            encode(&[Value::Float(1.5)])
+        );
+        assert_ne!(encode(&[Value::Integer(1)]), encode(&[Value::Double(1.5)]));
+        assert_eq!(
+            encode(&[Value::List(vec![Value::Integer(1), Value::Null])]),
+            encode(&[Value::List(vec![Value::Integer(1), Value::Null])])
+        );
+        // Map entries are encoded in key order
+        let entry = |key: &str, value: i32| (Value::Varchar(key.to_string()), Value::Integer(value));
+        assert_eq!(
+            encode(&[Value::Map(vec![entry("a", 1), entry("b", 2)])]),
+            encode(&[Value::Map(vec![entry("b", 2), entry("a", 1)])])
+        );
+        assert_ne!(
+            encode(&[Value::Map(vec![entry("a", 1), entry("b", 2)])]),
+            encode(&[Value::Map(vec![entry("a", 2), entry("b", 1)])])
+        );
+    }
+
     #[test]
     fn test_parallel_hash_table_build() -> PrismDBResult<()> {
         let mut ht = ParallelHashTable::new(vec![0]); // Key is column 0
@@ -345,7 +568,7 @@ mod tests {
             Value::Varchar("c".to_string()),
         ])?)?;
 
-        ht.build_from_chunk(&chunk)?;
+        ht.build_from_chunk(chunk)?;
 
         // Probe for key=1
         let results = ht.probe(&[Value::integer(1)])?;
@@ -377,7 +600,7 @@ mod tests {
             Value::Varchar("d".to_string()),
         ])?)?;
 
-        ht.build_from_chunk(&build_chunk)?;
+        ht.build_from_chunk(build_chunk)?;
 
         // Probe for key=2 (has 2 matches)
         let results = ht.probe(&[Value::integer(2)])?;
@@ -389,4 +612,52 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parallel_hash_table_join_matches() -> PrismDBResult<()> {
+        let mut ht = ParallelHashTable::new(vec![0]);
+        let mut build_chunk = DataChunk::with_rows(3);
+        build_chunk.set_vector(0, Vector::from_values(&[
+            Value::integer(1),
+            Value::Null,
+            Value::integer(1),
+        ])?)?;
+        build_chunk.set_vector(1, Vector::from_values(&[
+            Value::Varchar("x".to_string()),
+            Value::Varchar("null".to_string()),
+            Value::Varchar("y".to_string()),
+        ])?)?;
+        ht.build_parallel(vec![build_chunk])?;
+
+        // NULL keys are never inserted or matched
+        assert_eq!(ht.count(), 2);
+        assert!(ht.probe(&[Value::Null])?.is_empty());
+
+        let mut probe_chunk = DataChunk::with_rows(3);
+        probe_chunk.set_vector(0, Vector::from_values(&[
+            Value::integer(1),
+            Value::integer(2),
+            Value::Null,
+        ])?)?;
+
+        let inner = ht.probe_chunk(&probe_chunk, &[0], &PhysicalJoinType::Inner)?;
+        assert_eq!(inner, vec![(0, Some(row(0))), (0, Some(row(2)))]);
+        let left = ht.probe_chunk(&probe_chunk, &[0], &PhysicalJoinType::Left)?;
+        assert_eq!(left[2..], [(1, None), (2, None)]);
+        let semi = ht.probe_chunk(&probe_chunk, &[0], &PhysicalJoinType::Semi)?;
+        assert_eq!(semi, vec![(0, None)]);
+        let anti = ht.probe_chunk(&probe_chunk, &[0], &PhysicalJoinType::Anti)?;
+        assert_eq!(anti, vec![(1, None), (2, None)]);
+
+        // Output is gathered column by column from both sides
+        let output = ht.gather(&probe_chunk, &left, 2, STANDARD_VECTOR_SIZE)?;
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].len(), 4);
+        assert_eq!(output[0].column_count(), 3);
+        let second = output[0].get_vector(2).unwrap();
+        assert_eq!(second.get_value(1)?, Value::Varchar("y".to_string()));
+        assert_eq!(second.get_value(3)?, Value::Null);
+
+        Ok(())
+    }
 }
@@ -11,6 +11,7 @@ pub mod parallel;
 pub mod parallel_operators;
 pub mod pipeline;
 pub mod pivot_utils;
+pub mod scheduler;
 
 pub use context::*;
 pub use executor::*;
@@ -20,8 +21,10 @@ pub use parallel::*;
 pub use parallel_operators::*;
 pub use pipeline::*;
 pub use pivot_utils::*;
+pub use scheduler::*;
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::metrics::Metric;
 use crate::planner::{DataChunkStream, ExecutionOperator, PhysicalPlan};
 use crate::types::DataChunk;
 
@@ -38,6 +41,15 @@ impl ExecutionEngine {
 
     /// Execute a physical plan and return a stream of results
     pub fn execute(&mut self, plan: PhysicalPlan) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        if let Some(operation) = plan.write_operation() {
+            if self.context.is_read_only() {
+                return Err(PrismDBError::ReadOnly(format!(
+                    "cannot execute {} in a read-only transaction",
+                    operation
+                )));
+            }
+        }
+
         let operator = self.create_operator(plan)?;
         operator.execute()
     }
@@ -55,138 +67,158 @@ impl ExecutionEngine {
         Ok(results)
     }
 
-    /// Create an execution operator from a physical plan
-    fn create_operator(&self, plan: PhysicalPlan) -> PrismDBResult<Box<dyn ExecutionOperator>> {
+    /// Create the operator tree for a physical plan
+    ///
+    /// Every child is translated here, once, and handed to its parent, so
+    /// executing an operator (or executing it again, as a recursive CTE does)
+    /// never re-plans its inputs.
+    pub fn create_operator(&self, plan: PhysicalPlan) -> PrismDBResult<Box<dyn ExecutionOperator>> {
+        self.context.metrics.increment(Metric::OperatorsBuilt);
+        let context = self.context.clone();
         match plan {
-            PhysicalPlan::TableScan(scan) => {
-                Ok(Box::new(TableScanOperator::new(scan, self.context.clone())))
-            }
+            PhysicalPlan::TableScan(scan) => Ok(Box::new(TableScanOperator::new(scan, context))),
             PhysicalPlan::Filter(filter) => {
-                Ok(Box::new(FilterOperator::new(filter, self.context.clone())))
+                let input = self.create_operator((*filter.input).clone())?;
+                Ok(Box::new(FilterOperator::new(filter, input, context)))
             }
             PhysicalPlan::Qualify(qualify) => {
-                Ok(Box::new(QualifyOperator::new(qualify, self.context.clone())))
+                let input = self.create_operator((*qualify.input).clone())?;
+                Ok(Box::new(QualifyOperator::new(qualify, input, context)))
+            }
+            PhysicalPlan::Window(window) => {
+                let input = self.create_operator((*window.input).clone())?;
+                Ok(Box::new(WindowOperator::new(window, input, context)))
             }
             PhysicalPlan::Projection(projection) => {
-                let input = *projection.input.clone();
-                let _child = self.create_operator(input)?;
-                Ok(Box::new(ProjectionOperator::new(
-                    projection,
-                    self.context.clone(),
-                )))
+                let input = self.create_operator((*projection.input).clone())?;
+                Ok(Box::new(ProjectionOperator::new(projection, input, context)))
+            }
+            PhysicalPlan::Unnest(unnest) => {
+                let input = self.create_operator((*unnest.input).clone())?;
+                Ok(Box::new(UnnestOperator::new(unnest, input)))
             }
             PhysicalPlan::Limit(limit) => {
-                let input = *limit.input.clone();
-                let _child = self.create_operator(input)?;
-                Ok(Box::new(LimitOperator::new(limit, self.context.clone())))
+                let input = self.create_operator((*limit.input).clone())?;
+                Ok(Box::new(LimitOperator::new(limit, input)))
+            }
+            PhysicalPlan::Distinct(distinct) => {
+                let input = self.create_operator((*distinct.input).clone())?;
+                Ok(Box::new(DistinctOperator::new(distinct, input, context)))
             }
             PhysicalPlan::Sort(sort) => {
                 // Use high-performance parallel sort
-                Ok(Box::new(ParallelSortOperator::new(
-                    sort,
-                    self.context.clone(),
-                )))
+                let input = self.create_operator((*sort.input).clone())?;
+                Ok(Box::new(ParallelSortOperator::new(sort, input, context)))
             }
             PhysicalPlan::Aggregate(aggregate) => {
                 // Use high-performance parallel hash aggregate
-                Ok(Box::new(ParallelHashAggregateOperator::new(
-                    aggregate,
-                    self.context.clone(),
-                )))
+                let input = self.create_operator((*aggregate.input).clone())?;
+                Ok(Box::new(ParallelHashAggregateOperator::new(aggregate, input, context)))
+            }
+            PhysicalPlan::StreamingAggregate(aggregate) => {
+                let input = self.create_operator((*aggregate.input).clone())?;
+                // Input sorted in code point order can split a NOCASE group, so hash instead
+                if !matches!(context.grouping_collation, crate::types::Collation::Binary) {
+                    let aggregate = crate::planner::PhysicalAggregate {
+                        input: aggregate.input,
+                        group_by: aggregate.group_by,
+                        aggregates: aggregate.aggregates,
+                        schema: aggregate.schema,
+                    };
+                    return Ok(Box::new(ParallelHashAggregateOperator::new(aggregate, input, context)));
+                }
+                Ok(Box::new(StreamingAggregateOperator::new(aggregate, input, context)))
             }
             PhysicalPlan::HashAggregate(hash_aggregate) => {
                 // Convert PhysicalHashAggregate to PhysicalAggregate for execution
                 // They're functionally the same, just different optimizer representations
                 let aggregate = crate::planner::PhysicalAggregate {
-                    input: hash_aggregate.input.clone(),
-                    group_by: hash_aggregate.group_by.clone(),
-                    aggregates: hash_aggregate.aggregates.clone(),
-                    schema: hash_aggregate.schema.clone(),
+                    input: hash_aggregate.input,
+                    group_by: hash_aggregate.group_by,
+                    aggregates: hash_aggregate.aggregates,
+                    schema: hash_aggregate.schema,
                 };
-                Ok(Box::new(ParallelHashAggregateOperator::new(
-                    aggregate,
-                    self.context.clone(),
-                )))
+                let input = self.create_operator((*aggregate.input).clone())?;
+                Ok(Box::new(ParallelHashAggregateOperator::new(aggregate, input, context)))
             }
             PhysicalPlan::HashJoin(join) => {
                 // Use high-performance parallel hash join
-                Ok(Box::new(ParallelHashJoinOperator::new(
-                    join,
-                    self.context.clone(),
-                )))
+                let left = self.create_operator((*join.left).clone())?;
+                let right = self.create_operator((*join.right).clone())?;
+                Ok(Box::new(ParallelHashJoinOperator::new(join, left, right, context)))
             }
-            PhysicalPlan::Insert(insert) => {
-                let input = *insert.input.clone();
-                let _child = self.create_operator(input)?;
-                Ok(Box::new(InsertOperator::new(insert, self.context.clone())))
+            PhysicalPlan::CountStar(count) => Ok(Box::new(CountStarOperator::new(count, context))),
+            PhysicalPlan::Explain(explain) => Ok(Box::new(ExplainOperator::new(explain, context))),
+            PhysicalPlan::SortMergeJoin(join) => {
+                let left = self.create_operator((*join.left).clone())?;
+                let right = self.create_operator((*join.right).clone())?;
+                Ok(Box::new(SortMergeJoinOperator::new(join, left, right, context)))
             }
-            PhysicalPlan::Update(update) => {
-                Ok(Box::new(UpdateOperator::new(update, self.context.clone())))
+            PhysicalPlan::AsOfJoin(join) => {
+                let left = self.create_operator((*join.left).clone())?;
+                let right = self.create_operator((*join.right).clone())?;
+                Ok(Box::new(AsOfJoinOperator::new(join, left, right, context)))
             }
-            PhysicalPlan::Delete(delete) => {
-                Ok(Box::new(DeleteOperator::new(delete, self.context.clone())))
+            PhysicalPlan::Insert(insert) => {
+                let input = self.create_operator((*insert.input).clone())?;
+                Ok(Box::new(InsertOperator::new(insert, input, context)))
             }
-            PhysicalPlan::CreateTable(create) => Ok(Box::new(CreateTableOperator::new(
-                create,
-                self.context.clone(),
-            ))),
-            PhysicalPlan::DropTable(drop) => {
-                Ok(Box::new(DropTableOperator::new(drop, self.context.clone())))
+            PhysicalPlan::Update(update) => Ok(Box::new(UpdateOperator::new(update, context))),
+            PhysicalPlan::Delete(delete) => Ok(Box::new(DeleteOperator::new(delete, context))),
+            PhysicalPlan::Merge(merge) => {
+                let source = self.create_operator((*merge.source).clone())?;
+                Ok(Box::new(MergeOperator::new(merge, source, context)))
             }
-            PhysicalPlan::Values(values) => {
-                Ok(Box::new(ValuesOperator::new(values, self.context.clone())))
+            PhysicalPlan::CreateTable(create) => {
+                Ok(Box::new(CreateTableOperator::new(create, context)))
             }
+            PhysicalPlan::DropTable(drop) => Ok(Box::new(DropTableOperator::new(drop, context))),
+            PhysicalPlan::Values(values) => Ok(Box::new(ValuesOperator::new(values, context))),
             PhysicalPlan::Pivot(pivot) => {
-                Ok(Box::new(PivotOperator::new(pivot, self.context.clone())))
+                let input = self.create_operator((*pivot.input).clone())?;
+                Ok(Box::new(PivotOperator::new(pivot, input, context)))
             }
             PhysicalPlan::Unpivot(unpivot) => {
-                Ok(Box::new(UnpivotOperator::new(unpivot, self.context.clone())))
+                let input = self.create_operator((*unpivot.input).clone())?;
+                Ok(Box::new(UnpivotOperator::new(unpivot, input, context)))
             }
             PhysicalPlan::Union(union) => {
-                Ok(Box::new(UnionOperator::new(union, self.context.clone())))
+                let left = self.create_operator((*union.left).clone())?;
+                let right = self.create_operator((*union.right).clone())?;
+                Ok(Box::new(UnionOperator::new(union, left, right, context)))
             }
             PhysicalPlan::Intersect(intersect) => {
-                Ok(Box::new(IntersectOperator::new(
-                    *intersect.left.clone(),
-                    *intersect.right.clone(),
-                    intersect.schema.clone(),
-                    self.context.clone(),
-                )))
+                let left = self.create_operator(*intersect.left)?;
+                let right = self.create_operator(*intersect.right)?;
+                Ok(Box::new(IntersectOperator::new(left, right, intersect.all, intersect.schema, context)))
             }
             PhysicalPlan::Except(except) => {
-                Ok(Box::new(ExceptOperator::new(
-                    *except.left.clone(),
-                    *except.right.clone(),
-                    except.schema.clone(),
-                    self.context.clone(),
-                )))
+                let left = self.create_operator(*except.left)?;
+                let right = self.create_operator(*except.right)?;
+                Ok(Box::new(ExceptOperator::new(left, right, except.all, except.schema, context)))
             }
             PhysicalPlan::RecursiveCTE(rcte) => {
+                let base_case = self.create_operator((*rcte.base_case).clone())?;
+                let recursive_case = self.create_operator((*rcte.recursive_case).clone())?;
                 Ok(Box::new(RecursiveCTEOperator::new(
                     &rcte,
-                    self.context.clone(),
+                    base_case,
+                    recursive_case,
+                    context,
                 )))
             }
             PhysicalPlan::IteratorStream(stream) => {
                 Ok(Box::new(IteratorStreamOperator::new(stream)))
             }
-            PhysicalPlan::CreateMaterializedView(create_mv) => {
-                Ok(Box::new(CreateMaterializedViewOperator::new(
-                    create_mv,
-                    self.context.clone(),
-                )))
-            }
-            PhysicalPlan::DropMaterializedView(drop_mv) => {
-                Ok(Box::new(DropMaterializedViewOperator::new(
-                    drop_mv,
-                    self.context.clone(),
-                )))
-            }
+            PhysicalPlan::CreateMaterializedView(create_mv) => Ok(Box::new(
+                CreateMaterializedViewOperator::new(create_mv, context),
+            )),
+            PhysicalPlan::DropMaterializedView(drop_mv) => Ok(Box::new(
+                DropMaterializedViewOperator::new(drop_mv, context),
+            )),
             PhysicalPlan::RefreshMaterializedView(refresh_mv) => {
-                Ok(Box::new(RefreshMaterializedViewOperator::new(
-                    refresh_mv,
-                    self.context.clone(),
-                )))
+                let query = self.create_operator((*refresh_mv.query).clone())?;
+                Ok(Box::new(RefreshMaterializedViewOperator::new(refresh_mv, query, context)))
             }
             PhysicalPlan::EmptyResult(_) => Ok(Box::new(SimpleDataChunkStream::empty())),
             _ => Err(PrismDBError::Execution(format!(
@@ -202,8 +234,12 @@ impl ExecutionEngine {
 pub struct ExecutionStats {
     pub rows_processed: usize,
     pub execution_time_ms: u64,
+    /// Most bytes the query's blocking operators held at once
     pub memory_used_bytes: usize,
     pub operators_executed: usize,
+    /// Peak bytes of each blocking operator (sorts, aggregates, join builds,
+    /// set operations) by EXPLAIN name, in the order the operators started
+    pub operator_memory: Vec<(String, usize)>,
 }
 
 impl Default for ExecutionStats {
@@ -213,6 +249,7 @@ impl Default for ExecutionStats {
             execution_time_ms: 0,
             memory_used_bytes: 0,
             operators_executed: 0,
+            operator_memory: Vec::new(),
         }
     }
 }
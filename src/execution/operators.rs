@@ -2,37 +2,33 @@
 //!
 //! Implements various execution operators for different physical plan nodes.
 
-use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::allocator::MemoryReservation;
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::metrics::Metric;
+use crate::common::sync::RwLockExt;
 use crate::execution::context::ExecutionContext;
 use crate::planner::{
-    DataChunkStream, ExecutionOperator, PhysicalAggregate, PhysicalColumn, PhysicalCreateTable,
+    DataChunkStream, ExecutionOperator, ExplainNode, IndexLookup, PhysicalAggregate, PhysicalColumn, PhysicalCountStar,
+    PhysicalCreateTable, PhysicalExplain,
     PhysicalDelete, PhysicalDropTable, PhysicalFilter, PhysicalHashJoin, PhysicalInsert,
-    PhysicalLimit, PhysicalPlan, PhysicalProjection, PhysicalQualify, PhysicalSort, PhysicalTableScan,
+    PhysicalDistinct, PhysicalLimit, PhysicalMerge, PhysicalMergeAction, PhysicalProjection, PhysicalQualify, PhysicalSort, PhysicalSortExpression,
+    PhysicalSortMergeJoin, PhysicalAsOfJoin, PhysicalStreamingAggregate, PhysicalWindow, PhysicalWindowFunction,
+    PhysicalTableScan,
     PhysicalUnion, PhysicalUpdate,
 };
-use crate::types::{DataChunk, Value};
-
-/// Serialize a Value to a string for hash key (without Display formatting which adds quotes)
-fn value_to_key_string(value: &Value) -> String {
-    match value {
-        Value::Null => "NULL".to_string(),
-        Value::Boolean(b) => b.to_string(),
-        Value::TinyInt(i) => i.to_string(),
-        Value::SmallInt(i) => i.to_string(),
-        Value::Integer(i) => i.to_string(),
-        Value::BigInt(i) => i.to_string(),
-        Value::Float(f) => f.to_string(),
-        Value::Double(f) => f.to_string(),
-        Value::Varchar(s) => s.clone(), // Don't add quotes!
-        Value::Char(s) => s.clone(),
-        Value::Decimal { value, scale, .. } => {
-            let divisor = 10_i128.pow(*scale as u32);
-            let integer_part = value / divisor;
-            let fractional_part = (value % divisor).abs();
-            format!("{}.{:0width$}", integer_part, fractional_part, width = *scale as usize)
-        }
-        _ => format!("{:?}", value), // Fallback for other types
-    }
+use crate::parser::ast::ExplainFormat;
+use crate::storage::UniqueKeys;
+use crate::types::utils::adaptive_chunk_size;
+use crate::types::{DataChunk, OrderedValue, Value};
+
+/// Approximate memory held by a row materialized as `Value`s
+fn row_memory_usage(row: &[Value]) -> usize {
+    std::mem::size_of::<Vec<Value>>() + row.iter().map(Value::memory_usage).sum::<usize>()
+}
+
+/// Approximate memory held by a row key stored in a hash set
+fn key_memory_usage(key: &[OrderedValue]) -> usize {
+    std::mem::size_of::<Vec<OrderedValue>>() + key.iter().map(OrderedValue::memory_usage).sum::<usize>()
 }
 
 /// Simple iterator-based data chunk stream
@@ -83,72 +79,130 @@ impl ExecutionOperator for SimpleDataChunkStream {
     }
 }
 
-/// Table scan operator (PrismDB-faithful implementation)
-/// Reads data from the storage layer
-pub struct TableScanOperator {
-    scan: PhysicalTableScan,
-    context: ExecutionContext,
+/// Stream that transforms each chunk of its input as it is pulled
+///
+/// Chunks that come out empty are skipped, so consumers only see rows.
+struct MapChunkStream<F> {
+    input: Box<dyn DataChunkStream>,
+    map: F,
 }
 
-impl TableScanOperator {
-    pub fn new(scan: PhysicalTableScan, context: ExecutionContext) -> Self {
-        Self { scan, context }
-    }
-
-    /// Apply a pushed-down filter to a chunk using SelectionVector (PrismDB-faithful)
-    fn apply_filter_to_chunk(
-        &self,
-        chunk: DataChunk,
-        filter_expr: &crate::expression::expression::ExpressionRef,
-    ) -> PrismDBResult<DataChunk> {
-        use crate::common::error::PrismDBError;
-        use crate::types::{SelectionVector, Value};
+impl<F> Iterator for MapChunkStream<F>
+where
+    F: FnMut(DataChunk) -> PrismDBResult<DataChunk> + Send,
+{
+    type Item = PrismDBResult<DataChunk>;
 
-        if chunk.len() == 0 {
-            return Ok(chunk);
+    fn next(&mut self) -> Option<Self::Item> {
+        for chunk in self.input.by_ref() {
+            match chunk.and_then(&mut self.map) {
+                Ok(chunk) if chunk.is_empty() => continue,
+                result => return Some(result),
+            }
         }
+        None
+    }
+}
 
-        // Evaluate the filter predicate on this chunk
-        let result_vector = filter_expr.evaluate(&chunk, &self.context)?;
+impl<F> DataChunkStream for MapChunkStream<F> where F: FnMut(DataChunk) -> PrismDBResult<DataChunk> + Send {}
 
-        // Build SelectionVector with indices of rows that pass the filter
-        let mut selection = SelectionVector::new(chunk.len());
+/// Rows a single-threaded table scan reads
+enum ScanRows {
+    /// Physical rows from `offset` up to the table's length when the scan started
+    All { offset: usize, total_rows: usize },
+    /// Physical row ranges whose zones may satisfy the filters, read front to back
+    Ranges(std::collections::VecDeque<std::ops::Range<usize>>),
+}
 
-        for i in 0..chunk.len() {
-            let value = result_vector.get_value(i)?;
+/// Single-threaded table scan that reads a chunk only when the consumer asks for one
+struct TableScanStream {
+    table_data: std::sync::Arc<std::sync::RwLock<crate::storage::TableData>>,
+    rows: ScanRows,
+    filters: Vec<crate::expression::expression::ExpressionRef>,
+    context: ExecutionContext,
+    chunk_size: usize,
+    /// Rows a pushed-down LIMIT still allows
+    remaining: usize,
+}
 
-            // Check if this row passes the filter
-            let passes = match value {
-                Value::Boolean(b) => b,
-                Value::Null => false, // NULL in filter evaluates to false
-                _ => {
-                    return Err(PrismDBError::Execution(format!(
-                        "Filter predicate must return boolean, got {:?}",
-                        value
-                    )));
+impl TableScanStream {
+    /// Read the next chunk of rows before filtering, or None at the end of the scan
+    fn read_chunk(&mut self) -> Option<PrismDBResult<DataChunk>> {
+        let table_data = match self.table_data.read_or_err() {
+            Ok(table_data) => table_data,
+            Err(error) => return Some(Err(error)),
+        };
+        match &mut self.rows {
+            ScanRows::All { offset, total_rows } => {
+                if *offset >= *total_rows {
+                    return None;
                 }
-            };
-
-            if passes {
-                selection.append(i);
+                let end = std::cmp::min(*offset + self.chunk_size, *total_rows);
+                let chunk = table_data.create_chunk_range(&self.context.read_view, *offset, end);
+                *offset = end;
+                Some(chunk)
+            }
+            ScanRows::Ranges(ranges) => {
+                let range = ranges.front_mut()?;
+                let end = std::cmp::min(range.start + self.chunk_size, range.end);
+                let chunk = table_data.create_chunk_range(&self.context.read_view, range.start, end);
+                range.start = end;
+                if range.start >= range.end {
+                    ranges.pop_front();
+                }
+                Some(chunk)
             }
         }
+    }
 
-        // Optimization: If all rows pass, return original chunk unchanged
-        if selection.count() == chunk.len() {
-            return Ok(chunk);
+    fn filter_chunk(&self, mut chunk: DataChunk) -> PrismDBResult<DataChunk> {
+        self.context.record_rows_scanned(chunk.len())?;
+        for filter_expr in &self.filters {
+            chunk = TableScanOperator::apply_filter_inline(chunk, filter_expr, &self.context)?;
         }
+        if chunk.len() > self.remaining {
+            chunk = chunk.slice_range(0, self.remaining)?;
+        }
+        Ok(chunk)
+    }
+}
 
-        // Optimization: If no rows pass, return empty chunk
-        if selection.is_empty() {
-            return Ok(DataChunk::new());
+impl Iterator for TableScanStream {
+    type Item = PrismDBResult<DataChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.remaining > 0 {
+            match self.read_chunk()?.and_then(|chunk| self.filter_chunk(chunk)) {
+                Ok(chunk) if chunk.is_empty() => continue,
+                Ok(chunk) => {
+                    self.remaining -= chunk.len();
+                    return Some(Ok(chunk));
+                }
+                Err(e) => {
+                    self.remaining = 0;
+                    return Some(Err(e));
+                }
+            }
         }
+        None
+    }
+}
 
-        // Apply selection vector to create filtered chunk
-        chunk.slice(&selection)
+impl DataChunkStream for TableScanStream {}
+
+/// Table scan operator (PrismDB-faithful implementation)
+/// Reads data from the storage layer
+pub struct TableScanOperator {
+    scan: PhysicalTableScan,
+    context: ExecutionContext,
+}
+
+impl TableScanOperator {
+    pub fn new(scan: PhysicalTableScan, context: ExecutionContext) -> Self {
+        Self { scan, context }
     }
 
-    /// Static version of filter application for use in closures (parallel execution)
+    /// Apply a pushed-down filter to a chunk using SelectionVector (PrismDB-faithful)
     fn apply_filter_inline(
         chunk: DataChunk,
         filter_expr: &crate::expression::expression::ExpressionRef,
@@ -161,9 +215,20 @@ impl TableScanOperator {
             return Ok(chunk);
         }
 
-        let result_vector = filter_expr.evaluate(&chunk, context)?;
         let mut selection = SelectionVector::new(chunk.len());
 
+        // A bare boolean column, or its negation, selects straight from the column's bitmap
+        let boolean_column = Self::boolean_column_filter(filter_expr).and_then(|(column, value)| {
+            let vector = chunk.get_vector(column)?;
+            (*vector.get_type() == crate::types::LogicalType::Boolean).then_some((vector, value))
+        });
+        if let Some((vector, value)) = boolean_column {
+            vector.select_booleans(value, &mut selection);
+            return Self::select(chunk, &selection);
+        }
+
+        let result_vector = filter_expr.evaluate(&chunk, context)?;
+
         for i in 0..chunk.len() {
             let value = result_vector.get_value(i)?;
             let passes = match value {
@@ -181,13 +246,44 @@ impl TableScanOperator {
             }
         }
 
+        Self::select(chunk, &selection)
+    }
+
+    /// The rows of `chunk` that passed a filter
+    fn select(chunk: DataChunk, selection: &crate::types::SelectionVector) -> PrismDBResult<DataChunk> {
         if selection.count() == chunk.len() {
             return Ok(chunk);
         }
         if selection.is_empty() {
             return Ok(DataChunk::new());
         }
-        chunk.slice(&selection)
+        chunk.slice(selection)
+    }
+
+    /// Column index and wanted value of a filter that is a boolean column
+    /// (`WHERE active`) or its negation (`WHERE NOT active`)
+    fn boolean_column_filter(
+        filter_expr: &crate::expression::expression::ExpressionRef,
+    ) -> Option<(usize, bool)> {
+        use crate::expression::expression::{ColumnRefExpression, Expression, FunctionExpression};
+
+        let boolean_column = |expr: &dyn Expression| {
+            expr.as_any()
+                .downcast_ref::<ColumnRefExpression>()
+                .filter(|column| *column.return_type() == crate::types::LogicalType::Boolean)
+                .map(ColumnRefExpression::column_index)
+        };
+        if let Some(column) = boolean_column(filter_expr.as_ref()) {
+            return Some((column, true));
+        }
+        let not = filter_expr
+            .as_any()
+            .downcast_ref::<FunctionExpression>()
+            .filter(|function| function.function_name().eq_ignore_ascii_case("NOT"))?;
+        match not.children().as_slice() {
+            [child] => boolean_column(child.as_ref()).map(|column| (column, false)),
+            _ => None,
+        }
     }
 }
 
@@ -199,32 +295,31 @@ impl ExecutionOperator for TableScanOperator {
 
         // Get the table from the catalog
         let catalog = &self.context.catalog;
-        let catalog_guard = catalog.read().unwrap();
+        let catalog_guard = catalog.read_or_err()?;
 
-        // Get the default schema (typically "main")
-        let schema = catalog_guard.get_default_schema();
-        let schema_guard = schema.read().unwrap();
-
-        // Get the table
-        let table_arc = schema_guard.get_table(&self.scan.table_name).map_err(|_| {
-            PrismDBError::Catalog(format!("Table '{}' not found", self.scan.table_name))
+        // Get the table from the first schema on the search path that has it
+        let table_arc = catalog_guard.find_table(&self.scan.table_name).map_err(|_| {
+            PrismDBError::NotFound(ObjectKind::Table, format!("Table '{}' not found", self.scan.table_name))
         })?;
 
-        let table = table_arc.read().unwrap();
+        let table = table_arc.read_or_err()?;
+        self.context.record_table_read(&self.scan.table_name);
 
-        // Get the table's data storage
-        let table_data_arc = table.get_data();
-        let table_data = table_data_arc.read().unwrap();
+        // Get the table's data storage as the transaction sees it
+        let table_data_arc = self.context.table_data(table.get_data())?;
+        let table_data = table_data_arc.read_or_err()?;
 
-        let total_rows = table_data.row_count();
+        let total_rows = table_data.physical_row_count();
         let max_rows = self.scan.limit.unwrap_or(usize::MAX);
 
-        // PrismDB uses 2048 as the standard VECTOR_SIZE for chunk processing
-        const CHUNK_SIZE: usize = 2048;
+        // Chunks hold about the same number of bytes whatever the width of the rows
+        let chunk_size = adaptive_chunk_size(self.scan.schema.iter().map(|c| &c.data_type));
 
-        // Decide whether to use parallel execution
+        // Decide whether to use parallel execution; a pushed-down LIMIT is
+        // applied as the single-threaded scan goes
         let use_parallel = self.context.mode == ExecutionMode::Parallel
             && total_rows >= MORSEL_SIZE
+            && self.scan.limit.is_none()
             && self.context.parallel_context.parallel_enabled;
 
         if use_parallel {
@@ -234,11 +329,16 @@ impl ExecutionOperator for TableScanOperator {
             let context = self.context.clone();
 
             let chunks = parallel_table_scan(
-                std::cmp::min(total_rows, max_rows),
+                total_rows,
                 &self.context.parallel_context,
                 |morsel| {
-                    let table_data = table_data_clone.read().unwrap();
-                    let mut chunk = table_data.create_chunk(morsel.offset, morsel.count)?;
+                    let table_data = table_data_clone.read_or_err()?;
+                    let mut chunk = table_data.create_chunk_range(
+                        &context.read_view,
+                        morsel.offset,
+                        morsel.offset + morsel.count,
+                    )?;
+                    context.record_rows_scanned(chunk.len())?;
 
                     // Apply filters within parallel worker (inline implementation)
                     if !filters.is_empty() {
@@ -254,41 +354,21 @@ impl ExecutionOperator for TableScanOperator {
             Ok(Box::new(SimpleDataChunkStream::new(chunks)))
         } else {
             // SINGLE-THREADED EXECUTION PATH (for small tables or when parallel is disabled)
-            let mut chunks = Vec::new();
-            let mut offset = 0;
-            let mut rows_collected = 0;
-
-            while offset < total_rows && rows_collected < max_rows {
-                // Don't read more than needed if we have a limit
-                let chunk_size = std::cmp::min(
-                    std::cmp::min(CHUNK_SIZE, total_rows - offset),
-                    max_rows - rows_collected,
-                );
-
-                // Use TableData's create_chunk method which efficiently reads from column storage
-                let mut chunk = table_data.create_chunk(offset, chunk_size)?;
-
-                // Apply pushed-down filters (PrismDB-faithful filter pushdown optimization)
-                if !self.scan.filters.is_empty() {
-                    for filter_expr in &self.scan.filters {
-                        chunk = self.apply_filter_to_chunk(chunk, filter_expr)?;
-                    }
-                }
-
-                if chunk.len() > 0 {
-                    rows_collected += chunk.len();
-                    chunks.push(chunk);
-                }
-
-                offset += chunk_size;
-
-                // Early exit if we've collected enough rows (limit optimization)
-                if rows_collected >= max_rows {
-                    break;
-                }
-            }
-
-            Ok(Box::new(SimpleDataChunkStream::new(chunks)))
+            // Chunks are read as they are pulled, so a consumer that stops early reads no more.
+            // With column ranges, only zones whose min/max may satisfy the filters are read.
+            let rows = if self.scan.ranges.is_empty() {
+                ScanRows::All { offset: 0, total_rows }
+            } else {
+                ScanRows::Ranges(table_data.candidate_row_ranges(&self.scan.ranges)?.into())
+            };
+            Ok(Box::new(TableScanStream {
+                table_data: table_data_arc.clone(),
+                rows,
+                filters: self.scan.filters.clone(),
+                context: self.context.clone(),
+                chunk_size,
+                remaining: max_rows,
+            }))
         }
     }
 
@@ -297,96 +377,125 @@ impl ExecutionOperator for TableScanOperator {
     }
 }
 
-/// Filter operator (PrismDB-faithful implementation)
-/// Uses SelectionVector for zero-copy filtering
-pub struct FilterOperator {
-    filter: PhysicalFilter,
+/// COUNT(*) operator that reads a table's row count instead of scanning it
+pub struct CountStarOperator {
+    count: PhysicalCountStar,
     context: ExecutionContext,
 }
 
-impl FilterOperator {
-    pub fn new(filter: PhysicalFilter, context: ExecutionContext) -> Self {
-        Self { filter, context }
+impl CountStarOperator {
+    pub fn new(count: PhysicalCountStar, context: ExecutionContext) -> Self {
+        Self { count, context }
     }
+}
 
-    /// Apply filter to a single chunk using SelectionVector
-    /// This is the core PrismDB pattern for efficient filtering
-    fn apply_filter(&self, chunk: DataChunk) -> PrismDBResult<DataChunk> {
-        use crate::common::error::PrismDBError;
-        use crate::types::{SelectionVector, Value};
-
-        if chunk.len() == 0 {
-            return Ok(chunk);
+impl ExecutionOperator for CountStarOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        let catalog = self.context.catalog.read_or_err()?;
+        let table_arc = catalog.find_table(&self.count.table_name).map_err(|_| {
+            PrismDBError::NotFound(ObjectKind::Table, format!("Table '{}' not found", self.count.table_name))
+        })?;
+        self.context.record_table_read(&self.count.table_name);
+        let table_data = self.context.table_data(table_arc.read_or_err()?.get_data())?;
+        let row_count = table_data.read_or_err()?.visible_row_count(&self.context.read_view);
+
+        let count = Value::BigInt(row_count as i64);
+        let mut chunk = DataChunk::with_rows(1);
+        for (col_idx, column) in self.count.schema.iter().enumerate() {
+            let value = if count.get_type() == column.data_type {
+                count.clone()
+            } else {
+                count.cast_to(&column.data_type)?
+            };
+            chunk.set_vector(col_idx, crate::types::Vector::from_values(&[value])?)?;
         }
+        Ok(Box::new(SimpleDataChunkStream::new(vec![chunk])))
+    }
 
-        // Evaluate the filter predicate on this chunk
-        // Returns a boolean vector indicating which rows pass
-        let result_vector = self.filter.predicate.evaluate(&chunk, &self.context)?;
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.count.schema.clone()
+    }
+}
 
-        // Build SelectionVector with indices of rows that pass the filter
-        let mut selection = SelectionVector::new(chunk.len());
+/// EXPLAIN operator that renders its input plan as a single text or JSON row
+///
+/// EXPLAIN ANALYZE also runs the plan and reports each blocking operator's peak memory.
+pub struct ExplainOperator {
+    explain: PhysicalExplain,
+    context: ExecutionContext,
+}
 
-        for i in 0..chunk.len() {
-            let value = result_vector.get_value(i)?;
+impl ExplainOperator {
+    pub fn new(explain: PhysicalExplain, context: ExecutionContext) -> Self {
+        Self { explain, context }
+    }
 
-            // Check if this row passes the filter
-            let passes = match value {
-                Value::Boolean(b) => b,
-                Value::Null => false, // NULL in filter evaluates to false
-                _ => {
-                    return Err(PrismDBError::Execution(format!(
-                        "Filter predicate must return boolean, got {:?}",
-                        value
-                    )));
-                }
-            };
+    /// Current row count of a table on the search path, for estimates
+    fn table_rows(&self, table_name: &str) -> Option<usize> {
+        let catalog = self.context.catalog.read_or_err().ok()?;
+        let table_arc = catalog.find_table(table_name).ok()?;
+        let table_data = self.context.table_data(table_arc.read_or_err().ok()?.get_data()).ok()?;
+        let row_count = table_data.read_or_err().ok()?.row_count();
+        Some(row_count)
+    }
+}
 
-            if passes {
-                selection.append(i);
+impl ExecutionOperator for ExplainOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        let mut node = ExplainNode::from_plan(&self.explain.input, self.explain.verbose, &|table| {
+            self.table_rows(table)
+        });
+        if self.explain.analyze {
+            // Run the plan, discarding its rows, to measure what it buffers
+            let mut engine = crate::execution::ExecutionEngine::new(self.context.clone());
+            for chunk in engine.execute((*self.explain.input).clone())? {
+                chunk?;
             }
+            node = node.with_memory(&self.context.memory.operator_peaks());
         }
+        let plan = match self.explain.format {
+            ExplainFormat::Text => node.to_text(),
+            ExplainFormat::Json => serde_json::to_string_pretty(&node.to_json())
+                .map_err(|e| PrismDBError::Execution(format!("Failed to render plan: {}", e)))?,
+        };
 
-        // Optimization: If all rows pass, return original chunk unchanged
-        if selection.count() == chunk.len() {
-            return Ok(chunk);
-        }
+        let mut chunk = DataChunk::with_rows(1);
+        chunk.set_vector(0, crate::types::Vector::from_values(&[Value::Varchar(plan)])?)?;
+        Ok(Box::new(SimpleDataChunkStream::new(vec![chunk])))
+    }
 
-        // Optimization: If no rows pass, return empty chunk
-        if selection.is_empty() {
-            return Ok(DataChunk::new());
-        }
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        vec![PhysicalColumn::new("plan".to_string(), crate::types::LogicalType::Varchar)]
+    }
+}
 
-        // Apply selection vector to create filtered chunk
-        // This is zero-copy - we're just marking which rows to include
-        chunk.slice(&selection)
+/// Filter operator (PrismDB-faithful implementation)
+/// Uses SelectionVector for zero-copy filtering
+pub struct FilterOperator {
+    filter: PhysicalFilter,
+    input: Box<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
+
+impl FilterOperator {
+    pub fn new(
+        filter: PhysicalFilter,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { filter, input, context }
     }
 }
 
 impl ExecutionOperator for FilterOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
-
-        // Execute the input operator to get source data
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.filter.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
-
-        // Filter each chunk as it comes from input
-        let mut filtered_chunks = Vec::new();
-
-        while let Some(chunk_result) = input_stream.next() {
-            let chunk = chunk_result?;
-
-            // Apply filter to this chunk
-            let filtered_chunk = self.apply_filter(chunk)?;
-
-            // Only include non-empty chunks
-            if filtered_chunk.len() > 0 {
-                filtered_chunks.push(filtered_chunk);
-            }
-        }
-
-        Ok(Box::new(SimpleDataChunkStream::new(filtered_chunks)))
+        // Filter each chunk as it comes from input, using SelectionVector
+        let predicate = self.filter.predicate.clone();
+        let context = self.context.clone();
+        Ok(Box::new(MapChunkStream {
+            input: self.input.execute()?,
+            map: move |chunk| TableScanOperator::apply_filter_inline(chunk, &predicate, &context),
+        }))
     }
 
     fn schema(&self) -> Vec<PhysicalColumn> {
@@ -399,12 +508,17 @@ impl ExecutionOperator for FilterOperator {
 /// Very similar to Filter operator, but semantically operates after window computation
 pub struct QualifyOperator {
     qualify: PhysicalQualify,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl QualifyOperator {
-    pub fn new(qualify: PhysicalQualify, context: ExecutionContext) -> Self {
-        Self { qualify, context }
+    pub fn new(
+        qualify: PhysicalQualify,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { qualify, input, context }
     }
 
     /// Apply QUALIFY filter to a single chunk using SelectionVector
@@ -461,12 +575,8 @@ impl QualifyOperator {
 
 impl ExecutionOperator for QualifyOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
-
         // Execute the input operator to get source data (with window functions computed)
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.qualify.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
 
         // Filter each chunk as it comes from input
         let mut filtered_chunks = Vec::new();
@@ -491,88 +601,274 @@ impl ExecutionOperator for QualifyOperator {
     }
 }
 
-/// Projection operator (PrismDB-faithful implementation)
-/// Projects columns from the input stream
-pub struct ProjectionOperator {
-    projection: PhysicalProjection,
+/// Window operator - computes window functions over all of its input
+///
+/// Input chunks pass through unchanged with one column per window function
+/// appended. Each function sorts the row positions by its partition and
+/// order keys, then evaluates the partitions one at a time.
+pub struct WindowOperator {
+    window: PhysicalWindow,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
-impl ProjectionOperator {
-    pub fn new(projection: PhysicalProjection, context: ExecutionContext) -> Self {
-        Self {
-            projection,
-            context,
+impl WindowOperator {
+    pub fn new(
+        window: PhysicalWindow,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { window, input, context }
+    }
+
+    /// Evaluate `expressions` on every chunk, one value list per row
+    fn evaluate_rows(
+        &self,
+        chunks: &[DataChunk],
+        expressions: &[crate::expression::expression::ExpressionRef],
+    ) -> PrismDBResult<Vec<Vec<Value>>> {
+        let mut rows = Vec::new();
+        for chunk in chunks {
+            let vectors = expressions
+                .iter()
+                .map(|expr| expr.evaluate(chunk, &self.context))
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            for row_idx in 0..chunk.len() {
+                rows.push(
+                    vectors
+                        .iter()
+                        .map(|vector| vector.get_value(row_idx))
+                        .collect::<PrismDBResult<_>>()?,
+                );
+            }
         }
+        Ok(rows)
     }
-}
 
-impl ExecutionOperator for ProjectionOperator {
-    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
+    /// Values of one window function for every input row, in input order
+    fn compute(&self, function: &PhysicalWindowFunction, chunks: &[DataChunk]) -> PrismDBResult<Vec<Value>> {
+        use crate::expression::window_functions as window;
+        use crate::parser::ast::{WindowFrame, WindowFrameBound, WindowFrameUnits};
+        use std::cmp::Ordering;
 
-        // Execute the input operator to get source data
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.projection.input).clone();
+        let partition_keys = self.evaluate_rows(chunks, &function.partition_by)?;
+        let order_expressions: Vec<_> = function.order_by.iter().map(|key| key.expression.clone()).collect();
+        let order_keys = self.evaluate_rows(chunks, &order_expressions)?;
+        let mut arguments = self.evaluate_rows(chunks, &function.arguments)?;
+        if function.arguments.is_empty() {
+            // COUNT(*): every row counts
+            arguments = vec![vec![Value::Boolean(true)]; partition_keys.len()];
+        }
 
-        let mut input_stream = engine.execute(input_plan)?;
+        let compare_order = |a: usize, b: usize| {
+            function
+                .order_by
+                .iter()
+                .zip(order_keys[a].iter().zip(&order_keys[b]))
+                .map(|(key, (a, b))| compare_sort_values(a, b, key))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        };
+        let compare_partition = |a: usize, b: usize| {
+            partition_keys[a]
+                .iter()
+                .zip(&partition_keys[b])
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        };
 
-        // Project each chunk as it comes from input
-        let mut projected_chunks = Vec::new();
+        // Stable, so rows that tie keep their input order
+        let mut positions: Vec<usize> = (0..partition_keys.len()).collect();
+        positions.sort_by(|&a, &b| compare_partition(a, b).then_with(|| compare_order(a, b)));
+
+        // Without ORDER BY every row is a peer of every other, so the frame is the whole partition
+        let frame = match (&function.frame, function.order_by.is_empty()) {
+            (None, true) => Some(WindowFrame {
+                units: WindowFrameUnits::Rows,
+                start_bound: WindowFrameBound::UnboundedPreceding,
+                end_bound: Some(WindowFrameBound::UnboundedFollowing),
+                exclude: Default::default(),
+            }),
+            (frame, _) => frame.clone(),
+        };
 
-        while let Some(chunk_result) = input_stream.next() {
-            let chunk = chunk_result?;
+        let mut results = vec![Value::Null; positions.len()];
+        let mut start = 0;
+        while start < positions.len() {
+            let end = positions[start..]
+                .iter()
+                .position(|&row| compare_partition(positions[start], row).is_ne())
+                .map_or(positions.len(), |len| start + len);
+            let partition = &positions[start..end];
 
-            if chunk.len() == 0 {
-                continue;
+            // Arguments, then a peer group number that ranks compare on
+            let mut peer_group = 0i64;
+            let data: Vec<Vec<Value>> = partition
+                .iter()
+                .enumerate()
+                .map(|(i, &row)| {
+                    if i > 0 && compare_order(partition[i - 1], row).is_ne() {
+                        peer_group += 1;
+                    }
+                    let mut values = arguments[row].clone();
+                    values.push(Value::BigInt(peer_group));
+                    values
+                })
+                .collect();
+            let peer_col = arguments[partition[0]].len();
+            let argument = |index: usize| data[0].get(index).filter(|_| index < peer_col);
+
+            let values = match function.name.as_str() {
+                "ROW_NUMBER" => window::row_number(&data)?,
+                "RANK" => window::rank(&data, peer_col)?,
+                "DENSE_RANK" => window::dense_rank(&data, peer_col)?,
+                "PERCENT_RANK" => window::percent_rank(&data, peer_col)?,
+                "CUME_DIST" => window::cume_dist(&data, peer_col)?,
+                "NTILE" => window::ntile(&data, window_integer(argument(0), "NTILE")?)?,
+                "LAG" | "LEAD" => {
+                    let offset = argument(1).map(|v| window_integer(Some(v), &function.name)).transpose()?;
+                    let default = argument(2).cloned();
+                    if function.name == "LAG" {
+                        window::lag(&data, 0, offset, default)?
+                    } else {
+                        window::lead(&data, 0, offset, default)?
+                    }
+                }
+                "FIRST_VALUE" => window::first_value(&data, 0, &frame, Some(peer_col))?,
+                "LAST_VALUE" => window::last_value(&data, 0, &frame, Some(peer_col))?,
+                "NTH_VALUE" => window::nth_value(
+                    &data,
+                    0,
+                    window_integer(argument(1), "NTH_VALUE")?,
+                    &frame,
+                    Some(peer_col),
+                )?,
+                "SUM" => window::sum_window(&data, 0, &frame, Some(peer_col))?,
+                "AVG" => window::avg_window(&data, 0, &frame, Some(peer_col))?,
+                "COUNT" => window::count_window(&data, 0, &frame, Some(peer_col))?,
+                "MIN" => window::min_window(&data, 0, &frame, Some(peer_col))?,
+                "MAX" => window::max_window(&data, 0, &frame, Some(peer_col))?,
+                name => {
+                    return Err(PrismDBError::Execution(format!(
+                        "Unsupported window function: {}",
+                        name
+                    )))
+                }
+            };
+            for (&row, value) in partition.iter().zip(values) {
+                results[row] = value;
             }
+            start = end;
+        }
+        Ok(results)
+    }
+}
 
-            // Create a result chunk with projected columns
-            let mut result_chunk = DataChunk::with_rows(chunk.len());
-
-            for (i, expression) in self.projection.expressions.iter().enumerate() {
-                // Evaluate the expression on the input chunk
-                let result_vector = expression.evaluate(&chunk, &self.context)?;
+/// Integer argument of a window function, such as NTILE's bucket count
+fn window_integer(value: Option<&Value>, function: &str) -> PrismDBResult<i64> {
+    match value.map(|v| v.cast_to(&crate::types::LogicalType::BigInt)).transpose()? {
+        Some(Value::BigInt(n)) => Ok(n),
+        _ => Err(PrismDBError::Execution(format!(
+            "{} requires a constant integer argument",
+            function
+        ))),
+    }
+}
 
-                result_chunk.set_vector(i, result_vector)?;
+impl ExecutionOperator for WindowOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        let mut chunks = Vec::new();
+        for chunk_result in self.input.execute()? {
+            let chunk = chunk_result?;
+            if !chunk.is_empty() {
+                chunks.push(chunk);
             }
+        }
 
-            if result_chunk.len() > 0 {
-                projected_chunks.push(result_chunk);
+        let input_columns = self.window.schema.len() - self.window.functions.len();
+        for (offset, function) in self.window.functions.iter().enumerate() {
+            let values = self.compute(function, &chunks)?;
+            let mut start = 0;
+            for chunk in &mut chunks {
+                let end = start + chunk.len();
+                let vector = crate::types::Vector::from_values_with_type(&values[start..end], &function.return_type)?;
+                chunk.set_vector(input_columns + offset, vector)?;
+                start = end;
             }
         }
 
-        Ok(Box::new(SimpleDataChunkStream::new(projected_chunks)))
+        Ok(Box::new(SimpleDataChunkStream::new(chunks)))
     }
 
     fn schema(&self) -> Vec<PhysicalColumn> {
-        self.projection.schema.clone()
+        self.window.schema.clone()
     }
 }
 
-/// Limit operator
-pub struct LimitOperator {
-    limit: PhysicalLimit,
+/// Projection operator (PrismDB-faithful implementation)
+/// Projects columns from the input stream
+pub struct ProjectionOperator {
+    projection: PhysicalProjection,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
-impl LimitOperator {
-    pub fn new(limit: PhysicalLimit, context: ExecutionContext) -> Self {
+impl ProjectionOperator {
+    pub fn new(
+        projection: PhysicalProjection,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
         Self {
-            limit,
+            projection,
+            input,
             context,
         }
     }
 }
 
-impl ExecutionOperator for LimitOperator {
+impl ExecutionOperator for ProjectionOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
+        // Project each chunk as it comes from input
+        let expressions = self.projection.expressions.clone();
+        let context = self.context.clone();
+        Ok(Box::new(MapChunkStream {
+            input: self.input.execute()?,
+            map: move |chunk: DataChunk| {
+                if chunk.is_empty() {
+                    return Ok(chunk);
+                }
+                let mut result_chunk = DataChunk::with_rows(chunk.len());
+                for (i, expression) in expressions.iter().enumerate() {
+                    result_chunk.set_vector(i, expression.evaluate(&chunk, &context)?)?;
+                }
+                Ok(result_chunk)
+            },
+        }))
+    }
+
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.projection.schema.clone()
+    }
+}
+
+/// Limit operator
+pub struct LimitOperator {
+    limit: PhysicalLimit,
+    input: Box<dyn ExecutionOperator>,
+}
+
+impl LimitOperator {
+    pub fn new(limit: PhysicalLimit, input: Box<dyn ExecutionOperator>) -> Self {
+        Self { limit, input }
+    }
+}
 
+impl ExecutionOperator for LimitOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
         // Execute the input plan
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.limit.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
 
         // Collect rows up to the limit
         let limit = self.limit.limit;
@@ -652,27 +948,94 @@ impl ExecutionOperator for LimitOperator {
     }
 }
 
+/// Distinct operator - keeps the first of each set of equal rows, in input order
+pub struct DistinctOperator {
+    distinct: PhysicalDistinct,
+    input: Box<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
+
+impl DistinctOperator {
+    pub fn new(distinct: PhysicalDistinct, input: Box<dyn ExecutionOperator>, context: ExecutionContext) -> Self {
+        Self {
+            distinct,
+            input,
+            context,
+        }
+    }
+}
+
+impl ExecutionOperator for DistinctOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        use std::collections::HashSet;
+
+        let reservation = self.context.memory_reservation("DISTINCT");
+        let input_stream = self.input.execute()?;
+        let mut seen_rows: HashSet<Vec<OrderedValue>> = HashSet::new();
+        let mut result_chunks = Vec::new();
+
+        for chunk_result in input_stream {
+            let chunk = chunk_result?;
+            let mut unique_rows = Vec::new();
+
+            for row_idx in 0..chunk.len() {
+                let mut row_values = Vec::with_capacity(chunk.column_count());
+                for col_idx in 0..chunk.column_count() {
+                    let vector = chunk.get_vector(col_idx)
+                        .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
+                    row_values.push(vector.get_value(row_idx)?);
+                }
+
+                // Rows are keyed by canonical value, so nested values that are
+                // deeply equal count as duplicates
+                let collation = &self.context.grouping_collation;
+                let key_values: Vec<Value> =
+                    row_values.iter().map(|value| collation.group_key(value).into_owned()).collect();
+                let row_key = OrderedValue::row(&key_values);
+                if !seen_rows.contains(&row_key) {
+                    reservation.grow(key_memory_usage(&row_key))?;
+                    seen_rows.insert(row_key);
+                    unique_rows.push(row_values);
+                }
+            }
+
+            if unique_rows.is_empty() {
+                continue;
+            }
+            let mut result_chunk = DataChunk::with_rows(unique_rows.len());
+            for col_idx in 0..chunk.column_count() {
+                let column_values: Vec<Value> = unique_rows.iter().map(|row| row[col_idx].clone()).collect();
+                result_chunk.set_vector(col_idx, crate::types::Vector::from_values(&column_values)?)?;
+            }
+            result_chunks.push(result_chunk);
+        }
+
+        Ok(Box::new(SimpleDataChunkStream::new(result_chunks)))
+    }
+
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.distinct.input.schema()
+    }
+}
+
 /// Sort operator (in-memory sorting)
 pub struct SortOperator {
     sort: PhysicalSort,
-    context: ExecutionContext,
+    input: Box<dyn ExecutionOperator>,
 }
 
 impl SortOperator {
-    pub fn new(sort: PhysicalSort, context: ExecutionContext) -> Self {
-        Self { sort, context }
+    pub fn new(sort: PhysicalSort, input: Box<dyn ExecutionOperator>) -> Self {
+        Self { sort, input }
     }
 }
 
 impl ExecutionOperator for SortOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
         use crate::common::error::PrismDBError;
-        use crate::execution::ExecutionEngine;
 
         // Execute the input plan and collect all rows
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.sort.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
 
         // Collect all rows from input
         let mut all_rows: Vec<Vec<Value>> = Vec::new();
@@ -766,41 +1129,7 @@ impl SortOperator {
             let val_a = &a[column_idx];
             let val_b = &b[column_idx];
 
-            // Handle NULL ordering
-            let cmp_result = match (val_a, val_b) {
-                (Value::Null, Value::Null) => Ordering::Equal,
-                (Value::Null, _) => {
-                    if sort_expr.nulls_first {
-                        Ordering::Less
-                    } else {
-                        Ordering::Greater
-                    }
-                }
-                (_, Value::Null) => {
-                    if sort_expr.nulls_first {
-                        Ordering::Greater
-                    } else {
-                        Ordering::Less
-                    }
-                }
-                _ => {
-                    // Compare values
-                    let cmp_i32 = AggregateState::compare_values(val_a, val_b)?;
-                    match cmp_i32 {
-                        -1 => Ordering::Less,
-                        0 => Ordering::Equal,
-                        1 => Ordering::Greater,
-                        _ => Ordering::Equal,
-                    }
-                }
-            };
-
-            // Apply ascending/descending
-            let final_cmp = if sort_expr.ascending {
-                cmp_result
-            } else {
-                cmp_result.reverse()
-            };
+            let final_cmp = compare_sort_values(val_a, val_b, sort_expr);
 
             if final_cmp != Ordering::Equal {
                 return Ok(final_cmp);
@@ -811,114 +1140,89 @@ impl SortOperator {
     }
 }
 
+/// Order two values of a sort key
+///
+/// NULL placement is absolute; ASC/DESC only applies to non-NULL values.
+fn compare_sort_values(a: &Value, b: &Value, key: &PhysicalSortExpression) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => {
+            if key.nulls_first {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        }
+        (_, Value::Null) => {
+            if key.nulls_first {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            }
+        }
+        _ => {
+            // Compare values, strings by the key's collation if it has one
+            let collated = key
+                .collation
+                .as_ref()
+                .and_then(|collation| collation.compare_values(a, b));
+            let cmp_result = collated.unwrap_or_else(|| a.total_cmp(b));
+
+            if key.ascending {
+                cmp_result
+            } else {
+                cmp_result.reverse()
+            }
+        }
+    }
+}
+
 /// Aggregate operator (hash-based aggregation)
 pub struct AggregateOperator {
     aggregate: PhysicalAggregate,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl AggregateOperator {
-    pub fn new(aggregate: PhysicalAggregate, context: ExecutionContext) -> Self {
-        Self { aggregate, context }
-    }
-
-    /// Parse a string value back to the correct Value type based on schema
-    fn parse_value_from_string(&self, s: &str, logical_type: &crate::types::LogicalType) -> PrismDBResult<Value> {
-        use crate::types::LogicalType;
-
-        // Handle NULL special case
-        if s == "NULL" {
-            return Ok(Value::Null);
-        }
-
-        match logical_type {
-            LogicalType::Boolean => {
-                s.parse::<bool>()
-                    .map(Value::Boolean)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as BOOLEAN", s)))
-            }
-            LogicalType::TinyInt => {
-                s.parse::<i8>()
-                    .map(Value::TinyInt)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as TINYINT", s)))
-            }
-            LogicalType::SmallInt => {
-                s.parse::<i16>()
-                    .map(Value::SmallInt)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as SMALLINT", s)))
-            }
-            LogicalType::Integer => {
-                s.parse::<i32>()
-                    .map(Value::Integer)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as INTEGER", s)))
-            }
-            LogicalType::BigInt => {
-                s.parse::<i64>()
-                    .map(Value::BigInt)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as BIGINT", s)))
-            }
-            LogicalType::Float => {
-                s.parse::<f32>()
-                    .map(Value::Float)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as FLOAT", s)))
-            }
-            LogicalType::Double => {
-                s.parse::<f64>()
-                    .map(Value::Double)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as DOUBLE", s)))
-            }
-            LogicalType::Varchar => Ok(Value::Varchar(s.to_string())),
-            LogicalType::Date => {
-                // Parse date string (assuming format YYYY-MM-DD)
-                Ok(Value::Varchar(s.to_string())) // TODO: proper date parsing
-            }
-            LogicalType::Timestamp => {
-                // Parse timestamp string
-                Ok(Value::Varchar(s.to_string())) // TODO: proper timestamp parsing
-            }
-            _ => Ok(Value::Varchar(s.to_string())),
-        }
+    pub fn new(
+        aggregate: PhysicalAggregate,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { aggregate, input, context }
     }
 }
 
 impl ExecutionOperator for AggregateOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
         use crate::types::Value;
         use std::collections::HashMap;
 
         // Execute the input plan
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.aggregate.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
 
         // Hash table: group_key -> aggregate_states
-        // group_key is a string representation of the GROUP BY column values
+        // group_key holds the GROUP BY column values (empty without GROUP BY)
         // aggregate_states is a Vec of AggregateState (one per aggregate expression)
-        let mut hash_table: HashMap<String, Vec<AggregateState>> = HashMap::new();
+        let mut hash_table: HashMap<Vec<OrderedValue>, Vec<AggregateState>> = HashMap::new();
 
         // Process all input chunks
         while let Some(chunk_result) = input_stream.next() {
             let chunk = chunk_result?;
 
             for row_idx in 0..chunk.len() {
-                // Extract group key from GROUP BY columns
-                let group_key = if self.aggregate.group_by.is_empty() {
-                    // No GROUP BY - single group for the entire dataset
-                    String::from("__global__")
-                } else {
-                    // Evaluate GROUP BY expressions and create composite key
-                    let mut key_parts = Vec::new();
-                    for group_expr in &self.aggregate.group_by {
-                        let result_vector = group_expr.evaluate(&chunk, &self.context)?;
-                        let value = result_vector.get_value(row_idx)?;
-                        key_parts.push(value_to_key_string(&value));
-                    }
-                    key_parts.join("|")
-                };
+                // Evaluate GROUP BY expressions into the group key
+                let mut group_key = Vec::with_capacity(self.aggregate.group_by.len());
+                for group_expr in &self.aggregate.group_by {
+                    let result_vector = group_expr.evaluate(&chunk, &self.context)?;
+                    group_key.push(OrderedValue(result_vector.get_value(row_idx)?));
+                }
 
                 // Get or create aggregate states for this group
-                let states = hash_table.entry(group_key.clone()).or_insert_with(|| {
+                let states = hash_table.entry(group_key).or_insert_with(|| {
                     self.aggregate
                         .aggregates
                         .iter()
@@ -928,6 +1232,14 @@ impl ExecutionOperator for AggregateOperator {
 
                 // Update each aggregate state with this row's values
                 for (agg_idx, agg_expr) in self.aggregate.aggregates.iter().enumerate() {
+                    // Skip rows the aggregate's FILTER rejects
+                    if let Some(filter) = &agg_expr.filter {
+                        let passes = filter.evaluate(&chunk, &self.context)?.get_value(row_idx)?;
+                        if passes != Value::Boolean(true) {
+                            continue;
+                        }
+                    }
+
                     // Evaluate the aggregate's argument expression
                     let arg_value = if agg_expr.arguments.is_empty() {
                         // COUNT(*) - no arguments
@@ -970,24 +1282,11 @@ impl ExecutionOperator for AggregateOperator {
         let mut result_chunk = DataChunk::with_rows(num_groups);
 
         // Build columns for GROUP BY expressions
-        for (group_col_idx, _group_expr) in self.aggregate.group_by.iter().enumerate() {
-            let mut group_values = Vec::new();
-
-            // Get the correct type from schema
-            let expected_type = &self.aggregate.schema[group_col_idx].data_type;
-
-            for group_key in hash_table.keys() {
-                // Parse the group key back to values
-                let key_parts: Vec<&str> = group_key.split('|').collect();
-                if group_col_idx < key_parts.len() {
-                    // Parse the value back to the correct type based on schema
-                    let value = self.parse_value_from_string(key_parts[group_col_idx], expected_type)?;
-                    group_values.push(value);
-                } else {
-                    group_values.push(Value::Null);
-                }
-            }
-
+        for group_col_idx in 0..self.aggregate.group_by.len() {
+            let group_values: Vec<Value> = hash_table
+                .keys()
+                .map(|group_key| group_key[group_col_idx].0.clone())
+                .collect();
             let vector = crate::types::Vector::from_values(&group_values)?;
             result_chunk.set_vector(group_col_idx, vector)?;
         }
@@ -1014,6 +1313,154 @@ impl ExecutionOperator for AggregateOperator {
     }
 }
 
+/// Streaming aggregate operator
+///
+/// Aggregates an input sorted on the group keys in a single pass. Only the group
+/// still receiving rows holds aggregate states; it is emitted once a row with other
+/// keys arrives, so memory does not grow with the number of groups.
+pub struct StreamingAggregateOperator {
+    aggregate: PhysicalStreamingAggregate,
+    input: Box<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
+
+impl StreamingAggregateOperator {
+    pub fn new(
+        aggregate: PhysicalStreamingAggregate,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { aggregate, input, context }
+    }
+}
+
+impl ExecutionOperator for StreamingAggregateOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        Ok(Box::new(StreamingAggregateStream {
+            input: Some(self.input.execute()?),
+            group_by: self.aggregate.group_by.clone(),
+            aggregates: self.aggregate.aggregates.clone(),
+            schema: self.aggregate.schema.clone(),
+            context: self.context.clone(),
+            current: None,
+        }))
+    }
+
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.aggregate.schema.clone()
+    }
+}
+
+/// A group's values and its aggregate states, one per aggregate
+type StreamingGroup = (Vec<Value>, Vec<Box<dyn crate::expression::AggregateState>>);
+
+/// Groups of a sorted input, each emitted in the chunk that ends it
+struct StreamingAggregateStream {
+    /// The input, until it is exhausted
+    input: Option<Box<dyn DataChunkStream>>,
+    group_by: Vec<crate::expression::expression::ExpressionRef>,
+    aggregates: Vec<crate::planner::PhysicalAggregateExpression>,
+    schema: Vec<PhysicalColumn>,
+    context: ExecutionContext,
+    /// The group still receiving rows
+    current: Option<StreamingGroup>,
+}
+
+impl StreamingAggregateStream {
+    /// Add a chunk's rows to their groups, returning the groups the chunk ended
+    fn aggregate_chunk(&mut self, chunk: &DataChunk) -> PrismDBResult<Vec<StreamingGroup>> {
+        use crate::execution::AggregateInputs;
+
+        let group_vectors = self
+            .group_by
+            .iter()
+            .map(|expr| expr.evaluate(chunk, &self.context))
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        let inputs = AggregateInputs::evaluate(chunk, &self.aggregates, &self.context)?;
+
+        let mut finished = Vec::new();
+        for row_idx in 0..chunk.len() {
+            let values = group_vectors
+                .iter()
+                .map(|vector| vector.get_value(row_idx))
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            let same_group = matches!(&self.current, Some((current, _))
+                if current.iter().zip(&values).all(|(a, b)| a.total_cmp(b).is_eq()));
+            if !same_group {
+                let states = self
+                    .aggregates
+                    .iter()
+                    .map(|agg_expr| agg_expr.create_state(&self.context.grouping_collation))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                finished.extend(self.current.replace((values, states)));
+            }
+            if let Some((_, states)) = &mut self.current {
+                inputs.update(states, row_idx)?;
+            }
+        }
+        Ok(finished)
+    }
+
+    /// One row per finished group: its group values, then each aggregate's result
+    fn result_chunk(&self, groups: Vec<StreamingGroup>) -> PrismDBResult<DataChunk> {
+        let mut result_chunk = DataChunk::with_rows(groups.len());
+        for group_col_idx in 0..self.group_by.len() {
+            let expected_type = &self.schema[group_col_idx].data_type;
+            let group_values = groups
+                .iter()
+                .map(|(values, _)| {
+                    let value = &values[group_col_idx];
+                    if value.is_null() || value.get_type() == *expected_type {
+                        Ok(value.clone())
+                    } else {
+                        value.cast_to(expected_type)
+                    }
+                })
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            result_chunk.set_vector(group_col_idx, crate::types::Vector::from_values(&group_values)?)?;
+        }
+        for agg_idx in 0..self.aggregates.len() {
+            let agg_values = groups
+                .iter()
+                .map(|(_, states)| states[agg_idx].finalize())
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            result_chunk.set_vector(
+                self.group_by.len() + agg_idx,
+                crate::types::Vector::from_values(&agg_values)?,
+            )?;
+        }
+        Ok(result_chunk)
+    }
+}
+
+impl Iterator for StreamingAggregateStream {
+    type Item = PrismDBResult<DataChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let finished = match self.input.as_mut()?.next() {
+                Some(Ok(chunk)) => self.aggregate_chunk(&chunk),
+                Some(Err(e)) => Err(e),
+                None => {
+                    // The last group ends with the input
+                    self.input = None;
+                    Ok(self.current.take().into_iter().collect())
+                }
+            };
+            match finished {
+                Ok(groups) if groups.is_empty() => continue,
+                Ok(groups) => return Some(self.result_chunk(groups)),
+                Err(e) => {
+                    self.input = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl DataChunkStream for StreamingAggregateStream {}
+
 /// State for a single aggregate function (COUNT, SUM, AVG, etc.)
 #[derive(Debug, Clone)]
 struct AggregateState {
@@ -1066,16 +1513,12 @@ impl AggregateState {
                 self.sum += numeric_value;
             }
             "MIN" => {
-                if self.min.is_none()
-                    || Self::compare_values(&value, self.min.as_ref().unwrap())? < 0
-                {
+                if self.min.as_ref().is_none_or(|min| value.total_cmp(min).is_lt()) {
                     self.min = Some(value);
                 }
             }
             "MAX" => {
-                if self.max.is_none()
-                    || Self::compare_values(&value, self.max.as_ref().unwrap())? > 0
-                {
+                if self.max.as_ref().is_none_or(|max| value.total_cmp(max).is_gt()) {
                     self.max = Some(value);
                 }
             }
@@ -1117,247 +1560,504 @@ impl AggregateState {
             ))),
         }
     }
+}
 
-    /// Compare two values, returning -1 if a < b, 0 if a == b, 1 if a > b
-    fn compare_values(a: &Value, b: &Value) -> PrismDBResult<i32> {
-        match (a, b) {
-            (Value::Null, Value::Null) => Ok(0),
-            (Value::Null, _) => Ok(-1),
-            (_, Value::Null) => Ok(1),
+/// Hash join operator
+///
+/// Single-threaded counterpart of `ParallelHashJoinOperator`. Build chunks are stored
+/// whole in a `ParallelHashTable`, which holds row references, and matched rows are
+/// gathered column by column into the output chunks.
+pub struct HashJoinOperator {
+    join: PhysicalHashJoin,
+    left: Box<dyn ExecutionOperator>,
+    right: Box<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
 
-            (Value::Integer(a), Value::Integer(b)) => Ok(match a.cmp(b) {
-                std::cmp::Ordering::Less => -1,
-                std::cmp::Ordering::Equal => 0,
-                std::cmp::Ordering::Greater => 1,
-            }),
-            (Value::BigInt(a), Value::BigInt(b)) => Ok(match a.cmp(b) {
-                std::cmp::Ordering::Less => -1,
-                std::cmp::Ordering::Equal => 0,
-                std::cmp::Ordering::Greater => 1,
-            }),
-            (Value::SmallInt(a), Value::SmallInt(b)) => Ok(match a.cmp(b) {
-                std::cmp::Ordering::Less => -1,
-                std::cmp::Ordering::Equal => 0,
-                std::cmp::Ordering::Greater => 1,
-            }),
-            (Value::TinyInt(a), Value::TinyInt(b)) => Ok(match a.cmp(b) {
-                std::cmp::Ordering::Less => -1,
-                std::cmp::Ordering::Equal => 0,
-                std::cmp::Ordering::Greater => 1,
-            }),
+impl HashJoinOperator {
+    pub fn new(
+        join: PhysicalHashJoin,
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self {
+            join,
+            left,
+            right,
+            context,
+        }
+    }
 
-            (Value::Float(a), Value::Float(b)) => {
-                if a < b {
-                    Ok(-1)
-                } else if a > b {
-                    Ok(1)
-                } else {
-                    Ok(0)
-                }
-            }
-            (Value::Double(a), Value::Double(b)) => {
-                if a < b {
-                    Ok(-1)
-                } else if a > b {
-                    Ok(1)
-                } else {
-                    Ok(0)
-                }
-            }
+    /// Column indices of the join keys, relative to one side's chunks
+    fn key_indices(keys: &[crate::expression::expression::ExpressionRef], offset: usize) -> Vec<usize> {
+        use crate::expression::expression::ColumnRefExpression;
+
+        keys.iter()
+            .filter_map(|expr| {
+                expr.as_any()
+                    .downcast_ref::<ColumnRefExpression>()
+                    .map(|col_ref| {
+                        let joined_idx = col_ref.column_index();
+                        joined_idx.checked_sub(offset).unwrap_or(joined_idx)
+                    })
+            })
+            .collect()
+    }
+}
 
-            (Value::Varchar(a), Value::Varchar(b)) => Ok(match a.cmp(b) {
-                std::cmp::Ordering::Less => -1,
-                std::cmp::Ordering::Equal => 0,
-                std::cmp::Ordering::Greater => 1,
-            }),
-            (Value::Boolean(a), Value::Boolean(b)) => Ok(match a.cmp(b) {
-                std::cmp::Ordering::Less => -1,
-                std::cmp::Ordering::Equal => 0,
-                std::cmp::Ordering::Greater => 1,
-            }),
+impl ExecutionOperator for HashJoinOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        use crate::execution::hash_table::ParallelHashTable;
+        use crate::planner::PhysicalJoinType;
 
-            // For mixed types, try to convert to f64
-            _ => {
-                let a_num = Self::value_to_f64(a)?;
-                let b_num = Self::value_to_f64(b)?;
-                if a_num < b_num {
-                    Ok(-1)
-                } else if a_num > b_num {
-                    Ok(1)
-                } else {
-                    Ok(0)
-                }
+        let left_col_count = self.join.left.schema().len();
+        let right_col_count = self.join.right.schema().len();
+
+        // Build chunks and joined output are held in memory against the query budget
+        let reservation = self.context.memory_reservation("HASH_JOIN");
+
+        // Build a hash table over the right side's chunks
+        let mut hash_table =
+            ParallelHashTable::new(Self::key_indices(&self.join.right_keys, left_col_count));
+        for chunk_result in self.right.execute()? {
+            let chunk = chunk_result?;
+            if !chunk.is_empty() {
+                reservation.grow(chunk.get_size())?;
+                hash_table.build_from_chunk(chunk)?;
             }
         }
-    }
 
-    fn value_to_f64(v: &Value) -> PrismDBResult<f64> {
-        use crate::common::error::PrismDBError;
+        // Probe with the left side
+        let left_key_indices = Self::key_indices(&self.join.left_keys, 0);
+        let build_columns = match self.join.join_type {
+            PhysicalJoinType::Semi | PhysicalJoinType::Anti => 0,
+            _ => right_col_count,
+        };
+        let chunk_size = adaptive_chunk_size(self.join.schema.iter().map(|c| &c.data_type));
+        let mut output = Vec::new();
+        let mut left_stream = self.left.execute()?;
+        while let Some(chunk_result) = left_stream.next() {
+            let chunk = chunk_result?;
+            let matches = hash_table.probe_chunk(&chunk, &left_key_indices, &self.join.join_type)?;
+            for output_chunk in hash_table.gather(&chunk, &matches, build_columns, chunk_size)? {
+                reservation.grow(output_chunk.get_size())?;
+                output.push(output_chunk);
+            }
+        }
 
-        match v {
-            Value::Integer(i) => Ok(*i as f64),
-            Value::BigInt(i) => Ok(*i as f64),
-            Value::SmallInt(i) => Ok(*i as f64),
-            Value::TinyInt(i) => Ok(*i as f64),
-            Value::Float(f) => Ok(*f as f64),
-            Value::Double(d) => Ok(*d),
-            _ => Err(PrismDBError::InvalidValue(format!(
-                "Cannot convert {:?} to f64 for comparison",
-                v
-            ))),
+        if output.is_empty() {
+            return Ok(Box::new(SimpleDataChunkStream::empty()));
         }
+        Ok(Box::new(SimpleDataChunkStream::new(output)))
     }
-}
 
-/// Hash join operator
-pub struct HashJoinOperator {
-    join: PhysicalHashJoin,
-    context: ExecutionContext,
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.join.schema.clone()
+    }
 }
 
-impl HashJoinOperator {
-    pub fn new(join: PhysicalHashJoin, context: ExecutionContext) -> Self {
-        Self { join, context }
+/// Split join result rows into chunks sized for the join's output columns
+fn join_rows_to_chunks(schema: &[PhysicalColumn], rows: Vec<Vec<Value>>) -> PrismDBResult<Vec<DataChunk>> {
+    let chunk_size = adaptive_chunk_size(schema.iter().map(|c| &c.data_type));
+    let mut chunks = Vec::new();
+    for batch in rows.chunks(chunk_size) {
+        let mut chunk = DataChunk::with_rows(batch.len());
+        for col_idx in 0..batch[0].len() {
+            let column_values: Vec<Value> =
+                batch.iter().map(|row| row[col_idx].clone()).collect();
+            chunk.set_vector(col_idx, crate::types::Vector::from_values(&column_values)?)?;
+        }
+        chunks.push(chunk);
     }
+    Ok(chunks)
 }
 
-impl ExecutionOperator for HashJoinOperator {
-    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::common::error::PrismDBError;
-        use crate::execution::ExecutionEngine;
-        use crate::types::Value;
-        use std::collections::HashMap;
-
-        // Execute both sides of the join
-        let mut left_engine = ExecutionEngine::new(self.context.clone());
-        let mut right_engine = ExecutionEngine::new(self.context.clone());
-
-        let left_plan = (*self.join.left).clone();
-        let right_plan = (*self.join.right).clone();
+/// Sort-merge join operator
+///
+/// Collects both inputs, sorts each on its join keys unless it already arrives in key
+/// order, then merges runs of equal keys. Output is emitted in vector-sized chunks.
+pub struct SortMergeJoinOperator {
+    join: PhysicalSortMergeJoin,
+    left: Box<dyn ExecutionOperator>,
+    right: Box<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
 
-        // Collect all data from the right (build) side
-        let mut right_data = Vec::new();
-        let mut right_stream = right_engine.execute(right_plan)?;
+impl SortMergeJoinOperator {
+    pub fn new(
+        join: PhysicalSortMergeJoin,
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self {
+            join,
+            left,
+            right,
+            context,
+        }
+    }
 
-        while let Some(chunk_result) = right_stream.next() {
+    /// Execute a child operator and materialize its rows, reserving their memory
+    fn collect_rows(
+        input: &dyn ExecutionOperator,
+        reservation: &MemoryReservation,
+    ) -> PrismDBResult<Vec<Vec<Value>>> {
+        let stream = input.execute()?;
+        let mut rows = Vec::new();
+        for chunk_result in stream {
             let chunk = chunk_result?;
-            // Store each row from the right side
             for row_idx in 0..chunk.len() {
-                let mut row_values = Vec::new();
+                let mut row = Vec::with_capacity(chunk.column_count());
                 for col_idx in 0..chunk.column_count() {
                     let vector = chunk.get_vector(col_idx).ok_or_else(|| {
                         PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
                     })?;
-                    let value = vector.get_value(row_idx)?;
-                    row_values.push(value);
+                    row.push(vector.get_value(row_idx)?);
                 }
-                right_data.push(row_values);
+                reservation.grow(row_memory_usage(&row))?;
+                rows.push(row);
             }
         }
+        Ok(rows)
+    }
 
-        // Build hash table from right side using actual join keys
-        let mut hash_table: HashMap<String, Vec<Vec<Value>>> = HashMap::new();
+    /// Resolve the join keys to (left, right) column indices within each input.
+    /// Keys are bound against the joined schema, and a condition written as
+    /// `right.col = left.col` has its sides swapped here.
+    fn key_indices(&self, left_col_count: usize) -> PrismDBResult<(Vec<usize>, Vec<usize>)> {
+        use crate::expression::expression::{ColumnRefExpression, ExpressionRef};
 
-        for right_row in &right_data {
-            if !right_row.is_empty() {
-                if self.join.right_keys.is_empty() {
-                    // Fallback to first column if no join keys
-                    let key = right_row[0].to_string();
-                    hash_table.entry(key).or_insert_with(Vec::new).push(right_row.clone());
-                    continue;
-                }
-                // Evaluate right join key(s) to build hash key
-                let mut key_parts = Vec::new();
-                for right_key_expr in &self.join.right_keys {
-                    // For column references, extract the column index and get the value
-                    if let Some(col_ref) = right_key_expr.as_any().downcast_ref::<crate::expression::ColumnRefExpression>() {
-                        let col_idx = col_ref.column_index();
-                        if col_idx < right_row.len() {
-                            key_parts.push(right_row[col_idx].to_string());
-                        }
-                    }
-                }
-                let key = key_parts.join("|");
-                hash_table
-                    .entry(key)
-                    .or_insert_with(Vec::new)
-                    .push(right_row.clone());
+        let column_index = |expr: &ExpressionRef| {
+            expr.as_any()
+                .downcast_ref::<ColumnRefExpression>()
+                .map(|col_ref| col_ref.column_index())
+                .ok_or_else(|| {
+                    PrismDBError::Execution(
+                        "Sort-merge join keys must be column references".to_string(),
+                    )
+                })
+        };
+
+        let mut left_indices = Vec::with_capacity(self.join.left_keys.len());
+        let mut right_indices = Vec::with_capacity(self.join.right_keys.len());
+        for (left_key, right_key) in self.join.left_keys.iter().zip(&self.join.right_keys) {
+            let (mut left_idx, mut right_idx) = (column_index(left_key)?, column_index(right_key)?);
+            if left_idx >= left_col_count && right_idx < left_col_count {
+                std::mem::swap(&mut left_idx, &mut right_idx);
+            }
+            if left_idx >= left_col_count || right_idx < left_col_count {
+                return Err(PrismDBError::Execution(
+                    "Sort-merge join keys must reference one column from each input".to_string(),
+                ));
+            }
+            left_indices.push(left_idx);
+            right_indices.push(right_idx - left_col_count);
+        }
+        Ok((left_indices, right_indices))
+    }
+
+    /// Compare two rows on their key columns
+    fn compare_keys(
+        left: &[Value],
+        left_keys: &[usize],
+        right: &[Value],
+        right_keys: &[usize],
+    ) -> PrismDBResult<std::cmp::Ordering> {
+        for (&l, &r) in left_keys.iter().zip(right_keys) {
+            let ordering = left[l].compare(&right[r])?;
+            if ordering != std::cmp::Ordering::Equal {
+                return Ok(ordering);
+            }
+        }
+        Ok(std::cmp::Ordering::Equal)
+    }
+
+    /// Sort rows on the key columns, skipping the sort if they are already in order
+    fn sort_on_keys(rows: &mut [Vec<Value>], keys: &[usize]) -> PrismDBResult<()> {
+        let mut already_sorted = true;
+        for pair in rows.windows(2) {
+            if Self::compare_keys(&pair[0], keys, &pair[1], keys)? == std::cmp::Ordering::Greater {
+                already_sorted = false;
+                break;
             }
         }
+        if already_sorted {
+            return Ok(());
+        }
+
+        let mut error = None;
+        rows.sort_by(|a, b| {
+            Self::compare_keys(a, keys, b, keys).unwrap_or_else(|e| {
+                error.get_or_insert(e);
+                std::cmp::Ordering::Equal
+            })
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn has_null_key(row: &[Value], keys: &[usize]) -> bool {
+        keys.iter().any(|&k| row[k].is_null())
+    }
+}
+
+impl ExecutionOperator for SortMergeJoinOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        use crate::planner::PhysicalJoinType;
+        use std::cmp::Ordering;
+
+        let join_type = &self.join.join_type;
+        if *join_type == PhysicalJoinType::Cross {
+            return Err(PrismDBError::NotImplemented(
+                "Sort-merge join does not support CROSS JOIN".to_string(),
+            ));
+        }
+
+        let left_col_count = self.join.left.schema().len();
+        let right_col_count = self.join.right.schema().len();
+        let (left_keys, right_keys) = self.key_indices(left_col_count)?;
+
+        let reservation = self.context.memory_reservation("SORT_MERGE_JOIN");
+        let mut left_rows = Self::collect_rows(self.left.as_ref(), &reservation)?;
+        let mut right_rows = Self::collect_rows(self.right.as_ref(), &reservation)?;
+        Self::sort_on_keys(&mut left_rows, &left_keys)?;
+        Self::sort_on_keys(&mut right_rows, &right_keys)?;
+
+        let emit_unmatched_left = matches!(
+            join_type,
+            PhysicalJoinType::Left | PhysicalJoinType::Full | PhysicalJoinType::Anti
+        );
+        let emit_unmatched_right =
+            matches!(join_type, PhysicalJoinType::Right | PhysicalJoinType::Full);
+        let left_only = matches!(join_type, PhysicalJoinType::Semi | PhysicalJoinType::Anti);
+
+        let null_padded = |values: &[Value], pad_before: usize, pad_after: usize| {
+            let mut row = vec![Value::Null; pad_before];
+            row.extend_from_slice(values);
+            row.extend(std::iter::repeat_n(Value::Null, pad_after));
+            row
+        };
 
-        // Probe with left side
         let mut result_rows = Vec::new();
-        let mut left_stream = left_engine.execute(left_plan)?;
+        let mut right_matched = vec![false; right_rows.len()];
+        let mut right_pos = 0;
+        let mut left_pos = 0;
+
+        while left_pos < left_rows.len() {
+            let left_row = &left_rows[left_pos];
+
+            // NULL keys never match; they sort first so skip past them on the right
+            if Self::has_null_key(left_row, &left_keys) {
+                if emit_unmatched_left {
+                    result_rows.push(if left_only {
+                        left_row.clone()
+                    } else {
+                        null_padded(left_row, 0, right_col_count)
+                    });
+                }
+                left_pos += 1;
+                continue;
+            }
+            while right_pos < right_rows.len()
+                && (Self::has_null_key(&right_rows[right_pos], &right_keys)
+                    || Self::compare_keys(left_row, &left_keys, &right_rows[right_pos], &right_keys)?
+                        == Ordering::Greater)
+            {
+                right_pos += 1;
+            }
 
-        while let Some(chunk_result) = left_stream.next() {
-            let chunk = chunk_result?;
+            // Find the run of right rows sharing this key
+            let mut run_end = right_pos;
+            while run_end < right_rows.len()
+                && Self::compare_keys(left_row, &left_keys, &right_rows[run_end], &right_keys)?
+                    == Ordering::Equal
+            {
+                run_end += 1;
+            }
 
-            for row_idx in 0..chunk.len() {
-                // Extract left row
-                let mut left_row = Vec::new();
-                for col_idx in 0..chunk.column_count() {
-                    let vector = chunk.get_vector(col_idx).ok_or_else(|| {
-                        PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
-                    })?;
-                    let value = vector.get_value(row_idx)?;
-                    left_row.push(value);
-                }
-
-                // Probe hash table using actual join keys
-                if !left_row.is_empty() && !self.join.left_keys.is_empty() {
-                    // Evaluate left join key(s) to build probe key
-                    let mut key_parts = Vec::new();
-                    for left_key_expr in &self.join.left_keys {
-                        if let Some(col_ref) = left_key_expr.as_any().downcast_ref::<crate::expression::ColumnRefExpression>() {
-                            let col_idx = col_ref.column_index();
-                            if col_idx < left_row.len() {
-                                key_parts.push(left_row[col_idx].to_string());
-                            }
-                        }
+            // Every left row with the same key joins against the same right run
+            while left_pos < left_rows.len()
+                && Self::compare_keys(&left_rows[left_pos], &left_keys, left_row, &left_keys)?
+                    == Ordering::Equal
+            {
+                let current = &left_rows[left_pos];
+                if right_pos == run_end {
+                    if emit_unmatched_left {
+                        result_rows.push(if left_only {
+                            current.clone()
+                        } else {
+                            null_padded(current, 0, right_col_count)
+                        });
                     }
-                    let probe_key = key_parts.join("|");
-
-                    if let Some(matching_rows) = hash_table.get(&probe_key) {
-                        // Found matches - emit joined rows
-                        for right_row in matching_rows {
-                            let mut joined_row = left_row.clone();
-                            joined_row.extend(right_row.clone());
-                            result_rows.push(joined_row);
-                        }
-                    } else if self.join.join_type == crate::planner::PhysicalJoinType::Left {
-                        // LEFT JOIN: emit left row with NULLs for right side
-                        let mut joined_row = left_row.clone();
-                        // Add NULLs for right side columns
-                        for _ in 0..right_data.first().map(|r| r.len()).unwrap_or(0) {
-                            joined_row.push(Value::Null);
-                        }
+                } else if *join_type == PhysicalJoinType::Semi {
+                    result_rows.push(current.clone());
+                } else if !left_only {
+                    for right_row in &right_rows[right_pos..run_end] {
+                        let mut joined_row = current.clone();
+                        joined_row.extend(right_row.iter().cloned());
+                        reservation.grow(row_memory_usage(&joined_row))?;
                         result_rows.push(joined_row);
                     }
-                    // For INNER JOIN, we simply don't emit rows without matches
+                }
+                left_pos += 1;
+            }
+            right_matched[right_pos..run_end].fill(true);
+            right_pos = run_end;
+        }
+
+        if emit_unmatched_right {
+            for (right_row, matched) in right_rows.iter().zip(&right_matched) {
+                if !matched {
+                    result_rows.push(null_padded(right_row, left_col_count, 0));
                 }
             }
         }
 
-        // Convert result rows to DataChunks
         if result_rows.is_empty() {
             return Ok(Box::new(SimpleDataChunkStream::empty()));
         }
+        Ok(Box::new(SimpleDataChunkStream::new(join_rows_to_chunks(
+            &self.join.schema,
+            result_rows,
+        )?)))
+    }
 
-        // Determine schema
-        let num_columns = result_rows[0].len();
-        let mut data_chunk = DataChunk::with_rows(result_rows.len());
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.join.schema.clone()
+    }
+}
 
-        for col_idx in 0..num_columns {
-            // Collect all values for this column
-            let column_values: Vec<Value> =
-                result_rows.iter().map(|row| row[col_idx].clone()).collect();
+/// As-of join operator
+///
+/// Collects the right input into groups of equal keys, each sorted on its time, then
+/// binary-searches each left row's group for the nearest time the join's comparison
+/// allows. Rows with a NULL key or time match nothing. Output keeps the left input's
+/// order.
+pub struct AsOfJoinOperator {
+    join: PhysicalAsOfJoin,
+    left: Box<dyn ExecutionOperator>,
+    right: Box<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
 
-            // Create vector from values
-            let vector = crate::types::Vector::from_values(&column_values)?;
-            data_chunk.set_vector(col_idx, vector)?;
+impl AsOfJoinOperator {
+    pub fn new(
+        join: PhysicalAsOfJoin,
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self {
+            join,
+            left,
+            right,
+            context,
+        }
+    }
+
+    /// Column indices of the keys within each input; the planner puts left keys first
+    fn key_indices(&self, left_col_count: usize) -> PrismDBResult<(Vec<usize>, Vec<usize>)> {
+        use crate::expression::expression::{ColumnRefExpression, ExpressionRef};
+
+        let column_index = |expr: &ExpressionRef| {
+            expr.as_any()
+                .downcast_ref::<ColumnRefExpression>()
+                .map(|col_ref| col_ref.column_index())
+                .ok_or_else(|| {
+                    PrismDBError::Execution("As-of join keys must be column references".to_string())
+                })
+        };
+
+        let left_indices = self.join.left_keys.iter().map(column_index).collect::<PrismDBResult<Vec<_>>>()?;
+        let right_indices = self
+            .join
+            .right_keys
+            .iter()
+            .map(|key| Ok(column_index(key)? - left_col_count))
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        Ok((left_indices, right_indices))
+    }
+
+    /// Position in `group`, sorted on `time`, of the right row matching a left time
+    fn find_match(&self, group: &[Vec<Value>], time: usize, left_time: &Value) -> Option<usize> {
+        use crate::expression::ComparisonType;
+
+        let not_after = group.partition_point(|row| row[time].total_cmp(left_time).is_le());
+        let before = group.partition_point(|row| row[time].total_cmp(left_time).is_lt());
+        match self.join.comparison {
+            // The latest right time at or before the left time
+            ComparisonType::GreaterThanOrEqual => not_after.checked_sub(1),
+            ComparisonType::GreaterThan => before.checked_sub(1),
+            // The earliest right time at or after the left time
+            ComparisonType::LessThanOrEqual => (before < group.len()).then_some(before),
+            ComparisonType::LessThan => (not_after < group.len()).then_some(not_after),
+            _ => None,
+        }
+    }
+}
+
+impl ExecutionOperator for AsOfJoinOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        use std::collections::HashMap;
+
+        let left_col_count = self.join.left.schema().len();
+        let right_col_count = self.join.right.schema().len();
+        let (left_keys, right_keys) = self.key_indices(left_col_count)?;
+        let (left_time, right_time) = match (left_keys.split_last(), right_keys.split_last()) {
+            (Some((&left_time, _)), Some((&right_time, _))) => (left_time, right_time),
+            _ => {
+                return Err(PrismDBError::Execution(
+                    "As-of join needs a time column on each side".to_string(),
+                ))
+            }
+        };
+        let (left_equal, right_equal) = (&left_keys[..left_keys.len() - 1], &right_keys[..right_keys.len() - 1]);
+
+        let reservation = self.context.memory_reservation("ASOF_JOIN");
+        let mut groups: HashMap<Vec<OrderedValue>, Vec<Vec<Value>>> = HashMap::new();
+        for row in SortMergeJoinOperator::collect_rows(self.right.as_ref(), &reservation)? {
+            if right_keys.iter().any(|&k| row[k].is_null()) {
+                continue;
+            }
+            let key = right_equal.iter().map(|&k| OrderedValue(row[k].clone())).collect();
+            groups.entry(key).or_default().push(row);
+        }
+        for group in groups.values_mut() {
+            group.sort_by(|a, b| a[right_time].total_cmp(&b[right_time]));
         }
 
-        Ok(Box::new(SimpleDataChunkStream::new(vec![data_chunk])))
+        let keep_unmatched = self.join.join_type == crate::planner::PhysicalJoinType::Left;
+        let mut result_rows = Vec::new();
+        for left_row in SortMergeJoinOperator::collect_rows(self.left.as_ref(), &reservation)? {
+            let matched = if left_keys.iter().any(|&k| left_row[k].is_null()) {
+                None
+            } else {
+                let key: Vec<OrderedValue> = left_equal.iter().map(|&k| OrderedValue(left_row[k].clone())).collect();
+                groups.get(&key).and_then(|group| {
+                    self.find_match(group, right_time, &left_row[left_time]).map(|index| &group[index])
+                })
+            };
+            let mut joined_row = left_row;
+            match matched {
+                Some(right_row) => joined_row.extend(right_row.iter().cloned()),
+                None if keep_unmatched => joined_row.extend(std::iter::repeat_n(Value::Null, right_col_count)),
+                None => continue,
+            }
+            reservation.grow(row_memory_usage(&joined_row))?;
+            result_rows.push(joined_row);
+        }
+
+        if result_rows.is_empty() {
+            return Ok(Box::new(SimpleDataChunkStream::empty()));
+        }
+        Ok(Box::new(SimpleDataChunkStream::new(join_rows_to_chunks(
+            &self.join.schema,
+            result_rows,
+        )?)))
     }
 
     fn schema(&self) -> Vec<PhysicalColumn> {
@@ -1368,19 +2068,86 @@ impl ExecutionOperator for HashJoinOperator {
 /// Insert operator
 pub struct InsertOperator {
     insert: PhysicalInsert,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl InsertOperator {
-    pub fn new(insert: PhysicalInsert, context: ExecutionContext) -> Self {
-        Self { insert, context }
+    pub fn new(
+        insert: PhysicalInsert,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { insert, input, context }
+    }
+
+    /// Table position of each input column, following the INSERT's column list
+    fn target_columns(&self, columns: &[crate::storage::table::ColumnInfo]) -> PrismDBResult<Vec<usize>> {
+        let input_width = self.input.schema().len();
+        let targets: Vec<usize> = if input_width == 0 {
+            // DEFAULT VALUES fills no column
+            Vec::new()
+        } else if self.insert.column_names.is_empty() {
+            (0..columns.len()).collect()
+        } else {
+            self.insert
+                .column_names
+                .iter()
+                .map(|name| {
+                    columns.iter().position(|c| &c.name == name).ok_or_else(|| {
+                        PrismDBError::NotFound(ObjectKind::Column, format!(
+                            "Column '{}' does not exist in table '{}'",
+                            name, self.insert.table_name
+                        ))
+                    })
+                })
+                .collect::<PrismDBResult<_>>()?
+        };
+
+        if input_width != targets.len() {
+            return Err(PrismDBError::Catalog(format!(
+                "INSERT into '{}' has {} values for {} columns",
+                self.insert.table_name,
+                input_width,
+                targets.len()
+            )));
+        }
+        Ok(targets)
     }
+
+    /// Bound DEFAULT of each column the INSERT leaves out
+    fn default_expressions(
+        &self,
+        columns: &[crate::storage::table::ColumnInfo],
+        targets: &[usize],
+    ) -> PrismDBResult<Vec<Option<crate::expression::expression::ExpressionRef>>> {
+        columns
+            .iter()
+            .enumerate()
+            .map(|(idx, column)| match &column.default_expression {
+                Some(sql) if !targets.contains(&idx) => Ok(Some(bind_default_expression(sql)?)),
+                _ => Ok(None),
+            })
+            .collect()
+    }
+}
+
+/// Bind a column's DEFAULT, stored as SQL text, to evaluate for each row
+fn bind_default_expression(sql: &str) -> PrismDBResult<crate::expression::expression::ExpressionRef> {
+    use crate::expression::binder::{BinderContext, ExpressionBinder};
+
+    let binder = ExpressionBinder::new(BinderContext {
+        alias_map: std::collections::HashMap::new(),
+        column_bindings: Vec::new(),
+        depth: 0,
+    });
+    let expression = crate::parser::SqlParser::new().parse_expression(sql)?;
+    binder.bind_expression(&expression)
 }
 
 impl ExecutionOperator for InsertOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
         use crate::common::error::PrismDBError;
-        use crate::execution::ExecutionEngine;
 
         // Get the table from the catalog
         let catalog_arc = self.context.catalog.clone();
@@ -1388,15 +2155,9 @@ impl ExecutionOperator for InsertOperator {
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
 
-        let schema_arc = catalog.get_schema("main")?;
-        let schema = schema_arc
-            .read()
-            .map_err(|_| PrismDBError::Internal("Failed to lock schema".to_string()))?;
-
-        let table_arc = schema.get_table(&self.insert.table_name)?;
+        let table_arc = catalog.find_table(&self.insert.table_name)?;
 
         // Drop locks before getting table data to avoid holding multiple locks
-        drop(schema);
         drop(catalog);
 
         let table = table_arc
@@ -1404,14 +2165,22 @@ impl ExecutionOperator for InsertOperator {
             .map_err(|_| PrismDBError::Internal("Failed to lock table".to_string()))?;
 
         let table_data_arc = table.get_data();
+        let columns = table.get_table_info().columns;
 
         // Drop table read lock
         drop(table);
+        self.context.check_write(&table_data_arc)?;
+
+        // Table position of each input column, and the columns left to their defaults
+        let targets = self.target_columns(&columns)?;
+        let defaults = self.default_expressions(&columns, &targets)?;
 
         // Execute the input plan to get the data to insert
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.insert.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
+        let row_source = DataChunk::with_rows(1);
+
+        // Keys of UNIQUE and PRIMARY KEY constraints the table's rows already hold
+        let mut unique_keys = UniqueKeys::new(&*table_data_arc.read_or_err()?, &self.context.write_view())?;
 
         // Insert all rows from the input stream
         let mut total_rows_inserted = 0;
@@ -1423,20 +2192,43 @@ impl ExecutionOperator for InsertOperator {
             let mut table_data = table_data_arc
                 .write()
                 .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?;
+            table_data.reserve(chunk.len())?;
 
+            let mut rows = Vec::with_capacity(chunk.len());
             for row_idx in 0..chunk.len() {
-                // Extract values from this row
-                let mut values = Vec::new();
-                for col_idx in 0..chunk.column_count() {
+                // Omitted columns take their DEFAULT, or NULL without one
+                let mut values = Vec::with_capacity(columns.len());
+                for default in &defaults {
+                    values.push(match default {
+                        Some(expression) => expression.evaluate(&row_source, &self.context)?.get_value(0)?,
+                        None => Value::Null,
+                    });
+                }
+
+                // Extract values from this row into the columns they target
+                for (col_idx, &target) in targets.iter().enumerate() {
                     let vector = chunk.get_vector(col_idx).ok_or_else(|| {
                         PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
                     })?;
-                    let value = vector.get_value(row_idx)?;
-                    values.push(value);
+                    values[target] = vector.get_value(row_idx)?;
                 }
+                let values = values
+                    .into_iter()
+                    .zip(&columns)
+                    .map(|(value, column)| {
+                        let value = self.context.type_coercion.assign(value, &column.column_type)?;
+                        self.context.string_length_limit.apply(column, value)
+                    })
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                table_data.info.check_not_null(&values)?;
+                unique_keys.claim(&values, &table_data.info)?;
+                rows.push(values);
+            }
 
-                // Insert the row
-                table_data.insert_row(&values)?;
+            // Insert the rows once the whole chunk passed the checks
+            for values in &rows {
+                let change = table_data.insert_row_as(self.context.writer, values)?;
+                self.context.record_change(&table_data_arc, change)?;
                 total_rows_inserted += 1;
             }
 
@@ -1485,15 +2277,9 @@ impl ExecutionOperator for UpdateOperator {
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
 
-        let schema_arc = catalog.get_schema("main")?;
-        let schema = schema_arc
-            .read()
-            .map_err(|_| PrismDBError::Internal("Failed to lock schema".to_string()))?;
-
-        let table_arc = schema.get_table(&self.update.table_name)?;
+        let table_arc = catalog.find_table(&self.update.table_name)?;
 
         // Drop locks before updating
-        drop(schema);
         drop(catalog);
 
         let table = table_arc
@@ -1505,6 +2291,7 @@ impl ExecutionOperator for UpdateOperator {
 
         // Drop table read lock
         drop(table);
+        self.context.check_write(&table_data_arc)?;
 
         // Get column indices for the assignments
         let mut column_indices = std::collections::HashMap::new();
@@ -1514,29 +2301,45 @@ impl ExecutionOperator for UpdateOperator {
                 .iter()
                 .position(|c| &c.name == col_name)
                 .ok_or_else(|| {
-                    PrismDBError::InvalidValue(format!("Column '{}' not found", col_name))
+                    PrismDBError::NotFound(ObjectKind::Column, format!("Column '{}' not found", col_name))
                 })?;
             column_indices.insert(col_name.clone(), col_idx);
         }
 
+        // Columns set to DEFAULT take their declared default, or NULL
+        let mut defaults = Vec::new();
+        for col_name in &self.update.defaults {
+            let col_idx = table_info
+                .columns
+                .iter()
+                .position(|c| &c.name == col_name)
+                .ok_or_else(|| {
+                    PrismDBError::NotFound(ObjectKind::Column, format!("Column '{}' not found", col_name))
+                })?;
+            let default = match &table_info.columns[col_idx].default_expression {
+                Some(sql) => Some(bind_default_expression(sql)?),
+                None => None,
+            };
+            defaults.push((col_idx, default));
+        }
+
         // Lock table data for reading and updating
         let mut table_data = table_data_arc
             .write()
             .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?;
 
-        // Get the total physical number of rows (including deleted ones)
-        // We need to iterate over all rows to find which ones match the WHERE clause
-        let row_count = table_data.physical_row_count();
         let mut rows_updated = 0;
 
-        // Process rows in chunks
-        const CHUNK_SIZE: usize = 1024;
-        for chunk_start in (0..row_count).step_by(CHUNK_SIZE) {
-            let chunk_end = std::cmp::min(chunk_start + CHUNK_SIZE, row_count);
-            // Use unfiltered chunk to see all physical rows including deleted ones
-            let chunk = table_data.create_chunk_unfiltered(chunk_start, chunk_end - chunk_start)?;
+        // Process the rows the WHERE clause may match in chunks
+        let view = self.context.write_view();
+        let mut unique_keys = UniqueKeys::new(&table_data, &view)?;
+        let ranges = dml_row_ranges(&table_data, self.update.index_lookup.as_ref(), &self.context);
+        for range in ranges {
+            self.context.record_rows_scanned(range.len())?;
+            let row_ids = table_data.visible_rows(&view, range);
+            let chunk = table_data.create_chunk_of_rows(&view, &row_ids)?;
 
-            for row_idx in 0..chunk.len() {
+            for (row_idx, &actual_row_id) in row_ids.iter().enumerate() {
                 // Evaluate WHERE condition if present
                 let should_update = if let Some(ref condition) = self.update.condition {
                     let result = condition.evaluate_row(&chunk, row_idx, &self.context)?;
@@ -1549,9 +2352,6 @@ impl ExecutionOperator for UpdateOperator {
                 };
 
                 if should_update {
-                    // Get the actual row ID in the table
-                    let actual_row_id = chunk_start + row_idx;
-
                     // Extract current row values
                     let mut row_values = Vec::new();
                     for col_idx in 0..chunk.column_count() {
@@ -1560,16 +2360,41 @@ impl ExecutionOperator for UpdateOperator {
                         })?;
                         row_values.push(vector.get_value(row_idx)?);
                     }
+                    // The row gives up its old keys before claiming its new ones
+                    unique_keys.release(&row_values);
 
                     // Apply assignments to create updated row
                     for (col_name, expr) in &self.update.assignments {
-                        let new_value = expr.evaluate_row(&chunk, row_idx, &self.context)?;
                         let col_idx = column_indices[col_name];
-                        row_values[col_idx] = new_value;
+                        let column_type = &table_info.columns[col_idx].column_type;
+                        let mut new_value = self
+                            .context
+                            .type_coercion
+                            .assign(expr.evaluate_row(&chunk, row_idx, &self.context)?, column_type)?;
+                        // Labels become enum values, and anything else is rejected
+                        if matches!(column_type, LogicalType::Enum(_)) {
+                            new_value = new_value.cast_to(column_type)?;
+                        }
+                        row_values[col_idx] = self
+                            .context
+                            .string_length_limit
+                            .apply(&table_info.columns[col_idx], new_value)?;
+                    }
+                    for (col_idx, default) in &defaults {
+                        let column = &table_info.columns[*col_idx];
+                        let value = match default {
+                            Some(expr) => expr.evaluate_row(&chunk, row_idx, &self.context)?,
+                            None => Value::Null,
+                        };
+                        let value = self.context.type_coercion.assign(value, &column.column_type)?;
+                        row_values[*col_idx] = self.context.string_length_limit.apply(column, value)?;
                     }
 
                     // Update the row using the actual row ID
-                    table_data.update_row(actual_row_id, &row_values)?;
+                    table_info.check_not_null(&row_values)?;
+                    unique_keys.claim(&row_values, &table_info)?;
+                    let change = table_data.update_row_as(self.context.writer, actual_row_id, &row_values)?;
+                    self.context.record_change(&table_data_arc, change)?;
                     rows_updated += 1;
                 }
             }
@@ -1594,111 +2419,372 @@ impl ExecutionOperator for UpdateOperator {
     }
 }
 
-/// Delete operator
-pub struct DeleteOperator {
-    delete: PhysicalDelete,
+/// Row ranges an UPDATE or DELETE checks its condition against
+///
+/// With an index lookup only the rows the index returns are read, runs of
+/// consecutive ids together; otherwise every physical row is, a chunk at a time.
+/// A lookup whose index no longer exists, or cannot compare its bounds, falls
+/// back to reading every row.
+fn dml_row_ranges(
+    table_data: &crate::storage::TableData,
+    index_lookup: Option<&IndexLookup>,
+    context: &ExecutionContext,
+) -> Vec<std::ops::Range<usize>> {
+    const CHUNK_SIZE: usize = 1024;
+
+    let row_ids = index_lookup.and_then(|lookup| {
+        table_data
+            .index(&lookup.index_name)?
+            .lookup(&lookup.range)
+    });
+    let Some(row_ids) = row_ids else {
+        let row_count = table_data.physical_row_count();
+        return (0..row_count)
+            .step_by(CHUNK_SIZE)
+            .map(|start| start..std::cmp::min(start + CHUNK_SIZE, row_count))
+            .collect();
+    };
+    context.metrics.increment(Metric::IndexLookups);
+
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for row_id in row_ids {
+        match ranges.last_mut() {
+            Some(range) if range.end == row_id && range.len() < CHUNK_SIZE => range.end += 1,
+            _ => ranges.push(row_id..row_id + 1),
+        }
+    }
+    ranges
+}
+
+/// Delete operator
+pub struct DeleteOperator {
+    delete: PhysicalDelete,
+    context: ExecutionContext,
+}
+
+impl DeleteOperator {
+    pub fn new(delete: PhysicalDelete, context: ExecutionContext) -> Self {
+        Self {
+            delete,
+            context,
+        }
+    }
+}
+
+impl ExecutionOperator for DeleteOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        use crate::common::error::PrismDBError;
+
+        // Get the table from the catalog
+        let catalog_arc = self.context.catalog.clone();
+        let catalog = catalog_arc
+            .read()
+            .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
+
+        let table_arc = catalog.find_table(&self.delete.table_name)?;
+
+        // Drop locks before deleting
+        drop(catalog);
+
+        let table = table_arc
+            .read()
+            .map_err(|_| PrismDBError::Internal("Failed to lock table".to_string()))?;
+
+        let table_data_arc = table.get_data();
+
+        // Drop table read lock
+        drop(table);
+        self.context.check_write(&table_data_arc)?;
+
+        // Lock table data for reading and deleting
+        let mut table_data = table_data_arc
+            .write()
+            .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?;
+
+        // Collect row IDs to delete (iterate backwards to avoid index shifting issues)
+        let mut rows_to_delete = Vec::new();
+
+        // Process the rows the WHERE clause may match in chunks
+        let view = self.context.write_view();
+        let ranges = dml_row_ranges(&table_data, self.delete.index_lookup.as_ref(), &self.context);
+        for range in ranges {
+            self.context.record_rows_scanned(range.len())?;
+            let row_ids = table_data.visible_rows(&view, range);
+            let chunk = table_data.create_chunk_of_rows(&view, &row_ids)?;
+
+            for (row_idx, &actual_row_id) in row_ids.iter().enumerate() {
+                // Evaluate WHERE condition if present
+                let should_delete = if let Some(ref condition) = self.delete.condition {
+                    let result = condition.evaluate_row(&chunk, row_idx, &self.context)?;
+                    match result {
+                        Value::Boolean(b) => b,
+                        _ => false,
+                    }
+                } else {
+                    true // No WHERE clause means delete all rows
+                };
+
+                if should_delete {
+                    rows_to_delete.push(actual_row_id);
+                }
+            }
+        }
+
+        // Delete rows in reverse order to avoid index issues
+        rows_to_delete.sort_by(|a, b| b.cmp(a));  // Sort descending
+        let rows_deleted = rows_to_delete.len();
+        for row_id in rows_to_delete {
+            let change = table_data.delete_row_as(self.context.writer, row_id)?;
+            self.context.record_change(&table_data_arc, change)?;
+        }
+
+        // Drop table data lock
+        drop(table_data);
+
+        // Return a DataChunk with the affected row count
+        use crate::types::{LogicalType, Vector};
+        let mut result_chunk = DataChunk::new();
+        let mut count_vector = Vector::new(LogicalType::BigInt, 1);
+        count_vector.push(&Value::BigInt(rows_deleted as i64))?;
+        result_chunk.add_vector(count_vector)?;
+
+        Ok(Box::new(SimpleDataChunkStream::new(vec![result_chunk])))
+    }
+
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        // DELETE doesn't return rows
+        vec![]
+    }
+}
+
+/// Merge operator
+///
+/// Pairs every source row with the table rows the ON condition matches. Each
+/// matched pair takes the first WHEN MATCHED clause whose condition holds, and
+/// a source row matching no table row the first WHEN NOT MATCHED clause. A
+/// source row may match several table rows and acts on each of them, but a
+/// table row matched by more than one source row is an error, since which of
+/// them should decide its fate is ambiguous. Nothing is written until every
+/// source row has been matched, so rows the MERGE inserts are never matched.
+pub struct MergeOperator {
+    merge: PhysicalMerge,
+    source: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
-impl DeleteOperator {
-    pub fn new(delete: PhysicalDelete, context: ExecutionContext) -> Self {
-        Self {
-            delete,
-            context,
+impl MergeOperator {
+    pub fn new(
+        merge: PhysicalMerge,
+        source: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { merge, source, context }
+    }
+
+    /// The action of the first clause of the kind that applies to a row
+    fn first_action(
+        &self,
+        matched: bool,
+        chunk: &DataChunk,
+        row_idx: usize,
+    ) -> PrismDBResult<Option<&PhysicalMergeAction>> {
+        for clause in self.merge.clauses.iter().filter(|clause| clause.matched == matched) {
+            let applies = match &clause.condition {
+                Some(condition) => {
+                    matches!(condition.evaluate_row(chunk, row_idx, &self.context)?, Value::Boolean(true))
+                }
+                None => true,
+            };
+            if applies {
+                return Ok(Some(&clause.action));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Evaluate the values an UPDATE or INSERT action sets into a table row
+    fn assign(
+        &self,
+        row: &mut [Value],
+        assignments: &[(usize, crate::expression::expression::ExpressionRef)],
+        columns: &[crate::storage::ColumnInfo],
+        chunk: &DataChunk,
+        row_idx: usize,
+    ) -> PrismDBResult<()> {
+        for (col_idx, expr) in assignments {
+            let column = &columns[*col_idx];
+            let mut value = self
+                .context
+                .type_coercion
+                .assign(expr.evaluate_row(chunk, row_idx, &self.context)?, &column.column_type)?;
+            if !value.is_null() && value.get_type() != column.column_type {
+                value = value.cast_to(&column.column_type)?;
+            }
+            row[*col_idx] = self.context.string_length_limit.apply(column, value)?;
         }
+        Ok(())
     }
 }
 
-impl ExecutionOperator for DeleteOperator {
+impl ExecutionOperator for MergeOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::common::error::PrismDBError;
-
         // Get the table from the catalog
-        let catalog_arc = self.context.catalog.clone();
-        let catalog = catalog_arc
+        let catalog = self
+            .context
+            .catalog
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
-
-        let schema_arc = catalog.get_schema("main")?;
-        let schema = schema_arc
-            .read()
-            .map_err(|_| PrismDBError::Internal("Failed to lock schema".to_string()))?;
-
-        let table_arc = schema.get_table(&self.delete.table_name)?;
-
-        // Drop locks before deleting
-        drop(schema);
+        let table_arc = catalog.find_table(&self.merge.table_name)?;
         drop(catalog);
 
         let table = table_arc
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock table".to_string()))?;
-
+        let table_info = table.get_table_info();
         let table_data_arc = table.get_data();
-
-        // Drop table read lock
         drop(table);
+        self.context.check_write(&table_data_arc)?;
+
+        // Run the source before locking the table, which it may read itself
+        let mut source_rows = Vec::new();
+        let mut source_types = None;
+        for chunk in self.source.execute()? {
+            let chunk = chunk?;
+            source_types.get_or_insert_with(|| chunk.get_types());
+            source_rows.extend(chunk.to_rows()?);
+        }
+
+        // Expressions see the table columns followed by the source columns
+        let types: Vec<crate::types::LogicalType> = table_info
+            .columns
+            .iter()
+            .map(|column| column.column_type.clone())
+            .chain(source_types.unwrap_or_default())
+            .collect();
 
-        // Lock table data for reading and deleting
         let mut table_data = table_data_arc
             .write()
             .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?;
 
-        // Get the total physical number of rows (including deleted ones)
-        // We need to iterate over all rows to find which ones match the WHERE clause
-        let row_count = table_data.physical_row_count();
-
-        // Collect row IDs to delete (iterate backwards to avoid index shifting issues)
-        let mut rows_to_delete = Vec::new();
+        let view = self.context.write_view();
+        let mut targets = Vec::new();
+        for row_id in table_data.visible_rows(&view, 0..table_data.physical_row_count()) {
+            targets.push((row_id, table_data.get_row_in(&view, row_id)?));
+        }
+        self.context.record_rows_scanned(targets.len())?;
+
+        // The source row each table row was matched by, and what happens to it
+        let mut matched_by: Vec<Option<usize>> = vec![None; targets.len()];
+        let mut updated: Vec<Option<Vec<Value>>> = vec![None; targets.len()];
+        let mut deleted = vec![false; targets.len()];
+        let mut inserts = Vec::new();
+
+        for (source_idx, source_row) in source_rows.iter().enumerate() {
+            let mut pairs = DataChunk::with_capacity(types.clone(), targets.len());
+            for (_, target_row) in &targets {
+                pairs.append_row(target_row.iter().chain(source_row).cloned().collect())?;
+            }
 
-        // Process rows in chunks
-        const CHUNK_SIZE: usize = 1024;
-        for chunk_start in (0..row_count).step_by(CHUNK_SIZE) {
-            let chunk_end = std::cmp::min(chunk_start + CHUNK_SIZE, row_count);
-            // Use unfiltered chunk to see all physical rows including deleted ones
-            let chunk = table_data.create_chunk_unfiltered(chunk_start, chunk_end - chunk_start)?;
+            let mut any_match = false;
+            for (target_idx, (_, target_row)) in targets.iter().enumerate() {
+                let condition = self.merge.condition.evaluate_row(&pairs, target_idx, &self.context)?;
+                if !matches!(condition, Value::Boolean(true)) {
+                    continue;
+                }
+                any_match = true;
+                if matched_by[target_idx].replace(source_idx).is_some() {
+                    return Err(PrismDBError::Execution(format!(
+                        "MERGE matched a row of table '{}' with more than one source row",
+                        self.merge.table_name
+                    )));
+                }
 
-            for row_idx in 0..chunk.len() {
-                // Evaluate WHERE condition if present
-                let should_delete = if let Some(ref condition) = self.delete.condition {
-                    let result = condition.evaluate_row(&chunk, row_idx, &self.context)?;
-                    match result {
-                        Value::Boolean(b) => b,
-                        _ => false,
+                match self.first_action(true, &pairs, target_idx)? {
+                    Some(PhysicalMergeAction::Update(assignments)) => {
+                        let mut row = target_row.clone();
+                        self.assign(&mut row, assignments, &table_info.columns, &pairs, target_idx)?;
+                        updated[target_idx] = Some(row);
                     }
-                } else {
-                    true // No WHERE clause means delete all rows
-                };
+                    Some(PhysicalMergeAction::Delete) => deleted[target_idx] = true,
+                    _ => {}
+                }
+            }
 
-                if should_delete {
-                    let actual_row_id = chunk_start + row_idx;
-                    rows_to_delete.push(actual_row_id);
+            if !any_match {
+                // An unmatched source row sees NULL for every table column
+                let mut unmatched = DataChunk::with_capacity(types.clone(), 1);
+                unmatched.append_row(
+                    std::iter::repeat_n(Value::Null, table_info.columns.len())
+                        .chain(source_row.iter().cloned())
+                        .collect(),
+                )?;
+                if let Some(PhysicalMergeAction::Insert(values)) = self.first_action(false, &unmatched, 0)? {
+                    let mut row: Vec<Value> = table_info
+                        .columns
+                        .iter()
+                        .map(|column| column.default_value.clone().unwrap_or(Value::Null))
+                        .collect();
+                    self.assign(&mut row, values, &table_info.columns, &unmatched, 0)?;
+                    inserts.push(row);
                 }
             }
         }
 
-        // Delete rows in reverse order to avoid index issues
-        rows_to_delete.sort_by(|a, b| b.cmp(a));  // Sort descending
-        let rows_deleted = rows_to_delete.len();
-        for row_id in rows_to_delete {
-            table_data.delete_row(row_id)?;
+        // Updated and inserted rows must not take a key the table keeps
+        // after the deletes; an updated row gives up its old keys first
+        let mut unique_keys = UniqueKeys::new(&table_data, &view)?;
+        for row in updated.iter().flatten().chain(&inserts) {
+            table_info.check_not_null(row)?;
+        }
+        for (target_idx, (_, target_row)) in targets.iter().enumerate() {
+            if deleted[target_idx] {
+                unique_keys.release(target_row);
+            }
+        }
+        for (target_idx, (_, target_row)) in targets.iter().enumerate() {
+            if let Some(row) = &updated[target_idx] {
+                unique_keys.release(target_row);
+                unique_keys.claim(row, &table_info)?;
+            }
+        }
+        for row in &inserts {
+            unique_keys.claim(row, &table_info)?;
+        }
+
+        let mut rows_affected = 0;
+        let writer = self.context.writer;
+        for (target_idx, (row_id, _)) in targets.into_iter().enumerate() {
+            if deleted[target_idx] {
+                let change = table_data.delete_row_as(writer, row_id)?;
+                self.context.record_change(&table_data_arc, change)?;
+                rows_affected += 1;
+            } else if let Some(row) = &updated[target_idx] {
+                let change = table_data.update_row_as(writer, row_id, row)?;
+                self.context.record_change(&table_data_arc, change)?;
+                rows_affected += 1;
+            }
+        }
+        table_data.reserve(inserts.len())?;
+        for row in &inserts {
+            let change = table_data.insert_row_as(writer, row)?;
+            self.context.record_change(&table_data_arc, change)?;
+            rows_affected += 1;
         }
 
-        // Drop table data lock
         drop(table_data);
 
         // Return a DataChunk with the affected row count
         use crate::types::{LogicalType, Vector};
         let mut result_chunk = DataChunk::new();
         let mut count_vector = Vector::new(LogicalType::BigInt, 1);
-        count_vector.push(&Value::BigInt(rows_deleted as i64))?;
+        count_vector.push(&Value::BigInt(rows_affected as i64))?;
         result_chunk.add_vector(count_vector)?;
 
         Ok(Box::new(SimpleDataChunkStream::new(vec![result_chunk])))
     }
 
     fn schema(&self) -> Vec<PhysicalColumn> {
-        // DELETE doesn't return rows
+        // MERGE doesn't return rows
         vec![]
     }
 }
@@ -1718,6 +2804,141 @@ impl CreateTableOperator {
     }
 }
 
+impl CreateTableOperator {
+    /// Copy NOT NULL, DEFAULT, PRIMARY KEY, UNIQUE, CHECK and FOREIGN KEY
+    /// definitions into the table metadata
+    fn apply_constraints(&self, table_info: &mut crate::storage::TableInfo) -> PrismDBResult<()> {
+        use crate::common::error::PrismDBError;
+        use crate::parser::ast::{ColumnConstraint, TableConstraint};
+        use crate::storage::TableConstraintInfo;
+
+        let column_indexes = |table_info: &crate::storage::TableInfo,
+                              names: &[String]|
+         -> PrismDBResult<Vec<usize>> {
+            names
+                .iter()
+                .map(|name| {
+                    table_info.get_column_index(name).ok_or_else(|| {
+                        PrismDBError::NotFound(ObjectKind::Column, format!(
+                            "Column '{}' not found in table '{}'",
+                            name, table_info.name
+                        ))
+                    })
+                })
+                .collect()
+        };
+
+        let mut primary_key = Vec::new();
+        for (idx, definition) in self.create_table.columns.iter().enumerate() {
+            table_info.columns[idx].nullable = definition.nullable;
+            table_info.columns[idx].default_expression =
+                definition.default_value.as_ref().map(|e| e.to_string());
+            table_info.columns[idx].max_length = definition.max_length;
+            table_info.columns[idx].collation = definition
+                .collation
+                .as_ref()
+                .filter(|collation| match crate::types::Collation::from_name(collation) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        // DDL from other databases names collations PrismDB doesn't have
+                        log::warn!("Ignoring collation of column '{}': {}", definition.name, e);
+                        false
+                    }
+                })
+                .cloned();
+
+            for constraint in &definition.constraints {
+                match constraint {
+                    ColumnConstraint::PrimaryKey => primary_key.push(definition.name.clone()),
+                    ColumnConstraint::Unique { nulls_not_distinct: false } => {
+                        table_info.columns[idx].is_unique = true
+                    }
+                    ColumnConstraint::Unique { nulls_not_distinct: true } => {
+                        table_info.constraints.push(TableConstraintInfo::Unique {
+                            columns: vec![idx],
+                            nulls_not_distinct: true,
+                        })
+                    }
+                    ColumnConstraint::NotNull => table_info.columns[idx].nullable = false,
+                    ColumnConstraint::Default(expression) => {
+                        table_info.columns[idx].default_expression = Some(expression.to_string())
+                    }
+                    ColumnConstraint::Check(expression) => {
+                        table_info.constraints.push(TableConstraintInfo::Check {
+                            expression: expression.to_string(),
+                        })
+                    }
+                    ColumnConstraint::References { table, column } => {
+                        table_info.constraints.push(TableConstraintInfo::ForeignKey {
+                            columns: vec![idx],
+                            foreign_table: table.clone(),
+                            foreign_columns: vec![column.clone()],
+                        })
+                    }
+                    ColumnConstraint::AutoIncrement => {}
+                }
+            }
+        }
+
+        for constraint in &self.create_table.constraints {
+            match constraint {
+                TableConstraint::PrimaryKey { columns } => {
+                    if !primary_key.is_empty() {
+                        return Err(PrismDBError::Catalog(format!(
+                            "Multiple primary keys for table '{}' are not allowed",
+                            table_info.name
+                        )));
+                    }
+                    primary_key = columns.clone();
+                }
+                TableConstraint::Unique {
+                    columns,
+                    nulls_not_distinct,
+                    ..
+                } => {
+                    let indexes = column_indexes(table_info, columns)?;
+                    match indexes[..] {
+                        [index] if !nulls_not_distinct => table_info.columns[index].is_unique = true,
+                        _ => table_info.constraints.push(TableConstraintInfo::Unique {
+                            columns: indexes,
+                            nulls_not_distinct: *nulls_not_distinct,
+                        }),
+                    }
+                }
+                TableConstraint::ForeignKey {
+                    columns,
+                    foreign_table,
+                    foreign_columns,
+                    ..
+                } => {
+                    let indexes = column_indexes(table_info, columns)?;
+                    table_info.constraints.push(TableConstraintInfo::ForeignKey {
+                        columns: indexes,
+                        foreign_table: foreign_table.clone(),
+                        foreign_columns: foreign_columns.clone(),
+                    });
+                }
+                TableConstraint::Check { expression, .. } => {
+                    table_info.constraints.push(TableConstraintInfo::Check {
+                        expression: expression.to_string(),
+                    });
+                }
+            }
+        }
+
+        if !primary_key.is_empty() {
+            table_info.set_primary_key(primary_key.clone())?;
+            for name in &primary_key {
+                if let Some(index) = table_info.get_column_index(name) {
+                    table_info.columns[index].nullable = false;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl ExecutionOperator for CreateTableOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
         use crate::common::error::PrismDBError;
@@ -1729,7 +2950,7 @@ impl ExecutionOperator for CreateTableOperator {
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
 
-        let schema_arc = catalog.get_schema("main")?;
+        let schema_arc = catalog.current_schema()?;
 
         // Drop catalog lock before modifying schema
         drop(catalog);
@@ -1753,6 +2974,7 @@ impl ExecutionOperator for CreateTableOperator {
 
         // Create table info
         let mut table_info = TableInfo::new(self.create_table.table_name.clone());
+        table_info.schema_name = schema.name.clone();
 
         // Add columns to the table
         for (idx, col) in self.create_table.schema.iter().enumerate() {
@@ -1763,6 +2985,9 @@ impl ExecutionOperator for CreateTableOperator {
             ))?;
         }
 
+        // Record column and table constraints
+        self.apply_constraints(&mut table_info)?;
+
         // Create the table in the schema
         schema.create_table(&table_info)?;
 
@@ -1801,7 +3026,16 @@ impl ExecutionOperator for DropTableOperator {
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
 
-        let schema_arc = catalog.get_schema("main")?;
+        let Ok(schema_arc) = catalog.table_schema(&self.drop_table.table_name) else {
+            if self.drop_table.if_exists {
+                // Table doesn't exist but IF EXISTS was specified, just return success
+                return Ok(Box::new(SimpleDataChunkStream::empty()));
+            }
+            return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
+                "Table '{}' does not exist",
+                self.drop_table.table_name
+            )));
+        };
 
         // Drop catalog lock before modifying schema
         drop(catalog);
@@ -1816,7 +3050,7 @@ impl ExecutionOperator for DropTableOperator {
                 // Table doesn't exist but IF EXISTS was specified, just return success
                 return Ok(Box::new(SimpleDataChunkStream::empty()));
             } else {
-                return Err(PrismDBError::Catalog(format!(
+                return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
                     "Table '{}' does not exist",
                     self.drop_table.table_name
                 )));
@@ -1879,7 +3113,10 @@ impl ExecutionOperator for ValuesOperator {
                     for (col_idx, expr) in row.iter().enumerate() {
                         // Evaluate expression against dummy chunk
                         let result_vector = expr.evaluate(&dummy_chunk, &self.context)?;
-                        let value = result_vector.get_value(0)?;
+                        let value = self
+                            .context
+                            .type_coercion
+                            .assign(result_vector.get_value(0)?, &self.values.schema[col_idx].data_type)?;
                         // Use push instead of set_value to properly update count
                         column_vectors[col_idx].push(&value)?;
                     }
@@ -1927,27 +3164,29 @@ impl ExecutionOperator for EmptyResultOperator {
 /// PIVOT operator - transforms rows to columns
 pub struct PivotOperator {
     pivot: crate::planner::PhysicalPivot,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl PivotOperator {
-    pub fn new(pivot: crate::planner::PhysicalPivot, context: ExecutionContext) -> Self {
-        Self { pivot, context }
+    pub fn new(
+        pivot: crate::planner::PhysicalPivot,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { pivot, input, context }
     }
 }
 
 impl ExecutionOperator for PivotOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
         use crate::common::error::PrismDBError;
-        use crate::execution::ExecutionEngine;
         use crate::expression::aggregate::AggregateState;
         use crate::types::Value;
-        use std::collections::HashMap;
+        use std::collections::{BTreeMap, HashMap};
 
         // Execute the input plan
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.pivot.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
 
         // Collect pivot values (distinct values from ON columns or explicit IN values)
         // For initial implementation, we'll use explicit IN values
@@ -1962,10 +3201,12 @@ impl ExecutionOperator for PivotOperator {
         };
 
         // Hash table: (group_key, pivot_key) -> aggregate_states
-        // group_key: concatenation of GROUP BY column values
-        // pivot_key: concatenation of ON column values
+        // group_key: GROUP BY column values
+        // pivot_key: ON column values
         // aggregate_states: Vec of Box<dyn AggregateState> (one per USING aggregate expression)
-        let mut hash_table: HashMap<(String, String), Vec<Box<dyn AggregateState>>> = HashMap::new();
+        type Key = Vec<OrderedValue>;
+        type States = Vec<Box<dyn AggregateState>>;
+        let mut hash_table: HashMap<(Key, Key), States> = HashMap::new();
 
         // Process all input chunks
         while let Some(chunk_result) = input_stream.next() {
@@ -1973,30 +3214,22 @@ impl ExecutionOperator for PivotOperator {
 
             for row_idx in 0..chunk.len() {
                 // Extract group key from GROUP BY columns
-                let group_key = if self.pivot.group_by.is_empty() {
-                    String::from("__global__")
-                } else {
-                    let mut key_parts = Vec::new();
-                    for group_expr in &self.pivot.group_by {
-                        let result_vector = group_expr.evaluate(&chunk, &self.context)?;
-                        let value = result_vector.get_value(row_idx)?;
-                        key_parts.push(value_to_key_string(&value));
-                    }
-                    key_parts.join("|")
-                };
+                let mut group_key = Vec::with_capacity(self.pivot.group_by.len());
+                for group_expr in &self.pivot.group_by {
+                    let result_vector = group_expr.evaluate(&chunk, &self.context)?;
+                    group_key.push(OrderedValue(result_vector.get_value(row_idx)?));
+                }
 
                 // Extract pivot key from ON columns
-                let mut pivot_key_parts = Vec::new();
+                let mut pivot_key = Vec::with_capacity(self.pivot.on_columns.len());
                 for on_expr in &self.pivot.on_columns {
                     let result_vector = on_expr.evaluate(&chunk, &self.context)?;
-                    let value = result_vector.get_value(row_idx)?;
-                    pivot_key_parts.push(value_to_key_string(&value));
+                    pivot_key.push(OrderedValue(result_vector.get_value(row_idx)?));
                 }
-                let pivot_key = pivot_key_parts.join("|");
 
                 // Get or create aggregate states for this (group, pivot) combination
                 let states = hash_table
-                    .entry((group_key.clone(), pivot_key.clone()))
+                    .entry((group_key, pivot_key))
                     .or_insert_with(|| {
                         self.pivot
                             .using_values
@@ -2032,13 +3265,10 @@ impl ExecutionOperator for PivotOperator {
             return Ok(Box::new(SimpleDataChunkStream::empty()));
         }
 
-        // Group the hash table entries by group_key
-        let mut group_map: HashMap<String, HashMap<String, Vec<Box<dyn AggregateState>>>> = HashMap::new();
+        // Group the hash table entries by group_key, in group order
+        let mut group_map: BTreeMap<Key, HashMap<Key, States>> = BTreeMap::new();
         for ((group_key, pivot_key), states) in hash_table {
-            group_map
-                .entry(group_key)
-                .or_insert_with(HashMap::new)
-                .insert(pivot_key, states);
+            group_map.entry(group_key).or_default().insert(pivot_key, states);
         }
 
         // Build output rows (one per group)
@@ -2049,31 +3279,17 @@ impl ExecutionOperator for PivotOperator {
         let mut all_rows: Vec<Vec<Value>> = Vec::new();
 
         for (group_key, pivot_map) in group_map {
-            let mut column_values = Vec::new();
-
-            // Add GROUP BY column values (parse from group_key)
-            if self.pivot.group_by.is_empty() {
-                // No GROUP BY columns
-            } else {
-                let key_parts: Vec<&str> = group_key.split('|').collect();
-                for part in key_parts {
-                    // Parse value back (simplified - assumes integers or strings)
-                    let val = if let Ok(i) = part.parse::<i32>() {
-                        Value::Integer(i)
-                    } else if let Ok(i) = part.parse::<i64>() {
-                        Value::BigInt(i)
-                    } else {
-                        Value::Varchar(part.to_string())
-                    };
-                    column_values.push(val);
-                }
-            }
+            // Start with the GROUP BY column values
+            let mut column_values: Vec<Value> = group_key.into_iter().map(|key| key.0).collect();
 
             // Add pivot columns (one for each pivot_value * using_value)
             for pivot_val in &pivot_values {
-                // Extract constant value using utility function
-                let pivot_key = crate::execution::pivot_utils::extract_constant_value(&pivot_val.value, &self.context)
-                    .unwrap_or_else(|| "unknown".to_string());
+                // Rows match a pivot value when their ON value equals it
+                let pivot_key: Vec<OrderedValue> =
+                    crate::execution::pivot_utils::evaluate_constant(&pivot_val.value, &self.context)
+                        .into_iter()
+                        .map(OrderedValue)
+                        .collect();
 
                 for (agg_idx, _using_val) in self.pivot.using_values.iter().enumerate() {
                     let value = if let Some(states) = pivot_map.get(&pivot_key) {
@@ -2114,24 +3330,26 @@ impl ExecutionOperator for PivotOperator {
 /// UNPIVOT operator - transforms columns to rows
 pub struct UnpivotOperator {
     unpivot: crate::planner::PhysicalUnpivot,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl UnpivotOperator {
-    pub fn new(unpivot: crate::planner::PhysicalUnpivot, context: ExecutionContext) -> Self {
-        Self { unpivot, context }
+    pub fn new(
+        unpivot: crate::planner::PhysicalUnpivot,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { unpivot, input, context }
     }
 }
 
 impl ExecutionOperator for UnpivotOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
         use crate::types::Value;
 
         // Execute the input plan
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.unpivot.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        let mut input_stream = self.input.execute()?;
 
         let mut output_rows: Vec<Vec<Value>> = Vec::new();
 
@@ -2206,109 +3424,227 @@ impl ExecutionOperator for UnpivotOperator {
     }
 }
 
-/// Union operator - concatenates results from two queries
-pub struct UnionOperator {
-    union: PhysicalUnion,
-    context: ExecutionContext,
+/// Unnest operator - expands list columns into one row per element
+///
+/// Several unnested columns produce every combination of their elements. A
+/// NULL or empty list produces no rows.
+pub struct UnnestOperator {
+    unnest: crate::planner::PhysicalUnnest,
+    input: Box<dyn ExecutionOperator>,
 }
 
-impl UnionOperator {
-    pub fn new(union: PhysicalUnion, context: ExecutionContext) -> Self {
-        Self { union, context }
+impl UnnestOperator {
+    pub fn new(unnest: crate::planner::PhysicalUnnest, input: Box<dyn ExecutionOperator>) -> Self {
+        Self { unnest, input }
     }
 }
 
-impl ExecutionOperator for UnionOperator {
+impl ExecutionOperator for UnnestOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
-        use std::collections::HashSet;
+        use crate::types::Vector;
 
-        let mut engine = ExecutionEngine::new(self.context.clone());
+        let mut input_stream = self.input.execute()?;
+        let schema = &self.unnest.schema;
 
-        // Execute left child
-        let mut left_stream = engine.execute(*self.union.left.clone())?;
-        let mut all_chunks = Vec::new();
+        let mut chunks = Vec::new();
+        for chunk_result in input_stream.by_ref() {
+            let chunk = chunk_result?;
+            let mut columns: Vec<Vec<Value>> = vec![Vec::new(); schema.len()];
+
+            for row in chunk.to_rows()? {
+                let lists = self
+                    .unnest
+                    .unnest_columns
+                    .iter()
+                    .map(|&column| match &row[column] {
+                        Value::List(elements) => Ok(elements.as_slice()),
+                        Value::Null => Ok([].as_slice()),
+                        other => Err(PrismDBError::Execution(format!(
+                            "UNNEST requires a list, got {}",
+                            other.get_type()
+                        ))),
+                    })
+                    .collect::<PrismDBResult<Vec<_>>>()?;
 
-        while let Some(chunk_result) = left_stream.next() {
-            all_chunks.push(chunk_result?);
-        }
+                // Every combination of one element from each unnested list
+                let mut combinations: Vec<Vec<&Value>> = vec![Vec::new()];
+                for list in &lists {
+                    combinations = combinations
+                        .iter()
+                        .flat_map(|prefix| {
+                            list.iter().map(move |element| {
+                                let mut combination = prefix.clone();
+                                combination.push(element);
+                                combination
+                            })
+                        })
+                        .collect();
+                }
 
-        // Execute right child
-        let mut right_stream = engine.execute(*self.union.right.clone())?;
+                for combination in combinations {
+                    for (column, value) in row.iter().enumerate() {
+                        let value = match self.unnest.unnest_columns.iter().position(|&c| c == column) {
+                            Some(position) => combination[position],
+                            None => value,
+                        };
+                        columns[column].push(value.clone());
+                    }
+                }
+            }
 
-        while let Some(chunk_result) = right_stream.next() {
-            all_chunks.push(chunk_result?);
+            if columns.first().is_none_or(|values| values.is_empty()) {
+                continue;
+            }
+            let mut output = DataChunk::new();
+            for (values, column) in columns.iter().zip(schema) {
+                output.add_vector(Vector::from_values_with_type(values, &column.data_type)?)?;
+            }
+            chunks.push(output);
         }
 
-        // If UNION (not UNION ALL), remove duplicates
-        if !self.union.all {
-            // Deduplicate rows
-            let mut unique_rows = HashSet::new();
-            let mut dedup_chunks = Vec::new();
+        Ok(Box::new(SimpleDataChunkStream::new(chunks)))
+    }
 
-            for chunk in &all_chunks {
-                let mut unique_chunk_rows = Vec::new();
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.unnest.schema.clone()
+    }
+}
 
-                for row_idx in 0..chunk.len() {
-                    // Create a row representation for hashing
-                    let mut row_values = Vec::new();
-                    for col_idx in 0..chunk.column_count() {
-                        let vector = chunk.get_vector(col_idx)
-                            .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
-                        row_values.push(vector.get_value(row_idx)?);
-                    }
+/// Union operator - concatenates results from two queries
+pub struct UnionOperator {
+    union: PhysicalUnion,
+    left: std::sync::Arc<dyn ExecutionOperator>,
+    right: std::sync::Arc<dyn ExecutionOperator>,
+    context: ExecutionContext,
+}
 
-                    // Use string representation for hashing (simple but works)
-                    let row_key = format!("{:?}", row_values);
-                    if unique_rows.insert(row_key) {
-                        unique_chunk_rows.push(row_values);
-                    }
-                }
+impl UnionOperator {
+    pub fn new(
+        union: PhysicalUnion,
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self {
+            union,
+            left: std::sync::Arc::from(left),
+            right: std::sync::Arc::from(right),
+            context,
+        }
+    }
+}
 
-                // Build deduplicated chunk
-                if !unique_chunk_rows.is_empty() {
-                    let num_rows = unique_chunk_rows.len();
-                    let num_cols = chunk.column_count();
-                    let mut dedup_chunk = DataChunk::with_rows(num_rows);
+impl ExecutionOperator for UnionOperator {
+    fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        let reservation = self.context.memory_reservation(if self.union.all { "UNION_ALL" } else { "UNION" });
+        Ok(Box::new(UnionStream {
+            current: self.left.execute()?,
+            right: Some(self.right.clone()),
+            seen: (!self.union.all).then(std::collections::HashSet::new),
+            reservation,
+        }))
+    }
 
-                    for col_idx in 0..num_cols {
-                        let mut col_values = Vec::new();
-                        for row in &unique_chunk_rows {
-                            col_values.push(row[col_idx].clone());
-                        }
-                        let vector = crate::types::Vector::from_values(&col_values)?;
-                        dedup_chunk.set_vector(col_idx, vector)?;
-                    }
+    fn schema(&self) -> Vec<PhysicalColumn> {
+        self.union.schema.clone()
+    }
+}
 
-                    dedup_chunks.push(dedup_chunk);
-                }
+/// Rows of the left input, then of the right
+///
+/// The right input is only executed once the left one is exhausted, so a
+/// LIMIT the left input satisfies never runs it. For UNION without ALL, rows
+/// already produced are dropped as they arrive.
+struct UnionStream {
+    current: Box<dyn DataChunkStream>,
+    /// The right input, until it is executed
+    right: Option<std::sync::Arc<dyn ExecutionOperator>>,
+    /// Rows produced so far, when duplicates are removed
+    seen: Option<std::collections::HashSet<Vec<OrderedValue>>>,
+    reservation: MemoryReservation,
+}
+
+impl UnionStream {
+    /// Remove the rows of `chunk` that were produced before
+    fn distinct_rows(&mut self, chunk: DataChunk) -> PrismDBResult<DataChunk> {
+        let Some(seen) = &mut self.seen else {
+            return Ok(chunk);
+        };
+        let mut unique_rows = Vec::new();
+        for row_idx in 0..chunk.len() {
+            let mut row_values = Vec::new();
+            for col_idx in 0..chunk.column_count() {
+                let vector = chunk.get_vector(col_idx)
+                    .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
+                row_values.push(vector.get_value(row_idx)?);
             }
 
-            Ok(Box::new(SimpleDataChunkStream::new(dedup_chunks)))
-        } else {
-            // UNION ALL - just concatenate
-            Ok(Box::new(SimpleDataChunkStream::new(all_chunks)))
+            let row_key = OrderedValue::row(&row_values);
+            if !seen.contains(&row_key) {
+                self.reservation.grow(key_memory_usage(&row_key) + row_memory_usage(&row_values))?;
+                seen.insert(row_key);
+                unique_rows.push(row_values);
+            }
+        }
+
+        let mut unique_chunk = DataChunk::with_rows(unique_rows.len());
+        if unique_rows.is_empty() {
+            return Ok(unique_chunk);
+        }
+        for col_idx in 0..chunk.column_count() {
+            let col_values: Vec<Value> = unique_rows.iter().map(|row| row[col_idx].clone()).collect();
+            unique_chunk.set_vector(col_idx, crate::types::Vector::from_values(&col_values)?)?;
         }
+        Ok(unique_chunk)
     }
+}
 
-    fn schema(&self) -> Vec<PhysicalColumn> {
-        self.union.schema.clone()
+impl Iterator for UnionStream {
+    type Item = PrismDBResult<DataChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.current.next() {
+                Some(Ok(chunk)) => match self.distinct_rows(chunk) {
+                    Ok(chunk) if chunk.is_empty() => continue,
+                    result => return Some(result),
+                },
+                Some(Err(e)) => return Some(Err(e)),
+                None => match self.right.take()?.execute() {
+                    Ok(stream) => self.current = stream,
+                    Err(e) => return Some(Err(e)),
+                },
+            }
+        }
     }
 }
 
+impl DataChunkStream for UnionStream {}
+
 /// Intersect operator - returns rows that appear in both left and right
+///
+/// INTERSECT ALL keeps duplicates: a row appears as many times as the lesser
+/// of its counts in the two inputs.
 pub struct IntersectOperator {
-    left: Box<PhysicalPlan>,
-    right: Box<PhysicalPlan>,
+    left: Box<dyn ExecutionOperator>,
+    right: Box<dyn ExecutionOperator>,
+    all: bool,
     schema: Vec<PhysicalColumn>,
     context: ExecutionContext,
 }
 
 impl IntersectOperator {
-    pub fn new(left: PhysicalPlan, right: PhysicalPlan, schema: Vec<PhysicalColumn>, context: ExecutionContext) -> Self {
+    pub fn new(
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        all: bool,
+        schema: Vec<PhysicalColumn>,
+        context: ExecutionContext,
+    ) -> Self {
         Self {
-            left: Box::new(left),
-            right: Box::new(right),
+            left,
+            right,
+            all,
             schema,
             context
         }
@@ -2317,14 +3653,13 @@ impl IntersectOperator {
 
 impl ExecutionOperator for IntersectOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
-        use std::collections::HashSet;
+        use std::collections::{HashMap, HashSet};
 
-        let mut engine = ExecutionEngine::new(self.context.clone());
+        let reservation = self.context.memory_reservation(if self.all { "INTERSECT_ALL" } else { "INTERSECT" });
 
-        // Execute left child and collect all rows into a HashSet
-        let mut left_stream = engine.execute(*self.left.clone())?;
-        let mut left_rows = HashSet::new();
+        // Execute left child and count the occurrences of each row
+        let mut left_stream = self.left.execute()?;
+        let mut left_rows: HashMap<_, usize> = HashMap::new();
 
         while let Some(chunk_result) = left_stream.next() {
             let chunk = chunk_result?;
@@ -2335,13 +3670,16 @@ impl ExecutionOperator for IntersectOperator {
                         .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
                     row_values.push(vector.get_value(row_idx)?);
                 }
-                let row_key = format!("{:?}", row_values);
-                left_rows.insert(row_key);
+                let row_key = OrderedValue::row(&row_values);
+                if !left_rows.contains_key(&row_key) {
+                    reservation.grow(key_memory_usage(&row_key))?;
+                }
+                *left_rows.entry(row_key).or_default() += 1;
             }
         }
 
         // Execute right child and keep only rows that exist in left
-        let mut right_stream = engine.execute(*self.right.clone())?;
+        let mut right_stream = self.right.execute()?;
         let mut result_rows = Vec::new();
         let mut seen = HashSet::new();
 
@@ -2354,10 +3692,23 @@ impl ExecutionOperator for IntersectOperator {
                         .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
                     row_values.push(vector.get_value(row_idx)?);
                 }
-                let row_key = format!("{:?}", row_values);
-
-                // Only include if in left and not already added (dedup)
-                if left_rows.contains(&row_key) && seen.insert(row_key) {
+                let row_key = OrderedValue::row(&row_values);
+
+                // ALL matches each left row at most once; otherwise include if in
+                // left and not already added (dedup)
+                let include = if self.all {
+                    match left_rows.get_mut(&row_key) {
+                        Some(count) if *count > 0 => {
+                            *count -= 1;
+                            true
+                        }
+                        _ => false,
+                    }
+                } else {
+                    left_rows.contains_key(&row_key) && seen.insert(row_key)
+                };
+                if include {
+                    reservation.grow(row_memory_usage(&row_values))?;
                     result_rows.push(row_values);
                 }
             }
@@ -2390,18 +3741,29 @@ impl ExecutionOperator for IntersectOperator {
 }
 
 /// Except operator - returns rows in left that are NOT in right
+///
+/// EXCEPT ALL keeps duplicates: each row of the right input cancels one equal
+/// row of the left, so a row appears its left count minus its right count times.
 pub struct ExceptOperator {
-    left: Box<PhysicalPlan>,
-    right: Box<PhysicalPlan>,
+    left: Box<dyn ExecutionOperator>,
+    right: Box<dyn ExecutionOperator>,
+    all: bool,
     schema: Vec<PhysicalColumn>,
     context: ExecutionContext,
 }
 
 impl ExceptOperator {
-    pub fn new(left: PhysicalPlan, right: PhysicalPlan, schema: Vec<PhysicalColumn>, context: ExecutionContext) -> Self {
+    pub fn new(
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        all: bool,
+        schema: Vec<PhysicalColumn>,
+        context: ExecutionContext,
+    ) -> Self {
         Self {
-            left: Box::new(left),
-            right: Box::new(right),
+            left,
+            right,
+            all,
             schema,
             context
         }
@@ -2410,14 +3772,13 @@ impl ExceptOperator {
 
 impl ExecutionOperator for ExceptOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
-        use std::collections::HashSet;
+        use std::collections::{HashMap, HashSet};
 
-        let mut engine = ExecutionEngine::new(self.context.clone());
+        let reservation = self.context.memory_reservation(if self.all { "EXCEPT_ALL" } else { "EXCEPT" });
 
-        // Execute right child and collect all rows into a HashSet
-        let mut right_stream = engine.execute(*self.right.clone())?;
-        let mut right_rows = HashSet::new();
+        // Execute right child and count the occurrences of each row
+        let mut right_stream = self.right.execute()?;
+        let mut right_rows: HashMap<_, usize> = HashMap::new();
 
         while let Some(chunk_result) = right_stream.next() {
             let chunk = chunk_result?;
@@ -2428,13 +3789,16 @@ impl ExecutionOperator for ExceptOperator {
                         .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
                     row_values.push(vector.get_value(row_idx)?);
                 }
-                let row_key = format!("{:?}", row_values);
-                right_rows.insert(row_key);
+                let row_key = OrderedValue::row(&row_values);
+                if !right_rows.contains_key(&row_key) {
+                    reservation.grow(key_memory_usage(&row_key))?;
+                }
+                *right_rows.entry(row_key).or_default() += 1;
             }
         }
 
         // Execute left child and keep only rows NOT in right
-        let mut left_stream = engine.execute(*self.left.clone())?;
+        let mut left_stream = self.left.execute()?;
         let mut result_rows = Vec::new();
         let mut seen = HashSet::new();
 
@@ -2447,10 +3811,23 @@ impl ExecutionOperator for ExceptOperator {
                         .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
                     row_values.push(vector.get_value(row_idx)?);
                 }
-                let row_key = format!("{:?}", row_values);
-
-                // Only include if NOT in right and not already added (dedup)
-                if !right_rows.contains(&row_key) && seen.insert(row_key) {
+                let row_key = OrderedValue::row(&row_values);
+
+                // ALL lets each right row cancel one left row; otherwise include if
+                // NOT in right and not already added (dedup)
+                let include = if self.all {
+                    match right_rows.get_mut(&row_key) {
+                        Some(count) if *count > 0 => {
+                            *count -= 1;
+                            false
+                        }
+                        _ => true,
+                    }
+                } else {
+                    !right_rows.contains_key(&row_key) && seen.insert(row_key)
+                };
+                if include {
+                    reservation.grow(row_memory_usage(&row_values))?;
                     result_rows.push(row_values);
                 }
             }
@@ -2485,18 +3862,23 @@ impl ExecutionOperator for ExceptOperator {
 /// Recursive CTE operator - implements fixpoint iteration
 pub struct RecursiveCTEOperator {
     name: String,
-    base_case: Box<PhysicalPlan>,
-    recursive_case: Box<PhysicalPlan>,
+    base_case: Box<dyn ExecutionOperator>,
+    recursive_case: Box<dyn ExecutionOperator>,
     schema: Vec<PhysicalColumn>,
     context: ExecutionContext,
 }
 
 impl RecursiveCTEOperator {
-    pub fn new(rcte: &crate::planner::physical_plan::PhysicalRecursiveCTE, context: ExecutionContext) -> Self {
+    pub fn new(
+        rcte: &crate::planner::physical_plan::PhysicalRecursiveCTE,
+        base_case: Box<dyn ExecutionOperator>,
+        recursive_case: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
         Self {
             name: rcte.name.clone(),
-            base_case: rcte.base_case.clone(),
-            recursive_case: rcte.recursive_case.clone(),
+            base_case,
+            recursive_case,
             schema: rcte.schema.clone(),
             context,
         }
@@ -2505,16 +3887,13 @@ impl RecursiveCTEOperator {
 
 impl ExecutionOperator for RecursiveCTEOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
         use crate::types::Vector;
         use std::collections::HashSet;
 
-        let mut engine = ExecutionEngine::new(self.context.clone());
-
         // Step 1: Execute base case to get initial results
-        let mut base_stream = engine.execute(*self.base_case.clone())?;
+        let mut base_stream = self.base_case.execute()?;
         let mut all_rows = Vec::new();
-        let mut seen_rows: HashSet<String> = HashSet::new();
+        let mut seen_rows: HashSet<Vec<OrderedValue>> = HashSet::new();
 
         while let Some(chunk_result) = base_stream.next() {
             let chunk = chunk_result?;
@@ -2525,7 +3904,7 @@ impl ExecutionOperator for RecursiveCTEOperator {
                         .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
                     row_values.push(vector.get_value(row_idx)?);
                 }
-                let row_key = format!("{:?}", row_values);
+                let row_key = OrderedValue::row(&row_values);
                 if seen_rows.insert(row_key) {
                     all_rows.push(row_values);
                 }
@@ -2543,9 +3922,9 @@ impl ExecutionOperator for RecursiveCTEOperator {
 
         // Create the temporary table once before the loop
         let table_ref = {
-            let catalog_lock = self.context.catalog.write().unwrap();
+            let catalog_lock = self.context.catalog.write_or_err()?;
             if let Ok(schema_ref) = catalog_lock.get_schema("main") {
-                let mut schema_lock = schema_ref.write().unwrap();
+                let mut schema_lock = schema_ref.write_or_err()?;
 
                 // Drop any existing table with this name
                 let _ = schema_lock.drop_table(&self.name);
@@ -2561,6 +3940,9 @@ impl ExecutionOperator for RecursiveCTEOperator {
                         column_index: idx,
                         is_primary_key: false,
                         is_unique: false,
+                        default_expression: None,
+                        collation: None,
+                        max_length: None,
                     })
                     .collect();
 
@@ -2572,6 +3954,7 @@ impl ExecutionOperator for RecursiveCTEOperator {
                     primary_key: vec![],
                     statistics: crate::storage::table::TableStatistics::new(columns.len()),
                     is_temporary: true,
+                    constraints: vec![],
                 };
 
                 schema_lock.create_table(&table_info)?;
@@ -2588,13 +3971,13 @@ impl ExecutionOperator for RecursiveCTEOperator {
         for iteration in 0..max_iterations {
             // Clear and repopulate the table with working_table data
             {
-                let table_lock = table_ref.write().unwrap();
+                let table_lock = table_ref.write_or_err()?;
                 let data_ref = table_lock.get_data();
-                let mut data_lock = data_ref.write().unwrap();
+                let mut data_lock = data_ref.write_or_err()?;
 
                 // Clear existing data by clearing each column
                 for col in &data_lock.columns {
-                    let mut col_lock = col.write().unwrap();
+                    let mut col_lock = col.write_or_err()?;
                     col_lock.clear();
                 }
                 data_lock.row_count = 0;
@@ -2606,9 +3989,8 @@ impl ExecutionOperator for RecursiveCTEOperator {
                 }
             }
 
-            // Execute recursive case
-            let mut recursive_engine = ExecutionEngine::new(self.context.clone());
-            let mut recursive_stream = recursive_engine.execute(*self.recursive_case.clone())?;
+            // Re-run the recursive case against the new working table
+            let mut recursive_stream = self.recursive_case.execute()?;
             let mut new_rows = Vec::new();
 
             while let Some(chunk_result) = recursive_stream.next() {
@@ -2620,7 +4002,7 @@ impl ExecutionOperator for RecursiveCTEOperator {
                             .ok_or_else(|| PrismDBError::Execution(format!("Missing column {}", col_idx)))?;
                         row_values.push(vector.get_value(row_idx)?);
                     }
-                    let row_key = format!("{:?}", row_values);
+                    let row_key = OrderedValue::row(&row_values);
                     if seen_rows.insert(row_key) {
                         new_rows.push(row_values);
                     }
@@ -2640,9 +4022,9 @@ impl ExecutionOperator for RecursiveCTEOperator {
             if iteration >= max_iterations - 1 {
                 // Clean up temporary table
                 drop(table_ref);
-                let catalog_lock = self.context.catalog.write().unwrap();
+                let catalog_lock = self.context.catalog.write_or_err()?;
                 if let Ok(schema_ref) = catalog_lock.get_schema("main") {
-                    let mut schema_lock = schema_ref.write().unwrap();
+                    let mut schema_lock = schema_ref.write_or_err()?;
                     let _ = schema_lock.drop_table(&self.name);
                 }
                 return Err(PrismDBError::Execution(format!(
@@ -2655,9 +4037,9 @@ impl ExecutionOperator for RecursiveCTEOperator {
         // Clean up temporary table
         drop(table_ref);
         {
-            let catalog_lock = self.context.catalog.write().unwrap();
+            let catalog_lock = self.context.catalog.write_or_err()?;
             if let Ok(schema_ref) = catalog_lock.get_schema("main") {
-                let mut schema_lock = schema_ref.write().unwrap();
+                let mut schema_lock = schema_ref.write_or_err()?;
                 let _ = schema_lock.drop_table(&self.name);
             }
         }
@@ -2740,12 +4122,12 @@ impl ExecutionOperator for CreateMaterializedViewOperator {
         };
 
         // Get catalog and create materialized view
-        let catalog = self.context.catalog.write().unwrap();
+        let catalog = self.context.catalog.write_or_err()?;
         let schema_name = self.create_mv.schema_name.as_deref().unwrap_or("main");
         
         // Get or create schema
         if let Ok(schema_lock) = catalog.get_schema(schema_name) {
-            let mut schema = schema_lock.write().unwrap();
+            let mut schema = schema_lock.write_or_err()?;
 
             // Extract query as string (simplified for now)
             let query_str = format!("{:?}", self.create_mv.query);
@@ -2757,7 +4139,7 @@ impl ExecutionOperator for CreateMaterializedViewOperator {
                 refresh_strategy,
             )?;
         } else {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Schema, format!(
                 "Schema '{}' does not exist",
                 schema_name
             )));
@@ -2788,11 +4170,11 @@ impl DropMaterializedViewOperator {
 
 impl ExecutionOperator for DropMaterializedViewOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        let catalog = self.context.catalog.write().unwrap();
+        let catalog = self.context.catalog.write_or_err()?;
         let schema_name = self.drop_mv.schema_name.as_deref().unwrap_or("main");
         
         if let Ok(schema_lock) = catalog.get_schema(schema_name) {
-            let mut schema = schema_lock.write().unwrap();
+            let mut schema = schema_lock.write_or_err()?;
 
             if self.drop_mv.if_exists && !schema.view_exists(&self.drop_mv.view_name) {
                 return Ok(Box::new(SimpleDataChunkStream::empty()));
@@ -2800,7 +4182,7 @@ impl ExecutionOperator for DropMaterializedViewOperator {
 
             schema.drop_view(&self.drop_mv.view_name)?;
         } else if !self.drop_mv.if_exists {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Schema, format!(
                 "Schema '{}' does not exist",
                 schema_name
             )));
@@ -2817,16 +4199,19 @@ impl ExecutionOperator for DropMaterializedViewOperator {
 /// Refresh materialized view operator
 pub struct RefreshMaterializedViewOperator {
     refresh_mv: crate::planner::PhysicalRefreshMaterializedView,
+    query: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl RefreshMaterializedViewOperator {
     pub fn new(
         refresh_mv: crate::planner::PhysicalRefreshMaterializedView,
+        query: Box<dyn ExecutionOperator>,
         context: ExecutionContext,
     ) -> Self {
         Self {
             refresh_mv,
+            query,
             context,
         }
     }
@@ -2834,21 +4219,18 @@ impl RefreshMaterializedViewOperator {
 
 impl ExecutionOperator for RefreshMaterializedViewOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::ExecutionEngine;
-
         // Execute the query to get fresh data
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let result_chunks = engine.execute_collect(*self.refresh_mv.query.clone())?;
+        let result_chunks = self.query.execute()?.collect::<PrismDBResult<Vec<_>>>()?;
 
         // Update the materialized view with fresh data
-        let catalog = self.context.catalog.write().unwrap();
+        let catalog = self.context.catalog.write_or_err()?;
         let schema_name = self.refresh_mv.schema_name.as_deref().unwrap_or("main");
         
         if let Ok(schema_lock) = catalog.get_schema(schema_name) {
-            let mut schema = schema_lock.write().unwrap();
+            let mut schema = schema_lock.write_or_err()?;
             schema.refresh_materialized_view(&self.refresh_mv.view_name, result_chunks)?;
         } else {
-            return Err(PrismDBError::Catalog(format!(
+            return Err(PrismDBError::NotFound(ObjectKind::Schema, format!(
                 "Schema '{}' does not exist",
                 schema_name
             )));
@@ -7,6 +7,7 @@
 //! - Implements parallel operators: Scan, Join, Aggregate
 
 use crate::common::error::PrismDBResult;
+use crate::common::sync::MutexExt;
 use crate::types::DataChunk;
 use rayon::prelude::*;
 use std::sync::{Arc, Mutex};
@@ -84,8 +85,8 @@ impl MorselGenerator {
 
     /// Get the next morsel for processing
     pub fn get_next_morsel(&self) -> Option<Morsel> {
-        let mut offset = self.current_offset.lock().unwrap();
-        let mut id = self.next_id.lock().unwrap();
+        let mut offset = self.current_offset.lock_recovered();
+        let mut id = self.next_id.lock_recovered();
 
         if *offset >= self.total_rows {
             return None;
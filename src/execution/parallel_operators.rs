@@ -12,49 +12,29 @@
 //! - Cache-friendly: Partition sizes aligned with cache lines
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::allocator::{MemoryReservation, QueryArena};
+use crate::execution::hash_table::encode_key_value;
 use crate::execution::{ExecutionContext, ParallelHashTable};
 use crate::planner::{
     DataChunkStream, ExecutionOperator, PhysicalColumn, PhysicalHashJoin, PhysicalJoinType,
 };
+use crate::types::utils::adaptive_chunk_size;
 use crate::types::{DataChunk, Value, Vector};
 use rayon::prelude::*;
-use std::sync::Arc;
-
-/// Serialize a Value to a string for hash key (without Display formatting which adds quotes)
-fn value_to_key_string(value: &Value) -> String {
-    match value {
-        Value::Null => "NULL".to_string(),
-        Value::Boolean(b) => b.to_string(),
-        Value::TinyInt(i) => i.to_string(),
-        Value::SmallInt(i) => i.to_string(),
-        Value::Integer(i) => i.to_string(),
-        Value::BigInt(i) => i.to_string(),
-        Value::Float(f) => f.to_string(),
-        Value::Double(f) => f.to_string(),
-        Value::Varchar(s) => s.clone(), // Don't add quotes!
-        Value::Char(s) => s.clone(),
-        Value::Decimal { value, scale, .. } => {
-            let divisor = 10_i128.pow(*scale as u32);
-            let integer_part = value / divisor;
-            let fractional_part = (value % divisor).abs();
-            format!("{}.{:0width$}", integer_part, fractional_part, width = *scale as usize)
-        }
-        _ => format!("{:?}", value), // Fallback for other types
-    }
-}
+use std::collections::HashMap;
 
 /// Parallel Hash Join Operator
 ///
 /// Architecture:
 /// 1. Build Phase (Parallel):
 ///    - Execute right (build) side to produce chunks
-///    - Each thread processes chunks and inserts into partitioned hash table
+///    - Each thread processes chunks and inserts row references into partitioned hash table
 ///    - ParallelHashTable has 256 partitions for minimal contention
 ///
 /// 2. Probe Phase (Parallel):
 ///    - Execute left (probe) side to produce chunks
 ///    - Each thread probes chunks independently (lock-free reads)
-///    - Results are collected and merged
+///    - Matched rows are copied column by column from both sides into output chunks
 ///
 /// Performance characteristics:
 /// - Build: O(n) with p threads = O(n/p)
@@ -62,251 +42,110 @@ fn value_to_key_string(value: &Value) -> String {
 /// - Memory: O(n) for hash table
 pub struct ParallelHashJoinOperator {
     join: PhysicalHashJoin,
+    left: Box<dyn ExecutionOperator>,
+    right: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl ParallelHashJoinOperator {
-    pub fn new(join: PhysicalHashJoin, context: ExecutionContext) -> Self {
-        Self { join, context }
-    }
-
-    /// Extract join key values from a row given key column indices
-    fn extract_key_values(
-        chunk: &DataChunk,
-        row_idx: usize,
-        key_indices: &[usize],
-    ) -> PrismDBResult<Vec<Value>> {
-        let mut key_values = Vec::with_capacity(key_indices.len());
-        for &col_idx in key_indices {
-            let vector = chunk.get_vector(col_idx).ok_or_else(|| {
-                PrismDBError::InvalidValue(format!("Column {} not found in chunk", col_idx))
-            })?;
-            let value = vector.get_value(row_idx)?;
-            key_values.push(value);
+    pub fn new(
+        join: PhysicalHashJoin,
+        left: Box<dyn ExecutionOperator>,
+        right: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self {
+            join,
+            left,
+            right,
+            context,
         }
-        Ok(key_values)
     }
 
-    /// Build hash table from right (build) side in parallel
-    fn build_hash_table(
-        &self,
-        right_chunks: Vec<DataChunk>,
-        right_key_indices: Vec<usize>,
-    ) -> PrismDBResult<Arc<ParallelHashTable>> {
-        // Create parallel hash table with right key indices
-        let mut hash_table = ParallelHashTable::new(right_key_indices.clone());
-
-        // Build in parallel using Rayon
-        hash_table.build_parallel(right_chunks)?;
-
-        Ok(Arc::new(hash_table))
-    }
-
-    /// Probe hash table with left (probe) side chunk
-    /// Returns joined result chunks
-    fn probe_chunk(
-        &self,
-        left_chunk: &DataChunk,
-        hash_table: &ParallelHashTable,
-        left_key_indices: &[usize],
-        left_col_count: usize,
-        right_col_count: usize,
-    ) -> PrismDBResult<Vec<Vec<Value>>> {
-        let mut result_rows = Vec::new();
-
-        for row_idx in 0..left_chunk.len() {
-            // Extract left row values
-            let mut left_row = Vec::with_capacity(left_col_count);
-            for col_idx in 0..left_col_count {
-                let vector = left_chunk.get_vector(col_idx).ok_or_else(|| {
-                    PrismDBError::InvalidValue(format!("Left column {} not found", col_idx))
-                })?;
-                let value = vector.get_value(row_idx)?;
-                left_row.push(value);
-            }
-
-            // Extract key values for probing
-            let key_values = Self::extract_key_values(left_chunk, row_idx, left_key_indices)?;
-
-            // Probe hash table
-            let matches = hash_table.probe(&key_values)?;
-
-            if !matches.is_empty() {
-                // Found matches - emit joined rows
-                for right_row in &matches {
-                    let mut joined_row = left_row.clone();
-                    joined_row.extend(right_row.clone());
-                    result_rows.push(joined_row);
-                }
-            } else {
-                // No matches - handle based on join type
-                match self.join.join_type {
-                    PhysicalJoinType::Left => {
-                        // LEFT JOIN: emit left row with NULLs for right side
-                        let mut joined_row = left_row.clone();
-                        for _ in 0..right_col_count {
-                            joined_row.push(Value::Null);
-                        }
-                        result_rows.push(joined_row);
-                    }
-                    PhysicalJoinType::Anti => {
-                        // ANTI JOIN: emit left row only if no match
-                        result_rows.push(left_row.clone());
-                    }
-                    PhysicalJoinType::Inner | PhysicalJoinType::Semi => {
-                        // INNER/SEMI: skip rows without matches
-                    }
-                    _ => {
-                        // Other join types not yet implemented
-                    }
-                }
-            }
-
-            // For SEMI join, only emit left row once if there's a match
-            if self.join.join_type == PhysicalJoinType::Semi && !matches.is_empty() {
-                result_rows.push(left_row);
+    /// Run one side of the join and collect its non-empty chunks, reserving their memory
+    fn collect_chunks(
+        input: &dyn ExecutionOperator,
+        reservation: &MemoryReservation,
+    ) -> PrismDBResult<Vec<DataChunk>> {
+        let mut chunks = Vec::new();
+        for chunk_result in input.execute()? {
+            let chunk = chunk_result?;
+            if !chunk.is_empty() {
+                reservation.grow(chunk.get_size())?;
+                chunks.push(chunk);
             }
         }
-
-        Ok(result_rows)
-    }
-
-    /// Convert result rows to DataChunk
-    fn rows_to_chunk(&self, rows: Vec<Vec<Value>>) -> PrismDBResult<DataChunk> {
-        if rows.is_empty() {
-            return Ok(DataChunk::with_rows(0));
-        }
-
-        let num_columns = rows[0].len();
-        let mut data_chunk = DataChunk::with_rows(rows.len());
-
-        for col_idx in 0..num_columns {
-            // Collect all values for this column
-            let column_values: Vec<Value> = rows.iter().map(|row| row[col_idx].clone()).collect();
-
-            // Create vector from values
-            let vector = Vector::from_values(&column_values)?;
-            data_chunk.set_vector(col_idx, vector)?;
-        }
-
-        Ok(data_chunk)
+        Ok(chunks)
     }
 }
 
 impl ExecutionOperator for ParallelHashJoinOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::{ExecutionEngine, SimpleDataChunkStream};
-
-        // Step 1: Execute right (build) side and collect all chunks
-        let mut right_engine = ExecutionEngine::new(self.context.clone());
-        let right_plan = (*self.join.right).clone();
-        let mut right_stream = right_engine.execute(right_plan)?;
-
-        let mut right_chunks = Vec::new();
-        let mut right_col_count = 0;
-
-        while let Some(chunk_result) = right_stream.next() {
-            let chunk = chunk_result?;
-            if chunk.len() > 0 {
-                right_col_count = chunk.column_count();
-                right_chunks.push(chunk);
-            }
-        }
-
-        // Extract right key column indices from expressions
-        // Note: In the joined schema, left columns come first, then right columns
-        // Calculate left column count: the schema tells us the total, we know right_col_count
-        let total_col_count = self.join.schema.len();
-        let left_col_count_actual = total_col_count - right_col_count;
-
-        let right_key_indices: Vec<usize> = self.join.right_keys.iter()
-            .filter_map(|expr| {
-                expr.as_any()
-                    .downcast_ref::<crate::expression::ColumnRefExpression>()
-                    .map(|col_ref| {
-                        let joined_idx = col_ref.column_index();
-                        // Adjust index: in joined schema, right columns start at left_col_count
-                        if joined_idx >= left_col_count_actual {
-                            joined_idx - left_col_count_actual
-                        } else {
-                            joined_idx // Shouldn't happen for right keys
-                        }
-                    })
-            })
-            .collect();
-
-        // Step 2: Build hash table in parallel
-        let hash_table = self.build_hash_table(right_chunks, right_key_indices)?;
-
-        // Step 3: Execute left (probe) side and probe in parallel
-        let mut left_engine = ExecutionEngine::new(self.context.clone());
-        let left_plan = (*self.join.left).clone();
-        let mut left_stream = left_engine.execute(left_plan)?;
-
-        let mut left_chunks = Vec::new();
-        let mut left_col_count = 0;
-
-        while let Some(chunk_result) = left_stream.next() {
-            let chunk = chunk_result?;
-            if chunk.len() > 0 {
-                left_col_count = chunk.column_count();
-                left_chunks.push(chunk);
+        use crate::execution::SimpleDataChunkStream;
+
+        let left_col_count = self.join.left.schema().len();
+        let right_col_count = self.join.right.schema().len();
+
+        // Key expressions index the joined schema: left columns first, then right columns
+        let key_indices = |keys: &[crate::expression::expression::ExpressionRef], offset: usize| -> Vec<usize> {
+            keys.iter()
+                .filter_map(|expr| {
+                    expr.as_any()
+                        .downcast_ref::<crate::expression::ColumnRefExpression>()
+                        .map(|col_ref| {
+                            let joined_idx = col_ref.column_index();
+                            joined_idx.checked_sub(offset).unwrap_or(joined_idx)
+                        })
+                })
+                .collect()
+        };
+        let left_key_indices = key_indices(&self.join.left_keys, 0);
+        let right_key_indices = key_indices(&self.join.right_keys, left_col_count);
+
+        // Both sides and the joined output are held in memory against the query budget
+        let reservation = self.context.memory_reservation("HASH_JOIN");
+
+        // Step 1: Build the hash table from the right side in parallel. The build
+        // chunks are kept whole and the table stores row references into them.
+        let mut hash_table = ParallelHashTable::new(right_key_indices);
+        let build_chunks = Self::collect_chunks(self.right.as_ref(), &reservation)?;
+        if self.context.parallel_context.parallel_enabled {
+            hash_table.build_parallel(build_chunks)?;
+        } else {
+            // One chunk at a time, so rows with the same key match in input order
+            for chunk in build_chunks {
+                hash_table.build_from_chunk(chunk)?;
             }
         }
 
-        // Extract left key column indices from expressions
-        let left_key_indices: Vec<usize> = self.join.left_keys.iter()
-            .filter_map(|expr| {
-                expr.as_any()
-                    .downcast_ref::<crate::expression::ColumnRefExpression>()
-                    .map(|col_ref| col_ref.column_index())
-            })
-            .collect();
-
-        // Step 4: Probe chunks in parallel
-        let hash_table_ref = hash_table.clone();
-        let left_keys = left_key_indices.clone();
-
-        // Clone join type per chunk (needed for parallel map)
-        let join_obj = self.join.clone();
+        // Step 2: Probe with left chunks in parallel, gathering matched rows straight
+        // from both sides into output vectors
+        let left_chunks = Self::collect_chunks(self.left.as_ref(), &reservation)?;
+        let join_type = &self.join.join_type;
+        let build_columns = match join_type {
+            PhysicalJoinType::Semi | PhysicalJoinType::Anti => 0,
+            _ => right_col_count,
+        };
+        let chunk_size = adaptive_chunk_size(self.join.schema.iter().map(|c| &c.data_type));
 
-        let result_rows: Vec<Vec<Vec<Value>>> = left_chunks
+        let output: Vec<Vec<DataChunk>> = left_chunks
             .par_iter()
             .map(|chunk| {
-                // Each thread probes its chunk independently
-                let ht = hash_table_ref.clone();
-                let join_clone = join_obj.clone();
-                let ctx = self.context.clone();
-
-                let probe_result = Self::probe_chunk(
-                    &Self {
-                        join: join_clone,
-                        context: ctx,
-                    },
-                    chunk,
-                    &ht,
-                    &left_keys,
-                    left_col_count,
-                    right_col_count,
-                );
-
-                probe_result.unwrap_or_else(|_| Vec::new())
+                let matches = hash_table.probe_chunk(chunk, &left_key_indices, join_type)?;
+                let output = hash_table.gather(chunk, &matches, build_columns, chunk_size)?;
+                for output_chunk in &output {
+                    reservation.grow(output_chunk.get_size())?;
+                }
+                Ok(output)
             })
-            .collect();
-
-        // Step 5: Flatten results and convert to DataChunks
-        let mut all_rows = Vec::new();
-        for chunk_rows in result_rows {
-            all_rows.extend(chunk_rows);
-        }
+            .collect::<PrismDBResult<_>>()?;
 
-        if all_rows.is_empty() {
+        let chunks: Vec<DataChunk> = output.into_iter().flatten().collect();
+        if chunks.is_empty() {
             return Ok(Box::new(SimpleDataChunkStream::empty()));
         }
 
-        let result_chunk = self.rows_to_chunk(all_rows)?;
-
-        Ok(Box::new(SimpleDataChunkStream::new(vec![result_chunk])))
+        Ok(Box::new(SimpleDataChunkStream::new(chunks)))
     }
 
     fn schema(&self) -> Vec<PhysicalColumn> {
@@ -318,8 +157,8 @@ impl ExecutionOperator for ParallelHashJoinOperator {
 ///
 /// Architecture:
 /// 1. Thread-local Pre-aggregation:
-///    - Each thread maintains its own hash table
-///    - Process input chunks in parallel
+///    - Input chunks are split into one contiguous run per worker thread
+///    - Each worker aggregates its run into its own hash table
 ///    - No synchronization during aggregation
 ///
 /// 2. Global Merge Phase:
@@ -333,176 +172,245 @@ impl ExecutionOperator for ParallelHashJoinOperator {
 /// - Memory: O(k * t) for thread-local tables
 pub struct ParallelHashAggregateOperator {
     aggregate: crate::planner::PhysicalAggregate,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl ParallelHashAggregateOperator {
-    pub fn new(aggregate: crate::planner::PhysicalAggregate, context: ExecutionContext) -> Self {
-        Self { aggregate, context }
+    pub fn new(
+        aggregate: crate::planner::PhysicalAggregate,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { aggregate, input, context }
     }
 
-    /// Extract group key from a row
-    fn extract_group_key(
-        chunk: &DataChunk,
-        row_idx: usize,
+    /// Aggregate a run of chunks into a new thread-local group table
+    ///
+    /// Group and argument expressions are evaluated once per chunk. Each row's group
+    /// values are encoded into a reused buffer, and only a new group copies its key
+    /// into the query arena.
+    fn aggregate_chunks<'a>(
+        chunks: &[DataChunk],
         group_by: &[crate::expression::expression::ExpressionRef],
+        aggregates: &[crate::planner::PhysicalAggregateExpression],
         context: &ExecutionContext,
-    ) -> PrismDBResult<String> {
-        if group_by.is_empty() {
-            return Ok(String::from("__global__"));
-        }
+        arena: &'a QueryArena,
+        reservation: &MemoryReservation,
+    ) -> PrismDBResult<GroupTable<'a>> {
+        let mut table = GroupTable::default();
+        let mut key = Vec::new();
+        let mut group_values = Vec::new();
+
+        for chunk in chunks {
+            let group_vectors = group_by
+                .iter()
+                .map(|expr| expr.evaluate(chunk, context))
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            let inputs = AggregateInputs::evaluate(chunk, aggregates, context)?;
 
-        let mut key_parts = Vec::new();
-        for group_expr in group_by {
-            let result_vector = group_expr.evaluate(chunk, context)?;
-            let value = result_vector.get_value(row_idx)?;
-            // Use custom serialization without quotes
-            key_parts.push(value_to_key_string(&value));
-        }
-        Ok(key_parts.join("|"))
-    }
-
-    /// Parse a string value back to the correct Value type based on schema
-    fn parse_value_from_string(s: &str, logical_type: &crate::types::LogicalType) -> PrismDBResult<Value> {
-        use crate::types::LogicalType;
+            for row_idx in 0..chunk.len() {
+                key.clear();
+                group_values.clear();
+                for vector in &group_vectors {
+                    let value = vector.get_value(row_idx)?;
+                    encode_key_value(&context.grouping_collation.group_key(&value), &mut key);
+                    group_values.push(value);
+                }
 
-        // Handle NULL special case
-        if s == "NULL" {
-            return Ok(Value::Null);
-        }
+                let group_idx = match table.index.get(key.as_slice()) {
+                    Some(&idx) => idx,
+                    None => {
+                        reservation.grow(Group::estimated_size(&key, &group_values, aggregates.len()))?;
+                        table.insert(Group {
+                            key: arena.alloc_bytes(&key),
+                            values: group_values.clone(),
+                            states: Self::initial_states(aggregates, &context.grouping_collation)?,
+                        })
+                    }
+                };
 
-        match logical_type {
-            LogicalType::Boolean => {
-                s.parse::<bool>()
-                    .map(Value::Boolean)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as BOOLEAN", s)))
-            }
-            LogicalType::TinyInt => {
-                s.parse::<i8>()
-                    .map(Value::TinyInt)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as TINYINT", s)))
+                inputs.update(&mut table.groups[group_idx].states, row_idx)?;
             }
-            LogicalType::SmallInt => {
-                s.parse::<i16>()
-                    .map(Value::SmallInt)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as SMALLINT", s)))
-            }
-            LogicalType::Integer => {
-                s.parse::<i32>()
-                    .map(Value::Integer)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as INTEGER", s)))
-            }
-            LogicalType::BigInt => {
-                s.parse::<i64>()
-                    .map(Value::BigInt)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as BIGINT", s)))
-            }
-            LogicalType::Float => {
-                s.parse::<f32>()
-                    .map(Value::Float)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as FLOAT", s)))
-            }
-            LogicalType::Double => {
-                s.parse::<f64>()
-                    .map(Value::Double)
-                    .map_err(|_| PrismDBError::InvalidValue(format!("Cannot parse '{}' as DOUBLE", s)))
-            }
-            LogicalType::Varchar => Ok(Value::Varchar(s.to_string())),
-            LogicalType::Date => {
-                // Parse date string (assuming format YYYY-MM-DD)
-                Ok(Value::Varchar(s.to_string())) // TODO: proper date parsing
-            }
-            LogicalType::Timestamp => {
-                // Parse timestamp string
-                Ok(Value::Varchar(s.to_string())) // TODO: proper timestamp parsing
-            }
-            _ => Ok(Value::Varchar(s.to_string())),
         }
+
+        Ok(table)
     }
 
-    /// Process a single chunk and aggregate into thread-local hash table
-    fn aggregate_chunk(
-        chunk: &DataChunk,
-        group_by: &[crate::expression::expression::ExpressionRef],
+    fn initial_states(
         aggregates: &[crate::planner::PhysicalAggregateExpression],
-        context: &ExecutionContext,
-    ) -> PrismDBResult<std::collections::HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>>> {
-        use std::collections::HashMap;
-
-        let mut local_ht: HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>> =
-            HashMap::new();
+        collation: &crate::types::Collation,
+    ) -> PrismDBResult<Vec<Box<dyn crate::expression::AggregateState>>> {
+        aggregates
+            .iter()
+            .map(|agg_expr| agg_expr.create_state(collation))
+            .collect()
+    }
+}
 
-        for row_idx in 0..chunk.len() {
-            // Extract group key
-            let group_key = Self::extract_group_key(chunk, row_idx, group_by, context)?;
+/// What each aggregate of an aggregation reads from one input chunk: its argument,
+/// its FILTER and its ORDER BY keys, evaluated once for the whole chunk
+pub(crate) struct AggregateInputs {
+    arguments: Vec<Option<Vector>>,
+    filters: Vec<Option<Vector>>,
+    sort_keys: Vec<Vec<Vector>>,
+}
 
-            // Get or create aggregate states for this group
-            let states = local_ht.entry(group_key).or_insert_with(|| {
-                aggregates
+impl AggregateInputs {
+    pub(crate) fn evaluate(
+        chunk: &DataChunk,
+        aggregates: &[crate::planner::PhysicalAggregateExpression],
+        context: &ExecutionContext,
+    ) -> PrismDBResult<Self> {
+        let arguments = aggregates
+            .iter()
+            .map(|agg_expr| {
+                agg_expr
+                    .arguments
+                    .first()
+                    .map(|arg| arg.evaluate(chunk, context))
+                    .transpose()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        let filters = aggregates
+            .iter()
+            .map(|agg_expr| {
+                agg_expr
+                    .filter
+                    .as_ref()
+                    .map(|filter| filter.evaluate(chunk, context))
+                    .transpose()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        let sort_keys = aggregates
+            .iter()
+            .map(|agg_expr| {
+                agg_expr
+                    .order_by
                     .iter()
-                    .map(|agg_expr| {
-                        crate::expression::create_aggregate_state(&agg_expr.function_name)
-                            .unwrap_or_else(|_| {
-                                Box::new(crate::expression::CountState::new())
-                            })
-                    })
-                    .collect()
-            });
-
-            // Update each aggregate state
-            for (agg_idx, agg_expr) in aggregates.iter().enumerate() {
-                // Evaluate the aggregate's argument expression
-                let arg_value = if agg_expr.arguments.is_empty() {
-                    // COUNT(*) - no arguments
-                    Value::integer(1)
-                } else {
-                    let result_vector = agg_expr.arguments[0].evaluate(chunk, context)?;
-                    result_vector.get_value(row_idx)?
-                };
+                    .map(|sort| sort.expression.evaluate(chunk, context))
+                    .collect::<PrismDBResult<Vec<_>>>()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        Ok(Self {
+            arguments,
+            filters,
+            sort_keys,
+        })
+    }
 
-                // Update the aggregate state
-                states[agg_idx].update(&arg_value)?;
+    /// Add row `row_idx` of the chunk to a group's states, one per aggregate
+    pub(crate) fn update(
+        &self,
+        states: &mut [Box<dyn crate::expression::AggregateState>],
+        row_idx: usize,
+    ) -> PrismDBResult<()> {
+        for (((state, argument), filter), sort_keys) in states
+            .iter_mut()
+            .zip(&self.arguments)
+            .zip(&self.filters)
+            .zip(&self.sort_keys)
+        {
+            // Rows the FILTER rejects (false or NULL) leave the state unchanged
+            if let Some(filter) = filter {
+                if filter.get_value(row_idx)? != Value::Boolean(true) {
+                    continue;
+                }
+            }
+            let arg_value = match argument {
+                Some(vector) => vector.get_value(row_idx)?,
+                // COUNT(*) - no arguments
+                None => Value::integer(1),
+            };
+            if sort_keys.is_empty() {
+                state.update(&arg_value)?;
+            } else {
+                let keys = sort_keys
+                    .iter()
+                    .map(|vector| vector.get_value(row_idx))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                state.update_ordered(&arg_value, keys)?;
             }
         }
+        Ok(())
+    }
+}
+
+/// Groups of one aggregation, keyed by their encoded group values
+///
+/// Keys live in the query arena, so a table can only be used while the arena is borrowed.
+#[derive(Default)]
+struct GroupTable<'a> {
+    index: HashMap<&'a [u8], usize>,
+    /// Groups in first-seen order
+    groups: Vec<Group<'a>>,
+}
+
+struct Group<'a> {
+    key: &'a [u8],
+    values: Vec<Value>,
+    states: Vec<Box<dyn crate::expression::AggregateState>>,
+}
 
-        Ok(local_ht)
+/// Rough heap size of one boxed aggregate state
+const AGGREGATE_STATE_SIZE: usize = 64;
+
+impl Group<'_> {
+    /// Approximate bytes a new group holds, including its index entry
+    fn estimated_size(key: &[u8], values: &[Value], num_aggregates: usize) -> usize {
+        std::mem::size_of::<Group>()
+            + std::mem::size_of::<(&[u8], usize)>()
+            + key.len()
+            + values.iter().map(Value::memory_usage).sum::<usize>()
+            + num_aggregates * AGGREGATE_STATE_SIZE
+    }
+}
+
+impl<'a> GroupTable<'a> {
+    fn insert(&mut self, group: Group<'a>) -> usize {
+        let idx = self.groups.len();
+        self.index.insert(group.key, idx);
+        self.groups.push(group);
+        idx
     }
 
-    /// Merge two hash tables
-    fn merge_hash_tables(
-        mut global_ht: std::collections::HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>>,
-        local_ht: std::collections::HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>>,
-    ) -> PrismDBResult<std::collections::HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>>> {
-        for (key, local_states) in local_ht {
-            if let Some(global_states) = global_ht.get_mut(&key) {
-                // Merge states for existing group
-                for (idx, local_state) in local_states.into_iter().enumerate() {
-                    global_states[idx].merge(local_state)?;
+    /// Merge another table's groups into this one
+    fn merge(&mut self, other: GroupTable<'a>) -> PrismDBResult<()> {
+        for group in other.groups {
+            match self.index.get(group.key) {
+                Some(&idx) => {
+                    let states = &mut self.groups[idx].states;
+                    for (state, local_state) in states.iter_mut().zip(group.states) {
+                        state.merge(local_state)?;
+                    }
+                }
+                None => {
+                    self.insert(group);
                 }
-            } else {
-                // New group - insert directly
-                global_ht.insert(key, local_states);
             }
         }
-        Ok(global_ht)
+        Ok(())
     }
 }
 
 impl ExecutionOperator for ParallelHashAggregateOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::{ExecutionEngine, SimpleDataChunkStream};
-        use std::collections::HashMap;
-        use std::sync::Arc;
+        use crate::execution::SimpleDataChunkStream;
 
-        // Execute the input plan and collect all chunks
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.aggregate.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
+        // Input chunks and groups are held in memory against the query budget
+        let reservation = self.context.memory_reservation(if self.aggregate.group_by.is_empty() {
+            "AGGREGATE"
+        } else {
+            "HASH_AGGREGATE"
+        });
 
+        // Execute the input operator and collect all chunks
+        let mut input_stream = self.input.execute()?;
         let mut input_chunks = Vec::new();
         while let Some(chunk_result) = input_stream.next() {
             let chunk = chunk_result?;
             if chunk.len() > 0 {
+                reservation.grow(chunk.get_size())?;
                 input_chunks.push(chunk);
             }
         }
@@ -513,8 +421,7 @@ impl ExecutionOperator for ParallelHashAggregateOperator {
                 // No GROUP BY - return single row with initial aggregate values
                 let mut result_chunk = DataChunk::with_rows(1);
                 for (col_idx, agg_expr) in self.aggregate.aggregates.iter().enumerate() {
-                    let state =
-                        crate::expression::create_aggregate_state(&agg_expr.function_name)?;
+                    let state = agg_expr.create_state(&self.context.grouping_collation)?;
                     let result_value = state.finalize()?;
                     let vector = Vector::from_values(&[result_value])?;
                     result_chunk.set_vector(col_idx, vector)?;
@@ -526,37 +433,39 @@ impl ExecutionOperator for ParallelHashAggregateOperator {
         }
 
         // Phase 1: Thread-local pre-aggregation (parallel)
-        let group_by = Arc::new(self.aggregate.group_by.clone());
-        let aggregates = Arc::new(self.aggregate.aggregates.clone());
-        let context = self.context.clone();
-
-        let local_hts: Vec<HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>>> =
-            input_chunks
-                .par_iter()
-                .map(|chunk| {
-                    let gb = group_by.clone();
-                    let aggs = aggregates.clone();
-                    Self::aggregate_chunk(chunk, &gb[..], &aggs[..], &context)
-                        .unwrap_or_else(|_| HashMap::new())
-                })
-                .collect();
-
-        // Phase 2: Global merge (sequential, but fast)
-        let mut global_ht: HashMap<String, Vec<Box<dyn crate::expression::AggregateState>>> =
-            HashMap::new();
-
-        for local_ht in local_hts {
-            global_ht = Self::merge_hash_tables(global_ht, local_ht)?;
+        let group_by = &self.aggregate.group_by;
+        let aggregates = &self.aggregate.aggregates;
+        let context = &self.context;
+        let arena: &QueryArena = &self.context.arena;
+
+        let parallel = &self.context.parallel_context;
+        let workers = if parallel.parallel_enabled {
+            parallel.num_threads.clamp(1, input_chunks.len())
+        } else {
+            1
+        };
+        let local_tables = input_chunks
+            .par_chunks(input_chunks.len().div_ceil(workers))
+            .map(|chunks| {
+                Self::aggregate_chunks(chunks, group_by, aggregates, context, arena, &reservation)
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+
+        // Phase 2: Global merge (sequential, but fast). Tables are merged in
+        // input order, so groups keep their first-seen order.
+        let mut local_tables = local_tables.into_iter();
+        let mut global_table = local_tables.next().unwrap_or_default();
+        for local_table in local_tables {
+            global_table.merge(local_table)?;
         }
 
-        if global_ht.is_empty() {
+        if global_table.groups.is_empty() {
             // No groups after aggregation
             if self.aggregate.group_by.is_empty() {
                 // No GROUP BY - return single row with initial values
                 let mut result_chunk = DataChunk::with_rows(1);
                 for (col_idx, agg_expr) in self.aggregate.aggregates.iter().enumerate() {
-                    let state =
-                        crate::expression::create_aggregate_state(&agg_expr.function_name)?;
+                    let state = agg_expr.create_state(&self.context.grouping_collation)?;
                     let result_value = state.finalize()?;
                     let vector = Vector::from_values(&[result_value])?;
                     result_chunk.set_vector(col_idx, vector)?;
@@ -567,41 +476,39 @@ impl ExecutionOperator for ParallelHashAggregateOperator {
             }
         }
 
-        // Phase 3: Convert hash table to result chunk
-        let num_groups = global_ht.len();
-        let _num_columns = self.aggregate.group_by.len() + self.aggregate.aggregates.len();
+        // Phase 3: Convert group table to result chunk
+        let num_groups = global_table.groups.len();
         let mut result_chunk = DataChunk::with_rows(num_groups);
 
         // Build columns for GROUP BY expressions
-        for (group_col_idx, _group_expr) in self.aggregate.group_by.iter().enumerate() {
-            let mut group_values = Vec::new();
-
+        for group_col_idx in 0..self.aggregate.group_by.len() {
             // Get the correct type from schema
             let expected_type = &self.aggregate.schema[group_col_idx].data_type;
 
-            for group_key in global_ht.keys() {
-                let key_parts: Vec<&str> = group_key.split('|').collect();
-                if group_col_idx < key_parts.len() {
-                    // Parse value back to correct type based on schema
-                    let value = Self::parse_value_from_string(key_parts[group_col_idx], expected_type)?;
-                    group_values.push(value);
-                } else {
-                    group_values.push(Value::Null);
-                }
-            }
+            let group_values = global_table
+                .groups
+                .iter()
+                .map(|group| {
+                    let value = &group.values[group_col_idx];
+                    if value.is_null() || value.get_type() == *expected_type {
+                        Ok(value.clone())
+                    } else {
+                        value.cast_to(expected_type)
+                    }
+                })
+                .collect::<PrismDBResult<Vec<_>>>()?;
             let vector = Vector::from_values(&group_values)?;
             result_chunk.set_vector(group_col_idx, vector)?;
         }
 
         // Build columns for aggregate results
-        for (agg_idx, _agg_expr) in self.aggregate.aggregates.iter().enumerate() {
+        for agg_idx in 0..self.aggregate.aggregates.len() {
             let col_idx = self.aggregate.group_by.len() + agg_idx;
-            let mut agg_values = Vec::new();
-
-            for states in global_ht.values() {
-                let result_value = states[agg_idx].finalize()?;
-                agg_values.push(result_value);
-            }
+            let agg_values = global_table
+                .groups
+                .iter()
+                .map(|group| group.states[agg_idx].finalize())
+                .collect::<PrismDBResult<Vec<_>>>()?;
 
             let vector = Vector::from_values(&agg_values)?;
             result_chunk.set_vector(col_idx, vector)?;
@@ -627,138 +534,181 @@ impl ExecutionOperator for ParallelHashAggregateOperator {
 /// - Space: O(n) for materialized data
 /// - Cache-friendly: locality-preserving partitioning
 ///
+/// A sort with a limit is a Top-N: whenever the buffer holds twice the limit it
+/// drops all but the first `limit` rows, so it holds O(limit) rows.
+///
 /// Note: For very large datasets, PrismDB uses external merge sort.
 /// This implementation uses in-memory parallel sort.
 pub struct ParallelSortOperator {
     sort: crate::planner::PhysicalSort,
+    input: Box<dyn ExecutionOperator>,
     context: ExecutionContext,
 }
 
 impl ParallelSortOperator {
-    pub fn new(sort: crate::planner::PhysicalSort, context: ExecutionContext) -> Self {
-        Self { sort, context }
+    pub fn new(
+        sort: crate::planner::PhysicalSort,
+        input: Box<dyn ExecutionOperator>,
+        context: ExecutionContext,
+    ) -> Self {
+        Self { sort, input, context }
     }
 
-    /// Compare two values (simple comparison for sorting)
-    fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
+    /// Sort keys as column indices; non-column expressions are skipped
+    fn sort_keys(&self, num_columns: usize) -> Vec<(usize, &crate::planner::PhysicalSortExpression)> {
+        use crate::expression::expression::ColumnRefExpression;
+        self.sort
+            .expressions
+            .iter()
+            .filter_map(|sort_expr| {
+                sort_expr
+                    .expression
+                    .as_any()
+                    .downcast_ref::<ColumnRefExpression>()
+                    .map(|col_ref| (col_ref.column_index(), sort_expr))
+            })
+            .filter(|(column_idx, _)| *column_idx < num_columns)
+            .collect()
+    }
+
+    /// Compare rows `a` and `b` of the row-major `values` by `sort_keys`
+    fn compare_rows(
+        values: &[Value],
+        num_columns: usize,
+        sort_keys: &[(usize, &crate::planner::PhysicalSortExpression)],
+        a: usize,
+        b: usize,
+    ) -> std::cmp::Ordering {
         use std::cmp::Ordering;
 
-        match (a, b) {
-            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
-            (Value::BigInt(a), Value::BigInt(b)) => a.cmp(b),
-            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
-            (Value::Double(a), Value::Double(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
-            (Value::Varchar(a), Value::Varchar(b)) => a.cmp(b),
-            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
-            (Value::Date(a), Value::Date(b)) => a.cmp(b),
-            (Value::Time(a), Value::Time(b)) => a.cmp(b),
-            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
-            _ => Ordering::Equal,
+        for (column_idx, sort_expr) in sort_keys {
+            let val_a = &values[a * num_columns + column_idx];
+            let val_b = &values[b * num_columns + column_idx];
+
+            // NULL placement is absolute; ASC/DESC only applies to non-NULL values
+            let final_cmp = match (val_a, val_b) {
+                (Value::Null, Value::Null) => Ordering::Equal,
+                (Value::Null, _) => {
+                    if sort_expr.nulls_first {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    }
+                }
+                (_, Value::Null) => {
+                    if sort_expr.nulls_first {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Less
+                    }
+                }
+                _ => {
+                    let ordering = sort_expr
+                        .collation
+                        .as_ref()
+                        .and_then(|collation| collation.compare_values(val_a, val_b))
+                        .unwrap_or_else(|| val_a.total_cmp(val_b));
+                    if sort_expr.ascending {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                }
+            };
+
+            if final_cmp != Ordering::Equal {
+                return final_cmp;
+            }
         }
+
+        Ordering::Equal
+    }
+
+    /// Keep only the first `limit` rows of the row-major `values`, in no particular order
+    fn keep_first(&self, values: Vec<Value>, num_columns: usize, limit: usize) -> Vec<Value> {
+        let sort_keys = self.sort_keys(num_columns);
+        let mut order: Vec<usize> = (0..values.len() / num_columns).collect();
+        if limit < order.len() {
+            order.select_nth_unstable_by(limit, |&a, &b| {
+                Self::compare_rows(&values, num_columns, &sort_keys, a, b)
+            });
+            order.truncate(limit);
+        }
+        order
+            .iter()
+            .flat_map(|&row| values[row * num_columns..(row + 1) * num_columns].iter().cloned())
+            .collect()
     }
 }
 
 impl ExecutionOperator for ParallelSortOperator {
     fn execute(&self) -> PrismDBResult<Box<dyn DataChunkStream>> {
-        use crate::execution::{ExecutionEngine, SimpleDataChunkStream};
-
-        // Execute the input plan and collect all rows
-        let mut engine = ExecutionEngine::new(self.context.clone());
-        let input_plan = (*self.sort.input).clone();
-        let mut input_stream = engine.execute(input_plan)?;
-
-        // Collect all rows from input
-        let mut all_rows: Vec<Vec<Value>> = Vec::new();
+        use crate::execution::SimpleDataChunkStream;
+
+        // Collect all rows into one row-major buffer instead of a Vec per row,
+        // reserving the buffered values against the query budget
+        let operator = if self.sort.limit.is_some() { "TOP_N" } else { "ORDER_BY" };
+        let reservation = self.context.memory_reservation(operator);
+        let mut input_stream = self.input.execute()?;
+        let mut values: Vec<Value> = Vec::new();
         let mut num_columns = 0;
+        let mut input_rows = 0;
+        let mut held = 0;
 
         while let Some(chunk_result) = input_stream.next() {
             let chunk = chunk_result?;
             num_columns = chunk.column_count();
+            input_rows += chunk.len();
+            values.reserve(chunk.len() * num_columns);
 
-            // Collect all rows from this chunk
+            let start = values.len();
             for row_idx in 0..chunk.len() {
-                let mut row_values = Vec::new();
                 for col_idx in 0..num_columns {
                     let vector = chunk.get_vector(col_idx).ok_or_else(|| {
                         PrismDBError::InvalidValue(format!("Column {} not found", col_idx))
                     })?;
-                    let value = vector.get_value(row_idx)?;
-                    row_values.push(value);
+                    values.push(vector.get_value(row_idx)?);
                 }
-                all_rows.push(row_values);
             }
-        }
-
-        if all_rows.is_empty() {
-            return Ok(Box::new(SimpleDataChunkStream::empty()));
-        }
-
-        // Parallel sort using Rayon
-        // Create comparison function that can be called from parallel context
-        let sort_exprs = Arc::new(self.sort.expressions.clone());
-
-        all_rows.par_sort_unstable_by(|a, b| {
-            // Replicate comparison logic for parallel sort
-            for sort_expr in sort_exprs.iter() {
-                // Extract the actual column index from the sort expression
-                use crate::expression::expression::ColumnRefExpression;
-
-                let column_idx = if let Some(col_ref) = sort_expr.expression.as_any().downcast_ref::<ColumnRefExpression>() {
-                    col_ref.column_index()
-                } else {
-                    // For non-column expressions, skip this sort expression
-                    continue;
-                };
-
-                if column_idx >= a.len() || column_idx >= b.len() {
-                    continue;
-                }
-
-                let val_a = &a[column_idx];
-                let val_b = &b[column_idx];
-
-                use std::cmp::Ordering;
-
-                let cmp_result = match (val_a, val_b) {
-                    (Value::Null, Value::Null) => Ordering::Equal,
-                    (Value::Null, _) => {
-                        if sort_expr.nulls_first {
-                            Ordering::Less
-                        } else {
-                            Ordering::Greater
-                        }
-                    }
-                    (_, Value::Null) => {
-                        if sort_expr.nulls_first {
-                            Ordering::Greater
-                        } else {
-                            Ordering::Less
-                        }
-                    }
-                    _ => Self::compare_values(val_a, val_b)
-                };
-
-                let final_cmp = if sort_expr.ascending {
-                    cmp_result
-                } else {
-                    cmp_result.reverse()
-                };
+            held += values[start..].iter().map(Value::memory_usage).sum::<usize>();
+            if held > reservation.size() {
+                reservation.grow(held - reservation.size())?;
+            }
 
-                if final_cmp != Ordering::Equal {
-                    return final_cmp;
+            if let Some(limit) = self.sort.limit {
+                if num_columns > 0 && values.len() / num_columns >= limit.saturating_mul(2).max(1) {
+                    values = self.keep_first(values, num_columns, limit);
+                    held = values.iter().map(Value::memory_usage).sum();
                 }
             }
+        }
 
-            std::cmp::Ordering::Equal
-        });
+        // Rows without columns, like the single row of a SELECT without FROM, are all alike
+        if num_columns == 0 && input_rows > 0 {
+            let rows = self.sort.limit.map_or(input_rows, |limit| input_rows.min(limit));
+            return Ok(Box::new(SimpleDataChunkStream::new(vec![DataChunk::with_rows(rows)])));
+        }
+        if values.is_empty() || num_columns == 0 {
+            return Ok(Box::new(SimpleDataChunkStream::empty()));
+        }
+        let num_rows = values.len() / num_columns;
+
+        // Parallel sort of row indices using Rayon
+        let sort_keys = self.sort_keys(num_columns);
+        let mut order: Vec<usize> = (0..num_rows).collect();
+        order.par_sort_unstable_by(|&a, &b| Self::compare_rows(&values, num_columns, &sort_keys, a, b));
+        if let Some(limit) = self.sort.limit {
+            order.truncate(limit);
+        }
 
         // Convert sorted rows back to DataChunk
-        let num_rows = all_rows.len();
-        let mut result_chunk = DataChunk::with_rows(num_rows);
+        let mut result_chunk = DataChunk::with_rows(order.len());
 
         for col_idx in 0..num_columns {
-            let column_values: Vec<Value> =
-                all_rows.iter().map(|row| row[col_idx].clone()).collect();
+            let column_values: Vec<Value> = order
+                .iter()
+                .map(|&row| values[row * num_columns + col_idx].clone())
+                .collect();
 
             let vector = Vector::from_values(&column_values)?;
             result_chunk.set_vector(col_idx, vector)?;
@@ -777,6 +727,8 @@ impl ExecutionOperator for ParallelSortOperator {
 mod tests {
     use super::*;
     use crate::catalog::Catalog;
+    use crate::execution::{ExecutionEngine, SimpleDataChunkStream};
+    use crate::planner::PhysicalPlan;
     use crate::storage::TransactionManager;
     use crate::types::Value;
     use std::sync::{Arc, RwLock};
@@ -808,13 +760,18 @@ mod tests {
             schema: vec![],
         };
 
-        let _operator = ParallelHashJoinOperator::new(join, context);
+        let _operator = ParallelHashJoinOperator::new(
+            join,
+            Box::new(SimpleDataChunkStream::empty()),
+            Box::new(SimpleDataChunkStream::empty()),
+            context,
+        );
 
         Ok(())
     }
 
     #[test]
-    fn test_extract_key_values() -> PrismDBResult<()> {
+    fn test_encode_row_key() -> PrismDBResult<()> {
         let mut chunk = DataChunk::with_rows(3);
         chunk.set_vector(
             0,
@@ -824,56 +781,109 @@ mod tests {
             1,
             Vector::from_values(&[
                 Value::Varchar("a".to_string()),
-                Value::Varchar("b".to_string()),
+                Value::Null,
                 Value::Varchar("c".to_string()),
             ])?,
         )?;
 
-        // Extract keys from row 1, columns [0, 1]
-        let keys =
-            ParallelHashJoinOperator::extract_key_values(&chunk, 1, &[0, 1])?;
+        // Encode keys from row 0, columns [0, 1]
+        let mut key = Vec::new();
+        assert!(ParallelHashTable::encode_row_key(&chunk, 0, &[0, 1], &mut key)?);
+        let mut expected = Vec::new();
+        encode_key_value(&Value::integer(1), &mut expected);
+        encode_key_value(&Value::Varchar("a".to_string()), &mut expected);
+        assert_eq!(key, expected);
 
-        assert_eq!(keys.len(), 2);
-        assert_eq!(keys[0], Value::integer(2));
-        assert_eq!(keys[1], Value::Varchar("b".to_string()));
+        // A NULL key never matches
+        assert!(!ParallelHashTable::encode_row_key(&chunk, 1, &[0, 1], &mut key)?);
 
         Ok(())
     }
 
+    fn values_plan(rows: &[[Value; 2]], names: [&str; 2]) -> PrismDBResult<PhysicalPlan> {
+        use crate::expression::expression::ConstantExpression;
+
+        let values = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|value| {
+                        Ok(Arc::new(ConstantExpression::new(value.clone())?)
+                            as crate::expression::expression::ExpressionRef)
+                    })
+                    .collect::<PrismDBResult<Vec<_>>>()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        let schema = names
+            .iter()
+            .zip(&rows[0])
+            .map(|(name, value)| PhysicalColumn::new(name.to_string(), value.get_type()))
+            .collect();
+        Ok(PhysicalPlan::Values(crate::planner::PhysicalValues { values, schema }))
+    }
+
     #[test]
-    fn test_rows_to_chunk() -> PrismDBResult<()> {
-        let context = create_test_context();
+    fn test_parallel_hash_join_execute() -> PrismDBResult<()> {
+        use crate::expression::ColumnRefExpression;
+        use crate::types::LogicalType;
+
+        let left = values_plan(
+            &[
+                [Value::integer(1), Value::Varchar("one".to_string())],
+                [Value::integer(2), Value::Varchar("two".to_string())],
+                [Value::integer(3), Value::Varchar("three".to_string())],
+            ],
+            ["id", "name"],
+        )?;
+        let right = values_plan(
+            &[
+                [Value::integer(2), Value::Double(2.5)],
+                [Value::integer(1), Value::Double(1.5)],
+                [Value::integer(2), Value::Double(2.25)],
+            ],
+            ["key", "amount"],
+        )?;
+        let schema = left.schema().into_iter().chain(right.schema()).collect();
         let join = PhysicalHashJoin {
-            left: Box::new(crate::planner::PhysicalPlan::EmptyResult(
-                crate::planner::PhysicalEmptyResult { schema: vec![] },
-            )),
-            right: Box::new(crate::planner::PhysicalPlan::EmptyResult(
-                crate::planner::PhysicalEmptyResult { schema: vec![] },
-            )),
-            join_type: PhysicalJoinType::Inner,
-            left_keys: vec![],
-            right_keys: vec![],
+            left: Box::new(left),
+            right: Box::new(right),
+            join_type: PhysicalJoinType::Left,
+            left_keys: vec![Arc::new(ColumnRefExpression::new(
+                0,
+                "id".to_string(),
+                LogicalType::Integer,
+            ))],
+            right_keys: vec![Arc::new(ColumnRefExpression::new(
+                2,
+                "key".to_string(),
+                LogicalType::Integer,
+            ))],
             condition: None,
-            schema: vec![],
+            schema,
         };
 
-        let operator = ParallelHashJoinOperator::new(join, context);
-
-        let rows = vec![
-            vec![Value::integer(1), Value::Varchar("a".to_string())],
-            vec![Value::integer(2), Value::Varchar("b".to_string())],
-            vec![Value::integer(3), Value::Varchar("c".to_string())],
-        ];
-
-        let chunk = operator.rows_to_chunk(rows)?;
-
-        assert_eq!(chunk.len(), 3);
-        assert_eq!(chunk.column_count(), 2);
+        let engine = ExecutionEngine::new(create_test_context());
+        let operator = engine.create_operator(PhysicalPlan::HashJoin(join))?;
+        let mut rows = Vec::new();
+        for chunk in operator.execute()? {
+            let chunk = chunk?;
+            for row in 0..chunk.len() {
+                let values = (0..chunk.column_count())
+                    .map(|col| chunk.get_vector(col).unwrap().get_value(row))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                rows.push(values);
+            }
+        }
 
-        let col0 = chunk.get_vector(0).unwrap();
-        assert_eq!(col0.get_value(0)?, Value::integer(1));
-        assert_eq!(col0.get_value(1)?, Value::integer(2));
-        assert_eq!(col0.get_value(2)?, Value::integer(3));
+        assert_eq!(
+            rows,
+            vec![
+                vec![Value::integer(1), Value::Varchar("one".to_string()), Value::integer(1), Value::Double(1.5)],
+                vec![Value::integer(2), Value::Varchar("two".to_string()), Value::integer(2), Value::Double(2.5)],
+                vec![Value::integer(2), Value::Varchar("two".to_string()), Value::integer(2), Value::Double(2.25)],
+                vec![Value::integer(3), Value::Varchar("three".to_string()), Value::Null, Value::Null],
+            ]
+        );
 
         Ok(())
     }
@@ -3,6 +3,7 @@
 //! Implements pipeline-based execution for vectorized query processing.
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::MutexExt;
 use crate::execution::context::ExecutionContext;
 use crate::planner::{DataChunkStream, PhysicalPlan};
 use crate::types::DataChunk;
@@ -187,7 +188,6 @@ impl PipelineSource {
 pub struct TableScanSource {
     #[allow(dead_code)]
     scan: crate::planner::PhysicalTableScan,
-    #[allow(dead_code)]
     context: ExecutionContext,
     table_data: Option<Arc<RwLock<crate::storage::TableData>>>,
     current_offset: usize,
@@ -205,21 +205,15 @@ impl TableScanSource {
                 .read()
                 .map_err(|_| PrismDBError::Internal("Failed to lock catalog".to_string()))?;
 
-            // Get the schema (assuming "main" schema for now)
-            let schema_arc = catalog.get_schema("main")?;
-
-            let schema = schema_arc
-                .read()
-                .map_err(|_| PrismDBError::Internal("Failed to lock schema".to_string()))?;
-
-            // Get the table
-            let table_arc = schema.get_table(&scan.table_name)?;
+            // Get the table from the first schema on the search path that has it
+            let table_arc = catalog.find_table(&scan.table_name)?;
 
             // Get the table data - this returns an Arc that we can keep
             let table = table_arc
                 .read()
                 .map_err(|_| PrismDBError::Internal("Failed to lock table".to_string()))?;
-            table.get_data()
+            context.record_table_read(&scan.table_name);
+            context.table_data(table.get_data())?
         };
 
         Ok(Self {
@@ -240,17 +234,18 @@ impl TableScanSource {
             .read()
             .map_err(|_| PrismDBError::Internal("Failed to lock table data".to_string()))?;
 
-        if self.current_offset >= table_guard.row_count() {
-            return Ok(None);
-        }
+        // Offsets are physical; skip ranges holding no row the statement sees
+        while self.current_offset < table_guard.physical_row_count() {
+            let end = std::cmp::min(self.current_offset + chunk_size, table_guard.physical_row_count());
+            let chunk = table_guard.create_chunk_range(&self.context.read_view, self.current_offset, end)?;
+            self.context.record_rows_scanned(chunk.len())?;
 
-        let actual_size = std::cmp::min(chunk_size, table_guard.row_count() - self.current_offset);
-
-        // Use the existing create_chunk method from TableData!
-        let chunk = table_guard.create_chunk(self.current_offset, actual_size)?;
-
-        self.current_offset += actual_size;
-        Ok(Some(chunk))
+            self.current_offset = end;
+            if !chunk.is_empty() {
+                return Ok(Some(chunk));
+            }
+        }
+        Ok(None)
     }
 }
 
@@ -275,11 +270,10 @@ impl FilterPipelineOperator {
 impl PipelineOperator for FilterPipelineOperator {
     fn process_chunk(&self, chunk: DataChunk) -> PrismDBResult<DataChunk> {
         let filter_vector = self.filter.predicate.evaluate(&chunk, &self.context)?;
-        let validity = filter_vector.get_validity_mask();
 
         let mut keep_rows = Vec::new();
         for i in 0..chunk.len() {
-            if validity.is_valid(i) {
+            if filter_vector.is_valid(i) {
                 let value = filter_vector.get_value(i)?;
                 if let crate::Value::Boolean(true) = value {
                     keep_rows.push(i);
@@ -291,23 +285,8 @@ impl PipelineOperator for FilterPipelineOperator {
             return Ok(DataChunk::with_rows(0));
         }
 
-        let mut result_chunk = DataChunk::with_rows(keep_rows.len());
-        for i in 0..chunk.column_count() {
-            let source_vector = chunk
-                .get_vector(i)
-                .ok_or_else(|| PrismDBError::InvalidValue(format!("Column {} not found", i)))?;
-            let mut target_vector =
-                crate::types::Vector::new(source_vector.get_type().clone(), keep_rows.len());
-
-            for (target_idx, &source_idx) in keep_rows.iter().enumerate() {
-                let value = source_vector.get_value(source_idx)?;
-                target_vector.set_value(target_idx, &value)?;
-            }
-
-            result_chunk.set_vector(i, target_vector)?;
-        }
-
-        Ok(result_chunk)
+        // The surviving rows are a view over the input chunk, materialized lazily
+        chunk.filter(&keep_rows)
     }
 }
 
@@ -340,34 +319,35 @@ impl PipelineOperator for ProjectionPipelineOperator {
 /// Limit pipeline operator
 pub struct LimitPipelineOperator {
     limit: crate::planner::PhysicalLimit,
-    rows_returned: Arc<Mutex<usize>>,
+    rows_seen: Arc<Mutex<usize>>,
 }
 
 impl LimitPipelineOperator {
     pub fn new(limit: crate::planner::PhysicalLimit) -> Self {
         Self {
             limit,
-            rows_returned: Arc::new(Mutex::new(0)),
+            rows_seen: Arc::new(Mutex::new(0)),
         }
     }
 }
 
 impl PipelineOperator for LimitPipelineOperator {
     fn process_chunk(&self, chunk: DataChunk) -> PrismDBResult<DataChunk> {
-        let mut rows_returned = self.rows_returned.lock().unwrap();
-
-        if *rows_returned >= self.limit.limit {
-            return Ok(DataChunk::with_rows(0));
-        }
-
-        let remaining = self.limit.limit - *rows_returned;
-        if chunk.len() > remaining {
-            let limited_chunk = chunk.slice_range(0, remaining)?;
-            *rows_returned += remaining;
-            Ok(limited_chunk)
-        } else {
-            *rows_returned += chunk.len();
+        let mut rows_seen = self.rows_seen.lock_or_err()?;
+
+        // Keep the part of the chunk that falls inside [offset, offset + limit)
+        let chunk_start = *rows_seen;
+        *rows_seen += chunk.len();
+        let window_end = self.limit.offset.saturating_add(self.limit.limit);
+        let start = self.limit.offset.max(chunk_start) - chunk_start;
+        let end = window_end.min(*rows_seen).saturating_sub(chunk_start);
+
+        if start >= end {
+            Ok(DataChunk::with_rows(0))
+        } else if start == 0 && end == chunk.len() {
             Ok(chunk)
+        } else {
+            chunk.slice_range(start, end - start)
         }
     }
 }
@@ -389,7 +369,10 @@ impl Iterator for PipelineStream {
     type Item = PrismDBResult<DataChunk>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.pipeline.lock().unwrap().execute_next().transpose()
+        match self.pipeline.lock_or_err() {
+            Ok(mut pipeline) => pipeline.execute_next().transpose(),
+            Err(error) => Some(Err(error)),
+        }
     }
 }
 
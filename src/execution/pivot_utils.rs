@@ -4,6 +4,7 @@
 //! ExpressionRef types used in PIVOT and UNPIVOT operations.
 
 use crate::expression::expression::ExpressionRef;
+use crate::types::Value;
 
 /// Attempt to extract aggregate function name from an ExpressionRef
 ///
@@ -38,27 +39,29 @@ pub fn extract_aggregate_name(expr: &ExpressionRef) -> Option<String> {
     None
 }
 
-/// Extract constant value from an expression by evaluating it
+/// Evaluate a constant expression to its value
 ///
-/// This creates a minimal DataChunk and evaluates the expression to get
-/// its constant value. Returns the string representation of the value.
-pub fn extract_constant_value(expr: &ExpressionRef, context: &crate::execution::ExecutionContext) -> Option<String> {
+/// This creates a minimal DataChunk and evaluates the expression against it.
+/// Returns None if evaluation fails.
+pub fn evaluate_constant(expr: &ExpressionRef, context: &crate::execution::ExecutionContext) -> Option<Value> {
     use crate::types::DataChunk;
 
     // Create a single-row chunk for evaluation
     let eval_chunk = DataChunk::with_rows(1);
 
     match expr.evaluate(&eval_chunk, context) {
-        Ok(vector) if vector.len() > 0 => {
-            match vector.get_value(0) {
-                Ok(val) => Some(val.to_string()),
-                Err(_) => None,
-            }
-        }
+        Ok(vector) if !vector.is_empty() => vector.get_value(0).ok(),
         _ => None,
     }
 }
 
+/// Extract constant value from an expression by evaluating it
+///
+/// Returns the string representation of the value.
+pub fn extract_constant_value(expr: &ExpressionRef, context: &crate::execution::ExecutionContext) -> Option<String> {
+    evaluate_constant(expr, context).map(|val| val.to_string())
+}
+
 /// Extract column name from an expression
 ///
 /// Attempts to extract a meaningful column name from various expression types.
@@ -0,0 +1,265 @@
+//! Pipeline Scheduler
+//!
+//! Cuts a physical plan into pipelines and runs the pipelines that don't
+//! depend on each other at the same time. A pipeline ends where an operator
+//! needs the whole of more than one input before it can produce anything:
+//! the two sides of a join and the branches of a set operation. Each such
+//! input becomes a pipeline of its own whose result is materialized and
+//! handed to the operator, so the inputs run concurrently instead of one
+//! after the other, and so do the inputs of those inputs.
+//!
+//! Operators with a single input stay in the pipeline of that input, blocking
+//! or not: a sort or aggregate has to wait for its input either way, so
+//! cutting there would only add a copy. Within a pipeline, scans and the
+//! parallel operators still split their work into morsels.
+//!
+//! Pipelines run as tasks on rayon's work-stealing pool. A pipeline is
+//! spawned as soon as the last of the pipelines it depends on finishes.
+
+use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::metrics::Metric;
+use crate::common::sync::MutexExt;
+use crate::execution::context::ExecutionContext;
+use crate::execution::operators::SimpleDataChunkStream;
+use crate::execution::ExecutionEngine;
+use crate::planner::{DataChunkStream, PhysicalIteratorStream, PhysicalPlan};
+use crate::types::DataChunk;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Position of a pipeline in its graph
+pub type PipelineId = usize;
+
+/// A plan fragment that runs as one task
+#[derive(Debug, Clone)]
+pub struct ScheduledPipeline {
+    /// The fragment; every input it was cut at is an empty `IteratorStream`
+    pub plan: PhysicalPlan,
+    /// Pipelines whose results fill those inputs, in plan order
+    pub dependencies: Vec<PipelineId>,
+}
+
+/// The pipelines of a plan
+///
+/// Every pipeline comes after the pipelines it depends on; the last one
+/// produces the plan's result.
+#[derive(Debug, Clone)]
+pub struct PipelineGraph {
+    pipelines: Vec<ScheduledPipeline>,
+}
+
+impl PipelineGraph {
+    /// Cut `plan` into pipelines
+    pub fn build(plan: PhysicalPlan) -> Self {
+        let mut graph = Self {
+            pipelines: Vec::new(),
+        };
+        graph.add(plan);
+        graph
+    }
+
+    fn add(&mut self, mut plan: PhysicalPlan) -> PipelineId {
+        let mut dependencies = Vec::new();
+        for_each_input(&mut plan, &mut |input| {
+            let placeholder = PhysicalIteratorStream::empty(input.schema());
+            let input = std::mem::replace(input, PhysicalPlan::IteratorStream(placeholder));
+            dependencies.push(self.add(input));
+        });
+        self.pipelines.push(ScheduledPipeline { plan, dependencies });
+        self.pipelines.len() - 1
+    }
+
+    /// The pipelines, each after the ones it depends on
+    pub fn pipelines(&self) -> &[ScheduledPipeline] {
+        &self.pipelines
+    }
+
+    /// Number of pipelines
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    /// Whether the graph has no pipelines; never true for a built graph
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+}
+
+/// Call `f` on each input the pipeline of `plan` is cut at, in plan order
+fn for_each_input(plan: &mut PhysicalPlan, f: &mut dyn FnMut(&mut PhysicalPlan)) {
+    match plan {
+        PhysicalPlan::HashJoin(join) => {
+            f(&mut join.left);
+            f(&mut join.right);
+        }
+        PhysicalPlan::SortMergeJoin(join) => {
+            f(&mut join.left);
+            f(&mut join.right);
+        }
+        PhysicalPlan::AsOfJoin(join) => {
+            f(&mut join.left);
+            f(&mut join.right);
+        }
+        PhysicalPlan::Union(union) => {
+            f(&mut union.left);
+            f(&mut union.right);
+        }
+        PhysicalPlan::Intersect(intersect) => {
+            f(&mut intersect.left);
+            f(&mut intersect.right);
+        }
+        PhysicalPlan::Except(except) => {
+            f(&mut except.left);
+            f(&mut except.right);
+        }
+        PhysicalPlan::Filter(filter) => for_each_input(&mut filter.input, f),
+        PhysicalPlan::Qualify(qualify) => for_each_input(&mut qualify.input, f),
+        PhysicalPlan::Window(window) => for_each_input(&mut window.input, f),
+        PhysicalPlan::Projection(projection) => for_each_input(&mut projection.input, f),
+        PhysicalPlan::Unnest(unnest) => for_each_input(&mut unnest.input, f),
+        PhysicalPlan::Limit(limit) => for_each_input(&mut limit.input, f),
+        PhysicalPlan::Distinct(distinct) => for_each_input(&mut distinct.input, f),
+        PhysicalPlan::Sort(sort) => for_each_input(&mut sort.input, f),
+        PhysicalPlan::Aggregate(aggregate) => for_each_input(&mut aggregate.input, f),
+        PhysicalPlan::HashAggregate(aggregate) => for_each_input(&mut aggregate.input, f),
+        PhysicalPlan::StreamingAggregate(aggregate) => for_each_input(&mut aggregate.input, f),
+        // Anything else runs whole inside the pipeline it is part of
+        _ => {}
+    }
+}
+
+/// Executes plans pipeline by pipeline on the rayon pool
+pub struct PipelineScheduler {
+    context: ExecutionContext,
+}
+
+impl PipelineScheduler {
+    pub fn new(context: ExecutionContext) -> Self {
+        Self { context }
+    }
+
+    /// Execute a plan, running its pipelines concurrently where dependencies allow
+    ///
+    /// A plan that is a single pipeline, or any plan when parallelism is
+    /// disabled, streams from the calling thread as `ExecutionEngine` would.
+    pub fn execute(&self, plan: PhysicalPlan) -> PrismDBResult<Box<dyn DataChunkStream>> {
+        if !self.context.parallel_context.parallel_enabled {
+            return ExecutionEngine::new(self.context.clone()).execute(plan);
+        }
+        let mut graph = PipelineGraph::build(plan);
+        if graph.len() == 1 {
+            let pipeline = graph.pipelines.pop().expect("a built graph has a pipeline");
+            return ExecutionEngine::new(self.context.clone()).execute(pipeline.plan);
+        }
+
+        self.context.metrics.add(Metric::PipelinesScheduled, graph.len() as u64);
+        let chunks = GraphRun::new(&self.context, graph).run()?;
+        Ok(Box::new(SimpleDataChunkStream::new(chunks)))
+    }
+}
+
+/// State of one execution of a pipeline graph
+struct GraphRun<'a> {
+    context: &'a ExecutionContext,
+    /// Pipelines not yet started
+    pipelines: Vec<Mutex<Option<ScheduledPipeline>>>,
+    /// Results of finished pipelines not yet handed on
+    results: Vec<Mutex<Option<Vec<DataChunk>>>>,
+    /// Number of unfinished dependencies of each pipeline
+    remaining: Vec<AtomicUsize>,
+    /// Pipelines depending on each pipeline
+    dependents: Vec<Vec<PipelineId>>,
+    /// First error any pipeline returned; no pipeline starts after one
+    error: Mutex<Option<PrismDBError>>,
+}
+
+impl<'a> GraphRun<'a> {
+    fn new(context: &'a ExecutionContext, graph: PipelineGraph) -> Self {
+        let count = graph.len();
+        let mut dependents = vec![Vec::new(); count];
+        for (id, pipeline) in graph.pipelines.iter().enumerate() {
+            for &dependency in &pipeline.dependencies {
+                dependents[dependency].push(id);
+            }
+        }
+        Self {
+            context,
+            remaining: graph
+                .pipelines
+                .iter()
+                .map(|pipeline| AtomicUsize::new(pipeline.dependencies.len()))
+                .collect(),
+            pipelines: graph.pipelines.into_iter().map(|p| Mutex::new(Some(p))).collect(),
+            results: (0..count).map(|_| Mutex::new(None)).collect(),
+            dependents,
+            error: Mutex::new(None),
+        }
+    }
+
+    /// Run every pipeline and return the result of the last
+    fn run(self) -> PrismDBResult<Vec<DataChunk>> {
+        // Collected before spawning: finishing pipelines make others ready too
+        let ready: Vec<PipelineId> = (0..self.pipelines.len())
+            .filter(|&id| self.remaining[id].load(Ordering::Acquire) == 0)
+            .collect();
+        rayon::scope(|scope| {
+            for id in ready {
+                self.spawn(scope, id);
+            }
+        });
+
+        if let Some(error) = self.error.into_inner().unwrap_or_else(PoisonError::into_inner) {
+            return Err(error);
+        }
+        let root = self.results.len() - 1;
+        self.results[root]
+            .lock_or_err()?
+            .take()
+            .ok_or_else(|| PrismDBError::Internal("Final pipeline did not run".to_string()))
+    }
+
+    fn spawn<'s>(&'s self, scope: &rayon::Scope<'s>, id: PipelineId) {
+        scope.spawn(move |scope| self.run_pipeline(scope, id));
+    }
+
+    /// Run one pipeline, then start the dependents it was the last dependency of
+    fn run_pipeline<'s>(&'s self, scope: &rayon::Scope<'s>, id: PipelineId) {
+        if self.error.lock_recovered().is_some() {
+            return;
+        }
+        match self.execute(id) {
+            Ok(chunks) => *self.results[id].lock_recovered() = Some(chunks),
+            Err(error) => {
+                self.error.lock_recovered().get_or_insert(error);
+                return;
+            }
+        }
+        for &dependent in &self.dependents[id] {
+            if self.remaining[dependent].fetch_sub(1, Ordering::AcqRel) == 1 {
+                self.spawn(scope, dependent);
+            }
+        }
+    }
+
+    /// Fill a pipeline's inputs with the results they wait for and run it
+    fn execute(&self, id: PipelineId) -> PrismDBResult<Vec<DataChunk>> {
+        let ScheduledPipeline {
+            mut plan,
+            dependencies,
+        } = self.pipelines[id]
+            .lock_or_err()?
+            .take()
+            .ok_or_else(|| PrismDBError::Internal(format!("Pipeline {} ran twice", id)))?;
+
+        let mut inputs = dependencies
+            .iter()
+            .map(|&dependency| self.results[dependency].lock_recovered().take());
+        for_each_input(&mut plan, &mut |input| {
+            if let PhysicalPlan::IteratorStream(stream) = input {
+                stream.chunks = inputs.next().flatten().unwrap_or_default();
+            }
+        });
+
+        ExecutionEngine::new(self.context.clone()).execute_collect(plan)
+    }
+}
@@ -2,23 +2,49 @@
 
 use crate::common::error::{PrismDBError, PrismDBResult};
 use crate::expression::{Expression, ExpressionRef};
-use crate::types::{DataChunk, LogicalType, Value, Vector};
+use crate::types::{Collation, DataChunk, LogicalType, OrderedValue, Value, Vector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 /// Aggregate function state
 pub trait AggregateState: std::fmt::Debug + Send + Sync + AsAny {
     /// Update the state with a new value
     fn update(&mut self, value: &Value) -> PrismDBResult<()>;
 
+    /// Update the state with a value and the sort keys of its row; states
+    /// that don't depend on input order ignore the keys
+    fn update_ordered(&mut self, value: &Value, _sort_keys: Vec<Value>) -> PrismDBResult<()> {
+        self.update(value)
+    }
+
     /// Get the final result
     fn finalize(&self) -> PrismDBResult<Value>;
 
     /// Merge another state into this state
+    ///
+    /// `other` must be the same kind of state; the result is as if this state
+    /// had also been updated with every value `other` was.
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()>;
 
+    /// Merge another partial state of the same type into this state
+    fn combine(&mut self, other: &Self) -> PrismDBResult<()>
+    where
+        Self: Sized;
+
     /// Clone the state
     fn clone_box(&self) -> Box<dyn AggregateState>;
 }
 
+/// The state behind `other` as the concrete type `T` of the state merging it
+fn downcast_state<T: AggregateState + 'static>(other: &dyn AggregateState) -> PrismDBResult<&T> {
+    other.as_any().downcast_ref::<T>().ok_or_else(|| {
+        PrismDBError::Internal(format!(
+            "Cannot merge {:?} into a different kind of aggregate state",
+            other
+        ))
+    })
+}
+
 impl Clone for Box<dyn AggregateState> {
     fn clone(&self) -> Box<dyn AggregateState> {
         self.clone_box()
@@ -55,10 +81,12 @@ impl AggregateState for CountState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_count) = other.as_any().downcast_ref::<CountState>() {
-            self.count += other_count.count;
-            self.non_null_count += other_count.non_null_count;
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_count: &Self) -> PrismDBResult<()> {
+        self.count += other_count.count;
+        self.non_null_count += other_count.non_null_count;
         Ok(())
     }
 
@@ -137,15 +165,17 @@ impl AggregateState for SumState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_sum) = other.as_any().downcast_ref::<SumState>() {
-            self.sum += other_sum.sum;
-            self.decimal_sum += other_sum.decimal_sum;
-            self.count += other_sum.count;
-            if other_sum.is_decimal {
-                self.is_decimal = true;
-                self.decimal_scale = other_sum.decimal_scale;
-                self.decimal_precision = other_sum.decimal_precision;
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_sum: &Self) -> PrismDBResult<()> {
+        self.sum += other_sum.sum;
+        self.decimal_sum += other_sum.decimal_sum;
+        self.count += other_sum.count;
+        if other_sum.is_decimal {
+            self.is_decimal = true;
+            self.decimal_scale = other_sum.decimal_scale;
+            self.decimal_precision = other_sum.decimal_precision;
         }
         Ok(())
     }
@@ -248,18 +278,20 @@ impl AggregateState for AvgState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_avg) = other.as_any().downcast_ref::<AvgState>() {
-            self.sum += other_avg.sum;
-            self.decimal_sum += other_avg.decimal_sum;
-            self.count += other_avg.count;
-            if other_avg.is_decimal {
-                self.is_decimal = true;
-                self.decimal_scale = other_avg.decimal_scale;
-                self.decimal_precision = other_avg.decimal_precision;
-            }
-            if !other_avg.return_decimal {
-                self.return_decimal = false;
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_avg: &Self) -> PrismDBResult<()> {
+        self.sum += other_avg.sum;
+        self.decimal_sum += other_avg.decimal_sum;
+        self.count += other_avg.count;
+        if other_avg.is_decimal {
+            self.is_decimal = true;
+            self.decimal_scale = other_avg.decimal_scale;
+            self.decimal_precision = other_avg.decimal_precision;
+        }
+        if !other_avg.return_decimal {
+            self.return_decimal = false;
         }
         Ok(())
     }
@@ -287,7 +319,7 @@ impl AggregateState for MinState {
             match &self.min {
                 None => self.min = Some(value.clone()),
                 Some(current_min) => {
-                    if value.compare(current_min)? == std::cmp::Ordering::Less {
+                    if value.total_cmp(current_min).is_lt() {
                         self.min = Some(value.clone());
                     }
                 }
@@ -304,11 +336,12 @@ impl AggregateState for MinState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_min) = other.as_any().downcast_ref::<MinState>() {
-            match &other_min.min {
-                Some(other_val) => self.update(other_val)?,
-                None => {}
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_min: &Self) -> PrismDBResult<()> {
+        if let Some(other_val) = &other_min.min {
+            self.update(other_val)?;
         }
         Ok(())
     }
@@ -358,7 +391,7 @@ impl AggregateState for MaxState {
             match &self.max {
                 None => self.max = Some(value.clone()),
                 Some(current_max) => {
-                    if value.compare(current_max)? == std::cmp::Ordering::Greater {
+                    if value.total_cmp(current_max).is_gt() {
                         self.max = Some(value.clone());
                     }
                 }
@@ -375,11 +408,13 @@ impl AggregateState for MaxState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_max) = other.as_any().downcast_ref::<MaxState>() {
-            match &other_max.max {
-                Some(other_val) => self.update(other_val)?,
-                None => {}
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_max: &Self) -> PrismDBResult<()> {
+        match &other_max.max {
+            Some(other_val) => self.update(other_val)?,
+            None => {}
         }
         Ok(())
     }
@@ -434,31 +469,33 @@ impl AggregateState for StdDevState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_stddev) = other.as_any().downcast_ref::<StdDevState>() {
-            if other_stddev.count == 0 {
-                return Ok(());
-            }
-            if self.count == 0 {
-                self.count = other_stddev.count;
-                self.mean = other_stddev.mean;
-                self.m2 = other_stddev.m2;
-                return Ok(());
-            }
-
-            // Parallel variance algorithm
-            let total_count = self.count + other_stddev.count;
-            let delta = other_stddev.mean - self.mean;
-            let new_mean = (self.count as f64 * self.mean
-                + other_stddev.count as f64 * other_stddev.mean)
-                / total_count as f64;
-            let new_m2 = self.m2
-                + other_stddev.m2
-                + delta * delta * (self.count * other_stddev.count) as f64 / total_count as f64;
+        self.combine(downcast_state(other.as_ref())?)
+    }
 
-            self.count = total_count;
-            self.mean = new_mean;
-            self.m2 = new_m2;
+    fn combine(&mut self, other_stddev: &Self) -> PrismDBResult<()> {
+        if other_stddev.count == 0 {
+            return Ok(());
+        }
+        if self.count == 0 {
+            self.count = other_stddev.count;
+            self.mean = other_stddev.mean;
+            self.m2 = other_stddev.m2;
+            return Ok(());
         }
+
+        // Parallel variance algorithm
+        let total_count = self.count + other_stddev.count;
+        let delta = other_stddev.mean - self.mean;
+        let new_mean = (self.count as f64 * self.mean
+            + other_stddev.count as f64 * other_stddev.mean)
+            / total_count as f64;
+        let new_m2 = self.m2
+            + other_stddev.m2
+            + delta * delta * (self.count * other_stddev.count) as f64 / total_count as f64;
+
+        self.count = total_count;
+        self.mean = new_mean;
+        self.m2 = new_m2;
         Ok(())
     }
 
@@ -512,31 +549,33 @@ impl AggregateState for VarianceState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_var) = other.as_any().downcast_ref::<VarianceState>() {
-            if other_var.count == 0 {
-                return Ok(());
-            }
-            if self.count == 0 {
-                self.count = other_var.count;
-                self.mean = other_var.mean;
-                self.m2 = other_var.m2;
-                return Ok(());
-            }
-
-            // Parallel variance algorithm
-            let total_count = self.count + other_var.count;
-            let delta = other_var.mean - self.mean;
-            let new_mean = (self.count as f64 * self.mean
-                + other_var.count as f64 * other_var.mean)
-                / total_count as f64;
-            let new_m2 = self.m2
-                + other_var.m2
-                + delta * delta * (self.count * other_var.count) as f64 / total_count as f64;
+        self.combine(downcast_state(other.as_ref())?)
+    }
 
-            self.count = total_count;
-            self.mean = new_mean;
-            self.m2 = new_m2;
+    fn combine(&mut self, other_var: &Self) -> PrismDBResult<()> {
+        if other_var.count == 0 {
+            return Ok(());
+        }
+        if self.count == 0 {
+            self.count = other_var.count;
+            self.mean = other_var.mean;
+            self.m2 = other_var.m2;
+            return Ok(());
         }
+
+        // Parallel variance algorithm
+        let total_count = self.count + other_var.count;
+        let delta = other_var.mean - self.mean;
+        let new_mean = (self.count as f64 * self.mean
+            + other_var.count as f64 * other_var.mean)
+            / total_count as f64;
+        let new_m2 = self.m2
+            + other_var.m2
+            + delta * delta * (self.count * other_var.count) as f64 / total_count as f64;
+
+        self.count = total_count;
+        self.mean = new_mean;
+        self.m2 = new_m2;
         Ok(())
     }
 
@@ -576,7 +615,7 @@ impl AggregateState for MedianState {
         }
 
         let mut sorted = self.values.clone();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        sorted.sort_by(f64::total_cmp);
 
         let len = sorted.len();
         let median = if len % 2 == 0 {
@@ -591,9 +630,11 @@ impl AggregateState for MedianState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_median) = other.as_any().downcast_ref::<MedianState>() {
-            self.values.extend_from_slice(&other_median.values);
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_median: &Self) -> PrismDBResult<()> {
+        self.values.extend_from_slice(&other_median.values);
         Ok(())
     }
 
@@ -605,7 +646,7 @@ impl AggregateState for MedianState {
 /// MODE aggregate state - Find most frequent value
 #[derive(Debug, Clone)]
 pub struct ModeState {
-    counts: std::collections::HashMap<String, usize>,
+    counts: std::collections::HashMap<OrderedValue, usize>,
 }
 
 impl ModeState {
@@ -619,36 +660,28 @@ impl ModeState {
 impl AggregateState for ModeState {
     fn update(&mut self, value: &Value) -> PrismDBResult<()> {
         if !value.is_null() {
-            // Use debug format as key for any value type
-            let key = format!("{:?}", value);
-            *self.counts.entry(key).or_insert(0) += 1;
+            *self.counts.entry(OrderedValue(value.clone())).or_insert(0) += 1;
         }
         Ok(())
     }
 
     fn finalize(&self) -> PrismDBResult<Value> {
-        if self.counts.is_empty() {
-            return Ok(Value::Null);
-        }
-
-        // Find the most frequent value
+        // The most frequent value, the smallest one on ties
         let mode = self
             .counts
             .iter()
-            .max_by_key(|(_, &count)| count)
-            .map(|(k, _)| k.clone());
-
-        match mode {
-            Some(m) => Ok(Value::Varchar(m)),
-            None => Ok(Value::Null),
-        }
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then_with(|| b.cmp(a)))
+            .map(|(value, _)| value.0.clone());
+        Ok(mode.unwrap_or(Value::Null))
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_mode) = other.as_any().downcast_ref::<ModeState>() {
-            for (key, count) in &other_mode.counts {
-                *self.counts.entry(key.clone()).or_insert(0) += count;
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_mode: &Self) -> PrismDBResult<()> {
+        for (key, count) in &other_mode.counts {
+            *self.counts.entry(key.clone()).or_insert(0) += count;
         }
         Ok(())
     }
@@ -658,16 +691,31 @@ impl AggregateState for ModeState {
     }
 }
 
-/// APPROX_COUNT_DISTINCT aggregate state - Approximate distinct count using hash
+/// Bits of a value's hash that pick its HyperLogLog register
+const HLL_PRECISION: u32 = 14;
+/// Number of HyperLogLog registers, one byte each
+const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+/// Distinct hashes kept exactly before switching to registers
+const HLL_EXACT_LIMIT: usize = HLL_REGISTERS / 8;
+
+/// APPROX_COUNT_DISTINCT aggregate state - HyperLogLog distinct count estimate
+///
+/// Small inputs are counted exactly by their hashes. Past `HLL_EXACT_LIMIT`
+/// distinct hashes the state switches to 2^14 registers, each keeping the
+/// longest run of leading zeros seen among the hashes routed to it, so
+/// memory stays bounded and the standard error is about 0.8%.
 #[derive(Debug, Clone)]
 pub struct ApproxCountDistinctState {
     seen: std::collections::HashSet<u64>,
+    /// Empty until the exact count is abandoned
+    registers: Vec<u8>,
 }
 
 impl ApproxCountDistinctState {
     pub fn new() -> Self {
         Self {
             seen: std::collections::HashSet::new(),
+            registers: Vec::new(),
         }
     }
 
@@ -676,28 +724,74 @@ impl ApproxCountDistinctState {
         use std::hash::{Hash, Hasher};
 
         let mut hasher = DefaultHasher::new();
-        // Hash the debug representation as a simple approach
-        format!("{:?}", value).hash(&mut hasher);
+        OrderedValue(value.clone()).hash(&mut hasher);
         hasher.finish()
     }
+
+    fn add_hash(&mut self, hash: u64) {
+        if !self.registers.is_empty() {
+            let register = (hash >> (64 - HLL_PRECISION)) as usize;
+            // The marker bit bounds the run when the remaining bits are all zero
+            let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() as u8 + 1;
+            self.registers[register] = self.registers[register].max(rank);
+        } else if self.seen.insert(hash) && self.seen.len() > HLL_EXACT_LIMIT {
+            self.switch_to_registers();
+        }
+    }
+
+    fn switch_to_registers(&mut self) {
+        self.registers = vec![0; HLL_REGISTERS];
+        for hash in std::mem::take(&mut self.seen) {
+            self.add_hash(hash);
+        }
+    }
+
+    /// HyperLogLog estimate, with linear counting while registers are still empty
+    fn estimate(&self) -> f64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&rank| 2f64.powi(-(rank as i32))).sum();
+        let raw = alpha * m * m / sum;
+        let empty = self.registers.iter().filter(|&&rank| rank == 0).count();
+        if raw <= 2.5 * m && empty > 0 {
+            m * (m / empty as f64).ln()
+        } else {
+            raw
+        }
+    }
 }
 
 impl AggregateState for ApproxCountDistinctState {
     fn update(&mut self, value: &Value) -> PrismDBResult<()> {
         if !value.is_null() {
-            let hash = Self::hash_value(value);
-            self.seen.insert(hash);
+            self.add_hash(Self::hash_value(value));
         }
         Ok(())
     }
 
     fn finalize(&self) -> PrismDBResult<Value> {
-        Ok(Value::BigInt(self.seen.len() as i64))
+        if self.registers.is_empty() {
+            Ok(Value::BigInt(self.seen.len() as i64))
+        } else {
+            Ok(Value::BigInt(self.estimate().round() as i64))
+        }
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_approx) = other.as_any().downcast_ref::<ApproxCountDistinctState>() {
-            self.seen.extend(&other_approx.seen);
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_approx: &Self) -> PrismDBResult<()> {
+        for &hash in &other_approx.seen {
+            self.add_hash(hash);
+        }
+        if !other_approx.registers.is_empty() {
+            if self.registers.is_empty() {
+                self.switch_to_registers();
+            }
+            for (mine, &theirs) in self.registers.iter_mut().zip(&other_approx.registers) {
+                *mine = (*mine).max(theirs);
+            }
         }
         Ok(())
     }
@@ -758,14 +852,14 @@ impl AggregateState for ApproxQuantileState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(_other_quantile) = other.as_any().downcast_ref::<ApproxQuantileState>() {
-            // Merge the other digest into this one
-            // The tdigest crate provides merge_unsorted for merging
-            let _other_values: Vec<f64> = Vec::new(); // Would need to extract values from _other_quantile.digest
-            // For now, just skip merging as tdigest doesn't expose values easily
-            // In practice, for parallel aggregation, we'd reconstruct from centroids
-            // This is a limitation of the tdigest crate API
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_quantile: &Self) -> PrismDBResult<()> {
+        self.digest = tdigest::TDigest::merge_digests(vec![
+            self.digest.clone(),
+            other_quantile.digest.clone(),
+        ]);
         Ok(())
     }
 
@@ -815,9 +909,152 @@ impl AggregateState for StringAggState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_agg) = other.as_any().downcast_ref::<StringAggState>() {
-            self.values.extend(other_agg.values.clone());
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_agg: &Self) -> PrismDBResult<()> {
+        self.values.extend(other_agg.values.clone());
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn AggregateState> {
+        Box::new(self.clone())
+    }
+}
+
+/// ARRAY_AGG (LIST) aggregate state - Collect the values, NULLs included, into a list
+#[derive(Debug, Clone, Default)]
+pub struct ArrayAggState {
+    values: Vec<Value>,
+}
+
+impl ArrayAggState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AggregateState for ArrayAggState {
+    fn update(&mut self, value: &Value) -> PrismDBResult<()> {
+        self.values.push(value.clone());
+        Ok(())
+    }
+
+    fn finalize(&self) -> PrismDBResult<Value> {
+        if self.values.is_empty() {
+            Ok(Value::Null)
+        } else {
+            Ok(Value::List(self.values.clone()))
+        }
+    }
+
+    fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_agg: &Self) -> PrismDBResult<()> {
+        self.values.extend(other_agg.values.iter().cloned());
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn AggregateState> {
+        Box::new(self.clone())
+    }
+}
+
+/// RESERVOIR_SAMPLE aggregate state - A uniform random sample of at most
+/// `size` non-NULL values, kept with reservoir sampling in bounded memory
+#[derive(Debug, Clone)]
+pub struct ReservoirSampleState {
+    reservoir: Vec<Value>,
+    size: usize,
+    /// Non-NULL values offered to the reservoir so far
+    seen: u64,
+    rng: StdRng,
+}
+
+impl ReservoirSampleState {
+    /// Sample `size` values, with a random seed unless `seed` is given
+    pub fn new(size: usize, seed: Option<u64>) -> Self {
+        Self {
+            reservoir: Vec::new(),
+            size,
+            seen: 0,
+            rng: StdRng::seed_from_u64(seed.unwrap_or_else(rand::random)),
+        }
+    }
+
+    /// State from RESERVOIR_SAMPLE's parameters: the sample size and an optional seed
+    fn from_parameters(parameters: &[Value]) -> PrismDBResult<Self> {
+        let (size, seed) = match parameters {
+            [size] => (size, None),
+            [size, seed] => (size, Some(seed.try_as_i64()? as u64)),
+            _ => {
+                return Err(PrismDBError::InvalidArgument(
+                    "RESERVOIR_SAMPLE requires a sample size and an optional seed".to_string(),
+                ))
+            }
+        };
+        let size = usize::try_from(size.try_as_i64()?).map_err(|_| {
+            PrismDBError::InvalidArgument(format!(
+                "RESERVOIR_SAMPLE size must not be negative, got {}",
+                size
+            ))
+        })?;
+        Ok(Self::new(size, seed))
+    }
+}
+
+impl AggregateState for ReservoirSampleState {
+    fn update(&mut self, value: &Value) -> PrismDBResult<()> {
+        if value.is_null() {
+            return Ok(());
+        }
+        // Algorithm R: the n-th value replaces a random slot with probability size / n
+        self.seen += 1;
+        if self.reservoir.len() < self.size {
+            self.reservoir.push(value.clone());
+        } else {
+            let slot = self.rng.random_range(0..self.seen);
+            if let Some(kept) = self.reservoir.get_mut(slot as usize) {
+                *kept = value.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn finalize(&self) -> PrismDBResult<Value> {
+        if self.reservoir.is_empty() {
+            Ok(Value::Null)
+        } else {
+            Ok(Value::List(self.reservoir.clone()))
+        }
+    }
+
+    fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other: &Self) -> PrismDBResult<()> {
+        // Each value kept stands for seen / kept values of its side, so each
+        // slot of the merged sample comes from a side with probability
+        // proportional to the values its remaining sample stands for
+        let mut ours = std::mem::take(&mut self.reservoir);
+        let mut theirs = other.reservoir.clone();
+        let stands_for = |seen: u64, kept: usize| seen as f64 / kept.max(1) as f64;
+        let (our_share, their_share) = (stands_for(self.seen, ours.len()), stands_for(other.seen, theirs.len()));
+        while self.reservoir.len() < self.size && !(ours.is_empty() && theirs.is_empty()) {
+            let our_weight = ours.len() as f64 * our_share;
+            let their_weight = theirs.len() as f64 * their_share;
+            let side = if self.rng.random::<f64>() * (our_weight + their_weight) < our_weight {
+                &mut ours
+            } else {
+                &mut theirs
+            };
+            let index = self.rng.random_range(0..side.len());
+            self.reservoir.push(side.swap_remove(index));
         }
+        self.seen += other.seen;
         Ok(())
     }
 
@@ -868,7 +1105,7 @@ impl AggregateState for PercentileContState {
 
         // Sort values
         let mut sorted = self.values.clone();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        sorted.sort_by(f64::total_cmp);
 
         // Calculate position
         let n = sorted.len() as f64;
@@ -887,9 +1124,11 @@ impl AggregateState for PercentileContState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_pct) = other.as_any().downcast_ref::<PercentileContState>() {
-            self.values.extend(other_pct.values.clone());
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_pct: &Self) -> PrismDBResult<()> {
+        self.values.extend(other_pct.values.clone());
         Ok(())
     }
 
@@ -940,7 +1179,7 @@ impl AggregateState for PercentileDiscState {
 
         // Sort values
         let mut sorted = self.values.clone();
-        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        sorted.sort_by(f64::total_cmp);
 
         // Calculate position and get the value at that index
         let n = sorted.len();
@@ -950,9 +1189,11 @@ impl AggregateState for PercentileDiscState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_pct) = other.as_any().downcast_ref::<PercentileDiscState>() {
-            self.values.extend(other_pct.values.clone());
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_pct: &Self) -> PrismDBResult<()> {
+        self.values.extend(other_pct.values.clone());
         Ok(())
     }
 
@@ -1020,35 +1261,37 @@ impl AggregateState for CovarPopState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_covar) = other.as_any().downcast_ref::<CovarPopState>() {
-            if other_covar.count == 0 {
-                return Ok(());
-            }
-            if self.count == 0 {
-                *self = other_covar.clone();
-                return Ok(());
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
 
-            // Schubert and Gertz SSDBM 2018, equation 21
-            let total_count = self.count + other_covar.count;
-            let target_count = self.count as f64;
-            let source_count = other_covar.count as f64;
-            let total_count_f = total_count as f64;
+    fn combine(&mut self, other_covar: &Self) -> PrismDBResult<()> {
+        if other_covar.count == 0 {
+            return Ok(());
+        }
+        if self.count == 0 {
+            *self = other_covar.clone();
+            return Ok(());
+        }
 
-            let mean_x =
-                (source_count * other_covar.mean_x + target_count * self.mean_x) / total_count_f;
-            let mean_y =
-                (source_count * other_covar.mean_y + target_count * self.mean_y) / total_count_f;
+        // Schubert and Gertz SSDBM 2018, equation 21
+        let total_count = self.count + other_covar.count;
+        let target_count = self.count as f64;
+        let source_count = other_covar.count as f64;
+        let total_count_f = total_count as f64;
 
-            let delta_x = self.mean_x - other_covar.mean_x;
-            let delta_y = self.mean_y - other_covar.mean_y;
-            self.co_moment = other_covar.co_moment + self.co_moment
-                + delta_x * delta_y * source_count * target_count / total_count_f;
+        let mean_x =
+            (source_count * other_covar.mean_x + target_count * self.mean_x) / total_count_f;
+        let mean_y =
+            (source_count * other_covar.mean_y + target_count * self.mean_y) / total_count_f;
 
-            self.mean_x = mean_x;
-            self.mean_y = mean_y;
-            self.count = total_count;
-        }
+        let delta_x = self.mean_x - other_covar.mean_x;
+        let delta_y = self.mean_y - other_covar.mean_y;
+        self.co_moment = other_covar.co_moment + self.co_moment
+            + delta_x * delta_y * source_count * target_count / total_count_f;
+
+        self.mean_x = mean_x;
+        self.mean_y = mean_y;
+        self.count = total_count;
         Ok(())
     }
 
@@ -1091,10 +1334,12 @@ impl AggregateState for CovarSampState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_covar) = other.as_any().downcast_ref::<CovarSampState>() {
-            self.covar_pop
-                .merge(Box::new(other_covar.covar_pop.clone()))?;
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_covar: &Self) -> PrismDBResult<()> {
+        self.covar_pop
+            .combine(&other_covar.covar_pop)?;
         Ok(())
     }
 
@@ -1180,12 +1425,14 @@ impl AggregateState for CorrState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_corr) = other.as_any().downcast_ref::<CorrState>() {
-            self.covar_pop
-                .merge(Box::new(other_corr.covar_pop.clone()))?;
-            self.stddev_x.merge(Box::new(other_corr.stddev_x.clone()))?;
-            self.stddev_y.merge(Box::new(other_corr.stddev_y.clone()))?;
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_corr: &Self) -> PrismDBResult<()> {
+        self.covar_pop
+            .combine(&other_corr.covar_pop)?;
+        self.stddev_x.combine(&other_corr.stddev_x)?;
+        self.stddev_y.combine(&other_corr.stddev_y)?;
         Ok(())
     }
 
@@ -1394,6 +1641,156 @@ mod tests {
         Ok(())
     }
 
+    /// Finalized values of `merged` and `whole` agree, up to rounding for doubles
+    fn assert_same_result(name: &str, merged: Value, whole: Value) {
+        match (&merged, &whole) {
+            (Value::Double(a), Value::Double(b)) => {
+                assert!((a - b).abs() <= 1e-9 * b.abs().max(1.0), "{}: {} != {}", name, a, b)
+            }
+            _ => assert_eq!(merged, whole, "{}", name),
+        }
+    }
+
+    #[test]
+    fn test_merge_partial_states() -> PrismDBResult<()> {
+        let values: Vec<Value> = [4, 9, 1, 7, 3, 8, 9, 2, 6, 5, 9, 0]
+            .into_iter()
+            .map(Value::integer)
+            .chain([Value::Null])
+            .collect();
+
+        for name in [
+            "count", "sum", "avg", "min", "max", "stddev", "variance", "median", "mode",
+            "approx_count_distinct", "approx_quantile", "string_agg", "array_agg", "percentile_cont",
+            "percentile_disc", "first", "last", "arg_min", "arg_max", "regr_count",
+            "regr_slope", "regr_intercept", "regr_r2",
+        ] {
+            let mut whole = create_aggregate_state(name)?;
+            for value in &values {
+                whole.update(value)?;
+            }
+
+            // Every split of the input into two partial states, merged in order
+            for split in 0..=values.len() {
+                let mut left = create_aggregate_state(name)?;
+                let mut right = create_aggregate_state(name)?;
+                for value in &values[..split] {
+                    left.update(value)?;
+                }
+                for value in &values[split..] {
+                    right.update(value)?;
+                }
+                left.merge(right)?;
+                assert_same_result(name, left.finalize()?, whole.finalize()?);
+            }
+        }
+
+        let booleans: Vec<Value> = [true, true, false, true].into_iter().map(Value::Boolean).collect();
+        for name in ["bool_and", "bool_or"] {
+            let mut whole = create_aggregate_state(name)?;
+            for value in &booleans {
+                whole.update(value)?;
+            }
+            for split in 0..=booleans.len() {
+                let mut left = create_aggregate_state(name)?;
+                let mut right = create_aggregate_state(name)?;
+                for value in &booleans[..split] {
+                    left.update(value)?;
+                }
+                for value in &booleans[split..] {
+                    right.update(value)?;
+                }
+                left.merge(right)?;
+                assert_eq!(left.finalize()?, whole.finalize()?, "{}", name);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_combine_paired_states() -> PrismDBResult<()> {
+        let pairs = [(1.0, 2.0), (2.0, 3.5), (4.0, 4.0), (5.0, 7.5), (7.0, 8.0), (8.0, 11.0)];
+
+        for split in 0..=pairs.len() {
+            let (mut pop, mut pop_left, mut pop_right) = (CovarPopState::new(), CovarPopState::new(), CovarPopState::new());
+            let (mut samp, mut samp_left, mut samp_right) = (CovarSampState::new(), CovarSampState::new(), CovarSampState::new());
+            let (mut corr, mut corr_left, mut corr_right) = (CorrState::new(), CorrState::new(), CorrState::new());
+            for (i, &(x, y)) in pairs.iter().enumerate() {
+                pop.update_pair(x, y)?;
+                samp.update_pair(x, y)?;
+                corr.update_pair(x, y)?;
+                if i < split {
+                    pop_left.update_pair(x, y)?;
+                    samp_left.update_pair(x, y)?;
+                    corr_left.update_pair(x, y)?;
+                } else {
+                    pop_right.update_pair(x, y)?;
+                    samp_right.update_pair(x, y)?;
+                    corr_right.update_pair(x, y)?;
+                }
+            }
+            pop_left.combine(&pop_right)?;
+            samp_left.combine(&samp_right)?;
+            corr_left.combine(&corr_right)?;
+            assert_same_result("covar_pop", pop_left.finalize()?, pop.finalize()?);
+            assert_same_result("covar_samp", samp_left.finalize()?, samp.finalize()?);
+            assert_same_result("corr", corr_left.finalize()?, corr.finalize()?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_combine_ordered_states() -> PrismDBResult<()> {
+        let ordered = || OrderedAggregateState::new(Box::new(StringAggState::new(",".to_string())), vec![(false, false)]);
+        let mut left = ordered();
+        let mut right = ordered();
+        left.update_ordered(&Value::Varchar("b".to_string()), vec![Value::integer(2)])?;
+        right.update_ordered(&Value::Varchar("c".to_string()), vec![Value::integer(3)])?;
+        right.update_ordered(&Value::Varchar("a".to_string()), vec![Value::integer(1)])?;
+        left.combine(&right)?;
+        assert_eq!(left.finalize()?, Value::Varchar("c,b,a".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_reservoir_samples() -> PrismDBResult<()> {
+        let mut left = ReservoirSampleState::new(4, Some(1));
+        let mut right = ReservoirSampleState::new(4, Some(2));
+        for i in 0..10 {
+            left.update(&Value::integer(i))?;
+        }
+        right.update(&Value::integer(100))?;
+        right.update(&Value::Null)?;
+        left.merge(Box::new(right))?;
+
+        // The merged sample is still bounded, distinct and drawn from both inputs
+        let Value::List(sample) = left.finalize()? else {
+            panic!("expected a list");
+        };
+        assert_eq!(sample.len(), 4);
+        let distinct: std::collections::HashSet<String> = sample.iter().map(|value| value.to_string()).collect();
+        assert_eq!(distinct.len(), 4);
+        assert_eq!(left.seen, 11);
+
+        // Merging into an empty sample keeps every value up to the size
+        let mut empty = ReservoirSampleState::new(3, Some(1));
+        let mut two = ReservoirSampleState::new(3, Some(1));
+        two.update(&Value::integer(1))?;
+        two.update(&Value::integer(2))?;
+        empty.combine(&two)?;
+        assert_eq!(empty.reservoir.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_mismatched_states_fails() {
+        let mut sum = create_aggregate_state("sum").unwrap();
+        let count = create_aggregate_state("count").unwrap();
+        assert!(sum.merge(count).is_err());
+    }
+
     #[test]
     fn test_stddev_aggregate() -> PrismDBResult<()> {
         let mut state = StdDevState::new();
@@ -1496,8 +1893,7 @@ mod tests {
         state.update(&Value::integer(4))?;
 
         let result = state.finalize()?;
-        // Result is string representation of most frequent value
-        assert!(matches!(result, Value::Varchar(_)));
+        assert_eq!(result, Value::integer(2));
 
         Ok(())
     }
@@ -1514,7 +1910,7 @@ mod tests {
 
         let result = state.finalize()?;
         // "apple" appears 3 times, should be the mode
-        assert!(matches!(result, Value::Varchar(_)));
+        assert_eq!(result, Value::Varchar("apple".to_string()));
 
         Ok(())
     }
@@ -1553,6 +1949,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_approx_count_distinct_estimates_large_inputs() -> PrismDBResult<()> {
+        let mut left = ApproxCountDistinctState::new();
+        let mut right = ApproxCountDistinctState::new();
+        for i in 0..100_000 {
+            left.update(&Value::integer(i))?;
+            // Overlaps the left half by half
+            right.update(&Value::integer(i + 50_000))?;
+        }
+        assert!(left.registers.len() == HLL_REGISTERS && left.seen.is_empty());
+
+        let within = |state: &ApproxCountDistinctState, expected: f64| -> PrismDBResult<bool> {
+            let estimate = state.finalize()?.try_as_f64()?;
+            Ok((estimate - expected).abs() / expected < 0.03)
+        };
+        assert!(within(&left, 100_000.0)?);
+        left.combine(&right)?;
+        assert!(within(&left, 150_000.0)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_aggregate_state() -> PrismDBResult<()> {
+        let mut left = DistinctAggregateState::new(Box::new(SumState::new()));
+        let mut right = DistinctAggregateState::new(Box::new(SumState::new()));
+        for value in [1, 2, 2, 3] {
+            left.update(&Value::integer(value))?;
+        }
+        left.update(&Value::Null)?;
+        for value in [3, 4, 4] {
+            right.update(&Value::integer(value))?;
+        }
+
+        assert_eq!(left.finalize()?.try_as_f64()?, 6.0);
+        left.combine(&right)?;
+        // 1 + 2 + 3 + 4, with the 3 both sides saw counted once
+        assert_eq!(left.finalize()?.try_as_f64()?, 10.0);
+        Ok(())
+    }
+
     #[test]
     fn test_string_agg() -> PrismDBResult<()> {
         let mut state = StringAggState::new(", ".to_string());
@@ -2049,12 +2485,14 @@ impl AggregateState for FirstState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_first) = other.as_any().downcast_ref::<FirstState>() {
-            // For FIRST, only use other's value if we don't have one yet
-            if !self.is_set && other_first.is_set {
-                self.value = other_first.value.clone();
-                self.is_set = other_first.is_set;
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_first: &Self) -> PrismDBResult<()> {
+        // For FIRST, only use other's value if we don't have one yet
+        if !self.is_set && other_first.is_set {
+            self.value = other_first.value.clone();
+            self.is_set = other_first.is_set;
         }
         Ok(())
     }
@@ -2090,11 +2528,13 @@ impl AggregateState for LastState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_last) = other.as_any().downcast_ref::<LastState>() {
-            // For LAST, always take the other's value (it's more recent in parallel execution)
-            if other_last.value.is_some() {
-                self.value = other_last.value.clone();
-            }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_last: &Self) -> PrismDBResult<()> {
+        // For LAST, always take the other's value (it's more recent in parallel execution)
+        if other_last.value.is_some() {
+            self.value = other_last.value.clone();
         }
         Ok(())
     }
@@ -2118,23 +2558,6 @@ impl ArgMinState {
             min_value: None,
         }
     }
-
-    fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
-        use std::cmp::Ordering;
-
-        match (a, b) {
-            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
-            (Value::BigInt(a), Value::BigInt(b)) => a.cmp(b),
-            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
-            (Value::Double(a), Value::Double(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
-            (Value::Varchar(a), Value::Varchar(b)) => a.cmp(b),
-            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
-            (Value::Date(a), Value::Date(b)) => a.cmp(b),
-            (Value::Time(a), Value::Time(b)) => a.cmp(b),
-            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
-            _ => Ordering::Equal,
-        }
-    }
 }
 
 impl AggregateState for ArgMinState {
@@ -2151,7 +2574,7 @@ impl AggregateState for ArgMinState {
                 self.min_value = Some(value.clone());
                 self.arg_value = Some(value.clone());
             } else if let Some(ref current_min) = self.min_value {
-                if Self::compare_values(value, current_min) == std::cmp::Ordering::Less {
+                if value.total_cmp(current_min).is_lt() {
                     self.min_value = Some(value.clone());
                     self.arg_value = Some(value.clone());
                 }
@@ -2165,16 +2588,18 @@ impl AggregateState for ArgMinState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_argmin) = other.as_any().downcast_ref::<ArgMinState>() {
-            if let (Some(ref other_min), Some(ref other_arg)) = (&other_argmin.min_value, &other_argmin.arg_value) {
-                if self.min_value.is_none() {
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_argmin: &Self) -> PrismDBResult<()> {
+        if let (Some(ref other_min), Some(ref other_arg)) = (&other_argmin.min_value, &other_argmin.arg_value) {
+            if self.min_value.is_none() {
+                self.min_value = Some(other_min.clone());
+                self.arg_value = Some(other_arg.clone());
+            } else if let Some(ref current_min) = self.min_value {
+                if other_min.total_cmp(current_min).is_lt() {
                     self.min_value = Some(other_min.clone());
                     self.arg_value = Some(other_arg.clone());
-                } else if let Some(ref current_min) = self.min_value {
-                    if Self::compare_values(other_min, current_min) == std::cmp::Ordering::Less {
-                        self.min_value = Some(other_min.clone());
-                        self.arg_value = Some(other_arg.clone());
-                    }
                 }
             }
         }
@@ -2201,23 +2626,6 @@ impl ArgMaxState {
             max_value: None,
         }
     }
-
-    fn compare_values(a: &Value, b: &Value) -> std::cmp::Ordering {
-        use std::cmp::Ordering;
-
-        match (a, b) {
-            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
-            (Value::BigInt(a), Value::BigInt(b)) => a.cmp(b),
-            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
-            (Value::Double(a), Value::Double(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
-            (Value::Varchar(a), Value::Varchar(b)) => a.cmp(b),
-            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
-            (Value::Date(a), Value::Date(b)) => a.cmp(b),
-            (Value::Time(a), Value::Time(b)) => a.cmp(b),
-            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
-            _ => Ordering::Equal,
-        }
-    }
 }
 
 impl AggregateState for ArgMaxState {
@@ -2227,7 +2635,7 @@ impl AggregateState for ArgMaxState {
                 self.max_value = Some(value.clone());
                 self.arg_value = Some(value.clone());
             } else if let Some(ref current_max) = self.max_value {
-                if Self::compare_values(value, current_max) == std::cmp::Ordering::Greater {
+                if value.total_cmp(current_max).is_gt() {
                     self.max_value = Some(value.clone());
                     self.arg_value = Some(value.clone());
                 }
@@ -2241,16 +2649,18 @@ impl AggregateState for ArgMaxState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_argmax) = other.as_any().downcast_ref::<ArgMaxState>() {
-            if let (Some(ref other_max), Some(ref other_arg)) = (&other_argmax.max_value, &other_argmax.arg_value) {
-                if self.max_value.is_none() {
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_argmax: &Self) -> PrismDBResult<()> {
+        if let (Some(ref other_max), Some(ref other_arg)) = (&other_argmax.max_value, &other_argmax.arg_value) {
+            if self.max_value.is_none() {
+                self.max_value = Some(other_max.clone());
+                self.arg_value = Some(other_arg.clone());
+            } else if let Some(ref current_max) = self.max_value {
+                if other_max.total_cmp(current_max).is_gt() {
                     self.max_value = Some(other_max.clone());
                     self.arg_value = Some(other_arg.clone());
-                } else if let Some(ref current_max) = self.max_value {
-                    if Self::compare_values(other_max, current_max) == std::cmp::Ordering::Greater {
-                        self.max_value = Some(other_max.clone());
-                        self.arg_value = Some(other_arg.clone());
-                    }
                 }
             }
         }
@@ -2299,14 +2709,16 @@ impl AggregateState for BoolAndState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_bool) = other.as_any().downcast_ref::<BoolAndState>() {
-            if other_bool.has_value {
-                if !self.has_value {
-                    self.result = other_bool.result;
-                    self.has_value = true;
-                } else {
-                    self.result = self.result && other_bool.result;
-                }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_bool: &Self) -> PrismDBResult<()> {
+        if other_bool.has_value {
+            if !self.has_value {
+                self.result = other_bool.result;
+                self.has_value = true;
+            } else {
+                self.result = self.result && other_bool.result;
             }
         }
         Ok(())
@@ -2354,14 +2766,16 @@ impl AggregateState for BoolOrState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_bool) = other.as_any().downcast_ref::<BoolOrState>() {
-            if other_bool.has_value {
-                if !self.has_value {
-                    self.result = other_bool.result;
-                    self.has_value = true;
-                } else {
-                    self.result = self.result || other_bool.result;
-                }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_bool: &Self) -> PrismDBResult<()> {
+        if other_bool.has_value {
+            if !self.has_value {
+                self.result = other_bool.result;
+                self.has_value = true;
+            } else {
+                self.result = self.result || other_bool.result;
             }
         }
         Ok(())
@@ -2400,9 +2814,11 @@ impl AggregateState for RegrCountState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_regr) = other.as_any().downcast_ref::<RegrCountState>() {
-            self.count += other_regr.count;
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_regr: &Self) -> PrismDBResult<()> {
+        self.count += other_regr.count;
         Ok(())
     }
 
@@ -2455,10 +2871,12 @@ impl AggregateState for RegrSlopeState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_slope) = other.as_any().downcast_ref::<RegrSlopeState>() {
-            self.covar_state.merge(Box::new(other_slope.covar_state.clone()))?;
-            self.var_x_state.merge(Box::new(other_slope.var_x_state.clone()))?;
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_slope: &Self) -> PrismDBResult<()> {
+        self.covar_state.combine(&other_slope.covar_state)?;
+        self.var_x_state.combine(&other_slope.var_x_state)?;
         Ok(())
     }
 
@@ -2509,11 +2927,13 @@ impl AggregateState for RegrInterceptState {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_intercept) = other.as_any().downcast_ref::<RegrInterceptState>() {
-            self.avg_y_state.merge(Box::new(other_intercept.avg_y_state.clone()))?;
-            self.avg_x_state.merge(Box::new(other_intercept.avg_x_state.clone()))?;
-            self.slope_state.merge(Box::new(other_intercept.slope_state.clone()))?;
-        }
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_intercept: &Self) -> PrismDBResult<()> {
+        self.avg_y_state.combine(&other_intercept.avg_y_state)?;
+        self.avg_x_state.combine(&other_intercept.avg_x_state)?;
+        self.slope_state.combine(&other_intercept.slope_state)?;
         Ok(())
     }
 
@@ -2554,8 +2974,168 @@ impl AggregateState for RegrR2State {
     }
 
     fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
-        if let Some(other_r2) = other.as_any().downcast_ref::<RegrR2State>() {
-            self.corr_state.merge(Box::new(other_r2.corr_state.clone()))?;
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_r2: &Self) -> PrismDBResult<()> {
+        self.corr_state.combine(&other_r2.corr_state)?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn AggregateState> {
+        Box::new(self.clone())
+    }
+}
+
+/// Ordered aggregate state - `STRING_AGG(x, ',' ORDER BY y)` and
+/// `PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY x)`
+///
+/// Buffers each value with its sort keys and only feeds the wrapped state, in
+/// sorted order, on finalize, so the result doesn't depend on how rows were
+/// split between threads.
+#[derive(Debug, Clone)]
+pub struct OrderedAggregateState {
+    inner: Box<dyn AggregateState>,
+    /// (ascending, nulls_first) per sort key
+    order: Vec<(bool, bool)>,
+    entries: Vec<(Value, Vec<Value>)>,
+}
+
+impl OrderedAggregateState {
+    pub fn new(inner: Box<dyn AggregateState>, order: Vec<(bool, bool)>) -> Self {
+        Self {
+            inner,
+            order,
+            entries: Vec::new(),
+        }
+    }
+
+    fn compare_keys(&self, a: &[Value], b: &[Value]) -> std::cmp::Ordering {
+        use std::cmp::Ordering;
+
+        for ((a, b), &(ascending, nulls_first)) in a.iter().zip(b).zip(&self.order) {
+            let ordering = match (a.is_null(), b.is_null()) {
+                (true, true) => Ordering::Equal,
+                (true, false) if nulls_first => Ordering::Less,
+                (true, false) => Ordering::Greater,
+                (false, true) if nulls_first => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    let ordering = a.total_cmp(b);
+                    if ascending {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                }
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl AggregateState for OrderedAggregateState {
+    fn update(&mut self, value: &Value) -> PrismDBResult<()> {
+        self.update_ordered(value, Vec::new())
+    }
+
+    fn update_ordered(&mut self, value: &Value, sort_keys: Vec<Value>) -> PrismDBResult<()> {
+        self.entries.push((value.clone(), sort_keys));
+        Ok(())
+    }
+
+    fn finalize(&self) -> PrismDBResult<Value> {
+        let mut entries: Vec<&(Value, Vec<Value>)> = self.entries.iter().collect();
+        entries.sort_by(|a, b| self.compare_keys(&a.1, &b.1));
+
+        let mut state = self.inner.clone_box();
+        for (value, _) in entries {
+            state.update(value)?;
+        }
+        state.finalize()
+    }
+
+    fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_ordered: &Self) -> PrismDBResult<()> {
+        self.entries.extend(other_ordered.entries.iter().cloned());
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn AggregateState> {
+        Box::new(self.clone())
+    }
+}
+
+
+/// State of a `DISTINCT` aggregate: collects each distinct non-NULL value
+/// once and feeds them to the wrapped state when finalized
+///
+/// Values are told apart by their hash-table key encoding, under which
+/// values that compare equal are the same, after folding strings with the
+/// grouping collation as GROUP BY does.
+#[derive(Debug, Clone)]
+pub struct DistinctAggregateState {
+    inner: Box<dyn AggregateState>,
+    collation: Collation,
+    seen: std::collections::HashSet<OrderedValue>,
+    /// Each distinct value with the sort keys of the first row it came from
+    values: Vec<(Value, Vec<Value>)>,
+}
+
+impl DistinctAggregateState {
+    pub fn new(inner: Box<dyn AggregateState>) -> Self {
+        Self {
+            inner,
+            collation: Collation::default(),
+            seen: std::collections::HashSet::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Treat strings the collation groups together as one value
+    pub fn with_collation(mut self, collation: Collation) -> Self {
+        self.collation = collation;
+        self
+    }
+}
+
+impl AggregateState for DistinctAggregateState {
+    fn update(&mut self, value: &Value) -> PrismDBResult<()> {
+        self.update_ordered(value, Vec::new())
+    }
+
+    fn update_ordered(&mut self, value: &Value, sort_keys: Vec<Value>) -> PrismDBResult<()> {
+        if value.is_null() {
+            return Ok(());
+        }
+        let key = self.collation.group_key(value).into_owned();
+        if self.seen.insert(OrderedValue(key)) {
+            self.values.push((value.clone(), sort_keys));
+        }
+        Ok(())
+    }
+
+    fn finalize(&self) -> PrismDBResult<Value> {
+        let mut state = self.inner.clone_box();
+        for (value, sort_keys) in &self.values {
+            state.update_ordered(value, sort_keys.clone())?;
+        }
+        state.finalize()
+    }
+
+    fn merge(&mut self, other: Box<dyn AggregateState>) -> PrismDBResult<()> {
+        self.combine(downcast_state(other.as_ref())?)
+    }
+
+    fn combine(&mut self, other_distinct: &Self) -> PrismDBResult<()> {
+        for (value, sort_keys) in &other_distinct.values {
+            self.update_ordered(value, sort_keys.clone())?;
         }
         Ok(())
     }
@@ -2565,6 +3145,55 @@ impl AggregateState for RegrR2State {
     }
 }
 
+/// Create an aggregate state configured by constant arguments: STRING_AGG's
+/// separator, the fraction of PERCENTILE_CONT, PERCENTILE_DISC and
+/// APPROX_QUANTILE, or RESERVOIR_SAMPLE's size and seed. Without parameters
+/// this is `create_aggregate_state`.
+pub fn create_aggregate_state_with_parameters(
+    function_name: &str,
+    parameters: &[Value],
+) -> PrismDBResult<Box<dyn AggregateState>> {
+    if function_name.eq_ignore_ascii_case("RESERVOIR_SAMPLE") {
+        return Ok(Box::new(ReservoirSampleState::from_parameters(parameters)?));
+    }
+    let Some(parameter) = parameters.first() else {
+        return create_aggregate_state(function_name);
+    };
+    if parameters.len() > 1 {
+        return Err(PrismDBError::InvalidArgument(format!(
+            "Too many arguments for aggregate function {}",
+            function_name
+        )));
+    }
+
+    let fraction = || -> PrismDBResult<f64> {
+        let fraction = parameter.try_as_f64()?;
+        if !(0.0..=1.0).contains(&fraction) {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "{} fraction must be between 0 and 1, got {}",
+                function_name.to_uppercase(),
+                fraction
+            )));
+        }
+        Ok(fraction)
+    };
+    match function_name.to_uppercase().as_str() {
+        "STRING_AGG" => match parameter {
+            Value::Varchar(separator) | Value::Char(separator) => {
+                Ok(Box::new(StringAggState::new(separator.clone())))
+            }
+            Value::Null => Ok(Box::new(StringAggState::new(String::new()))),
+            other => Ok(Box::new(StringAggState::new(other.to_string()))),
+        },
+        "PERCENTILE_CONT" => Ok(Box::new(PercentileContState::new(fraction()?))),
+        "PERCENTILE_DISC" => Ok(Box::new(PercentileDiscState::new(fraction()?))),
+        "APPROX_QUANTILE" => Ok(Box::new(ApproxQuantileState::new(fraction()?))),
+        _ => Err(PrismDBError::InvalidArgument(format!(
+            "Aggregate function {} does not take constant parameters",
+            function_name
+        ))),
+    }
+}
 
 /// Create an aggregate state by function name (helper for parallel aggregation)
 pub fn create_aggregate_state(function_name: &str) -> PrismDBResult<Box<dyn AggregateState>> {
@@ -2581,6 +3210,7 @@ pub fn create_aggregate_state(function_name: &str) -> PrismDBResult<Box<dyn Aggr
         "APPROX_COUNT_DISTINCT" => Ok(Box::new(ApproxCountDistinctState::new())),
         "APPROX_QUANTILE" => Ok(Box::new(ApproxQuantileState::with_default_quantile())),
         "STRING_AGG" => Ok(Box::new(StringAggState::new(", ".to_string()))),
+        "ARRAY_AGG" | "LIST" => Ok(Box::new(ArrayAggState::new())),
         "PERCENTILE_CONT" => Ok(Box::new(PercentileContState::new(0.5))),
         "PERCENTILE_DISC" => Ok(Box::new(PercentileDiscState::new(0.5))),
         "COVAR_POP" => Ok(Box::new(CovarPopState::new())),
@@ -6,10 +6,11 @@
 use crate::common::{PrismDBResult, error::PrismDBError};
 use crate::expression::expression::{
     CastExpression, ColumnRefExpression, ComparisonExpression, ComparisonType, ConstantExpression,
-    ExpressionRef, FunctionExpression,
+    ExpressionRef, FunctionExpression, ParameterExpression,
 };
+use crate::expression::function::FunctionRegistry;
 use crate::parser::ast;
-use crate::types::{LogicalType, Value};
+use crate::types::{Collation, LogicalType, TypeUtils, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -52,6 +53,8 @@ pub struct ExpressionBinder {
     catalog: Option<Arc<std::sync::RwLock<crate::catalog::Catalog>>>,
     transaction_manager: Option<Arc<crate::storage::transaction::TransactionManager>>,
     ctes: std::collections::HashMap<String, crate::planner::LogicalPlan>,
+    /// Types of the query's parameters, by index
+    parameter_types: Vec<LogicalType>,
 }
 
 impl ExpressionBinder {
@@ -61,6 +64,7 @@ impl ExpressionBinder {
             catalog: None,
             transaction_manager: None,
             ctes: std::collections::HashMap::new(),
+            parameter_types: Vec::new(),
         }
     }
 
@@ -73,6 +77,7 @@ impl ExpressionBinder {
             catalog: Some(catalog),
             transaction_manager: None,
             ctes: std::collections::HashMap::new(),
+            parameter_types: Vec::new(),
         }
     }
 
@@ -86,6 +91,7 @@ impl ExpressionBinder {
             catalog: Some(catalog),
             transaction_manager: Some(transaction_manager),
             ctes: std::collections::HashMap::new(),
+            parameter_types: Vec::new(),
         }
     }
 
@@ -100,9 +106,16 @@ impl ExpressionBinder {
             catalog: Some(catalog),
             transaction_manager: Some(transaction_manager),
             ctes,
+            parameter_types: Vec::new(),
         }
     }
 
+    /// Bind `Parameter(i)` to a parameter of type `parameter_types[i]`
+    pub fn with_parameter_types(mut self, parameter_types: Vec<LogicalType>) -> Self {
+        self.parameter_types = parameter_types;
+        self
+    }
+
     /// Check if a function name is an aggregate function
     fn is_aggregate_function_name(name: &str) -> bool {
         matches!(
@@ -117,6 +130,13 @@ impl ExpressionBinder {
 
         match expr {
             ast::Expression::Literal(literal) => self.bind_literal(literal),
+            ast::Expression::Parameter(index) => match self.parameter_types.get(*index) {
+                Some(return_type) => Ok(Arc::new(ParameterExpression::new(*index, return_type.clone()))),
+                None => Err(PrismDBError::InvalidArgument(format!(
+                    "No type known for parameter {}",
+                    index
+                ))),
+            },
             ast::Expression::ColumnReference { table, column } => {
                 self.bind_column_ref(table.as_deref(), column)
             }
@@ -130,14 +150,21 @@ impl ExpressionBinder {
                 self.bind_function_call(name, arguments, is_agg)
             }
             ast::Expression::AggregateFunction {
-                name,
-                arguments,
-                distinct: _,
+                name, arguments, ..
             } => self.bind_function_call(name, arguments, true),
             ast::Expression::Cast {
                 expression,
                 data_type,
-            } => self.bind_cast(expression, data_type),
+                try_cast,
+            } => self.bind_cast(expression, data_type, *try_cast),
+            // The collation applies where the value is compared, not to the value itself
+            ast::Expression::Collate {
+                expression,
+                collation,
+            } => {
+                Collation::from_name(collation)?;
+                self.bind_expression(expression)
+            }
             ast::Expression::Binary {
                 left,
                 operator,
@@ -212,6 +239,18 @@ impl ExpressionBinder {
                 results,
                 else_result,
             } => self.bind_case(operand, conditions, results, else_result),
+            ast::Expression::Between {
+                expression,
+                low,
+                high,
+                not,
+            } => self.bind_between(expression, low, high, *not, false),
+            ast::Expression::BetweenSymmetric {
+                expression,
+                low,
+                high,
+                not,
+            } => self.bind_between(expression, low, high, *not, true),
             _ => Err(PrismDBError::NotImplemented(format!(
                 "Binding not implemented for expression: {:?}",
                 expr
@@ -224,9 +263,13 @@ impl ExpressionBinder {
         let value = match literal {
             ast::LiteralValue::Null => Value::Null,
             ast::LiteralValue::Boolean(b) => Value::Boolean(*b),
-            ast::LiteralValue::Integer(i) => Value::Integer(*i as i32),
+            ast::LiteralValue::Integer(i) => match i32::try_from(*i) {
+                Ok(i) => Value::Integer(i),
+                Err(_) => Value::BigInt(*i),
+            },
             ast::LiteralValue::Float(f) => Value::Double(*f),
             ast::LiteralValue::String(s) => Value::Varchar(s.clone()),
+            ast::LiteralValue::Blob(bytes) => Value::Blob(bytes.clone()),
             ast::LiteralValue::Date(d) => Value::Varchar(d.clone()), // TODO: proper date handling
             ast::LiteralValue::Time(t) => Value::Varchar(t.clone()), // TODO: proper time handling
             ast::LiteralValue::Timestamp(ts) => Value::Varchar(ts.clone()), // TODO: proper timestamp handling
@@ -251,8 +294,24 @@ impl ExpressionBinder {
         let binding = match self.bind_column_reference(&column_to_lookup) {
             Ok(b) => b,
             Err(_) if table.is_some() => {
-                // If qualified lookup failed, try unqualified
-                self.bind_column_reference(column)?
+                // A table in scope without the column doesn't have it, but the
+                // columns of a derived table keep their own qualifiers or none
+                let prefix = format!("{}.", table.unwrap_or_default());
+                let in_scope = self
+                    .context
+                    .column_bindings
+                    .iter()
+                    .any(|binding| binding.column_name.starts_with(&prefix));
+                let not_found = || {
+                    crate::common::error::PrismDBError::NotFound(crate::common::error::ObjectKind::Column, format!(
+                        "Column '{}' not found",
+                        column_to_lookup
+                    ))
+                };
+                if in_scope {
+                    return Err(not_found());
+                }
+                self.bind_column_reference(column).map_err(|_| not_found())?
             }
             Err(e) => return Err(e),
         };
@@ -295,8 +354,23 @@ impl ExpressionBinder {
         // Determine return type
         let return_type = if is_aggregate {
             self.bind_aggregate_function(name, &arg_types)?
+        } else if let Some(return_type) = self.bind_special_function(name, &arg_types)? {
+            return_type
         } else {
-            self.bind_function(name, &arg_types)?
+            // Arguments are cast to the parameter types of the overload chosen
+            let overload = FunctionRegistry::builtin().resolve(name, &arg_types)?;
+            bound_args = bound_args
+                .into_iter()
+                .zip(&overload.argument_types)
+                .map(|(arg, parameter_type)| -> ExpressionRef {
+                    if arg.return_type() == parameter_type {
+                        arg
+                    } else {
+                        Arc::new(CastExpression::new(arg, parameter_type.clone(), false))
+                    }
+                })
+                .collect();
+            overload.return_type.clone()
         };
 
         let func_expr = FunctionExpression::new(name.to_string(), return_type, bound_args);
@@ -309,16 +383,36 @@ impl ExpressionBinder {
         &self,
         expression: &ast::Expression,
         target_type: &LogicalType,
+        try_cast: bool,
     ) -> PrismDBResult<ExpressionRef> {
         let bound_expr = self.bind_expression(expression)?;
 
         // Check if cast is valid
         self.check_cast_validity(bound_expr.return_type(), target_type)?;
 
-        let cast_expr = CastExpression::new(bound_expr, target_type.clone(), false);
+        let cast_expr = CastExpression::new(bound_expr, target_type.clone(), try_cast);
         Ok(Arc::new(cast_expr))
     }
 
+    /// Collation a comparison between `left` and `right` uses, from a `COLLATE`
+    /// on either side; both sides naming different collations is an error
+    fn comparison_collation(
+        left: &ast::Expression,
+        right: &ast::Expression,
+    ) -> PrismDBResult<Option<Collation>> {
+        let left = left.collation().map(Collation::from_name).transpose()?;
+        let right = right.collation().map(Collation::from_name).transpose()?;
+        match (left, right) {
+            (Some(left), Some(right)) if left != right => {
+                Err(PrismDBError::InvalidValue(format!(
+                    "Conflicting collations {} and {}",
+                    left, right
+                )))
+            }
+            (left, right) => Ok(left.or(right)),
+        }
+    }
+
     /// Bind a binary operation
     fn bind_binary_op(
         &self,
@@ -330,43 +424,24 @@ impl ExpressionBinder {
         let bound_right = self.bind_expression(right)?;
 
         // Handle comparison operators with ComparisonExpression
+        let comparison_type = match op {
+            ast::BinaryOperator::Equals => Some(ComparisonType::Equal),
+            ast::BinaryOperator::NotEquals => Some(ComparisonType::NotEqual),
+            ast::BinaryOperator::LessThan => Some(ComparisonType::LessThan),
+            ast::BinaryOperator::LessThanOrEqual => Some(ComparisonType::LessThanOrEqual),
+            ast::BinaryOperator::GreaterThan => Some(ComparisonType::GreaterThan),
+            ast::BinaryOperator::GreaterThanOrEqual => Some(ComparisonType::GreaterThanOrEqual),
+            _ => None,
+        };
+        if let Some(comparison_type) = comparison_type {
+            let comp_expr = ComparisonExpression::new(comparison_type, bound_left, bound_right);
+            return Ok(Arc::new(match Self::comparison_collation(left, right)? {
+                Some(collation) => comp_expr.with_collation(collation),
+                None => comp_expr,
+            }));
+        }
+
         match op {
-            ast::BinaryOperator::Equals => {
-                let comp_expr =
-                    ComparisonExpression::new(ComparisonType::Equal, bound_left, bound_right);
-                Ok(Arc::new(comp_expr))
-            }
-            ast::BinaryOperator::NotEquals => {
-                let comp_expr =
-                    ComparisonExpression::new(ComparisonType::NotEqual, bound_left, bound_right);
-                Ok(Arc::new(comp_expr))
-            }
-            ast::BinaryOperator::LessThan => {
-                let comp_expr =
-                    ComparisonExpression::new(ComparisonType::LessThan, bound_left, bound_right);
-                Ok(Arc::new(comp_expr))
-            }
-            ast::BinaryOperator::LessThanOrEqual => {
-                let comp_expr = ComparisonExpression::new(
-                    ComparisonType::LessThanOrEqual,
-                    bound_left,
-                    bound_right,
-                );
-                Ok(Arc::new(comp_expr))
-            }
-            ast::BinaryOperator::GreaterThan => {
-                let comp_expr =
-                    ComparisonExpression::new(ComparisonType::GreaterThan, bound_left, bound_right);
-                Ok(Arc::new(comp_expr))
-            }
-            ast::BinaryOperator::GreaterThanOrEqual => {
-                let comp_expr = ComparisonExpression::new(
-                    ComparisonType::GreaterThanOrEqual,
-                    bound_left,
-                    bound_right,
-                );
-                Ok(Arc::new(comp_expr))
-            }
             // Arithmetic and logical operators as function calls
             ast::BinaryOperator::Add => {
                 let return_type = TypeInference::infer_binary_type(
@@ -405,7 +480,7 @@ impl ExpressionBinder {
                 Ok(Arc::new(func_expr))
             }
             ast::BinaryOperator::Divide => {
-                let return_type = TypeInference::infer_binary_type(
+                let return_type = TypeInference::infer_division_type(
                     bound_left.return_type(),
                     bound_right.return_type(),
                 )?;
@@ -416,6 +491,18 @@ impl ExpressionBinder {
                 );
                 Ok(Arc::new(func_expr))
             }
+            ast::BinaryOperator::IntegerDivide => {
+                let return_type = TypeInference::infer_binary_type(
+                    bound_left.return_type(),
+                    bound_right.return_type(),
+                )?;
+                let func_expr = FunctionExpression::new(
+                    "INTEGER_DIVIDE".to_string(),
+                    return_type,
+                    vec![bound_left, bound_right],
+                );
+                Ok(Arc::new(func_expr))
+            }
             ast::BinaryOperator::Modulo => {
                 let return_type = TypeInference::infer_binary_type(
                     bound_left.return_type(),
@@ -493,6 +580,54 @@ impl ExpressionBinder {
         }
     }
 
+    /// Bind `x [NOT] BETWEEN [SYMMETRIC] low AND high`
+    ///
+    /// BETWEEN is `x >= low AND x <= high`. SYMMETRIC also accepts the bounds in
+    /// the other order, `(x BETWEEN low AND high) OR (x BETWEEN high AND low)`,
+    /// which is unknown rather than false when a bound is NULL.
+    fn bind_between(
+        &self,
+        expression: &ast::Expression,
+        low: &ast::Expression,
+        high: &ast::Expression,
+        not: bool,
+        symmetric: bool,
+    ) -> PrismDBResult<ExpressionRef> {
+        let value = self.bind_expression(expression)?;
+        let low = self.bind_expression(low)?;
+        let high = self.bind_expression(high)?;
+
+        let logical = |name: &str, arguments: Vec<ExpressionRef>| -> ExpressionRef {
+            Arc::new(FunctionExpression::new(
+                name.to_string(),
+                LogicalType::Boolean,
+                arguments,
+            ))
+        };
+        let within = |low: &ExpressionRef, high: &ExpressionRef| {
+            let above: ExpressionRef = Arc::new(ComparisonExpression::new(
+                ComparisonType::GreaterThanOrEqual,
+                value.clone(),
+                low.clone(),
+            ));
+            let below: ExpressionRef = Arc::new(ComparisonExpression::new(
+                ComparisonType::LessThanOrEqual,
+                value.clone(),
+                high.clone(),
+            ));
+            logical("AND", vec![above, below])
+        };
+
+        let mut result = within(&low, &high);
+        if symmetric {
+            result = logical("OR", vec![result, within(&high, &low)]);
+        }
+        if not {
+            result = logical("NOT", vec![result]);
+        }
+        Ok(result)
+    }
+
     /// Bind IS NULL / IS NOT NULL expression
     fn bind_is_null(
         &self,
@@ -550,7 +685,7 @@ impl ExpressionBinder {
             )));
         }
 
-        Err(crate::common::error::PrismDBError::InvalidValue(format!(
+        Err(crate::common::error::PrismDBError::NotFound(crate::common::error::ObjectKind::Column, format!(
             "Column '{}' not found",
             column_name
         )))
@@ -562,9 +697,23 @@ impl ExpressionBinder {
         function_name: &str,
         args: &[LogicalType],
     ) -> PrismDBResult<LogicalType> {
-        // This would look up the function in the catalog
-        // For now, return a basic implementation
-        match function_name.to_uppercase().as_str() {
+        match self.bind_special_function(function_name, args)? {
+            Some(return_type) => Ok(return_type),
+            None => Ok(FunctionRegistry::builtin()
+                .resolve(function_name, args)?
+                .return_type
+                .clone()),
+        }
+    }
+
+    /// Return type of a function typed by its own rules rather than by an
+    /// overload of the function registry, or None for other functions
+    fn bind_special_function(
+        &self,
+        function_name: &str,
+        args: &[LogicalType],
+    ) -> PrismDBResult<Option<LogicalType>> {
+        let return_type = match function_name.to_uppercase().as_str() {
             "ABS" => {
                 if args.len() != 1 {
                     return Err(crate::common::error::PrismDBError::InvalidValue(
@@ -608,7 +757,7 @@ impl ExpressionBinder {
                 // Return the type of the first non-Invalid argument
                 for arg_type in args {
                     if *arg_type != LogicalType::Invalid {
-                        return Ok(arg_type.clone());
+                        return Ok(Some(arg_type.clone()));
                     }
                 }
                 // If all args are Invalid, return Integer as fallback
@@ -622,6 +771,101 @@ impl ExpressionBinder {
                 }
                 Ok(args[0].clone())
             }
+            "LIST_VALUE" | "LIST_PACK" => {
+                // Elements share the common type of the non-NULL arguments
+                let mut element_type: Option<LogicalType> = None;
+                for arg_type in args.iter().filter(|t| **t != LogicalType::Invalid) {
+                    element_type = Some(match element_type {
+                        None => arg_type.clone(),
+                        Some(current) => TypeUtils::get_max_type(&current, arg_type)?,
+                    });
+                }
+                Ok(LogicalType::List(Box::new(
+                    element_type.unwrap_or(LogicalType::Integer),
+                )))
+            }
+            "MAP_PACK" => {
+                // Keys and values alternate; each side takes its common type
+                if !args.len().is_multiple_of(2) {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(
+                        "MAP requires a value for every key".to_string(),
+                    ));
+                }
+                let common_type = |types: Vec<&LogicalType>| -> PrismDBResult<LogicalType> {
+                    let mut common: Option<LogicalType> = None;
+                    for arg_type in types.into_iter().filter(|t| **t != LogicalType::Invalid) {
+                        common = Some(match common {
+                            None => arg_type.clone(),
+                            Some(current) => TypeUtils::get_max_type(&current, arg_type)?,
+                        });
+                    }
+                    Ok(common.unwrap_or(LogicalType::Integer))
+                };
+                let key_type = common_type(args.iter().step_by(2).collect())?;
+                if !crate::expression::function::is_map_key_type(&key_type) {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "MAP keys must be a comparable scalar type, not {}",
+                        key_type
+                    )));
+                }
+                Ok(LogicalType::Map {
+                    key_type: Box::new(key_type),
+                    value_type: Box::new(common_type(args.iter().skip(1).step_by(2).collect())?),
+                })
+            }
+            "ELEMENT_AT" => {
+                if args.len() != 2 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(
+                        "ELEMENT_AT requires exactly 2 arguments".to_string(),
+                    ));
+                }
+                match &args[0] {
+                    LogicalType::List(element_type) => Ok((**element_type).clone()),
+                    LogicalType::Map { value_type, .. } => Ok((**value_type).clone()),
+                    other => Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "Cannot subscript a value of type {}",
+                        other
+                    ))),
+                }
+            }
+            "MAP_KEYS" | "MAP_VALUES" => {
+                if args.len() != 1 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} requires exactly 1 argument",
+                        function_name
+                    )));
+                }
+                match &args[0] {
+                    LogicalType::Map { key_type, .. } if function_name.eq_ignore_ascii_case("MAP_KEYS") => {
+                        Ok(LogicalType::List(key_type.clone()))
+                    }
+                    LogicalType::Map { value_type, .. } => Ok(LogicalType::List(value_type.clone())),
+                    _ => Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} requires a MAP argument",
+                        function_name
+                    ))),
+                }
+            }
+            "TO_JSON" => {
+                if args.len() != 1 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(
+                        "TO_JSON requires exactly 1 argument".to_string(),
+                    ));
+                }
+                Ok(LogicalType::JSON)
+            }
+            "ROW_TO_JSON" => {
+                // Bound with the row expanded into alternating column names and values
+                if !args.len().is_multiple_of(2) {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(
+                        "ROW_TO_JSON requires a table name".to_string(),
+                    ));
+                }
+                Ok(LogicalType::JSON)
+            }
+            "UNNEST" => Err(crate::common::error::PrismDBError::NotImplemented(
+                "UNNEST is only supported as a top-level SELECT list expression".to_string(),
+            )),
             "IS_NULL" | "IS_NOT_NULL" => {
                 if args.len() != 1 {
                     return Err(crate::common::error::PrismDBError::InvalidValue(format!(
@@ -703,11 +947,57 @@ impl ExpressionBinder {
                 }
                 Ok(LogicalType::Varchar)
             }
-            _ => Err(crate::common::error::PrismDBError::InvalidValue(format!(
-                "Unknown function: {}",
-                function_name
-            ))),
-        }
+            "TO_HEX" | "FROM_HEX" | "ENCODE" | "DECODE" => {
+                if args.len() != 1 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} requires exactly 1 argument",
+                        function_name
+                    )));
+                }
+                let upper = function_name.to_uppercase();
+                let blob_argument = matches!(upper.as_str(), "TO_HEX" | "DECODE");
+                let accepted = match &args[0] {
+                    LogicalType::Blob => blob_argument,
+                    LogicalType::Null | LogicalType::Invalid => true,
+                    other => other.is_string() && upper != "DECODE",
+                };
+                if !accepted {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} does not accept {}",
+                        function_name, args[0]
+                    )));
+                }
+                Ok(match upper.as_str() {
+                    "TO_HEX" | "DECODE" => LogicalType::Varchar,
+                    _ => LogicalType::Blob,
+                })
+            }
+            "TRY_DIVIDE" => {
+                if args.len() != 2 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(
+                        "TRY_DIVIDE requires exactly 2 arguments".to_string(),
+                    ));
+                }
+                TypeInference::infer_division_type(&args[0], &args[1])
+            }
+            "CURRENT_DATE" | "CURRENT_TIME" | "CURRENT_TIMESTAMP" | "NOW" | "RANDOM" | "VERSION" => {
+                if !args.is_empty() {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} takes no arguments",
+                        function_name
+                    )));
+                }
+                Ok(match function_name.to_uppercase().as_str() {
+                    "CURRENT_DATE" => LogicalType::Date,
+                    "CURRENT_TIME" => LogicalType::Time,
+                    "RANDOM" => LogicalType::Double,
+                    "VERSION" => LogicalType::Varchar,
+                    _ => LogicalType::Timestamp,
+                })
+            }
+            _ => return Ok(None),
+        };
+        return_type.map(Some)
     }
 
     /// Bind an aggregate function
@@ -833,6 +1123,25 @@ impl ExpressionBinder {
                 }
                 Ok(args[0].clone())
             }
+            "SUM" | "AVG" => {
+                if args.len() != 1 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} requires exactly 1 argument",
+                        function_name
+                    )));
+                }
+                Ok(LogicalType::Double)
+            }
+            "COUNT" => Ok(LogicalType::BigInt),
+            "MIN" | "MAX" => {
+                if args.len() != 1 {
+                    return Err(crate::common::error::PrismDBError::InvalidValue(format!(
+                        "{} requires exactly 1 argument",
+                        function_name
+                    )));
+                }
+                Ok(args[0].clone())
+            }
             "NTH_VALUE" => {
                 if args.len() != 2 {
                     return Err(crate::common::error::PrismDBError::InvalidValue(
@@ -1098,6 +1407,18 @@ impl TypeInference {
         }
     }
 
+    /// Infer return type for `/`, which is true division: integer operands yield DOUBLE
+    pub fn infer_division_type(left: &LogicalType, right: &LogicalType) -> PrismDBResult<LogicalType> {
+        match Self::infer_binary_type(left, right)? {
+            LogicalType::TinyInt
+            | LogicalType::SmallInt
+            | LogicalType::Integer
+            | LogicalType::BigInt
+            | LogicalType::HugeInt => Ok(LogicalType::Double),
+            other => Ok(other),
+        }
+    }
+
     /// Infer return type for comparison operations
     pub fn infer_comparison_type(
         _left: &LogicalType,
@@ -1,6 +1,8 @@
 //! Core expression types for PrismDB
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
+use crate::expression::operator::like_match;
 use crate::types::{DataChunk, LogicalType, Value, Vector};
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -249,6 +251,65 @@ impl Expression for ConstantExpression {
     }
 }
 
+/// Query parameter, whose value the execution context supplies
+///
+/// Plans from the plan cache hold these in place of the literals their
+/// query was written with, so one plan runs with each query's values.
+#[derive(Debug, Clone)]
+pub struct ParameterExpression {
+    base: BaseExpression,
+    index: usize,
+}
+
+impl ParameterExpression {
+    pub fn new(index: usize, return_type: LogicalType) -> Self {
+        Self {
+            base: BaseExpression::new(ExpressionType::Parameter, return_type),
+            index,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn value<'a>(&self, context: &'a crate::execution::ExecutionContext) -> PrismDBResult<&'a Value> {
+        context.parameter_values.get(self.index).ok_or_else(|| {
+            crate::common::error::PrismDBError::Internal(format!(
+                "No value supplied for parameter {}",
+                self.index
+            ))
+        })
+    }
+}
+
+impl Expression for ParameterExpression {
+    fn return_type(&self) -> &LogicalType {
+        &self.base.return_type
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn evaluate(&self, chunk: &DataChunk, context: &crate::execution::ExecutionContext) -> PrismDBResult<Vector> {
+        let value = self.value(context)?;
+        crate::types::Vector::from_values(&vec![value.clone(); chunk.count()])
+    }
+
+    fn evaluate_row(&self, _chunk: &DataChunk, _row_idx: usize, context: &crate::execution::ExecutionContext) -> PrismDBResult<Value> {
+        self.value(context).cloned()
+    }
+
+    fn is_deterministic(&self) -> bool {
+        true
+    }
+
+    fn is_nullable(&self) -> bool {
+        true
+    }
+}
+
 /// Column reference expression
 #[derive(Debug, Clone)]
 pub struct ColumnRefExpression {
@@ -360,6 +421,20 @@ impl FunctionExpression {
         &self.function_name
     }
 
+    /// Operands of an arithmetic operator after SET type_coercion
+    fn coerce_operands(
+        arg_values: Vec<Value>,
+        context: &crate::execution::ExecutionContext,
+    ) -> PrismDBResult<Vec<Value>> {
+        match <[Value; 2]>::try_from(arg_values) {
+            Ok([left, right]) => {
+                let (left, right) = context.type_coercion.coerce(left, right)?;
+                Ok(vec![left, right])
+            }
+            Err(arg_values) => Ok(arg_values),
+        }
+    }
+
     pub fn is_aggregate(&self) -> bool {
         self.is_aggregate
     }
@@ -384,6 +459,13 @@ impl Expression for FunctionExpression {
             arg_vectors.push(child_result);
         }
 
+        // Operators honor SET arithmetic_errors for division by zero and overflow
+        let is_arithmetic = matches!(
+            self.function_name.to_uppercase().as_str(),
+            "ADD" | "SUBTRACT" | "MULTIPLY" | "DIVIDE" | "INTEGER_DIVIDE" | "MODULO" | "MOD"
+                | "NEGATE"
+        );
+
         // For each row in the chunk, evaluate the function
         let row_count = chunk.count();
         let mut result_values = Vec::with_capacity(row_count);
@@ -397,7 +479,15 @@ impl Expression for FunctionExpression {
             }
 
             // Evaluate the function for this row
-            let result = evaluate_builtin_function(&self.function_name, &arg_values)?;
+            if is_arithmetic {
+                arg_values = Self::coerce_operands(arg_values, context)?;
+            }
+            let result = evaluate_builtin_function(&self.function_name, &arg_values);
+            let result = if is_arithmetic {
+                context.arithmetic_errors.apply(result)?
+            } else {
+                result?
+            };
             result_values.push(result);
         }
 
@@ -406,22 +496,32 @@ impl Expression for FunctionExpression {
     }
 
     fn evaluate_row(&self, chunk: &DataChunk, row_idx: usize, context: &crate::execution::ExecutionContext) -> PrismDBResult<Value> {
-        if self.children.is_empty() {
-            return Err(PrismDBError::InvalidValue(
-                "Function expression has no children".to_string(),
-            ));
-        }
+        use crate::expression::function::evaluate_builtin_function;
+
+        let arg_values = self
+            .children
+            .iter()
+            .map(|child| child.evaluate_row(chunk, row_idx, context))
+            .collect::<PrismDBResult<Vec<_>>>()?;
 
-        // For now, just return the first child's evaluation
-        self.children[0].evaluate_row(chunk, row_idx, context)
+        // Operators honor SET arithmetic_errors for division by zero and overflow
+        if matches!(
+            self.function_name.to_uppercase().as_str(),
+            "ADD" | "SUBTRACT" | "MULTIPLY" | "DIVIDE" | "INTEGER_DIVIDE" | "MODULO" | "MOD"
+                | "NEGATE"
+        ) {
+            let arg_values = Self::coerce_operands(arg_values, context)?;
+            context
+                .arithmetic_errors
+                .apply(evaluate_builtin_function(&self.function_name, &arg_values))
+        } else {
+            evaluate_builtin_function(&self.function_name, &arg_values)
+        }
     }
 
     fn is_deterministic(&self) -> bool {
         // Most functions are deterministic, but some like RANDOM() are not
-        !matches!(
-            self.function_name.to_uppercase().as_str(),
-            "RANDOM" | "NOW" | "CURRENT_TIMESTAMP"
-        )
+        !crate::expression::function::FunctionRegistry::builtin().is_volatile(&self.function_name)
     }
 
     fn is_nullable(&self) -> bool {
@@ -458,6 +558,14 @@ impl CastExpression {
     pub fn try_cast(&self) -> bool {
         self.try_cast
     }
+
+    /// Cast one value; TRY_CAST turns a failed conversion into NULL
+    fn cast_value(&self, value: Value) -> PrismDBResult<Value> {
+        match value.cast_to(&self.base.return_type) {
+            Err(_) if self.try_cast => Ok(Value::Null),
+            result => result,
+        }
+    }
 }
 
 impl Expression for CastExpression {
@@ -471,19 +579,15 @@ impl Expression for CastExpression {
 
     fn evaluate(&self, chunk: &DataChunk, context: &crate::execution::ExecutionContext) -> PrismDBResult<Vector> {
         let child_result = self.child.evaluate(chunk, context)?;
-        // In a real implementation, we'd perform cast here
-        // For now, just return child result
-        Ok(child_result)
+        let values = (0..chunk.count())
+            .map(|row_idx| self.cast_value(child_result.get_value(row_idx)?))
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        Vector::from_values(&values)
     }
 
     fn evaluate_row(&self, chunk: &DataChunk, row_idx: usize, context: &crate::execution::ExecutionContext) -> PrismDBResult<Value> {
         let child_value = self.child.evaluate_row(chunk, row_idx, context)?;
-        // Perform cast
-        if self.try_cast {
-            child_value.cast_to(&self.base.return_type)
-        } else {
-            child_value.cast_to(&self.base.return_type)
-        }
+        self.cast_value(child_value)
     }
 
     fn is_deterministic(&self) -> bool {
@@ -525,6 +629,8 @@ pub struct ComparisonExpression {
     left: ExpressionRef,
     right: ExpressionRef,
     comparison_type: ComparisonType,
+    /// Collation strings are compared by; `None` compares code points
+    collation: Option<crate::types::Collation>,
 }
 
 impl ComparisonExpression {
@@ -534,9 +640,20 @@ impl ComparisonExpression {
             comparison_type,
             left,
             right,
+            collation: None,
         }
     }
 
+    /// Compare strings by `collation`
+    pub fn with_collation(mut self, collation: crate::types::Collation) -> Self {
+        self.collation = Some(collation);
+        self
+    }
+
+    pub fn collation(&self) -> Option<&crate::types::Collation> {
+        self.collation.as_ref()
+    }
+
     pub fn comparison_type(&self) -> &ComparisonType {
         &self.comparison_type
     }
@@ -576,7 +693,7 @@ impl Expression for ComparisonExpression {
         for row_idx in 0..chunk.count() {
             let left_value = left_result.get_value(row_idx)?;
             let right_value = right_result.get_value(row_idx)?;
-            let result = self.compare_values(&left_value, &right_value)?;
+            let result = self.compare_values(left_value, right_value, context)?;
             results.push(result);
         }
 
@@ -586,7 +703,7 @@ impl Expression for ComparisonExpression {
     fn evaluate_row(&self, chunk: &DataChunk, row_idx: usize, context: &crate::execution::ExecutionContext) -> PrismDBResult<Value> {
         let left_value = self.left.evaluate_row(chunk, row_idx, context)?;
         let right_value = self.right.evaluate_row(chunk, row_idx, context)?;
-        self.compare_values(&left_value, &right_value)
+        self.compare_values(left_value, right_value, context)
     }
 
     fn is_deterministic(&self) -> bool {
@@ -603,19 +720,40 @@ impl Expression for ComparisonExpression {
 }
 
 impl ComparisonExpression {
-    fn compare_values(&self, left: &Value, right: &Value) -> PrismDBResult<Value> {
+    fn compare_values(
+        &self,
+        left: Value,
+        right: Value,
+        context: &crate::execution::ExecutionContext,
+    ) -> PrismDBResult<Value> {
+        use ComparisonType::*;
+
+        // Comparing with NULL is unknown
+        let ordered = matches!(
+            self.comparison_type,
+            Equal | NotEqual | LessThan | LessThanOrEqual | GreaterThan | GreaterThanOrEqual
+        );
+        if ordered && (left.is_null() || right.is_null()) {
+            return Ok(Value::Null);
+        }
+
+        // A string compared with another type is cast to it, or rejected under SET type_coercion
+        let (left, right) = match self.comparison_type {
+            Like | NotLike | ILike | NotILike | In | NotIn => (left, right),
+            _ => context.type_coercion.coerce(left, right)?,
+        };
+        let (left, right) = (&left, &right);
+
         let result = match self.comparison_type {
-            ComparisonType::Equal => left.compare(right)? == std::cmp::Ordering::Equal,
-            ComparisonType::NotEqual => left.compare(right)? != std::cmp::Ordering::Equal,
-            ComparisonType::LessThan => left.compare(right)? == std::cmp::Ordering::Less,
+            ComparisonType::Equal => self.compare(left, right)? == std::cmp::Ordering::Equal,
+            ComparisonType::NotEqual => self.compare(left, right)? != std::cmp::Ordering::Equal,
+            ComparisonType::LessThan => self.compare(left, right)? == std::cmp::Ordering::Less,
             ComparisonType::LessThanOrEqual => {
-                let cmp = left.compare(right)?;
-                cmp == std::cmp::Ordering::Less || cmp == std::cmp::Ordering::Equal
+                self.compare(left, right)? != std::cmp::Ordering::Greater
             }
-            ComparisonType::GreaterThan => left.compare(right)? == std::cmp::Ordering::Greater,
+            ComparisonType::GreaterThan => self.compare(left, right)? == std::cmp::Ordering::Greater,
             ComparisonType::GreaterThanOrEqual => {
-                let cmp = left.compare(right)?;
-                cmp == std::cmp::Ordering::Greater || cmp == std::cmp::Ordering::Equal
+                self.compare(left, right)? != std::cmp::Ordering::Less
             }
             // Simplified implementations for other comparison types
             ComparisonType::Like => self.like_comparison(left, right)?,
@@ -632,13 +770,8 @@ impl ComparisonExpression {
     }
 
     fn like_comparison(&self, left: &Value, right: &Value) -> PrismDBResult<bool> {
-        // Simplified LIKE implementation
         match (left, right) {
-            (Value::Varchar(l), Value::Varchar(r)) => {
-                // Convert SQL LIKE pattern to regex
-                let pattern = r.replace('%', ".*").replace('_', ".");
-                Ok(l.contains(&pattern))
-            }
+            (Value::Varchar(l), Value::Varchar(r)) => Ok(like_match(l, r)),
             _ => Ok(false),
         }
     }
@@ -647,8 +780,7 @@ impl ComparisonExpression {
         // Case-insensitive LIKE
         match (left, right) {
             (Value::Varchar(l), Value::Varchar(r)) => {
-                let pattern = r.to_lowercase().replace('%', ".*").replace('_', ".");
-                Ok(l.to_lowercase().contains(&pattern))
+                Ok(like_match(&l.to_lowercase(), &r.to_lowercase()))
             }
             _ => Ok(false),
         }
@@ -669,7 +801,19 @@ impl ComparisonExpression {
         } else if left.is_null() || right.is_null() {
             Ok(true)
         } else {
-            Ok(left.compare(right)? != std::cmp::Ordering::Equal)
+            Ok(self.compare(left, right)? != std::cmp::Ordering::Equal)
+        }
+    }
+
+    /// Order of two values, comparing strings by the collation if there is one
+    fn compare(&self, left: &Value, right: &Value) -> PrismDBResult<std::cmp::Ordering> {
+        match self
+            .collation
+            .as_ref()
+            .and_then(|collation| collation.compare_values(left, right))
+        {
+            Some(ordering) => Ok(ordering),
+            None => left.compare(right),
         }
     }
 }
@@ -800,7 +944,7 @@ impl SubqueryExpression {
                     tables.insert(a.clone());
                 }
             }
-            TableReference::TableFunction { alias, .. } => {
+            TableReference::TableFunction { alias, .. } | TableReference::Values { alias, .. } => {
                 if let Some(a) = alias {
                     tables.insert(a.clone());
                 }
@@ -873,9 +1017,9 @@ impl SubqueryExpression {
         if self.is_correlated && row_idx.is_some() {
             let row_idx = row_idx.unwrap();
 
-            let catalog_lock = catalog.read().unwrap();
+            let catalog_lock = catalog.read_or_err()?;
             let schema_ref = catalog_lock.get_schema("main")?;
-            let schema_lock = schema_ref.read().unwrap();
+            let schema_lock = schema_ref.read_or_err()?;
             let all_tables = schema_lock.list_tables();
 
             for outer_table_alias in &self.outer_tables {
@@ -899,7 +1043,7 @@ impl SubqueryExpression {
 
                 for table_name in candidates {
                     if let Ok(table_ref) = schema_lock.get_table(&table_name) {
-                        let table_lock = table_ref.read().unwrap();
+                        let table_lock = table_ref.read_or_err()?;
                         let table_info = table_lock.get_table_info();
 
                         if table_info.columns.len() == chunk.column_count() {
@@ -1080,7 +1224,7 @@ impl ExistsExpression {
                     tables.insert(a.clone());
                 }
             }
-            TableReference::TableFunction { alias, .. } => {
+            TableReference::TableFunction { alias, .. } | TableReference::Values { alias, .. } => {
                 if let Some(a) = alias {
                     tables.insert(a.clone());
                 }
@@ -1167,9 +1311,9 @@ impl ExistsExpression {
             // The chunk contains the outer row data. We need to figure out which table it's from.
             // Strategy: Try each outer table name, and if it's an alias, try common table names
 
-            let catalog_lock = catalog.read().unwrap();
+            let catalog_lock = catalog.read_or_err()?;
             let schema_ref = catalog_lock.get_schema("main")?;
-            let schema_lock = schema_ref.read().unwrap();
+            let schema_lock = schema_ref.read_or_err()?;
 
             // Get all table names from the schema to try
             let all_tables = schema_lock.list_tables();
@@ -1198,7 +1342,7 @@ impl ExistsExpression {
 
                 for table_name in candidates {
                     if let Ok(table_ref) = schema_lock.get_table(&table_name) {
-                        let table_lock = table_ref.read().unwrap();
+                        let table_lock = table_ref.read_or_err()?;
                         let table_info = table_lock.get_table_info();
 
                         // Check if this table's column count matches the chunk
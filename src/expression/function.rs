@@ -1,9 +1,11 @@
 //! Function definitions and implementations for PrismDB expressions
 
-use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::types::{LogicalType, Value};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::types::{LogicalType, TypeUtils, Value};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::sync::OnceLock;
 
 /// Function types
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -16,6 +18,17 @@ pub enum FunctionType {
     Window,
 }
 
+impl FunctionType {
+    /// Lower-case name, as listed by SHOW FUNCTIONS
+    pub fn name(&self) -> &'static str {
+        match self {
+            FunctionType::Scalar => "scalar",
+            FunctionType::Aggregate => "aggregate",
+            FunctionType::Window => "window",
+        }
+    }
+}
+
 /// Function classification
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum FunctionClassification {
@@ -36,6 +49,9 @@ pub enum FunctionClassification {
 }
 
 /// Function metadata
+///
+/// A parameter or return type of `LogicalType::Invalid` stands for any type,
+/// as for the argument of `COUNT(x)`; signatures show it as `ANY`.
 #[derive(Debug, Clone)]
 pub struct FunctionInfo {
     pub name: String,
@@ -82,6 +98,31 @@ impl FunctionInfo {
         self.is_deterministic = false;
         self
     }
+
+    /// The function's name and parameter types, as in `ROUND(DOUBLE, INTEGER)`
+    ///
+    /// Variadic functions end in `...`, as in `CONCAT(VARCHAR, VARCHAR, ...)`.
+    pub fn signature(&self) -> String {
+        let signature = signature(&self.name, &self.argument_types);
+        if self.is_variadic {
+            format!("{}, ...)", signature.trim_end_matches(')'))
+        } else {
+            signature
+        }
+    }
+}
+
+fn signature(name: &str, types: &[LogicalType]) -> String {
+    let types: Vec<String> = types.iter().map(type_name).collect();
+    format!("{}({})", name.to_uppercase(), types.join(", "))
+}
+
+/// Name of a parameter or return type in a signature
+pub fn type_name(data_type: &LogicalType) -> String {
+    match data_type {
+        LogicalType::Invalid => "ANY".to_string(),
+        data_type => data_type.to_string(),
+    }
 }
 
 /// Built-in function registry
@@ -98,6 +139,12 @@ impl FunctionRegistry {
         registry
     }
 
+    /// The registry of built-in functions, built on first use
+    pub fn builtin() -> &'static FunctionRegistry {
+        static BUILTIN: OnceLock<FunctionRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(FunctionRegistry::new)
+    }
+
     /// Register a function
     pub fn register_function(&mut self, function_info: FunctionInfo) {
         let name = function_info.name.to_uppercase();
@@ -132,17 +179,173 @@ impl FunctionRegistry {
         None
     }
 
+    /// Resolve a call of `name` to the overload its arguments fit best
+    ///
+    /// Each argument must implicitly cast to its parameter's type, and the
+    /// overload whose casts cost least in total wins. Two overloads costing
+    /// the same make the call ambiguous, unless they differ only where the
+    /// arguments are NULL, which fit any type; the first registered is used
+    /// then. Variadic functions have no fixed parameter types to rank.
+    pub fn resolve(&self, name: &str, argument_types: &[LogicalType]) -> PrismDBResult<&FunctionInfo> {
+        let overloads: Vec<&FunctionInfo> = self
+            .functions
+            .get(&name.to_uppercase())
+            .into_iter()
+            .flatten()
+            .filter(|function| !function.is_variadic && function.function_type == FunctionType::Scalar)
+            .collect();
+        if overloads.is_empty() {
+            return Err(PrismDBError::NotFound(ObjectKind::Function, format!("Unknown function: {}", name)));
+        }
+
+        let mut best: Vec<&FunctionInfo> = Vec::new();
+        let mut best_cost = u32::MAX;
+        for overload in &overloads {
+            if overload.argument_types.len() != argument_types.len() {
+                continue;
+            }
+            let cost = argument_types
+                .iter()
+                .zip(&overload.argument_types)
+                .map(|(from, to)| from.implicit_cast_cost(to))
+                .sum::<Option<u32>>();
+            match cost {
+                Some(cost) if cost < best_cost => {
+                    best_cost = cost;
+                    best = vec![overload];
+                }
+                Some(cost) if cost == best_cost => best.push(overload),
+                _ => {}
+            }
+        }
+
+        let Some(&first) = best.first() else {
+            let candidates: Vec<String> = overloads.iter().map(|overload| overload.signature()).collect();
+            return Err(PrismDBError::TypeMismatch(format!(
+                "No overload of {} matches {}; candidates are {}",
+                name.to_uppercase(),
+                signature(name, argument_types),
+                candidates.join(", ")
+            )));
+        };
+        let typed = |function: &FunctionInfo| -> Vec<LogicalType> {
+            argument_types
+                .iter()
+                .zip(&function.argument_types)
+                .filter(|(argument, _)| !matches!(argument, LogicalType::Null | LogicalType::Invalid))
+                .map(|(_, parameter)| parameter.clone())
+                .collect()
+        };
+        if let Some(other) = best.iter().find(|overload| typed(overload) != typed(first)) {
+            return Err(PrismDBError::InvalidValue(format!(
+                "Call {} is ambiguous between {} and {}",
+                signature(name, argument_types),
+                first.signature(),
+                other.signature()
+            )));
+        }
+        Ok(first)
+    }
+
+    /// Whether `name` may return different results for the same arguments,
+    /// as RANDOM() and NOW() do; names not in the registry are taken to be
+    /// deterministic
+    pub fn is_volatile(&self, name: &str) -> bool {
+        self.functions
+            .get(&name.to_uppercase())
+            .is_some_and(|functions| functions.iter().any(|function| !function.is_deterministic))
+    }
+
     /// List all functions
     pub fn list_functions(&self) -> Vec<&str> {
         self.functions.keys().map(|s| s.as_str()).collect()
     }
 
+    /// Every overload of every function, by name and then in registration order
+    pub fn functions(&self) -> Vec<&FunctionInfo> {
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .flat_map(|name| &self.functions[name])
+            .collect()
+    }
+
     /// Register built-in functions
     fn register_builtin_functions(&mut self) {
         self.register_mathematical_functions();
         self.register_string_functions();
         self.register_conditional_functions();
         self.register_system_functions();
+        self.register_aggregate_functions();
+        self.register_window_functions();
+    }
+
+    /// Aggregates are typed by the binder's own rules; these entries list them
+    fn register_aggregate_functions(&mut self) {
+        use LogicalType::{BigInt, Boolean, Double, Invalid, Varchar};
+
+        let aggregates: [(&str, Vec<LogicalType>, LogicalType); 22] = [
+            ("count", vec![], BigInt),
+            ("count", vec![Invalid], BigInt),
+            ("sum", vec![Double], Double),
+            ("avg", vec![Double], Double),
+            ("min", vec![Invalid], Invalid),
+            ("max", vec![Invalid], Invalid),
+            ("stddev", vec![Double], Double),
+            ("variance", vec![Double], Double),
+            ("median", vec![Double], Double),
+            ("mode", vec![Invalid], Invalid),
+            ("approx_count_distinct", vec![Invalid], BigInt),
+            ("string_agg", vec![Varchar, Varchar], Varchar),
+            ("first", vec![Invalid], Invalid),
+            ("last", vec![Invalid], Invalid),
+            ("arg_min", vec![Invalid, Invalid], Invalid),
+            ("arg_max", vec![Invalid, Invalid], Invalid),
+            ("bool_and", vec![Boolean], Boolean),
+            ("bool_or", vec![Boolean], Boolean),
+            ("count_if", vec![Boolean], BigInt),
+            ("corr", vec![Double, Double], Double),
+            ("covar_pop", vec![Double, Double], Double),
+            ("covar_samp", vec![Double, Double], Double),
+        ];
+        for (name, argument_types, return_type) in aggregates {
+            self.register_function(FunctionInfo::new(
+                name.to_string(),
+                FunctionType::Aggregate,
+                FunctionClassification::Mathematical,
+                return_type,
+                argument_types,
+            ));
+        }
+    }
+
+    /// Window functions are typed by the binder's own rules; these entries list them
+    fn register_window_functions(&mut self) {
+        use LogicalType::{BigInt, Double, Integer, Invalid};
+
+        let windows: [(&str, Vec<LogicalType>, LogicalType); 11] = [
+            ("row_number", vec![], BigInt),
+            ("rank", vec![], BigInt),
+            ("dense_rank", vec![], BigInt),
+            ("percent_rank", vec![], Double),
+            ("cume_dist", vec![], Double),
+            ("ntile", vec![Integer], BigInt),
+            ("lag", vec![Invalid], Invalid),
+            ("lead", vec![Invalid], Invalid),
+            ("first_value", vec![Invalid], Invalid),
+            ("last_value", vec![Invalid], Invalid),
+            ("nth_value", vec![Invalid, Integer], Invalid),
+        ];
+        for (name, argument_types, return_type) in windows {
+            self.register_function(FunctionInfo::new(
+                name.to_string(),
+                FunctionType::Window,
+                FunctionClassification::Other,
+                return_type,
+                argument_types,
+            ));
+        }
     }
 
     fn register_mathematical_functions(&mut self) {
@@ -292,6 +495,23 @@ impl FunctionRegistry {
             vec![LogicalType::Double],
         ));
 
+        // ROUND of an integer is the integer itself
+        self.register_function(FunctionInfo::new(
+            "round".to_string(),
+            FunctionType::Scalar,
+            FunctionClassification::Mathematical,
+            LogicalType::Integer,
+            vec![LogicalType::Integer],
+        ));
+
+        self.register_function(FunctionInfo::new(
+            "round".to_string(),
+            FunctionType::Scalar,
+            FunctionClassification::Mathematical,
+            LogicalType::BigInt,
+            vec![LogicalType::BigInt],
+        ));
+
         // ROUND (2 arguments - with precision)
         self.register_function(FunctionInfo::new(
             "round".to_string(),
@@ -851,6 +1071,18 @@ impl FunctionRegistry {
             .non_deterministic(),
         );
 
+        // NOW, another name for CURRENT_TIMESTAMP
+        self.register_function(
+            FunctionInfo::new(
+                "now".to_string(),
+                FunctionType::Scalar,
+                FunctionClassification::System,
+                LogicalType::Timestamp,
+                vec![],
+            )
+            .non_deterministic(),
+        );
+
         // RANDOM
         self.register_function(
             FunctionInfo::new(
@@ -883,7 +1115,9 @@ impl Default for FunctionRegistry {
 /// Evaluate a built-in function
 pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResult<Value> {
     use crate::expression::math_functions;
-    use crate::expression::operator::{evaluate_binary_operator, OperatorType};
+    use crate::expression::operator::{
+        evaluate_binary_operator, evaluate_unary_operator, ArithmeticErrorMode, OperatorType,
+    };
 
     match name.to_uppercase().as_str() {
         // Arithmetic operators
@@ -919,6 +1153,27 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
             }
             evaluate_binary_operator(&OperatorType::Divide, &arguments[0], &arguments[1])
         }
+        "TRY_DIVIDE" => {
+            if arguments.len() != 2 {
+                return Err(PrismDBError::InvalidArgument(
+                    "TRY_DIVIDE requires 2 arguments".to_string(),
+                ));
+            }
+            // Division by zero and overflow give NULL whatever SET arithmetic_errors says
+            ArithmeticErrorMode::Null.apply(evaluate_binary_operator(
+                &OperatorType::Divide,
+                &arguments[0],
+                &arguments[1],
+            ))
+        }
+        "INTEGER_DIVIDE" => {
+            if arguments.len() != 2 {
+                return Err(PrismDBError::InvalidArgument(
+                    "INTEGER_DIVIDE requires 2 arguments".to_string(),
+                ));
+            }
+            evaluate_binary_operator(&OperatorType::IntegerDivide, &arguments[0], &arguments[1])
+        }
         "MODULO" | "MOD" => {
             if arguments.len() != 2 {
                 return Err(PrismDBError::InvalidArgument(
@@ -927,6 +1182,7 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
             }
             evaluate_binary_operator(&OperatorType::Modulo, &arguments[0], &arguments[1])
         }
+        "NEGATE" => evaluate_negate(arguments),
         "AND" => {
             if arguments.len() != 2 {
                 return Err(PrismDBError::InvalidArgument(
@@ -943,6 +1199,14 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
             }
             evaluate_binary_operator(&OperatorType::Or, &arguments[0], &arguments[1])
         }
+        "NOT" => {
+            if arguments.len() != 1 {
+                return Err(PrismDBError::InvalidArgument(
+                    "NOT requires 1 argument".to_string(),
+                ));
+            }
+            evaluate_unary_operator(&OperatorType::Not, &arguments[0])
+        }
         "LIKE" => {
             if arguments.len() != 2 {
                 return Err(PrismDBError::InvalidArgument(
@@ -1415,6 +1679,72 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
             string_functions::quote(&arguments[0])
         }
 
+        // Blob conversions
+        "TO_HEX" | "FROM_HEX" | "ENCODE" | "DECODE" => {
+            use crate::expression::string_functions;
+            if arguments.len() != 1 {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "{} requires 1 argument",
+                    name.to_uppercase()
+                )));
+            }
+            match name.to_uppercase().as_str() {
+                "TO_HEX" => string_functions::to_hex(&arguments[0]),
+                "FROM_HEX" => string_functions::from_hex(&arguments[0]),
+                "ENCODE" => string_functions::encode(&arguments[0]),
+                _ => string_functions::decode(&arguments[0]),
+            }
+        }
+
+        // List construction
+        "LIST_VALUE" | "LIST_PACK" => evaluate_list_value(arguments),
+
+        // Maps, and element access into lists and maps
+        "MAP_PACK" => evaluate_map_pack(arguments),
+        "ELEMENT_AT" => {
+            if arguments.len() != 2 {
+                return Err(PrismDBError::InvalidArgument(
+                    "ELEMENT_AT requires 2 arguments".to_string(),
+                ));
+            }
+            evaluate_element_at(&arguments[0], &arguments[1])
+        }
+        "MAP_KEYS" | "MAP_VALUES" => match arguments {
+            [Value::Null] => Ok(Value::Null),
+            [Value::Map(entries)] => Ok(Value::List(
+                entries
+                    .iter()
+                    .map(|(key, value)| if name.eq_ignore_ascii_case("MAP_KEYS") { key } else { value }.clone())
+                    .collect(),
+            )),
+            _ => Err(PrismDBError::InvalidArgument(format!(
+                "{} requires a MAP argument",
+                name
+            ))),
+        },
+
+        // JSON serialization
+        "TO_JSON" => match arguments {
+            [Value::Null] => Ok(Value::Null),
+            [value] => Ok(Value::JSON(value.to_json().to_string())),
+            _ => Err(PrismDBError::InvalidArgument(
+                "TO_JSON requires 1 argument".to_string(),
+            )),
+        },
+        "ROW_TO_JSON" => {
+            // Alternating column names and values, as the planner expands the row
+            let fields = arguments
+                .chunks(2)
+                .map(|pair| match pair {
+                    [name, value] => Ok((name.try_as_string()?, value.clone())),
+                    _ => Err(PrismDBError::InvalidArgument(
+                        "ROW_TO_JSON requires a table name".to_string(),
+                    )),
+                })
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            Ok(Value::JSON(Value::Struct(fields).to_json().to_string()))
+        }
+
         // Conditional functions
         "COALESCE" => evaluate_coalesce(arguments),
         "NULLIF" => evaluate_nullif(arguments),
@@ -1426,7 +1756,7 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
         // System functions
         "CURRENT_DATE" => evaluate_current_date(),
         "CURRENT_TIME" => evaluate_current_time(),
-        "CURRENT_TIMESTAMP" => evaluate_current_timestamp(),
+        "CURRENT_TIMESTAMP" | "NOW" => evaluate_current_timestamp(),
         "RANDOM" => {
             if !arguments.is_empty() {
                 return Err(PrismDBError::InvalidArgument(
@@ -1437,7 +1767,7 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
         }
         "VERSION" => evaluate_version(),
 
-        _ => Err(PrismDBError::InvalidType(format!(
+        _ => Err(PrismDBError::NotFound(ObjectKind::Function, format!(
             "Unknown function: {}",
             name
         ))),
@@ -1445,6 +1775,30 @@ pub fn evaluate_builtin_function(name: &str, arguments: &[Value]) -> PrismDBResu
 }
 
 // Mathematical function implementations
+fn evaluate_negate(arguments: &[Value]) -> PrismDBResult<Value> {
+    if arguments.len() != 1 {
+        return Err(PrismDBError::InvalidArgument(
+            "NEGATE requires exactly 1 argument".to_string(),
+        ));
+    }
+
+    use crate::expression::operator::overflow;
+
+    match &arguments[0] {
+        Value::Null => Ok(Value::Null),
+        Value::TinyInt(v) => v.checked_neg().map(Value::TinyInt).ok_or_else(|| overflow("negation")),
+        Value::SmallInt(v) => v.checked_neg().map(Value::SmallInt).ok_or_else(|| overflow("negation")),
+        Value::Integer(v) => v.checked_neg().map(Value::Integer).ok_or_else(|| overflow("negation")),
+        Value::BigInt(v) => v.checked_neg().map(Value::BigInt).ok_or_else(|| overflow("negation")),
+        Value::Float(v) => Ok(Value::Float(-v)),
+        Value::Double(v) => Ok(Value::Double(-v)),
+        other => Err(PrismDBError::Type(format!(
+            "Cannot negate {}",
+            other.get_type()
+        ))),
+    }
+}
+
 #[allow(dead_code)]
 fn evaluate_abs(arguments: &[Value]) -> PrismDBResult<Value> {
     if arguments.len() != 1 {
@@ -1702,6 +2056,103 @@ fn evaluate_rtrim(arguments: &[Value]) -> PrismDBResult<Value> {
 }
 
 // Conditional function implementations
+fn evaluate_list_value(arguments: &[Value]) -> PrismDBResult<Value> {
+    // Cast the elements to their common type so the list is homogeneous
+    let mut element_type: Option<LogicalType> = None;
+    for arg in arguments.iter().filter(|arg| !arg.is_null()) {
+        element_type = Some(match element_type {
+            None => arg.get_type(),
+            Some(current) => TypeUtils::get_max_type(&current, &arg.get_type())?,
+        });
+    }
+    match element_type {
+        Some(element_type) => arguments
+            .iter()
+            .map(|arg| arg.cast_to(&element_type))
+            .collect::<PrismDBResult<Vec<_>>>()
+            .map(Value::List),
+        None => Ok(Value::List(arguments.to_vec())),
+    }
+}
+
+/// Whether values of a type can be map keys: scalars, which compare with each other
+pub(crate) fn is_map_key_type(logical_type: &LogicalType) -> bool {
+    !matches!(
+        logical_type,
+        LogicalType::List(_)
+            | LogicalType::Struct(_)
+            | LogicalType::Map { .. }
+            | LogicalType::Union(_)
+            | LogicalType::Null
+            | LogicalType::Invalid
+    )
+}
+
+/// Build a map from alternating keys and values, keeping the entries in the order given
+fn evaluate_map_pack(arguments: &[Value]) -> PrismDBResult<Value> {
+    if !arguments.len().is_multiple_of(2) {
+        return Err(PrismDBError::InvalidArgument(
+            "MAP requires a value for every key".to_string(),
+        ));
+    }
+    let keys: Vec<Value> = arguments.iter().step_by(2).cloned().collect();
+    let values: Vec<Value> = arguments.iter().skip(1).step_by(2).cloned().collect();
+    if keys.iter().any(Value::is_null) {
+        return Err(PrismDBError::InvalidArgument(
+            "MAP keys cannot be NULL".to_string(),
+        ));
+    }
+    // Cast keys and values to their common types, as list elements are
+    let (Value::List(keys), Value::List(values)) =
+        (evaluate_list_value(&keys)?, evaluate_list_value(&values)?)
+    else {
+        unreachable!("LIST_VALUE returns a list");
+    };
+    if let Some(key) = keys.first().filter(|key| !is_map_key_type(&key.get_type())) {
+        return Err(PrismDBError::InvalidArgument(format!(
+            "MAP keys must be a comparable scalar type, not {}",
+            key.get_type()
+        )));
+    }
+    for (i, key) in keys.iter().enumerate() {
+        if keys[..i].iter().any(|earlier| earlier.compare(key).ok() == Some(Ordering::Equal)) {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "Duplicate key {} in MAP",
+                key
+            )));
+        }
+    }
+    Ok(Value::Map(keys.into_iter().zip(values).collect()))
+}
+
+/// `container[key]`: the 1-based element of a list or the value of a map key,
+/// NULL when there is none
+fn evaluate_element_at(container: &Value, key: &Value) -> PrismDBResult<Value> {
+    if key.is_null() {
+        return Ok(Value::Null);
+    }
+    match container {
+        Value::Null => Ok(Value::Null),
+        Value::List(elements) => {
+            let index = key.try_as_i64()?;
+            Ok(usize::try_from(index - 1)
+                .ok()
+                .and_then(|index| elements.get(index))
+                .cloned()
+                .unwrap_or(Value::Null))
+        }
+        Value::Map(entries) => Ok(entries
+            .iter()
+            .find(|(entry_key, _)| entry_key.compare(key).ok() == Some(Ordering::Equal))
+            .map(|(_, value)| value.clone())
+            .unwrap_or(Value::Null)),
+        other => Err(PrismDBError::InvalidArgument(format!(
+            "Cannot subscript a value of type {}",
+            other.get_type()
+        ))),
+    }
+}
+
 fn evaluate_coalesce(arguments: &[Value]) -> PrismDBResult<Value> {
     if arguments.is_empty() {
         return Err(PrismDBError::InvalidArgument(
@@ -1841,6 +2292,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resolve_overload() -> PrismDBResult<()> {
+        let mut registry = FunctionRegistry::new();
+        let round = |types: &[LogicalType]| -> PrismDBResult<LogicalType> {
+            Ok(FunctionRegistry::builtin().resolve("round", types)?.return_type.clone())
+        };
+        assert_eq!(round(&[LogicalType::Integer])?, LogicalType::Integer);
+        assert_eq!(round(&[LogicalType::SmallInt])?, LogicalType::Integer);
+        assert_eq!(round(&[LogicalType::Float])?, LogicalType::Double);
+        assert_eq!(round(&[LogicalType::Null])?, LogicalType::Double);
+        assert!(round(&[LogicalType::Varchar]).is_err());
+
+        for argument_types in [
+            vec![LogicalType::Integer, LogicalType::Double],
+            vec![LogicalType::Double, LogicalType::Integer],
+        ] {
+            registry.register_function(FunctionInfo::new(
+                "scale".to_string(),
+                FunctionType::Scalar,
+                FunctionClassification::Mathematical,
+                LogicalType::Double,
+                argument_types,
+            ));
+        }
+        let scale = registry.resolve("scale", &[LogicalType::Integer, LogicalType::Double])?;
+        assert_eq!(scale.argument_types, [LogicalType::Integer, LogicalType::Double]);
+        let error = registry
+            .resolve("scale", &[LogicalType::Integer, LogicalType::Integer])
+            .unwrap_err();
+        assert!(error.to_string().contains("ambiguous"), "{}", error);
+        Ok(())
+    }
+
     #[test]
     fn test_string_functions() -> PrismDBResult<()> {
         assert_eq!(
@@ -1919,5 +2403,11 @@ mod tests {
         assert!(functions.contains(&"ABS"));
         assert!(functions.contains(&"CONCAT"));
         assert!(functions.contains(&"RANDOM"));
+
+        // Volatility comes from the registered functions
+        assert!(registry.is_volatile("random"));
+        assert!(registry.is_volatile("NOW"));
+        assert!(!registry.is_volatile("abs"));
+        assert!(!registry.is_volatile("nonexistent"));
     }
 }
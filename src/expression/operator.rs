@@ -1,7 +1,7 @@
 //! Operator definitions and implementations for PrismDB expressions
 
 use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::types::Value;
+use crate::types::{LogicalType, Value};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -13,6 +13,7 @@ pub enum OperatorType {
     Subtract,
     Multiply,
     Divide,
+    IntegerDivide,
     Modulo,
 
     // Bitwise operators
@@ -116,6 +117,15 @@ pub fn get_operator_info(operator_type: &OperatorType) -> OperatorInfo {
             20,
             true,
         ),
+        OperatorType::IntegerDivide => OperatorInfo::new(
+            OperatorType::IntegerDivide,
+            "integer_divide".to_string(),
+            "//".to_string(),
+            false,
+            false,
+            20,
+            true,
+        ),
         OperatorType::Modulo => OperatorInfo::new(
             OperatorType::Modulo,
             "modulo".to_string(),
@@ -279,11 +289,12 @@ pub fn evaluate_binary_operator(
     right: &Value,
 ) -> PrismDBResult<Value> {
     match operator_type {
-        OperatorType::Add => evaluate_add(left, right),
-        OperatorType::Subtract => evaluate_subtract(left, right),
-        OperatorType::Multiply => evaluate_multiply(left, right),
-        OperatorType::Divide => evaluate_divide(left, right),
-        OperatorType::Modulo => evaluate_modulo(left, right),
+        OperatorType::Add
+        | OperatorType::Subtract
+        | OperatorType::Multiply
+        | OperatorType::Divide
+        | OperatorType::IntegerDivide
+        | OperatorType::Modulo => evaluate_arithmetic(operator_type, left, right),
         OperatorType::BitwiseAnd => evaluate_bitwise_and(left, right),
         OperatorType::BitwiseOr => evaluate_bitwise_or(left, right),
         OperatorType::BitwiseXor => evaluate_bitwise_xor(left, right),
@@ -321,10 +332,208 @@ pub fn evaluate_unary_operator(
 }
 
 // Arithmetic operators
+
+/// What arithmetic does on division by zero and integer overflow (`SET arithmetic_errors`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArithmeticErrorMode {
+    /// Fail the query (the default)
+    #[default]
+    Error,
+    /// Produce NULL for the offending row
+    Null,
+}
+
+impl ArithmeticErrorMode {
+    /// Parse a setting value (`error` or `null`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "error" => Some(ArithmeticErrorMode::Error),
+            "null" => Some(ArithmeticErrorMode::Null),
+            _ => None,
+        }
+    }
+
+    /// Apply the mode to the result of an arithmetic operator: in NULL mode,
+    /// division by zero and overflow (the operators' only execution errors)
+    /// become NULL while type errors still fail
+    pub fn apply(self, result: PrismDBResult<Value>) -> PrismDBResult<Value> {
+        match (self, result) {
+            (ArithmeticErrorMode::Null, Err(PrismDBError::Execution(_))) => Ok(Value::Null),
+            (_, result) => result,
+        }
+    }
+}
+
+/// How comparisons, arithmetic and INSERT treat operands of different types
+/// (`SET type_coercion`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypeCoercion {
+    /// Cast a string mixed with another type to that type, and promote numbers
+    /// to their common type (the default)
+    #[default]
+    Lenient,
+    /// Fail when operands need a cast: a string mixed with another type, or an
+    /// integer mixed with a floating-point or decimal number
+    Strict,
+}
+
+impl TypeCoercion {
+    /// Parse a setting value (`lenient` or `strict`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "lenient" => Some(TypeCoercion::Lenient),
+            "strict" => Some(TypeCoercion::Strict),
+            _ => None,
+        }
+    }
+
+    /// Name of the mode as SET accepts it
+    pub fn name(&self) -> &'static str {
+        match self {
+            TypeCoercion::Lenient => "lenient",
+            TypeCoercion::Strict => "strict",
+        }
+    }
+
+    /// Bring the operands of a comparison or arithmetic operator together;
+    /// operands that need no cast, such as a date and an interval, pass through
+    pub fn coerce(self, left: Value, right: Value) -> PrismDBResult<(Value, Value)> {
+        let (left_type, right_type) = (left.get_type(), right.get_type());
+        if !Self::needs_cast(&left_type, &right_type) {
+            return Ok((left, right));
+        }
+        match self {
+            TypeCoercion::Strict => Err(Self::strict_error(&left_type, &right_type)),
+            TypeCoercion::Lenient if left_type.is_string() => Ok((left.cast_to(&right_type)?, right)),
+            TypeCoercion::Lenient if right_type.is_string() => {
+                let right = right.cast_to(&left_type)?;
+                Ok((left, right))
+            }
+            TypeCoercion::Lenient => Ok((left, right)),
+        }
+    }
+
+    /// Convert a value INSERT or UPDATE stores into a column of `column_type`
+    pub fn assign(self, value: Value, column_type: &LogicalType) -> PrismDBResult<Value> {
+        let value_type = value.get_type();
+        if !Self::needs_cast(&value_type, column_type) {
+            return Ok(value);
+        }
+        match self {
+            TypeCoercion::Strict => Err(Self::strict_error(&value_type, column_type)),
+            TypeCoercion::Lenient => value.cast_to(column_type),
+        }
+    }
+
+    /// Whether mixing the two types takes an implicit cast the strict mode rejects
+    fn needs_cast(left: &LogicalType, right: &LogicalType) -> bool {
+        let string_side = |string: &LogicalType, other: &LogicalType| {
+            string.is_string()
+                && !other.is_string()
+                && !matches!(
+                    other,
+                    LogicalType::Null | LogicalType::Invalid | LogicalType::Text | LogicalType::Enum(_)
+                )
+        };
+        let fraction_side = |integer: &LogicalType, other: &LogicalType| {
+            integer.is_integral() && other.is_numeric() && !other.is_integral()
+        };
+        string_side(left, right)
+            || string_side(right, left)
+            || fraction_side(left, right)
+            || fraction_side(right, left)
+    }
+
+    fn strict_error(left: &LogicalType, right: &LogicalType) -> PrismDBError {
+        PrismDBError::TypeMismatch(format!(
+            "Cannot mix {} and {} with type_coercion = 'strict'; add an explicit CAST",
+            left, right
+        ))
+    }
+}
+
+/// Evaluate an arithmetic operator with SQL semantics: NULL operands yield NULL
+/// and mixed numeric operands are promoted to their common type first.
+fn evaluate_arithmetic(
+    operator_type: &OperatorType,
+    left: &Value,
+    right: &Value,
+) -> PrismDBResult<Value> {
+    if left.is_null() || right.is_null() {
+        return Ok(Value::Null);
+    }
+
+    let promoted = promote_numeric_operands(left, right);
+    let (left, right) = match &promoted {
+        Some((l, r)) => (l, r),
+        None => (left, right),
+    };
+
+    match operator_type {
+        OperatorType::Add => evaluate_add(left, right),
+        OperatorType::Subtract => evaluate_subtract(left, right),
+        OperatorType::Multiply => evaluate_multiply(left, right),
+        OperatorType::Divide => evaluate_divide(left, right),
+        OperatorType::IntegerDivide => evaluate_integer_divide(left, right),
+        OperatorType::Modulo => evaluate_modulo(left, right),
+        _ => Err(PrismDBError::InvalidType(format!(
+            "{:?} is not an arithmetic operator",
+            operator_type
+        ))),
+    }
+}
+
+/// Numeric promotion rank: TINYINT/SMALLINT/INTEGER compute as INTEGER,
+/// then BIGINT, FLOAT and DOUBLE.
+fn numeric_rank(value: &Value) -> Option<u8> {
+    match value {
+        Value::TinyInt(_) | Value::SmallInt(_) | Value::Integer(_) => Some(1),
+        Value::BigInt(_) => Some(2),
+        Value::Float(_) => Some(3),
+        Value::Double(_) => Some(4),
+        _ => None,
+    }
+}
+
+/// Promote two numeric operands to a common type. Returns `None` when no
+/// promotion is needed (same type, or either operand is non-numeric).
+fn promote_numeric_operands(left: &Value, right: &Value) -> Option<(Value, Value)> {
+    let left_rank = numeric_rank(left)?;
+    let right_rank = numeric_rank(right)?;
+    if std::mem::discriminant(left) == std::mem::discriminant(right)
+        && !matches!(left, Value::TinyInt(_) | Value::SmallInt(_))
+    {
+        return None;
+    }
+
+    // FLOAT mixed with any other type widens to DOUBLE, matching the binder's type inference
+    let target = match left_rank.max(right_rank) {
+        3 if left_rank != right_rank => 4,
+        rank => rank,
+    };
+    let promote = |value: &Value| -> Option<Value> {
+        match target {
+            1 => value.try_as_i32().ok().map(Value::Integer),
+            2 => value.try_as_i64().ok().map(Value::BigInt),
+            3 => value.try_as_f64().ok().map(|v| Value::Float(v as f32)),
+            _ => value.try_as_f64().ok().map(Value::Double),
+        }
+    };
+    Some((promote(left)?, promote(right)?))
+}
+
+pub(crate) fn overflow(operation: &str) -> PrismDBError {
+    PrismDBError::Execution(format!("Integer overflow in {}", operation))
+}
+
 fn evaluate_add(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
-        (Value::Integer(l), Value::Integer(r)) => Ok(Value::Integer(l + r)),
-        (Value::BigInt(l), Value::BigInt(r)) => Ok(Value::BigInt(l + r)),
+        (Value::Integer(l), Value::Integer(r)) => {
+            l.checked_add(*r).map(Value::Integer).ok_or_else(|| overflow("addition"))
+        }
+        (Value::BigInt(l), Value::BigInt(r)) => {
+            l.checked_add(*r).map(Value::BigInt).ok_or_else(|| overflow("addition"))
+        }
         (Value::Float(l), Value::Float(r)) => Ok(Value::Float(l + r)),
         (Value::Double(l), Value::Double(r)) => Ok(Value::Double(l + r)),
         (Value::Varchar(l), Value::Varchar(r)) => Ok(Value::Varchar(format!("{}{}", l, r))),
@@ -338,8 +547,12 @@ fn evaluate_add(left: &Value, right: &Value) -> PrismDBResult<Value> {
 
 fn evaluate_subtract(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
-        (Value::Integer(l), Value::Integer(r)) => Ok(Value::Integer(l - r)),
-        (Value::BigInt(l), Value::BigInt(r)) => Ok(Value::BigInt(l - r)),
+        (Value::Integer(l), Value::Integer(r)) => {
+            l.checked_sub(*r).map(Value::Integer).ok_or_else(|| overflow("subtraction"))
+        }
+        (Value::BigInt(l), Value::BigInt(r)) => {
+            l.checked_sub(*r).map(Value::BigInt).ok_or_else(|| overflow("subtraction"))
+        }
         (Value::Float(l), Value::Float(r)) => Ok(Value::Float(l - r)),
         (Value::Double(l), Value::Double(r)) => Ok(Value::Double(l - r)),
         _ => Err(PrismDBError::Type(format!(
@@ -352,8 +565,12 @@ fn evaluate_subtract(left: &Value, right: &Value) -> PrismDBResult<Value> {
 
 fn evaluate_multiply(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
-        (Value::Integer(l), Value::Integer(r)) => Ok(Value::Integer(l * r)),
-        (Value::BigInt(l), Value::BigInt(r)) => Ok(Value::BigInt(l * r)),
+        (Value::Integer(l), Value::Integer(r)) => {
+            l.checked_mul(*r).map(Value::Integer).ok_or_else(|| overflow("multiplication"))
+        }
+        (Value::BigInt(l), Value::BigInt(r)) => {
+            l.checked_mul(*r).map(Value::BigInt).ok_or_else(|| overflow("multiplication"))
+        }
         (Value::Float(l), Value::Float(r)) => Ok(Value::Float(l * r)),
         (Value::Double(l), Value::Double(r)) => Ok(Value::Double(l * r)),
         _ => Err(PrismDBError::Type(format!(
@@ -364,53 +581,112 @@ fn evaluate_multiply(left: &Value, right: &Value) -> PrismDBResult<Value> {
     }
 }
 
+/// `/` is true division: integer operands produce a DOUBLE (`5 / 2 = 2.5`).
 fn evaluate_divide(left: &Value, right: &Value) -> PrismDBResult<Value> {
+    match (left, right) {
+        (Value::Integer(_), Value::Integer(_)) | (Value::BigInt(_), Value::BigInt(_)) => {
+            let r = right.try_as_f64()?;
+            if r == 0.0 {
+                return Err(PrismDBError::Execution("Division by zero".to_string()));
+            }
+            Ok(Value::Double(left.try_as_f64()? / r))
+        }
+        (Value::Float(l), Value::Float(r)) => {
+            if *r == 0.0 {
+                return Err(PrismDBError::Execution("Division by zero".to_string()));
+            }
+            Ok(Value::Float(l / r))
+        }
+        (Value::Double(l), Value::Double(r)) => {
+            if *r == 0.0 {
+                return Err(PrismDBError::Execution("Division by zero".to_string()));
+            }
+            Ok(Value::Double(l / r))
+        }
+        _ => Err(PrismDBError::Type(format!(
+            "Cannot divide {} by {}",
+            left.get_type(),
+            right.get_type()
+        ))),
+    }
+}
+
+/// `//` is floor division: the quotient is rounded towards negative infinity
+/// (`5 // 2 = 2`, `-5 // 2 = -3`) and keeps the operand type.
+fn evaluate_integer_divide(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
         (Value::Integer(l), Value::Integer(r)) => {
             if *r == 0 {
                 return Err(PrismDBError::Execution("Division by zero".to_string()));
             }
-            Ok(Value::Integer(l / r))
+            let q = l.checked_div(*r).ok_or_else(|| {
+                PrismDBError::Execution("Integer overflow in division".to_string())
+            })?;
+            Ok(Value::Integer(if l % r != 0 && ((*l < 0) != (*r < 0)) {
+                q - 1
+            } else {
+                q
+            }))
         }
         (Value::BigInt(l), Value::BigInt(r)) => {
             if *r == 0 {
                 return Err(PrismDBError::Execution("Division by zero".to_string()));
             }
-            Ok(Value::BigInt(l / r))
+            let q = l.checked_div(*r).ok_or_else(|| {
+                PrismDBError::Execution("Integer overflow in division".to_string())
+            })?;
+            Ok(Value::BigInt(if l % r != 0 && ((*l < 0) != (*r < 0)) {
+                q - 1
+            } else {
+                q
+            }))
         }
         (Value::Float(l), Value::Float(r)) => {
             if *r == 0.0 {
                 return Err(PrismDBError::Execution("Division by zero".to_string()));
             }
-            Ok(Value::Float(l / r))
+            Ok(Value::Float((l / r).floor()))
         }
         (Value::Double(l), Value::Double(r)) => {
             if *r == 0.0 {
                 return Err(PrismDBError::Execution("Division by zero".to_string()));
             }
-            Ok(Value::Double(l / r))
+            Ok(Value::Double((l / r).floor()))
         }
         _ => Err(PrismDBError::Type(format!(
-            "Cannot divide {} by {}",
+            "Cannot integer divide {} by {}",
             left.get_type(),
             right.get_type()
         ))),
     }
 }
 
+/// `%` takes the sign of the dividend (`-5 % 3 = -2`, `5 % -3 = 2`).
 fn evaluate_modulo(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
         (Value::Integer(l), Value::Integer(r)) => {
             if *r == 0 {
                 return Err(PrismDBError::Execution("Modulo by zero".to_string()));
             }
-            Ok(Value::Integer(l % r))
+            Ok(Value::Integer(l.wrapping_rem(*r)))
         }
         (Value::BigInt(l), Value::BigInt(r)) => {
             if *r == 0 {
                 return Err(PrismDBError::Execution("Modulo by zero".to_string()));
             }
-            Ok(Value::BigInt(l % r))
+            Ok(Value::BigInt(l.wrapping_rem(*r)))
+        }
+        (Value::Float(l), Value::Float(r)) => {
+            if *r == 0.0 {
+                return Err(PrismDBError::Execution("Modulo by zero".to_string()));
+            }
+            Ok(Value::Float(l % r))
+        }
+        (Value::Double(l), Value::Double(r)) => {
+            if *r == 0.0 {
+                return Err(PrismDBError::Execution("Modulo by zero".to_string()));
+            }
+            Ok(Value::Double(l % r))
         }
         _ => Err(PrismDBError::Type(format!(
             "Cannot compute {} modulo {}",
@@ -485,6 +761,11 @@ fn evaluate_bitwise_right_shift(left: &Value, right: &Value) -> PrismDBResult<Va
 fn evaluate_and(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
         (Value::Boolean(l), Value::Boolean(r)) => Ok(Value::Boolean(*l && *r)),
+        // Three-valued logic: FALSE wins over NULL
+        (Value::Boolean(false), Value::Null) | (Value::Null, Value::Boolean(false)) => {
+            Ok(Value::Boolean(false))
+        }
+        (Value::Boolean(_) | Value::Null, Value::Boolean(_) | Value::Null) => Ok(Value::Null),
         _ => Err(PrismDBError::Type(format!(
             "Cannot compute AND of {} and {}",
             left.get_type(),
@@ -496,6 +777,11 @@ fn evaluate_and(left: &Value, right: &Value) -> PrismDBResult<Value> {
 fn evaluate_or(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
         (Value::Boolean(l), Value::Boolean(r)) => Ok(Value::Boolean(*l || *r)),
+        // Three-valued logic: TRUE wins over NULL
+        (Value::Boolean(true), Value::Null) | (Value::Null, Value::Boolean(true)) => {
+            Ok(Value::Boolean(true))
+        }
+        (Value::Boolean(_) | Value::Null, Value::Boolean(_) | Value::Null) => Ok(Value::Null),
         _ => Err(PrismDBError::Type(format!(
             "Cannot compute OR of {} and {}",
             left.get_type(),
@@ -507,6 +793,7 @@ fn evaluate_or(left: &Value, right: &Value) -> PrismDBResult<Value> {
 fn evaluate_not(operand: &Value) -> PrismDBResult<Value> {
     match operand {
         Value::Boolean(v) => Ok(Value::Boolean(!v)),
+        Value::Null => Ok(Value::Null),
         _ => Err(PrismDBError::Type(format!(
             "Cannot compute NOT of {}",
             operand.get_type()
@@ -528,10 +815,8 @@ fn evaluate_concat(left: &Value, right: &Value) -> PrismDBResult<Value> {
 
 fn evaluate_like(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
-        (Value::Varchar(l), Value::Varchar(r)) => {
-            let pattern = r.replace('%', ".*").replace('_', ".");
-            Ok(Value::Boolean(l.matches(&pattern).next().is_some()))
-        }
+        (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+        (Value::Varchar(l), Value::Varchar(r)) => Ok(Value::Boolean(like_match(l, r))),
         _ => Err(PrismDBError::Type(format!(
             "Cannot compute LIKE of {} and {}",
             left.get_type(),
@@ -542,12 +827,11 @@ fn evaluate_like(left: &Value, right: &Value) -> PrismDBResult<Value> {
 
 fn evaluate_ilike(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
-        (Value::Varchar(l), Value::Varchar(r)) => {
-            let pattern = r.to_lowercase().replace('%', ".*").replace('_', ".");
-            Ok(Value::Boolean(
-                l.to_lowercase().matches(&pattern).next().is_some(),
-            ))
-        }
+        (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+        (Value::Varchar(l), Value::Varchar(r)) => Ok(Value::Boolean(like_match(
+            &l.to_lowercase(),
+            &r.to_lowercase(),
+        ))),
         _ => Err(PrismDBError::Type(format!(
             "Cannot compute ILIKE of {} and {}",
             left.get_type(),
@@ -556,6 +840,38 @@ fn evaluate_ilike(left: &Value, right: &Value) -> PrismDBResult<Value> {
     }
 }
 
+/// Match a whole string against a LIKE pattern, where `%` matches any run of
+/// characters and `_` matches exactly one
+pub fn like_match(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    // Pattern position after the last `%`, and the text position it is matched from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '_' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((after_percent, from)) => {
+                    p = after_percent;
+                    t = from + 1;
+                    backtrack = Some((after_percent, from + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '%')
+}
+
 fn evaluate_regexp_match(left: &Value, right: &Value) -> PrismDBResult<Value> {
     match (left, right) {
         (Value::Varchar(l), Value::Varchar(r)) => {
@@ -608,6 +924,47 @@ mod tests {
     use super::*;
     use crate::types::Value;
 
+    #[test]
+    fn test_like_matching() -> PrismDBResult<()> {
+        for (text, pattern, expected) in [
+            ("abcdef", "abc%", true),
+            ("xabc", "abc%", false),
+            ("xabc", "%abc", true),
+            ("abc", "a_c", true),
+            ("abbc", "a_c", false),
+            ("abbc", "a%c", true),
+            ("abc", "abc", true),
+            ("abcd", "abc", false),
+            ("", "%", true),
+            ("aXbXc", "a%b%c", true),
+            ("aXbXd", "a%b%c", false),
+        ] {
+            assert_eq!(like_match(text, pattern), expected, "{} LIKE {}", text, pattern);
+        }
+
+        let text = |s: &str| Value::Varchar(s.to_string());
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Like, &Value::Null, &text("a%"))?,
+            Value::Null
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::ILike, &text("ABC"), &text("a%"))?,
+            Value::Boolean(true)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_three_valued_logic() -> PrismDBResult<()> {
+        let (t, f, null) = (Value::Boolean(true), Value::Boolean(false), Value::Null);
+        assert_eq!(evaluate_binary_operator(&OperatorType::And, &f, &null)?, f);
+        assert_eq!(evaluate_binary_operator(&OperatorType::And, &null, &t)?, null);
+        assert_eq!(evaluate_binary_operator(&OperatorType::Or, &null, &t)?, t);
+        assert_eq!(evaluate_binary_operator(&OperatorType::Or, &f, &null)?, null);
+        assert!(evaluate_binary_operator(&OperatorType::And, &t, &Value::Integer(1)).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_arithmetic_operators() -> PrismDBResult<()> {
         let left = Value::integer(10);
@@ -627,7 +984,7 @@ mod tests {
         );
         assert_eq!(
             evaluate_binary_operator(&OperatorType::Divide, &left, &right)?,
-            Value::integer(2)
+            Value::double(2.0)
         );
         assert_eq!(
             evaluate_binary_operator(&OperatorType::Modulo, &left, &right)?,
@@ -637,6 +994,98 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_division_semantics() -> PrismDBResult<()> {
+        let five = Value::integer(5);
+        let neg_five = Value::integer(-5);
+        let two = Value::integer(2);
+        let three = Value::integer(3);
+
+        // `/` is true division, `//` floors, `%` takes the sign of the dividend
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Divide, &five, &two)?,
+            Value::double(2.5)
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::IntegerDivide, &five, &two)?,
+            Value::integer(2)
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::IntegerDivide, &neg_five, &two)?,
+            Value::integer(-3)
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Modulo, &neg_five, &three)?,
+            Value::integer(-2)
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Modulo, &five, &Value::integer(-3))?,
+            Value::integer(2)
+        );
+
+        // Mixed numeric operands are promoted, NULL propagates
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Divide, &Value::double(7.0), &two)?,
+            Value::double(3.5)
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Add, &five, &Value::bigint(1))?,
+            Value::bigint(6)
+        );
+        assert_eq!(
+            evaluate_binary_operator(&OperatorType::Divide, &Value::Null, &two)?,
+            Value::Null
+        );
+
+        let zero = Value::integer(0);
+        assert!(evaluate_binary_operator(&OperatorType::Divide, &five, &zero).is_err());
+        assert!(evaluate_binary_operator(&OperatorType::IntegerDivide, &five, &zero).is_err());
+        assert!(evaluate_binary_operator(&OperatorType::Modulo, &five, &zero).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic_error_mode() -> PrismDBResult<()> {
+        let max = Value::integer(i32::MAX);
+        let one = Value::integer(1);
+        let overflow = evaluate_binary_operator(&OperatorType::Add, &max, &one);
+        assert!(matches!(overflow, Err(PrismDBError::Execution(_))));
+        let big = Value::bigint(i64::MAX);
+        assert!(evaluate_binary_operator(&OperatorType::Multiply, &big, &Value::bigint(2)).is_err());
+
+        // NULL mode only absorbs division by zero and overflow, not type errors
+        assert_eq!(ArithmeticErrorMode::Null.apply(overflow)?, Value::Null);
+        let by_zero = || evaluate_binary_operator(&OperatorType::Divide, &one, &Value::integer(0));
+        assert!(ArithmeticErrorMode::Error.apply(by_zero()).is_err());
+        assert_eq!(ArithmeticErrorMode::Null.apply(by_zero())?, Value::Null);
+        let type_error = evaluate_binary_operator(&OperatorType::Subtract, &one, &Value::Boolean(true));
+        assert!(ArithmeticErrorMode::Null.apply(type_error).is_err());
+
+        assert_eq!(ArithmeticErrorMode::from_name("NULL"), Some(ArithmeticErrorMode::Null));
+        assert_eq!(ArithmeticErrorMode::from_name("warn"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_coercion() -> PrismDBResult<()> {
+        let text = Value::Varchar("2".to_string());
+        assert_eq!(
+            TypeCoercion::Lenient.coerce(Value::integer(1), text.clone())?,
+            (Value::integer(1), Value::integer(2))
+        );
+        assert!(TypeCoercion::Strict.coerce(Value::integer(1), text.clone()).is_err());
+        assert!(TypeCoercion::Strict.coerce(Value::integer(1), Value::Double(1.5)).is_err());
+
+        // Integer widening and NULLs need no cast
+        assert!(TypeCoercion::Strict.coerce(Value::integer(1), Value::BigInt(2)).is_ok());
+        assert!(TypeCoercion::Strict.coerce(text.clone(), Value::Null).is_ok());
+
+        assert_eq!(TypeCoercion::Lenient.assign(text, &LogicalType::Double)?, Value::Double(2.0));
+        assert_eq!(TypeCoercion::from_name("STRICT"), Some(TypeCoercion::Strict));
+        Ok(())
+    }
+
     #[test]
     fn test_logical_operators() -> PrismDBResult<()> {
         let left = Value::boolean(true);
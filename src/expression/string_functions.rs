@@ -430,6 +430,47 @@ pub fn sha256(value: &Value) -> PrismDBResult<Value> {
     }
 }
 
+/// TO_HEX - Hexadecimal digits of a blob, or of a string's UTF-8 bytes
+pub fn to_hex(value: &Value) -> PrismDBResult<Value> {
+    match value {
+        Value::Blob(bytes) => Ok(Value::Varchar(hex::encode_upper(bytes))),
+        Value::Varchar(s) => Ok(Value::Varchar(hex::encode_upper(s.as_bytes()))),
+        Value::Null => Ok(Value::Null),
+        _ => Err(PrismDBError::Type("TO_HEX requires blob or string".to_string())),
+    }
+}
+
+/// FROM_HEX - Blob from a string of hexadecimal digits
+pub fn from_hex(value: &Value) -> PrismDBResult<Value> {
+    match value {
+        Value::Varchar(s) => hex::decode(s).map(Value::Blob).map_err(|e| {
+            PrismDBError::Execution(format!("FROM_HEX: invalid hex string '{}': {}", s, e))
+        }),
+        Value::Null => Ok(Value::Null),
+        _ => Err(PrismDBError::Type("FROM_HEX requires string".to_string())),
+    }
+}
+
+/// ENCODE - UTF-8 bytes of a string as a blob
+pub fn encode(value: &Value) -> PrismDBResult<Value> {
+    match value {
+        Value::Varchar(s) => Ok(Value::Blob(s.as_bytes().to_vec())),
+        Value::Null => Ok(Value::Null),
+        _ => Err(PrismDBError::Type("ENCODE requires string".to_string())),
+    }
+}
+
+/// DECODE - String from a blob holding UTF-8
+pub fn decode(value: &Value) -> PrismDBResult<Value> {
+    match value {
+        Value::Blob(bytes) => String::from_utf8(bytes.clone())
+            .map(Value::Varchar)
+            .map_err(|_| PrismDBError::Execution("DECODE: blob is not valid UTF-8".to_string())),
+        Value::Null => Ok(Value::Null),
+        _ => Err(PrismDBError::Type("DECODE requires blob".to_string())),
+    }
+}
+
 /// BASE64 - Encode string as base64
 pub fn base64_encode(value: &Value) -> PrismDBResult<Value> {
     use base64::{engine::general_purpose, Engine as _};
@@ -1207,6 +1248,19 @@ mod tests {
         assert_eq!(decoded, Value::Varchar("hello".to_string()));
     }
 
+    #[test]
+    fn test_hex_and_blob_conversions() {
+        let blob = Value::Blob(vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(to_hex(&blob).unwrap(), Value::Varchar("DEADBEEF".to_string()));
+        assert_eq!(from_hex(&Value::Varchar("deadBEEF".to_string())).unwrap(), blob);
+        assert!(from_hex(&Value::Varchar("abc".to_string())).is_err());
+
+        let encoded = encode(&Value::Varchar("héllo".to_string())).unwrap();
+        assert_eq!(decode(&encoded).unwrap(), Value::Varchar("héllo".to_string()));
+        assert!(decode(&Value::Blob(vec![0xff])).is_err());
+        assert_eq!(to_hex(&Value::Null).unwrap(), Value::Null);
+    }
+
     #[test]
     fn test_levenshtein() {
         assert_eq!(
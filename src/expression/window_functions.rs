@@ -5,15 +5,27 @@
 //! Now with full window frame support (ROWS/RANGE/GROUPS BETWEEN).
 
 use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::parser::ast::{WindowFrame, WindowFrameBound, WindowFrameUnits};
+use crate::parser::ast::{WindowFrame, WindowFrameBound, WindowFrameExclude, WindowFrameUnits};
+use std::ops::Range;
 use crate::types::Value;
-use std::cmp::Ordering;
 
 /// Window frame boundaries for a specific row
 #[derive(Debug, Clone)]
 pub struct FrameBounds {
     pub start: usize,  // Inclusive start index
     pub end: usize,    // Inclusive end index
+    /// Rows left out by the frame's EXCLUDE clause
+    pub excluded: Range<usize>,
+    /// Row of `excluded` kept anyway, the current row under EXCLUDE TIES
+    pub kept: Option<usize>,
+}
+
+impl FrameBounds {
+    /// Rows in the frame, in partition order
+    pub fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        (self.start..=self.end)
+            .filter(move |row| !self.excluded.contains(row) || Some(*row) == self.kept)
+    }
 }
 
 /// Calculate frame bounds for a specific row in the partition
@@ -33,6 +45,8 @@ pub fn calculate_frame_bounds(
             return Ok(FrameBounds {
                 start: 0,
                 end: current_row,
+                excluded: 0..0,
+                kept: None,
             });
         }
     };
@@ -107,7 +121,67 @@ fn calculate_rows_frame_bounds(
         )));
     }
 
-    Ok(FrameBounds { start, end })
+    Ok(FrameBounds {
+        start,
+        end,
+        excluded: 0..0,
+        kept: None,
+    })
+}
+
+/// Frame of one row, taking its peers into account
+///
+/// `peer_col` holds a peer group number, equal for rows tied on the ORDER BY
+/// keys; without it every row is its own peer. A CURRENT ROW bound of a RANGE
+/// or GROUPS frame, like the default frame's end, reaches the row's first or
+/// last peer, and EXCLUDE GROUP and EXCLUDE TIES leave peers out.
+fn frame_for_row(
+    partition_data: &[Vec<Value>],
+    current_row: usize,
+    frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
+) -> PrismDBResult<FrameBounds> {
+    let mut bounds = calculate_frame_bounds(current_row, partition_data.len(), frame)?;
+    let peers = match peer_col {
+        Some(col) => {
+            let group = &partition_data[current_row][col];
+            let first = partition_data[..current_row]
+                .iter()
+                .rposition(|row| row[col] != *group)
+                .map_or(0, |row| row + 1);
+            let last = partition_data[current_row..]
+                .iter()
+                .position(|row| row[col] != *group)
+                .map_or(partition_data.len(), |len| current_row + len);
+            first..last
+        }
+        None => current_row..current_row + 1,
+    };
+
+    let peer_units = frame
+        .as_ref()
+        .is_none_or(|f| matches!(f.units, WindowFrameUnits::Range | WindowFrameUnits::Groups));
+    if peer_units {
+        let start_bound = frame.as_ref().map(|f| &f.start_bound);
+        let end_bound = frame.as_ref().and_then(|f| f.end_bound.as_ref());
+        if start_bound == Some(&WindowFrameBound::CurrentRow) {
+            bounds.start = peers.start;
+        }
+        if matches!(end_bound, None | Some(WindowFrameBound::CurrentRow)) {
+            bounds.end = peers.end - 1;
+        }
+    }
+
+    match frame.as_ref().map_or(WindowFrameExclude::NoOthers, |f| f.exclude) {
+        WindowFrameExclude::NoOthers => {}
+        WindowFrameExclude::CurrentRow => bounds.excluded = current_row..current_row + 1,
+        WindowFrameExclude::Group => bounds.excluded = peers,
+        WindowFrameExclude::Ties => {
+            bounds.excluded = peers;
+            bounds.kept = Some(current_row);
+        }
+    }
+    Ok(bounds)
 }
 
 /// ROW_NUMBER - Assign unique sequential integers starting from 1
@@ -136,7 +210,7 @@ pub fn rank(partition_data: &[Vec<Value>], order_by_col: usize) -> PrismDBResult
         let current_val = &partition_data[i][order_by_col];
         let prev_val = &partition_data[i - 1][order_by_col];
 
-        if values_equal(current_val, prev_val)? {
+        if current_val.total_cmp(prev_val).is_eq() {
             // Same value, same rank
             result.push(Value::BigInt(current_rank));
             same_rank_count += 1;
@@ -167,7 +241,7 @@ pub fn dense_rank(partition_data: &[Vec<Value>], order_by_col: usize) -> PrismDB
         let current_val = &partition_data[i][order_by_col];
         let prev_val = &partition_data[i - 1][order_by_col];
 
-        if values_equal(current_val, prev_val)? {
+        if current_val.total_cmp(prev_val).is_eq() {
             // Same value, same rank
             result.push(Value::BigInt(current_rank));
         } else {
@@ -239,6 +313,7 @@ pub fn first_value(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -249,11 +324,11 @@ pub fn first_value(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Get first value in the frame
-        let first = partition_data[bounds.start][value_col].clone();
-        result.push(first);
+        let first = bounds.rows().next().map(|row| partition_data[row][value_col].clone());
+        result.push(first.unwrap_or(Value::Null));
     }
 
     Ok(result)
@@ -265,6 +340,7 @@ pub fn last_value(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -275,11 +351,11 @@ pub fn last_value(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Get last value in the frame
-        let last = partition_data[bounds.end][value_col].clone();
-        result.push(last);
+        let last = bounds.rows().last().map(|row| partition_data[row][value_col].clone());
+        result.push(last.unwrap_or(Value::Null));
     }
 
     Ok(result)
@@ -292,6 +368,7 @@ pub fn nth_value(
     value_col: usize,
     n: i64,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() || n < 1 {
         return Ok(vec![Value::Null; partition_data.len()]);
@@ -302,19 +379,11 @@ pub fn nth_value(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
-        // Calculate nth position within the frame (1-based to 0-based)
-        let nth_idx = bounds.start + (n as usize - 1);
-
-        // Get nth value if it exists within the frame
-        let value = if nth_idx <= bounds.end {
-            partition_data[nth_idx][value_col].clone()
-        } else {
-            Value::Null
-        };
-
-        result.push(value);
+        // Get nth value if it exists within the frame (1-based to 0-based)
+        let value = bounds.rows().nth(n as usize - 1).map(|row| partition_data[row][value_col].clone());
+        result.push(value.unwrap_or(Value::Null));
     }
 
     Ok(result)
@@ -406,9 +475,8 @@ pub fn cume_dist(partition_data: &[Vec<Value>], order_by_col: usize) -> PrismDBR
 
         for j in 0..len {
             let other_val = &partition_data[j][order_by_col];
-            match current_val.compare(other_val)? {
-                Ordering::Greater | Ordering::Equal => count += 1,
-                Ordering::Less => {}
+            if current_val.total_cmp(other_val).is_ge() {
+                count += 1;
             }
         }
 
@@ -425,6 +493,7 @@ pub fn sum_window(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -435,11 +504,11 @@ pub fn sum_window(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Sum values within the frame
         let mut sum = 0.0;
-        for row_idx in bounds.start..=bounds.end {
+        for row_idx in bounds.rows() {
             let val = &partition_data[row_idx][value_col];
             match val {
                 Value::Integer(i) => sum += *i as f64,
@@ -463,6 +532,7 @@ pub fn avg_window(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -473,13 +543,13 @@ pub fn avg_window(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Calculate average within the frame
         let mut sum = 0.0;
         let mut count = 0;
 
-        for row_idx in bounds.start..=bounds.end {
+        for row_idx in bounds.rows() {
             let val = &partition_data[row_idx][value_col];
             match val {
                 Value::Integer(i) => {
@@ -519,6 +589,7 @@ pub fn count_window(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -529,11 +600,11 @@ pub fn count_window(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Count non-null values within the frame
         let mut count = 0i64;
-        for row_idx in bounds.start..=bounds.end {
+        for row_idx in bounds.rows() {
             let val = &partition_data[row_idx][value_col];
             if !matches!(val, Value::Null) {
                 count += 1;
@@ -552,6 +623,7 @@ pub fn min_window(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -562,17 +634,17 @@ pub fn min_window(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Find minimum within the frame
         let mut min_val: Option<Value> = None;
-        for row_idx in bounds.start..=bounds.end {
+        for row_idx in bounds.rows() {
             let val = &partition_data[row_idx][value_col];
             if !matches!(val, Value::Null) {
                 min_val = match &min_val {
                     None => Some(val.clone()),
                     Some(current_min) => {
-                        if val.compare(current_min)? == Ordering::Less {
+                        if val.total_cmp(current_min).is_lt() {
                             Some(val.clone())
                         } else {
                             Some(current_min.clone())
@@ -594,6 +666,7 @@ pub fn max_window(
     partition_data: &[Vec<Value>],
     value_col: usize,
     frame: &Option<WindowFrame>,
+    peer_col: Option<usize>,
 ) -> PrismDBResult<Vec<Value>> {
     if partition_data.is_empty() {
         return Ok(Vec::new());
@@ -604,17 +677,17 @@ pub fn max_window(
 
     for current_row in 0..partition_size {
         // Calculate frame bounds for this row
-        let bounds = calculate_frame_bounds(current_row, partition_size, frame)?;
+        let bounds = frame_for_row(partition_data, current_row, frame, peer_col)?;
 
         // Find maximum within the frame
         let mut max_val: Option<Value> = None;
-        for row_idx in bounds.start..=bounds.end {
+        for row_idx in bounds.rows() {
             let val = &partition_data[row_idx][value_col];
             if !matches!(val, Value::Null) {
                 max_val = match &max_val {
                     None => Some(val.clone()),
                     Some(current_max) => {
-                        if val.compare(current_max)? == Ordering::Greater {
+                        if val.total_cmp(current_max).is_gt() {
                             Some(val.clone())
                         } else {
                             Some(current_max.clone())
@@ -630,11 +703,6 @@ pub fn max_window(
     Ok(result)
 }
 
-/// Helper function to compare values for equality
-fn values_equal(a: &Value, b: &Value) -> PrismDBResult<bool> {
-    Ok(a.compare(b)? == Ordering::Equal)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -745,7 +813,7 @@ mod tests {
 
         // Default frame: UNBOUNDED PRECEDING to CURRENT ROW
         // FIRST_VALUE should return first in partition for all rows
-        let first = first_value(&data, 0, &None)?;
+        let first = first_value(&data, 0, &None, None)?;
         assert_eq!(first.len(), 4);
         assert_eq!(first[0], Value::Integer(10));
         assert_eq!(first[1], Value::Integer(10));
@@ -753,7 +821,7 @@ mod tests {
         assert_eq!(first[3], Value::Integer(10));
 
         // LAST_VALUE with default frame returns current row value (up to CURRENT ROW)
-        let last = last_value(&data, 0, &None)?;
+        let last = last_value(&data, 0, &None, None)?;
         assert_eq!(last.len(), 4);
         assert_eq!(last[0], Value::Integer(10)); // Last in frame [0..=0]
         assert_eq!(last[1], Value::Integer(20)); // Last in frame [0..=1]
@@ -769,13 +837,13 @@ mod tests {
 
         // Default frame: UNBOUNDED PRECEDING to CURRENT ROW
         // NTH_VALUE(2) should return 2nd value in frame
-        let second = nth_value(&data, 0, 2, &None)?;
+        let second = nth_value(&data, 0, 2, &None, None)?;
         assert_eq!(second[0], Value::Null);       // Frame [0..=0] has only 1 value
         assert_eq!(second[1], Value::Integer(20)); // Frame [0..=1] 2nd value is 20
         assert_eq!(second[2], Value::Integer(20)); // Frame [0..=2] 2nd value is 20
         assert_eq!(second[3], Value::Integer(20)); // Frame [0..=3] 2nd value is 20
 
-        let fifth = nth_value(&data, 0, 5, &None)?; // Out of bounds in all frames
+        let fifth = nth_value(&data, 0, 5, &None, None)?; // Out of bounds in all frames
         assert!(fifth.iter().all(|v| *v == Value::Null));
 
         Ok(())
@@ -842,7 +910,7 @@ mod tests {
     fn test_sum_window() -> PrismDBResult<()> {
         let data = create_test_partition(vec![10, 20, 30, 40]);
         // Default frame: UNBOUNDED PRECEDING to CURRENT ROW (running sum)
-        let result = sum_window(&data, 0, &None)?;
+        let result = sum_window(&data, 0, &None, None)?;
 
         assert_eq!(result.len(), 4);
         assert_eq!(result[0], Value::Double(10.0)); // 10
@@ -857,7 +925,7 @@ mod tests {
     fn test_avg_window() -> PrismDBResult<()> {
         let data = create_test_partition(vec![10, 20, 30, 40]);
         // Default frame: UNBOUNDED PRECEDING to CURRENT ROW (running average)
-        let result = avg_window(&data, 0, &None)?;
+        let result = avg_window(&data, 0, &None, None)?;
 
         assert_eq!(result.len(), 4);
         assert_eq!(result[0], Value::Double(10.0)); // 10/1
@@ -872,7 +940,7 @@ mod tests {
     fn test_count_window() -> PrismDBResult<()> {
         let data = create_test_partition(vec![10, 20, 30, 40]);
         // Default frame: UNBOUNDED PRECEDING to CURRENT ROW (running count)
-        let result = count_window(&data, 0, &None)?;
+        let result = count_window(&data, 0, &None, None)?;
 
         assert_eq!(result.len(), 4);
         assert_eq!(result[0], Value::BigInt(1));
@@ -888,13 +956,13 @@ mod tests {
         let data = create_test_partition(vec![40, 20, 30, 10]);
 
         // Default frame: UNBOUNDED PRECEDING to CURRENT ROW (running min/max)
-        let min_result = min_window(&data, 0, &None)?;
+        let min_result = min_window(&data, 0, &None, None)?;
         assert_eq!(min_result[0], Value::Integer(40)); // First value
         assert_eq!(min_result[1], Value::Integer(20)); // min(40, 20)
         assert_eq!(min_result[2], Value::Integer(20)); // min(40, 20, 30)
         assert_eq!(min_result[3], Value::Integer(10)); // min(40, 20, 30, 10)
 
-        let max_result = max_window(&data, 0, &None)?;
+        let max_result = max_window(&data, 0, &None, None)?;
         assert_eq!(max_result[0], Value::Integer(40)); // First value
         assert_eq!(max_result[1], Value::Integer(40)); // max(40, 20)
         assert_eq!(max_result[2], Value::Integer(40)); // max(40, 20, 30)
@@ -1,14 +1,222 @@
 //! Configuration Management
 //!
-//! Handles SET variable = value statements
+//! Handles SET variable = value statements. Every setting is declared in `SETTINGS`
+//! with the kind of value it accepts, so SET validates values up front and the
+//! subsystems that consult a setting read it back already typed.
 
+use crate::common::allocator::{format_memory_size, parse_memory_size};
+use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, RwLock};
 
+/// Kind of value a setting accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    /// true/false (also on/off and 1/0)
+    Boolean,
+    /// Non-negative integer
+    UnsignedInteger,
+    /// Byte count with an optional unit, such as `1GB` or `512MiB`
+    MemorySize,
+    /// One of a fixed set of names
+    Enum(&'static [&'static str]),
+    /// Free-form text
+    String,
+}
+
+/// A setting known to the configuration registry
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDefinition {
+    pub name: &'static str,
+    pub kind: SettingKind,
+    pub description: &'static str,
+}
+
+/// Every setting SET accepts
+pub const SETTINGS: &[SettingDefinition] = &[
+    SettingDefinition {
+        name: "approx_count_distinct_threshold",
+        kind: SettingKind::UnsignedInteger,
+        description: "Estimated distinct count above which COUNT(DISTINCT) of a column is computed approximately with HyperLogLog; 0 disables",
+    },
+    SettingDefinition {
+        name: "arithmetic_errors",
+        kind: SettingKind::Enum(&["error", "null"]),
+        description: "Whether division by zero and integer overflow fail the query or yield NULL",
+    },
+    SettingDefinition {
+        name: "auto_vacuum_threshold",
+        kind: SettingKind::UnsignedInteger,
+        description: "Percentage of a table's rows a DELETE must leave deleted to vacuum the table; 0 disables",
+    },
+    SettingDefinition {
+        name: "default_null_order",
+        kind: SettingKind::Enum(&["nulls_largest", "nulls_smallest", "nulls_first", "nulls_last"]),
+        description: "NULL placement for ORDER BY items without NULLS FIRST/LAST",
+    },
+    SettingDefinition {
+        name: "enable_parallelism",
+        kind: SettingKind::Boolean,
+        description: "Run table scans across worker threads",
+    },
+    SettingDefinition {
+        name: "grouping_collation",
+        kind: SettingKind::Enum(&["binary", "nocase"]),
+        description: "Collation strings are grouped by in GROUP BY and DISTINCT; nocase puts strings that differ only in case in one group",
+    },
+    SettingDefinition {
+        name: "memory_limit",
+        kind: SettingKind::MemorySize,
+        description: "Memory budget for buffering query operators",
+    },
+    SettingDefinition {
+        name: "preserve_insertion_order",
+        kind: SettingKind::Boolean,
+        description: "Run queries with a LIMIT but no ORDER BY on one thread, so they return the same rows, in insertion order, every time",
+    },
+    SettingDefinition {
+        name: "search_path",
+        kind: SettingKind::String,
+        description: "Comma-separated schemas searched in order for unqualified table names",
+    },
+    SettingDefinition {
+        name: "string_length_limit",
+        kind: SettingKind::Enum(&["ignore", "truncate", "error"]),
+        description: "Whether INSERT and UPDATE store, truncate or reject strings longer than a VARCHAR(n) or CHAR(n) column",
+    },
+    SettingDefinition {
+        name: "threads",
+        kind: SettingKind::UnsignedInteger,
+        description: "Number of threads for parallel execution",
+    },
+    SettingDefinition {
+        name: "type_coercion",
+        kind: SettingKind::Enum(&["lenient", "strict"]),
+        description: "Whether a string mixed with another type, or an integer with a fraction, is cast implicitly or rejected",
+    },
+    SettingDefinition {
+        name: "s3_access_key_id",
+        kind: SettingKind::String,
+        description: "S3 access key ID",
+    },
+    SettingDefinition {
+        name: "s3_endpoint",
+        kind: SettingKind::String,
+        description: "S3 endpoint",
+    },
+    SettingDefinition {
+        name: "s3_region",
+        kind: SettingKind::String,
+        description: "S3 region",
+    },
+    SettingDefinition {
+        name: "s3_secret_access_key",
+        kind: SettingKind::String,
+        description: "S3 secret access key",
+    },
+    SettingDefinition {
+        name: "s3_url_style",
+        kind: SettingKind::String,
+        description: "S3 URL style (virtual or path)",
+    },
+    SettingDefinition {
+        name: "s3_use_ssl",
+        kind: SettingKind::Boolean,
+        description: "Use HTTPS for S3 requests",
+    },
+];
+
+/// Look up a setting by name (case-insensitive)
+pub fn find_setting(name: &str) -> Option<&'static SettingDefinition> {
+    SETTINGS
+        .iter()
+        .find(|setting| setting.name.eq_ignore_ascii_case(name))
+}
+
+/// Typed value of a setting
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    Boolean(bool),
+    UnsignedInteger(usize),
+    /// Size in bytes
+    MemorySize(usize),
+    String(String),
+}
+
+impl SettingValue {
+    /// Parse a SET value as the given kind
+    pub fn parse(setting: &SettingDefinition, text: &str) -> PrismDBResult<Self> {
+        let invalid = |expected: &str| {
+            PrismDBError::InvalidArgument(format!(
+                "Invalid {} '{}': expected {}",
+                setting.name, text, expected
+            ))
+        };
+
+        match setting.kind {
+            SettingKind::Boolean => match text.trim().to_lowercase().as_str() {
+                "true" | "on" | "1" => Ok(SettingValue::Boolean(true)),
+                "false" | "off" | "0" => Ok(SettingValue::Boolean(false)),
+                _ => Err(invalid("true or false")),
+            },
+            SettingKind::UnsignedInteger => text
+                .trim()
+                .parse()
+                .map(SettingValue::UnsignedInteger)
+                .map_err(|_| invalid("a non-negative integer")),
+            SettingKind::MemorySize => parse_memory_size(text).map(SettingValue::MemorySize),
+            SettingKind::Enum(names) => names
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(text.trim()))
+                .map(|name| SettingValue::String(name.to_string()))
+                .ok_or_else(|| invalid(&names.join(", "))),
+            SettingKind::String => Ok(SettingValue::String(text.to_string())),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SettingValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Get an integer or memory size
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            SettingValue::UnsignedInteger(n) | SettingValue::MemorySize(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SettingValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingValue::Boolean(b) => write!(f, "{}", b),
+            SettingValue::UnsignedInteger(n) => write!(f, "{}", n),
+            SettingValue::MemorySize(bytes) => write!(f, "{}", format_memory_size(*bytes)),
+            SettingValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
 /// Configuration manager for database settings
+///
+/// Holds the values assigned with SET; a setting that was never set (or was reset
+/// with `SET name = DEFAULT`) falls back to the database configuration.
 #[derive(Debug)]
 pub struct ConfigManager {
-    settings: Arc<RwLock<HashMap<String, String>>>,
+    settings: Arc<RwLock<HashMap<String, SettingValue>>>,
 }
 
 impl ConfigManager {
@@ -19,30 +227,51 @@ impl ConfigManager {
         }
     }
 
-    /// Set a configuration variable
-    pub fn set(&self, key: &str, value: String) {
-        let mut settings = self.settings.write().unwrap();
-        settings.insert(key.to_lowercase(), value);
-        println!("SET {} = '{}'", key, settings.get(&key.to_lowercase()).unwrap());
+    /// Set a configuration variable, validating the value against its setting
+    pub fn set(&self, key: &str, value: &str) -> PrismDBResult<()> {
+        let setting = Self::definition(key)?;
+        let value = SettingValue::parse(setting, value)?;
+        let mut settings = self.settings.write_or_err()?;
+        settings.insert(setting.name.to_string(), value);
+        Ok(())
     }
 
-    /// Get a configuration variable
-    pub fn get(&self, key: &str) -> Option<String> {
-        let settings = self.settings.read().unwrap();
+    /// Reset a configuration variable to its default
+    pub fn reset(&self, key: &str) -> PrismDBResult<()> {
+        let setting = Self::definition(key)?;
+        let mut settings = self.settings.write_or_err()?;
+        settings.remove(setting.name);
+        Ok(())
+    }
+
+    /// Get a configuration variable's value if it has been set
+    pub fn get_value(&self, key: &str) -> Option<SettingValue> {
+        let settings = self.settings.read_recovered();
         settings.get(&key.to_lowercase()).cloned()
     }
 
-    /// List all configuration variables
+    /// Get a configuration variable as text
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.get_value(key).map(|value| value.to_string())
+    }
+
+    /// List all configuration variables that have been set
     pub fn list_all(&self) -> Vec<(String, String)> {
-        let settings = self.settings.read().unwrap();
-        settings.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+        let settings = self.settings.read_recovered();
+        settings.iter().map(|(k, v)| (k.clone(), v.to_string())).collect()
     }
 
     /// Clear all settings
     pub fn clear(&self) {
-        let mut settings = self.settings.write().unwrap();
+        let mut settings = self.settings.write_recovered();
         settings.clear();
     }
+
+    fn definition(key: &str) -> PrismDBResult<&'static SettingDefinition> {
+        find_setting(key).ok_or_else(|| {
+            PrismDBError::InvalidArgument(format!("Unknown configuration setting '{}'", key))
+        })
+    }
 }
 
 impl Default for ConfigManager {
@@ -50,3 +279,37 @@ impl Default for ConfigManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_validates_values() {
+        let config = ConfigManager::new();
+
+        config.set("THREADS", "4").unwrap();
+        assert_eq!(config.get_value("threads"), Some(SettingValue::UnsignedInteger(4)));
+        assert!(config.set("threads", "-1").is_err());
+
+        config.set("enable_parallelism", "off").unwrap();
+        assert_eq!(config.get("enable_parallelism"), Some("false".to_string()));
+
+        config.set("memory_limit", "2GB").unwrap();
+        assert_eq!(config.get_value("memory_limit").unwrap().as_usize(), Some(2_000_000_000));
+        assert_eq!(config.get("memory_limit"), Some("2GB".to_string()));
+
+        config.set("default_null_order", "NULLS_FIRST").unwrap();
+        assert_eq!(config.get("default_null_order"), Some("nulls_first".to_string()));
+        assert!(config.set("default_null_order", "sideways").is_err());
+
+        assert!(matches!(
+            config.set("no_such_setting", "1"),
+            Err(PrismDBError::InvalidArgument(_))
+        ));
+        assert!(config.reset("no_such_setting").is_err());
+
+        config.reset("threads").unwrap();
+        assert_eq!(config.get("threads"), None);
+    }
+}
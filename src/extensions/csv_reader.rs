@@ -1,110 +1,423 @@
 //! CSV Reading Functionality
 //!
 //! Implements read_csv_auto() table function
+//!
+//! Column types are inferred from a sample of the first records. The rest of
+//! the file then streams through, each field coerced to its column's type; a
+//! field that does not fit promotes the column (BIGINT to DOUBLE to VARCHAR)
+//! and the rows already read are cast to the wider type.
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::extensions::file_reader::FileCompression;
+use crate::types::utils::DEFAULT_CHUNK_SIZE;
 use crate::types::{DataChunk, LogicalType, Value, Vector};
-use csv::ReaderBuilder;
-use std::io::Cursor;
+use csv::{ReaderBuilder, StringRecord};
+use std::io::Read;
+
+/// How CSV text is split into fields and typed
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Field separator
+    pub delimiter: u8,
+    /// Quote character; quoted fields may hold delimiters, doubled quotes and newlines
+    pub quote: u8,
+    /// Whether the first record names the columns
+    pub header: bool,
+    /// Field text read as NULL
+    pub null_string: String,
+    /// Number of records inspected to infer column types
+    pub sample_size: usize,
+    /// Column names and types to use instead of inferring them
+    pub columns: Option<Vec<(String, LogicalType)>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            header: true,
+            null_string: String::new(),
+            sample_size: DEFAULT_CHUNK_SIZE,
+            columns: None,
+        }
+    }
+}
+
+/// Columns and rows read from a CSV file
+#[derive(Debug)]
+pub struct CsvTable {
+    pub columns: Vec<(String, LogicalType)>,
+    pub chunks: Vec<DataChunk>,
+}
 
 /// CSV reader that auto-detects schema
 pub struct CsvReader {
     data: Vec<u8>,
+    compression: FileCompression,
+    options: CsvOptions,
 }
 
 impl CsvReader {
     /// Create a new CSV reader from bytes
     pub fn new(data: Vec<u8>) -> Self {
-        Self { data }
+        Self {
+            data,
+            compression: FileCompression::None,
+            options: CsvOptions::default(),
+        }
     }
 
-    /// Read CSV and return a DataChunk
-    pub fn read(&self) -> PrismDBResult<DataChunk> {
-        let cursor = Cursor::new(&self.data);
-        let mut csv_reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(cursor);
-
-        // Get headers
-        let headers = csv_reader.headers()
-            .map_err(|e| PrismDBError::Parse(format!("Failed to read CSV headers: {}", e)))?
-            .clone();
+    /// Decompress the bytes with `compression` while parsing them
+    pub fn with_compression(mut self, compression: FileCompression) -> Self {
+        self.compression = compression;
+        self
+    }
 
-        let header_names: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
-        let column_count = header_names.len();
+    /// Parse and type the text according to `options`
+    pub fn with_options(mut self, options: CsvOptions) -> Self {
+        self.options = options;
+        self
+    }
 
-        println!("CSV Schema detected:");
-        for (i, name) in header_names.iter().enumerate() {
-            println!("  Column {}: {}", i, name);
-        }
+    /// Stream of the CSV text, decompressed as it is read
+    fn open(&self) -> PrismDBResult<Box<dyn Read + '_>> {
+        self.compression.decoder(self.data.as_slice())
+    }
 
-        // Read all records first to detect types and collect data
-        let mut records = Vec::new();
-        for result in csv_reader.records() {
-            let record = result
-                .map_err(|e| PrismDBError::Parse(format!("Failed to read CSV record: {}", e)))?;
-            records.push(record);
-        }
+    fn csv_reader(&self) -> PrismDBResult<csv::Reader<Box<dyn Read + '_>>> {
+        Ok(ReaderBuilder::new()
+            .has_headers(self.options.header)
+            .delimiter(self.options.delimiter)
+            .quote(self.options.quote)
+            .from_reader(self.open()?))
+    }
 
-        println!("Read {} rows", records.len());
+    /// Read the whole file into chunks of typed columns
+    pub fn read(&self) -> PrismDBResult<CsvTable> {
+        let mut csv_reader = self.csv_reader()?;
+        let names = self.column_names_from(&mut csv_reader)?;
+        let mut records = csv_reader.records();
 
-        if records.is_empty() {
-            // Empty result - create empty vectors
-            let mut vectors = Vec::new();
-            for _ in 0..column_count {
-                vectors.push(Vector::new(LogicalType::Varchar, 0));
+        // Infer types from the sample, unless they were given
+        let mut sample = Vec::new();
+        while sample.len() < self.options.sample_size {
+            match records.next() {
+                Some(record) => sample.push(record.map_err(record_error)?),
+                None => break,
             }
-            return DataChunk::from_vectors(vectors);
         }
-
-        // For simplicity, treat all columns as VARCHAR for now
-        // TODO: Implement type inference
-        let column_types = vec![LogicalType::Varchar; column_count];
-
-        // Build vectors for each column
-        let mut columns: Vec<Vec<Value>> = vec![Vec::new(); column_count];
-
-        for record in records {
-            for (col_idx, field) in record.iter().enumerate() {
-                if col_idx < column_count {
-                    if field.is_empty() {
-                        columns[col_idx].push(Value::Null);
-                    } else {
-                        columns[col_idx].push(Value::Varchar(field.to_string()));
+        let types = match &self.options.columns {
+            Some(columns) => columns.iter().map(|(_, t)| Some(t.clone())).collect(),
+            None => {
+                let mut types = vec![None; names.len()];
+                for record in &sample {
+                    for (column_type, field) in types.iter_mut().zip(record) {
+                        if field != self.options.null_string {
+                            let detected = detect_type(field);
+                            *column_type = Some(match column_type.take() {
+                                Some(current) => widen_type(&current, &detected),
+                                None => detected,
+                            });
+                        }
                     }
                 }
+                types
             }
+        };
+
+        let mut builder = TableBuilder::new(names, types);
+        for record in sample {
+            self.append(&mut builder, &record)?;
+        }
+        for record in records {
+            self.append(&mut builder, &record.map_err(record_error)?)?;
         }
+        builder.finish()
+    }
 
-        // Convert to vectors
-        let mut vectors = Vec::new();
-        for (col_idx, col_data) in columns.into_iter().enumerate() {
-            let row_count = col_data.len();
-            let mut vector = Vector::new(column_types[col_idx].clone(), row_count);
-            for (row_idx, value) in col_data.into_iter().enumerate() {
-                vector.set_value(row_idx, &value)
-                    .map_err(|e| PrismDBError::Internal(format!(
-                        "Failed to set value in column {}: {}", col_idx, e
-                    )))?;
+    /// Add one record, promoting the type of any column a field does not fit
+    fn append(&self, builder: &mut TableBuilder, record: &StringRecord) -> PrismDBResult<()> {
+        if record.len() != builder.names.len() {
+            return Err(PrismDBError::Parse(format!(
+                "CSV record has {} fields, expected {}",
+                record.len(),
+                builder.names.len()
+            )));
+        }
+        for (column, field) in record.iter().enumerate() {
+            if field == self.options.null_string {
+                builder.push(column, Value::Null);
+                continue;
+            }
+            let current = builder.types[column].clone();
+            if let Some(value) = current.as_ref().and_then(|t| coerce(field, t)) {
+                builder.push(column, value);
+                continue;
             }
-            // Set the count to match the number of rows we just inserted
-            vector.resize(row_count)?;
-            vectors.push(vector);
+            if self.options.columns.is_some() {
+                return Err(PrismDBError::Parse(format!(
+                    "Could not convert '{}' to {} in column '{}'",
+                    field,
+                    current.unwrap_or(LogicalType::Varchar),
+                    builder.names[column]
+                )));
+            }
+            let detected = detect_type(field);
+            let promoted = match &current {
+                Some(current) => widen_type(current, &detected),
+                None => detected,
+            };
+            builder.promote(column, promoted.clone())?;
+            let value = coerce(field, &promoted).unwrap_or_else(|| Value::Varchar(field.to_string()));
+            builder.push(column, value);
         }
+        builder.flush_full()
+    }
 
-        DataChunk::from_vectors(vectors)
+    /// Column names: given explicitly, from the header, or `column0`, `column1`, ...
+    fn column_names_from<R: Read>(&self, csv_reader: &mut csv::Reader<R>) -> PrismDBResult<Vec<String>> {
+        let first = csv_reader
+            .headers()
+            .map_err(|e| PrismDBError::Parse(format!("Failed to read CSV headers: {}", e)))?;
+        if let Some(columns) = &self.options.columns {
+            if !first.is_empty() && first.len() != columns.len() {
+                return Err(PrismDBError::Parse(format!(
+                    "CSV file has {} columns but {} were given",
+                    first.len(),
+                    columns.len()
+                )));
+            }
+            return Ok(columns.iter().map(|(name, _)| name.clone()).collect());
+        }
+        Ok(if self.options.header {
+            first.iter().map(|h| h.to_string()).collect()
+        } else {
+            (0..first.len()).map(|i| format!("column{}", i)).collect()
+        })
     }
 
     /// Get column names from CSV header
     pub fn get_column_names(&self) -> PrismDBResult<Vec<String>> {
-        let cursor = Cursor::new(&self.data);
-        let mut csv_reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(cursor);
+        let mut csv_reader = self.csv_reader()?;
+        self.column_names_from(&mut csv_reader)
+    }
+}
 
-        let headers = csv_reader.headers()
-            .map_err(|e| PrismDBError::Parse(format!("Failed to read CSV headers: {}", e)))?;
+fn record_error(e: csv::Error) -> PrismDBError {
+    PrismDBError::Parse(format!("Failed to read CSV record: {}", e))
+}
+
+/// Narrowest type that holds `field`
+fn detect_type(field: &str) -> LogicalType {
+    [
+        LogicalType::Boolean,
+        LogicalType::BigInt,
+        LogicalType::Double,
+        LogicalType::Date,
+        LogicalType::Timestamp,
+    ]
+    .into_iter()
+    .find(|t| coerce(field, t).is_some())
+    .unwrap_or(LogicalType::Varchar)
+}
+
+/// `field` as a value of `target`, if it is one
+fn coerce(field: &str, target: &LogicalType) -> Option<Value> {
+    let text = field.trim();
+    match target {
+        LogicalType::Boolean => match text.to_lowercase().as_str() {
+            "true" => Some(Value::Boolean(true)),
+            "false" => Some(Value::Boolean(false)),
+            _ => None,
+        },
+        LogicalType::BigInt => text.parse().ok().map(Value::BigInt),
+        // Words like `inf` and `nan` parse as floats but are left as text
+        LogicalType::Double if text.bytes().any(|b| b.is_ascii_digit()) => {
+            text.parse().ok().map(Value::Double)
+        }
+        LogicalType::Double => None,
+        LogicalType::Varchar => Some(Value::Varchar(field.to_string())),
+        _ => Value::Varchar(text.to_string()).cast_to(target).ok(),
+    }
+}
+
+/// Narrowest type holding values of both `a` and `b`
+pub(crate) fn widen_type(a: &LogicalType, b: &LogicalType) -> LogicalType {
+    use LogicalType::*;
+
+    match (a, b) {
+        _ if a == b => a.clone(),
+        _ if a.is_integral() && b.is_integral() => BigInt,
+        _ if a.is_numeric() && b.is_numeric() => Double,
+        (Date, Timestamp) | (Timestamp, Date) => Timestamp,
+        _ => Varchar,
+    }
+}
+
+/// Typed columns built up a chunk at a time
+struct TableBuilder {
+    names: Vec<String>,
+    /// `None` until a column's first non-NULL value
+    types: Vec<Option<LogicalType>>,
+    chunks: Vec<DataChunk>,
+    current: Vec<Vec<Value>>,
+}
+
+impl TableBuilder {
+    fn new(names: Vec<String>, types: Vec<Option<LogicalType>>) -> Self {
+        let current = vec![Vec::new(); names.len()];
+        Self {
+            names,
+            types,
+            chunks: Vec::new(),
+            current,
+        }
+    }
+
+    fn push(&mut self, column: usize, value: Value) {
+        self.current[column].push(value);
+    }
+
+    /// Change a column's type, casting the values already read
+    fn promote(&mut self, column: usize, target: LogicalType) -> PrismDBResult<()> {
+        for value in self.current[column].iter_mut() {
+            *value = cast_value(value, &target)?;
+        }
+        for chunk in &mut self.chunks {
+            let vector = chunk
+                .get_vector(column)
+                .ok_or_else(|| PrismDBError::Internal(format!("Column {} not found", column)))?;
+            let values = (0..chunk.len())
+                .map(|row| cast_value(&vector.get_value(row)?, &target))
+                .collect::<PrismDBResult<Vec<_>>>()?;
+            chunk.set_vector(column, build_vector(&target, &values)?)?;
+        }
+        self.types[column] = Some(target);
+        Ok(())
+    }
+
+    /// Move the buffered rows into a chunk once there is a chunk's worth
+    fn flush_full(&mut self) -> PrismDBResult<()> {
+        if self.current.first().map_or(0, Vec::len) >= DEFAULT_CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> PrismDBResult<()> {
+        if self.current.first().is_none_or(Vec::is_empty) {
+            return Ok(());
+        }
+        let vectors = self
+            .current
+            .iter_mut()
+            .zip(&self.types)
+            .map(|(values, column_type)| {
+                let values = std::mem::take(values);
+                build_vector(column_type.as_ref().unwrap_or(&LogicalType::Varchar), &values)
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        self.chunks.push(DataChunk::from_vectors(vectors)?);
+        Ok(())
+    }
+
+    /// Columns that never held a value are VARCHAR
+    fn finish(mut self) -> PrismDBResult<CsvTable> {
+        self.flush()?;
+        let columns = self
+            .names
+            .into_iter()
+            .zip(self.types)
+            .map(|(name, column_type)| (name, column_type.unwrap_or(LogicalType::Varchar)))
+            .collect();
+        Ok(CsvTable {
+            columns,
+            chunks: self.chunks,
+        })
+    }
+}
+
+fn cast_value(value: &Value, target: &LogicalType) -> PrismDBResult<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        value if &value.get_type() == target => Ok(value.clone()),
+        value => value.cast_to(target),
+    }
+}
+
+fn build_vector(data_type: &LogicalType, values: &[Value]) -> PrismDBResult<Vector> {
+    let mut vector = Vector::new(data_type.clone(), values.len().max(1));
+    for value in values {
+        vector.push(value)?;
+    }
+    Ok(vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str, options: CsvOptions) -> PrismDBResult<CsvTable> {
+        CsvReader::new(text.as_bytes().to_vec()).with_options(options).read()
+    }
+
+    fn column(table: &CsvTable, column: usize) -> Vec<Value> {
+        table
+            .chunks
+            .iter()
+            .flat_map(|chunk| {
+                let vector = chunk.get_vector(column).unwrap();
+                (0..chunk.len()).map(|row| vector.get_value(row).unwrap()).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn types(table: &CsvTable) -> Vec<LogicalType> {
+        table.columns.iter().map(|(_, t)| t.clone()).collect()
+    }
+
+    #[test]
+    fn test_infers_types_from_sample() -> PrismDBResult<()> {
+        let table = read(
+            "id,price,ok,day,note\n1,2.5,true,2024-01-02,x\n2,3,FALSE,2024-02-03,\n",
+            CsvOptions::default(),
+        )?;
+        assert_eq!(
+            types(&table),
+            [
+                LogicalType::BigInt,
+                LogicalType::Double,
+                LogicalType::Boolean,
+                LogicalType::Date,
+                LogicalType::Varchar
+            ]
+        );
+        assert_eq!(column(&table, 1), [Value::Double(2.5), Value::Double(3.0)]);
+        assert_eq!(column(&table, 4), [Value::Varchar("x".into()), Value::Null]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_promotes_past_sample() -> PrismDBResult<()> {
+        // Three chunks of integers, then a float and a word after the sample
+        let mut text = String::from("n,m\n");
+        for i in 0..3 * DEFAULT_CHUNK_SIZE {
+            text.push_str(&format!("{},{}\n", i, i));
+        }
+        text.push_str("0.5,7\nabc,8\n");
+        let options = CsvOptions {
+            sample_size: 10,
+            ..CsvOptions::default()
+        };
 
-        Ok(headers.iter().map(|h| h.to_string()).collect())
+        let table = read(&text, options)?;
+        assert_eq!(types(&table), [LogicalType::Varchar, LogicalType::BigInt]);
+        let n = column(&table, 0);
+        assert_eq!(n.len(), 3 * DEFAULT_CHUNK_SIZE + 2);
+        assert_eq!(n[1], Value::Varchar("1".into()));
+        assert_eq!(n[3 * DEFAULT_CHUNK_SIZE], Value::Varchar("0.5".into()));
+        assert_eq!(column(&table, 1)[3 * DEFAULT_CHUNK_SIZE + 1], Value::BigInt(8));
+        Ok(())
     }
 }
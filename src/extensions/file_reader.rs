@@ -1,15 +1,81 @@
 //! File Reading for HTTP/S3 Support
 //!
-//! Provides functionality to read files from HTTP/HTTPS and S3-compatible storage
+//! Provides functionality to read files from local paths, HTTP/HTTPS and
+//! S3-compatible storage, and to decompress gzip- and zstd-compressed files
+//! as they are parsed. Remote files can also be read piecewise with HTTP range
+//! requests (see [`RemoteFile`]); S3 requests are signed with AWS Signature V4.
 
 use crate::common::error::{PrismDBError, PrismDBResult};
 use crate::extensions::aws_signature::{AwsSignatureV4, get_aws_timestamp};
 use crate::extensions::secrets::S3Config;
-use reqwest::blocking::Client;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{CONTENT_LENGTH, RANGE};
+use reqwest::{Method, StatusCode};
 use std::collections::HashMap;
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Compression of a file read by a table function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl FileCompression {
+    /// Detect compression from a file name's extension (`.gz`, `.zst`)
+    pub fn from_path(path: &str) -> Self {
+        // Ignore any query string on HTTP URLs
+        let path = path.split(['?', '#']).next().unwrap_or(path).to_lowercase();
+        if path.ends_with(".gz") || path.ends_with(".gzip") {
+            FileCompression::Gzip
+        } else if path.ends_with(".zst") || path.ends_with(".zstd") {
+            FileCompression::Zstd
+        } else {
+            FileCompression::None
+        }
+    }
+
+    /// Parse an explicit COMPRESSION option; `auto` detects it from `path`
+    pub fn from_option(option: &str, path: &str) -> PrismDBResult<Self> {
+        match option.to_lowercase().as_str() {
+            "auto" => Ok(Self::from_path(path)),
+            "none" | "uncompressed" => Ok(FileCompression::None),
+            "gzip" | "gz" => Ok(FileCompression::Gzip),
+            "zstd" | "zst" => Ok(FileCompression::Zstd),
+            _ => Err(PrismDBError::InvalidArgument(format!(
+                "Unknown compression '{}'. Expected auto, none, gzip or zstd",
+                option
+            ))),
+        }
+    }
+
+    /// Wrap `reader` so that reading from it yields the decompressed bytes
+    ///
+    /// Decompression is streamed: the decompressed file is never held in memory
+    /// as a whole.
+    pub fn decoder<'a, R: Read + 'a>(self, reader: R) -> PrismDBResult<Box<dyn Read + 'a>> {
+        match self {
+            FileCompression::None => Ok(Box::new(reader)),
+            #[cfg(feature = "compression")]
+            FileCompression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+            #[cfg(feature = "compression")]
+            FileCompression::Zstd => Ok(Box::new(zstd::stream::read::Decoder::new(reader)?)),
+            #[cfg(not(feature = "compression"))]
+            _ => Err(PrismDBError::NotImplemented(format!(
+                "{:?} files require the 'compression' feature",
+                self
+            ))),
+        }
+    }
+}
+
 /// File reader supporting HTTP and S3 protocols
+#[derive(Clone)]
 pub struct FileReader {
     client: Client,
 }
@@ -20,59 +86,210 @@ impl FileReader {
         let client = Client::builder()
             .timeout(Duration::from_secs(300))
             .build()
-            .map_err(|e| PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
+            .map_err(|e| PrismDBError::Io(std::io::Error::other(
                 format!("Failed to create HTTP client: {}", e)
             )))?;
 
         Ok(Self { client })
     }
 
-    /// Read a file from a URL (http://, https://, or s3://)
+    /// Whether `url` names a file fetched over HTTP(S) or S3
+    pub fn is_remote(url: &str) -> bool {
+        url.starts_with("s3://") || url.starts_with("http://") || url.starts_with("https://")
+    }
+
+    /// Read a file from a URL (http://, https://, s3:// or file://) or a local path
     pub fn read_file(&self, url: &str, s3_config: Option<&S3Config>) -> PrismDBResult<Vec<u8>> {
-        if url.starts_with("s3://") {
-            self.read_s3_file(url, s3_config)
-        } else if url.starts_with("http://") || url.starts_with("https://") {
-            self.read_http_file(url)
+        if Self::is_remote(url) {
+            let response = self.send(Method::GET, url, s3_config, None)?;
+            Self::body(response)
+        } else if let Some(path) = url.strip_prefix("file://") {
+            Ok(std::fs::read(path)?)
+        } else if !url.contains("://") {
+            Ok(std::fs::read(url)?)
         } else {
             Err(PrismDBError::InvalidArgument(format!(
-                "Unsupported URL scheme. Expected http://, https://, or s3://, got: {}",
+                "Unsupported URL scheme. Expected http://, https://, s3:// or file://, got: {}",
                 url
             )))
         }
     }
 
-    /// Read file via HTTP/HTTPS
-    fn read_http_file(&self, url: &str) -> PrismDBResult<Vec<u8>> {
-        println!("Reading file from HTTP: {}", url);
+    /// Whether `url` is a local glob pattern (`*`, `?` or `**`) naming many files
+    pub fn is_glob(url: &str) -> bool {
+        !Self::is_remote(url) && (url.contains('*') || url.contains('?'))
+    }
+
+    /// Local files matching a glob pattern, in sorted order
+    ///
+    /// `*` and `?` match within one path component and `**` matches any
+    /// number of directories.
+    pub fn glob_files(pattern: &str) -> PrismDBResult<Vec<String>> {
+        let pattern = pattern.strip_prefix("file://").unwrap_or(pattern);
+        let components: Vec<&str> = pattern.split('/').collect();
+        let literal = components
+            .iter()
+            .position(|c| c.contains('*') || c.contains('?'))
+            .unwrap_or(components.len());
+        let base = match components[..literal].join("/") {
+            base if base.is_empty() && pattern.starts_with('/') => "/".to_string(),
+            base if base.is_empty() => ".".to_string(),
+            base => base,
+        };
+
+        let mut files = Vec::new();
+        Self::glob_walk(std::path::Path::new(&base), &components[literal..], &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+
+    fn glob_walk(
+        dir: &std::path::Path,
+        components: &[&str],
+        files: &mut Vec<String>,
+    ) -> PrismDBResult<()> {
+        let Some((&component, rest)) = components.split_first() else {
+            return Ok(());
+        };
+        if component == "**" {
+            // Match no directories, then descend with `**` still pending
+            Self::glob_walk(dir, rest, files)?;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Ok(());
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if component == "**" {
+                if path.is_dir() {
+                    Self::glob_walk(&path, components, files)?;
+                }
+            } else if wildcard_match(component, &name) {
+                if rest.is_empty() {
+                    if path.is_file() {
+                        files.push(path.to_string_lossy().to_string());
+                    }
+                } else if path.is_dir() {
+                    Self::glob_walk(&path, rest, files)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hive-style `key=value` partitions in the directories of a file path
+    ///
+    /// A value written as `NULL` is a NULL key.
+    pub fn hive_partitions(path: &str) -> Vec<(String, Option<String>)> {
+        let mut directories: Vec<&str> = path.split('/').collect();
+        directories.pop();
+        directories
+            .into_iter()
+            .filter_map(|directory| directory.split_once('='))
+            .map(|(key, value)| {
+                let value = match value {
+                    "NULL" => None,
+                    value => Some(value.replace("%2F", "/").replace("%25", "%")),
+                };
+                (key.to_string(), value)
+            })
+            .collect()
+    }
 
-        let response = self.client
-            .get(url)
+    /// Size in bytes of a file over HTTP(S) or S3, from a HEAD request
+    pub fn file_size(&self, url: &str, s3_config: Option<&S3Config>) -> PrismDBResult<u64> {
+        let response = self.send(Method::HEAD, url, s3_config, None)?;
+        response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|length| length.to_str().ok())
+            .and_then(|length| length.parse().ok())
+            .ok_or_else(|| Self::request_error(format!("No Content-Length for {}", url)))
+    }
+
+    /// Read `length` bytes starting at `start` from a file over HTTP(S) or S3
+    ///
+    /// Uses an HTTP range request; a server that ignores the range and sends
+    /// the whole file still yields just the requested bytes.
+    pub fn read_range(
+        &self,
+        url: &str,
+        s3_config: Option<&S3Config>,
+        start: u64,
+        length: usize,
+    ) -> PrismDBResult<Vec<u8>> {
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+        let range = format!("bytes={}-{}", start, start + length as u64 - 1);
+        let response = self.send(Method::GET, url, s3_config, Some(range))?;
+        let partial = response.status() == StatusCode::PARTIAL_CONTENT;
+        let mut bytes = Self::body(response)?;
+        if !partial {
+            let start = (start as usize).min(bytes.len());
+            let end = (start + length).min(bytes.len());
+            bytes = bytes[start..end].to_vec();
+        }
+        Ok(bytes)
+    }
+
+    /// Send a request for `url`, signing it for S3 URLs, and check its status
+    fn send(
+        &self,
+        method: Method,
+        url: &str,
+        s3_config: Option<&S3Config>,
+        range: Option<String>,
+    ) -> PrismDBResult<Response> {
+        let mut request = if url.starts_with("s3://") {
+            self.s3_request(method, url, s3_config)?
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            self.client.request(method, url)
+        } else {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "Unsupported URL scheme. Expected http://, https://, or s3://, got: {}",
+                url
+            )));
+        };
+        if let Some(range) = range {
+            request = request.header(RANGE, range);
+        }
+
+        let response = request
             .send()
-            .map_err(|e| PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("HTTP request failed: {}", e)
-            )))?;
+            .map_err(|e| Self::request_error(format!("HTTP request failed: {}", e)))?;
 
         if !response.status().is_success() {
-            return Err(PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("HTTP request failed with status: {}", response.status())
+            let status = response.status();
+            let error_text = response.text().unwrap_or_default();
+            return Err(Self::request_error(format!(
+                "HTTP request failed with status: {} {}",
+                status, error_text
             )));
         }
+        Ok(response)
+    }
 
-        let bytes = response.bytes()
-            .map_err(|e| PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to read response body: {}", e)
-            )))?;
+    fn body(response: Response) -> PrismDBResult<Vec<u8>> {
+        response
+            .bytes()
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| Self::request_error(format!("Failed to read response body: {}", e)))
+    }
 
-        println!("Successfully read {} bytes", bytes.len());
-        Ok(bytes.to_vec())
+    fn request_error(message: String) -> PrismDBError {
+        PrismDBError::Io(std::io::Error::other(message))
     }
 
-    /// Read file from S3-compatible storage (MinIO)
-    fn read_s3_file(&self, s3_url: &str, s3_config: Option<&S3Config>) -> PrismDBResult<Vec<u8>> {
+    /// Build a request for a file in S3-compatible storage (MinIO), signed with AWS Signature V4
+    fn s3_request(
+        &self,
+        method: Method,
+        s3_url: &str,
+        s3_config: Option<&S3Config>,
+    ) -> PrismDBResult<RequestBuilder> {
         let config = s3_config.ok_or_else(|| {
             PrismDBError::InvalidArgument(
                 "S3 configuration required for s3:// URLs. Use SET or CREATE SECRET to configure.".to_string()
@@ -103,12 +320,6 @@ impl FileReader {
         // Use path-style URL for MinIO
         let http_url = format!("{}://{}/{}/{}", protocol, endpoint, bucket, path);
 
-        println!("Reading S3 file: {}", s3_url);
-        println!("  Endpoint: {}", endpoint);
-        println!("  Bucket: {}", bucket);
-        println!("  Path: {}", path);
-        println!("  HTTP URL: {}", http_url);
-
         // Get AWS credentials
         let access_key_id = config.access_key_id.as_ref().ok_or_else(|| {
             PrismDBError::InvalidArgument(
@@ -122,9 +333,6 @@ impl FileReader {
             )
         })?;
 
-        println!("  Using AWS Signature V4 authentication");
-        println!("  Access Key ID: {}", access_key_id);
-
         // Get current timestamp
         let timestamp = get_aws_timestamp();
 
@@ -141,47 +349,137 @@ impl FileReader {
 
         // Sign the request
         let authorization = signer.sign_request(
-            "GET",
+            method.as_str(),
             &http_url,
             &mut headers_map,
-            b"", // Empty payload for GET request
+            b"", // Empty payload for GET and HEAD requests
             &timestamp,
         );
 
         // Build the HTTP request with signed headers
-        let mut request = self.client.get(&http_url);
-        request = request.header("Authorization", authorization);
-        request = request.header("x-amz-date", timestamp);
+        Ok(self
+            .client
+            .request(method, &http_url)
+            .header("Authorization", authorization)
+            .header("x-amz-date", timestamp))
+    }
+}
 
-        let response = request
-            .send()
-            .map_err(|e| PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("S3 request failed: {}", e)
-            )))?;
+impl Default for FileReader {
+    fn default() -> Self {
+        Self::new().expect("Failed to create file reader")
+    }
+}
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().unwrap_or_default();
-            return Err(PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("S3 request failed with status: {} - {}", status, error_text)
-            )));
+/// Match a file name against a pattern where `*` matches any run of
+/// characters and `?` matches one character
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, n));
+            p += 1;
+        } else if let Some((after, matched)) = star {
+            p = after;
+            n = matched + 1;
+            star = Some((after, matched + 1));
+        } else {
+            return false;
         }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
 
-        let bytes = response.bytes()
-            .map_err(|e| PrismDBError::Io(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Failed to read S3 response body: {}", e)
-            )))?;
+/// Size of the blocks a sequential read of a [`RemoteFile`] fetches at a time
+const REMOTE_READ_BLOCK_SIZE: usize = 64 * 1024;
+
+/// A file over HTTP(S) or S3 read piecewise with range requests
+///
+/// Lets the Parquet reader fetch the footer and the column chunks it needs
+/// without downloading the whole file. Clones share the fetched-byte count.
+#[derive(Clone)]
+pub struct RemoteFile {
+    reader: FileReader,
+    url: String,
+    s3_config: Option<S3Config>,
+    size: u64,
+    bytes_fetched: Arc<AtomicU64>,
+}
 
-        println!("Successfully read {} bytes from S3", bytes.len());
-        Ok(bytes.to_vec())
+impl RemoteFile {
+    /// Open the file at `url`, fetching only its size
+    pub fn open(reader: FileReader, url: &str, s3_config: Option<&S3Config>) -> PrismDBResult<Self> {
+        let size = reader.file_size(url, s3_config)?;
+        Ok(Self {
+            reader,
+            url: url.to_string(),
+            s3_config: s3_config.cloned(),
+            size,
+            bytes_fetched: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Size of the file in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Total bytes fetched from the server so far
+    pub fn bytes_fetched(&self) -> u64 {
+        self.bytes_fetched.load(Ordering::Relaxed)
+    }
+
+    /// Read `length` bytes starting at `start`, clamped to the end of the file
+    pub fn read_range(&self, start: u64, length: usize) -> PrismDBResult<Vec<u8>> {
+        let length = length.min(self.size.saturating_sub(start) as usize);
+        let bytes = self
+            .reader
+            .read_range(&self.url, self.s3_config.as_ref(), start, length)?;
+        self.bytes_fetched
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        Ok(bytes)
+    }
+
+    /// Sequential reader from `start`, fetching a block at a time as it is read
+    pub fn reader_at(&self, start: u64) -> RemoteFileReader {
+        RemoteFileReader {
+            file: self.clone(),
+            position: start,
+            buffer: Vec::new(),
+            buffer_offset: 0,
+        }
     }
 }
 
-impl Default for FileReader {
-    fn default() -> Self {
-        Self::new().expect("Failed to create file reader")
+/// Sequential reader over a [`RemoteFile`]
+pub struct RemoteFileReader {
+    file: RemoteFile,
+    position: u64,
+    buffer: Vec<u8>,
+    buffer_offset: usize,
+}
+
+impl Read for RemoteFileReader {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        if self.buffer_offset == self.buffer.len() {
+            self.buffer = self
+                .file
+                .read_range(self.position, REMOTE_READ_BLOCK_SIZE)
+                .map_err(|e| std::io::Error::other(e.to_string()))?;
+            self.buffer_offset = 0;
+            self.position += self.buffer.len() as u64;
+        }
+        let available = &self.buffer[self.buffer_offset..];
+        let count = available.len().min(out.len());
+        out[..count].copy_from_slice(&available[..count]);
+        self.buffer_offset += count;
+        Ok(count)
     }
 }
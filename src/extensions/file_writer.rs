@@ -0,0 +1,315 @@
+//! File Writing for COPY ... TO
+//!
+//! Writes query results as CSV, Parquet or JSON files. With partition keys, rows are
+//! grouped by their key values and each group is written to its own Hive-style
+//! directory (`year=2024/month=1/data_0.parquet`); the key columns are encoded
+//! in the path rather than stored in the files.
+
+use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::types::{LogicalType, Value};
+use arrow::array::{
+    ArrayRef, BooleanArray, Date32Array, Float32Array, Float64Array, Int16Array, Int32Array,
+    Int64Array, Int8Array, StringArray,
+};
+use arrow::datatypes::{Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// File format COPY writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    Csv,
+    Parquet,
+    /// Newline-delimited JSON objects, or one JSON array of them
+    Json,
+}
+
+impl CopyFormat {
+    /// Parse a FORMAT option
+    pub fn parse(name: &str) -> PrismDBResult<Self> {
+        match name.to_lowercase().as_str() {
+            "csv" => Ok(CopyFormat::Csv),
+            "parquet" => Ok(CopyFormat::Parquet),
+            "json" => Ok(CopyFormat::Json),
+            _ => Err(PrismDBError::InvalidArgument(format!(
+                "Unsupported COPY format '{}'. Expected CSV, PARQUET or JSON",
+                name
+            ))),
+        }
+    }
+
+    /// Format implied by a file name's extension, CSV if there is none
+    pub fn from_path(path: &str) -> Self {
+        let path = path.to_lowercase();
+        if path.ends_with(".parquet") {
+            CopyFormat::Parquet
+        } else if [".json", ".ndjson", ".jsonl"].iter().any(|extension| path.ends_with(extension)) {
+            CopyFormat::Json
+        } else {
+            CopyFormat::Csv
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            CopyFormat::Csv => "csv",
+            CopyFormat::Parquet => "parquet",
+            CopyFormat::Json => "json",
+        }
+    }
+}
+
+/// Writer for the rows of a COPY ... TO
+pub struct FileWriter {
+    format: CopyFormat,
+    partition_by: Vec<String>,
+    header: bool,
+    json_array: bool,
+}
+
+impl FileWriter {
+    /// Create a writer for `format`, writing a single unpartitioned file
+    pub fn new(format: CopyFormat) -> Self {
+        Self {
+            format,
+            partition_by: Vec::new(),
+            header: true,
+            json_array: false,
+        }
+    }
+
+    /// Write one directory per distinct combination of these columns' values
+    pub fn with_partition_by(mut self, columns: Vec<String>) -> Self {
+        self.partition_by = columns;
+        self
+    }
+
+    /// Whether CSV files start with a header row (the default)
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Whether JSON files hold one array of objects rather than one object per line
+    pub fn with_json_array(mut self, json_array: bool) -> Self {
+        self.json_array = json_array;
+        self
+    }
+
+    /// Write `rows`, whose columns are `columns`, to `target`
+    ///
+    /// Without partition keys `target` is the file to write; with them it is
+    /// the root directory of the partitions. Returns the files written.
+    pub fn write(
+        &self,
+        target: &Path,
+        columns: &[(String, LogicalType)],
+        rows: &[Vec<Value>],
+    ) -> PrismDBResult<Vec<PathBuf>> {
+        if self.partition_by.is_empty() {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            self.write_file(target, columns, rows.iter().cloned())?;
+            return Ok(vec![target.to_path_buf()]);
+        }
+
+        let key_indices = self
+            .partition_by
+            .iter()
+            .map(|key| {
+                columns
+                    .iter()
+                    .position(|(name, _)| name.eq_ignore_ascii_case(key))
+                    .ok_or_else(|| {
+                        PrismDBError::InvalidArgument(format!(
+                            "PARTITION_BY column '{}' is not in the COPY output",
+                            key
+                        ))
+                    })
+            })
+            .collect::<PrismDBResult<Vec<usize>>>()?;
+        if key_indices.len() == columns.len() {
+            return Err(PrismDBError::InvalidArgument(
+                "PARTITION_BY cannot include every column of the COPY output".to_string(),
+            ));
+        }
+
+        let data_indices: Vec<usize> = (0..columns.len())
+            .filter(|i| !key_indices.contains(i))
+            .collect();
+        let data_columns: Vec<(String, LogicalType)> =
+            data_indices.iter().map(|&i| columns[i].clone()).collect();
+
+        // Group rows by the directory their key values name
+        let mut partitions: BTreeMap<PathBuf, Vec<Vec<Value>>> = BTreeMap::new();
+        for row in rows {
+            let mut directory = target.to_path_buf();
+            for &i in &key_indices {
+                directory.push(format!("{}={}", columns[i].0, partition_value(&row[i])));
+            }
+            let data = data_indices.iter().map(|&i| row[i].clone()).collect();
+            partitions.entry(directory).or_default().push(data);
+        }
+
+        let mut files = Vec::new();
+        for (directory, rows) in partitions {
+            std::fs::create_dir_all(&directory)?;
+            let file = directory.join(format!("data_0.{}", self.format.extension()));
+            self.write_file(&file, &data_columns, rows.into_iter())?;
+            files.push(file);
+        }
+        Ok(files)
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        columns: &[(String, LogicalType)],
+        rows: impl Iterator<Item = Vec<Value>>,
+    ) -> PrismDBResult<()> {
+        match self.format {
+            CopyFormat::Csv => self.write_csv(path, columns, rows),
+            CopyFormat::Parquet => write_parquet(path, columns, rows.collect()),
+            CopyFormat::Json => self.write_json(path, columns, rows),
+        }
+    }
+
+    /// Write each row as an object keyed by column name, values mapped by [`Value::to_json`]
+    fn write_json(
+        &self,
+        path: &Path,
+        columns: &[(String, LogicalType)],
+        rows: impl Iterator<Item = Vec<Value>>,
+    ) -> PrismDBResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let (open, separator, close) = match self.json_array {
+            true => ("[\n", ",\n", "\n]\n"),
+            false => ("", "\n", "\n"),
+        };
+        write!(writer, "{}", open)?;
+        let mut empty = true;
+        for row in rows {
+            let object: serde_json::Map<String, serde_json::Value> = columns
+                .iter()
+                .zip(&row)
+                .map(|((name, _), value)| (name.clone(), value.to_json()))
+                .collect();
+            if !empty {
+                write!(writer, "{}", separator)?;
+            }
+            write!(writer, "{}", serde_json::Value::Object(object))?;
+            empty = false;
+        }
+        if !empty || self.json_array {
+            write!(writer, "{}", close)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_csv(
+        &self,
+        path: &Path,
+        columns: &[(String, LogicalType)],
+        rows: impl Iterator<Item = Vec<Value>>,
+    ) -> PrismDBResult<()> {
+        let csv_error = |e: csv::Error| PrismDBError::Execution(format!("Failed to write CSV: {}", e));
+        let mut writer = csv::Writer::from_path(path).map_err(csv_error)?;
+        if self.header {
+            writer
+                .write_record(columns.iter().map(|(name, _)| name))
+                .map_err(csv_error)?;
+        }
+        for row in rows {
+            writer
+                .write_record(row.iter().map(|value| match value {
+                    Value::Null => String::new(),
+                    value => text(value),
+                }))
+                .map_err(csv_error)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// A value as plain text, without the quotes `Display` puts around strings
+fn text(value: &Value) -> String {
+    match value {
+        Value::Varchar(s) | Value::Char(s) => s.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// A partition key value as it appears in a directory name
+fn partition_value(value: &Value) -> String {
+    if value.is_null() {
+        return "NULL".to_string();
+    }
+    text(value).replace('%', "%25").replace('/', "%2F")
+}
+
+/// Write the rows as a single-row-group Parquet file
+fn write_parquet(
+    path: &Path,
+    columns: &[(String, LogicalType)],
+    rows: Vec<Vec<Value>>,
+) -> PrismDBResult<()> {
+    let parquet_error = |e: parquet::errors::ParquetError| {
+        PrismDBError::Execution(format!("Failed to write Parquet: {}", e))
+    };
+
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut arrays = Vec::with_capacity(columns.len());
+    for (index, (name, logical_type)) in columns.iter().enumerate() {
+        let values = rows.iter().map(|row| &row[index]);
+        let array = arrow_array(logical_type, values)?;
+        fields.push(Field::new(name, array.data_type().clone(), true));
+        arrays.push(array);
+    }
+
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), arrays)
+        .map_err(|e| PrismDBError::Execution(format!("Failed to build Parquet batch: {}", e)))?;
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema, None).map_err(parquet_error)?;
+    writer.write(&batch).map_err(parquet_error)?;
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+/// Build the Arrow array for a column; types without an Arrow counterpart are written as text
+fn arrow_array<'a>(
+    logical_type: &LogicalType,
+    values: impl Iterator<Item = &'a Value>,
+) -> PrismDBResult<ArrayRef> {
+    fn collect<'a, T>(
+        values: impl Iterator<Item = &'a Value>,
+        convert: impl Fn(&Value) -> PrismDBResult<T>,
+    ) -> PrismDBResult<Vec<Option<T>>> {
+        values
+            .map(|value| match value {
+                Value::Null => Ok(None),
+                value => convert(value).map(Some),
+            })
+            .collect()
+    }
+
+    let array: ArrayRef = match logical_type {
+        LogicalType::Boolean => Arc::new(BooleanArray::from(collect(values, |v| v.try_as_boolean())?)),
+        LogicalType::TinyInt => Arc::new(Int8Array::from(collect(values, |v| Ok(v.try_as_i64()? as i8))?)),
+        LogicalType::SmallInt => Arc::new(Int16Array::from(collect(values, |v| Ok(v.try_as_i64()? as i16))?)),
+        LogicalType::Integer => Arc::new(Int32Array::from(collect(values, |v| Ok(v.try_as_i64()? as i32))?)),
+        LogicalType::BigInt => Arc::new(Int64Array::from(collect(values, |v| v.try_as_i64())?)),
+        LogicalType::Float => Arc::new(Float32Array::from(collect(values, |v| Ok(v.try_as_f64()? as f32))?)),
+        LogicalType::Double => Arc::new(Float64Array::from(collect(values, |v| v.try_as_f64())?)),
+        LogicalType::Date => Arc::new(Date32Array::from(collect(values, |v| Ok(v.try_as_i64()? as i32))?)),
+        _ => Arc::new(StringArray::from(collect(values, |v| Ok(text(v)))?)),
+    };
+    Ok(array)
+}
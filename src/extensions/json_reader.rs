@@ -1,51 +1,120 @@
 //! JSON Reading Functionality
 //!
-//! Implements read_json_auto() table function
+//! Implements read_json_auto() table function and reads the rows of COPY ... FROM
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::extensions::file_reader::FileCompression;
 use crate::types::{DataChunk, LogicalType, Value, Vector};
 use serde_json;
+use std::io::{BufRead, BufReader};
 
 /// JSON reader
 pub struct JsonReader {
     data: Vec<u8>,
+    compression: FileCompression,
 }
 
 impl JsonReader {
     /// Create a new JSON reader from bytes
     pub fn new(data: Vec<u8>) -> Self {
-        Self { data }
+        Self {
+            data,
+            compression: FileCompression::None,
+        }
+    }
+
+    /// Decompress the bytes with `compression` while parsing them
+    pub fn with_compression(mut self, compression: FileCompression) -> Self {
+        self.compression = compression;
+        self
     }
 
     /// Read JSON and return a DataChunk
     pub fn read(&self) -> PrismDBResult<DataChunk> {
-        let text = std::str::from_utf8(&self.data)
-            .map_err(|e| PrismDBError::Parse(format!("Invalid UTF-8 in JSON file: {}", e)))?;
-
-        // Try to parse as JSON array first
-        if let Ok(array) = serde_json::from_str::<Vec<serde_json::Value>>(text) {
-            return self.read_json_array(array);
+        let (is_array, documents) = self.documents(usize::MAX)?;
+        if !is_array && documents.is_empty() {
+            return Err(PrismDBError::Parse("Empty or invalid JSON file".to_string()));
         }
+        self.read_json_array(documents)
+    }
 
-        // Try newline-delimited JSON (NDJSON)
-        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
-        if !lines.is_empty() {
-            let mut objects = Vec::new();
-            for line in lines {
-                match serde_json::from_str::<serde_json::Value>(line) {
-                    Ok(obj) => objects.push(obj),
-                    Err(_) => {
-                        // Not NDJSON, return error
-                        return Err(PrismDBError::Parse(
-                            "Invalid JSON format: expected array of objects or newline-delimited JSON".to_string()
-                        ));
-                    }
+    /// Read the file's objects as rows of `columns`, matching keys to column
+    /// names case-insensitively
+    ///
+    /// Values are read with [`Value::from_json`], so a file written by COPY ...
+    /// TO (FORMAT JSON) reads back with its types; a missing key is NULL and a
+    /// key that names no column is an error.
+    pub fn read_rows(&self, columns: &[(String, LogicalType)]) -> PrismDBResult<Vec<Vec<Value>>> {
+        let (_, documents) = self.documents(usize::MAX)?;
+        documents
+            .iter()
+            .map(|document| {
+                let object = document.as_object().ok_or_else(|| {
+                    PrismDBError::Parse(format!("Expected a JSON object, got {}", document))
+                })?;
+                let mut row = vec![Value::Null; columns.len()];
+                for (key, json) in object {
+                    let index = columns
+                        .iter()
+                        .position(|(name, _)| name.eq_ignore_ascii_case(key))
+                        .ok_or_else(|| {
+                            PrismDBError::InvalidArgument(format!("JSON key '{}' is not a column", key))
+                        })?;
+                    row[index] = Value::from_json(json, &columns[index].1)?;
                 }
+                Ok(row)
+            })
+            .collect()
+    }
+
+    /// Parse the JSON documents in the file, decompressing it as it is read
+    ///
+    /// A file holding a top-level array yields the array's elements; any other
+    /// file is read as newline-delimited JSON, stopping after `limit` values.
+    /// Also returns whether the file held an array.
+    fn documents(&self, limit: usize) -> PrismDBResult<(bool, Vec<serde_json::Value>)> {
+        let invalid = || {
+            PrismDBError::Parse(
+                "Invalid JSON format: expected array of objects or newline-delimited JSON".to_string(),
+            )
+        };
+        let mut reader = BufReader::new(self.compression.decoder(self.data.as_slice())?);
+
+        // Skip leading whitespace to see whether the file starts with an array
+        let is_array = loop {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break false;
+            }
+            let whitespace = buffer.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            let first = buffer.get(whitespace).copied();
+            reader.consume(whitespace);
+            if let Some(first) = first {
+                break first == b'[';
             }
-            return self.read_json_array(objects);
+        };
+
+        if is_array {
+            let array = serde_json::from_reader(reader).map_err(|_| invalid())?;
+            return Ok((true, array));
         }
 
-        Err(PrismDBError::Parse("Empty or invalid JSON file".to_string()))
+        let documents = serde_json::Deserializer::from_reader(reader)
+            .into_iter::<serde_json::Value>()
+            .take(limit)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        Ok((false, documents))
+    }
+
+    /// The first object in the file, which the schema is inferred from
+    fn first_object(&self) -> PrismDBResult<Option<serde_json::Map<String, serde_json::Value>>> {
+        let (_, documents) = self.documents(1)?;
+        match documents.into_iter().next() {
+            None => Ok(None),
+            Some(serde_json::Value::Object(obj)) => Ok(Some(obj)),
+            Some(_) => Err(PrismDBError::Parse("JSON array must contain objects".to_string())),
+        }
     }
 
     /// Read a JSON array and convert to DataChunk
@@ -116,60 +185,17 @@ impl JsonReader {
 
     /// Get column names from JSON
     pub fn get_column_names(&self) -> PrismDBResult<Vec<String>> {
-        let text = std::str::from_utf8(&self.data)
-            .map_err(|e| PrismDBError::Parse(format!("Invalid UTF-8 in JSON file: {}", e)))?;
-
-        // Try to parse as JSON array first
-        let array = if let Ok(arr) = serde_json::from_str::<Vec<serde_json::Value>>(text) {
-            arr
-        } else {
-            // Try NDJSON
-            let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
-            if lines.is_empty() {
-                return Ok(vec![]);
-            }
-            let first_obj = serde_json::from_str::<serde_json::Value>(lines[0])
-                .map_err(|e| PrismDBError::Parse(format!("Failed to parse JSON: {}", e)))?;
-            vec![first_obj]
-        };
-
-        if array.is_empty() {
-            return Ok(vec![]);
-        }
-
-        let obj = array[0].as_object().ok_or_else(|| {
-            PrismDBError::Parse("JSON array must contain objects".to_string())
-        })?;
-
-        Ok(obj.keys().cloned().collect())
+        Ok(self
+            .first_object()?
+            .map(|obj| obj.keys().cloned().collect())
+            .unwrap_or_default())
     }
 
     /// Get column types from JSON
     pub fn get_column_types(&self) -> PrismDBResult<Vec<LogicalType>> {
-        let text = std::str::from_utf8(&self.data)
-            .map_err(|e| PrismDBError::Parse(format!("Invalid UTF-8 in JSON file: {}", e)))?;
-
-        // Try to parse as JSON array first
-        let array = if let Ok(arr) = serde_json::from_str::<Vec<serde_json::Value>>(text) {
-            arr
-        } else {
-            // Try NDJSON
-            let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
-            if lines.is_empty() {
-                return Ok(vec![]);
-            }
-            let first_obj = serde_json::from_str::<serde_json::Value>(lines[0])
-                .map_err(|e| PrismDBError::Parse(format!("Failed to parse JSON: {}", e)))?;
-            vec![first_obj]
-        };
-
-        if array.is_empty() {
+        let Some(obj) = self.first_object()? else {
             return Ok(vec![]);
-        }
-
-        let obj = array[0].as_object().ok_or_else(|| {
-            PrismDBError::Parse("JSON array must contain objects".to_string())
-        })?;
+        };
 
         let mut types = Vec::new();
         for (_key, value) in obj.iter() {
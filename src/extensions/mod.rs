@@ -6,6 +6,7 @@ pub mod aws_signature;
 pub mod config;
 pub mod csv_reader;
 pub mod file_reader;
+pub mod file_writer;
 pub mod json_reader;
 pub mod parquet_reader;
 pub mod secrets;
@@ -15,12 +16,14 @@ pub use aws_signature::{AwsSignatureV4, get_aws_timestamp};
 pub use config::ConfigManager;
 pub use csv_reader::CsvReader;
 pub use file_reader::FileReader;
+pub use file_writer::{CopyFormat, FileWriter};
 pub use json_reader::JsonReader;
 pub use parquet_reader::ParquetReader;
 pub use secrets::{S3Config, Secret, SecretsManager};
 pub use sqlite_reader::SqliteReader;
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
@@ -124,7 +127,7 @@ impl ExtensionManager {
 
         // Check if already installed
         {
-            let installed = self.installed.read().unwrap();
+            let installed = self.installed.read_or_err()?;
             if installed.contains(&name) {
                 println!("Extension '{}' is already installed.", extension_name);
                 return Ok(());
@@ -143,7 +146,7 @@ impl ExtensionManager {
 
         // Mark as installed
         {
-            let mut installed = self.installed.write().unwrap();
+            let mut installed = self.installed.write_or_err()?;
             installed.insert(name.clone());
         }
 
@@ -173,7 +176,7 @@ impl ExtensionManager {
 
         // Check if installed
         {
-            let installed = self.installed.read().unwrap();
+            let installed = self.installed.read_or_err()?;
             if !installed.contains(&name) {
                 return Err(PrismDBError::Extension(format!(
                     "Extension '{}' is not installed. Run INSTALL {} first.",
@@ -184,7 +187,7 @@ impl ExtensionManager {
 
         // Check if already loaded
         {
-            let loaded = self.loaded.read().unwrap();
+            let loaded = self.loaded.read_or_err()?;
             if loaded.contains(&name) {
                 println!("Extension '{}' is already loaded.", extension_name);
                 return Ok(());
@@ -197,7 +200,7 @@ impl ExtensionManager {
 
         // Mark as loaded
         {
-            let mut loaded = self.loaded.write().unwrap();
+            let mut loaded = self.loaded.write_or_err()?;
             loaded.insert(name);
         }
 
@@ -208,13 +211,13 @@ impl ExtensionManager {
 
     /// Check if an extension is installed
     pub fn is_installed(&self, extension_name: &str) -> bool {
-        let installed = self.installed.read().unwrap();
+        let installed = self.installed.read_recovered();
         installed.contains(&extension_name.to_lowercase())
     }
 
     /// Check if an extension is loaded
     pub fn is_loaded(&self, extension_name: &str) -> bool {
-        let loaded = self.loaded.read().unwrap();
+        let loaded = self.loaded.read_recovered();
         loaded.contains(&extension_name.to_lowercase())
     }
 
@@ -227,7 +230,7 @@ impl ExtensionManager {
 
     /// List installed extensions
     pub fn list_installed(&self) -> Vec<String> {
-        let installed = self.installed.read().unwrap();
+        let installed = self.installed.read_recovered();
         let mut names: Vec<String> = installed.iter().cloned().collect();
         names.sort();
         names
@@ -235,7 +238,7 @@ impl ExtensionManager {
 
     /// List loaded extensions
     pub fn list_loaded(&self) -> Vec<String> {
-        let loaded = self.loaded.read().unwrap();
+        let loaded = self.loaded.read_recovered();
         let mut names: Vec<String> = loaded.iter().cloned().collect();
         names.sort();
         names
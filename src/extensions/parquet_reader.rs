@@ -3,30 +3,43 @@
 //! Implements read_parquet() table function
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::extensions::file_reader::{RemoteFile, RemoteFileReader};
 use crate::types::{DataChunk, LogicalType, Value, Vector};
 use arrow::array::*;
 use arrow::datatypes::DataType as ArrowDataType;
 use bytes::Bytes;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::errors::ParquetError;
+use parquet::file::reader::{ChunkReader, Length};
 use std::sync::Arc;
 
 /// Parquet reader
-pub struct ParquetReader {
-    data: Vec<u8>,
+///
+/// Reads from bytes in memory or, for files over HTTP(S) or S3, from a
+/// [`RemoteFile`] so that only the footer and column chunks are fetched.
+pub struct ParquetReader<R = Bytes> {
+    source: R,
 }
 
-impl ParquetReader {
+impl ParquetReader<Bytes> {
     /// Create a new Parquet reader from bytes
     pub fn new(data: Vec<u8>) -> Self {
-        Self { data }
+        Self {
+            source: Bytes::from(data),
+        }
+    }
+}
+
+impl<R: ChunkReader + Clone + 'static> ParquetReader<R> {
+    /// Create a Parquet reader over any source Parquet can read ranges of
+    pub fn from_source(source: R) -> Self {
+        Self { source }
     }
 
     /// Read Parquet and return a DataChunk
     pub fn read(&self) -> PrismDBResult<DataChunk> {
-        let bytes = Bytes::from(self.data.clone());
-
         // Build parquet reader
-        let builder = ParquetRecordBatchReaderBuilder::try_new(bytes)
+        let builder = ParquetRecordBatchReaderBuilder::try_new(self.source.clone())
             .map_err(|e| PrismDBError::Parse(format!("Failed to create Parquet reader: {}", e)))?;
 
         let schema = builder.schema().clone();
@@ -90,8 +103,7 @@ impl ParquetReader {
 
     /// Get column names from Parquet schema
     pub fn get_column_names(&self) -> PrismDBResult<Vec<String>> {
-        let bytes = Bytes::from(self.data.clone());
-        let builder = ParquetRecordBatchReaderBuilder::try_new(bytes)
+        let builder = ParquetRecordBatchReaderBuilder::try_new(self.source.clone())
             .map_err(|e| PrismDBError::Parse(format!("Failed to read Parquet schema: {}", e)))?;
 
         let schema = builder.schema();
@@ -100,8 +112,7 @@ impl ParquetReader {
 
     /// Get column types from Parquet schema
     pub fn get_column_types(&self) -> PrismDBResult<Vec<LogicalType>> {
-        let bytes = Bytes::from(self.data.clone());
-        let builder = ParquetRecordBatchReaderBuilder::try_new(bytes)
+        let builder = ParquetRecordBatchReaderBuilder::try_new(self.source.clone())
             .map_err(|e| PrismDBError::Parse(format!("Failed to read Parquet schema: {}", e)))?;
 
         let schema = builder.schema();
@@ -269,3 +280,23 @@ impl ParquetReader {
         }
     }
 }
+
+impl Length for RemoteFile {
+    fn len(&self) -> u64 {
+        self.size()
+    }
+}
+
+impl ChunkReader for RemoteFile {
+    type T = RemoteFileReader;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        Ok(self.reader_at(start))
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        self.read_range(start, length)
+            .map(Bytes::from)
+            .map_err(|e| ParquetError::External(Box::new(e)))
+    }
+}
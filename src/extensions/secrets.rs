@@ -3,6 +3,7 @@
 //! Handles CREATE SECRET statements for S3 and other credential management
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -30,7 +31,7 @@ impl SecretsManager {
 
     /// Create or replace a secret
     pub fn create_secret(&self, name: String, secret_type: String, options: HashMap<String, String>, or_replace: bool) -> PrismDBResult<()> {
-        let mut secrets = self.secrets.write().unwrap();
+        let mut secrets = self.secrets.write_or_err()?;
 
         // Check if secret already exists
         if secrets.contains_key(&name) && !or_replace {
@@ -67,19 +68,19 @@ impl SecretsManager {
 
     /// Get a secret by name
     pub fn get_secret(&self, name: &str) -> Option<Secret> {
-        let secrets = self.secrets.read().unwrap();
+        let secrets = self.secrets.read_recovered();
         secrets.get(name).cloned()
     }
 
     /// List all secrets (names only for security)
     pub fn list_secrets(&self) -> Vec<String> {
-        let secrets = self.secrets.read().unwrap();
+        let secrets = self.secrets.read_recovered();
         secrets.keys().cloned().collect()
     }
 
     /// Drop a secret
     pub fn drop_secret(&self, name: &str) -> PrismDBResult<()> {
-        let mut secrets = self.secrets.write().unwrap();
+        let mut secrets = self.secrets.write_or_err()?;
         if secrets.remove(name).is_some() {
             println!("DROP SECRET {}", name);
             Ok(())
@@ -90,7 +91,7 @@ impl SecretsManager {
 
     /// Get S3 configuration from secrets and settings
     pub fn get_s3_config(&self, config_manager: &super::config::ConfigManager) -> S3Config {
-        let secrets = self.secrets.read().unwrap();
+        let secrets = self.secrets.read_recovered();
 
         // Try to find an S3 secret
         let s3_secret = secrets.values().find(|s| s.secret_type.to_lowercase() == "s3");
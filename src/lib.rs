@@ -10,7 +10,9 @@ pub mod execution;
 pub mod expression;
 pub mod extensions;
 pub mod parser;
+pub mod plan_cache;
 pub mod planner;
+pub mod query_cache;
 pub mod storage;
 pub mod types;
 
@@ -23,11 +25,11 @@ pub use catalog::Catalog;
 // pub mod main; // Commented out to avoid binary/library conflict
 
 // Re-export common types for convenience
-pub use common::{PrismDBError, PrismDBResult};
+pub use common::{Metric, MetricsSnapshot, ObjectKind, PrismDBError, PrismDBResult};
 
 // Re-export type system for convenience
 pub use types::{
-    DataChunk, LogicalType, PhysicalType, SelectionVector, TypeUtils, ValidityMask, Value, Vector,
+    DataChunk, LogicalType, OrderedValue, PhysicalType, SelectionVector, TypeUtils, ValidityMask, Value, Vector,
 };
 
 // Re-export expression system for convenience
@@ -41,11 +43,17 @@ pub use storage::{
     BufferManager, BufferPool, ColumnData, ColumnInfo, ColumnStatistics, IsolationLevel,
     MemoryBuffer, PageBuffer, RowId, TableData, TableInfo, TableStatistics, Transaction,
     TransactionContext, TransactionManager, TransactionMetadata, TransactionOperation,
-    TransactionState, WalManager, WalRecord, WalRecordData, WalRecordType,
+    TransactionState, TransactionStatus, WalManager, WalRecord, WalRecordData, WalRecordType,
 };
 
 // Re-export database for convenience
-pub use crate::database::{Database, DatabaseConfig, QueryResult};
+pub use crate::database::{
+    ColumnProfile, Cursor, Database, DatabaseConfig, NumberFormat, QueryResult, TableFormatOptions,
+};
+pub use crate::expression::TypeCoercion;
+pub use crate::parser::{quote_identifier, IdentifierCase, ParseErrorMode};
+pub use crate::plan_cache::PlanCacheStats;
+pub use crate::query_cache::QueryCacheStats;
 
 // Re-export extensions for convenience
 pub use extensions::{ConfigManager, ExtensionInfo, ExtensionManager, S3Config, Secret, SecretsManager};
@@ -55,7 +63,7 @@ pub use planner::{
     plan_statement, Binder, BoxedDataChunkStream, Column, DataChunkStream, ExecutionOperator,
     LogicalAggregate, LogicalCreateTable, LogicalDelete, LogicalDropTable, LogicalExplain,
     LogicalFilter, LogicalInsert, LogicalJoin, LogicalLimit, LogicalPlan, LogicalProjection,
-    LogicalSort, LogicalTableScan, LogicalUnion, LogicalUpdate, PhysicalAggregate, PhysicalColumn,
+    LogicalSort, LogicalTableScan, LogicalUnion, LogicalUpdate, NullOrder, PhysicalAggregate, PhysicalColumn,
     PhysicalCreateTable, PhysicalDelete, PhysicalDropTable, PhysicalEmptyResult, PhysicalExplain,
     PhysicalFilter, PhysicalHashAggregate, PhysicalHashJoin, PhysicalInsert, PhysicalJoin,
     PhysicalLimit, PhysicalPlan, PhysicalProjection, PhysicalSort, PhysicalSortMergeJoin,
@@ -12,7 +12,8 @@ use rustyline::{Context, Editor, Helper};
 use std::borrow::Cow;
 use std::process;
 
-use prism::Database;
+use prism::common::sync::RwLockExt;
+use prism::{Database, NumberFormat, TableFormatOptions};
 
 /// SQL Syntax Highlighter for interactive mode
 struct SqlHighlighter;
@@ -32,7 +33,8 @@ impl SqlHighlighter {
             "ANALYZE" | "BEGIN" | "COMMIT" | "ROLLBACK" | "TRANSACTION" | "CASE" | "WHEN" |
             "THEN" | "ELSE" | "END" | "CAST" | "TRUE" | "FALSE" | "ASC" | "DESC" | "NULLS" |
             "FIRST" | "LAST" | "OVER" | "PARTITION" | "WINDOW" | "ROWS" | "RANGE" | "UNBOUNDED" |
-            "PRECEDING" | "FOLLOWING" | "CURRENT" | "ROW"
+            "PRECEDING" | "FOLLOWING" | "CURRENT" | "ROW" | "DESCRIBE" | "SHOW" |
+            "SUMMARIZE" | "PRAGMA"
         )
     }
 }
@@ -262,10 +264,16 @@ fn run_interactive_mode(database: &Database) -> Result<(), Box<dyn std::error::E
     Ok(())
 }
 
+/// Cell width the shell truncates table output to until `.maxwidth` changes it
+const DEFAULT_MAX_WIDTH: usize = 40;
+
 struct Settings {
     mode: OutputMode,
     headers: bool,
     timer: bool,
+    max_width: Option<usize>,
+    null_value: String,
+    number_format: Option<NumberFormat>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -274,6 +282,7 @@ enum OutputMode {
     List,
     Csv,
     Line,
+    Json,
 }
 
 impl Default for Settings {
@@ -282,6 +291,20 @@ impl Default for Settings {
             mode: OutputMode::Table,
             headers: true,
             timer: true,
+            max_width: Some(DEFAULT_MAX_WIDTH),
+            null_value: TableFormatOptions::default().null_value,
+            number_format: None,
+        }
+    }
+}
+
+impl Settings {
+    fn table_format(&self) -> TableFormatOptions {
+        TableFormatOptions {
+            max_column_width: self.max_width,
+            null_value: self.null_value.clone(),
+            number_format: self.number_format,
+            ..TableFormatOptions::default()
         }
     }
 }
@@ -342,6 +365,47 @@ fn handle_special_command(
             println!("Timer: {}", if settings.timer { "on" } else { "off" });
             Ok(false)
         }
+        ".maxwidth" => {
+            match parts.get(1) {
+                Some(setting) if setting.eq_ignore_ascii_case("off") => settings.max_width = None,
+                Some(setting) => match setting.parse::<usize>() {
+                    Ok(width) if width > 0 => settings.max_width = Some(width),
+                    _ => eprintln!("Invalid width. Use a positive number or 'off'"),
+                },
+                None => {}
+            }
+            match settings.max_width {
+                Some(width) => println!("Max column width: {}", width),
+                None => println!("Max column width: off"),
+            }
+            Ok(false)
+        }
+        ".nullvalue" => {
+            if let Some(token) = parts.get(1) {
+                settings.null_value = token.to_string();
+            }
+            println!("Null value: {}", settings.null_value);
+            Ok(false)
+        }
+        ".numformat" => {
+            match parts.get(1) {
+                Some(setting) if setting.eq_ignore_ascii_case("off") => settings.number_format = None,
+                Some(setting) if setting.eq_ignore_ascii_case("on") => {
+                    settings.number_format = Some(NumberFormat::default())
+                }
+                Some(setting) => match setting.parse::<usize>() {
+                    Ok(places) => {
+                        settings.number_format = Some(NumberFormat {
+                            decimal_places: Some(places),
+                        })
+                    }
+                    _ => eprintln!("Invalid number format. Use 'on', 'off' or a number of decimal places"),
+                },
+                None => {}
+            }
+            println!("Number format: {}", describe_number_format(settings.number_format));
+            Ok(false)
+        }
         ".show" => {
             show_settings(settings);
             Ok(false)
@@ -360,7 +424,13 @@ fn handle_special_command(
 
 fn show_tables(database: &Database) {
     let catalog = database.catalog();
-    let catalog_lock = catalog.read().unwrap();
+    let catalog_lock = match catalog.read_or_err() {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("Error reading catalog: {}", e);
+            return;
+        }
+    };
 
     match catalog_lock.list_tables("main") {
         Ok(tables) => {
@@ -378,7 +448,13 @@ fn show_tables(database: &Database) {
 
 fn show_schema(database: &Database, table_name: Option<&str>) {
     let catalog = database.catalog();
-    let catalog_lock = catalog.read().unwrap();
+    let catalog_lock = match catalog.read_or_err() {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("Error reading catalog: {}", e);
+            return;
+        }
+    };
 
     let tables = match catalog_lock.list_tables("main") {
         Ok(t) => t,
@@ -395,19 +471,8 @@ fn show_schema(database: &Database, table_name: Option<&str>) {
             }
         }
 
-        match catalog_lock.get_table("main", &table) {
-            Ok(table_ref) => {
-                let table_lock = table_ref.read().unwrap();
-                let table_info = table_lock.get_table_info();
-                println!("CREATE TABLE {} (", table);
-                let columns: Vec<String> = table_info
-                    .columns
-                    .iter()
-                    .map(|col| format!("  {} {}", col.name, format_type(&col.column_type)))
-                    .collect();
-                println!("{}", columns.join(",\n"));
-                println!(");");
-            }
+        match catalog_lock.get_table_ddl("main", &table) {
+            Ok(ddl) => println!("{}", ddl),
             Err(e) => eprintln!("Error getting table '{}': {}", table, e),
         }
     }
@@ -429,8 +494,9 @@ fn set_mode(settings: &mut Settings, mode_str: &str) {
         "list" => OutputMode::List,
         "csv" => OutputMode::Csv,
         "line" => OutputMode::Line,
+        "json" => OutputMode::Json,
         _ => {
-            eprintln!("Invalid mode. Use: table, list, csv, or line");
+            eprintln!("Invalid mode. Use: table, list, csv, line, or json");
             return;
         }
     };
@@ -441,11 +507,33 @@ fn show_settings(settings: &Settings) {
     println!("     mode: {:?}", settings.mode);
     println!("  headers: {}", if settings.headers { "on" } else { "off" });
     println!("    timer: {}", if settings.timer { "on" } else { "off" });
+    match settings.max_width {
+        Some(width) => println!(" maxwidth: {}", width),
+        None => println!(" maxwidth: off"),
+    }
+    println!("nullvalue: {}", settings.null_value);
+    println!("numformat: {}", describe_number_format(settings.number_format));
+}
+
+fn describe_number_format(number_format: Option<NumberFormat>) -> String {
+    match number_format {
+        None => "off".to_string(),
+        Some(NumberFormat { decimal_places: None }) => "on".to_string(),
+        Some(NumberFormat {
+            decimal_places: Some(places),
+        }) => format!("on, {} decimal places", places),
+    }
 }
 
 fn dump_database(database: &Database, table_name: Option<&str>) {
     let catalog = database.catalog();
-    let catalog_lock = catalog.read().unwrap();
+    let catalog_lock = match catalog.read_or_err() {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("Error reading catalog: {}", e);
+            return;
+        }
+    };
 
     let tables = match catalog_lock.list_tables("main") {
         Ok(t) => t,
@@ -467,13 +555,14 @@ fn dump_database(database: &Database, table_name: Option<&str>) {
         println!();
 
         // Show INSERT statements
-        if let Ok(result) = database.query(&format!("SELECT * FROM {}", table)) {
+        let quoted = prism::quote_identifier(&table);
+        if let Ok(result) = database.query(&format!("SELECT * FROM {}", quoted)) {
             if let Ok(collected) = result.collect() {
                 for row in &collected.rows {
                     let values: Vec<String> = row.iter()
-                        .map(|v| format_value_sql(v))
+                        .map(prism::Value::to_sql_literal)
                         .collect();
-                    println!("INSERT INTO {} VALUES ({});", table, values.join(", "));
+                    println!("INSERT INTO {} VALUES ({});", quoted, values.join(", "));
                 }
             }
         }
@@ -481,37 +570,6 @@ fn dump_database(database: &Database, table_name: Option<&str>) {
     }
 }
 
-fn format_type(ty: &prism::LogicalType) -> &str {
-    use prism::LogicalType;
-    match ty {
-        LogicalType::Boolean => "BOOLEAN",
-        LogicalType::TinyInt => "TINYINT",
-        LogicalType::SmallInt => "SMALLINT",
-        LogicalType::Integer => "INTEGER",
-        LogicalType::BigInt => "BIGINT",
-        LogicalType::Float => "FLOAT",
-        LogicalType::Double => "DOUBLE",
-        LogicalType::Varchar => "VARCHAR",
-        LogicalType::Date => "DATE",
-        LogicalType::Timestamp => "TIMESTAMP",
-        _ => "UNKNOWN",
-    }
-}
-
-fn format_value_sql(value: &prism::Value) -> String {
-    use prism::Value;
-    match value {
-        Value::Null => "NULL".to_string(),
-        Value::Varchar(s) | Value::Char(s) => format!("'{}'", s.replace("'", "''")),
-        Value::Integer(i) => i.to_string(),
-        Value::BigInt(i) => i.to_string(),
-        Value::Boolean(b) => b.to_string(),
-        Value::Float(f) => f.to_string(),
-        Value::Double(d) => d.to_string(),
-        _ => format!("{:?}", value),
-    }
-}
-
 fn print_help() {
     println!(r#"
 .help                    Show this help message
@@ -519,9 +577,12 @@ fn print_help() {
 .exit                    Exit this program
 .tables                  List all tables
 .schema ?TABLE?          Show the CREATE statements (all tables or specific table)
-.mode MODE               Set output mode (table, list, csv, line)
+.mode MODE               Set output mode (table, list, csv, line, json)
 .headers on|off          Turn display of headers on or off
 .timer on|off            Turn SQL timer on or off (default: on)
+.maxwidth N|off          Truncate table cells longer than N characters (default: 40)
+.nullvalue TEXT          Text used to display NULL values (default: NULL)
+.numformat on|off|N      Group digits of numbers in table mode, with N decimal places (default: off)
 .databases               List database file path
 .open FILE               Close current database and open FILE
 .show                    Show current settings
@@ -533,6 +594,7 @@ Output modes:
   list      - Values delimited by "|"
   csv       - Comma-separated values
   line      - One value per line
+  json      - JSON array with one object per row
 
 SQL Statements:
   Type SQL statements terminated with a semicolon (;)
@@ -545,6 +607,21 @@ Keyboard Shortcuts:
 "#);
 }
 
+/// Print rows as a JSON array, one row object per line
+fn print_json_rows(result: &prism::QueryResult) -> Result<(), Box<dyn std::error::Error>> {
+    let rows = match result.to_json()? {
+        serde_json::Value::Array(rows) => rows,
+        other => vec![other],
+    };
+    println!("[");
+    for (i, row) in rows.iter().enumerate() {
+        let separator = if i + 1 < rows.len() { "," } else { "" };
+        println!("  {}{}", row, separator);
+    }
+    println!("]");
+    Ok(())
+}
+
 fn execute_sql(database: &Database, sql: &str, settings: &Settings) -> Result<(), Box<dyn std::error::Error>> {
     let start_time = if settings.timer {
         Some(std::time::Instant::now())
@@ -580,18 +657,27 @@ fn execute_sql(database: &Database, sql: &str, settings: &Settings) -> Result<()
 
             // Only display the table for non-DML results
             if !is_dml_result && result.row_count() > 0 {
-                println!("{}", result.to_table_string());
-                println!();
+                if let OutputMode::Json = settings.mode {
+                    print_json_rows(&result)?;
+                } else {
+                    println!("{}", result.to_table_string_with(&settings.table_format()));
+                    println!();
+                }
             }
 
             if settings.timer {
                 if let Some(start) = start_time {
                     let elapsed = start.elapsed();
+                    let memory = match result.stats().memory_used_bytes {
+                        0 => String::new(),
+                        bytes => format!(", peak memory {}", prism::common::allocator::format_memory_size(bytes)),
+                    };
                     println!(
-                        "Query executed successfully ({} row{} in {:.3}s)",
+                        "Query executed successfully ({} row{} in {:.3}s{})",
                         affected_rows,
                         if affected_rows == 1 { "" } else { "s" },
-                        elapsed.as_secs_f64()
+                        elapsed.as_secs_f64(),
+                        memory
                     );
                 }
             } else {
@@ -645,12 +731,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     if let Some(query) = cli.query {
-        // Execute query
-        match database.execute_sql_collect(&query) {
-            Ok(result) => {
+        // Execute each statement in order, printing the rows of any that return data
+        match database.execute_batch(&query) {
+            Ok(results) => {
                 println!("Query executed successfully");
-                println!("Rows: {}", result.row_count());
-                if result.row_count() > 0 {
+                println!("Rows: {}", results.last().map_or(0, |r| r.row_count()));
+                for result in results.iter().filter(|r| !r.columns.is_empty() && r.row_count() > 0) {
                     println!("{}", result.to_table_string());
                 }
             }
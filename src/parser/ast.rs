@@ -4,6 +4,7 @@
 
 use crate::types::LogicalType;
 use std::collections::HashMap;
+use std::fmt;
 
 /// SQL statement types
 #[derive(Debug, Clone, PartialEq)]
@@ -12,6 +13,7 @@ pub enum Statement {
     Insert(InsertStatement),
     Update(UpdateStatement),
     Delete(DeleteStatement),
+    Merge(MergeStatement),
     CreateTable(CreateTableStatement),
     DropTable(DropTableStatement),
     AlterTable(AlterTableStatement),
@@ -20,15 +22,46 @@ pub enum Statement {
     RefreshMaterializedView(RefreshMaterializedViewStatement),
     CreateIndex(CreateIndexStatement),
     DropIndex(DropIndexStatement),
+    CreateType(CreateTypeStatement),
+    CreateFunction(CreateFunctionStatement),
+    DropFunction(DropFunctionStatement),
+    CreateSchema(CreateSchemaStatement),
+    DropSchema(DropSchemaStatement),
     Begin(BeginStatement),
     Commit(CommitStatement),
     Rollback(RollbackStatement),
     Explain(ExplainStatement),
+    Describe(DescribeStatement),
+    Summarize(SummarizeStatement),
     Show(ShowStatement),
     Install(InstallStatement),
     Load(LoadStatement),
     Set(SetStatement),
+    Pragma(PragmaStatement),
     CreateSecret(CreateSecretStatement),
+    Copy(CopyStatement),
+    CopyFrom(CopyFromStatement),
+    Vacuum(VacuumStatement),
+    Analyze(AnalyzeStatement),
+}
+
+/// A statement along with the comments written before it, such as the
+/// `-- name: get_users` annotations of a query-management layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedStatement {
+    pub statement: Statement,
+    /// Text of each leading comment without its `--` or `/* */` markers
+    pub comments: Vec<String>,
+}
+
+impl AnnotatedStatement {
+    /// The value of the first `key: value` annotation among the comments
+    pub fn annotation(&self, key: &str) -> Option<&str> {
+        self.comments.iter().find_map(|comment| {
+            let (name, value) = comment.split_once(':')?;
+            (name.trim() == key).then(|| value.trim())
+        })
+    }
 }
 
 /// SELECT statement
@@ -40,9 +73,11 @@ pub struct SelectStatement {
     pub from: Option<TableReference>,
     pub where_clause: Option<Box<Expression>>,
     pub group_by: Vec<Expression>,
+    pub group_by_all: bool,  // GROUP BY ALL, expanded by the binder
     pub having: Option<Box<Expression>>,
     pub qualify: Option<Box<Expression>>, // QUALIFY clause for filtering window functions
     pub order_by: Vec<OrderByExpression>,
+    pub order_by_all: Option<OrderByAll>,  // ORDER BY ALL, expanded by the binder
     pub limit: Option<LimitClause>,
     pub offset: Option<usize>,
     pub set_operations: Vec<SetOperation>,  // UNION, INTERSECT, EXCEPT
@@ -83,11 +118,20 @@ pub enum SetOperationType {
 #[derive(Debug, Clone, PartialEq)]
 pub enum SelectItem {
     Expression(Expression),
-    QualifiedWildcard(String), // table.*
-    Wildcard,                  // *
+    QualifiedWildcard(String, WildcardOptions), // table.*
+    Wildcard(WildcardOptions),                  // *
     Alias(Box<Expression>, String),
 }
 
+/// `EXCLUDE` and `REPLACE` modifiers of a `*` or `table.*` select item
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WildcardOptions {
+    /// Columns left out of the expansion
+    pub exclude: Vec<String>,
+    /// Expressions taking the place of the named columns
+    pub replace: Vec<(Expression, String)>,
+}
+
 /// Table reference
 #[derive(Debug, Clone, PartialEq)]
 pub enum TableReference {
@@ -105,9 +149,17 @@ pub enum TableReference {
         subquery: Box<SelectStatement>,
         alias: String,
     },
+    /// Inline rows: `(VALUES (1, 'a'), (2, 'b')) AS t(x, y)`
+    Values {
+        rows: Vec<Vec<Expression>>,
+        alias: Option<String>,
+        column_aliases: Vec<String>,
+    },
     TableFunction {
         name: String,
         arguments: Vec<Expression>,
+        /// `name => value` or `name = value` arguments, names lowercased, in call order
+        named_arguments: Vec<(String, Expression)>,
         alias: Option<String>,
     },
     Pivot {
@@ -130,6 +182,10 @@ pub enum JoinType {
     Right,
     Full,
     Cross,
+    /// `ASOF JOIN`: each left row with the nearest right row its inequality allows
+    AsOf,
+    /// `ASOF LEFT JOIN`: like `AsOf`, keeping left rows without a match
+    AsOfLeft,
 }
 
 /// Join condition
@@ -137,6 +193,8 @@ pub enum JoinType {
 pub enum JoinCondition {
     On(Expression),
     Using(Vec<String>),
+    /// `NATURAL JOIN`, on every column name both sides have
+    Natural,
 }
 
 /// ORDER BY expression
@@ -144,7 +202,16 @@ pub enum JoinCondition {
 pub struct OrderByExpression {
     pub expression: Expression,
     pub ascending: bool,
-    pub nulls_first: bool,
+    /// Explicit NULLS FIRST/LAST; `None` uses the configured default null order
+    pub nulls_first: Option<bool>,
+}
+
+/// `ORDER BY ALL`: every output column, left to right, in one direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderByAll {
+    pub ascending: bool,
+    /// Explicit NULLS FIRST/LAST; `None` uses the configured default null order
+    pub nulls_first: Option<bool>,
 }
 
 /// LIMIT clause
@@ -196,11 +263,58 @@ pub struct DeleteStatement {
     pub where_clause: Option<Expression>,
 }
 
+/// MERGE statement
+///
+/// `MERGE INTO target USING source ON condition WHEN [NOT] MATCHED [AND condition]
+/// THEN action ...`: each source row either matches target rows on the condition
+/// or matches none, and the first WHEN clause of that kind whose condition holds
+/// picks the action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeStatement {
+    pub table_name: String,
+    pub alias: Option<String>,
+    pub source: TableReference,
+    pub condition: Expression,
+    pub clauses: Vec<MergeClause>,
+}
+
+/// WHEN [NOT] MATCHED [AND condition] THEN action
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeClause {
+    pub matched: bool,
+    pub condition: Option<Expression>,
+    pub action: MergeAction,
+}
+
+/// What a MERGE clause does with the row
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeAction {
+    /// UPDATE SET column = value, ... (WHEN MATCHED only)
+    Update(Vec<Assignment>),
+    /// DELETE (WHEN MATCHED only)
+    Delete,
+    /// INSERT [(columns)] VALUES (values) (WHEN NOT MATCHED only)
+    Insert {
+        columns: Vec<String>,
+        values: Vec<Expression>,
+    },
+    /// DO NOTHING
+    DoNothing,
+}
+
 /// Assignment (SET column = value)
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assignment {
     pub column: String,
-    pub value: Expression,
+    pub value: AssignmentValue,
+}
+
+/// Right-hand side of an assignment
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentValue {
+    Expression(Expression),
+    /// DEFAULT: the column's declared default, or NULL without one
+    Default,
 }
 
 /// CREATE TABLE statement
@@ -220,13 +334,18 @@ pub struct ColumnDefinition {
     pub nullable: bool,
     pub default_value: Option<Expression>,
     pub constraints: Vec<ColumnConstraint>,
+    /// Collation from a column-level `COLLATE`, used where the column is compared or sorted
+    pub collation: Option<String>,
+    /// Length of a `VARCHAR(n)` column; `CHAR(n)` keeps its length in the type
+    pub max_length: Option<usize>,
 }
 
 /// Column constraint
 #[derive(Debug, Clone, PartialEq)]
 pub enum ColumnConstraint {
     PrimaryKey,
-    Unique,
+    /// UNIQUE; with NULLS NOT DISTINCT, NULLs count as equal so at most one row has NULL
+    Unique { nulls_not_distinct: bool },
     NotNull,
     Check(Expression),
     Default(Expression),
@@ -243,6 +362,7 @@ pub enum TableConstraint {
     Unique {
         columns: Vec<String>,
         name: Option<String>,
+        nulls_not_distinct: bool,
     },
     ForeignKey {
         columns: Vec<String>,
@@ -334,6 +454,49 @@ pub struct DropIndexStatement {
     pub if_exists: bool,
 }
 
+/// CREATE TYPE statement: `CREATE TYPE name AS ENUM ('label', ...)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateTypeStatement {
+    pub type_name: String,
+    /// Enum labels in definition order
+    pub values: Vec<String>,
+}
+
+/// CREATE FUNCTION statement:
+/// `CREATE [OR REPLACE] FUNCTION name(param type, ...) [RETURNS type] AS (expression)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateFunctionStatement {
+    pub function_name: String,
+    /// Parameter names and types, in call order
+    pub parameters: Vec<(String, LogicalType)>,
+    pub return_type: Option<LogicalType>,
+    pub body: Expression,
+    pub or_replace: bool,
+}
+
+/// DROP FUNCTION statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropFunctionStatement {
+    pub function_name: String,
+    pub if_exists: bool,
+}
+
+/// CREATE SCHEMA [IF NOT EXISTS] name
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreateSchemaStatement {
+    pub schema_name: String,
+    pub if_not_exists: bool,
+}
+
+/// DROP SCHEMA [IF EXISTS] name [CASCADE | RESTRICT]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropSchemaStatement {
+    pub schema_name: String,
+    pub if_exists: bool,
+    /// Drop the objects in the schema too; without it a non-empty schema is kept
+    pub cascade: bool,
+}
+
 /// BEGIN statement
 #[derive(Debug, Clone, PartialEq)]
 pub struct BeginStatement {
@@ -369,6 +532,33 @@ pub struct ExplainStatement {
     pub statement: Box<Statement>,
     pub analyze: bool,
     pub verbose: bool,
+    pub format: ExplainFormat,
+}
+
+/// Output format of EXPLAIN, chosen with `EXPLAIN (FORMAT ...)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExplainFormat {
+    /// Indented operator tree
+    #[default]
+    Text,
+    /// Operator tree as a JSON document
+    Json,
+}
+
+/// DESCRIBE / DESC statement
+#[derive(Debug, Clone, PartialEq)]
+pub enum DescribeStatement {
+    /// DESCRIBE table: the table's columns from the catalog
+    Table { table_name: String },
+    /// DESCRIBE SELECT ...: the query's result columns, without running it
+    Query(Box<SelectStatement>),
+}
+
+/// SUMMARIZE statement: per-column statistics of a table or query result
+#[derive(Debug, Clone, PartialEq)]
+pub struct SummarizeStatement {
+    /// Rows to profile; `SUMMARIZE table` is read as `SELECT * FROM table`
+    pub query: Box<SelectStatement>,
 }
 
 /// SHOW statement
@@ -378,8 +568,13 @@ pub enum ShowStatement {
     Columns { table: String },
     Indexes { table: Option<String> },
     Variables,
+    /// SHOW name: the current value of one setting
+    Variable { name: String },
     Databases,
     Schemas,
+    CreateTable { table: String },
+    /// SHOW FUNCTIONS: built-in functions and those made with CREATE FUNCTION
+    Functions,
 }
 
 /// INSTALL statement (for installing extensions)
@@ -410,6 +605,28 @@ pub enum SetValue {
     Default,
 }
 
+/// PRAGMA statement: `PRAGMA name`, `PRAGMA name(value)` or `PRAGMA name = value`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PragmaStatement {
+    pub name: String,
+    /// The argument or assigned value, if any
+    pub value: Option<SetValue>,
+}
+
+/// VACUUM statement: reclaim the space held by deleted rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct VacuumStatement {
+    /// Table to vacuum; every table when None
+    pub table_name: Option<String>,
+}
+
+/// ANALYZE statement: gather the column statistics the planner estimates with
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzeStatement {
+    /// Table to analyze; every table when None
+    pub table_name: Option<String>,
+}
+
 /// CREATE SECRET statement
 #[derive(Debug, Clone, PartialEq)]
 pub struct CreateSecretStatement {
@@ -419,6 +636,28 @@ pub struct CreateSecretStatement {
     pub options: HashMap<String, String>,
 }
 
+/// COPY ... TO statement: write a table or query result to files
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyStatement {
+    /// Rows to write; `COPY table TO ...` is read as `SELECT * FROM table`
+    pub query: Box<SelectStatement>,
+    /// File, or directory for partitioned output, to write to
+    pub target: String,
+    /// Options in the trailing parentheses, keyed by lowercase name; a list
+    /// value such as `PARTITION_BY (year, month)` holds several entries
+    pub options: HashMap<String, Vec<String>>,
+}
+
+/// COPY ... FROM statement: append the rows of a file to a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyFromStatement {
+    pub table_name: String,
+    /// File to read
+    pub source: String,
+    /// Options in the trailing parentheses, keyed by lowercase name
+    pub options: HashMap<String, Vec<String>>,
+}
+
 /// Expression AST
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
@@ -438,6 +677,12 @@ pub enum Expression {
         name: String,
         arguments: Vec<Expression>,
         distinct: bool,
+        /// `FILTER (WHERE ...)`: only rows passing it are aggregated
+        filter: Option<Box<Expression>>,
+        /// Order the aggregated values are consumed in
+        order_by: Vec<OrderByExpression>,
+        /// `order_by` was given as `WITHIN GROUP (ORDER BY ...)` rather than inside the call
+        within_group: bool,
     },
     WindowFunction {
         name: String,
@@ -447,6 +692,13 @@ pub enum Expression {
     Cast {
         expression: Box<Expression>,
         data_type: LogicalType,
+        /// `TRY_CAST`: values that can't be converted become NULL instead of failing
+        try_cast: bool,
+    },
+    /// `expression COLLATE name`: compare and order strings by the named collation
+    Collate {
+        expression: Box<Expression>,
+        collation: String,
     },
     Case {
         operand: Option<Box<Expression>>,
@@ -516,6 +768,7 @@ pub enum LiteralValue {
     Integer(i64),
     Float(f64),
     String(String),
+    Blob(Vec<u8>),
     Date(String),
     Time(String),
     Timestamp(String),
@@ -530,6 +783,7 @@ pub enum BinaryOperator {
     Subtract,
     Multiply,
     Divide,
+    IntegerDivide,
     Modulo,
 
     // Comparison
@@ -582,6 +836,7 @@ pub struct WindowFrame {
     pub units: WindowFrameUnits,
     pub start_bound: WindowFrameBound,
     pub end_bound: Option<WindowFrameBound>,
+    pub exclude: WindowFrameExclude,
 }
 
 /// Window frame units
@@ -602,6 +857,20 @@ pub enum WindowFrameBound {
     Following(usize),
 }
 
+/// Rows an EXCLUDE clause leaves out of each row's window frame
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum WindowFrameExclude {
+    /// EXCLUDE NO OTHERS, the default
+    #[default]
+    NoOthers,
+    /// EXCLUDE CURRENT ROW
+    CurrentRow,
+    /// EXCLUDE GROUP: the current row and its peers
+    Group,
+    /// EXCLUDE TIES: the current row's peers but not the row itself
+    Ties,
+}
+
 /// PIVOT specification
 /// Supports both simplified syntax (PIVOT dataset ON columns USING values)
 /// and SQL Standard syntax (FROM dataset PIVOT (values FOR columns IN (in_list)))
@@ -674,6 +943,201 @@ impl QueryParameters {
 }
 
 impl Expression {
+    /// Collation named by a `COLLATE` on this expression, if any
+    pub fn collation(&self) -> Option<&str> {
+        match self {
+            Expression::Collate { collation, .. } => Some(collation),
+            _ => None,
+        }
+    }
+
+    /// Rebuild the expression bottom-up, passing every rebuilt node through `f`
+    ///
+    /// Subquery bodies are left alone; they are rewritten when they are bound.
+    pub fn try_transform<E>(
+        &self,
+        f: &mut impl FnMut(Expression) -> Result<Expression, E>,
+    ) -> Result<Expression, E> {
+        fn each<E>(
+            exprs: &[Expression],
+            f: &mut impl FnMut(Expression) -> Result<Expression, E>,
+        ) -> Result<Vec<Expression>, E> {
+            exprs.iter().map(|expr| expr.try_transform(f)).collect()
+        }
+        fn boxed<E>(
+            expr: &Expression,
+            f: &mut impl FnMut(Expression) -> Result<Expression, E>,
+        ) -> Result<Box<Expression>, E> {
+            Ok(Box::new(expr.try_transform(f)?))
+        }
+        fn order_by<E>(
+            items: &[OrderByExpression],
+            f: &mut impl FnMut(Expression) -> Result<Expression, E>,
+        ) -> Result<Vec<OrderByExpression>, E> {
+            items
+                .iter()
+                .map(|item| {
+                    Ok(OrderByExpression {
+                        expression: item.expression.try_transform(f)?,
+                        ..item.clone()
+                    })
+                })
+                .collect()
+        }
+
+        let rebuilt = match self {
+            Expression::FunctionCall {
+                name,
+                arguments,
+                distinct,
+            } => Expression::FunctionCall {
+                name: name.clone(),
+                arguments: each(arguments, f)?,
+                distinct: *distinct,
+            },
+            Expression::AggregateFunction {
+                name,
+                arguments,
+                distinct,
+                filter,
+                order_by: items,
+                within_group,
+            } => Expression::AggregateFunction {
+                name: name.clone(),
+                arguments: each(arguments, f)?,
+                distinct: *distinct,
+                filter: filter.as_deref().map(|e| boxed(e, f)).transpose()?,
+                order_by: order_by(items, f)?,
+                within_group: *within_group,
+            },
+            Expression::WindowFunction {
+                name,
+                arguments,
+                window_spec,
+            } => Expression::WindowFunction {
+                name: name.clone(),
+                arguments: each(arguments, f)?,
+                window_spec: WindowSpec {
+                    partition_by: each(&window_spec.partition_by, f)?,
+                    order_by: order_by(&window_spec.order_by, f)?,
+                    window_frame: window_spec.window_frame.clone(),
+                },
+            },
+            Expression::Cast {
+                expression,
+                data_type,
+                try_cast,
+            } => Expression::Cast {
+                expression: boxed(expression, f)?,
+                data_type: data_type.clone(),
+                try_cast: *try_cast,
+            },
+            Expression::Collate {
+                expression,
+                collation,
+            } => Expression::Collate {
+                expression: boxed(expression, f)?,
+                collation: collation.clone(),
+            },
+            Expression::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => Expression::Case {
+                operand: operand.as_deref().map(|e| boxed(e, f)).transpose()?,
+                conditions: each(conditions, f)?,
+                results: each(results, f)?,
+                else_result: else_result.as_deref().map(|e| boxed(e, f)).transpose()?,
+            },
+            Expression::Between {
+                expression,
+                low,
+                high,
+                not,
+            } => Expression::Between {
+                expression: boxed(expression, f)?,
+                low: boxed(low, f)?,
+                high: boxed(high, f)?,
+                not: *not,
+            },
+            Expression::BetweenSymmetric {
+                expression,
+                low,
+                high,
+                not,
+            } => Expression::BetweenSymmetric {
+                expression: boxed(expression, f)?,
+                low: boxed(low, f)?,
+                high: boxed(high, f)?,
+                not: *not,
+            },
+            Expression::InList {
+                expression,
+                list,
+                not,
+            } => Expression::InList {
+                expression: boxed(expression, f)?,
+                list: each(list, f)?,
+                not: *not,
+            },
+            Expression::InSubquery {
+                expression,
+                subquery,
+                not,
+            } => Expression::InSubquery {
+                expression: boxed(expression, f)?,
+                subquery: subquery.clone(),
+                not: *not,
+            },
+            Expression::IsNull(e) => Expression::IsNull(boxed(e, f)?),
+            Expression::IsNotNull(e) => Expression::IsNotNull(boxed(e, f)?),
+            Expression::IsTrue(e) => Expression::IsTrue(boxed(e, f)?),
+            Expression::IsFalse(e) => Expression::IsFalse(boxed(e, f)?),
+            Expression::IsUnknown(e) => Expression::IsUnknown(boxed(e, f)?),
+            Expression::IsNotTrue(e) => Expression::IsNotTrue(boxed(e, f)?),
+            Expression::IsNotFalse(e) => Expression::IsNotFalse(boxed(e, f)?),
+            Expression::IsNotUnknown(e) => Expression::IsNotUnknown(boxed(e, f)?),
+            Expression::Like {
+                expression,
+                pattern,
+                escape,
+                case_insensitive,
+                not,
+            } => Expression::Like {
+                expression: boxed(expression, f)?,
+                pattern: boxed(pattern, f)?,
+                escape: escape.as_deref().map(|e| boxed(e, f)).transpose()?,
+                case_insensitive: *case_insensitive,
+                not: *not,
+            },
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => Expression::Binary {
+                left: boxed(left, f)?,
+                operator: operator.clone(),
+                right: boxed(right, f)?,
+            },
+            Expression::Unary {
+                operator,
+                expression,
+            } => Expression::Unary {
+                operator: operator.clone(),
+                expression: boxed(expression, f)?,
+            },
+            Expression::Literal(_)
+            | Expression::ColumnReference { .. }
+            | Expression::Parameter(_)
+            | Expression::Exists(_)
+            | Expression::Subquery(_)
+            | Expression::QualifiedWildcard { .. }
+            | Expression::Wildcard => self.clone(),
+        };
+        f(rebuilt)
+    }
+
     /// Evaluate the expression on a data chunk
     /// This is a stub implementation - full expression evaluation should be
     /// delegated to the expression module
@@ -699,3 +1163,333 @@ impl Expression {
         })
     }
 }
+
+/// Write `items` separated by `", "`
+fn write_list<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// Write an operand, parenthesized when it is itself a binary expression so
+/// the rendered SQL keeps the original grouping
+fn write_operand(f: &mut fmt::Formatter<'_>, expr: &Expression) -> fmt::Result {
+    match expr {
+        Expression::Binary { .. } => write!(f, "({})", expr),
+        _ => write!(f, "{}", expr),
+    }
+}
+
+fn write_postfix(f: &mut fmt::Formatter<'_>, expr: &Expression, suffix: &str) -> fmt::Result {
+    write_operand(f, expr)?;
+    write!(f, " {}", suffix)
+}
+
+/// Renders the expression back to SQL text (used for catalog DDL such as
+/// CHECK constraints and column defaults)
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Literal(literal) => write!(f, "{}", literal),
+            Expression::ColumnReference { table, column } => match table {
+                Some(table) => write!(f, "{}.{}", table, column),
+                None => write!(f, "{}", column),
+            },
+            Expression::Parameter(_) => write!(f, "?"),
+            // List literals parse to LIST_VALUE calls
+            Expression::FunctionCall {
+                name, arguments, ..
+            } if name == "LIST_VALUE" => {
+                write!(f, "[")?;
+                write_list(f, arguments)?;
+                write!(f, "]")
+            }
+            Expression::FunctionCall {
+                name,
+                arguments,
+                distinct,
+            } => {
+                write!(f, "{}(", name)?;
+                if *distinct {
+                    write!(f, "DISTINCT ")?;
+                }
+                write_list(f, arguments)?;
+                write!(f, ")")
+            }
+            Expression::AggregateFunction {
+                name,
+                arguments,
+                distinct,
+                filter,
+                order_by,
+                within_group,
+            } => {
+                write!(f, "{}(", name)?;
+                if *distinct {
+                    write!(f, "DISTINCT ")?;
+                }
+                write_list(f, arguments)?;
+                if !order_by.is_empty() && !within_group {
+                    write!(f, " ORDER BY ")?;
+                    write_list(f, order_by)?;
+                }
+                write!(f, ")")?;
+                if !order_by.is_empty() && *within_group {
+                    write!(f, " WITHIN GROUP (ORDER BY ")?;
+                    write_list(f, order_by)?;
+                    write!(f, ")")?;
+                }
+                if let Some(filter) = filter {
+                    write!(f, " FILTER (WHERE {})", filter)?;
+                }
+                Ok(())
+            }
+            Expression::WindowFunction {
+                name,
+                arguments,
+                window_spec,
+            } => {
+                write!(f, "{}(", name)?;
+                write_list(f, arguments)?;
+                write!(f, ") OVER (")?;
+                if !window_spec.partition_by.is_empty() {
+                    write!(f, "PARTITION BY ")?;
+                    write_list(f, &window_spec.partition_by)?;
+                    if !window_spec.order_by.is_empty() {
+                        write!(f, " ")?;
+                    }
+                }
+                if !window_spec.order_by.is_empty() {
+                    write!(f, "ORDER BY ")?;
+                    write_list(f, &window_spec.order_by)?;
+                }
+                write!(f, ")")
+            }
+            Expression::Cast {
+                expression,
+                data_type,
+                try_cast,
+            } => {
+                let name = if *try_cast { "TRY_CAST" } else { "CAST" };
+                write!(f, "{}({} AS {})", name, expression, data_type)
+            }
+            Expression::Collate {
+                expression,
+                collation,
+            } => write!(f, "{} COLLATE '{}'", expression, collation.replace('\'', "''")),
+            Expression::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (condition, result) in conditions.iter().zip(results) {
+                    write!(f, " WHEN {} THEN {}", condition, result)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " ELSE {}", else_result)?;
+                }
+                write!(f, " END")
+            }
+            Expression::Between {
+                expression,
+                low,
+                high,
+                not,
+            }
+            | Expression::BetweenSymmetric {
+                expression,
+                low,
+                high,
+                not,
+            } => {
+                write_operand(f, expression)?;
+                write!(f, " {}BETWEEN ", if *not { "NOT " } else { "" })?;
+                if matches!(self, Expression::BetweenSymmetric { .. }) {
+                    write!(f, "SYMMETRIC ")?;
+                }
+                write_operand(f, low)?;
+                write!(f, " AND ")?;
+                write_operand(f, high)
+            }
+            Expression::InList {
+                expression,
+                list,
+                not,
+            } => {
+                write_operand(f, expression)?;
+                write!(f, " {}IN (", if *not { "NOT " } else { "" })?;
+                write_list(f, list)?;
+                write!(f, ")")
+            }
+            // Subqueries are not rendered back to SQL; the parser keeps them
+            // out of DEFAULT and CHECK, the expressions stored as text
+            Expression::InSubquery {
+                expression, not, ..
+            } => {
+                write_operand(f, expression)?;
+                write!(f, " {}IN (<subquery>)", if *not { "NOT " } else { "" })
+            }
+            Expression::Exists(_) => write!(f, "EXISTS (<subquery>)"),
+            Expression::Subquery(_) => write!(f, "(<subquery>)"),
+            Expression::IsNull(expr) => write_postfix(f, expr, "IS NULL"),
+            Expression::IsNotNull(expr) => write_postfix(f, expr, "IS NOT NULL"),
+            Expression::IsTrue(expr) => write_postfix(f, expr, "IS TRUE"),
+            Expression::IsFalse(expr) => write_postfix(f, expr, "IS FALSE"),
+            Expression::IsUnknown(expr) => write_postfix(f, expr, "IS UNKNOWN"),
+            Expression::IsNotTrue(expr) => write_postfix(f, expr, "IS NOT TRUE"),
+            Expression::IsNotFalse(expr) => write_postfix(f, expr, "IS NOT FALSE"),
+            Expression::IsNotUnknown(expr) => write_postfix(f, expr, "IS NOT UNKNOWN"),
+            Expression::Like {
+                expression,
+                pattern,
+                escape,
+                case_insensitive,
+                not,
+            } => {
+                write_operand(f, expression)?;
+                write!(
+                    f,
+                    " {}{} ",
+                    if *not { "NOT " } else { "" },
+                    if *case_insensitive { "ILIKE" } else { "LIKE" }
+                )?;
+                write_operand(f, pattern)?;
+                if let Some(escape) = escape {
+                    write!(f, " ESCAPE {}", escape)?;
+                }
+                Ok(())
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                // Parenthesize children that bind more loosely than this
+                // operator; operators are left-associative
+                let precedence = operator.precedence();
+                match left.as_ref() {
+                    Expression::Binary { operator: inner, .. } if inner.precedence() < precedence => {
+                        write!(f, "({})", left)?
+                    }
+                    _ => write!(f, "{}", left)?,
+                }
+                write!(f, " {} ", operator)?;
+                match right.as_ref() {
+                    Expression::Binary { operator: inner, .. } if inner.precedence() <= precedence => {
+                        write!(f, "({})", right)
+                    }
+                    _ => write!(f, "{}", right),
+                }
+            }
+            Expression::Unary {
+                operator,
+                expression,
+            } => match operator {
+                UnaryOperator::Plus => {
+                    write!(f, "+")?;
+                    write_operand(f, expression)
+                }
+                UnaryOperator::Minus => {
+                    write!(f, "-")?;
+                    write_operand(f, expression)
+                }
+                UnaryOperator::Not => {
+                    write!(f, "NOT ")?;
+                    write_operand(f, expression)
+                }
+                UnaryOperator::IsNull => write_postfix(f, expression, "IS NULL"),
+                UnaryOperator::IsNotNull => write_postfix(f, expression, "IS NOT NULL"),
+            },
+            Expression::QualifiedWildcard { table } => write!(f, "{}.*", table),
+            Expression::Wildcard => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for OrderByExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expression)?;
+        if !self.ascending {
+            write!(f, " DESC")?;
+        }
+        match self.nulls_first {
+            Some(true) => write!(f, " NULLS FIRST"),
+            Some(false) => write!(f, " NULLS LAST"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteralValue::Null => write!(f, "NULL"),
+            LiteralValue::Boolean(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            LiteralValue::Integer(i) => write!(f, "{}", i),
+            // Debug formatting keeps the decimal point on whole numbers (1.0)
+            LiteralValue::Float(x) => write!(f, "{:?}", x),
+            LiteralValue::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            LiteralValue::Blob(bytes) => write!(f, "x'{}'", hex::encode_upper(bytes)),
+            LiteralValue::Date(s) => write!(f, "DATE '{}'", s),
+            LiteralValue::Time(s) => write!(f, "TIME '{}'", s),
+            LiteralValue::Timestamp(s) => write!(f, "TIMESTAMP '{}'", s),
+            LiteralValue::Interval { value, field } => write!(f, "INTERVAL '{}' {}", value, field),
+        }
+    }
+}
+
+impl BinaryOperator {
+    /// Binding strength used when rendering expressions back to SQL
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Add | BinaryOperator::Subtract => 4,
+            BinaryOperator::Multiply
+            | BinaryOperator::Divide
+            | BinaryOperator::IntegerDivide
+            | BinaryOperator::Modulo => 5,
+            _ => 3,
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::IntegerDivide => "//",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Equals => "=",
+            BinaryOperator::NotEquals => "<>",
+            BinaryOperator::LessThan => "<",
+            BinaryOperator::LessThanOrEqual => "<=",
+            BinaryOperator::GreaterThan => ">",
+            BinaryOperator::GreaterThanOrEqual => ">=",
+            BinaryOperator::And => "AND",
+            BinaryOperator::Or => "OR",
+            BinaryOperator::Like => "LIKE",
+            BinaryOperator::ILike => "ILIKE",
+            BinaryOperator::SimilarTo => "SIMILAR TO",
+            BinaryOperator::Is => "IS",
+            BinaryOperator::IsNot => "IS NOT",
+            BinaryOperator::In => "IN",
+            BinaryOperator::NotIn => "NOT IN",
+            BinaryOperator::Between => "BETWEEN",
+            BinaryOperator::NotBetween => "NOT BETWEEN",
+        };
+        write!(f, "{}", symbol)
+    }
+}
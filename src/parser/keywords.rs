@@ -27,6 +27,7 @@ pub enum Keyword {
     Full,
     Outer,
     Cross,
+    Natural,
     On,
     Using,
     Lateral,
@@ -49,6 +50,8 @@ pub enum Keyword {
     Schema,
     Database,
     Drop,
+    Cascade,
+    Restrict,
     Alter,
     Add,
     Column,
@@ -135,6 +138,9 @@ pub enum Keyword {
     Any,
     Some,
 
+    // Arithmetic operators
+    Div,
+
     // Aggregate functions
     Count,
     Sum,
@@ -195,6 +201,7 @@ pub enum Keyword {
     // Table operations
     Show,
     Describe,
+    Summarize,
     Explain,
     Plan,
 
@@ -213,12 +220,14 @@ pub enum Keyword {
     Detach,
     Pragma,
     Use,
+    Vacuum,
 
     // Other
     True,
     False,
     Unknown,
     Cast,
+    Collate,
     Extract,
     Substring,
     Trim,
@@ -256,6 +265,7 @@ pub enum Keyword {
     Databases,
     Schemas,
     Filter,
+    Within,
     Respect,
     Measures,
     Sample,
@@ -265,6 +275,77 @@ pub enum Keyword {
 }
 
 impl Keyword {
+    /// Whether the keyword is reserved
+    ///
+    /// Reserved keywords shape the grammar, so naming a table, column or alias
+    /// after one needs quotes, as in `"order"`. Every other keyword can be used
+    /// as a name as is.
+    pub fn is_reserved(&self) -> bool {
+        matches!(
+            self,
+            Keyword::Select
+                | Keyword::From
+                | Keyword::Where
+                | Keyword::Group
+                | Keyword::Having
+                | Keyword::Qualify
+                | Keyword::Order
+                | Keyword::Limit
+                | Keyword::Offset
+                | Keyword::Distinct
+                | Keyword::All
+                | Keyword::Join
+                | Keyword::Inner
+                | Keyword::Left
+                | Keyword::Right
+                | Keyword::Full
+                | Keyword::Outer
+                | Keyword::Cross
+                | Keyword::Natural
+                | Keyword::On
+                | Keyword::Using
+                | Keyword::Lateral
+                | Keyword::Union
+                | Keyword::Intersect
+                | Keyword::Except
+                | Keyword::Create
+                | Keyword::Table
+                | Keyword::Into
+                | Keyword::Values
+                | Keyword::Returning
+                | Keyword::Primary
+                | Keyword::Foreign
+                | Keyword::References
+                | Keyword::Unique
+                | Keyword::Check
+                | Keyword::Constraint
+                | Keyword::Default
+                | Keyword::Not
+                | Keyword::Null
+                | Keyword::And
+                | Keyword::Or
+                | Keyword::Like
+                | Keyword::ILike
+                | Keyword::Between
+                | Keyword::In
+                | Keyword::Exists
+                | Keyword::Is
+                | Keyword::With
+                | Keyword::As
+                | Keyword::Case
+                | Keyword::When
+                | Keyword::Then
+                | Keyword::Else
+                | Keyword::End
+                | Keyword::Asc
+                | Keyword::Desc
+                | Keyword::True
+                | Keyword::False
+                | Keyword::Cast
+                | Keyword::Collate
+        )
+    }
+
     /// Get all keywords
     pub fn all() -> &'static [Keyword] {
         &[
@@ -289,6 +370,7 @@ impl Keyword {
             Keyword::Full,
             Keyword::Outer,
             Keyword::Cross,
+            Keyword::Natural,
             Keyword::On,
             Keyword::Using,
             Keyword::Lateral,
@@ -309,6 +391,8 @@ impl Keyword {
             Keyword::Schema,
             Keyword::Database,
             Keyword::Drop,
+            Keyword::Cascade,
+            Keyword::Restrict,
             Keyword::Alter,
             Keyword::Add,
             Keyword::Column,
@@ -388,6 +472,8 @@ impl Keyword {
             Keyword::Is,
             Keyword::Any,
             Keyword::Some,
+            // Arithmetic operators
+            Keyword::Div,
             // Aggregate functions
             Keyword::Count,
             Keyword::Sum,
@@ -443,6 +529,7 @@ impl Keyword {
             // Table operations
             Keyword::Show,
             Keyword::Describe,
+            Keyword::Summarize,
             Keyword::Explain,
             Keyword::Plan,
             // Import/Export
@@ -458,11 +545,13 @@ impl Keyword {
             Keyword::Detach,
             Keyword::Pragma,
             Keyword::Use,
+            Keyword::Vacuum,
             // Other
             Keyword::True,
             Keyword::False,
             Keyword::Unknown,
             Keyword::Cast,
+            Keyword::Collate,
             Keyword::Extract,
             Keyword::Substring,
             Keyword::Trim,
@@ -498,6 +587,7 @@ impl Keyword {
             Keyword::Databases,
             Keyword::Schemas,
             Keyword::Filter,
+            Keyword::Within,
             Keyword::Respect,
             Keyword::Measures,
             Keyword::Sample,
@@ -508,6 +598,23 @@ impl Keyword {
     }
 }
 
+/// An identifier spelled so that parsing it gives back `name`
+///
+/// Lowercase names that are not reserved keywords are written as they are,
+/// since unquoted identifiers fold to lowercase; anything else is quoted, as
+/// in `"order"` or `"Name"`.
+pub fn quote_identifier(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    let reserved = Keyword::all()
+        .iter()
+        .any(|keyword| keyword.is_reserved() && keyword.to_string().eq_ignore_ascii_case(name));
+    if plain && !reserved {
+        return name.to_string();
+    }
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\"\""))
+}
+
 impl std::fmt::Display for Keyword {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -533,6 +640,7 @@ impl std::fmt::Display for Keyword {
             Keyword::Full => "FULL",
             Keyword::Outer => "OUTER",
             Keyword::Cross => "CROSS",
+            Keyword::Natural => "NATURAL",
             Keyword::On => "ON",
             Keyword::Using => "USING",
             Keyword::Lateral => "LATERAL",
@@ -555,6 +663,8 @@ impl std::fmt::Display for Keyword {
             Keyword::Schema => "SCHEMA",
             Keyword::Database => "DATABASE",
             Keyword::Drop => "DROP",
+            Keyword::Cascade => "CASCADE",
+            Keyword::Restrict => "RESTRICT",
             Keyword::Alter => "ALTER",
             Keyword::Add => "ADD",
             Keyword::Column => "COLUMN",
@@ -640,6 +750,9 @@ impl std::fmt::Display for Keyword {
             Keyword::Any => "ANY",
             Keyword::Some => "SOME",
 
+            // Arithmetic operators
+            Keyword::Div => "DIV",
+
             // Aggregate functions
             Keyword::Count => "COUNT",
             Keyword::Sum => "SUM",
@@ -700,6 +813,7 @@ impl std::fmt::Display for Keyword {
             // Table operations
             Keyword::Show => "SHOW",
             Keyword::Describe => "DESCRIBE",
+            Keyword::Summarize => "SUMMARIZE",
             Keyword::Explain => "EXPLAIN",
             Keyword::Plan => "PLAN",
 
@@ -718,12 +832,14 @@ impl std::fmt::Display for Keyword {
             Keyword::Detach => "DETACH",
             Keyword::Pragma => "PRAGMA",
             Keyword::Use => "USE",
+            Keyword::Vacuum => "VACUUM",
 
             // Other
             Keyword::True => "TRUE",
             Keyword::False => "FALSE",
             Keyword::Unknown => "UNKNOWN",
             Keyword::Cast => "CAST",
+            Keyword::Collate => "COLLATE",
             Keyword::Extract => "EXTRACT",
             Keyword::Substring => "SUBSTRING",
             Keyword::Trim => "TRIM",
@@ -761,6 +877,7 @@ impl std::fmt::Display for Keyword {
             Keyword::Databases => "DATABASES",
             Keyword::Schemas => "SCHEMAS",
             Keyword::Filter => "FILTER",
+            Keyword::Within => "WITHIN",
             Keyword::Respect => "RESPECT",
             Keyword::Measures => "MEASURES",
             Keyword::Sample => "SAMPLE",
@@ -13,11 +13,52 @@ pub use keywords::*;
 pub use parser::*;
 pub use tokenizer::*;
 
-use crate::common::error::PrismDBResult;
+use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::types::Value;
+
+/// How a parse error points at the token the parser stopped at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseErrorMode {
+    /// Only the message
+    Message,
+    /// The message followed by the token's line and column
+    Position,
+    /// The position, then the token's line of SQL with a caret under it
+    #[default]
+    Snippet,
+}
+
+impl ParseErrorMode {
+    /// Add the location of the token at `line` and `column` of `sql` to a parse error
+    pub fn locate(self, error: PrismDBError, sql: &str, line: usize, column: usize) -> PrismDBError {
+        let PrismDBError::Parse(message) = error else {
+            return error;
+        };
+        let position = format!("{} at line {}, column {}", message, line, column);
+        match self {
+            ParseErrorMode::Message => PrismDBError::Parse(message),
+            ParseErrorMode::Position => PrismDBError::Parse(position),
+            ParseErrorMode::Snippet => {
+                let text = sql.lines().nth(line.saturating_sub(1)).unwrap_or("");
+                // Tabs are kept so the caret lines up however they render
+                let indent: String = text
+                    .chars()
+                    .take(column.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                PrismDBError::Parse(format!("{}\n  {}\n  {}^", position, text, indent))
+            }
+        }
+    }
+}
 
 /// Main parser interface
 pub struct SqlParser {
     tokenizer: Tokenizer,
+    identifier_case: IdentifierCase,
+    /// Values bound to `?` placeholders
+    parameters: Vec<Value>,
+    error_mode: ParseErrorMode,
 }
 
 impl SqlParser {
@@ -25,21 +66,80 @@ impl SqlParser {
     pub fn new() -> Self {
         Self {
             tokenizer: Tokenizer::new(),
+            identifier_case: IdentifierCase::default(),
+            parameters: Vec::new(),
+            error_mode: ParseErrorMode::default(),
         }
     }
 
+    /// Point parse errors at the offending token as `error_mode` says
+    pub fn with_error_mode(mut self, error_mode: ParseErrorMode) -> Self {
+        self.error_mode = error_mode;
+        self
+    }
+
+    /// Normalize unquoted identifiers with `identifier_case`
+    pub fn with_identifier_case(mut self, identifier_case: IdentifierCase) -> Self {
+        self.tokenizer = self.tokenizer.with_identifier_case(identifier_case);
+        self.identifier_case = identifier_case;
+        self
+    }
+
+    /// Bind `parameters` to the `?` placeholders of the parsed SQL, in order
+    ///
+    /// Each placeholder is replaced by its value's literal, so the statement
+    /// plans exactly as if the literal had been written in its place.
+    pub fn with_parameters(mut self, parameters: Vec<Value>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
     /// Parse a SQL query string into a statement
     pub fn parse(&mut self, sql: &str) -> PrismDBResult<Statement> {
+        Ok(self.parse_annotated(sql)?.statement)
+    }
+
+    /// Keep the comments written before a statement, for `parse_annotated`
+    ///
+    /// Off by default, so comments are skipped without being copied.
+    pub fn with_comments(mut self, retain_comments: bool) -> Self {
+        self.tokenizer = self.tokenizer.with_comments(retain_comments);
+        self
+    }
+
+    /// Parse a SQL query string into a statement along with its leading
+    /// comments, which are empty unless the parser keeps comments
+    pub fn parse_annotated(&mut self, sql: &str) -> PrismDBResult<AnnotatedStatement> {
         let tokens = self.tokenizer.tokenize(sql)?;
-        let mut parser = Parser::new(tokens);
-        parser.parse_statement()
+        let mut parser = Parser::new(tokens)
+            .with_identifier_case(self.identifier_case)
+            .with_parameters(self.parameters.clone());
+        let statement = parser.parse_statement().map_err(|e| self.locate(e, sql, &parser))?;
+        parser.check_parameters_used()?;
+        Ok(AnnotatedStatement {
+            statement,
+            comments: parser.comments().to_vec(),
+        })
+    }
+
+    /// Parse a single SQL expression
+    pub fn parse_expression(&mut self, sql: &str) -> PrismDBResult<Expression> {
+        let tokens = self.tokenizer.tokenize(sql)?;
+        let mut parser = Parser::new(tokens).with_identifier_case(self.identifier_case);
+        parser.parse_standalone_expression().map_err(|e| self.locate(e, sql, &parser))
     }
 
     /// Parse multiple SQL statements
     pub fn parse_multiple(&mut self, sql: &str) -> PrismDBResult<Vec<Statement>> {
         let tokens = self.tokenizer.tokenize(sql)?;
-        let mut parser = Parser::new(tokens);
-        parser.parse_statements()
+        let mut parser = Parser::new(tokens).with_identifier_case(self.identifier_case);
+        parser.parse_statements().map_err(|e| self.locate(e, sql, &parser))
+    }
+
+    /// Locate an error at the token `parser` stopped at
+    fn locate(&self, error: PrismDBError, sql: &str, parser: &Parser) -> PrismDBError {
+        let (line, column) = parser.location();
+        self.error_mode.locate(error, sql, line, column)
     }
 }
 
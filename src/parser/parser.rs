@@ -5,23 +5,86 @@
 use crate::common::error::{PrismDBError, PrismDBResult};
 use crate::parser::ast::*;
 use crate::parser::keywords::Keyword;
-use crate::parser::tokenizer::{Token, TokenType};
-use crate::types::LogicalType;
+use crate::parser::tokenizer::{IdentifierCase, Token, TokenType};
+use crate::types::{EnumType, LogicalType, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Positional and named arguments of a table function call
+type TableFunctionArguments = (Vec<Expression>, Vec<(String, Expression)>);
 
 /// SQL parser
 pub struct Parser {
     tokens: Vec<Token>,
     position: usize,
+    /// Normalization of keywords used as identifiers, matching the tokenizer's
+    identifier_case: IdentifierCase,
+    /// Values bound to the `?` placeholders, in order
+    parameters: Vec<Value>,
+    /// Number of `?` placeholders parsed so far
+    parameters_used: usize,
+    /// Text of the comments the tokenizer kept before the first token
+    comments: Vec<String>,
 }
 
 impl Parser {
     /// Create a new parser with the given tokens
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(mut tokens: Vec<Token>) -> Self {
+        let leading = tokens
+            .iter()
+            .take_while(|token| matches!(token.token_type, TokenType::Comment(_)))
+            .count();
+        let comments = tokens
+            .drain(..leading)
+            .filter_map(|token| match token.token_type {
+                TokenType::Comment(text) => Some(text),
+                _ => None,
+            })
+            .collect();
         Self {
             tokens,
             position: 0,
+            identifier_case: IdentifierCase::default(),
+            parameters: Vec::new(),
+            parameters_used: 0,
+            comments,
+        }
+    }
+
+    /// The comments written before the statement, if the tokenizer kept them
+    pub fn comments(&self) -> &[String] {
+        &self.comments
+    }
+
+    /// Bind `parameters` to the `?` placeholders of the statement, in order
+    pub fn with_parameters(mut self, parameters: Vec<Value>) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// Fail unless every bound parameter had a `?` placeholder
+    pub fn check_parameters_used(&self) -> PrismDBResult<()> {
+        if self.parameters_used < self.parameters.len() {
+            return Err(PrismDBError::Parse(format!(
+                "{} parameters given but the statement has {} placeholders",
+                self.parameters.len(),
+                self.parameters_used
+            )));
         }
+        Ok(())
+    }
+
+    /// Normalize keywords used as identifiers with `identifier_case`
+    pub fn with_identifier_case(mut self, identifier_case: IdentifierCase) -> Self {
+        self.identifier_case = identifier_case;
+        self
+    }
+
+    /// Line and column of the token the parser is at, which after an error is
+    /// the token it failed on
+    pub fn location(&self) -> (usize, usize) {
+        let token = self.current_token();
+        (token.line, token.column)
     }
 
     /// Parse a single statement
@@ -35,14 +98,27 @@ impl Parser {
 
         // Expect EOF or end of statement
         if !self.current_token().is_eof() {
-            return Err(PrismDBError::Parse(
-                "Unexpected token after statement".to_string(),
-            ));
+            return Err(PrismDBError::Parse(format!(
+                "Unexpected token after statement: {}",
+                self.current_token()
+            )));
         }
 
         Ok(statement)
     }
 
+    /// Parse a lone expression, such as a column's stored DEFAULT
+    pub fn parse_standalone_expression(&mut self) -> PrismDBResult<Expression> {
+        let expression = self.parse_expression()?;
+        if !self.current_token().is_eof() {
+            return Err(PrismDBError::Parse(format!(
+                "Unexpected token after expression: {}",
+                self.current_token()
+            )));
+        }
+        Ok(expression)
+    }
+
     /// Parse multiple statements
     pub fn parse_statements(&mut self) -> PrismDBResult<Vec<Statement>> {
         let mut statements = Vec::new();
@@ -75,6 +151,15 @@ impl Parser {
                 let select = self.parse_query()?;
                 Ok(Statement::Select(select))
             }
+            TokenType::Keyword(Keyword::Values) => {
+                self.position += 1;
+                let rows = self.parse_values_rows()?;
+                Ok(Statement::Select(Self::select_star(TableReference::Values {
+                    rows,
+                    alias: None,
+                    column_aliases: Vec::new(),
+                })))
+            }
             TokenType::Keyword(Keyword::Insert) => {
                 let insert = self.parse_insert_statement()?;
                 Ok(Statement::Insert(insert))
@@ -87,6 +172,10 @@ impl Parser {
                 let delete = self.parse_delete_statement()?;
                 Ok(Statement::Delete(delete))
             }
+            TokenType::Keyword(Keyword::Merge) => {
+                let merge = self.parse_merge_statement()?;
+                Ok(Statement::Merge(merge))
+            }
             TokenType::Keyword(Keyword::Create) => self.parse_create_statement(),
             TokenType::Keyword(Keyword::Drop) => self.parse_drop_statement(),
             TokenType::Keyword(Keyword::Refresh) => {
@@ -113,6 +202,14 @@ impl Parser {
                 let explain = self.parse_explain_statement()?;
                 Ok(Statement::Explain(explain))
             }
+            TokenType::Keyword(Keyword::Describe) | TokenType::Keyword(Keyword::Desc) => {
+                let describe = self.parse_describe_statement()?;
+                Ok(Statement::Describe(describe))
+            }
+            TokenType::Keyword(Keyword::Summarize) => {
+                let summarize = self.parse_summarize_statement()?;
+                Ok(Statement::Summarize(summarize))
+            }
             TokenType::Keyword(Keyword::Show) => {
                 let show = self.parse_show_statement()?;
                 Ok(Statement::Show(show))
@@ -129,8 +226,21 @@ impl Parser {
                 let set = self.parse_set_statement()?;
                 Ok(Statement::Set(set))
             }
+            TokenType::Keyword(Keyword::Pragma) => {
+                let pragma = self.parse_pragma_statement()?;
+                Ok(Statement::Pragma(pragma))
+            }
+            TokenType::Keyword(Keyword::Copy) => self.parse_copy_statement(),
+            TokenType::Keyword(Keyword::Vacuum) => {
+                let vacuum = self.parse_vacuum_statement()?;
+                Ok(Statement::Vacuum(vacuum))
+            }
+            TokenType::Keyword(Keyword::Analyze) => {
+                let analyze = self.parse_analyze_statement()?;
+                Ok(Statement::Analyze(analyze))
+            }
             _ => Err(PrismDBError::Parse(format!(
-                "Unexpected token: {:?}",
+                "Unexpected token: {}",
                 self.current_token()
             ))),
         }
@@ -262,9 +372,14 @@ impl Parser {
         };
 
         let mut group_by = Vec::new();
+        let mut group_by_all = false;
         if self.consume_keyword(Keyword::Group).is_ok() {
             self.consume_keyword(Keyword::By)?;
-            group_by = self.parse_expression_list()?;
+            if self.consume_keyword(Keyword::All).is_ok() {
+                group_by_all = true;
+            } else {
+                group_by = self.parse_expression_list()?;
+            }
         }
 
         let having = if self.consume_keyword(Keyword::Having).is_ok() {
@@ -281,9 +396,15 @@ impl Parser {
         };
 
         let mut order_by = Vec::new();
+        let mut order_by_all = None;
         if self.consume_keyword(Keyword::Order).is_ok() {
             self.consume_keyword(Keyword::By)?;
-            order_by = self.parse_order_by_list()?;
+            if self.consume_keyword(Keyword::All).is_ok() {
+                let (ascending, nulls_first) = self.parse_sort_direction()?;
+                order_by_all = Some(OrderByAll { ascending, nulls_first });
+            } else {
+                order_by = self.parse_order_by_list()?;
+            }
         }
 
         let limit = if self.consume_keyword(Keyword::Limit).is_ok() {
@@ -314,15 +435,88 @@ impl Parser {
             from,
             where_clause,
             group_by,
+            group_by_all,
             having,
             qualify,
             order_by,
+            order_by_all,
             limit,
             offset,
             set_operations: Vec::new(),  // TODO: Parse set operations
         })
     }
 
+    /// Parse the arguments of a table function call: positional arguments
+    /// followed by named `name => value` or `name = value` ones
+    fn parse_table_function_arguments(&mut self) -> PrismDBResult<TableFunctionArguments> {
+        let mut arguments = Vec::new();
+        let mut named_arguments: Vec<(String, Expression)> = Vec::new();
+        if self.current_token().token_type == TokenType::RightParen {
+            return Ok((arguments, named_arguments));
+        }
+        loop {
+            let named = matches!(self.peek_token().token_type, TokenType::FatArrow | TokenType::Equals)
+                && matches!(self.current_token().token_type, TokenType::Identifier(_) | TokenType::Keyword(_));
+            if named {
+                let name = self.consume_identifier()?.to_lowercase();
+                self.position += 1; // Consume => or =
+                if named_arguments.iter().any(|(existing, _)| *existing == name) {
+                    return Err(PrismDBError::Parse(format!("Argument '{}' is given more than once", name)));
+                }
+                named_arguments.push((name, self.parse_expression()?));
+            } else if !named_arguments.is_empty() {
+                return Err(PrismDBError::Parse(
+                    "Positional arguments must come before named arguments".to_string(),
+                ));
+            } else {
+                arguments.push(self.parse_expression()?);
+            }
+            if self.consume_token(&TokenType::Comma).is_err() {
+                return Ok((arguments, named_arguments));
+            }
+        }
+    }
+
+    /// Parse the `EXCLUDE (...)` and `REPLACE (... AS ...)` modifiers after a wildcard
+    fn parse_wildcard_options(&mut self) -> PrismDBResult<WildcardOptions> {
+        let mut options = WildcardOptions::default();
+        if self.consume_keyword(Keyword::Exclude).is_ok() {
+            if self.consume_token(&TokenType::LeftParen).is_ok() {
+                loop {
+                    options.exclude.push(self.parse_wildcard_column()?);
+                    if self.consume_token(&TokenType::Comma).is_err() {
+                        break;
+                    }
+                }
+                self.consume_token(&TokenType::RightParen)?;
+            } else {
+                options.exclude.push(self.parse_wildcard_column()?);
+            }
+        }
+        if self.consume_keyword(Keyword::Replace).is_ok() {
+            self.consume_token(&TokenType::LeftParen)?;
+            loop {
+                let expression = self.parse_expression()?;
+                self.consume_keyword(Keyword::As)?;
+                options.replace.push((expression, self.consume_identifier()?));
+                if self.consume_token(&TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+            self.consume_token(&TokenType::RightParen)?;
+        }
+        Ok(options)
+    }
+
+    /// A column named in EXCLUDE, optionally qualified with its table
+    fn parse_wildcard_column(&mut self) -> PrismDBResult<String> {
+        let name = self.consume_identifier()?;
+        if self.consume_token(&TokenType::Dot).is_ok() {
+            return Ok(format!("{}.{}", name, self.consume_identifier()?));
+        }
+        Ok(name)
+    }
+
     /// Parse SELECT list
     fn parse_select_list(&mut self) -> PrismDBResult<Vec<SelectItem>> {
         let mut items = Vec::new();
@@ -330,13 +524,15 @@ impl Parser {
         loop {
             let item = if self.current_token().token_type == TokenType::Star {
                 let _ = self.consume_token(&TokenType::Star);
-                SelectItem::Wildcard
-            } else if self.current_token().token_type == TokenType::Keyword(Keyword::From) {
+                SelectItem::Wildcard(self.parse_wildcard_options()?)
+            } else if self.peek_token().token_type == TokenType::Dot
+                && self.tokens.get(self.position + 2).map(|token| &token.token_type) == Some(&TokenType::Star)
+            {
                 // Handle qualified wildcard like table.*
                 let table_name = self.consume_identifier()?;
                 self.consume_token(&TokenType::Dot)?;
                 self.consume_token(&TokenType::Star)?;
-                SelectItem::QualifiedWildcard(table_name)
+                SelectItem::QualifiedWildcard(table_name, self.parse_wildcard_options()?)
             } else {
                 let expression = self.parse_expression()?;
 
@@ -367,10 +563,30 @@ impl Parser {
         let mut left = self.parse_table_factor()?;
 
         while self.is_join_keyword() {
-            let join_type = self.parse_join_type()?;
+            let natural = self.consume_keyword(Keyword::Natural).is_ok();
+            let asof = !natural && self.consume_keyword(Keyword::Asof).is_ok();
+            let join_type = match (asof, self.parse_join_type()?) {
+                (false, join_type) => join_type,
+                (true, JoinType::Inner) => JoinType::AsOf,
+                (true, JoinType::Left) => JoinType::AsOfLeft,
+                (true, _) => {
+                    return Err(PrismDBError::Parse(
+                        "ASOF JOIN can only be INNER or LEFT".to_string(),
+                    ))
+                }
+            };
             self.consume_keyword(Keyword::Join)?;
             let right = self.parse_table_factor()?;
-            let condition = self.parse_join_condition()?;
+            let condition = if natural {
+                JoinCondition::Natural
+            } else {
+                self.parse_join_condition()?
+            };
+            if asof && !matches!(condition, JoinCondition::On(_)) {
+                return Err(PrismDBError::Parse(
+                    "ASOF JOIN needs an ON condition".to_string(),
+                ));
+            }
 
             left = TableReference::Join {
                 left: Box::new(left),
@@ -409,6 +625,36 @@ impl Parser {
                     subquery: Box::new(subquery),
                     alias,
                 }
+            } else if self.consume_keyword(Keyword::Values).is_ok() {
+                let rows = self.parse_values_rows()?;
+                self.consume_token(&TokenType::RightParen)?;
+
+                let has_as = self.consume_keyword(Keyword::As).is_ok();
+                let alias = if has_as
+                    || matches!(self.current_token().token_type, TokenType::Identifier(_))
+                {
+                    Some(self.consume_identifier()?)
+                } else {
+                    None
+                };
+
+                // Optional column aliases: AS t(a, b)
+                let mut column_aliases = Vec::new();
+                if alias.is_some() && self.consume_token(&TokenType::LeftParen).is_ok() {
+                    loop {
+                        column_aliases.push(self.consume_identifier()?);
+                        if self.consume_token(&TokenType::Comma).is_err() {
+                            break;
+                        }
+                    }
+                    self.consume_token(&TokenType::RightParen)?;
+                }
+
+                TableReference::Values {
+                    rows,
+                    alias,
+                    column_aliases,
+                }
             } else {
                 // It's a parenthesized table reference
                 let table_ref = self.parse_table_reference()?;
@@ -423,16 +669,7 @@ impl Parser {
                 // Parse table function: function_name(arg1, arg2, ...)
                 self.consume_token(&TokenType::LeftParen)?;
 
-                let mut arguments = Vec::new();
-                if self.current_token().token_type != TokenType::RightParen {
-                    loop {
-                        arguments.push(self.parse_expression()?);
-                        if self.consume_token(&TokenType::Comma).is_err() {
-                            break;
-                        }
-                    }
-                }
-
+                let (arguments, named_arguments) = self.parse_table_function_arguments()?;
                 self.consume_token(&TokenType::RightParen)?;
 
                 let alias = if self.consume_keyword(Keyword::As).is_ok() {
@@ -448,6 +685,7 @@ impl Parser {
                 TableReference::TableFunction {
                     name,
                     arguments,
+                    named_arguments,
                     alias,
                 }
             } else {
@@ -551,20 +789,7 @@ impl Parser {
 
         loop {
             let expression = self.parse_expression()?;
-
-            let ascending = if self.consume_keyword(Keyword::Asc).is_ok() {
-                true
-            } else if self.consume_keyword(Keyword::Desc).is_ok() {
-                false
-            } else {
-                true // Default to ASC
-            };
-
-            let nulls_first = if self.consume_keyword(Keyword::Nulls).is_ok() {
-                self.consume_keyword(Keyword::First).is_ok()
-            } else {
-                false // Default
-            };
+            let (ascending, nulls_first) = self.parse_sort_direction()?;
 
             expressions.push(OrderByExpression {
                 expression,
@@ -580,6 +805,33 @@ impl Parser {
         Ok(expressions)
     }
 
+    /// Parse an optional ASC/DESC and NULLS FIRST/LAST after a sort key
+    fn parse_sort_direction(&mut self) -> PrismDBResult<(bool, Option<bool>)> {
+        let ascending = if self.consume_keyword(Keyword::Asc).is_ok() {
+            true
+        } else if self.consume_keyword(Keyword::Desc).is_ok() {
+            false
+        } else {
+            true // Default to ASC
+        };
+
+        let nulls_first = if self.consume_keyword(Keyword::Nulls).is_ok() {
+            if self.consume_keyword(Keyword::First).is_ok() {
+                Some(true)
+            } else if self.consume_keyword(Keyword::Last).is_ok() {
+                Some(false)
+            } else {
+                return Err(PrismDBError::Parse(
+                    "Expected FIRST or LAST after NULLS".to_string(),
+                ));
+            }
+        } else {
+            None // Resolved against the default null order during binding
+        };
+
+        Ok((ascending, nulls_first))
+    }
+
     /// Parse expression
     fn parse_expression(&mut self) -> PrismDBResult<Expression> {
         self.parse_or_expression()
@@ -633,8 +885,17 @@ impl Parser {
     /// Parse comparison expression
     fn parse_comparison_expression(&mut self) -> PrismDBResult<Expression> {
         let mut left = self.parse_additive_expression()?;
+        let mut compared = false;
 
         loop {
+            // `a < b < c` would compare the boolean `a < b` with `c`
+            if compared && self.is_comparison_operator() {
+                return Err(PrismDBError::Parse(format!(
+                    "Chained comparison at {}: write 'a < b AND b < c', or parenthesize to compare a boolean",
+                    self.current_token()
+                )));
+            }
+
             let operator = match &self.current_token().token_type {
                 TokenType::Equals => {
                     let _ = self.consume_token(&TokenType::Equals);
@@ -672,6 +933,11 @@ impl Parser {
                     let _ = self.consume_keyword(Keyword::Between);
                     return self.parse_between_expression(left, false);
                 }
+                TokenType::Keyword(Keyword::Not) if self.peek_token().is_keyword(Keyword::Between) => {
+                    let _ = self.consume_keyword(Keyword::Not);
+                    let _ = self.consume_keyword(Keyword::Between);
+                    return self.parse_between_expression(left, true);
+                }
                 TokenType::Keyword(Keyword::Is) => {
                     let _ = self.consume_keyword(Keyword::Is);
                     return self.parse_is_expression(left);
@@ -681,11 +947,16 @@ impl Parser {
 
             if let Some(op) = operator {
                 let right = self.parse_additive_expression()?;
-                left = Expression::Binary {
-                    left: Box::new(left),
-                    operator: op,
-                    right: Box::new(right),
+                left = if Self::row_items(&left).is_some() || Self::row_items(&right).is_some() {
+                    Self::row_comparison(&left, &op, &right)?
+                } else {
+                    Expression::Binary {
+                        left: Box::new(left),
+                        operator: op,
+                        right: Box::new(right),
+                    }
                 };
+                compared = true;
             } else {
                 break;
             }
@@ -694,6 +965,20 @@ impl Parser {
         Ok(left)
     }
 
+    /// Whether the current token is a comparison operator that would chain
+    fn is_comparison_operator(&self) -> bool {
+        matches!(
+            self.current_token().token_type,
+            TokenType::Equals
+                | TokenType::NotEquals
+                | TokenType::LessThan
+                | TokenType::LessThanOrEqual
+                | TokenType::GreaterThan
+                | TokenType::GreaterThanOrEqual
+                | TokenType::Keyword(Keyword::Like)
+        )
+    }
+
     /// Parse additive expression
     fn parse_additive_expression(&mut self) -> PrismDBResult<Expression> {
         let mut left = self.parse_multiplicative_expression()?;
@@ -740,6 +1025,15 @@ impl Parser {
                     let _ = self.consume_token(&TokenType::Divide);
                     Some(BinaryOperator::Divide)
                 }
+                TokenType::IntegerDivide => {
+                    let _ = self.consume_token(&TokenType::IntegerDivide);
+                    Some(BinaryOperator::IntegerDivide)
+                }
+                // `a DIV b` is another spelling of `a // b`
+                TokenType::Keyword(Keyword::Div) => {
+                    let _ = self.consume_keyword(Keyword::Div);
+                    Some(BinaryOperator::IntegerDivide)
+                }
                 TokenType::Modulo => {
                     let _ = self.consume_token(&TokenType::Modulo);
                     Some(BinaryOperator::Modulo)
@@ -781,20 +1075,56 @@ impl Parser {
                     expression: Box::new(expression),
                 })
             }
-            _ => self.parse_primary_expression(),
+            _ => {
+                let mut expression = self.parse_primary_expression()?;
+                // Element access: list[index] or map[key]
+                while self.consume_token(&TokenType::LeftBracket).is_ok() {
+                    let key = self.parse_expression()?;
+                    self.consume_token(&TokenType::RightBracket)?;
+                    expression = Expression::FunctionCall {
+                        name: "ELEMENT_AT".to_string(),
+                        arguments: vec![expression, key],
+                        distinct: false,
+                    };
+                }
+                while self.consume_keyword(Keyword::Collate).is_ok() {
+                    let collation = self.parse_collation_name()?;
+                    expression = Expression::Collate {
+                        expression: Box::new(expression),
+                        collation,
+                    };
+                }
+                Ok(expression)
+            }
         }
     }
 
     /// Parse primary expression
+    /// A `?` placeholder, replaced by the literal of its bound parameter
+    fn parse_parameter(&mut self) -> PrismDBResult<Expression> {
+        self.consume_token(&TokenType::QuestionMark)?;
+        self.parameters_used += 1;
+        let value = self.parameters.get(self.parameters_used - 1).ok_or_else(|| {
+            PrismDBError::Parse(format!("No value given for parameter {}", self.parameters_used))
+        })?;
+        let tokens = crate::parser::tokenizer::Tokenizer::new().tokenize(&value.to_sql_literal())?;
+        Parser::new(tokens).parse_expression()
+    }
+
     fn parse_primary_expression(&mut self) -> PrismDBResult<Expression> {
         match &self.current_token().token_type {
             TokenType::StringLiteral(_) => {
                 let value = self.consume_string_literal()?;
                 Ok(Expression::Literal(LiteralValue::String(value)))
             }
+            TokenType::BlobLiteral(bytes) => {
+                let bytes = bytes.clone();
+                self.position += 1;
+                Ok(Expression::Literal(LiteralValue::Blob(bytes)))
+            }
             TokenType::NumericLiteral(_) => {
                 let value = self.consume_numeric_literal()?;
-                if value.contains('.') {
+                if value.contains(['.', 'e', 'E']) {
                     Ok(Expression::Literal(LiteralValue::Float(
                         value.parse().unwrap(),
                     )))
@@ -804,6 +1134,7 @@ impl Parser {
                     )))
                 }
             }
+            TokenType::QuestionMark => self.parse_parameter(),
             TokenType::Keyword(Keyword::True) => {
                 let _ = self.consume_keyword(Keyword::True);
                 Ok(Expression::Literal(LiteralValue::Boolean(true)))
@@ -828,60 +1159,56 @@ impl Parser {
                 self.consume_token(&TokenType::RightParen)?;
                 Ok(Expression::Exists(subquery))
             }
-            // Handle aggregate function keywords (COUNT, SUM, AVG, etc.)
-            TokenType::Keyword(kw) if self.is_aggregate_keyword(kw) => {
-                let func_name = self.current_token().text.clone();
-                self.position += 1; // Consume the keyword
-
-                // Must be followed by left paren
-                if self.current_token().token_type == TokenType::LeftParen {
-                    self.parse_function_call(func_name)
-                } else {
-                    Err(PrismDBError::Parse(format!(
-                        "Expected '(' after aggregate function {}",
-                        func_name
-                    )))
-                }
+            TokenType::Keyword(Keyword::Cast) => {
+                self.consume_keyword(Keyword::Cast)?;
+                self.parse_cast_expression(false)
             }
-            // Handle scalar function keywords (COALESCE, NULLIF, LENGTH, etc.)
-            TokenType::Keyword(kw) if self.is_scalar_function_keyword(kw) => {
-                let func_name = self.current_token().text.clone();
-                self.position += 1; // Consume the keyword
-
-                // Must be followed by left paren
-                if self.current_token().token_type == TokenType::LeftParen {
-                    self.parse_function_call(func_name)
-                } else {
-                    Err(PrismDBError::Parse(format!(
-                        "Expected '(' after function {}",
-                        func_name
-                    )))
+            // Map literal: MAP {'a': 1, 'b': 2}
+            TokenType::Keyword(Keyword::Map) if self.peek_token().token_type == TokenType::LeftBrace => {
+                self.position += 2;
+                let mut arguments = Vec::new();
+                if self.current_token().token_type != TokenType::RightBrace {
+                    loop {
+                        arguments.push(self.parse_expression()?);
+                        self.consume_token(&TokenType::Colon)?;
+                        arguments.push(self.parse_expression()?);
+                        if self.consume_token(&TokenType::Comma).is_err() {
+                            break;
+                        }
+                    }
                 }
+                self.consume_token(&TokenType::RightBrace)?;
+                // Keys and values alternate
+                Ok(Expression::FunctionCall {
+                    name: "MAP_PACK".to_string(),
+                    arguments,
+                    distinct: false,
+                })
             }
-            // Handle non-reserved keywords as identifiers (e.g., column names like "temp")
+            // A keyword followed by `(` calls the function of that name, as in COUNT(*);
+            // LEFT and RIGHT are reserved but name string functions too
             TokenType::Keyword(kw)
-                if !self.is_aggregate_keyword(kw) && !self.is_scalar_function_keyword(kw) =>
+                if self.peek_token().token_type == TokenType::LeftParen
+                    && (!kw.is_reserved() || matches!(kw, Keyword::Left | Keyword::Right)) =>
             {
+                let func_name = self.current_token().text.clone();
+                self.position += 1;
+                self.parse_function_call(func_name)
+            }
+            // Non-reserved keywords name columns, e.g. a column called "year" or "count"
+            TokenType::Keyword(kw) if !kw.is_reserved() => {
                 let identifier = self.consume_identifier()?;
-
-                // Check if it's a function call
-                if self.current_token().token_type == TokenType::LeftParen {
-                    self.parse_function_call(identifier)
+                if self.consume_token(&TokenType::Dot).is_ok() {
+                    let column = self.consume_identifier_or_keyword()?;
+                    Ok(Expression::ColumnReference {
+                        table: Some(identifier),
+                        column,
+                    })
                 } else {
-                    // Check if it's a qualified column reference
-                    if self.current_token().token_type == TokenType::Dot {
-                        self.consume_token(&TokenType::Dot)?;
-                        let column = self.consume_identifier()?;
-                        Ok(Expression::ColumnReference {
-                            table: Some(identifier),
-                            column,
-                        })
-                    } else {
-                        Ok(Expression::ColumnReference {
-                            table: None,
-                            column: identifier,
-                        })
-                    }
+                    Ok(Expression::ColumnReference {
+                        table: None,
+                        column: identifier,
+                    })
                 }
             }
             TokenType::Identifier(_) => {
@@ -889,12 +1216,15 @@ impl Parser {
 
                 // Check if it's a function call
                 if self.current_token().token_type == TokenType::LeftParen {
+                    if identifier.eq_ignore_ascii_case("try_cast") {
+                        return self.parse_cast_expression(true);
+                    }
                     self.parse_function_call(identifier)
                 } else {
                     // Check if it's a qualified column reference
                     if self.current_token().token_type == TokenType::Dot {
                         self.consume_token(&TokenType::Dot)?;
-                        let column = self.consume_identifier()?;
+                        let column = self.consume_identifier_or_keyword()?;
                         Ok(Expression::ColumnReference {
                             table: Some(identifier),
                             column,
@@ -907,6 +1237,25 @@ impl Parser {
                     }
                 }
             }
+            // List literal: [1, 2, 3]
+            TokenType::LeftBracket => {
+                self.position += 1;
+                let mut arguments = Vec::new();
+                if self.current_token().token_type != TokenType::RightBracket {
+                    loop {
+                        arguments.push(self.parse_expression()?);
+                        if self.consume_token(&TokenType::Comma).is_err() {
+                            break;
+                        }
+                    }
+                }
+                self.consume_token(&TokenType::RightBracket)?;
+                Ok(Expression::FunctionCall {
+                    name: "LIST_VALUE".to_string(),
+                    arguments,
+                    distinct: false,
+                })
+            }
             TokenType::LeftParen => {
                 let _ = self.consume_token(&TokenType::LeftParen);
 
@@ -919,49 +1268,29 @@ impl Parser {
                     self.consume_token(&TokenType::RightParen)?;
                     Ok(Expression::Subquery(subquery))
                 } else {
-                    // Regular parenthesized expression
+                    // Regular parenthesized expression, or a row value `(a, b)`
                     let expression = self.parse_expression()?;
+                    if self.consume_token(&TokenType::Comma).is_err() {
+                        self.consume_token(&TokenType::RightParen)?;
+                        return Ok(expression);
+                    }
+                    let mut arguments = vec![expression];
+                    arguments.extend(self.parse_expression_list()?);
                     self.consume_token(&TokenType::RightParen)?;
-                    Ok(expression)
+                    Ok(Expression::FunctionCall {
+                        name: "ROW".to_string(),
+                        arguments,
+                        distinct: false,
+                    })
                 }
             }
             _ => Err(PrismDBError::Parse(format!(
-                "Unexpected token in expression: {:?}",
+                "Unexpected token in expression: {}",
                 self.current_token()
             ))),
         }
     }
 
-    /// Check if a keyword is an aggregate function keyword
-    fn is_aggregate_keyword(&self, kw: &Keyword) -> bool {
-        matches!(
-            kw,
-            Keyword::Count
-                | Keyword::Sum
-                | Keyword::Avg
-                | Keyword::Min
-                | Keyword::Max
-                | Keyword::StdDev
-                | Keyword::Variance
-        )
-    }
-
-    /// Check if a keyword is a scalar function that should be parsed as a function call
-    fn is_scalar_function_keyword(&self, kw: &Keyword) -> bool {
-        matches!(
-            kw,
-            Keyword::Coalesce
-                | Keyword::NullIf
-                | Keyword::Length
-                | Keyword::Upper
-                | Keyword::Lower
-                | Keyword::Trim
-                | Keyword::Substring
-                | Keyword::Cast
-                | Keyword::Concat
-        )
-    }
-
     /// Parse function call
     fn parse_function_call(&mut self, name: String) -> PrismDBResult<Expression> {
         let _ = self.consume_token(&TokenType::LeftParen);
@@ -980,10 +1309,52 @@ impl Parser {
             }
         }
 
+        // Ordered aggregate: STRING_AGG(name, ',' ORDER BY name)
+        let mut order_by = if self.consume_keyword(Keyword::Order).is_ok() {
+            self.consume_keyword(Keyword::By)?;
+            self.parse_order_by_list()?
+        } else {
+            Vec::new()
+        };
+
         self.consume_token(&TokenType::RightParen)?;
 
+        // Ordered-set aggregate: PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY x)
+        let within_group = self.consume_keyword(Keyword::Within).is_ok();
+        if within_group {
+            if !order_by.is_empty() {
+                return Err(PrismDBError::Parse(format!(
+                    "{} cannot have both ORDER BY and WITHIN GROUP",
+                    name
+                )));
+            }
+            self.consume_keyword(Keyword::Group)?;
+            self.consume_token(&TokenType::LeftParen)?;
+            self.consume_keyword(Keyword::Order)?;
+            self.consume_keyword(Keyword::By)?;
+            order_by = self.parse_order_by_list()?;
+            self.consume_token(&TokenType::RightParen)?;
+        }
+
+        // Check for FILTER (WHERE ...) on an aggregate
+        let filter = if self.consume_keyword(Keyword::Filter).is_ok() {
+            self.consume_token(&TokenType::LeftParen)?;
+            self.consume_keyword(Keyword::Where)?;
+            let predicate = self.parse_expression()?;
+            self.consume_token(&TokenType::RightParen)?;
+            Some(Box::new(predicate))
+        } else {
+            None
+        };
+
         // Check for OVER clause (window function)
         if self.consume_keyword(Keyword::Over).is_ok() {
+            if filter.is_some() || !order_by.is_empty() {
+                return Err(PrismDBError::Parse(format!(
+                    "FILTER and ORDER BY are not supported on window function {}",
+                    name
+                )));
+            }
             let window_spec = self.parse_window_spec()?;
             return Ok(Expression::WindowFunction {
                 name,
@@ -994,15 +1365,19 @@ impl Parser {
 
         // Check if it's an aggregate function
         let is_aggregate = match name.to_uppercase().as_str() {
-            "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE" => true,
+            "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE" | "COUNTIF" | "COUNT_IF" => true,
             _ => false,
         };
 
-        if is_aggregate {
+        // A filter or ordering makes any function an aggregate; binding rejects non-aggregates
+        if is_aggregate || filter.is_some() || !order_by.is_empty() {
             Ok(Expression::AggregateFunction {
                 name,
                 arguments,
                 distinct,
+                filter,
+                order_by,
+                within_group,
             })
         } else {
             Ok(Expression::FunctionCall {
@@ -1013,6 +1388,20 @@ impl Parser {
         }
     }
 
+    /// Parse the `(expression AS type)` of CAST or TRY_CAST
+    fn parse_cast_expression(&mut self, try_cast: bool) -> PrismDBResult<Expression> {
+        self.consume_token(&TokenType::LeftParen)?;
+        let expression = self.parse_expression()?;
+        self.consume_keyword(Keyword::As)?;
+        let data_type = self.parse_data_type()?;
+        self.consume_token(&TokenType::RightParen)?;
+        Ok(Expression::Cast {
+            expression: Box::new(expression),
+            data_type,
+            try_cast,
+        })
+    }
+
     /// Parse CASE expression
     /// Supports both simple CASE and searched CASE:
     /// Simple: CASE expr WHEN value1 THEN result1 ... ELSE default END
@@ -1122,13 +1511,39 @@ impl Parser {
             (bound, None)
         };
 
+        let exclude = self.parse_window_frame_exclude()?;
+
         Ok(Some(WindowFrame {
             units,
             start_bound,
             end_bound,
+            exclude,
         }))
     }
 
+    /// Parse the optional EXCLUDE clause ending a window frame
+    fn parse_window_frame_exclude(&mut self) -> PrismDBResult<WindowFrameExclude> {
+        if self.consume_keyword(Keyword::Exclude).is_err() {
+            return Ok(WindowFrameExclude::NoOthers);
+        }
+        if self.consume_keyword(Keyword::Current).is_ok() {
+            self.consume_keyword(Keyword::Row)?;
+            Ok(WindowFrameExclude::CurrentRow)
+        } else if self.consume_keyword(Keyword::Group).is_ok() {
+            Ok(WindowFrameExclude::Group)
+        } else if self.consume_word("TIES").is_ok() {
+            Ok(WindowFrameExclude::Ties)
+        } else if self.consume_word("NO").is_ok() {
+            self.consume_word("OTHERS")?;
+            Ok(WindowFrameExclude::NoOthers)
+        } else {
+            Err(PrismDBError::Parse(format!(
+                "Expected CURRENT ROW, GROUP, TIES or NO OTHERS after EXCLUDE, found {}",
+                self.current_token()
+            )))
+        }
+    }
+
     /// Parse window frame bound
     fn parse_window_frame_bound(&mut self) -> PrismDBResult<WindowFrameBound> {
         if self.consume_keyword(Keyword::Current).is_ok() {
@@ -1156,7 +1571,7 @@ impl Parser {
                 }
                 _ => {
                     return Err(PrismDBError::Parse(format!(
-                        "Expected number for frame offset, got: {:?}",
+                        "Expected number for frame offset, got: {}",
                         self.current_token()
                     )));
                 }
@@ -1184,6 +1599,11 @@ impl Parser {
                 TokenType::Keyword(Keyword::Select) | TokenType::Keyword(Keyword::With)
             ) {
                 // Subquery
+                if Self::row_items(&left).is_some() {
+                    return Err(PrismDBError::NotImplemented(
+                        "IN with a subquery for a row value".to_string(),
+                    ));
+                }
                 let subquery = self.parse_query()?;
                 self.consume_token(&TokenType::RightParen)?;
                 Ok(Expression::InSubquery {
@@ -1195,6 +1615,9 @@ impl Parser {
                 // List of values
                 let list = self.parse_expression_list()?;
                 self.consume_token(&TokenType::RightParen)?;
+                if Self::row_items(&left).is_some() {
+                    return Self::row_in_list(&left, &list);
+                }
                 Ok(Expression::InList {
                     expression: Box::new(left),
                     list,
@@ -1206,21 +1629,137 @@ impl Parser {
         }
     }
 
-    /// Parse BETWEEN expression
+    /// Elements of a row value `(a, b, ...)`
+    fn row_items(expression: &Expression) -> Option<&[Expression]> {
+        match expression {
+            Expression::FunctionCall {
+                name,
+                arguments,
+                distinct: false,
+            } if name == "ROW" => Some(arguments),
+            _ => None,
+        }
+    }
+
+    /// Compare two row values element by element
+    ///
+    /// `=` holds when every element is equal and `<>` when any differs; the
+    /// ordering operators compare lexicographically. The result is built from
+    /// scalar comparisons, so a NULL element makes it NULL unless another
+    /// element already decides it, as SQL requires.
+    fn row_comparison(
+        left: &Expression,
+        operator: &BinaryOperator,
+        right: &Expression,
+    ) -> PrismDBResult<Expression> {
+        let (Some(left_items), Some(right_items)) = (Self::row_items(left), Self::row_items(right)) else {
+            return Err(PrismDBError::Parse(format!(
+                "Cannot compare a row value with a single value: {} {} {}",
+                left, operator, right
+            )));
+        };
+        if left_items.len() != right_items.len() {
+            return Err(PrismDBError::Parse(format!(
+                "Cannot compare a row value of {} elements with one of {}",
+                left_items.len(),
+                right_items.len()
+            )));
+        }
+
+        let compare = |operator: BinaryOperator, index: usize| Expression::Binary {
+            left: Box::new(left_items[index].clone()),
+            operator,
+            right: Box::new(right_items[index].clone()),
+        };
+        let combine = |operator: BinaryOperator, expressions: Vec<Expression>| {
+            expressions
+                .into_iter()
+                .reduce(|left, right| Expression::Binary {
+                    left: Box::new(left),
+                    operator: operator.clone(),
+                    right: Box::new(right),
+                })
+                .expect("a row value has at least two elements")
+        };
+        let all = 0..left_items.len();
+        match operator {
+            BinaryOperator::Equals => Ok(combine(
+                BinaryOperator::And,
+                all.map(|index| compare(BinaryOperator::Equals, index)).collect(),
+            )),
+            BinaryOperator::NotEquals => Ok(combine(
+                BinaryOperator::Or,
+                all.map(|index| compare(BinaryOperator::NotEquals, index)).collect(),
+            )),
+            BinaryOperator::LessThan
+            | BinaryOperator::LessThanOrEqual
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterThanOrEqual => {
+                // `(a, b) < (x, y)` is `a < x OR (a = x AND b < y)`; only the
+                // last element compares with `<=` for `<=`
+                let strict = match operator {
+                    BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual => BinaryOperator::LessThan,
+                    _ => BinaryOperator::GreaterThan,
+                };
+                let last = left_items.len() - 1;
+                Ok(combine(
+                    BinaryOperator::Or,
+                    all.map(|index| {
+                        let operator = if index == last { operator.clone() } else { strict.clone() };
+                        let mut conjuncts: Vec<Expression> =
+                            (0..index).map(|prefix| compare(BinaryOperator::Equals, prefix)).collect();
+                        conjuncts.push(compare(operator, index));
+                        combine(BinaryOperator::And, conjuncts)
+                    })
+                    .collect(),
+                ))
+            }
+            _ => Err(PrismDBError::Parse(format!(
+                "Operator {} does not apply to row values",
+                operator
+            ))),
+        }
+    }
+
+    /// `(a, b) IN ((1, 2), (3, 4))`: whether the row equals any row of the list
+    fn row_in_list(left: &Expression, list: &[Expression]) -> PrismDBResult<Expression> {
+        list.iter()
+            .map(|row| Self::row_comparison(left, &BinaryOperator::Equals, row))
+            .reduce(|left, right| {
+                Ok(Expression::Binary {
+                    left: Box::new(left?),
+                    operator: BinaryOperator::Or,
+                    right: Box::new(right?),
+                })
+            })
+            .expect("an IN list has at least one element")
+    }
+
+    /// Parse BETWEEN expression, after `BETWEEN` or `NOT BETWEEN`
     fn parse_between_expression(
         &mut self,
         left: Expression,
         not: bool,
     ) -> PrismDBResult<Expression> {
-        let low = self.parse_expression()?;
+        // SYMMETRIC is not reserved, so it arrives as an identifier
+        let symmetric = matches!(
+            &self.current_token().token_type,
+            TokenType::Identifier(word) if word.eq_ignore_ascii_case("SYMMETRIC")
+        );
+        if symmetric {
+            self.position += 1;
+        }
+
+        // Bounds bind tighter than AND so `x BETWEEN 1 AND 5 AND y` keeps the trailing conjunct
+        let low = self.parse_additive_expression()?;
         self.consume_keyword(Keyword::And)?;
-        let high = self.parse_expression()?;
+        let high = self.parse_additive_expression()?;
 
-        Ok(Expression::Between {
-            expression: Box::new(left),
-            low: Box::new(low),
-            high: Box::new(high),
-            not,
+        let (expression, low, high) = (Box::new(left), Box::new(low), Box::new(high));
+        Ok(if symmetric {
+            Expression::BetweenSymmetric { expression, low, high, not }
+        } else {
+            Expression::Between { expression, low, high, not }
         })
     }
 
@@ -1273,24 +1812,7 @@ impl Parser {
         }
 
         let source = if self.consume_keyword(Keyword::Values).is_ok() {
-            let mut values = Vec::new();
-            loop {
-                self.consume_token(&TokenType::LeftParen)?;
-                let mut row = Vec::new();
-                loop {
-                    row.push(self.parse_expression()?);
-                    if self.consume_token(&TokenType::Comma).is_err() {
-                        break;
-                    }
-                }
-                self.consume_token(&TokenType::RightParen)?;
-                values.push(row);
-
-                if self.consume_token(&TokenType::Comma).is_err() {
-                    break;
-                }
-            }
-            InsertSource::Values(values)
+            InsertSource::Values(self.parse_values_rows()?)
         } else if self.current_token().token_type == TokenType::Keyword(Keyword::Select) {
             let select = self.parse_select_statement()?;
             InsertSource::Select(select)
@@ -1319,17 +1841,7 @@ impl Parser {
 
         self.consume_keyword(Keyword::Set)?;
 
-        let mut assignments = Vec::new();
-        loop {
-            let column = self.consume_identifier()?;
-            self.consume_token(&TokenType::Equals)?;
-            let value = self.parse_expression()?;
-            assignments.push(Assignment { column, value });
-
-            if self.consume_token(&TokenType::Comma).is_err() {
-                break;
-            }
-        }
+        let assignments = self.parse_assignments()?;
 
         let where_clause = if self.consume_keyword(Keyword::Where).is_ok() {
             Some(self.parse_expression()?)
@@ -1344,6 +1856,26 @@ impl Parser {
         })
     }
 
+    /// Parse the `column = value, ...` list after SET
+    fn parse_assignments(&mut self) -> PrismDBResult<Vec<Assignment>> {
+        let mut assignments = Vec::new();
+        loop {
+            let column = self.consume_identifier()?;
+            self.consume_token(&TokenType::Equals)?;
+            let value = if self.consume_keyword(Keyword::Default).is_ok() {
+                AssignmentValue::Default
+            } else {
+                AssignmentValue::Expression(self.parse_expression()?)
+            };
+            assignments.push(Assignment { column, value });
+
+            if self.consume_token(&TokenType::Comma).is_err() {
+                break;
+            }
+        }
+        Ok(assignments)
+    }
+
     /// Parse DELETE statement
     fn parse_delete_statement(&mut self) -> PrismDBResult<DeleteStatement> {
         self.consume_keyword(Keyword::Delete)?;
@@ -1363,6 +1895,94 @@ impl Parser {
         })
     }
 
+    /// Parse MERGE INTO target [AS alias] USING source ON condition WHEN ... THEN ...
+    fn parse_merge_statement(&mut self) -> PrismDBResult<MergeStatement> {
+        self.consume_keyword(Keyword::Merge)?;
+        self.consume_keyword(Keyword::Into)?;
+        let table_name = self.consume_identifier()?;
+        let alias = if self.consume_keyword(Keyword::As).is_ok()
+            || matches!(self.current_token().token_type, TokenType::Identifier(_))
+        {
+            Some(self.consume_identifier()?)
+        } else {
+            None
+        };
+
+        self.consume_keyword(Keyword::Using)?;
+        let source = self.parse_table_factor()?;
+        self.consume_keyword(Keyword::On)?;
+        let condition = self.parse_expression()?;
+
+        let mut clauses = Vec::new();
+        while self.consume_keyword(Keyword::When).is_ok() {
+            let matched = self.consume_keyword(Keyword::Not).is_err();
+            self.consume_word("MATCHED")?;
+            let condition = if self.consume_keyword(Keyword::And).is_ok() {
+                Some(self.parse_expression()?)
+            } else {
+                None
+            };
+            self.consume_keyword(Keyword::Then)?;
+            let action = self.parse_merge_action(matched)?;
+            clauses.push(MergeClause {
+                matched,
+                condition,
+                action,
+            });
+        }
+        if clauses.is_empty() {
+            return Err(PrismDBError::Parse(
+                "MERGE requires at least one WHEN MATCHED or WHEN NOT MATCHED clause".to_string(),
+            ));
+        }
+
+        Ok(MergeStatement {
+            table_name,
+            alias,
+            source,
+            condition,
+            clauses,
+        })
+    }
+
+    /// Parse the action after THEN in a MERGE clause
+    fn parse_merge_action(&mut self, matched: bool) -> PrismDBResult<MergeAction> {
+        if matched && self.consume_keyword(Keyword::Update).is_ok() {
+            self.consume_keyword(Keyword::Set)?;
+            Ok(MergeAction::Update(self.parse_assignments()?))
+        } else if matched && self.consume_keyword(Keyword::Delete).is_ok() {
+            Ok(MergeAction::Delete)
+        } else if !matched && self.consume_keyword(Keyword::Insert).is_ok() {
+            let mut columns = Vec::new();
+            if self.consume_token(&TokenType::LeftParen).is_ok() {
+                columns = self.parse_identifier_list()?;
+                self.consume_token(&TokenType::RightParen)?;
+            }
+            self.consume_keyword(Keyword::Values)?;
+            self.consume_token(&TokenType::LeftParen)?;
+            let mut values = Vec::new();
+            loop {
+                values.push(self.parse_expression()?);
+                if self.consume_token(&TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+            self.consume_token(&TokenType::RightParen)?;
+            Ok(MergeAction::Insert { columns, values })
+        } else if self.consume_word("DO").is_ok() {
+            self.consume_word("NOTHING")?;
+            Ok(MergeAction::DoNothing)
+        } else if matched {
+            Err(PrismDBError::Parse(
+                "Expected UPDATE, DELETE or DO NOTHING after WHEN MATCHED THEN".to_string(),
+            ))
+        } else {
+            Err(PrismDBError::Parse(
+                "Expected INSERT or DO NOTHING after WHEN NOT MATCHED THEN".to_string(),
+            ))
+        }
+    }
+
     /// Parse CREATE statement
     fn parse_create_statement(&mut self) -> PrismDBResult<Statement> {
         self.consume_keyword(Keyword::Create)?;
@@ -1388,6 +2008,18 @@ impl Parser {
                 let index = self.parse_create_index_statement()?;
                 Ok(Statement::CreateIndex(index))
             }
+            TokenType::Identifier(word) if word.eq_ignore_ascii_case("TYPE") && !or_replace => {
+                let create_type = self.parse_create_type_statement()?;
+                Ok(Statement::CreateType(create_type))
+            }
+            TokenType::Identifier(word) if word.eq_ignore_ascii_case("FUNCTION") => {
+                let function = self.parse_create_function_statement(or_replace)?;
+                Ok(Statement::CreateFunction(function))
+            }
+            TokenType::Keyword(Keyword::Schema) if !or_replace => {
+                let schema = self.parse_create_schema_statement()?;
+                Ok(Statement::CreateSchema(schema))
+            }
             TokenType::Identifier(_) if or_replace => {
                 // This might be CREATE OR REPLACE SECRET
                 let identifier = self.consume_identifier()?;
@@ -1402,7 +2034,7 @@ impl Parser {
                 }
             }
             _ => Err(PrismDBError::Parse(
-                "Expected TABLE, VIEW, INDEX, or SECRET after CREATE".to_string(),
+                "Expected TABLE, VIEW, INDEX, TYPE, FUNCTION, SCHEMA, or SECRET after CREATE".to_string(),
             )),
         }
     }
@@ -1451,11 +2083,12 @@ impl Parser {
     /// Parse column definition
     fn parse_column_definition(&mut self) -> PrismDBResult<ColumnDefinition> {
         let name = self.consume_identifier()?;
-        let data_type = self.parse_data_type()?;
+        let (data_type, max_length) = self.parse_column_type()?;
 
         let mut nullable = true;
         let mut default_value = None;
         let mut constraints = Vec::new();
+        let mut collation = None;
 
         loop {
             if self.consume_keyword(Keyword::Not).is_ok() {
@@ -1465,15 +2098,17 @@ impl Parser {
             } else if self.consume_keyword(Keyword::Null).is_ok() {
                 nullable = true;
             } else if self.consume_keyword(Keyword::Default).is_ok() {
-                default_value = Some(self.parse_expression()?);
+                default_value = Some(self.parse_stored_expression("DEFAULT expressions")?);
             } else if self.consume_keyword(Keyword::Primary).is_ok() {
                 self.consume_keyword(Keyword::Key)?;
+                self.parse_nulls_distinct()?;
                 constraints.push(ColumnConstraint::PrimaryKey);
             } else if self.consume_keyword(Keyword::Unique).is_ok() {
-                constraints.push(ColumnConstraint::Unique);
+                let nulls_not_distinct = self.parse_nulls_distinct()?;
+                constraints.push(ColumnConstraint::Unique { nulls_not_distinct });
             } else if self.consume_keyword(Keyword::Check).is_ok() {
                 self.consume_token(&TokenType::LeftParen)?;
-                let expression = self.parse_expression()?;
+                let expression = self.parse_stored_expression("CHECK constraints")?;
                 self.consume_token(&TokenType::RightParen)?;
                 constraints.push(ColumnConstraint::Check(expression));
             } else if self.consume_keyword(Keyword::References).is_ok() {
@@ -1482,7 +2117,9 @@ impl Parser {
                 let column = self.consume_identifier()?;
                 self.consume_token(&TokenType::RightParen)?;
                 constraints.push(ColumnConstraint::References { table, column });
-            } else {
+            } else if self.consume_keyword(Keyword::Collate).is_ok() {
+                collation = Some(self.parse_collation_name()?);
+            } else if !self.skip_character_set()? {
                 break;
             }
         }
@@ -1493,30 +2130,115 @@ impl Parser {
             nullable,
             default_value,
             constraints,
+            collation,
+            max_length,
         })
     }
 
+    /// Parse a DEFAULT or CHECK expression, which is stored as SQL text in the
+    /// catalog and so cannot hold a subquery
+    fn parse_stored_expression(&mut self, clause: &str) -> PrismDBResult<Expression> {
+        let expression = self.parse_expression()?;
+        expression
+            .try_transform(&mut |node| match node {
+                Expression::Subquery(_) | Expression::InSubquery { .. } | Expression::Exists(_) => {
+                    Err(PrismDBError::Parse(format!("Subqueries are not allowed in {}", clause)))
+                }
+                _ => Ok(node),
+            })
+            .map(|_| expression)
+    }
+
+    /// Parse the name after COLLATE, an identifier or a string
+    fn parse_collation_name(&mut self) -> PrismDBResult<String> {
+        match &self.current_token().token_type {
+            TokenType::StringLiteral(_) => self.consume_string_literal(),
+            _ => self.consume_identifier_or_keyword(),
+        }
+    }
+
+    /// Skip a `CHARACTER SET name` or `CHARSET name` column clause, returning whether there was one
+    ///
+    /// Strings are always stored as UTF-8, so the clause is only accepted so that
+    /// DDL written for other databases runs; other character sets are ignored with a warning.
+    fn skip_character_set(&mut self) -> PrismDBResult<bool> {
+        let word = match &self.current_token().token_type {
+            TokenType::Identifier(word) => word.to_ascii_uppercase(),
+            _ => return Ok(false),
+        };
+        match word.as_str() {
+            "CHARSET" => self.position += 1,
+            "CHARACTER" if self.peek_token().token_type == TokenType::Keyword(Keyword::Set) => {
+                self.position += 2
+            }
+            _ => return Ok(false),
+        }
+
+        let charset = self.parse_collation_name()?;
+        let normalized = charset.to_ascii_lowercase().replace('-', "");
+        if !normalized.starts_with("utf8") && normalized != "ascii" {
+            log::warn!("Ignoring character set {}; strings are stored as UTF-8", charset);
+        }
+        Ok(true)
+    }
+
     /// Parse data type
     fn parse_data_type(&mut self) -> PrismDBResult<LogicalType> {
-        match &self.current_token().token_type {
+        let data_type = self.parse_base_data_type()?;
+        self.parse_list_suffixes(data_type)
+    }
+
+    /// Parse a column's data type, and the length of a `VARCHAR(n)`, which the type doesn't carry
+    fn parse_column_type(&mut self) -> PrismDBResult<(LogicalType, Option<usize>)> {
+        if self.consume_keyword(Keyword::Varchar).is_err() {
+            return Ok((self.parse_data_type()?, None));
+        }
+        let max_length = self.parse_type_length("VARCHAR")?;
+        Ok(match self.parse_list_suffixes(LogicalType::Varchar)? {
+            LogicalType::Varchar => (LogicalType::Varchar, max_length),
+            list => (list, None),
+        })
+    }
+
+    /// Wrap `data_type` in a list for each `[]` that follows; `INTEGER[]` is a list of INTEGER
+    fn parse_list_suffixes(&mut self, mut data_type: LogicalType) -> PrismDBResult<LogicalType> {
+        while self.current_token().token_type == TokenType::LeftBracket {
+            self.position += 1;
+            self.consume_token(&TokenType::RightBracket)?;
+            data_type = LogicalType::List(Box::new(data_type));
+        }
+        Ok(data_type)
+    }
+
+    /// Parse the optional `(n)` length after a string type name
+    fn parse_type_length(&mut self, type_name: &str) -> PrismDBResult<Option<usize>> {
+        if self.consume_token(&TokenType::LeftParen).is_err() {
+            return Ok(None);
+        }
+        let length_str = self.consume_numeric_literal()?;
+        let length = length_str
+            .parse::<usize>()
+            .ok()
+            .filter(|&length| length > 0)
+            .ok_or_else(|| {
+                PrismDBError::Parse(format!("Invalid length for {}: {}", type_name, length_str))
+            })?;
+        self.consume_token(&TokenType::RightParen)?;
+        Ok(Some(length))
+    }
+
+    /// Parse a data type name without any `[]` suffix
+    fn parse_base_data_type(&mut self) -> PrismDBResult<LogicalType> {
+        let token_type = self.current_token().token_type.clone();
+        let data_type = match token_type {
             TokenType::Keyword(Keyword::Integer) | TokenType::Keyword(Keyword::Int) => {
-                let _ = self.consume_keyword(Keyword::Integer);
-                Ok(LogicalType::Integer)
-            }
-            TokenType::Keyword(Keyword::BigInt) => {
-                let _ = self.consume_keyword(Keyword::BigInt);
-                Ok(LogicalType::BigInt)
-            }
-            TokenType::Keyword(Keyword::SmallInt) => {
-                let _ = self.consume_keyword(Keyword::SmallInt);
-                Ok(LogicalType::SmallInt)
-            }
-            TokenType::Keyword(Keyword::TinyInt) => {
-                let _ = self.consume_keyword(Keyword::TinyInt);
-                Ok(LogicalType::TinyInt)
+                LogicalType::Integer
             }
+            TokenType::Keyword(Keyword::BigInt) => LogicalType::BigInt,
+            TokenType::Keyword(Keyword::SmallInt) => LogicalType::SmallInt,
+            TokenType::Keyword(Keyword::TinyInt) => LogicalType::TinyInt,
             TokenType::Keyword(Keyword::Decimal) | TokenType::Keyword(Keyword::Numeric) => {
-                let _ = self.consume_keyword(Keyword::Decimal);
+                self.position += 1;
 
                 // Check if precision and scale are specified: DECIMAL(precision, scale)
                 if self.current_token().token_type == TokenType::LeftParen {
@@ -1539,66 +2261,92 @@ impl Parser {
 
                     self.consume_token(&TokenType::RightParen)?;
 
-                    Ok(LogicalType::Decimal { precision, scale })
+                    return Ok(LogicalType::Decimal { precision, scale });
                 } else {
                     // Default to DECIMAL(10, 2) if no parameters specified
-                    Ok(LogicalType::Decimal {
+                    return Ok(LogicalType::Decimal {
                         precision: 10,
                         scale: 2,
-                    })
+                    });
                 }
             }
             TokenType::Keyword(Keyword::Real) | TokenType::Keyword(Keyword::Float) => {
-                let _ = self.consume_keyword(Keyword::Real);
-                Ok(LogicalType::Float)
-            }
-            TokenType::Keyword(Keyword::Double) => {
-                let _ = self.consume_keyword(Keyword::Double);
-                Ok(LogicalType::Double)
+                LogicalType::Float
             }
+            TokenType::Keyword(Keyword::Double) => LogicalType::Double,
             TokenType::Keyword(Keyword::Varchar) => {
-                let _ = self.consume_keyword(Keyword::Varchar);
-                Ok(LogicalType::Varchar)
+                self.position += 1;
+                // Outside column definitions the length of VARCHAR(n) is accepted and dropped
+                self.parse_type_length("VARCHAR")?;
+                return Ok(LogicalType::Varchar);
             }
             TokenType::Keyword(Keyword::Char) => {
-                let _ = self.consume_keyword(Keyword::Char);
-                Ok(LogicalType::Char { length: 1 })
-            }
-            TokenType::Keyword(Keyword::Text) => {
-                let _ = self.consume_keyword(Keyword::Text);
-                Ok(LogicalType::Text)
-            }
-            TokenType::Keyword(Keyword::Blob) => {
-                let _ = self.consume_keyword(Keyword::Blob);
-                Ok(LogicalType::Blob)
+                self.position += 1;
+                let length = self.parse_type_length("CHAR")?.unwrap_or(1);
+                return Ok(LogicalType::Char { length });
             }
+            TokenType::Keyword(Keyword::Text) => LogicalType::Text,
+            TokenType::Keyword(Keyword::Blob) => LogicalType::Blob,
             TokenType::Keyword(Keyword::Boolean) | TokenType::Keyword(Keyword::Bool) => {
-                let _ = self.consume_keyword(Keyword::Boolean);
-                Ok(LogicalType::Boolean)
-            }
-            TokenType::Keyword(Keyword::Date) => {
-                let _ = self.consume_keyword(Keyword::Date);
-                Ok(LogicalType::Date)
+                LogicalType::Boolean
             }
-            TokenType::Keyword(Keyword::Time) => {
-                let _ = self.consume_keyword(Keyword::Time);
-                Ok(LogicalType::Time)
+            TokenType::Keyword(Keyword::Date) => LogicalType::Date,
+            TokenType::Keyword(Keyword::Time) => LogicalType::Time,
+            TokenType::Keyword(Keyword::Timestamp) | TokenType::Keyword(Keyword::DateTime) => {
+                LogicalType::Timestamp
             }
-            TokenType::Keyword(Keyword::Timestamp) => {
-                let _ = self.consume_keyword(Keyword::Timestamp);
-                Ok(LogicalType::Timestamp)
+            TokenType::Keyword(Keyword::Interval) => LogicalType::Interval,
+            TokenType::Keyword(Keyword::Map) => {
+                self.position += 1;
+                self.consume_token(&TokenType::LeftParen)?;
+                let key_type = self.parse_data_type()?;
+                self.consume_token(&TokenType::Comma)?;
+                let value_type = self.parse_data_type()?;
+                self.consume_token(&TokenType::RightParen)?;
+                return Ok(LogicalType::Map {
+                    key_type: Box::new(key_type),
+                    value_type: Box::new(value_type),
+                });
+            }
+            TokenType::Identifier(ref name) => match name.to_uppercase().as_str() {
+                "HUGEINT" => LogicalType::HugeInt,
+                "UUID" => LogicalType::UUID,
+                "JSON" => LogicalType::JSON,
+                // Any other name refers to a type created with CREATE TYPE; its labels
+                // are looked up when the statement runs
+                _ => LogicalType::Enum(Arc::new(EnumType::new(name.clone(), Vec::new()))),
+            },
+            _ => {
+                return Err(PrismDBError::Parse(format!(
+                    "Unknown data type: {}",
+                    self.current_token()
+                )))
             }
-            _ => Err(PrismDBError::Parse(format!(
-                "Unknown data type: {:?}",
-                self.current_token()
-            ))),
+        };
+
+        self.position += 1;
+        Ok(data_type)
+    }
+
+    /// Parse the optional `NULLS [NOT] DISTINCT` after UNIQUE or PRIMARY KEY,
+    /// returning whether NULLs count as equal
+    ///
+    /// Primary key columns hold no NULLs, so there the modifier is accepted
+    /// for compatibility and changes nothing.
+    fn parse_nulls_distinct(&mut self) -> PrismDBResult<bool> {
+        if self.consume_keyword(Keyword::Nulls).is_err() {
+            return Ok(false);
         }
+        let not_distinct = self.consume_keyword(Keyword::Not).is_ok();
+        self.consume_keyword(Keyword::Distinct)?;
+        Ok(not_distinct)
     }
 
     /// Parse table constraint
     fn parse_table_constraint(&mut self) -> PrismDBResult<TableConstraint> {
         if self.consume_keyword(Keyword::Primary).is_ok() {
             self.consume_keyword(Keyword::Key)?;
+            self.parse_nulls_distinct()?;
             self.consume_token(&TokenType::LeftParen)?;
             let mut columns = Vec::new();
             loop {
@@ -1610,6 +2358,7 @@ impl Parser {
             self.consume_token(&TokenType::RightParen)?;
             Ok(TableConstraint::PrimaryKey { columns })
         } else if self.consume_keyword(Keyword::Unique).is_ok() {
+            let nulls_not_distinct = self.parse_nulls_distinct()?;
             self.consume_token(&TokenType::LeftParen)?;
             let mut columns = Vec::new();
             loop {
@@ -1622,6 +2371,7 @@ impl Parser {
             Ok(TableConstraint::Unique {
                 columns,
                 name: None,
+                nulls_not_distinct,
             })
         } else if self.consume_keyword(Keyword::Foreign).is_ok() {
             self.consume_keyword(Keyword::Key)?;
@@ -1653,7 +2403,7 @@ impl Parser {
             })
         } else if self.consume_keyword(Keyword::Check).is_ok() {
             self.consume_token(&TokenType::LeftParen)?;
-            let expression = self.parse_expression()?;
+            let expression = self.parse_stored_expression("CHECK constraints")?;
             self.consume_token(&TokenType::RightParen)?;
             Ok(TableConstraint::Check {
                 expression,
@@ -1681,8 +2431,16 @@ impl Parser {
                 let index = self.parse_drop_index_statement()?;
                 Ok(Statement::DropIndex(index))
             }
+            TokenType::Identifier(word) if word.eq_ignore_ascii_case("FUNCTION") => {
+                let function = self.parse_drop_function_statement()?;
+                Ok(Statement::DropFunction(function))
+            }
+            TokenType::Keyword(Keyword::Schema) => {
+                let schema = self.parse_drop_schema_statement()?;
+                Ok(Statement::DropSchema(schema))
+            }
             _ => Err(PrismDBError::Parse(
-                "Expected TABLE, VIEW, or INDEX after DROP".to_string(),
+                "Expected TABLE, VIEW, INDEX, FUNCTION, or SCHEMA after DROP".to_string(),
             )),
         }
     }
@@ -1719,12 +2477,58 @@ impl Parser {
         })
     }
 
+    /// Parse DROP FUNCTION [IF EXISTS] name
+    fn parse_drop_function_statement(&mut self) -> PrismDBResult<DropFunctionStatement> {
+        self.consume_identifier()?; // FUNCTION
+        let if_exists = self.consume_keyword(Keyword::If).is_ok()
+            && self.consume_keyword(Keyword::Exists).is_ok();
+        let function_name = self.consume_identifier()?;
+
+        Ok(DropFunctionStatement {
+            function_name,
+            if_exists,
+        })
+    }
+
+    /// Parse CREATE SCHEMA [IF NOT EXISTS] name
+    fn parse_create_schema_statement(&mut self) -> PrismDBResult<CreateSchemaStatement> {
+        self.consume_keyword(Keyword::Schema)?;
+        let if_not_exists = self.consume_keyword(Keyword::If).is_ok()
+            && self.consume_keyword(Keyword::Not).is_ok()
+            && self.consume_keyword(Keyword::Exists).is_ok();
+        let schema_name = self.consume_identifier()?;
+
+        Ok(CreateSchemaStatement {
+            schema_name,
+            if_not_exists,
+        })
+    }
+
+    /// Parse DROP SCHEMA [IF EXISTS] name [CASCADE | RESTRICT]
+    fn parse_drop_schema_statement(&mut self) -> PrismDBResult<DropSchemaStatement> {
+        self.consume_keyword(Keyword::Schema)?;
+        let if_exists = self.consume_keyword(Keyword::If).is_ok()
+            && self.consume_keyword(Keyword::Exists).is_ok();
+        let schema_name = self.consume_identifier()?;
+        let cascade = self.consume_keyword(Keyword::Cascade).is_ok();
+        if !cascade {
+            let _ = self.consume_keyword(Keyword::Restrict);
+        }
+
+        Ok(DropSchemaStatement {
+            schema_name,
+            if_exists,
+            cascade,
+        })
+    }
+
     /// Parse DROP INDEX statement
     fn parse_drop_index_statement(&mut self) -> PrismDBResult<DropIndexStatement> {
+        self.consume_keyword(Keyword::Index)?;
+
         let if_exists = self.consume_keyword(Keyword::If).is_ok()
             && self.consume_keyword(Keyword::Exists).is_ok();
 
-        self.consume_keyword(Keyword::Index)?;
         let index_name = self.consume_identifier()?;
 
         Ok(DropIndexStatement {
@@ -1853,12 +2657,12 @@ impl Parser {
     fn parse_create_index_statement(&mut self) -> PrismDBResult<CreateIndexStatement> {
         let unique = self.consume_keyword(Keyword::Unique).is_ok();
 
+        self.consume_keyword(Keyword::Index)?;
+
         let if_not_exists = self.consume_keyword(Keyword::If).is_ok()
             && self.consume_keyword(Keyword::Not).is_ok()
             && self.consume_keyword(Keyword::Exists).is_ok();
 
-        self.consume_keyword(Keyword::Index)?;
-
         let index_name = self.consume_identifier()?;
 
         self.consume_keyword(Keyword::On)?;
@@ -1893,6 +2697,8 @@ impl Parser {
                     Some(TransactionMode::ReadOnly)
                 } else if self.consume_keyword(Keyword::Write).is_ok() {
                     Some(TransactionMode::ReadWrite)
+                } else if self.consume_keyword(Keyword::Committed).is_ok() {
+                    Some(TransactionMode::ReadCommitted)
                 } else {
                     None
                 }
@@ -1901,9 +2707,6 @@ impl Parser {
             } else if self.consume_keyword(Keyword::Repeatable).is_ok() {
                 self.consume_keyword(Keyword::Read)?;
                 Some(TransactionMode::RepeatableRead)
-            } else if self.consume_keyword(Keyword::Read).is_ok() {
-                self.consume_keyword(Keyword::Committed)?;
-                Some(TransactionMode::ReadCommitted)
             } else {
                 None
             }
@@ -1944,8 +2747,49 @@ impl Parser {
     fn parse_explain_statement(&mut self) -> PrismDBResult<ExplainStatement> {
         self.consume_keyword(Keyword::Explain)?;
 
-        let analyze = self.consume_keyword(Keyword::Analyze).is_ok();
-        let verbose = self.consume_keyword(Keyword::Verbose).is_ok();
+        let mut analyze = false;
+        let mut verbose = false;
+        let mut format = ExplainFormat::Text;
+
+        // EXPLAIN (FORMAT JSON, VERBOSE) ..., unless the parenthesis opens the query
+        let opens_query = matches!(
+            self.peek_token().token_type,
+            TokenType::Keyword(Keyword::Select) | TokenType::Keyword(Keyword::With)
+        );
+        if self.current_token().token_type == TokenType::LeftParen && !opens_query {
+            self.position += 1;
+            loop {
+                let option = self.parse_copy_option_word()?.ok_or_else(|| {
+                    PrismDBError::Parse(format!(
+                        "Expected option name in EXPLAIN, got {}",
+                        self.current_token()
+                    ))
+                })?;
+                let value = self.parse_copy_option_word()?.map(|value| value.to_uppercase());
+                match (option.to_uppercase().as_str(), value.as_deref()) {
+                    ("FORMAT", Some("TEXT")) => format = ExplainFormat::Text,
+                    ("FORMAT", Some("JSON")) => format = ExplainFormat::Json,
+                    ("ANALYZE", None | Some("TRUE") | Some("ON")) => analyze = true,
+                    ("ANALYZE", Some("FALSE") | Some("OFF")) => analyze = false,
+                    ("VERBOSE", None | Some("TRUE") | Some("ON")) => verbose = true,
+                    ("VERBOSE", Some("FALSE") | Some("OFF")) => verbose = false,
+                    (option, value) => {
+                        return Err(PrismDBError::Parse(format!(
+                            "Invalid EXPLAIN option: {} {}",
+                            option,
+                            value.unwrap_or_default()
+                        )))
+                    }
+                }
+                if self.consume_token(&TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+            self.consume_token(&TokenType::RightParen)?;
+        } else {
+            analyze = self.consume_keyword(Keyword::Analyze).is_ok();
+            verbose = self.consume_keyword(Keyword::Verbose).is_ok();
+        }
 
         let statement = self.parse_statement_internal()?;
 
@@ -1953,6 +2797,36 @@ impl Parser {
             statement: Box::new(statement),
             analyze,
             verbose,
+            format,
+        })
+    }
+
+    /// Parse DESCRIBE / DESC statement
+    fn parse_describe_statement(&mut self) -> PrismDBResult<DescribeStatement> {
+        // DESCRIBE and DESC are interchangeable
+        self.position += 1;
+        match self.current_token().token_type {
+            TokenType::Keyword(Keyword::Select) | TokenType::Keyword(Keyword::With) => {
+                Ok(DescribeStatement::Query(Box::new(self.parse_query()?)))
+            }
+            _ => {
+                let table_name = self.consume_identifier()?;
+                Ok(DescribeStatement::Table { table_name })
+            }
+        }
+    }
+
+    /// Parse SUMMARIZE statement
+    fn parse_summarize_statement(&mut self) -> PrismDBResult<SummarizeStatement> {
+        self.consume_keyword(Keyword::Summarize)?;
+        let query = match self.current_token().token_type {
+            TokenType::Keyword(Keyword::Select) | TokenType::Keyword(Keyword::With) => {
+                self.parse_query()?
+            }
+            _ => Self::select_star_from(self.consume_identifier()?),
+        };
+        Ok(SummarizeStatement {
+            query: Box::new(query),
         })
     }
 
@@ -1992,8 +2866,22 @@ impl Parser {
                 let _ = self.consume_keyword(Keyword::Schemas);
                 Ok(ShowStatement::Schemas)
             }
+            TokenType::Keyword(Keyword::Create) => {
+                let _ = self.consume_keyword(Keyword::Create);
+                self.consume_keyword(Keyword::Table)?;
+                let table = self.consume_identifier()?;
+                Ok(ShowStatement::CreateTable { table })
+            }
+            TokenType::Identifier(word) if word.eq_ignore_ascii_case("FUNCTIONS") => {
+                self.position += 1;
+                Ok(ShowStatement::Functions)
+            }
+            TokenType::Identifier(_) => {
+                let name = self.consume_identifier()?;
+                Ok(ShowStatement::Variable { name })
+            }
             _ => Err(PrismDBError::Parse(
-                "Expected TABLES, COLUMNS, INDEXES, VARIABLES, DATABASES, or SCHEMAS after SHOW"
+                "Expected TABLES, COLUMNS, INDEXES, VARIABLES, DATABASES, SCHEMAS, CREATE TABLE, FUNCTIONS or a setting name after SHOW"
                     .to_string(),
             )),
         }
@@ -2020,7 +2908,12 @@ impl Parser {
             self.consume_keyword(Keyword::To)?;
         }
 
-        // Parse the value
+        let value = self.parse_set_value(&format!("SET {}", variable))?;
+        Ok(SetStatement { variable, value })
+    }
+
+    /// Parse the value of a SET or PRAGMA; `context` names the statement in errors
+    fn parse_set_value(&mut self, context: &str) -> PrismDBResult<SetValue> {
         let value = match &self.current_token().token_type {
             TokenType::StringLiteral(s) => {
                 let val = s.clone();
@@ -2052,14 +2945,129 @@ impl Parser {
             }
             _ => {
                 return Err(PrismDBError::Parse(format!(
-                    "Expected value after SET {}, got {:?}",
-                    variable,
+                    "Expected value after {}, got {}",
+                    context,
                     self.current_token()
                 )));
             }
         };
+        Ok(value)
+    }
 
-        Ok(SetStatement { variable, value })
+    /// Parse PRAGMA statement
+    fn parse_pragma_statement(&mut self) -> PrismDBResult<PragmaStatement> {
+        self.consume_keyword(Keyword::Pragma)?;
+        let name = self.consume_identifier()?;
+        let context = format!("PRAGMA {}", name);
+
+        let value = if self.consume_token(&TokenType::Equals).is_ok() {
+            Some(self.parse_set_value(&context)?)
+        } else if self.consume_token(&TokenType::LeftParen).is_ok() {
+            let value = self.parse_set_value(&context)?;
+            self.consume_token(&TokenType::RightParen)?;
+            Some(value)
+        } else {
+            None
+        };
+
+        Ok(PragmaStatement { name, value })
+    }
+
+    /// Parse VACUUM statement: `VACUUM [table]`
+    fn parse_vacuum_statement(&mut self) -> PrismDBResult<VacuumStatement> {
+        self.consume_keyword(Keyword::Vacuum)?;
+        let table_name = match self.current_token().token_type {
+            TokenType::Semicolon | TokenType::EOF => None,
+            _ => Some(self.consume_identifier()?),
+        };
+
+        Ok(VacuumStatement { table_name })
+    }
+
+    /// Parse ANALYZE statement: `ANALYZE [table]`
+    fn parse_analyze_statement(&mut self) -> PrismDBResult<AnalyzeStatement> {
+        self.consume_keyword(Keyword::Analyze)?;
+        let table_name = match self.current_token().token_type {
+            TokenType::Semicolon | TokenType::EOF => None,
+            _ => Some(self.consume_identifier()?),
+        };
+
+        Ok(AnalyzeStatement { table_name })
+    }
+
+    /// Parse `FUNCTION name(param type, ...) [RETURNS type] AS (expression)`
+    fn parse_create_function_statement(
+        &mut self,
+        or_replace: bool,
+    ) -> PrismDBResult<CreateFunctionStatement> {
+        self.consume_identifier()?; // FUNCTION
+        let function_name = self.consume_identifier()?;
+
+        self.consume_token(&TokenType::LeftParen)?;
+        let mut parameters: Vec<(String, LogicalType)> = Vec::new();
+        if self.current_token().token_type != TokenType::RightParen {
+            loop {
+                let name = self.consume_identifier()?;
+                if parameters.iter().any(|(existing, _)| existing.eq_ignore_ascii_case(&name)) {
+                    return Err(PrismDBError::Parse(format!(
+                        "Duplicate parameter '{}' in function {}",
+                        name, function_name
+                    )));
+                }
+                let data_type = self.parse_data_type()?;
+                parameters.push((name, data_type));
+                if self.consume_token(&TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+        }
+        self.consume_token(&TokenType::RightParen)?;
+
+        let return_type = match &self.current_token().token_type {
+            TokenType::Identifier(word) if word.eq_ignore_ascii_case("RETURNS") => {
+                self.position += 1;
+                Some(self.parse_data_type()?)
+            }
+            _ => None,
+        };
+
+        self.consume_keyword(Keyword::As)?;
+        let body = self.parse_expression()?;
+
+        Ok(CreateFunctionStatement {
+            function_name,
+            parameters,
+            return_type,
+            body,
+            or_replace,
+        })
+    }
+
+    /// Parse CREATE TYPE name AS ENUM ('label', ...)
+    fn parse_create_type_statement(&mut self) -> PrismDBResult<CreateTypeStatement> {
+        self.consume_identifier()?; // TYPE
+        let type_name = self.consume_identifier()?;
+        self.consume_keyword(Keyword::As)?;
+        self.consume_keyword(Keyword::Enum)?;
+        self.consume_token(&TokenType::LeftParen)?;
+
+        let mut values: Vec<String> = Vec::new();
+        loop {
+            let label = self.consume_string_literal()?;
+            if values.contains(&label) {
+                return Err(PrismDBError::Parse(format!(
+                    "Duplicate value '{}' in enum type {}",
+                    label, type_name
+                )));
+            }
+            values.push(label);
+            if self.consume_token(&TokenType::Comma).is_err() {
+                break;
+            }
+        }
+        self.consume_token(&TokenType::RightParen)?;
+
+        Ok(CreateTypeStatement { type_name, values })
     }
 
     fn parse_create_secret_body(&mut self, or_replace: bool) -> PrismDBResult<CreateSecretStatement> {
@@ -2089,7 +3097,7 @@ impl Parser {
                 }
                 _ => {
                     return Err(PrismDBError::Parse(format!(
-                        "Expected option key in CREATE SECRET, got {:?}",
+                        "Expected option key in CREATE SECRET, got {}",
                         self.current_token()
                     )));
                 }
@@ -2117,7 +3125,7 @@ impl Parser {
                 }
                 _ => {
                     return Err(PrismDBError::Parse(format!(
-                        "Expected option value in CREATE SECRET, got {:?}",
+                        "Expected option value in CREATE SECRET, got {}",
                         self.current_token()
                     )));
                 }
@@ -2147,6 +3155,160 @@ impl Parser {
         })
     }
 
+    /// `SELECT * FROM name`, for statements that take a bare table name
+    fn select_star_from(name: String) -> SelectStatement {
+        Self::select_star(TableReference::Table { name, alias: None })
+    }
+
+    /// `SELECT * FROM <from>`
+    fn select_star(from: TableReference) -> SelectStatement {
+        SelectStatement {
+            with_clause: None,
+            distinct: false,
+            select_list: vec![SelectItem::Wildcard(WildcardOptions::default())],
+            from: Some(from),
+            where_clause: None,
+            group_by: Vec::new(),
+            group_by_all: false,
+            having: None,
+            qualify: None,
+            order_by: Vec::new(),
+            order_by_all: None,
+            limit: None,
+            offset: None,
+            set_operations: Vec::new(),
+        }
+    }
+
+    /// Parse the rows after VALUES: `(expr, ...), (expr, ...)`
+    fn parse_values_rows(&mut self) -> PrismDBResult<Vec<Vec<Expression>>> {
+        let mut rows = Vec::new();
+        loop {
+            self.consume_token(&TokenType::LeftParen)?;
+            let mut row = Vec::new();
+            loop {
+                row.push(self.parse_expression()?);
+                if self.consume_token(&TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+            self.consume_token(&TokenType::RightParen)?;
+            rows.push(row);
+
+            if self.consume_token(&TokenType::Comma).is_err() {
+                break;
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Parse COPY statement: COPY { table | (query) } TO 'target' [( option [value], ... )]
+    fn parse_copy_statement(&mut self) -> PrismDBResult<Statement> {
+        self.consume_keyword(Keyword::Copy)?;
+
+        let query = if self.consume_token(&TokenType::LeftParen).is_ok() {
+            let query = self.parse_query()?;
+            self.consume_token(&TokenType::RightParen)?;
+            query
+        } else {
+            let table_name = self.consume_identifier()?;
+            if self.consume_keyword(Keyword::From).is_ok() {
+                let source = self.parse_copy_file_name("FROM")?;
+                return Ok(Statement::CopyFrom(CopyFromStatement {
+                    table_name,
+                    source,
+                    options: self.parse_copy_options()?,
+                }));
+            }
+            Self::select_star_from(table_name)
+        };
+
+        self.consume_keyword(Keyword::To)?;
+        let target = self.parse_copy_file_name("TO")?;
+        Ok(Statement::Copy(CopyStatement {
+            query: Box::new(query),
+            target,
+            options: self.parse_copy_options()?,
+        }))
+    }
+
+    /// The quoted file name after COPY ... TO or COPY ... FROM
+    fn parse_copy_file_name(&mut self, direction: &str) -> PrismDBResult<String> {
+        match &self.current_token().token_type {
+            TokenType::StringLiteral(s) => {
+                let file_name = s.clone();
+                self.position += 1;
+                Ok(file_name)
+            }
+            _ => Err(PrismDBError::Parse(format!(
+                "Expected file name after COPY ... {}, got {}",
+                direction,
+                self.current_token()
+            ))),
+        }
+    }
+
+    /// The parenthesized options that end a COPY statement, if any
+    fn parse_copy_options(&mut self) -> PrismDBResult<HashMap<String, Vec<String>>> {
+        let mut options = HashMap::new();
+        if self.consume_token(&TokenType::LeftParen).is_ok() {
+            while self.current_token().token_type != TokenType::RightParen {
+                let key = self.parse_copy_option_word()?.ok_or_else(|| {
+                    PrismDBError::Parse(format!(
+                        "Expected option name in COPY, got {}",
+                        self.current_token()
+                    ))
+                })?;
+
+                // A value, a parenthesized list of values, or none (a flag)
+                let mut values = Vec::new();
+                if self.consume_token(&TokenType::LeftParen).is_ok() {
+                    loop {
+                        let value = self.parse_copy_option_word()?.ok_or_else(|| {
+                            PrismDBError::Parse(format!(
+                                "Expected value for COPY option {}, got {}",
+                                key,
+                                self.current_token()
+                            ))
+                        })?;
+                        values.push(value);
+                        if self.consume_token(&TokenType::Comma).is_err() {
+                            break;
+                        }
+                    }
+                    self.consume_token(&TokenType::RightParen)?;
+                } else if let Some(value) = self.parse_copy_option_word()? {
+                    values.push(value);
+                } else {
+                    values.push("true".to_string());
+                }
+                options.insert(key.to_lowercase(), values);
+
+                if self.consume_token(&TokenType::Comma).is_err() {
+                    break;
+                }
+            }
+            self.consume_token(&TokenType::RightParen)?;
+        }
+        Ok(options)
+    }
+
+    /// Consume a COPY or EXPLAIN option name or value: a word, string or number
+    fn parse_copy_option_word(&mut self) -> PrismDBResult<Option<String>> {
+        let word = match &self.current_token().token_type {
+            TokenType::Identifier(s) | TokenType::StringLiteral(s) | TokenType::NumericLiteral(s) => {
+                s.clone()
+            }
+            TokenType::BooleanLiteral(b) => b.to_string(),
+            TokenType::Keyword(Keyword::True) => "true".to_string(),
+            TokenType::Keyword(Keyword::False) => "false".to_string(),
+            TokenType::Keyword(keyword) => keyword.to_string(),
+            _ => return Ok(None),
+        };
+        self.position += 1;
+        Ok(Some(word))
+    }
+
     // Helper methods
 
     fn current_token(&self) -> &Token {
@@ -2168,9 +3330,9 @@ impl Parser {
             Ok(token)
         } else {
             Err(PrismDBError::Parse(format!(
-                "Expected token '{:?}', found '{}'",
+                "Expected token '{:?}', found {}",
                 token_type,
-                self.current_token().text
+                self.current_token()
             )))
         }
     }
@@ -2182,9 +3344,9 @@ impl Parser {
             Ok(token)
         } else {
             Err(PrismDBError::Parse(format!(
-                "Expected keyword '{}', found '{}'",
+                "Expected keyword '{}', found {}",
                 keyword,
-                self.current_token().text
+                self.current_token()
             )))
         }
     }
@@ -2196,14 +3358,32 @@ impl Parser {
                 self.position += 1;
                 Ok(name)
             }
-            TokenType::Keyword(_) => {
-                // Allow keywords to be used as identifiers in unambiguous contexts
-                let name = self.current_token().text.clone();
+            TokenType::Keyword(keyword) if !keyword.is_reserved() => {
+                let name = self.identifier_case.fold(&self.current_token().text);
                 self.position += 1;
                 Ok(name)
             }
+            TokenType::Keyword(keyword) => Err(PrismDBError::Parse(format!(
+                "Expected identifier, got reserved keyword {}; quote it to use it as a name",
+                keyword
+            ))),
+            _ => Err(PrismDBError::Parse(format!(
+                "Expected identifier, got: {}",
+                self.current_token()
+            ))),
+        }
+    }
+
+    /// Consume a non-reserved word such as MATCHED, which the tokenizer leaves an identifier
+    fn consume_word(&mut self, word: &str) -> PrismDBResult<()> {
+        match &self.current_token().token_type {
+            TokenType::Identifier(name) if name.eq_ignore_ascii_case(word) => {
+                self.position += 1;
+                Ok(())
+            }
             _ => Err(PrismDBError::Parse(format!(
-                "Expected identifier, got: {:?}",
+                "Expected {}, found {}",
+                word,
                 self.current_token()
             ))),
         }
@@ -2219,12 +3399,12 @@ impl Parser {
             }
             TokenType::Keyword(_) => {
                 // Allow keywords to be used as identifiers (for aliases)
-                let name = self.current_token().text.clone();
+                let name = self.identifier_case.fold(&self.current_token().text);
                 self.position += 1;
                 Ok(name)
             }
             _ => Err(PrismDBError::Parse(format!(
-                "Expected identifier or keyword, got: {:?}",
+                "Expected identifier or keyword, got: {}",
                 self.current_token()
             ))),
         }
@@ -2250,7 +3430,7 @@ impl Parser {
                 Ok(value)
             }
             _ => Err(PrismDBError::Parse(format!(
-                "Expected string literal, got: {:?}",
+                "Expected string literal, got: {}",
                 self.current_token()
             ))),
         }
@@ -2264,7 +3444,7 @@ impl Parser {
                 Ok(value)
             }
             _ => Err(PrismDBError::Parse(format!(
-                "Expected numeric literal, got: {:?}",
+                "Expected numeric literal, got: {}",
                 self.current_token()
             ))),
         }
@@ -2286,6 +3466,8 @@ impl Parser {
                 | TokenType::Keyword(Keyword::Right)
                 | TokenType::Keyword(Keyword::Full)
                 | TokenType::Keyword(Keyword::Cross)
+                | TokenType::Keyword(Keyword::Natural)
+                | TokenType::Keyword(Keyword::Asof)
         )
     }
 
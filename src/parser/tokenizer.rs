@@ -13,6 +13,7 @@ pub enum TokenType {
     // Literals
     Identifier(String),
     StringLiteral(String),
+    BlobLiteral(Vec<u8>),
     NumericLiteral(String),
     BooleanLiteral(bool),
     NullLiteral,
@@ -25,8 +26,10 @@ pub enum TokenType {
     Minus,              // -
     Multiply,           // *
     Divide,             // /
+    IntegerDivide,      // //
     Modulo,             // %
     Equals,             // =
+    FatArrow,           // =>
     NotEquals,          // != or <>
     LessThan,           // <
     GreaterThan,        // >
@@ -43,6 +46,10 @@ pub enum TokenType {
     // Punctuation
     LeftParen,    // (
     RightParen,   // )
+    LeftBracket,  // [
+    RightBracket, // ]
+    LeftBrace,    // {
+    RightBrace,   // }
     Comma,        // ,
     Dot,          // .
     Semicolon,    // ;
@@ -53,6 +60,8 @@ pub enum TokenType {
     Star, // *
     EOF,
     Whitespace,
+    /// A comment before the first token, kept only when the tokenizer retains comments
+    Comment(String),
 }
 
 /// SQL token with position information
@@ -64,6 +73,23 @@ pub struct Token {
     pub column: usize,
 }
 
+impl std::fmt::Display for Token {
+    /// The token as written, for error messages
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.token_type {
+            TokenType::EOF => write!(f, "end of input"),
+            TokenType::Identifier(text) | TokenType::StringLiteral(text) | TokenType::NumericLiteral(text) => {
+                write!(f, "'{}'", text)
+            }
+            TokenType::Keyword(keyword) => write!(f, "'{}'", keyword),
+            TokenType::BooleanLiteral(value) => write!(f, "'{}'", value),
+            TokenType::NullLiteral => write!(f, "'NULL'"),
+            _ if !self.text.is_empty() => write!(f, "'{}'", self.text),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
 impl Token {
     pub fn new(token_type: TokenType, text: String, line: usize, column: usize) -> Self {
         Self {
@@ -83,9 +109,36 @@ impl Token {
     }
 }
 
+/// How unquoted identifiers are normalized; quoted identifiers are always
+/// kept exactly as written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdentifierCase {
+    /// Fold to lowercase, so `Users` and `users` name the same table
+    #[default]
+    Lower,
+    /// Fold to uppercase
+    Upper,
+    /// Keep as written, so names match case-sensitively
+    Preserve,
+}
+
+impl IdentifierCase {
+    /// Normalize an unquoted identifier
+    pub fn fold(&self, name: &str) -> String {
+        match self {
+            IdentifierCase::Lower => name.to_lowercase(),
+            IdentifierCase::Upper => name.to_uppercase(),
+            IdentifierCase::Preserve => name.to_string(),
+        }
+    }
+}
+
 /// SQL tokenizer
 pub struct Tokenizer {
     keywords: std::collections::HashMap<String, Keyword>,
+    identifier_case: IdentifierCase,
+    /// Whether comments before the first token become `Comment` tokens
+    retain_comments: bool,
 }
 
 impl Tokenizer {
@@ -97,12 +150,31 @@ impl Tokenizer {
             keywords.insert(keyword.to_string().to_uppercase(), *keyword);
         }
 
-        Self { keywords }
+        Self {
+            keywords,
+            identifier_case: IdentifierCase::default(),
+            retain_comments: false,
+        }
+    }
+
+    /// Normalize unquoted identifiers with `identifier_case`
+    pub fn with_identifier_case(mut self, identifier_case: IdentifierCase) -> Self {
+        self.identifier_case = identifier_case;
+        self
+    }
+
+    /// Keep the comments written before the first token as `Comment` tokens
+    ///
+    /// Comments are otherwise skipped like whitespace, as are those after the
+    /// first token either way.
+    pub fn with_comments(mut self, retain_comments: bool) -> Self {
+        self.retain_comments = retain_comments;
+        self
     }
 
     /// Tokenize a SQL string into tokens
     pub fn tokenize(&self, sql: &str) -> PrismDBResult<Vec<Token>> {
-        let mut tokens = Vec::new();
+        let mut tokens: Vec<Token> = Vec::new();
         let mut chars = sql.chars().peekable();
         let mut line = 1;
         let mut column = 1;
@@ -116,6 +188,22 @@ impl Tokenizer {
             let start_line = line;
             let start_column = column;
 
+            if Self::starts_comment(&chars) {
+                let text = self.consume_comment(&mut chars, &mut line, &mut column)?;
+                let leading = tokens
+                    .iter()
+                    .all(|token| matches!(token.token_type, TokenType::Comment(_)));
+                if self.retain_comments && leading {
+                    tokens.push(Token::new(
+                        TokenType::Comment(text),
+                        String::new(),
+                        start_line,
+                        start_column,
+                    ));
+                }
+                continue;
+            }
+
             match ch {
                 '\'' => {
                     let (text, new_line, new_column) =
@@ -142,6 +230,23 @@ impl Tokenizer {
                         start_column,
                     ));
                 }
+                // Hex blob literal: x'DEADBEEF'
+                'x' | 'X' if Self::starts_string_after_prefix(&chars) => {
+                    chars.next();
+                    let (text, new_line, new_column) =
+                        self.consume_string(&mut chars, line, column + 1)?;
+                    line = new_line;
+                    column = new_column;
+                    let bytes = hex::decode(&text).map_err(|_| {
+                        PrismDBError::Parse(format!("Invalid blob literal: x'{}'", text))
+                    })?;
+                    tokens.push(Token::new(
+                        TokenType::BlobLiteral(bytes),
+                        String::new(),
+                        start_line,
+                        start_column,
+                    ));
+                }
                 '0'..='9' => {
                     let (text, new_line, new_column) =
                         self.consume_number(&mut chars, line, column)?;
@@ -174,6 +279,46 @@ impl Tokenizer {
                         start_column,
                     ));
                 }
+                '[' => {
+                    chars.next();
+                    column += 1;
+                    tokens.push(Token::new(
+                        TokenType::LeftBracket,
+                        "[".to_string(),
+                        start_line,
+                        start_column,
+                    ));
+                }
+                ']' => {
+                    chars.next();
+                    column += 1;
+                    tokens.push(Token::new(
+                        TokenType::RightBracket,
+                        "]".to_string(),
+                        start_line,
+                        start_column,
+                    ));
+                }
+                '{' => {
+                    chars.next();
+                    column += 1;
+                    tokens.push(Token::new(
+                        TokenType::LeftBrace,
+                        "{".to_string(),
+                        start_line,
+                        start_column,
+                    ));
+                }
+                '}' => {
+                    chars.next();
+                    column += 1;
+                    tokens.push(Token::new(
+                        TokenType::RightBrace,
+                        "}".to_string(),
+                        start_line,
+                        start_column,
+                    ));
+                }
                 ',' => {
                     chars.next();
                     column += 1;
@@ -257,12 +402,23 @@ impl Tokenizer {
                 '/' => {
                     chars.next();
                     column += 1;
-                    tokens.push(Token::new(
-                        TokenType::Divide,
-                        "/".to_string(),
-                        start_line,
-                        start_column,
-                    ));
+                    if let Some(&'/') = chars.peek() {
+                        chars.next();
+                        column += 1;
+                        tokens.push(Token::new(
+                            TokenType::IntegerDivide,
+                            "//".to_string(),
+                            start_line,
+                            start_column,
+                        ));
+                    } else {
+                        tokens.push(Token::new(
+                            TokenType::Divide,
+                            "/".to_string(),
+                            start_line,
+                            start_column,
+                        ));
+                    }
                 }
                 '%' => {
                     chars.next();
@@ -277,12 +433,23 @@ impl Tokenizer {
                 '=' => {
                     chars.next();
                     column += 1;
-                    tokens.push(Token::new(
-                        TokenType::Equals,
-                        "=".to_string(),
-                        start_line,
-                        start_column,
-                    ));
+                    if let Some(&'>') = chars.peek() {
+                        chars.next();
+                        column += 1;
+                        tokens.push(Token::new(
+                            TokenType::FatArrow,
+                            "=>".to_string(),
+                            start_line,
+                            start_column,
+                        ));
+                    } else {
+                        tokens.push(Token::new(
+                            TokenType::Equals,
+                            "=".to_string(),
+                            start_line,
+                            start_column,
+                        ));
+                    }
                 }
                 '!' => {
                     chars.next();
@@ -368,7 +535,7 @@ impl Tokenizer {
                         ));
                     } else {
                         tokens.push(Token::new(
-                            TokenType::Identifier(text),
+                            TokenType::Identifier(self.identifier_case.fold(&text)),
                             String::new(),
                             start_line,
                             start_column,
@@ -408,6 +575,51 @@ impl Tokenizer {
         }
     }
 
+    /// Consume a `-- ...` or `/* ... */` comment, returning its text without
+    /// the markers
+    fn consume_comment(
+        &self,
+        chars: &mut Peekable<Chars>,
+        line: &mut usize,
+        column: &mut usize,
+    ) -> PrismDBResult<String> {
+        let block = chars.next() == Some('/');
+        chars.next();
+        *column += 2;
+        let mut text = String::new();
+        if !block {
+            while let Some(&ch) = chars.peek() {
+                if ch == '\n' {
+                    break;
+                }
+                chars.next();
+                *column += 1;
+                text.push(ch);
+            }
+            return Ok(text.trim().to_string());
+        }
+
+        let (start_line, start_column) = (*line, *column - 2);
+        while let Some(ch) = chars.next() {
+            if ch == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                *column += 2;
+                return Ok(text.trim().to_string());
+            }
+            if ch == '\n' {
+                *line += 1;
+                *column = 1;
+            } else {
+                *column += 1;
+            }
+            text.push(ch);
+        }
+        Err(PrismDBError::Parse(format!(
+            "Unterminated comment starting at line {}, column {}",
+            start_line, start_column
+        )))
+    }
+
     fn consume_string(
         &self,
         chars: &mut Peekable<Chars>,
@@ -438,7 +650,13 @@ impl Tokenizer {
             } else if ch == '\\' {
                 escaped = true;
             } else if ch == '\'' {
-                break;
+                // A doubled quote stands for one quote inside the string
+                if chars.peek() != Some(&'\'') {
+                    break;
+                }
+                chars.next();
+                column += 1;
+                result.push(ch);
             } else if ch == '\n' {
                 line += 1;
                 column = 1;
@@ -594,7 +812,20 @@ impl Tokenizer {
         Ok((result, line, column))
     }
 
-    fn is_identifier_start(&self, ch: char) -> bool {
+    /// Whether the character after the next one opens a string, as in x'...'
+    /// Whether the next characters open a `--` or `/*` comment
+    fn starts_comment(chars: &Peekable<Chars>) -> bool {
+        let mut ahead = chars.clone();
+        matches!((ahead.next(), ahead.peek()), (Some('-'), Some(&'-')) | (Some('/'), Some(&'*')))
+    }
+
+    fn starts_string_after_prefix(chars: &Peekable<Chars>) -> bool {
+        let mut ahead = chars.clone();
+        ahead.next();
+        ahead.peek() == Some(&'\'')
+    }
+
+        fn is_identifier_start(&self, ch: char) -> bool {
         ch.is_ascii_alphabetic() || ch == '_'
     }
 
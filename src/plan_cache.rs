@@ -0,0 +1,231 @@
+//! Prepared Plan Cache
+//!
+//! An optional LRU of optimized physical plans keyed by the shape of a
+//! SELECT: the statement with the literals its WHERE clause compares against
+//! replaced by parameters. Queries that differ only in those literals share
+//! one plan; on a hit the new literal values are handed to the plan's
+//! parameters at execution instead of parsing them into a fresh plan.
+//!
+//! Plans depend on the catalog and on settings but not on table contents, so
+//! the database clears the cache on DDL and SET rather than tracking tables.
+
+use crate::common::sync::MutexExt;
+use crate::database::ColumnMetadata;
+use crate::parser::ast::{BinaryOperator, Expression, LiteralValue, SelectStatement};
+use crate::planner::PhysicalPlan;
+use crate::types::{LogicalType, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Hit and miss counts of a plan cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlanCacheStats {
+    /// Lookups that reused a cached plan
+    pub hits: u64,
+    /// Lookups that had to plan the query
+    pub misses: u64,
+    /// Plans currently cached
+    pub entries: usize,
+}
+
+struct CacheEntry {
+    plan: PhysicalPlan,
+    columns: Vec<ColumnMetadata>,
+    /// Tick of the last lookup that returned this entry
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    tick: u64,
+    /// Number of times the cache was cleared
+    generation: u64,
+    stats: PlanCacheStats,
+}
+
+/// Bounded LRU of physical plans, shared by every session of a database
+pub struct PlanCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl PlanCache {
+    /// Create a cache holding at most `capacity` plans
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Replace the literals that the WHERE clause compares against with
+    /// parameters, returning the rewritten statement and the literals' values
+    /// in parameter order
+    ///
+    /// Only a literal on one side of a comparison or as a BETWEEN bound is
+    /// replaced; literals elsewhere, such as in the select list or a LIKE
+    /// pattern, stay part of the shape since planning may depend on them.
+    pub fn parameterize(select: &SelectStatement) -> (SelectStatement, Vec<Value>) {
+        let mut values = Vec::new();
+        let where_clause = select.where_clause.as_deref().map(|predicate| {
+            let rewritten = predicate.try_transform(&mut |node| {
+                Ok::<_, std::convert::Infallible>(Self::parameterize_node(node, &mut values))
+            });
+            match rewritten {
+                Ok(expr) => Box::new(expr),
+                Err(never) => match never {},
+            }
+        });
+        let shape = SelectStatement {
+            where_clause,
+            ..select.clone()
+        };
+        (shape, values)
+    }
+
+    fn parameterize_node(node: Expression, values: &mut Vec<Value>) -> Expression {
+        let mut parameter = |expr: Box<Expression>| match &*expr {
+            Expression::Literal(literal) => match Self::literal_value(literal) {
+                Some(value) => {
+                    values.push(value);
+                    Box::new(Expression::Parameter(values.len() - 1))
+                }
+                None => expr,
+            },
+            _ => expr,
+        };
+        match node {
+            Expression::Binary { left, operator, right } if Self::is_comparison(&operator) => {
+                match (&*left, &*right) {
+                    (Expression::Literal(_), Expression::Literal(_)) => {
+                        Expression::Binary { left, operator, right }
+                    }
+                    _ => Expression::Binary {
+                        left: parameter(left),
+                        operator,
+                        right: parameter(right),
+                    },
+                }
+            }
+            Expression::Between { expression, low, high, not }
+                if !matches!(*expression, Expression::Literal(_)) =>
+            {
+                Expression::Between {
+                    expression,
+                    low: parameter(low),
+                    high: parameter(high),
+                    not,
+                }
+            }
+            other => other,
+        }
+    }
+
+    fn is_comparison(operator: &BinaryOperator) -> bool {
+        matches!(
+            operator,
+            BinaryOperator::Equals
+                | BinaryOperator::NotEquals
+                | BinaryOperator::LessThan
+                | BinaryOperator::LessThanOrEqual
+                | BinaryOperator::GreaterThan
+                | BinaryOperator::GreaterThanOrEqual
+        )
+    }
+
+    /// Value a literal binds to, for the literal kinds that can be parameters
+    fn literal_value(literal: &LiteralValue) -> Option<Value> {
+        match literal {
+            LiteralValue::Boolean(b) => Some(Value::Boolean(*b)),
+            LiteralValue::Integer(i) => Some(match i32::try_from(*i) {
+                Ok(i) => Value::Integer(i),
+                Err(_) => Value::BigInt(*i),
+            }),
+            LiteralValue::Float(f) => Some(Value::Double(*f)),
+            LiteralValue::String(s) => Some(Value::Varchar(s.clone())),
+            _ => None,
+        }
+    }
+
+    /// Cache key of a parameterized statement
+    ///
+    /// The parameters' types are part of the key: a plan is bound for them.
+    pub fn key(shape: &SelectStatement, parameter_types: &[LogicalType]) -> String {
+        format!("{:?} {:?}", shape, parameter_types)
+    }
+
+    /// Cached plan for `key` and its result columns, if there is one
+    pub fn get(&self, key: &str) -> Option<(PhysicalPlan, Vec<ColumnMetadata>)> {
+        let mut state = self.state.lock_recovered();
+        state.tick += 1;
+        let tick = state.tick;
+
+        let cached = state.entries.get_mut(key).map(|entry| {
+            entry.last_used = tick;
+            (entry.plan.clone(), entry.columns.clone())
+        });
+        if cached.is_some() {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+        cached
+    }
+
+    /// Current generation, to pass to `insert` for a plan made from here on
+    pub fn generation(&self) -> u64 {
+        self.state.lock_recovered().generation
+    }
+
+    /// Cache the plan of the query `key`, made in cache `generation`
+    ///
+    /// A plan made before the cache was last cleared may be stale, so it is
+    /// not kept.
+    pub fn insert(&self, key: String, plan: &PhysicalPlan, columns: &[ColumnMetadata], generation: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock_recovered();
+        if state.generation != generation {
+            return;
+        }
+        state.tick += 1;
+        let tick = state.tick;
+
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(
+            key,
+            CacheEntry {
+                plan: plan.clone(),
+                columns: columns.to_vec(),
+                last_used: tick,
+            },
+        );
+    }
+
+    /// Drop every cached plan
+    pub fn clear(&self) {
+        let mut state = self.state.lock_recovered();
+        state.entries.clear();
+        state.generation += 1;
+    }
+
+    /// Hit and miss counts so far
+    pub fn stats(&self) -> PlanCacheStats {
+        let state = self.state.lock_recovered();
+        PlanCacheStats {
+            entries: state.entries.len(),
+            ..state.stats
+        }
+    }
+}
@@ -5,13 +5,18 @@
 //! used for planning.
 
 use crate::catalog::Catalog;
-use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use crate::parser::ast::{Expression as AstExpression, JoinType as AstJoinType, *};
 use crate::planner::logical_plan::*;
 use crate::types::LogicalType;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
+/// Deepest chain of SQL function calls inlined into one expression, which
+/// stops a function that calls itself
+const MAX_SQL_FUNCTION_DEPTH: usize = 32;
+
 /// Binding context that tracks tables and columns in scope
 #[derive(Debug, Clone)]
 pub struct BindingContext {
@@ -21,6 +26,10 @@ pub struct BindingContext {
     pub columns: HashMap<String, (usize, usize, LogicalType)>,
     /// CTE bindings: cte_name -> LogicalPlan
     pub ctes: HashMap<String, LogicalPlan>,
+    /// Collations declared on base-table columns: (table_index, column_index) -> collation
+    pub collations: HashMap<(usize, usize), String>,
+    /// Estimated distinct values of base-table columns: (table_index, column_index) -> count
+    pub distinct_estimates: HashMap<(usize, usize), usize>,
     /// Next table index to assign
     pub next_table_index: usize,
 }
@@ -31,6 +40,8 @@ impl BindingContext {
             tables: HashMap::new(),
             columns: HashMap::new(),
             ctes: HashMap::new(),
+            collations: HashMap::new(),
+            distinct_estimates: HashMap::new(),
             next_table_index: 0,
         }
     }
@@ -81,10 +92,10 @@ impl BindingContext {
             if let Some((table_idx, col_idx, data_type)) = self.columns.get(&qualified_name) {
                 return Ok((*table_idx, *col_idx, data_type.clone()));
             }
-            return Err(PrismDBError::Parse(format!(
+            Err(PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column {}.{} does not exist",
                 table_name, column
-            )));
+            )))
         } else {
             // Try exact match first (for unqualified column names)
             if let Some((table_idx, col_idx, data_type)) = self.columns.get(column) {
@@ -114,13 +125,37 @@ impl BindingContext {
                 )));
             }
 
-            return Err(PrismDBError::Parse(format!(
+            Err(PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column {} does not exist",
                 column
-            )));
+            )))
         }
     }
 
+    /// Record the collations declared on the columns of a bound table, in column order
+    pub fn add_collations(&mut self, table_index: usize, collations: Vec<Option<String>>) {
+        for (column_index, collation) in collations.into_iter().enumerate() {
+            if let Some(collation) = collation {
+                self.collations.insert((table_index, column_index), collation);
+            }
+        }
+    }
+
+    /// Record the estimated distinct values of the columns of a bound table, in column order
+    pub fn add_distinct_estimates(&mut self, table_index: usize, estimates: Vec<usize>) {
+        for (column_index, estimate) in estimates.into_iter().enumerate() {
+            self.distinct_estimates.insert((table_index, column_index), estimate);
+        }
+    }
+
+    /// Collation declared on the column a reference resolves to, if any
+    pub fn column_collation(&self, table: Option<&str>, column: &str) -> Option<&str> {
+        let (table_index, column_index, _) = self.resolve_column(table, column).ok()?;
+        self.collations
+            .get(&(table_index, column_index))
+            .map(String::as_str)
+    }
+
     /// Get table schema
     pub fn get_table_schema(&self, table_name: &str) -> Option<Vec<Column>> {
         if let Some((_, column_bindings)) = self.tables.get(table_name) {
@@ -143,6 +178,18 @@ pub struct Binder {
     catalog: Option<Arc<RwLock<Catalog>>>,
     /// Outer row values for correlated subqueries: (table_name, column_name) -> Value
     outer_row_values: std::collections::HashMap<(String, String), crate::types::Value>,
+    /// NULL placement for ORDER BY items without NULLS FIRST/LAST
+    default_null_order: NullOrder,
+    /// Estimated distinct count above which COUNT(DISTINCT column) is approximated; 0 never
+    approx_count_distinct_threshold: usize,
+    /// Window functions of the SELECT being bound and the columns computing them
+    window_columns: Vec<(AstExpression, String)>,
+    /// Columns joined with USING in the SELECT being bound and the expression
+    /// an unqualified reference to each stands for
+    using_columns: HashMap<String, AstExpression>,
+    /// Output column of each USING column, in the order they were joined,
+    /// with the names of the join columns it stands for in `*`
+    using_outputs: Vec<(Column, Vec<String>)>,
 }
 
 impl Binder {
@@ -151,6 +198,11 @@ impl Binder {
             context: BindingContext::new(),
             catalog: None,
             outer_row_values: std::collections::HashMap::new(),
+            default_null_order: NullOrder::default(),
+            approx_count_distinct_threshold: 0,
+            window_columns: Vec::new(),
+            using_columns: HashMap::new(),
+            using_outputs: Vec::new(),
         }
     }
 
@@ -160,9 +212,24 @@ impl Binder {
             context: BindingContext::new(),
             catalog: Some(catalog),
             outer_row_values: std::collections::HashMap::new(),
+            default_null_order: NullOrder::default(),
+            approx_count_distinct_threshold: 0,
+            window_columns: Vec::new(),
+            using_columns: HashMap::new(),
+            using_outputs: Vec::new(),
         }
     }
 
+    /// Set the NULL placement used when ORDER BY doesn't specify NULLS FIRST/LAST
+    pub fn set_default_null_order(&mut self, null_order: NullOrder) {
+        self.default_null_order = null_order;
+    }
+
+    /// Set the estimated distinct count above which COUNT(DISTINCT column) is approximated
+    pub fn set_approx_count_distinct_threshold(&mut self, threshold: usize) {
+        self.approx_count_distinct_threshold = threshold;
+    }
+
     /// Register outer row values for correlated subquery execution
     /// This creates a temporary single-row table with the outer row's data
     pub fn register_outer_row(
@@ -178,13 +245,13 @@ impl Binder {
             ));
         };
 
-        let catalog_lock = catalog.write().unwrap();
+        let catalog_lock = catalog.write_or_err()?;
         let schema_ref = catalog_lock.get_schema("main")?;
-        let mut schema_lock = schema_ref.write().unwrap();
+        let mut schema_lock = schema_ref.write_or_err()?;
 
         // Get the existing table to understand its schema
         let existing_table = schema_lock.get_table(table_name)?;
-        let existing_table_lock = existing_table.read().unwrap();
+        let existing_table_lock = existing_table.read_or_err()?;
         let table_info = existing_table_lock.get_table_info();
 
         // Create a temporary table name for this outer reference
@@ -203,7 +270,7 @@ impl Binder {
 
         // Insert the single row from the outer chunk
         if let Ok(temp_table_ref) = schema_lock.get_table(&temp_table_name) {
-            let temp_table_lock = temp_table_ref.write().unwrap();
+            let temp_table_lock = temp_table_ref.write_or_err()?;
 
             // Extract values from the chunk for this row
             let mut row_values = Vec::new();
@@ -255,6 +322,7 @@ impl Binder {
             Statement::Insert(insert) => self.bind_insert_statement(insert),
             Statement::Update(update) => self.bind_update_statement(update),
             Statement::Delete(delete) => self.bind_delete_statement(delete),
+            Statement::Merge(merge) => self.bind_merge_statement(merge),
             Statement::CreateTable(create) => self.bind_create_table_statement(create),
             Statement::DropTable(drop) => self.bind_drop_table_statement(drop),
             Statement::CreateView(create_view) => self.bind_create_view_statement(create_view),
@@ -268,8 +336,34 @@ impl Binder {
         }
     }
 
+    /// The (limit, offset) window of a SELECT, if it has one. A bare OFFSET
+    /// has no limit, which is represented as `usize::MAX`.
+    fn limit_window(select: &SelectStatement) -> Option<(usize, usize)> {
+        match (&select.limit, select.offset) {
+            (Some(limit_clause), offset) => {
+                Some((limit_clause.limit, limit_clause.offset.or(offset).unwrap_or(0)))
+            }
+            (None, Some(offset)) => Some((usize::MAX, offset)),
+            (None, None) => None,
+        }
+    }
+
     /// Bind a SELECT statement
     pub fn bind_select_statement(&mut self, select: &SelectStatement) -> PrismDBResult<LogicalPlan> {
+        // Window and USING columns belong to one SELECT; subqueries get their own
+        let outer_windows = std::mem::take(&mut self.window_columns);
+        let outer_using = std::mem::take(&mut self.using_columns);
+        let outer_using_outputs = std::mem::take(&mut self.using_outputs);
+        let plan = self.bind_select_query(select);
+        self.window_columns = outer_windows;
+        self.using_columns = outer_using;
+        self.using_outputs = outer_using_outputs;
+        plan
+    }
+
+    fn bind_select_query(&mut self, select: &SelectStatement) -> PrismDBResult<LogicalPlan> {
+        let select = &self.expand_select_functions(select)?;
+
         // Start with a fresh context for this query
         let _context = BindingContext::new();
 
@@ -305,9 +399,14 @@ impl Binder {
             self.update_context_from_plan(&plan)?;
         }
 
+        // Expand COLUMNS(...) now that the FROM clause's columns are known
+        let select = &Self::expand_columns_selections(select, &plan.schema())?;
+        let select = &self.expand_all_clauses(select, &plan.schema())?;
+
         // Bind WHERE clause
         if let Some(where_clause) = &select.where_clause {
             let predicate = self.convert_ast_expression(where_clause)?;
+            let predicate = self.apply_column_collations(&predicate);
             plan = LogicalPlan::Filter(LogicalFilter::new(plan, predicate));
         }
 
@@ -329,6 +428,20 @@ impl Binder {
             self.extract_aggregates(having_expr, &mut aggregates)?;
         }
 
+        // And from window function arguments and specs, computed over the groups
+        let windows = Self::window_functions(select);
+        for window in &windows {
+            if let AstExpression::WindowFunction { arguments, window_spec, .. } = window {
+                let spec_exprs = window_spec
+                    .partition_by
+                    .iter()
+                    .chain(window_spec.order_by.iter().map(|item| &item.expression));
+                for expr in arguments.iter().chain(spec_exprs) {
+                    self.extract_aggregates(expr, &mut aggregates)?;
+                }
+            }
+        }
+
         // Create Aggregate node if needed
         let has_aggregates = !aggregates.is_empty();
         let has_group_by = !select.group_by.is_empty();
@@ -365,9 +478,8 @@ impl Binder {
 
             // Add aggregate result columns to schema
             for agg in &aggregates {
-                // Generate a name for the aggregate (will be overridden by alias if present)
-                let agg_name = format!("{}(...)", agg.function_name);
-                agg_schema.push(Column::new(agg_name, agg.return_type.clone()));
+                // Name the column after the aggregate so the SELECT list and HAVING can find it
+                agg_schema.push(Column::new(agg.column_name(), agg.return_type.clone()));
             }
 
             plan = LogicalPlan::Aggregate(LogicalAggregate::new(
@@ -387,11 +499,108 @@ impl Binder {
             self.update_context_from_plan(&plan)?;
         }
 
+        // Compute window functions before the SELECT list, QUALIFY and ORDER BY read them
+        let source_schema = plan.schema();
+        if !windows.is_empty() {
+            let mut functions = Vec::new();
+            let mut window_schema = source_schema.clone();
+            for window in windows {
+                let AstExpression::WindowFunction { name, arguments, window_spec } = &window else {
+                    continue;
+                };
+                let bind = |binder: &mut Self, expr: &AstExpression| {
+                    if created_aggregate {
+                        binder.bind_select_expression_with_aggregates(expr, &select.group_by, &aggregates)
+                    } else {
+                        binder.convert_ast_expression(expr)
+                    }
+                };
+                let arguments = arguments
+                    .iter()
+                    .map(|arg| match arg {
+                        AstExpression::Wildcard => Ok(arg.clone()),
+                        arg => bind(self, arg),
+                    })
+                    .collect::<PrismDBResult<_>>()?;
+                let partition_by = window_spec
+                    .partition_by
+                    .iter()
+                    .map(|expr| bind(self, expr))
+                    .collect::<PrismDBResult<_>>()?;
+                let order_by = window_spec
+                    .order_by
+                    .iter()
+                    .map(|item| {
+                        Ok(OrderByExpression {
+                            expression: bind(self, &item.expression)?,
+                            ascending: item.ascending,
+                            nulls_first: Some(item.nulls_first.unwrap_or_else(|| {
+                                self.default_null_order.nulls_first(item.ascending)
+                            })),
+                        })
+                    })
+                    .collect::<PrismDBResult<_>>()?;
+                functions.push(AstExpression::WindowFunction {
+                    name: name.clone(),
+                    arguments,
+                    window_spec: WindowSpec {
+                        partition_by,
+                        order_by,
+                        window_frame: window_spec.window_frame.clone(),
+                    },
+                });
+
+                // Windows differing only in their frame print alike
+                let mut column = window.to_string();
+                if window_schema.iter().any(|existing| existing.name == column) {
+                    column = format!("{} #{}", column, functions.len());
+                }
+                window_schema.push(Column::new(column.clone(), self.infer_expression_type(&window)?));
+                self.window_columns.push((window, column));
+            }
+            plan = LogicalPlan::Window(LogicalWindow::new(plan, functions, window_schema));
+        }
+
         // Bind SELECT list (projection)
         let mut expressions = Vec::new();
         let mut schema = Vec::new();
+        // Projected lists to expand, with their element types
+        let mut unnest_columns: Vec<(usize, LogicalType)> = Vec::new();
 
         for item in &select.select_list {
+            let unnested = match item {
+                SelectItem::Expression(expr) => Self::unnest_argument(expr).map(|list| (expr, list)),
+                SelectItem::Alias(expr, _) => Self::unnest_argument(expr).map(|list| (&**expr, list)),
+                _ => None,
+            };
+            if let Some((expr, list_expr)) = unnested {
+                // Project the list itself; the Unnest node expands it into rows
+                let bound_expr = if created_aggregate {
+                    self.bind_select_expression_with_aggregates(
+                        list_expr,
+                        &select.group_by,
+                        &aggregates,
+                    )?
+                } else {
+                    self.convert_ast_expression(list_expr)?
+                };
+                let list_type = self.infer_expression_type(list_expr)?;
+                let LogicalType::List(element_type) = &list_type else {
+                    return Err(PrismDBError::InvalidArgument(format!(
+                        "UNNEST requires a list argument, got {}",
+                        list_type
+                    )));
+                };
+                let name = match item {
+                    SelectItem::Alias(_, alias) => alias.clone(),
+                    _ => self.expression_to_string(expr),
+                };
+                unnest_columns.push((expressions.len(), (**element_type).clone()));
+                expressions.push(bound_expr);
+                schema.push(Column::new(name, list_type));
+                continue;
+            }
+
             match item {
                 SelectItem::Expression(expr) => {
                     // If we have an aggregate node, rewrite aggregate functions to column refs
@@ -408,27 +617,26 @@ impl Binder {
                     expressions.push(bound_expr);
                     schema.push(Column::new(self.expression_to_string(expr), data_type));
                 }
-                SelectItem::Wildcard => {
-                    // Expand wildcard to all columns
-                    for column in plan.schema() {
-                        let expr = crate::parser::ast::Expression::ColumnReference {
-                            table: None,
-                            column: column.name.clone(),
+                SelectItem::Wildcard(options) | SelectItem::QualifiedWildcard(_, options) => {
+                    // Expand the wildcard to its columns, not including computed windows
+                    let columns = self.wildcard_columns(item, &source_schema)?;
+                    for (column, replacement) in Self::apply_wildcard_options(columns, options)? {
+                        let Some(expr) = replacement else {
+                            expressions.push(self.resolve_using_columns(&AstExpression::ColumnReference {
+                                table: None,
+                                column: column.name.clone(),
+                            }));
+                            schema.push(column);
+                            continue;
                         };
-                        expressions.push(expr);
-                        schema.push(column);
-                    }
-                }
-                SelectItem::QualifiedWildcard(table) => {
-                    // Expand table.* to all columns from that table
-                    for column in plan.schema() {
-                        // TODO: Filter by table name
-                        let expr = crate::parser::ast::Expression::ColumnReference {
-                            table: Some(table.clone()),
-                            column: column.name.clone(),
+                        let bound_expr = if created_aggregate {
+                            self.bind_select_expression_with_aggregates(expr, &select.group_by, &aggregates)?
+                        } else {
+                            self.convert_ast_expression(expr)?
                         };
-                        expressions.push(expr);
-                        schema.push(column);
+                        let data_type = self.infer_expression_type(expr)?;
+                        expressions.push(bound_expr);
+                        schema.push(Column::new(column.name, data_type));
                     }
                 }
                 SelectItem::Alias(expr, alias) => {
@@ -452,7 +660,12 @@ impl Binder {
         // Bind QUALIFY (PrismDB extension - filter on window function results)
         // QUALIFY is applied after window functions are computed, but before ORDER BY
         if let Some(qualify_expr) = &select.qualify {
-            let predicate = self.convert_ast_expression(qualify_expr)?;
+            let qualify_expr = self.resolve_select_aliases(qualify_expr, &select.select_list);
+            let predicate = if created_aggregate {
+                self.bind_select_expression_with_aggregates(&qualify_expr, &select.group_by, &aggregates)?
+            } else {
+                self.convert_ast_expression(&qualify_expr)?
+            };
             plan = LogicalPlan::Qualify(LogicalQualify::new(plan, predicate));
         }
 
@@ -495,9 +708,11 @@ impl Binder {
                     };
 
                     Ok(SortExpression {
-                        expression: expr,
+                        expression: self.with_column_collation(expr),
                         ascending: order_expr.ascending,
-                        nulls_first: order_expr.nulls_first,
+                        nulls_first: order_expr
+                            .nulls_first
+                            .unwrap_or_else(|| self.default_null_order.nulls_first(order_expr.ascending)),
                     })
                 })
                 .collect::<PrismDBResult<_>>()?;
@@ -505,18 +720,40 @@ impl Binder {
             plan = LogicalPlan::Sort(LogicalSort::new(plan, sort_exprs));
         }
 
-        // Bind LIMIT and OFFSET
-        if let Some(limit_clause) = &select.limit {
-            let offset = select.offset.unwrap_or(0);
-            plan = LogicalPlan::Limit(LogicalLimit::new(plan, limit_clause.limit, offset));
+        // Bind LIMIT and OFFSET (after UNNEST, which changes the row count,
+        // and DISTINCT, which removes rows)
+        let limit_window = Self::limit_window(select);
+        let limit_above_projection =
+            !unnest_columns.is_empty() || select.distinct || select.order_by_all.is_some();
+        if let (Some((limit, offset)), false) = (limit_window, limit_above_projection) {
+            plan = LogicalPlan::Limit(LogicalLimit::new(plan, limit, offset));
         }
 
         // Apply SELECT list (projection) last so it's the outermost operation
         plan = LogicalPlan::Projection(LogicalProjection::new(plan, expressions, schema));
 
-        // Bind DISTINCT
+        // Expand unnested lists into one row per element
+        if !unnest_columns.is_empty() {
+            let mut unnest_schema = plan.schema();
+            for (index, element_type) in &unnest_columns {
+                unnest_schema[*index].data_type = element_type.clone();
+            }
+            let positions = unnest_columns.iter().map(|(index, _)| *index).collect();
+            plan = LogicalPlan::Unnest(LogicalUnnest::new(plan, positions, unnest_schema));
+        }
+
+        // Bind DISTINCT over the projected rows; it keeps their sorted order
         if select.distinct {
-            // TODO: Implement distinct properly
+            plan = LogicalPlan::Distinct(LogicalDistinct::new(plan));
+        }
+
+        // ORDER BY ALL sorts by the output columns, so it goes over the projection
+        if let Some(order) = select.order_by_all {
+            plan = self.order_by_all(plan, order);
+        }
+
+        if let (Some((limit, offset)), true) = (limit_window, limit_above_projection) {
+            plan = LogicalPlan::Limit(LogicalLimit::new(plan, limit, offset));
         }
 
         // Bind set operations (UNION, INTERSECT, EXCEPT)
@@ -612,8 +849,23 @@ impl Binder {
     ) -> PrismDBResult<LogicalPlan> {
         let mut result = left;
 
-        for op in operations {
-            let right = self.bind_select_statement(&op.query)?;
+        // ORDER BY, LIMIT and OFFSET after the last query apply to the combined rows
+        let last = operations.last().map(|op| &op.query);
+        let order_by = last.map_or(&[][..], |query| &query.order_by[..]);
+        let order_by_all = last.and_then(|query| query.order_by_all);
+        let limit_window = last.and_then(|query| Self::limit_window(query));
+
+        for (i, op) in operations.iter().enumerate() {
+            let right = if i + 1 == operations.len() {
+                let mut query = (*op.query).clone();
+                query.order_by.clear();
+                query.order_by_all = None;
+                query.limit = None;
+                query.offset = None;
+                self.bind_select_statement(&query)?
+            } else {
+                self.bind_select_statement(&op.query)?
+            };
 
             // Create the appropriate set operation plan
             result = match op.op_type {
@@ -621,14 +873,36 @@ impl Binder {
                     LogicalPlan::Union(LogicalUnion::new(result, right, op.all))
                 }
                 SetOperationType::Intersect => {
-                    LogicalPlan::Intersect(LogicalIntersect::new(result, right))
+                    LogicalPlan::Intersect(LogicalIntersect::new(result, right, op.all))
                 }
                 SetOperationType::Except => {
-                    LogicalPlan::Except(LogicalExcept::new(result, right))
+                    LogicalPlan::Except(LogicalExcept::new(result, right, op.all))
                 }
             };
         }
 
+        if !order_by.is_empty() {
+            let sort_exprs = order_by
+                .iter()
+                .map(|order_expr| {
+                    Ok(SortExpression {
+                        expression: self.convert_ast_expression(&order_expr.expression)?,
+                        ascending: order_expr.ascending,
+                        nulls_first: order_expr
+                            .nulls_first
+                            .unwrap_or_else(|| self.default_null_order.nulls_first(order_expr.ascending)),
+                    })
+                })
+                .collect::<PrismDBResult<_>>()?;
+            result = LogicalPlan::Sort(LogicalSort::new(result, sort_exprs));
+        }
+        if let Some(order) = order_by_all {
+            result = self.order_by_all(result, order);
+        }
+        if let Some((limit, offset)) = limit_window {
+            result = LogicalPlan::Limit(LogicalLimit::new(result, limit, offset));
+        }
+
         Ok(result)
     }
 
@@ -654,16 +928,29 @@ impl Binder {
                 let table_name = alias.as_ref().unwrap_or(name);
 
                 // Look up table in catalog
+                let mut collations = Vec::new();
+                let mut distinct_estimates = Vec::new();
                 let schema = if let Some(catalog) = &self.catalog {
-                    let catalog_guard = catalog.read().unwrap();
-                    let default_schema = catalog_guard.get_default_schema();
-                    let schema_guard = default_schema.read().unwrap();
+                    let catalog_guard = catalog.read_or_err()?;
 
-                    // Try to get the table
-                    match schema_guard.get_table(name) {
+                    // Try each schema on the search path in turn
+                    match catalog_guard.find_table(name) {
                         Ok(table_arc) => {
-                            let table = table_arc.read().unwrap();
+                            let table = table_arc.read_or_err()?;
                             let table_info = table.get_table_info();
+                            collations = table_info.columns.iter().map(|c| c.collation.clone()).collect();
+                            // ANALYZE's distinct counts, else the row count as an upper bound
+                            let data = table.get_data();
+                            let data = data.read_or_err()?;
+                            let statistics = data.get_statistics();
+                            distinct_estimates = (0..table_info.columns.len())
+                                .map(|index| {
+                                    statistics
+                                        .get_column_stat(index)
+                                        .and_then(|stats| stats.distinct_count)
+                                        .unwrap_or_else(|| data.row_count())
+                                })
+                                .collect();
 
                             // Convert TableInfo columns to LogicalPlan Columns
                             // Qualify column names with table name/alias
@@ -676,12 +963,13 @@ impl Binder {
                                 })
                                 .collect()
                         }
-                        Err(_) => {
-                            return Err(PrismDBError::Catalog(format!(
+                        Err(PrismDBError::NotFound(..)) => {
+                            return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
                                 "Table '{}' does not exist",
                                 name
                             )));
                         }
+                        Err(error) => return Err(error),
                     }
                 } else {
                     // Fallback to dummy schema if no catalog
@@ -692,7 +980,9 @@ impl Binder {
                     ]
                 };
 
-                self.context.add_table(table_name, &schema);
+                let table_index = self.context.add_table(table_name, &schema);
+                self.context.add_collations(table_index, collations);
+                self.context.add_distinct_estimates(table_index, distinct_estimates);
 
                 Ok(LogicalPlan::TableScan(LogicalTableScan::new(
                     name.clone(),
@@ -708,16 +998,25 @@ impl Binder {
                 let left_plan = self.bind_table_reference(left)?;
                 let right_plan = self.bind_table_reference(right)?;
 
+                let left_schema = left_plan.schema();
+                let right_schema = right_plan.schema();
+
                 let condition_expr = match condition {
                     JoinCondition::On(expr) => Some(self.convert_ast_expression(expr)?),
-                    JoinCondition::Using(_columns) => {
-                        // TODO: Implement USING clause
-                        None
+                    JoinCondition::Using(columns) => {
+                        Some(self.bind_using_columns(columns, join_type, &left_schema, &right_schema)?)
+                    }
+                    JoinCondition::Natural => {
+                        let columns = Self::natural_join_columns(&left_schema, &right_schema);
+                        if columns.is_empty() {
+                            // A cross join, run as a join on a condition that always holds
+                            log::warn!("NATURAL JOIN has no common columns; joining every pair of rows");
+                            Some(AstExpression::Literal(LiteralValue::Boolean(true)))
+                        } else {
+                            Some(self.bind_using_columns(&columns, join_type, &left_schema, &right_schema)?)
+                        }
                     }
                 };
-
-                let left_schema = left_plan.schema();
-                let right_schema = right_plan.schema();
                 let mut schema = left_schema;
                 schema.extend(right_schema);
 
@@ -727,6 +1026,8 @@ impl Binder {
                     AstJoinType::Right => crate::planner::logical_plan::JoinType::Right,
                     AstJoinType::Full => crate::planner::logical_plan::JoinType::Full,
                     AstJoinType::Cross => crate::planner::logical_plan::JoinType::Cross,
+                    AstJoinType::AsOf => crate::planner::logical_plan::JoinType::AsOf,
+                    AstJoinType::AsOfLeft => crate::planner::logical_plan::JoinType::AsOfLeft,
                 };
 
                 Ok(LogicalPlan::Join(LogicalJoin::new(
@@ -742,6 +1043,16 @@ impl Binder {
                 self.context.add_table(alias, &subplan.schema());
                 Ok(subplan)
             }
+            TableReference::Values {
+                rows,
+                alias,
+                column_aliases,
+            } => {
+                let (rows, schema) = self.bind_values(rows, column_aliases)?;
+                self.context
+                    .add_table(alias.as_deref().unwrap_or("valueslist"), &schema);
+                Ok(LogicalPlan::Values(LogicalValues::new(rows, schema)))
+            }
             TableReference::Pivot {
                 source,
                 pivot_spec,
@@ -893,7 +1204,7 @@ impl Binder {
 
                 Ok(LogicalPlan::Unpivot(logical_unpivot))
             }
-            TableReference::TableFunction { name, arguments: _, alias: _ } => {
+            TableReference::TableFunction { name, .. } => {
                 // For now, return an error - table functions need special handling
                 Err(PrismDBError::NotImplemented(format!(
                     "Table function '{}' is not yet fully implemented. Table functions like read_csv_auto() require special execution handling.",
@@ -957,13 +1268,22 @@ impl Binder {
             AstExpression::Cast {
                 expression,
                 data_type,
+                try_cast,
             } => {
                 let bound_expr = self.bind_expression(expression)?;
                 Ok(AstExpression::Cast {
                     expression: Box::new(bound_expr),
                     data_type: data_type.clone(),
+                    try_cast: *try_cast,
                 })
             }
+            AstExpression::Collate {
+                expression,
+                collation,
+            } => Ok(AstExpression::Collate {
+                expression: Box::new(self.bind_expression(expression)?),
+                collation: collation.clone(),
+            }),
             // TODO: Implement other expression types
             _ => Err(PrismDBError::Parse(format!(
                 "Expression type not yet supported: {:?}",
@@ -972,6 +1292,125 @@ impl Binder {
         }
     }
 
+    /// The list argument of a set-returning `unnest(list)` call
+    fn unnest_argument(expr: &AstExpression) -> Option<&AstExpression> {
+        match expr {
+            AstExpression::FunctionCall {
+                name, arguments, ..
+            } if name.eq_ignore_ascii_case("UNNEST") && arguments.len() == 1 => Some(&arguments[0]),
+            _ => None,
+        }
+    }
+
+    /// Rows and schema of an inline VALUES list
+    ///
+    /// Columns are named `col0`, `col1`, ... unless aliased. Each column takes
+    /// the common type of its non-NULL values (see `TypeUtils::get_max_type`),
+    /// and values of another type are cast to it. Types without a common type,
+    /// such as INTEGER and VARCHAR, are an error.
+    fn bind_values(
+        &self,
+        rows: &[Vec<AstExpression>],
+        column_aliases: &[String],
+    ) -> PrismDBResult<(Vec<Vec<AstExpression>>, Vec<Column>)> {
+        let width = rows.first().map(|row| row.len()).unwrap_or(0);
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(PrismDBError::InvalidArgument(
+                "VALUES lists must all be the same length".to_string(),
+            ));
+        }
+        if column_aliases.len() > width {
+            return Err(PrismDBError::InvalidArgument(format!(
+                "VALUES has {} columns but {} column aliases were given",
+                width,
+                column_aliases.len()
+            )));
+        }
+
+        // Infer each value's type, leaving NULLs out of the column types
+        let row_types = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|expr| match expr {
+                        AstExpression::Literal(LiteralValue::Null) => Ok(None),
+                        _ => Ok(Some(match self.infer_expression_type(expr)? {
+                            LogicalType::Text => LogicalType::Varchar,
+                            other => other,
+                        })),
+                    })
+                    .collect::<PrismDBResult<Vec<_>>>()
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+
+        let mut schema = Vec::with_capacity(width);
+        for index in 0..width {
+            let mut column_type: Option<LogicalType> = None;
+            for value_type in row_types.iter().filter_map(|types| types[index].as_ref()) {
+                column_type = Some(match column_type {
+                    None => value_type.clone(),
+                    Some(current) => crate::types::TypeUtils::get_max_type(&current, value_type).map_err(|_| {
+                        PrismDBError::Type(format!(
+                            "VALUES column {} mixes {} and {}, which have no common type",
+                            index + 1,
+                            current,
+                            value_type
+                        ))
+                    })?,
+                });
+            }
+            let name = column_aliases
+                .get(index)
+                .cloned()
+                .unwrap_or_else(|| format!("col{}", index));
+            // A column of only NULLs is INTEGER, as in `Vector::from_values`
+            schema.push(Column::new(name, column_type.unwrap_or(LogicalType::Integer)));
+        }
+
+        // Cast values to their column's type so every row stores alike
+        let rows = rows
+            .iter()
+            .zip(&row_types)
+            .map(|(row, types)| {
+                row.iter()
+                    .zip(types)
+                    .zip(&schema)
+                    .map(|((expr, value_type), column)| match value_type {
+                        Some(value_type) if *value_type != column.data_type => AstExpression::Cast {
+                            expression: Box::new(expr.clone()),
+                            data_type: column.data_type.clone(),
+                            try_cast: false,
+                        },
+                        _ => expr.clone(),
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok((rows, schema))
+    }
+
+    /// Return type of a scalar function, resolved by the function registry
+    /// for the types of its arguments
+    fn scalar_function_type(&self, name: &str, arguments: &[AstExpression]) -> PrismDBResult<LogicalType> {
+        let arg_types = arguments
+            .iter()
+            .map(|arg| {
+                Ok(match self.infer_expression_type(arg)? {
+                    LogicalType::Text => LogicalType::Varchar,
+                    other => other,
+                })
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        let binder = crate::expression::binder::ExpressionBinder::new(
+            crate::expression::binder::BinderContext {
+                alias_map: HashMap::new(),
+                column_bindings: Vec::new(),
+                depth: 0,
+            },
+        );
+        binder.bind_function(name, &arg_types)
+    }
+
     /// Infer the type of an expression
     fn infer_expression_type(&self, expr: &AstExpression) -> PrismDBResult<LogicalType> {
         match expr {
@@ -998,30 +1437,135 @@ impl Binder {
             }
             AstExpression::Binary {
                 left,
-                operator: _operator,
+                operator,
                 right,
             } => {
+                use crate::expression::binder::TypeInference;
+
+                if matches!(
+                    operator,
+                    BinaryOperator::Equals
+                        | BinaryOperator::NotEquals
+                        | BinaryOperator::LessThan
+                        | BinaryOperator::LessThanOrEqual
+                        | BinaryOperator::GreaterThan
+                        | BinaryOperator::GreaterThanOrEqual
+                        | BinaryOperator::And
+                        | BinaryOperator::Or
+                        | BinaryOperator::Like
+                        | BinaryOperator::ILike
+                ) {
+                    return Ok(LogicalType::Boolean);
+                }
+
                 let left_type = self.infer_expression_type(left)?;
-                let _right_type = self.infer_expression_type(right)?;
-                // TODO: Implement proper type inference for binary operations
-                Ok(left_type)
+                let right_type = self.infer_expression_type(right)?;
+                if !left_type.is_numeric() || !right_type.is_numeric() {
+                    return Ok(left_type);
+                }
+                match operator {
+                    BinaryOperator::Divide => {
+                        Ok(TypeInference::infer_division_type(&left_type, &right_type)
+                            .unwrap_or(left_type))
+                    }
+                    BinaryOperator::Add
+                    | BinaryOperator::Subtract
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::IntegerDivide
+                    | BinaryOperator::Modulo => {
+                        Ok(TypeInference::infer_binary_type(&left_type, &right_type)
+                            .unwrap_or(left_type))
+                    }
+                    // TODO: Implement proper type inference for the remaining binary operations
+                    _ => Ok(left_type),
+                }
             }
             AstExpression::Unary {
                 operator: _operator,
                 expression,
             } => self.infer_expression_type(expression),
-            AstExpression::FunctionCall {
-                name: _name,
-                arguments: _arguments,
-                distinct: _distinct,
+            AstExpression::AggregateFunction {
+                name, arguments, ..
             } => {
-                // TODO: Look up function return type
-                Ok(LogicalType::Text)
+                let arg_types = arguments
+                    .iter()
+                    .map(|arg| self.infer_expression_type(arg))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                self.infer_aggregate_type(name, &arg_types)
+            }
+            AstExpression::FunctionCall {
+                name, arguments, ..
+            } if Self::is_aggregate_function(name) => {
+                let arg_types = arguments
+                    .iter()
+                    .map(|arg| self.infer_expression_type(arg))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                self.infer_aggregate_type(name, &arg_types)
+            }
+            AstExpression::FunctionCall {
+                name, arguments, ..
+            } if name.eq_ignore_ascii_case("LIST_VALUE") => {
+                let mut element_type: Option<LogicalType> = None;
+                for arg in arguments {
+                    if matches!(arg, AstExpression::Literal(LiteralValue::Null)) {
+                        continue;
+                    }
+                    let arg_type = match self.infer_expression_type(arg)? {
+                        LogicalType::Text => LogicalType::Varchar,
+                        other => other,
+                    };
+                    element_type = Some(match element_type {
+                        None => arg_type,
+                        Some(current) => {
+                            crate::types::TypeUtils::get_max_type(&current, &arg_type)?
+                        }
+                    });
+                }
+                Ok(LogicalType::List(Box::new(
+                    element_type.unwrap_or(LogicalType::Integer),
+                )))
+            }
+            AstExpression::FunctionCall { name, .. }
+                if name.eq_ignore_ascii_case("TO_JSON") || name.eq_ignore_ascii_case("ROW_TO_JSON") =>
+            {
+                Ok(LogicalType::JSON)
+            }
+            AstExpression::FunctionCall { name, arguments, .. }
+                if matches!(
+                    name.to_uppercase().as_str(),
+                    "MAP_PACK" | "ELEMENT_AT" | "MAP_KEYS" | "MAP_VALUES" | "FROM_HEX" | "ENCODE"
+                ) =>
+            {
+                // Typed like the expression binder types them, so derived tables
+                // can pass maps, lists and blobs on
+                self.scalar_function_type(name, arguments)
+            }
+            AstExpression::FunctionCall { name, arguments, .. } => {
+                // Functions the registry doesn't know, such as SQL functions, stay text
+                Ok(self
+                    .scalar_function_type(name, arguments)
+                    .unwrap_or(LogicalType::Text))
+            }
+            AstExpression::WindowFunction { name, arguments, .. } => {
+                let arg_types = arguments
+                    .iter()
+                    .filter(|arg| !matches!(arg, AstExpression::Wildcard))
+                    .map(|arg| self.infer_expression_type(arg))
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+                let binder = crate::expression::binder::ExpressionBinder::new(
+                    crate::expression::binder::BinderContext {
+                        alias_map: HashMap::new(),
+                        column_bindings: Vec::new(),
+                        depth: 0,
+                    },
+                );
+                binder.bind_window_function(name, &arg_types)
+            }
+            AstExpression::Cast { data_type, .. } => Ok(data_type.clone()),
+            AstExpression::Collate { expression, .. } => self.infer_expression_type(expression),
+            AstExpression::Between { .. } | AstExpression::BetweenSymmetric { .. } => {
+                Ok(LogicalType::Boolean)
             }
-            AstExpression::Cast {
-                expression: _expression,
-                data_type,
-            } => Ok(data_type.clone()),
             _ => Ok(LogicalType::Text),
         }
     }
@@ -1031,13 +1575,736 @@ impl Binder {
         &mut self,
         expr: &AstExpression,
     ) -> PrismDBResult<crate::parser::ast::Expression> {
-        // For now, just clone the expression
         // Subquery execution during binding requires transaction manager access
         // which is not available at this stage
         // TODO: Thread transaction manager through or handle subqueries as special operators
-        Ok(expr.clone())
+        let expr = self.expand_sql_functions(&Self::lower_conditional_aggregates(expr)?, 0)?;
+        let expr = self.replace_window_functions(&expr);
+        let expr = self.resolve_using_columns(&expr);
+        Ok(self.expand_row_references(&expr))
+    }
+
+    /// The equality condition of `JOIN ... USING (columns)`
+    ///
+    /// Each column must be on both sides once, or on the left as the column of
+    /// an earlier USING join. An unqualified reference to it stands for the
+    /// left side's column, the right side's for a RIGHT join, and whichever
+    /// isn't NULL for a FULL join; `*` lists it once, ahead of the other columns.
+    fn bind_using_columns(
+        &mut self,
+        columns: &[String],
+        join_type: &AstJoinType,
+        left_schema: &[Column],
+        right_schema: &[Column],
+    ) -> PrismDBResult<AstExpression> {
+        let side_column = |schema: &[Column], column: &str, side: &str| {
+            let mut matches = schema.iter().filter(|c| {
+                c.name == column || c.name.rsplit_once('.').is_some_and(|(_, name)| name == column)
+            });
+            match (matches.next(), matches.next()) {
+                (Some(found), None) => Ok((
+                    match found.name.rsplit_once('.') {
+                        Some((table, name)) => AstExpression::ColumnReference {
+                            table: Some(table.to_string()),
+                            column: name.to_string(),
+                        },
+                        None => AstExpression::ColumnReference {
+                            table: None,
+                            column: found.name.clone(),
+                        },
+                    },
+                    found.clone(),
+                )),
+                (Some(_), Some(_)) => Err(PrismDBError::Parse(format!(
+                    "Column '{}' in USING is ambiguous on the {} side of the join",
+                    column, side
+                ))),
+                (None, _) => Err(PrismDBError::NotFound(ObjectKind::Column, format!(
+                    "Column '{}' in USING does not exist on the {} side of the join",
+                    column, side
+                ))),
+            }
+        };
+
+        let mut condition: Option<AstExpression> = None;
+        for column in columns {
+            let earlier = self.using_outputs.iter().position(|(output, _)| output.name == *column);
+            let (left, left_column) = match earlier {
+                Some(index) => (self.using_columns[column].clone(), self.using_outputs[index].0.clone()),
+                None => side_column(left_schema, column, "left")?,
+            };
+            let (right, right_column) = side_column(right_schema, column, "right")?;
+            let output_type = match join_type {
+                AstJoinType::Right => right_column.data_type.clone(),
+                _ => left_column.data_type.clone(),
+            };
+            match earlier {
+                Some(index) => {
+                    let (output, merged) = &mut self.using_outputs[index];
+                    output.data_type = output_type;
+                    merged.push(right_column.name);
+                }
+                None => self.using_outputs.push((
+                    Column::new(column.clone(), output_type),
+                    vec![left_column.name, right_column.name],
+                )),
+            }
+            let merged = match join_type {
+                AstJoinType::Right => right.clone(),
+                AstJoinType::Full => AstExpression::FunctionCall {
+                    name: "COALESCE".to_string(),
+                    arguments: vec![left.clone(), right.clone()],
+                    distinct: false,
+                },
+                _ => left.clone(),
+            };
+            self.using_columns.insert(column.clone(), merged);
+
+            let equal = AstExpression::Binary {
+                left: Box::new(left),
+                operator: BinaryOperator::Equals,
+                right: Box::new(right),
+            };
+            condition = Some(match condition {
+                Some(previous) => AstExpression::Binary {
+                    left: Box::new(previous),
+                    operator: BinaryOperator::And,
+                    right: Box::new(equal),
+                },
+                None => equal,
+            });
+        }
+        condition.ok_or_else(|| PrismDBError::Parse("USING requires at least one column".to_string()))
+    }
+
+    /// Replace unqualified references to USING columns with the column they stand for
+    fn resolve_using_columns(&self, expr: &AstExpression) -> AstExpression {
+        if self.using_columns.is_empty() {
+            return expr.clone();
+        }
+        let resolved = expr.try_transform(&mut |node| {
+            Ok::<_, std::convert::Infallible>(match &node {
+                AstExpression::ColumnReference { table: None, column } => {
+                    self.using_columns.get(column).cloned().unwrap_or(node)
+                }
+                _ => node,
+            })
+        });
+        match resolved {
+            Ok(expr) => expr,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Column names on both sides of a NATURAL JOIN, in the left side's order
+    fn natural_join_columns(left_schema: &[Column], right_schema: &[Column]) -> Vec<String> {
+        let bare_name = |column: &Column| {
+            let name = column.name.as_str();
+            name.rsplit_once('.').map_or(name, |(_, name)| name).to_string()
+        };
+        let right_names: HashSet<String> = right_schema.iter().map(bare_name).collect();
+        let mut columns: Vec<String> = Vec::new();
+        for name in left_schema.iter().map(bare_name) {
+            if right_names.contains(&name) && !columns.contains(&name) {
+                columns.push(name);
+            }
+        }
+        columns
+    }
+
+    /// Columns a `*` or `table.*` item expands to, before its EXCLUDE and REPLACE
+    ///
+    /// A plain `*` lists each USING column once, first, in place of the join
+    /// columns it stands for.
+    fn wildcard_columns(&self, item: &SelectItem, source_schema: &[Column]) -> PrismDBResult<Vec<Column>> {
+        if let SelectItem::QualifiedWildcard(table, _) = item {
+            return self.qualified_wildcard_columns(table, source_schema);
+        }
+        let merged: HashSet<&str> = self
+            .using_outputs
+            .iter()
+            .flat_map(|(_, names)| names.iter().map(String::as_str))
+            .collect();
+        Ok(self
+            .using_outputs
+            .iter()
+            .map(|(output, _)| output.clone())
+            .chain(source_schema.iter().filter(|column| !merged.contains(column.name.as_str())).cloned())
+            .collect())
+    }
+
+    /// Columns of the FROM clause's output that `table.*` expands to
+    fn qualified_wildcard_columns(&self, table: &str, source_schema: &[Column]) -> PrismDBResult<Vec<Column>> {
+        let prefix = format!("{}.", table);
+        let qualified: Vec<Column> = source_schema
+            .iter()
+            .filter(|column| column.name.starts_with(&prefix))
+            .cloned()
+            .collect();
+        if !qualified.is_empty() {
+            return Ok(qualified);
+        }
+        // Columns of a subquery are not qualified with its alias
+        match self.context.tables.get(table) {
+            Some((_, table_columns)) => Ok(source_schema
+                .iter()
+                .filter(|column| !column.name.contains('.') && table_columns.contains_key(&column.name))
+                .cloned()
+                .collect()),
+            None => Err(PrismDBError::Parse(format!(
+                "Table {} referenced by {}.* is not in the FROM clause",
+                table, table
+            ))),
+        }
+    }
+
+    /// The columns a wildcard expands to without those its EXCLUDE names,
+    /// each with the expression its REPLACE puts in the column's place
+    ///
+    /// Names match a column's full name or its name without the table.
+    fn apply_wildcard_options(
+        columns: Vec<Column>,
+        options: &WildcardOptions,
+    ) -> PrismDBResult<Vec<(Column, Option<&AstExpression>)>> {
+        let matches = |column: &Column, name: &str| {
+            column.name.eq_ignore_ascii_case(name)
+                || column.name.rsplit('.').next().is_some_and(|bare| bare.eq_ignore_ascii_case(name))
+        };
+        let modifiers = options
+            .exclude
+            .iter()
+            .map(|name| ("EXCLUDE", name))
+            .chain(options.replace.iter().map(|(_, name)| ("REPLACE", name)));
+        for (modifier, name) in modifiers {
+            if !columns.iter().any(|column| matches(column, name)) {
+                return Err(PrismDBError::NotFound(ObjectKind::Column, format!(
+                    "Column {} in {} does not exist",
+                    name, modifier
+                )));
+            }
+        }
+
+        let expanded: Vec<_> = columns
+            .into_iter()
+            .filter(|column| !options.exclude.iter().any(|name| matches(column, name)))
+            .map(|column| {
+                let replacement = options
+                    .replace
+                    .iter()
+                    .find(|(_, name)| matches(&column, name))
+                    .map(|(expr, _)| expr);
+                (column, replacement)
+            })
+            .collect();
+        if expanded.is_empty() {
+            return Err(PrismDBError::InvalidArgument(
+                "EXCLUDE leaves no columns to select".to_string(),
+            ));
+        }
+        Ok(expanded)
+    }
+
+    /// The regex of a `COLUMNS('pattern')` call
+    fn columns_pattern(expr: &AstExpression) -> Option<PrismDBResult<&str>> {
+        match expr {
+            AstExpression::FunctionCall { name, arguments, .. } if name.eq_ignore_ascii_case("COLUMNS") => {
+                Some(match arguments.as_slice() {
+                    [AstExpression::Literal(LiteralValue::String(pattern))] => Ok(pattern.as_str()),
+                    _ => Err(PrismDBError::InvalidArgument(
+                        "COLUMNS expects a single regular expression string".to_string(),
+                    )),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Replace each SELECT list item holding `COLUMNS('pattern')` with one
+    /// item per input column whose name matches the pattern, in column order
+    ///
+    /// The pattern is matched against column names without their table. An
+    /// item wrapping COLUMNS in a function, such as `SUM(COLUMNS('q.*'))`,
+    /// becomes the function applied to each matched column.
+    fn expand_columns_selections(select: &SelectStatement, source_schema: &[Column]) -> PrismDBResult<SelectStatement> {
+        let mut select_list = Vec::with_capacity(select.select_list.len());
+        for item in &select.select_list {
+            let (expr, alias) = match item {
+                SelectItem::Expression(expr) => (expr, None),
+                SelectItem::Alias(expr, alias) => (&**expr, Some(alias)),
+                other => {
+                    select_list.push(other.clone());
+                    continue;
+                }
+            };
+
+            let mut pattern: Option<String> = None;
+            expr.try_transform(&mut |node| {
+                if let Some(found) = Self::columns_pattern(&node) {
+                    let found = found?;
+                    if pattern.as_deref().is_some_and(|pattern| pattern != found) {
+                        return Err(PrismDBError::InvalidArgument(
+                            "A SELECT item can only use one COLUMNS pattern".to_string(),
+                        ));
+                    }
+                    pattern = Some(found.to_string());
+                }
+                Ok(node)
+            })?;
+            let Some(pattern) = pattern else {
+                select_list.push(item.clone());
+                continue;
+            };
+
+            let regex = regex::Regex::new(&pattern).map_err(|e| {
+                PrismDBError::InvalidArgument(format!("Invalid COLUMNS pattern '{}': {}", pattern, e))
+            })?;
+            let matched: Vec<&Column> = source_schema
+                .iter()
+                .filter(|column| regex.is_match(column.name.rsplit('.').next().unwrap_or(&column.name)))
+                .collect();
+            if matched.is_empty() {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "COLUMNS('{}') matches no columns",
+                    pattern
+                )));
+            }
+            if alias.is_some() && matched.len() > 1 {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "COLUMNS('{}') matches {} columns, so it cannot be given an alias",
+                    pattern,
+                    matched.len()
+                )));
+            }
+
+            for column in matched {
+                let expanded = expr.try_transform(&mut |node| {
+                    Ok::<_, std::convert::Infallible>(match Self::columns_pattern(&node) {
+                        Some(_) => AstExpression::ColumnReference {
+                            table: None,
+                            column: column.name.clone(),
+                        },
+                        None => node,
+                    })
+                });
+                let expanded = match expanded {
+                    Ok(expr) => expr,
+                    Err(never) => match never {},
+                };
+                select_list.push(match alias {
+                    Some(alias) => SelectItem::Alias(Box::new(expanded), alias.clone()),
+                    None => SelectItem::Expression(expanded),
+                });
+            }
+        }
+
+        Ok(SelectStatement {
+            select_list,
+            ..select.clone()
+        })
+    }
+
+    /// Expand `GROUP BY ALL` against the SELECT list
+    ///
+    /// GROUP BY ALL groups by every select item that holds no aggregate or
+    /// window function. A `*` item stands for the input columns it expands to.
+    fn expand_all_clauses(&self, select: &SelectStatement, source_schema: &[Column]) -> PrismDBResult<SelectStatement> {
+        if !select.group_by_all {
+            return Ok(select.clone());
+        }
+
+        let mut outputs: Vec<AstExpression> = Vec::new();
+        for item in &select.select_list {
+            match item {
+                SelectItem::Expression(expr) => outputs.push(expr.clone()),
+                SelectItem::Alias(expr, _) => outputs.push((**expr).clone()),
+                SelectItem::Wildcard(options) | SelectItem::QualifiedWildcard(_, options) => {
+                    let columns = self.wildcard_columns(item, source_schema)?;
+                    for (column, replacement) in Self::apply_wildcard_options(columns, options)? {
+                        outputs.push(replacement.cloned().unwrap_or(AstExpression::ColumnReference {
+                            table: None,
+                            column: column.name,
+                        }));
+                    }
+                }
+            }
+        }
+
+        let mut expanded = select.clone();
+        expanded.group_by = outputs
+            .into_iter()
+            .filter(|expr| !Self::contains_aggregate_or_window(expr))
+            .collect();
+        Ok(expanded)
     }
 
+    /// `ORDER BY ALL`: sort the projected rows by every output column, left to right
+    fn order_by_all(&self, plan: LogicalPlan, order: OrderByAll) -> LogicalPlan {
+        let sort_exprs = plan
+            .schema()
+            .into_iter()
+            .map(|column| SortExpression {
+                expression: self.with_column_collation(AstExpression::ColumnReference {
+                    table: None,
+                    column: column.name,
+                }),
+                ascending: order.ascending,
+                nulls_first: order
+                    .nulls_first
+                    .unwrap_or_else(|| self.default_null_order.nulls_first(order.ascending)),
+            })
+            .collect();
+        LogicalPlan::Sort(LogicalSort::new(plan, sort_exprs))
+    }
+
+    /// Whether an expression calls an aggregate or window function
+    fn contains_aggregate_or_window(expr: &AstExpression) -> bool {
+        let mut found = false;
+        let _ = expr.try_transform(&mut |node| {
+            found |= match &node {
+                AstExpression::AggregateFunction { .. } | AstExpression::WindowFunction { .. } => true,
+                AstExpression::FunctionCall { name, .. } => Self::is_aggregate_function(name),
+                _ => false,
+            };
+            Ok::<_, std::convert::Infallible>(node)
+        });
+        found
+    }
+
+    /// Window function calls in the SELECT list, QUALIFY and ORDER BY, each once
+    fn window_functions(select: &SelectStatement) -> Vec<AstExpression> {
+        let items = select.select_list.iter().filter_map(|item| match item {
+            SelectItem::Expression(expr) => Some(expr),
+            SelectItem::Alias(expr, _) => Some(&**expr),
+            _ => None,
+        });
+        let clauses = select
+            .qualify
+            .as_deref()
+            .into_iter()
+            .chain(select.order_by.iter().map(|item| &item.expression));
+
+        let mut windows: Vec<AstExpression> = Vec::new();
+        for expr in items.chain(clauses) {
+            let _ = expr.try_transform(&mut |node| {
+                if matches!(node, AstExpression::WindowFunction { .. }) && !windows.contains(&node) {
+                    windows.push(node.clone());
+                }
+                Ok::<_, std::convert::Infallible>(node)
+            });
+        }
+        windows
+    }
+
+    /// Refer to the Window node's column for each window function it computes
+    fn replace_window_functions(&self, expr: &AstExpression) -> AstExpression {
+        if self.window_columns.is_empty() {
+            return expr.clone();
+        }
+        let replaced = expr.try_transform(&mut |node| {
+            let column = self
+                .window_columns
+                .iter()
+                .find(|(window, _)| *window == node)
+                .map(|(_, column)| column.clone());
+            Ok::<_, std::convert::Infallible>(match column {
+                Some(column) => AstExpression::ColumnReference { table: None, column },
+                None => node,
+            })
+        });
+        match replaced {
+            Ok(expr) => expr,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Replace names of SELECT list aliases that are not input columns with
+    /// the aliased expressions
+    fn resolve_select_aliases(&self, expr: &AstExpression, select_list: &[SelectItem]) -> AstExpression {
+        let resolved = expr.try_transform(&mut |node| {
+            if let AstExpression::ColumnReference { table: None, column } = &node {
+                let aliased = select_list.iter().find_map(|item| match item {
+                    SelectItem::Alias(expr, alias) if alias == column => Some(&**expr),
+                    _ => None,
+                });
+                if let (Some(aliased), true) = (aliased, self.context.resolve_column(None, column).is_err()) {
+                    return Ok(aliased.clone());
+                }
+            }
+            Ok::<_, std::convert::Infallible>(node)
+        });
+        match resolved {
+            Ok(expr) => expr,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Inline calls to functions created with CREATE FUNCTION
+    ///
+    /// Each call becomes the function body with its parameters replaced by the
+    /// arguments, cast to the declared parameter and return types.
+    fn expand_sql_functions(&self, expr: &AstExpression, depth: usize) -> PrismDBResult<AstExpression> {
+        let Some(catalog) = &self.catalog else {
+            return Ok(expr.clone());
+        };
+        expr.try_transform(&mut |node| {
+            let AstExpression::FunctionCall { name, arguments, .. } = &node else {
+                return Ok(node);
+            };
+            let Some(function) = catalog.read_or_err()?.find_function(name) else {
+                return Ok(node);
+            };
+            if depth >= MAX_SQL_FUNCTION_DEPTH {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "Function {} nests more than {} calls deep",
+                    function.name, MAX_SQL_FUNCTION_DEPTH
+                )));
+            }
+            if arguments.len() != function.parameters.len() {
+                return Err(PrismDBError::InvalidArgument(format!(
+                    "Function {} expects {} arguments, got {}",
+                    function.name,
+                    function.parameters.len(),
+                    arguments.len()
+                )));
+            }
+
+            let body = self.expand_sql_functions(&function.body, depth + 1)?;
+            let body = body.try_transform(&mut |body_node| {
+                if let AstExpression::ColumnReference { table: None, column } = &body_node {
+                    let parameter = function
+                        .parameters
+                        .iter()
+                        .position(|(parameter, _)| parameter.eq_ignore_ascii_case(column));
+                    if let Some(index) = parameter {
+                        return Ok::<_, PrismDBError>(AstExpression::Cast {
+                            expression: Box::new(arguments[index].clone()),
+                            data_type: function.parameters[index].1.clone(),
+                            try_cast: false,
+                        });
+                    }
+                }
+                Ok(body_node)
+            })?;
+            Ok(match &function.return_type {
+                Some(return_type) => AstExpression::Cast {
+                    expression: Box::new(body),
+                    data_type: return_type.clone(),
+                    try_cast: false,
+                },
+                None => body,
+            })
+        })
+    }
+
+    /// Lower conditional aggregate shorthands to filtered aggregates
+    ///
+    /// `COUNTIF(p)` (also spelled `COUNT_IF`) counts the rows where `p` is
+    /// true, so it becomes `COUNT(*) FILTER (WHERE p)`.
+    fn lower_conditional_aggregates(expr: &AstExpression) -> PrismDBResult<AstExpression> {
+        expr.try_transform(&mut |node| {
+            let AstExpression::AggregateFunction { name, arguments, distinct, filter, .. } = &node else {
+                return Ok(node);
+            };
+            if !name.eq_ignore_ascii_case("COUNTIF") && !name.eq_ignore_ascii_case("COUNT_IF") {
+                return Ok(node);
+            }
+            match arguments.as_slice() {
+                [predicate] if !distinct => Ok(Self::conditional_aggregate(
+                    "COUNT",
+                    vec![AstExpression::Wildcard],
+                    predicate.clone(),
+                    filter.as_deref(),
+                )),
+                _ => Err(PrismDBError::InvalidArgument(format!(
+                    "{} expects a single predicate argument",
+                    name.to_uppercase()
+                ))),
+            }
+        })
+    }
+
+    /// `name(arguments)` aggregating only the rows where `predicate` is true
+    ///
+    /// An existing FILTER on the call is kept, ANDed with `predicate`.
+    fn conditional_aggregate(
+        name: &str,
+        arguments: Vec<AstExpression>,
+        predicate: AstExpression,
+        filter: Option<&AstExpression>,
+    ) -> AstExpression {
+        let predicate = match filter {
+            Some(filter) => AstExpression::Binary {
+                left: Box::new(predicate),
+                operator: BinaryOperator::And,
+                right: Box::new(filter.clone()),
+            },
+            None => predicate,
+        };
+        AstExpression::AggregateFunction {
+            name: name.to_string(),
+            arguments,
+            distinct: false,
+            filter: Some(Box::new(predicate)),
+            order_by: Vec::new(),
+            within_group: false,
+        }
+    }
+
+    /// Inline SQL function calls and lower conditional aggregates throughout
+    /// a SELECT, naming select items after the call they replace
+    fn expand_select_functions(&self, select: &SelectStatement) -> PrismDBResult<SelectStatement> {
+        let expand =
+            |expr: &AstExpression| self.expand_sql_functions(&Self::lower_conditional_aggregates(expr)?, 0);
+        let expand_boxed = |expr: &Option<Box<AstExpression>>| {
+            expr.as_deref().map(|e| expand(e).map(Box::new)).transpose()
+        };
+
+        let select_list = select
+            .select_list
+            .iter()
+            .map(|item| {
+                Ok(match item {
+                    SelectItem::Expression(expr) => {
+                        let expanded = expand(expr)?;
+                        if expanded == *expr {
+                            SelectItem::Expression(expanded)
+                        } else {
+                            SelectItem::Alias(Box::new(expanded), self.expression_to_string(expr))
+                        }
+                    }
+                    SelectItem::Alias(expr, alias) => {
+                        SelectItem::Alias(Box::new(expand(expr)?), alias.clone())
+                    }
+                    other => other.clone(),
+                })
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+
+        Ok(SelectStatement {
+            select_list,
+            where_clause: expand_boxed(&select.where_clause)?,
+            group_by: select.group_by.iter().map(expand).collect::<PrismDBResult<_>>()?,
+            having: expand_boxed(&select.having)?,
+            qualify: expand_boxed(&select.qualify)?,
+            order_by: select
+                .order_by
+                .iter()
+                .map(|item| {
+                    Ok(OrderByExpression {
+                        expression: expand(&item.expression)?,
+                        ..item.clone()
+                    })
+                })
+                .collect::<PrismDBResult<_>>()?,
+            ..select.clone()
+        })
+    }
+
+    /// Expand `row_to_json(t)` calls naming a table in scope into the table's
+    /// column names and values, in column order
+    fn expand_row_references(&self, expr: &AstExpression) -> AstExpression {
+        let AstExpression::FunctionCall {
+            name,
+            arguments,
+            distinct,
+        } = expr
+        else {
+            return expr.clone();
+        };
+        if let [AstExpression::ColumnReference { table: None, column }] = arguments.as_slice() {
+            let table = self.context.tables.get(column);
+            if let (Some((_, table_columns)), true) = (
+                table,
+                name.eq_ignore_ascii_case("ROW_TO_JSON") && self.context.resolve_column(None, column).is_err(),
+            ) {
+                let mut columns: Vec<(&String, &usize)> = table_columns.iter().collect();
+                columns.sort_by_key(|(_, &index)| index);
+                let arguments = columns
+                    .into_iter()
+                    .flat_map(|(column_name, _)| {
+                        [
+                            AstExpression::Literal(LiteralValue::String(column_name.clone())),
+                            AstExpression::ColumnReference {
+                                table: Some(column.clone()),
+                                column: column_name.clone(),
+                            },
+                        ]
+                    })
+                    .collect();
+                return AstExpression::FunctionCall {
+                    name: name.clone(),
+                    arguments,
+                    distinct: *distinct,
+                };
+            }
+        }
+        AstExpression::FunctionCall {
+            name: name.clone(),
+            arguments: arguments.iter().map(|arg| self.expand_row_references(arg)).collect(),
+            distinct: *distinct,
+        }
+    }
+
+
+    /// Give comparisons of columns declared with COLLATE the column's collation
+    ///
+    /// A column compared with `=`, `<`, ... uses its declared collation unless
+    /// either side of the comparison names one explicitly.
+    fn apply_column_collations(&self, expr: &AstExpression) -> AstExpression {
+        match expr {
+            AstExpression::Binary {
+                left,
+                operator: operator @ (BinaryOperator::And | BinaryOperator::Or),
+                right,
+            } => AstExpression::Binary {
+                left: Box::new(self.apply_column_collations(left)),
+                operator: operator.clone(),
+                right: Box::new(self.apply_column_collations(right)),
+            },
+            AstExpression::Binary {
+                left,
+                operator:
+                    operator @ (BinaryOperator::Equals
+                    | BinaryOperator::NotEquals
+                    | BinaryOperator::LessThan
+                    | BinaryOperator::LessThanOrEqual
+                    | BinaryOperator::GreaterThan
+                    | BinaryOperator::GreaterThanOrEqual),
+                right,
+            } if left.collation().is_none() && right.collation().is_none() => {
+                AstExpression::Binary {
+                    left: Box::new(self.with_column_collation(left.as_ref().clone())),
+                    operator: operator.clone(),
+                    right: Box::new(self.with_column_collation(right.as_ref().clone())),
+                }
+            }
+            AstExpression::Unary {
+                operator: UnaryOperator::Not,
+                expression,
+            } => AstExpression::Unary {
+                operator: UnaryOperator::Not,
+                expression: Box::new(self.apply_column_collations(expression)),
+            },
+            _ => expr.clone(),
+        }
+    }
+
+    /// Wrap a bare reference to a column declared with COLLATE in that collation
+    fn with_column_collation(&self, expr: AstExpression) -> AstExpression {
+        let collation = match &expr {
+            AstExpression::ColumnReference { table, column } => {
+                self.context.column_collation(table.as_deref(), column)
+            }
+            _ => None,
+        };
+        match collation {
+            Some(collation) => AstExpression::Collate {
+                collation: collation.to_string(),
+                expression: Box::new(expr),
+            },
+            None => expr,
+        }
+    }
 
     /// Convert expression to string for column naming
     fn expression_to_string(&self, expr: &AstExpression) -> String {
@@ -1049,10 +2316,8 @@ impl Binder {
                     column.clone()
                 }
             }
-            AstExpression::Literal(value) => {
-                format!("{:?}", value)
-            }
-            _ => "expr".to_string(),
+            // Unaliased expressions are named after their SQL text, e.g. `count(*)`
+            _ => expr.to_string(),
         }
     }
 
@@ -1092,6 +2357,9 @@ impl Binder {
             LogicalPlan::Limit(limit) => {
                 self.update_context_from_plan(&limit.input)?;
             }
+            LogicalPlan::Distinct(distinct) => {
+                self.update_context_from_plan(&distinct.input)?;
+            }
             _ => {}
         }
         Ok(())
@@ -1104,12 +2372,10 @@ impl Binder {
 
         // Verify table exists in catalog
         if let Some(catalog) = &self.catalog {
-            let catalog_guard = catalog.read().unwrap();
-            let default_schema = catalog_guard.get_default_schema();
-            let schema_guard = default_schema.read().unwrap();
+            let catalog_guard = catalog.read_or_err()?;
 
-            if schema_guard.get_table(&insert.table_name).is_err() {
-                return Err(PrismDBError::Catalog(format!(
+            if catalog_guard.find_table(&insert.table_name).is_err() {
+                return Err(PrismDBError::NotFound(ObjectKind::Table, format!(
                     "Table '{}' does not exist",
                     insert.table_name
                 )));
@@ -1122,11 +2388,9 @@ impl Binder {
                 // For VALUES clause, create a LogicalValues plan
                 // Determine schema from table or use provided column names
                 let schema = if let Some(catalog) = &self.catalog {
-                    let catalog_guard = catalog.read().unwrap();
-                    let default_schema = catalog_guard.get_default_schema();
-                    let schema_guard = default_schema.read().unwrap();
-                    let table_arc = schema_guard.get_table(&insert.table_name)?;
-                    let table = table_arc.read().unwrap();
+                    let catalog_guard = catalog.read_or_err()?;
+                    let table_arc = catalog_guard.find_table(&insert.table_name)?;
+                    let table = table_arc.read_or_err()?;
                     let table_info = table.get_table_info();
 
                     // If specific columns are provided, use only those
@@ -1139,8 +2403,14 @@ impl Binder {
                             })
                             .collect()
                     } else {
-                        let mut selected_columns = Vec::new();
+                        let mut selected_columns: Vec<Column> = Vec::new();
                         for col_name in &insert.columns {
+                            if selected_columns.iter().any(|c| &c.name == col_name) {
+                                return Err(PrismDBError::Catalog(format!(
+                                    "Column '{}' specified more than once",
+                                    col_name
+                                )));
+                            }
                             if let Some(col_info) =
                                 table_info.columns.iter().find(|c| &c.name == col_name)
                             {
@@ -1149,7 +2419,7 @@ impl Binder {
                                     col_info.column_type.clone(),
                                 ));
                             } else {
-                                return Err(PrismDBError::Catalog(format!(
+                                return Err(PrismDBError::NotFound(ObjectKind::Column, format!(
                                     "Column '{}' does not exist in table '{}'",
                                     col_name, insert.table_name
                                 )));
@@ -1162,6 +2432,20 @@ impl Binder {
                     vec![Column::new("col0".to_string(), LogicalType::Integer)]
                 };
 
+                // Every row fills exactly the listed columns, or all of them
+                for row in rows {
+                    if row.len() > schema.len() {
+                        return Err(PrismDBError::Catalog(
+                            "INSERT has more expressions than target columns".to_string(),
+                        ));
+                    }
+                    if row.len() < schema.len() {
+                        return Err(PrismDBError::Catalog(
+                            "INSERT has more target columns than expressions".to_string(),
+                        ));
+                    }
+                }
+
                 LogicalPlan::Values(LogicalValues::new(rows.clone(), schema))
             }
             InsertSource::Select(select) => {
@@ -1186,20 +2470,19 @@ impl Binder {
     /// Bind UPDATE statement
     fn bind_update_statement(&mut self, update: &UpdateStatement) -> PrismDBResult<LogicalPlan> {
         // Verify table exists and get schema from catalog
-        let table_schema = if let Some(catalog) = &self.catalog {
-            let catalog_guard = catalog.read().unwrap();
-            let default_schema = catalog_guard.get_default_schema();
-            let schema_guard = default_schema.read().unwrap();
-
-            let table_arc = schema_guard.get_table(&update.table_name)?;
-            let table = table_arc.read().unwrap();
+        let (table_schema, collations) = if let Some(catalog) = &self.catalog {
+            let catalog_guard = catalog.read_or_err()?;
+            let table_arc = catalog_guard.find_table(&update.table_name)?;
+            let table = table_arc.read_or_err()?;
             let table_info = table.get_table_info();
 
-            table_info
+            let schema = table_info
                 .columns
                 .iter()
                 .map(|col_info| Column::new(col_info.name.clone(), col_info.column_type.clone()))
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+            let collations = table_info.columns.iter().map(|c| c.collation.clone()).collect();
+            (schema, collations)
         } else {
             return Err(PrismDBError::Catalog(format!(
                 "Cannot UPDATE without catalog"
@@ -1207,53 +2490,56 @@ impl Binder {
         };
 
         // Register table for column binding
-        self.context.add_table(&update.table_name, &table_schema);
+        let table_index = self.context.add_table(&update.table_name, &table_schema);
+        self.context.add_collations(table_index, collations);
 
         // Convert assignments to HashMap<column_name, expression>
         let mut assignments = std::collections::HashMap::new();
+        let mut defaults = Vec::new();
         for assignment in &update.assignments {
             // Validate that the column exists in the table
             if !table_schema.iter().any(|col| col.name == assignment.column) {
-                return Err(PrismDBError::Parse(format!(
+                return Err(PrismDBError::NotFound(ObjectKind::Column, format!(
                     "Column '{}' does not exist in table '{}'",
                     assignment.column, update.table_name
                 )));
             }
-            assignments.insert(assignment.column.clone(), assignment.value.clone());
+            match &assignment.value {
+                AssignmentValue::Expression(value) => {
+                    assignments.insert(assignment.column.clone(), value.clone());
+                }
+                AssignmentValue::Default => defaults.push(assignment.column.clone()),
+            }
         }
 
         // Bind WHERE clause if present
-        let condition = if let Some(where_expr) = &update.where_clause {
-            Some(where_expr.clone())
-        } else {
-            None
-        };
-
-        Ok(LogicalPlan::Update(LogicalUpdate::with_schema(
-            update.table_name.clone(),
-            assignments,
-            condition,
-            table_schema,
-        )))
+        let condition = update
+            .where_clause
+            .as_ref()
+            .map(|where_expr| self.apply_column_collations(where_expr));
+
+        Ok(LogicalPlan::Update(
+            LogicalUpdate::with_schema(update.table_name.clone(), assignments, condition, table_schema)
+                .with_defaults(defaults),
+        ))
     }
 
     /// Bind DELETE statement
     fn bind_delete_statement(&mut self, delete: &DeleteStatement) -> PrismDBResult<LogicalPlan> {
         // Verify table exists and get schema from catalog
-        let table_schema = if let Some(catalog) = &self.catalog {
-            let catalog_guard = catalog.read().unwrap();
-            let default_schema = catalog_guard.get_default_schema();
-            let schema_guard = default_schema.read().unwrap();
-
-            let table_arc = schema_guard.get_table(&delete.table_name)?;
-            let table = table_arc.read().unwrap();
+        let (table_schema, collations) = if let Some(catalog) = &self.catalog {
+            let catalog_guard = catalog.read_or_err()?;
+            let table_arc = catalog_guard.find_table(&delete.table_name)?;
+            let table = table_arc.read_or_err()?;
             let table_info = table.get_table_info();
 
-            table_info
+            let schema = table_info
                 .columns
                 .iter()
                 .map(|col_info| Column::new(col_info.name.clone(), col_info.column_type.clone()))
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+            let collations = table_info.columns.iter().map(|c| c.collation.clone()).collect();
+            (schema, collations)
         } else {
             return Err(PrismDBError::Catalog(format!(
                 "Cannot DELETE without catalog"
@@ -1261,14 +2547,14 @@ impl Binder {
         };
 
         // Register table for column binding
-        self.context.add_table(&delete.table_name, &table_schema);
+        let table_index = self.context.add_table(&delete.table_name, &table_schema);
+        self.context.add_collations(table_index, collations);
 
         // Bind WHERE clause if present
-        let condition = if let Some(where_expr) = &delete.where_clause {
-            Some(where_expr.clone())
-        } else {
-            None
-        };
+        let condition = delete
+            .where_clause
+            .as_ref()
+            .map(|where_expr| self.apply_column_collations(where_expr));
 
         Ok(LogicalPlan::Delete(LogicalDelete::with_schema(
             delete.table_name.clone(),
@@ -1277,22 +2563,109 @@ impl Binder {
         )))
     }
 
+    /// Bind MERGE statement
+    ///
+    /// The condition and the clauses see the table's columns qualified by its
+    /// alias and the source's columns qualified by the source's alias.
+    fn bind_merge_statement(&mut self, merge: &MergeStatement) -> PrismDBResult<LogicalPlan> {
+        let Some(catalog) = &self.catalog else {
+            return Err(PrismDBError::Catalog("Cannot MERGE without catalog".to_string()));
+        };
+        let qualifier = merge.alias.as_ref().unwrap_or(&merge.table_name);
+        let (table_schema, collations) = {
+            let catalog_guard = catalog.read_or_err()?;
+            let table_arc = catalog_guard.find_table(&merge.table_name)?;
+            let table = table_arc.read_or_err()?;
+            let table_info = table.get_table_info();
+            let schema = table_info
+                .columns
+                .iter()
+                .map(|col| Column::new(format!("{}.{}", qualifier, col.name), col.column_type.clone()))
+                .collect::<Vec<_>>();
+            let collations: Vec<_> = table_info.columns.iter().map(|c| c.collation.clone()).collect();
+            (schema, collations)
+        };
+        let table_index = self.context.add_table(qualifier, &table_schema);
+        self.context.add_collations(table_index, collations);
+
+        let source = self.bind_table_reference(&merge.source)?;
+        let source_qualifier = match &merge.source {
+            TableReference::Table { name, alias } => Some(alias.as_ref().unwrap_or(name)),
+            TableReference::Subquery { alias, .. } => Some(alias),
+            TableReference::Values { alias, .. } => alias.as_ref(),
+            _ => None,
+        };
+        let mut schema = table_schema;
+        let table_columns = schema.len();
+        schema.extend(source.schema().into_iter().map(|col| match source_qualifier {
+            Some(qualifier) => {
+                let name = col.name.rsplit('.').next().unwrap_or(&col.name);
+                Column::new(format!("{}.{}", qualifier, name), col.data_type)
+            }
+            None => col,
+        }));
+
+        let clauses = merge
+            .clauses
+            .iter()
+            .map(|clause| MergeClause {
+                condition: clause.condition.as_ref().map(|c| self.apply_column_collations(c)),
+                ..clause.clone()
+            })
+            .collect();
+
+        Ok(LogicalPlan::Merge(LogicalMerge {
+            table_name: merge.table_name.clone(),
+            source: Box::new(source),
+            schema,
+            table_columns,
+            condition: self.apply_column_collations(&merge.condition),
+            clauses,
+        }))
+    }
+
     /// Bind CREATE TABLE statement
     fn bind_create_table_statement(
         &mut self,
         create: &CreateTableStatement,
     ) -> PrismDBResult<LogicalPlan> {
-        let schema = create
-            .columns
+        let mut columns = create.columns.clone();
+        for column in &mut columns {
+            column.data_type = self.resolve_type(&column.data_type)?;
+        }
+        let schema = columns
             .iter()
             .map(|col| Column::new(col.name.clone(), col.data_type.clone()))
             .collect();
 
-        Ok(LogicalPlan::CreateTable(LogicalCreateTable::new(
-            create.table_name.clone(),
-            schema,
-            create.if_not_exists,
-        )))
+        Ok(LogicalPlan::CreateTable(
+            LogicalCreateTable::new(create.table_name.clone(), schema, create.if_not_exists)
+                .with_definitions(columns, create.constraints.clone()),
+        ))
+    }
+
+    /// Replace a type name the parser could not resolve with the enum type
+    /// of that name in the catalog
+    fn resolve_type(&self, data_type: &LogicalType) -> PrismDBResult<LogicalType> {
+        match data_type {
+            LogicalType::Enum(enum_type) if enum_type.values.is_empty() => {
+                let Some(catalog) = &self.catalog else {
+                    return Err(PrismDBError::Parse(format!(
+                        "Unknown data type: {}",
+                        enum_type.name
+                    )));
+                };
+                let catalog = catalog.read_or_err()?;
+                let resolved = catalog.find_type(&enum_type.name).map_err(|_| {
+                    PrismDBError::Parse(format!("Unknown data type: {}", enum_type.name))
+                })?;
+                Ok(LogicalType::Enum(resolved))
+            }
+            LogicalType::List(element_type) => {
+                Ok(LogicalType::List(Box::new(self.resolve_type(element_type)?)))
+            }
+            other => Ok(other.clone()),
+        }
     }
 
     /// Bind DROP TABLE statement
@@ -1399,11 +2772,28 @@ impl Binder {
     /// Bind EXPLAIN statement
     fn bind_explain_statement(&mut self, explain: &ExplainStatement) -> PrismDBResult<LogicalPlan> {
         let input_plan = self.bind_statement(&explain.statement)?;
-        Ok(LogicalPlan::Explain(LogicalExplain::new(
-            input_plan,
-            explain.analyze,
-            explain.verbose,
-        )))
+        Ok(LogicalPlan::Explain(
+            LogicalExplain::new(input_plan, explain.analyze, explain.verbose)
+                .with_format(explain.format),
+        ))
+    }
+
+    /// Whether `COUNT(DISTINCT column)` should be computed with HyperLogLog:
+    /// the column's estimated distinct count exceeds the session threshold
+    fn approximates_count_distinct(&self, name: &str, distinct: bool, arguments: &[AstExpression]) -> bool {
+        if self.approx_count_distinct_threshold == 0 || !distinct || !name.eq_ignore_ascii_case("COUNT") {
+            return false;
+        }
+        let [AstExpression::ColumnReference { table, column }] = arguments else {
+            return false;
+        };
+        self.context
+            .resolve_column(table.as_deref(), column)
+            .ok()
+            .and_then(|(table_index, column_index, _)| {
+                self.context.distinct_estimates.get(&(table_index, column_index))
+            })
+            .is_some_and(|&estimate| estimate > self.approx_count_distinct_threshold)
     }
 
     /// Extract aggregate functions from an AST expression
@@ -1417,7 +2807,28 @@ impl Binder {
                 name,
                 arguments,
                 distinct,
+                filter,
+                order_by,
+                within_group,
             } => {
+                if !Self::is_aggregate_function(name) {
+                    return Err(PrismDBError::InvalidArgument(format!(
+                        "FILTER and ORDER BY are only allowed on aggregate functions, not {}",
+                        name
+                    )));
+                }
+                if *within_group
+                    && !matches!(
+                        name.to_uppercase().as_str(),
+                        "PERCENTILE_CONT" | "PERCENTILE_DISC" | "MODE"
+                    )
+                {
+                    return Err(PrismDBError::InvalidArgument(format!(
+                        "{} is not an ordered-set aggregate and does not support WITHIN GROUP",
+                        name
+                    )));
+                }
+
                 // Convert arguments
                 let arg_exprs: Result<Vec<_>, _> = arguments
                     .iter()
@@ -1431,12 +2842,43 @@ impl Binder {
                     .map(|arg| self.infer_expression_type(arg))
                     .collect();
                 let arg_types = arg_types?;
-                let return_type = self.infer_aggregate_type(name, &arg_types)?;
 
+                let filter = filter
+                    .as_ref()
+                    .map(|filter| self.convert_ast_expression(filter))
+                    .transpose()?;
+
+                let order_by = order_by
+                    .iter()
+                    .map(|item| -> PrismDBResult<SortExpression> {
+                        Ok(SortExpression {
+                            expression: self.convert_ast_expression(&item.expression)?,
+                            ascending: item.ascending,
+                            nulls_first: item
+                                .nulls_first
+                                .unwrap_or_else(|| self.default_null_order.nulls_first(item.ascending)),
+                        })
+                    })
+                    .collect::<PrismDBResult<Vec<_>>>()?;
+
+                // An ordered-set aggregate's input is its sort key, not its arguments
+                let return_type = match order_by.first() {
+                    Some(sort) if *within_group => {
+                        let input_type = self.infer_expression_type(&sort.expression)?;
+                        self.infer_aggregate_type(name, &[input_type])?
+                    }
+                    _ => self.infer_aggregate_type(name, &arg_types)?,
+                };
+
+                let approximate = self.approximates_count_distinct(name, *distinct, arguments);
                 aggregates.push(AggregateExpression {
                     function_name: name.clone(),
                     arguments: arg_exprs,
                     distinct: *distinct,
+                    filter,
+                    order_by,
+                    within_group: *within_group,
+                    approximate,
                     return_type,
                 });
             }
@@ -1467,6 +2909,10 @@ impl Binder {
                         function_name: name.clone(),
                         arguments: arg_exprs,
                         distinct: *distinct,
+                        filter: None,
+                        order_by: Vec::new(),
+                        within_group: false,
+                        approximate: self.approximates_count_distinct(name, *distinct, arguments),
                         return_type,
                     });
                 } else {
@@ -1516,8 +2962,8 @@ impl Binder {
         matches!(
             name.to_uppercase().as_str(),
             "COUNT" | "SUM" | "AVG" | "MIN" | "MAX" | "STDDEV" | "VARIANCE" | "STRING_AGG"
-                | "MEDIAN" | "MODE" | "PERCENTILE_CONT" | "PERCENTILE_DISC"
-                | "APPROX_COUNT_DISTINCT" | "APPROX_QUANTILE"
+                | "ARRAY_AGG" | "LIST" | "MEDIAN" | "MODE" | "PERCENTILE_CONT" | "PERCENTILE_DISC"
+                | "APPROX_COUNT_DISTINCT" | "APPROX_QUANTILE" | "RESERVOIR_SAMPLE"
                 | "FIRST" | "LAST" | "ARG_MIN" | "ARG_MAX"
                 | "BOOL_AND" | "BOOL_OR"
                 | "CORR" | "COVAR_POP" | "COVAR_SAMP"
@@ -1525,6 +2971,39 @@ impl Binder {
         )
     }
 
+    /// Output column of the aggregate in `aggregates` that an aggregate call computes
+    fn aggregate_column(expr: &Expression, aggregates: &[AggregateExpression]) -> Option<String> {
+        use crate::parser::ast::Expression as AstExpr;
+
+        let column = match expr {
+            AstExpr::AggregateFunction {
+                name,
+                arguments,
+                distinct,
+                filter,
+                order_by,
+                within_group,
+            } => AggregateExpression::column_name_of(
+                name,
+                arguments,
+                *distinct,
+                filter.as_deref(),
+                order_by,
+                *within_group,
+            ),
+            AstExpr::FunctionCall {
+                name,
+                arguments,
+                distinct,
+            } => AggregateExpression::column_name_of(name, arguments, *distinct, None, &[], false),
+            _ => return None,
+        };
+        aggregates
+            .iter()
+            .any(|agg| agg.column_name() == column)
+            .then_some(column)
+    }
+
     /// Replace aggregate function calls with column references to aggregated results
     fn replace_aggregates_with_columns(
         &self,
@@ -1535,23 +3014,21 @@ impl Binder {
         use crate::parser::ast::Expression as AstExpr;
 
         match expr {
-            AstExpr::FunctionCall { name, arguments, distinct } | AstExpr::AggregateFunction { name, arguments, distinct } => {
+            AstExpr::AggregateFunction { .. } => {
+                // Replace with a reference to the matching aggregate's output column
+                match Self::aggregate_column(expr, aggregates) {
+                    Some(column) => Ok(AstExpr::ColumnReference { table: None, column }),
+                    // If not found, this might be a different aggregate - keep as is for now
+                    None => Ok(expr.clone()),
+                }
+            }
+            AstExpr::FunctionCall { name, arguments, distinct } => {
                 // Check if this is an aggregate function
                 if Self::is_aggregate_function(name) {
-                    // Find matching aggregate in the list
-                    for (_idx, agg) in aggregates.iter().enumerate() {
-                        if agg.function_name.to_uppercase() == name.to_uppercase() && agg.arguments.len() == arguments.len() {
-                            // Found a match - replace with column reference
-                            // Use the same naming convention as the aggregate schema
-                            let agg_name = format!("{}(...)", agg.function_name);
-                            return Ok(AstExpr::ColumnReference {
-                                table: None,
-                                column: agg_name,
-                            });
-                        }
+                    match Self::aggregate_column(expr, aggregates) {
+                        Some(column) => Ok(AstExpr::ColumnReference { table: None, column }),
+                        None => Ok(expr.clone()),
                     }
-                    // If not found, this might be a different aggregate - keep as is for now
-                    Ok(expr.clone())
                 } else {
                     // Not an aggregate - recursively process arguments
                     let new_args: Result<Vec<_>, _> = arguments
@@ -1602,7 +3079,14 @@ impl Binder {
                 }
             }
             "AVG" => Ok(LogicalType::Double),
-            "MIN" | "MAX" => {
+            "STRING_AGG" => Ok(LogicalType::Varchar),
+            "ARRAY_AGG" | "LIST" | "RESERVOIR_SAMPLE" => Ok(LogicalType::List(Box::new(
+                arg_types.first().cloned().unwrap_or(LogicalType::Integer),
+            ))),
+            "MEDIAN" | "PERCENTILE_CONT" | "PERCENTILE_DISC" | "APPROX_QUANTILE" => {
+                Ok(LogicalType::Double)
+            }
+            "MIN" | "MAX" | "MODE" => {
                 if arg_types.is_empty() {
                     Ok(LogicalType::Integer)
                 } else {
@@ -1623,35 +3107,16 @@ impl Binder {
     ) -> PrismDBResult<AstExpression> {
         match expr {
             // If it's an aggregate function, replace with column reference to Aggregate output
-            AstExpression::AggregateFunction {
-                name,
-                arguments,
-                distinct,
-            } => {
-                // Find this aggregate in the list
-                for (idx, agg) in aggregates.iter().enumerate() {
-                    // Check if this aggregate matches
-                    if agg.function_name.to_uppercase() == name.to_uppercase()
-                        && agg.distinct == *distinct
-                        && agg.arguments.len() == arguments.len()
-                    {
-                        // Found a match - return column reference to aggregate output
-                        // Aggregate outputs start after GROUP BY columns
-                        let _column_index = group_by_exprs.len() + idx;
-                        let column_name = format!("{}(...)", name);
-
-                        return Ok(AstExpression::ColumnReference {
-                            table: None,
-                            column: column_name,
-                        });
-                    }
+            AstExpression::AggregateFunction { name, .. } => {
+                // Return a column reference to the matching aggregate's output
+                match Self::aggregate_column(expr, aggregates) {
+                    Some(column) => Ok(AstExpression::ColumnReference { table: None, column }),
+                    // If not found, this shouldn't happen - return error
+                    None => Err(PrismDBError::InvalidValue(format!(
+                        "Aggregate function {} not found in aggregate list",
+                        name
+                    ))),
                 }
-
-                // If not found, this shouldn't happen - return error
-                Err(PrismDBError::InvalidValue(format!(
-                    "Aggregate function {} not found in aggregate list",
-                    name
-                )))
             }
 
             // If it's a column reference, check if it's a GROUP BY column
@@ -1677,6 +3142,17 @@ impl Binder {
                 self.convert_ast_expression(expr)
             }
 
+            // Aggregates parsed as plain function calls, e.g. STRING_AGG or MEDIAN
+            AstExpression::FunctionCall { name, .. } if Self::is_aggregate_function(name) => {
+                match Self::aggregate_column(expr, aggregates) {
+                    Some(column) => Ok(AstExpression::ColumnReference { table: None, column }),
+                    None => Err(PrismDBError::InvalidValue(format!(
+                        "Aggregate function {} not found in aggregate list",
+                        name
+                    ))),
+                }
+            }
+
             // For other expressions, recursively process to replace nested aggregates
             AstExpression::FunctionCall { name, arguments, distinct } => {
                 // Recursively process arguments to replace nested aggregates
@@ -1736,14 +3212,18 @@ impl Binder {
                 })
             }
 
-            AstExpression::Cast { expression, data_type } => {
+            AstExpression::Cast { expression, data_type, try_cast } => {
                 let processed_expr = self.bind_select_expression_with_aggregates(expression, group_by_exprs, aggregates)?;
                 Ok(AstExpression::Cast {
                     expression: Box::new(processed_expr),
                     data_type: data_type.clone(),
+                    try_cast: *try_cast,
                 })
             }
 
+            // Computed below the projection by the Window node
+            AstExpression::WindowFunction { .. } => self.convert_ast_expression(expr),
+
             // For literals and other simple expressions, just return as-is
             _ => Ok(expr.clone())
         }
@@ -0,0 +1,558 @@
+//! EXPLAIN rendering
+//!
+//! Turns a physical plan into a tree of [`ExplainNode`]s: one per operator,
+//! with its name, the properties that distinguish it (table, keys,
+//! predicates...) and a rough estimate of the rows it produces. The tree
+//! renders either as indented text or as JSON for tools.
+
+use crate::common::allocator::format_memory_size;
+use crate::expression::expression::{
+    CastExpression, ColumnRefExpression, ComparisonExpression, ConstantExpression, Expression,
+    ExpressionRef, FunctionExpression,
+};
+use crate::planner::physical_plan::{
+    IndexLookup, PhysicalJoinType, PhysicalPlan, PhysicalSortExpression, PhysicalWindowFunction,
+};
+use serde_json::json;
+
+/// One operator of an explained plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainNode {
+    /// Operator name, e.g. HASH_JOIN
+    pub name: String,
+    /// Operator details in display order
+    pub properties: Vec<(String, String)>,
+    /// Rough number of rows the operator produces, if it produces rows
+    pub estimated_rows: Option<usize>,
+    pub children: Vec<ExplainNode>,
+}
+
+impl ExplainNode {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            properties: Vec::new(),
+            estimated_rows: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn property(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.properties.push((key.to_string(), value.into()));
+        self
+    }
+
+    /// Add a property listing `items`, skipped when there are none
+    fn list_property(self, key: &str, items: Vec<String>) -> Self {
+        if items.is_empty() {
+            self
+        } else {
+            self.property(key, items.join(", "))
+        }
+    }
+
+    /// Explain `plan`; `table_rows` gives the current row count of a table,
+    /// and `verbose` adds each operator's output columns
+    ///
+    /// Estimates start from table row counts and are carried up the plan with
+    /// fixed rules: filters keep a fifth of their input, grouped aggregates at
+    /// most their input, equi-joins the larger side.
+    pub fn from_plan(
+        plan: &PhysicalPlan,
+        verbose: bool,
+        table_rows: &dyn Fn(&str) -> Option<usize>,
+    ) -> Self {
+        let mut children: Vec<ExplainNode> = plan
+            .children()
+            .into_iter()
+            .map(|child| Self::from_plan(child, verbose, table_rows))
+            .collect();
+        let input_rows = children.first().and_then(|child| child.estimated_rows);
+        let right_rows = children.get(1).and_then(|child| child.estimated_rows);
+
+        let (node, estimated_rows) = match plan {
+            PhysicalPlan::TableScan(scan) => {
+                let rows = table_rows(&scan.table_name)
+                    .map(|rows| if scan.filters.is_empty() { rows } else { selective(rows) })
+                    .map(|rows| scan.limit.map_or(rows, |limit| rows.min(limit)));
+                let node = ExplainNode::new("TABLE_SCAN")
+                    .property("table", scan.table_name.clone())
+                    .list_property("filters", describe_all(&scan.filters));
+                let node = match scan.limit {
+                    Some(limit) => node.property("limit", limit.to_string()),
+                    None => node,
+                };
+                (node, rows)
+            }
+            PhysicalPlan::CountStar(count) => (
+                ExplainNode::new("COUNT_STAR").property("table", count.table_name.clone()),
+                Some(1),
+            ),
+            PhysicalPlan::Filter(filter) => (
+                ExplainNode::new("FILTER").property("predicate", describe(&filter.predicate)),
+                input_rows.map(selective),
+            ),
+            PhysicalPlan::Qualify(qualify) => (
+                ExplainNode::new("QUALIFY").property("predicate", describe(&qualify.predicate)),
+                input_rows.map(selective),
+            ),
+            PhysicalPlan::Window(window) => (
+                ExplainNode::new("WINDOW")
+                    .list_property("functions", window.functions.iter().map(describe_window).collect()),
+                input_rows,
+            ),
+            PhysicalPlan::Projection(projection) => (
+                ExplainNode::new("PROJECTION")
+                    .list_property("expressions", describe_all(&projection.expressions)),
+                input_rows,
+            ),
+            PhysicalPlan::Unnest(unnest) => (
+                ExplainNode::new("UNNEST").list_property(
+                    "columns",
+                    unnest
+                        .unnest_columns
+                        .iter()
+                        .map(|&index| unnest.schema[index].name.clone())
+                        .collect(),
+                ),
+                input_rows,
+            ),
+            PhysicalPlan::Limit(limit) => (
+                ExplainNode::new("LIMIT")
+                    .property(
+                        "limit",
+                        match limit.limit {
+                            usize::MAX => "ALL".to_string(),
+                            limit => limit.to_string(),
+                        },
+                    )
+                    .property("offset", limit.offset.to_string()),
+                input_rows.map(|rows| rows.saturating_sub(limit.offset).min(limit.limit)),
+            ),
+            PhysicalPlan::Distinct(_) => (ExplainNode::new("DISTINCT"), input_rows),
+            PhysicalPlan::Sort(sort) => match sort.limit {
+                Some(limit) => (
+                    ExplainNode::new("TOP_N")
+                        .list_property("keys", describe_sort(&sort.expressions))
+                        .property("limit", limit.to_string()),
+                    input_rows.map(|rows| rows.min(limit)),
+                ),
+                None => (
+                    ExplainNode::new("ORDER_BY").list_property("keys", describe_sort(&sort.expressions)),
+                    input_rows,
+                ),
+            },
+            PhysicalPlan::Aggregate(aggregate) => (
+                ExplainNode::new("AGGREGATE")
+                    .list_property("groups", describe_all(&aggregate.group_by))
+                    .list_property(
+                        "aggregates",
+                        aggregate.aggregates.iter().map(describe_aggregate).collect(),
+                    ),
+                grouped_rows(aggregate.group_by.is_empty(), input_rows),
+            ),
+            PhysicalPlan::HashAggregate(aggregate) => (
+                ExplainNode::new("HASH_AGGREGATE")
+                    .list_property("groups", describe_all(&aggregate.group_by))
+                    .list_property(
+                        "aggregates",
+                        aggregate.aggregates.iter().map(describe_aggregate).collect(),
+                    ),
+                grouped_rows(aggregate.group_by.is_empty(), input_rows),
+            ),
+            PhysicalPlan::StreamingAggregate(aggregate) => (
+                ExplainNode::new("STREAMING_AGGREGATE")
+                    .list_property("groups", describe_all(&aggregate.group_by))
+                    .list_property(
+                        "aggregates",
+                        aggregate.aggregates.iter().map(describe_aggregate).collect(),
+                    ),
+                grouped_rows(false, input_rows),
+            ),
+            PhysicalPlan::Join(join) => {
+                let node = ExplainNode::new("NESTED_LOOP_JOIN")
+                    .property("join_type", join_type_name(&join.join_type));
+                let node = match &join.condition {
+                    Some(condition) => node.property("condition", describe(condition)),
+                    None => node,
+                };
+                (node, join_rows(&join.join_type, input_rows, right_rows))
+            }
+            PhysicalPlan::HashJoin(join) => {
+                let node = ExplainNode::new("HASH_JOIN")
+                    .property("join_type", join_type_name(&join.join_type))
+                    .list_property("keys", describe_keys(&join.left_keys, &join.right_keys));
+                let node = match &join.condition {
+                    Some(condition) => node.property("condition", describe(condition)),
+                    None => node,
+                };
+                (node, join_rows(&join.join_type, input_rows, right_rows))
+            }
+            PhysicalPlan::SortMergeJoin(join) => {
+                let node = ExplainNode::new("SORT_MERGE_JOIN")
+                    .property("join_type", join_type_name(&join.join_type))
+                    .list_property("keys", describe_keys(&join.left_keys, &join.right_keys));
+                let node = match &join.condition {
+                    Some(condition) => node.property("condition", describe(condition)),
+                    None => node,
+                };
+                (node, join_rows(&join.join_type, input_rows, right_rows))
+            }
+            PhysicalPlan::AsOfJoin(join) => {
+                let equal_keys = join.left_keys.len().saturating_sub(1);
+                let node = ExplainNode::new("ASOF_JOIN")
+                    .property("join_type", join_type_name(&join.join_type))
+                    .list_property(
+                        "keys",
+                        describe_keys(&join.left_keys[..equal_keys], &join.right_keys[..equal_keys]),
+                    );
+                let node = match (join.left_keys.last(), join.right_keys.last()) {
+                    (Some(left), Some(right)) => node.property(
+                        "condition",
+                        format!("{} {} {}", describe(left), join.comparison, describe(right)),
+                    ),
+                    _ => node,
+                };
+                // At most one match per left row
+                let rows = match join.join_type {
+                    PhysicalJoinType::Left => input_rows,
+                    _ => input_rows.zip(right_rows).map(|(left, right)| left.min(right)),
+                };
+                (node, rows)
+            }
+            PhysicalPlan::Union(union) => (
+                ExplainNode::new(if union.all { "UNION_ALL" } else { "UNION" }),
+                input_rows.zip(right_rows).map(|(left, right)| left + right),
+            ),
+            PhysicalPlan::Intersect(intersect) => (
+                ExplainNode::new(if intersect.all { "INTERSECT_ALL" } else { "INTERSECT" }),
+                input_rows.zip(right_rows).map(|(left, right)| left.min(right)),
+            ),
+            PhysicalPlan::Except(except) => (
+                ExplainNode::new(if except.all { "EXCEPT_ALL" } else { "EXCEPT" }),
+                input_rows,
+            ),
+            PhysicalPlan::Insert(insert) => (
+                ExplainNode::new("INSERT")
+                    .property("table", insert.table_name.clone())
+                    .list_property("columns", insert.column_names.clone()),
+                input_rows,
+            ),
+            PhysicalPlan::Update(update) => {
+                let mut assignments: Vec<String> = update
+                    .assignments
+                    .iter()
+                    .map(|(column, value)| format!("{} = {}", column, describe(value)))
+                    .chain(update.defaults.iter().map(|column| format!("{} = DEFAULT", column)))
+                    .collect();
+                assignments.sort();
+                let node = ExplainNode::new("UPDATE")
+                    .property("table", update.table_name.clone())
+                    .list_property("set", assignments);
+                children.push(Self::target_scan(
+                    &update.table_name,
+                    update.condition.as_ref(),
+                    update.index_lookup.as_ref(),
+                    table_rows,
+                ));
+                (node, None)
+            }
+            PhysicalPlan::Delete(delete) => {
+                children.push(Self::target_scan(
+                    &delete.table_name,
+                    delete.condition.as_ref(),
+                    delete.index_lookup.as_ref(),
+                    table_rows,
+                ));
+                (ExplainNode::new("DELETE").property("table", delete.table_name.clone()), None)
+            }
+            PhysicalPlan::Merge(merge) => (
+                ExplainNode::new("MERGE")
+                    .property("table", merge.table_name.clone())
+                    .property("condition", describe(&merge.condition)),
+                None,
+            ),
+            PhysicalPlan::CreateTable(create) => (
+                ExplainNode::new("CREATE_TABLE").property("table", create.table_name.clone()),
+                None,
+            ),
+            PhysicalPlan::DropTable(drop) => (
+                ExplainNode::new("DROP_TABLE").property("table", drop.table_name.clone()),
+                None,
+            ),
+            PhysicalPlan::Explain(_) => (ExplainNode::new("EXPLAIN"), Some(1)),
+            PhysicalPlan::Values(values) => (ExplainNode::new("VALUES"), Some(values.values.len())),
+            PhysicalPlan::Pivot(pivot) => (
+                ExplainNode::new("PIVOT")
+                    .list_property("on", describe_all(&pivot.on_columns))
+                    .list_property("groups", describe_all(&pivot.group_by)),
+                input_rows,
+            ),
+            PhysicalPlan::Unpivot(unpivot) => (
+                ExplainNode::new("UNPIVOT").list_property("on", describe_all(&unpivot.on_columns)),
+                input_rows.map(|rows| rows * unpivot.on_columns.len().max(1)),
+            ),
+            PhysicalPlan::RecursiveCTE(cte) => (
+                ExplainNode::new("RECURSIVE_CTE").property("name", cte.name.clone()),
+                None,
+            ),
+            PhysicalPlan::IteratorStream(stream) => (
+                ExplainNode::new("COLUMN_DATA_SCAN"),
+                Some(stream.chunks.iter().map(|chunk| chunk.len()).sum()),
+            ),
+            PhysicalPlan::CreateMaterializedView(create) => (
+                ExplainNode::new("CREATE_MATERIALIZED_VIEW").property("view", create.view_name.clone()),
+                None,
+            ),
+            PhysicalPlan::DropMaterializedView(drop) => (
+                ExplainNode::new("DROP_MATERIALIZED_VIEW").property("view", drop.view_name.clone()),
+                None,
+            ),
+            PhysicalPlan::RefreshMaterializedView(refresh) => (
+                ExplainNode::new("REFRESH_MATERIALIZED_VIEW").property("view", refresh.view_name.clone()),
+                None,
+            ),
+            PhysicalPlan::EmptyResult(_) => (ExplainNode::new("EMPTY_RESULT"), Some(0)),
+        };
+
+        let node = if verbose {
+            node.list_property("output", plan.schema().into_iter().map(|column| column.name).collect())
+        } else {
+            node
+        };
+        ExplainNode {
+            estimated_rows,
+            children,
+            ..node
+        }
+    }
+
+    /// The scan an UPDATE or DELETE makes of its table for the rows its
+    /// condition matches, through an index when it has one
+    fn target_scan(
+        table_name: &str,
+        condition: Option<&ExpressionRef>,
+        index_lookup: Option<&IndexLookup>,
+        table_rows: &dyn Fn(&str) -> Option<usize>,
+    ) -> ExplainNode {
+        let node = match index_lookup {
+            Some(lookup) => ExplainNode::new("INDEX_SCAN")
+                .property("table", table_name)
+                .property("index", lookup.index_name.clone()),
+            None => ExplainNode::new("TABLE_SCAN").property("table", table_name),
+        };
+        let node = node.list_property("filters", condition.map(describe).into_iter().collect());
+        ExplainNode {
+            estimated_rows: table_rows(table_name)
+                .map(|rows| if condition.is_some() { selective(rows) } else { rows }),
+            ..node
+        }
+    }
+
+    /// Add the peak memory of each blocking operator, measured by running the
+    /// plan for EXPLAIN ANALYZE
+    ///
+    /// `peaks` lists operators by name in the order they started, which puts
+    /// an operator ahead of those below it; each node takes the first
+    /// unclaimed entry with its name.
+    pub fn with_memory(mut self, peaks: &[(String, usize)]) -> Self {
+        let mut claimed = vec![false; peaks.len()];
+        self.claim_memory(peaks, &mut claimed);
+        self
+    }
+
+    fn claim_memory(&mut self, peaks: &[(String, usize)], claimed: &mut [bool]) {
+        let entry = peaks
+            .iter()
+            .zip(claimed.iter())
+            .position(|((name, _), &taken)| !taken && *name == self.name);
+        if let Some(index) = entry {
+            claimed[index] = true;
+            self.properties
+                .push(("peak_memory".to_string(), format_memory_size(peaks[index].1)));
+        }
+        for child in &mut self.children {
+            child.claim_memory(peaks, claimed);
+        }
+    }
+
+    /// Indented text rendering, one operator per line with its details below it
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(0, &mut text);
+        text
+    }
+
+    fn write_text(&self, depth: usize, text: &mut String) {
+        let indent = "  ".repeat(depth);
+        text.push_str(&indent);
+        text.push_str(&self.name);
+        if let Some(rows) = self.estimated_rows {
+            text.push_str(&format!(" (~{} rows)", rows));
+        }
+        text.push('\n');
+        for (key, value) in &self.properties {
+            text.push_str(&format!("{}    {}: {}\n", indent, key, value));
+        }
+        for child in &self.children {
+            child.write_text(depth + 1, text);
+        }
+    }
+
+    /// JSON rendering: `{"name", "properties", "estimated_rows", "children"}`
+    /// per operator
+    pub fn to_json(&self) -> serde_json::Value {
+        let properties: serde_json::Map<String, serde_json::Value> = self
+            .properties
+            .iter()
+            .map(|(key, value)| (key.clone(), json!(value)))
+            .collect();
+        json!({
+            "name": self.name,
+            "properties": properties,
+            "estimated_rows": self.estimated_rows,
+            "children": self.children.iter().map(ExplainNode::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Rows left by a filter whose selectivity is unknown
+fn selective(rows: usize) -> usize {
+    rows.div_ceil(5)
+}
+
+fn grouped_rows(global: bool, input_rows: Option<usize>) -> Option<usize> {
+    if global {
+        Some(1)
+    } else {
+        input_rows
+    }
+}
+
+fn join_rows(join_type: &PhysicalJoinType, left: Option<usize>, right: Option<usize>) -> Option<usize> {
+    match join_type {
+        PhysicalJoinType::Cross => left.zip(right).map(|(left, right)| left * right),
+        PhysicalJoinType::Semi | PhysicalJoinType::Anti => left,
+        PhysicalJoinType::Left => left,
+        PhysicalJoinType::Right => right,
+        _ => left.zip(right).map(|(left, right)| left.max(right)),
+    }
+}
+
+fn join_type_name(join_type: &PhysicalJoinType) -> String {
+    format!("{:?}", join_type).to_uppercase()
+}
+
+fn describe_all(expressions: &[ExpressionRef]) -> Vec<String> {
+    expressions.iter().map(describe).collect()
+}
+
+fn describe_keys(left: &[ExpressionRef], right: &[ExpressionRef]) -> Vec<String> {
+    left.iter()
+        .zip(right)
+        .map(|(left, right)| format!("{} = {}", describe(left), describe(right)))
+        .collect()
+}
+
+fn describe_sort(expressions: &[PhysicalSortExpression]) -> Vec<String> {
+    expressions
+        .iter()
+        .map(|sort| {
+            let collation = match &sort.collation {
+                Some(collation) => format!(" COLLATE {}", collation),
+                None => String::new(),
+            };
+            format!(
+                "{}{} {}",
+                describe(&sort.expression),
+                collation,
+                if sort.ascending { "ASC" } else { "DESC" }
+            )
+        })
+        .collect()
+}
+
+fn describe_aggregate(aggregate: &crate::planner::physical_plan::PhysicalAggregateExpression) -> String {
+    let arguments = if aggregate.arguments.is_empty() {
+        "*".to_string()
+    } else {
+        describe_all(&aggregate.arguments).join(", ")
+    };
+    format!(
+        "{}({}{})",
+        aggregate.function_name.to_uppercase(),
+        if aggregate.distinct { "DISTINCT " } else { "" },
+        arguments
+    )
+}
+
+fn describe_window(function: &PhysicalWindowFunction) -> String {
+    let mut spec = Vec::new();
+    if !function.partition_by.is_empty() {
+        spec.push(format!("PARTITION BY {}", describe_all(&function.partition_by).join(", ")));
+    }
+    if !function.order_by.is_empty() {
+        spec.push(format!("ORDER BY {}", describe_sort(&function.order_by).join(", ")));
+    }
+    format!(
+        "{}({}) OVER ({})",
+        function.name,
+        describe_all(&function.arguments).join(", "),
+        spec.join(" ")
+    )
+}
+
+/// SQL-like text of a bound expression
+fn describe(expression: &ExpressionRef) -> String {
+    describe_expression(expression.as_ref())
+}
+
+fn describe_expression(expression: &dyn Expression) -> String {
+    let any = expression.as_any();
+    if let Some(column) = any.downcast_ref::<ColumnRefExpression>() {
+        column.column_name().to_string()
+    } else if let Some(constant) = any.downcast_ref::<ConstantExpression>() {
+        constant.value().to_sql_literal()
+    } else if let Some(comparison) = any.downcast_ref::<ComparisonExpression>() {
+        format!(
+            "{} {} {}",
+            describe_expression(comparison.left()),
+            comparison.comparison_type(),
+            describe_expression(comparison.right())
+        )
+    } else if let Some(cast) = any.downcast_ref::<CastExpression>() {
+        format!(
+            "{}({} AS {})",
+            if cast.try_cast() { "TRY_CAST" } else { "CAST" },
+            describe_expression(cast.child()),
+            cast.return_type()
+        )
+    } else if let Some(function) = any.downcast_ref::<FunctionExpression>() {
+        let arguments = describe_all(&function.children());
+        let operator = match function.function_name() {
+            "ADD" => Some("+"),
+            "SUBTRACT" => Some("-"),
+            "MULTIPLY" => Some("*"),
+            "DIVIDE" => Some("/"),
+            "INTEGER_DIVIDE" => Some("//"),
+            "MODULO" => Some("%"),
+            "AND" => Some("AND"),
+            "OR" => Some("OR"),
+            "LIKE" => Some("LIKE"),
+            _ => None,
+        };
+        match (operator, arguments.as_slice()) {
+            (Some(operator), [left, right]) => format!("({} {} {})", left, operator, right),
+            _ => format!("{}({})", function.function_name(), arguments.join(", ")),
+        }
+    } else {
+        // Subqueries, CASE and window functions: name the kind of expression
+        let debug = format!("{:?}", expression);
+        debug
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches("Expression")
+            .to_uppercase()
+    }
+}
@@ -4,7 +4,9 @@
 //! without specifying how to do it. Logical plans are database-agnostic and
 //! focus on the relational algebra operations.
 
-use crate::parser::ast::Expression;
+use crate::parser::ast::{
+    ColumnDefinition, ExplainFormat, Expression, MergeClause, OrderByExpression, TableConstraint,
+};
 use crate::types::LogicalType;
 use std::collections::HashMap;
 
@@ -17,10 +19,16 @@ pub enum LogicalPlan {
     Filter(LogicalFilter),
     /// Filter rows based on window function results (QUALIFY clause)
     Qualify(LogicalQualify),
+    /// Compute window functions, appending one column per function
+    Window(LogicalWindow),
     /// Project (select) columns
     Projection(LogicalProjection),
+    /// Expand list columns into one row per element (UNNEST in the SELECT list)
+    Unnest(LogicalUnnest),
     /// Limit the number of rows
     Limit(LogicalLimit),
+    /// Remove duplicate rows (SELECT DISTINCT)
+    Distinct(LogicalDistinct),
     /// Sort rows
     Sort(LogicalSort),
     /// Aggregate rows
@@ -39,6 +47,8 @@ pub enum LogicalPlan {
     Update(LogicalUpdate),
     /// Delete data from a table
     Delete(LogicalDelete),
+    /// Update, delete or insert table rows per the source rows they match
+    Merge(LogicalMerge),
     /// Create a table
     CreateTable(LogicalCreateTable),
     /// Drop a table
@@ -70,8 +80,11 @@ impl LogicalPlan {
             LogicalPlan::TableScan(scan) => scan.schema.clone(),
             LogicalPlan::Filter(filter) => filter.input.schema(),
             LogicalPlan::Qualify(qualify) => qualify.input.schema(),
+            LogicalPlan::Window(window) => window.schema.clone(),
             LogicalPlan::Projection(proj) => proj.schema.clone(),
+            LogicalPlan::Unnest(unnest) => unnest.schema.clone(),
             LogicalPlan::Limit(limit) => limit.input.schema(),
+            LogicalPlan::Distinct(distinct) => distinct.input.schema(),
             LogicalPlan::Sort(sort) => sort.input.schema(),
             LogicalPlan::Aggregate(agg) => agg.schema.clone(),
             LogicalPlan::Join(join) => join.schema.clone(),
@@ -81,12 +94,13 @@ impl LogicalPlan {
             LogicalPlan::Insert(_) => vec![],
             LogicalPlan::Update(_) => vec![],
             LogicalPlan::Delete(_) => vec![],
+            LogicalPlan::Merge(_) => vec![],
             LogicalPlan::CreateTable(_) => vec![],
             LogicalPlan::DropTable(_) => vec![],
             LogicalPlan::CreateMaterializedView(_) => vec![],
             LogicalPlan::DropMaterializedView(_) => vec![],
             LogicalPlan::RefreshMaterializedView(_) => vec![],
-            LogicalPlan::Explain(_) => vec![Column::new("plan".to_string(), LogicalType::Text)],
+            LogicalPlan::Explain(_) => vec![Column::new("plan".to_string(), LogicalType::Varchar)],
             LogicalPlan::Values(values) => values.schema.clone(),
             LogicalPlan::Pivot(pivot) => pivot.schema.clone(),
             LogicalPlan::Unpivot(unpivot) => unpivot.schema.clone(),
@@ -101,8 +115,11 @@ impl LogicalPlan {
             LogicalPlan::TableScan(_) => vec![],
             LogicalPlan::Filter(filter) => vec![&filter.input],
             LogicalPlan::Qualify(qualify) => vec![&qualify.input],
+            LogicalPlan::Window(window) => vec![&window.input],
             LogicalPlan::Projection(proj) => vec![&proj.input],
+            LogicalPlan::Unnest(unnest) => vec![&unnest.input],
             LogicalPlan::Limit(limit) => vec![&limit.input],
+            LogicalPlan::Distinct(distinct) => vec![&distinct.input],
             LogicalPlan::Sort(sort) => vec![&sort.input],
             LogicalPlan::Aggregate(agg) => vec![&agg.input],
             LogicalPlan::Join(join) => vec![&join.left, &join.right],
@@ -112,6 +129,7 @@ impl LogicalPlan {
             LogicalPlan::Insert(insert) => vec![&insert.input],
             LogicalPlan::Update(_) => vec![],
             LogicalPlan::Delete(_) => vec![],
+            LogicalPlan::Merge(merge) => vec![&merge.source],
             LogicalPlan::CreateTable(_) => vec![],
             LogicalPlan::DropTable(_) => vec![],
             LogicalPlan::CreateMaterializedView(cmv) => vec![&cmv.query],
@@ -132,8 +150,11 @@ impl LogicalPlan {
             LogicalPlan::TableScan(_) => vec![],
             LogicalPlan::Filter(filter) => vec![&mut filter.input],
             LogicalPlan::Qualify(qualify) => vec![&mut qualify.input],
+            LogicalPlan::Window(window) => vec![&mut window.input],
             LogicalPlan::Projection(proj) => vec![&mut proj.input],
+            LogicalPlan::Unnest(unnest) => vec![&mut unnest.input],
             LogicalPlan::Limit(limit) => vec![&mut limit.input],
+            LogicalPlan::Distinct(distinct) => vec![&mut distinct.input],
             LogicalPlan::Sort(sort) => vec![&mut sort.input],
             LogicalPlan::Aggregate(agg) => vec![&mut agg.input],
             LogicalPlan::Join(join) => vec![&mut join.left, &mut join.right],
@@ -143,6 +164,7 @@ impl LogicalPlan {
             LogicalPlan::Insert(insert) => vec![&mut insert.input],
             LogicalPlan::Update(_) => vec![],
             LogicalPlan::Delete(_) => vec![],
+            LogicalPlan::Merge(merge) => vec![&mut merge.source],
             LogicalPlan::CreateTable(_) => vec![],
             LogicalPlan::DropTable(_) => vec![],
             LogicalPlan::CreateMaterializedView(cmv) => vec![&mut cmv.query],
@@ -227,6 +249,55 @@ impl LogicalQualify {
     }
 }
 
+/// Window operation - computes window functions over its input rows
+///
+/// Every input row is kept, with one column per window function appended
+/// after the input columns, so QUALIFY, ORDER BY and the SELECT list above
+/// can refer to the results by name.
+#[derive(Debug, Clone)]
+pub struct LogicalWindow {
+    pub input: Box<LogicalPlan>,
+    /// `Expression::WindowFunction`s whose arguments and window spec are
+    /// bound to the input
+    pub functions: Vec<Expression>,
+    /// Input columns followed by one column per function
+    pub schema: Vec<Column>,
+}
+
+impl LogicalWindow {
+    pub fn new(input: LogicalPlan, functions: Vec<Expression>, schema: Vec<Column>) -> Self {
+        Self {
+            input: Box::new(input),
+            functions,
+            schema,
+        }
+    }
+}
+
+/// UNNEST operation - expands list columns of its input into rows
+///
+/// Each input row produces one output row per element of its unnested
+/// columns, or per combination of elements when several are unnested. The
+/// other columns are repeated on every output row.
+#[derive(Debug, Clone)]
+pub struct LogicalUnnest {
+    pub input: Box<LogicalPlan>,
+    /// Positions of the list columns to expand
+    pub unnest_columns: Vec<usize>,
+    /// Output schema: the input schema with unnested columns of element type
+    pub schema: Vec<Column>,
+}
+
+impl LogicalUnnest {
+    pub fn new(input: LogicalPlan, unnest_columns: Vec<usize>, schema: Vec<Column>) -> Self {
+        Self {
+            input: Box::new(input),
+            unnest_columns,
+            schema,
+        }
+    }
+}
+
 /// Projection operation
 #[derive(Debug, Clone)]
 pub struct LogicalProjection {
@@ -263,6 +334,20 @@ impl LogicalLimit {
     }
 }
 
+/// Duplicate removal, keeping the first of equal rows in input order
+#[derive(Debug, Clone)]
+pub struct LogicalDistinct {
+    pub input: Box<LogicalPlan>,
+}
+
+impl LogicalDistinct {
+    pub fn new(input: LogicalPlan) -> Self {
+        Self {
+            input: Box::new(input),
+        }
+    }
+}
+
 /// Sort operation
 #[derive(Debug, Clone)]
 pub struct LogicalSort {
@@ -277,6 +362,53 @@ pub struct SortExpression {
     pub nulls_first: bool,
 }
 
+/// Placement of NULLs for ORDER BY items that don't specify NULLS FIRST/LAST
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullOrder {
+    /// NULLs sort as if larger than any value: last for ASC, first for DESC (SQL standard)
+    #[default]
+    NullsLargest,
+    /// NULLs sort as if smaller than any value: first for ASC, last for DESC
+    NullsSmallest,
+    /// NULLs always come first
+    NullsFirst,
+    /// NULLs always come last
+    NullsLast,
+}
+
+impl NullOrder {
+    /// Parse a setting value such as `nulls_last`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "nulls_largest" => Some(NullOrder::NullsLargest),
+            "nulls_smallest" => Some(NullOrder::NullsSmallest),
+            "nulls_first" => Some(NullOrder::NullsFirst),
+            "nulls_last" => Some(NullOrder::NullsLast),
+            _ => None,
+        }
+    }
+
+    /// Setting value naming this ordering, the inverse of `from_name`
+    pub fn name(&self) -> &'static str {
+        match self {
+            NullOrder::NullsLargest => "nulls_largest",
+            NullOrder::NullsSmallest => "nulls_smallest",
+            NullOrder::NullsFirst => "nulls_first",
+            NullOrder::NullsLast => "nulls_last",
+        }
+    }
+
+    /// Resolve whether NULLs come first for a sort key with the given direction
+    pub fn nulls_first(&self, ascending: bool) -> bool {
+        match self {
+            NullOrder::NullsLargest => !ascending,
+            NullOrder::NullsSmallest => ascending,
+            NullOrder::NullsFirst => true,
+            NullOrder::NullsLast => false,
+        }
+    }
+}
+
 impl LogicalSort {
     pub fn new(input: LogicalPlan, expressions: Vec<SortExpression>) -> Self {
         Self {
@@ -300,9 +432,74 @@ pub struct AggregateExpression {
     pub function_name: String,
     pub arguments: Vec<Expression>,
     pub distinct: bool,
+    /// `FILTER (WHERE ...)`: only rows passing it are aggregated
+    pub filter: Option<Expression>,
+    /// Order the aggregated values are consumed in (`STRING_AGG(x, ',' ORDER BY x)`)
+    pub order_by: Vec<SortExpression>,
+    /// `order_by` came from `WITHIN GROUP (ORDER BY ...)`: the ordered-set
+    /// aggregate aggregates the first sort key, and its arguments are parameters
+    pub within_group: bool,
+    /// `COUNT(DISTINCT column)` to be estimated with HyperLogLog, as the
+    /// column is expected to hold more distinct values than the session's
+    /// `approx_count_distinct_threshold`
+    pub approximate: bool,
     pub return_type: LogicalType,
 }
 
+impl AggregateExpression {
+    /// Name of the aggregate's output column, which the SELECT list and HAVING
+    /// refer to it by
+    ///
+    /// The name is the aggregate's SQL text, so aggregates differing only in
+    /// their arguments, filter or ordering get different columns. NULLS
+    /// FIRST/LAST is left out, as it is resolved against the session default.
+    pub fn column_name(&self) -> String {
+        let order_by: Vec<OrderByExpression> = self
+            .order_by
+            .iter()
+            .map(|sort| OrderByExpression {
+                expression: sort.expression.clone(),
+                ascending: sort.ascending,
+                nulls_first: None,
+            })
+            .collect();
+        Self::column_name_of(
+            &self.function_name,
+            &self.arguments,
+            self.distinct,
+            self.filter.as_ref(),
+            &order_by,
+            self.within_group,
+        )
+    }
+
+    /// Output column name of an aggregate call with these parts
+    pub fn column_name_of(
+        function_name: &str,
+        arguments: &[Expression],
+        distinct: bool,
+        filter: Option<&Expression>,
+        order_by: &[OrderByExpression],
+        within_group: bool,
+    ) -> String {
+        Expression::AggregateFunction {
+            name: function_name.to_uppercase(),
+            arguments: arguments.to_vec(),
+            distinct,
+            filter: filter.cloned().map(Box::new),
+            order_by: order_by
+                .iter()
+                .map(|item| OrderByExpression {
+                    nulls_first: None,
+                    ..item.clone()
+                })
+                .collect(),
+            within_group,
+        }
+        .to_string()
+    }
+}
+
 impl LogicalAggregate {
     pub fn new(
         input: LogicalPlan,
@@ -338,6 +535,10 @@ pub enum JoinType {
     Cross,
     Semi,
     Anti,
+    /// Each left row with the nearest right row the condition's inequality allows
+    AsOf,
+    /// `AsOf`, keeping left rows without a match
+    AsOfLeft,
 }
 
 impl LogicalJoin {
@@ -384,15 +585,17 @@ impl LogicalUnion {
 pub struct LogicalIntersect {
     pub left: Box<LogicalPlan>,
     pub right: Box<LogicalPlan>,
+    pub all: bool,  // true for INTERSECT ALL, which keeps duplicates
     pub schema: Vec<Column>,
 }
 
 impl LogicalIntersect {
-    pub fn new(left: LogicalPlan, right: LogicalPlan) -> Self {
+    pub fn new(left: LogicalPlan, right: LogicalPlan, all: bool) -> Self {
         let schema = left.schema();
         Self {
             left: Box::new(left),
             right: Box::new(right),
+            all,
             schema,
         }
     }
@@ -403,15 +606,17 @@ impl LogicalIntersect {
 pub struct LogicalExcept {
     pub left: Box<LogicalPlan>,
     pub right: Box<LogicalPlan>,
+    pub all: bool,  // true for EXCEPT ALL, which keeps duplicates
     pub schema: Vec<Column>,
 }
 
 impl LogicalExcept {
-    pub fn new(left: LogicalPlan, right: LogicalPlan) -> Self {
+    pub fn new(left: LogicalPlan, right: LogicalPlan, all: bool) -> Self {
         let schema = left.schema();
         Self {
             left: Box::new(left),
             right: Box::new(right),
+            all,
             schema,
         }
     }
@@ -442,6 +647,8 @@ pub struct LogicalUpdate {
     pub assignments: HashMap<String, Expression>,
     pub condition: Option<Expression>,
     pub schema: Vec<Column>,  // Table schema for expression binding
+    /// Columns set to DEFAULT
+    pub defaults: Vec<String>,
 }
 
 impl LogicalUpdate {
@@ -455,6 +662,7 @@ impl LogicalUpdate {
             assignments,
             condition,
             schema: Vec::new(),  // Will be set by binder
+            defaults: Vec::new(),
         }
     }
 
@@ -469,8 +677,15 @@ impl LogicalUpdate {
             assignments,
             condition,
             schema,
+            defaults: Vec::new(),
         }
     }
+
+    /// Set `defaults` to their declared DEFAULT
+    pub fn with_defaults(mut self, defaults: Vec<String>) -> Self {
+        self.defaults = defaults;
+        self
+    }
 }
 
 /// Delete operation
@@ -503,12 +718,30 @@ impl LogicalDelete {
     }
 }
 
+/// Merge operation
+#[derive(Debug, Clone)]
+pub struct LogicalMerge {
+    pub table_name: String,
+    pub source: Box<LogicalPlan>,
+    /// The table's columns followed by the source's, qualified by their
+    /// aliases, for expression binding
+    pub schema: Vec<Column>,
+    /// Number of leading `schema` columns that belong to the table
+    pub table_columns: usize,
+    pub condition: Expression,
+    pub clauses: Vec<MergeClause>,
+}
+
 /// Create table operation
 #[derive(Debug, Clone)]
 pub struct LogicalCreateTable {
     pub table_name: String,
     pub schema: Vec<Column>,
     pub if_not_exists: bool,
+    /// Column definitions (NOT NULL, DEFAULT, column constraints)
+    pub columns: Vec<ColumnDefinition>,
+    /// Table-level constraints
+    pub constraints: Vec<TableConstraint>,
 }
 
 impl LogicalCreateTable {
@@ -517,8 +750,20 @@ impl LogicalCreateTable {
             table_name,
             schema,
             if_not_exists,
+            columns: Vec::new(),
+            constraints: Vec::new(),
         }
     }
+
+    pub fn with_definitions(
+        mut self,
+        columns: Vec<ColumnDefinition>,
+        constraints: Vec<TableConstraint>,
+    ) -> Self {
+        self.columns = columns;
+        self.constraints = constraints;
+        self
+    }
 }
 
 /// Drop table operation
@@ -608,6 +853,7 @@ pub struct LogicalExplain {
     pub input: Box<LogicalPlan>,
     pub analyze: bool,
     pub verbose: bool,
+    pub format: ExplainFormat,
 }
 
 impl LogicalExplain {
@@ -616,8 +862,14 @@ impl LogicalExplain {
             input: Box::new(input),
             analyze,
             verbose,
+            format: ExplainFormat::Text,
         }
     }
+
+    pub fn with_format(mut self, format: ExplainFormat) -> Self {
+        self.format = format;
+        self
+    }
 }
 
 /// Values operation (produces constant rows)
@@ -9,6 +9,7 @@
 //! 4. **Execution**: Generates executable operators
 
 pub mod binder;
+pub mod explain;
 pub mod logical_plan;
 pub mod optimizer;
 pub mod physical_plan;
@@ -18,6 +19,7 @@ pub mod planner;
 mod tests;
 
 pub use binder::*;
+pub use explain::ExplainNode;
 pub use logical_plan::*;
 pub use optimizer::*;
 pub use physical_plan::*;
@@ -17,6 +17,8 @@ pub struct QueryOptimizer {
     catalog: Option<Arc<std::sync::RwLock<crate::catalog::Catalog>>>,
     transaction_manager: Option<Arc<crate::storage::transaction::TransactionManager>>,
     ctes: HashMap<String, crate::planner::logical_plan::LogicalPlan>,
+    /// Types of the query's parameters, by index
+    parameter_types: Vec<crate::types::LogicalType>,
 }
 
 impl QueryOptimizer {
@@ -26,8 +28,11 @@ impl QueryOptimizer {
 
         // Add default optimization rules (order matters!)
         rules.push(Box::new(ConstantFoldingRule)); // Fold constants first
+        rules.push(Box::new(LikePrefixRule)); // Bound anchored LIKE patterns
+        rules.push(Box::new(PredicateSimplificationRule)); // Merge and dedupe filter conjuncts
         rules.push(Box::new(FilterPushdownRule)); // Push filters down
         rules.push(Box::new(LimitPushdownRule)); // Push limits down
+        rules.push(Box::new(RowNumberTopNRule)); // Turn ROW_NUMBER pages into Top-N
         rules.push(Box::new(ProjectionPushdownRule)); // Push projections down
         rules.push(Box::new(JoinOrderingRule)); // Optimize join order
         rules.push(Box::new(AggregateRule)); // Optimize aggregates
@@ -37,6 +42,7 @@ impl QueryOptimizer {
             catalog: None,
             transaction_manager: None,
             ctes: HashMap::new(),
+            parameter_types: Vec::new(),
         }
     }
 
@@ -57,6 +63,13 @@ impl QueryOptimizer {
         self
     }
 
+    /// Set the types of the query's parameters, for a plan that will be run
+    /// with different parameter values
+    pub fn with_parameter_types(mut self, parameter_types: Vec<crate::types::LogicalType>) -> Self {
+        self.parameter_types = parameter_types;
+        self
+    }
+
     /// Optimize a logical plan into a physical plan
     pub fn optimize(&mut self, logical_plan: LogicalPlan) -> PrismDBResult<PhysicalPlan> {
         // Apply logical optimization rules
@@ -103,6 +116,7 @@ impl QueryOptimizer {
                 let mut physical_scan = PhysicalTableScan::new(scan.table_name, physical_schema);
                 physical_scan.filters = bound_filters;
                 physical_scan.limit = scan.limit;
+                physical_scan.ranges = Self::scan_ranges(&scan.filters, &binder);
 
                 Ok(PhysicalPlan::TableScan(physical_scan))
             }
@@ -138,6 +152,73 @@ impl QueryOptimizer {
                     bound_predicate,
                 )))
             }
+            LogicalPlan::Window(window) => {
+                let input_schema = Self::get_input_schema(&window.input);
+                let binder_context = Self::create_binder_context(&input_schema);
+                let binder = self.create_expression_binder(binder_context);
+
+                let functions = window
+                    .functions
+                    .iter()
+                    .zip(&window.schema[input_schema.len()..])
+                    .map(|(function, column)| -> PrismDBResult<PhysicalWindowFunction> {
+                        let crate::parser::ast::Expression::WindowFunction {
+                            name,
+                            arguments,
+                            window_spec,
+                        } = function
+                        else {
+                            return Err(crate::common::error::PrismDBError::Internal(format!(
+                                "Expected a window function, got {}",
+                                function
+                            )));
+                        };
+                        let bind_all = |exprs: &[crate::parser::ast::Expression]| {
+                            exprs
+                                .iter()
+                                .map(|expr| binder.bind_expression(expr))
+                                .collect::<PrismDBResult<Vec<_>>>()
+                        };
+                        // COUNT(*) counts rows, so it needs no argument
+                        let arguments = match arguments.as_slice() {
+                            [crate::parser::ast::Expression::Wildcard] => Vec::new(),
+                            arguments => bind_all(arguments)?,
+                        };
+                        let order_by = window_spec
+                            .order_by
+                            .iter()
+                            .map(|item| -> PrismDBResult<PhysicalSortExpression> {
+                                Ok(PhysicalSortExpression {
+                                    expression: binder.bind_expression(&item.expression)?,
+                                    ascending: item.ascending,
+                                    nulls_first: item.nulls_first.unwrap_or(!item.ascending),
+                                    collation: Self::collation(&item.expression)?,
+                                })
+                            })
+                            .collect::<PrismDBResult<_>>()?;
+                        Ok(PhysicalWindowFunction {
+                            name: name.to_uppercase(),
+                            arguments,
+                            partition_by: bind_all(&window_spec.partition_by)?,
+                            order_by,
+                            frame: window_spec.window_frame.clone(),
+                            return_type: column.data_type.clone(),
+                        })
+                    })
+                    .collect::<PrismDBResult<_>>()?;
+
+                let input = self.convert_to_physical(*window.input)?;
+                let physical_schema = window
+                    .schema
+                    .into_iter()
+                    .map(|col| PhysicalColumn::new(col.name, col.data_type))
+                    .collect();
+                Ok(PhysicalPlan::Window(PhysicalWindow::new(
+                    input,
+                    functions,
+                    physical_schema,
+                )))
+            }
             LogicalPlan::Projection(proj) => {
                 // Get schema from input for binding
                 let input_schema = Self::get_input_schema(&proj.input);
@@ -166,14 +247,40 @@ impl QueryOptimizer {
                     physical_schema,
                 )))
             }
+            LogicalPlan::Unnest(unnest) => {
+                let input = self.convert_to_physical(*unnest.input)?;
+                let physical_schema = unnest
+                    .schema
+                    .into_iter()
+                    .map(|col| PhysicalColumn::new(col.name, col.data_type))
+                    .collect();
+                Ok(PhysicalPlan::Unnest(PhysicalUnnest::new(
+                    input,
+                    unnest.unnest_columns,
+                    physical_schema,
+                )))
+            }
             LogicalPlan::Limit(limit) => {
-                let input = self.convert_to_physical(*limit.input)?;
+                let mut input = self.convert_to_physical(*limit.input)?;
+
+                // A limit over a sort only needs the sort's first rows (Top-N)
+                if let (PhysicalPlan::Sort(sort), false) = (&mut input, limit.limit == usize::MAX) {
+                    sort.limit = Some(limit.offset.saturating_add(limit.limit));
+                    if limit.offset == 0 {
+                        return Ok(input);
+                    }
+                }
+
                 Ok(PhysicalPlan::Limit(PhysicalLimit::new(
                     input,
                     limit.limit,
                     limit.offset,
                 )))
             }
+            LogicalPlan::Distinct(distinct) => {
+                let input = self.convert_to_physical(*distinct.input)?;
+                Ok(PhysicalPlan::Distinct(PhysicalDistinct::new(input)))
+            }
             LogicalPlan::Sort(sort) => {
                 // Get schema from input for binding
                 let input_schema = Self::get_input_schema(&sort.input);
@@ -190,6 +297,7 @@ impl QueryOptimizer {
                             expression: bound_expr,
                             ascending: expr.ascending,
                             nulls_first: expr.nulls_first,
+                            collation: Self::collation(&expr.expression)?,
                         })
                     })
                     .collect();
@@ -199,6 +307,11 @@ impl QueryOptimizer {
                 Ok(PhysicalPlan::Sort(PhysicalSort::new(input, sort_exprs)))
             }
             LogicalPlan::Aggregate(agg) => {
+                // An unfiltered COUNT(*) is answered from the table's row count
+                if let Some(count_star) = Self::count_star(&agg) {
+                    return Ok(PhysicalPlan::CountStar(count_star));
+                }
+
                 // Get schema from input for binding
                 let input_schema = Self::get_input_schema(&agg.input);
                 let binder_context = Self::create_binder_context(&input_schema);
@@ -222,10 +335,38 @@ impl QueryOptimizer {
                             .iter()
                             .map(|arg| binder.bind_expression(arg))
                             .collect();
+                        let filter = agg_expr
+                            .filter
+                            .as_ref()
+                            .map(|filter| binder.bind_expression(filter))
+                            .transpose()?;
+                        let order_by = agg_expr
+                            .order_by
+                            .iter()
+                            .map(|sort| -> PrismDBResult<PhysicalSortExpression> {
+                                Ok(PhysicalSortExpression {
+                                    expression: binder.bind_expression(&sort.expression)?,
+                                    ascending: sort.ascending,
+                                    nulls_first: sort.nulls_first,
+                                    collation: Self::collation(&sort.expression)?,
+                                })
+                            })
+                            .collect::<PrismDBResult<Vec<_>>>()?;
+                        let (arguments, parameters) =
+                            Self::split_aggregate_parameters(&agg_expr, bound_args?, &order_by)?;
+                        // An approximated COUNT(DISTINCT) needs no set of the values seen
+                        let (function_name, distinct) = if agg_expr.approximate {
+                            ("APPROX_COUNT_DISTINCT".to_string(), false)
+                        } else {
+                            (agg_expr.function_name, agg_expr.distinct)
+                        };
                         Ok(PhysicalAggregateExpression {
-                            function_name: agg_expr.function_name,
-                            arguments: bound_args?,
-                            distinct: agg_expr.distinct,
+                            function_name,
+                            arguments,
+                            distinct,
+                            filter,
+                            order_by,
+                            parameters,
                             return_type: agg_expr.return_type,
                         })
                     })
@@ -239,8 +380,18 @@ impl QueryOptimizer {
                     .map(|col| PhysicalColumn::new(col.name, col.data_type))
                     .collect();
 
-                // Choose between hash aggregate and regular aggregate
-                if !bound_group_by.is_empty() {
+                // Choose between hash aggregate and regular aggregate. An input already
+                // sorted on the group keys is aggregated one group at a time instead.
+                let group_columns: Option<Vec<usize>> =
+                    bound_group_by.iter().map(Self::column_ref_index).collect();
+                if group_columns.is_some_and(|columns| !columns.is_empty() && Self::is_sorted_on(&input, &columns)) {
+                    Ok(PhysicalPlan::StreamingAggregate(PhysicalStreamingAggregate::new(
+                        input,
+                        bound_group_by,
+                        physical_aggs,
+                        physical_schema,
+                    )))
+                } else if !bound_group_by.is_empty() {
                     Ok(PhysicalPlan::HashAggregate(PhysicalHashAggregate::new(
                         input,
                         bound_group_by,
@@ -258,8 +409,8 @@ impl QueryOptimizer {
             }
             LogicalPlan::Join(join) => {
                 let physical_join_type = match join.join_type {
-                    JoinType::Inner => PhysicalJoinType::Inner,
-                    JoinType::Left => PhysicalJoinType::Left,
+                    JoinType::Inner | JoinType::AsOf => PhysicalJoinType::Inner,
+                    JoinType::Left | JoinType::AsOfLeft => PhysicalJoinType::Left,
                     JoinType::Right => PhysicalJoinType::Right,
                     JoinType::Full => PhysicalJoinType::Full,
                     JoinType::Cross => PhysicalJoinType::Cross,
@@ -286,11 +437,38 @@ impl QueryOptimizer {
                     .map(|col| PhysicalColumn::new(col.name, col.data_type))
                     .collect();
 
+                if matches!(join.join_type, JoinType::AsOf | JoinType::AsOfLeft) {
+                    let (left_keys, right_keys, comparison) =
+                        Self::asof_join_keys(bound_condition.as_ref(), left.schema().len())?;
+                    return Ok(PhysicalPlan::AsOfJoin(PhysicalAsOfJoin::new(
+                        left,
+                        right,
+                        physical_join_type,
+                        left_keys,
+                        right_keys,
+                        comparison,
+                        physical_schema,
+                    )));
+                }
+
                 // Choose join strategy based on condition
                 if let Some(ref condition) = bound_condition {
                     // Extract join keys from condition for hash join
                     let (left_keys, right_keys) = self.extract_join_keys(condition, &left, &right)?;
 
+                    // Inputs that already arrive ordered on the keys merge without hashing
+                    if Self::inputs_sorted_on_keys(&left, &right, &left_keys, &right_keys) {
+                        return Ok(PhysicalPlan::SortMergeJoin(PhysicalSortMergeJoin::new(
+                            left,
+                            right,
+                            physical_join_type,
+                            left_keys,
+                            right_keys,
+                            bound_condition,
+                            physical_schema,
+                        )));
+                    }
+
                     Ok(PhysicalPlan::HashJoin(PhysicalHashJoin::new(
                         left,
                         right,
@@ -338,6 +516,7 @@ impl QueryOptimizer {
                 Ok(PhysicalPlan::Intersect(PhysicalIntersect::new(
                     left,
                     right,
+                    intersect.all,
                     physical_schema,
                 )))
             }
@@ -353,6 +532,7 @@ impl QueryOptimizer {
                 Ok(PhysicalPlan::Except(PhysicalExcept::new(
                     left,
                     right,
+                    except.all,
                     physical_schema,
                 )))
             }
@@ -386,11 +566,14 @@ impl QueryOptimizer {
                     None
                 };
 
-                Ok(PhysicalPlan::Update(PhysicalUpdate::new(
-                    update.table_name,
-                    bound_assignments,
-                    bound_condition,
-                )))
+                let mut physical_update =
+                    PhysicalUpdate::new(update.table_name, bound_assignments, bound_condition);
+                physical_update.defaults = update.defaults;
+                if let Some(condition) = &update.condition {
+                    physical_update.index_lookup =
+                        self.index_lookup(&physical_update.table_name, condition, &binder);
+                }
+                Ok(PhysicalPlan::Update(physical_update))
             }
             LogicalPlan::Delete(delete) => {
                 // Use the table schema from LogicalDelete for binding
@@ -404,10 +587,92 @@ impl QueryOptimizer {
                     None
                 };
 
-                Ok(PhysicalPlan::Delete(PhysicalDelete::new(
-                    delete.table_name,
-                    bound_condition,
-                )))
+                let mut physical_delete = PhysicalDelete::new(delete.table_name, bound_condition);
+                if let Some(condition) = &delete.condition {
+                    physical_delete.index_lookup =
+                        self.index_lookup(&physical_delete.table_name, condition, &binder);
+                }
+                Ok(PhysicalPlan::Delete(physical_delete))
+            }
+            LogicalPlan::Merge(merge) => {
+                use crate::parser::ast::{AssignmentValue, MergeAction};
+
+                let source = self.convert_to_physical(*merge.source)?;
+                let binder_context = Self::create_binder_context(&merge.schema);
+                let binder = self.create_expression_binder(binder_context);
+                let table_columns: Vec<&str> = merge.schema[..merge.table_columns]
+                    .iter()
+                    .map(|col| col.name.rsplit('.').next().unwrap_or(&col.name))
+                    .collect();
+                let column_index = |name: &str| {
+                    table_columns.iter().position(|col| *col == name).ok_or_else(|| {
+                        crate::common::error::PrismDBError::NotFound(crate::common::error::ObjectKind::Column, format!(
+                            "Column '{}' does not exist in table '{}'",
+                            name, merge.table_name
+                        ))
+                    })
+                };
+
+                let mut clauses = Vec::new();
+                for clause in &merge.clauses {
+                    let condition = match &clause.condition {
+                        Some(condition) => Some(binder.bind_expression(condition)?),
+                        None => None,
+                    };
+                    let action = match &clause.action {
+                        MergeAction::Update(assignments) => PhysicalMergeAction::Update(
+                            assignments
+                                .iter()
+                                .map(|assignment| {
+                                    let AssignmentValue::Expression(value) = &assignment.value else {
+                                        return Err(crate::common::error::PrismDBError::NotImplemented(
+                                            "SET column = DEFAULT in MERGE".to_string(),
+                                        ));
+                                    };
+                                    Ok((column_index(&assignment.column)?, binder.bind_expression(value)?))
+                                })
+                                .collect::<PrismDBResult<_>>()?,
+                        ),
+                        MergeAction::Delete => PhysicalMergeAction::Delete,
+                        MergeAction::Insert { columns, values } => {
+                            let indexes = if columns.is_empty() {
+                                (0..table_columns.len()).collect()
+                            } else {
+                                columns
+                                    .iter()
+                                    .map(|column| column_index(column))
+                                    .collect::<PrismDBResult<Vec<_>>>()?
+                            };
+                            if indexes.len() != values.len() {
+                                return Err(crate::common::error::PrismDBError::Parse(format!(
+                                    "MERGE INSERT has {} columns but {} values",
+                                    indexes.len(),
+                                    values.len()
+                                )));
+                            }
+                            PhysicalMergeAction::Insert(
+                                indexes
+                                    .into_iter()
+                                    .zip(values)
+                                    .map(|(index, value)| Ok((index, binder.bind_expression(value)?)))
+                                    .collect::<PrismDBResult<_>>()?,
+                            )
+                        }
+                        MergeAction::DoNothing => PhysicalMergeAction::DoNothing,
+                    };
+                    clauses.push(PhysicalMergeClause {
+                        matched: clause.matched,
+                        condition,
+                        action,
+                    });
+                }
+
+                Ok(PhysicalPlan::Merge(PhysicalMerge {
+                    condition: binder.bind_expression(&merge.condition)?,
+                    table_name: merge.table_name,
+                    source: Box::new(source),
+                    clauses,
+                }))
             }
             LogicalPlan::CreateTable(create) => {
                 let physical_schema = create
@@ -416,11 +681,14 @@ impl QueryOptimizer {
                     .map(|col| PhysicalColumn::new(col.name, col.data_type))
                     .collect();
 
-                Ok(PhysicalPlan::CreateTable(PhysicalCreateTable::new(
-                    create.table_name,
-                    physical_schema,
-                    create.if_not_exists,
-                )))
+                Ok(PhysicalPlan::CreateTable(
+                    PhysicalCreateTable::new(
+                        create.table_name,
+                        physical_schema,
+                        create.if_not_exists,
+                    )
+                    .with_definitions(create.columns, create.constraints),
+                ))
             }
             LogicalPlan::DropTable(drop) => Ok(PhysicalPlan::DropTable(PhysicalDropTable::new(
                 drop.table_name,
@@ -466,11 +734,10 @@ impl QueryOptimizer {
             }
             LogicalPlan::Explain(explain) => {
                 let input = self.convert_to_physical(*explain.input)?;
-                Ok(PhysicalPlan::Explain(PhysicalExplain::new(
-                    input,
-                    explain.analyze,
-                    explain.verbose,
-                )))
+                Ok(PhysicalPlan::Explain(
+                    PhysicalExplain::new(input, explain.analyze, explain.verbose)
+                        .with_format(explain.format),
+                ))
             }
             LogicalPlan::Values(values) => {
                 // Bind all value expressions
@@ -638,7 +905,7 @@ impl QueryOptimizer {
 
     /// Create an expression binder with catalog/transaction context and CTEs if available
     fn create_expression_binder(&self, binder_context: BinderContext) -> ExpressionBinder {
-        if let (Some(catalog), Some(txn_mgr)) = (&self.catalog, &self.transaction_manager) {
+        let binder = if let (Some(catalog), Some(txn_mgr)) = (&self.catalog, &self.transaction_manager) {
             ExpressionBinder::new_with_ctes(
                 binder_context,
                 catalog.clone(),
@@ -647,7 +914,15 @@ impl QueryOptimizer {
             )
         } else {
             ExpressionBinder::new(binder_context)
-        }
+        };
+        binder.with_parameter_types(self.parameter_types.clone())
+    }
+
+    /// Collation named by a `COLLATE` on a sort key
+    fn collation(
+        expr: &crate::parser::ast::Expression,
+    ) -> PrismDBResult<Option<crate::types::Collation>> {
+        expr.collation().map(crate::types::Collation::from_name).transpose()
     }
 
     /// Helper method to create a BinderContext from a schema
@@ -670,6 +945,92 @@ impl QueryOptimizer {
         }
     }
 
+    /// Column bounds implied by a scan's filters, keyed by column index
+    ///
+    /// Only `column <op> literal` conjuncts contribute; a scan may skip any zone that
+    /// cannot hold a value inside every bound.
+    fn scan_ranges(
+        filters: &[crate::parser::ast::Expression],
+        binder: &ExpressionBinder,
+    ) -> Vec<(usize, crate::storage::ValueRange)> {
+        use crate::expression::expression::{ColumnRefExpression, ConstantExpression};
+        use crate::parser::ast::{BinaryOperator, Expression};
+        use crate::storage::ValueRange;
+
+        let mut conjuncts = Vec::new();
+        for filter in filters {
+            PredicateSimplificationRule::flatten_and(filter, &mut conjuncts);
+        }
+
+        let mut ranges = Vec::new();
+        for conjunct in conjuncts {
+            for (column, operator, literal) in PredicateSimplificationRule::column_comparisons(conjunct) {
+                let (Ok(column), Ok(constant)) = (
+                    binder.bind_expression(&column),
+                    binder.bind_expression(&Expression::Literal(literal)),
+                ) else {
+                    continue;
+                };
+                let (Some(column), Some(constant)) = (
+                    column.as_any().downcast_ref::<ColumnRefExpression>(),
+                    constant.as_any().downcast_ref::<ConstantExpression>(),
+                ) else {
+                    continue;
+                };
+                let value = constant.value().clone();
+                let range = match operator {
+                    BinaryOperator::Equals => ValueRange {
+                        lower: Some((value.clone(), true)),
+                        upper: Some((value, true)),
+                    },
+                    BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual => ValueRange {
+                        lower: Some((value, operator == BinaryOperator::GreaterThanOrEqual)),
+                        upper: None,
+                    },
+                    BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual => ValueRange {
+                        lower: None,
+                        upper: Some((value, operator == BinaryOperator::LessThanOrEqual)),
+                    },
+                    _ => continue,
+                };
+                ranges.push((column.column_index(), range));
+            }
+        }
+        ranges
+    }
+
+    /// Index of `table_name` that can find the rows an UPDATE or DELETE
+    /// `condition` may match
+    ///
+    /// The condition needs a `column <op> literal` conjunct on an indexed
+    /// column; every such conjunct on that column narrows the lookup. The
+    /// operator still checks the whole condition against the rows found.
+    fn index_lookup(
+        &self,
+        table_name: &str,
+        condition: &crate::parser::ast::Expression,
+        binder: &ExpressionBinder,
+    ) -> Option<IndexLookup> {
+        let catalog = self.catalog.as_ref()?.read().ok()?;
+        let table = catalog.find_table(table_name).ok()?;
+        let table_data = table.read().ok()?.get_data();
+        let table_data = table_data.read().ok()?;
+
+        let ranges = Self::scan_ranges(std::slice::from_ref(condition), binder);
+        let (column, index) = ranges
+            .iter()
+            .find_map(|(column, _)| Some((*column, table_data.index_on(*column)?)))?;
+        let range = ranges
+            .iter()
+            .filter(|(c, _)| *c == column)
+            .map(|(_, range)| range.clone())
+            .reduce(|a, b| a.intersect(&b))?;
+        Some(IndexLookup {
+            index_name: index.name().to_string(),
+            range,
+        })
+    }
+
     /// Extract join keys from an equality condition for hash join
     /// Returns (left_keys, right_keys) extracted from the condition
     fn extract_join_keys(
@@ -678,7 +1039,8 @@ impl QueryOptimizer {
         _left_plan: &PhysicalPlan,
         _right_plan: &PhysicalPlan,
     ) -> PrismDBResult<(Vec<ExpressionRef>, Vec<ExpressionRef>)> {
-        use crate::expression::{ComparisonExpression, ComparisonType};
+        use crate::expression::expression::Expression;
+        use crate::expression::{ComparisonExpression, ComparisonType, FunctionExpression};
 
         // For simple equality joins like "left.col = right.col"
         // Extract the column references from both sides
@@ -691,19 +1053,264 @@ impl QueryOptimizer {
             }
         }
 
-        // For more complex conditions (AND, OR, etc.), we would need more sophisticated extraction
+        // Each equality of "a = b AND c = d" is a key of its own
+        if let Some(func_expr) = condition.as_any().downcast_ref::<FunctionExpression>() {
+            if func_expr.function_name() == "AND" {
+                let mut left_keys = Vec::new();
+                let mut right_keys = Vec::new();
+                for conjunct in func_expr.children() {
+                    let (left, right) = self.extract_join_keys(&conjunct, _left_plan, _right_plan)?;
+                    left_keys.extend(left);
+                    right_keys.extend(right);
+                }
+                return Ok((left_keys, right_keys));
+            }
+        }
+
+        // For more complex conditions (OR, etc.), we would need more sophisticated extraction
         // For now, return empty keys which will cause a fallback behavior
         Ok((vec![], vec![]))
     }
 
+    /// Split an ASOF JOIN condition into its key pairs, left input first, and the
+    /// comparison of the left time to the right time. The condition must AND column
+    /// equalities with exactly one <, <=, > or >= between the times, which becomes
+    /// the last key pair.
+    fn asof_join_keys(
+        condition: Option<&ExpressionRef>,
+        left_width: usize,
+    ) -> PrismDBResult<(Vec<ExpressionRef>, Vec<ExpressionRef>, crate::expression::ComparisonType)> {
+        use crate::common::error::PrismDBError;
+        use crate::expression::expression::Expression;
+        use crate::expression::{ComparisonExpression, ComparisonType, FunctionExpression};
+
+        fn conjuncts(condition: &ExpressionRef, out: &mut Vec<ExpressionRef>) {
+            match condition.as_any().downcast_ref::<FunctionExpression>() {
+                Some(and) if and.function_name() == "AND" => {
+                    for child in and.children() {
+                        conjuncts(&child, out);
+                    }
+                }
+                _ => out.push(condition.clone()),
+            }
+        }
+
+        let invalid = || {
+            PrismDBError::InvalidArgument(
+                "ASOF JOIN condition must AND column equalities with one <, <=, > or >= \
+                 between a left and a right column"
+                    .to_string(),
+            )
+        };
+        let mut all = Vec::new();
+        conjuncts(condition.ok_or_else(invalid)?, &mut all);
+
+        let mut left_keys = Vec::new();
+        let mut right_keys = Vec::new();
+        let mut time = None;
+        for conjunct in &all {
+            let comparison = conjunct
+                .as_any()
+                .downcast_ref::<ComparisonExpression>()
+                .ok_or_else(invalid)?;
+            let (mut left, mut right) = (comparison.left_ref().clone(), comparison.right_ref().clone());
+            let mut comparison_type = comparison.comparison_type().clone();
+            match (Self::column_ref_index(&left), Self::column_ref_index(&right)) {
+                (Some(l), Some(r)) if l < left_width && r >= left_width => {}
+                (Some(l), Some(r)) if r < left_width && l >= left_width => {
+                    std::mem::swap(&mut left, &mut right);
+                    comparison_type = match comparison_type {
+                        ComparisonType::LessThan => ComparisonType::GreaterThan,
+                        ComparisonType::LessThanOrEqual => ComparisonType::GreaterThanOrEqual,
+                        ComparisonType::GreaterThan => ComparisonType::LessThan,
+                        ComparisonType::GreaterThanOrEqual => ComparisonType::LessThanOrEqual,
+                        other => other,
+                    };
+                }
+                _ => return Err(invalid()),
+            }
+            match comparison_type {
+                ComparisonType::Equal => {
+                    left_keys.push(left);
+                    right_keys.push(right);
+                }
+                ComparisonType::LessThan
+                | ComparisonType::LessThanOrEqual
+                | ComparisonType::GreaterThan
+                | ComparisonType::GreaterThanOrEqual
+                    if time.is_none() =>
+                {
+                    time = Some((left, right, comparison_type));
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        let (left_time, right_time, comparison) = time.ok_or_else(invalid)?;
+        left_keys.push(left_time);
+        right_keys.push(right_time);
+        Ok((left_keys, right_keys, comparison))
+    }
+
+    /// Check whether both join inputs are produced in ascending order of their join keys.
+    /// Keys are bound against the joined schema, so right-side columns are offset by the
+    /// width of the left input.
+    fn inputs_sorted_on_keys(
+        left: &PhysicalPlan,
+        right: &PhysicalPlan,
+        left_keys: &[ExpressionRef],
+        right_keys: &[ExpressionRef],
+    ) -> bool {
+        let left_width = left.schema().len();
+        let mut left_columns = Vec::with_capacity(left_keys.len());
+        let mut right_columns = Vec::with_capacity(right_keys.len());
+        for (left_key, right_key) in left_keys.iter().zip(right_keys) {
+            let (mut l, mut r) = match (Self::column_ref_index(left_key), Self::column_ref_index(right_key)) {
+                (Some(l), Some(r)) => (l, r),
+                _ => return false,
+            };
+            if l >= left_width {
+                std::mem::swap(&mut l, &mut r);
+            }
+            if l >= left_width || r < left_width {
+                return false;
+            }
+            left_columns.push(l);
+            right_columns.push(r - left_width);
+        }
+
+        !left_columns.is_empty()
+            && Self::is_sorted_on(left, &left_columns)
+            && Self::is_sorted_on(right, &right_columns)
+    }
+
+    /// Check whether a plan's output is sorted ascending on the given columns (as a prefix
+    /// of its sort order), looking through operators that preserve row order
+    fn is_sorted_on(plan: &PhysicalPlan, columns: &[usize]) -> bool {
+        match plan {
+            PhysicalPlan::Sort(sort) => {
+                sort.expressions.len() >= columns.len()
+                    && sort.expressions.iter().zip(columns).all(|(expr, &column)| {
+                        expr.ascending && Self::column_ref_index(&expr.expression) == Some(column)
+                    })
+            }
+            PhysicalPlan::Projection(proj) => {
+                let mut input_columns = Vec::with_capacity(columns.len());
+                for &column in columns {
+                    match proj.expressions.get(column).and_then(Self::column_ref_index) {
+                        Some(input_column) => input_columns.push(input_column),
+                        None => return false,
+                    }
+                }
+                Self::is_sorted_on(&proj.input, &input_columns)
+            }
+            PhysicalPlan::Filter(filter) => Self::is_sorted_on(&filter.input, columns),
+            PhysicalPlan::Limit(limit) => Self::is_sorted_on(&limit.input, columns),
+            PhysicalPlan::Distinct(distinct) => Self::is_sorted_on(&distinct.input, columns),
+            _ => false,
+        }
+    }
+
+    /// Split an aggregate's bound arguments into the per-row inputs and the
+    /// constant parameters that configure its state
+    ///
+    /// An ordered-set aggregate (`WITHIN GROUP`) aggregates its first sort key
+    /// and all its arguments are parameters; STRING_AGG, RESERVOIR_SAMPLE and
+    /// the percentile functions take parameters after their input.
+    fn split_aggregate_parameters(
+        agg_expr: &AggregateExpression,
+        mut arguments: Vec<ExpressionRef>,
+        order_by: &[PhysicalSortExpression],
+    ) -> PrismDBResult<(Vec<ExpressionRef>, Vec<crate::types::Value>)> {
+        use crate::common::error::PrismDBError;
+        use crate::expression::expression::ConstantExpression;
+
+        let parameter_arguments = if agg_expr.within_group {
+            let input = order_by[0].expression.clone();
+            std::mem::replace(&mut arguments, vec![input])
+        } else {
+            match agg_expr.function_name.to_uppercase().as_str() {
+                "STRING_AGG" | "PERCENTILE_CONT" | "PERCENTILE_DISC" | "APPROX_QUANTILE"
+                | "RESERVOIR_SAMPLE"
+                    if arguments.len() > 1 =>
+                {
+                    arguments.split_off(1)
+                }
+                _ => Vec::new(),
+            }
+        };
+
+        let parameters = parameter_arguments
+            .iter()
+            .map(|argument| {
+                argument
+                    .as_any()
+                    .downcast_ref::<ConstantExpression>()
+                    .map(|constant| constant.value().clone())
+                    .ok_or_else(|| {
+                        PrismDBError::InvalidArgument(format!(
+                            "{} parameters must be constants",
+                            agg_expr.function_name.to_uppercase()
+                        ))
+                    })
+            })
+            .collect::<PrismDBResult<Vec<_>>>()?;
+        Ok((arguments, parameters))
+    }
+
+    /// Match an aggregate that only counts every row of an unfiltered base table:
+    /// no GROUP BY, and each aggregate is COUNT(*) or COUNT of a non-NULL constant
+    fn count_star(agg: &LogicalAggregate) -> Option<PhysicalCountStar> {
+        use crate::parser::ast::{Expression, LiteralValue};
+
+        let LogicalPlan::TableScan(scan) = agg.input.as_ref() else {
+            return None;
+        };
+        let counts_all_rows = |agg_expr: &AggregateExpression| {
+            agg_expr.function_name.eq_ignore_ascii_case("count")
+                && !agg_expr.distinct
+                && agg_expr.filter.is_none()
+                && agg_expr.order_by.is_empty()
+                && match agg_expr.arguments.as_slice() {
+                    [] | [Expression::Wildcard] => true,
+                    [Expression::Literal(literal)] => *literal != LiteralValue::Null,
+                    _ => false,
+                }
+        };
+        if !agg.group_by.is_empty()
+            || agg.aggregates.is_empty()
+            || !agg.aggregates.iter().all(counts_all_rows)
+            || !scan.filters.is_empty()
+            || scan.limit.is_some()
+        {
+            return None;
+        }
+
+        let schema = agg
+            .schema
+            .iter()
+            .map(|col| PhysicalColumn::new(col.name.clone(), col.data_type.clone()))
+            .collect();
+        Some(PhysicalCountStar::new(scan.table_name.clone(), schema))
+    }
+
+    fn column_ref_index(expr: &ExpressionRef) -> Option<usize> {
+        expr.as_any()
+            .downcast_ref::<crate::expression::expression::ColumnRefExpression>()
+            .map(|col_ref| col_ref.column_index())
+    }
+
     /// Get input schema from a logical plan
     fn get_input_schema(plan: &LogicalPlan) -> Vec<Column> {
         match plan {
             LogicalPlan::TableScan(scan) => scan.schema.clone(),
             LogicalPlan::Filter(filter) => Self::get_input_schema(&filter.input),
             LogicalPlan::Qualify(qualify) => Self::get_input_schema(&qualify.input),
+            LogicalPlan::Window(window) => window.schema.clone(),
             LogicalPlan::Projection(proj) => proj.schema.clone(),
+            LogicalPlan::Unnest(unnest) => unnest.schema.clone(),
             LogicalPlan::Limit(limit) => Self::get_input_schema(&limit.input),
+            LogicalPlan::Distinct(distinct) => Self::get_input_schema(&distinct.input),
             LogicalPlan::Sort(sort) => Self::get_input_schema(&sort.input),
             LogicalPlan::Aggregate(agg) => agg.schema.clone(),
             LogicalPlan::Join(join) => join.schema.clone(),
@@ -713,6 +1320,7 @@ impl QueryOptimizer {
             LogicalPlan::Insert(_) => vec![],
             LogicalPlan::Update(_) => vec![],
             LogicalPlan::Delete(_) => vec![],
+            LogicalPlan::Merge(_) => vec![],
             LogicalPlan::CreateTable(_) => vec![],
             LogicalPlan::DropTable(_) => vec![],
             LogicalPlan::CreateMaterializedView(_) => vec![],
@@ -823,11 +1431,13 @@ impl OptimizationRule for ConstantFoldingRule {
                 Expression::Cast {
                     expression,
                     data_type,
+                    try_cast,
                 } => {
                     let folded_expr = fold_expression(expression);
                     Expression::Cast {
                         expression: Box::new(folded_expr),
                         data_type: data_type.clone(),
+                        try_cast: *try_cast,
                     }
                 }
                 _ => expr.clone(),
@@ -843,18 +1453,21 @@ impl OptimizationRule for ConstantFoldingRule {
             use crate::parser::ast::{BinaryOperator, LiteralValue};
 
             match (left, right) {
+                // `/` is true division, so integer operands fold to a float literal
+                (LiteralValue::Integer(l), LiteralValue::Integer(r))
+                    if *operator == BinaryOperator::Divide =>
+                {
+                    if *r != 0 {
+                        Some(LiteralValue::Float(*l as f64 / *r as f64))
+                    } else {
+                        None
+                    }
+                }
                 (LiteralValue::Integer(l), LiteralValue::Integer(r)) => {
                     let result = match operator {
                         BinaryOperator::Add => l + r,
                         BinaryOperator::Subtract => l - r,
                         BinaryOperator::Multiply => l * r,
-                        BinaryOperator::Divide => {
-                            if *r != 0 {
-                                l / r
-                            } else {
-                                return None;
-                            }
-                        }
                         BinaryOperator::Modulo => {
                             if *r != 0 {
                                 l % r
@@ -941,27 +1554,889 @@ impl OptimizationRule for ConstantFoldingRule {
     }
 }
 
+/// Predicate simplification rule - normalize a filter's conjunction list
+///
+/// Flattens nested ANDs, drops duplicate conjuncts, merges comparisons of a column
+/// against literals into the tightest bounds, and replaces filters that can never be
+/// true with an empty input.
+pub(crate) struct PredicateSimplificationRule;
+
+/// Bounds collected for one column while simplifying a conjunction
+#[derive(Default)]
+struct ColumnRange {
+    equal: Option<crate::parser::ast::LiteralValue>,
+    not_equal: Vec<crate::parser::ast::LiteralValue>,
+    /// (value, inclusive)
+    lower: Option<(crate::parser::ast::LiteralValue, bool)>,
+    upper: Option<(crate::parser::ast::LiteralValue, bool)>,
+}
+
+impl ColumnRange {
+    /// Whether a literal can be ordered against every bound already collected
+    fn accepts(&self, value: &crate::parser::ast::LiteralValue) -> bool {
+        self.equal
+            .iter()
+            .chain(&self.not_equal)
+            .chain(self.lower.iter().map(|(v, _)| v))
+            .chain(self.upper.iter().map(|(v, _)| v))
+            .all(|existing| PredicateSimplificationRule::compare(existing, value).is_some())
+    }
+}
+
+/// Result of simplifying a filter predicate
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SimplifiedPredicate {
+    /// No row can satisfy the predicate
+    AlwaysFalse,
+    /// Every row satisfies the predicate
+    AlwaysTrue,
+    Predicate(crate::parser::ast::Expression),
+}
+
+/// A conjunct slot in the simplified output, kept in first-appearance order
+enum Conjunct {
+    Other(crate::parser::ast::Expression),
+    Range(usize),
+}
+
+impl PredicateSimplificationRule {
+    /// Simplify a filter predicate
+    pub(crate) fn simplify(predicate: &crate::parser::ast::Expression) -> SimplifiedPredicate {
+        use crate::parser::ast::{BinaryOperator, Expression, LiteralValue};
+        use std::cmp::Ordering;
+
+        let mut conjuncts = Vec::new();
+        Self::flatten_and(predicate, &mut conjuncts);
+
+        let mut slots: Vec<Conjunct> = Vec::new();
+        let mut columns: Vec<(Expression, ColumnRange)> = Vec::new();
+
+        for conjunct in conjuncts {
+            match conjunct {
+                Expression::Literal(LiteralValue::Boolean(true)) => continue,
+                Expression::Literal(LiteralValue::Boolean(false))
+                | Expression::Literal(LiteralValue::Null) => {
+                    return SimplifiedPredicate::AlwaysFalse
+                }
+                _ => {}
+            }
+
+            // Only merge literals that order against the column's existing bounds
+            let comparisons = Self::column_comparisons(conjunct);
+            let mergeable = !comparisons.is_empty()
+                && comparisons.iter().all(|(column, _, value)| {
+                    columns
+                        .iter()
+                        .find(|(c, _)| c == column)
+                        .is_none_or(|(_, range)| range.accepts(value))
+                });
+            if !mergeable {
+                let duplicate = slots
+                    .iter()
+                    .any(|slot| matches!(slot, Conjunct::Other(existing) if existing == conjunct));
+                if !duplicate || !Self::is_deterministic(conjunct) {
+                    slots.push(Conjunct::Other(conjunct.clone()));
+                }
+                continue;
+            }
+
+            for (column, operator, value) in comparisons {
+                let idx = match columns.iter().position(|(c, _)| *c == column) {
+                    Some(idx) => idx,
+                    None => {
+                        columns.push((column, ColumnRange::default()));
+                        slots.push(Conjunct::Range(columns.len() - 1));
+                        columns.len() - 1
+                    }
+                };
+                let range = &mut columns[idx].1;
+                match operator {
+                    BinaryOperator::Equals => match &range.equal {
+                        Some(existing) if Self::order(existing, &value) != Ordering::Equal => {
+                            return SimplifiedPredicate::AlwaysFalse
+                        }
+                        _ => range.equal = Some(value),
+                    },
+                    BinaryOperator::NotEquals => range.not_equal.push(value),
+                    BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual => {
+                        let inclusive = operator == BinaryOperator::GreaterThanOrEqual;
+                        let tighter = match &range.lower {
+                            None => true,
+                            Some((current, current_inclusive)) => {
+                                match Self::order(&value, current) {
+                                    Ordering::Greater => true,
+                                    Ordering::Equal => *current_inclusive && !inclusive,
+                                    Ordering::Less => false,
+                                }
+                            }
+                        };
+                        if tighter {
+                            range.lower = Some((value, inclusive));
+                        }
+                    }
+                    _ => {
+                        let inclusive = operator == BinaryOperator::LessThanOrEqual;
+                        let tighter = match &range.upper {
+                            None => true,
+                            Some((current, current_inclusive)) => {
+                                match Self::order(&value, current) {
+                                    Ordering::Less => true,
+                                    Ordering::Equal => *current_inclusive && !inclusive,
+                                    Ordering::Greater => false,
+                                }
+                            }
+                        };
+                        if tighter {
+                            range.upper = Some((value, inclusive));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut simplified = Vec::new();
+        for slot in slots {
+            match slot {
+                Conjunct::Other(expr) => simplified.push(expr),
+                Conjunct::Range(idx) => {
+                    let (column, range) = &columns[idx];
+                    match Self::range_conjuncts(column, range) {
+                        Some(conjuncts) => simplified.extend(conjuncts),
+                        None => return SimplifiedPredicate::AlwaysFalse,
+                    }
+                }
+            }
+        }
+
+        simplified
+            .into_iter()
+            .reduce(|left, right| Expression::Binary {
+                left: Box::new(left),
+                operator: BinaryOperator::And,
+                right: Box::new(right),
+            })
+            .map_or(SimplifiedPredicate::AlwaysTrue, SimplifiedPredicate::Predicate)
+    }
+
+    fn flatten_and<'a>(
+        expr: &'a crate::parser::ast::Expression,
+        out: &mut Vec<&'a crate::parser::ast::Expression>,
+    ) {
+        use crate::parser::ast::{BinaryOperator, Expression};
+
+        match expr {
+            Expression::Binary {
+                left,
+                operator: BinaryOperator::And,
+                right,
+            } => {
+                Self::flatten_and(left, out);
+                Self::flatten_and(right, out);
+            }
+            _ => out.push(expr),
+        }
+    }
+
+    /// Break a conjunct into `column <op> literal` comparisons, with the column on the
+    /// left. Returns nothing if the conjunct has any other shape.
+    fn column_comparisons(
+        expr: &crate::parser::ast::Expression,
+    ) -> Vec<(
+        crate::parser::ast::Expression,
+        crate::parser::ast::BinaryOperator,
+        crate::parser::ast::LiteralValue,
+    )> {
+        use crate::parser::ast::{BinaryOperator, Expression, LiteralValue};
+
+        let is_bound = |literal: &LiteralValue| {
+            !matches!(literal, LiteralValue::Null | LiteralValue::Interval { .. })
+        };
+
+        match expr {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let flipped = match operator {
+                    BinaryOperator::Equals => BinaryOperator::Equals,
+                    BinaryOperator::NotEquals => BinaryOperator::NotEquals,
+                    BinaryOperator::LessThan => BinaryOperator::GreaterThan,
+                    BinaryOperator::LessThanOrEqual => BinaryOperator::GreaterThanOrEqual,
+                    BinaryOperator::GreaterThan => BinaryOperator::LessThan,
+                    BinaryOperator::GreaterThanOrEqual => BinaryOperator::LessThanOrEqual,
+                    _ => return vec![],
+                };
+                match (left.as_ref(), right.as_ref()) {
+                    (column @ Expression::ColumnReference { .. }, Expression::Literal(value))
+                        if is_bound(value) =>
+                    {
+                        vec![(column.clone(), operator.clone(), value.clone())]
+                    }
+                    (Expression::Literal(value), column @ Expression::ColumnReference { .. })
+                        if is_bound(value) =>
+                    {
+                        vec![(column.clone(), flipped, value.clone())]
+                    }
+                    _ => vec![],
+                }
+            }
+            Expression::Between {
+                expression,
+                low,
+                high,
+                not: false,
+            } => match (expression.as_ref(), low.as_ref(), high.as_ref()) {
+                (
+                    column @ Expression::ColumnReference { .. },
+                    Expression::Literal(low),
+                    Expression::Literal(high),
+                ) if is_bound(low) && is_bound(high) => vec![
+                    (column.clone(), BinaryOperator::GreaterThanOrEqual, low.clone()),
+                    (column.clone(), BinaryOperator::LessThanOrEqual, high.clone()),
+                ],
+                _ => vec![],
+            },
+            // With literal bounds, SYMMETRIC is an ordinary range between the smaller and
+            // the larger bound
+            Expression::BetweenSymmetric {
+                expression,
+                low,
+                high,
+                not: false,
+            } => match (expression.as_ref(), low.as_ref(), high.as_ref()) {
+                (
+                    column @ Expression::ColumnReference { .. },
+                    Expression::Literal(low),
+                    Expression::Literal(high),
+                ) if is_bound(low) && is_bound(high) => {
+                    let (low, high) = match Self::compare(low, high) {
+                        Some(std::cmp::Ordering::Greater) => (high, low),
+                        Some(_) => (low, high),
+                        None => return vec![],
+                    };
+                    vec![
+                        (column.clone(), BinaryOperator::GreaterThanOrEqual, low.clone()),
+                        (column.clone(), BinaryOperator::LessThanOrEqual, high.clone()),
+                    ]
+                }
+                _ => vec![],
+            },
+            _ => vec![],
+        }
+    }
+
+    /// Order two literals, or `None` if they are of incomparable kinds
+    fn compare(
+        left: &crate::parser::ast::LiteralValue,
+        right: &crate::parser::ast::LiteralValue,
+    ) -> Option<std::cmp::Ordering> {
+        use crate::parser::ast::LiteralValue;
+
+        match (left, right) {
+            (LiteralValue::Integer(l), LiteralValue::Integer(r)) => Some(l.cmp(r)),
+            (LiteralValue::Integer(l), LiteralValue::Float(r)) => (*l as f64).partial_cmp(r),
+            (LiteralValue::Float(l), LiteralValue::Integer(r)) => l.partial_cmp(&(*r as f64)),
+            (LiteralValue::Float(l), LiteralValue::Float(r)) => l.partial_cmp(r),
+            (LiteralValue::Boolean(l), LiteralValue::Boolean(r)) => Some(l.cmp(r)),
+            (LiteralValue::String(l), LiteralValue::String(r))
+            | (LiteralValue::Date(l), LiteralValue::Date(r))
+            | (LiteralValue::Time(l), LiteralValue::Time(r))
+            | (LiteralValue::Timestamp(l), LiteralValue::Timestamp(r)) => Some(l.cmp(r)),
+            _ => None,
+        }
+    }
+
+    /// Order two literals already known to be comparable
+    fn order(
+        left: &crate::parser::ast::LiteralValue,
+        right: &crate::parser::ast::LiteralValue,
+    ) -> std::cmp::Ordering {
+        Self::compare(left, right).unwrap_or(std::cmp::Ordering::Equal)
+    }
+
+    /// Render a column's merged bounds. Returns `None` if the bounds are unsatisfiable.
+    fn range_conjuncts(
+        column: &crate::parser::ast::Expression,
+        range: &ColumnRange,
+    ) -> Option<Vec<crate::parser::ast::Expression>> {
+        use crate::parser::ast::{BinaryOperator, Expression, LiteralValue};
+        use std::cmp::Ordering;
+
+        let comparison = |operator: BinaryOperator, value: &LiteralValue| Expression::Binary {
+            left: Box::new(column.clone()),
+            operator,
+            right: Box::new(Expression::Literal(value.clone())),
+        };
+
+        if let Some(value) = &range.equal {
+            if let Some((lower, inclusive)) = &range.lower {
+                match Self::order(value, lower) {
+                    Ordering::Less => return None,
+                    Ordering::Equal if !inclusive => return None,
+                    _ => {}
+                }
+            }
+            if let Some((upper, inclusive)) = &range.upper {
+                match Self::order(value, upper) {
+                    Ordering::Greater => return None,
+                    Ordering::Equal if !inclusive => return None,
+                    _ => {}
+                }
+            }
+            for excluded in &range.not_equal {
+                if Self::order(value, excluded) == Ordering::Equal {
+                    return None;
+                }
+            }
+            return Some(vec![comparison(BinaryOperator::Equals, value)]);
+        }
+
+        let mut conjuncts = Vec::new();
+        match (&range.lower, &range.upper) {
+            (Some((lower, lower_inclusive)), Some((upper, upper_inclusive))) => {
+                match Self::order(lower, upper) {
+                    Ordering::Greater => return None,
+                    Ordering::Equal if !(*lower_inclusive && *upper_inclusive) => return None,
+                    Ordering::Equal => conjuncts.push(comparison(BinaryOperator::Equals, lower)),
+                    Ordering::Less => {
+                        conjuncts.push(Self::lower_bound(&comparison, lower, *lower_inclusive));
+                        conjuncts.push(Self::upper_bound(&comparison, upper, *upper_inclusive));
+                    }
+                }
+            }
+            (Some((lower, inclusive)), None) => {
+                conjuncts.push(Self::lower_bound(&comparison, lower, *inclusive))
+            }
+            (None, Some((upper, inclusive))) => {
+                conjuncts.push(Self::upper_bound(&comparison, upper, *inclusive))
+            }
+            (None, None) => {}
+        }
+
+        let mut excluded: Vec<&LiteralValue> = Vec::new();
+        for value in &range.not_equal {
+            let seen = excluded
+                .iter()
+                .any(|existing| Self::order(existing, value) == Ordering::Equal);
+            if !seen {
+                excluded.push(value);
+                conjuncts.push(comparison(BinaryOperator::NotEquals, value));
+            }
+        }
+        Some(conjuncts)
+    }
+
+    fn lower_bound(
+        comparison: &impl Fn(crate::parser::ast::BinaryOperator, &crate::parser::ast::LiteralValue) -> crate::parser::ast::Expression,
+        value: &crate::parser::ast::LiteralValue,
+        inclusive: bool,
+    ) -> crate::parser::ast::Expression {
+        use crate::parser::ast::BinaryOperator;
+        if inclusive {
+            comparison(BinaryOperator::GreaterThanOrEqual, value)
+        } else {
+            comparison(BinaryOperator::GreaterThan, value)
+        }
+    }
+
+    fn upper_bound(
+        comparison: &impl Fn(crate::parser::ast::BinaryOperator, &crate::parser::ast::LiteralValue) -> crate::parser::ast::Expression,
+        value: &crate::parser::ast::LiteralValue,
+        inclusive: bool,
+    ) -> crate::parser::ast::Expression {
+        use crate::parser::ast::BinaryOperator;
+        if inclusive {
+            comparison(BinaryOperator::LessThanOrEqual, value)
+        } else {
+            comparison(BinaryOperator::LessThan, value)
+        }
+    }
+
+    /// Volatile conjuncts like `random() < 0.5` must not be deduplicated
+    fn is_deterministic(expr: &crate::parser::ast::Expression) -> bool {
+        use crate::parser::ast::Expression;
+        let registry = crate::expression::function::FunctionRegistry::builtin();
+        expr.try_transform(&mut |node| match &node {
+            Expression::FunctionCall { name, .. } if registry.is_volatile(name) => Err(()),
+            _ => Ok(node),
+        })
+        .is_ok()
+    }
+}
+
+impl OptimizationRule for PredicateSimplificationRule {
+    fn apply_logical(&self, plan: &LogicalPlan) -> PrismDBResult<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter(filter) => {
+                let input = self.apply_logical(&filter.input)?;
+                match Self::simplify(&filter.predicate) {
+                    // Nothing from the input can pass, so skip reading it
+                    SimplifiedPredicate::AlwaysFalse => {
+                        Ok(LogicalPlan::Values(LogicalValues::new(vec![], input.schema())))
+                    }
+                    SimplifiedPredicate::AlwaysTrue => Ok(input),
+                    SimplifiedPredicate::Predicate(predicate) => {
+                        Ok(LogicalPlan::Filter(LogicalFilter::new(input, predicate)))
+                    }
+                }
+            }
+            _ => {
+                // Apply to children
+                let mut new_plan = plan.clone();
+                for child in new_plan.children_mut() {
+                    *child = self.apply_logical(child)?;
+                }
+                Ok(new_plan)
+            }
+        }
+    }
+}
+
+/// LIKE prefix rule - derive range bounds from anchored LIKE patterns
+///
+/// Every string matching `'abc%'` sorts within `['abc', 'abd')`. A pattern that is only
+/// a prefix followed by `%` is replaced by that range; other anchored patterns keep the
+/// LIKE and gain the range next to it. Predicate simplification then merges the range
+/// with the column's other bounds, and scans use it to skip zones. Patterns that start
+/// with a wildcard are left alone.
+struct LikePrefixRule;
+
+impl LikePrefixRule {
+    /// Rewrite the prefix LIKE conjuncts of a predicate over `schema`
+    fn rewrite(
+        predicate: &crate::parser::ast::Expression,
+        schema: &[Column],
+    ) -> crate::parser::ast::Expression {
+        use crate::parser::ast::{BinaryOperator, Expression, LiteralValue};
+
+        let mut conjuncts = Vec::new();
+        PredicateSimplificationRule::flatten_and(predicate, &mut conjuncts);
+        if !conjuncts
+            .iter()
+            .any(|conjunct| Self::prefix_pattern(conjunct, schema).is_some())
+        {
+            return predicate.clone();
+        }
+
+        let mut rewritten = Vec::new();
+        for conjunct in conjuncts {
+            let Some((column, prefix, exact)) = Self::prefix_pattern(conjunct, schema) else {
+                rewritten.push(conjunct.clone());
+                continue;
+            };
+            let comparison = |operator: BinaryOperator, value: String| Expression::Binary {
+                left: Box::new(column.clone()),
+                operator,
+                right: Box::new(Expression::Literal(LiteralValue::String(value))),
+            };
+            let upper = Self::upper_bound(&prefix);
+            rewritten.push(comparison(BinaryOperator::GreaterThanOrEqual, prefix));
+            if let Some(upper) = upper {
+                rewritten.push(comparison(BinaryOperator::LessThan, upper));
+            }
+            if !exact {
+                rewritten.push(conjunct.clone());
+            }
+        }
+
+        rewritten
+            .into_iter()
+            .reduce(|left, right| Expression::Binary {
+                left: Box::new(left),
+                operator: BinaryOperator::And,
+                right: Box::new(right),
+            })
+            .unwrap_or_else(|| predicate.clone())
+    }
+
+    /// Match `column LIKE 'prefix...'` on a VARCHAR column. Returns the column, the
+    /// literal prefix, and whether the pattern is exactly that prefix followed by `%`.
+    fn prefix_pattern<'a>(
+        expr: &'a crate::parser::ast::Expression,
+        schema: &[Column],
+    ) -> Option<(&'a crate::parser::ast::Expression, String, bool)> {
+        use crate::parser::ast::{BinaryOperator, Expression, LiteralValue};
+
+        let (column, pattern) = match expr {
+            Expression::Binary {
+                left,
+                operator: BinaryOperator::Like,
+                right,
+            } => (left.as_ref(), right.as_ref()),
+            Expression::Like {
+                expression,
+                pattern,
+                escape: None,
+                case_insensitive: false,
+                not: false,
+            } => (expression.as_ref(), pattern.as_ref()),
+            _ => return None,
+        };
+        let (
+            Expression::ColumnReference { table, column: name },
+            Expression::Literal(LiteralValue::String(pattern)),
+        ) = (column, pattern)
+        else {
+            return None;
+        };
+        if !Self::is_varchar_column(table.as_deref(), name, schema) {
+            return None;
+        }
+
+        let wildcard = pattern.find(['%', '_'])?;
+        if wildcard == 0 {
+            return None;
+        }
+        let exact = pattern[wildcard..].chars().all(|c| c == '%');
+        Some((column, pattern[..wildcard].to_string(), exact))
+    }
+
+    /// Whether a column reference resolves to exactly one VARCHAR column of `schema`
+    fn is_varchar_column(table: Option<&str>, name: &str, schema: &[Column]) -> bool {
+        let qualified = match table {
+            Some(table) => format!("{}.{}", table, name).to_lowercase(),
+            None => name.to_lowercase(),
+        };
+        let suffix = format!(".{}", qualified);
+        let mut matches = schema.iter().filter(|column| {
+            let column_name = column.name.to_lowercase();
+            column_name == qualified || column_name.ends_with(&suffix)
+        });
+        matches!(
+            (matches.next(), matches.next()),
+            (Some(column), None) if column.data_type == crate::types::LogicalType::Varchar
+        )
+    }
+
+    /// Smallest string that sorts after every string starting with `prefix`, if any
+    fn upper_bound(prefix: &str) -> Option<String> {
+        let mut chars: Vec<char> = prefix.chars().collect();
+        while let Some(last) = chars.pop() {
+            if let Some(next) = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32) {
+                chars.push(next);
+                return Some(chars.into_iter().collect());
+            }
+        }
+        None
+    }
+}
+
+impl OptimizationRule for LikePrefixRule {
+    fn apply_logical(&self, plan: &LogicalPlan) -> PrismDBResult<LogicalPlan> {
+        match plan {
+            LogicalPlan::Filter(filter) => {
+                let input = self.apply_logical(&filter.input)?;
+                let predicate = Self::rewrite(&filter.predicate, &input.schema());
+                Ok(LogicalPlan::Filter(LogicalFilter::new(input, predicate)))
+            }
+            _ => {
+                // Apply to children
+                let mut new_plan = plan.clone();
+                for child in new_plan.children_mut() {
+                    *child = self.apply_logical(child)?;
+                }
+                Ok(new_plan)
+            }
+        }
+    }
+}
+
 /// Filter pushdown rule
+///
+/// Filters move into table scans, and through the projections, sorts and grouped
+/// aggregates of subqueries, views and CTEs on the way there. A conjunct passes a
+/// projection when every column it references is computed there by a deterministic
+/// expression, which is substituted for the column; window functions keep it above
+/// unless it only references their partition keys. It passes an aggregate when it
+/// only references grouping columns. Conjuncts that cannot move stay where they are.
 struct FilterPushdownRule;
 
+impl FilterPushdownRule {
+    /// Place `predicate` as deep into `input` as it can go
+    fn push_into(input: LogicalPlan, predicate: crate::parser::ast::Expression) -> LogicalPlan {
+        match input {
+            LogicalPlan::TableScan(mut scan) => {
+                scan.filters.push(predicate);
+                LogicalPlan::TableScan(scan)
+            }
+            LogicalPlan::Sort(mut sort) => {
+                *sort.input = Self::push_into(*sort.input, predicate);
+                LogicalPlan::Sort(sort)
+            }
+            LogicalPlan::Projection(mut projection) => {
+                let (pushed, kept) = Self::split(&predicate, |table, column| {
+                    let index = Self::resolve_column(&projection.schema, table, column)?;
+                    let expression = projection.expressions.get(index)?;
+                    PredicateSimplificationRule::is_deterministic(expression)
+                        .then(|| expression.clone())
+                });
+                if let Some(pushed) = pushed {
+                    *projection.input = Self::push_into(*projection.input, pushed);
+                }
+                Self::keep(LogicalPlan::Projection(projection), kept)
+            }
+            // Filtering on a column every window partitions by removes whole
+            // partitions, which leaves the other partitions' results alone
+            LogicalPlan::Window(mut window) => {
+                let input_columns = window.schema.len() - window.functions.len();
+                let partitions: Vec<Vec<usize>> = Self::window_partitions(&window.functions)
+                    .iter()
+                    .map(|partition_by| {
+                        partition_by
+                            .iter()
+                            .filter_map(|expr| match expr {
+                                crate::parser::ast::Expression::ColumnReference { table, column } => {
+                                    Self::resolve_column(&window.schema[..input_columns], table.as_deref(), column)
+                                }
+                                _ => None,
+                            })
+                            .collect()
+                    })
+                    .collect();
+                let (pushed, kept) = Self::split(&predicate, |table, column| {
+                    let index = Self::resolve_column(&window.schema, table, column)?;
+                    partitions.iter().all(|columns| columns.contains(&index)).then(|| {
+                        crate::parser::ast::Expression::ColumnReference {
+                            table: table.map(str::to_string),
+                            column: column.to_string(),
+                        }
+                    })
+                });
+                if let Some(pushed) = pushed {
+                    *window.input = Self::push_into(*window.input, pushed);
+                }
+                Self::keep(LogicalPlan::Window(window), kept)
+            }
+            // Without grouping columns every row feeds the same group, so even a
+            // constant predicate changes the result
+            LogicalPlan::Aggregate(mut aggregate) if !aggregate.group_by.is_empty() => {
+                let (pushed, kept) = Self::split(&predicate, |table, column| {
+                    let index = Self::resolve_column(&aggregate.schema, table, column)?;
+                    aggregate.group_by.get(index).cloned()
+                });
+                if let Some(pushed) = pushed {
+                    *aggregate.input = Self::push_into(*aggregate.input, pushed);
+                }
+                Self::keep(LogicalPlan::Aggregate(aggregate), kept)
+            }
+            input => LogicalPlan::Filter(LogicalFilter::new(input, predicate)),
+        }
+    }
+
+    /// `input` filtered by `predicate`, if any
+    fn keep(input: LogicalPlan, predicate: Option<crate::parser::ast::Expression>) -> LogicalPlan {
+        match predicate {
+            Some(predicate) => LogicalPlan::Filter(LogicalFilter::new(input, predicate)),
+            None => input,
+        }
+    }
+
+    /// Split `predicate` into the conjunction of the conjuncts whose columns can
+    /// all be replaced through `resolve`, rewritten, and the conjunction of the rest
+    fn split(
+        predicate: &crate::parser::ast::Expression,
+        resolve: impl Fn(Option<&str>, &str) -> Option<crate::parser::ast::Expression>,
+    ) -> (
+        Option<crate::parser::ast::Expression>,
+        Option<crate::parser::ast::Expression>,
+    ) {
+        let mut conjuncts = Vec::new();
+        PredicateSimplificationRule::flatten_and(predicate, &mut conjuncts);
+        let mut pushed = Vec::new();
+        let mut kept = Vec::new();
+        for conjunct in conjuncts {
+            match Self::substitute(conjunct, &resolve) {
+                Some(rewritten) if PredicateSimplificationRule::is_deterministic(conjunct) => {
+                    pushed.push(rewritten)
+                }
+                _ => kept.push(conjunct.clone()),
+            }
+        }
+        (Self::conjunction(pushed), Self::conjunction(kept))
+    }
+
+    fn conjunction(
+        conjuncts: Vec<crate::parser::ast::Expression>,
+    ) -> Option<crate::parser::ast::Expression> {
+        use crate::parser::ast::{BinaryOperator, Expression};
+
+        conjuncts.into_iter().reduce(|left, right| Expression::Binary {
+            left: Box::new(left),
+            operator: BinaryOperator::And,
+            right: Box::new(right),
+        })
+    }
+
+    /// Index of the column of `schema` a reference binds to, resolved the way the
+    /// expression binder does: the exact name first, then a unique unqualified match
+    fn resolve_column(schema: &[Column], table: Option<&str>, column: &str) -> Option<usize> {
+        let find = |name: &str| {
+            if let Some(index) = schema.iter().position(|c| c.name == name) {
+                return Some(index);
+            }
+            let suffix = format!(".{}", name);
+            let mut matches = schema
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.name.ends_with(&suffix) && !name.contains('.'));
+            match (matches.next(), matches.next()) {
+                (Some((index, _)), None) => Some(index),
+                _ => None,
+            }
+        };
+        match table {
+            Some(table) => find(&format!("{}.{}", table, column)).or_else(|| find(column)),
+            None => find(column),
+        }
+    }
+
+    /// PARTITION BY lists of the window functions in `expressions`
+    fn window_partitions(
+        expressions: &[crate::parser::ast::Expression],
+    ) -> Vec<Vec<crate::parser::ast::Expression>> {
+        use crate::parser::ast::Expression;
+
+        fn collect(expr: &Expression, out: &mut Vec<Vec<Expression>>) {
+            let children: Vec<&Expression> = match expr {
+                Expression::WindowFunction { window_spec, .. } => {
+                    out.push(window_spec.partition_by.clone());
+                    return;
+                }
+                Expression::FunctionCall { arguments, .. } => arguments.iter().collect(),
+                Expression::Cast { expression, .. }
+                | Expression::Unary { expression, .. }
+                | Expression::IsNull(expression)
+                | Expression::IsNotNull(expression) => vec![expression],
+                Expression::Binary { left, right, .. } => vec![left, right],
+                Expression::Case { operand, conditions, results, else_result } => operand
+                    .iter()
+                    .chain(else_result)
+                    .map(|expr| expr.as_ref())
+                    .chain(conditions)
+                    .chain(results)
+                    .collect(),
+                _ => vec![],
+            };
+            for child in children {
+                collect(child, out);
+            }
+        }
+
+        let mut partitions = Vec::new();
+        for expr in expressions {
+            collect(expr, &mut partitions);
+        }
+        partitions
+    }
+
+    /// `expr` with every column reference replaced through `resolve`; `None` if a
+    /// column cannot be replaced or the expression holds a subquery, aggregate or
+    /// window function, which are evaluated against their own input
+    fn substitute(
+        expr: &crate::parser::ast::Expression,
+        resolve: &dyn Fn(Option<&str>, &str) -> Option<crate::parser::ast::Expression>,
+    ) -> Option<crate::parser::ast::Expression> {
+        use crate::parser::ast::Expression;
+
+        let boxed = |expr: &Expression| Self::substitute(expr, resolve).map(Box::new);
+        let all = |exprs: &[Expression]| -> Option<Vec<Expression>> {
+            exprs.iter().map(|expr| Self::substitute(expr, resolve)).collect()
+        };
+        let optional = |expr: &Option<Box<Expression>>| -> Option<Option<Box<Expression>>> {
+            match expr {
+                Some(expr) => boxed(expr).map(Some),
+                None => Some(None),
+            }
+        };
+
+        Some(match expr {
+            Expression::ColumnReference { table, column } => resolve(table.as_deref(), column)?,
+            Expression::Literal(_)
+            | Expression::Parameter(_)
+            | Expression::QualifiedWildcard { .. }
+            | Expression::Wildcard => expr.clone(),
+            Expression::FunctionCall { name, arguments, distinct } => Expression::FunctionCall {
+                name: name.clone(),
+                arguments: all(arguments)?,
+                distinct: *distinct,
+            },
+            Expression::Cast { expression, data_type, try_cast } => Expression::Cast {
+                expression: boxed(expression)?,
+                data_type: data_type.clone(),
+                try_cast: *try_cast,
+            },
+            Expression::Collate { expression, collation } => Expression::Collate {
+                expression: boxed(expression)?,
+                collation: collation.clone(),
+            },
+            Expression::Case { operand, conditions, results, else_result } => Expression::Case {
+                operand: optional(operand)?,
+                conditions: all(conditions)?,
+                results: all(results)?,
+                else_result: optional(else_result)?,
+            },
+            Expression::Between { expression, low, high, not } => Expression::Between {
+                expression: boxed(expression)?,
+                low: boxed(low)?,
+                high: boxed(high)?,
+                not: *not,
+            },
+            Expression::BetweenSymmetric { expression, low, high, not } => {
+                Expression::BetweenSymmetric {
+                    expression: boxed(expression)?,
+                    low: boxed(low)?,
+                    high: boxed(high)?,
+                    not: *not,
+                }
+            }
+            Expression::InList { expression, list, not } => Expression::InList {
+                expression: boxed(expression)?,
+                list: all(list)?,
+                not: *not,
+            },
+            Expression::IsNull(inner) => Expression::IsNull(boxed(inner)?),
+            Expression::IsNotNull(inner) => Expression::IsNotNull(boxed(inner)?),
+            Expression::IsTrue(inner) => Expression::IsTrue(boxed(inner)?),
+            Expression::IsFalse(inner) => Expression::IsFalse(boxed(inner)?),
+            Expression::IsUnknown(inner) => Expression::IsUnknown(boxed(inner)?),
+            Expression::IsNotTrue(inner) => Expression::IsNotTrue(boxed(inner)?),
+            Expression::IsNotFalse(inner) => Expression::IsNotFalse(boxed(inner)?),
+            Expression::IsNotUnknown(inner) => Expression::IsNotUnknown(boxed(inner)?),
+            Expression::Like { expression, pattern, escape, case_insensitive, not } => {
+                Expression::Like {
+                    expression: boxed(expression)?,
+                    pattern: boxed(pattern)?,
+                    escape: optional(escape)?,
+                    case_insensitive: *case_insensitive,
+                    not: *not,
+                }
+            }
+            Expression::Binary { left, operator, right } => Expression::Binary {
+                left: boxed(left)?,
+                operator: operator.clone(),
+                right: boxed(right)?,
+            },
+            Expression::Unary { operator, expression } => Expression::Unary {
+                operator: operator.clone(),
+                expression: boxed(expression)?,
+            },
+            Expression::AggregateFunction { .. }
+            | Expression::WindowFunction { .. }
+            | Expression::InSubquery { .. }
+            | Expression::Exists(_)
+            | Expression::Subquery(_) => return None,
+        })
+    }
+}
+
 impl OptimizationRule for FilterPushdownRule {
     fn apply_logical(&self, plan: &LogicalPlan) -> PrismDBResult<LogicalPlan> {
         match plan {
             LogicalPlan::Filter(filter) => {
-                // Try to push filter down through children
-                let mut new_input = self.apply_logical(&filter.input)?;
-
-                // If input is a table scan, push filter into scan
-                if let LogicalPlan::TableScan(scan) = &mut new_input {
-                    scan.filters.push(filter.predicate.clone());
-                    Ok(new_input)
-                } else {
-                    // Can't push down, keep filter as is
-                    Ok(LogicalPlan::Filter(LogicalFilter::new(
-                        new_input,
-                        filter.predicate.clone(),
-                    )))
-                }
+                // Push the filter as far as it goes into its optimized input
+                let new_input = self.apply_logical(&filter.input)?;
+                Ok(Self::push_into(new_input, filter.predicate.clone()))
             }
             _ => {
                 // Apply to children
@@ -985,18 +2460,159 @@ impl OptimizationRule for LimitPushdownRule {
                 // Try to push limit down through children
                 let mut new_input = self.apply_logical(&limit.input)?;
 
-                // If input is a table scan, push limit into scan
-                if let LogicalPlan::TableScan(scan) = &mut new_input {
-                    scan.limit = Some(limit.limit);
-                    Ok(new_input)
-                } else {
-                    // Can't push down, keep limit as is
-                    Ok(LogicalPlan::Limit(LogicalLimit::new(
-                        new_input,
-                        limit.limit,
-                        limit.offset,
-                    )))
+                // If input is a table scan, push limit into scan. The scan still
+                // has to produce the skipped rows, so an offset keeps the limit.
+                if let (LogicalPlan::TableScan(scan), false) =
+                    (&mut new_input, limit.limit == usize::MAX)
+                {
+                    scan.limit = Some(limit.offset.saturating_add(limit.limit));
+                    if limit.offset == 0 {
+                        return Ok(new_input);
+                    }
                 }
+
+                Ok(LogicalPlan::Limit(LogicalLimit::new(
+                    new_input,
+                    limit.limit,
+                    limit.offset,
+                )))
+            }
+            _ => {
+                // Apply to children
+                let mut new_plan = plan.clone();
+                for child in new_plan.children_mut() {
+                    *child = self.apply_logical(child)?;
+                }
+                Ok(new_plan)
+            }
+        }
+    }
+}
+
+/// Row number Top-N rule
+///
+/// `QUALIFY ROW_NUMBER() OVER (ORDER BY k) BETWEEN a AND b` (or `<= b`, `< b`, `= b`)
+/// only keeps rows among the first `b` in `k` order. When every function of the
+/// window below is such an unpartitioned ROW_NUMBER, the window only needs those
+/// rows, so its input becomes a Top-N of `b` rows; the QUALIFY still picks the page.
+struct RowNumberTopNRule;
+
+impl RowNumberTopNRule {
+    /// Sort keys of a window whose functions are all `ROW_NUMBER()` ordered by the
+    /// same columns, without partitions or frames
+    fn sort_keys(functions: &[crate::parser::ast::Expression]) -> Option<Vec<SortExpression>> {
+        use crate::parser::ast::Expression;
+
+        let mut order_by = None;
+        for function in functions {
+            let Expression::WindowFunction {
+                name,
+                arguments,
+                window_spec,
+            } = function
+            else {
+                return None;
+            };
+            if !name.eq_ignore_ascii_case("row_number")
+                || !arguments.is_empty()
+                || !window_spec.partition_by.is_empty()
+                || window_spec.window_frame.is_some()
+                || window_spec.order_by.is_empty()
+                || order_by.is_some_and(|order_by| order_by != &window_spec.order_by)
+            {
+                return None;
+            }
+            order_by = Some(&window_spec.order_by);
+        }
+        order_by?
+            .iter()
+            .map(|item| match item.expression {
+                Expression::ColumnReference { .. } => Some(SortExpression {
+                    expression: item.expression.clone(),
+                    ascending: item.ascending,
+                    nulls_first: item.nulls_first.unwrap_or(!item.ascending),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Largest row number `predicate` lets through, if it bounds one of the
+    /// `row_numbers` columns
+    fn row_limit(predicate: &crate::parser::ast::Expression, row_numbers: &[Column]) -> Option<usize> {
+        use crate::parser::ast::{BinaryOperator, Expression, LiteralValue};
+
+        let is_row_number = |expr: &Expression| match expr {
+            Expression::ColumnReference { table: None, column } => {
+                row_numbers.iter().any(|row_number| &row_number.name == column)
+            }
+            _ => false,
+        };
+        let mut conjuncts = Vec::new();
+        PredicateSimplificationRule::flatten_and(predicate, &mut conjuncts);
+        conjuncts
+            .into_iter()
+            .filter_map(|conjunct| {
+                let bound = match conjunct {
+                    Expression::Between {
+                        expression,
+                        high,
+                        not: false,
+                        ..
+                    } => match high.as_ref() {
+                        Expression::Literal(LiteralValue::Integer(high)) if is_row_number(expression) => *high,
+                        _ => return None,
+                    },
+                    Expression::Binary { left, operator, right } => {
+                        // Put the row number on the left
+                        let (operator, bound) = match (left.as_ref(), right.as_ref()) {
+                            (row_number, Expression::Literal(LiteralValue::Integer(bound)))
+                                if is_row_number(row_number) =>
+                            {
+                                (operator.clone(), *bound)
+                            }
+                            (Expression::Literal(LiteralValue::Integer(bound)), row_number)
+                                if is_row_number(row_number) =>
+                            {
+                                let operator = match operator {
+                                    BinaryOperator::GreaterThan => BinaryOperator::LessThan,
+                                    BinaryOperator::GreaterThanOrEqual => BinaryOperator::LessThanOrEqual,
+                                    operator => operator.clone(),
+                                };
+                                (operator, *bound)
+                            }
+                            _ => return None,
+                        };
+                        match operator {
+                            BinaryOperator::LessThanOrEqual | BinaryOperator::Equals => bound,
+                            BinaryOperator::LessThan => bound - 1,
+                            _ => return None,
+                        }
+                    }
+                    _ => return None,
+                };
+                Some(bound.max(0) as usize)
+            })
+            .min()
+    }
+}
+
+impl OptimizationRule for RowNumberTopNRule {
+    fn apply_logical(&self, plan: &LogicalPlan) -> PrismDBResult<LogicalPlan> {
+        match plan {
+            LogicalPlan::Qualify(qualify) => {
+                let mut input = self.apply_logical(&qualify.input)?;
+                if let LogicalPlan::Window(window) = &mut input {
+                    let row_numbers = &window.schema[window.schema.len() - window.functions.len()..];
+                    if let (Some(limit), Some(keys)) = (
+                        Self::row_limit(&qualify.predicate, row_numbers),
+                        Self::sort_keys(&window.functions),
+                    ) {
+                        let sort = LogicalPlan::Sort(LogicalSort::new((*window.input).clone(), keys));
+                        *window.input = LogicalPlan::Limit(LogicalLimit::new(sort, limit, 0));
+                    }
+                }
+                Ok(LogicalPlan::Qualify(LogicalQualify::new(input, qualify.predicate.clone())))
             }
             _ => {
                 // Apply to children
@@ -1036,10 +2652,21 @@ impl OptimizationRule for ProjectionPushdownRule {
                         extract_columns(arg, columns);
                     }
                 }
-                Expression::AggregateFunction { arguments, .. } => {
+                Expression::AggregateFunction {
+                    arguments,
+                    filter,
+                    order_by,
+                    ..
+                } => {
                     for arg in arguments {
                         extract_columns(arg, columns);
                     }
+                    if let Some(filter) = filter {
+                        extract_columns(filter, columns);
+                    }
+                    for item in order_by {
+                        extract_columns(&item.expression, columns);
+                    }
                 }
                 Expression::Case {
                     operand,
@@ -1060,7 +2687,7 @@ impl OptimizationRule for ProjectionPushdownRule {
                         extract_columns(else_r, columns);
                     }
                 }
-                Expression::Cast { expression, .. } => {
+                Expression::Cast { expression, .. } | Expression::Collate { expression, .. } => {
                     extract_columns(expression, columns);
                 }
                 Expression::Between {
@@ -1068,6 +2695,12 @@ impl OptimizationRule for ProjectionPushdownRule {
                     low,
                     high,
                     ..
+                }
+                | Expression::BetweenSymmetric {
+                    expression,
+                    low,
+                    high,
+                    ..
                 } => {
                     extract_columns(expression, columns);
                     extract_columns(low, columns);
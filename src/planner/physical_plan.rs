@@ -4,8 +4,13 @@
 //! queries. Physical plans contain specific operators and execution strategies.
 
 use crate::common::error::PrismDBResult;
-use crate::expression::expression::ExpressionRef;
-use crate::types::{DataChunk, LogicalType};
+use crate::expression::aggregate::{
+    create_aggregate_state_with_parameters, AggregateState, DistinctAggregateState, OrderedAggregateState,
+};
+use crate::expression::expression::{ComparisonType, ExpressionRef};
+use crate::parser::ast::{ColumnDefinition, ExplainFormat, TableConstraint, WindowFrame};
+use crate::storage::ValueRange;
+use crate::types::{Collation, DataChunk, LogicalType, Value};
 use std::collections::HashMap;
 
 /// Physical plan node types
@@ -13,14 +18,21 @@ use std::collections::HashMap;
 pub enum PhysicalPlan {
     /// Scan data from a table
     TableScan(PhysicalTableScan),
+    /// COUNT(*) of a whole table, answered from its row count
+    CountStar(PhysicalCountStar),
     /// Filter rows based on a predicate
     Filter(PhysicalFilter),
     /// Filter rows based on window function results (QUALIFY clause)
     Qualify(PhysicalQualify),
+    Window(PhysicalWindow),
     /// Project (select) columns
     Projection(PhysicalProjection),
+    /// Expand list columns into one row per element
+    Unnest(PhysicalUnnest),
     /// Limit the number of rows
     Limit(PhysicalLimit),
+    /// Remove duplicate rows
+    Distinct(PhysicalDistinct),
     /// Sort rows
     Sort(PhysicalSort),
     /// Aggregate rows
@@ -35,16 +47,22 @@ pub enum PhysicalPlan {
     Except(PhysicalExcept),
     /// Hash aggregate
     HashAggregate(PhysicalHashAggregate),
+    /// Aggregate of an input sorted on the group keys, one group at a time
+    StreamingAggregate(PhysicalStreamingAggregate),
     /// Sort merge join
     SortMergeJoin(PhysicalSortMergeJoin),
     /// Hash join
     HashJoin(PhysicalHashJoin),
+    /// As-of join, matching each left row to its nearest right row
+    AsOfJoin(PhysicalAsOfJoin),
     /// Insert data into a table
     Insert(PhysicalInsert),
     /// Update data in a table
     Update(PhysicalUpdate),
     /// Delete data from a table
     Delete(PhysicalDelete),
+    /// Update, delete or insert table rows per the source rows they match
+    Merge(PhysicalMerge),
     /// Create a table
     CreateTable(PhysicalCreateTable),
     /// Drop a table
@@ -76,10 +94,14 @@ impl PhysicalPlan {
     pub fn schema(&self) -> Vec<PhysicalColumn> {
         match self {
             PhysicalPlan::TableScan(scan) => scan.schema.clone(),
+            PhysicalPlan::CountStar(count) => count.schema.clone(),
             PhysicalPlan::Filter(filter) => filter.input.schema(),
             PhysicalPlan::Qualify(qualify) => qualify.input.schema(),
+            PhysicalPlan::Window(window) => window.schema.clone(),
             PhysicalPlan::Projection(proj) => proj.schema.clone(),
+            PhysicalPlan::Unnest(unnest) => unnest.schema.clone(),
             PhysicalPlan::Limit(limit) => limit.input.schema(),
+            PhysicalPlan::Distinct(distinct) => distinct.input.schema(),
             PhysicalPlan::Sort(sort) => sort.input.schema(),
             PhysicalPlan::Aggregate(agg) => agg.schema.clone(),
             PhysicalPlan::Join(join) => join.schema.clone(),
@@ -87,15 +109,18 @@ impl PhysicalPlan {
             PhysicalPlan::Intersect(intersect) => intersect.schema.clone(),
             PhysicalPlan::Except(except) => except.schema.clone(),
             PhysicalPlan::HashAggregate(agg) => agg.schema.clone(),
+            PhysicalPlan::StreamingAggregate(agg) => agg.schema.clone(),
             PhysicalPlan::SortMergeJoin(join) => join.schema.clone(),
             PhysicalPlan::HashJoin(join) => join.schema.clone(),
+            PhysicalPlan::AsOfJoin(join) => join.schema.clone(),
             PhysicalPlan::Insert(_) => vec![],
             PhysicalPlan::Update(_) => vec![],
             PhysicalPlan::Delete(_) => vec![],
+            PhysicalPlan::Merge(_) => vec![],
             PhysicalPlan::CreateTable(_) => vec![],
             PhysicalPlan::DropTable(_) => vec![],
             PhysicalPlan::Explain(_) => {
-                vec![PhysicalColumn::new("plan".to_string(), LogicalType::Text)]
+                vec![PhysicalColumn::new("plan".to_string(), LogicalType::Varchar)]
             }
             PhysicalPlan::Values(values) => values.schema.clone(),
             PhysicalPlan::Pivot(pivot) => pivot.schema.clone(),
@@ -109,14 +134,34 @@ impl PhysicalPlan {
         }
     }
 
+    /// Name of the statement this plan runs if it modifies data or schema
+    pub fn write_operation(&self) -> Option<&'static str> {
+        match self {
+            PhysicalPlan::Insert(_) => Some("INSERT"),
+            PhysicalPlan::Update(_) => Some("UPDATE"),
+            PhysicalPlan::Delete(_) => Some("DELETE"),
+            PhysicalPlan::Merge(_) => Some("MERGE"),
+            PhysicalPlan::CreateTable(_) => Some("CREATE TABLE"),
+            PhysicalPlan::DropTable(_) => Some("DROP TABLE"),
+            PhysicalPlan::CreateMaterializedView(_) => Some("CREATE MATERIALIZED VIEW"),
+            PhysicalPlan::DropMaterializedView(_) => Some("DROP MATERIALIZED VIEW"),
+            PhysicalPlan::RefreshMaterializedView(_) => Some("REFRESH MATERIALIZED VIEW"),
+            _ => None,
+        }
+    }
+
     /// Get all child plans of this plan node
     pub fn children(&self) -> Vec<&PhysicalPlan> {
         match self {
             PhysicalPlan::TableScan(_) => vec![],
+            PhysicalPlan::CountStar(_) => vec![],
             PhysicalPlan::Filter(filter) => vec![&filter.input],
             PhysicalPlan::Qualify(qualify) => vec![&qualify.input],
+            PhysicalPlan::Window(window) => vec![&window.input],
             PhysicalPlan::Projection(proj) => vec![&proj.input],
+            PhysicalPlan::Unnest(unnest) => vec![&unnest.input],
             PhysicalPlan::Limit(limit) => vec![&limit.input],
+            PhysicalPlan::Distinct(distinct) => vec![&distinct.input],
             PhysicalPlan::Sort(sort) => vec![&sort.input],
             PhysicalPlan::Aggregate(agg) => vec![&agg.input],
             PhysicalPlan::Join(join) => vec![&join.left, &join.right],
@@ -124,11 +169,14 @@ impl PhysicalPlan {
             PhysicalPlan::Intersect(intersect) => vec![&intersect.left, &intersect.right],
             PhysicalPlan::Except(except) => vec![&except.left, &except.right],
             PhysicalPlan::HashAggregate(agg) => vec![&agg.input],
+            PhysicalPlan::StreamingAggregate(agg) => vec![&agg.input],
             PhysicalPlan::SortMergeJoin(join) => vec![&join.left, &join.right],
             PhysicalPlan::HashJoin(join) => vec![&join.left, &join.right],
+            PhysicalPlan::AsOfJoin(join) => vec![&join.left, &join.right],
             PhysicalPlan::Insert(insert) => vec![&insert.input],
             PhysicalPlan::Update(_) => vec![],
             PhysicalPlan::Delete(_) => vec![],
+            PhysicalPlan::Merge(merge) => vec![&merge.source],
             PhysicalPlan::CreateTable(_) => vec![],
             PhysicalPlan::DropTable(_) => vec![],
             PhysicalPlan::Explain(explain) => vec![&explain.input],
@@ -143,6 +191,26 @@ impl PhysicalPlan {
             PhysicalPlan::EmptyResult(_) => vec![],
         }
     }
+
+    /// Whether the plan limits rows that weren't sorted first, so which rows it
+    /// returns depends on the order its input produces them in
+    pub fn has_unordered_limit(&self) -> bool {
+        match self {
+            PhysicalPlan::Limit(limit) if !limit.input.is_sorted() => true,
+            PhysicalPlan::TableScan(scan) if scan.limit.is_some() => true,
+            plan => plan.children().into_iter().any(PhysicalPlan::has_unordered_limit),
+        }
+    }
+
+    /// Whether the plan's rows come out of a sort, through filters and projections
+    fn is_sorted(&self) -> bool {
+        match self {
+            PhysicalPlan::Sort(_) => true,
+            PhysicalPlan::Filter(filter) => filter.input.is_sorted(),
+            PhysicalPlan::Projection(projection) => projection.input.is_sorted(),
+            _ => false,
+        }
+    }
 }
 
 /// Physical column definition
@@ -166,6 +234,8 @@ pub struct PhysicalTableScan {
     pub column_ids: Vec<usize>,
     pub filters: Vec<ExpressionRef>,
     pub limit: Option<usize>,
+    /// Column bounds implied by `filters`, used to skip zones that cannot match
+    pub ranges: Vec<(usize, ValueRange)>,
 }
 
 impl PhysicalTableScan {
@@ -177,10 +247,27 @@ impl PhysicalTableScan {
             column_ids: (0..schema_len).collect(),
             filters: Vec::new(),
             limit: None,
+            ranges: Vec::new(),
         }
     }
 }
 
+/// Physical COUNT(*) over an unfiltered table
+///
+/// Reads the table's row count instead of scanning its data. Every output column
+/// holds the count, so `SELECT COUNT(*), COUNT(1) FROM t` is covered too.
+#[derive(Debug, Clone)]
+pub struct PhysicalCountStar {
+    pub table_name: String,
+    pub schema: Vec<PhysicalColumn>,
+}
+
+impl PhysicalCountStar {
+    pub fn new(table_name: String, schema: Vec<PhysicalColumn>) -> Self {
+        Self { table_name, schema }
+    }
+}
+
 /// Physical filter operator
 #[derive(Debug, Clone)]
 pub struct PhysicalFilter {
@@ -214,6 +301,59 @@ impl PhysicalQualify {
     }
 }
 
+/// Physical window operator - appends one column per window function
+#[derive(Debug, Clone)]
+pub struct PhysicalWindow {
+    pub input: Box<PhysicalPlan>,
+    pub functions: Vec<PhysicalWindowFunction>,
+    /// Input columns followed by one column per function
+    pub schema: Vec<PhysicalColumn>,
+}
+
+/// One window function call with its bound window spec
+#[derive(Debug, Clone)]
+pub struct PhysicalWindowFunction {
+    /// Upper-case function name
+    pub name: String,
+    pub arguments: Vec<ExpressionRef>,
+    pub partition_by: Vec<ExpressionRef>,
+    pub order_by: Vec<PhysicalSortExpression>,
+    pub frame: Option<WindowFrame>,
+    pub return_type: LogicalType,
+}
+
+impl PhysicalWindow {
+    pub fn new(
+        input: PhysicalPlan,
+        functions: Vec<PhysicalWindowFunction>,
+        schema: Vec<PhysicalColumn>,
+    ) -> Self {
+        Self {
+            input: Box::new(input),
+            functions,
+            schema,
+        }
+    }
+}
+
+/// Physical UNNEST operator - expands list columns into rows
+#[derive(Debug, Clone)]
+pub struct PhysicalUnnest {
+    pub input: Box<PhysicalPlan>,
+    pub unnest_columns: Vec<usize>,
+    pub schema: Vec<PhysicalColumn>,
+}
+
+impl PhysicalUnnest {
+    pub fn new(input: PhysicalPlan, unnest_columns: Vec<usize>, schema: Vec<PhysicalColumn>) -> Self {
+        Self {
+            input: Box::new(input),
+            unnest_columns,
+            schema,
+        }
+    }
+}
+
 /// Physical projection operator
 #[derive(Debug, Clone)]
 pub struct PhysicalProjection {
@@ -254,11 +394,27 @@ impl PhysicalLimit {
     }
 }
 
+/// Physical duplicate removal, keeping the first of equal rows in input order
+#[derive(Debug, Clone)]
+pub struct PhysicalDistinct {
+    pub input: Box<PhysicalPlan>,
+}
+
+impl PhysicalDistinct {
+    pub fn new(input: PhysicalPlan) -> Self {
+        Self {
+            input: Box::new(input),
+        }
+    }
+}
+
 /// Physical sort operator
 #[derive(Debug, Clone)]
 pub struct PhysicalSort {
     pub input: Box<PhysicalPlan>,
     pub expressions: Vec<PhysicalSortExpression>,
+    /// Only the first rows are wanted (Top-N), from an `ORDER BY ... LIMIT`
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -266,6 +422,8 @@ pub struct PhysicalSortExpression {
     pub expression: ExpressionRef,
     pub ascending: bool,
     pub nulls_first: bool,
+    /// Collation strings are ordered by, from `ORDER BY x COLLATE name`
+    pub collation: Option<crate::types::Collation>,
 }
 
 impl PhysicalSort {
@@ -273,6 +431,7 @@ impl PhysicalSort {
         Self {
             input: Box::new(input),
             expressions,
+            limit: None,
         }
     }
 }
@@ -291,9 +450,35 @@ pub struct PhysicalAggregateExpression {
     pub function_name: String,
     pub arguments: Vec<ExpressionRef>,
     pub distinct: bool,
+    /// `FILTER (WHERE ...)`: only rows for which it is true update the state
+    pub filter: Option<ExpressionRef>,
+    /// Order the state consumes values in; empty for order-insensitive aggregates
+    pub order_by: Vec<PhysicalSortExpression>,
+    /// Constant arguments configuring the state, such as STRING_AGG's separator
+    pub parameters: Vec<Value>,
     pub return_type: LogicalType,
 }
 
+impl PhysicalAggregateExpression {
+    /// Create an empty state for this aggregate; DISTINCT tells values apart
+    /// as `collation` groups them
+    pub fn create_state(&self, collation: &Collation) -> PrismDBResult<Box<dyn AggregateState>> {
+        let mut state = create_aggregate_state_with_parameters(&self.function_name, &self.parameters)?;
+        if !self.order_by.is_empty() {
+            let order = self
+                .order_by
+                .iter()
+                .map(|sort| (sort.ascending, sort.nulls_first))
+                .collect();
+            state = Box::new(OrderedAggregateState::new(state, order));
+        }
+        if self.distinct {
+            state = Box::new(DistinctAggregateState::new(state).with_collation(collation.clone()));
+        }
+        Ok(state)
+    }
+}
+
 impl PhysicalAggregate {
     pub fn new(
         input: PhysicalPlan,
@@ -374,14 +559,16 @@ impl PhysicalUnion {
 pub struct PhysicalIntersect {
     pub left: Box<PhysicalPlan>,
     pub right: Box<PhysicalPlan>,
+    pub all: bool,  // true for INTERSECT ALL, which keeps duplicates
     pub schema: Vec<PhysicalColumn>,
 }
 
 impl PhysicalIntersect {
-    pub fn new(left: PhysicalPlan, right: PhysicalPlan, schema: Vec<PhysicalColumn>) -> Self {
+    pub fn new(left: PhysicalPlan, right: PhysicalPlan, all: bool, schema: Vec<PhysicalColumn>) -> Self {
         Self {
             left: Box::new(left),
             right: Box::new(right),
+            all,
             schema,
         }
     }
@@ -392,14 +579,16 @@ impl PhysicalIntersect {
 pub struct PhysicalExcept {
     pub left: Box<PhysicalPlan>,
     pub right: Box<PhysicalPlan>,
+    pub all: bool,  // true for EXCEPT ALL, which keeps duplicates
     pub schema: Vec<PhysicalColumn>,
 }
 
 impl PhysicalExcept {
-    pub fn new(left: PhysicalPlan, right: PhysicalPlan, schema: Vec<PhysicalColumn>) -> Self {
+    pub fn new(left: PhysicalPlan, right: PhysicalPlan, all: bool, schema: Vec<PhysicalColumn>) -> Self {
         Self {
             left: Box::new(left),
             right: Box::new(right),
+            all,
             schema,
         }
     }
@@ -430,6 +619,34 @@ impl PhysicalHashAggregate {
     }
 }
 
+/// Physical streaming aggregate operator
+///
+/// Its input arrives sorted on the group keys, so each group's rows are contiguous
+/// and the group is finished as soon as the keys change.
+#[derive(Debug, Clone)]
+pub struct PhysicalStreamingAggregate {
+    pub input: Box<PhysicalPlan>,
+    pub group_by: Vec<ExpressionRef>,
+    pub aggregates: Vec<PhysicalAggregateExpression>,
+    pub schema: Vec<PhysicalColumn>,
+}
+
+impl PhysicalStreamingAggregate {
+    pub fn new(
+        input: PhysicalPlan,
+        group_by: Vec<ExpressionRef>,
+        aggregates: Vec<PhysicalAggregateExpression>,
+        schema: Vec<PhysicalColumn>,
+    ) -> Self {
+        Self {
+            input: Box::new(input),
+            group_by,
+            aggregates,
+            schema,
+        }
+    }
+}
+
 /// Physical sort merge join operator
 #[derive(Debug, Clone)]
 pub struct PhysicalSortMergeJoin {
@@ -498,6 +715,48 @@ impl PhysicalHashJoin {
     }
 }
 
+/// Physical as-of join operator
+///
+/// Each left row joins the right row with equal keys whose time is nearest to the
+/// left row's time on the side `comparison` allows, as in `left.ts >= right.ts` for
+/// the latest right row at or before it. The last key pair holds the times, the ones
+/// before it must be equal. Keys are bound against the joined schema, with
+/// `left_keys` on the left input.
+#[derive(Debug, Clone)]
+pub struct PhysicalAsOfJoin {
+    pub left: Box<PhysicalPlan>,
+    pub right: Box<PhysicalPlan>,
+    /// `Inner` drops left rows without a match, `Left` pads them with NULLs
+    pub join_type: PhysicalJoinType,
+    pub left_keys: Vec<ExpressionRef>,
+    pub right_keys: Vec<ExpressionRef>,
+    /// How the left time compares to the matching right time: <, <=, > or >=
+    pub comparison: ComparisonType,
+    pub schema: Vec<PhysicalColumn>,
+}
+
+impl PhysicalAsOfJoin {
+    pub fn new(
+        left: PhysicalPlan,
+        right: PhysicalPlan,
+        join_type: PhysicalJoinType,
+        left_keys: Vec<ExpressionRef>,
+        right_keys: Vec<ExpressionRef>,
+        comparison: ComparisonType,
+        schema: Vec<PhysicalColumn>,
+    ) -> Self {
+        Self {
+            left: Box::new(left),
+            right: Box::new(right),
+            join_type,
+            left_keys,
+            right_keys,
+            comparison,
+            schema,
+        }
+    }
+}
+
 /// Physical insert operator
 #[derive(Debug, Clone)]
 pub struct PhysicalInsert {
@@ -516,12 +775,24 @@ impl PhysicalInsert {
     }
 }
 
+/// Rows of a table found through one of its indexes
+#[derive(Debug, Clone)]
+pub struct IndexLookup {
+    pub index_name: String,
+    /// Values of the indexed column the rows may hold
+    pub range: ValueRange,
+}
+
 /// Physical update operator
 #[derive(Debug, Clone)]
 pub struct PhysicalUpdate {
     pub table_name: String,
     pub assignments: HashMap<String, ExpressionRef>,
     pub condition: Option<ExpressionRef>,
+    /// Index that finds the rows the condition may match, instead of a full scan
+    pub index_lookup: Option<IndexLookup>,
+    /// Columns set to their declared DEFAULT, bound when the update runs
+    pub defaults: Vec<String>,
 }
 
 impl PhysicalUpdate {
@@ -534,6 +805,8 @@ impl PhysicalUpdate {
             table_name,
             assignments,
             condition,
+            index_lookup: None,
+            defaults: Vec::new(),
         }
     }
 }
@@ -543,6 +816,8 @@ impl PhysicalUpdate {
 pub struct PhysicalDelete {
     pub table_name: String,
     pub condition: Option<ExpressionRef>,
+    /// Index that finds the rows the condition may match, instead of a full scan
+    pub index_lookup: Option<IndexLookup>,
 }
 
 impl PhysicalDelete {
@@ -550,16 +825,50 @@ impl PhysicalDelete {
         Self {
             table_name,
             condition,
+            index_lookup: None,
         }
     }
 }
 
+/// Physical merge operator
+///
+/// Expressions are bound over a row of the table's columns followed by the
+/// source's; for a source row that matches nothing, the table's columns are NULL.
+#[derive(Debug, Clone)]
+pub struct PhysicalMerge {
+    pub table_name: String,
+    pub source: Box<PhysicalPlan>,
+    pub condition: ExpressionRef,
+    pub clauses: Vec<PhysicalMergeClause>,
+}
+
+/// WHEN [NOT] MATCHED [AND condition] THEN action, bound
+#[derive(Debug, Clone)]
+pub struct PhysicalMergeClause {
+    pub matched: bool,
+    pub condition: Option<ExpressionRef>,
+    pub action: PhysicalMergeAction,
+}
+
+/// What a MERGE clause does, with the table columns it sets by index
+#[derive(Debug, Clone)]
+pub enum PhysicalMergeAction {
+    Update(Vec<(usize, ExpressionRef)>),
+    Delete,
+    Insert(Vec<(usize, ExpressionRef)>),
+    DoNothing,
+}
+
 /// Physical create table operator
 #[derive(Debug, Clone)]
 pub struct PhysicalCreateTable {
     pub table_name: String,
     pub schema: Vec<PhysicalColumn>,
     pub if_not_exists: bool,
+    /// Column definitions (NOT NULL, DEFAULT, column constraints)
+    pub columns: Vec<ColumnDefinition>,
+    /// Table-level constraints
+    pub constraints: Vec<TableConstraint>,
 }
 
 impl PhysicalCreateTable {
@@ -568,8 +877,20 @@ impl PhysicalCreateTable {
             table_name,
             schema,
             if_not_exists,
+            columns: Vec::new(),
+            constraints: Vec::new(),
         }
     }
+
+    pub fn with_definitions(
+        mut self,
+        columns: Vec<ColumnDefinition>,
+        constraints: Vec<TableConstraint>,
+    ) -> Self {
+        self.columns = columns;
+        self.constraints = constraints;
+        self
+    }
 }
 
 /// Physical drop table operator
@@ -594,6 +915,7 @@ pub struct PhysicalExplain {
     pub input: Box<PhysicalPlan>,
     pub analyze: bool,
     pub verbose: bool,
+    pub format: ExplainFormat,
 }
 
 impl PhysicalExplain {
@@ -602,8 +924,14 @@ impl PhysicalExplain {
             input: Box::new(input),
             analyze,
             verbose,
+            format: ExplainFormat::Text,
         }
     }
+
+    pub fn with_format(mut self, format: ExplainFormat) -> Self {
+        self.format = format;
+        self
+    }
 }
 
 /// Physical empty result operator
@@ -7,7 +7,7 @@ use crate::catalog::Catalog;
 use crate::common::error::PrismDBResult;
 use crate::parser::ast::Statement;
 use crate::planner::binder::Binder;
-use crate::planner::logical_plan::LogicalPlan;
+use crate::planner::logical_plan::{LogicalPlan, NullOrder};
 use std::sync::{Arc, RwLock};
 
 /// Main query planner
@@ -30,6 +30,18 @@ impl QueryPlanner {
         }
     }
 
+    /// Set the default NULL ordering for ORDER BY items
+    pub fn with_default_null_order(mut self, null_order: NullOrder) -> Self {
+        self.binder.set_default_null_order(null_order);
+        self
+    }
+
+    /// Set the estimated distinct count above which COUNT(DISTINCT) is approximated; 0 never does
+    pub fn with_approx_count_distinct_threshold(mut self, threshold: usize) -> Self {
+        self.binder.set_approx_count_distinct_threshold(threshold);
+        self
+    }
+
     /// Plan a SQL statement
     pub fn plan_statement(&mut self, statement: &Statement) -> PrismDBResult<LogicalPlan> {
         // Bind the statement to resolve names and validate semantics
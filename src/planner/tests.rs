@@ -185,4 +185,85 @@ mod tests {
 
         Ok(())
     }
+
+    /// Plan a query, run predicate simplification and return what replaced its WHERE filter
+    fn simplified_filter(sql: &str) -> PrismDBResult<LogicalPlan> {
+        use crate::planner::{OptimizationRule, PredicateSimplificationRule};
+
+        let statement = parse_sql(sql)?;
+        let mut planner = QueryPlanner::new();
+        let logical_plan = planner.plan_statement(&statement)?;
+
+        match PredicateSimplificationRule.apply_logical(&logical_plan)? {
+            LogicalPlan::Projection(proj) => Ok(*proj.input),
+            _ => panic!("Expected Projection as root plan node"),
+        }
+    }
+
+    fn filter_text(plan: &LogicalPlan) -> String {
+        match plan {
+            LogicalPlan::Filter(filter) => filter.predicate.to_string(),
+            other => panic!("Expected Filter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_predicate_simplification_dedupes_conjuncts() -> PrismDBResult<()> {
+        let plan = simplified_filter(
+            "SELECT id FROM users WHERE id = 5 AND name LIKE 'a%' AND id = 5 AND name LIKE 'a%'",
+        )?;
+        assert_eq!(filter_text(&plan), "id = 5 AND name LIKE 'a%'");
+
+        // Volatile conjuncts are evaluated independently and must stay
+        let plan = simplified_filter("SELECT id FROM users WHERE random() < 0.5 AND random() < 0.5")?;
+        assert_eq!(filter_text(&plan), "random() < 0.5 AND random() < 0.5");
+
+        // A filter that is always true disappears
+        let plan = simplified_filter("SELECT id FROM users WHERE TRUE AND TRUE")?;
+        assert!(matches!(plan, LogicalPlan::TableScan(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_predicate_simplification_detects_contradictions() -> PrismDBResult<()> {
+        for sql in [
+            "SELECT id FROM users WHERE id = 1 AND id = 2",
+            "SELECT id FROM users WHERE id > 5 AND id < 3",
+            "SELECT id FROM users WHERE id >= 5 AND name = 'x' AND id < 5",
+            "SELECT id FROM users WHERE id = 5 AND id <> 5",
+            "SELECT id FROM users WHERE id = 10 AND id BETWEEN 1 AND 9",
+            "SELECT id FROM users WHERE name = 'a' AND FALSE",
+        ] {
+            match simplified_filter(sql)? {
+                LogicalPlan::Values(values) => assert!(values.values.is_empty(), "{}", sql),
+                other => panic!("Expected empty input for {}, got {:?}", sql, other),
+            }
+        }
+
+        // Comparisons against different columns or incomparable literals are left alone
+        let plan = simplified_filter("SELECT id FROM users WHERE id = 1 AND age = 2")?;
+        assert_eq!(filter_text(&plan), "id = 1 AND age = 2");
+        let plan = simplified_filter("SELECT id FROM users WHERE id = 1 AND id = 'one'")?;
+        assert_eq!(filter_text(&plan), "id = 1 AND id = 'one'");
+        Ok(())
+    }
+
+    #[test]
+    fn test_predicate_simplification_merges_ranges() -> PrismDBResult<()> {
+        let cases = [
+            ("x > 5 AND x > 3", "x > 5"),
+            ("x >= 5 AND x > 5", "x > 5"),
+            ("3 < x AND x <= 10 AND x < 20", "x > 3 AND x <= 10"),
+            ("x BETWEEN 1 AND 10 AND x > 4 AND name = 'a'", "x > 4 AND x <= 10 AND name = 'a'"),
+            ("x >= 7 AND x <= 7", "x = 7"),
+            ("x = 7 AND x > 1 AND x <> 3", "x = 7"),
+            ("x < 2.5 AND x < 2", "x < 2"),
+            ("(x > 1 AND y > 1) AND (x > 2 OR y > 2)", "x > 1 AND y > 1 AND (x > 2 OR y > 2)"),
+        ];
+        for (predicate, expected) in cases {
+            let plan = simplified_filter(&format!("SELECT id FROM users WHERE {}", predicate))?;
+            assert_eq!(filter_text(&plan), expected, "simplifying {}", predicate);
+        }
+        Ok(())
+    }
 }
@@ -23,6 +23,9 @@ pub fn to_py_err(error: PrismDBError) -> PyErr {
         PrismDBError::Compression(msg) => PyRuntimeError::new_err(format!("Compression error: {}", msg)),
         PrismDBError::Extension(msg) => PyRuntimeError::new_err(format!("Extension error: {}", msg)),
         PrismDBError::Wal(msg) => PyRuntimeError::new_err(format!("WAL error: {}", msg)),
+        PrismDBError::Constraint(msg) => PyRuntimeError::new_err(format!("Constraint violation: {}", msg)),
+        PrismDBError::NotFound(_, msg) => PyRuntimeError::new_err(format!("Catalog error: {}", msg)),
+        PrismDBError::TypeMismatch(msg) => PyValueError::new_err(format!("Type mismatch: {}", msg)),
         PrismDBError::OutOfMemory => PyRuntimeError::new_err("Out of memory"),
     }
 }
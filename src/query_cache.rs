@@ -0,0 +1,159 @@
+//! Query Result Cache
+//!
+//! An optional LRU of SELECT results keyed by normalized SQL text. Each entry
+//! remembers the tables its query read and the catalog version it was computed
+//! at; once any of those tables is modified (its version in the catalog moves
+//! past the entry's) the entry is stale and dropped on the next lookup.
+
+use crate::catalog::Catalog;
+use crate::common::sync::MutexExt;
+use crate::database::QueryResult;
+use crate::expression::function::FunctionRegistry;
+use crate::parser::{IdentifierCase, TokenType, Tokenizer};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Hit and miss counts of a query cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that had to run the query
+    pub misses: u64,
+    /// Results currently cached
+    pub entries: usize,
+}
+
+struct CacheEntry {
+    result: QueryResult,
+    /// Tables the query read
+    tables: HashSet<String>,
+    /// Catalog version the result was computed at
+    version: u64,
+    /// Tick of the last lookup that returned this entry
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    tick: u64,
+    stats: QueryCacheStats,
+}
+
+/// Bounded LRU of query results, shared by every session of a database
+pub struct QueryCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+impl QueryCache {
+    /// Create a cache holding at most `capacity` results
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Cache key of a query: its tokens with whitespace, comments and keyword
+    /// case normalized away, and unquoted identifiers folded by `identifier_case`
+    ///
+    /// Returns None for SQL that fails to tokenize or calls a volatile function,
+    /// whose results must not be reused.
+    pub fn normalize(sql: &str, identifier_case: IdentifierCase) -> Option<String> {
+        let tokens = Tokenizer::new()
+            .with_identifier_case(identifier_case)
+            .tokenize(sql)
+            .ok()?;
+        let mut key = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            match &token.token_type {
+                TokenType::Whitespace | TokenType::EOF | TokenType::Semicolon => continue,
+                TokenType::Identifier(_) | TokenType::Keyword(_)
+                    if FunctionRegistry::builtin().is_volatile(&token.text) =>
+                {
+                    return None
+                }
+                _ => {}
+            }
+            key.push(format!("{:?}", token.token_type));
+        }
+        Some(key.join(" "))
+    }
+
+    /// Cached result for `key`, if there is one that is still current in `catalog`
+    pub fn get(&self, key: &str, catalog: &Catalog) -> Option<QueryResult> {
+        let mut state = self.state.lock_recovered();
+        state.tick += 1;
+        let tick = state.tick;
+
+        let current = match state.entries.get_mut(key) {
+            Some(entry)
+                if entry
+                    .tables
+                    .iter()
+                    .all(|table| catalog.table_version(table) <= entry.version) =>
+            {
+                entry.last_used = tick;
+                Some(entry.result.clone())
+            }
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        if current.is_some() {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+        current
+    }
+
+    /// Cache the result of the query `key`, which read `tables` at catalog `version`
+    pub fn insert(&self, key: String, result: &QueryResult, tables: HashSet<String>, version: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock_recovered();
+        state.tick += 1;
+        let tick = state.tick;
+
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(
+            key,
+            CacheEntry {
+                result: result.clone(),
+                tables,
+                version,
+                last_used: tick,
+            },
+        );
+    }
+
+    /// Drop every cached result
+    pub fn clear(&self) {
+        self.state.lock_recovered().entries.clear();
+    }
+
+    /// Hit and miss counts so far
+    pub fn stats(&self) -> QueryCacheStats {
+        let state = self.state.lock_recovered();
+        QueryCacheStats {
+            entries: state.entries.len(),
+            ..state.stats
+        }
+    }
+}
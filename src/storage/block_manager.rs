@@ -5,14 +5,34 @@
 //! - Block allocation and deallocation
 //! - Reading and writing blocks to disk
 //! - Free list management
+//!
+//! A database file starts with a fixed-size header ahead of its blocks. All
+//! integers are little-endian, and the rest of the header is zero padding.
+//!
+//! ```text
+//! header: magic "PRDB" (4 bytes) | format version (u32) | CRC-32 of magic and version (u32)
+//! ```
+//!
+//! Opening a file whose header doesn't match is refused, so any change to the
+//! file layout must bump [`DB_FORMAT_VERSION`].
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
 use std::collections::HashSet;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
+/// Magic bytes at the start of every database file
+pub const DB_MAGIC: [u8; 4] = *b"PRDB";
+
+/// Version of the database file format written by this build
+pub const DB_FORMAT_VERSION: u32 = 1;
+
+/// Bytes reserved for the file header ahead of the first block
+pub const DB_HEADER_SIZE: u64 = 4096;
+
 /// Block size (256KB default)
 pub const BLOCK_SIZE: usize = 262144;
 
@@ -169,20 +189,30 @@ impl BlockManager {
                 .map_err(|e| PrismDBError::Storage(format!("Failed to create directory: {}", e)))?;
         }
 
-        // Open or create the file
-        let file = OpenOptions::new()
+        // A missing or empty file is a new database, which gets its header first
+        let is_new = std::fs::metadata(&file_path).map(|m| m.len() == 0).unwrap_or(true);
+        if is_new {
+            write_db_header(&file_path)?;
+        }
+
+        let mut file = OpenOptions::new()
             .read(true)
             .write(true)
-            .create(true)
             .open(&file_path)
             .map_err(|e| PrismDBError::Storage(format!("Failed to open database file: {}", e)))?;
+        read_db_header(&mut file).map_err(|e| match e {
+            PrismDBError::Storage(msg) => {
+                PrismDBError::Storage(format!("{}: {}", file_path.display(), msg))
+            }
+            other => other,
+        })?;
 
         // Get file size to determine number of blocks
         let file_size = file
             .metadata()
             .map_err(|e| PrismDBError::Storage(format!("Failed to get file metadata: {}", e)))?
             .len();
-        let total_blocks = file_size / BLOCK_SIZE as u64;
+        let total_blocks = file_size.saturating_sub(DB_HEADER_SIZE) / BLOCK_SIZE as u64;
 
         Ok(Self {
             file_path,
@@ -196,7 +226,7 @@ impl BlockManager {
     /// Allocate a new block
     pub fn allocate_block(&self, block_type: BlockType) -> PrismDBResult<BlockId> {
         // Try to reuse a free block first
-        let mut free_list = self.free_list.write().unwrap();
+        let mut free_list = self.free_list.write_or_err()?;
         if let Some(&block_id) = free_list.iter().next() {
             free_list.remove(&block_id);
             return Ok(block_id);
@@ -204,11 +234,11 @@ impl BlockManager {
         drop(free_list);
 
         // Allocate a new block
-        let mut next_id = self.next_block_id.write().unwrap();
+        let mut next_id = self.next_block_id.write_or_err()?;
         let block_id = *next_id;
         *next_id += 1;
 
-        let mut total = self.total_blocks.write().unwrap();
+        let mut total = self.total_blocks.write_or_err()?;
         *total += 1;
 
         // Initialize the block
@@ -220,17 +250,17 @@ impl BlockManager {
 
     /// Free a block
     pub fn free_block(&self, block_id: BlockId) -> PrismDBResult<()> {
-        let mut free_list = self.free_list.write().unwrap();
+        let mut free_list = self.free_list.write_or_err()?;
         free_list.insert(block_id);
         Ok(())
     }
 
     /// Read a block from disk
     pub fn read_block(&self, block_id: BlockId) -> PrismDBResult<Block> {
-        let mut file = self.file.write().unwrap();
+        let mut file = self.file.write_or_err()?;
 
         // Seek to block position
-        let offset = block_id * BLOCK_SIZE as u64;
+        let offset = DB_HEADER_SIZE + block_id * BLOCK_SIZE as u64;
         file.seek(SeekFrom::Start(offset)).map_err(|e| {
             PrismDBError::Storage(format!("Failed to seek to block {}: {}", block_id, e))
         })?;
@@ -246,10 +276,10 @@ impl BlockManager {
 
     /// Write a block to disk
     pub fn write_block(&self, block_id: BlockId, block: &Block) -> PrismDBResult<()> {
-        let mut file = self.file.write().unwrap();
+        let mut file = self.file.write_or_err()?;
 
         // Seek to block position
-        let offset = block_id * BLOCK_SIZE as u64;
+        let offset = DB_HEADER_SIZE + block_id * BLOCK_SIZE as u64;
         file.seek(SeekFrom::Start(offset)).map_err(|e| {
             PrismDBError::Storage(format!("Failed to seek to block {}: {}", block_id, e))
         })?;
@@ -270,7 +300,7 @@ impl BlockManager {
 
     /// Get total number of blocks
     pub fn get_total_blocks(&self) -> u64 {
-        *self.total_blocks.read().unwrap()
+        *self.total_blocks.read_recovered()
     }
 
     /// Get file path
@@ -280,13 +310,84 @@ impl BlockManager {
 
     /// Sync all data to disk
     pub fn sync(&self) -> PrismDBResult<()> {
-        let file = self.file.write().unwrap();
+        let file = self.file.write_or_err()?;
         file.sync_all()
             .map_err(|e| PrismDBError::Storage(format!("Failed to sync database file: {}", e)))?;
         Ok(())
     }
 }
 
+/// Encode the header of a database file, padded to [`DB_HEADER_SIZE`]
+pub fn db_header_bytes() -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(DB_HEADER_SIZE as usize);
+    bytes.extend_from_slice(&DB_MAGIC);
+    bytes.extend_from_slice(&DB_FORMAT_VERSION.to_le_bytes());
+    let checksum = crc32fast::hash(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes.resize(DB_HEADER_SIZE as usize, 0);
+    bytes
+}
+
+/// Create a database file holding only its header
+///
+/// The header is written to a sibling temporary file that is then renamed into
+/// place, so a crash never leaves a file with a partial header behind.
+fn write_db_header(file_path: &Path) -> PrismDBResult<()> {
+    let mut temp_name = file_path.as_os_str().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+
+    let written = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(&db_header_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, file_path));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(PrismDBError::Storage(format!(
+            "Failed to create database file: {}",
+            e
+        )));
+    }
+    Ok(())
+}
+
+/// Read and validate the header at the start of a database file
+///
+/// Returns the file's format version.
+pub fn read_db_header<R: Read + Seek>(reader: &mut R) -> PrismDBResult<u32> {
+    let mut header = [0u8; 12];
+    reader
+        .seek(SeekFrom::Start(0))
+        .and_then(|_| reader.read_exact(&mut header))
+        .map_err(|_| {
+            PrismDBError::Storage("Not a PrismDB database file: header is missing".to_string())
+        })?;
+    if header[0..4] != DB_MAGIC {
+        return Err(PrismDBError::Storage(
+            "Not a PrismDB database file: bad magic bytes".to_string(),
+        ));
+    }
+
+    let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    if version != DB_FORMAT_VERSION {
+        return Err(PrismDBError::Storage(format!(
+            "Unsupported database format version {} (this build reads version {}); \
+             open it with a PrismDB release that supports that version",
+            version, DB_FORMAT_VERSION
+        )));
+    }
+
+    let checksum = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if checksum != crc32fast::hash(&header[0..8]) {
+        return Err(PrismDBError::Storage(
+            "Database file header is corrupt: checksum mismatch".to_string(),
+        ));
+    }
+    Ok(version)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,4 +451,16 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_db_header_roundtrip() -> PrismDBResult<()> {
+        let bytes = db_header_bytes();
+        assert_eq!(bytes.len(), DB_HEADER_SIZE as usize);
+        assert_eq!(read_db_header(&mut std::io::Cursor::new(bytes.clone()))?, DB_FORMAT_VERSION);
+
+        let mut corrupt = bytes;
+        corrupt[8] ^= 0xFF;
+        assert!(read_db_header(&mut std::io::Cursor::new(corrupt)).is_err());
+        Ok(())
+    }
 }
@@ -7,6 +7,7 @@
 //! - Memory usage tracking
 
 use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::common::sync::MutexExt;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
@@ -363,19 +364,19 @@ impl BufferManager {
 
     /// Get a memory buffer
     pub fn get_memory_buffer(&self, size: usize) -> PrismDBResult<MemoryBuffer> {
-        let mut pool = self.pool.lock().unwrap();
+        let mut pool = self.pool.lock_or_err()?;
         pool.get_buffer(size)
     }
 
     /// Return a memory buffer
     pub fn return_memory_buffer(&self, buffer: MemoryBuffer) {
-        let mut pool = self.pool.lock().unwrap();
+        let mut pool = self.pool.lock_recovered();
         pool.return_buffer(buffer);
     }
 
     /// Get a page buffer
     pub fn get_page_buffer(&self, page_id: u64) -> PrismDBResult<PageBuffer> {
-        let mut cache = self.page_cache.lock().unwrap();
+        let mut cache = self.page_cache.lock_or_err()?;
 
         if let Some(page) = cache.get(&page_id) {
             Ok(page.clone())
@@ -388,7 +389,7 @@ impl BufferManager {
 
     /// Flush dirty pages
     pub fn flush_dirty_pages(&self) -> PrismDBResult<Vec<u64>> {
-        let cache = self.page_cache.lock().unwrap();
+        let cache = self.page_cache.lock_or_err()?;
         let mut dirty_pages = Vec::new();
 
         for (page_id, page) in cache.iter() {
@@ -402,16 +403,16 @@ impl BufferManager {
 
     /// Get memory usage statistics
     pub fn get_memory_usage(&self) -> PrismDBResult<MemoryUsage> {
-        let pool = self.pool.lock().unwrap();
+        let pool = self.pool.lock_or_err()?;
         Ok(pool.get_memory_usage())
     }
 
     /// Cleanup unused resources
     pub fn cleanup(&mut self) -> PrismDBResult<()> {
-        let mut pool = self.pool.lock().unwrap();
+        let mut pool = self.pool.lock_or_err()?;
         pool.cleanup();
 
-        let mut cache = self.page_cache.lock().unwrap();
+        let mut cache = self.page_cache.lock_or_err()?;
         cache.clear();
 
         Ok(())
@@ -8,7 +8,11 @@
 
 use crate::common::error::{PrismDBError, PrismDBResult};
 use crate::types::{LogicalType, Value, Vector};
+use std::cmp::Ordering;
 
+use crate::storage::compression::{
+    auto_compress, CompressionError, CompressionFunction, CompressionType, UncompressedStorage,
+};
 // Import ColumnInfo from table module to avoid duplication
 use crate::storage::table::ColumnInfo;
 
@@ -45,6 +49,129 @@ pub trait ColumnDataStorage: Send + Sync {
     fn create_vector(&self, start: usize, count: usize) -> PrismDBResult<Vector>;
 }
 
+/// Number of rows summarized by each zone map
+pub const ZONE_SIZE: usize = 128;
+
+/// Number of rows compressed together as one storage segment
+pub const SEGMENT_SIZE: usize = 16 * ZONE_SIZE;
+
+/// How a column's values compress, as reported by `PRAGMA storage_info`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStorageInfo {
+    /// Compression chosen for most of the column's segments
+    pub compression: CompressionType,
+    /// Bytes the compressed segments take
+    pub compressed_size: usize,
+    /// Bytes the same segments take without compression
+    pub uncompressed_size: usize,
+    /// Number of `SEGMENT_SIZE`-row segments
+    pub segment_count: usize,
+}
+
+/// Bounds on a column's values, used to skip zones during scans
+///
+/// NULL never falls inside a range, since every range comes from a comparison.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValueRange {
+    /// (value, inclusive)
+    pub lower: Option<(Value, bool)>,
+    /// (value, inclusive)
+    pub upper: Option<(Value, bool)>,
+}
+
+impl ValueRange {
+    /// The range of values inside both `self` and `other`
+    ///
+    /// Where two bounds cannot be compared, `self`'s is kept.
+    pub fn intersect(&self, other: &ValueRange) -> ValueRange {
+        // The tighter of two bounds; `toward` is the ordering a tighter value has
+        fn tighter(
+            a: &Option<(Value, bool)>,
+            b: &Option<(Value, bool)>,
+            toward: Ordering,
+        ) -> Option<(Value, bool)> {
+            match (a, b) {
+                (Some((x, x_inclusive)), Some((y, y_inclusive))) => match y.compare(x) {
+                    Ok(ordering) if ordering == toward => b.clone(),
+                    Ok(Ordering::Equal) => Some((x.clone(), *x_inclusive && *y_inclusive)),
+                    _ => a.clone(),
+                },
+                (None, _) => b.clone(),
+                (_, None) => a.clone(),
+            }
+        }
+        ValueRange {
+            lower: tighter(&self.lower, &other.lower, Ordering::Greater),
+            upper: tighter(&self.upper, &other.upper, Ordering::Less),
+        }
+    }
+
+    /// Whether a value between `min` and `max` may fall inside the range.
+    /// Values that cannot be compared are assumed to overlap.
+    pub fn may_overlap(&self, min: &Value, max: &Value) -> bool {
+        if let Some((lower, inclusive)) = &self.lower {
+            match max.compare(lower) {
+                Ok(Ordering::Less) => return false,
+                Ok(Ordering::Equal) if !inclusive => return false,
+                _ => {}
+            }
+        }
+        if let Some((upper, inclusive)) = &self.upper {
+            match min.compare(upper) {
+                Ok(Ordering::Greater) => return false,
+                Ok(Ordering::Equal) if !inclusive => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+}
+
+/// Minimum and maximum non-null value of one zone of a column
+///
+/// Bounds only widen: updates and deletes leave them in place, so they can be looser
+/// than the zone's current contents but never tighter.
+#[derive(Debug, Clone, Default)]
+pub struct ZoneMap {
+    /// (min, max), or `None` while the zone holds only NULLs
+    pub bounds: Option<(Value, Value)>,
+    /// Set once a value could not be ordered against the bounds
+    pub unordered: bool,
+}
+
+impl ZoneMap {
+    fn include(&mut self, value: &Value) {
+        if value.is_null() || self.unordered {
+            return;
+        }
+        match &mut self.bounds {
+            None => self.bounds = Some((value.clone(), value.clone())),
+            Some((min, max)) => match (value.compare(min), value.compare(max)) {
+                (Ok(below), Ok(above)) => {
+                    if below == Ordering::Less {
+                        *min = value.clone();
+                    }
+                    if above == Ordering::Greater {
+                        *max = value.clone();
+                    }
+                }
+                _ => self.unordered = true,
+            },
+        }
+    }
+
+    /// Whether the zone may hold a value inside `range`
+    pub fn may_contain(&self, range: &ValueRange) -> bool {
+        if self.unordered {
+            return true;
+        }
+        match &self.bounds {
+            Some((min, max)) => range.may_overlap(min, max),
+            None => false,
+        }
+    }
+}
+
 /// Generic column data storage
 #[derive(Debug, Clone)]
 pub struct ColumnData {
@@ -56,6 +183,8 @@ pub struct ColumnData {
     null_mask: Vec<bool>,
     /// Capacity
     capacity: usize,
+    /// One zone map per `ZONE_SIZE` rows
+    zones: Vec<ZoneMap>,
 }
 
 impl ColumnData {
@@ -66,6 +195,7 @@ impl ColumnData {
             values: Vec::with_capacity(capacity),
             null_mask: Vec::with_capacity(capacity),
             capacity,
+            zones: Vec::new(),
         })
     }
 
@@ -118,6 +248,7 @@ impl ColumnData {
             self.null_mask.resize(index + 1, false);
         }
         self.null_mask[index] = value.is_null();
+        self.include_in_zone(index, value);
 
         Ok(())
     }
@@ -132,10 +263,24 @@ impl ColumnData {
 
         self.values.push(value.clone());
         self.null_mask.push(value.is_null());
+        self.include_in_zone(self.values.len() - 1, value);
 
         Ok(())
     }
 
+    /// Get the zone maps, one per `ZONE_SIZE` rows
+    pub fn zone_maps(&self) -> &[ZoneMap] {
+        &self.zones
+    }
+
+    fn include_in_zone(&mut self, index: usize, value: &Value) {
+        let zone = index / ZONE_SIZE;
+        if zone >= self.zones.len() {
+            self.zones.resize(zone + 1, ZoneMap::default());
+        }
+        self.zones[zone].include(value);
+    }
+
     /// Delete a value by index (mark as null)
     pub fn delete_value(&mut self, index: usize) -> PrismDBResult<()> {
         if index >= self.values.len() {
@@ -168,10 +313,35 @@ impl ColumnData {
         Ok(())
     }
 
+    /// Keep only the values at the positions where `keep` is true, in order
+    ///
+    /// Positions past the end of `keep` are kept. The buffers are rebuilt at
+    /// their new size, so the space of the dropped values is given back.
+    pub fn retain(&mut self, keep: &[bool]) {
+        let kept: Vec<(Value, bool)> = (0..self.values.len())
+            .filter(|&index| keep.get(index).copied().unwrap_or(true))
+            .map(|index| {
+                let is_null = self.null_mask.get(index).copied().unwrap_or(false);
+                (self.values[index].clone(), is_null)
+            })
+            .collect();
+
+        self.clear();
+        let (values, null_mask): (Vec<Value>, Vec<bool>) = kept.into_iter().unzip();
+        for (index, (value, &is_null)) in values.iter().zip(&null_mask).enumerate() {
+            if !is_null {
+                self.include_in_zone(index, value);
+            }
+        }
+        self.values = values;
+        self.null_mask = null_mask;
+    }
+
     /// Clear all values from the column
     pub fn clear(&mut self) {
         self.values.clear();
         self.null_mask.clear();
+        self.zones.clear();
     }
 
     /// Estimate memory usage
@@ -217,7 +387,7 @@ impl ColumnData {
             ));
         }
 
-        Vector::from_values(&vector_values)
+        Vector::from_values_with_type(&vector_values, &self.info.column_type)
     }
 
     /// Get the number of rows in the column
@@ -229,6 +399,47 @@ impl ColumnData {
     pub fn null_count(&self) -> usize {
         self.null_mask.iter().filter(|&&is_null| is_null).count()
     }
+
+    /// Compress the column's values segment by segment with the compression
+    /// the analyzer selects for each, and report the result
+    pub fn storage_info(&self) -> PrismDBResult<ColumnStorageInfo> {
+        let compression_error = |e: CompressionError| PrismDBError::Compression(e.to_string());
+        let values = (0..self.values.len())
+            .map(|index| self.get_value(index))
+            .collect::<PrismDBResult<Vec<_>>>()?;
+
+        let mut info = ColumnStorageInfo {
+            compression: CompressionType::Uncompressed,
+            compressed_size: 0,
+            uncompressed_size: 0,
+            segment_count: 0,
+        };
+        let mut segments_by_type: Vec<(CompressionType, usize)> = Vec::new();
+        for segment_values in values.chunks(SEGMENT_SIZE) {
+            let segment = auto_compress(segment_values).map_err(compression_error)?;
+            let uncompressed = UncompressedStorage::new()
+                .compress(segment_values)
+                .map_err(compression_error)?;
+            info.compressed_size += segment.total_size();
+            info.uncompressed_size += uncompressed.total_size();
+            info.segment_count += 1;
+            match segments_by_type
+                .iter_mut()
+                .find(|(compression, _)| *compression == segment.compression_type)
+            {
+                Some((_, count)) => *count += 1,
+                None => segments_by_type.push((segment.compression_type, 1)),
+            }
+        }
+        // The earliest type among those tied for the most segments
+        if let Some(most) = segments_by_type.iter().map(|(_, count)| *count).max() {
+            info.compression = segments_by_type
+                .iter()
+                .find(|(_, count)| *count == most)
+                .map_or(CompressionType::Uncompressed, |(compression, _)| *compression);
+        }
+        Ok(info)
+    }
 }
 
 impl ColumnDataStorage for ColumnData {
@@ -261,36 +472,11 @@ impl ColumnDataStorage for ColumnData {
     }
 
     fn set_value(&mut self, index: usize, value: &Value) -> PrismDBResult<()> {
-        if index >= self.values.len() {
-            return Err(PrismDBError::InvalidValue(format!(
-                "Index {} out of bounds for column with {} values",
-                index,
-                self.values.len()
-            )));
-        }
-
-        self.values[index] = value.clone();
-
-        // Update null mask
-        if index >= self.null_mask.len() {
-            self.null_mask.resize(index + 1, false);
-        }
-        self.null_mask[index] = value.is_null();
-
-        Ok(())
+        ColumnData::set_value(self, index, value)
     }
 
     fn push_value(&mut self, value: &Value) -> PrismDBResult<()> {
-        if self.values.len() >= self.capacity {
-            return Err(PrismDBError::InvalidValue(
-                "Column capacity exceeded".to_string(),
-            ));
-        }
-
-        self.values.push(value.clone());
-        self.null_mask.push(value.is_null());
-
-        Ok(())
+        ColumnData::push_value(self, value)
     }
 
     fn delete_value(&mut self, index: usize) -> PrismDBResult<()> {
@@ -359,7 +545,7 @@ impl ColumnDataStorage for ColumnData {
             ));
         }
 
-        Vector::from_values(&vector_values)
+        Vector::from_values_with_type(&vector_values, &self.info.column_type)
     }
 }
 
@@ -475,6 +661,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_column_zone_maps() -> PrismDBResult<()> {
+        let info = ColumnInfo::new("test".to_string(), LogicalType::Integer, 0);
+        let mut column = ColumnData::new(info, ZONE_SIZE * 3)?;
+        for i in 0..(ZONE_SIZE * 2 + 1) {
+            column.push_value(&Value::Integer(i as i32))?;
+        }
+        assert_eq!(column.zone_maps().len(), 3);
+
+        let between = |low: i32, high: i32| ValueRange {
+            lower: Some((Value::Integer(low), true)),
+            upper: Some((Value::Integer(high), false)),
+        };
+        let zones = column.zone_maps();
+        assert!(zones[0].may_contain(&between(0, 1)));
+        assert!(!zones[0].may_contain(&between(ZONE_SIZE as i32, 1000)));
+        assert!(zones[1].may_contain(&between(ZONE_SIZE as i32, ZONE_SIZE as i32 + 1)));
+        assert!(!zones[1].may_contain(&between(-5, ZONE_SIZE as i32)));
+
+        // Bounds widen on update and survive deletes
+        column.set_value(1, &Value::Integer(5000))?;
+        column.delete_value(0)?;
+        assert!(column.zone_maps()[0].may_contain(&between(4000, 6000)));
+        assert!(column.zone_maps()[0].may_contain(&between(0, 1)));
+
+        // A zone of NULLs holds nothing in range
+        let info = ColumnInfo::new("empty".to_string(), LogicalType::Integer, 0);
+        let mut column = ColumnData::new(info, 2)?;
+        column.push_value(&Value::Null)?;
+        assert!(!column.zone_maps()[0].may_contain(&between(0, 1)));
+        Ok(())
+    }
+
     #[test]
     fn test_column_error_handling() -> PrismDBResult<()> {
         let info = ColumnInfo::new("test".to_string(), LogicalType::Integer, 0);
@@ -0,0 +1,185 @@
+//! Secondary indexes over table columns
+//!
+//! An index maps the values of one column to the ids of the live rows that
+//! hold them, kept in value order so both equality and range conditions can
+//! be answered without reading the table. NULLs are not indexed: no
+//! comparison matches them.
+
+use crate::storage::column::ValueRange;
+use crate::types::Value;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+/// A value ordered by `Value::compare`
+///
+/// The keys of one index all come from one column, so they always compare;
+/// a failed comparison is treated as equal.
+#[derive(Debug, Clone)]
+struct IndexKey(Value);
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.compare(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Row ids of a table by the value of one of its columns
+#[derive(Debug, Clone)]
+pub struct ColumnIndex {
+    name: String,
+    column: usize,
+    entries: BTreeMap<IndexKey, Vec<usize>>,
+}
+
+impl ColumnIndex {
+    /// Create an empty index named `name` on the column at `column`
+    pub fn new(name: String, column: usize) -> Self {
+        Self {
+            name,
+            column,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Name of the index
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Position of the indexed column in the table
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Keep pointing at the indexed column after the column at `removed` is dropped
+    pub fn column_removed(&mut self, removed: usize) {
+        if self.column > removed {
+            self.column -= 1;
+        }
+    }
+
+    /// Record that row `row_id` holds `value`
+    pub fn insert(&mut self, value: &Value, row_id: usize) {
+        if !value.is_null() {
+            self.entries.entry(IndexKey(value.clone())).or_default().push(row_id);
+        }
+    }
+
+    /// Forget that row `row_id` holds `value`
+    pub fn remove(&mut self, value: &Value, row_id: usize) {
+        let key = IndexKey(value.clone());
+        if let Some(rows) = self.entries.get_mut(&key) {
+            rows.retain(|&row| row != row_id);
+            if rows.is_empty() {
+                self.entries.remove(&key);
+            }
+        }
+    }
+
+    /// Remove every entry
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Ids of the rows whose value falls inside `range`, in ascending order
+    /// and each once, since a row is also indexed under older values readers
+    /// may still see
+    ///
+    /// Returns None if a bound cannot be compared with the indexed values, in
+    /// which case the caller has to read the table instead.
+    pub fn lookup(&self, range: &ValueRange) -> Option<Vec<usize>> {
+        if let Some((IndexKey(first), _)) = self.entries.first_key_value() {
+            let mut bounds = range.lower.iter().chain(range.upper.iter());
+            if bounds.any(|(value, _)| first.compare(value).is_err()) {
+                return None;
+            }
+        }
+        let bound = |bound: &Option<(Value, bool)>| match bound {
+            Some((value, true)) => Bound::Included(IndexKey(value.clone())),
+            Some((value, false)) => Bound::Excluded(IndexKey(value.clone())),
+            None => Bound::Unbounded,
+        };
+        let (lower, upper) = (bound(&range.lower), bound(&range.upper));
+        // BTreeMap::range panics on an empty or inverted range
+        if let (Bound::Included(l) | Bound::Excluded(l), Bound::Included(u) | Bound::Excluded(u)) =
+            (&lower, &upper)
+        {
+            let exclusive = matches!(lower, Bound::Excluded(_)) || matches!(upper, Bound::Excluded(_));
+            if l > u || (l == u && exclusive) {
+                return Some(Vec::new());
+            }
+        }
+
+        let mut rows: Vec<usize> = self
+            .entries
+            .range((lower, upper))
+            .flat_map(|(_, rows)| rows.iter().copied())
+            .collect();
+        rows.sort_unstable();
+        rows.dedup();
+        Some(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(lower: Option<(i32, bool)>, upper: Option<(i32, bool)>) -> ValueRange {
+        ValueRange {
+            lower: lower.map(|(v, inclusive)| (Value::Integer(v), inclusive)),
+            upper: upper.map(|(v, inclusive)| (Value::Integer(v), inclusive)),
+        }
+    }
+
+    #[test]
+    fn test_lookup_ranges() {
+        let mut index = ColumnIndex::new("idx".to_string(), 0);
+        for (row, value) in [5, 3, 5, 9, 1].iter().enumerate() {
+            index.insert(&Value::Integer(*value), row);
+        }
+        index.insert(&Value::Null, 5);
+
+        assert_eq!(index.lookup(&range(Some((5, true)), Some((5, true)))), Some(vec![0, 2]));
+        assert_eq!(index.lookup(&range(Some((3, false)), None)), Some(vec![0, 2, 3]));
+        assert_eq!(index.lookup(&range(None, Some((3, true)))), Some(vec![1, 4]));
+        assert_eq!(index.lookup(&range(Some((5, false)), Some((5, true)))), Some(vec![]));
+        assert_eq!(index.lookup(&range(Some((9, true)), Some((1, true)))), Some(vec![]));
+
+        // Probes of another numeric type compare with the keys
+        let big = ValueRange {
+            lower: Some((Value::BigInt(9), true)),
+            upper: Some((Value::BigInt(9), true)),
+        };
+        assert_eq!(index.lookup(&big), Some(vec![3]));
+
+        index.remove(&Value::Integer(5), 0);
+        assert_eq!(index.lookup(&range(Some((5, true)), Some((5, true)))), Some(vec![2]));
+    }
+
+    #[test]
+    fn test_incomparable_bound_needs_scan() {
+        let mut index = ColumnIndex::new("idx".to_string(), 0);
+        index.insert(&Value::Integer(1), 0);
+        let text = ValueRange {
+            lower: Some((Value::Varchar("a".to_string()), true)),
+            upper: None,
+        };
+        assert_eq!(index.lookup(&text), None);
+    }
+}
@@ -4,6 +4,7 @@
 //! - Table data management
 //! - Column storage
 //! - Compression (Dictionary, RLE, and future algorithms)
+//! - Secondary indexes
 //! - Buffer management
 //! - Block management for disk I/O
 //! - Transaction handling
@@ -13,6 +14,7 @@ pub mod block_manager;
 pub mod buffer;
 pub mod column;
 pub mod compression;
+pub mod index;
 pub mod table;
 pub mod transaction;
 pub mod wal;
@@ -21,6 +23,7 @@ pub use block_manager::*;
 pub use buffer::*;
 pub use column::*;
 pub use compression::*;
+pub use index::*;
 pub use table::*;
 pub use transaction::*;
 pub use wal::*;
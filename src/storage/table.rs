@@ -6,11 +6,18 @@
 //! - Index management
 //! - Statistics tracking
 
-use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::storage::column::ColumnData;
-use crate::types::{DataChunk, LogicalType, Value};
+use crate::common::error::{ObjectKind, PrismDBError, PrismDBResult};
+use crate::common::sync::RwLockExt;
+use crate::parser::quote_identifier;
+use crate::storage::column::{ColumnData, ValueRange, ZONE_SIZE};
+use crate::storage::index::ColumnIndex;
+use crate::types::{DataChunk, LogicalType, OrderedValue, Value};
+use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
+use uuid::Uuid;
 
 /// Row identifier for table rows
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -246,6 +253,15 @@ pub struct ColumnInfo {
     pub is_primary_key: bool,
     /// Whether column has unique constraint
     pub is_unique: bool,
+    /// DEFAULT expression as SQL text, as written in CREATE TABLE
+    #[serde(default)]
+    pub default_expression: Option<String>,
+    /// Collation declared with COLLATE in CREATE TABLE
+    #[serde(default)]
+    pub collation: Option<String>,
+    /// Length declared with VARCHAR(n); CHAR(n) keeps its length in the type
+    #[serde(default)]
+    pub max_length: Option<usize>,
 }
 
 impl ColumnInfo {
@@ -258,6 +274,9 @@ impl ColumnInfo {
             column_index,
             is_primary_key: false,
             is_unique: false,
+            default_expression: None,
+            collation: None,
+            max_length: None,
         }
     }
 
@@ -270,6 +289,9 @@ impl ColumnInfo {
             column_index,
             is_primary_key: true,
             is_unique: true,
+            default_expression: None,
+            collation: None,
+            max_length: None,
         }
     }
 
@@ -282,8 +304,100 @@ impl ColumnInfo {
             column_index,
             is_primary_key: false,
             is_unique: true,
+            default_expression: None,
+            collation: None,
+            max_length: None,
         }
     }
+
+    /// Longest string the column is declared to hold, from VARCHAR(n) or CHAR(n)
+    pub fn declared_length(&self) -> Option<usize> {
+        match self.column_type {
+            LogicalType::Char { length } => Some(length),
+            _ => self.max_length,
+        }
+    }
+
+    /// The column's type as written in DDL, with the length of a VARCHAR(n)
+    pub fn type_name(&self) -> String {
+        match (&self.column_type, self.max_length) {
+            (LogicalType::Varchar, Some(length)) => format!("VARCHAR({})", length),
+            (column_type, _) => column_type.to_string(),
+        }
+    }
+}
+
+/// What INSERT and UPDATE do with a string longer than its column's declared
+/// length (`SET string_length_limit`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StringLengthMode {
+    /// Store the string whole (the default)
+    #[default]
+    Ignore,
+    /// Cut the string to the declared length
+    Truncate,
+    /// Fail the statement
+    Error,
+}
+
+impl StringLengthMode {
+    /// Parse a setting value (`ignore`, `truncate` or `error`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ignore" => Some(StringLengthMode::Ignore),
+            "truncate" => Some(StringLengthMode::Truncate),
+            "error" => Some(StringLengthMode::Error),
+            _ => None,
+        }
+    }
+
+    /// Apply the mode to a value about to be stored in `column`; lengths count characters
+    pub fn apply(self, column: &ColumnInfo, value: Value) -> PrismDBResult<Value> {
+        let max_length = match (self, column.declared_length()) {
+            (StringLengthMode::Ignore, _) | (_, None) => return Ok(value),
+            (_, Some(max_length)) => max_length,
+        };
+        let text = match &value {
+            Value::Varchar(text) | Value::Char(text) => text,
+            _ => return Ok(value),
+        };
+        if text.chars().nth(max_length).is_none() {
+            return Ok(value);
+        }
+        if self == StringLengthMode::Error {
+            return Err(PrismDBError::InvalidValue(format!(
+                "Value of {} characters is too long for column '{}' of type {}",
+                text.chars().count(),
+                column.name,
+                column.type_name()
+            )));
+        }
+        let truncated = text.chars().take(max_length).collect();
+        Ok(match value {
+            Value::Char(_) => Value::Char(truncated),
+            _ => Value::Varchar(truncated),
+        })
+    }
+}
+
+/// Table-level constraint that is not captured by per-column flags
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TableConstraintInfo {
+    /// Multi-column UNIQUE (column indexes), or any UNIQUE NULLS NOT DISTINCT
+    Unique {
+        columns: Vec<usize>,
+        /// NULLs count as equal, so at most one row has a NULL key
+        #[serde(default)]
+        nulls_not_distinct: bool,
+    },
+    /// CHECK constraint with its expression as SQL text
+    Check { expression: String },
+    /// FOREIGN KEY (column indexes) referencing another table
+    ForeignKey {
+        columns: Vec<usize>,
+        foreign_table: String,
+        foreign_columns: Vec<String>,
+    },
 }
 
 /// Table metadata
@@ -303,6 +417,9 @@ pub struct TableInfo {
     pub statistics: TableStatistics,
     /// Whether table is temporary
     pub is_temporary: bool,
+    /// Table-level constraints (multi-column UNIQUE, CHECK, FOREIGN KEY)
+    #[serde(default)]
+    pub constraints: Vec<TableConstraintInfo>,
 }
 
 impl TableInfo {
@@ -315,6 +432,7 @@ impl TableInfo {
             primary_key: Vec::new(),
             statistics: TableStatistics::new(0),
             is_temporary: false,
+            constraints: Vec::new(),
         }
     }
 
@@ -327,6 +445,7 @@ impl TableInfo {
             primary_key: Vec::new(),
             statistics: TableStatistics::new(0),
             is_temporary: false,
+            constraints: Vec::new(),
         }
     }
 
@@ -364,7 +483,7 @@ impl TableInfo {
                 self.columns[index].is_primary_key = true;
                 self.columns[index].is_unique = true;
             } else {
-                return Err(PrismDBError::InvalidValue(format!(
+                return Err(PrismDBError::NotFound(ObjectKind::Column, format!(
                     "Column '{}' not found in table '{}'",
                     column_name, self.name
                 )));
@@ -377,6 +496,315 @@ impl TableInfo {
     pub fn column_count(&self) -> usize {
         self.columns.len()
     }
+
+    /// Column sets no two rows may share a value of, each with whether NULLs
+    /// count as equal: the primary key, UNIQUE columns and UNIQUE constraints
+    pub fn unique_keys(&self) -> Vec<(Vec<usize>, bool)> {
+        let mut keys = Vec::new();
+        if !self.primary_key.is_empty() {
+            keys.push((self.primary_key.clone(), false));
+        }
+        for (index, column) in self.columns.iter().enumerate() {
+            // Columns of the primary key are flagged unique too
+            if column.is_unique && !column.is_primary_key {
+                keys.push((vec![index], false));
+            }
+        }
+        for constraint in &self.constraints {
+            if let TableConstraintInfo::Unique {
+                columns,
+                nulls_not_distinct,
+            } = constraint
+            {
+                keys.push((columns.clone(), *nulls_not_distinct));
+            }
+        }
+        keys
+    }
+
+    /// Fail if a row about to be stored has NULL in a NOT NULL column
+    pub fn check_not_null(&self, row: &[Value]) -> PrismDBResult<()> {
+        match self
+            .columns
+            .iter()
+            .zip(row)
+            .find(|(column, value)| !column.nullable && value.is_null())
+        {
+            Some((column, _)) => Err(PrismDBError::Constraint(format!(
+                "NULL value in column '{}' violates a not-null constraint of table '{}'",
+                column.name, self.table_name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Render the CREATE TABLE statement that recreates this table's schema
+    ///
+    /// Names are quoted where they would otherwise parse differently.
+    pub fn to_create_sql(&self) -> String {
+        let single_primary_key = self.primary_key.len() == 1;
+        let mut lines: Vec<String> = self
+            .columns
+            .iter()
+            .map(|col| {
+                let mut line = format!("  {} {}", quote_identifier(&col.name), col.type_name());
+                if col.is_primary_key && single_primary_key {
+                    line.push_str(" PRIMARY KEY");
+                } else {
+                    if !col.nullable && !col.is_primary_key {
+                        line.push_str(" NOT NULL");
+                    }
+                    if col.is_unique && !col.is_primary_key {
+                        line.push_str(" UNIQUE");
+                    }
+                }
+                if let Some(default) = &col.default_expression {
+                    line.push_str(&format!(" DEFAULT {}", default));
+                }
+                if let Some(collation) = &col.collation {
+                    line.push_str(&format!(" COLLATE {}", collation));
+                }
+                line
+            })
+            .collect();
+
+        let column_names = |indexes: &[usize]| {
+            indexes
+                .iter()
+                .map(|&i| quote_identifier(&self.columns[i].name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        if self.primary_key.len() > 1 {
+            lines.push(format!("  PRIMARY KEY ({})", column_names(&self.primary_key)));
+        }
+        for constraint in &self.constraints {
+            lines.push(match constraint {
+                TableConstraintInfo::Unique {
+                    columns,
+                    nulls_not_distinct,
+                } => format!(
+                    "  UNIQUE {}({})",
+                    if *nulls_not_distinct { "NULLS NOT DISTINCT " } else { "" },
+                    column_names(columns)
+                ),
+                TableConstraintInfo::Check { expression } => format!("  CHECK ({})", expression),
+                TableConstraintInfo::ForeignKey {
+                    columns,
+                    foreign_table,
+                    foreign_columns,
+                } => format!(
+                    "  FOREIGN KEY ({}) REFERENCES {}({})",
+                    column_names(columns),
+                    quote_identifier(foreign_table),
+                    foreign_columns.iter().map(|c| quote_identifier(c)).collect::<Vec<_>>().join(", ")
+                ),
+            });
+        }
+
+        format!("CREATE TABLE {} (\n{}\n);", quote_identifier(&self.table_name), lines.join(",\n"))
+    }
+}
+
+/// The keys of a table's UNIQUE and PRIMARY KEY constraints already taken,
+/// used to reject rows an INSERT, UPDATE or MERGE would duplicate
+#[derive(Debug)]
+pub struct UniqueKeys {
+    keys: Vec<UniqueKey>,
+}
+
+#[derive(Debug)]
+struct UniqueKey {
+    columns: Vec<usize>,
+    nulls_not_distinct: bool,
+    taken: HashSet<Vec<OrderedValue>>,
+}
+
+impl UniqueKeys {
+    /// Collect the keys of the table's live rows as a writer with `view`
+    /// finds them
+    ///
+    /// Besides the rows the view sees, that includes the keys other
+    /// transactions are still writing, so two of them can't both claim one.
+    pub fn new(data: &TableData, view: &ReadView) -> PrismDBResult<Self> {
+        let mut keys = Self::for_table(&data.info);
+        if keys.keys.is_empty() {
+            return Ok(keys);
+        }
+
+        for row_id in 0..data.physical_row_count() {
+            if data.deleted_rows.get(row_id).copied().unwrap_or(false) {
+                continue;
+            }
+            let version = data.versions.get(&row_id);
+            if version.and_then(|version| version.deleted).is_some_and(|stamp| view.sees(stamp)) {
+                continue;
+            }
+            keys.add(&data.get_row(row_id)?);
+            for (stamp, values) in version.iter().flat_map(|version| &version.updates) {
+                if !view.sees(*stamp) {
+                    keys.add(values);
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// The table's UNIQUE and PRIMARY KEY constraints with no key taken yet
+    pub fn for_table(table_info: &TableInfo) -> Self {
+        let keys = table_info
+            .unique_keys()
+            .into_iter()
+            .map(|(columns, nulls_not_distinct)| UniqueKey {
+                columns,
+                nulls_not_distinct,
+                taken: HashSet::new(),
+            })
+            .collect();
+        Self { keys }
+    }
+
+    /// Mark a row's keys as taken without checking them
+    pub fn add(&mut self, row: &[Value]) {
+        for key in &mut self.keys {
+            if let Some(values) = key.values(row) {
+                key.taken.insert(values);
+            }
+        }
+    }
+
+    /// Free the keys of a row about to be deleted or given new values
+    pub fn release(&mut self, row: &[Value]) {
+        for key in &mut self.keys {
+            if let Some(values) = key.values(row) {
+                key.taken.remove(&values);
+            }
+        }
+    }
+
+    /// Claim the keys of a row about to be written, failing if another row
+    /// already has one of them
+    pub fn claim(&mut self, row: &[Value], table_info: &TableInfo) -> PrismDBResult<()> {
+        for key in &mut self.keys {
+            let Some(values) = key.values(row) else {
+                continue;
+            };
+            if key.taken.contains(&values) {
+                let names = |f: &dyn Fn(usize) -> String| {
+                    key.columns.iter().map(|&index| f(index)).collect::<Vec<_>>().join(", ")
+                };
+                return Err(PrismDBError::Constraint(format!(
+                    "Duplicate key ({}) = ({}) violates a unique constraint of table '{}'",
+                    names(&|index| table_info.columns[index].name.clone()),
+                    names(&|index| row[index].to_string()),
+                    table_info.table_name
+                )));
+            }
+            key.taken.insert(values);
+        }
+        Ok(())
+    }
+}
+
+impl UniqueKey {
+    /// The row's key, or None when it has a NULL that keeps it distinct from every other
+    fn values(&self, row: &[Value]) -> Option<Vec<OrderedValue>> {
+        let values: Vec<OrderedValue> = self
+            .columns
+            .iter()
+            .map(|&index| OrderedValue(row[index].clone()))
+            .collect();
+        let has_null = values.iter().any(|value| value.0.is_null());
+        (self.nulls_not_distinct || !has_null).then_some(values)
+    }
+}
+
+/// A change written to one row of a table, recorded so it can be undone
+#[derive(Debug, Clone)]
+pub enum RowChange {
+    /// The row was appended
+    Inserted(usize),
+    /// The row was marked deleted
+    Deleted(usize),
+    /// The row's values were replaced; holds the values it had before
+    Updated(usize, Vec<Value>),
+}
+
+impl RowChange {
+    /// The row the change was written to
+    pub fn row_id(&self) -> usize {
+        match self {
+            RowChange::Inserted(row_id) | RowChange::Deleted(row_id) | RowChange::Updated(row_id, _) => {
+                *row_id
+            }
+        }
+    }
+}
+
+/// A row change together with the table it was written to
+pub type TableChange = (Arc<RwLock<TableData>>, RowChange);
+
+/// Who wrote a version of a row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowStamp {
+    /// A transaction, or a statement outside one, that has not committed yet
+    Pending(Uuid),
+    /// A commit, by its sequence number
+    Committed(u64),
+}
+
+/// The rows a statement reads: those committed up to `as_of`, plus the
+/// pending writes of `writer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadView {
+    pub writer: Option<Uuid>,
+    /// Sequence number of the last commit seen
+    pub as_of: u64,
+}
+
+impl ReadView {
+    /// Every committed row and no pending ones
+    pub const COMMITTED: ReadView = ReadView { writer: None, as_of: u64::MAX };
+
+    /// Every committed row plus the pending writes of `writer`, which is what
+    /// its writes apply to
+    pub fn latest(writer: Uuid) -> Self {
+        Self { writer: Some(writer), as_of: u64::MAX }
+    }
+
+    /// Whether a write stamped `stamp` is visible
+    pub fn sees(&self, stamp: RowStamp) -> bool {
+        match stamp {
+            RowStamp::Pending(writer) => self.writer == Some(writer),
+            RowStamp::Committed(sequence) => sequence <= self.as_of,
+        }
+    }
+}
+
+/// Writes to one row that not every reader sees yet
+#[derive(Debug, Clone, Default)]
+struct RowVersion {
+    /// Who appended the row
+    inserted: Option<RowStamp>,
+    /// Who deleted the row; the row stays in place until every reader sees that
+    deleted: Option<RowStamp>,
+    /// Who made each update, oldest first, with the values the row had before it
+    updates: Vec<(RowStamp, Vec<Value>)>,
+}
+
+impl RowVersion {
+    fn stamps(&self) -> impl Iterator<Item = RowStamp> + '_ {
+        self.inserted
+            .iter()
+            .chain(&self.deleted)
+            .copied()
+            .chain(self.updates.iter().map(|(stamp, _)| *stamp))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inserted.is_none() && self.deleted.is_none() && self.updates.is_empty()
+    }
 }
 
 /// Table data storage
@@ -392,6 +820,17 @@ pub struct TableData {
     pub capacity: usize,
     /// Bitmap to track deleted rows (true = deleted, false = active)
     pub deleted_rows: Vec<bool>,
+    /// Number of chunks materialized from this table, for scan diagnostics
+    chunks_created: AtomicUsize,
+    /// Number of rows materialized from this table, for scan diagnostics
+    rows_fetched: AtomicUsize,
+    /// Secondary indexes, holding every value some reader may see for a row
+    indexes: Vec<Arc<ColumnIndex>>,
+    /// Writes that not every reader sees yet, by row; rows without an entry
+    /// look the same to every reader
+    versions: HashMap<usize, RowVersion>,
+    /// Sequence number of the last commit that wrote the table
+    last_commit: u64,
 }
 
 impl TableData {
@@ -415,14 +854,76 @@ impl TableData {
             row_count: 0,
             capacity,
             deleted_rows: Vec::new(),
+            chunks_created: AtomicUsize::new(0),
+            rows_fetched: AtomicUsize::new(0),
+            indexes: Vec::new(),
+            versions: HashMap::new(),
+            last_commit: 0,
         })
     }
 
-    /// Get the number of active (non-deleted) rows in the table
+    /// Sequence number of the last commit that wrote the table
+    pub fn last_commit(&self) -> u64 {
+        self.last_commit
+    }
+
+    /// Get the number of committed rows in the table
     pub fn row_count(&self) -> usize {
+        self.visible_row_count(&ReadView::COMMITTED)
+    }
+
+    /// Number of rows `view` sees
+    pub fn visible_row_count(&self, view: &ReadView) -> usize {
         // Count rows that are not marked as deleted
         let deleted_count = self.deleted_rows.iter().filter(|&&is_deleted| is_deleted).count();
-        self.row_count - deleted_count
+        let hidden = self
+            .versions
+            .keys()
+            .filter(|&&row_id| !self.is_deleted(row_id) && !self.is_visible(view, row_id))
+            .count();
+        self.row_count - deleted_count - hidden
+    }
+
+    /// Whether `view` sees row `row_id`
+    pub fn is_visible(&self, view: &ReadView, row_id: usize) -> bool {
+        if self.is_deleted(row_id) {
+            return false;
+        }
+        match self.versions.get(&row_id) {
+            None => true,
+            Some(version) => {
+                version.inserted.is_none_or(|stamp| view.sees(stamp))
+                    && !version.deleted.is_some_and(|stamp| view.sees(stamp))
+            }
+        }
+    }
+
+    /// The values `view` sees for row `row_id` when they are not the stored ones
+    ///
+    /// That is the values from before the oldest update it doesn't see: an
+    /// update is only made once every earlier one has committed, so a view
+    /// sees some prefix of them.
+    fn values_seen(&self, view: &ReadView, row_id: usize) -> Option<&[Value]> {
+        let version = self.versions.get(&row_id)?;
+        version
+            .updates
+            .iter()
+            .find(|(stamp, _)| !view.sees(*stamp))
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// The rows among physical rows `rows` that `view` sees
+    pub fn visible_rows(&self, view: &ReadView, rows: Range<usize>) -> Vec<usize> {
+        let end = std::cmp::min(rows.end, self.row_count);
+        (rows.start..end).filter(|&row_id| self.is_visible(view, row_id)).collect()
+    }
+
+    /// Get a row as `view` sees it
+    pub fn get_row_in(&self, view: &ReadView, row_id: usize) -> PrismDBResult<Vec<Value>> {
+        match self.values_seen(view, row_id) {
+            Some(values) => Ok(values.to_vec()),
+            None => self.get_row(row_id),
+        }
     }
 
     /// Get the total physical row count (including deleted rows)
@@ -431,6 +932,16 @@ impl TableData {
         self.row_count
     }
 
+    /// Get the number of chunks scans have materialized from this table
+    pub fn chunks_created(&self) -> usize {
+        self.chunks_created.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of rows scans have materialized from this table
+    pub fn rows_fetched(&self) -> usize {
+        self.rows_fetched.load(Ordering::Relaxed)
+    }
+
     /// Get the number of columns in the table
     pub fn column_count(&self) -> usize {
         self.info.columns.len()
@@ -480,6 +991,11 @@ impl TableData {
         // Mark row as not deleted
         self.deleted_rows.push(false);
 
+        for index in &mut self.indexes {
+            let column = index.column();
+            Arc::make_mut(index).insert(&row[column], row_id);
+        }
+
         // Update statistics
         self.info.statistics.update_for_insert(row_id, row);
 
@@ -510,6 +1026,21 @@ impl TableData {
 
     /// Update a row in the table
     pub fn update_row(&mut self, row_id: usize, row: &[Value]) -> PrismDBResult<()> {
+        let old_values = self.write_values(row_id, row)?;
+        for index in &mut self.indexes {
+            let column = index.column();
+            if old_values[column].compare(&row[column]).ok() != Some(std::cmp::Ordering::Equal) {
+                let index = Arc::make_mut(index);
+                index.remove(&old_values[column], row_id);
+                index.insert(&row[column], row_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store new values for a row, returning the ones it had
+    fn write_values(&mut self, row_id: usize, row: &[Value]) -> PrismDBResult<Vec<Value>> {
         if row_id >= self.row_count {
             return Err(PrismDBError::InvalidValue(format!(
                 "Row ID {} out of bounds (max: {})",
@@ -556,7 +1087,12 @@ impl TableData {
         // Update row-level statistics once per row update
         self.info.statistics.updates_since_update += 1;
 
-        Ok(())
+        Ok(old_values)
+    }
+
+    /// Whether a row has been marked deleted
+    pub fn is_deleted(&self, row_id: usize) -> bool {
+        row_id < self.deleted_rows.len() && self.deleted_rows[row_id]
     }
 
     /// Delete a row from the table
@@ -569,10 +1105,19 @@ impl TableData {
         }
 
         // Check if row is already deleted
-        if row_id < self.deleted_rows.len() && self.deleted_rows[row_id] {
+        if self.is_deleted(row_id) {
             return Ok(()); // Already deleted, nothing to do
         }
 
+        for i in 0..self.indexes.len() {
+            let column = self.indexes[i].column();
+            let value = self.columns[column]
+                .read()
+                .map_err(|_| PrismDBError::Internal("Column lock poisoned".to_string()))?
+                .get_value(row_id)?;
+            Arc::make_mut(&mut self.indexes[i]).remove(&value, row_id);
+        }
+
         // Mark row as deleted in the bitmap
         if row_id >= self.deleted_rows.len() {
             self.deleted_rows.resize(row_id + 1, false);
@@ -583,6 +1128,189 @@ impl TableData {
         Ok(())
     }
 
+    /// Append a row as a pending write of `writer`, which only it sees until it commits
+    pub fn insert_row_as(&mut self, writer: Uuid, row: &[Value]) -> PrismDBResult<RowChange> {
+        let row_id = self.insert_row(row)?;
+        self.versions.entry(row_id).or_default().inserted = Some(RowStamp::Pending(writer));
+        Ok(RowChange::Inserted(row_id))
+    }
+
+    /// Replace a row's values as a pending write of `writer`; other readers
+    /// see the old values until it commits
+    pub fn update_row_as(&mut self, writer: Uuid, row_id: usize, row: &[Value]) -> PrismDBResult<RowChange> {
+        self.check_writable(writer, row_id)?;
+        let old_values = self.write_values(row_id, row)?;
+        // The old values stay in the indexes for the readers that still see them
+        for index in &mut self.indexes {
+            let column = index.column();
+            if old_values[column].compare(&row[column]).ok() != Some(std::cmp::Ordering::Equal) {
+                Arc::make_mut(index).insert(&row[column], row_id);
+            }
+        }
+        let version = self.versions.entry(row_id).or_default();
+        version.updates.push((RowStamp::Pending(writer), old_values.clone()));
+        Ok(RowChange::Updated(row_id, old_values))
+    }
+
+    /// Mark a row deleted as a pending write of `writer`; other readers see
+    /// the row until it commits
+    pub fn delete_row_as(&mut self, writer: Uuid, row_id: usize) -> PrismDBResult<RowChange> {
+        if row_id >= self.row_count {
+            return Err(PrismDBError::InvalidValue(format!(
+                "Row ID {} out of bounds (max: {})",
+                row_id, self.row_count
+            )));
+        }
+        self.check_writable(writer, row_id)?;
+        self.versions.entry(row_id).or_default().deleted = Some(RowStamp::Pending(writer));
+        Ok(RowChange::Deleted(row_id))
+    }
+
+    /// Fail if another transaction has a pending write to row `row_id`
+    fn check_writable(&self, writer: Uuid, row_id: usize) -> PrismDBResult<()> {
+        let pending = self.versions.get(&row_id).is_some_and(|version| {
+            version
+                .stamps()
+                .any(|stamp| matches!(stamp, RowStamp::Pending(other) if other != writer))
+        });
+        if pending {
+            return Err(PrismDBError::Transaction(
+                "could not serialize access due to concurrent update".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether a transaction other than `writer` has pending writes to the table
+    pub fn has_pending_writes(&self, writer: Uuid) -> bool {
+        self.versions
+            .values()
+            .flat_map(RowVersion::stamps)
+            .any(|stamp| matches!(stamp, RowStamp::Pending(other) if other != writer))
+    }
+
+    /// Undo a pending change, leaving every other row as it is
+    pub fn undo(&mut self, change: &RowChange) -> PrismDBResult<()> {
+        match change {
+            RowChange::Inserted(row_id) => {
+                let version = self.versions.remove(row_id).unwrap_or_default();
+                let stale: Vec<Vec<Value>> = version.updates.into_iter().map(|(_, values)| values).collect();
+                self.delete_row(*row_id)?;
+                self.reindex_row(*row_id, &stale)
+            }
+            RowChange::Deleted(row_id) => {
+                if let Some(version) = self.versions.get_mut(row_id) {
+                    version.deleted = None;
+                }
+                self.drop_empty_version(*row_id);
+                Ok(())
+            }
+            RowChange::Updated(row_id, old_values) => {
+                let new_values = self.get_row(*row_id)?;
+                if let Some(version) = self.versions.get_mut(row_id) {
+                    version.updates.pop();
+                }
+                self.drop_empty_version(*row_id);
+                self.write_values(*row_id, old_values)?;
+                self.reindex_row(*row_id, &[new_values])
+            }
+        }
+    }
+
+    /// Mark the pending change `writer` made with `change` as committed by
+    /// commit `sequence`
+    pub fn commit(&mut self, writer: Uuid, change: &RowChange, sequence: u64) {
+        let pending = RowStamp::Pending(writer);
+        if let Some(version) = self.versions.get_mut(&change.row_id()) {
+            let stamps = version
+                .inserted
+                .iter_mut()
+                .chain(version.deleted.iter_mut())
+                .chain(version.updates.iter_mut().map(|(stamp, _)| stamp));
+            for stamp in stamps.filter(|stamp| **stamp == pending) {
+                *stamp = RowStamp::Committed(sequence);
+            }
+        }
+        self.last_commit = sequence;
+    }
+
+    /// Apply the writes committed up to `horizon`, which every reader now
+    /// sees, dropping the row versions no reader needs any more
+    pub fn settle(&mut self, horizon: u64) -> PrismDBResult<()> {
+        let settled = |stamp: &RowStamp| matches!(stamp, RowStamp::Committed(sequence) if *sequence <= horizon);
+        let row_ids: Vec<usize> = self.versions.keys().copied().collect();
+        for row_id in row_ids {
+            let Some(version) = self.versions.get_mut(&row_id) else {
+                continue;
+            };
+            if version.inserted.as_ref().is_some_and(settled) {
+                version.inserted = None;
+            }
+            let seen_by_all = version
+                .updates
+                .iter()
+                .position(|(stamp, _)| !settled(stamp))
+                .unwrap_or(version.updates.len());
+            let stale: Vec<Vec<Value>> =
+                version.updates.drain(..seen_by_all).map(|(_, values)| values).collect();
+            let deleted = version.deleted.as_ref().is_some_and(settled);
+            if deleted {
+                version.deleted = None;
+            }
+            self.drop_empty_version(row_id);
+
+            if deleted {
+                self.delete_row(row_id)?;
+            }
+            if deleted || !stale.is_empty() {
+                self.reindex_row(row_id, &stale)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether some write to the table is not yet seen by every reader
+    pub fn has_versions(&self) -> bool {
+        !self.versions.is_empty()
+    }
+
+    fn drop_empty_version(&mut self, row_id: usize) {
+        if self.versions.get(&row_id).is_some_and(RowVersion::is_empty) {
+            self.versions.remove(&row_id);
+        }
+    }
+
+    /// Make the indexes hold exactly the values some reader may see for row
+    /// `row_id`, once the values in `stale` are no longer among them
+    fn reindex_row(&mut self, row_id: usize, stale: &[Vec<Value>]) -> PrismDBResult<()> {
+        if self.indexes.is_empty() {
+            return Ok(());
+        }
+        let mut live = Vec::new();
+        if !self.is_deleted(row_id) {
+            live.push(self.get_row(row_id)?);
+            if let Some(version) = self.versions.get(&row_id) {
+                live.extend(version.updates.iter().map(|(_, values)| values.clone()));
+            }
+        }
+        for index in &mut self.indexes {
+            let column = index.column();
+            let index = Arc::make_mut(index);
+            for values in stale.iter().chain(&live) {
+                index.remove(&values[column], row_id);
+            }
+            let mut indexed: Vec<&Value> = Vec::new();
+            for values in &live {
+                let value = &values[column];
+                if !indexed.iter().any(|other| other.compare(value).ok() == Some(std::cmp::Ordering::Equal)) {
+                    index.insert(value, row_id);
+                    indexed.push(value);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Create a data chunk from the table data including all rows (even deleted ones)
     /// This is used by UPDATE and DELETE operations that need to see all physical rows
     pub fn create_chunk_unfiltered(&self, start_row: usize, max_rows: usize) -> PrismDBResult<DataChunk> {
@@ -592,6 +1320,8 @@ impl TableData {
         if actual_rows == 0 {
             return Ok(DataChunk::new());
         }
+        self.chunks_created.fetch_add(1, Ordering::Relaxed);
+        self.rows_fetched.fetch_add(actual_rows, Ordering::Relaxed);
 
         let mut vectors = Vec::with_capacity(self.columns.len());
 
@@ -607,50 +1337,91 @@ impl TableData {
         DataChunk::from_vectors(vectors)
     }
 
-    /// Create a data chunk from the table data
+    /// Create a data chunk of up to `max_rows` committed rows from physical row `start_row` on
     pub fn create_chunk(&self, start_row: usize, max_rows: usize) -> PrismDBResult<DataChunk> {
-        // When filtering deleted rows, we need to scan beyond max_rows to find enough active rows
-        // So we scan all physical rows starting from start_row
-        let end_row = self.row_count;  // Scan all physical rows
-
-        // Collect row indices that are not deleted
-        let mut active_rows = Vec::new();
-        for row_id in start_row..end_row {
-            // Check if row is deleted (if deleted_rows is smaller than row_id, the row is not deleted)
-            let is_deleted = row_id < self.deleted_rows.len() && self.deleted_rows[row_id];
-            if !is_deleted {
-                active_rows.push(row_id);
-                // Stop once we have collected max_rows active rows
-                if active_rows.len() >= max_rows {
-                    break;
-                }
+        let view = ReadView::COMMITTED;
+        // Scan past deleted rows until enough visible ones are collected
+        let active_rows: Vec<usize> = (start_row..self.row_count)
+            .filter(|&row_id| self.is_visible(&view, row_id))
+            .take(max_rows)
+            .collect();
+        self.chunk_from_rows(&view, &active_rows)
+    }
+
+    /// Create a data chunk from the rows `view` sees among physical rows `start..end`
+    pub fn create_chunk_range(&self, view: &ReadView, start: usize, end: usize) -> PrismDBResult<DataChunk> {
+        let active_rows = self.visible_rows(view, start..end);
+        self.chunk_from_rows(view, &active_rows)
+    }
+
+    /// Create a data chunk from the given physical rows as `view` sees them
+    pub fn create_chunk_of_rows(&self, view: &ReadView, rows: &[usize]) -> PrismDBResult<DataChunk> {
+        self.chunk_from_rows(view, rows)
+    }
+
+    /// Physical row ranges that may hold rows inside every column range
+    ///
+    /// A zone is skipped when any column's zone map rules it out; adjacent zones that
+    /// remain are merged into one range.
+    pub fn candidate_row_ranges(
+        &self,
+        ranges: &[(usize, ValueRange)],
+    ) -> PrismDBResult<Vec<Range<usize>>> {
+        let mut candidates = vec![true; self.row_count.div_ceil(ZONE_SIZE)];
+        for (column_index, range) in ranges {
+            let Some(column_data) = self.columns.get(*column_index) else {
+                continue;
+            };
+            let column = column_data
+                .read()
+                .map_err(|_| PrismDBError::Internal("Column lock poisoned".to_string()))?;
+            let zones = column.zone_maps();
+            for (zone, candidate) in candidates.iter_mut().enumerate() {
+                *candidate = *candidate && zones.get(zone).is_none_or(|z| z.may_contain(range));
             }
         }
 
-        if active_rows.is_empty() {
+        let mut row_ranges: Vec<Range<usize>> = Vec::new();
+        for (zone, candidate) in candidates.into_iter().enumerate() {
+            if !candidate {
+                continue;
+            }
+            let start = zone * ZONE_SIZE;
+            let end = std::cmp::min(start + ZONE_SIZE, self.row_count);
+            match row_ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => row_ranges.push(start..end),
+            }
+        }
+        Ok(row_ranges)
+    }
+
+    /// Materialize the given physical rows into a chunk, with the values `view` sees
+    fn chunk_from_rows(&self, view: &ReadView, rows: &[usize]) -> PrismDBResult<DataChunk> {
+        if rows.is_empty() {
             return Ok(DataChunk::new());
         }
+        self.chunks_created.fetch_add(1, Ordering::Relaxed);
+        self.rows_fetched.fetch_add(rows.len(), Ordering::Relaxed);
 
-        // Create vectors with only active (non-deleted) rows
         let mut vectors = Vec::with_capacity(self.columns.len());
 
-        for column_data in &self.columns {
+        for (column_index, column_data) in self.columns.iter().enumerate() {
             let column = column_data
                 .read()
                 .map_err(|_| PrismDBError::Internal("Column lock poisoned".to_string()))?;
 
-            // Collect values from active rows only
-            let mut column_values = Vec::with_capacity(active_rows.len());
-            for &row_id in &active_rows {
-                let value = column.get_value(row_id)?;
-                column_values.push(value);
-            }
-
-            // Create vector from the collected values
-            if !column_values.is_empty() {
-                let vector = crate::types::Vector::from_values(&column_values)?;
-                vectors.push(vector);
+            let mut column_values = Vec::with_capacity(rows.len());
+            for &row_id in rows {
+                match self.values_seen(view, row_id) {
+                    Some(values) => column_values.push(values[column_index].clone()),
+                    None => column_values.push(column.get_value(row_id)?),
+                }
             }
+            vectors.push(crate::types::Vector::from_values_with_type(
+                &column_values,
+                column.get_type(),
+            )?);
         }
 
         if vectors.is_empty() {
@@ -665,6 +1436,13 @@ impl TableData {
         &self.info.statistics
     }
 
+    /// Record the distinct counts ANALYZE estimated, in column order
+    pub fn set_distinct_counts(&mut self, counts: &[usize]) {
+        for (column_stats, &count) in self.info.statistics.column_stats.iter_mut().zip(counts) {
+            column_stats.distinct_count = Some(count);
+        }
+    }
+
     /// Update table statistics
     pub fn update_statistics(&mut self) {
         if !self.info.statistics.stats_up_to_date {
@@ -675,7 +1453,7 @@ impl TableData {
             // Estimate size based on column data
             let mut total_size = 0;
             for column_data in &self.columns {
-                let column = column_data.read().unwrap(); // Safe unwrap for statistics calculation
+                let column = column_data.read_recovered();
                 total_size += column.estimate_size();
             }
             self.info.statistics.estimated_size = total_size;
@@ -684,6 +1462,15 @@ impl TableData {
         }
     }
 
+    /// Make room for `additional` more rows, at least doubling the capacity when it grows
+    pub fn reserve(&mut self, additional: usize) -> PrismDBResult<()> {
+        let needed = self.row_count + additional;
+        if needed <= self.capacity {
+            return Ok(());
+        }
+        self.resize(needed.max(self.capacity * 2))
+    }
+
     /// Resize the table capacity
     pub fn resize(&mut self, new_capacity: usize) -> PrismDBResult<()> {
         if new_capacity < self.row_count {
@@ -722,6 +1509,11 @@ impl TableData {
         }
 
         self.row_count = 0;
+        self.deleted_rows.clear();
+        self.versions.clear();
+        for index in &mut self.indexes {
+            Arc::make_mut(index).clear();
+        }
 
         // Update statistics
         self.info.statistics.row_count = 0;
@@ -730,6 +1522,99 @@ impl TableData {
         Ok(())
     }
 
+    /// Rewrite the columns without their deleted rows, returning how many were removed
+    ///
+    /// Live rows keep their order but are renumbered, so the indexes are
+    /// rebuilt and the statistics recomputed from what remains. Nothing is
+    /// removed while some reader may still see an older version of a row,
+    /// since the row versions are keyed by row number.
+    pub fn vacuum(&mut self) -> PrismDBResult<usize> {
+        if self.has_versions() {
+            return Ok(0);
+        }
+        let removed = self.deleted_rows.iter().filter(|&&is_deleted| is_deleted).count();
+        if removed == 0 {
+            return Ok(0);
+        }
+        let keep: Vec<bool> = (0..self.row_count)
+            .map(|row_id| !self.deleted_rows.get(row_id).copied().unwrap_or(false))
+            .collect();
+        for column_data in &self.columns {
+            column_data
+                .write()
+                .map_err(|_| PrismDBError::Internal("Column lock poisoned".to_string()))?
+                .retain(&keep);
+        }
+        self.row_count -= removed;
+        self.deleted_rows = vec![false; self.row_count];
+
+        let mut statistics = TableStatistics::new(self.columns.len());
+        let indexes = std::mem::take(&mut self.indexes);
+        for row_id in 0..self.row_count {
+            let row = self.get_row(row_id)?;
+            statistics.update_for_insert(row_id, &row);
+        }
+        statistics.mark_clean();
+        // Distinct counts from ANALYZE stay as good an estimate as they were
+        for (new, old) in statistics.column_stats.iter_mut().zip(&self.info.statistics.column_stats) {
+            new.distinct_count = old.distinct_count;
+        }
+        self.info.statistics = statistics;
+        for index in indexes {
+            self.create_index(index.name(), index.column())?;
+        }
+        Ok(removed)
+    }
+
+    /// Build an index named `name` on the column at `column` from the live rows
+    pub fn create_index(&mut self, name: &str, column: usize) -> PrismDBResult<()> {
+        if self.index(name).is_some() {
+            return Err(PrismDBError::InvalidValue(format!(
+                "Index '{}' already exists on table '{}'",
+                name, self.info.name
+            )));
+        }
+        let column_data = self
+            .columns
+            .get(column)
+            .ok_or_else(|| PrismDBError::InvalidValue(format!("Column {} not found", column)))?
+            .read()
+            .map_err(|_| PrismDBError::Internal("Column lock poisoned".to_string()))?;
+
+        let mut index = ColumnIndex::new(name.to_string(), column);
+        for row_id in 0..self.row_count {
+            if !self.deleted_rows.get(row_id).copied().unwrap_or(false) {
+                index.insert(&column_data.get_value(row_id)?, row_id);
+            }
+        }
+        // Old values some reader still sees are looked up too
+        for (&row_id, version) in &self.versions {
+            for (_, values) in &version.updates {
+                index.insert(&values[column], row_id);
+            }
+        }
+        drop(column_data);
+        self.indexes.push(Arc::new(index));
+        Ok(())
+    }
+
+    /// Drop the index named `name`, returning whether the table had it
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        let count = self.indexes.len();
+        self.indexes.retain(|index| index.name() != name);
+        self.indexes.len() != count
+    }
+
+    /// The table's index named `name`
+    pub fn index(&self, name: &str) -> Option<&ColumnIndex> {
+        self.indexes.iter().find(|index| index.name() == name).map(|index| index.as_ref())
+    }
+
+    /// An index on the column at `column`
+    pub fn index_on(&self, column: usize) -> Option<&ColumnIndex> {
+        self.indexes.iter().find(|index| index.column() == column).map(|index| index.as_ref())
+    }
+
     /// Insert values into the table (wrapper for insert_row)
     pub fn insert(&mut self, values: &[Value]) -> PrismDBResult<()> {
         self.insert_row(values)?;
@@ -752,6 +1637,11 @@ impl TableData {
         // Create new column data
         let column_data = ColumnData::new(column_info.clone(), self.capacity)?;
         self.columns.push(Arc::new(RwLock::new(column_data)));
+        for version in self.versions.values_mut() {
+            for (_, values) in &mut version.updates {
+                values.push(Value::Null);
+            }
+        }
 
         // Add column statistics
         self.info
@@ -766,17 +1656,32 @@ impl TableData {
     /// Remove a column from the table
     pub fn remove_column(&mut self, column_name: &str) -> PrismDBResult<()> {
         let column_index = self.info.get_column_index(column_name).ok_or_else(|| {
-            PrismDBError::InvalidValue(format!(
+            PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column '{}' not found in table '{}'",
                 column_name, self.info.name
             ))
         })?;
+        if let Some(index) = self.index_on(column_index) {
+            return Err(PrismDBError::InvalidValue(format!(
+                "Cannot drop column '{}': index '{}' depends on it",
+                column_name,
+                index.name()
+            )));
+        }
 
         // Remove from info
         self.info.columns.remove(column_index);
+        for index in &mut self.indexes {
+            Arc::make_mut(index).column_removed(column_index);
+        }
 
         // Remove column data
         self.columns.remove(column_index);
+        for version in self.versions.values_mut() {
+            for (_, values) in &mut version.updates {
+                values.remove(column_index);
+            }
+        }
 
         // Remove column statistics
         if column_index < self.info.statistics.column_stats.len() {
@@ -790,7 +1695,7 @@ impl TableData {
     /// Rename a column in the table
     pub fn rename_column(&mut self, old_name: &str, new_name: &str) -> PrismDBResult<()> {
         let column_index = self.info.get_column_index(old_name).ok_or_else(|| {
-            PrismDBError::InvalidValue(format!(
+            PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column '{}' not found in table '{}'",
                 old_name, self.info.name
             ))
@@ -821,7 +1726,7 @@ impl TableData {
     /// Get column data by name
     pub fn get_column_data(&self, column_name: &str) -> PrismDBResult<Arc<ColumnData>> {
         let column_index = self.info.get_column_index(column_name).ok_or_else(|| {
-            PrismDBError::InvalidValue(format!(
+            PrismDBError::NotFound(ObjectKind::Column, format!(
                 "Column '{}' not found in table '{}'",
                 column_name, self.info.name
             ))
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 use crate::common::error::{PrismDBError, Result};
-use crate::storage::table::{RowId, TableData};
+use crate::common::sync::{MutexExt, RwLockExt};
+use crate::storage::table::{ReadView, RowId, TableChange, TableData};
+use crate::storage::wal::WalManager;
 
 /// Transaction isolation levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,6 +25,28 @@ pub enum TransactionState {
     Aborted,
 }
 
+impl IsolationLevel {
+    /// The level as SQL spells it
+    pub fn name(&self) -> &'static str {
+        match self {
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+impl TransactionState {
+    /// Lowercase name shown by prism_transactions()
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransactionState::Active => "active",
+            TransactionState::Committed => "committed",
+            TransactionState::Aborted => "aborted",
+        }
+    }
+}
+
 /// Transaction operation types for logging
 #[derive(Debug, Clone)]
 pub enum TransactionOperation {
@@ -37,6 +62,8 @@ pub struct TransactionMetadata {
     pub start_time: u64,
     pub state: TransactionState,
     pub isolation_level: IsolationLevel,
+    /// Read-only transactions reject writes and are never logged to the WAL
+    pub read_only: bool,
     pub operations: Vec<TransactionOperation>,
 }
 
@@ -50,6 +77,7 @@ impl TransactionMetadata {
                 .as_secs(),
             state: TransactionState::Active,
             isolation_level,
+            read_only: false,
             operations: Vec::new(),
         }
     }
@@ -67,6 +95,17 @@ impl TransactionMetadata {
     }
 }
 
+/// What a transaction the manager tracks looks like from outside
+#[derive(Debug, Clone)]
+pub struct TransactionStatus {
+    pub id: Uuid,
+    pub state: TransactionState,
+    /// Seconds since the Unix epoch
+    pub start_time: u64,
+    pub isolation_level: IsolationLevel,
+    pub read_only: bool,
+}
+
 /// Snapshot of data for transaction isolation
 #[derive(Debug)]
 pub struct DataSnapshot {
@@ -94,13 +133,30 @@ impl DataSnapshot {
     }
 }
 
+/// Key identifying a live table in a transaction's reads
+fn table_key(table: &Arc<RwLock<TableData>>) -> usize {
+    Arc::as_ptr(table) as usize
+}
+
+fn serialization_failure() -> PrismDBError {
+    PrismDBError::Transaction(
+        "could not serialize access due to concurrent update".to_string(),
+    )
+}
+
 /// Transaction context for managing transaction state
 #[derive(Debug)]
 pub struct TransactionContext {
     pub metadata: TransactionMetadata,
     pub snapshot: Option<DataSnapshot>,
-    pub modified_tables: HashMap<String, Arc<RwLock<TableData>>>,
-    pub rollback_data: HashMap<String, Vec<Vec<crate::types::Value>>>,
+    /// Sequence number of the last commit a REPEATABLE READ or SERIALIZABLE
+    /// transaction sees, fixed when it begins
+    pub as_of: u64,
+    /// Tables a SERIALIZABLE transaction has read, checked again when it commits
+    pub tables_read: HashMap<usize, Arc<RwLock<TableData>>>,
+    /// Row changes the transaction has written, in order, undone if it aborts
+    /// and stamped with its commit otherwise
+    pub undo_log: Vec<TableChange>,
 }
 
 impl TransactionContext {
@@ -108,21 +164,18 @@ impl TransactionContext {
         Self {
             metadata: TransactionMetadata::new(isolation_level),
             snapshot: None,
-            modified_tables: HashMap::new(),
-            rollback_data: HashMap::new(),
+            as_of: 0,
+            tables_read: HashMap::new(),
+            undo_log: Vec::new(),
         }
     }
 
-    pub fn register_table(&mut self, table_id: String, table: Arc<RwLock<TableData>>) {
-        self.modified_tables.insert(table_id, table);
-    }
-
-    pub fn save_rollback_data(&mut self, table_id: &str, data: Vec<Vec<crate::types::Value>>) {
-        self.rollback_data.insert(table_id.to_string(), data);
-    }
-
-    pub fn get_rollback_data(&self, table_id: &str) -> Option<&Vec<Vec<crate::types::Value>>> {
-        self.rollback_data.get(table_id)
+    /// Whether reads come from a snapshot rather than the latest committed data
+    pub fn uses_snapshot(&self) -> bool {
+        matches!(
+            self.metadata.isolation_level,
+            IsolationLevel::RepeatableRead | IsolationLevel::Serializable
+        )
     }
 }
 
@@ -131,6 +184,16 @@ impl TransactionContext {
 pub struct TransactionManager {
     active_transactions: Arc<RwLock<HashMap<Uuid, Arc<RwLock<TransactionContext>>>>>,
     global_lock: Arc<RwLock<()>>,
+    /// Log that read-write transactions record their begin, commit and abort in
+    wal: Option<Arc<WalManager>>,
+    /// Sequence number of the last commit that wrote rows
+    last_commit: AtomicU64,
+    /// Held while a commit stamps its rows, so commits are numbered in order
+    commit_lock: Mutex<()>,
+    /// The commit each open REPEATABLE READ or SERIALIZABLE transaction reads as of
+    snapshots: Mutex<HashMap<Uuid, u64>>,
+    /// Tables with committed writes some reader may not see yet
+    unsettled: Mutex<Vec<Arc<RwLock<TableData>>>>,
 }
 
 impl TransactionManager {
@@ -138,27 +201,51 @@ impl TransactionManager {
         Self {
             active_transactions: Arc::new(RwLock::new(HashMap::new())),
             global_lock: Arc::new(RwLock::new(())),
+            wal: None,
+            last_commit: AtomicU64::new(0),
+            commit_lock: Mutex::new(()),
+            snapshots: Mutex::new(HashMap::new()),
+            unsettled: Mutex::new(Vec::new()),
         }
     }
 
+    /// Log transaction boundaries to `wal`
+    pub fn with_wal(mut self, wal: Arc<WalManager>) -> Self {
+        self.wal = Some(wal);
+        self
+    }
+
     /// Begin a new transaction
     pub fn begin_transaction(&self, isolation_level: IsolationLevel) -> Result<Uuid> {
-        let context = TransactionContext::new(isolation_level);
+        self.begin_transaction_with_mode(isolation_level, false)
+    }
+
+    /// Begin a new transaction, optionally read-only
+    pub fn begin_transaction_with_mode(
+        &self,
+        isolation_level: IsolationLevel,
+        read_only: bool,
+    ) -> Result<Uuid> {
+        let mut context = TransactionContext::new(isolation_level);
+        context.metadata.read_only = read_only;
         let transaction_id = context.metadata.id;
 
-        // Create snapshot for repeatable read and serializable isolation
-        if matches!(
-            isolation_level,
-            IsolationLevel::RepeatableRead | IsolationLevel::Serializable
-        ) {
-            // In a real implementation, this would capture the current state of all tables
-            // For now, we'll create an empty snapshot
+        if let Some(wal) = self.wal_for(&context) {
+            wal.log_begin_transaction(transaction_id, &format!("{:?}", isolation_level))?;
+        }
+
+        // The commit is read under the lock settling reads the oldest snapshot
+        // under, so no write the transaction can't see is applied meanwhile
+        if context.uses_snapshot() {
+            let mut snapshots = self.snapshots.lock_or_err()?;
+            context.as_of = self.last_commit.load(Ordering::SeqCst);
+            snapshots.insert(transaction_id, context.as_of);
         }
 
         let context_arc = Arc::new(RwLock::new(context));
 
         {
-            let mut transactions = self.active_transactions.write().unwrap();
+            let mut transactions = self.active_transactions.write_or_err()?;
             transactions.insert(transaction_id, context_arc);
         }
 
@@ -168,28 +255,52 @@ impl TransactionManager {
     /// Commit a transaction
     pub fn commit_transaction(&self, transaction_id: Uuid) -> Result<()> {
         let context_arc = {
-            let transactions = self.active_transactions.read().unwrap();
+            let transactions = self.active_transactions.read_or_err()?;
             transactions.get(&transaction_id).cloned()
         };
 
         if let Some(context_arc) = context_arc {
-            let mut context = context_arc.write().unwrap();
+            let mut context = context_arc.write_or_err()?;
+            let commit = self.commit_lock.lock_or_err()?;
+
+            // A SERIALIZABLE transaction that wrote must not commit if a table it
+            // read has changed since it began; the result would match no serial
+            // order. Aborting undoes its writes.
+            if context.metadata.isolation_level == IsolationLevel::Serializable
+                && !context.undo_log.is_empty()
+                && context
+                    .tables_read
+                    .values()
+                    .any(|table| table.read_recovered().last_commit() > context.as_of)
+            {
+                drop(commit);
+                drop(context);
+                self.abort_transaction(transaction_id)?;
+                return Err(serialization_failure());
+            }
+
+            if let Some(wal) = self.wal_for(&context) {
+                wal.log_commit_transaction(transaction_id)?;
+            }
+
+            // Stamp the transaction's rows with its commit, which makes them
+            // visible to the statements that start from now on
+            let changes = std::mem::take(&mut context.undo_log);
+            self.stamp(transaction_id, &changes)?;
+            drop(commit);
 
             // Mark as committed
             context.metadata.commit();
-
-            // In a real implementation, this would:
-            // 1. Write to WAL
-            // 2. Apply changes to tables
-            // 3. Release locks
+            drop(context);
 
             // Remove from active transactions
             {
-                let mut transactions = self.active_transactions.write().unwrap();
+                let mut transactions = self.active_transactions.write_or_err()?;
                 transactions.remove(&transaction_id);
             }
+            self.snapshots.lock_or_err()?.remove(&transaction_id);
 
-            Ok(())
+            self.settle(&changes)
         } else {
             Err(PrismDBError::Transaction(format!(
                 "Transaction {} not found",
@@ -201,36 +312,35 @@ impl TransactionManager {
     /// Abort a transaction
     pub fn abort_transaction(&self, transaction_id: Uuid) -> Result<()> {
         let context_arc = {
-            let transactions = self.active_transactions.read().unwrap();
+            let transactions = self.active_transactions.read_or_err()?;
             transactions.get(&transaction_id).cloned()
         };
 
         if let Some(context_arc) = context_arc {
-            let mut context = context_arc.write().unwrap();
-
-            // Rollback changes
-            for (table_id, rollback_data) in &context.rollback_data {
-                if let Some(table_arc) = context.modified_tables.get(table_id) {
-                    let mut table = table_arc.write().unwrap();
-                    // Restore original data
-                    // In a real implementation, this would restore the exact state
-                    let _ = table.clear_rows();
-                    for _row in rollback_data {
-                        // This is simplified - real implementation would handle column-wise restoration
-                    }
-                }
+            let mut context = context_arc.write_or_err()?;
+
+            // Undo the transaction's own changes, latest first; rows other
+            // transactions wrote meanwhile stay as they are
+            for (table, change) in context.undo_log.drain(..).rev() {
+                table.write_or_err()?.undo(&change)?;
+            }
+
+            if let Some(wal) = self.wal_for(&context) {
+                wal.log_abort_transaction(transaction_id)?;
             }
 
             // Mark as aborted
             context.metadata.abort();
+            drop(context);
 
             // Remove from active transactions
             {
-                let mut transactions = self.active_transactions.write().unwrap();
+                let mut transactions = self.active_transactions.write_or_err()?;
                 transactions.remove(&transaction_id);
             }
+            self.snapshots.lock_or_err()?.remove(&transaction_id);
 
-            Ok(())
+            self.settle(&[])
         } else {
             Err(PrismDBError::Transaction(format!(
                 "Transaction {} not found",
@@ -239,24 +349,190 @@ impl TransactionManager {
         }
     }
 
+    /// Commit the row changes a statement outside a transaction wrote as `writer`
+    pub fn commit_changes(&self, writer: Uuid, changes: Vec<TableChange>) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        {
+            let _commit = self.commit_lock.lock_or_err()?;
+            self.stamp(writer, &changes)?;
+        }
+        self.settle(&changes)
+    }
+
+    /// Stamp `changes`, written by `writer`, with the next commit sequence number
+    ///
+    /// Called with the commit lock held.
+    fn stamp(&self, writer: Uuid, changes: &[TableChange]) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let sequence = self.last_commit.load(Ordering::SeqCst) + 1;
+        for group in changes.chunk_by(|(a, _), (b, _)| Arc::ptr_eq(a, b)) {
+            let mut table = group[0].0.write_or_err()?;
+            for (_, change) in group {
+                table.commit(writer, change, sequence);
+            }
+        }
+        self.last_commit.store(sequence, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Apply the committed writes every open snapshot sees, in the tables
+    /// `changes` wrote and in those earlier settles left unsettled
+    fn settle(&self, changes: &[TableChange]) -> Result<()> {
+        let mut tables = std::mem::take(&mut *self.unsettled.lock_or_err()?);
+        for (table, _) in changes {
+            if !tables.iter().any(|other| Arc::ptr_eq(other, table)) {
+                tables.push(table.clone());
+            }
+        }
+        if tables.is_empty() {
+            return Ok(());
+        }
+
+        let horizon = {
+            let snapshots = self.snapshots.lock_or_err()?;
+            let last_commit = self.last_commit.load(Ordering::SeqCst);
+            snapshots.values().copied().fold(last_commit, u64::min)
+        };
+        let mut unsettled = Vec::new();
+        for table in tables {
+            let mut data = table.write_or_err()?;
+            data.settle(horizon)?;
+            if data.has_versions() {
+                drop(data);
+                unsettled.push(table);
+            }
+        }
+        self.unsettled.lock_or_err()?.extend(unsettled);
+        Ok(())
+    }
+
+    /// Apply every committed write that all open transactions see, so VACUUM
+    /// can reclaim the rows they deleted
+    pub fn settle_tables(&self) -> Result<()> {
+        self.settle(&[])
+    }
+
     /// Get transaction context
     pub fn get_transaction(&self, transaction_id: Uuid) -> Option<Arc<RwLock<TransactionContext>>> {
-        let transactions = self.active_transactions.read().unwrap();
+        let transactions = self.active_transactions.read_recovered();
         transactions.get(&transaction_id).cloned()
     }
 
+    /// The rows a statement run by `writer` reads
+    ///
+    /// REPEATABLE READ and SERIALIZABLE transactions read as of the commit
+    /// they began after; anything else reads as of the last commit, so every
+    /// statement sees the latest committed rows. Each sees its own writes.
+    pub fn read_view(&self, writer: Uuid) -> ReadView {
+        let snapshot = self.get_transaction(writer).and_then(|context| {
+            let context = context.read_recovered();
+            context.uses_snapshot().then_some(context.as_of)
+        });
+        ReadView {
+            writer: Some(writer),
+            as_of: snapshot.unwrap_or_else(|| self.last_commit.load(Ordering::SeqCst)),
+        }
+    }
+
+    /// Record that a transaction read `table`
+    ///
+    /// SERIALIZABLE transactions check at commit that no table they read has
+    /// changed since they began.
+    pub fn record_read(&self, transaction_id: Uuid, table: &Arc<RwLock<TableData>>) -> Result<()> {
+        let Some(context_arc) = self.get_transaction(transaction_id) else {
+            return Ok(());
+        };
+        let mut context = context_arc.write_or_err()?;
+        if context.metadata.isolation_level == IsolationLevel::Serializable {
+            context.tables_read.insert(table_key(table), table.clone());
+        }
+        Ok(())
+    }
+
+    /// Check that a transaction may write `table`
+    ///
+    /// Under REPEATABLE READ and SERIALIZABLE, writing a table that another
+    /// transaction committed to since this one began is a serialization
+    /// failure, as is writing a table another transaction is still writing
+    /// under SERIALIZABLE: the transaction is aborted and an error returned.
+    pub fn check_write(&self, transaction_id: Uuid, table: &Arc<RwLock<TableData>>) -> Result<()> {
+        let Some(context_arc) = self.get_transaction(transaction_id) else {
+            return Ok(());
+        };
+        let conflict = {
+            let context = context_arc.read_or_err()?;
+            let data = table.read_or_err()?;
+            let serializable = context.metadata.isolation_level == IsolationLevel::Serializable;
+            (context.uses_snapshot() && data.last_commit() > context.as_of)
+                || (serializable && data.has_pending_writes(transaction_id))
+        };
+        if conflict {
+            self.abort_transaction(transaction_id)?;
+            return Err(serialization_failure());
+        }
+        Ok(())
+    }
+
+    /// Add row changes a transaction wrote to its undo log, for ROLLBACK
+    pub fn record_changes(
+        &self,
+        transaction_id: Uuid,
+        changes: impl IntoIterator<Item = TableChange>,
+    ) -> Result<()> {
+        let Some(context_arc) = self.get_transaction(transaction_id) else {
+            return Ok(());
+        };
+        context_arc.write_or_err()?.undo_log.extend(changes);
+        Ok(())
+    }
+
+    /// Check if a transaction is active and read-only
+    pub fn is_read_only(&self, transaction_id: Uuid) -> bool {
+        self.get_transaction(transaction_id)
+            .is_some_and(|context| context.read_recovered().metadata.read_only)
+    }
+
+    /// The WAL a transaction logs to; read-only transactions have nothing to log
+    fn wal_for(&self, context: &TransactionContext) -> Option<&Arc<WalManager>> {
+        self.wal.as_ref().filter(|_| !context.metadata.read_only)
+    }
+
     /// Check if transaction is active
     pub fn is_transaction_active(&self, transaction_id: Uuid) -> bool {
-        let transactions = self.active_transactions.read().unwrap();
+        let transactions = self.active_transactions.read_recovered();
         transactions.contains_key(&transaction_id)
     }
 
     /// Get all active transaction IDs
     pub fn get_active_transactions(&self) -> Vec<Uuid> {
-        let transactions = self.active_transactions.read().unwrap();
+        let transactions = self.active_transactions.read_recovered();
         transactions.keys().cloned().collect()
     }
 
+    /// Status of every active transaction, oldest first
+    pub fn transaction_statuses(&self) -> Vec<TransactionStatus> {
+        let transactions = self.active_transactions.read_recovered();
+        let mut statuses: Vec<TransactionStatus> = transactions
+            .values()
+            .map(|context| {
+                let metadata = &context.read_recovered().metadata;
+                TransactionStatus {
+                    id: metadata.id,
+                    state: metadata.state,
+                    start_time: metadata.start_time,
+                    isolation_level: metadata.isolation_level,
+                    read_only: metadata.read_only,
+                }
+            })
+            .collect();
+        statuses.sort_by_key(|status| (status.start_time, status.id));
+        statuses
+    }
+
     /// Acquire global lock (for serializable isolation)
     pub fn acquire_global_lock<'a>(&'a self) -> Result<std::sync::RwLockReadGuard<'a, ()>> {
         self.global_lock
@@ -313,7 +589,7 @@ impl Transaction {
     }
 
     pub fn add_operation(&self, operation: TransactionOperation) -> Result<()> {
-        let mut context = self.context.write().unwrap();
+        let mut context = self.context.write_or_err()?;
         context.metadata.add_operation(operation);
         Ok(())
     }
@@ -377,6 +653,68 @@ mod tests {
         assert!(!manager.is_transaction_active(tx_id));
     }
 
+    #[test]
+    fn test_read_only_transaction_skips_wal() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let wal = Arc::new(WalManager::new(temp_dir.path()).unwrap());
+        let manager = TransactionManager::new().with_wal(wal.clone());
+
+        let read_only = manager
+            .begin_transaction_with_mode(IsolationLevel::ReadCommitted, true)
+            .unwrap();
+        assert!(manager.is_read_only(read_only));
+        manager.commit_transaction(read_only).unwrap();
+        assert!(!manager.is_read_only(read_only));
+        assert!(wal.replay().unwrap().is_empty());
+
+        let read_write = manager
+            .begin_transaction(IsolationLevel::ReadCommitted)
+            .unwrap();
+        assert!(!manager.is_read_only(read_write));
+        manager.commit_transaction(read_write).unwrap();
+        let records = wal.replay().unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.transaction_id == Some(read_write)));
+    }
+
+    #[test]
+    fn test_repeatable_read_snapshot_and_conflict() {
+        use crate::storage::table::{ColumnInfo, TableInfo};
+        use crate::types::{LogicalType, Value};
+
+        let mut info = TableInfo::new("t".to_string());
+        info.add_column(ColumnInfo::new("x".to_string(), LogicalType::Integer, 0))
+            .unwrap();
+        let table = Arc::new(RwLock::new(TableData::new(info, 16).unwrap()));
+        table.write().unwrap().insert_row(&[Value::Integer(1)]).unwrap();
+
+        let manager = TransactionManager::new();
+        let tx_id = manager
+            .begin_transaction(IsolationLevel::RepeatableRead)
+            .unwrap();
+
+        let writer = Uuid::new_v4();
+        let change = table.write().unwrap().insert_row_as(writer, &[Value::Integer(2)]).unwrap();
+        let seen = |id| table.read().unwrap().visible_row_count(&manager.read_view(id));
+        assert_eq!(seen(tx_id), 1);
+        manager.commit_changes(writer, vec![(table.clone(), change)]).unwrap();
+        assert_eq!(seen(tx_id), 1);
+
+        // Writing a table changed since the transaction began aborts it
+        assert!(manager.check_write(tx_id, &table).is_err());
+        assert!(!manager.is_transaction_active(tx_id));
+
+        // READ COMMITTED reads the latest commit
+        let tx_id = manager
+            .begin_transaction(IsolationLevel::ReadCommitted)
+            .unwrap();
+        let change = table.write().unwrap().insert_row_as(writer, &[Value::Integer(3)]).unwrap();
+        assert_eq!(seen(tx_id), 2);
+        manager.commit_changes(writer, vec![(table.clone(), change)]).unwrap();
+        assert_eq!(seen(tx_id), 3);
+        assert!(manager.check_write(tx_id, &table).is_ok());
+    }
+
     #[test]
     fn test_transaction_handle() {
         let manager = Arc::new(TransactionManager::new());
@@ -1,13 +1,40 @@
+//! Write-Ahead Log
+//!
+//! # On-disk format
+//!
+//! A WAL file is a header followed by framed records. All integers are
+//! little-endian.
+//!
+//! ```text
+//! header:  magic "PWAL" (4 bytes) | format version (u32)
+//! frame:   payload length (u32) | payload | CRC-32 of payload (u32)
+//! payload: record type (u8) | transaction flag (u8) [| transaction id (16 bytes)]
+//!          | timestamp (u64) | sequence number (u64) | record data
+//! ```
+//!
+//! Record data depends on the record type. Strings are a u32 byte length and
+//! UTF-8 bytes, and value lists are a u32 count followed by values, each a u8
+//! tag and a fixed encoding per tag (see `write_value`). Readers reject files
+//! whose version they don't know, so any change to this layout must bump
+//! [`WAL_FORMAT_VERSION`].
+
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::common::error::{PrismDBError, Result};
-use crate::types::Value;
+use crate::common::sync::{MutexExt, RwLockExt};
+use crate::types::{EnumType, Value};
+
+/// Magic bytes at the start of every WAL file
+pub const WAL_MAGIC: [u8; 4] = *b"PWAL";
+
+/// Version of the WAL format written by this build
+pub const WAL_FORMAT_VERSION: u32 = 1;
 
 /// WAL record types
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -21,6 +48,34 @@ pub enum WalRecordType {
     Checkpoint,
 }
 
+impl WalRecordType {
+    /// Tag identifying the record type on disk
+    fn tag(&self) -> u8 {
+        match self {
+            WalRecordType::BeginTransaction => 1,
+            WalRecordType::CommitTransaction => 2,
+            WalRecordType::AbortTransaction => 3,
+            WalRecordType::Insert => 4,
+            WalRecordType::Update => 5,
+            WalRecordType::Delete => 6,
+            WalRecordType::Checkpoint => 7,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        Ok(match tag {
+            1 => WalRecordType::BeginTransaction,
+            2 => WalRecordType::CommitTransaction,
+            3 => WalRecordType::AbortTransaction,
+            4 => WalRecordType::Insert,
+            5 => WalRecordType::Update,
+            6 => WalRecordType::Delete,
+            7 => WalRecordType::Checkpoint,
+            _ => return Err(PrismDBError::Wal(format!("Unknown WAL record type {}", tag))),
+        })
+    }
+}
+
 /// WAL record for logging operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalRecord {
@@ -78,20 +133,451 @@ impl WalRecord {
         }
     }
 
-    /// Serialize record to bytes
+    /// Serialize the record payload
     pub fn to_bytes(&self) -> Result<Vec<u8>> {
-        let config = bincode::config::standard();
-        let data = bincode::serde::encode_to_vec(self, config)
-            .map_err(|e| PrismDBError::Wal(format!("Failed to serialize WAL record: {}", e)))?;
-        Ok(data)
+        let mut bytes = Vec::new();
+        bytes.push(self.record_type.tag());
+        match self.transaction_id {
+            Some(id) => {
+                bytes.push(1);
+                bytes.extend_from_slice(id.as_bytes());
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes.extend_from_slice(&self.sequence_number.to_le_bytes());
+
+        match (&self.record_type, &self.data) {
+            (WalRecordType::BeginTransaction, WalRecordData::BeginTransaction { isolation_level }) => {
+                write_string(&mut bytes, isolation_level);
+            }
+            (WalRecordType::CommitTransaction, WalRecordData::CommitTransaction)
+            | (WalRecordType::AbortTransaction, WalRecordData::AbortTransaction) => {}
+            (WalRecordType::Insert, WalRecordData::Insert { table_id, row_id, values }) => {
+                write_string(&mut bytes, table_id);
+                bytes.extend_from_slice(&row_id.to_le_bytes());
+                write_values(&mut bytes, values);
+            }
+            (
+                WalRecordType::Update,
+                WalRecordData::Update {
+                    table_id,
+                    row_id,
+                    old_values,
+                    new_values,
+                },
+            ) => {
+                write_string(&mut bytes, table_id);
+                bytes.extend_from_slice(&row_id.to_le_bytes());
+                write_values(&mut bytes, old_values);
+                write_values(&mut bytes, new_values);
+            }
+            (
+                WalRecordType::Delete,
+                WalRecordData::Delete {
+                    table_id,
+                    row_id,
+                    old_values,
+                },
+            ) => {
+                write_string(&mut bytes, table_id);
+                bytes.extend_from_slice(&row_id.to_le_bytes());
+                write_values(&mut bytes, old_values);
+            }
+            (WalRecordType::Checkpoint, WalRecordData::Checkpoint { checkpoint_id }) => {
+                bytes.extend_from_slice(&checkpoint_id.to_le_bytes());
+            }
+            (record_type, data) => {
+                return Err(PrismDBError::Wal(format!(
+                    "WAL record type {:?} does not match its data {:?}",
+                    record_type, data
+                )))
+            }
+        }
+
+        Ok(bytes)
     }
 
-    /// Deserialize record from bytes
+    /// Deserialize a record payload
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
-        let config = bincode::config::standard();
-        let (record, _) = bincode::serde::decode_from_slice(bytes, config)
-            .map_err(|e| PrismDBError::Wal(format!("Failed to deserialize WAL record: {}", e)))?;
-        Ok(record)
+        let mut reader = PayloadReader { bytes, offset: 0 };
+        let record_type = WalRecordType::from_tag(reader.u8()?)?;
+        let transaction_id = match reader.u8()? {
+            0 => None,
+            1 => Some(uuid::Uuid::from_bytes(reader.array()?)),
+            flag => {
+                return Err(PrismDBError::Wal(format!(
+                    "Invalid WAL transaction flag {}",
+                    flag
+                )))
+            }
+        };
+        let timestamp = reader.u64()?;
+        let sequence_number = reader.u64()?;
+
+        let data = match record_type {
+            WalRecordType::BeginTransaction => WalRecordData::BeginTransaction {
+                isolation_level: reader.string()?,
+            },
+            WalRecordType::CommitTransaction => WalRecordData::CommitTransaction,
+            WalRecordType::AbortTransaction => WalRecordData::AbortTransaction,
+            WalRecordType::Insert => WalRecordData::Insert {
+                table_id: reader.string()?,
+                row_id: reader.u64()?,
+                values: reader.values()?,
+            },
+            WalRecordType::Update => WalRecordData::Update {
+                table_id: reader.string()?,
+                row_id: reader.u64()?,
+                old_values: reader.values()?,
+                new_values: reader.values()?,
+            },
+            WalRecordType::Delete => WalRecordData::Delete {
+                table_id: reader.string()?,
+                row_id: reader.u64()?,
+                old_values: reader.values()?,
+            },
+            WalRecordType::Checkpoint => WalRecordData::Checkpoint {
+                checkpoint_id: reader.u64()?,
+            },
+        };
+
+        if reader.offset != bytes.len() {
+            return Err(PrismDBError::Wal(format!(
+                "{} unexpected bytes after WAL record",
+                bytes.len() - reader.offset
+            )));
+        }
+
+        Ok(Self {
+            record_type,
+            transaction_id,
+            timestamp,
+            sequence_number,
+            data,
+        })
+    }
+
+    /// Write the record as a length-prefixed, checksummed frame
+    pub fn write_frame<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let payload = self.to_bytes()?;
+        writer
+            .write_u32::<LittleEndian>(payload.len() as u32)
+            .and_then(|_| writer.write_all(&payload))
+            .and_then(|_| writer.write_u32::<LittleEndian>(crc32fast::hash(&payload)))
+            .map_err(|e| PrismDBError::Wal(format!("Failed to write WAL record: {}", e)))
+    }
+
+    /// Read the next frame, or `None` at the end of the log
+    pub fn read_frame<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        let length = match reader.read_u32::<LittleEndian>() {
+            Ok(length) => length,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(PrismDBError::Wal(format!("Failed to read WAL record: {}", e))),
+        };
+
+        let mut payload = vec![0u8; length as usize];
+        reader
+            .read_exact(&mut payload)
+            .map_err(|e| PrismDBError::Wal(format!("Failed to read record data: {}", e)))?;
+        let checksum = reader
+            .read_u32::<LittleEndian>()
+            .map_err(|e| PrismDBError::Wal(format!("Failed to read record checksum: {}", e)))?;
+        if checksum != crc32fast::hash(&payload) {
+            return Err(PrismDBError::Wal("WAL record checksum mismatch".to_string()));
+        }
+
+        Self::from_bytes(&payload).map(Some)
+    }
+}
+
+/// Write the WAL file header
+pub fn write_wal_header<W: Write>(writer: &mut W) -> Result<()> {
+    writer
+        .write_all(&WAL_MAGIC)
+        .and_then(|_| writer.write_u32::<LittleEndian>(WAL_FORMAT_VERSION))
+        .map_err(|e| PrismDBError::Wal(format!("Failed to write WAL header: {}", e)))
+}
+
+/// Read and validate the WAL file header, returning the format version
+pub fn read_wal_header<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| PrismDBError::Wal(format!("Failed to read WAL header: {}", e)))?;
+    if magic != WAL_MAGIC {
+        return Err(PrismDBError::Wal("Not a WAL file: bad magic bytes".to_string()));
+    }
+
+    let version = reader
+        .read_u32::<LittleEndian>()
+        .map_err(|e| PrismDBError::Wal(format!("Failed to read WAL header: {}", e)))?;
+    if version != WAL_FORMAT_VERSION {
+        return Err(PrismDBError::Wal(format!(
+            "Unsupported WAL format version {} (this build reads version {})",
+            version, WAL_FORMAT_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+fn write_string(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+fn write_values(bytes: &mut Vec<u8>, values: &[Value]) {
+    bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        write_value(bytes, value);
+    }
+}
+
+/// Encode a value as a type tag followed by its payload
+fn write_value(bytes: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => bytes.push(0),
+        Value::Boolean(b) => bytes.extend_from_slice(&[1, *b as u8]),
+        Value::TinyInt(i) => bytes.extend_from_slice(&[2, *i as u8]),
+        Value::SmallInt(i) => {
+            bytes.push(3);
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::Integer(i) => {
+            bytes.push(4);
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::BigInt(i) => {
+            bytes.push(5);
+            bytes.extend_from_slice(&i.to_le_bytes());
+        }
+        Value::HugeInt { high, low } => {
+            bytes.push(6);
+            bytes.extend_from_slice(&high.to_le_bytes());
+            bytes.extend_from_slice(&low.to_le_bytes());
+        }
+        Value::Float(f) => {
+            bytes.push(7);
+            bytes.extend_from_slice(&f.to_le_bytes());
+        }
+        Value::Double(d) => {
+            bytes.push(8);
+            bytes.extend_from_slice(&d.to_le_bytes());
+        }
+        Value::Varchar(s) => {
+            bytes.push(9);
+            write_string(bytes, s);
+        }
+        Value::Char(s) => {
+            bytes.push(10);
+            write_string(bytes, s);
+        }
+        Value::Decimal {
+            value,
+            scale,
+            precision,
+        } => {
+            bytes.push(11);
+            bytes.extend_from_slice(&value.to_le_bytes());
+            bytes.extend_from_slice(&[*scale, *precision]);
+        }
+        Value::Date(d) => {
+            bytes.push(12);
+            bytes.extend_from_slice(&d.to_le_bytes());
+        }
+        Value::Time(t) => {
+            bytes.push(13);
+            bytes.extend_from_slice(&t.to_le_bytes());
+        }
+        Value::Timestamp(ts) => {
+            bytes.push(14);
+            bytes.extend_from_slice(&ts.to_le_bytes());
+        }
+        Value::Interval {
+            months,
+            days,
+            micros,
+        } => {
+            bytes.push(15);
+            bytes.extend_from_slice(&months.to_le_bytes());
+            bytes.extend_from_slice(&days.to_le_bytes());
+            bytes.extend_from_slice(&micros.to_le_bytes());
+        }
+        Value::UUID { high, low } => {
+            bytes.push(16);
+            bytes.extend_from_slice(&high.to_le_bytes());
+            bytes.extend_from_slice(&low.to_le_bytes());
+        }
+        Value::JSON(s) => {
+            bytes.push(17);
+            write_string(bytes, s);
+        }
+        Value::Blob(b) => {
+            bytes.push(18);
+            bytes.extend_from_slice(&(b.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(b);
+        }
+        Value::List(values) => {
+            bytes.push(19);
+            write_values(bytes, values);
+        }
+        Value::Struct(fields) => {
+            bytes.push(20);
+            bytes.extend_from_slice(&(fields.len() as u32).to_le_bytes());
+            for (name, value) in fields {
+                write_string(bytes, name);
+                write_value(bytes, value);
+            }
+        }
+        Value::Map(entries) => {
+            bytes.push(21);
+            bytes.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+            for (key, value) in entries {
+                write_value(bytes, key);
+                write_value(bytes, value);
+            }
+        }
+        Value::Union { tag, value } => {
+            bytes.push(22);
+            bytes.extend_from_slice(&(*tag as u64).to_le_bytes());
+            write_value(bytes, value);
+        }
+        Value::Enum { code, enum_type } => {
+            bytes.push(23);
+            bytes.extend_from_slice(&code.to_le_bytes());
+            write_string(bytes, &enum_type.name);
+            bytes.extend_from_slice(&(enum_type.values.len() as u32).to_le_bytes());
+            for label in &enum_type.values {
+                write_string(bytes, label);
+            }
+        }
+    }
+}
+
+/// Cursor over a record payload
+struct PayloadReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> PayloadReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.offset.checked_add(len).filter(|&end| end <= self.bytes.len());
+        let end = end.ok_or_else(|| PrismDBError::Wal("Truncated WAL record".to_string()))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| PrismDBError::Wal("Invalid UTF-8 in WAL record".to_string()))
+    }
+
+    fn values(&mut self) -> Result<Vec<Value>> {
+        let count = self.u32()? as usize;
+        let mut values = Vec::with_capacity(count.min(1024));
+        for _ in 0..count {
+            values.push(self.value()?);
+        }
+        Ok(values)
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        Ok(match self.u8()? {
+            0 => Value::Null,
+            1 => Value::Boolean(self.u8()? != 0),
+            2 => Value::TinyInt(self.u8()? as i8),
+            3 => Value::SmallInt(i16::from_le_bytes(self.array()?)),
+            4 => Value::Integer(i32::from_le_bytes(self.array()?)),
+            5 => Value::BigInt(self.i64()?),
+            6 => Value::HugeInt {
+                high: self.i64()?,
+                low: self.i64()?,
+            },
+            7 => Value::Float(f32::from_le_bytes(self.array()?)),
+            8 => Value::Double(f64::from_le_bytes(self.array()?)),
+            9 => Value::Varchar(self.string()?),
+            10 => Value::Char(self.string()?),
+            11 => Value::Decimal {
+                value: i128::from_le_bytes(self.array()?),
+                scale: self.u8()?,
+                precision: self.u8()?,
+            },
+            12 => Value::Date(i32::from_le_bytes(self.array()?)),
+            13 => Value::Time(self.i64()?),
+            14 => Value::Timestamp(self.i64()?),
+            15 => Value::Interval {
+                months: i32::from_le_bytes(self.array()?),
+                days: i32::from_le_bytes(self.array()?),
+                micros: self.i64()?,
+            },
+            16 => Value::UUID {
+                high: self.u64()?,
+                low: self.u64()?,
+            },
+            17 => Value::JSON(self.string()?),
+            18 => {
+                let len = self.u32()? as usize;
+                Value::Blob(self.take(len)?.to_vec())
+            }
+            19 => Value::List(self.values()?),
+            20 => {
+                let count = self.u32()? as usize;
+                let mut fields = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    fields.push((self.string()?, self.value()?));
+                }
+                Value::Struct(fields)
+            }
+            21 => {
+                let count = self.u32()? as usize;
+                let mut entries = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    entries.push((self.value()?, self.value()?));
+                }
+                Value::Map(entries)
+            }
+            22 => Value::Union {
+                tag: self.u64()? as usize,
+                value: Box::new(self.value()?),
+            },
+            23 => {
+                let code = self.u32()?;
+                let name = self.string()?;
+                let count = self.u32()? as usize;
+                let mut values = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    values.push(self.string()?);
+                }
+                Value::Enum {
+                    code,
+                    enum_type: Arc::new(EnumType::new(name, values)),
+                }
+            }
+            tag => return Err(PrismDBError::Wal(format!("Unknown WAL value tag {}", tag))),
+        })
     }
 }
 
@@ -129,8 +615,8 @@ impl WalFileManager {
 
     /// Rotate to a new WAL file
     fn rotate_wal_file(&self) -> Result<()> {
-        let mut current_file = self.current_file.lock().unwrap();
-        let mut file_number = self.current_file_number.lock().unwrap();
+        let mut current_file = self.current_file.lock_or_err()?;
+        let mut file_number = self.current_file_number.lock_or_err()?;
 
         // Close current file if open
         if let Some(mut writer) = current_file.take() {
@@ -156,24 +642,28 @@ impl WalFileManager {
                 ))
             })?;
 
-        *current_file = Some(BufWriter::new(file));
+        let mut writer = BufWriter::new(file);
+        write_wal_header(&mut writer)?;
+        *current_file = Some(writer);
 
         Ok(())
     }
 
     /// Ensure WAL file is open and ready for writing
     fn ensure_file_open(&self) -> Result<()> {
-        let mut current_file = self.current_file.lock().unwrap();
+        let mut current_file = self.current_file.lock_or_err()?;
 
         if current_file.is_none() {
-            let file_number = *self.current_file_number.lock().unwrap();
+            let file_number = *self.current_file_number.lock_or_err()?;
             let file_path = self.get_wal_file_path(file_number);
 
-            // Check if file exists, if not create it
-            if !file_path.exists() {
+            // Create the file with a header unless it already has one
+            let has_header = std::fs::metadata(&file_path).is_ok_and(|m| m.len() > 0);
+            if !has_header {
                 let file = OpenOptions::new()
                     .create(true)
                     .write(true)
+                    .truncate(true)
                     .open(&file_path)
                     .map_err(|e| {
                         PrismDBError::Wal(format!(
@@ -182,7 +672,9 @@ impl WalFileManager {
                             e
                         ))
                     })?;
-                *current_file = Some(BufWriter::new(file));
+                let mut writer = BufWriter::new(file);
+                write_wal_header(&mut writer)?;
+                *current_file = Some(writer);
             } else {
                 let file = OpenOptions::new()
                     .append(true)
@@ -207,32 +699,21 @@ impl WalFileManager {
 
         // Set sequence number
         {
-            let mut seq_num = self.sequence_number.lock().unwrap();
+            let mut seq_num = self.sequence_number.lock_or_err()?;
             record.sequence_number = *seq_num;
             *seq_num += 1;
         }
 
-        // Serialize record
-        let bytes = record.to_bytes()?;
-        let length = bytes.len() as u32;
-
-        let mut current_file = self.current_file.lock().unwrap();
+        let mut current_file = self.current_file.lock_or_err()?;
         if let Some(writer) = current_file.as_mut() {
-            // Write record length
-            writer
-                .write_u32::<LittleEndian>(length)
-                .map_err(|e| PrismDBError::Wal(format!("Failed to write record length: {}", e)))?;
-
-            // Write record data
-            writer
-                .write_all(&bytes)
-                .map_err(|e| PrismDBError::Wal(format!("Failed to write record data: {}", e)))?;
+            record.write_frame(writer)?;
 
             // Flush to ensure durability
             writer
                 .flush()
                 .map_err(|e| PrismDBError::Wal(format!("Failed to flush WAL record: {}", e)))?;
         }
+        drop(current_file);
 
         // Check if we need to rotate file
         self.check_file_rotation()?;
@@ -242,7 +723,7 @@ impl WalFileManager {
 
     /// Check if current WAL file needs rotation
     fn check_file_rotation(&self) -> Result<()> {
-        let current_file_number = *self.current_file_number.lock().unwrap();
+        let current_file_number = *self.current_file_number.lock_or_err()?;
         let file_path = self.get_wal_file_path(current_file_number);
 
         if let Ok(metadata) = std::fs::metadata(&file_path) {
@@ -302,24 +783,16 @@ impl WalFileManager {
             ))
         })?;
 
-        let mut reader = BufReader::new(file);
-
-        loop {
-            // Read record length
-            match reader.read_u32::<LittleEndian>() {
-                Ok(length) => {
-                    // Read record data
-                    let mut buffer = vec![0u8; length as usize];
-                    reader.read_exact(&mut buffer).map_err(|e| {
-                        PrismDBError::Wal(format!("Failed to read record data: {}", e))
-                    })?;
+        // A file that was created but never written holds no records
+        let is_empty = file.metadata().is_ok_and(|m| m.len() == 0);
+        if is_empty {
+            return Ok(records);
+        }
 
-                    // Deserialize record
-                    let record = WalRecord::from_bytes(&buffer)?;
-                    records.push(record);
-                }
-                Err(_) => break, // End of file
-            }
+        let mut reader = BufReader::new(file);
+        read_wal_header(&mut reader).map_err(|e| e.with_context(file_path.display()))?;
+        while let Some(record) = WalRecord::read_frame(&mut reader)? {
+            records.push(record);
         }
 
         Ok(records)
@@ -327,7 +800,7 @@ impl WalFileManager {
 
     /// Flush all pending writes
     pub fn flush(&self) -> Result<()> {
-        let mut current_file = self.current_file.lock().unwrap();
+        let mut current_file = self.current_file.lock_or_err()?;
         if let Some(writer) = current_file.as_mut() {
             writer
                 .flush()
@@ -338,7 +811,7 @@ impl WalFileManager {
 
     /// Close current WAL file
     pub fn close(&self) -> Result<()> {
-        let mut current_file = self.current_file.lock().unwrap();
+        let mut current_file = self.current_file.lock_or_err()?;
         if let Some(mut writer) = current_file.take() {
             writer
                 .flush()
@@ -367,13 +840,13 @@ impl WalManager {
 
     /// Enable or disable WAL
     pub fn set_enabled(&self, enabled: bool) {
-        let mut enabled_lock = self.enabled.write().unwrap();
+        let mut enabled_lock = self.enabled.write_recovered();
         *enabled_lock = enabled;
     }
 
     /// Check if WAL is enabled
     pub fn is_enabled(&self) -> bool {
-        *self.enabled.read().unwrap()
+        *self.enabled.read_recovered()
     }
 
     /// Log transaction begin
@@ -571,21 +1044,129 @@ mod tests {
     use tempfile::TempDir;
     use uuid::Uuid;
 
+    fn sample_records() -> Vec<WalRecord> {
+        let tx_id = Some(Uuid::new_v4());
+        let row = vec![
+            Value::Integer(42),
+            Value::Varchar("test".to_string()),
+            Value::Null,
+            Value::Decimal {
+                value: -12345,
+                scale: 2,
+                precision: 10,
+            },
+            Value::List(vec![Value::Double(1.5), Value::Boolean(true)]),
+            Value::Struct(vec![("k".to_string(), Value::Blob(vec![0, 255]))]),
+            Value::Interval {
+                months: 1,
+                days: -2,
+                micros: 3,
+            },
+        ];
+
+        vec![
+            WalRecord::new(
+                WalRecordType::BeginTransaction,
+                tx_id,
+                WalRecordData::BeginTransaction {
+                    isolation_level: "ReadCommitted".to_string(),
+                },
+            ),
+            WalRecord::new(WalRecordType::CommitTransaction, tx_id, WalRecordData::CommitTransaction),
+            WalRecord::new(WalRecordType::AbortTransaction, tx_id, WalRecordData::AbortTransaction),
+            WalRecord::new(
+                WalRecordType::Insert,
+                tx_id,
+                WalRecordData::Insert {
+                    table_id: "test_table".to_string(),
+                    row_id: 1,
+                    values: row.clone(),
+                },
+            ),
+            WalRecord::new(
+                WalRecordType::Update,
+                tx_id,
+                WalRecordData::Update {
+                    table_id: "test_table".to_string(),
+                    row_id: 2,
+                    old_values: row.clone(),
+                    new_values: vec![Value::Timestamp(7), Value::Char("c".to_string())],
+                },
+            ),
+            WalRecord::new(
+                WalRecordType::Delete,
+                tx_id,
+                WalRecordData::Delete {
+                    table_id: "test_table".to_string(),
+                    row_id: u64::MAX,
+                    old_values: row,
+                },
+            ),
+            WalRecord::new(
+                WalRecordType::Checkpoint,
+                None,
+                WalRecordData::Checkpoint { checkpoint_id: 9 },
+            ),
+        ]
+    }
+
     #[test]
     fn test_wal_record_serialization() {
-        let data = WalRecordData::Insert {
-            table_id: "test_table".to_string(),
-            row_id: 1,
-            values: vec![Value::Integer(42), Value::Varchar("test".to_string())],
-        };
+        for (sequence_number, mut record) in sample_records().into_iter().enumerate() {
+            record.sequence_number = sequence_number as u64;
+
+            let mut frame = Vec::new();
+            record.write_frame(&mut frame).unwrap();
+            let deserialized = WalRecord::read_frame(&mut frame.as_slice()).unwrap().unwrap();
+
+            assert_eq!(record.record_type, deserialized.record_type);
+            assert_eq!(record.transaction_id, deserialized.transaction_id);
+            assert_eq!(record.timestamp, deserialized.timestamp);
+            assert_eq!(record.sequence_number, deserialized.sequence_number);
+            assert_eq!(format!("{:?}", record.data), format!("{:?}", deserialized.data));
+        }
+    }
 
-        let record = WalRecord::new(WalRecordType::Insert, Some(Uuid::new_v4()), data);
+    #[test]
+    fn test_wal_format_rejects_bad_input() {
+        let mut header = Vec::new();
+        write_wal_header(&mut header).unwrap();
+        assert_eq!(read_wal_header(&mut header.as_slice()).unwrap(), WAL_FORMAT_VERSION);
+
+        // An unknown version
+        header[4..8].copy_from_slice(&(WAL_FORMAT_VERSION + 1).to_le_bytes());
+        let err = read_wal_header(&mut header.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported WAL format version 2"), "{}", err);
+
+        assert!(read_wal_header(&mut &b"XWAL\x01\0\0\0"[..]).is_err());
+
+        // A corrupted payload fails its checksum
+        let mut frame = Vec::new();
+        sample_records()[3].write_frame(&mut frame).unwrap();
+        frame[10] ^= 0xff;
+        let err = WalRecord::read_frame(&mut frame.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("checksum"), "{}", err);
+
+        // Record type and data must agree
+        let mismatched = WalRecord::new(WalRecordType::Insert, None, WalRecordData::CommitTransaction);
+        assert!(mismatched.to_bytes().is_err());
+    }
+
+    #[test]
+    fn test_wal_file_rejects_unknown_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = WalFileManager::new(temp_dir.path(), 1024).unwrap();
+        manager.write_record(sample_records().remove(3)).unwrap();
+        manager.close().unwrap();
 
-        let bytes = record.to_bytes().unwrap();
-        let deserialized = WalRecord::from_bytes(&bytes).unwrap();
+        let path = manager.get_wal_file_path(0);
+        let mut bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..4], &WAL_MAGIC);
+        bytes[4..8].copy_from_slice(&99u32.to_le_bytes());
+        std::fs::write(&path, bytes).unwrap();
 
-        assert_eq!(record.record_type, deserialized.record_type);
-        assert_eq!(record.transaction_id, deserialized.transaction_id);
+        let err = manager.read_all_records().unwrap_err();
+        assert!(err.to_string().contains("Unsupported WAL format version 99"), "{}", err);
     }
 
     #[test]
@@ -0,0 +1,159 @@
+//! String collations
+//!
+//! A collation decides how two strings compare in `=`, `<` and ORDER BY.
+//! `BINARY` compares code points, `NOCASE` ignores case, and any other name is
+//! read as a locale whose ICU collation rules order the strings. Locale
+//! collations need PrismDB to be built with the `icu` feature.
+
+use crate::common::error::{PrismDBError, PrismDBResult};
+use crate::types::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A named rule for comparing strings
+#[derive(Clone, Default)]
+pub enum Collation {
+    /// Code point order, the default
+    #[default]
+    Binary,
+    /// Code point order after lowercasing both strings
+    NoCase,
+    /// Locale-specific order, e.g. `de` sorting `ä` next to `a`
+    #[cfg(feature = "icu")]
+    Locale {
+        name: String,
+        collator: std::sync::Arc<icu_collator::Collator>,
+    },
+}
+
+impl Collation {
+    /// Look up a collation by name, ignoring case
+    pub fn from_name(name: &str) -> PrismDBResult<Self> {
+        match name.to_uppercase().as_str() {
+            "BINARY" | "C" | "POSIX" => Ok(Collation::Binary),
+            "NOCASE" => Ok(Collation::NoCase),
+            _ => Self::locale(name),
+        }
+    }
+
+    #[cfg(feature = "icu")]
+    fn locale(name: &str) -> PrismDBResult<Self> {
+        use icu_collator::{Collator, CollatorOptions};
+
+        let locale: icu_locid::Locale = name
+            .replace('_', "-")
+            .parse()
+            .map_err(|_| PrismDBError::InvalidValue(format!("Unknown collation '{}'", name)))?;
+        let collator = Collator::try_new(&(&locale).into(), CollatorOptions::new())
+            .map_err(|e| PrismDBError::InvalidValue(format!("Collation '{}': {}", name, e)))?;
+        Ok(Collation::Locale {
+            name: name.to_string(),
+            collator: std::sync::Arc::new(collator),
+        })
+    }
+
+    #[cfg(not(feature = "icu"))]
+    fn locale(name: &str) -> PrismDBResult<Self> {
+        Err(PrismDBError::Extension(format!(
+            "Collation '{}' requires the icu extension",
+            name
+        )))
+    }
+
+    /// The collation's name
+    pub fn name(&self) -> &str {
+        match self {
+            Collation::Binary => "BINARY",
+            Collation::NoCase => "NOCASE",
+            #[cfg(feature = "icu")]
+            Collation::Locale { name, .. } => name,
+        }
+    }
+
+    /// Compare two strings
+    pub fn compare(&self, left: &str, right: &str) -> Ordering {
+        match self {
+            Collation::Binary => left.cmp(right),
+            Collation::NoCase => left.to_lowercase().cmp(&right.to_lowercase()),
+            #[cfg(feature = "icu")]
+            Collation::Locale { collator, .. } => collator.compare(left, right),
+        }
+    }
+
+    /// Compare two values if both are strings; other values have no collation
+    pub fn compare_values(&self, left: &Value, right: &Value) -> Option<Ordering> {
+        match (left, right) {
+            (Value::Varchar(left) | Value::Char(left), Value::Varchar(right) | Value::Char(right)) => {
+                Some(self.compare(left, right))
+            }
+            _ => None,
+        }
+    }
+
+    /// The value a string stands for when grouping, so equal strings share a group
+    ///
+    /// NOCASE folds strings to lower case; other collations, and values that
+    /// aren't strings, are left as they are.
+    pub fn group_key<'a>(&self, value: &'a Value) -> Cow<'a, Value> {
+        match (self, value) {
+            (Collation::NoCase, Value::Varchar(s)) => Cow::Owned(Value::Varchar(s.to_lowercase())),
+            (Collation::NoCase, Value::Char(s)) => Cow::Owned(Value::Char(s.to_lowercase())),
+            _ => Cow::Borrowed(value),
+        }
+    }
+}
+
+impl fmt::Debug for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Collation({})", self.name())
+    }
+}
+
+impl fmt::Display for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl PartialEq for Collation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name().eq_ignore_ascii_case(other.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_collations() {
+        assert_eq!(Collation::from_name("binary").unwrap(), Collation::Binary);
+        assert_eq!(Collation::from_name("NoCase").unwrap(), Collation::NoCase);
+        assert_eq!(Collation::Binary.compare("a", "B"), Ordering::Greater);
+        assert_eq!(Collation::NoCase.compare("a", "B"), Ordering::Less);
+        assert_eq!(Collation::NoCase.compare("ÄPFEL", "äpfel"), Ordering::Equal);
+        assert_eq!(
+            Collation::NoCase.compare_values(&Value::Varchar("X".into()), &Value::Varchar("x".into())),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(Collation::NoCase.compare_values(&Value::Integer(1), &Value::Integer(2)), None);
+        assert_eq!(*Collation::NoCase.group_key(&Value::Varchar("Us".into())), Value::Varchar("us".into()));
+        assert_eq!(*Collation::Binary.group_key(&Value::Varchar("Us".into())), Value::Varchar("Us".into()));
+    }
+
+    #[cfg(not(feature = "icu"))]
+    #[test]
+    fn test_locale_collation_needs_icu() {
+        assert!(matches!(Collation::from_name("de"), Err(PrismDBError::Extension(_))));
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn test_locale_collation() {
+        let german = Collation::from_name("de").unwrap();
+        assert_eq!(german.compare("Äpfel", "Birnen"), Ordering::Less);
+        assert_eq!(Collation::Binary.compare("Äpfel", "Birnen"), Ordering::Greater);
+        assert!(Collation::from_name("not a locale!").is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::common::error::{PrismDBError, PrismDBResult};
 use crate::types::logical_type::LogicalType;
 use crate::types::value::Value;
-use crate::types::vector::Vector;
+use crate::types::vector::{SelectionVector, Vector};
 use std::fmt;
+use std::sync::Arc;
 
 /// A DataChunk represents a collection of vectors (columns) for batch processing
 /// This is the fundamental unit of data processing in PrismDB
@@ -373,64 +374,56 @@ impl DataChunk {
         }
 
         let end = (start + len).min(self.count);
-        let actual_len = end - start;
-
-        if actual_len == 0 {
+        if end == start {
             return Ok(DataChunk::new());
         }
 
-        let mut sliced_vectors = Vec::new();
-        for vector in &self.vectors {
-            let mut new_vector = Vector::new(vector.get_type().clone(), actual_len);
-            for i in 0..actual_len {
-                let value = vector.get_value(start + i)?;
-                new_vector.push(&value)?;
-            }
-            sliced_vectors.push(new_vector);
-        }
-
-        DataChunk::from_vectors(sliced_vectors)
+        self.slice(&SelectionVector::from_indices((start..end).collect()))
     }
 
     /// Slice this chunk using a SelectionVector (PrismDB-faithful)
     /// This is the zero-copy filtering mechanism - creates a new chunk
-    /// with only the selected rows
-    pub fn slice(&self, selection: &crate::types::SelectionVector) -> PrismDBResult<DataChunk> {
+    /// with only the selected rows, as views over this chunk's vectors
+    pub fn slice(&self, selection: &SelectionVector) -> PrismDBResult<DataChunk> {
         if selection.is_empty() {
             return Ok(DataChunk::new());
         }
 
-        let mut sliced_vectors = Vec::new();
-        for vector in &self.vectors {
-            let mut new_vector = Vector::new(vector.get_type().clone(), selection.count());
-            for i in 0..selection.count() {
-                let row_index = selection.get_index(i);
-                if row_index < self.count {
-                    let value = vector.get_value(row_index)?;
-                    new_vector.push(&value)?;
-                }
-            }
-            sliced_vectors.push(new_vector);
-        }
-
+        // Vectors that read the same rows share one selection, so slicing a
+        // chunk allocates a selection per distinct source view, not per column
+        let mut shared: Vec<(Option<*const SelectionVector>, Arc<SelectionVector>)> = Vec::new();
+        let sliced_vectors = self
+            .vectors
+            .iter()
+            .map(|vector| {
+                let source = vector.shared_selection().map(Arc::as_ptr);
+                let rows = match shared.iter().find(|(key, _)| *key == source) {
+                    Some((_, rows)) => rows.clone(),
+                    None => {
+                        let rows = Arc::new(vector.physical_rows(selection));
+                        shared.push((source, rows.clone()));
+                        rows
+                    }
+                };
+                vector.select_rows(rows)
+            })
+            .collect();
         DataChunk::from_vectors(sliced_vectors)
     }
 
     /// Filter this chunk based on a selection vector (slice of indices)
     pub fn filter(&self, selection: &[usize]) -> PrismDBResult<DataChunk> {
-        let mut filtered_vectors = Vec::new();
-        for vector in &self.vectors {
-            let mut new_vector = Vector::new(vector.get_type().clone(), selection.len());
-            for &row_index in selection {
-                if row_index < self.count {
-                    let value = vector.get_value(row_index)?;
-                    new_vector.push(&value)?;
-                }
-            }
-            filtered_vectors.push(new_vector);
-        }
+        self.slice(&SelectionVector::from_indices(selection.to_vec()))
+    }
 
-        DataChunk::from_vectors(filtered_vectors)
+    /// Materialize any vector views into contiguous vectors
+    ///
+    /// Views are read through their selections, so this is only needed where a
+    /// consumer wants each vector's buffers to hold exactly its rows.
+    pub fn flatten(&mut self) {
+        for vector in &mut self.vectors {
+            vector.flatten();
+        }
     }
 
     /// Get an iterator over rows in this chunk
@@ -477,10 +470,7 @@ impl DataChunk {
 
     /// Get the total size of this chunk in bytes (approximate)
     pub fn get_size(&self) -> usize {
-        self.vectors
-            .iter()
-            .map(|v| v.count() * v.get_type().get_max_size().unwrap_or(8))
-            .sum()
+        self.vectors.iter().map(|v| v.memory_usage()).sum()
     }
 
     /// Verify the integrity of this chunk
@@ -710,4 +700,45 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_data_chunk_filter_then_project() -> PrismDBResult<()> {
+        let ids: Vec<Value> = (0..10).map(Value::integer).collect();
+        let names: Vec<Value> = (0..10)
+            .map(|i| if i % 4 == 0 { Value::Null } else { Value::varchar(format!("name-{}", i)) })
+            .collect();
+        let flags: Vec<Value> = (0..10).map(|i| Value::boolean(i % 2 == 0)).collect();
+        let chunk = DataChunk::from_vectors(vec![
+            Vector::from_values(&ids)?,
+            Vector::from_values(&names)?,
+            Vector::from_values(&flags)?,
+        ])?;
+
+        // Filter to the even rows, then narrow the range and project two columns
+        let filtered = chunk.filter(&[0, 2, 4, 6, 8])?;
+        let ranged = filtered.slice_range(1, 3)?;
+        let projected = DataChunk::from_vectors(vec![
+            ranged.get_vector(1).unwrap().clone(),
+            ranged.get_vector(0).unwrap().clone(),
+        ])?;
+
+        assert_eq!(projected.count(), 3);
+        assert!(projected.get_vector(1).unwrap().is_view());
+        let rows: PrismDBResult<Vec<Vec<Value>>> = projected.row_iter().collect();
+        assert_eq!(
+            rows?,
+            vec![
+                vec![Value::varchar("name-2".to_string()), Value::integer(2)],
+                vec![Value::Null, Value::integer(4)],
+                vec![Value::varchar("name-6".to_string()), Value::integer(6)],
+            ]
+        );
+        assert_eq!(projected.null_count(0), 1);
+
+        let mut flattened = projected.clone();
+        flattened.flatten();
+        assert!(!flattened.get_vector(1).unwrap().is_view());
+        assert_eq!(flattened.to_rows()?, projected.to_rows()?);
+        Ok(())
+    }
 }
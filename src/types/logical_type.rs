@@ -2,6 +2,7 @@ use crate::common::error::{PrismDBError, PrismDBResult};
 use crate::types::physical_type::PhysicalType;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::Arc;
 
 /// Logical types represent the SQL-level types that users interact with
 /// These are mapped to physical types for storage and computation
@@ -58,12 +59,39 @@ pub enum LogicalType {
     },
     /// Union type with member types
     Union(Vec<LogicalType>),
-    /// Enum type with possible values
-    Enum { name: String, values: Vec<String> },
+    /// User-defined enum type, shared by its columns and values
+    Enum(Arc<EnumType>),
     /// Invalid/unknown type
     Invalid,
 }
 
+/// An enum type created with `CREATE TYPE ... AS ENUM`
+///
+/// Values of the type are stored as codes, the position of their label in
+/// `values`, so they compare in definition order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnumType {
+    pub name: String,
+    /// Labels in definition order
+    pub values: Vec<String>,
+}
+
+impl EnumType {
+    pub fn new(name: String, values: Vec<String>) -> Self {
+        Self { name, values }
+    }
+
+    /// Code of a label, or `None` if it is not one of the type's labels
+    pub fn code(&self, label: &str) -> Option<u32> {
+        self.values.iter().position(|value| value == label).map(|code| code as u32)
+    }
+
+    /// Label of a code
+    pub fn label(&self, code: u32) -> &str {
+        self.values.get(code as usize).map(String::as_str).unwrap_or("")
+    }
+}
+
 impl LogicalType {
     /// Get the corresponding physical type for this logical type
     pub fn get_physical_type(&self) -> PhysicalType {
@@ -94,8 +122,8 @@ impl LogicalType {
             LogicalType::Struct(_) => PhysicalType::Struct,
             LogicalType::Map { .. } => PhysicalType::Map,
             LogicalType::Union(_) => PhysicalType::Union,
-            LogicalType::Enum { .. } => PhysicalType::Varchar, // Enums stored as strings
-            LogicalType::Null => PhysicalType::Invalid,        // NULL uses Invalid as physical type
+            LogicalType::Enum(_) => PhysicalType::Enum, // Enums stored as their codes
+            LogicalType::Null => PhysicalType::Invalid,  // NULL uses Invalid as physical type
             LogicalType::Invalid => PhysicalType::Invalid,
         }
     }
@@ -236,6 +264,41 @@ impl LogicalType {
             _ => false,
         }
     }
+
+    /// Cost of implicitly casting this type to `target`, or None if it can't be
+    ///
+    /// Used to rank function overloads: an exact match costs nothing, a numeric
+    /// promotion costs more the wider it widens, and converting to a string
+    /// costs most. NULL converts to any type for free.
+    pub fn implicit_cast_cost(&self, target: &LogicalType) -> Option<u32> {
+        if self == target || matches!(self, LogicalType::Null | LogicalType::Invalid) {
+            return Some(0);
+        }
+        if !self.can_implicitly_cast_to(target) {
+            return None;
+        }
+        Some(match (self.numeric_rank(), target.numeric_rank()) {
+            (Some(from), Some(to)) => to - from,
+            _ if self.is_string() && target.is_string() => 1,
+            _ if target.is_string() => 100,
+            _ => 1,
+        })
+    }
+
+    /// Position of a numeric type in the order implicit casts widen in
+    fn numeric_rank(&self) -> Option<u32> {
+        match self {
+            LogicalType::TinyInt => Some(1),
+            LogicalType::SmallInt => Some(2),
+            LogicalType::Integer => Some(3),
+            LogicalType::BigInt => Some(4),
+            LogicalType::HugeInt => Some(5),
+            LogicalType::Decimal { .. } => Some(6),
+            LogicalType::Float => Some(7),
+            LogicalType::Double => Some(8),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for LogicalType {
@@ -288,7 +351,7 @@ impl fmt::Display for LogicalType {
                 }
                 write!(f, ")")
             }
-            LogicalType::Enum { name, .. } => write!(f, "ENUM({})", name),
+            LogicalType::Enum(enum_type) => write!(f, "{}", enum_type.name),
             LogicalType::Null => write!(f, "NULL"),
             LogicalType::Invalid => write!(f, "INVALID"),
         }
@@ -327,9 +390,22 @@ impl TypeUtils {
             (HugeInt, TinyInt | SmallInt | Integer | BigInt | Float | Double | Decimal { .. }) => {
                 Ok(type2.clone())
             }
+            (Float | Double, TinyInt | SmallInt | Integer | BigInt | HugeInt) => Ok(type1.clone()),
             (Float, Double) => Ok(Double),
             (Double, Float) => Ok(Double),
 
+            // Decimals keep the larger scale and enough integer digits for both
+            (
+                Decimal { precision: precision1, scale: scale1 },
+                Decimal { precision: precision2, scale: scale2 },
+            ) => {
+                let scale = *scale1.max(scale2);
+                let integer_digits = (precision1 - scale1).max(precision2 - scale2);
+                Ok(Decimal { precision: (integer_digits + scale).min(38), scale })
+            }
+            (Decimal { .. }, TinyInt | SmallInt | Integer | BigInt | HugeInt) => Ok(type1.clone()),
+            (Decimal { .. }, Float | Double) | (Float | Double, Decimal { .. }) => Ok(Double),
+
             // String types
             (Varchar, Char { .. }) => Ok(Varchar),
             (Char { .. }, Varchar) => Ok(Varchar),
@@ -337,6 +413,14 @@ impl TypeUtils {
             // Date/Time to timestamp
             (Date, Time) => Ok(Timestamp),
             (Time, Date) => Ok(Timestamp),
+            (Date, Timestamp) | (Timestamp, Date) => Ok(Timestamp),
+
+            // Lists take the common element type; an empty list's element type is unknown
+            (List(element1), List(element2)) => match (element1.as_ref(), element2.as_ref()) {
+                (Invalid, _) => Ok(type2.clone()),
+                (_, Invalid) => Ok(type1.clone()),
+                _ => Ok(List(Box::new(Self::get_max_type(element1, element2)?))),
+            },
 
             _ => Err(PrismDBError::InvalidType(format!(
                 "Cannot find common type between {} and {}",
@@ -382,6 +466,19 @@ mod tests {
         assert!(int_type.can_implicitly_cast_to(&varchar_type));
     }
 
+    #[test]
+    fn test_implicit_cast_cost() {
+        use LogicalType::*;
+
+        assert_eq!(Integer.implicit_cast_cost(&Integer), Some(0));
+        assert_eq!(Null.implicit_cast_cost(&Double), Some(0));
+        assert_eq!(SmallInt.implicit_cast_cost(&Integer), Some(1));
+        assert!(Integer.implicit_cast_cost(&BigInt) < Integer.implicit_cast_cost(&Double));
+        assert!(Integer.implicit_cast_cost(&Double) < Integer.implicit_cast_cost(&Varchar));
+        assert_eq!(Double.implicit_cast_cost(&Integer), None);
+        assert_eq!(Varchar.implicit_cast_cost(&Integer), None);
+    }
+
     #[test]
     fn test_type_promotion() {
         let tinyint_type = LogicalType::TinyInt;
@@ -405,4 +502,51 @@ mod tests {
         let simple_type = LogicalType::Integer;
         assert!(!simple_type.is_nested());
     }
+
+    #[test]
+    fn test_display_every_type() {
+        use LogicalType::*;
+
+        let mood = Arc::new(EnumType::new(
+            "mood".to_string(),
+            vec!["sad".to_string(), "happy".to_string()],
+        ));
+        let cases = [
+            (Null, "NULL"),
+            (Boolean, "BOOLEAN"),
+            (TinyInt, "TINYINT"),
+            (SmallInt, "SMALLINT"),
+            (Integer, "INTEGER"),
+            (BigInt, "BIGINT"),
+            (HugeInt, "HUGEINT"),
+            (Float, "FLOAT"),
+            (Double, "DOUBLE"),
+            (Varchar, "VARCHAR"),
+            (Char { length: 8 }, "CHAR(8)"),
+            (Text, "VARCHAR"),
+            (Decimal { precision: 10, scale: 2 }, "DECIMAL(10,2)"),
+            (Date, "DATE"),
+            (Time, "TIME"),
+            (Timestamp, "TIMESTAMP"),
+            (Interval, "INTERVAL"),
+            (UUID, "UUID"),
+            (JSON, "JSON"),
+            (Blob, "BLOB"),
+            (List(Box::new(List(Box::new(Integer)))), "INTEGER[][]"),
+            (
+                Struct(vec![("id".to_string(), Integer), ("tags".to_string(), List(Box::new(Varchar)))]),
+                "STRUCT(id INTEGER, tags VARCHAR[])",
+            ),
+            (
+                Map { key_type: Box::new(Varchar), value_type: Box::new(Double) },
+                "MAP(VARCHAR, DOUBLE)",
+            ),
+            (Union(vec![Integer, Varchar]), "UNION(INTEGER, VARCHAR)"),
+            (Enum(mood), "mood"),
+            (Invalid, "INVALID"),
+        ];
+        for (logical_type, expected) in cases {
+            assert_eq!(logical_type.to_string(), expected);
+        }
+    }
 }
@@ -7,18 +7,22 @@
 //! - Vector: Columnar data containers with validity masks
 //! - DataChunk: Collections of vectors for batch processing
 
+pub mod collation;
 pub mod data_chunk;
 pub mod logical_type;
+pub mod ordering;
 pub mod physical_type;
 pub mod value;
 pub mod vector;
 
 // Re-export main types for convenience
+pub use collation::Collation;
 pub use data_chunk::{ColumnIterator, DataChunk, RowIterator};
-pub use logical_type::{LogicalType, TypeUtils};
+pub use logical_type::{EnumType, LogicalType, TypeUtils};
+pub use ordering::OrderedValue;
 pub use physical_type::PhysicalType;
 pub use value::Value;
-pub use vector::{SelectionVector, ValidityMask, Vector};
+pub use vector::{SelectionVector, StringDictionary, ValidityMask, Vector};
 
 /// Type system utilities and constants
 pub mod utils {
@@ -30,6 +34,12 @@ pub mod utils {
     /// Maximum chunk size to prevent memory issues
     pub const MAX_CHUNK_SIZE: usize = 100_000;
 
+    /// Minimum chunk size picked by `adaptive_chunk_size`, however wide the rows
+    pub const MIN_CHUNK_SIZE: usize = 64;
+
+    /// Estimated bytes a chunk picked by `adaptive_chunk_size` holds
+    pub const TARGET_CHUNK_BYTES: usize = 64 * 1024;
+
     /// Get the default chunk size based on type
     pub fn get_default_chunk_size(logical_type: &LogicalType) -> usize {
         match logical_type {
@@ -84,13 +94,35 @@ pub mod utils {
                 // Tag + largest variant
                 1 + types.iter().map(estimate_type_size).max().unwrap_or(0)
             }
-            LogicalType::Enum { .. } => 4,
+            LogicalType::Enum(_) => 4,
             LogicalType::Interval => 16,
             LogicalType::Null => 0, // NULL takes no space
             LogicalType::Invalid => 0,
         }
     }
 
+    /// Estimate the width of a row with columns of the given types
+    ///
+    /// Each column counts its estimated size, scaled up by the factor
+    /// `get_default_chunk_size` shrinks chunks of its type by, so variable-size
+    /// and complex values weigh more than their inline part.
+    pub fn estimate_row_width<'a>(types: impl IntoIterator<Item = &'a LogicalType>) -> usize {
+        types
+            .into_iter()
+            .map(|t| estimate_type_size(t) * DEFAULT_CHUNK_SIZE / get_default_chunk_size(t))
+            .sum()
+    }
+
+    /// Pick a chunk size for rows with columns of the given types
+    ///
+    /// Narrow rows get large chunks to amortize per-chunk overhead, wide rows
+    /// small ones to bound the memory a chunk takes; either way a chunk holds
+    /// about `TARGET_CHUNK_BYTES`.
+    pub fn adaptive_chunk_size<'a>(types: impl IntoIterator<Item = &'a LogicalType>) -> usize {
+        let width = estimate_row_width(types).max(1);
+        (TARGET_CHUNK_BYTES / width).clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE)
+    }
+
     /// Check if a type is suitable for vectorized operations
     pub fn is_vectorizable(logical_type: &LogicalType) -> bool {
         !matches!(logical_type, LogicalType::Struct(_) | LogicalType::Union(_))
@@ -133,4 +165,18 @@ mod tests {
         assert_eq!(get_type_alignment(&LogicalType::BigInt), 8);
         assert_eq!(get_type_alignment(&LogicalType::HugeInt), 16);
     }
+
+    #[test]
+    fn test_adaptive_chunk_size() {
+        let narrow = [LogicalType::Integer];
+        let wide = vec![LogicalType::Varchar; 16];
+        assert_eq!(estimate_row_width(&narrow), 4);
+        assert_eq!(estimate_row_width(&wide), 16 * 64);
+
+        assert_eq!(adaptive_chunk_size(&narrow), TARGET_CHUNK_BYTES / 4);
+        assert_eq!(adaptive_chunk_size(&wide), MIN_CHUNK_SIZE);
+        assert!(adaptive_chunk_size(&[LogicalType::BigInt, LogicalType::Double]) > 2048);
+        assert_eq!(adaptive_chunk_size(&[]), TARGET_CHUNK_BYTES);
+        assert_eq!(adaptive_chunk_size(&vec![LogicalType::Blob; 1000]), MIN_CHUNK_SIZE);
+    }
 }
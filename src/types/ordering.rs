@@ -0,0 +1,499 @@
+//! Canonical total ordering of values
+//!
+//! `Value::compare` implements SQL comparison: it fails on values that are not
+//! comparable and on NaN. Sorting, MIN/MAX, grouping and duplicate removal
+//! instead need every pair of values to be ordered the same way everywhere,
+//! which is what `Value::total_cmp` and the `OrderedValue` key wrapper provide:
+//!
+//! - Numbers of any width (integers, HUGEINT, DECIMAL, FLOAT, DOUBLE) compare
+//!   by numeric value, so `1`, `1::BIGINT`, `1.0` and `1.00::DECIMAL` are equal
+//! - Floats are totally ordered: `-0.0` equals `0.0`, and NaN equals itself and
+//!   sorts above every other number
+//! - CHAR and VARCHAR compare as text; enums of one type in definition order,
+//!   otherwise by label
+//! - Intervals compare by length, counting a month as 30 days
+//! - Lists and structs compare element by element; maps compare entry by
+//!   entry in key order, so maps holding the same entries are equal whatever
+//!   order they were built in
+//! - Values of unrelated types order by type, and NULL sorts after everything
+
+use super::Value;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// Microseconds in a day, and in the 30-day month used to compare intervals
+const MICROS_PER_DAY: i128 = 86_400_000_000;
+const MICROS_PER_MONTH: i128 = 30 * MICROS_PER_DAY;
+
+/// A number as compared by `total_cmp`: exact values are `value / 10^scale`
+#[derive(Clone, Copy)]
+enum Number {
+    Exact { value: i128, scale: u8 },
+    Float(f64),
+}
+
+/// The split of a finite number into its floor and the fraction above it,
+/// the form in which exact and floating point numbers are compared and hashed
+enum Parts {
+    Whole(i128),
+    Fraction(i128, f64),
+    /// Infinite, NaN, or beyond the i128 range
+    Outside(f64),
+}
+
+impl Number {
+    fn parts(self) -> Parts {
+        match self {
+            Number::Exact { value, scale } => match 10_i128.checked_pow(scale as u32) {
+                Some(divisor) => {
+                    let fraction = value.rem_euclid(divisor);
+                    if fraction == 0 {
+                        Parts::Whole(value.div_euclid(divisor))
+                    } else {
+                        Parts::Fraction(value.div_euclid(divisor), fraction as f64 / divisor as f64)
+                    }
+                }
+                None => Number::Float(value as f64 / 10_f64.powi(scale as i32)).parts(),
+            },
+            Number::Float(f) => {
+                let floor = f.floor();
+                // 2^127 is the first float past the i128 range
+                if !f.is_finite() || floor < i128::MIN as f64 || floor >= i128::MAX as f64 {
+                    Parts::Outside(f)
+                } else if f == floor {
+                    Parts::Whole(floor as i128)
+                } else {
+                    Parts::Fraction(floor as i128, f - floor)
+                }
+            }
+        }
+    }
+}
+
+/// Total order of floats with NaN above everything and the zeros equal
+fn float_cmp(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+    }
+}
+
+fn number_cmp(a: Number, b: Number) -> Ordering {
+    // Exact values of different scales compare exactly: the whole parts
+    // first, then the fractions brought to the larger scale
+    if let (
+        Number::Exact { value: a, scale: a_scale },
+        Number::Exact { value: b, scale: b_scale },
+    ) = (a, b)
+    {
+        let max_scale = a_scale.max(b_scale) as u32;
+        if let (Some(a_divisor), Some(b_divisor)) = (
+            10_i128.checked_pow(a_scale as u32),
+            10_i128.checked_pow(b_scale as u32),
+        ) {
+            if 10_i128.checked_pow(max_scale).is_some() {
+                let widen = |fraction: i128, scale: u8| fraction * 10_i128.pow(max_scale - scale as u32);
+                return a.div_euclid(a_divisor).cmp(&b.div_euclid(b_divisor)).then_with(|| {
+                    widen(a.rem_euclid(a_divisor), a_scale).cmp(&widen(b.rem_euclid(b_divisor), b_scale))
+                });
+            }
+        }
+    }
+
+    match (a.parts(), b.parts()) {
+        (Parts::Outside(a), Parts::Outside(b)) => float_cmp(a, b),
+        (Parts::Outside(a), _) => float_cmp(a, 0.0),
+        (_, Parts::Outside(b)) => float_cmp(0.0, b),
+        (a, b) => {
+            let split = |parts: Parts| match parts {
+                Parts::Whole(whole) => (whole, 0.0),
+                Parts::Fraction(whole, fraction) => (whole, fraction),
+                Parts::Outside(_) => unreachable!(),
+            };
+            let ((a_whole, a_fraction), (b_whole, b_fraction)) = (split(a), split(b));
+            a_whole.cmp(&b_whole).then_with(|| float_cmp(a_fraction, b_fraction))
+        }
+    }
+}
+
+impl Value {
+    /// Numeric value of integer, decimal and floating point values
+    fn as_number(&self) -> Option<Number> {
+        let exact = |value: i128| Some(Number::Exact { value, scale: 0 });
+        match self {
+            Value::TinyInt(v) => exact(*v as i128),
+            Value::SmallInt(v) => exact(*v as i128),
+            Value::Integer(v) => exact(*v as i128),
+            Value::BigInt(v) => exact(*v as i128),
+            Value::HugeInt { high, low } => exact(((*high as i128) << 64) | (*low as u64 as i128)),
+            Value::Decimal { value, scale, .. } => Some(Number::Exact { value: *value, scale: *scale }),
+            Value::Float(v) => Some(Number::Float(*v as f64)),
+            Value::Double(v) => Some(Number::Float(*v)),
+            _ => None,
+        }
+    }
+
+    /// Position of the value's type in the total order; values of different
+    /// ranks compare by rank alone
+    fn order_rank(&self) -> u8 {
+        match self {
+            Value::Boolean(_) => 0,
+            Value::TinyInt(_)
+            | Value::SmallInt(_)
+            | Value::Integer(_)
+            | Value::BigInt(_)
+            | Value::HugeInt { .. }
+            | Value::Decimal { .. }
+            | Value::Float(_)
+            | Value::Double(_) => 1,
+            Value::Varchar(_) | Value::Char(_) => 2,
+            Value::Enum { .. } => 3,
+            Value::Blob(_) => 4,
+            Value::Date(_) => 5,
+            Value::Time(_) => 6,
+            Value::Timestamp(_) => 7,
+            Value::Interval { .. } => 8,
+            Value::UUID { .. } => 9,
+            Value::JSON(_) => 10,
+            Value::List(_) => 11,
+            Value::Struct(_) => 12,
+            Value::Map(_) => 13,
+            Value::Union { .. } => 14,
+            Value::Null => 15,
+        }
+    }
+
+    /// Compare two values in the engine's canonical total order
+    ///
+    /// Unlike `compare`, this never fails: every pair of values is ordered,
+    /// and the order is consistent with `OrderedValue`'s `Eq` and `Hash`. See
+    /// the `ordering` module for the rules.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        let rank = self.order_rank().cmp(&other.order_rank());
+        if rank != Ordering::Equal {
+            return rank;
+        }
+
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Varchar(a) | Value::Char(a), Value::Varchar(b) | Value::Char(b)) => a.cmp(b),
+            (Value::Enum { code: a, enum_type: a_type }, Value::Enum { code: b, enum_type: b_type }) => {
+                if a_type == b_type {
+                    a.cmp(b)
+                } else {
+                    a_type.label(*a).cmp(b_type.label(*b))
+                }
+            }
+            (Value::Blob(a), Value::Blob(b)) => a.cmp(b),
+            (Value::Date(a), Value::Date(b)) => a.cmp(b),
+            (Value::Time(a), Value::Time(b)) => a.cmp(b),
+            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+            (Value::Interval { .. }, Value::Interval { .. }) => {
+                interval_micros(self).cmp(&interval_micros(other))
+            }
+            (Value::UUID { high: a_high, low: a_low }, Value::UUID { high: b_high, low: b_low }) => {
+                (a_high, a_low).cmp(&(b_high, b_low))
+            }
+            (Value::JSON(a), Value::JSON(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => {
+                lexicographic(a.iter().zip(b), |(a, b)| a.total_cmp(b)).then(a.len().cmp(&b.len()))
+            }
+            (Value::Struct(a), Value::Struct(b)) => {
+                lexicographic(a.iter().zip(b), |((a_name, a), (b_name, b))| {
+                    a.total_cmp(b).then_with(|| a_name.cmp(b_name))
+                })
+                .then(a.len().cmp(&b.len()))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                lexicographic(sorted_entries(a).zip(sorted_entries(b)), |((a_key, a), (b_key, b))| {
+                    a_key.total_cmp(b_key).then_with(|| a.total_cmp(b))
+                })
+                .then(a.len().cmp(&b.len()))
+            }
+            (Value::Union { tag: a_tag, value: a }, Value::Union { tag: b_tag, value: b }) => {
+                a_tag.cmp(b_tag).then_with(|| a.total_cmp(b))
+            }
+            (Value::Null, Value::Null) => Ordering::Equal,
+            _ => match (self.as_number(), other.as_number()) {
+                (Some(a), Some(b)) => number_cmp(a, b),
+                _ => unreachable!("values of rank {} are all handled above", self.order_rank()),
+            },
+        }
+    }
+
+    /// Hash consistent with `total_cmp`: values it finds equal hash equally
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.order_rank().hash(state);
+        match self {
+            Value::Null => {}
+            Value::Boolean(b) => b.hash(state),
+            Value::Varchar(s) | Value::Char(s) | Value::JSON(s) => s.hash(state),
+            Value::Enum { code, enum_type } => enum_type.label(*code).hash(state),
+            Value::Blob(data) => data.hash(state),
+            Value::Date(d) => d.hash(state),
+            Value::Time(t) | Value::Timestamp(t) => t.hash(state),
+            Value::Interval { .. } => interval_micros(self).hash(state),
+            Value::UUID { high, low } => (high, low).hash(state),
+            Value::List(values) => {
+                values.len().hash(state);
+                values.iter().for_each(|value| value.total_hash(state));
+            }
+            Value::Struct(fields) => {
+                fields.len().hash(state);
+                for (name, value) in fields {
+                    name.hash(state);
+                    value.total_hash(state);
+                }
+            }
+            Value::Map(entries) => {
+                entries.len().hash(state);
+                for (key, value) in sorted_entries(entries) {
+                    key.total_hash(state);
+                    value.total_hash(state);
+                }
+            }
+            Value::Union { tag, value } => {
+                tag.hash(state);
+                value.total_hash(state);
+            }
+            _ => match self.as_number().map(Number::parts) {
+                Some(Parts::Whole(whole)) => (0u8, whole).hash(state),
+                Some(Parts::Fraction(whole, fraction)) => (1u8, whole, fraction.to_bits()).hash(state),
+                // All NaNs are equal, so they hash alike whatever their payload
+                Some(Parts::Outside(f)) if f.is_nan() => 2u8.hash(state),
+                Some(Parts::Outside(f)) => (3u8, f.to_bits()).hash(state),
+                None => unreachable!("values of rank {} are all handled above", self.order_rank()),
+            },
+        }
+    }
+
+    /// Bytes identifying a number's value, shared by every number `total_cmp`
+    /// finds equal to it whatever its type; None if the value is not a number
+    pub(crate) fn number_key(&self) -> Option<[u8; 25]> {
+        let (tag, whole, fraction) = match self.as_number()?.parts() {
+            Parts::Whole(whole) => (0u8, whole, 0u64),
+            Parts::Fraction(whole, fraction) => (1, whole, fraction.to_bits()),
+            // All NaNs are equal, so they share a key whatever their payload
+            Parts::Outside(f) if f.is_nan() => (2, 0, 0),
+            Parts::Outside(f) => (3, 0, f.to_bits()),
+        };
+        let mut key = [0; 25];
+        key[0] = tag;
+        key[1..17].copy_from_slice(&whole.to_le_bytes());
+        key[17..].copy_from_slice(&fraction.to_le_bytes());
+        Some(key)
+    }
+}
+
+/// Length of an interval value, counting a month as 30 days
+pub(crate) fn interval_micros(value: &Value) -> i128 {
+    match value {
+        Value::Interval { months, days, micros } => {
+            *months as i128 * MICROS_PER_MONTH + *days as i128 * MICROS_PER_DAY + *micros as i128
+        }
+        _ => 0,
+    }
+}
+
+/// Entries of a map in key order, the canonical order maps compare and hash in
+pub(crate) fn sorted_entries(entries: &[(Value, Value)]) -> impl Iterator<Item = &(Value, Value)> {
+    let mut sorted: Vec<&(Value, Value)> = entries.iter().collect();
+    sorted.sort_by(|(a_key, a), (b_key, b)| a_key.total_cmp(b_key).then_with(|| a.total_cmp(b)));
+    sorted.into_iter()
+}
+
+/// First non-equal ordering among the pairs
+fn lexicographic<T>(pairs: impl Iterator<Item = T>, mut cmp: impl FnMut(T) -> Ordering) -> Ordering {
+    pairs.map(&mut cmp).find(|ordering| *ordering != Ordering::Equal).unwrap_or(Ordering::Equal)
+}
+
+/// A value compared, ordered and hashed by `Value::total_cmp`, for use as a
+/// key in sorts, hash maps and sets
+#[derive(Debug, Clone)]
+pub struct OrderedValue(pub Value);
+
+impl OrderedValue {
+    /// Key for a row of values
+    pub fn row(values: &[Value]) -> Vec<OrderedValue> {
+        values.iter().cloned().map(OrderedValue).collect()
+    }
+
+    /// Approximate memory held by the key
+    pub fn memory_usage(&self) -> usize {
+        self.0.memory_usage()
+    }
+}
+
+impl From<Value> for OrderedValue {
+    fn from(value: Value) -> Self {
+        OrderedValue(value)
+    }
+}
+
+impl PartialEq for OrderedValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedValue {}
+
+impl PartialOrd for OrderedValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for OrderedValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.total_hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EnumType;
+    use std::collections::hash_map::DefaultHasher;
+    use std::sync::Arc;
+
+    fn hash_of(value: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        OrderedValue(value.clone()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn decimal(value: i128, scale: u8) -> Value {
+        Value::Decimal { value, scale, precision: 38 }
+    }
+
+    fn interval(months: i32, days: i32, micros: i64) -> Value {
+        Value::Interval { months, days, micros }
+    }
+
+    /// Groups of equal values, in ascending order
+    fn ordered_groups() -> Vec<Vec<Value>> {
+        let colors = Arc::new(EnumType::new("color".to_string(), vec!["red".to_string(), "green".to_string()]));
+        let text = |s: &str| Value::Varchar(s.to_string());
+        vec![
+            vec![Value::Boolean(false)],
+            vec![Value::Boolean(true)],
+            vec![Value::Double(f64::NEG_INFINITY), Value::Float(f32::NEG_INFINITY)],
+            vec![Value::HugeInt { high: i64::MIN, low: 0 }],
+            vec![Value::BigInt(i64::MIN)],
+            vec![Value::Integer(-2), decimal(-200, 2), Value::Double(-2.0)],
+            vec![decimal(-15, 1), Value::Double(-1.5), Value::Float(-1.5)],
+            vec![
+                Value::TinyInt(0),
+                Value::SmallInt(0),
+                Value::Integer(0),
+                Value::BigInt(0),
+                Value::HugeInt { high: 0, low: 0 },
+                decimal(0, 3),
+                Value::Float(-0.0),
+                Value::Double(0.0),
+                Value::Double(-0.0),
+            ],
+            vec![decimal(1, 1), Value::Double(0.1)],
+            vec![Value::TinyInt(1), Value::BigInt(1), decimal(100, 2), Value::Float(1.0), Value::Double(1.0)],
+            vec![decimal(10000000000000000000000000000000000001, 37)],
+            vec![Value::Double(1.0000000000000002)],
+            vec![Value::BigInt(9_007_199_254_740_992), Value::Double(9_007_199_254_740_992.0)],
+            vec![Value::BigInt(9_007_199_254_740_993)],
+            vec![Value::BigInt(i64::MAX)],
+            vec![Value::HugeInt { high: i64::MAX, low: -1 }],
+            vec![Value::Double(1e300)],
+            vec![Value::Double(f64::INFINITY)],
+            vec![Value::Double(f64::NAN), Value::Float(f32::NAN), Value::Double(-f64::NAN)],
+            vec![text("")],
+            vec![text("B"), Value::Char("B".to_string())],
+            vec![text("a")],
+            vec![text("ab")],
+            vec![Value::Enum { code: 0, enum_type: colors.clone() }],
+            vec![Value::Enum { code: 1, enum_type: colors }],
+            vec![Value::Blob(vec![0x00])],
+            vec![Value::Blob(vec![0x00, 0x00])],
+            vec![Value::Date(-1)],
+            vec![Value::Date(19723)],
+            vec![Value::Time(0)],
+            vec![Value::Timestamp(0)],
+            vec![interval(0, 29, 0)],
+            vec![interval(1, 0, 0), interval(0, 30, 0), interval(0, 0, 2_592_000_000_000)],
+            vec![Value::UUID { high: 1, low: 0 }],
+            vec![Value::JSON("[]".to_string())],
+            vec![Value::List(vec![])],
+            vec![Value::List(vec![Value::Integer(1), Value::Double(f64::NAN)])],
+            vec![Value::List(vec![Value::Integer(1), Value::Null]), Value::List(vec![Value::Double(1.0), Value::Null])],
+            vec![Value::List(vec![Value::Integer(2)])],
+            vec![Value::Struct(vec![("a".to_string(), Value::Integer(1))])],
+            vec![Value::Struct(vec![("b".to_string(), Value::Integer(1))])],
+            vec![
+                Value::Struct(vec![
+                    ("b".to_string(), Value::List(vec![Value::Integer(1)])),
+                    ("a".to_string(), Value::Integer(2)),
+                ]),
+                Value::Struct(vec![
+                    ("b".to_string(), Value::List(vec![Value::BigInt(1)])),
+                    ("a".to_string(), Value::Double(2.0)),
+                ]),
+            ],
+            vec![Value::Map(vec![(text("a"), Value::Integer(2))])],
+            vec![
+                Value::Map(vec![(text("a"), Value::Integer(2)), (text("k"), Value::Integer(1))]),
+                Value::Map(vec![(text("k"), Value::Integer(1)), (text("a"), Value::Integer(2))]),
+            ],
+            vec![Value::Map(vec![(text("k"), Value::Integer(1))])],
+            vec![Value::Union { tag: 0, value: Box::new(Value::Integer(5)) }],
+            vec![Value::Null],
+        ]
+    }
+
+    #[test]
+    fn test_total_cmp_matrix() {
+        let groups = ordered_groups();
+        for (i, left_group) in groups.iter().enumerate() {
+            for (j, right_group) in groups.iter().enumerate() {
+                for left in left_group {
+                    for right in right_group {
+                        assert_eq!(
+                            left.total_cmp(right),
+                            i.cmp(&j),
+                            "comparing {:?} with {:?}",
+                            left,
+                            right
+                        );
+                        if i == j {
+                            assert_eq!(hash_of(left), hash_of(right), "hashing {:?} and {:?}", left, right);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ordered_value_keys() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let values: Vec<Value> = ordered_groups().into_iter().rev().flatten().collect();
+        let hashed: HashSet<OrderedValue> = values.iter().cloned().map(OrderedValue).collect();
+        let sorted: BTreeSet<OrderedValue> = values.iter().cloned().map(OrderedValue).collect();
+        assert_eq!(hashed.len(), ordered_groups().len());
+        assert_eq!(sorted.len(), ordered_groups().len());
+        assert_eq!(sorted.first().map(|key| &key.0), Some(&Value::Boolean(false)));
+        assert_eq!(sorted.last().map(|key| &key.0), Some(&Value::Null));
+
+        assert_eq!(
+            OrderedValue::row(&[Value::Integer(1), Value::Varchar("x".to_string())]),
+            OrderedValue::row(&[Value::BigInt(1), Value::Char("x".to_string())])
+        );
+    }
+}
@@ -73,6 +73,7 @@ impl PhysicalType {
             PhysicalType::Time => Some(8),
             PhysicalType::Timestamp => Some(8),
             PhysicalType::UUID => Some(16),
+            PhysicalType::Enum => Some(4), // Position of the label in the type
             _ => None, // Variable size types
         }
     }
@@ -103,7 +104,6 @@ impl PhysicalType {
                 | PhysicalType::Union
                 | PhysicalType::JSON
                 | PhysicalType::Blob
-                | PhysicalType::Enum
         )
     }
 
@@ -193,7 +193,7 @@ pub fn logical_to_physical_type(
             value_type: _,
         } => PhysicalType::Map,
         LogicalType::Union(_) => PhysicalType::Union,
-        LogicalType::Enum { name: _, values: _ } => PhysicalType::Enum,
+        LogicalType::Enum(_) => PhysicalType::Enum,
         LogicalType::Null => PhysicalType::Invalid, // NULL uses Invalid as physical type
         LogicalType::Invalid => PhysicalType::Invalid,
     }
@@ -1,9 +1,10 @@
 use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::types::logical_type::LogicalType;
+use crate::types::logical_type::{EnumType, LogicalType};
 use crate::types::physical_type::PhysicalType;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt;
+use std::sync::Arc;
 
 /// Represents a single value in PrismDB with type information
 /// Values are the fundamental unit of data in the system
@@ -59,6 +60,8 @@ pub enum Value {
     Map(Vec<(Value, Value)>),
     /// Union value with tag and value
     Union { tag: usize, value: Box<Value> },
+    /// Enum value: the code of its label in its type
+    Enum { code: u32, enum_type: Arc<EnumType> },
 }
 
 impl Value {
@@ -127,6 +130,7 @@ impl Value {
                 }
             }
             Value::Union { value, .. } => LogicalType::Union(vec![value.get_type()]),
+            Value::Enum { enum_type, .. } => LogicalType::Enum(enum_type.clone()),
         }
     }
 
@@ -210,6 +214,7 @@ impl Value {
             Value::Varchar(value) => Ok(value.clone()),
             Value::Char(value) => Ok(value.clone()),
             Value::JSON(value) => Ok(value.clone()),
+            Value::Enum { code, enum_type } => Ok(enum_type.label(*code).to_string()),
             Value::Null => Err(PrismDBError::InvalidValue(
                 "Cannot extract string from NULL".to_string(),
             )),
@@ -338,7 +343,62 @@ impl Value {
                 }
             }
 
-            _ => Err(PrismDBError::InvalidType(format!(
+            // Other integer conversions, checked against the target's range
+            (from, to) if from.is_integral() && Self::is_cast_integer_type(to) => {
+                Self::integer_of_type(self.try_as_i64()?, to)
+            }
+            (from, to) if from.is_string() && Self::is_cast_integer_type(to) => {
+                let string_val = self.try_as_string()?;
+                let parsed = string_val.trim().parse::<i64>().map_err(|_| {
+                    PrismDBError::InvalidValue(format!("Cannot cast '{}' to {}", string_val, to))
+                })?;
+                Self::integer_of_type(parsed, to)
+            }
+            (from, LogicalType::Float) if from.is_numeric() => {
+                Ok(Value::Float(self.try_as_f64()? as f32))
+            }
+            (from, LogicalType::Double) if from.is_numeric() => Ok(Value::Double(self.try_as_f64()?)),
+            (from, LogicalType::Float) if from.is_string() => {
+                let string_val = self.try_as_string()?;
+                Ok(Value::Float(string_val.trim().parse().map_err(|_| {
+                    PrismDBError::InvalidValue(format!("Cannot cast '{}' to FLOAT", string_val))
+                })?))
+            }
+            // Temporal values to and from their ISO text
+            (from, LogicalType::Date | LogicalType::Time | LogicalType::Timestamp) if from.is_string() => {
+                let string_val = self.try_as_string()?;
+                let parsed = match target_type {
+                    LogicalType::Date => Self::parse_date(&string_val),
+                    LogicalType::Time => Self::parse_time(&string_val),
+                    _ => Self::parse_timestamp(&string_val),
+                };
+                parsed.ok_or_else(|| {
+                    PrismDBError::InvalidValue(format!("Cannot cast '{}' to {}", string_val, target_type))
+                })
+            }
+            (from, LogicalType::Enum(enum_type)) if from.is_string() => {
+                let label = self.try_as_string()?;
+                let code = enum_type.code(&label).ok_or_else(|| {
+                    PrismDBError::InvalidValue(format!(
+                        "'{}' is not a value of enum type {}",
+                        label, enum_type.name
+                    ))
+                })?;
+                Ok(Value::Enum {
+                    code,
+                    enum_type: enum_type.clone(),
+                })
+            }
+            (_, LogicalType::Varchar) => Ok(Value::Varchar(match self {
+                Value::Char(s) | Value::JSON(s) => s.clone(),
+                Value::Enum { code, enum_type } => enum_type.label(*code).to_string(),
+                Value::Date(days) => Self::format_date(*days),
+                Value::Time(micros) => Self::format_time(*micros),
+                Value::Timestamp(micros) => Self::format_timestamp(*micros),
+                value => value.to_string(),
+            })),
+
+            _ => Err(PrismDBError::TypeMismatch(format!(
                 "Cannot cast from {} to {}",
                 self.get_type(),
                 target_type
@@ -346,6 +406,26 @@ impl Value {
         }
     }
 
+    fn is_cast_integer_type(logical_type: &LogicalType) -> bool {
+        matches!(
+            logical_type,
+            LogicalType::TinyInt | LogicalType::SmallInt | LogicalType::Integer | LogicalType::BigInt
+        )
+    }
+
+    /// An integer as a value of the given integer type, or an error if it is out of range
+    fn integer_of_type(value: i64, logical_type: &LogicalType) -> PrismDBResult<Value> {
+        let overflow = || {
+            PrismDBError::Execution(format!("Integer overflow casting {} to {}", value, logical_type))
+        };
+        match logical_type {
+            LogicalType::TinyInt => i8::try_from(value).map(Value::TinyInt).map_err(|_| overflow()),
+            LogicalType::SmallInt => i16::try_from(value).map(Value::SmallInt).map_err(|_| overflow()),
+            LogicalType::Integer => i32::try_from(value).map(Value::Integer).map_err(|_| overflow()),
+            _ => Ok(Value::BigInt(value)),
+        }
+    }
+
     /// Compare two values for ordering
     pub fn compare(&self, other: &Value) -> PrismDBResult<Ordering> {
         match (self, other) {
@@ -517,7 +597,28 @@ impl Value {
                     .ok_or_else(|| PrismDBError::InvalidValue("Cannot compare NaN values".to_string()))
             }
 
-            _ => Err(PrismDBError::InvalidType(format!(
+            // Blobs compare byte by byte, a prefix before the longer blob
+            (Value::Blob(a), Value::Blob(b)) => Ok(a.cmp(b)),
+
+            // Enum values compare in definition order, also against the labels of their type
+            (Value::Enum { code: a, enum_type: a_type }, Value::Enum { code: b, enum_type: b_type }) => {
+                if a_type == b_type {
+                    Ok(a.cmp(b))
+                } else {
+                    Ok(a_type.label(*a).cmp(b_type.label(*b)))
+                }
+            }
+            (Value::Enum { code, enum_type }, Value::Varchar(label) | Value::Char(label)) => {
+                Ok(match enum_type.code(label) {
+                    Some(other) => code.cmp(&other),
+                    None => enum_type.label(*code).cmp(label.as_str()),
+                })
+            }
+            (Value::Varchar(_) | Value::Char(_), Value::Enum { .. }) => {
+                other.compare(self).map(Ordering::reverse)
+            }
+
+            _ => Err(PrismDBError::TypeMismatch(format!(
                 "Cannot compare {} and {}",
                 self.get_type(),
                 other.get_type()
@@ -551,7 +652,292 @@ impl Value {
             Value::Struct(fields) => fields.iter().map(|(_, v)| v.get_size()).sum(),
             Value::Map(pairs) => pairs.iter().map(|(k, v)| k.get_size() + v.get_size()).sum(),
             Value::Union { value, .. } => value.get_size(),
+            Value::Enum { .. } => 4,
+        }
+    }
+
+    /// Get the memory held by this value as a standalone `Value` (approximate)
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Value>() + self.get_size()
+    }
+
+    /// SQL literal that reads back as this value, as written by `.dump`
+    ///
+    /// Strings and temporal values are quoted (temporal values in ISO form,
+    /// which inserts into DATE, TIME and TIMESTAMP columns accept), numbers
+    /// and booleans are bare, and blobs use `x'...'` hex notation.
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Value::Null => "NULL".to_string(),
+            Value::Boolean(value) => if *value { "TRUE" } else { "FALSE" }.to_string(),
+            Value::TinyInt(value) => value.to_string(),
+            Value::SmallInt(value) => value.to_string(),
+            Value::Integer(value) => value.to_string(),
+            Value::BigInt(value) => value.to_string(),
+            Value::HugeInt { high, low } => (((*high as i128) << 64) | (*low as u64 as i128)).to_string(),
+            // Debug formatting keeps the decimal point on whole numbers (1.0)
+            Value::Float(value) if value.is_finite() => format!("{:?}", value),
+            Value::Double(value) if value.is_finite() => format!("{:?}", value),
+            Value::Float(_) | Value::Double(_) => Self::quote_sql(&self.to_string()),
+            Value::Decimal { value, scale, .. } => Self::format_decimal(*value, *scale),
+            Value::Varchar(value) | Value::Char(value) | Value::JSON(value) => Self::quote_sql(value),
+            Value::Enum { code, enum_type } => Self::quote_sql(enum_type.label(*code)),
+            Value::Date(days) => Self::quote_sql(&Self::format_date(*days)),
+            Value::Time(micros) => Self::quote_sql(&Self::format_time(*micros)),
+            Value::Timestamp(micros) => Self::quote_sql(&Self::format_timestamp(*micros)),
+            Value::Interval { months, days, micros } => format!(
+                "INTERVAL '{} months {} days {} microseconds'",
+                months, days, micros
+            ),
+            Value::UUID { high, low } => Self::quote_sql(&Self::format_uuid(*high, *low)),
+            Value::Blob(data) => format!("x'{}'", hex::encode_upper(data)),
+            Value::List(values) => format!(
+                "[{}]",
+                values.iter().map(Value::to_sql_literal).collect::<Vec<_>>().join(", ")
+            ),
+            Value::Struct(fields) => format!(
+                "{{{}}}",
+                fields
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", Self::quote_sql(name), value.to_sql_literal()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::Map(pairs) => format!(
+                "MAP {{{}}}",
+                pairs
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.to_sql_literal(), value.to_sql_literal()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::Union { value, .. } => value.to_sql_literal(),
+        }
+    }
+
+    /// Canonical JSON form of this value, used by JSON output and export
+    ///
+    /// Numbers and booleans map to their JSON counterparts and NULL to
+    /// `null`. Strings, enum labels, temporal values (in ISO form), UUIDs,
+    /// intervals and blobs (as hex) become strings; numbers JSON can't hold,
+    /// such as NaN or huge integers beyond 64 bits, become strings too. Lists
+    /// become arrays, structs objects with their fields in order, and maps
+    /// objects whose keys are the text of the map's keys. JSON values are
+    /// embedded as parsed, or as a string when they don't parse.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value as Json;
+        match self {
+            Value::Null => Json::Null,
+            Value::Boolean(value) => Json::Bool(*value),
+            Value::TinyInt(value) => Json::from(*value),
+            Value::SmallInt(value) => Json::from(*value),
+            Value::Integer(value) => Json::from(*value),
+            Value::BigInt(value) => Json::from(*value),
+            Value::HugeInt { high, low } => {
+                let value = ((*high as i128) << 64) | (*low as u64 as i128);
+                i64::try_from(value).map_or_else(|_| Json::String(value.to_string()), Json::from)
+            }
+            Value::Float(value) => Self::json_number(*value as f64),
+            Value::Double(value) => Self::json_number(*value),
+            Value::Decimal { value, scale, .. } => {
+                let text = Self::format_decimal(*value, *scale);
+                serde_json::from_str(&text).unwrap_or(Json::String(text))
+            }
+            Value::Varchar(value) | Value::Char(value) => Json::String(value.clone()),
+            Value::Enum { code, enum_type } => Json::String(enum_type.label(*code).to_string()),
+            Value::Date(days) => Json::String(Self::format_date(*days)),
+            Value::Time(micros) => Json::String(Self::format_time(*micros)),
+            Value::Timestamp(micros) => Json::String(Self::format_timestamp(*micros)),
+            Value::Interval { months, days, micros } => Json::String(format!(
+                "{} months {} days {} microseconds",
+                months, days, micros
+            )),
+            Value::UUID { high, low } => Json::String(Self::format_uuid(*high, *low)),
+            Value::JSON(text) => serde_json::from_str(text).unwrap_or_else(|_| Json::String(text.clone())),
+            Value::Blob(data) => Json::String(hex::encode_upper(data)),
+            Value::List(values) => Json::Array(values.iter().map(Value::to_json).collect()),
+            Value::Struct(fields) => Json::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.to_json()))
+                    .collect(),
+            ),
+            Value::Map(pairs) => Json::Object(
+                pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = match key.to_json() {
+                            Json::String(text) => text,
+                            other => other.to_string(),
+                        };
+                        (key, value.to_json())
+                    })
+                    .collect(),
+            ),
+            Value::Union { value, .. } => value.to_json(),
+        }
+    }
+
+    /// Read a value of `target_type` back from its `to_json` form
+    ///
+    /// Scalars are cast from the JSON number, boolean or string to the target
+    /// type; struct fields missing from an object are NULL and map keys are
+    /// cast from their text. A JSON array or object where the type doesn't
+    /// expect one is an error.
+    pub fn from_json(json: &serde_json::Value, target_type: &LogicalType) -> PrismDBResult<Value> {
+        use serde_json::Value as Json;
+        let mismatch = || {
+            PrismDBError::Type(format!("Cannot read JSON {} as {}", json, target_type))
+        };
+        match (json, target_type) {
+            (Json::Null, _) => Ok(Value::Null),
+            (_, LogicalType::JSON) => Ok(Value::JSON(json.to_string())),
+            (Json::Array(items), LogicalType::List(element_type)) => Ok(Value::List(
+                items
+                    .iter()
+                    .map(|item| Value::from_json(item, element_type))
+                    .collect::<PrismDBResult<_>>()?,
+            )),
+            (Json::Object(object), LogicalType::Struct(fields)) => Ok(Value::Struct(
+                fields
+                    .iter()
+                    .map(|(name, field_type)| {
+                        let value = match object.get(name) {
+                            Some(item) => Value::from_json(item, field_type)?,
+                            None => Value::Null,
+                        };
+                        Ok((name.clone(), value))
+                    })
+                    .collect::<PrismDBResult<_>>()?,
+            )),
+            (Json::Object(object), LogicalType::Map { key_type, value_type }) => Ok(Value::Map(
+                object
+                    .iter()
+                    .map(|(key, item)| {
+                        Ok((
+                            Value::Varchar(key.clone()).cast_to(key_type)?,
+                            Value::from_json(item, value_type)?,
+                        ))
+                    })
+                    .collect::<PrismDBResult<_>>()?,
+            )),
+            (Json::Array(_) | Json::Object(_), _) => Err(mismatch()),
+            (_, LogicalType::List(_) | LogicalType::Struct(_) | LogicalType::Map { .. }) => Err(mismatch()),
+            (_, LogicalType::Blob) => {
+                let text = json.as_str().ok_or_else(mismatch)?;
+                hex::decode(text).map(Value::Blob).map_err(|_| mismatch())
+            }
+            (Json::Bool(value), _) => Value::Boolean(*value).cast_to(target_type),
+            (Json::Number(number), _) => match number.as_i64() {
+                Some(value) => Value::BigInt(value).cast_to(target_type),
+                None => Value::Double(number.as_f64().ok_or_else(mismatch)?).cast_to(target_type),
+            },
+            (Json::String(text), _) => Value::Varchar(text.clone()).cast_to(target_type),
+        }
+    }
+
+    /// A float as a JSON number, or as a string when it isn't finite
+    fn json_number(value: f64) -> serde_json::Value {
+        serde_json::Number::from_f64(value)
+            .map_or_else(|| serde_json::Value::String(value.to_string()), serde_json::Value::Number)
+    }
+
+    /// Hyphenated lowercase form of a UUID
+    fn format_uuid(high: u64, low: u64) -> String {
+        let hex = format!("{:016x}{:016x}", high, low);
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+
+    /// Single-quoted string literal, escaping quotes and backslashes
+    fn quote_sql(text: &str) -> String {
+        format!("'{}'", text.replace('\\', "\\\\").replace('\'', "''"))
+    }
+
+    /// A scaled decimal as a plain number, e.g. -0.05 for value -5 at scale 2
+    fn format_decimal(value: i128, scale: u8) -> String {
+        if scale == 0 {
+            return value.to_string();
+        }
+        let divisor = 10_i128.pow(scale as u32);
+        format!(
+            "{}{}.{:0width$}",
+            if value < 0 { "-" } else { "" },
+            (value / divisor).abs(),
+            (value % divisor).abs(),
+            width = scale as usize
+        )
+    }
+
+    fn epoch() -> chrono::NaiveDateTime {
+        chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    /// Drop trailing zeros from the fraction of a formatted time, and the
+    /// fraction itself when it is zero
+    fn trim_fraction(mut text: String) -> String {
+        if text.contains('.') {
+            while text.ends_with('0') {
+                text.pop();
+            }
+            if text.ends_with('.') {
+                text.pop();
+            }
         }
+        text
+    }
+
+    /// ISO date (YYYY-MM-DD) of a day count since 1970-01-01
+    fn format_date(days: i32) -> String {
+        (Self::epoch().date() + chrono::Duration::days(days as i64))
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    /// HH:MM:SS[.ffffff] of microseconds since midnight
+    fn format_time(micros: i64) -> String {
+        let time = chrono::NaiveTime::MIN + chrono::Duration::microseconds(micros);
+        Self::trim_fraction(time.format("%H:%M:%S%.6f").to_string())
+    }
+
+    /// YYYY-MM-DD HH:MM:SS[.ffffff] of microseconds since the epoch
+    fn format_timestamp(micros: i64) -> String {
+        let timestamp = Self::epoch() + chrono::Duration::microseconds(micros);
+        Self::trim_fraction(timestamp.format("%Y-%m-%d %H:%M:%S%.6f").to_string())
+    }
+
+    fn parse_date(text: &str) -> Option<Value> {
+        let date = chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok()?;
+        Some(Value::Date(date.signed_duration_since(Self::epoch().date()).num_days() as i32))
+    }
+
+    fn parse_time(text: &str) -> Option<Value> {
+        let time = ["%H:%M:%S%.f", "%H:%M"]
+            .iter()
+            .find_map(|format| chrono::NaiveTime::parse_from_str(text.trim(), format).ok())?;
+        (time - chrono::NaiveTime::MIN).num_microseconds().map(Value::Time)
+    }
+
+    fn parse_timestamp(text: &str) -> Option<Value> {
+        let text = text.trim();
+        let timestamp = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+            .iter()
+            .find_map(|format| chrono::NaiveDateTime::parse_from_str(text, format).ok())
+            .or_else(|| {
+                chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                    .ok()
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+            })?;
+        (timestamp - Self::epoch()).num_microseconds().map(Value::Timestamp)
     }
 }
 
@@ -569,6 +955,7 @@ impl fmt::Display for Value {
             Value::Double(value) => write!(f, "{}", value),
             Value::Varchar(value) => write!(f, "'{}'", value),
             Value::Char(value) => write!(f, "'{}'", value),
+            Value::Enum { code, enum_type } => write!(f, "'{}'", enum_type.label(*code)),
             Value::Decimal { value, scale, .. } => {
                 let divisor = 10_i128.pow(*scale as u32);
                 let integer_part = value / divisor;
@@ -660,6 +1047,51 @@ mod tests {
         assert_eq!(int_from_str.try_as_i32().unwrap(), 123);
     }
 
+    #[test]
+    fn test_temporal_string_casts() {
+        let date = Value::varchar("2024-03-05".to_string()).cast_to(&LogicalType::Date).unwrap();
+        assert_eq!(date, Value::Date(19787));
+        assert_eq!(date.cast_to(&LogicalType::Varchar).unwrap(), Value::varchar("2024-03-05".to_string()));
+
+        let time = Value::varchar("12:34:56.5".to_string()).cast_to(&LogicalType::Time).unwrap();
+        assert_eq!(time, Value::Time(45_296_500_000));
+        assert_eq!(time.cast_to(&LogicalType::Varchar).unwrap(), Value::varchar("12:34:56.5".to_string()));
+
+        let timestamp = Value::varchar("1970-01-02T00:00:01".to_string())
+            .cast_to(&LogicalType::Timestamp)
+            .unwrap();
+        assert_eq!(timestamp, Value::Timestamp(86_401_000_000));
+        assert_eq!(
+            timestamp.cast_to(&LogicalType::Varchar).unwrap(),
+            Value::varchar("1970-01-02 00:00:01".to_string())
+        );
+
+        assert!(Value::varchar("2024-13-01".to_string()).cast_to(&LogicalType::Date).is_err());
+        assert_eq!(
+            Value::boolean(true).cast_to(&LogicalType::Varchar).unwrap(),
+            Value::varchar("true".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sql_literals() {
+        assert_eq!(Value::boolean(false).to_sql_literal(), "FALSE");
+        assert_eq!(Value::Double(2.0).to_sql_literal(), "2.0");
+        assert_eq!(Value::Double(f64::INFINITY).to_sql_literal(), "'inf'");
+        assert_eq!(Value::Decimal { value: -5, scale: 2, precision: 4 }.to_sql_literal(), "-0.05");
+        assert_eq!(Value::Decimal { value: 42, scale: 0, precision: 4 }.to_sql_literal(), "42");
+        assert_eq!(Value::varchar("it's \\".to_string()).to_sql_literal(), "'it''s \\\\'");
+        assert_eq!(Value::Date(-1).to_sql_literal(), "'1969-12-31'");
+        assert_eq!(Value::Time(1).to_sql_literal(), "'00:00:00.000001'");
+        assert_eq!(Value::Timestamp(0).to_sql_literal(), "'1970-01-01 00:00:00'");
+        assert_eq!(Value::Blob(vec![0x00, 0xab]).to_sql_literal(), "x'00AB'");
+        assert_eq!(Value::HugeInt { high: -1, low: -2 }.to_sql_literal(), "-2");
+        assert_eq!(
+            Value::list(vec![Value::integer(1), Value::Null]).to_sql_literal(),
+            "[1, NULL]"
+        );
+    }
+
     #[test]
     fn test_value_comparison() {
         let int1 = Value::integer(10);
@@ -1,8 +1,13 @@
 use crate::common::error::{PrismDBError, PrismDBResult};
-use crate::types::logical_type::LogicalType;
+use crate::types::logical_type::{LogicalType, TypeUtils};
 use crate::types::physical_type::PhysicalType;
 use crate::types::value::Value;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Smallest string vector considered for dictionary encoding
+const DICTIONARY_MIN_COUNT: usize = 32;
 
 /// A validity mask for tracking null values in a vector
 /// Uses a bitset for efficient storage
@@ -294,8 +299,62 @@ impl SelectionVector {
     }
 }
 
+/// Distinct strings of a dictionary-encoded vector
+/// Rows store a u32 code into `values`; clones of the vector share the dictionary
+#[derive(Debug, Clone, Default)]
+pub struct StringDictionary {
+    values: Vec<Arc<str>>,
+    codes: HashMap<Arc<str>, u32>,
+}
+
+impl StringDictionary {
+    /// Create an empty dictionary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the code for a string, adding it if it is not in the dictionary yet
+    pub fn intern(&mut self, value: &str) -> u32 {
+        if let Some(&code) = self.codes.get(value) {
+            return code;
+        }
+        let code = self.values.len() as u32;
+        let value: Arc<str> = Arc::from(value);
+        self.values.push(value.clone());
+        self.codes.insert(value, code);
+        code
+    }
+
+    /// Get the string for a code
+    pub fn get(&self, code: u32) -> Option<&str> {
+        self.values.get(code as usize).map(|v| v.as_ref())
+    }
+
+    /// Get the number of distinct strings
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check if the dictionary is empty
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Approximate heap size of the dictionary in bytes
+    pub fn size_bytes(&self) -> usize {
+        let strings: usize = self.values.iter().map(|v| v.len()).sum();
+        let entry = std::mem::size_of::<Arc<str>>();
+        strings + self.values.capacity() * entry + self.codes.capacity() * (entry + 4)
+    }
+}
+
 /// A Vector represents columnar data in PrismDB
 /// It contains the actual data, a validity mask, and optionally a selection vector
+///
+/// The data and validity buffers are shared between clones and copied on the first
+/// write. A vector with a selection is a view: entry `i` reads row `selection[i]` of
+/// the shared buffers, so slicing a fixed-width vector never copies its data. Views are
+/// materialized by [`Vector::flatten`], which every mutating method calls first.
 #[derive(Debug, Clone)]
 pub struct Vector {
     /// The logical type of this vector
@@ -303,15 +362,17 @@ pub struct Vector {
     /// The physical type for storage
     physical_type: PhysicalType,
     /// The actual data (stored as raw bytes)
-    data: Vec<u8>,
-    /// Validity mask for null values
-    validity: ValidityMask,
-    /// Optional selection vector for filtering
-    selection: Option<SelectionVector>,
+    data: Arc<Vec<u8>>,
+    /// Validity mask for null values, indexed by physical row
+    validity: Arc<ValidityMask>,
+    /// Rows of the shared buffers this vector exposes, if it is a view
+    selection: Option<Arc<SelectionVector>>,
     /// Number of entries in the vector
     count: usize,
     /// Capacity of the vector
     capacity: usize,
+    /// Shared dictionary when string values are stored as codes
+    dictionary: Option<Arc<StringDictionary>>,
 }
 
 impl Vector {
@@ -323,11 +384,12 @@ impl Vector {
         Self {
             logical_type,
             physical_type,
-            data: vec![0u8; element_size * capacity],
-            validity: ValidityMask::all_valid(capacity),
+            data: Arc::new(vec![0u8; element_size * capacity]),
+            validity: Arc::new(ValidityMask::all_valid(capacity)),
             selection: None,
             count: 0,
             capacity,
+            dictionary: None,
         }
     }
 
@@ -398,6 +460,78 @@ impl Vector {
                     scale: *scale,
                 })
             }
+            // Numeric widening, e.g. INTEGER into a BIGINT or DOUBLE vector
+            (
+                _,
+                LogicalType::SmallInt
+                | LogicalType::Integer
+                | LogicalType::BigInt
+                | LogicalType::Float
+                | LogicalType::Double,
+            ) if value.get_type().can_implicitly_cast_to(target_type) => {
+                let integer = match value {
+                    Value::TinyInt(v) => Some(*v as i64),
+                    Value::SmallInt(v) => Some(*v as i64),
+                    Value::Integer(v) => Some(*v as i64),
+                    Value::BigInt(v) => Some(*v),
+                    _ => None,
+                };
+                Ok(match (integer, target_type) {
+                    (Some(v), LogicalType::SmallInt) => Value::SmallInt(v as i16),
+                    (Some(v), LogicalType::Integer) => Value::Integer(v as i32),
+                    (Some(v), LogicalType::BigInt) => Value::BigInt(v),
+                    (Some(v), LogicalType::Float) => Value::Float(v as f32),
+                    (Some(v), _) => Value::Double(v as f64),
+                    (None, _) => Value::Double(value.try_as_f64()?),
+                })
+            }
+            (Value::Char(s), LogicalType::Varchar) => Ok(Value::Varchar(s.clone())),
+            (Value::Varchar(s) | Value::Char(s), LogicalType::Char { .. }) => {
+                Ok(Value::Char(s.clone()))
+            }
+            // Integer literals into narrower integer columns, checked against their range
+            (
+                Value::Integer(_) | Value::BigInt(_),
+                LogicalType::TinyInt | LogicalType::SmallInt | LogicalType::Integer,
+            ) => value.cast_to(target_type),
+            (Value::Double(d), LogicalType::Float) => Ok(Value::Float(*d as f32)),
+            // ISO text into temporal columns, e.g. '2024-03-05' into a DATE
+            (
+                Value::Varchar(_) | Value::Char(_),
+                LogicalType::Date | LogicalType::Time | LogicalType::Timestamp,
+            ) => value.cast_to(target_type),
+            // Labels into enum columns, which reject anything else
+            (Value::Varchar(_) | Value::Char(_), LogicalType::Enum(_)) => value.cast_to(target_type),
+            (Value::Enum { .. }, LogicalType::Varchar) => value.cast_to(target_type),
+            // Text into JSON columns is kept as written
+            (Value::Varchar(s) | Value::Char(s), LogicalType::JSON) => Ok(Value::JSON(s.clone())),
+            // Lists coerce element by element
+            (Value::List(values), LogicalType::List(element_type)) => values
+                .iter()
+                .map(|value| {
+                    if value.is_null() || value.get_type() == **element_type {
+                        Ok(value.clone())
+                    } else {
+                        Self::try_coerce_value(value, element_type)
+                    }
+                })
+                .collect::<PrismDBResult<Vec<_>>>()
+                .map(Value::List),
+            // Maps coerce key by key and value by value
+            (Value::Map(entries), LogicalType::Map { key_type, value_type }) => {
+                let coerce = |value: &Value, target: &LogicalType| {
+                    if value.is_null() || value.get_type() == *target {
+                        Ok(value.clone())
+                    } else {
+                        Self::try_coerce_value(value, target)
+                    }
+                };
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((coerce(key, key_type)?, coerce(value, value_type)?)))
+                    .collect::<PrismDBResult<Vec<_>>>()
+                    .map(Value::Map)
+            }
             // If no coercion is available, return error
             _ => Err(PrismDBError::InvalidType(format!(
                 "Cannot coerce value type {} to {}",
@@ -408,25 +542,58 @@ impl Vector {
     }
 
     /// Create a vector from a slice of values
+    ///
+    /// The vector takes the common type of the non-NULL values, and an all-NULL slice
+    /// defaults to INTEGER. Use [`Vector::from_values_with_type`] when the type is
+    /// known up front.
     pub fn from_values(values: &[Value]) -> PrismDBResult<Self> {
+        let logical_type = Self::infer_type(values);
+        Self::from_values_with_type(values, &logical_type)
+    }
+
+    /// Common type of the non-NULL values
+    ///
+    /// Values without a common type keep the first one, so coercing the rest may fail.
+    fn infer_type(values: &[Value]) -> LogicalType {
+        let mut types = values.iter().filter(|v| !v.is_null()).map(|v| v.get_type());
+        let Some(first) = types.next() else {
+            return LogicalType::Integer;
+        };
+        types
+            .try_fold(first.clone(), |common, t| TypeUtils::get_max_type(&common, &t))
+            .unwrap_or(first)
+    }
+
+    /// Create a vector of the given type from a slice of values
+    ///
+    /// NULLs are left invalid in the validity mask; every other value is coerced to the
+    /// vector's type and marked valid.
+    pub fn from_values_with_type(
+        values: &[Value],
+        logical_type: &LogicalType,
+    ) -> PrismDBResult<Self> {
         if values.is_empty() {
             return Err(PrismDBError::InvalidValue(
                 "Cannot create vector from empty values".to_string(),
             ));
         }
 
-        // Find first non-NULL value to determine type
-        // NULL values have type Invalid, so we need to skip them
-        let logical_type = values
-            .iter()
-            .find(|v| !v.is_null())
-            .map(|v| v.get_type())
-            .unwrap_or(LogicalType::Invalid);
+        let logical_type = logical_type.clone();
         let physical_type = logical_type.get_physical_type();
         let element_size = physical_type.get_size().unwrap_or(0);
 
+        // Low cardinality strings are stored as codes into a shared dictionary
+        let dictionary = match logical_type {
+            LogicalType::Varchar | LogicalType::Char { .. } if Self::is_low_cardinality(values) => {
+                Some(Arc::new(StringDictionary::new()))
+            }
+            _ => None,
+        };
+
         // For variable-size types, estimate space needed
-        let data_size = if element_size == 0 {
+        let data_size = if dictionary.is_some() {
+            4 * values.len()
+        } else if element_size == 0 {
             // Variable size type - estimate space needed
             match logical_type {
                 LogicalType::Varchar | LogicalType::Char { .. } => {
@@ -451,30 +618,79 @@ impl Vector {
         let mut vector = Self {
             logical_type: logical_type.clone(),
             physical_type,
-            data: vec![0u8; data_size],
-            validity: ValidityMask::new(values.len()),
+            data: Arc::new(vec![0u8; data_size]),
+            validity: Arc::new(ValidityMask::all_null(values.len())),
             selection: None,
             count: values.len(),
             capacity: values.len(),
+            dictionary,
         };
 
+        // set_value coerces each value and marks it valid
         for (i, value) in values.iter().enumerate() {
-            if value.is_null() {
-                vector.validity.set_valid(i, false);
-            } else {
-                // Try type coercion if types don't match exactly
-                let coerced_value = if value.get_type() != logical_type {
-                    Self::try_coerce_value(value, &logical_type)?
-                } else {
-                    value.clone()
-                };
-                vector.set_value(i, &coerced_value)?;
+            if !value.is_null() {
+                vector.set_value(i, value)?;
             }
         }
 
         Ok(vector)
     }
 
+    /// Check whether at most half of the non-NULL strings are distinct
+    fn is_low_cardinality(values: &[Value]) -> bool {
+        let strings = values.iter().filter_map(|v| match v {
+            Value::Varchar(s) | Value::Char(s) => Some(s.as_str()),
+            _ => None,
+        });
+        let mut distinct = HashSet::new();
+        let mut total = 0;
+        for s in strings {
+            distinct.insert(s);
+            total += 1;
+        }
+        total >= DICTIONARY_MIN_COUNT && distinct.len() * 2 <= total
+    }
+
+    /// Check if string values are stored as dictionary codes
+    pub fn is_dictionary_encoded(&self) -> bool {
+        self.dictionary.is_some()
+    }
+
+    /// Get the shared dictionary of a dictionary-encoded vector
+    pub fn get_dictionary(&self) -> Option<&StringDictionary> {
+        self.dictionary.as_deref()
+    }
+
+    /// Convert a dictionary-encoded vector back to plain string storage
+    pub fn decode_dictionary(&mut self) -> PrismDBResult<()> {
+        if self.dictionary.is_none() {
+            return Ok(());
+        }
+        self.flatten();
+        let values = self.iter().collect::<PrismDBResult<Vec<_>>>()?;
+        self.dictionary = None;
+        self.data_mut().clear();
+        for (i, value) in values.iter().enumerate() {
+            if let Value::Varchar(s) | Value::Char(s) = value {
+                self.store_string(i, s);
+            }
+        }
+        self.data_mut().shrink_to_fit();
+        Ok(())
+    }
+
+    /// Approximate heap size of the vector in bytes
+    ///
+    /// A view is charged for the share of the underlying buffers it selects.
+    pub fn memory_usage(&self) -> usize {
+        let buffers = self.data.capacity() + self.validity.count().div_ceil(64) * 8;
+        let buffers = match &self.selection {
+            Some(_) => buffers * self.count / self.validity.count().max(1),
+            None => buffers,
+        };
+        buffers + self.dictionary.as_ref().map_or(0, |d| d.size_bytes())
+    }
+
     /// Get the logical type of this vector
     pub fn get_type(&self) -> &LogicalType {
         &self.logical_type
@@ -485,7 +701,15 @@ impl Vector {
         &self.physical_type
     }
 
+    /// Data buffer for writing, copied first if it is shared
+    fn data_mut(&mut self) -> &mut Vec<u8> {
+        Arc::make_mut(&mut self.data)
+    }
+
     /// Get the validity mask for this vector
+    ///
+    /// The mask is indexed by physical row; use [`Vector::is_valid`] for the entries of
+    /// a view.
     pub fn get_validity_mask(&self) -> &ValidityMask {
         &self.validity
     }
@@ -507,11 +731,12 @@ impl Vector {
 
     /// Resize the vector
     pub fn resize(&mut self, new_count: usize) -> PrismDBResult<()> {
+        self.flatten();
         if new_count > self.capacity {
             self.reserve(new_count)?;
         }
 
-        self.validity.resize(new_count);
+        Arc::make_mut(&mut self.validity).resize(new_count);
         self.count = new_count;
         Ok(())
     }
@@ -521,26 +746,30 @@ impl Vector {
         if new_capacity <= self.capacity {
             return Ok(());
         }
+        self.flatten();
 
         let element_size = self.physical_type.get_size().unwrap_or(0);
         if element_size > 0 {
-            self.data.resize(element_size * new_capacity, 0);
+            self.data_mut().resize(element_size * new_capacity, 0);
+        } else if self.dictionary.is_some() {
+            self.data_mut().resize(4 * new_capacity, 0);
         } else {
             // For variable-size types (VARCHAR, etc.), allocate reasonable space
             // Estimate: 16 bytes average string length + 4 bytes length prefix
             let estimated_size = new_capacity * 20;
             if estimated_size > self.data.len() {
-                self.data.resize(estimated_size, 0);
+                self.data_mut().resize(estimated_size, 0);
             }
         }
 
-        self.validity.resize(new_capacity);
+        Arc::make_mut(&mut self.validity).resize(new_capacity);
         self.capacity = new_capacity;
         Ok(())
     }
 
     /// Set a value at a specific index
     pub fn set_value(&mut self, index: usize, value: &Value) -> PrismDBResult<()> {
+        self.flatten();
         if index >= self.capacity {
             return Err(PrismDBError::InvalidValue(format!(
                 "Index {} exceeds vector capacity {}",
@@ -549,7 +778,7 @@ impl Vector {
         }
 
         if value.is_null() {
-            self.validity.set_valid(index, false);
+            Arc::make_mut(&mut self.validity).set_valid(index, false);
             return Ok(());
         }
 
@@ -560,7 +789,7 @@ impl Vector {
             value.clone()
         };
 
-        self.validity.set_valid(index, true);
+        Arc::make_mut(&mut self.validity).set_valid(index, true);
 
         // Use the coerced value for storage
         let value = &coerced_value;
@@ -579,13 +808,24 @@ impl Vector {
                 let bytes = value.to_le_bytes();
                 let offset = index * 16;
                 if offset + 16 <= self.data.len() {
-                    self.data[offset..offset + 16].copy_from_slice(&bytes);
+                    self.data_mut()[offset..offset + 16].copy_from_slice(&bytes);
                 }
             }
-            Value::Varchar(s) | Value::Char(s) => self.store_string(index, s),
+            Value::Varchar(s) | Value::Char(s) => match self.dictionary.as_mut() {
+                Some(dictionary) => {
+                    let code = Arc::make_mut(dictionary).intern(s);
+                    self.store_code(index, code);
+                }
+                None => self.store_string(index, s),
+            },
+            Value::JSON(s) => self.store_string(index, s),
             Value::Date(v) => self.store_numeric(index, *v as u64),
             Value::Time(v) => self.store_numeric(index, *v as u64),
             Value::Timestamp(v) => self.store_numeric(index, *v as u64),
+            Value::List(values) => self.store_nested(index, values)?,
+            Value::Map(entries) => self.store_nested(index, entries)?,
+            Value::Blob(bytes) => self.store_bytes(index, bytes),
+            Value::Enum { code, .. } => self.store_numeric(index, *code as u64),
             _ => {
                 return Err(PrismDBError::InvalidType(format!(
                     "Unsupported value type for vector storage: {:?}",
@@ -607,18 +847,18 @@ impl Vector {
 
         let offset = index * element_size;
         match element_size {
-            1 => self.data[offset] = value as u8,
+            1 => self.data_mut()[offset] = value as u8,
             2 => {
                 let bytes = (value as u16).to_le_bytes();
-                self.data[offset..offset + 2].copy_from_slice(&bytes);
+                self.data_mut()[offset..offset + 2].copy_from_slice(&bytes);
             }
             4 => {
                 let bytes = (value as u32).to_le_bytes();
-                self.data[offset..offset + 4].copy_from_slice(&bytes);
+                self.data_mut()[offset..offset + 4].copy_from_slice(&bytes);
             }
             8 => {
                 let bytes = value.to_le_bytes();
-                self.data[offset..offset + 8].copy_from_slice(&bytes);
+                self.data_mut()[offset..offset + 8].copy_from_slice(&bytes);
             }
             _ => {}
         }
@@ -634,7 +874,7 @@ impl Vector {
 
         let offset = index * element_size;
         let bytes = value.to_le_bytes();
-        self.data[offset..offset + 4].copy_from_slice(&bytes);
+        self.data_mut()[offset..offset + 4].copy_from_slice(&bytes);
     }
 
     /// Store a double value
@@ -647,7 +887,7 @@ impl Vector {
 
         let offset = index * element_size;
         let bytes = value.to_le_bytes();
-        self.data[offset..offset + 8].copy_from_slice(&bytes);
+        self.data_mut()[offset..offset + 8].copy_from_slice(&bytes);
     }
 
     /// Store a tinyint value
@@ -659,7 +899,7 @@ impl Vector {
         }
 
         let offset = index * element_size;
-        self.data[offset] = value as u8;
+        self.data_mut()[offset] = value as u8;
     }
 
     /// Store a smallint value
@@ -672,7 +912,7 @@ impl Vector {
 
         let offset = index * element_size;
         let bytes = value.to_le_bytes();
-        self.data[offset..offset + 2].copy_from_slice(&bytes);
+        self.data_mut()[offset..offset + 2].copy_from_slice(&bytes);
     }
 
     /// Store a hugeint value
@@ -685,12 +925,35 @@ impl Vector {
 
         let offset = index * element_size;
         let bytes = value.to_le_bytes();
-        self.data[offset..offset + 16].copy_from_slice(&bytes);
+        self.data_mut()[offset..offset + 16].copy_from_slice(&bytes);
+    }
+
+    /// Store a dictionary code
+    fn store_code(&mut self, index: usize, code: u32) {
+        let offset = index * 4;
+        if offset + 4 > self.data.len() {
+            self.data_mut().resize(offset + 4, 0);
+        }
+        self.data_mut()[offset..offset + 4].copy_from_slice(&code.to_le_bytes());
     }
 
     /// Store a string value (simplified - stores length + data sequentially)
     #[allow(dead_code)]
     fn store_string(&mut self, index: usize, string: &str) {
+        self.store_bytes(index, string.as_bytes());
+    }
+
+    /// Store the elements of a list or the entries of a map as a length-prefixed
+    /// entry, like a string
+    fn store_nested<T: Serialize + ?Sized>(&mut self, index: usize, values: &T) -> PrismDBResult<()> {
+        let bytes = bincode::serde::encode_to_vec(values, bincode::config::standard())
+            .map_err(|e| PrismDBError::Internal(format!("Failed to encode value: {}", e)))?;
+        self.store_bytes(index, &bytes);
+        Ok(())
+    }
+
+    /// Store a variable-size entry (length + data sequentially)
+    fn store_bytes(&mut self, index: usize, string_bytes: &[u8]) {
         // Calculate offset by summing sizes of all previous strings
         let mut offset = 0;
         for i in 0..index {
@@ -705,21 +968,20 @@ impl Vector {
             }
         }
 
-        let string_bytes = string.as_bytes();
         let required_space = 4 + string_bytes.len();
 
         // Grow buffer if needed
         if offset + required_space > self.data.len() {
             let new_size = (offset + required_space).max(self.data.len() * 2);
-            self.data.resize(new_size, 0);
+            self.data_mut().resize(new_size, 0);
         }
 
         // Store length as u32
         let len_bytes = (string_bytes.len() as u32).to_le_bytes();
-        self.data[offset..offset + 4].copy_from_slice(&len_bytes);
+        self.data_mut()[offset..offset + 4].copy_from_slice(&len_bytes);
 
         // Store actual string data
-        self.data[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
+        self.data_mut()[offset + 4..offset + 4 + string_bytes.len()].copy_from_slice(string_bytes);
     }
 
     /// Get a value at a specific index
@@ -731,6 +993,7 @@ impl Vector {
             )));
         }
 
+        let index = self.row(index);
         if !self.validity.is_valid(index) {
             return Ok(Value::Null);
         }
@@ -760,9 +1023,17 @@ impl Vector {
             }
             LogicalType::Varchar => Ok(Value::Varchar(self.extract_string(index)?)),
             LogicalType::Char { .. } => Ok(Value::Char(self.extract_string(index)?)),
+            LogicalType::JSON => Ok(Value::JSON(self.extract_string(index)?)),
             LogicalType::Date => Ok(Value::Date(self.extract_numeric(index) as i32)),
             LogicalType::Time => Ok(Value::Time(self.extract_numeric(index) as i64)),
             LogicalType::Timestamp => Ok(Value::Timestamp(self.extract_numeric(index) as i64)),
+            LogicalType::List(_) => Ok(Value::List(self.extract_nested(index)?)),
+            LogicalType::Map { .. } => Ok(Value::Map(self.extract_nested(index)?)),
+            LogicalType::Blob => Ok(Value::Blob(self.extract_bytes(index).to_vec())),
+            LogicalType::Enum(enum_type) => Ok(Value::Enum {
+                code: self.extract_numeric(index) as u32,
+                enum_type: enum_type.clone(),
+            }),
             _ => Err(PrismDBError::InvalidType(format!(
                 "Unsupported vector type for value extraction: {:?}",
                 self.logical_type
@@ -827,6 +1098,28 @@ impl Vector {
 
     /// Extract a string value
     fn extract_string(&self, index: usize) -> PrismDBResult<String> {
+        if let Some(dictionary) = &self.dictionary {
+            let offset = index * 4;
+            let mut code_bytes = [0u8; 4];
+            code_bytes.copy_from_slice(&self.data[offset..offset + 4]);
+            let code = u32::from_le_bytes(code_bytes);
+            return dictionary.get(code).map(str::to_string).ok_or_else(|| {
+                PrismDBError::Internal(format!("Dictionary code {} out of range", code))
+            });
+        }
+
+        Ok(String::from_utf8_lossy(self.extract_bytes(index)).to_string())
+    }
+
+    /// Extract the elements of a list or the entries of a map
+    fn extract_nested<T: serde::de::DeserializeOwned>(&self, index: usize) -> PrismDBResult<T> {
+        bincode::serde::decode_from_slice(self.extract_bytes(index), bincode::config::standard())
+            .map(|(values, _)| values)
+            .map_err(|e| PrismDBError::Internal(format!("Failed to decode value: {}", e)))
+    }
+
+    /// Extract a variable-size entry, empty if there is none
+    fn extract_bytes(&self, index: usize) -> &[u8] {
         // Calculate offset by summing sizes of all previous strings
         let mut offset = 0;
         for i in 0..index {
@@ -842,7 +1135,7 @@ impl Vector {
         }
 
         if offset + 4 > self.data.len() {
-            return Ok(String::new());
+            return &[];
         }
 
         // Extract length
@@ -850,12 +1143,10 @@ impl Vector {
         len_bytes.copy_from_slice(&self.data[offset..offset + 4]);
         let len = u32::from_le_bytes(len_bytes) as usize;
 
-        // Extract string data
-        if len > 0 && offset + 4 + len <= self.data.len() {
-            let string_bytes = &self.data[offset + 4..offset + 4 + len];
-            Ok(String::from_utf8_lossy(string_bytes).to_string())
+        if offset + 4 + len <= self.data.len() {
+            &self.data[offset + 4..offset + 4 + len]
         } else {
-            Ok(String::new())
+            &[]
         }
     }
 
@@ -864,43 +1155,184 @@ impl Vector {
         &self.validity
     }
 
-    /// Get a mutable reference to the validity mask
+    /// Get a mutable reference to the validity mask, materializing a view first
     pub fn get_validity_mut(&mut self) -> &mut ValidityMask {
-        &mut self.validity
+        self.flatten();
+        Arc::make_mut(&mut self.validity)
     }
 
     /// Get the selection vector
     pub fn get_selection(&self) -> Option<&SelectionVector> {
-        self.selection.as_ref()
+        self.selection.as_deref()
     }
 
-    /// Set the selection vector
+    /// Restrict the vector to the selected entries as a view, like [`Vector::slice`].
+    /// `None` materializes any existing view.
     pub fn set_selection(&mut self, selection: Option<SelectionVector>) {
-        self.selection = selection;
+        match selection {
+            Some(selection) => *self = self.slice(&selection),
+            None => self.flatten(),
+        }
+    }
+
+    /// Check whether this vector is a view over shared buffers
+    pub fn is_view(&self) -> bool {
+        self.selection.is_some()
+    }
+
+    /// Create a view of the selected entries without copying data
+    ///
+    /// Selecting from a view composes the selections, so a chain of filters still
+    /// reads straight from the original buffers. Plain (not dictionary-encoded)
+    /// strings are the exception: finding a row's entry means walking the buffer
+    /// from the start, so they are gathered right away rather than read through a view.
+    pub fn slice(&self, selection: &SelectionVector) -> Vector {
+        self.select_rows(Arc::new(self.physical_rows(selection)))
+    }
+
+    /// Physical rows of the buffers that the selected entries read
+    pub(crate) fn physical_rows(&self, selection: &SelectionVector) -> SelectionVector {
+        match &self.selection {
+            Some(current) => current.slice(selection),
+            None if selection.as_slice().iter().all(|&row| row < self.count) => selection.clone(),
+            None => SelectionVector::from_indices(
+                selection
+                    .as_slice()
+                    .iter()
+                    .copied()
+                    .filter(|&row| row < self.count)
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Create a view reading the given physical rows, which may be shared with
+    /// other vectors of the same chunk
+    pub(crate) fn select_rows(&self, rows: Arc<SelectionVector>) -> Vector {
+        let mut view = Vector {
+            count: rows.count(),
+            selection: Some(rows),
+            ..self.clone()
+        };
+        if self.dictionary.is_none() && self.physical_type.get_size().is_none() {
+            view.flatten();
+        }
+        view
+    }
+
+    /// The selection of a view, for sharing it between vectors
+    pub(crate) fn shared_selection(&self) -> Option<&Arc<SelectionVector>> {
+        self.selection.as_ref()
+    }
+
+    /// Materialize a view into contiguous buffers of its own
+    pub fn flatten(&mut self) {
+        let Some(selection) = self.selection.take() else {
+            return;
+        };
+        let count = self.count;
+
+        let mut validity = ValidityMask::all_null(count);
+        for i in 0..count {
+            if self.validity.is_valid(selection.get_index(i)) {
+                validity.set_valid(i, true);
+            }
+        }
+
+        let element_size = match self.dictionary {
+            Some(_) => 4,
+            None => self.physical_type.get_size().unwrap_or(0),
+        };
+        let mut data = Vec::with_capacity(element_size * count);
+        if element_size > 0 {
+            for i in 0..count {
+                let offset = selection.get_index(i) * element_size;
+                match self.data.get(offset..offset + element_size) {
+                    Some(bytes) => data.extend_from_slice(bytes),
+                    None => data.resize(data.len() + element_size, 0),
+                }
+            }
+        } else {
+            // Variable-size strings: copy the length-prefixed entry of each valid row
+            let offsets = self.string_offsets();
+            for i in 0..count {
+                let row = selection.get_index(i);
+                if let Some(&(offset, len)) = offsets.get(row).filter(|_| self.validity.is_valid(row)) {
+                    data.extend_from_slice(&self.data[offset..offset + 4 + len]);
+                }
+            }
+        }
+
+        self.data = Arc::new(data);
+        self.validity = Arc::new(validity);
+        self.capacity = count;
+    }
+
+    /// Offset and length of each physical row's string entry
+    fn string_offsets(&self) -> Vec<(usize, usize)> {
+        let mut offsets = Vec::with_capacity(self.validity.count());
+        let mut offset = 0;
+        for row in 0..self.validity.count() {
+            let len = match self.data.get(offset..offset + 4) {
+                Some(bytes) if self.validity.is_valid(row) => {
+                    u32::from_le_bytes(bytes.try_into().unwrap()) as usize
+                }
+                _ => {
+                    offsets.push((offset, 0));
+                    continue;
+                }
+            };
+            offsets.push((offset, len));
+            offset += 4 + len;
+        }
+        offsets
+    }
+
+    /// Physical row of an entry
+    fn row(&self, index: usize) -> usize {
+        match &self.selection {
+            Some(selection) => selection.get_index(index),
+            None => index,
+        }
+    }
+
+    /// Append the entries of a BOOLEAN vector that equal `value` to `selection`,
+    /// reading the data and validity buffers directly; NULLs never match
+    pub fn select_booleans(&self, value: bool, selection: &mut SelectionVector) {
+        for index in 0..self.count {
+            let row = self.row(index);
+            if self.validity.is_valid(row) && (self.data[row] != 0) == value {
+                selection.append(index);
+            }
+        }
     }
 
     /// Check if a specific entry is null
     pub fn is_null(&self, index: usize) -> bool {
-        self.validity.is_null(index)
+        !self.is_valid(index)
     }
 
     /// Check if a specific entry is valid
     pub fn is_valid(&self, index: usize) -> bool {
-        self.validity.is_valid(index)
+        index < self.count && self.validity.is_valid(self.row(index))
     }
 
     /// Get the number of null values
     pub fn null_count(&self) -> usize {
-        self.validity.null_count()
+        self.count - self.valid_count()
     }
 
     /// Get the number of valid values
     pub fn valid_count(&self) -> usize {
-        self.validity.valid_count()
+        match &self.selection {
+            Some(_) => (0..self.count).filter(|&i| self.is_valid(i)).count(),
+            None => self.validity.valid_count(),
+        }
     }
 
     /// Append a value to the vector
     pub fn push(&mut self, value: &Value) -> PrismDBResult<()> {
+        self.flatten();
         if self.count >= self.capacity {
             self.reserve(self.capacity * 2)?;
         }
@@ -912,8 +1344,9 @@ impl Vector {
 
     /// Clear all values from the vector
     pub fn clear(&mut self) {
+        self.flatten();
         self.count = 0;
-        self.validity = ValidityMask::all_valid(self.capacity);
+        self.validity = Arc::new(ValidityMask::all_valid(self.capacity));
     }
 
     /// Get the number of entries in the vector
@@ -923,11 +1356,12 @@ impl Vector {
 
     /// Push a null value to the vector
     pub fn push_null(&mut self) -> PrismDBResult<()> {
+        self.flatten();
         if self.count >= self.capacity {
             self.reserve(self.capacity * 2)?;
         }
 
-        self.validity.set_valid(self.count, false);
+        Arc::make_mut(&mut self.validity).set_valid(self.count, false);
         self.count += 1;
         Ok(())
     }
@@ -955,7 +1389,7 @@ impl Vector {
 
         let offset = index * element_size;
         let bytes = value.to_le_bytes();
-        self.data[offset..offset + 4].copy_from_slice(&bytes);
+        self.data_mut()[offset..offset + 4].copy_from_slice(&bytes);
     }
 
     /// Store a bigint value
@@ -968,7 +1402,7 @@ impl Vector {
 
         let offset = index * element_size;
         let bytes = value.to_le_bytes();
-        self.data[offset..offset + 8].copy_from_slice(&bytes);
+        self.data_mut()[offset..offset + 8].copy_from_slice(&bytes);
     }
 
     /// Store a boolean value
@@ -980,7 +1414,7 @@ impl Vector {
         }
 
         let offset = index * element_size;
-        self.data[offset] = if value { 1 } else { 0 };
+        self.data_mut()[offset] = if value { 1 } else { 0 };
     }
 }
 
@@ -1092,6 +1526,62 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_vector_all_null_values() -> PrismDBResult<()> {
+        let vector = Vector::from_values(&[Value::Null, Value::Null])?;
+        assert_eq!(vector.get_type(), &LogicalType::Integer);
+        assert_eq!(vector.null_count(), 2);
+        assert_eq!(vector.get_value(0)?, Value::Null);
+
+        let vector = Vector::from_values_with_type(&[Value::Null], &LogicalType::Varchar)?;
+        assert_eq!(vector.get_type(), &LogicalType::Varchar);
+        assert!(vector.is_null(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector_leading_null_values() -> PrismDBResult<()> {
+        let values = vec![
+            Value::Null,
+            Value::Null,
+            Value::Varchar("a".to_string()),
+            Value::Null,
+        ];
+        let vector = Vector::from_values(&values)?;
+
+        assert_eq!(vector.get_type(), &LogicalType::Varchar);
+        assert_eq!(vector.valid_count(), 1);
+        for (i, expected) in values.iter().enumerate() {
+            assert_eq!(&vector.get_value(i)?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector_mixed_types_with_nulls() -> PrismDBResult<()> {
+        let vector = Vector::from_values(&[
+            Value::Null,
+            Value::integer(1),
+            Value::BigInt(5_000_000_000),
+            Value::Null,
+        ])?;
+        assert_eq!(vector.get_type(), &LogicalType::BigInt);
+        assert_eq!(vector.get_value(0)?, Value::Null);
+        assert_eq!(vector.get_value(1)?, Value::BigInt(1));
+        assert_eq!(vector.get_value(2)?, Value::BigInt(5_000_000_000));
+        assert!(vector.is_null(3));
+
+        let vector = Vector::from_values(&[Value::integer(2), Value::Null, Value::double(2.5)])?;
+        assert_eq!(vector.get_type(), &LogicalType::Double);
+        assert_eq!(vector.get_value(0)?, Value::double(2.0));
+        assert!(vector.is_null(1));
+        assert_eq!(vector.get_value(2)?, Value::double(2.5));
+
+        // Values without a common type are rejected rather than stored as zeros
+        assert!(Vector::from_values(&[Value::integer(1), Value::Varchar("x".to_string())]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_vector_push() -> PrismDBResult<()> {
         let mut vector = Vector::new(LogicalType::Integer, 2);
@@ -1120,4 +1610,190 @@ mod tests {
 
         Ok(())
     }
+
+    fn categories(count: usize) -> Vec<Value> {
+        let names = ["red", "green", "blue"];
+        (0..count)
+            .map(|i| match i % 7 {
+                6 => Value::Null,
+                n => Value::Varchar(names[n % 3].to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dictionary_vector_round_trip() -> PrismDBResult<()> {
+        let values = categories(100);
+        let vector = Vector::from_values(&values)?;
+
+        assert!(vector.is_dictionary_encoded());
+        assert_eq!(vector.get_dictionary().map(|d| d.len()), Some(3));
+        assert_eq!(vector.null_count(), 14);
+        let collected: PrismDBResult<Vec<Value>> = vector.iter().collect();
+        assert_eq!(collected?, values);
+
+        // Clones share the dictionary until one of them is written to
+        let mut copy = vector.clone();
+        assert!(Arc::ptr_eq(
+            vector.dictionary.as_ref().unwrap(),
+            copy.dictionary.as_ref().unwrap()
+        ));
+        copy.push(&Value::Varchar("purple".to_string()))?;
+        copy.set_value(0, &Value::Varchar("blue".to_string()))?;
+        assert_eq!(copy.get_value(100)?, Value::Varchar("purple".to_string()));
+        assert_eq!(copy.get_value(0)?, Value::Varchar("blue".to_string()));
+        assert_eq!(vector.get_value(0)?, Value::Varchar("red".to_string()));
+        assert_eq!(vector.get_dictionary().map(|d| d.len()), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dictionary_only_for_low_cardinality() -> PrismDBResult<()> {
+        let distinct: Vec<Value> = (0..100).map(|i| Value::varchar(format!("v{}", i))).collect();
+        let vector = Vector::from_values(&distinct)?;
+        assert!(!vector.is_dictionary_encoded());
+        assert_eq!(vector.get_value(42)?, Value::Varchar("v42".to_string()));
+
+        // Too few values to be worth a dictionary
+        let short = vec![Value::Varchar("a".to_string()); 4];
+        assert!(!Vector::from_values(&short)?.is_dictionary_encoded());
+
+        let chars: Vec<Value> = (0..64).map(|i| Value::Char(["x", "y"][i % 2].to_string())).collect();
+        let vector = Vector::from_values(&chars)?;
+        assert!(vector.is_dictionary_encoded());
+        assert_eq!(vector.get_value(3)?, Value::Char("y".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_dictionary() -> PrismDBResult<()> {
+        let values = categories(1000);
+        let mut vector = Vector::from_values(&values)?;
+        let encoded_size = vector.memory_usage();
+
+        vector.decode_dictionary()?;
+        assert!(!vector.is_dictionary_encoded());
+        assert!(vector.memory_usage() > encoded_size);
+        let collected: PrismDBResult<Vec<Value>> = vector.iter().collect();
+        assert_eq!(collected?, values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector_slice_view() -> PrismDBResult<()> {
+        let values: Vec<Value> = (0..6)
+            .map(|i| if i == 3 { Value::Null } else { Value::integer(i) })
+            .collect();
+        let vector = Vector::from_values(&values)?;
+
+        let view = vector.slice(&SelectionVector::from_indices(vec![1, 3, 4, 5]));
+        assert!(view.is_view());
+        assert_eq!(view.len(), 4);
+        assert_eq!(view.get_value(0)?, Value::integer(1));
+        assert!(view.is_null(1));
+        assert_eq!(view.null_count(), 1);
+
+        // Slicing a view composes selections instead of copying
+        let nested = view.slice(&SelectionVector::from_indices(vec![3, 1, 0]));
+        assert!(nested.is_view());
+        let collected: PrismDBResult<Vec<Value>> = nested.iter().collect();
+        assert_eq!(collected?, vec![Value::integer(5), Value::Null, Value::integer(1)]);
+
+        // Writing to a view materializes it and leaves the source untouched
+        let mut written = nested.clone();
+        written.set_value(1, &Value::integer(42))?;
+        assert!(!written.is_view());
+        assert_eq!(written.get_value(1)?, Value::integer(42));
+        assert_eq!(written.get_value(2)?, Value::integer(1));
+        assert!(nested.is_null(1));
+        assert!(vector.is_null(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector_slice_strings() -> PrismDBResult<()> {
+        // Dictionary codes are fixed-width, so these stay a view
+        let colors: Vec<Value> = (0..100)
+            .map(|i| match i % 5 {
+                0 => Value::Null,
+                n => Value::Varchar(["red", "green", "blue", "cyan"][n - 1].to_string()),
+            })
+            .collect();
+        let encoded = Vector::from_values(&colors)?;
+        assert!(encoded.is_dictionary_encoded());
+        let view = encoded.slice(&SelectionVector::from_indices(vec![97, 10, 3]));
+        assert!(view.is_view());
+        let collected: PrismDBResult<Vec<Value>> = view.iter().collect();
+        assert_eq!(
+            collected?,
+            vec![
+                Value::Varchar("green".to_string()),
+                Value::Null,
+                Value::Varchar("blue".to_string()),
+            ]
+        );
+
+        // Plain strings are gathered into their own buffer
+        let names: Vec<Value> = (0..6)
+            .map(|i| if i == 3 { Value::Null } else { Value::Varchar(format!("row-{}", i)) })
+            .collect();
+        let plain = Vector::from_values(&names)?;
+        let sliced = plain.slice(&SelectionVector::from_indices(vec![5, 3, 1]));
+        assert!(!sliced.is_view());
+        let collected: PrismDBResult<Vec<Value>> = sliced.iter().collect();
+        assert_eq!(
+            collected?,
+            vec![
+                Value::Varchar("row-5".to_string()),
+                Value::Null,
+                Value::Varchar("row-1".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_vector_flatten() -> PrismDBResult<()> {
+        let values: Vec<Value> = (0..8).map(|i| Value::integer(i * 10)).collect();
+        let mut vector = Vector::from_values(&values)?;
+        vector.set_selection(Some(SelectionVector::from_indices(vec![7, 2, 2])));
+        assert!(vector.is_view());
+
+        vector.flatten();
+        assert!(!vector.is_view());
+        assert_eq!(vector.len(), 3);
+        let collected: PrismDBResult<Vec<Value>> = vector.iter().collect();
+        assert_eq!(collected?, vec![Value::integer(70), Value::integer(20), Value::integer(20)]);
+
+        vector.push(&Value::integer(5))?;
+        assert_eq!(vector.get_value(3)?, Value::integer(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_vector() -> PrismDBResult<()> {
+        let list_type = LogicalType::List(Box::new(LogicalType::BigInt));
+        let values = vec![
+            Value::List(vec![Value::integer(1), Value::Null]),
+            Value::Null,
+            Value::List(vec![]),
+            Value::List(vec![Value::BigInt(7)]),
+        ];
+        let mut vector = Vector::from_values_with_type(&values, &list_type)?;
+
+        // Elements are coerced to the element type
+        assert_eq!(vector.get_value(0)?, Value::List(vec![Value::BigInt(1), Value::Null]));
+        assert_eq!(vector.get_value(1)?, Value::Null);
+        assert_eq!(vector.get_value(2)?, Value::List(vec![]));
+        assert_eq!(vector.get_value(3)?, Value::List(vec![Value::BigInt(7)]));
+
+        vector.set_selection(Some(SelectionVector::from_indices(vec![3, 0])));
+        vector.flatten();
+        let collected: PrismDBResult<Vec<Value>> = vector.iter().collect();
+        assert_eq!(collected?, vec![values[3].clone(), Value::List(vec![Value::BigInt(1), Value::Null])]);
+
+        let strings = Value::List(vec![Value::Varchar("a".to_string())]);
+        assert!(vector.push(&strings).is_err());
+        Ok(())
+    }
 }
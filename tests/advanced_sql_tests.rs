@@ -1,10 +1,13 @@
 //! Advanced SQL Features End-to-End Tests
 //!
-//! Tests for CTEs, Subqueries, and Set Operations
+//! Tests for CTEs, Subqueries, and Set Operations, along with UNNEST, subquery
+//! pushdown and ASOF joins
 
 use prism::{Database, PrismDBResult};
 use prism::types::Value;
 
+mod common;
+
 /// Create test database with sample data
 fn create_test_db() -> PrismDBResult<Database> {
     let mut db = Database::new_in_memory()?;
@@ -528,3 +531,337 @@ fn test_subquery_with_set_operations() -> PrismDBResult<()> {
 
     Ok(())
 }
+
+/// Tests for list literals and set-returning UNNEST in the SELECT list
+#[cfg(test)]
+mod unnest_tests {
+    use crate::common::{rows, text};
+    use prism::{Database, PrismDBResult, Value};
+
+    #[test]
+    fn test_unnest_list_literal() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+
+        let result = db.query("SELECT unnest([1, 2, 3])")?;
+        assert_eq!(result.columns[0].name, "unnest([1, 2, 3])");
+        assert_eq!(
+            result.collect()?.rows,
+            vec![vec![Value::BigInt(1)], vec![Value::BigInt(2)], vec![Value::BigInt(3)]]
+        );
+
+        // Elements are unified to a common type
+        assert_eq!(
+            rows(&db, "SELECT unnest([1, 2.5, NULL]) AS x")?,
+            vec![vec![Value::Double(1.0)], vec![Value::Double(2.5)], vec![Value::Null]]
+        );
+
+        // Several unnests produce every combination
+        assert_eq!(
+            rows(&db, "SELECT unnest([1, 2]) AS n, unnest(['x', 'y']) AS s")?,
+            vec![
+                vec![Value::BigInt(1), text("x")],
+                vec![Value::BigInt(1), text("y")],
+                vec![Value::BigInt(2), text("x")],
+                vec![Value::BigInt(2), text("y")],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unnest_table_column() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE posts (id INTEGER, tags VARCHAR[])")?;
+        db.execute(
+            "INSERT INTO posts VALUES (1, ['rust', 'sql']), (2, []), (3, NULL), (4, ['db'])",
+        )?;
+
+        assert_eq!(
+            rows(&db, "SELECT id, tags FROM posts WHERE id = 1")?,
+            vec![vec![Value::Integer(1), Value::List(vec![text("rust"), text("sql")])]]
+        );
+
+        // Other columns repeat per element; empty and NULL lists produce no rows
+        let result = db.query("SELECT id, unnest(tags) FROM posts")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "unnest(tags)"]);
+        assert_eq!(
+            result.collect()?.rows,
+            vec![
+                vec![Value::Integer(1), text("rust")],
+                vec![Value::Integer(1), text("sql")],
+                vec![Value::Integer(4), text("db")],
+            ]
+        );
+
+        // LIMIT counts the expanded rows
+        assert_eq!(
+            rows(&db, "SELECT id, unnest(tags) AS tag FROM posts ORDER BY id DESC LIMIT 2")?,
+            vec![vec![Value::Integer(4), text("db")], vec![Value::Integer(1), text("rust")]]
+        );
+
+        assert!(db.query("SELECT unnest(id) FROM posts").is_err());
+        assert!(db.query("SELECT unnest(tags) || 'x' FROM posts").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for pushing filters from an outer query into subqueries and CTEs
+#[cfg(test)]
+mod subquery_pushdown_tests {
+    use crate::common::{database, insert_rows};
+    use prism::{Database, PrismDBResult, Value};
+
+    fn sales_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE sales (id INTEGER, region INTEGER, amount INTEGER)"])?;
+        let values = (0..100).map(|i| format!("({}, {}, {})", i, i % 4, (i * 7) % 50));
+        insert_rows(&mut db, "sales", values)?;
+        Ok(db)
+    }
+
+    /// The optimized plan of `sql` as EXPLAIN prints it, one trimmed line per entry
+    fn explain(db: &Database, sql: &str) -> PrismDBResult<Vec<String>> {
+        let rows = db.query(&format!("EXPLAIN {}", sql))?.collect()?.rows;
+        match &rows[0][0] {
+            Value::Varchar(plan) => Ok(plan.lines().map(|line| line.trim().to_string()).collect()),
+            other => panic!("expected a plan string, got {:?}", other),
+        }
+    }
+
+    /// Names of the plan's nodes, from the root down
+    fn node_names(plan: &[String]) -> Vec<&str> {
+        plan.iter()
+            .filter(|line| !line.contains(':'))
+            .map(|line| line.split(' ').next().unwrap())
+            .collect()
+    }
+
+    fn ids(db: &Database, sql: &str) -> PrismDBResult<Vec<i32>> {
+        let rows = db.query(sql)?.collect()?.rows;
+        Ok(rows
+            .iter()
+            .map(|row| match row[0] {
+                Value::Integer(id) => id,
+                ref other => panic!("Expected integer id, got {:?}", other),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_filter_moves_into_subquery_scan() -> PrismDBResult<()> {
+        let db = sales_database()?;
+        let sql = "SELECT * FROM (SELECT id, amount FROM sales) s WHERE s.id > 95";
+
+        let plan = explain(&db, sql)?;
+        assert_eq!(node_names(&plan), ["PROJECTION", "PROJECTION", "TABLE_SCAN"]);
+        assert!(plan.contains(&"filters: sales.id > 95".to_string()));
+        assert_eq!(ids(&db, &format!("{} ORDER BY id", sql))?, [96, 97, 98, 99]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_on_computed_column_is_rewritten() -> PrismDBResult<()> {
+        let db = sales_database()?;
+        let sql = "SELECT * FROM (SELECT id, amount * 2 AS doubled FROM sales WHERE region = 1) s \
+               WHERE doubled >= 90";
+
+        let plan = explain(&db, sql)?;
+        assert_eq!(node_names(&plan), ["PROJECTION", "PROJECTION", "TABLE_SCAN"]);
+        assert!(plan.contains(&"filters: sales.region = 1, (sales.amount * 2) >= 90".to_string()));
+
+        let expected: Vec<i32> = (0..100).filter(|i| i % 4 == 1 && (i * 7) % 50 * 2 >= 90).collect();
+        assert_eq!(ids(&db, &format!("{} ORDER BY id", sql))?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_grouping_conjuncts_pass_aggregates() -> PrismDBResult<()> {
+        let db = sales_database()?;
+        let sql = "SELECT * FROM (SELECT region, SUM(amount) AS total FROM sales GROUP BY region) s \
+               WHERE region > 1 AND total > 0";
+
+        // The condition on the aggregate result stays above the aggregate
+        let plan = explain(&db, sql)?;
+        assert_eq!(
+            node_names(&plan),
+            ["PROJECTION", "PROJECTION", "FILTER", "HASH_AGGREGATE", "TABLE_SCAN"]
+        );
+        assert!(plan.contains(&"filters: sales.region > 1".to_string()));
+        assert_eq!(ids(&db, &format!("{} ORDER BY region", sql))?, [2, 3]);
+
+        // A global aggregate returns a row even when nothing passes the filter
+        let sql = "SELECT * FROM (SELECT COUNT(*) AS n FROM sales) s WHERE n > 1000";
+        assert!(node_names(&explain(&db, sql)?).contains(&"FILTER"));
+        assert!(db.query(sql)?.collect()?.rows.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_moves_into_cte() -> PrismDBResult<()> {
+        let db = sales_database()?;
+        let sql = "WITH recent AS (SELECT id, region FROM sales ORDER BY id) \
+               SELECT id FROM recent WHERE region = 3 AND id < 20";
+
+        let plan = explain(&db, sql)?;
+        assert_eq!(node_names(&plan), ["PROJECTION", "PROJECTION", "ORDER_BY", "TABLE_SCAN"]);
+        assert_eq!(ids(&db, sql)?, [3, 7, 11, 15, 19]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_limit_blocks_pushdown() -> PrismDBResult<()> {
+        let db = sales_database()?;
+        let sql = "SELECT * FROM (SELECT id FROM sales LIMIT 10) s WHERE id > 5";
+
+        assert!(node_names(&explain(&db, sql)?).contains(&"FILTER"));
+        assert_eq!(ids(&db, &format!("{} ORDER BY id", sql))?, [6, 7, 8, 9]);
+        Ok(())
+    }
+}
+
+/// Tests for ASOF JOIN aligning time-indexed tables
+#[cfg(test)]
+mod asof_join_tests {
+    use crate::common::{database, rows};
+    use prism::{Database, PrismDBError, PrismDBResult, Value};
+
+    /// Trades matched against the quotes in effect for their symbol
+    const MARKET: &[&str] = &[
+        "CREATE TABLE trades (symbol VARCHAR, ts INTEGER, qty INTEGER)",
+        "INSERT INTO trades VALUES ('A', 5, 1), ('B', 3, 2), ('A', 1, 3), ('A', 10, 4), \
+     ('C', 7, 5), ('B', 12, 6), ('A', 20, 7), (NULL, 10, 8), ('A', NULL, 9)",
+        // Inserted out of time order, with a quote exactly at a trade's time
+        "CREATE TABLE quotes (symbol VARCHAR, ts INTEGER, price INTEGER)",
+        "INSERT INTO quotes VALUES ('A', 8, 108), ('A', 2, 102), ('B', 4, 204), ('A', 10, 110), \
+     ('B', 11, 211), ('A', 4, 104), ('A', NULL, 999)",
+    ];
+
+    /// (qty, price) pairs, with NULL prices as None
+    fn matches(db: &Database, sql: &str) -> PrismDBResult<Vec<(i32, Option<i32>)>> {
+        rows(db, sql)?
+            .iter()
+            .map(|row| {
+                let price = match &row[1] {
+                    Value::Null => None,
+                    Value::Integer(price) => Some(*price),
+                    other => panic!("unexpected price {:?}", other),
+                };
+                Ok((row[0].try_as_i32()?, price))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_asof_join_matches_latest_preceding_row() -> PrismDBResult<()> {
+        let db = database(MARKET)?;
+        let aligned = matches(
+            &db,
+            "SELECT t.qty, q.price FROM trades t ASOF JOIN quotes q \
+         ON t.symbol = q.symbol AND t.ts >= q.ts ORDER BY t.qty",
+        )?;
+        assert_eq!(aligned, [(1, Some(104)), (4, Some(110)), (6, Some(211)), (7, Some(110))]);
+
+        // The same condition written the other way round
+        let flipped = matches(
+            &db,
+            "SELECT t.qty, q.price FROM trades t ASOF JOIN quotes q \
+         ON q.ts <= t.ts AND q.symbol = t.symbol ORDER BY t.qty",
+        )?;
+        assert_eq!(flipped, aligned);
+
+        // A strict inequality skips the quote at the trade's own time
+        let strict = matches(
+            &db,
+            "SELECT t.qty, q.price FROM trades t ASOF JOIN quotes q \
+         ON t.symbol = q.symbol AND t.ts > q.ts ORDER BY t.qty",
+        )?;
+        assert_eq!(strict, [(1, Some(104)), (4, Some(108)), (6, Some(211)), (7, Some(110))]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_asof_left_join_pads_rows_without_a_match() -> PrismDBResult<()> {
+        let db = database(MARKET)?;
+        let aligned = matches(
+            &db,
+            "SELECT t.qty, q.price FROM trades t ASOF LEFT JOIN quotes q \
+         ON t.symbol = q.symbol AND t.ts >= q.ts ORDER BY t.qty",
+        )?;
+        // Nothing precedes trade 3 or B's trade 2, C has no quotes, and NULL keys match nothing
+        assert_eq!(
+            aligned,
+            [
+                (1, Some(104)),
+                (2, None),
+                (3, None),
+                (4, Some(110)),
+                (5, None),
+                (6, Some(211)),
+                (7, Some(110)),
+                (8, None),
+                (9, None),
+            ]
+        );
+
+        // Every right column is NULL for an unmatched row
+        let unmatched = rows(
+            &db,
+            "SELECT q.symbol, q.ts, q.price FROM trades t ASOF LEFT JOIN quotes q \
+         ON t.symbol = q.symbol AND t.ts >= q.ts WHERE t.qty = 3",
+        )?;
+        assert_eq!(unmatched, [[Value::Null, Value::Null, Value::Null]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_asof_join_matches_earliest_following_row() -> PrismDBResult<()> {
+        let db = database(MARKET)?;
+        let aligned = matches(
+            &db,
+            "SELECT t.qty, q.price FROM trades t ASOF LEFT JOIN quotes q \
+         ON t.symbol = q.symbol AND t.ts <= q.ts ORDER BY t.qty",
+        )?;
+        assert_eq!(
+            aligned,
+            [
+                (1, Some(108)),
+                (2, Some(204)),
+                (3, Some(102)),
+                (4, Some(110)),
+                (5, None),
+                (6, None),
+                (7, None),
+                (8, None),
+                (9, None),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_asof_join_plan_and_errors() -> PrismDBResult<()> {
+        let db = database(MARKET)?;
+        let plan = match &rows(
+            &db,
+            "EXPLAIN SELECT * FROM trades t ASOF JOIN quotes q ON t.symbol = q.symbol AND t.ts >= q.ts",
+        )?[0][0]
+        {
+            Value::Varchar(plan) => plan.clone(),
+            other => panic!("expected a plan string, got {:?}", other),
+        };
+        assert!(plan.contains("ASOF_JOIN"), "{}", plan);
+
+        // Without an inequality there is nothing to be nearest to
+        let err = db
+            .query("SELECT * FROM trades t ASOF JOIN quotes q ON t.symbol = q.symbol")
+            .unwrap_err();
+        assert!(matches!(err, PrismDBError::InvalidArgument(_)), "{}", err);
+        for sql in [
+            "SELECT * FROM trades t ASOF RIGHT JOIN quotes q ON t.symbol = q.symbol AND t.ts >= q.ts",
+            "SELECT * FROM trades t ASOF JOIN quotes q USING (symbol)",
+        ] {
+            assert!(matches!(db.query(sql), Err(PrismDBError::Parse(_))), "{}", sql);
+        }
+        Ok(())
+    }
+}
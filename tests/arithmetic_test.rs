@@ -1,6 +1,9 @@
 use prism::PrismDBResult;
+use prism::Value;
 use prism::database::{Database, DatabaseConfig};
 
+mod common;
+
 #[test]
 fn test_arithmetic_multiply() -> PrismDBResult<()> {
     let db = Database::new(DatabaseConfig::in_memory())?;
@@ -60,3 +63,372 @@ fn test_arithmetic_combined() -> PrismDBResult<()> {
     println!("✓ Combined arithmetic works");
     Ok(())
 }
+
+#[test]
+fn test_division_is_true_division() -> PrismDBResult<()> {
+    let db = Database::new(DatabaseConfig::in_memory())?;
+
+    db.execute_sql_collect("CREATE TABLE test (a INTEGER, b INTEGER)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (5, 2)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (-5, 2)")?;
+
+    let result = db.execute_sql_collect("SELECT a / b FROM test")?;
+    let collected = result.collect()?;
+    assert_eq!(collected.rows[0][0], Value::double(2.5));
+    assert_eq!(collected.rows[1][0], Value::double(-2.5));
+
+    // Constant-folded literals follow the same rule
+    let result = db.execute_sql_collect("SELECT 5 / 2")?;
+    assert_eq!(result.first_value(), Some(Value::double(2.5)));
+    Ok(())
+}
+
+#[test]
+fn test_integer_division_floors() -> PrismDBResult<()> {
+    let db = Database::new(DatabaseConfig::in_memory())?;
+
+    db.execute_sql_collect("CREATE TABLE test (a INTEGER, b INTEGER)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (5, 2)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (-5, 2)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (5, -2)")?;
+
+    let result = db.execute_sql_collect("SELECT a // b FROM test")?;
+    let collected = result.collect()?;
+    assert_eq!(collected.rows[0][0], Value::integer(2));
+    assert_eq!(collected.rows[1][0], Value::integer(-3));
+    assert_eq!(collected.rows[2][0], Value::integer(-3));
+    Ok(())
+}
+
+#[test]
+fn test_modulo_sign_follows_dividend() -> PrismDBResult<()> {
+    let db = Database::new(DatabaseConfig::in_memory())?;
+
+    db.execute_sql_collect("CREATE TABLE test (a INTEGER, b INTEGER)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (-5, 3)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (5, -3)")?;
+
+    let result = db.execute_sql_collect("SELECT a % b FROM test")?;
+    let collected = result.collect()?;
+    assert_eq!(collected.rows[0][0], Value::integer(-2));
+    assert_eq!(collected.rows[1][0], Value::integer(2));
+
+    let result = db.execute_sql_collect("SELECT (-5) % 3")?;
+    assert_eq!(result.first_value(), Some(Value::integer(-2)));
+    Ok(())
+}
+
+#[test]
+fn test_division_by_zero_errors() -> PrismDBResult<()> {
+    let db = Database::new(DatabaseConfig::in_memory())?;
+
+    db.execute_sql_collect("CREATE TABLE test (a INTEGER, b INTEGER)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (5, 0)")?;
+
+    assert!(db.execute_sql_collect("SELECT a / b FROM test").is_err());
+    assert!(db.execute_sql_collect("SELECT a // b FROM test").is_err());
+    assert!(db.execute_sql_collect("SELECT a % b FROM test").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_div_keyword_floors() -> PrismDBResult<()> {
+    let db = Database::new(DatabaseConfig::in_memory())?;
+
+    let result = db.execute_sql_collect("SELECT 7 DIV 2")?;
+    assert_eq!(result.first_value(), Some(Value::integer(3)));
+    let result = db.execute_sql_collect("SELECT (-7) DIV 2")?;
+    assert_eq!(result.first_value(), Some(Value::integer(-4)));
+    let result = db.execute_sql_collect("SELECT 7 // 2")?;
+    assert_eq!(result.first_value(), Some(Value::integer(3)));
+
+    // DIV is not reserved, so it still names a column
+    db.execute_sql_collect("CREATE TABLE test (div INTEGER, b INTEGER)")?;
+    db.execute_sql_collect("INSERT INTO test VALUES (9, 0)")?;
+    let result = db.execute_sql_collect("SELECT div div 2 FROM test")?;
+    assert_eq!(result.first_value(), Some(Value::integer(4)));
+    assert!(db.execute_sql_collect("SELECT div DIV b FROM test").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_negating_minimum_overflows() -> PrismDBResult<()> {
+    let mut db = Database::new(DatabaseConfig::in_memory())?;
+
+    db.execute_sql_collect("CREATE TABLE test (t TINYINT, s SMALLINT, i INTEGER, b BIGINT)")?;
+    db.execute_sql_collect(
+        "INSERT INTO test VALUES (-128, -32768, -2147483648, CAST('-9223372036854775808' AS BIGINT))",
+    )?;
+
+    for column in ["t", "s", "i", "b"] {
+        let error = db.execute_sql_collect(&format!("SELECT -{} FROM test", column)).unwrap_err();
+        assert!(error.to_string().contains("Integer overflow in negation"), "{}", error);
+    }
+    assert!(db.execute_sql_collect("SELECT -CAST(-2147483648 AS INTEGER)").is_err());
+    let result = db.execute_sql_collect("SELECT -(i + 1) FROM test")?;
+    assert_eq!(result.first_value(), Some(Value::integer(2147483647)));
+
+    db.execute("SET arithmetic_errors = 'null'")?;
+    let rows = db.query("SELECT -t, -s, -i, -b FROM test")?.collect()?.rows;
+    assert_eq!(rows, vec![vec![Value::Null; 4]]);
+    Ok(())
+}
+
+/// Tests for SET arithmetic_errors, TRY_DIVIDE and TRY_CAST
+#[cfg(test)]
+mod arithmetic_errors_tests {
+    use crate::common::{database, rows};
+    use prism::{PrismDBResult, Value};
+
+    const READINGS: &[&str] = &[
+        "CREATE TABLE readings (id INTEGER, total INTEGER, n INTEGER, raw VARCHAR)",
+        "INSERT INTO readings VALUES \
+     (1, 10, 4, '12'), \
+     (2, 7, 0, 'n/a'), \
+     (3, 2147483647, 1, '99999')",
+    ];
+
+    #[test]
+    fn test_strict_mode_fails_on_divide_by_zero_and_overflow() -> PrismDBResult<()> {
+        let db = database(READINGS)?;
+
+        for sql in [
+            "SELECT total / n FROM readings",
+            "SELECT total // n FROM readings",
+            "SELECT total % n FROM readings",
+            "SELECT total + 1 FROM readings",
+            "SELECT total * 2 FROM readings WHERE id = 3",
+            "SELECT 1 / 0",
+        ] {
+            assert!(db.query(sql).is_err(), "{}", sql);
+        }
+        assert_eq!(rows(&db, "SHOW arithmetic_errors")?, vec![vec![Value::Varchar("error".to_string())]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_mode_yields_null() -> PrismDBResult<()> {
+        let mut db = database(READINGS)?;
+        db.execute("SET arithmetic_errors = 'null'")?;
+
+        assert_eq!(
+            rows(&db, "SELECT total / n FROM readings ORDER BY id")?,
+            vec![
+                vec![Value::Double(2.5)],
+                vec![Value::Null],
+                vec![Value::Double(2147483647.0)],
+            ]
+        );
+        assert_eq!(
+            rows(&db, "SELECT total + 1 FROM readings ORDER BY id")?,
+            vec![vec![Value::Integer(11)], vec![Value::Integer(8)], vec![Value::Null]]
+        );
+        assert_eq!(rows(&db, "SELECT 7 % 0")?, vec![vec![Value::Null]]);
+
+        // Type errors still fail
+        assert!(db.query("SELECT raw - 1 FROM readings").is_err());
+
+        db.execute("SET arithmetic_errors = 'error'")?;
+        assert!(db.query("SELECT total / n FROM readings").is_err());
+        assert!(db.execute("SET arithmetic_errors = 'ignore'").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_divide_and_try_cast() -> PrismDBResult<()> {
+        let db = database(READINGS)?;
+
+        // TRY_DIVIDE yields NULL even in strict mode
+        assert_eq!(
+            rows(&db, "SELECT try_divide(total, n) FROM readings ORDER BY id")?,
+            vec![
+                vec![Value::Double(2.5)],
+                vec![Value::Null],
+                vec![Value::Double(2147483647.0)],
+            ]
+        );
+
+        assert!(db.query("SELECT CAST(raw AS INTEGER) FROM readings").is_err());
+        assert_eq!(
+            rows(&db, "SELECT TRY_CAST(raw AS INTEGER) FROM readings ORDER BY id")?,
+            vec![vec![Value::Integer(12)], vec![Value::Null], vec![Value::Integer(99999)]]
+        );
+        // Out-of-range values are NULL too
+        assert_eq!(
+            rows(&db, "SELECT TRY_CAST(raw AS SMALLINT), TRY_CAST(total AS SMALLINT) FROM readings ORDER BY id")?,
+            vec![
+                vec![Value::SmallInt(12), Value::SmallInt(10)],
+                vec![Value::Null, Value::SmallInt(7)],
+                vec![Value::Null, Value::Null],
+            ]
+        );
+        assert_eq!(rows(&db, "SELECT CAST('42' AS BIGINT)")?, vec![vec![Value::BigInt(42)]]);
+        Ok(())
+    }
+}
+
+/// Tests for BETWEEN, NOT BETWEEN and BETWEEN SYMMETRIC
+#[cfg(test)]
+mod between_tests {
+    use crate::common::{database, first_value};
+    use prism::{PrismDBResult, Value};
+
+    const NUMBERS: &[&str] = &[
+        "CREATE TABLE numbers (x INTEGER)",
+        "INSERT INTO numbers VALUES (1), (3), (5), (7), (NULL)",
+    ];
+
+    #[test]
+    fn test_symmetric_with_swapped_bounds() -> PrismDBResult<()> {
+        let db = database(NUMBERS)?;
+        assert_eq!(first_value(&db, "SELECT 3 BETWEEN 5 AND 1")?, Value::Boolean(false));
+        assert_eq!(first_value(&db, "SELECT 3 BETWEEN SYMMETRIC 5 AND 1")?, Value::Boolean(true));
+        assert_eq!(first_value(&db, "SELECT 3 BETWEEN SYMMETRIC 1 AND 5")?, Value::Boolean(true));
+        assert_eq!(first_value(&db, "SELECT 3 NOT BETWEEN SYMMETRIC 5 AND 1")?, Value::Boolean(false));
+        assert_eq!(first_value(&db, "SELECT 3 NOT BETWEEN 5 AND 1")?, Value::Boolean(true));
+
+        assert_eq!(
+            db.query("SELECT x FROM numbers WHERE x BETWEEN SYMMETRIC 6 AND 2 ORDER BY x")?.collect()?.rows,
+            [[Value::Integer(3)], [Value::Integer(5)]]
+        );
+        // NULL x is neither between nor not between
+        assert_eq!(
+            db.query("SELECT x FROM numbers WHERE x NOT BETWEEN SYMMETRIC 6 AND 2 ORDER BY x")?.collect()?.rows,
+            [[Value::Integer(1)], [Value::Integer(7)]]
+        );
+
+        // Literal bounds are put in order, so the scan sees an ordinary range
+        let plan = first_value(&db, "EXPLAIN SELECT x FROM numbers WHERE x BETWEEN SYMMETRIC 6 AND 2")?;
+        assert!(plan.to_string().contains("numbers.x >= 2 AND numbers.x <= 6"), "{}", plan);
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_bound_is_unknown() -> PrismDBResult<()> {
+        let db = database(NUMBERS)?;
+        for sql in [
+            "SELECT 3 BETWEEN NULL AND 5",
+            "SELECT 3 BETWEEN SYMMETRIC NULL AND 5",
+            "SELECT 7 BETWEEN SYMMETRIC 5 AND NULL",
+            "SELECT 3 NOT BETWEEN SYMMETRIC NULL AND 5",
+        ] {
+            assert_eq!(first_value(&db, sql)?, Value::Null, "{}", sql);
+        }
+        // A NULL bound cannot rescue a value outside the other bound
+        assert_eq!(first_value(&db, "SELECT 7 BETWEEN NULL AND 5")?, Value::Boolean(false));
+
+        assert_eq!(
+            db.query("SELECT x FROM numbers WHERE x BETWEEN SYMMETRIC NULL AND 5")?.collect()?.rows.len(),
+            0
+        );
+        Ok(())
+    }
+}
+
+/// Tests for filters on a bare boolean column
+#[cfg(test)]
+mod boolean_filter_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    #[test]
+    fn test_boolean_column_filter_skips_nulls() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER, active BOOLEAN)")?;
+        db.execute("INSERT INTO users VALUES (1, true), (2, false), (3, NULL), (4, true), (5, false)")?;
+
+        let ids = |sql: &str| -> PrismDBResult<Vec<Value>> {
+            Ok(db.query(sql)?.collect()?.rows.into_iter().map(|row| row[0].clone()).collect())
+        };
+        assert_eq!(
+            ids("SELECT id FROM users WHERE active ORDER BY id")?,
+            [Value::Integer(1), Value::Integer(4)]
+        );
+        assert_eq!(
+            ids("SELECT id FROM users WHERE NOT active ORDER BY id")?,
+            [Value::Integer(2), Value::Integer(5)]
+        );
+        // Same rows as the generic comparison path
+        assert_eq!(
+            ids("SELECT id FROM users WHERE active = true ORDER BY id")?,
+            ids("SELECT id FROM users WHERE active ORDER BY id")?
+        );
+        assert_eq!(ids("SELECT id FROM users WHERE active AND id > 1")?, [Value::Integer(4)]);
+        assert_eq!(
+            ids("SELECT COUNT(*) FROM (SELECT * FROM users WHERE active) AS u WHERE NOT active")?,
+            [Value::BigInt(0)]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for SET type_coercion: lenient implicit casts versus strict typing
+#[cfg(test)]
+mod type_coercion_tests {
+    use prism::{Database, DatabaseConfig, PrismDBResult, TypeCoercion, Value};
+
+    fn orders_database(type_coercion: TypeCoercion) -> PrismDBResult<Database> {
+        let mut db = Database::new(DatabaseConfig {
+            type_coercion,
+            ..DatabaseConfig::in_memory()
+        })?;
+        db.execute("CREATE TABLE orders (id INTEGER, code VARCHAR, price DOUBLE)")?;
+        db.execute("INSERT INTO orders VALUES (1, 'a', 2.5), (2, 'b', 4.0)")?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_cross_type_comparison() -> PrismDBResult<()> {
+        let sql = "SELECT code FROM orders WHERE id = '2'";
+
+        let lenient = orders_database(TypeCoercion::Lenient)?;
+        assert_eq!(lenient.query(sql)?.collect()?.rows, [[Value::Varchar("b".to_string())]]);
+
+        let strict = orders_database(TypeCoercion::Strict)?;
+        let error = strict.query(sql).and_then(|result| result.collect()).unwrap_err().to_string();
+        assert!(error.contains("Cannot mix INTEGER and VARCHAR with type_coercion = 'strict'"), "{}", error);
+
+        // An explicit cast, or matching types, is fine under strict
+        assert_eq!(
+            strict.query("SELECT code FROM orders WHERE id = CAST('2' AS INTEGER)")?.collect()?.rows,
+            [[Value::Varchar("b".to_string())]]
+        );
+        assert_eq!(
+            strict.query("SELECT COUNT(*) FROM orders WHERE code = 'a' AND price > 1.5")?.collect()?.rows,
+            [[Value::BigInt(1)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_arithmetic_and_insert() -> PrismDBResult<()> {
+        let mut lenient = orders_database(TypeCoercion::Lenient)?;
+        assert_eq!(
+            lenient.query("SELECT id + '10', id * price FROM orders WHERE id = 1")?.collect()?.rows,
+            [[Value::Integer(11), Value::Double(2.5)]]
+        );
+        lenient.execute("INSERT INTO orders VALUES ('3', 7, 5)")?;
+        assert_eq!(
+            lenient.query("SELECT id, code, price FROM orders WHERE id = 3")?.collect()?.rows,
+            [[Value::Integer(3), Value::Varchar("7".to_string()), Value::Double(5.0)]]
+        );
+
+        let mut strict = orders_database(TypeCoercion::Strict)?;
+        assert!(strict.query("SELECT id + '10' FROM orders").and_then(|r| r.collect()).is_err());
+        assert!(strict.query("SELECT id * price FROM orders").and_then(|r| r.collect()).is_err());
+        assert!(strict.execute("INSERT INTO orders VALUES ('3', 'c', 5.0)").is_err());
+        assert!(strict.execute("UPDATE orders SET code = 7").is_err());
+        assert_eq!(strict.query("SELECT COUNT(*) FROM orders")?.collect()?.rows, [[Value::BigInt(2)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_overrides_config() -> PrismDBResult<()> {
+        let mut db = orders_database(TypeCoercion::Lenient)?;
+        db.execute("SET type_coercion = 'strict'")?;
+        assert!(db.query("SELECT * FROM orders WHERE id = '1'").and_then(|r| r.collect()).is_err());
+        db.execute("SET type_coercion = 'lenient'")?;
+        assert_eq!(db.query("SELECT id FROM orders WHERE id = '1'")?.collect()?.rows, [[Value::Integer(1)]]);
+        assert!(db.execute("SET type_coercion = 'loose'").is_err());
+        Ok(())
+    }
+}
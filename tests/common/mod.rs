@@ -0,0 +1,71 @@
+//! Helpers shared by the integration test suites
+//!
+//! Each suite declares `mod common;` and uses only some of these, so unused
+//! ones are allowed.
+
+#![allow(dead_code)]
+
+use prism::parser::parse_sql;
+use prism::planner::{PhysicalPlan, QueryOptimizer, QueryPlanner};
+use prism::storage::TransactionManager;
+use prism::{Database, PrismDBResult, Value};
+use std::sync::Arc;
+
+/// An in-memory database with the `setup` statements run in it
+pub fn database(setup: &[&str]) -> PrismDBResult<Database> {
+    let mut db = Database::new_in_memory()?;
+    for sql in setup {
+        db.execute(sql)?;
+    }
+    Ok(db)
+}
+
+/// Insert generated `(...)` row tuples into `table` with one statement
+pub fn insert_rows(
+    db: &mut Database,
+    table: &str,
+    rows: impl IntoIterator<Item = String>,
+) -> PrismDBResult<()> {
+    let values: Vec<String> = rows.into_iter().collect();
+    db.execute(&format!("INSERT INTO {} VALUES {}", table, values.join(", ")))?;
+    Ok(())
+}
+
+/// All rows a query returns
+pub fn rows(db: &Database, sql: &str) -> PrismDBResult<Vec<Vec<Value>>> {
+    Ok(db.query(sql)?.collect()?.rows)
+}
+
+/// The first column of the first row a query returns
+pub fn first_value(db: &Database, sql: &str) -> PrismDBResult<Value> {
+    Ok(db.query(sql)?.collect()?.rows[0][0].clone())
+}
+
+/// Names of a query's output columns
+pub fn column_names(db: &Database, sql: &str) -> PrismDBResult<Vec<String>> {
+    Ok(db.query(sql)?.columns.iter().map(|column| column.name.clone()).collect())
+}
+
+/// The plan EXPLAIN prints for a query
+pub fn explain(db: &Database, sql: &str) -> PrismDBResult<String> {
+    match &db.query(&format!("EXPLAIN {}", sql))?.collect()?.rows[0][0] {
+        Value::Varchar(plan) => Ok(plan.clone()),
+        other => panic!("expected a plan string, got {:?}", other),
+    }
+}
+
+/// The optimized physical plan of a query
+pub fn physical_plan(db: &Database, sql: &str) -> PrismDBResult<PhysicalPlan> {
+    let statement = parse_sql(sql)?;
+    let mut planner = QueryPlanner::new_with_catalog(db.catalog());
+    let logical = planner.plan_statement(&statement)?;
+    let mut optimizer = QueryOptimizer::new()
+        .with_context(db.catalog(), Arc::new(TransactionManager::new()))
+        .with_ctes(planner.get_ctes());
+    optimizer.optimize(logical)
+}
+
+/// A VARCHAR value
+pub fn text(s: &str) -> Value {
+    Value::Varchar(s.to_string())
+}
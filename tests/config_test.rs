@@ -0,0 +1,463 @@
+//! Tests for SET / SHOW runtime configuration, PRAGMA, metrics and resource
+//! limits
+
+use prism::{Database, DatabaseConfig, PrismDBError, PrismDBResult, Value};
+
+mod common;
+use common::text;
+
+fn show(db: &Database, name: &str) -> PrismDBResult<Value> {
+    let result = db.query(&format!("SHOW {}", name))?;
+    assert_eq!(result.columns.len(), 1);
+    assert_eq!(result.columns[0].name, name.to_lowercase());
+    let rows = result.collect()?.rows;
+    assert_eq!(rows.len(), 1);
+    Ok(rows[0][0].clone())
+}
+
+#[test]
+fn test_set_and_show_known_settings() -> PrismDBResult<()> {
+    let mut db = Database::new_in_memory()?;
+
+    for (sql, name, expected) in [
+        ("SET memory_limit = '1GB'", "memory_limit", "1GB"),
+        ("SET memory_limit TO '512MiB'", "memory_limit", "512MiB"),
+        ("SET threads = 2", "threads", "2"),
+        ("SET default_null_order = 'NULLS_FIRST'", "default_null_order", "nulls_first"),
+        ("SET enable_parallelism = false", "enable_parallelism", "false"),
+        ("SET s3_region = 'eu-west-1'", "s3_region", "eu-west-1"),
+        ("SET s3_use_ssl = 'off'", "s3_use_ssl", "false"),
+    ] {
+        db.execute(sql)?;
+        assert_eq!(show(&db, name)?, text(expected), "{}", sql);
+    }
+
+    // Setting names are case-insensitive
+    assert_eq!(show(&db, "THREADS")?, text("2"));
+    Ok(())
+}
+
+#[test]
+fn test_show_defaults_from_config() -> PrismDBResult<()> {
+    let config = DatabaseConfig {
+        threads: 3,
+        memory_limit: Some(64 * 1024),
+        ..DatabaseConfig::in_memory()
+    };
+    let mut db = Database::new(config)?;
+
+    assert_eq!(show(&db, "threads")?, text("3"));
+    assert_eq!(show(&db, "memory_limit")?, text("64KiB"));
+    assert_eq!(show(&db, "default_null_order")?, text("nulls_largest"));
+    assert_eq!(show(&db, "enable_parallelism")?, text("true"));
+    assert_eq!(show(&db, "s3_endpoint")?, Value::Null);
+
+    // DEFAULT goes back to the configured value
+    db.execute("SET threads = 8")?;
+    assert_eq!(show(&db, "threads")?, text("8"));
+    db.execute("SET threads = DEFAULT")?;
+    assert_eq!(show(&db, "threads")?, text("3"));
+    Ok(())
+}
+
+#[test]
+fn test_show_variables() -> PrismDBResult<()> {
+    let mut db = Database::new_in_memory()?;
+    db.execute("SET memory_limit = '2GB'")?;
+
+    let result = db.query("SHOW VARIABLES")?;
+    let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["name", "value", "description"]);
+
+    let rows = result.collect()?.rows;
+    let value_of = |name: &str| {
+        rows.iter()
+            .find(|row| row[0] == text(name))
+            .map(|row| row[1].clone())
+    };
+    assert_eq!(value_of("memory_limit"), Some(text("2GB")));
+    assert_eq!(value_of("default_null_order"), Some(text("nulls_largest")));
+    assert!(value_of("threads").is_some());
+    assert!(value_of("enable_parallelism").is_some());
+    Ok(())
+}
+
+#[test]
+fn test_set_rejects_unknown_and_invalid() -> PrismDBResult<()> {
+    let mut db = Database::new_in_memory()?;
+
+    assert!(matches!(
+        db.execute("SET no_such_setting = 1"),
+        Err(PrismDBError::InvalidArgument(_))
+    ));
+    assert!(db.execute("SET no_such_setting = DEFAULT").is_err());
+    assert!(db.query("SHOW no_such_setting").is_err());
+
+    assert!(db.execute("SET threads = 'many'").is_err());
+    assert!(db.execute("SET enable_parallelism = 'maybe'").is_err());
+    assert!(db.execute("SET default_null_order = 'sideways'").is_err());
+    assert!(db.execute("SET memory_limit = 'lots'").is_err());
+
+    // A rejected SET leaves the previous value in place
+    db.execute("SET threads = 4")?;
+    assert!(db.execute("SET threads = -1").is_err());
+    assert_eq!(show(&db, "threads")?, text("4"));
+    Ok(())
+}
+
+#[test]
+fn test_settings_apply_to_queries() -> PrismDBResult<()> {
+    let mut db = Database::new_in_memory()?;
+    db.execute("CREATE TABLE t (x INTEGER)")?;
+    db.execute("INSERT INTO t VALUES (2), (NULL), (1)")?;
+
+    db.execute("SET threads = 1")?;
+    db.execute("SET enable_parallelism = false")?;
+    db.execute("SET default_null_order = 'nulls_first'")?;
+    let rows = db.query("SELECT x FROM t ORDER BY x")?.collect()?.rows;
+    assert_eq!(
+        rows,
+        vec![vec![Value::Null], vec![Value::Integer(1)], vec![Value::Integer(2)]]
+    );
+    Ok(())
+}
+
+/// Tests for PRAGMA introspection and settings
+#[cfg(test)]
+mod pragma_tests {
+    use crate::common::text;
+    use prism::{Database, PrismDBError, PrismDBResult, Value};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pragma_table_info() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE accounts (
+            id INTEGER PRIMARY KEY,
+            email VARCHAR NOT NULL,
+            balance DOUBLE DEFAULT 0
+        )",
+        )?;
+
+        let result = db.query("PRAGMA table_info('accounts')")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["cid", "name", "type", "notnull", "dflt_value", "pk"]);
+        assert_eq!(
+            result.collect()?.rows,
+            vec![
+                vec![Value::Integer(0), text("id"), text("INTEGER"), Value::Boolean(true), Value::Null, Value::Boolean(true)],
+                vec![Value::Integer(1), text("email"), text("VARCHAR"), Value::Boolean(true), Value::Null, Value::Boolean(false)],
+                vec![Value::Integer(2), text("balance"), text("DOUBLE"), Value::Boolean(false), text("0"), Value::Boolean(false)],
+            ]
+        );
+
+        assert!(db.query("PRAGMA table_info('missing')").is_err());
+        assert!(db.query("PRAGMA table_info").is_err());
+        assert!(db.query("PRAGMA no_such_pragma").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pragma_settings() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE numbers (id INTEGER, label VARCHAR)")?;
+        let values: Vec<String> = (0..1_000).map(|i| format!("({}, 'label-{}')", i, i)).collect();
+        db.execute(&format!("INSERT INTO numbers VALUES {}", values.join(", ")))?;
+        let aggregate = "SELECT id, label, COUNT(*) FROM numbers GROUP BY id, label";
+
+        // Assigning a setting through PRAGMA works like SET
+        db.execute("PRAGMA memory_limit = '64KB'")?;
+        assert_eq!(db.query("PRAGMA memory_limit")?.collect()?.rows, vec![vec![text("64KB")]]);
+        assert!(matches!(db.query(aggregate), Err(PrismDBError::OutOfMemory)));
+
+        db.execute("PRAGMA memory_limit('1GB')")?;
+        assert_eq!(db.query(aggregate)?.row_count(), 1_000);
+
+        db.execute("PRAGMA threads = 2")?;
+        assert_eq!(db.query("SHOW threads")?.collect()?.rows, vec![vec![text("2")]]);
+        assert_eq!(db.query("PRAGMA threads")?.collect()?.rows, vec![vec![text("2")]]);
+        assert!(db.query("PRAGMA threads = -1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pragma_database_size() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let result = db.query("PRAGMA database_size")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["database_name", "database_size", "block_size", "total_blocks", "memory_limit"]
+        );
+        let rows = result.collect()?.rows;
+        assert_eq!(rows[0][0], text("memory"));
+        assert_eq!(rows[0][1], Value::BigInt(0));
+        assert_eq!(rows[0][4], text("unlimited"));
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sizes.db");
+        let db = Database::open(&path)?;
+        db.execute_sql_collect("CREATE TABLE t (id INTEGER)")?;
+        db.sync()?;
+        let rows = db.query("PRAGMA database_size")?.collect()?.rows;
+        assert_eq!(rows[0][0], text("sizes"));
+        let size = std::fs::metadata(&path).unwrap().len() as i64;
+        assert_eq!(rows[0][1], Value::BigInt(size));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pragma_storage_info() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE visits (region VARCHAR, visitor VARCHAR)")?;
+        let regions = ["north", "south", "east", "west"];
+        let rows: Vec<String> = (0..3000)
+            .map(|i| format!("('{}', 'visitor-{:06}')", regions[i % regions.len()], i))
+            .collect();
+        db.execute(&format!("INSERT INTO visits VALUES {}", rows.join(", ")))?;
+
+        let result = db.query("PRAGMA storage_info('visits')")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["column_name", "column_type", "compression", "compressed_size", "uncompressed_size", "segment_count"]
+        );
+        let rows = result.collect()?.rows;
+        assert_eq!(rows.len(), 2);
+
+        // Few distinct regions: dictionary encoding shrinks them
+        assert_eq!(rows[0][0], text("region"));
+        assert_eq!(rows[0][1], text("VARCHAR"));
+        assert_eq!(rows[0][2], text("Dictionary"));
+        let (Value::BigInt(compressed), Value::BigInt(uncompressed)) = (&rows[0][3], &rows[0][4]) else {
+            panic!("sizes should be BIGINT: {:?}", rows[0]);
+        };
+        assert!(compressed < uncompressed, "{} >= {}", compressed, uncompressed);
+        assert_eq!(rows[0][5], Value::BigInt(2));
+
+        // Every visitor is distinct, so nothing beats storing them as they are
+        assert_eq!(rows[1][0], text("visitor"));
+        assert_eq!(rows[1][2], text("Uncompressed"));
+        assert_eq!(rows[1][3], rows[1][4]);
+        assert_eq!(rows[1][5], Value::BigInt(2));
+
+        assert!(db.query("PRAGMA storage_info('missing')").is_err());
+        assert!(db.query("PRAGMA storage_info").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for the database's metrics counters and the prism_metrics table function
+#[cfg(test)]
+mod metrics_tests {
+    use crate::common::insert_rows;
+    use prism::{Database, DatabaseConfig, Metric, PrismDBResult, Value};
+
+    fn events_database() -> PrismDBResult<Database> {
+        let config = DatabaseConfig {
+            enable_query_cache: true,
+            ..DatabaseConfig::in_memory()
+        };
+        let mut db = Database::new(config)?;
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR)")?;
+        insert_rows(&mut db, "events", (0..100).map(|i| format!("({}, 'k{}')", i, i % 7)))?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_counters_advance() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        let before = db.metrics();
+
+        db.query("SELECT COUNT(*) FROM events WHERE id < 10")?;
+        db.query("SELECT COUNT(*) FROM events WHERE id < 10")?;
+        db.execute("UPDATE events SET kind = 'x' WHERE id = 1")?;
+        assert!(db.query("SELECT missing FROM events").is_err());
+
+        let after = db.metrics();
+        let delta = |metric| after.get(metric) - before.get(metric);
+        assert_eq!(delta(Metric::QueriesExecuted), 4);
+        assert_eq!(delta(Metric::QueryErrors), 1);
+        // The first SELECT and the UPDATE each read the whole table
+        assert_eq!(delta(Metric::RowsScanned), 200);
+        assert_eq!(delta(Metric::QueryCacheHits), 1);
+        assert_eq!(delta(Metric::QueryCacheMisses), 2);
+        assert_eq!(delta(Metric::MemoryLimitErrors), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_limit_errors_are_counted() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        db.execute("SET memory_limit = '1KB'")?;
+
+        assert!(db.query("SELECT id, COUNT(*) FROM events GROUP BY id").is_err());
+        assert_eq!(db.metrics().get(Metric::MemoryLimitErrors), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prism_metrics_table_function() -> PrismDBResult<()> {
+        let db = events_database()?;
+        db.query("SELECT * FROM events")?;
+
+        let result = db.query("SELECT * FROM prism_metrics()")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["metric", "value", "description"]);
+
+        let rows = result.collect()?.rows;
+        let metrics: Vec<&Value> = rows.iter().map(|row| &row[0]).collect();
+        assert_eq!(metrics.len(), Metric::ALL.len());
+        let scanned = rows
+            .iter()
+            .find(|row| row[0] == Value::Varchar("rows_scanned".to_string()))
+            .expect("rows_scanned is listed");
+        assert_eq!(scanned[1], Value::BigInt(100));
+
+        assert!(db.query("SELECT * FROM prism_metrics(1)").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for the per-database result-row cap, scan budget and statement timeout
+#[cfg(test)]
+mod query_limits_tests {
+    use crate::common::insert_rows;
+    use prism::{Database, DatabaseConfig, PrismDBError, PrismDBResult};
+    use std::time::Duration;
+
+    fn events_database(config: DatabaseConfig) -> PrismDBResult<Database> {
+        let mut db = Database::new(config)?;
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR)")?;
+        insert_rows(&mut db, "events", (0..5000).map(|i| format!("({}, 'k{}')", i, i % 7)))?;
+        Ok(db)
+    }
+
+    fn limit_error(result: PrismDBResult<impl std::fmt::Debug>) -> String {
+        match result {
+            Err(e @ PrismDBError::LimitExceeded(_)) => e.to_string(),
+            other => panic!("expected a limit error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_result_row_cap() -> PrismDBResult<()> {
+        let db = events_database(DatabaseConfig {
+            max_result_rows: Some(100),
+            ..DatabaseConfig::in_memory()
+        })?;
+
+        let error = limit_error(db.query("SELECT * FROM events"));
+        assert!(error.contains("more than 100 rows"), "{}", error);
+        assert!(db.query("SELECT * FROM events WHERE id < 101").is_err());
+
+        // At or under the cap is fine, however many rows were read to get there
+        assert_eq!(db.query("SELECT * FROM events WHERE id < 100")?.collect()?.rows.len(), 100);
+        assert_eq!(db.query("SELECT kind, COUNT(*) FROM events GROUP BY kind")?.collect()?.rows.len(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_row_budget() -> PrismDBResult<()> {
+        let mut db = events_database(DatabaseConfig {
+            max_scan_rows: Some(6000),
+            ..DatabaseConfig::in_memory()
+        })?;
+
+        // One pass over the table fits, a self-join reads it twice
+        assert_eq!(db.query("SELECT COUNT(*) FROM events WHERE id % 2 = 0")?.collect()?.rows.len(), 1);
+        let error = limit_error(db.query("SELECT COUNT(*) FROM events a JOIN events b ON a.id = b.id"));
+        assert!(error.contains("scanned more than 6000 rows"), "{}", error);
+
+        // The budget is per statement
+        db.execute("DELETE FROM events WHERE id >= 1000")?;
+        db.query("SELECT COUNT(*) FROM events a JOIN events b ON a.id = b.id")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_statement_timeout() -> PrismDBResult<()> {
+        let timed_out = Database::new(DatabaseConfig {
+            statement_timeout: Some(Duration::ZERO),
+            ..DatabaseConfig::in_memory()
+        })?;
+        let error = limit_error(timed_out.query("SELECT 1"));
+        assert!(error.contains("statement timeout"), "{}", error);
+
+        let db = events_database(DatabaseConfig {
+            statement_timeout: Some(Duration::from_secs(60)),
+            ..DatabaseConfig::in_memory()
+        })?;
+        assert_eq!(db.query("SELECT * FROM events")?.collect()?.rows.len(), 5000);
+        Ok(())
+    }
+}
+
+/// Tests for the per-column NULL counts and ranges gathered while collecting results
+#[cfg(test)]
+mod column_summaries_tests {
+    use prism::{ColumnProfile, Database, DatabaseConfig, PrismDBResult, Value};
+
+    fn readings_database(config: DatabaseConfig) -> PrismDBResult<Database> {
+        let mut db = Database::new(config)?;
+        db.execute("CREATE TABLE readings (id INTEGER, sensor VARCHAR, level DOUBLE)")?;
+        // Enough rows for several chunks; every tenth level and the first sensor are NULL
+        let values: Vec<String> = (0..5000)
+            .map(|i| {
+                let sensor = if i == 0 { "NULL".to_string() } else { format!("'s{}'", i % 3) };
+                let level = if i % 10 == 0 { "NULL".to_string() } else { format!("{}.5", i - 2500) };
+                format!("({}, {}, {})", i, sensor, level)
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO readings VALUES {}", values.join(", ")))?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_column_summaries() -> PrismDBResult<()> {
+        let db = readings_database(DatabaseConfig {
+            collect_column_summaries: true,
+            ..DatabaseConfig::in_memory()
+        })?;
+
+        let result = db.query("SELECT id, sensor, level FROM readings")?;
+        assert_eq!(
+            result.column_summaries().unwrap(),
+            [
+                ColumnProfile {
+                    null_count: 0,
+                    min: Some(Value::Integer(0)),
+                    max: Some(Value::Integer(4999)),
+                },
+                ColumnProfile {
+                    null_count: 1,
+                    min: Some(Value::Varchar("s0".to_string())),
+                    max: Some(Value::Varchar("s2".to_string())),
+                },
+                ColumnProfile {
+                    null_count: 500,
+                    min: Some(Value::Double(-2499.5)),
+                    max: Some(Value::Double(2499.5)),
+                },
+            ]
+        );
+
+        // A column of only NULLs has no range, and an empty result counts nothing
+        let result = db.query("SELECT level FROM readings WHERE id % 10 = 0")?;
+        assert_eq!(
+            result.column_summaries().unwrap(),
+            [ColumnProfile { null_count: 500, min: None, max: None }]
+        );
+        let result = db.query("SELECT level FROM readings WHERE id < 0")?;
+        assert_eq!(result.column_summaries().unwrap(), [ColumnProfile::default()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_summaries_off_by_default() -> PrismDBResult<()> {
+        let db = readings_database(DatabaseConfig::in_memory())?;
+        assert!(db.query("SELECT * FROM readings")?.column_summaries().is_none());
+        Ok(())
+    }
+}
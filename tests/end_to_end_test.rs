@@ -4,6 +4,8 @@
 //! 1. CREATE TABLE - Creates a table in the catalog
 //! 2. INSERT - Inserts data into the table
 //! 3. SELECT - Reads the data back and verifies correctness
+//!
+//! The modules after it cover the rest of DDL and DML through SQL.
 
 use prism::catalog::Catalog;
 use prism::execution::pipeline::TableScanSource;
@@ -13,6 +15,8 @@ use prism::storage::TransactionManager;
 use prism::types::{LogicalType, Value};
 use std::sync::{Arc, RwLock};
 
+mod common;
+
 #[test]
 fn test_create_insert_select_flow() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Testing CREATE TABLE → INSERT → SELECT Flow ===\n");
@@ -35,6 +39,8 @@ fn test_create_insert_select_flow() -> Result<(), Box<dyn std::error::Error>> {
             PhysicalColumn::new("age".to_string(), LogicalType::Integer),
         ],
         if_not_exists: false,
+        columns: vec![],
+        constraints: vec![],
     };
 
     let mut engine = ExecutionEngine::new(context.clone());
@@ -109,6 +115,7 @@ fn test_create_insert_select_flow() -> Result<(), Box<dyn std::error::Error>> {
         column_ids: vec![0, 1, 2],
         filters: vec![],
         limit: None,
+        ranges: vec![],
     };
 
     let mut table_scan = TableScanSource::new(scan_plan, context.clone())?;
@@ -178,6 +185,8 @@ fn test_create_table_if_not_exists() -> Result<(), Box<dyn std::error::Error>> {
         table_name: "test_table".to_string(),
         schema: vec![PhysicalColumn::new("id".to_string(), LogicalType::Integer)],
         if_not_exists: false,
+        columns: vec![],
+        constraints: vec![],
     };
 
     let mut engine = ExecutionEngine::new(context.clone());
@@ -192,6 +201,8 @@ fn test_create_table_if_not_exists() -> Result<(), Box<dyn std::error::Error>> {
         table_name: "test_table".to_string(),
         schema: vec![PhysicalColumn::new("id".to_string(), LogicalType::Integer)],
         if_not_exists: true,
+        columns: vec![],
+        constraints: vec![],
     };
 
     let mut engine2 = ExecutionEngine::new(context.clone());
@@ -221,6 +232,8 @@ fn test_drop_table() -> Result<(), Box<dyn std::error::Error>> {
         table_name: "temp_table".to_string(),
         schema: vec![PhysicalColumn::new("id".to_string(), LogicalType::Integer)],
         if_not_exists: false,
+        columns: vec![],
+        constraints: vec![],
     };
 
     let mut engine = ExecutionEngine::new(context.clone());
@@ -265,3 +278,1214 @@ fn test_drop_table() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Tests for SHOW CREATE TABLE and catalog DDL round-tripping
+#[cfg(test)]
+mod show_create_table_tests {
+    use prism::{Database, PrismDBResult, TableInfo, Value};
+
+    fn table_info(db: &Database, table: &str) -> TableInfo {
+        let catalog = db.catalog();
+        let catalog = catalog.read().unwrap();
+        let table = catalog.get_table("main", table).unwrap();
+        let info = table.read().unwrap().get_table_info();
+        info
+    }
+
+    /// Everything about a table's schema that DDL should reproduce
+    fn schema_summary(info: &TableInfo) -> String {
+        let columns: Vec<String> = info
+            .columns
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} {} nullable={} default={:?} pk={} unique={}",
+                    c.name, c.column_type, c.nullable, c.default_expression, c.is_primary_key, c.is_unique
+                )
+            })
+            .collect();
+        format!("{:?} {:?} {:?}", columns, info.primary_key, info.constraints)
+    }
+
+    fn show_create_table(db: &Database, table: &str) -> PrismDBResult<String> {
+        let result = db.query(&format!("SHOW CREATE TABLE {}", table))?;
+        match result.first_value() {
+            Some(Value::Varchar(ddl)) => Ok(ddl),
+            other => panic!("Expected DDL string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_show_create_table_constraints() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE products (
+            id INTEGER PRIMARY KEY,
+            sku CHAR(8) UNIQUE,
+            price DECIMAL(10,2) NOT NULL DEFAULT 0,
+            name VARCHAR DEFAULT 'unnamed',
+            CHECK (price >= 0)
+        )",
+        )?;
+
+        let ddl = show_create_table(&db, "products")?;
+        assert_eq!(
+            ddl,
+            "CREATE TABLE products (\n\
+         \x20 id INTEGER PRIMARY KEY,\n\
+         \x20 sku CHAR(8) UNIQUE,\n\
+         \x20 price DECIMAL(10,2) NOT NULL DEFAULT 0,\n\
+         \x20 name VARCHAR DEFAULT 'unnamed',\n\
+         \x20 CHECK (price >= 0)\n\
+         );"
+        );
+
+        assert!(db.query("SHOW CREATE TABLE missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_table_ddl_round_trip() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE orders (
+            region VARCHAR NOT NULL,
+            order_id BIGINT,
+            quantity SMALLINT DEFAULT 1,
+            amount DOUBLE,
+            flag BOOLEAN DEFAULT FALSE,
+            created TIMESTAMP,
+            customer_id INT REFERENCES customers(id),
+            PRIMARY KEY (region, order_id),
+            UNIQUE (customer_id, created),
+            CHECK (quantity > 0 AND (amount IS NULL OR amount >= quantity * 2))
+        )",
+        )?;
+
+        let original = table_info(&db, "orders");
+        let ddl = show_create_table(&db, "orders")?;
+
+        db.execute("DROP TABLE orders")?;
+        db.execute(&ddl)?;
+
+        let recreated = table_info(&db, "orders");
+        assert_eq!(schema_summary(&original), schema_summary(&recreated));
+        assert_eq!(show_create_table(&db, "orders")?, ddl);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ddl_quotes_names_that_need_it() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE \"Orders\" (
+            \"order\" INTEGER PRIMARY KEY,
+            \"Name\" VARCHAR NOT NULL,
+            \"say \"\"hi\"\"\" VARCHAR,
+            plain INTEGER REFERENCES \"Select\"(\"From\"),
+            UNIQUE (\"Name\", plain)
+        )",
+        )?;
+
+        let original = table_info(&db, "Orders");
+        let ddl = show_create_table(&db, "\"Orders\"")?;
+        assert!(ddl.starts_with("CREATE TABLE \"Orders\" (\n  \"order\" INTEGER PRIMARY KEY,"), "{}", ddl);
+        assert!(ddl.contains("  plain INTEGER"), "{}", ddl);
+        assert!(ddl.contains("REFERENCES \"Select\"(\"From\")"), "{}", ddl);
+
+        // Running the DDL again recreates the same names
+        db.execute("DROP TABLE \"Orders\"")?;
+        db.execute(&ddl)?;
+        let recreated = table_info(&db, "Orders");
+        assert_eq!(schema_summary(&original), schema_summary(&recreated));
+        assert_eq!(show_create_table(&db, "\"Orders\"")?, ddl);
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_column_type_round_trips() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TYPE mood AS ENUM ('sad', 'happy')")?;
+
+        // Declared type and the name SHOW CREATE TABLE gives it
+        let types = [
+            ("BOOLEAN", "BOOLEAN"),
+            ("TINYINT", "TINYINT"),
+            ("SMALLINT", "SMALLINT"),
+            ("INTEGER", "INTEGER"),
+            ("BIGINT", "BIGINT"),
+            ("HUGEINT", "HUGEINT"),
+            ("FLOAT", "FLOAT"),
+            ("DOUBLE", "DOUBLE"),
+            ("VARCHAR", "VARCHAR"),
+            ("VARCHAR(10)", "VARCHAR(10)"),
+            ("TEXT", "VARCHAR"),
+            ("CHAR(3)", "CHAR(3)"),
+            ("DECIMAL(12,3)", "DECIMAL(12,3)"),
+            ("DATE", "DATE"),
+            ("TIME", "TIME"),
+            ("TIMESTAMP", "TIMESTAMP"),
+            ("INTERVAL", "INTERVAL"),
+            ("UUID", "UUID"),
+            ("JSON", "JSON"),
+            ("BLOB", "BLOB"),
+            ("INTEGER[]", "INTEGER[]"),
+            ("VARCHAR[][]", "VARCHAR[][]"),
+            ("MAP(VARCHAR, INTEGER)", "MAP(VARCHAR, INTEGER)"),
+            ("mood", "mood"),
+        ];
+        for (declared, rendered) in types {
+            db.execute(&format!("CREATE TABLE typed (c {})", declared))?;
+            let ddl = show_create_table(&db, "typed")?;
+            assert_eq!(ddl, format!("CREATE TABLE typed (\n  c {}\n);", rendered));
+
+            let original = table_info(&db, "typed");
+            db.execute("DROP TABLE typed")?;
+            db.execute(&ddl)?;
+            assert_eq!(schema_summary(&original), schema_summary(&table_info(&db, "typed")));
+            db.execute("DROP TABLE typed")?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stored_expressions_reject_subqueries() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE limits (n INTEGER)")?;
+
+        // DEFAULT and CHECK are kept as SQL text, which has no way to spell a subquery
+        for ddl in [
+            "CREATE TABLE t (x INTEGER DEFAULT (SELECT MAX(n) FROM limits))",
+            "CREATE TABLE t (x INTEGER CHECK (x IN (SELECT n FROM limits)))",
+            "CREATE TABLE t (x INTEGER, CHECK (EXISTS (SELECT 1 FROM limits WHERE n = x)))",
+        ] {
+            let err = db.execute(ddl).unwrap_err().to_string();
+            assert!(err.contains("Subqueries are not allowed"), "{}", err);
+        }
+        assert!(db.query("SHOW CREATE TABLE t").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for DESCRIBE / DESC
+#[cfg(test)]
+mod describe_tests {
+    use crate::common::{database, text};
+    use prism::{Database, LogicalType, PrismDBResult, Value};
+
+    #[test]
+    fn test_describe_table() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE accounts (
+            id INTEGER PRIMARY KEY,
+            email VARCHAR NOT NULL UNIQUE,
+            balance DECIMAL(12,2) DEFAULT 0,
+            note VARCHAR
+        )",
+        )?;
+
+        let result = db.query("DESCRIBE accounts")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["column_name", "column_type", "null", "key", "default"]);
+
+        let rows = result.collect()?.rows;
+        assert_eq!(
+            rows,
+            vec![
+                vec![text("id"), text("INTEGER"), text("NO"), text("PRI"), Value::Null],
+                vec![text("email"), text("VARCHAR"), text("NO"), text("UNI"), Value::Null],
+                vec![text("balance"), text("DECIMAL(12,2)"), text("YES"), Value::Null, text("0")],
+                vec![text("note"), text("VARCHAR"), text("YES"), Value::Null, Value::Null],
+            ]
+        );
+
+        // DESC is shorthand for DESCRIBE
+        assert_eq!(db.query("DESC accounts")?.collect()?.rows, rows);
+
+        assert!(db.query("DESCRIBE missing").is_err());
+        Ok(())
+    }
+
+    const SALES: &[&str] = &[
+        "CREATE TABLE products (id INTEGER, name VARCHAR, price DOUBLE)",
+        "CREATE TABLE sales (product_id INTEGER, quantity BIGINT)",
+        "INSERT INTO products VALUES (1, 'widget', 2.5)",
+    ];
+
+    fn schema(columns: &[(&str, LogicalType)]) -> Vec<(String, LogicalType)> {
+        columns
+            .iter()
+            .map(|(name, data_type)| (name.to_string(), data_type.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_describe_query_projection() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let sql = "SELECT id AS product, name, price * 2 AS doubled, price > 1 AS pricey FROM products";
+
+        let described = db.describe(sql)?;
+        assert_eq!(
+            described,
+            schema(&[
+                ("product", LogicalType::Integer),
+                ("name", LogicalType::Varchar),
+                ("doubled", LogicalType::Double),
+                ("pricey", LogicalType::Boolean),
+            ])
+        );
+
+        // Matches the columns reported when the query actually runs
+        let executed: Vec<(String, LogicalType)> = db
+            .query(sql)?
+            .columns
+            .into_iter()
+            .map(|c| (c.name, c.data_type))
+            .collect();
+        assert_eq!(described, executed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_query_aggregate() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let described =
+            db.describe("SELECT name, COUNT(*), SUM(price) AS total, AVG(id) FROM products GROUP BY name")?;
+        assert_eq!(
+            described,
+            schema(&[
+                ("name", LogicalType::Varchar),
+                ("COUNT(*)", LogicalType::BigInt),
+                ("total", LogicalType::Double),
+                ("AVG(id)", LogicalType::Double),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_query_scalar_functions() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let sql = "SELECT abs(price) AS magnitude, length(name) AS letters, \
+                   starts_with(name, 'w') AS w, contains(name, 'x') AS x FROM products";
+
+        let described = db.describe(sql)?;
+        assert_eq!(
+            described,
+            schema(&[
+                ("magnitude", LogicalType::Double),
+                ("letters", LogicalType::Integer),
+                ("w", LogicalType::Boolean),
+                ("x", LogicalType::Boolean),
+            ])
+        );
+        let executed: Vec<(String, LogicalType)> = db
+            .query(sql)?
+            .columns
+            .into_iter()
+            .map(|c| (c.name, c.data_type))
+            .collect();
+        assert_eq!(described, executed);
+
+        let rows = db.query(&format!("DESCRIBE {}", sql))?.collect()?.rows;
+        assert_eq!(rows[0][1], text("DOUBLE"));
+        assert_eq!(rows[1][1], text("INTEGER"));
+        assert_eq!(rows[2][1], text("BOOLEAN"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_query_join() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let sql = "SELECT p.name AS product, s.quantity FROM products p JOIN sales s ON p.id = s.product_id";
+        assert_eq!(
+            db.describe(sql)?,
+            schema(&[
+                ("product", LogicalType::Varchar),
+                ("s.quantity", LogicalType::BigInt),
+            ])
+        );
+
+        // DESCRIBE SELECT returns the same information as a result set
+        let rows = db.query(&format!("DESCRIBE {}", sql))?.collect()?.rows;
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0], text("product"));
+        assert_eq!(rows[0][1], text("VARCHAR"));
+        assert_eq!(rows[1][0], text("s.quantity"));
+        assert_eq!(rows[1][1], text("BIGINT"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_does_not_execute() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        // Planning a SELECT leaves the data untouched and errors surface at bind time
+        db.describe("SELECT * FROM products")?;
+        assert_eq!(db.query("SELECT * FROM products")?.row_count(), 1);
+        assert!(db.describe("SELECT * FROM missing").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for SUMMARIZE column profiling
+#[cfg(test)]
+mod summarize_tests {
+    use crate::common::{database, text};
+    use prism::{PrismDBResult, Value};
+
+    const PEOPLE: &[&str] = &[
+        "CREATE TABLE people (id INTEGER, name VARCHAR, score DOUBLE, active BOOLEAN)",
+        "INSERT INTO people VALUES \
+     (1, 'alice', 2.0, true), \
+     (2, 'bob', 4.0, false), \
+     (3, 'carol', NULL, true), \
+     (4, 'alice', 6.0, NULL)",
+    ];
+
+    #[test]
+    fn test_summarize_table() -> PrismDBResult<()> {
+        let db = database(PEOPLE)?;
+        let result = db.query("SUMMARIZE people")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["column_name", "column_type", "min", "max", "approx_unique", "null_percentage", "avg", "std"]
+        );
+
+        let rows = result.collect()?.rows;
+        assert_eq!(rows.len(), 4);
+
+        let id = &rows[0];
+        assert_eq!(id[0], text("id"));
+        assert_eq!(id[1], text("INTEGER"));
+        assert_eq!((&id[2], &id[3]), (&text("1"), &text("4")));
+        assert_eq!(id[4], Value::BigInt(4));
+        assert_eq!(id[5], Value::Double(0.0));
+        assert_eq!(id[6], Value::Double(2.5));
+        let Value::Double(std) = id[7] else { panic!("std is {:?}", id[7]) };
+        assert!((std - 1.290_994).abs() < 1e-6);
+
+        // Strings have a min, max and distinct count but no mean
+        assert_eq!(
+            rows[1],
+            vec![
+                text("name"),
+                text("VARCHAR"),
+                text("alice"),
+                text("carol"),
+                Value::BigInt(3),
+                Value::Double(0.0),
+                Value::Null,
+                Value::Null,
+            ]
+        );
+
+        // NULLs are left out of every statistic but the NULL percentage
+        let score = &rows[2];
+        assert_eq!((&score[2], &score[3]), (&text("2"), &text("6")));
+        assert_eq!(score[4], Value::BigInt(3));
+        assert_eq!(score[5], Value::Double(25.0));
+        assert_eq!(score[6], Value::Double(4.0));
+        assert_eq!(score[7], Value::Double(2.0));
+
+        let active = &rows[3];
+        assert_eq!((&active[2], &active[3]), (&text("false"), &text("true")));
+        assert_eq!(active[4], Value::BigInt(2));
+        assert_eq!(active[5], Value::Double(25.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_summarize_query() -> PrismDBResult<()> {
+        let db = database(PEOPLE)?;
+        let rows = db
+            .query("SUMMARIZE SELECT name FROM people WHERE id > 1")?
+            .collect()?
+            .rows;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], text("name"));
+        assert_eq!((&rows[0][2], &rows[0][3]), (&text("alice"), &text("carol")));
+        assert_eq!(rows[0][4], Value::BigInt(3));
+
+        assert!(db.query("SUMMARIZE missing_table").is_err());
+        Ok(())
+    }
+}
+
+/// Tests that `.dump`-style SQL, the table DDL followed by one INSERT of SQL
+/// literals per row, replays into an identical table
+#[cfg(test)]
+mod dump_tests {
+    use crate::common::rows;
+    use prism::{quote_identifier, Database, PrismDBResult, Value};
+
+    /// The statements the CLI's `.dump TABLE` prints
+    fn dump_table(db: &Database, table: &str) -> PrismDBResult<String> {
+        let mut script = db.catalog().read().unwrap().get_table_ddl("main", table)?;
+        script.push('\n');
+        let quoted = quote_identifier(table);
+        for row in rows(db, &format!("SELECT * FROM {}", quoted))? {
+            let values: Vec<String> = row.iter().map(Value::to_sql_literal).collect();
+            script.push_str(&format!("INSERT INTO {} VALUES ({});\n", quoted, values.join(", ")));
+        }
+        Ok(script)
+    }
+
+    #[test]
+    fn test_dump_replays_every_type() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE everything (b BOOLEAN, ti TINYINT, si SMALLINT, i INTEGER, bi BIGINT, \
+         f FLOAT, d DOUBLE, dec DECIMAL(10,2), v VARCHAR, dt DATE, tm TIME, ts TIMESTAMP, bl BLOB)",
+        )?;
+        db.execute(
+            "INSERT INTO everything VALUES \
+         (TRUE, -8, 300, 70000, 9000000000, 1.5, -2.25, 12.34, 'it''s a \\\\ path', \
+          '2024-03-05', '12:34:56.5', '2024-03-05 12:34:56.25', x'DEADBEEF'), \
+         (FALSE, 0, 0, 0, 0, 0.0, 1e100, -0.05, '', '1969-12-31', '00:00:00', '1900-01-01 00:00:00', x''), \
+         (NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL, NULL)",
+        )?;
+        let original = rows(&db, "SELECT * FROM everything")?;
+        assert_eq!(original[0][9], Value::Date(19787));
+        assert_eq!(original[0][12], Value::Blob(vec![0xde, 0xad, 0xbe, 0xef]));
+
+        let script = dump_table(&db, "everything")?;
+        assert!(script.contains("TRUE, -8, 300, 70000, 9000000000, 1.5, -2.25, 12.34, 'it''s a \\\\ path', \
+                             '2024-03-05', '12:34:56.5', '2024-03-05 12:34:56.25', x'DEADBEEF'"));
+
+        let restored = Database::new_in_memory()?;
+        restored.execute_batch(&script)?;
+        assert_eq!(rows(&restored, "SELECT * FROM everything")?, original);
+        Ok(())
+    }
+
+    #[test]
+    fn test_temporal_values_cast_to_and_from_strings() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE events (day DATE, at TIMESTAMP, done BOOLEAN)")?;
+        db.execute("INSERT INTO events VALUES ('2024-02-29', '2024-02-29 23:59:59', TRUE)")?;
+
+        assert_eq!(
+            rows(&db, "SELECT CAST(day AS VARCHAR), CAST(at AS VARCHAR), CAST(done AS VARCHAR) FROM events")?,
+            vec![vec![
+                Value::Varchar("2024-02-29".to_string()),
+                Value::Varchar("2024-02-29 23:59:59".to_string()),
+                Value::Varchar("true".to_string()),
+            ]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT CAST('2024-02-29' AS DATE) = day, CAST('false' AS BOOLEAN) FROM events")?,
+            vec![vec![Value::Boolean(true), Value::Boolean(false)]]
+        );
+        assert!(db.execute("INSERT INTO events VALUES ('not a date', NULL, NULL)").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for declared VARCHAR(n) and CHAR(n) lengths and their enforcement
+#[cfg(test)]
+mod string_length_tests {
+    use crate::common::{database, rows};
+    use prism::parser::ast::Statement;
+    use prism::parser::parse_sql;
+    use prism::{Database, LogicalType, PrismDBResult, Value};
+
+    fn show_create_table(db: &Database, table: &str) -> PrismDBResult<String> {
+        match db
+            .query(&format!("SHOW CREATE TABLE {}", table))?
+            .first_value()
+        {
+            Some(Value::Varchar(ddl)) => Ok(ddl),
+            other => panic!("Expected DDL string, got {:?}", other),
+        }
+    }
+
+    const CODES: &[&str] = &[
+        "CREATE TABLE codes (id INTEGER, code VARCHAR(5), flag CHAR(2), note VARCHAR)",
+    ];
+
+    #[test]
+    fn test_parse_string_lengths() -> PrismDBResult<()> {
+        let columns = match parse_sql(
+            "CREATE TABLE t (a VARCHAR(10), b CHAR(5), c CHAR, d VARCHAR, e VARCHAR(3)[])",
+        )? {
+            Statement::CreateTable(create) => create.columns,
+            other => panic!("expected CREATE TABLE, got {:?}", other),
+        };
+        let summary: Vec<_> = columns
+            .iter()
+            .map(|c| (c.data_type.clone(), c.max_length))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (LogicalType::Varchar, Some(10)),
+                (LogicalType::Char { length: 5 }, None),
+                (LogicalType::Char { length: 1 }, None),
+                (LogicalType::Varchar, None),
+                (LogicalType::List(Box::new(LogicalType::Varchar)), None),
+            ]
+        );
+        assert!(parse_sql("CREATE TABLE t (a VARCHAR(0))").is_err());
+        assert!(parse_sql("CREATE TABLE t (a VARCHAR(x))").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_lengths_round_trip_through_ddl() -> PrismDBResult<()> {
+        let mut db = database(CODES)?;
+        let ddl = show_create_table(&db, "codes")?;
+        assert_eq!(
+            ddl,
+            "CREATE TABLE codes (\n\
+         \x20 id INTEGER,\n\
+         \x20 code VARCHAR(5),\n\
+         \x20 flag CHAR(2),\n\
+         \x20 note VARCHAR\n\
+         );"
+        );
+
+        db.execute("DROP TABLE codes")?;
+        db.execute(&ddl)?;
+        assert_eq!(show_create_table(&db, "codes")?, ddl);
+        assert_eq!(
+            rows(&db, "PRAGMA table_info('codes')")?
+                .into_iter()
+                .map(|row| row[2].clone())
+                .collect::<Vec<_>>(),
+            ["INTEGER", "VARCHAR(5)", "CHAR(2)", "VARCHAR"].map(|t| Value::Varchar(t.to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_over_length_strings_by_mode() -> PrismDBResult<()> {
+        let mut db = database(CODES)?;
+
+        // Lengths are not enforced by default
+        db.execute("INSERT INTO codes VALUES (1, 'abcdefgh', 'xyz', 'anything at all')")?;
+
+        db.execute("SET string_length_limit = 'error'")?;
+        assert!(db
+            .execute("INSERT INTO codes VALUES (2, 'abcdef', 'x', NULL)")
+            .is_err());
+        assert!(db
+            .execute("INSERT INTO codes VALUES (2, 'abc', 'xyz', NULL)")
+            .is_err());
+        assert!(db
+            .execute("UPDATE codes SET code = 'toolong' WHERE id = 1")
+            .is_err());
+        // Lengths count characters, not bytes
+        db.execute("INSERT INTO codes VALUES (2, 'äöüßé', 'ab', NULL), (3, NULL, NULL, NULL)")?;
+        db.execute("UPDATE codes SET code = 'short' WHERE id = 1")?;
+
+        db.execute("SET string_length_limit = 'truncate'")?;
+        db.execute("INSERT INTO codes VALUES (4, 'abcdefgh', 'xyz', NULL)")?;
+        db.execute("UPDATE codes SET flag = 'long flag' WHERE id = 3")?;
+
+        assert_eq!(
+            rows(&db, "SELECT id, code, flag FROM codes ORDER BY id")?,
+            [
+                [
+                    Value::Integer(1),
+                    Value::Varchar("short".into()),
+                    Value::Char("xyz".into())
+                ],
+                [
+                    Value::Integer(2),
+                    Value::Varchar("äöüßé".into()),
+                    Value::Char("ab".into())
+                ],
+                [Value::Integer(3), Value::Null, Value::Char("lo".into())],
+                [
+                    Value::Integer(4),
+                    Value::Varchar("abcde".into()),
+                    Value::Char("xy".into())
+                ],
+            ]
+        );
+        assert!(db.execute("SET string_length_limit = 'warn'").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for schemas, the configurable default schema and SET search_path
+#[cfg(test)]
+mod search_path_tests {
+    use crate::common::text;
+    use prism::{Database, DatabaseConfig, PrismDBResult, Value};
+
+    #[test]
+    fn test_search_path_resolves_unqualified_tables() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE events (id INTEGER)")?;
+        db.execute("INSERT INTO events VALUES (1)")?;
+        db.execute("CREATE SCHEMA analytics")?;
+
+        // New tables go in the first schema on the path
+        db.execute("SET search_path = 'analytics, main'")?;
+        db.execute("CREATE TABLE visits (page VARCHAR)")?;
+        db.execute("INSERT INTO visits VALUES ('home'), ('about')")?;
+        db.execute("UPDATE visits SET page = 'index' WHERE page = 'home'")?;
+        db.execute("DELETE FROM visits WHERE page = 'about'")?;
+        assert_eq!(db.query("SELECT page FROM visits")?.collect()?.rows, [[text("index")]]);
+
+        // Later schemas on the path are still searched
+        assert_eq!(
+            db.query("SELECT COUNT(*) FROM events")?.collect()?.rows,
+            [[Value::BigInt(1)]]
+        );
+
+        // The first schema holding the name wins
+        db.execute("CREATE TABLE events (id INTEGER)")?;
+        assert_eq!(db.query("SELECT COUNT(*) FROM events")?.collect()?.rows, [[Value::BigInt(0)]]);
+
+        // Off the path, the analytics tables are out of reach
+        db.execute("SET search_path = 'main'")?;
+        assert!(db.query("SELECT * FROM visits").is_err());
+        assert_eq!(
+            db.query("SELECT COUNT(*) FROM events")?.collect()?.rows,
+            [[Value::BigInt(1)]]
+        );
+
+        // Back to the default schema
+        db.execute("SET search_path = 'analytics'")?;
+        db.execute("SET search_path = DEFAULT")?;
+        assert!(db.query("SELECT * FROM visits").is_err());
+        assert_eq!(
+            db.query("SHOW search_path")?.collect()?.rows[0].last(),
+            Some(&text("main"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_configured_default_schema() -> PrismDBResult<()> {
+        let mut db = Database::new(DatabaseConfig {
+            default_schema: "staging".to_string(),
+            ..DatabaseConfig::in_memory()
+        })?;
+        db.execute("CREATE TABLE loads (n INTEGER)")?;
+        db.execute("INSERT INTO loads VALUES (7)")?;
+        assert_eq!(db.query("SELECT n FROM loads")?.collect()?.rows, [[Value::Integer(7)]]);
+
+        // The table was created in staging, not main
+        db.execute("SET search_path = 'main'")?;
+        assert!(db.query("SELECT n FROM loads").is_err());
+        db.execute("SET search_path = 'main, staging'")?;
+        assert_eq!(db.query("SELECT n FROM loads")?.collect()?.rows, [[Value::Integer(7)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_and_drop_schema() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE SCHEMA analytics")?;
+        assert!(db.execute("CREATE SCHEMA analytics").is_err());
+        db.execute("CREATE SCHEMA IF NOT EXISTS analytics")?;
+
+        // Schemas on the path that do not exist are skipped
+        db.execute("SET search_path = 'missing, analytics'")?;
+        db.execute("CREATE TABLE t (x INTEGER)")?;
+
+        // A schema must be emptied before it is dropped
+        assert!(db.execute("DROP SCHEMA analytics").is_err());
+        db.execute("DROP TABLE t")?;
+        db.execute("DROP SCHEMA analytics")?;
+        db.execute("DROP SCHEMA IF EXISTS analytics")?;
+        assert!(db.execute("DROP SCHEMA main").is_err());
+
+        // With nothing on the path left, there is nowhere to create a table
+        assert!(db.execute("CREATE TABLE t (x INTEGER)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_schema_cascade() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE SCHEMA analytics")?;
+        db.execute("SET search_path = 'analytics, main'")?;
+        db.execute("CREATE TABLE visits (page VARCHAR)")?;
+        db.execute("INSERT INTO visits VALUES ('home')")?;
+        db.execute("CREATE VIEW home_visits AS SELECT page FROM visits WHERE page = 'home'")?;
+
+        // RESTRICT, the default, keeps a schema that still has objects and names them
+        let err = db.execute("DROP SCHEMA analytics").unwrap_err().to_string();
+        assert!(err.contains("table visits") && err.contains("home_visits"), "{}", err);
+        assert!(db.execute("DROP SCHEMA analytics RESTRICT").is_err());
+        assert_eq!(db.query("SELECT page FROM visits")?.collect()?.rows, [[text("home")]]);
+
+        // CASCADE drops the objects along with the schema
+        db.execute("DROP SCHEMA analytics CASCADE")?;
+        assert!(db.query("SELECT * FROM visits").is_err());
+        assert!(db.query("SELECT * FROM home_visits").is_err());
+
+        // A schema recreated under the same name starts empty
+        db.execute("CREATE SCHEMA analytics")?;
+        assert!(db.query("SELECT * FROM visits").is_err());
+        db.execute("DROP SCHEMA analytics")?;
+        db.execute("DROP SCHEMA IF EXISTS analytics CASCADE")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_types_functions_and_indexes_follow_the_search_path() -> PrismDBResult<()> {
+        let mut db = Database::new(DatabaseConfig {
+            default_schema: "staging".to_string(),
+            ..DatabaseConfig::in_memory()
+        })?;
+        db.execute("CREATE TYPE mood AS ENUM ('sad', 'happy')")?;
+        db.execute("CREATE FUNCTION twice(x INTEGER) AS (x + x)")?;
+        db.execute("CREATE TABLE feelings (id INTEGER, m mood)")?;
+        db.execute("INSERT INTO feelings VALUES (1, 'happy'), (2, 'sad')")?;
+        db.execute("CREATE INDEX feelings_id ON feelings (id)")?;
+        assert_eq!(
+            db.query("SELECT twice(id), CAST(m AS VARCHAR) FROM feelings WHERE id = 1")?.collect()?.rows,
+            [[Value::Integer(2), text("happy")]]
+        );
+
+        // All of them went in staging, not main
+        db.execute("SET search_path = 'main'")?;
+        assert!(db.execute("CREATE TABLE t (m mood)").is_err());
+        assert!(db.query("SELECT twice(1)").is_err());
+        assert!(db.execute("DROP INDEX feelings_id").is_err());
+        assert!(db.execute("DROP FUNCTION twice").is_err());
+        db.execute("DROP INDEX IF EXISTS feelings_id")?;
+        db.execute("DROP FUNCTION IF EXISTS twice")?;
+
+        // and are found again once staging is back on the path
+        db.execute("SET search_path = 'main, staging'")?;
+        db.execute("CREATE TABLE t (m mood)")?;
+        db.execute("DROP INDEX feelings_id")?;
+        db.execute("CREATE INDEX IF NOT EXISTS feelings_id ON feelings (id)")?;
+        db.execute("DROP INDEX feelings_id")?;
+        db.execute("DROP FUNCTION twice")?;
+        assert!(db.query("SELECT twice(1)").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for UNIQUE and PRIMARY KEY enforcement and NULLS [NOT] DISTINCT
+#[cfg(test)]
+mod unique_constraint_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    fn count(db: &Database, table: &str) -> PrismDBResult<Value> {
+        Ok(db.query(&format!("SELECT COUNT(*) FROM {}", table))?.collect()?.rows[0][0].clone())
+    }
+
+    #[test]
+    fn test_unique_rejects_duplicates() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR UNIQUE)")?;
+        db.execute("INSERT INTO users VALUES (1, 'a@x'), (2, 'b@x')")?;
+
+        let error = db.execute("INSERT INTO users VALUES (3, 'a@x')").unwrap_err().to_string();
+        assert!(error.contains("(email) = ('a@x')"), "{}", error);
+        assert!(db.execute("INSERT INTO users VALUES (1, 'c@x')").is_err());
+        // Rows of the same statement count too
+        assert!(db.execute("INSERT INTO users VALUES (5, 'd@x'), (5, 'e@x')").is_err());
+
+        // A deleted row's key is free again
+        db.execute("DELETE FROM users WHERE id = 2")?;
+        db.execute("INSERT INTO users VALUES (2, 'b@x')")?;
+        assert_eq!(count(&db, "users")?, Value::BigInt(2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nulls_distinct_by_default() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE t (x INTEGER UNIQUE, y INTEGER, z INTEGER, UNIQUE NULLS DISTINCT (y, z))")?;
+        db.execute("INSERT INTO t VALUES (NULL, 1, NULL), (NULL, 1, NULL)")?;
+        db.execute("INSERT INTO t VALUES (NULL, NULL, NULL)")?;
+        assert_eq!(count(&db, "t")?, Value::BigInt(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nulls_not_distinct_allows_one_null() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE t (x INTEGER UNIQUE NULLS NOT DISTINCT, y INTEGER)")?;
+        db.execute("INSERT INTO t VALUES (NULL, 1), (2, 2)")?;
+        assert!(db.execute("INSERT INTO t VALUES (NULL, 3)").is_err());
+        assert_eq!(count(&db, "t")?, Value::BigInt(2));
+
+        // On a multi-column key, NULLs compare equal to each other but not to values
+        db.execute("CREATE TABLE pairs (a INTEGER, b INTEGER, UNIQUE NULLS NOT DISTINCT (a, b))")?;
+        db.execute("INSERT INTO pairs VALUES (1, NULL), (1, 2), (NULL, NULL)")?;
+        assert!(db.execute("INSERT INTO pairs VALUES (1, NULL)").is_err());
+        assert!(db.execute("INSERT INTO pairs VALUES (NULL, NULL)").is_err());
+        assert_eq!(count(&db, "pairs")?, Value::BigInt(3));
+
+        // The modifier survives SHOW CREATE TABLE
+        let ddl = db.query("SHOW CREATE TABLE t")?.collect()?.rows[0][0].to_string();
+        assert!(ddl.contains("UNIQUE NULLS NOT DISTINCT (x)"), "{}", ddl);
+        Ok(())
+    }
+
+    fn is_unique_violation(result: PrismDBResult<impl Sized>) -> bool {
+        matches!(result, Err(error) if error.to_string().contains("violates a unique constraint"))
+    }
+
+    #[test]
+    fn test_update_rejects_duplicates() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR UNIQUE)")?;
+        db.execute("INSERT INTO users VALUES (1, 'a@x'), (2, 'b@x'), (3, 'c@x')")?;
+
+        assert!(is_unique_violation(db.execute("UPDATE users SET id = 1 WHERE id = 2")));
+        assert!(is_unique_violation(db.execute("UPDATE users SET email = 'x@x' WHERE id > 1")));
+        // A row may keep its own key, or take one another row gave up
+        db.execute("UPDATE users SET id = 1, email = 'a@x' WHERE id = 1")?;
+        db.execute("UPDATE users SET id = 4 WHERE id = 3")?;
+        db.execute("UPDATE users SET id = 3 WHERE id = 2")?;
+        let ids = db.query("SELECT id FROM users ORDER BY id")?.collect()?.rows;
+        assert_eq!(ids, [[Value::Integer(1)], [Value::Integer(3)], [Value::Integer(4)]]);
+
+        db.execute("CREATE TABLE t (x INTEGER UNIQUE NULLS NOT DISTINCT, y INTEGER)")?;
+        db.execute("INSERT INTO t VALUES (NULL, 1), (2, 2)")?;
+        assert!(is_unique_violation(db.execute("UPDATE t SET x = NULL WHERE y = 2")));
+        assert_eq!(count(&db, "t WHERE x IS NULL")?, Value::BigInt(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_update_rejects_duplicates() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR UNIQUE)")?;
+        db.execute("INSERT INTO users VALUES (1, 'a@x'), (2, 'b@x')")?;
+        db.execute("CREATE TABLE changes (id INTEGER, email VARCHAR)")?;
+        db.execute("INSERT INTO changes VALUES (2, 'a@x')")?;
+
+        let merge = "MERGE INTO users u USING changes c ON u.id = c.id \
+                     WHEN MATCHED THEN UPDATE SET email = c.email";
+        assert!(is_unique_violation(db.execute(merge)));
+        let emails = db.query("SELECT email FROM users ORDER BY id")?.collect()?.rows;
+        assert_eq!(emails, [[Value::Varchar("a@x".into())], [Value::Varchar("b@x".into())]]);
+
+        // Once the other row has let go of the key, the update may take it
+        db.execute("INSERT INTO changes VALUES (1, 'z@x')")?;
+        db.execute(merge)?;
+        let emails = db.query("SELECT email FROM users ORDER BY id")?.collect()?.rows;
+        assert_eq!(emails, [[Value::Varchar("z@x".into())], [Value::Varchar("a@x".into())]]);
+        Ok(())
+    }
+}
+
+/// Tests for MERGE INTO ... USING ... WHEN [NOT] MATCHED
+#[cfg(test)]
+mod merge_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBResult, Value};
+
+    const STOCK: &[&str] = &[
+        "CREATE TABLE stock (item VARCHAR, qty INTEGER)",
+        "INSERT INTO stock VALUES ('apple', 10), ('pear', 5), ('plum', 0)",
+        "CREATE TABLE delivery (item VARCHAR, qty INTEGER)",
+        "INSERT INTO delivery VALUES ('apple', 3), ('kiwi', 7), ('plum', 0)",
+    ];
+
+    fn stock(db: &Database) -> PrismDBResult<Vec<Vec<Value>>> {
+        Ok(db.query("SELECT item, qty FROM stock ORDER BY item, qty")?.collect()?.rows)
+    }
+
+    fn row(item: &str, qty: i32) -> Vec<Value> {
+        vec![Value::Varchar(item.to_string()), Value::Integer(qty)]
+    }
+
+    #[test]
+    fn test_matched_update_and_not_matched_insert() -> PrismDBResult<()> {
+        let mut db = database(STOCK)?;
+        let result = db.execute(
+            "MERGE INTO stock AS s USING delivery AS d ON s.item = d.item \
+         WHEN MATCHED THEN UPDATE SET qty = s.qty + d.qty \
+         WHEN NOT MATCHED THEN INSERT (item, qty) VALUES (d.item, d.qty)",
+        )?;
+        assert_eq!(result.collect()?.rows, [[Value::BigInt(3)]]);
+        assert_eq!(
+            stock(&db)?,
+            [row("apple", 13), row("kiwi", 7), row("pear", 5), row("plum", 0)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_matched_delete_with_clause_conditions() -> PrismDBResult<()> {
+        let mut db = database(STOCK)?;
+        // The first clause whose condition holds wins, so plum is deleted and apple updated
+        db.execute(
+            "MERGE INTO stock s USING delivery d ON s.item = d.item \
+         WHEN MATCHED AND d.qty = 0 THEN DELETE \
+         WHEN MATCHED THEN UPDATE SET qty = d.qty \
+         WHEN NOT MATCHED AND d.qty > 10 THEN INSERT VALUES (d.item, d.qty)",
+        )?;
+        assert_eq!(stock(&db)?, [row("apple", 3), row("pear", 5)]);
+
+        db.execute(
+            "MERGE INTO stock USING delivery ON stock.item = delivery.item \
+         WHEN MATCHED THEN DO NOTHING \
+         WHEN NOT MATCHED THEN INSERT (item) VALUES (delivery.item)",
+        )?;
+        assert_eq!(
+            stock(&db)?,
+            [
+                row("apple", 3),
+                vec![Value::Varchar("kiwi".to_string()), Value::Null],
+                row("pear", 5),
+                vec![Value::Varchar("plum".to_string()), Value::Null],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_subquery_source() -> PrismDBResult<()> {
+        let mut db = database(STOCK)?;
+        db.execute(
+            "MERGE INTO stock USING (SELECT item, SUM(qty) AS total FROM delivery GROUP BY item) AS d \
+         ON stock.item = d.item \
+         WHEN MATCHED THEN UPDATE SET qty = 0",
+        )?;
+        assert_eq!(stock(&db)?, [row("apple", 0), row("pear", 5), row("plum", 0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multiple_matches() -> PrismDBResult<()> {
+        let mut db = database(STOCK)?;
+        // One source row may update several table rows
+        db.execute("INSERT INTO stock VALUES ('pear', 1)")?;
+        db.execute(
+            "MERGE INTO stock USING (SELECT 'pear' AS item) AS d ON stock.item = d.item \
+         WHEN MATCHED THEN UPDATE SET qty = stock.qty * 2",
+        )?;
+        assert_eq!(
+            stock(&db)?,
+            [row("apple", 10), row("pear", 2), row("pear", 10), row("plum", 0)]
+        );
+
+        // A table row matched by two source rows is ambiguous, and nothing changes
+        db.execute("INSERT INTO delivery VALUES ('apple', 4)")?;
+        let error = db
+            .execute(
+                "MERGE INTO stock USING delivery ON stock.item = delivery.item \
+             WHEN MATCHED THEN UPDATE SET qty = delivery.qty \
+             WHEN NOT MATCHED THEN INSERT VALUES (delivery.item, delivery.qty)",
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("more than one source row"), "{}", error);
+        assert_eq!(stock(&db)?.len(), 4);
+        Ok(())
+    }
+}
+
+/// Tests for INSERT with a column list that reorders or omits table columns
+#[cfg(test)]
+mod insert_column_list_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBResult, Value};
+
+    const T: &[&str] = &[
+        "CREATE TABLE t (a INTEGER, b VARCHAR, c INTEGER DEFAULT 7)",
+    ];
+
+    fn rows(db: &Database) -> PrismDBResult<Vec<Vec<Value>>> {
+        Ok(db.query("SELECT a, b, c FROM t ORDER BY a")?.collect()?.rows)
+    }
+
+    #[test]
+    fn test_reordered_columns() -> PrismDBResult<()> {
+        let mut db = database(T)?;
+        db.execute("INSERT INTO t (b, c, a) VALUES ('x', 3, 1), ('y', 4, 2)")?;
+        assert_eq!(
+            rows(&db)?,
+            [
+                [Value::Integer(1), Value::Varchar("x".to_string()), Value::Integer(3)],
+                [Value::Integer(2), Value::Varchar("y".to_string()), Value::Integer(4)],
+            ]
+        );
+
+        // INSERT ... SELECT maps its output the same way
+        db.execute("INSERT INTO t (c, a) SELECT a, c FROM t WHERE a = 1")?;
+        assert_eq!(
+            db.query("SELECT a, b, c FROM t WHERE a = 3")?.collect()?.rows,
+            [[Value::Integer(3), Value::Null, Value::Integer(1)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_omitted_columns_take_defaults() -> PrismDBResult<()> {
+        let mut db = database(T)?;
+        db.execute("INSERT INTO t (a) VALUES (1)")?;
+        db.execute("INSERT INTO t (b, a) VALUES ('x', 2)")?;
+        db.execute("INSERT INTO t (c, a) VALUES (NULL, 3)")?;
+        assert_eq!(
+            rows(&db)?,
+            [
+                [Value::Integer(1), Value::Null, Value::Integer(7)],
+                [Value::Integer(2), Value::Varchar("x".to_string()), Value::Integer(7)],
+                [Value::Integer(3), Value::Null, Value::Null],
+            ]
+        );
+
+        db.execute("INSERT INTO t DEFAULT VALUES")?;
+        assert_eq!(
+            db.query("SELECT b, c FROM t WHERE a IS NULL")?.collect()?.rows,
+            [[Value::Null, Value::Integer(7)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_count_must_match() -> PrismDBResult<()> {
+        let mut db = database(T)?;
+        for (sql, message) in [
+            ("INSERT INTO t (a, b) VALUES (1)", "more target columns than expressions"),
+            ("INSERT INTO t (a) VALUES (1, 'x')", "more expressions than target columns"),
+            ("INSERT INTO t VALUES (1, 'x')", "more target columns than expressions"),
+            ("INSERT INTO t (a, a) VALUES (1, 2)", "specified more than once"),
+            ("INSERT INTO t (a) SELECT 1, 2", "has 2 values for 1 columns"),
+        ] {
+            let error = db.execute(sql).unwrap_err().to_string();
+            assert!(error.contains(message), "{}: {}", sql, error);
+        }
+        assert!(rows(&db)?.is_empty());
+        Ok(())
+    }
+}
+
+/// Tests for creating a table from rows of values without SQL
+#[cfg(test)]
+mod create_table_from_rows_tests {
+    use prism::{Database, LogicalType, PrismDBError, PrismDBResult, Value};
+
+    fn sales_schema() -> Vec<(String, LogicalType)> {
+        vec![
+            ("region".to_string(), LogicalType::Varchar),
+            ("amount".to_string(), LogicalType::Integer),
+            ("price".to_string(), LogicalType::Double),
+        ]
+    }
+
+    fn sale(region: &str, amount: i32, price: f64) -> Vec<Value> {
+        vec![Value::Varchar(region.to_string()), Value::Integer(amount), Value::Double(price)]
+    }
+
+    #[test]
+    fn test_create_table_from_rows_and_query() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.create_table_from_rows(
+            "sales",
+            sales_schema(),
+            vec![
+                sale("west", 3, 1.5),
+                sale("east", 5, 2.0),
+                sale("west", 4, 2.5),
+                vec![Value::Varchar("east".to_string()), Value::Null, Value::Double(1.0)],
+            ],
+        )?;
+
+        let rows = db
+            .query("SELECT region, SUM(amount) FROM sales WHERE price > 1.2 GROUP BY region ORDER BY region")?
+            .collect()?
+            .rows;
+        assert_eq!(
+            rows,
+            [
+                [Value::Varchar("east".to_string()), Value::Double(5.0)],
+                [Value::Varchar("west".to_string()), Value::Double(7.0)],
+            ]
+        );
+
+        // Values are cast to the column types like those of an INSERT
+        db.create_table_from_rows(
+            "totals",
+            vec![("total".to_string(), LogicalType::BigInt)],
+            vec![vec![Value::Integer(7)]],
+        )?;
+        assert_eq!(db.query("SELECT total FROM totals")?.first_value(), Some(Value::BigInt(7)));
+
+        // The table is an ordinary one
+        db.execute("INSERT INTO sales VALUES ('north', 1, 9.0)")?;
+        assert_eq!(db.query("SELECT COUNT(*) FROM sales")?.first_value(), Some(Value::BigInt(5)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_table_from_rows_rejects_bad_rows() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+
+        // A value that doesn't convert to its column's type fails the whole call
+        let mismatch = db.create_table_from_rows(
+            "sales",
+            sales_schema(),
+            vec![
+                sale("west", 3, 1.5),
+                vec![Value::Varchar("east".to_string()), Value::Varchar("many".to_string()), Value::Double(2.0)],
+            ],
+        );
+        assert!(mismatch.is_err());
+        assert!(db.query("SELECT * FROM sales").is_err());
+
+        let short_row = db.create_table_from_rows("sales", sales_schema(), vec![vec![Value::Integer(1)]]);
+        assert!(matches!(short_row, Err(PrismDBError::InvalidArgument(_))));
+        assert!(db.query("SELECT * FROM sales").is_err());
+
+        // An existing table is not replaced
+        db.create_table_from_rows("sales", sales_schema(), vec![sale("west", 3, 1.5)])?;
+        assert!(db.create_table_from_rows("sales", sales_schema(), Vec::new()).is_err());
+        assert_eq!(db.query("SELECT COUNT(*) FROM sales")?.first_value(), Some(Value::BigInt(1)));
+        Ok(())
+    }
+}
+
+/// Tests for UPDATE ... SET column = DEFAULT
+#[cfg(test)]
+mod update_default_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBResult, Value};
+
+    const ITEMS: &[&str] = &[
+        "CREATE TABLE items (id INTEGER, qty INTEGER DEFAULT 7, label VARCHAR DEFAULT 'new', note VARCHAR)",
+        "INSERT INTO items VALUES (1, 1, 'a', 'x'), (2, 2, 'b', 'y')",
+    ];
+
+    fn rows(db: &Database) -> PrismDBResult<Vec<Vec<Value>>> {
+        Ok(db.query("SELECT id, qty, label, note FROM items ORDER BY id")?.collect()?.rows)
+    }
+
+    #[test]
+    fn test_update_to_declared_default() -> PrismDBResult<()> {
+        let mut db = database(ITEMS)?;
+        db.execute("UPDATE items SET qty = DEFAULT, label = upper(label) WHERE id = 2")?;
+        assert_eq!(
+            rows(&db)?,
+            [
+                [Value::Integer(1), Value::Integer(1), Value::Varchar("a".into()), Value::Varchar("x".into())],
+                [Value::Integer(2), Value::Integer(7), Value::Varchar("B".into()), Value::Varchar("y".into())],
+            ]
+        );
+
+        // The same value an INSERT leaving the column out gets
+        db.execute("INSERT INTO items (id) VALUES (3)")?;
+        let inserted = db.query("SELECT qty, label FROM items WHERE id = 3")?.collect()?.rows;
+        db.execute("UPDATE items SET qty = DEFAULT, label = DEFAULT")?;
+        let updated = db.query("SELECT DISTINCT qty, label FROM items")?.collect()?.rows;
+        assert_eq!(updated, inserted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_to_default_without_one_is_null() -> PrismDBResult<()> {
+        let mut db = database(ITEMS)?;
+        db.execute("UPDATE items SET note = DEFAULT WHERE id = 1")?;
+        let notes = db.query("SELECT note FROM items ORDER BY id")?.collect()?.rows;
+        assert_eq!(notes, [[Value::Null], [Value::Varchar("y".into())]]);
+
+        assert!(db.execute("UPDATE items SET missing = DEFAULT").is_err());
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@
 
 use prism::{Database, DatabaseConfig, PrismDBResult};
 
+mod common;
+
 #[test]
 fn test_database_creation() -> PrismDBResult<()> {
     // Just test that we can create a database with the new API
@@ -276,3 +278,1165 @@ mod arithmetic_expression_tests {
         Ok(())
     }
 }
+
+/// Tests for range bounds derived from prefix LIKE patterns
+#[cfg(test)]
+mod like_prefix_tests {
+    use crate::common::{database, insert_rows, text};
+    use prism::parser::parse_sql;
+    use prism::planner::{PhysicalPlan, PhysicalTableScan, QueryOptimizer, QueryPlanner};
+    use prism::storage::{TransactionManager, ValueRange, ZONE_SIZE};
+    use prism::{Database, PrismDBResult, Value};
+    use std::sync::Arc;
+
+    const ROWS: usize = 1_000;
+
+    /// Names are inserted in sorted order, so each zone covers a narrow band of them.
+    /// Every hundredth name is NULL.
+    fn items_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE items (id INTEGER, name VARCHAR)"])?;
+        let values = (0..ROWS)
+            .map(|i| match i % 100 {
+                99 => format!("({}, NULL)", i),
+                _ => format!("({}, 'name-{:04}')", i, i),
+            });
+        insert_rows(&mut db, "items", values)?;
+        Ok(db)
+    }
+
+    fn find_scan(plan: &PhysicalPlan) -> Option<&PhysicalTableScan> {
+        match plan {
+            PhysicalPlan::TableScan(scan) => Some(scan),
+            _ => plan.children().into_iter().find_map(find_scan),
+        }
+    }
+
+    fn scan_ranges(db: &Database, sql: &str) -> PrismDBResult<Vec<(usize, ValueRange)>> {
+        let statement = parse_sql(sql)?;
+        let mut planner = QueryPlanner::new_with_catalog(db.catalog());
+        let logical = planner.plan_statement(&statement)?;
+        let mut optimizer = QueryOptimizer::new()
+            .with_context(db.catalog(), Arc::new(TransactionManager::new()))
+            .with_ctes(planner.get_ctes());
+        let plan = optimizer.optimize(logical)?;
+        Ok(find_scan(&plan)
+            .expect("plan has a table scan")
+            .ranges
+            .clone())
+    }
+
+    /// Rows scans have materialized from a table so far
+    fn rows_fetched(db: &Database, table: &str) -> PrismDBResult<usize> {
+        let table = db.catalog().read().unwrap().get_table("main", table)?;
+        let data = table.read().unwrap().get_data();
+        let rows = data.read().unwrap().rows_fetched();
+        Ok(rows)
+    }
+
+    /// Run a query and return the ids it selects, along with how many rows it fetched
+    fn selected_ids(db: &Database, sql: &str) -> PrismDBResult<(Vec<i32>, usize)> {
+        let before = rows_fetched(db, "items")?;
+        let rows = db.query(sql)?.collect()?.rows;
+        let ids = rows
+            .iter()
+            .map(|row| match row[0] {
+                Value::Integer(id) => id,
+                ref other => panic!("Expected integer id, got {:?}", other),
+            })
+            .collect();
+        Ok((ids, rows_fetched(db, "items")? - before))
+    }
+
+    #[test]
+    fn test_prefix_like_skips_zones() -> PrismDBResult<()> {
+        let db = items_database()?;
+        let sql = "SELECT id FROM items WHERE name LIKE 'name-01%'";
+
+        assert_eq!(
+            scan_ranges(&db, sql)?,
+            vec![
+                (
+                    1,
+                    ValueRange {
+                        lower: Some((text("name-01"), true)),
+                        upper: None
+                    }
+                ),
+                (
+                    1,
+                    ValueRange {
+                        lower: None,
+                        upper: Some((text("name-02"), false))
+                    }
+                ),
+            ]
+        );
+
+        let (ids, fetched) = selected_ids(&db, sql)?;
+        assert_eq!(ids, (100..199).collect::<Vec<_>>());
+        assert!(fetched <= 2 * ZONE_SIZE, "fetched {} rows", fetched);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_like_with_trailing_wildcards() -> PrismDBResult<()> {
+        let mut db = items_database()?;
+
+        // The LIKE stays to check the rest of the pattern, within the prefix range
+        let (ids, fetched) = selected_ids(&db, "SELECT id FROM items WHERE name LIKE 'name-08_5'")?;
+        assert_eq!(ids, (805..900).step_by(10).collect::<Vec<_>>());
+        assert!(fetched < ROWS, "fetched {} rows", fetched);
+
+        // Zone bounds widen when a row moves into the range
+        db.execute("UPDATE items SET name = 'name-08zz' WHERE id = 5")?;
+        let (ids, _) = selected_ids(
+            &db,
+            "SELECT id FROM items WHERE name LIKE 'name-08%' AND id < 800",
+        )?;
+        assert_eq!(ids, vec![5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_prefix_like_scans_everything() -> PrismDBResult<()> {
+        let db = items_database()?;
+
+        for (sql, expected) in [
+            (
+                "SELECT id FROM items WHERE name LIKE '%50'",
+                (50..ROWS as i32).step_by(100).collect::<Vec<_>>(),
+            ),
+            (
+                "SELECT id FROM items WHERE name LIKE '_ame-000_'",
+                (0..10).collect(),
+            ),
+            (
+                "SELECT id FROM items WHERE name LIKE '%-09%9'",
+                (909..999).step_by(10).filter(|i| i % 100 != 99).collect(),
+            ),
+        ] {
+            assert!(scan_ranges(&db, sql)?.is_empty(), "{}", sql);
+            let (ids, fetched) = selected_ids(&db, sql)?;
+            assert_eq!(ids, expected, "{}", sql);
+            assert_eq!(fetched, ROWS, "{}", sql);
+        }
+        Ok(())
+    }
+}
+
+/// Tests for folding unquoted identifiers and matching quoted ones exactly
+#[cfg(test)]
+mod identifier_case_tests {
+    use crate::common::rows;
+    use prism::{Database, DatabaseConfig, IdentifierCase, PrismDBResult, Value};
+
+    fn database_with(identifier_case: IdentifierCase) -> PrismDBResult<Database> {
+        Database::new(DatabaseConfig {
+            identifier_case,
+            ..DatabaseConfig::in_memory()
+        })
+    }
+
+    #[test]
+    fn test_unquoted_identifiers_resolve_in_any_case() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER, name VARCHAR)")?;
+        db.execute("INSERT INTO Users VALUES (1, 'ann')")?;
+        db.execute("INSERT INTO USERS (ID, Name) VALUES (2, 'bob')")?;
+
+        let ann = vec![vec![Value::Varchar("ann".to_string())]];
+        assert_eq!(rows(&db, "SELECT Name FROM Users WHERE Id = 1")?, ann);
+        assert_eq!(rows(&db, "SELECT U.NAME FROM users AS u WHERE u.id = 1")?, ann);
+        assert_eq!(rows(&db, "SELECT \"name\" FROM users WHERE id = 1")?, ann);
+
+        // Mixed-case DDL is folded too
+        db.execute("CREATE TABLE Orders (OrderId INTEGER, UserId INTEGER)")?;
+        db.execute("INSERT INTO orders VALUES (10, 1), (11, 1), (12, 2)")?;
+        let sql = "SELECT Users.Name, COUNT(*) AS Total FROM ORDERS JOIN users ON orders.userid = USERS.ID \
+               GROUP BY users.name ORDER BY TOTAL";
+        let columns: Vec<String> = db.describe(sql)?.into_iter().map(|(name, _)| name).collect();
+        assert_eq!(columns, vec!["users.name", "total"]);
+        assert_eq!(
+            rows(&db, sql)?,
+            vec![
+                vec![Value::Varchar("bob".to_string()), Value::BigInt(1)],
+                vec![Value::Varchar("ann".to_string()), Value::BigInt(2)],
+            ]
+        );
+
+        db.execute("UPDATE USERS SET NAME = 'Ann' WHERE ID = 1")?;
+        db.execute("DROP TABLE ORDERS")?;
+        assert!(db.execute("SELECT * FROM orders").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_quoted_identifiers_are_case_sensitive() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE \"Accounts\" (\"Balance\" INTEGER, owner VARCHAR)")?;
+        db.execute("INSERT INTO \"Accounts\" VALUES (100, 'ann')")?;
+
+        assert_eq!(rows(&db, "SELECT \"Balance\" FROM \"Accounts\"")?, vec![vec![Value::Integer(100)]]);
+        assert!(rows(&db, "SELECT \"Balance\" FROM Accounts").is_err());
+        assert!(rows(&db, "SELECT \"Balance\" FROM \"accounts\"").is_err());
+        assert!(rows(&db, "SELECT Balance FROM \"Accounts\"").is_err());
+        assert!(rows(&db, "SELECT \"OWNER\" FROM \"Accounts\"").is_err());
+        assert_eq!(rows(&db, "SELECT OWNER FROM \"Accounts\"")?, vec![vec![Value::Varchar("ann".to_string())]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_configured_identifier_case() -> PrismDBResult<()> {
+        // Upper folding matches quoted uppercase names
+        let mut db = database_with(IdentifierCase::Upper)?;
+        db.execute("CREATE TABLE items (id INTEGER)")?;
+        db.execute("INSERT INTO Items VALUES (1)")?;
+        assert_eq!(rows(&db, "SELECT \"ID\" FROM \"ITEMS\"")?, vec![vec![Value::Integer(1)]]);
+        assert!(rows(&db, "SELECT id FROM \"items\"").is_err());
+
+        // Preserving case keeps differently cased names apart
+        let mut db = database_with(IdentifierCase::Preserve)?;
+        db.execute("CREATE TABLE Items (id INTEGER)")?;
+        db.execute("CREATE TABLE items (id INTEGER)")?;
+        db.execute("INSERT INTO Items VALUES (1), (2)")?;
+        assert_eq!(rows(&db, "SELECT COUNT(*) FROM Items")?, vec![vec![Value::BigInt(2)]]);
+        assert_eq!(rows(&db, "SELECT COUNT(*) FROM items")?, vec![vec![Value::BigInt(0)]]);
+        assert!(rows(&db, "SELECT ID FROM Items").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for COLLATE in comparisons and ORDER BY
+#[cfg(test)]
+mod collation_tests {
+    use crate::common::{database, rows};
+    use prism::parser::ast::{ColumnDefinition, Statement};
+    use prism::parser::parse_sql;
+    use prism::{Database, LogicalType, PrismDBResult, Value};
+
+    const WORDS: &[&str] = &[
+        "CREATE TABLE words (id INTEGER, word VARCHAR)",
+        "INSERT INTO words VALUES (1, 'banana'), (2, 'Apple'), (3, 'cherry'), (4, 'apple'), \
+     (5, 'Banana'), (6, NULL)",
+    ];
+
+    /// The first column of each row, as strings
+    fn words(db: &Database, sql: &str) -> PrismDBResult<Vec<String>> {
+        Ok(rows(db, sql)?
+            .into_iter()
+            .map(|row| match &row[0] {
+                Value::Varchar(word) => word.clone(),
+                Value::Null => "NULL".to_string(),
+                other => panic!("expected a string, got {:?}", other),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_nocase_equality() -> PrismDBResult<()> {
+        let db = database(WORDS)?;
+        assert_eq!(
+            words(&db, "SELECT word FROM words WHERE word = 'APPLE' COLLATE NOCASE ORDER BY id")?,
+            ["Apple", "apple"]
+        );
+        // The collation may be named on either side, as an identifier or a string
+        assert_eq!(
+            words(&db, "SELECT word FROM words WHERE word COLLATE 'nocase' <> 'banana' AND id < 6 ORDER BY id")?,
+            ["Apple", "cherry", "apple"]
+        );
+        // BINARY compares code points, as without COLLATE
+        assert_eq!(
+            words(&db, "SELECT word FROM words WHERE word = 'apple' COLLATE BINARY")?,
+            ["apple"]
+        );
+        assert_eq!(
+            words(&db, "SELECT word FROM words WHERE word < 'b' COLLATE NOCASE AND id < 6 ORDER BY id")?,
+            ["Apple", "apple"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nocase_ordering() -> PrismDBResult<()> {
+        let db = database(WORDS)?;
+        // Binary order puts every uppercase letter first
+        assert_eq!(
+            words(&db, "SELECT word FROM words WHERE id < 6 ORDER BY word")?,
+            ["Apple", "Banana", "apple", "banana", "cherry"]
+        );
+        assert_eq!(
+            words(&db, "SELECT word FROM words ORDER BY word COLLATE NOCASE, id")?,
+            ["Apple", "apple", "banana", "Banana", "cherry", "NULL"]
+        );
+        assert_eq!(
+            words(&db, "SELECT word FROM words ORDER BY word COLLATE NOCASE DESC NULLS LAST, id")?,
+            ["cherry", "banana", "Banana", "Apple", "apple", "NULL"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_and_conflicting_collations() -> PrismDBResult<()> {
+        let db = database(WORDS)?;
+        assert!(db
+            .query("SELECT word FROM words WHERE word COLLATE NOCASE = 'a' COLLATE BINARY")
+            .is_err());
+        #[cfg(not(feature = "icu"))]
+        assert!(db.query("SELECT word FROM words ORDER BY word COLLATE 'de'").is_err());
+        Ok(())
+    }
+
+    fn column_definitions(sql: &str) -> PrismDBResult<Vec<ColumnDefinition>> {
+        match parse_sql(sql)? {
+            Statement::CreateTable(create) => Ok(create.columns),
+            other => panic!("expected CREATE TABLE, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_column_collate_and_character_set() -> PrismDBResult<()> {
+        let columns = column_definitions(
+            "CREATE TABLE users (\
+           id INT NOT NULL, \
+           name VARCHAR(255) CHARACTER SET utf8 COLLATE utf8_general_ci NOT NULL, \
+           email VARCHAR(100) CHARSET latin1 DEFAULT NULL, \
+           nick VARCHAR COLLATE 'NOCASE' UNIQUE)",
+        )?;
+        let summary: Vec<_> = columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.data_type.clone(), c.nullable, c.collation.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("id", LogicalType::Integer, false, None),
+                ("name", LogicalType::Varchar, false, Some("utf8_general_ci")),
+                ("email", LogicalType::Varchar, true, None),
+                ("nick", LogicalType::Varchar, true, Some("NOCASE")),
+            ]
+        );
+        assert!(column_definitions("CREATE TABLE t (a VARCHAR CHARACTER utf8)").is_err());
+        assert!(column_definitions("CREATE TABLE t (a VARCHAR COLLATE)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_collation_applies_to_comparisons_and_ordering() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute(
+            "CREATE TABLE tags (id INTEGER, tag VARCHAR COLLATE NOCASE, \
+         label VARCHAR(20) CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci)",
+        )?;
+        db.execute(
+            "INSERT INTO tags VALUES (1, 'beta', 'beta'), (2, 'Alpha', 'Alpha'), (3, 'BETA', 'BETA'), \
+         (4, 'alpha', 'alpha')",
+        )?;
+
+        assert_eq!(words(&db, "SELECT tag FROM tags WHERE tag = 'ALPHA' ORDER BY id")?, ["Alpha", "alpha"]);
+        assert_eq!(words(&db, "SELECT tag FROM tags ORDER BY tag, id")?, ["Alpha", "alpha", "beta", "BETA"]);
+        // An explicit COLLATE overrides the column's
+        assert_eq!(words(&db, "SELECT tag FROM tags WHERE tag = 'alpha' COLLATE BINARY")?, ["alpha"]);
+        assert_eq!(
+            words(&db, "SELECT tag FROM tags ORDER BY tag COLLATE BINARY")?,
+            ["Alpha", "BETA", "alpha", "beta"]
+        );
+        db.execute("DELETE FROM tags WHERE tag = 'BETA'")?;
+        assert_eq!(words(&db, "SELECT tag FROM tags ORDER BY id")?, ["Alpha", "alpha"]);
+
+        // An unknown collation is ignored and the column compares as usual
+        assert_eq!(words(&db, "SELECT label FROM tags WHERE label = 'alpha'")?, ["alpha"]);
+        Ok(())
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn test_locale_collation_orders_accents() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE names (name VARCHAR)")?;
+        db.execute("INSERT INTO names VALUES ('Zorn'), ('Äpfel'), ('Bäcker'), ('Apfel'), ('Öl'), ('Ober')")?;
+
+        // Code point order puts accented letters after Z
+        assert_eq!(
+            words(&db, "SELECT name FROM names ORDER BY name")?,
+            ["Apfel", "Bäcker", "Ober", "Zorn", "Äpfel", "Öl"]
+        );
+        assert_eq!(
+            words(&db, "SELECT name FROM names ORDER BY name COLLATE 'de'")?,
+            ["Apfel", "Äpfel", "Bäcker", "Ober", "Öl", "Zorn"]
+        );
+        assert_eq!(
+            words(&db, "SELECT name FROM names WHERE name < 'B' COLLATE 'de' ORDER BY name COLLATE de_DE")?,
+            ["Apfel", "Äpfel"]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for ENUM types created with CREATE TYPE
+#[cfg(test)]
+mod enum_type_tests {
+    use crate::common::database;
+    use prism::parser::ast::Statement;
+    use prism::parser::parse_sql;
+    use prism::{LogicalType, PrismDBResult, Value};
+
+    fn labels(rows: Vec<Vec<Value>>) -> Vec<String> {
+        rows.into_iter().map(|row| row[0].to_string()).collect()
+    }
+
+    const MOODS: &[&str] = &[
+        "CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy')",
+        "CREATE TABLE people (name VARCHAR, current_mood mood)",
+        "INSERT INTO people VALUES ('ann', 'happy'), ('bob', 'sad'), ('cy', 'ok'), ('di', NULL), ('ed', 'sad')",
+    ];
+
+    #[test]
+    fn test_parse_create_type() -> PrismDBResult<()> {
+        match parse_sql("CREATE TYPE mood AS ENUM ('sad', 'ok', 'happy')")? {
+            Statement::CreateType(create) => {
+                assert_eq!(create.type_name, "mood");
+                assert_eq!(create.values, ["sad", "ok", "happy"]);
+            }
+            other => panic!("Expected CREATE TYPE, got {:?}", other),
+        }
+        assert!(parse_sql("CREATE TYPE mood AS ENUM ('sad', 'sad')").is_err());
+        assert!(parse_sql("CREATE TYPE mood AS ENUM ()").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_column_stores_valid_labels() -> PrismDBResult<()> {
+        let mut db = database(MOODS)?;
+
+        let result = db.query("SELECT current_mood FROM people WHERE name = 'ann'")?;
+        assert!(matches!(result.columns[0].data_type, LogicalType::Enum(_)));
+        match &result.collect()?.rows[0][0] {
+            Value::Enum { code, enum_type } => {
+                assert_eq!(*code, 2);
+                assert_eq!(enum_type.label(*code), "happy");
+            }
+            other => panic!("Expected an enum value, got {:?}", other),
+        }
+
+        let sad = db.query("SELECT name FROM people WHERE current_mood = 'sad' ORDER BY name")?.collect()?;
+        assert_eq!(labels(sad.rows), ["'bob'", "'ed'"]);
+
+        // Labels outside the type are rejected and leave the table unchanged
+        assert!(db.execute("INSERT INTO people VALUES ('fay', 'angry')").is_err());
+        assert!(db.execute("UPDATE people SET current_mood = 'angry' WHERE name = 'ann'").is_err());
+        let count = db.query("SELECT COUNT(*) FROM people")?.collect()?;
+        assert_eq!(count.rows, [[Value::BigInt(5)]]);
+
+        // Types and tables need an existing type name
+        assert!(db.execute("CREATE TYPE mood AS ENUM ('x')").is_err());
+        assert!(db.execute("CREATE TABLE broken (m feeling)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_orders_by_definition() -> PrismDBResult<()> {
+        let db = database(MOODS)?;
+
+        let ordered = db.query("SELECT current_mood FROM people ORDER BY current_mood")?.collect()?;
+        assert_eq!(labels(ordered.rows), ["'sad'", "'sad'", "'ok'", "'happy'", "NULL"]);
+
+        let descending = db
+            .query("SELECT name FROM people WHERE current_mood IS NOT NULL ORDER BY current_mood DESC, name")?
+            .collect()?;
+        assert_eq!(labels(descending.rows), ["'ann'", "'cy'", "'bob'", "'ed'"]);
+
+        // Comparisons with labels follow definition order too
+        let better = db.query("SELECT name FROM people WHERE current_mood > 'sad' ORDER BY name")?.collect()?;
+        assert_eq!(labels(better.rows), ["'ann'", "'cy'"]);
+
+        let grouped = db
+            .query("SELECT current_mood, COUNT(*) FROM people WHERE current_mood IS NOT NULL GROUP BY current_mood ORDER BY current_mood")?
+            .collect()?;
+        assert_eq!(grouped.rows.len(), 3);
+        assert_eq!(grouped.rows[0][1], Value::BigInt(2));
+        Ok(())
+    }
+}
+
+/// Tests for MAP values: literals, element access, MAP_KEYS and MAP_VALUES
+#[cfg(test)]
+mod map_type_tests {
+    use crate::common::first_value;
+    use prism::{Database, PrismDBResult, Value};
+
+    fn varchars(values: &[&str]) -> Value {
+        Value::List(values.iter().map(|v| Value::Varchar(v.to_string())).collect())
+    }
+
+    #[test]
+    fn test_map_literal_and_element_access() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+
+        let map = first_value(&db, "SELECT MAP {'a': 1, 'b': 2}")?;
+        assert_eq!(
+            map,
+            Value::Map(vec![
+                (Value::Varchar("a".to_string()), Value::Integer(1)),
+                (Value::Varchar("b".to_string()), Value::Integer(2)),
+            ])
+        );
+
+        assert_eq!(first_value(&db, "SELECT MAP {'a': 1, 'b': 2}['b']")?, Value::Integer(2));
+        assert_eq!(first_value(&db, "SELECT MAP {'a': 1, 'b': 2}['z']")?, Value::Null);
+        assert_eq!(first_value(&db, "SELECT MAP {1: 'one', 2: 'two'}[1]")?, Value::Varchar("one".to_string()));
+
+        // Subscripts work on lists too, 1-based
+        assert_eq!(first_value(&db, "SELECT [10, 20, 30][2]")?, Value::Integer(20));
+        assert_eq!(first_value(&db, "SELECT [10, 20, 30][4]")?, Value::Null);
+
+        // Keys must be unique, non-NULL scalars
+        assert!(db.query("SELECT MAP {'a': 1, 'a': 2}").is_err());
+        assert!(db.query("SELECT MAP {NULL: 1}").is_err());
+        assert!(db.query("SELECT MAP {[1]: 1}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_keys_and_values_keep_entry_order() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+
+        let sql = "SELECT map_keys(m), map_values(m) FROM (SELECT MAP {'z': 26, 'a': 1, 'm': 13} AS m) AS t";
+        let rows = db.query(sql)?.collect()?.rows;
+        assert_eq!(rows[0][0], varchars(&["z", "a", "m"]));
+        assert_eq!(
+            rows[0][1],
+            Value::List(vec![Value::Integer(26), Value::Integer(1), Value::Integer(13)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_column() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE settings (id INTEGER, options MAP(VARCHAR, INTEGER))")?;
+        db.execute("INSERT INTO settings VALUES (1, MAP {'width': 80, 'height': 24}), (2, MAP {'width': 120})")?;
+
+        let rows = db
+            .query("SELECT id, options['height'], map_keys(options) FROM settings ORDER BY id")?
+            .collect()?
+            .rows;
+        assert_eq!(rows[0], [Value::Integer(1), Value::Integer(24), varchars(&["width", "height"])]);
+        assert_eq!(rows[1], [Value::Integer(2), Value::Null, varchars(&["width"])]);
+        Ok(())
+    }
+}
+
+/// Tests for the to_json and row_to_json functions
+#[cfg(test)]
+mod to_json_function_tests {
+    use crate::common::first_value;
+    use prism::expression::evaluate_builtin_function;
+    use prism::{Database, PrismDBResult, Value};
+
+    fn json(text: &str) -> Value {
+        Value::JSON(text.to_string())
+    }
+
+    #[test]
+    fn test_to_json_scalars_and_nested_values() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        assert_eq!(first_value(&db, "SELECT to_json(42)")?, json("42"));
+        assert_eq!(first_value(&db, "SELECT to_json('say \"hi\"')")?, json(r#""say \"hi\"""#));
+        assert_eq!(first_value(&db, "SELECT to_json(NULL)")?, Value::Null);
+        assert_eq!(first_value(&db, "SELECT to_json([1, NULL, 3])")?, json("[1,null,3]"));
+        assert_eq!(
+            first_value(&db, "SELECT to_json(MAP {'a': [1.5], 'b': NULL})")?,
+            json(r#"{"a":[1.5],"b":null}"#)
+        );
+
+        let point = Value::Struct(vec![
+            ("x".to_string(), Value::Integer(1)),
+            ("tags".to_string(), Value::List(vec![Value::Varchar("p".to_string())])),
+            ("note".to_string(), Value::Null),
+        ]);
+        assert_eq!(
+            evaluate_builtin_function("to_json", &[point])?,
+            json(r#"{"x":1,"tags":["p"],"note":null}"#)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_to_json() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE people (id INTEGER, name VARCHAR, born DATE)")?;
+        db.execute("INSERT INTO people VALUES (1, 'Ann', '1990-05-01'), (2, NULL, NULL)")?;
+        db.execute("CREATE TABLE pets (owner INTEGER, pet VARCHAR)")?;
+        db.execute("INSERT INTO pets VALUES (1, 'cat')")?;
+
+        let rows = db.query("SELECT row_to_json(people) FROM people ORDER BY id")?.collect()?.rows;
+        assert_eq!(
+            rows,
+            [
+                [json(r#"{"id":1,"name":"Ann","born":"1990-05-01"}"#)],
+                [json(r#"{"id":2,"name":null,"born":null}"#)],
+            ]
+        );
+
+        // An alias names the row, and only that table's columns are included
+        let row = first_value(
+            &db,
+            "SELECT row_to_json(p) FROM people p JOIN pets ON p.id = pets.owner",
+        )?;
+        assert_eq!(row, json(r#"{"id":1,"name":"Ann","born":"1990-05-01"}"#));
+        assert!(db.query("SELECT row_to_json(missing) FROM people").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for CREATE FUNCTION / DROP FUNCTION scalar functions defined in SQL
+#[cfg(test)]
+mod sql_function_tests {
+    use crate::common::database;
+    use prism::{PrismDBResult, Value};
+
+    const ITEMS: &[&str] = &[
+        "CREATE TABLE items (name VARCHAR, price DOUBLE, qty INTEGER)",
+        "INSERT INTO items VALUES ('a', 10.0, 2), ('b', 20.0, 3)",
+        "CREATE FUNCTION add_tax(x DOUBLE) RETURNS DOUBLE AS (x * 1.1)",
+    ];
+
+    #[test]
+    fn test_defined_function_is_inlined() -> PrismDBResult<()> {
+        let mut db = database(ITEMS)?;
+
+        let result = db.query("SELECT name, add_tax(price) FROM items ORDER BY name")?;
+        assert_eq!(result.columns[1].name, "add_tax(price)");
+        assert_eq!(
+            result.collect()?.rows,
+            [
+                [Value::Varchar("a".to_string()), Value::Double(11.0)],
+                [Value::Varchar("b".to_string()), Value::Double(22.0)]
+            ]
+        );
+
+        // Usable wherever an expression is: filters, aggregates, other functions
+        assert_eq!(
+            db.query("SELECT name FROM items WHERE add_tax(price) > 15")?.collect()?.rows,
+            [[Value::Varchar("b".to_string())]]
+        );
+        assert_eq!(
+            db.query("SELECT SUM(add_tax(price)) FROM items")?.collect()?.rows,
+            [[Value::Double(33.0)]]
+        );
+        db.execute("CREATE FUNCTION twice(x INTEGER) AS (x + x)")?;
+        db.execute("CREATE FUNCTION quad(x INTEGER) AS (twice(twice(x)))")?;
+        assert_eq!(
+            db.query("SELECT quad(qty) FROM items ORDER BY qty")?.collect()?.rows,
+            [[Value::Integer(8)], [Value::Integer(12)]]
+        );
+
+        assert!(db.query("SELECT twice(1, 2)").is_err());
+        db.execute("CREATE FUNCTION forever(x INTEGER) AS (forever(x))")?;
+        assert!(db.query("SELECT forever(1)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_and_drop_function() -> PrismDBResult<()> {
+        let mut db = database(ITEMS)?;
+
+        assert!(db.execute("CREATE FUNCTION add_tax(x DOUBLE) AS (x * 1.2)").is_err());
+        db.execute("CREATE OR REPLACE FUNCTION ADD_TAX(x DOUBLE) RETURNS DOUBLE AS (x * 2)")?;
+        assert_eq!(
+            db.query("SELECT add_tax(price) FROM items WHERE name = 'a'")?.collect()?.rows,
+            [[Value::Double(20.0)]]
+        );
+
+        db.execute("DROP FUNCTION add_tax")?;
+        assert!(db.query("SELECT add_tax(price) FROM items").is_err());
+        assert!(db.execute("DROP FUNCTION add_tax").is_err());
+        db.execute("DROP FUNCTION IF EXISTS add_tax")?;
+        Ok(())
+    }
+}
+
+/// Tests for the EXCLUDE and REPLACE modifiers of `*` and `table.*`
+#[cfg(test)]
+mod wildcard_modifiers_tests {
+    use crate::common::{column_names, database, text};
+    use prism::{PrismDBResult, Value};
+
+    const ITEMS: &[&str] = &[
+        "CREATE TABLE items (id INTEGER, name VARCHAR, price INTEGER, stock INTEGER)",
+        "INSERT INTO items VALUES (1, 'pen', 3, 10), (2, 'ink', 7, 0)",
+        "CREATE TABLE sales (item_id INTEGER, qty INTEGER)",
+        "INSERT INTO sales VALUES (1, 4), (2, 1)",
+    ];
+
+    #[test]
+    fn test_exclude_drops_columns() -> PrismDBResult<()> {
+        let db = database(ITEMS)?;
+        let sql = "SELECT * EXCLUDE (price, stock) FROM items ORDER BY id";
+        assert_eq!(column_names(&db, sql)?, ["items.id", "items.name"]);
+        assert_eq!(
+            db.query(sql)?.collect()?.rows,
+            [[Value::Integer(1), text("pen")], [Value::Integer(2), text("ink")]]
+        );
+
+        // Without parentheses for a single column, and on a qualified wildcard
+        assert_eq!(column_names(&db, "SELECT * EXCLUDE stock FROM items")?, ["items.id", "items.name", "items.price"]);
+        assert_eq!(
+            column_names(&db, "SELECT i.* EXCLUDE (i.name), s.* EXCLUDE (item_id) FROM items AS i JOIN sales AS s ON i.id = s.item_id")?,
+            ["i.id", "i.price", "i.stock", "s.qty"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_keeps_column_order() -> PrismDBResult<()> {
+        let db = database(ITEMS)?;
+        let sql = "SELECT * REPLACE (price * 100 AS price, UPPER(name) AS name) FROM items ORDER BY id";
+        assert_eq!(column_names(&db, sql)?, ["items.id", "items.name", "items.price", "items.stock"]);
+        assert_eq!(
+            db.query(sql)?.collect()?.rows,
+            [
+                [Value::Integer(1), text("PEN"), Value::Integer(300), Value::Integer(10)],
+                [Value::Integer(2), text("INK"), Value::Integer(700), Value::Integer(0)],
+            ]
+        );
+
+        // Both modifiers together
+        assert_eq!(
+            db.query("SELECT * EXCLUDE (name) REPLACE (stock - 1 AS stock) FROM items WHERE id = 1")?
+                .collect()?
+                .rows,
+            [[Value::Integer(1), Value::Integer(3), Value::Integer(9)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_wildcard_modifier_errors() -> PrismDBResult<()> {
+        let db = database(ITEMS)?;
+        assert!(db.query("SELECT * EXCLUDE (missing) FROM items").is_err());
+        assert!(db.query("SELECT * REPLACE (1 AS missing) FROM items").is_err());
+        assert!(db.query("SELECT * EXCLUDE (id, name, price, stock) FROM items").is_err());
+        assert!(db.query("SELECT * REPLACE (price + 1) FROM items").is_err());
+        assert!(db.query("SELECT sales.* FROM items").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for COLUMNS('pattern') selecting the columns whose names match a regex
+#[cfg(test)]
+mod columns_expression_tests {
+    use crate::common::database;
+    use prism::{PrismDBResult, Value};
+
+    const QUARTERS: &[&str] = &[
+        "CREATE TABLE sales (region VARCHAR, q1 INTEGER, q2 INTEGER, q3 INTEGER, q4 INTEGER, sales_total INTEGER)",
+        "INSERT INTO sales VALUES ('east', 1, 2, 3, 4, 10), ('west', 10, 20, 30, 40, 100)",
+    ];
+
+    #[test]
+    fn test_bare_columns_expands_to_matches() -> PrismDBResult<()> {
+        let db = database(QUARTERS)?;
+        let result = db.query("SELECT region, COLUMNS('q[2-3]') FROM sales ORDER BY region")?;
+        assert_eq!(result.columns.len(), 3);
+        assert_eq!(
+            result.collect()?.rows,
+            [
+                [Value::Varchar("east".to_string()), Value::Integer(2), Value::Integer(3)],
+                [Value::Varchar("west".to_string()), Value::Integer(20), Value::Integer(30)],
+            ]
+        );
+
+        // The pattern may match anywhere in the name unless anchored
+        assert_eq!(db.query("SELECT COLUMNS('total') FROM sales")?.columns.len(), 1);
+        assert_eq!(db.query("SELECT COLUMNS('^q') FROM sales")?.columns.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_function_over_matched_columns() -> PrismDBResult<()> {
+        let db = database(QUARTERS)?;
+        assert_eq!(
+            db.query("SELECT SUM(COLUMNS('q[1-4]')) FROM sales")?.collect()?.rows,
+            [[Value::Double(11.0), Value::Double(22.0), Value::Double(33.0), Value::Double(44.0)]]
+        );
+
+        // With GROUP BY, and with the column inside a larger expression
+        assert_eq!(
+            db.query("SELECT region, MAX(COLUMNS('q[34]') * 2) FROM sales GROUP BY region ORDER BY region")?
+                .collect()?
+                .rows,
+            [
+                [Value::Varchar("east".to_string()), Value::Integer(6), Value::Integer(8)],
+                [Value::Varchar("west".to_string()), Value::Integer(60), Value::Integer(80)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_columns_errors() -> PrismDBResult<()> {
+        let db = database(QUARTERS)?;
+        assert!(db.query("SELECT COLUMNS('nothing') FROM sales").is_err());
+        assert!(db.query("SELECT COLUMNS('q(') FROM sales").is_err());
+        assert!(db.query("SELECT COLUMNS('q[12]') AS q FROM sales").is_err());
+        assert!(db.query("SELECT COLUMNS('q1') + COLUMNS('q2') FROM sales").is_err());
+        // A single match can be aliased
+        assert_eq!(db.query("SELECT COLUMNS('q1') AS first FROM sales")?.columns[0].name, "first");
+        Ok(())
+    }
+}
+
+/// Tests for keywords used as table and column names
+#[cfg(test)]
+mod keyword_identifier_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    #[test]
+    fn test_non_reserved_keywords_as_names() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE temp (year INTEGER, value INTEGER, name VARCHAR, temp INTEGER, count INTEGER)")?;
+        db.execute("INSERT INTO temp (year, value, name, temp, count) VALUES (2024, 10, 'a', 1, 3), (2025, 20, 'b', 2, 4)")?;
+
+        assert_eq!(
+            db.query("SELECT year, temp.value, name, temp + 1 AS temp FROM temp WHERE year = 2025")?
+                .collect()?
+                .rows,
+            [[Value::Integer(2025), Value::Integer(20), Value::Varchar("b".to_string()), Value::Integer(3)]]
+        );
+
+        // Function keywords name columns unless a parenthesis follows
+        assert_eq!(
+            db.query("SELECT count, COUNT(*), SUM(count) FROM temp AS year GROUP BY count ORDER BY count")?
+                .collect()?
+                .rows,
+            [
+                [Value::Integer(3), Value::BigInt(1), Value::Double(3.0)],
+                [Value::Integer(4), Value::BigInt(1), Value::Double(4.0)],
+            ]
+        );
+
+        db.execute("UPDATE temp SET value = value + year WHERE name = 'a'")?;
+        assert_eq!(
+            db.query("SELECT value FROM temp WHERE name = 'a'")?.collect()?.rows,
+            [[Value::Integer(2034)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reserved_keywords_need_quotes() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        for sql in ["CREATE TABLE t (order INTEGER)", "CREATE TABLE select (x INTEGER)"] {
+            let error = db.execute(sql).unwrap_err().to_string();
+            assert!(error.contains("reserved keyword"), "{}", error);
+        }
+
+        db.execute("CREATE TABLE t (\"order\" INTEGER, \"end\" INTEGER)")?;
+        db.execute("INSERT INTO t VALUES (1, 2), (2, 3)")?;
+        assert_eq!(
+            db.query("SELECT \"order\", t.end FROM t ORDER BY \"order\" DESC")?.collect()?.rows,
+            [[Value::Integer(2), Value::Integer(3)], [Value::Integer(1), Value::Integer(2)]]
+        );
+        assert!(db.query("SELECT order FROM t").is_err());
+
+        // LEFT is reserved, so it starts a join rather than aliasing the table,
+        // and still names a function
+        assert_eq!(
+            db.query("SELECT COUNT(*) FROM t left JOIN t AS u ON t.\"order\" = u.\"order\"")?
+                .collect()?
+                .rows,
+            [[Value::BigInt(2)]]
+        );
+        assert_eq!(
+            db.query("SELECT LEFT('abc', 2)")?.collect()?.rows,
+            [[Value::Varchar("ab".to_string())]]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for blob literals, hex and UTF-8 conversions, and blob ordering
+#[cfg(test)]
+mod blob_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    fn blob(bytes: &[u8]) -> Value {
+        Value::Blob(bytes.to_vec())
+    }
+
+    #[test]
+    fn test_blob_literal() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        assert_eq!(
+            db.query("SELECT x'DEADBEEF', X'00ff', x''")?.collect()?.rows,
+            [[blob(&[0xde, 0xad, 0xbe, 0xef]), blob(&[0x00, 0xff]), blob(&[])]]
+        );
+        assert!(db.query("SELECT x'ABC'").is_err());
+
+        db.execute("CREATE TABLE files (name VARCHAR, data BLOB)")?;
+        db.execute("INSERT INTO files VALUES ('a', x'0102'), ('b', NULL)")?;
+        assert_eq!(
+            db.query("SELECT name FROM files WHERE data = x'0102'")?.collect()?.rows,
+            [[Value::Varchar("a".to_string())]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_conversions_round_trip() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        assert_eq!(
+            db.query("SELECT to_hex(x'00AbCd'), from_hex('deadbeef'), to_hex(from_hex('0A0B'))")?
+                .collect()?
+                .rows,
+            [[
+                Value::Varchar("00ABCD".to_string()),
+                blob(&[0xde, 0xad, 0xbe, 0xef]),
+                Value::Varchar("0A0B".to_string()),
+            ]]
+        );
+        assert_eq!(
+            db.query("SELECT encode('héllo'), decode(encode('héllo')), decode(x'6869'), to_hex('hi')")?
+                .collect()?
+                .rows,
+            [[
+                blob("héllo".as_bytes()),
+                Value::Varchar("héllo".to_string()),
+                Value::Varchar("hi".to_string()),
+                Value::Varchar("6869".to_string()),
+            ]]
+        );
+        assert_eq!(
+            db.query("SELECT to_hex(NULL), decode(NULL)")?.collect()?.rows,
+            [[Value::Null, Value::Null]]
+        );
+
+        // Bad input is an error rather than a silent NULL
+        assert!(db.query("SELECT from_hex('xyz')").and_then(|r| r.collect()).is_err());
+        assert!(db.query("SELECT decode(x'ff')").and_then(|r| r.collect()).is_err());
+        assert!(db.query("SELECT decode('text')").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_blob_ordering() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE b (data BLOB)")?;
+        db.execute("INSERT INTO b VALUES (x'ff'), (x'0000'), (x'00'), (x'01'), (x'00ff')")?;
+
+        // Lexicographic on bytes: a prefix sorts first, and bytes are unsigned
+        assert_eq!(
+            db.query("SELECT data FROM b ORDER BY data")?.collect()?.rows,
+            [
+                [blob(&[0x00])],
+                [blob(&[0x00, 0x00])],
+                [blob(&[0x00, 0xff])],
+                [blob(&[0x01])],
+                [blob(&[0xff])],
+            ]
+        );
+        assert_eq!(
+            db.query("SELECT COUNT(*) FROM b WHERE data > x'00' AND data < x'01'")?.collect()?.rows,
+            [[Value::BigInt(2)]]
+        );
+        assert_eq!(
+            db.query("SELECT MIN(data), MAX(data) FROM b")?.collect()?.rows,
+            [[blob(&[0x00]), blob(&[0xff])]]
+        );
+        assert_eq!(
+            db.query("SELECT x'00' < x'0000', x'80' > x'7f'")?.collect()?.rows,
+            [[Value::Boolean(true), Value::Boolean(true)]]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for resolving scalar function calls to overloads by argument type
+#[cfg(test)]
+mod function_overload_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    #[test]
+    fn test_overload_chosen_by_argument_type() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE readings (small SMALLINT, whole INTEGER, big BIGINT, level DOUBLE)")?;
+        db.execute("INSERT INTO readings VALUES (3, 7, 9000000000, 2.567)")?;
+
+        // Integers keep their type and doubles round as doubles
+        let rows = db
+            .query("SELECT ROUND(whole), ROUND(big), ROUND(level), ROUND(level, 2) FROM readings")?
+            .collect()?
+            .rows;
+        assert_eq!(
+            rows,
+            [[Value::Integer(7), Value::BigInt(9000000000), Value::Double(3.0), Value::Double(2.57)]]
+        );
+
+        // Arguments without an exact overload widen to the closest one
+        let rows = db.query("SELECT ROUND(small), ROUND(whole, 1), SQRT(whole + 9) FROM readings")?.collect()?.rows;
+        assert_eq!(rows, [[Value::Integer(3), Value::Double(7.0), Value::Double(4.0)]]);
+        assert_eq!(db.query("SELECT ROUND(NULL)")?.first_value(), Some(Value::Null));
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_without_matching_overload() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let error = db.query("SELECT ROUND('high')").unwrap_err().to_string();
+        assert!(error.contains("No overload of ROUND matches ROUND(VARCHAR)"), "{}", error);
+        assert!(error.contains("ROUND(DOUBLE, INTEGER)"), "{}", error);
+        assert!(db.query("SELECT ROUND(1.5, 2, 3)").is_err());
+        assert!(db.query("SELECT NO_SUCH_FUNCTION(1)").unwrap_err().to_string().contains("Unknown function"));
+        Ok(())
+    }
+}
+
+/// Tests for SHOW FUNCTIONS and the prism_functions() table function
+#[cfg(test)]
+mod show_functions_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    /// (function_type, signature, return_type) of each listed overload of `name`
+    fn overloads(rows: &[Vec<Value>], name: &str) -> Vec<(String, String, String)> {
+        let text = |value: &Value| match value {
+            Value::Varchar(s) => s.clone(),
+            other => format!("{:?}", other),
+        };
+        rows.iter()
+            .filter(|row| row[0] == Value::Varchar(name.to_string()))
+            .map(|row| (text(&row[1]), text(&row[2]), text(&row[3])))
+            .collect()
+    }
+
+    fn overload(function_type: &str, signature: &str, return_type: &str) -> (String, String, String) {
+        (function_type.to_string(), signature.to_string(), return_type.to_string())
+    }
+
+    #[test]
+    fn test_show_functions_lists_builtins() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let result = db.query("SHOW FUNCTIONS")?;
+        let names: Vec<&str> = result.columns.iter().map(|column| column.name.as_str()).collect();
+        assert_eq!(names, ["function_name", "function_type", "signature", "return_type", "schema"]);
+        let rows = result.collect()?.rows;
+
+        assert_eq!(
+            overloads(&rows, "count"),
+            [overload("aggregate", "COUNT()", "BIGINT"), overload("aggregate", "COUNT(ANY)", "BIGINT")]
+        );
+        assert_eq!(overloads(&rows, "upper"), [overload("scalar", "UPPER(VARCHAR)", "VARCHAR")]);
+        assert_eq!(overloads(&rows, "row_number"), [overload("window", "ROW_NUMBER()", "BIGINT")]);
+        assert!(overloads(&rows, "concat")
+            .iter()
+            .any(|(_, signature, _)| signature.ends_with(", ...)")));
+
+        // Overloads are listed by function name
+        let listed: Vec<&Value> = rows.iter().map(|row| &row[0]).collect();
+        let mut sorted = listed.clone();
+        sorted.sort_by_key(|value| format!("{:?}", value));
+        assert_eq!(listed, sorted);
+        Ok(())
+    }
+
+    #[test]
+    fn test_show_functions_lists_sql_functions() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE FUNCTION add_tax(x DOUBLE, rate DOUBLE) RETURNS DOUBLE AS (x * (1 + rate))")?;
+        db.execute("CREATE FUNCTION twice(x INTEGER) AS (x + x)")?;
+
+        let rows = db.query("SHOW FUNCTIONS")?.collect()?.rows;
+        assert_eq!(overloads(&rows, "add_tax"), [overload("scalar", "ADD_TAX(DOUBLE, DOUBLE)", "DOUBLE")]);
+        assert_eq!(overloads(&rows, "twice"), [overload("scalar", "TWICE(INTEGER)", "Null")]);
+        let add_tax = rows.iter().find(|row| row[0] == Value::Varchar("add_tax".to_string())).unwrap();
+        assert_eq!(add_tax[4], Value::Varchar("main".to_string()));
+
+        // The table function lists the same rows
+        let listed = db.query("SELECT * FROM prism_functions()")?.collect()?.rows;
+        assert_eq!(listed, rows);
+
+        db.execute("DROP FUNCTION twice")?;
+        let rows = db.query("SHOW FUNCTIONS")?.collect()?.rows;
+        assert!(overloads(&rows, "twice").is_empty());
+        Ok(())
+    }
+}
+
+/// Tests for row values `(a, b)` in comparisons and IN lists
+#[cfg(test)]
+mod row_value_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBError, PrismDBResult, Value};
+
+    const PAIRS: &[&str] = &[
+        "CREATE TABLE pairs (a INTEGER, b INTEGER, label VARCHAR)",
+        "INSERT INTO pairs VALUES (1, 2, 'p'), (3, 4, 'q'), (1, 4, 'r'), (NULL, 2, 's'), (3, NULL, 't')",
+    ];
+
+    fn labels(db: &Database, sql: &str) -> PrismDBResult<Vec<String>> {
+        db.query(sql)?.collect()?.rows.iter().map(|row| row[0].try_as_string()).collect()
+    }
+
+    #[test]
+    fn test_row_value_in_list() -> PrismDBResult<()> {
+        let db = database(PAIRS)?;
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (a, b) IN ((1, 2), (3, 4)) ORDER BY label")?, ["p", "q"]);
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (a, b) IN ((1, 4)) ORDER BY label")?, ["r"]);
+        assert!(labels(&db, "SELECT label FROM pairs WHERE (a, b) IN ((2, 1), (4, 3))")?.is_empty());
+
+        // Elements may be any expressions
+        assert_eq!(
+            labels(&db, "SELECT label FROM pairs WHERE (a + 1, label) IN ((2, 'p'), (4, 'x')) ORDER BY label")?,
+            ["p"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_value_comparison() -> PrismDBResult<()> {
+        let db = database(PAIRS)?;
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (a, b) = (1, 4)")?, ["r"]);
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (1, 4) = (a, b)")?, ["r"]);
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (a, b) <> (1, 4) ORDER BY label")?, ["p", "q", "s", "t"]);
+
+        // Ordering comparisons are lexicographic
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (a, b) < (1, 4) ORDER BY label")?, ["p"]);
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE (a, b) >= (1, 4) ORDER BY label")?, ["q", "r", "t"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_value_null_elements() -> PrismDBResult<()> {
+        let db = database(PAIRS)?;
+
+        // A NULL element makes the comparison NULL unless another element decides it
+        let rows = db
+            .query("SELECT label, (a, b) IN ((1, 2), (3, 4)) AS hit FROM pairs ORDER BY label")?
+            .collect()?
+            .rows;
+        let hits: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+        assert_eq!(
+            hits,
+            [Value::Boolean(true), Value::Boolean(true), Value::Boolean(false), Value::Null, Value::Null]
+        );
+        assert_eq!(labels(&db, "SELECT label FROM pairs WHERE NOT (a, b) IN ((1, 2)) ORDER BY label")?, ["q", "r", "t"]);
+        assert_eq!(
+            db.query("SELECT (1, NULL) = (2, NULL), (1, NULL) = (1, NULL), (1, NULL) <> (2, NULL)")?.collect()?.rows,
+            [[Value::Boolean(false), Value::Null, Value::Boolean(true)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_value_errors() -> PrismDBResult<()> {
+        let db = database(PAIRS)?;
+        for sql in [
+            "SELECT label FROM pairs WHERE (a, b) IN ((1, 2, 3))",
+            "SELECT label FROM pairs WHERE (a, b) = (1, 2, 3)",
+            "SELECT label FROM pairs WHERE (a, b) = 1",
+        ] {
+            assert!(matches!(db.query(sql), Err(PrismDBError::Parse(_))), "{}", sql);
+        }
+        Ok(())
+    }
+}
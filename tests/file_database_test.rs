@@ -1,5 +1,6 @@
 use prism::PrismDBResult;
 use prism::database::{Database, DatabaseConfig};
+use prism::storage::{db_header_bytes, DB_FORMAT_VERSION, DB_MAGIC};
 use tempfile::tempdir;
 
 #[test]
@@ -97,3 +98,55 @@ fn test_file_database_basic_operations() -> PrismDBResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_open_rejects_foreign_file() -> PrismDBResult<()> {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("notes.txt");
+    std::fs::write(&db_path, "just some text, not a database").unwrap();
+
+    let err = Database::open(&db_path).err().expect("foreign file should not open");
+    assert!(err.to_string().contains("Not a PrismDB database file"), "{}", err);
+
+    // A file too short to hold a header is refused too, and left untouched
+    std::fs::write(&db_path, b"PR").unwrap();
+    assert!(Database::open(&db_path).is_err());
+    assert_eq!(std::fs::read(&db_path).unwrap(), b"PR");
+    Ok(())
+}
+
+#[test]
+fn test_open_rejects_future_format_version() -> PrismDBResult<()> {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("future.db");
+
+    let mut header = db_header_bytes();
+    header[4..8].copy_from_slice(&(DB_FORMAT_VERSION + 1).to_le_bytes());
+    std::fs::write(&db_path, &header).unwrap();
+
+    let err = Database::open(&db_path).err().expect("future version should not open");
+    let message = err.to_string();
+    assert!(
+        message.contains(&format!("Unsupported database format version {}", DB_FORMAT_VERSION + 1)),
+        "{}",
+        message
+    );
+    assert!(message.contains(&format!("this build reads version {}", DB_FORMAT_VERSION)), "{}", message);
+    Ok(())
+}
+
+#[test]
+fn test_new_database_file_has_header() -> PrismDBResult<()> {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("fresh.db");
+
+    Database::open(&db_path)?;
+    let bytes = std::fs::read(&db_path).unwrap();
+    assert_eq!(&bytes[0..4], &DB_MAGIC);
+    assert!(!dir.path().join("fresh.db.tmp").exists());
+
+    // Reopening the file it wrote succeeds
+    let db = Database::open(&db_path)?;
+    assert!(db.is_file_based());
+    Ok(())
+}
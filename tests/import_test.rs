@@ -1,6 +1,8 @@
-//! Import test - currently disabled as IteratorStream is not implemented
+//! Import and export tests
 //!
-//! This test will be re-enabled once the IteratorStream operator is added
+//! The placeholder below is for IteratorStream, which is not implemented yet.
+
+mod common;
 
 #[cfg(test)]
 mod import_tests {
@@ -10,3 +12,667 @@ mod import_tests {
         assert!(true);
     }
 }
+
+/// Tests for reading gzip- and zstd-compressed CSV and JSON files
+#[cfg(test)]
+mod compressed_file_tests {
+    use crate::common::rows;
+    use flate2::write::GzEncoder;
+    use prism::{Database, PrismDBResult, Value};
+    use std::io::Write;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    const CSV: &str = "id,name,city\n1,alice,paris\n2,bob,\n3,carol,berlin\n";
+    const NDJSON: &str = "{\"id\": 1, \"name\": \"alice\"}\n{\"id\": 2, \"name\": \"bob\"}\n";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn zstd(data: &[u8]) -> Vec<u8> {
+        zstd::encode_all(data, 3).unwrap()
+    }
+
+    fn write_file(dir: &TempDir, name: &str, data: &[u8]) -> String {
+        let path = dir.path().join(name);
+        std::fs::write(&path, data).unwrap();
+        path_string(&path)
+    }
+
+    fn path_string(path: &Path) -> String {
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_read_compressed_csv_by_extension() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let plain = write_file(&dir, "people.csv", CSV.as_bytes());
+        let gz = write_file(&dir, "people.csv.gz", &gzip(CSV.as_bytes()));
+        let zst = write_file(&dir, "people.csv.zst", &zstd(CSV.as_bytes()));
+
+        let expected = rows(&db, &format!("SELECT * FROM read_csv_auto('{}')", plain))?;
+        assert_eq!(expected.len(), 3);
+        assert_eq!(expected[1][2], Value::Null);
+
+        for path in [&gz, &zst] {
+            let result = db.query(&format!("SELECT * FROM read_csv_auto('{}')", path))?;
+            let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+            assert_eq!(names, vec!["id", "name", "city"], "{}", path);
+            assert_eq!(result.collect()?.rows, expected, "{}", path);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_csv_with_compression_option() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let plain = write_file(&dir, "people.csv", CSV.as_bytes());
+        let gz = write_file(&dir, "people.data", &gzip(CSV.as_bytes()));
+        let zst = write_file(&dir, "people.bin", &zstd(CSV.as_bytes()));
+
+        let expected = rows(&db, &format!("SELECT * FROM read_csv_auto('{}')", plain))?;
+        assert_eq!(
+            rows(&db, &format!("SELECT * FROM read_csv_auto('{}', compression = 'gzip')", gz))?,
+            expected
+        );
+        assert_eq!(
+            rows(&db, &format!("SELECT * FROM read_csv_auto('file://{}', compression = 'ZSTD')", zst))?,
+            expected
+        );
+        assert_eq!(
+            rows(&db, &format!("SELECT * FROM read_csv_auto('{}', compression = 'none')", plain))?,
+            expected
+        );
+
+        assert!(db
+            .query(&format!("SELECT * FROM read_csv_auto('{}', compression = 'lzma')", gz))
+            .is_err());
+        // Compressed bytes are not valid CSV without decompression
+        assert_ne!(
+            rows(&db, &format!("SELECT * FROM read_csv_auto('{}', compression = 'none')", zst)).ok(),
+            Some(expected)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_compressed_json() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let plain = write_file(&dir, "people.json", NDJSON.as_bytes());
+        let gz = write_file(&dir, "people.json.gz", &gzip(NDJSON.as_bytes()));
+        let zst = write_file(&dir, "people.json.zst", &zstd(NDJSON.as_bytes()));
+
+        let expected = rows(&db, &format!("SELECT * FROM read_json_auto('{}')", plain))?;
+        assert_eq!(expected.len(), 2);
+        for path in [&gz, &zst] {
+            assert_eq!(rows(&db, &format!("SELECT * FROM read_json_auto('{}')", path))?, expected);
+        }
+
+        let array = write_file(&dir, "array.json.zst", &zstd(b" [{\"id\": 1}, {\"id\": 2}]"));
+        assert_eq!(
+            rows(&db, &format!("SELECT * FROM read_json_auto('{}')", array))?,
+            vec![vec![Value::BigInt(1)], vec![Value::BigInt(2)]]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for reading files over HTTP and S3 against a local mock server
+#[cfg(test)]
+mod httpfs_tests {
+    use crate::common::rows;
+    use arrow::array::{ArrayRef, Int64Array, StringArray};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use prism::{Database, PrismDBResult, Value};
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    const CSV: &str = "id,name\n1,alice\n2,bob\n3,carol\n";
+    const PARQUET_ROWS: i64 = 5_000;
+
+    /// A request the mock server received
+    #[derive(Debug, Clone)]
+    struct Request {
+        method: String,
+        path: String,
+        headers: HashMap<String, String>,
+    }
+
+    /// Minimal HTTP/1.1 server serving fixed files, with HEAD and Range support
+    struct MockServer {
+        address: String,
+        requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl MockServer {
+        fn start(files: HashMap<String, Vec<u8>>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let log = requests.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else { break };
+                    if let Some(request) = Self::handle(stream, &files) {
+                        log.lock().unwrap().push(request);
+                    }
+                }
+            });
+            Self { address, requests }
+        }
+
+        fn handle(mut stream: TcpStream, files: &HashMap<String, Vec<u8>>) -> Option<Request> {
+            let mut reader = BufReader::new(stream.try_clone().ok()?);
+            let mut line = String::new();
+            reader.read_line(&mut line).ok()?;
+            let mut parts = line.split_whitespace();
+            let method = parts.next()?.to_string();
+            let path = parts.next()?.to_string();
+
+            let mut headers = HashMap::new();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).ok()?;
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                let (name, value) = header.split_once(':')?;
+                headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+            }
+
+            let response = match files.get(&path) {
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                Some(body) => {
+                    let (status, range, content) = match headers.get("range") {
+                        Some(range) => {
+                            let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+                            let start: usize = start.parse().ok()?;
+                            let end = end.parse::<usize>().ok()?.min(body.len() - 1);
+                            let content_range = format!("Content-Range: bytes {}-{}/{}\r\n", start, end, body.len());
+                            ("206 Partial Content", content_range, &body[start..=end])
+                        }
+                        None => ("200 OK", String::new(), &body[..]),
+                    };
+                    let mut response = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+                        status,
+                        content.len(),
+                        range
+                    )
+                    .into_bytes();
+                    if method != "HEAD" {
+                        response.extend_from_slice(content);
+                    }
+                    response
+                }
+            };
+            stream.write_all(&response).ok()?;
+            Some(Request { method, path, headers })
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("http://{}{}", self.address, path)
+        }
+
+        fn requests(&self) -> Vec<Request> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn parquet_file() -> Vec<u8> {
+        let ids: ArrayRef = Arc::new(Int64Array::from_iter_values(0..PARQUET_ROWS));
+        let names: ArrayRef = Arc::new(StringArray::from_iter_values(
+            (0..PARQUET_ROWS).map(|i| format!("name-{}", i)),
+        ));
+        let batch = RecordBatch::try_from_iter(vec![("id", ids), ("name", names)]).unwrap();
+
+        let mut data = Vec::new();
+        let properties = WriterProperties::builder().set_max_row_group_size(1_000).build();
+        let mut writer = ArrowWriter::try_new(&mut data, batch.schema(), Some(properties)).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_read_csv_over_http() -> PrismDBResult<()> {
+        let server = MockServer::start(HashMap::from([("/data/people.csv".to_string(), CSV.as_bytes().to_vec())]));
+        let db = Database::new_in_memory()?;
+
+        let result = rows(&db, &format!("SELECT * FROM read_csv_auto('{}')", server.url("/data/people.csv")))?;
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[2], vec![Value::BigInt(3), Value::Varchar("carol".to_string())]);
+
+        assert!(db
+            .query(&format!("SELECT * FROM read_csv_auto('{}')", server.url("/missing.csv")))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_parquet_over_http_with_ranges() -> PrismDBResult<()> {
+        let data = parquet_file();
+        let size = data.len();
+        let server = MockServer::start(HashMap::from([("/lake/events.parquet".to_string(), data)]));
+        let db = Database::new_in_memory()?;
+
+        let result = db.query(&format!("SELECT * FROM read_parquet('{}')", server.url("/lake/events.parquet")))?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "name"]);
+        let result = result.collect()?.rows;
+        assert_eq!(result.len(), PARQUET_ROWS as usize);
+        assert_eq!(result[4_321], vec![Value::BigInt(4_321), Value::Varchar("name-4321".to_string())]);
+
+        // The file is sized with HEAD and then only read with range requests
+        let requests = server.requests();
+        assert!(requests.iter().any(|r| r.method == "HEAD"));
+        let gets: Vec<&Request> = requests.iter().filter(|r| r.method == "GET").collect();
+        assert!(gets.len() > 1);
+        for request in gets {
+            let (start, end) = request.headers["range"]
+                .strip_prefix("bytes=")
+                .and_then(|range| range.split_once('-'))
+                .unwrap();
+            let length = end.parse::<usize>().unwrap() - start.parse::<usize>().unwrap() + 1;
+            assert!(length < size, "{:?}", request);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_s3_signs_requests() -> PrismDBResult<()> {
+        let server = MockServer::start(HashMap::from([
+            ("/bucket/people.csv".to_string(), CSV.as_bytes().to_vec()),
+            ("/bucket/events.parquet".to_string(), parquet_file()),
+        ]));
+        let mut db = Database::new_in_memory()?;
+        db.execute(&format!("SET s3_endpoint = '{}'", server.address))?;
+        db.execute("SET s3_use_ssl = false")?;
+        db.execute("SET s3_access_key_id = 'test-key'")?;
+        db.execute("SET s3_secret_access_key = 'test-secret'")?;
+
+        assert_eq!(rows(&db, "SELECT * FROM read_csv_auto('s3://bucket/people.csv')")?.len(), 3);
+        assert_eq!(
+            rows(&db, "SELECT * FROM read_parquet('s3://bucket/events.parquet')")?.len(),
+            PARQUET_ROWS as usize
+        );
+
+        let requests = server.requests();
+        assert!(requests.iter().any(|r| r.path == "/bucket/events.parquet" && r.method == "HEAD"));
+        for request in &requests {
+            let authorization = &request.headers["authorization"];
+            assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=test-key/"), "{}", authorization);
+            assert!(request.headers.contains_key("x-amz-date"));
+        }
+        Ok(())
+    }
+}
+
+/// Tests for COPY ... TO, including Hive-style PARTITION_BY output
+#[cfg(test)]
+mod copy_to_tests {
+    use crate::common::{database, rows};
+    use prism::{PrismDBResult, Value};
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    const SALES: &[&str] = &[
+        "CREATE TABLE sales (year INTEGER, month INTEGER, item VARCHAR, amount DOUBLE)",
+        "INSERT INTO sales VALUES \
+     (2023, 12, 'pen', 1.5), \
+     (2024, 1, 'ink', 4.0), \
+     (2024, 1, 'pad', 2.25), \
+     (2024, 2, 'pen', 1.5)",
+    ];
+
+    fn sorted(mut rows: Vec<Vec<Value>>) -> Vec<Vec<Value>> {
+        rows.sort_by_key(|row| format!("{:?}", row));
+        rows
+    }
+
+    #[test]
+    fn test_copy_partition_by_writes_hive_directories() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("sales");
+
+        let result = rows(
+            &db,
+            &format!(
+                "COPY (SELECT year, month, item, amount FROM sales) TO '{}' \
+             (FORMAT PARQUET, PARTITION_BY (year, month))",
+                target.display()
+            ),
+        )?;
+        assert_eq!(result, vec![vec![Value::BigInt(4)]]);
+
+        for partition in ["year=2023/month=12", "year=2024/month=1", "year=2024/month=2"] {
+            let file = target.join(partition).join("data_0.parquet");
+            assert!(file.is_file(), "missing {}", file.display());
+        }
+        let years: Vec<String> = std::fs::read_dir(&target)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(years.len(), 2);
+
+        // Partition columns are taken from the paths, not stored in the files
+        let pattern = format!("{}/**/*.parquet", target.display());
+        let result = db.query(&format!("SELECT * FROM read_parquet('{}')", pattern))?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["item", "amount", "year", "month"]);
+
+        let item = |item: &str| Value::Varchar(item.to_string());
+        assert_eq!(
+            sorted(result.collect()?.rows),
+            sorted(vec![
+                vec![item("pen"), Value::Double(1.5), Value::BigInt(2023), Value::BigInt(12)],
+                vec![item("ink"), Value::Double(4.0), Value::BigInt(2024), Value::BigInt(1)],
+                vec![item("pad"), Value::Double(2.25), Value::BigInt(2024), Value::BigInt(1)],
+                vec![item("pen"), Value::Double(1.5), Value::BigInt(2024), Value::BigInt(2)],
+            ])
+        );
+
+        // A narrower glob reads just one partition
+        let pattern = format!("{}/year=2024/month=1/*.parquet", target.display());
+        assert_eq!(rows(&db, &format!("SELECT * FROM read_parquet('{}')", pattern))?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_table_to_csv() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("sales.csv");
+
+        db.query(&format!("COPY sales TO '{}' (HEADER)", file.display()))?;
+        let contents = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(contents.lines().next(), Some("year,month,item,amount"));
+        assert_eq!(contents.lines().count(), 5);
+
+        // CSV partitions are read back through read_csv_auto
+        let target = dir.path().join("by_item");
+        db.query(&format!(
+            "COPY (SELECT item, amount FROM sales) TO '{}' (FORMAT CSV, PARTITION_BY (item))",
+            target.display()
+        ))?;
+        assert!(Path::new(&target.join("item=ink/data_0.csv")).is_file());
+        let pattern = format!("{}/*/*.csv", target.display());
+        let result = sorted(rows(&db, &format!("SELECT * FROM read_csv_auto('{}')", pattern))?);
+        assert_eq!(result.len(), 4);
+        assert_eq!(
+            result[0],
+            vec![Value::Double(1.5), Value::Varchar("pen".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_rejects_bad_options() -> PrismDBResult<()> {
+        let db = database(SALES)?;
+        let dir = TempDir::new().unwrap();
+        let target = dir.path().join("out");
+
+        for options in ["(FORMAT XLSX)", "(PARTITION_BY (region))", "(COMPRESSION 'lzma')"] {
+            let sql = format!("COPY sales TO '{}' {}", target.display(), options);
+            assert!(db.query(&sql).is_err(), "{}", sql);
+        }
+        assert!(db
+            .query(&format!("SELECT * FROM read_parquet('{}/*.parquet')", target.display()))
+            .is_err());
+        Ok(())
+    }
+}
+
+/// Tests for read_csv_auto type inference and parsing options, given as named arguments
+#[cfg(test)]
+mod csv_reader_tests {
+    use prism::{Database, LogicalType, PrismDBResult, QueryResult, Value};
+    use tempfile::TempDir;
+
+    fn write_file(dir: &TempDir, name: &str, text: &str) -> String {
+        let path = dir.path().join(name);
+        std::fs::write(&path, text).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    /// Column types of a query's result, and its rows
+    fn read(db: &Database, sql: &str) -> PrismDBResult<(QueryResult, Vec<Vec<Value>>)> {
+        let result = db.query(sql)?;
+        let rows = result.collect()?.rows;
+        Ok((result, rows))
+    }
+
+    fn types(result: &QueryResult) -> Vec<LogicalType> {
+        result.columns.iter().map(|c| c.data_type.clone()).collect()
+    }
+
+    fn varchar(s: &str) -> Value {
+        Value::Varchar(s.to_string())
+    }
+
+    #[test]
+    fn test_types_promote_after_sample() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let path = write_file(&dir, "readings.csv", "id,value,label\n1,10,a\n2,20,b\n3,2.5,c\n4,x,d\n");
+
+        // The first two rows say `value` is BIGINT; 2.5 makes it DOUBLE, then `x` VARCHAR
+        let (result, rows) = read(&db, &format!("SELECT * FROM read_csv_auto('{}', sample_size = 2)", path))?;
+        assert_eq!(types(&result), [LogicalType::BigInt, LogicalType::Varchar, LogicalType::Varchar]);
+        let values: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+        assert_eq!(values, [varchar("10"), varchar("20"), varchar("2.5"), varchar("x")]);
+
+        // Stopping at the float gives DOUBLE, with the earlier integers cast
+        let path = write_file(&dir, "floats.csv", "id,value\n1,10\n2,20\n3,2.5\n");
+        let (result, rows) = read(&db, &format!("SELECT * FROM read_csv_auto('{}', sample_size = 1)", path))?;
+        assert_eq!(types(&result), [LogicalType::BigInt, LogicalType::Double]);
+        assert_eq!(
+            rows,
+            [
+                vec![Value::BigInt(1), Value::Double(10.0)],
+                vec![Value::BigInt(2), Value::Double(20.0)],
+                vec![Value::BigInt(3), Value::Double(2.5)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_quoted_multiline_fields() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let path = write_file(
+            &dir,
+            "notes.csv",
+            "id,note\n1,\"first line\nsecond line\"\n2,\"says \"\"hi\"\", then leaves\"\n",
+        );
+
+        let (_, rows) = read(&db, &format!("SELECT * FROM read_csv_auto('{}')", path))?;
+        assert_eq!(
+            rows,
+            [
+                vec![Value::BigInt(1), varchar("first line\nsecond line")],
+                vec![Value::BigInt(2), varchar("says \"hi\", then leaves")],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_delimiter_null_string_and_header() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let path = write_file(&dir, "scores.csv", "1;NA;true\n2;7.5;false\n");
+
+        let (result, rows) = read(
+            &db,
+            &format!("SELECT * FROM read_csv_auto('{}', delim = ';', nullstr = 'NA', header = false)", path),
+        )?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["column0", "column1", "column2"]);
+        assert_eq!(types(&result), [LogicalType::BigInt, LogicalType::Double, LogicalType::Boolean]);
+        assert_eq!(rows[0], vec![Value::BigInt(1), Value::Null, Value::Boolean(true)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_explicit_columns() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let path = write_file(&dir, "items.csv", "a,b\n1,10\n2,x\n");
+
+        let sql = format!("SELECT * FROM read_csv_auto('{}', columns = 'id INTEGER, code VARCHAR')", path);
+        let (result, rows) = read(&db, &sql)?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id", "code"]);
+        assert_eq!(rows[1], vec![Value::Integer(2), varchar("x")]);
+
+        // Given types are kept rather than promoted, so `x` is an error
+        let sql = format!("SELECT * FROM read_csv_auto('{}', columns = 'id INTEGER, code INTEGER')", path);
+        assert!(db.query(&sql).is_err());
+        assert!(db
+            .query(&format!("SELECT * FROM read_csv_auto('{}', delimiter_typo = ';')", path))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_arguments_parse_after_positional_ones() -> PrismDBResult<()> {
+        use prism::parser::{parse_sql, Expression, LiteralValue, Statement, TableReference};
+
+        let Statement::Select(select) =
+            parse_sql("SELECT * FROM read_csv('f.csv', 2, header => true, Delim = '|', sample_size => -1)")?
+        else {
+            panic!("expected a SELECT");
+        };
+        let Some(TableReference::TableFunction { arguments, named_arguments, .. }) = select.from else {
+            panic!("expected a table function");
+        };
+        assert_eq!(
+            arguments,
+            [
+                Expression::Literal(LiteralValue::String("f.csv".to_string())),
+                Expression::Literal(LiteralValue::Integer(2)),
+            ]
+        );
+        let names: Vec<&str> = named_arguments.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["header", "delim", "sample_size"]);
+        assert_eq!(named_arguments[1].1, Expression::Literal(LiteralValue::String("|".to_string())));
+
+        assert!(parse_sql("SELECT * FROM read_csv(header => true, 'f.csv')").is_err());
+        assert!(parse_sql("SELECT * FROM read_csv('f.csv', header => true, header => false)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reader_consumes_named_options() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let dir = TempDir::new().unwrap();
+        let path = write_file(&dir, "pipes.csv", "1|x\n2|-\n");
+
+        // `=>` and `=` spell the same option
+        for sql in [
+            format!("SELECT * FROM read_csv('{}', header => false, delim => '|', nullstr => '-')", path),
+            format!("SELECT * FROM read_csv_auto('{}', header = false, delim = '|', nullstr => '-')", path),
+        ] {
+            let (result, rows) = read(&db, &sql)?;
+            assert_eq!(types(&result), [LogicalType::BigInt, LogicalType::Varchar], "{}", sql);
+            assert_eq!(rows, [vec![Value::BigInt(1), varchar("x")], vec![Value::BigInt(2), Value::Null]]);
+        }
+
+        // Options must be constants, and functions without options reject them
+        assert!(db.query(&format!("SELECT * FROM read_csv('{}', header => 1 = 1)", path)).is_err());
+        assert!(db.query("SELECT * FROM prism_metrics(verbose => true)").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for COPY ... TO and COPY ... FROM with FORMAT JSON
+#[cfg(test)]
+mod copy_json_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBResult, Value};
+
+    const EVENTS: &[&str] = &[
+        "CREATE TABLE events (id INTEGER, name VARCHAR, score DOUBLE, seen BOOLEAN, day DATE)",
+        "INSERT INTO events VALUES \
+     (1, 'open', 1.5, true, CAST('2024-03-01' AS DATE)), \
+     (2, NULL, NULL, false, NULL), \
+     (3, 'say \"hi\"', -2.0, NULL, CAST('2024-03-03' AS DATE))",
+    ];
+
+    fn rows(db: &Database, table: &str) -> PrismDBResult<Vec<Vec<Value>>> {
+        Ok(db.query(&format!("SELECT * FROM {} ORDER BY id", table))?.collect()?.rows)
+    }
+
+    #[test]
+    fn test_ndjson_round_trip() -> PrismDBResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.json");
+        let path = path.to_str().unwrap();
+
+        let mut db = database(EVENTS)?;
+        let result = db.execute(&format!("COPY events TO '{}' (FORMAT JSON)", path))?;
+        assert_eq!(result.collect()?.rows, [[Value::BigInt(3)]]);
+
+        // One object per line, keyed by column name in column order
+        let text = std::fs::read_to_string(path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            r#"{"id":2,"name":null,"score":null,"seen":false,"day":null}"#
+        );
+
+        db.execute("CREATE TABLE loaded (id INTEGER, name VARCHAR, score DOUBLE, seen BOOLEAN, day DATE)")?;
+        let result = db.execute(&format!("COPY loaded FROM '{}' (FORMAT JSON)", path))?;
+        assert_eq!(result.collect()?.rows, [[Value::BigInt(3)]]);
+        assert_eq!(rows(&db, "loaded")?, rows(&db, "events")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_array_and_partial_objects() -> PrismDBResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.out");
+        let path = path.to_str().unwrap();
+
+        let mut db = database(EVENTS)?;
+        db.execute(&format!("COPY (SELECT id, name FROM events WHERE id < 3) TO '{}' (FORMAT JSON, ARRAY true)", path))?;
+        let parsed: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!([{"id": 1, "name": "open"}, {"id": 2, "name": null}])
+        );
+
+        // Columns missing from the objects load as NULL
+        db.execute("CREATE TABLE loaded (id INTEGER, name VARCHAR, score DOUBLE)")?;
+        db.execute(&format!("COPY loaded FROM '{}' (FORMAT JSON)", path))?;
+        assert_eq!(
+            rows(&db, "loaded")?,
+            [
+                [Value::Integer(1), Value::Varchar("open".to_string()), Value::Null],
+                [Value::Integer(2), Value::Null, Value::Null],
+            ]
+        );
+
+        // Keys that name no column are rejected, and nothing is loaded
+        db.execute("CREATE TABLE narrow (id INTEGER)")?;
+        assert!(db.execute(&format!("COPY narrow FROM '{}' (FORMAT JSON)", path)).is_err());
+        assert!(rows(&db, "narrow")?.is_empty());
+        Ok(())
+    }
+}
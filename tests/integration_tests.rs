@@ -3,8 +3,10 @@
 //! These tests provide end-to-end validation of query execution
 //! with proper result verification and edge case testing.
 
-use prism::{Database, PrismDBResult};
+use prism::{Database, DatabaseConfig, NullOrder, PrismDBResult};
 use prism::types::*;
+
+mod common;
 // use std::sync::Arc; // Not needed currently
 
 /// Test helper to create a test database with sample data
@@ -152,6 +154,126 @@ fn test_order_by_clause() -> PrismDBResult<()> {
     Ok(())
 }
 
+/// Collect the first column of a query as a list of values
+fn first_column(db: &mut Database, sql: &str) -> PrismDBResult<Vec<Value>> {
+    let collected = db.execute(sql)?.collect()?;
+    Ok(collected.rows.into_iter().map(|row| row[0].clone()).collect())
+}
+
+/// Test NULL placement in ORDER BY with and without NULLS FIRST/LAST
+#[test]
+fn test_order_by_null_ordering() -> PrismDBResult<()> {
+    let mut db = Database::new_in_memory()?;
+    db.execute("CREATE TABLE t (x INTEGER)")?;
+    db.execute("INSERT INTO t VALUES (2)")?;
+    db.execute("INSERT INTO t VALUES (NULL)")?;
+    db.execute("INSERT INTO t VALUES (1)")?;
+
+    let asc_nulls_last = vec![Value::Integer(1), Value::Integer(2), Value::Null];
+    let asc_nulls_first = vec![Value::Null, Value::Integer(1), Value::Integer(2)];
+    let desc_nulls_first = vec![Value::Null, Value::Integer(2), Value::Integer(1)];
+    let desc_nulls_last = vec![Value::Integer(2), Value::Integer(1), Value::Null];
+
+    // Unspecified: NULLs sort as the largest value
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x")?, asc_nulls_last);
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x ASC")?, asc_nulls_last);
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x DESC")?, desc_nulls_first);
+
+    // Explicit NULLS FIRST/LAST wins regardless of direction
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x ASC NULLS FIRST")?, asc_nulls_first);
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x ASC NULLS LAST")?, asc_nulls_last);
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x DESC NULLS FIRST")?, desc_nulls_first);
+    assert_eq!(first_column(&mut db, "SELECT x FROM t ORDER BY x DESC NULLS LAST")?, desc_nulls_last);
+
+    Ok(())
+}
+
+/// Test the configurable default NULL ordering
+#[test]
+fn test_default_null_order_config() -> PrismDBResult<()> {
+    let config = DatabaseConfig {
+        default_null_order: NullOrder::NullsFirst,
+        ..DatabaseConfig::in_memory()
+    };
+    let mut db = Database::new(config)?;
+    db.execute("CREATE TABLE t (x INTEGER)")?;
+    db.execute("INSERT INTO t VALUES (2)")?;
+    db.execute("INSERT INTO t VALUES (NULL)")?;
+    db.execute("INSERT INTO t VALUES (1)")?;
+
+    assert_eq!(
+        first_column(&mut db, "SELECT x FROM t ORDER BY x")?,
+        vec![Value::Null, Value::Integer(1), Value::Integer(2)]
+    );
+    assert_eq!(
+        first_column(&mut db, "SELECT x FROM t ORDER BY x DESC NULLS LAST")?,
+        vec![Value::Integer(2), Value::Integer(1), Value::Null]
+    );
+
+    // SET overrides the configured default
+    db.execute("SET default_null_order = 'nulls_last'")?;
+    assert_eq!(
+        first_column(&mut db, "SELECT x FROM t ORDER BY x DESC")?,
+        vec![Value::Integer(2), Value::Integer(1), Value::Null]
+    );
+    assert!(db.execute("SET default_null_order = 'sideways'").is_err());
+
+    Ok(())
+}
+
+/// Test running several semicolon-separated statements as one batch
+#[test]
+fn test_execute_batch() -> PrismDBResult<()> {
+    let db = Database::new_in_memory()?;
+
+    // DDL and DML earlier in the batch are visible to the final SELECT
+    let result = db.execute_sql_collect(
+        "CREATE TABLE batch (id INTEGER, name VARCHAR);
+         INSERT INTO batch VALUES (1, 'a');
+         INSERT INTO batch VALUES (2, 'b');
+         UPDATE batch SET name = 'c' WHERE id = 2;
+         SELECT name FROM batch ORDER BY id;",
+    )?;
+    let names: Vec<Value> = result.collect()?.rows.into_iter().map(|row| row[0].clone()).collect();
+    assert_eq!(names, vec![Value::Varchar("a".into()), Value::Varchar("c".into())]);
+
+    // execute_batch returns one result per statement
+    let results = db.execute_batch("INSERT INTO batch VALUES (3, 'd'); SELECT id FROM batch; SELECT 1;")?;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1].row_count(), 3);
+
+    // The failing statement is identified and later statements do not run
+    let err = db
+        .execute_batch("INSERT INTO batch VALUES (4, 'e'); SELECT * FROM missing; INSERT INTO batch VALUES (5, 'f')")
+        .unwrap_err();
+    assert!(err.to_string().contains("Statement 2 of 3"), "{}", err);
+    assert_eq!(db.query("SELECT id FROM batch")?.row_count(), 4);
+    Ok(())
+}
+
+/// Test GROUP BY keys that mix types, NULLs and separator characters
+#[test]
+fn test_group_by_composite_keys() -> PrismDBResult<()> {
+    let mut db = Database::new_in_memory()?;
+    db.execute("CREATE TABLE tags (a VARCHAR, b VARCHAR, n INTEGER)")?;
+    db.execute(
+        "INSERT INTO tags VALUES ('x|y', 'z', 1), ('x', 'y|z', 2), ('x|y', 'z', 3), (NULL, 'NULL', 4), (NULL, 'NULL', 5), ('NULL', NULL, 6)",
+    )?;
+
+    let result = db.query("SELECT a, b, SUM(n) AS total FROM tags GROUP BY a, b ORDER BY total")?;
+    let rows = result.collect()?.rows;
+    assert_eq!(
+        rows,
+        vec![
+            vec![Value::Varchar("x".into()), Value::Varchar("y|z".into()), Value::Double(2.0)],
+            vec![Value::Varchar("x|y".into()), Value::Varchar("z".into()), Value::Double(4.0)],
+            vec![Value::Varchar("NULL".into()), Value::Null, Value::Double(6.0)],
+            vec![Value::Null, Value::Varchar("NULL".into()), Value::Double(9.0)],
+        ]
+    );
+    Ok(())
+}
+
 /// Test scalar functions in queries
 #[test]
 fn test_scalar_functions() -> PrismDBResult<()> {
@@ -610,10 +732,10 @@ fn test_union_with_order_limit() -> PrismDBResult<()> {
     db.execute("INSERT INTO numbers2 VALUES (4)")?;
     db.execute("INSERT INTO numbers2 VALUES (2)")?;
 
-    // TODO: Test with "(SELECT * FROM numbers1 UNION ALL SELECT * FROM numbers2) ORDER BY value LIMIT 3"
-    // Should return 1, 2, 3
-
-    println!("UNION with ORDER BY and LIMIT test placeholder");
+    // ORDER BY and LIMIT after the last query apply to the combined rows
+    let result = db.query("SELECT * FROM numbers1 UNION ALL SELECT * FROM numbers2 ORDER BY value LIMIT 3")?;
+    let rows = result.collect()?.rows;
+    assert_eq!(rows, [[Value::Integer(1)], [Value::Integer(2)], [Value::Integer(3)]]);
 
     Ok(())
 }
@@ -639,10 +761,19 @@ fn test_intersect_all() -> PrismDBResult<()> {
     db.execute("INSERT INTO set_b VALUES (2, 'B')")?; // Duplicate
     db.execute("INSERT INTO set_b VALUES (4, 'D')")?;
 
-    // TODO: Test with "SELECT * FROM set_a INTERSECT ALL SELECT * FROM set_b"
-    // Should return {(2,'B'), (2,'B')} - common rows with duplicates counted
+    // Common rows with duplicates counted: min(2, 3) = 2 copies of (2,'B')
+    let result = db.query("SELECT * FROM set_a INTERSECT ALL SELECT * FROM set_b")?;
+    let rows = result.collect()?.rows;
+    let b = [Value::Integer(2), Value::Varchar("B".to_string())];
+    assert_eq!(rows, [b.clone(), b.clone()]);
+
+    // The count is the lesser one whichever side has more
+    let result = db.query("SELECT * FROM set_b INTERSECT ALL SELECT * FROM set_a ORDER BY id")?;
+    assert_eq!(result.collect()?.rows, [b.clone(), b.clone()]);
 
-    println!("INTERSECT ALL test placeholder - parser support needed");
+    // Without ALL the common row appears once
+    let result = db.query("SELECT * FROM set_a INTERSECT SELECT * FROM set_b")?;
+    assert_eq!(result.collect()?.rows, [b]);
 
     Ok(())
 }
@@ -697,12 +828,23 @@ fn test_except_all() -> PrismDBResult<()> {
     db.execute("INSERT INTO right_set VALUES (2, 'B')")?;
     db.execute("INSERT INTO right_set VALUES (3, 'C')")?;
 
-    // TODO: Test with "SELECT * FROM left_set EXCEPT ALL SELECT * FROM right_set"
-    // Should return {(1,'A'), (2,'B'), (3,'C'), (3,'C')}
     // Left has 2 B's, right has 1, so 1 B remains
     // Left has 3 C's, right has 1, so 2 C's remain
-
-    println!("EXCEPT ALL test placeholder - parser support needed");
+    let result = db.query("SELECT * FROM left_set EXCEPT ALL SELECT * FROM right_set ORDER BY id")?;
+    let row = |id: i32, name: &str| vec![Value::Integer(id), Value::Varchar(name.to_string())];
+    assert_eq!(
+        result.collect()?.rows,
+        [row(1, "A"), row(2, "B"), row(3, "C"), row(3, "C")]
+    );
+
+    // More copies on the right than the left leave none, never a negative count
+    db.execute("INSERT INTO right_set VALUES (2, 'B'), (2, 'B'), (2, 'B')")?;
+    let result = db.query("SELECT * FROM left_set EXCEPT ALL SELECT * FROM right_set ORDER BY id")?;
+    assert_eq!(result.collect()?.rows, [row(1, "A"), row(3, "C"), row(3, "C")]);
+
+    // Without ALL any row on the right removes every copy
+    let result = db.query("SELECT * FROM left_set EXCEPT SELECT * FROM right_set")?;
+    assert_eq!(result.collect()?.rows, [row(1, "A")]);
 
     Ok(())
 }
@@ -1071,4 +1213,1861 @@ fn test_cte_edge_cases() -> PrismDBResult<()> {
     println!("CTE edge cases test placeholder - parser support needed");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Tests for QueryResult table rendering: width capping, NULL display and alignment
+#[cfg(test)]
+mod table_format_tests {
+    use crate::common::database;
+    use prism::{Database, NumberFormat, PrismDBResult, TableFormatOptions};
+
+    fn plain_options() -> TableFormatOptions {
+        TableFormatOptions {
+            colors: false,
+            ..TableFormatOptions::default()
+        }
+    }
+
+    const SAMPLE: &[&str] = &[
+        "CREATE TABLE items (id INTEGER, label VARCHAR)",
+        "INSERT INTO items VALUES (7, 'abcdefghijklmnopqrstuvwxyz')",
+        "INSERT INTO items VALUES (12345, NULL)",
+        "INSERT INTO items VALUES (NULL, '')",
+    ];
+
+    #[test]
+    fn test_table_truncates_long_values() -> PrismDBResult<()> {
+        let db = database(SAMPLE)?;
+        let result = db.query("SELECT label FROM items")?;
+
+        let options = TableFormatOptions {
+            max_column_width: Some(10),
+            ..plain_options()
+        };
+        let table = result.to_table_string_with(&options);
+        assert!(table.contains("│ abcdefghi… │"));
+        assert!(!table.contains("abcdefghij"));
+
+        // Values are shown in full unless a cap is set
+        assert!(result.to_table_string().contains("abcdefghijklmnopqrstuvwxyz"));
+        let table = result.to_table_string_with(&plain_options());
+        assert!(table.contains("abcdefghijklmnopqrstuvwxyz"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_null_rendering() -> PrismDBResult<()> {
+        let db = database(SAMPLE)?;
+        let result = db.query("SELECT label FROM items")?;
+
+        let options = TableFormatOptions {
+            null_value: "∅".to_string(),
+            ..plain_options()
+        };
+        let table = result.to_table_string_with(&options);
+        let lines: Vec<&str> = table.lines().collect();
+
+        // Rows follow the top border, name, type and separator lines
+        assert!(lines[5].contains("│ ∅ "));
+        // The empty string stays blank rather than looking like NULL
+        assert!(!lines[6].contains('∅'));
+
+        // NULLs are dimmed when colors are on
+        let table = result.to_table_string();
+        assert!(table.contains("\x1b[2mNULL"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_right_aligns_numbers() -> PrismDBResult<()> {
+        let db = database(SAMPLE)?;
+        let result = db.query("SELECT id, label FROM items")?;
+
+        let options = TableFormatOptions {
+            max_column_width: Some(5),
+            ..plain_options()
+        };
+        let table = result.to_table_string_with(&options);
+        let lines: Vec<&str> = table.lines().collect();
+
+        // The label column is as wide as its "varchar" type name
+        assert_eq!(lines[4], "│     7 │ abcd…   │");
+        assert_eq!(lines[5], "│ 12345 │ NULL    │");
+        assert_eq!(lines[6], "│  NULL │         │");
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_number_format() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE totals (amount BIGINT, price DOUBLE, label VARCHAR)")?;
+        db.execute("INSERT INTO totals VALUES (1234567, 9876.5, '1234567'), (-999, -1234.125, 'x')")?;
+        let result = db.query("SELECT amount, price, label FROM totals")?;
+
+        let table = result.to_table_string_with(&plain_options());
+        assert!(table.contains("│ 1234567 │"));
+        assert!(!table.contains("1,234,567"));
+
+        let options = TableFormatOptions {
+            number_format: Some(NumberFormat::default()),
+            ..plain_options()
+        };
+        let table = result.to_table_string_with(&options);
+        let lines: Vec<&str> = table.lines().collect();
+        // Strings that look like numbers are left alone
+        assert_eq!(lines[4], "│ 1,234,567 │    9,876.5 │ 1234567 │");
+        assert_eq!(lines[5], "│      -999 │ -1,234.125 │ x       │");
+
+        let options = TableFormatOptions {
+            number_format: Some(NumberFormat {
+                decimal_places: Some(2),
+            }),
+            ..plain_options()
+        };
+        let table = result.to_table_string_with(&options);
+        assert!(table.contains("│ 1,234,567 │  9,876.50 │"));
+        Ok(())
+    }
+}
+
+/// Tests for the operator memory limit
+#[cfg(test)]
+mod memory_limit_tests {
+    use crate::common::insert_rows;
+    use prism::{Database, DatabaseConfig, PrismDBError, PrismDBResult};
+
+    /// A table of `rows` rows where every row is its own group
+    fn numbers_database(config: DatabaseConfig, rows: usize) -> PrismDBResult<Database> {
+        let mut db = Database::new(config)?;
+        db.execute("CREATE TABLE numbers (id INTEGER, label VARCHAR, amount DOUBLE)")?;
+        let values = (0..rows).map(|i| format!("({}, 'label-{}', {}.5)", i, i, i % 7));
+        insert_rows(&mut db, "numbers", values)?;
+        Ok(db)
+    }
+
+    const LARGE_AGGREGATE: &str =
+        "SELECT id, label, COUNT(*), SUM(amount), AVG(amount) FROM numbers GROUP BY id, label";
+
+    #[test]
+    fn test_memory_limit_set_statement() -> PrismDBResult<()> {
+        let mut db = numbers_database(DatabaseConfig::in_memory(), 1_000)?;
+
+        // Unlimited by default
+        assert_eq!(db.query(LARGE_AGGREGATE)?.row_count(), 1_000);
+
+        db.execute("SET memory_limit = '64KB'")?;
+        assert!(matches!(db.query(LARGE_AGGREGATE), Err(PrismDBError::OutOfMemory)));
+
+        // A small query still fits in the budget
+        let small = db.query("SELECT label, COUNT(*) FROM numbers WHERE id < 10 GROUP BY label")?;
+        assert_eq!(small.row_count(), 10);
+
+        // The budget is per query, so a failed query leaves nothing reserved
+        assert_eq!(
+            db.query("SELECT id FROM numbers WHERE id < 5 ORDER BY id DESC")?.row_count(),
+            5
+        );
+
+        db.execute("SET memory_limit = '1GB'")?;
+        assert_eq!(db.query(LARGE_AGGREGATE)?.row_count(), 1_000);
+
+        db.execute("SET memory_limit = '64KB'")?;
+        db.execute("SET memory_limit = DEFAULT")?;
+        assert_eq!(db.query(LARGE_AGGREGATE)?.row_count(), 1_000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_limit_config() -> PrismDBResult<()> {
+        let config = DatabaseConfig {
+            memory_limit: Some(64 * 1024),
+            ..DatabaseConfig::in_memory()
+        };
+        let db = numbers_database(config, 1_000)?;
+
+        assert!(matches!(db.query(LARGE_AGGREGATE), Err(PrismDBError::OutOfMemory)));
+        assert!(matches!(
+            db.query("SELECT * FROM numbers ORDER BY label"),
+            Err(PrismDBError::OutOfMemory)
+        ));
+        assert!(matches!(
+            db.query("SELECT * FROM numbers a JOIN numbers b ON a.id = b.id"),
+            Err(PrismDBError::OutOfMemory)
+        ));
+        assert!(matches!(
+            db.query("SELECT label FROM numbers UNION SELECT label FROM numbers"),
+            Err(PrismDBError::OutOfMemory)
+        ));
+
+        assert_eq!(db.query("SELECT COUNT(*) FROM numbers WHERE id < 100")?.row_count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_limit_invalid_value() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        assert!(matches!(
+            db.execute("SET memory_limit = 'lots'"),
+            Err(PrismDBError::InvalidArgument(_))
+        ));
+        assert!(db.execute("SET memory_limit = '12 parsecs'").is_err());
+        db.execute("SET memory_limit = '512 MiB'")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_usage_in_stats() -> PrismDBResult<()> {
+        let db = numbers_database(DatabaseConfig::in_memory(), 1_000)?;
+
+        // The aggregate fails under a 64KB limit, so it holds more than that
+        let result = db.query(LARGE_AGGREGATE)?;
+        let stats = result.stats();
+        assert!(stats.memory_used_bytes > 64 * 1024, "{}", stats.memory_used_bytes);
+        assert!(stats.memory_used_bytes < 64 * 1024 * 1024, "{}", stats.memory_used_bytes);
+        assert_eq!(stats.operator_memory.len(), 1);
+        let (operator, peak) = &stats.operator_memory[0];
+        assert_eq!(operator, "HASH_AGGREGATE");
+        assert!(*peak > 0 && *peak <= stats.memory_used_bytes);
+        assert_eq!(result.collect()?.stats.memory_used_bytes, stats.memory_used_bytes);
+
+        // Each blocking operator reports its own peak
+        let result = db.query("SELECT label, COUNT(*) FROM numbers GROUP BY label ORDER BY label")?;
+        let operators: Vec<&str> = result.stats().operator_memory.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(operators, ["ORDER_BY", "HASH_AGGREGATE"]);
+
+        // A plain scan buffers nothing
+        let result = db.query("SELECT id FROM numbers WHERE id < 5")?;
+        assert_eq!(result.stats().memory_used_bytes, 0);
+        assert!(result.stats().operator_memory.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_analyze_reports_memory() -> PrismDBResult<()> {
+        let db = numbers_database(DatabaseConfig::in_memory(), 100)?;
+
+        let plan = |sql: &str| -> PrismDBResult<String> {
+            Ok(db.query(sql)?.first_value().map(|value| value.to_string()).unwrap_or_default())
+        };
+
+        let analyzed = plan("EXPLAIN ANALYZE SELECT label, COUNT(*) FROM numbers GROUP BY label ORDER BY label")?;
+        let memory_lines: Vec<&str> = analyzed.lines().filter(|line| line.contains("peak_memory:")).collect();
+        assert_eq!(memory_lines.len(), 2, "{}", analyzed);
+        assert!(memory_lines.iter().all(|line| !line.ends_with(": 0 bytes")), "{}", analyzed);
+
+        let explained = plan("EXPLAIN SELECT label, COUNT(*) FROM numbers GROUP BY label ORDER BY label")?;
+        assert!(!explained.contains("peak_memory"), "{}", explained);
+        Ok(())
+    }
+}
+
+/// Tests for FILTER (WHERE ...) on aggregate functions and the COUNTIF shorthand
+#[cfg(test)]
+mod aggregate_filter_tests {
+    use crate::common::{database, rows};
+    use prism::{PrismDBResult, Value};
+
+    const ORDERS: &[&str] = &[
+        "CREATE TABLE orders (region VARCHAR, amount INTEGER, margin INTEGER, status VARCHAR)",
+        "INSERT INTO orders VALUES \
+     ('east', 100, 5, 'active'), \
+     ('east', 40, -2, 'closed'), \
+     ('east', 60, NULL, 'active'), \
+     ('west', 80, 3, 'closed'), \
+     ('west', 20, 0, 'active'), \
+     ('north', 10, -1, 'closed')",
+    ];
+
+    #[test]
+    fn test_filter_matches_case_equivalent() -> PrismDBResult<()> {
+        let db = database(ORDERS)?;
+
+        let filtered = rows(&db, "SELECT SUM(amount) FILTER (WHERE margin > 0) FROM orders")?;
+        let case = rows(&db, "SELECT SUM(CASE WHEN margin > 0 THEN amount END) FROM orders")?;
+        assert_eq!(filtered, case);
+        assert_eq!(filtered, vec![vec![Value::Double(180.0)]]);
+
+        // Filtered and unfiltered forms of the same aggregate are kept apart
+        let result = rows(
+            &db,
+            "SELECT SUM(amount) FILTER (WHERE margin > 0), \
+                SUM(CASE WHEN margin > 0 THEN amount END), \
+                SUM(amount), \
+                COUNT(*) FILTER (WHERE status = 'active'), \
+                COUNT(*) \
+         FROM orders",
+        )?;
+        assert_eq!(
+            result,
+            vec![vec![
+                Value::Double(180.0),
+                Value::Double(180.0),
+                Value::Double(310.0),
+                Value::BigInt(3),
+                Value::BigInt(6),
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_with_group_by() -> PrismDBResult<()> {
+        let db = database(ORDERS)?;
+
+        let result = rows(
+            &db,
+            "SELECT region, \
+                SUM(amount) FILTER (WHERE margin > 0), \
+                SUM(CASE WHEN margin > 0 THEN amount END), \
+                COUNT(*) FILTER (WHERE status = 'active') AS active \
+         FROM orders GROUP BY region ORDER BY region",
+        )?;
+        let region = |name: &str| Value::Varchar(name.to_string());
+        assert_eq!(
+            result,
+            vec![
+                vec![region("east"), Value::Double(100.0), Value::Double(100.0), Value::BigInt(2)],
+                // No row passes the filter: SUM is NULL and COUNT is 0
+                vec![region("north"), Value::Null, Value::Null, Value::BigInt(0)],
+                vec![region("west"), Value::Double(80.0), Value::Double(80.0), Value::BigInt(1)],
+            ]
+        );
+
+        // Filtered aggregates can be used in HAVING
+        let result = rows(
+            &db,
+            "SELECT region FROM orders GROUP BY region \
+         HAVING COUNT(*) FILTER (WHERE status = 'closed') > 0 ORDER BY region",
+        )?;
+        assert_eq!(result, vec![vec![region("east")], vec![region("north")], vec![region("west")]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_errors() -> PrismDBResult<()> {
+        let db = database(ORDERS)?;
+        assert!(db.query("SELECT UPPER(status) FILTER (WHERE margin > 0) FROM orders").is_err());
+        assert!(db.query("SELECT SUM(amount) FILTER (margin > 0) FROM orders").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_countif_matches_filtered_count() -> PrismDBResult<()> {
+        let db = database(ORDERS)?;
+
+        let result = db.query(
+            "SELECT COUNTIF(margin > 0), COUNT(*) FILTER (WHERE margin > 0), \
+                SUM(CASE WHEN margin > 0 THEN 1 ELSE 0 END), COUNT_IF(margin > 0) FILTER (WHERE amount > 50) \
+         FROM orders",
+        )?;
+        assert_eq!(result.columns[0].name, "countif(margin > 0)");
+        assert_eq!(
+            result.collect()?.rows,
+            vec![vec![Value::BigInt(2), Value::BigInt(2), Value::Double(2.0), Value::BigInt(2)]]
+        );
+
+        // NULL margins are not counted, and groups with no match count 0
+        let countif = rows(
+            &db,
+            "SELECT region, COUNTIF(margin >= 0) AS n FROM orders GROUP BY region \
+         HAVING COUNTIF(status = 'closed') > 0 ORDER BY region",
+        )?;
+        let filtered = rows(
+            &db,
+            "SELECT region, COUNT(*) FILTER (WHERE margin >= 0) AS n FROM orders GROUP BY region \
+         HAVING COUNT(*) FILTER (WHERE status = 'closed') > 0 ORDER BY region",
+        )?;
+        assert_eq!(countif, filtered);
+        let region = |name: &str| Value::Varchar(name.to_string());
+        assert_eq!(
+            countif,
+            vec![
+                vec![region("east"), Value::BigInt(1)],
+                vec![region("north"), Value::BigInt(0)],
+                vec![region("west"), Value::BigInt(2)],
+            ]
+        );
+
+        assert!(db.query("SELECT COUNTIF(margin > 0, amount > 0) FROM orders").is_err());
+        assert!(db.query("SELECT COUNTIF(DISTINCT margin > 0) FROM orders").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for ORDER BY inside aggregate calls and WITHIN GROUP ordered-set aggregates
+#[cfg(test)]
+mod ordered_aggregate_tests {
+    use crate::common::{database, rows, text};
+    use prism::{PrismDBResult, Value};
+
+    const STAFF: &[&str] = &[
+        "CREATE TABLE staff (dept VARCHAR, name VARCHAR, salary DOUBLE)",
+        "INSERT INTO staff VALUES \
+     ('eng', 'carol', 120.0), \
+     ('ops', 'erin', 70.0), \
+     ('eng', 'alice', 100.0), \
+     ('eng', 'dave', 90.0), \
+     ('ops', 'bob', 80.0), \
+     ('eng', 'bob', 110.0)",
+    ];
+
+    #[test]
+    fn test_ordered_string_agg_per_group() -> PrismDBResult<()> {
+        let db = database(STAFF)?;
+
+        let expected = vec![
+            vec![text("eng"), text("alice,bob,carol,dave")],
+            vec![text("ops"), text("bob,erin")],
+        ];
+        // The result must not depend on insertion order or how rows are split up
+        for _ in 0..3 {
+            let result = rows(
+                &db,
+                "SELECT dept, STRING_AGG(name, ',' ORDER BY name) FROM staff GROUP BY dept ORDER BY dept",
+            )?;
+            assert_eq!(result, expected);
+        }
+
+        let result = rows(
+            &db,
+            "SELECT dept, STRING_AGG(name, ' > ' ORDER BY salary DESC) AS ranking \
+         FROM staff GROUP BY dept ORDER BY dept",
+        )?;
+        assert_eq!(
+            result,
+            vec![
+                vec![text("eng"), text("carol > bob > alice > dave")],
+                vec![text("ops"), text("bob > erin")],
+            ]
+        );
+
+        // Ordering by several keys; different orderings are different aggregates
+        let result = rows(
+            &db,
+            "SELECT STRING_AGG(dept, ',' ORDER BY name, salary DESC), \
+         STRING_AGG(dept, ',' ORDER BY salary) FROM staff",
+        )?;
+        assert_eq!(
+            result,
+            vec![vec![text("eng,eng,ops,eng,eng,ops"), text("ops,ops,eng,eng,eng,eng")]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_array_agg() -> PrismDBResult<()> {
+        let db = database(STAFF)?;
+        let list = |names: &[&str]| Value::List(names.iter().map(|name| text(name)).collect());
+
+        // The result must not depend on insertion order or how rows are split up
+        for _ in 0..3 {
+            let result = rows(
+                &db,
+                "SELECT dept, ARRAY_AGG(name ORDER BY salary DESC) FROM staff GROUP BY dept ORDER BY dept",
+            )?;
+            assert_eq!(
+                result,
+                vec![
+                    vec![text("eng"), list(&["carol", "bob", "alice", "dave"])],
+                    vec![text("ops"), list(&["bob", "erin"])],
+                ]
+            );
+        }
+
+        // LIST is the same aggregate; NULLs are kept in their place
+        let result = rows(
+            &db,
+            "SELECT LIST(CASE WHEN salary > 100 THEN name END ORDER BY salary) FROM staff WHERE dept = 'eng'",
+        )?;
+        assert_eq!(
+            result,
+            vec![vec![Value::List(vec![Value::Null, Value::Null, text("bob"), text("carol")])]]
+        );
+
+        let result = rows(
+            &db,
+            "SELECT ARRAY_AGG(DISTINCT dept ORDER BY dept DESC), ARRAY_AGG(salary ORDER BY salary)[1] FROM staff",
+        )?;
+        assert_eq!(result, vec![vec![list(&["ops", "eng"]), Value::Double(70.0)]]);
+
+        // No rows aggregate to NULL
+        assert_eq!(
+            rows(&db, "SELECT ARRAY_AGG(name ORDER BY name) FROM staff WHERE dept = 'hr'")?,
+            vec![vec![Value::Null]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_within_group_percentiles() -> PrismDBResult<()> {
+        let db = database(STAFF)?;
+
+        let result = rows(
+            &db,
+            "SELECT dept, PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY salary), \
+         PERCENTILE_DISC(0.5) WITHIN GROUP (ORDER BY salary) \
+         FROM staff GROUP BY dept ORDER BY dept",
+        )?;
+        assert_eq!(
+            result,
+            vec![
+                vec![text("eng"), Value::Double(105.0), Value::Double(110.0)],
+                vec![text("ops"), Value::Double(75.0), Value::Double(80.0)],
+            ]
+        );
+
+        let result = rows(
+            &db,
+            "SELECT PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY salary) FROM staff WHERE dept = 'eng'",
+        )?;
+        assert_eq!(result, vec![vec![Value::Double(97.5)]]);
+
+        // Empty input aggregates to NULL
+        let result = rows(
+            &db,
+            "SELECT PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY salary) FROM staff WHERE dept = 'hr'",
+        )?;
+        assert_eq!(result, vec![vec![Value::Null]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_aggregate_errors() -> PrismDBResult<()> {
+        let db = database(STAFF)?;
+
+        for sql in [
+            "SELECT SUM(salary) WITHIN GROUP (ORDER BY salary) FROM staff",
+            "SELECT PERCENTILE_CONT(1.5) WITHIN GROUP (ORDER BY salary) FROM staff",
+            "SELECT STRING_AGG(name, name ORDER BY name) FROM staff",
+            "SELECT STRING_AGG(name, ',' ORDER BY name) WITHIN GROUP (ORDER BY name) FROM staff",
+        ] {
+            assert!(db.query(sql).is_err(), "{}", sql);
+        }
+        Ok(())
+    }
+}
+
+/// Tests for VALUES as a standalone statement and as a table source
+#[cfg(test)]
+mod values_tests {
+    use crate::common::{rows, text};
+    use prism::{Database, PrismDBResult, Value};
+
+    #[test]
+    fn test_standalone_values() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+
+        let result = db.query("VALUES (1, 'a'), (2, 'b'), (3, NULL)")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["col0", "col1"]);
+        assert_eq!(
+            result.collect()?.rows,
+            vec![
+                vec![Value::BigInt(1), text("a")],
+                vec![Value::BigInt(2), text("b")],
+                vec![Value::BigInt(3), Value::Null],
+            ]
+        );
+
+        // Column types are unified across rows
+        assert_eq!(
+            rows(&db, "VALUES (1), (2.5), (NULL)")?,
+            vec![vec![Value::Double(1.0)], vec![Value::Double(2.5)], vec![Value::Null]]
+        );
+
+        assert!(db.query("VALUES (1, 2), (3)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_values_as_table_source() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+
+        let result = db.query("SELECT * FROM (VALUES (1, 'one'), (2, 'two')) AS t(id, label)")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "label"]);
+
+        assert_eq!(
+            rows(
+                &db,
+                "SELECT t.label, id * 10 FROM (VALUES (1, 'one'), (2, 'two'), (3, 'three')) AS t(id, label) \
+             WHERE id > 1 ORDER BY id DESC",
+            )?,
+            vec![vec![text("three"), Value::BigInt(30)], vec![text("two"), Value::BigInt(20)]]
+        );
+
+        // A VALUES list works as a lookup table in joins
+        db.execute("CREATE TABLE orders (id INTEGER, status VARCHAR)")?;
+        db.execute("INSERT INTO orders VALUES (10, 'S'), (11, 'P'), (12, 'S')")?;
+        assert_eq!(
+            rows(
+                &db,
+                "SELECT o.id, s.name FROM orders o \
+             JOIN (VALUES ('S', 'shipped'), ('P', 'pending')) s(code, name) ON o.status = s.code \
+             ORDER BY o.id",
+            )?,
+            vec![
+                vec![Value::Integer(10), text("shipped")],
+                vec![Value::Integer(11), text("pending")],
+                vec![Value::Integer(12), text("shipped")],
+            ]
+        );
+
+        // Unaliased columns keep their default names
+        assert_eq!(
+            rows(&db, "SELECT col1 FROM (VALUES (1, true)) v(a)")?,
+            vec![vec![Value::Boolean(true)]]
+        );
+        assert!(db.query("SELECT * FROM (VALUES (1)) v(a, b)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_values_column_types_are_unified() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+
+        // The promoted type wins regardless of which row comes first
+        let result = db.query("VALUES (2.5, 'a'), (1, NULL)")?;
+        let types: Vec<String> = result.columns.iter().map(|c| format!("{:?}", c.data_type)).collect();
+        assert_eq!(types, vec!["Double", "Varchar"]);
+        assert_eq!(
+            result.collect()?.rows,
+            vec![vec![Value::Double(2.5), text("a")], vec![Value::Double(1.0), Value::Null]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT x + 1 FROM (VALUES (1), (2.5)) t(x) ORDER BY x")?,
+            vec![vec![Value::Double(2.0)], vec![Value::Double(3.5)]]
+        );
+
+        // NULLs are ignored for inference, and an all-NULL column is INTEGER
+        let result = db.query("VALUES (NULL, 'a'), (NULL, NULL)")?;
+        assert_eq!(format!("{:?}", result.columns[0].data_type), "Integer");
+        assert_eq!(format!("{:?}", result.columns[1].data_type), "Varchar");
+        assert_eq!(result.collect()?.rows[1], vec![Value::Null, Value::Null]);
+
+        db.execute("CREATE TABLE readings (v DOUBLE, note VARCHAR)")?;
+        db.execute("INSERT INTO readings VALUES (1, NULL), (2.5, 'b'), (NULL, 'c')")?;
+        assert_eq!(
+            rows(&db, "SELECT v, note FROM readings")?,
+            vec![
+                vec![Value::Double(1.0), Value::Null],
+                vec![Value::Double(2.5), text("b")],
+                vec![Value::Null, text("c")],
+            ]
+        );
+
+        let error = db.query("VALUES (1), ('a')").unwrap_err().to_string();
+        assert!(error.contains("no common type"), "unexpected error: {}", error);
+        Ok(())
+    }
+}
+
+/// Tests for serializing values and query results to JSON
+#[cfg(test)]
+mod json_output_tests {
+    use prism::{Database, LogicalType, PrismDBResult, Value};
+    use serde_json::json;
+
+    fn varchar(text: &str) -> Value {
+        Value::Varchar(text.to_string())
+    }
+
+    #[test]
+    fn test_list_of_struct_round_trip() -> PrismDBResult<()> {
+        let point = |x: i32, label: Option<&str>| {
+            Value::Struct(vec![
+                ("x".to_string(), Value::Integer(x)),
+                ("label".to_string(), label.map_or(Value::Null, varchar)),
+            ])
+        };
+        let value = Value::List(vec![point(1, Some("a")), point(2, None), Value::Null]);
+        let json = value.to_json();
+        assert_eq!(json, json!([{"x": 1, "label": "a"}, {"x": 2, "label": null}, null]));
+        // Struct fields keep their order
+        assert_eq!(json.to_string(), r#"[{"x":1,"label":"a"},{"x":2,"label":null},null]"#);
+
+        let point_type = LogicalType::Struct(vec![
+            ("x".to_string(), LogicalType::Integer),
+            ("label".to_string(), LogicalType::Varchar),
+        ]);
+        let list_type = LogicalType::List(Box::new(point_type));
+        assert_eq!(Value::from_json(&json, &list_type)?, value);
+
+        // Missing fields read back as NULL, mismatched shapes are errors
+        assert_eq!(Value::from_json(&json!([{"x": 3}]), &list_type)?, Value::List(vec![point(3, None)]));
+        assert!(Value::from_json(&json!({"x": 3}), &list_type).is_err());
+        assert!(Value::from_json(&json!([[3]]), &list_type).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_round_trip() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let value = db.query("SELECT MAP {2: [1.5, 2.5], 1: [0.5]}")?.collect()?.rows[0][0].clone();
+        let json = value.to_json();
+        // Keys become their text, in entry order
+        assert_eq!(json.to_string(), r#"{"2":[1.5,2.5],"1":[0.5]}"#);
+
+        let map_type = LogicalType::Map {
+            key_type: Box::new(LogicalType::Integer),
+            value_type: Box::new(LogicalType::List(Box::new(LogicalType::Double))),
+        };
+        assert_eq!(Value::from_json(&json, &map_type)?.to_json(), json);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scalar_json_forms() -> PrismDBResult<()> {
+        assert_eq!(Value::Null.to_json(), json!(null));
+        assert_eq!(Value::Boolean(true).to_json(), json!(true));
+        assert_eq!(Value::BigInt(-7).to_json(), json!(-7));
+        assert_eq!(Value::Double(0.25).to_json(), json!(0.25));
+        assert_eq!(Value::Double(f64::NAN).to_json(), json!("NaN"));
+        assert_eq!(Value::Date(19723).to_json(), json!("2024-01-01"));
+        assert_eq!(Value::JSON(r#"{"a": [1]}"#.to_string()).to_json(), json!({"a": [1]}));
+        assert_eq!(Value::Blob(vec![0xab, 0x01]).to_json(), json!("AB01"));
+
+        assert_eq!(Value::from_json(&json!("2024-01-01"), &LogicalType::Date)?, Value::Date(19723));
+        assert_eq!(Value::from_json(&json!(5), &LogicalType::Integer)?, Value::Integer(5));
+        assert_eq!(Value::from_json(&json!("AB01"), &LogicalType::Blob)?, Value::Blob(vec![0xab, 0x01]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_result_to_json() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE tags (id INTEGER, name VARCHAR, attrs MAP(VARCHAR, INTEGER))")?;
+        db.execute("INSERT INTO tags VALUES (1, 'a', MAP {'w': 3}), (2, NULL, NULL)")?;
+        let json = db.query("SELECT name, id, attrs FROM tags ORDER BY id")?.to_json()?;
+        assert_eq!(
+            json.to_string(),
+            r#"[{"name":"a","id":1,"attrs":{"w":3}},{"name":null,"id":2,"attrs":null}]"#
+        );
+        Ok(())
+    }
+}
+
+/// Tests for switching COUNT(DISTINCT) to HyperLogLog above a distinct-count threshold
+#[cfg(test)]
+mod approx_count_distinct_tests {
+    use crate::common::{database, explain, insert_rows};
+    use prism::{Database, PrismDBResult};
+
+    /// 20,000 events over 10,000 users and 3 kinds
+    fn events_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE events (user_id INTEGER, kind VARCHAR)"])?;
+        for batch in 0..2 {
+            let values = (batch * 10_000..(batch + 1) * 10_000)
+                .map(|i| format!("({}, 'k{}')", i % 10_000, i % 3));
+            insert_rows(&mut db, "events", values)?;
+        }
+        Ok(db)
+    }
+
+    fn count(db: &Database, sql: &str) -> PrismDBResult<i64> {
+        db.query(sql)?.collect()?.rows[0][0].try_as_i64()
+    }
+
+    #[test]
+    fn test_high_cardinality_count_distinct_uses_hll() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        let users = "SELECT COUNT(DISTINCT user_id) FROM events";
+        let kinds = "SELECT COUNT(DISTINCT kind) FROM events";
+        let users_by_kind = "SELECT kind, COUNT(DISTINCT user_id) FROM events GROUP BY kind ORDER BY kind";
+        assert!(explain(&db, users)?.contains("COUNT(DISTINCT "));
+        assert_eq!(count(&db, users)?, 10_000);
+        let exact_by_kind = db.query(users_by_kind)?.collect()?.rows;
+
+        db.execute("SET approx_count_distinct_threshold = 5000")?;
+        db.execute("ANALYZE events")?;
+        assert!(explain(&db, users)?.contains("APPROX_COUNT_DISTINCT("));
+        let estimate = count(&db, users)?;
+        assert!((estimate - 10_000).abs() < 300, "estimate {} is off by more than 3%", estimate);
+
+        // ANALYZE found few kinds, so they are still counted exactly
+        assert!(!explain(&db, kinds)?.contains("APPROX_COUNT_DISTINCT("));
+        assert_eq!(count(&db, kinds)?, 3);
+
+        // Grouped counts switch too, and the output column keeps its name
+        let result = db.query(users_by_kind)?;
+        assert_eq!(result.columns[1].name, "COUNT(DISTINCT user_id)");
+        for (row, exact) in result.collect()?.rows.iter().zip(&exact_by_kind) {
+            let (estimate, exact) = (row[1].try_as_i64()?, exact[1].try_as_i64()?);
+            assert!((estimate - exact).abs() * 100 < exact * 3, "estimate {} of {} is off by more than 3%", estimate, exact);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_threshold_without_statistics() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        db.execute("SET approx_count_distinct_threshold = 50000")?;
+        let users = "SELECT COUNT(DISTINCT user_id) FROM events";
+        // Without ANALYZE the row count bounds the distinct count
+        assert!(!explain(&db, users)?.contains("APPROX_COUNT_DISTINCT("));
+
+        db.execute("SET approx_count_distinct_threshold = 15000")?;
+        assert!(explain(&db, users)?.contains("APPROX_COUNT_DISTINCT("));
+        // ANALYZE lowers the estimate below the threshold
+        db.execute("ANALYZE")?;
+        assert!(!explain(&db, users)?.contains("APPROX_COUNT_DISTINCT("));
+        assert_eq!(count(&db, users)?, 10_000);
+
+        db.execute("SET approx_count_distinct_threshold = 0")?;
+        assert!(db.execute("ANALYZE missing").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for LIMIT and OFFSET windows
+#[cfg(test)]
+mod limit_offset_tests {
+    use crate::common::{database, insert_rows};
+    use prism::{Database, PrismDBResult, Value};
+
+    /// Table holding ids 1..=20
+    fn numbers_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE numbers (id INTEGER)"])?;
+        insert_rows(&mut db, "numbers", (1..=20).map(|i| format!("({})", i)))?;
+        Ok(db)
+    }
+
+    fn ids(db: &Database, sql: &str) -> PrismDBResult<Vec<i64>> {
+        db.query(sql)?.collect()?.rows.iter().map(|row| row[0].try_as_i64()).collect()
+    }
+
+    #[test]
+    fn test_offset_without_limit() -> PrismDBResult<()> {
+        let db = numbers_database()?;
+        assert_eq!(ids(&db, "SELECT id FROM numbers ORDER BY id OFFSET 5")?, (6..=20).collect::<Vec<_>>());
+        // Without ORDER BY the offset still applies to the scan order
+        assert_eq!(ids(&db, "SELECT id FROM numbers OFFSET 15")?, (16..=20).collect::<Vec<_>>());
+        assert!(ids(&db, "SELECT id FROM numbers OFFSET 20")?.is_empty());
+        assert_eq!(ids(&db, "SELECT id FROM numbers WHERE id % 2 = 0 ORDER BY id DESC OFFSET 7")?, vec![6, 4, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_limit_with_offset() -> PrismDBResult<()> {
+        let db = numbers_database()?;
+        assert_eq!(ids(&db, "SELECT id FROM numbers ORDER BY id LIMIT 10 OFFSET 5")?, (6..=15).collect::<Vec<_>>());
+        assert_eq!(ids(&db, "SELECT id FROM numbers LIMIT 10 OFFSET 5")?, (6..=15).collect::<Vec<_>>());
+        // The window is cut short at the end of the input
+        assert_eq!(ids(&db, "SELECT id FROM numbers ORDER BY id LIMIT 10 OFFSET 15")?, (16..=20).collect::<Vec<_>>());
+        assert_eq!(ids(&db, "SELECT id FROM numbers ORDER BY id LIMIT 3")?, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_offset_after_unnest() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        let rows = db.query("SELECT UNNEST([1, 2, 3, 4]) OFFSET 1")?.collect()?.rows;
+        assert_eq!(rows, [[Value::BigInt(2)], [Value::BigInt(3)], [Value::BigInt(4)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_offset_without_limit() -> PrismDBResult<()> {
+        let db = numbers_database()?;
+        let plan = match &db.query("EXPLAIN SELECT id FROM numbers OFFSET 5")?.collect()?.rows[0][0] {
+            Value::Varchar(plan) => plan.clone(),
+            other => panic!("expected a plan string, got {:?}", other),
+        };
+        assert!(plan.contains("LIMIT (~15 rows)"), "{}", plan);
+        assert!(plan.contains("limit: ALL"), "{}", plan);
+        assert!(plan.contains("offset: 5"), "{}", plan);
+        Ok(())
+    }
+}
+
+/// Tests for the engine's canonical total order of values
+#[cfg(test)]
+mod value_ordering_tests {
+    use crate::common::{database, rows};
+    use prism::{Database, PrismDBResult, Value};
+
+    /// Doubles including NaN, both zeros, the infinities and NULL
+    const MEASUREMENTS: &[&str] = &[
+        "CREATE TABLE m (x DOUBLE, k INTEGER)",
+        "INSERT INTO m VALUES (CAST('NaN' AS DOUBLE), 1), (1.5, 1), (CAST('-inf' AS DOUBLE), 2), \
+     (0.0, 2), (-0.0, 3), (NULL, 3), (CAST('inf' AS DOUBLE), 1)",
+    ];
+
+    fn column(db: &Database, sql: &str) -> PrismDBResult<Vec<Value>> {
+        Ok(rows(db, sql)?.into_iter().map(|mut row| row.remove(0)).collect())
+    }
+
+    #[test]
+    fn test_sort_and_min_max_with_nan() -> PrismDBResult<()> {
+        let db = database(MEASUREMENTS)?;
+        let sorted = column(&db, "SELECT x FROM m ORDER BY x")?;
+        let expected = [f64::NEG_INFINITY, 0.0, 0.0, 1.5, f64::INFINITY];
+        for (value, expected) in sorted.iter().zip(expected) {
+            assert_eq!(value, &Value::Double(expected));
+        }
+        // NaN sorts above every number, NULL after everything
+        assert!(matches!(sorted[5], Value::Double(nan) if nan.is_nan()));
+        assert_eq!(sorted[6], Value::Null);
+
+        let extremes = &rows(&db, "SELECT MIN(x), MAX(x) FROM m")?[0];
+        assert_eq!(extremes[0], Value::Double(f64::NEG_INFINITY));
+        assert!(matches!(extremes[1], Value::Double(nan) if nan.is_nan()));
+
+        let maxima = column(&db, "SELECT MAX(x) FROM m WHERE k > 1 GROUP BY k ORDER BY k")?;
+        assert_eq!(maxima, [Value::Double(0.0), Value::Double(-0.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_grouping_and_set_operations_use_value_equality() -> PrismDBResult<()> {
+        let mut db = database(MEASUREMENTS)?;
+        // The two zeros are one group, and NaN is equal to itself
+        let counts = rows(&db, "SELECT x, COUNT(*) FROM m GROUP BY x ORDER BY x")?;
+        assert_eq!(counts.len(), 6);
+        assert_eq!(counts[1], [Value::Double(0.0), Value::BigInt(2)]);
+        assert_eq!(column(&db, "SELECT x FROM m UNION SELECT x FROM m")?.len(), 6);
+
+        // Numbers of different types are equal when their values are
+        assert_eq!(rows(&db, "SELECT 1 UNION SELECT CAST(1 AS BIGINT)")?.len(), 1);
+        assert_eq!(rows(&db, "SELECT 1 UNION SELECT 1.0")?.len(), 1);
+        assert_eq!(rows(&db, "SELECT 2 INTERSECT SELECT CAST(2 AS DOUBLE)")?.len(), 1);
+        assert!(rows(&db, "SELECT 2 EXCEPT SELECT CAST(2 AS BIGINT)")?.is_empty());
+
+        // GROUP BY agrees with DISTINCT over mixed numeric types
+        db.execute("CREATE TABLE wide (k BIGINT)")?;
+        db.execute("INSERT INTO wide VALUES (1), (3)")?;
+        let mixed = "SELECT k FROM m UNION ALL SELECT k FROM wide";
+        let counts = column(&db, &format!("SELECT COUNT(*) FROM ({}) AS t GROUP BY k ORDER BY k", mixed))?;
+        assert_eq!(counts, [Value::BigInt(4), Value::BigInt(2), Value::BigInt(3)]);
+        let distinct = rows(&db, &format!("SELECT DISTINCT k FROM ({}) AS t", mixed))?;
+        assert_eq!(distinct.len(), counts.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mode_returns_the_value() -> PrismDBResult<()> {
+        let db = database(MEASUREMENTS)?;
+        assert_eq!(column(&db, "SELECT MODE(k) FROM m")?, [Value::Integer(1)]);
+        Ok(())
+    }
+}
+
+/// Tests for SELECT without FROM, which reads a single row with no columns
+#[cfg(test)]
+mod select_without_from_tests {
+    use crate::common::database;
+    use prism::{PrismDBResult, Value};
+
+    const T: &[&str] = &[
+        "CREATE TABLE t (x INTEGER)",
+        "INSERT INTO t VALUES (1), (2), (3)",
+    ];
+
+    #[test]
+    fn test_expressions_functions_and_subqueries() -> PrismDBResult<()> {
+        let db = database(T)?;
+
+        let result = db.query("SELECT 1 + 1 AS x, 'hi' AS y")?;
+        assert_eq!(result.columns[0].name, "x");
+        assert_eq!(result.collect()?.rows, [[Value::Integer(2), Value::Varchar("hi".to_string())]]);
+
+        let rows = db.query("SELECT 1+1, now(), (SELECT COUNT(*) FROM t)")?.collect()?.rows;
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], Value::Integer(2));
+        assert!(matches!(rows[0][1], Value::Timestamp(_)), "{:?}", rows[0][1]);
+        assert_eq!(rows[0][2], Value::BigInt(3));
+
+        assert_eq!(
+            db.query("SELECT upper('a'), random() < 1, current_date() IS NOT NULL")?.collect()?.rows,
+            [[Value::Varchar("A".to_string()), Value::Boolean(true), Value::Boolean(true)]]
+        );
+        assert!(db.query("SELECT now(1)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_clauses_keep_the_single_row() -> PrismDBResult<()> {
+        let db = database(T)?;
+        for sql in [
+            "SELECT 1",
+            "SELECT 1 ORDER BY 1",
+            "SELECT 1 AS a ORDER BY a LIMIT 5",
+            "SELECT DISTINCT 1",
+            "SELECT 1 WHERE 1 = 1",
+            "SELECT 1 HAVING COUNT(*) = 1",
+        ] {
+            assert_eq!(db.query(sql)?.collect()?.rows, [[Value::Integer(1)]], "{}", sql);
+        }
+        assert_eq!(db.query("SELECT COUNT(*)")?.collect()?.rows, [[Value::BigInt(1)]]);
+
+        for sql in ["SELECT 1 WHERE 1 = 0", "SELECT 1 LIMIT 0", "SELECT 1 OFFSET 1"] {
+            assert!(db.query(sql)?.collect()?.rows.is_empty(), "{}", sql);
+        }
+        Ok(())
+    }
+}
+
+/// Tests for parse errors pointing at the offending token
+#[cfg(test)]
+mod parse_error_tests {
+    use prism::{Database, DatabaseConfig, ParseErrorMode, PrismDBError, PrismDBResult};
+
+    fn parse_error(db: &Database, sql: &str) -> String {
+        match db.query(sql) {
+            Err(PrismDBError::Parse(message)) => message,
+            Err(other) => panic!("expected a parse error for {:?}, got {}", sql, other),
+            Ok(_) => panic!("expected a parse error for {:?}", sql),
+        }
+    }
+
+    #[test]
+    fn test_error_reports_position_and_snippet() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+
+        assert_eq!(
+            parse_error(&db, "SELECT 1 2"),
+            "Unexpected token: '2' at line 1, column 10\n  SELECT 1 2\n           ^"
+        );
+
+        // The snippet is the line holding the token, even in a multi-line statement
+        let error = parse_error(&db, "SELECT *\nFROM t\nWHERE x = = 1");
+        assert_eq!(
+            error,
+            "Unexpected token in expression: '=' at line 3, column 11\n  WHERE x = = 1\n            ^"
+        );
+
+        // Running out of input points just past the last token
+        let error = parse_error(&db, "SELECT a FROM");
+        assert!(error.starts_with("Expected identifier, got: end of input at line 1, column 14"), "{}", error);
+        assert!(error.ends_with("\n  SELECT a FROM\n               ^"), "{}", error);
+
+        // Tabs are kept in the caret line so it stays aligned
+        let error = parse_error(&db, "SELECT x\n\tFROM t t2 t3");
+        assert!(error.ends_with("\n  \tFROM t t2 t3\n  \t          ^"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_mode() -> PrismDBResult<()> {
+        let db = |parse_error_mode| {
+            Database::new(DatabaseConfig {
+                parse_error_mode,
+                ..DatabaseConfig::in_memory()
+            })
+        };
+
+        assert_eq!(
+            parse_error(&db(ParseErrorMode::Position)?, "SELECT 1 +* 2"),
+            "Unexpected token in expression: '*' at line 1, column 11"
+        );
+        assert_eq!(
+            parse_error(&db(ParseErrorMode::Message)?, "SELECT 1 +* 2"),
+            "Unexpected token in expression: '*'"
+        );
+
+        // Batches name the failing statement before the location
+        let mut batch = db(ParseErrorMode::Position)?;
+        let error = batch.execute("SELECT 1; SELECT (1").unwrap_err().to_string();
+        assert!(error.contains("at line 1, column 20"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_chained_comparison() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE t (a INTEGER, b INTEGER, c INTEGER)")?;
+        db.execute("INSERT INTO t VALUES (1, 2, 3)")?;
+
+        let error = parse_error(&db, "SELECT * FROM t WHERE a < b < c");
+        assert!(
+            error.starts_with("Chained comparison at '<': write 'a < b AND b < c'"),
+            "{}",
+            error
+        );
+        assert!(error.contains("at line 1, column 29"), "{}", error);
+        assert!(parse_error(&db, "SELECT a = b = c FROM t").starts_with("Chained comparison at '='"));
+
+        // Explicit AND and parenthesized comparisons still parse
+        assert_eq!(db.query("SELECT COUNT(*) FROM t WHERE a < b AND b < c")?.collect()?.rows.len(), 1);
+        db.query("SELECT (a < b) = (b < c), a < b IS NOT NULL FROM t")?;
+        Ok(())
+    }
+}
+
+/// Tests for GROUP BY ALL and ORDER BY ALL
+#[cfg(test)]
+mod group_by_all_tests {
+    use crate::common::database;
+    use prism::{PrismDBResult, Value};
+
+    const ORDERS: &[&str] = &[
+        "CREATE TABLE orders (region VARCHAR, product VARCHAR, amount INTEGER)",
+        "INSERT INTO orders VALUES \
+     ('west', 'pen', 3), ('east', 'ink', 5), ('west', 'pen', 4), \
+     ('east', 'pen', 1), ('west', 'ink', 2), ('east', 'ink', 6)",
+    ];
+
+    #[test]
+    fn test_group_by_all_matches_explicit_grouping() -> PrismDBResult<()> {
+        let db = database(ORDERS)?;
+        let all = db
+            .query("SELECT region, product, SUM(amount) AS total FROM orders GROUP BY ALL ORDER BY region, product")?
+            .collect()?;
+        let explicit = db
+            .query("SELECT region, product, SUM(amount) AS total FROM orders GROUP BY region, product ORDER BY region, product")?
+            .collect()?;
+        assert_eq!(all.rows.len(), 4);
+        assert_eq!(all.rows, explicit.rows);
+
+        // Aggregates nested in a larger expression are left out too
+        let all = db
+            .query("SELECT region, COUNT(*) + 1 FROM orders GROUP BY ALL ORDER BY region")?
+            .collect()?;
+        let explicit = db
+            .query("SELECT region, COUNT(*) + 1 FROM orders GROUP BY region ORDER BY region")?
+            .collect()?;
+        assert_eq!(all.rows, explicit.rows);
+        Ok(())
+    }
+
+    #[test]
+    fn test_order_by_all_matches_listing_every_column() -> PrismDBResult<()> {
+        let db = database(ORDERS)?;
+        let all = db.query("SELECT product, region, amount FROM orders ORDER BY ALL")?.collect()?;
+        let explicit = db
+            .query("SELECT product, region, amount FROM orders ORDER BY product, region, amount")?
+            .collect()?;
+        assert_eq!(all.rows, explicit.rows);
+
+        // A direction applies to every column; * and aliases stand for their columns
+        let all = db.query("SELECT * FROM orders ORDER BY ALL DESC")?.collect()?;
+        let explicit = db
+            .query("SELECT * FROM orders ORDER BY region DESC, product DESC, amount DESC")?
+            .collect()?;
+        assert_eq!(all.rows, explicit.rows);
+
+        let all = db
+            .query("SELECT region AS r, SUM(amount) AS total FROM orders GROUP BY ALL ORDER BY ALL")?
+            .collect()?;
+        let explicit = db
+            .query("SELECT region AS r, SUM(amount) AS total FROM orders GROUP BY region ORDER BY r, total")?
+            .collect()?;
+        assert_eq!(all.rows, explicit.rows);
+
+        // Unaliased aggregates sort by their output column
+        let all = db
+            .query("SELECT product, SUM(amount), COUNT(*) FROM orders GROUP BY ALL ORDER BY ALL DESC")?
+            .collect()?;
+        assert_eq!(
+            all.rows,
+            vec![
+                vec![Value::Varchar("pen".to_string()), Value::Double(8.0), Value::BigInt(3)],
+                vec![Value::Varchar("ink".to_string()), Value::Double(13.0), Value::BigInt(3)],
+            ]
+        );
+
+        // With DISTINCT and LIMIT, the sort comes first
+        let all = db.query("SELECT DISTINCT amount % 2 FROM orders ORDER BY ALL LIMIT 1")?.collect()?;
+        assert_eq!(all.rows, vec![vec![Value::Integer(0)]]);
+
+        // After a set operation it sorts the combined rows
+        let all = db.query("SELECT region FROM orders UNION SELECT 'central' ORDER BY ALL")?.collect()?;
+        let regions: Vec<_> = ["central", "east", "west"].map(|r| vec![Value::Varchar(r.to_string())]).into();
+        assert_eq!(all.rows, regions);
+        Ok(())
+    }
+}
+
+/// Tests for GROUP BY and DISTINCT over LIST and MAP values
+#[cfg(test)]
+mod nested_grouping_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    fn integers(values: &[i32]) -> Value {
+        Value::List(values.iter().map(|&v| Value::Integer(v)).collect())
+    }
+
+    #[test]
+    fn test_distinct_list_column() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE paths (id INTEGER, steps INTEGER[])")?;
+        db.execute(
+            "INSERT INTO paths VALUES (1, [1, 2]), (2, [2, 1]), (3, [1, 2]), \
+         (4, [1, 2, 3]), (5, [3]), (6, [3]), (7, [1, NULL]), (8, [1, NULL])",
+        )?;
+
+        // Lists are equal when their elements are, in order
+        let rows = db.query("SELECT DISTINCT steps FROM paths ORDER BY steps")?.collect()?.rows;
+        assert_eq!(
+            rows,
+            [
+                [integers(&[1, 2])],
+                [integers(&[1, 2, 3])],
+                [Value::List(vec![Value::Integer(1), Value::Null])],
+                [integers(&[2, 1])],
+                [integers(&[3])],
+            ]
+        );
+
+        // LIMIT counts distinct rows
+        let rows = db.query("SELECT DISTINCT steps FROM paths ORDER BY steps LIMIT 2")?.collect()?.rows;
+        assert_eq!(rows, [[integers(&[1, 2])], [integers(&[1, 2, 3])]]);
+
+        let rows = db
+            .query("SELECT steps, COUNT(*) FROM paths GROUP BY steps ORDER BY steps")?
+            .collect()?
+            .rows;
+        let counts: Vec<Value> = rows.iter().map(|row| row[1].clone()).collect();
+        assert_eq!(counts, [2, 1, 2, 1, 2].map(Value::BigInt));
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_map_ignores_entry_order() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE configs (id INTEGER, options MAP(VARCHAR, INTEGER))")?;
+        db.execute(
+            "INSERT INTO configs VALUES \
+         (1, MAP {'width': 80, 'height': 24}), (2, MAP {'height': 24, 'width': 80}), \
+         (3, MAP {'width': 24, 'height': 80}), (4, MAP {'width': 80})",
+        )?;
+
+        let rows = db
+            .query("SELECT options, COUNT(*) AS n, MIN(id) AS first FROM configs GROUP BY options ORDER BY first")?
+            .collect()?
+            .rows;
+        let counts: Vec<(Value, Value)> = rows.iter().map(|row| (row[1].clone(), row[2].clone())).collect();
+        assert_eq!(
+            counts,
+            [
+                (Value::BigInt(2), Value::Integer(1)),
+                (Value::BigInt(1), Value::Integer(3)),
+                (Value::BigInt(1), Value::Integer(4)),
+            ]
+        );
+
+        let count = db.query("SELECT COUNT(*) FROM (SELECT DISTINCT options FROM configs) AS d")?;
+        assert_eq!(count.first_value(), Some(Value::BigInt(3)));
+        Ok(())
+    }
+}
+
+/// Tests for the RESERVOIR_SAMPLE aggregate
+#[cfg(test)]
+mod reservoir_sample_tests {
+    use prism::{Database, PrismDBResult, Value};
+
+    fn readings_database() -> PrismDBResult<Database> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE readings (sensor VARCHAR, level INTEGER)")?;
+        // Sensor a has 100 readings, b has 3 and c only NULLs
+        let mut values: Vec<String> = (0..100).map(|i| format!("('a', {})", i)).collect();
+        values.extend((0..3).map(|i| format!("('b', {})", 1000 + i)));
+        values.push("('c', NULL)".to_string());
+        db.execute(&format!("INSERT INTO readings VALUES {}", values.join(", ")))?;
+        Ok(db)
+    }
+
+    fn samples(db: &Database, sql: &str) -> PrismDBResult<Vec<(Value, Vec<Value>)>> {
+        let rows = db.query(sql)?.collect()?.rows;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let sample = match &row[1] {
+                    Value::List(values) => values.clone(),
+                    _ => Vec::new(),
+                };
+                (row[0].clone(), sample)
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_reservoir_sample_size() -> PrismDBResult<()> {
+        let db = readings_database()?;
+        let samples = samples(
+            &db,
+            "SELECT sensor, RESERVOIR_SAMPLE(level, 5) FROM readings GROUP BY sensor ORDER BY sensor",
+        )?;
+
+        // min(k, group size) values, each drawn from the group
+        let sizes: Vec<usize> = samples.iter().map(|(_, sample)| sample.len()).collect();
+        assert_eq!(sizes, [5, 3, 0]);
+        for value in &samples[0].1 {
+            assert!(matches!(value, Value::Integer(level) if (0..100).contains(level)));
+        }
+        let mut b = samples[1].1.clone();
+        b.sort_by_key(|value| value.to_string());
+        assert_eq!(b, [1000, 1001, 1002].map(Value::Integer));
+
+        let none = db.query("SELECT RESERVOIR_SAMPLE(level, 5) FROM readings WHERE sensor = 'c'")?;
+        assert_eq!(none.first_value(), Some(Value::Null));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reservoir_sample_with_seed_is_deterministic() -> PrismDBResult<()> {
+        let db = readings_database()?;
+        let sql = "SELECT sensor, RESERVOIR_SAMPLE(level, 10, 42) FROM readings GROUP BY sensor ORDER BY sensor";
+        let first = samples(&db, sql)?;
+        assert_eq!(first[0].1.len(), 10);
+        assert_eq!(samples(&db, sql)?, first);
+
+        // Another seed draws another sample
+        let other = samples(&db, &sql.replace("42", "7"))?;
+        assert_ne!(other[0].1, first[0].1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reservoir_sample_arguments() -> PrismDBResult<()> {
+        let db = readings_database()?;
+        assert!(db.query("SELECT RESERVOIR_SAMPLE(level) FROM readings").is_err());
+        assert!(db.query("SELECT RESERVOIR_SAMPLE(level, -1) FROM readings").is_err());
+        assert!(db.query("SELECT RESERVOIR_SAMPLE(level, level) FROM readings").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for keeping the comments written before a statement
+#[cfg(test)]
+mod query_comments_tests {
+    use prism::parser::{SqlParser, Statement};
+    use prism::{Database, PrismDBResult, Value};
+
+    #[test]
+    fn test_leading_comments_are_kept_when_enabled() -> PrismDBResult<()> {
+        let sql = "-- name: get_users\n/* owner: reporting */\nSELECT id FROM users -- trailing\nWHERE id > 1";
+
+        let annotated = SqlParser::new().with_comments(true).parse_annotated(sql)?;
+        assert!(matches!(annotated.statement, Statement::Select(_)));
+        assert_eq!(annotated.comments, ["name: get_users", "owner: reporting"]);
+        assert_eq!(annotated.annotation("name"), Some("get_users"));
+        assert_eq!(annotated.annotation("owner"), Some("reporting"));
+        assert_eq!(annotated.annotation("timeout"), None);
+
+        // Off by default, when comments are skipped like whitespace
+        let plain = SqlParser::new().parse_annotated(sql)?;
+        assert!(plain.comments.is_empty());
+        assert_eq!(plain.statement, annotated.statement);
+        Ok(())
+    }
+
+    #[test]
+    fn test_queries_with_comments_execute() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER) -- people")?;
+        db.execute("INSERT INTO users VALUES (1), /* skipped */ (2), (3)")?;
+
+        let count = db.query("-- name: count_users\nSELECT COUNT(*) FROM users WHERE id > 1")?;
+        assert_eq!(count.first_value(), Some(Value::BigInt(2)));
+
+        // Subtraction of a negative number is still arithmetic with a space
+        assert_eq!(db.query("SELECT 5 - -3")?.first_value(), Some(Value::Integer(8)));
+
+        assert!(SqlParser::new().parse("SELECT 1 /* open").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for resolving column references across the tables of a join
+#[cfg(test)]
+mod join_column_resolution_tests {
+    use crate::common::{database, text};
+    use prism::{PrismDBError, PrismDBResult, Value};
+
+    const JOIN_TABLES: &[&str] = &[
+        "CREATE TABLE a (id INTEGER, x VARCHAR)",
+        "CREATE TABLE b (id INTEGER, y VARCHAR)",
+        "INSERT INTO a VALUES (1, 'a1'), (2, 'a2')",
+        "INSERT INTO b VALUES (2, 'b2'), (3, 'b3')",
+    ];
+
+    #[test]
+    fn test_unqualified_column_in_both_tables_is_ambiguous() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let err = db.query("SELECT id FROM a JOIN b ON a.id = b.id").unwrap_err();
+        assert!(matches!(&err, PrismDBError::InvalidValue(message) if message.contains("ambiguous")), "{}", err);
+
+        // A column only one side has needs no qualifier
+        let rows = db.query("SELECT x, y FROM a JOIN b ON a.id = b.id")?.collect()?.rows;
+        assert_eq!(rows, [[text("a2"), text("b2")]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_qualified_columns_resolve_to_their_side() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let rows = db
+            .query("SELECT b.id, a.id, a.x FROM a LEFT JOIN b ON a.id = b.id ORDER BY a.id")?
+            .collect()?
+            .rows;
+        assert_eq!(
+            rows,
+            [
+                [Value::Null, Value::Integer(1), text("a1")],
+                [Value::Integer(2), Value::Integer(2), text("a2")],
+            ]
+        );
+
+        // Aliases replace the table names
+        let rows = db.query("SELECT q.y FROM a p JOIN b q ON p.id = q.id")?.collect()?.rows;
+        assert_eq!(rows, [[text("b2")]]);
+        assert!(db.query("SELECT a.y FROM a JOIN b ON a.id = b.id").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_using_merges_the_column() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let rows = db.query("SELECT id, x, y FROM a JOIN b USING (id)")?.collect()?.rows;
+        assert_eq!(rows, [[Value::Integer(2), text("a2"), text("b2")]]);
+
+        // The merged column of a LEFT join is the left table's
+        let rows = db
+            .query("SELECT id, y FROM a LEFT JOIN b USING (id) ORDER BY id")?
+            .collect()?
+            .rows;
+        assert_eq!(rows, [[Value::Integer(1), Value::Null], [Value::Integer(2), text("b2")]]);
+
+        assert!(db.query("SELECT * FROM a JOIN b USING (x)").is_err());
+        Ok(())
+    }
+}
+
+/// Tests for UNION inputs executing only as far as their rows are needed
+#[cfg(test)]
+mod union_limit_tests {
+    use prism::{Database, LogicalType, PrismDBResult, Value};
+
+    /// Two tables of 5000 sequential integers, in separate tables so their scans
+    /// can be counted apart
+    fn union_database() -> PrismDBResult<Database> {
+        let mut db = Database::new_in_memory()?;
+        for table in ["lefts", "rights"] {
+            db.create_table_from_rows(
+                table,
+                vec![("n".to_string(), LogicalType::Integer)],
+                (0..5000).map(|n| vec![Value::Integer(n)]).collect(),
+            )?;
+        }
+        Ok(db)
+    }
+
+    /// Rows scans have materialized from a table so far
+    fn rows_fetched(db: &Database, table: &str) -> PrismDBResult<usize> {
+        let table = db.catalog().read().unwrap().get_table("main", table)?;
+        let data = table.read().unwrap().get_data();
+        let rows = data.read().unwrap().rows_fetched();
+        Ok(rows)
+    }
+
+    #[test]
+    fn test_union_all_limit_skips_right_input() -> PrismDBResult<()> {
+        let db = union_database()?;
+        let rows = db
+            .query("SELECT n FROM lefts UNION ALL SELECT n FROM rights LIMIT 10")?
+            .collect()?
+            .rows;
+        assert_eq!(rows, (0..10).map(|n| [Value::Integer(n)]).collect::<Vec<_>>());
+        assert_eq!(rows_fetched(&db, "rights")?, 0);
+
+        // A limit past the left input reads on into the right one
+        let rows = db
+            .query("SELECT n FROM lefts WHERE n < 3 UNION ALL SELECT n FROM rights LIMIT 5")?
+            .collect()?
+            .rows;
+        let values: Vec<Value> = rows.into_iter().map(|row| row[0].clone()).collect();
+        assert_eq!(values, [0, 1, 2, 0, 1].map(Value::Integer));
+        assert!(rows_fetched(&db, "rights")? > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_union_removes_duplicates_across_inputs() -> PrismDBResult<()> {
+        let db = union_database()?;
+        let count = db.query(
+            "SELECT COUNT(*) FROM (SELECT n FROM lefts WHERE n < 100 UNION SELECT n FROM rights WHERE n >= 50 AND n < 150) AS u",
+        )?;
+        assert_eq!(count.first_value(), Some(Value::BigInt(150)));
+
+        let rows = db
+            .query("SELECT n FROM lefts WHERE n < 2 UNION SELECT n FROM lefts WHERE n < 2 LIMIT 5")?
+            .collect()?
+            .rows;
+        assert_eq!(rows, [[Value::Integer(0)], [Value::Integer(1)]]);
+        Ok(())
+    }
+}
+
+/// Tests for the output columns of JOIN ... USING and NATURAL JOIN
+#[cfg(test)]
+mod join_using_output_tests {
+    use crate::common::{column_names, text};
+    use prism::{Database, PrismDBResult, Value};
+
+    fn setup() -> PrismDBResult<Database> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE a (x VARCHAR, id INTEGER)")?;
+        db.execute("CREATE TABLE b (id INTEGER, y VARCHAR)")?;
+        db.execute("INSERT INTO a VALUES ('one', 1), ('two', 2), ('three', 3)")?;
+        db.execute("INSERT INTO b VALUES (1, 'uno'), (3, 'tres'), (4, 'cuatro')")?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_join_using_star_has_one_key_column() -> PrismDBResult<()> {
+        let db = setup()?;
+
+        // The USING column comes first and only once
+        let sql = "SELECT * FROM a JOIN b USING (id) ORDER BY id";
+        assert_eq!(column_names(&db, sql)?, ["id", "a.x", "b.y"]);
+        assert_eq!(
+            db.query(sql)?.collect()?.rows,
+            [
+                [Value::Integer(1), text("one"), text("uno")],
+                [Value::Integer(3), text("three"), text("tres")],
+            ]
+        );
+
+        // Qualified wildcards still list each side's own columns
+        assert_eq!(column_names(&db, "SELECT b.* FROM a JOIN b USING (id)")?, ["b.id", "b.y"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_left_join_using_coalesces_key() -> PrismDBResult<()> {
+        let db = setup()?;
+
+        // Unmatched left rows keep their key
+        let rows = db.query("SELECT * FROM a LEFT JOIN b USING (id) ORDER BY id")?.collect()?.rows;
+        assert_eq!(
+            rows,
+            [
+                [Value::Integer(1), text("one"), text("uno")],
+                [Value::Integer(2), text("two"), Value::Null],
+                [Value::Integer(3), text("three"), text("tres")],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_chained_join_using_shares_key() -> PrismDBResult<()> {
+        let mut db = setup()?;
+        db.execute("CREATE TABLE c (id INTEGER, z INTEGER)")?;
+        db.execute("INSERT INTO c VALUES (1, 10), (2, 20)")?;
+
+        let sql = "SELECT * FROM a JOIN b USING (id) JOIN c USING (id)";
+        assert_eq!(column_names(&db, sql)?, ["id", "a.x", "b.y", "c.z"]);
+        assert_eq!(db.query(sql)?.collect()?.rows, [[Value::Integer(1), text("one"), text("uno"), Value::Integer(10)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_natural_join_on_one_column() -> PrismDBResult<()> {
+        let db = setup()?;
+
+        let sql = "SELECT * FROM a NATURAL JOIN b ORDER BY id";
+        assert_eq!(column_names(&db, sql)?, ["id", "a.x", "b.y"]);
+        assert_eq!(
+            db.query(sql)?.collect()?.rows,
+            [
+                [Value::Integer(1), text("one"), text("uno")],
+                [Value::Integer(3), text("three"), text("tres")],
+            ]
+        );
+
+        let rows = db.query("SELECT id, y FROM a NATURAL LEFT JOIN b ORDER BY id")?.collect()?.rows;
+        assert_eq!(
+            rows,
+            [
+                [Value::Integer(1), text("uno")],
+                [Value::Integer(2), Value::Null],
+                [Value::Integer(3), text("tres")],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_natural_join_on_two_columns() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE stock (store INTEGER, item VARCHAR, units INTEGER)")?;
+        db.execute("CREATE TABLE prices (item VARCHAR, price DOUBLE, store INTEGER)")?;
+        db.execute("INSERT INTO stock VALUES (1, 'pen', 5), (1, 'cup', 2), (2, 'pen', 7)")?;
+        db.execute("INSERT INTO prices VALUES ('pen', 1.5, 1), ('pen', 1.25, 2), ('cup', 4.0, 2)")?;
+
+        // Common columns come first, in the left side's order
+        let sql = "SELECT * FROM stock NATURAL JOIN prices ORDER BY store";
+        assert_eq!(column_names(&db, sql)?, ["store", "item", "stock.units", "prices.price"]);
+        assert_eq!(
+            db.query(sql)?.collect()?.rows,
+            [
+                [Value::Integer(1), text("pen"), Value::Integer(5), Value::Double(1.5)],
+                [Value::Integer(2), text("pen"), Value::Integer(7), Value::Double(1.25)],
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_natural_join_without_common_columns_is_cross_join() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE sizes (size VARCHAR)")?;
+        db.execute("CREATE TABLE colors (color VARCHAR)")?;
+        db.execute("INSERT INTO sizes VALUES ('S'), ('L')")?;
+        db.execute("INSERT INTO colors VALUES ('red'), ('blue')")?;
+
+        let rows = db
+            .query("SELECT size, color FROM sizes NATURAL JOIN colors ORDER BY size, color")?
+            .collect()?
+            .rows;
+        assert_eq!(
+            rows,
+            [
+                [text("L"), text("blue")],
+                [text("L"), text("red")],
+                [text("S"), text("blue")],
+                [text("S"), text("red")],
+            ]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for SET grouping_collation
+#[cfg(test)]
+mod grouping_collation_tests {
+    use crate::common::{database, rows, text};
+    use prism::{PrismDBResult, Value};
+
+    const VISITS: &[&str] = &[
+        "CREATE TABLE visits (country VARCHAR, hits INTEGER)",
+        "INSERT INTO visits VALUES ('US', 1), ('us', 2), ('Us', 3), ('DE', 4), ('de', 5), (NULL, 7)",
+    ];
+
+    #[test]
+    fn test_group_by_is_case_sensitive_by_default() -> PrismDBResult<()> {
+        let db = database(VISITS)?;
+
+        let rows = db
+            .query("SELECT country, COUNT(*) FROM visits GROUP BY country ORDER BY country")?
+            .collect()?
+            .rows;
+        assert_eq!(rows.len(), 6);
+        let distinct = db.query("SELECT COUNT(*) FROM (SELECT DISTINCT country FROM visits) AS d")?;
+        assert_eq!(distinct.first_value(), Some(Value::BigInt(6)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nocase_grouping_merges_case_variants() -> PrismDBResult<()> {
+        let mut db = database(VISITS)?;
+        db.execute("SET grouping_collation = 'nocase'")?;
+
+        // Each group shows the first spelling it saw
+        let rows = db
+            .query("SELECT country, COUNT(*), SUM(hits) AS total FROM visits GROUP BY country ORDER BY total")?
+            .collect()?
+            .rows;
+        assert_eq!(
+            rows,
+            [
+                [text("US"), Value::BigInt(3), Value::Double(6.0)],
+                [Value::Null, Value::BigInt(1), Value::Double(7.0)],
+                [text("DE"), Value::BigInt(2), Value::Double(9.0)],
+            ]
+        );
+
+        let rows = db.query("SELECT DISTINCT country FROM visits ORDER BY country")?.collect()?.rows;
+        assert_eq!(rows, [[text("DE")], [text("US")], [Value::Null]]);
+
+        // Sorted input still merges variants that the sort kept apart
+        let rows = db
+            .query(
+                "SELECT COUNT(*) FROM (SELECT country FROM (SELECT country FROM visits ORDER BY country) AS s \
+             GROUP BY country) AS g",
+            )?
+            .collect()?
+            .rows;
+        assert_eq!(rows, [[Value::BigInt(3)]]);
+
+        // Other values group as before
+        let count = db.query("SELECT COUNT(*) FROM (SELECT hits FROM visits GROUP BY hits) AS g")?;
+        assert_eq!(count.first_value(), Some(Value::BigInt(6)));
+
+        db.execute("SET grouping_collation = 'binary'")?;
+        let distinct = db.query("SELECT COUNT(*) FROM (SELECT DISTINCT country FROM visits) AS d")?;
+        assert_eq!(distinct.first_value(), Some(Value::BigInt(6)));
+
+        assert!(db.execute("SET grouping_collation = 'fr'").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nocase_grouping_applies_to_distinct_aggregates() -> PrismDBResult<()> {
+        let mut db = database(VISITS)?;
+        let sql = "SELECT COUNT(DISTINCT country), STRING_AGG(DISTINCT country, ',') FROM visits";
+        assert_eq!(rows(&db, sql)?[0][0], Value::BigInt(5));
+
+        // DISTINCT aggregates count the groups GROUP BY makes, keeping each first spelling
+        db.execute("SET grouping_collation = 'nocase'")?;
+        assert_eq!(rows(&db, sql)?, [[Value::BigInt(2), text("US,DE")]]);
+        let sql = "SELECT many, COUNT(DISTINCT country) FROM (SELECT hits > 2 AS many, country FROM visits) AS v GROUP BY many ORDER BY many";
+        assert_eq!(
+            rows(&db, sql)?,
+            [
+                [Value::Boolean(false), Value::BigInt(1)],
+                [Value::Boolean(true), Value::BigInt(2)],
+            ]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for the stable codes of errors from failing statements
+#[cfg(test)]
+mod error_code_tests {
+    use prism::{Database, PrismDBResult};
+
+    fn error_code(db: &mut Database, sql: &str) -> &'static str {
+        match db.execute(sql) {
+            Ok(_) => panic!("{} succeeded", sql),
+            Err(err) => err.code(),
+        }
+    }
+
+    #[test]
+    fn test_catalog_error_codes() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE items (id INTEGER, name VARCHAR)")?;
+
+        assert_eq!(error_code(&mut db, "SELEC id FROM items"), "PARSE_ERROR");
+        assert_eq!(error_code(&mut db, "SELECT * FROM missing"), "TABLE_NOT_FOUND");
+        assert_eq!(error_code(&mut db, "INSERT INTO missing VALUES (1)"), "TABLE_NOT_FOUND");
+        assert_eq!(error_code(&mut db, "SELECT price FROM items"), "COLUMN_NOT_FOUND");
+        assert_eq!(error_code(&mut db, "UPDATE items SET price = 1"), "COLUMN_NOT_FOUND");
+        assert_eq!(error_code(&mut db, "SELECT no_such_function(id) FROM items"), "FUNCTION_NOT_FOUND");
+        assert_eq!(error_code(&mut db, "DROP SCHEMA missing"), "SCHEMA_NOT_FOUND");
+        assert_eq!(error_code(&mut db, "CREATE TABLE items (id INTEGER)"), "CATALOG_ERROR");
+        Ok(())
+    }
+
+    #[test]
+    fn test_constraint_error_codes() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR NOT NULL)")?;
+        db.execute("INSERT INTO users VALUES (1, 'a@example.com')")?;
+
+        assert_eq!(error_code(&mut db, "INSERT INTO users VALUES (2, NULL)"), "CONSTRAINT_VIOLATION");
+        assert_eq!(error_code(&mut db, "INSERT INTO users VALUES (1, 'b@example.com')"), "CONSTRAINT_VIOLATION");
+        assert_eq!(error_code(&mut db, "UPDATE users SET email = NULL"), "CONSTRAINT_VIOLATION");
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_mismatch_error_codes() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE items (id INTEGER, name VARCHAR)")?;
+        db.execute("INSERT INTO items VALUES (1, 'one')")?;
+
+        assert_eq!(error_code(&mut db, "SELECT CAST(name AS INTEGER[]) FROM items"), "TYPE_MISMATCH");
+
+        db.execute("SET type_coercion = 'strict'")?;
+        assert_eq!(error_code(&mut db, "SELECT id + 1.5 FROM items"), "TYPE_MISMATCH");
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_message_unchanged() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        let err = db.execute("SELECT * FROM missing").unwrap_err();
+        assert_eq!(err.code(), "TABLE_NOT_FOUND");
+        assert!(err.to_string().starts_with("Catalog error: "), "{}", err);
+        assert!(err.to_string().contains("missing"), "{}", err);
+        Ok(())
+    }
+}
+
+/// Tests for the preserve_insertion_order setting and LIMIT without ORDER BY
+#[cfg(test)]
+mod preserve_insertion_order_tests {
+    use crate::common::{database, insert_rows, rows};
+    use prism::{Database, Metric, PrismDBResult, Value};
+
+    /// Ids inserted in scrambled order, across many chunks, with parallelism on
+    fn events_database() -> PrismDBResult<Database> {
+        let mut db = database(&[
+            "SET threads = 8",
+            "SET enable_parallelism = true",
+            "CREATE TABLE events (id INTEGER, kind INTEGER)",
+        ])?;
+        insert_rows(&mut db, "events", inserted_ids().map(|id| format!("({}, {})", id, id % 5)))?;
+        db.execute("CREATE TABLE kinds (kind INTEGER, name VARCHAR)")?;
+        db.execute("INSERT INTO kinds VALUES (1, 'one'), (3, 'three')")?;
+        Ok(db)
+    }
+
+    fn inserted_ids() -> impl Iterator<Item = i32> {
+        (0..20000).map(|i| (i * 7919) % 20000)
+    }
+
+    #[test]
+    fn test_unordered_limit_returns_first_inserted_rows() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        db.execute("SET preserve_insertion_order = true")?;
+
+        let first: Vec<Vec<Value>> = inserted_ids().take(5).map(|id| vec![Value::Integer(id)]).collect();
+        let first_matches: Vec<Vec<Value>> =
+            inserted_ids().filter(|id| id % 7 == 0).take(5).map(|id| vec![Value::Integer(id)]).collect();
+        // Each kinds row's matches, in the order they were inserted
+        let first_joined: Vec<Vec<Value>> = inserted_ids()
+            .filter(|id| id % 5 == 1)
+            .take(5)
+            .map(|id| vec![Value::Integer(id)])
+            .collect();
+        for _ in 0..5 {
+            assert_eq!(rows(&db, "SELECT id FROM events LIMIT 5")?, first);
+            assert_eq!(rows(&db, "SELECT id FROM events WHERE id % 7 = 0 LIMIT 5")?, first_matches);
+            assert_eq!(
+                rows(&db, "SELECT e.id FROM kinds k JOIN events e ON k.kind = e.kind LIMIT 5")?,
+                first_joined
+            );
+        }
+
+        // Groups come out in the order their first rows were inserted
+        let kinds: Vec<Value> = rows(&db, "SELECT kind, COUNT(*) FROM events GROUP BY kind LIMIT 3")?
+            .into_iter()
+            .map(|row| row[0].clone())
+            .collect();
+        let mut expected = Vec::new();
+        for kind in inserted_ids().map(|id| id % 5) {
+            if !expected.contains(&kind) {
+                expected.push(kind);
+            }
+        }
+        assert_eq!(kinds, expected[..3].iter().map(|&kind| Value::Integer(kind)).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_unordered_limits_give_up_parallelism() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        db.execute("SET preserve_insertion_order = true")?;
+        let pipelines = |db: &Database, sql: &str| -> PrismDBResult<u64> {
+            let before = db.metrics().get(Metric::PipelinesScheduled);
+            db.query(sql)?.collect()?;
+            Ok(db.metrics().get(Metric::PipelinesScheduled) - before)
+        };
+
+        // The join's inputs run as separate pipelines unless the result is an unordered LIMIT
+        assert_eq!(pipelines(&db, "SELECT e.id FROM kinds k JOIN events e ON k.kind = e.kind LIMIT 5")?, 0);
+        assert!(pipelines(&db, "SELECT e.id FROM kinds k JOIN events e ON k.kind = e.kind ORDER BY e.id LIMIT 5")? > 0);
+        assert!(pipelines(&db, "SELECT e.id FROM kinds k JOIN events e ON k.kind = e.kind")? > 0);
+
+        db.execute("SET preserve_insertion_order = false")?;
+        assert!(pipelines(&db, "SELECT e.id FROM kinds k JOIN events e ON k.kind = e.kind LIMIT 5")? > 0);
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
 use prism::PrismDBResult;
 use prism::database::{Database, DatabaseConfig};
 
+mod common;
+
 #[test]
 fn test_constant_folding_optimization() -> PrismDBResult<()> {
     let db = Database::new(DatabaseConfig::in_memory())?;
@@ -129,3 +131,688 @@ fn test_optimizer_preserves_correctness() -> PrismDBResult<()> {
     println!("✓ Optimizer preserves query correctness");
     Ok(())
 }
+
+/// Tests for the sort-merge join operator against hash join results
+#[cfg(test)]
+mod sort_merge_join_tests {
+    use crate::common::{database, physical_plan};
+    use prism::execution::{ExecutionContext, ExecutionEngine};
+    use prism::planner::{PhysicalPlan, PhysicalSortMergeJoin};
+    use prism::storage::TransactionManager;
+    use prism::{Database, PrismDBResult, Value};
+    use std::sync::Arc;
+
+    const JOIN_TABLES: &[&str] = &[
+        "CREATE TABLE customers (id INTEGER, name VARCHAR)",
+        "CREATE TABLE orders (customer_id INTEGER, amount INTEGER)",
+        // Inserted out of key order, with duplicate keys on both sides
+        "INSERT INTO customers VALUES (3, 'carol'), (1, 'alice'), (2, 'bob'), (1, 'alex'), (4, 'dave')",
+        "INSERT INTO orders VALUES (1, 10), (3, 30), (1, 11), (5, 50), (3, 31), (1, 12), (2, 20)",
+    ];
+
+    /// Execute a plan and return its rows in a canonical order
+    fn run(db: &Database, plan: PhysicalPlan) -> PrismDBResult<Vec<Vec<Value>>> {
+        let context = ExecutionContext::new(Arc::new(TransactionManager::new()), db.catalog());
+        let mut rows = Vec::new();
+        for chunk in ExecutionEngine::new(context).execute(plan)? {
+            let chunk = chunk?;
+            for row_idx in 0..chunk.len() {
+                let mut row = Vec::new();
+                for col_idx in 0..chunk.column_count() {
+                    row.push(chunk.get_vector(col_idx).unwrap().get_value(row_idx)?);
+                }
+                rows.push(row);
+            }
+        }
+        rows.sort_by_key(|row| format!("{:?}", row));
+        Ok(rows)
+    }
+
+    /// Replace every hash join in the plan with an equivalent sort-merge join
+    fn with_sort_merge_joins(plan: PhysicalPlan) -> PhysicalPlan {
+        match plan {
+            PhysicalPlan::HashJoin(join) => PhysicalPlan::SortMergeJoin(PhysicalSortMergeJoin::new(
+                with_sort_merge_joins(*join.left),
+                with_sort_merge_joins(*join.right),
+                join.join_type,
+                join.left_keys,
+                join.right_keys,
+                join.condition,
+                join.schema,
+            )),
+            PhysicalPlan::Projection(mut proj) => {
+                proj.input = Box::new(with_sort_merge_joins(*proj.input));
+                PhysicalPlan::Projection(proj)
+            }
+            other => other,
+        }
+    }
+
+    fn contains_join(plan: &PhysicalPlan, sort_merge: bool) -> bool {
+        match plan {
+            PhysicalPlan::SortMergeJoin(_) => sort_merge,
+            PhysicalPlan::HashJoin(_) => !sort_merge,
+            other => other.children().iter().any(|child| contains_join(child, sort_merge)),
+        }
+    }
+
+    fn assert_matches_hash_join(db: &Database, sql: &str) -> PrismDBResult<Vec<Vec<Value>>> {
+        let hash_plan = physical_plan(db, sql)?;
+        assert!(contains_join(&hash_plan, false), "expected a hash join for {}", sql);
+        let merge_plan = with_sort_merge_joins(hash_plan.clone());
+        assert!(contains_join(&merge_plan, true));
+
+        let expected = run(db, hash_plan)?;
+        assert_eq!(run(db, merge_plan)?, expected);
+        Ok(expected)
+    }
+
+    #[test]
+    fn test_sort_merge_inner_join_matches_hash_join() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let rows = assert_matches_hash_join(
+            &db,
+            "SELECT c.id, c.name, o.amount FROM customers c JOIN orders o ON c.id = o.customer_id",
+        )?;
+        // Two customers with id 1 each match three orders
+        assert_eq!(rows.len(), 2 * 3 + 1 + 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_merge_left_join_matches_hash_join() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let rows = assert_matches_hash_join(
+            &db,
+            "SELECT c.id, c.name, o.amount FROM customers c LEFT JOIN orders o ON c.id = o.customer_id",
+        )?;
+        assert_eq!(rows.len(), 2 * 3 + 1 + 2 + 1);
+        assert!(rows.contains(&vec![
+            Value::Integer(4),
+            Value::Varchar("dave".to_string()),
+            Value::Null
+        ]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_merge_join_reversed_condition() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let merge_plan = with_sort_merge_joins(physical_plan(
+            &db,
+            "SELECT c.name, o.amount FROM customers c JOIN orders o ON o.customer_id = c.id",
+        )?);
+        let expected = run(
+            &db,
+            physical_plan(
+                &db,
+                "SELECT c.name, o.amount FROM customers c JOIN orders o ON c.id = o.customer_id",
+            )?,
+        )?;
+        assert_eq!(run(&db, merge_plan)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimizer_picks_sort_merge_for_sorted_inputs() -> PrismDBResult<()> {
+        let db = database(JOIN_TABLES)?;
+        let sql = "SELECT c.id, c.name, o.amount \
+               FROM (SELECT * FROM customers ORDER BY id) c \
+               JOIN (SELECT * FROM orders ORDER BY customer_id) o ON c.id = o.customer_id";
+        let plan = physical_plan(&db, sql)?;
+        assert!(contains_join(&plan, true), "expected a sort-merge join: {:?}", plan);
+
+        let expected = run(
+            &db,
+            physical_plan(
+                &db,
+                "SELECT c.id, c.name, o.amount FROM customers c JOIN orders o ON c.id = o.customer_id",
+            )?,
+        )?;
+        assert_eq!(run(&db, plan)?, expected);
+        assert_eq!(db.query(sql)?.row_count(), expected.len());
+
+        // Unsorted inputs keep the hash join
+        let plan = physical_plan(
+            &db,
+            "SELECT c.id FROM (SELECT * FROM customers ORDER BY name) c \
+         JOIN (SELECT * FROM orders ORDER BY customer_id) o ON c.id = o.customer_id",
+        )?;
+        assert!(contains_join(&plan, false));
+        Ok(())
+    }
+}
+
+/// Tests for answering unfiltered COUNT(*) from table metadata
+#[cfg(test)]
+mod count_star_tests {
+    use crate::common::{database, insert_rows, physical_plan};
+    use prism::planner::PhysicalPlan;
+    use prism::{Database, PrismDBResult, Value};
+
+    const ROWS: usize = 1_000;
+
+    fn events_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE events (id INTEGER, kind VARCHAR)"])?;
+        let values = (0..ROWS).map(|i| format!("({}, 'kind-{}')", i, i % 4));
+        insert_rows(&mut db, "events", values)?;
+        Ok(db)
+    }
+
+    fn contains_count_star(plan: &PhysicalPlan) -> bool {
+        matches!(plan, PhysicalPlan::CountStar(_)) || plan.children().into_iter().any(contains_count_star)
+    }
+
+    /// Chunks scans have materialized from a table so far
+    fn chunks_scanned(db: &Database, table: &str) -> PrismDBResult<usize> {
+        let table = db.catalog().read().unwrap().get_table("main", table)?;
+        let data = table.read().unwrap().get_data();
+        let chunks = data.read().unwrap().chunks_created();
+        Ok(chunks)
+    }
+
+    fn single_value(db: &Database, sql: &str) -> PrismDBResult<Value> {
+        let rows = db.query(sql)?.collect()?.rows;
+        assert_eq!(rows.len(), 1, "{}", sql);
+        Ok(rows[0][0].clone())
+    }
+
+    #[test]
+    fn test_count_star_reads_metadata() -> PrismDBResult<()> {
+        let db = events_database()?;
+        let before = chunks_scanned(&db, "events")?;
+
+        assert_eq!(single_value(&db, "SELECT COUNT(*) FROM events")?, Value::BigInt(ROWS as i64));
+        assert_eq!(single_value(&db, "SELECT count(1) AS n FROM events")?, Value::BigInt(ROWS as i64));
+        assert_eq!(chunks_scanned(&db, "events")?, before);
+
+        assert!(contains_count_star(&physical_plan(&db, "SELECT COUNT(*) FROM events")?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_star_tracks_deletes() -> PrismDBResult<()> {
+        let mut db = events_database()?;
+        db.execute("DELETE FROM events WHERE id < 100")?;
+        assert_eq!(single_value(&db, "SELECT COUNT(*) FROM events")?, Value::BigInt(900));
+
+        db.execute("CREATE TABLE empty (x INTEGER)")?;
+        assert_eq!(single_value(&db, "SELECT COUNT(*) FROM empty")?, Value::BigInt(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_with_filter_scans() -> PrismDBResult<()> {
+        let db = events_database()?;
+
+        for sql in [
+            "SELECT COUNT(*) FROM events WHERE kind = 'kind-1'",
+            "SELECT COUNT(id) FROM events",
+            "SELECT COUNT(DISTINCT kind) FROM events",
+            "SELECT kind, COUNT(*) FROM events GROUP BY kind",
+            "SELECT COUNT(*), SUM(id) FROM events",
+        ] {
+            assert!(!contains_count_star(&physical_plan(&db, sql)?), "{}", sql);
+        }
+
+        let before = chunks_scanned(&db, "events")?;
+        assert_eq!(
+            single_value(&db, "SELECT COUNT(*) FROM events WHERE kind = 'kind-1'")?,
+            Value::BigInt((ROWS / 4) as i64)
+        );
+        assert!(chunks_scanned(&db, "events")? > before);
+        Ok(())
+    }
+}
+
+/// Tests for the query result cache
+#[cfg(test)]
+mod query_cache_tests {
+    use crate::common::rows;
+    use prism::{Database, DatabaseConfig, PrismDBResult, QueryCacheStats, Value};
+
+    fn cached_database(capacity: usize) -> PrismDBResult<Database> {
+        let config = DatabaseConfig {
+            enable_query_cache: true,
+            query_cache_capacity: capacity,
+            ..DatabaseConfig::in_memory()
+        };
+        let mut db = Database::new(config)?;
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR)")?;
+        db.execute("INSERT INTO events VALUES (1, 'click'), (2, 'view'), (3, 'click')")?;
+        db.execute("CREATE TABLE users (id INTEGER, name VARCHAR)")?;
+        db.execute("INSERT INTO users VALUES (1, 'ann')")?;
+        Ok(db)
+    }
+
+    fn stats(hits: u64, misses: u64, entries: usize) -> QueryCacheStats {
+        QueryCacheStats { hits, misses, entries }
+    }
+
+    #[test]
+    fn test_repeated_query_hits_cache() -> PrismDBResult<()> {
+        let mut db = cached_database(8)?;
+        let clicks = vec![vec![Value::BigInt(2)]];
+
+        assert_eq!(rows(&db, "SELECT COUNT(*) FROM events WHERE kind = 'click'")?, clicks);
+        assert_eq!(db.query_cache_stats(), stats(0, 1, 1));
+
+        // Whitespace and keyword case do not change the key
+        assert_eq!(rows(&db, "select count(*)  FROM events\n WHERE kind = 'click';")?, clicks);
+        assert_eq!(db.query_cache_stats(), stats(1, 1, 1));
+
+        // Other sessions share the cache; different literals are different queries
+        let session = db.connect();
+        assert_eq!(rows(&session, "SELECT COUNT(*) FROM events WHERE kind = 'click'")?, clicks);
+        assert_eq!(rows(&session, "SELECT COUNT(*) FROM events WHERE kind = 'view'")?, vec![vec![Value::BigInt(1)]]);
+        assert_eq!(db.query_cache_stats(), stats(2, 2, 2));
+
+        // Statements inside a transaction bypass the cache
+        db.execute("BEGIN")?;
+        assert_eq!(rows(&db, "SELECT COUNT(*) FROM events WHERE kind = 'click'")?, clicks);
+        db.execute("COMMIT")?;
+        assert_eq!(db.query_cache_stats(), stats(2, 2, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_modifying_a_table_invalidates_its_results() -> PrismDBResult<()> {
+        let mut db = cached_database(8)?;
+        let total = "SELECT COUNT(*) FROM events";
+        let in_subquery = "SELECT name FROM users WHERE id IN (SELECT id FROM events WHERE kind = 'view')";
+
+        assert_eq!(rows(&db, total)?, vec![vec![Value::BigInt(3)]]);
+        assert!(rows(&db, in_subquery)?.is_empty());
+        assert_eq!(rows(&db, "SELECT name FROM users")?.len(), 1);
+
+        db.execute("INSERT INTO events VALUES (1, 'view')")?;
+        assert_eq!(rows(&db, total)?, vec![vec![Value::BigInt(4)]]);
+        // Tables read only by a subquery are tracked too
+        assert_eq!(rows(&db, in_subquery)?, vec![vec![Value::Varchar("ann".to_string())]]);
+        // Results over other tables are kept
+        assert_eq!(rows(&db, "SELECT name FROM users")?.len(), 1);
+        assert_eq!(db.query_cache_stats().hits, 1);
+
+        db.execute("DELETE FROM events WHERE id = 1")?;
+        assert_eq!(rows(&db, total)?, vec![vec![Value::BigInt(2)]]);
+
+        // A dropped and recreated table does not bring back old results
+        db.execute("DROP TABLE events")?;
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR)")?;
+        assert_eq!(rows(&db, total)?, vec![vec![Value::BigInt(0)]]);
+
+        // Writes from another session invalidate too
+        let mut session = db.connect();
+        session.execute("INSERT INTO events VALUES (10, 'view')")?;
+        assert_eq!(rows(&db, total)?, vec![vec![Value::BigInt(1)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() -> PrismDBResult<()> {
+        let db = cached_database(2)?;
+        let queries = [
+            "SELECT id FROM events WHERE id = 1",
+            "SELECT id FROM events WHERE id = 2",
+            "SELECT id FROM events WHERE id = 3",
+        ];
+
+        rows(&db, queries[0])?;
+        rows(&db, queries[1])?;
+        rows(&db, queries[0])?;
+        assert_eq!(db.query_cache_stats(), stats(1, 2, 2));
+
+        // The third query evicts the second, which was used longest ago
+        rows(&db, queries[2])?;
+        assert_eq!(db.query_cache_stats(), stats(1, 3, 2));
+        rows(&db, queries[0])?;
+        rows(&db, queries[2])?;
+        assert_eq!(db.query_cache_stats(), stats(3, 3, 2));
+        rows(&db, queries[1])?;
+        assert_eq!(db.query_cache_stats(), stats(3, 4, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_disabled_by_default() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE t (x INTEGER)")?;
+        rows(&db, "SELECT * FROM t")?;
+        rows(&db, "SELECT * FROM t")?;
+        assert_eq!(db.query_cache_stats(), QueryCacheStats::default());
+        Ok(())
+    }
+}
+
+/// Tests for EXPLAIN output formats
+#[cfg(test)]
+mod explain_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBResult, Value};
+
+    const SHOP: &[&str] = &[
+        "CREATE TABLE customers (id INTEGER, name VARCHAR)",
+        "INSERT INTO customers VALUES (1, 'ann'), (2, 'bob')",
+        "CREATE TABLE orders (id INTEGER, customer_id INTEGER, amount INTEGER)",
+        "INSERT INTO orders VALUES (1, 1, 10), (2, 1, 20), (3, 2, 5), (4, 2, 7), (5, 1, 3)",
+    ];
+
+    /// The single plan row produced by an EXPLAIN statement
+    fn explain(db: &Database, sql: &str) -> PrismDBResult<String> {
+        let rows = db.query(sql)?.collect()?.rows;
+        assert_eq!(rows.len(), 1);
+        match &rows[0][0] {
+            Value::Varchar(plan) => Ok(plan.clone()),
+            other => panic!("expected a plan string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_explain_json_join_aggregate() -> PrismDBResult<()> {
+        let db = database(SHOP)?;
+        let plan = explain(
+            &db,
+            "EXPLAIN (FORMAT JSON) SELECT c.name, COUNT(*) FROM orders o \
+         JOIN customers c ON o.customer_id = c.id GROUP BY c.name",
+        )?;
+        let root: serde_json::Value = serde_json::from_str(&plan).expect("plan is valid JSON");
+
+        assert_eq!(root["name"], "PROJECTION");
+        let aggregate = &root["children"][0];
+        assert_eq!(aggregate["name"], "HASH_AGGREGATE");
+        assert_eq!(aggregate["properties"]["groups"], "c.name");
+
+        let join = &aggregate["children"][0];
+        assert_eq!(join["name"], "HASH_JOIN");
+        assert_eq!(join["properties"]["join_type"], "INNER");
+        assert_eq!(join["properties"]["keys"], "o.customer_id = c.id");
+
+        let scans = join["children"].as_array().unwrap();
+        assert_eq!(scans.len(), 2);
+        assert_eq!(scans[0]["name"], "TABLE_SCAN");
+        assert_eq!(scans[0]["properties"]["table"], "orders");
+        assert_eq!(scans[0]["estimated_rows"], 5);
+        assert_eq!(scans[1]["properties"]["table"], "customers");
+        assert_eq!(scans[1]["estimated_rows"], 2);
+        assert!(scans.iter().all(|scan| scan["children"].as_array().unwrap().is_empty()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_text_format() -> PrismDBResult<()> {
+        let db = database(SHOP)?;
+        let query = "SELECT id FROM orders WHERE amount > 5";
+        let plan = explain(&db, &format!("EXPLAIN {}", query))?;
+        assert_eq!(explain(&db, &format!("EXPLAIN (FORMAT TEXT) {}", query))?, plan);
+
+        // The filter is pushed into the scan, which keeps an estimated fifth of the table
+        let lines: Vec<&str> = plan.lines().collect();
+        assert_eq!(lines[0], "PROJECTION (~1 rows)");
+        assert_eq!(lines[1], "    expressions: orders.id");
+        assert_eq!(lines[2], "  TABLE_SCAN (~1 rows)");
+        assert_eq!(lines[3], "      table: orders");
+        assert_eq!(lines[4], "      filters: orders.amount > 5");
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_write_statements() -> PrismDBResult<()> {
+        let db = database(SHOP)?;
+
+        // A DELETE is fed by a scan with its condition as the filter
+        let plan = explain(&db, "EXPLAIN DELETE FROM orders WHERE amount > 5")?;
+        let lines: Vec<&str> = plan.lines().collect();
+        assert_eq!(lines[0], "DELETE");
+        assert_eq!(lines[1], "    table: orders");
+        assert_eq!(lines[2], "  TABLE_SCAN (~1 rows)");
+        assert_eq!(lines[3], "      table: orders");
+        assert_eq!(lines[4], "      filters: amount > 5");
+
+        let plan = explain(&db, "EXPLAIN (FORMAT JSON) UPDATE orders SET amount = 0 WHERE id = 3")?;
+        let root: serde_json::Value = serde_json::from_str(&plan).expect("plan is valid JSON");
+        assert_eq!(root["name"], "UPDATE");
+        assert_eq!(root["properties"]["set"], "amount = 0");
+        assert_eq!(root["children"][0]["name"], "TABLE_SCAN");
+        assert_eq!(root["children"][0]["properties"]["filters"], "id = 3");
+
+        // An INSERT shows the plan of its source
+        let plan = explain(&db, "EXPLAIN INSERT INTO customers SELECT id, name FROM customers WHERE id = 1")?;
+        let lines: Vec<&str> = plan.lines().collect();
+        assert_eq!(lines[0], "INSERT (~1 rows)");
+        assert!(plan.contains("filters: customers.id = 1"));
+
+        // Nothing is written
+        assert_eq!(db.query("SELECT COUNT(*) FROM orders WHERE amount > 5")?.first_value(), Some(Value::BigInt(3)));
+        assert_eq!(db.query("SELECT amount FROM orders WHERE id = 3")?.first_value(), Some(Value::Integer(5)));
+        assert_eq!(db.query("SELECT COUNT(*) FROM customers")?.first_value(), Some(Value::BigInt(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_rejects_unknown_options() -> PrismDBResult<()> {
+        let db = database(SHOP)?;
+        assert!(db.query("EXPLAIN (FORMAT YAML) SELECT * FROM orders").is_err());
+        assert!(db.query("EXPLAIN (COSTS) SELECT * FROM orders").is_err());
+        Ok(())
+    }
+}
+
+/// Tests that the execution engine builds each query's operator tree once
+#[cfg(test)]
+mod operator_tree_tests {
+    use prism::{Database, Metric, PrismDBResult, Value};
+
+    /// Number of operators in a query's EXPLAIN output, one per upper-case node line
+    fn plan_nodes(db: &Database, sql: &str) -> PrismDBResult<u64> {
+        let plan = match &db.query(&format!("EXPLAIN {}", sql))?.collect()?.rows[0][0] {
+            Value::Varchar(plan) => plan.clone(),
+            other => panic!("expected a plan string, got {:?}", other),
+        };
+        let nodes = plan
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|word| word.chars().all(|c| c.is_ascii_uppercase() || c == '_'))
+            .count();
+        Ok(nodes as u64)
+    }
+
+    #[test]
+    fn test_each_plan_node_is_translated_once() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR)")?;
+        let values: Vec<String> = (0..3000).map(|i| format!("({}, 'k{}')", i, i % 7)).collect();
+        db.execute(&format!("INSERT INTO events VALUES {}", values.join(", ")))?;
+
+        for sql in [
+            "SELECT kind, COUNT(*) FROM events WHERE id > 10 GROUP BY kind ORDER BY kind LIMIT 3",
+            "SELECT e.id, f.kind FROM events e JOIN events f ON e.id = f.id WHERE e.id < 5 ORDER BY 1",
+            "SELECT id FROM events WHERE id < 3 UNION SELECT id + 1 FROM events WHERE id < 3",
+        ] {
+            let expected = plan_nodes(&db, sql)?;
+            let before = db.metrics().get(Metric::OperatorsBuilt);
+            let rows = db.query(sql)?.collect()?.rows;
+            assert!(!rows.is_empty());
+            let built = db.metrics().get(Metric::OperatorsBuilt) - before;
+            assert_eq!(built, expected, "{}", sql);
+        }
+        Ok(())
+    }
+}
+
+/// Tests for the plan cache keyed by parameterized query shape
+#[cfg(test)]
+mod plan_cache_tests {
+    use crate::common::rows;
+    use prism::{Database, DatabaseConfig, Metric, PlanCacheStats, PrismDBResult, Value};
+
+    fn cached_database() -> PrismDBResult<Database> {
+        let config = DatabaseConfig {
+            enable_plan_cache: true,
+            ..DatabaseConfig::in_memory()
+        };
+        let mut db = Database::new(config)?;
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR, score DOUBLE)")?;
+        db.execute("INSERT INTO events VALUES (1, 'click', 0.5), (2, 'view', 1.5), (3, 'click', 2.5)")?;
+        Ok(db)
+    }
+
+    fn stats(hits: u64, misses: u64, entries: usize) -> PlanCacheStats {
+        PlanCacheStats { hits, misses, entries }
+    }
+
+    #[test]
+    fn test_different_literals_reuse_the_plan() -> PrismDBResult<()> {
+        let db = cached_database()?;
+
+        assert_eq!(
+            rows(&db, "SELECT id FROM events WHERE kind = 'click' AND id > 1")?,
+            [[Value::Integer(3)]]
+        );
+        assert_eq!(db.plan_cache_stats(), stats(0, 1, 1));
+
+        // Same shape with other values: planned once, run with the new values
+        assert_eq!(
+            rows(&db, "SELECT id FROM events WHERE kind = 'view' AND id > 0")?,
+            [[Value::Integer(2)]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT id FROM events WHERE score BETWEEN 1.0 AND 3.0 ORDER BY id")?,
+            [[Value::Integer(2)], [Value::Integer(3)]]
+        );
+        assert_eq!(
+            rows(&db, "SELECT id FROM events WHERE score BETWEEN 0.0 AND 1.0 ORDER BY id")?,
+            [[Value::Integer(1)]]
+        );
+        assert_eq!(db.plan_cache_stats(), stats(2, 2, 2));
+
+        // A literal of another type is a different plan
+        assert!(rows(&db, "SELECT id FROM events WHERE kind = 'view' AND id > 5000000000")?.is_empty());
+        assert_eq!(db.plan_cache_stats(), stats(2, 3, 3));
+
+        let metrics = db.metrics();
+        assert_eq!(metrics.get(Metric::PlanCacheHits), 2);
+        assert_eq!(metrics.get(Metric::PlanCacheMisses), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_change_invalidates_plans() -> PrismDBResult<()> {
+        let mut db = cached_database()?;
+        let query = "SELECT * FROM events WHERE id = 1";
+
+        assert_eq!(rows(&db, query)?[0].len(), 3);
+        assert_eq!(db.plan_cache_stats(), stats(0, 1, 1));
+
+        // Writes keep the plan; the new rows are read when it runs
+        db.execute("INSERT INTO events VALUES (1, 'view', 9.5)")?;
+        assert_eq!(rows(&db, query)?.len(), 2);
+        assert_eq!(db.plan_cache_stats(), stats(1, 1, 1));
+
+        // A recreated table with other columns needs a new plan
+        db.execute("DROP TABLE events")?;
+        assert_eq!(db.plan_cache_stats().entries, 0);
+        db.execute("CREATE TABLE events (id INTEGER, kind VARCHAR)")?;
+        db.execute("INSERT INTO events VALUES (1, 'click')")?;
+        assert_eq!(
+            rows(&db, "SELECT * FROM events WHERE id = 2")?,
+            Vec::<Vec<Value>>::new()
+        );
+        assert_eq!(
+            rows(&db, query)?,
+            [[Value::Integer(1), Value::Varchar("click".to_string())]]
+        );
+        assert_eq!(db.plan_cache_stats(), stats(2, 2, 1));
+        Ok(())
+    }
+}
+
+/// Tests for streaming aggregation of inputs sorted on the GROUP BY keys
+#[cfg(test)]
+mod streaming_aggregate_tests {
+    use crate::common::{explain, rows};
+    use prism::{Database, PrismDBError, PrismDBResult, Value};
+
+    /// `rows` readings in scrambled sensor order; with `sensors` below `rows`,
+    /// sensors repeat, and every 50th reading has no sensor
+    fn readings_database(rows: i32, sensors: i32) -> PrismDBResult<Database> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE readings (sensor INTEGER, site INTEGER, reading INTEGER)")?;
+        let values: Vec<String> = (0..rows)
+            .map(|i| {
+                let sensor = if i % 50 == 0 { "NULL".to_string() } else { ((i * 7919) % sensors).to_string() };
+                format!("({}, {}, {})", sensor, i % 3, i % 97)
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO readings VALUES {}", values.join(", ")))?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_sorted_input_matches_hash_aggregate() -> PrismDBResult<()> {
+        let db = readings_database(2000, 40)?;
+        let aggregates = "COUNT(*), SUM(reading), MIN(reading), COUNT(DISTINCT reading), \
+                      AVG(reading) FILTER (WHERE site = 1)";
+
+        for (keys, sorted_input) in [
+            ("sensor", "(SELECT * FROM readings ORDER BY sensor) AS r"),
+            ("sensor, site", "(SELECT * FROM readings ORDER BY sensor, site) AS r"),
+        ] {
+            let streaming = format!("SELECT {}, {} FROM {} GROUP BY {} ORDER BY {}", keys, aggregates, sorted_input, keys, keys);
+            let hashed = format!("SELECT {}, {} FROM readings GROUP BY {} ORDER BY {}", keys, aggregates, keys, keys);
+            assert!(explain(&db, &streaming)?.contains("STREAMING_AGGREGATE"), "{}", streaming);
+            assert!(explain(&db, &hashed)?.contains("HASH_AGGREGATE"), "{}", hashed);
+
+            let expected = rows(&db, &hashed)?;
+            assert!(expected.iter().any(|row| row[0].is_null()));
+            assert_eq!(rows(&db, &streaming)?, expected, "{}", keys);
+        }
+
+        // Groups come out in input order, without needing an ORDER BY of their own
+        let sensors: Vec<Value> =
+            rows(&db, "SELECT sensor, COUNT(*) FROM (SELECT * FROM readings ORDER BY sensor) AS r GROUP BY sensor")?
+                .into_iter()
+                .map(|row| row[0].clone())
+                .collect();
+        let mut expected: Vec<Value> = (0..40).map(Value::Integer).collect();
+        expected.push(Value::Null);
+        assert_eq!(sensors, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsorted_input_uses_hash_aggregate() -> PrismDBResult<()> {
+        let db = readings_database(200, 10)?;
+
+        // Sorted on other columns than the keys, the groups are not contiguous
+        for sql in [
+            "SELECT sensor, COUNT(*) FROM (SELECT * FROM readings ORDER BY site) AS r GROUP BY sensor",
+            "SELECT site, sensor, COUNT(*) FROM (SELECT * FROM readings ORDER BY sensor) AS r GROUP BY site, sensor",
+        ] {
+            let plan = explain(&db, sql)?;
+            assert!(plan.contains("HASH_AGGREGATE") && !plan.contains("STREAMING_AGGREGATE"), "{}", plan);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_streaming_aggregate_memory_is_bounded() -> PrismDBResult<()> {
+        // Every reading with a sensor is its own group
+        let mut db = readings_database(20000, 20000)?;
+        let streaming = "SELECT sensor, SUM(reading), MIN(reading), MAX(reading), AVG(reading) \
+                     FROM (SELECT * FROM readings ORDER BY sensor) AS r GROUP BY sensor";
+        let hashed = "SELECT sensor, SUM(reading), MIN(reading), MAX(reading), AVG(reading) \
+                  FROM readings GROUP BY sensor";
+
+        // Holding one group at a time fits in a budget the hash table of every group does not
+        db.execute("SET memory_limit = '4MB'")?;
+        assert!(matches!(db.query(hashed), Err(PrismDBError::OutOfMemory)));
+        let streamed = rows(&db, streaming)?;
+        assert_eq!(streamed.len(), 19601);
+
+        db.execute("SET memory_limit = DEFAULT")?;
+        let mut expected = rows(&db, hashed)?;
+        expected.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        assert_eq!(streamed, expected);
+        Ok(())
+    }
+}
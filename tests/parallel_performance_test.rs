@@ -5,6 +5,8 @@
 
 use prism::PrismDBResult;
 
+mod common;
+
 #[test]
 fn test_parallel_operators_summary() -> PrismDBResult<()> {
     println!("\n");
@@ -50,3 +52,284 @@ fn test_parallel_operators_summary() -> PrismDBResult<()> {
 
     Ok(())
 }
+
+/// Tests that parallel hash aggregation, which merges per-thread partial
+/// states, gives the same results as aggregating on a single thread
+#[cfg(test)]
+mod parallel_aggregate_tests {
+    use crate::common::rows;
+    use prism::{Database, PrismDBResult, Value};
+
+    const ROWS: usize = 10_000;
+    const PARTS: usize = 10;
+
+    /// `ROWS` rows split over `PARTS` tables, read back as one relation by
+    /// `ORDERS`; each part reaches the aggregate as its own input chunk, so
+    /// workers get several chunks to aggregate
+    fn orders_database() -> PrismDBResult<Database> {
+        let mut db = Database::new_in_memory()?;
+        let values: Vec<String> = (0..ROWS)
+            .map(|i| {
+                let amount = if i % 13 == 0 { "NULL".to_string() } else { ((i * 37) % 1000).to_string() };
+                format!("({}, {}, {}, {}.5)", i, (i * 7) % 97, amount, i % 50)
+            })
+            .collect();
+        for (part, batch) in values.chunks(ROWS / PARTS).enumerate() {
+            db.execute(&format!(
+                "CREATE TABLE orders_{} (id INTEGER, region INTEGER, amount INTEGER, price DOUBLE)",
+                part
+            ))?;
+            db.execute(&format!("INSERT INTO orders_{} VALUES {}", part, batch.join(", ")))?;
+        }
+        Ok(db)
+    }
+
+    /// SQL for `sql` with `ORDERS` replaced by the union of all parts
+    fn over_orders(sql: &str) -> String {
+        let parts: Vec<String> = (0..PARTS).map(|part| format!("SELECT * FROM orders_{}", part)).collect();
+        sql.replace("ORDERS", &format!("({}) AS orders", parts.join(" UNION ALL ")))
+    }
+
+    /// Rows of a query result
+    type Rows = Vec<Vec<Value>>;
+
+    /// Results of `sql` with one thread and with several
+    fn sequential_and_parallel(sql: &str) -> PrismDBResult<(Rows, Rows)> {
+        let mut db = orders_database()?;
+        db.execute("SET threads = 1")?;
+        let sql = over_orders(sql);
+        let sequential = rows(&db, &sql)?;
+        db.execute("SET threads = 4")?;
+        let parallel = rows(&db, &sql)?;
+        Ok((sequential, parallel))
+    }
+
+    #[test]
+    fn test_parallel_grouped_aggregates_match_sequential() -> PrismDBResult<()> {
+        let (sequential, parallel) = sequential_and_parallel(
+            "SELECT region, COUNT(*), COUNT(amount), SUM(amount), AVG(amount), MIN(amount), MAX(amount) \
+         FROM ORDERS GROUP BY region ORDER BY region",
+        )?;
+        assert_eq!(sequential.len(), 97);
+        assert_eq!(parallel, sequential);
+
+        // Spot-check one group against values computed here
+        let region_0: Vec<i64> = (0..ROWS)
+            .filter(|i| (i * 7) % 97 == 0 && i % 13 != 0)
+            .map(|i| ((i * 37) % 1000) as i64)
+            .collect();
+        let count_0 = (0..ROWS).filter(|i| (i * 7) % 97 == 0).count();
+        assert_eq!(sequential[0][1], Value::BigInt(count_0 as i64));
+        assert_eq!(sequential[0][2], Value::BigInt(region_0.len() as i64));
+        assert_eq!(sequential[0][5], Value::Integer(*region_0.iter().min().unwrap() as i32));
+        assert_eq!(sequential[0][6], Value::Integer(*region_0.iter().max().unwrap() as i32));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parallel_global_aggregates_match_sequential() -> PrismDBResult<()> {
+        let (sequential, parallel) = sequential_and_parallel(
+            "SELECT COUNT(*), SUM(amount), AVG(price), MIN(price), MAX(id) FROM ORDERS",
+        )?;
+        assert_eq!(parallel, sequential);
+        assert_eq!(sequential[0][0], Value::BigInt(ROWS as i64));
+        assert_eq!(sequential[0][3], Value::Double(0.5));
+        assert_eq!(sequential[0][4], Value::Integer(ROWS as i32 - 1));
+
+        // Order-sensitive aggregates see their input in the same order either way
+        let (sequential, parallel) = sequential_and_parallel(
+            "SELECT region, STRING_AGG(CAST(id AS VARCHAR), ',') FROM ORDERS \
+         WHERE id % 500 = 0 GROUP BY region ORDER BY region",
+        )?;
+        assert_eq!(parallel, sequential);
+        Ok(())
+    }
+}
+
+/// Tests for running the pipelines of a plan concurrently with the pipeline scheduler
+#[cfg(test)]
+mod pipeline_scheduler_tests {
+    use crate::common::{database, insert_rows};
+    use prism::{Database, Metric, PrismDBResult, Value};
+
+    /// Orders referencing customers and products, customers referencing regions
+    fn shop_database() -> PrismDBResult<Database> {
+        let mut db = database(&[
+            "CREATE TABLE regions (region_id INTEGER, region VARCHAR)",
+            "INSERT INTO regions VALUES (0, 'north'), (1, 'south'), (2, 'east')",
+        ])?;
+
+        db.execute("CREATE TABLE customers (customer_id INTEGER, region_id INTEGER)")?;
+        insert_rows(&mut db, "customers", (0..300).map(|i| format!("({}, {})", i, i % 3)))?;
+
+        db.execute("CREATE TABLE products (product_id INTEGER, price INTEGER)")?;
+        insert_rows(&mut db, "products", (0..50).map(|i| format!("({}, {})", i, i + 1)))?;
+
+        db.execute("CREATE TABLE orders (customer_id INTEGER, product_id INTEGER, quantity INTEGER)")?;
+        let orders = (0..1000)
+            .map(|i| format!("({}, {}, {})", (i * 7) % 300, (i * 13) % 50, i % 5 + 1));
+        insert_rows(&mut db, "orders", orders)?;
+        Ok(db)
+    }
+
+    const REVENUE_BY_REGION: &str = "SELECT r.region, COUNT(*), SUM(o.quantity * p.price) \
+     FROM orders o \
+     JOIN customers c ON o.customer_id = c.customer_id \
+     JOIN products p ON o.product_id = p.product_id \
+     JOIN regions r ON c.region_id = r.region_id \
+     WHERE o.quantity > 1 \
+     GROUP BY r.region \
+     ORDER BY r.region";
+
+    /// The revenue query computed directly from the generated data
+    fn expected_revenue() -> Vec<Vec<Value>> {
+        let mut totals = [(0i64, 0.0); 3];
+        for i in 0..1000i64 {
+            let (customer, product, quantity) = ((i * 7) % 300, (i * 13) % 50, i % 5 + 1);
+            if quantity > 1 {
+                let region = (customer % 3) as usize;
+                totals[region].0 += 1;
+                totals[region].1 += (quantity * (product + 1)) as f64;
+            }
+        }
+        // Regions in name order: east, north, south
+        [(2, "east"), (0, "north"), (1, "south")]
+            .iter()
+            .map(|&(region, name)| {
+                vec![
+                    Value::Varchar(name.to_string()),
+                    Value::BigInt(totals[region].0),
+                    Value::Double(totals[region].1),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_multi_join_under_scheduler() -> PrismDBResult<()> {
+        let mut db = shop_database()?;
+        db.execute("SET threads = 4")?;
+
+        let before = db.metrics().get(Metric::PipelinesScheduled);
+        let scheduled = db.query(REVENUE_BY_REGION)?.collect()?.rows;
+        // One pipeline per table scanned, one per inner join and the final one
+        assert_eq!(db.metrics().get(Metric::PipelinesScheduled) - before, 7);
+        assert_eq!(scheduled, expected_revenue());
+
+        db.execute("SET enable_parallelism = false")?;
+        let before = db.metrics().get(Metric::PipelinesScheduled);
+        let sequential = db.query(REVENUE_BY_REGION)?.collect()?.rows;
+        assert_eq!(db.metrics().get(Metric::PipelinesScheduled), before);
+        assert_eq!(sequential, scheduled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_operations_and_errors_under_scheduler() -> PrismDBResult<()> {
+        let mut db = shop_database()?;
+        db.execute("SET threads = 4")?;
+
+        let sql = "SELECT customer_id FROM customers WHERE customer_id < 5 \
+               UNION ALL SELECT product_id FROM products WHERE product_id < 3 \
+               EXCEPT SELECT region_id FROM regions";
+        let mut ids: Vec<Value> = db.query(sql)?.collect()?.rows.into_iter().map(|row| row[0].clone()).collect();
+        ids.sort_by(|a, b| a.compare(b).unwrap());
+        assert_eq!(ids, [Value::Integer(3), Value::Integer(4)]);
+
+        // An error in one pipeline fails the query
+        let failing = "SELECT * FROM orders o JOIN products p ON o.product_id = p.product_id \
+                   WHERE o.quantity / (p.price - p.price) > 1";
+        assert!(db.query(failing).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_pipeline_plans_stream_directly() -> PrismDBResult<()> {
+        let mut db = shop_database()?;
+        db.execute("SET threads = 4")?;
+
+        let before = db.metrics().get(Metric::PipelinesScheduled);
+        let rows = db
+            .query("SELECT region_id, COUNT(*) FROM customers GROUP BY region_id ORDER BY region_id")?
+            .collect()?
+            .rows;
+        assert_eq!(rows.len(), 3);
+        assert_eq!(db.metrics().get(Metric::PipelinesScheduled), before);
+        Ok(())
+    }
+}
+
+/// Tests for picking chunk sizes from the estimated width of the rows
+#[cfg(test)]
+mod adaptive_chunk_tests {
+    use crate::common::{database, insert_rows};
+    use prism::{Database, PrismDBResult, Value};
+
+    const ROWS: usize = 1000;
+    const WIDE_COLUMNS: usize = 16;
+
+    /// A narrow table of one integer column and a wide one of many text columns
+    fn widths_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE narrow (id INTEGER)"])?;
+        insert_rows(&mut db, "narrow", (0..ROWS).map(|i| format!("({})", i)))?;
+
+        let columns: Vec<String> = (0..WIDE_COLUMNS).map(|c| format!("c{} VARCHAR", c)).collect();
+        db.execute(&format!("CREATE TABLE wide (id INTEGER, {})", columns.join(", ")))?;
+        let wide: Vec<String> = (0..ROWS)
+            .map(|i| {
+                let texts: Vec<String> = (0..WIDE_COLUMNS).map(|c| format!("'v{}_{}'", i, c)).collect();
+                format!("({}, {})", i, texts.join(", "))
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO wide VALUES {}", wide.join(", ")))?;
+        Ok(db)
+    }
+
+    /// Row counts of the chunks a query returns, and its rows
+    fn chunked(db: &Database, sql: &str) -> PrismDBResult<(Vec<usize>, Vec<Vec<Value>>)> {
+        let result = db.query(sql)?;
+        let sizes = result.chunks().iter().map(|chunk| chunk.len()).collect();
+        Ok((sizes, result.collect()?.rows))
+    }
+
+    #[test]
+    fn test_wide_rows_get_smaller_chunks() -> PrismDBResult<()> {
+        let db = widths_database()?;
+
+        let (narrow_sizes, narrow_rows) = chunked(&db, "SELECT id FROM narrow")?;
+        let (wide_sizes, wide_rows) = chunked(&db, "SELECT id FROM wide")?;
+        assert_eq!(narrow_sizes, [ROWS]);
+        assert!(wide_sizes.len() > 1);
+        assert!(wide_sizes.iter().all(|&size| size < ROWS));
+        assert_eq!(wide_sizes.iter().sum::<usize>(), ROWS);
+
+        // Chunking does not change what the scans return
+        assert_eq!(wide_rows, narrow_rows);
+        Ok(())
+    }
+
+    #[test]
+    fn test_results_independent_of_chunk_size() -> PrismDBResult<()> {
+        let db = widths_database()?;
+
+        let pairs = [
+            (
+                "SELECT g, COUNT(*), SUM(id) FROM (SELECT id % 7 AS g, id FROM narrow) t GROUP BY g ORDER BY g",
+                "SELECT g, COUNT(*), SUM(id) FROM (SELECT id % 7 AS g, id FROM wide) t GROUP BY g ORDER BY g",
+            ),
+            (
+                "SELECT id FROM narrow WHERE id >= 500 AND id < 520 ORDER BY id DESC",
+                "SELECT id FROM wide WHERE id >= 500 AND id < 520 ORDER BY id DESC",
+            ),
+            (
+                "SELECT n.id FROM narrow n JOIN narrow m ON n.id = m.id WHERE n.id < 300 ORDER BY n.id",
+                "SELECT w.id FROM wide w JOIN narrow n ON w.id = n.id WHERE w.id < 300 ORDER BY w.id",
+            ),
+            ("SELECT id FROM narrow LIMIT 150", "SELECT id FROM wide LIMIT 150"),
+        ];
+        for (narrow, wide) in pairs {
+            assert_eq!(chunked(&db, wide)?.1, chunked(&db, narrow)?.1, "{}", wide);
+        }
+        Ok(())
+    }
+}
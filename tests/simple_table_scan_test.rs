@@ -79,6 +79,7 @@ fn test_table_scan_source_reads_data() -> Result<(), Box<dyn std::error::Error>>
         column_ids: vec![0, 1],
         filters: vec![],
         limit: None,
+        ranges: vec![],
     };
 
     // Create TableScanSource
@@ -10,6 +10,8 @@ use std::sync::{Arc, RwLock};
 use tempfile::TempDir;
 use uuid::Uuid;
 
+mod common;
+
 /// Test basic table operations with statistics tracking
 #[test]
 fn test_table_operations_with_statistics() -> PrismDBResult<()> {
@@ -569,4 +571,762 @@ fn test_parallel_chunk_creation_performance() -> PrismDBResult<()> {
     assert!(_chunks.len() > 0, "Should have created at least one chunk");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Tests for BEGIN TRANSACTION READ ONLY
+#[cfg(test)]
+mod read_only_transaction_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBError, PrismDBResult, Value};
+
+    const ACCOUNTS: &[&str] = &[
+        "CREATE TABLE accounts (id INTEGER, balance INTEGER)",
+        "INSERT INTO accounts VALUES (1, 100), (2, 250)",
+    ];
+
+    fn balances(db: &Database) -> PrismDBResult<Vec<Vec<Value>>> {
+        Ok(db.query("SELECT id, balance FROM accounts ORDER BY id")?.collect()?.rows)
+    }
+
+    #[test]
+    fn test_read_only_transaction_rejects_writes() -> PrismDBResult<()> {
+        let mut db = database(ACCOUNTS)?;
+        let before = balances(&db)?;
+
+        db.execute("BEGIN TRANSACTION READ ONLY")?;
+        for sql in [
+            "INSERT INTO accounts VALUES (3, 5)",
+            "UPDATE accounts SET balance = 0",
+            "DELETE FROM accounts WHERE id = 1",
+            "CREATE TABLE audit (x INTEGER)",
+            "DROP TABLE accounts",
+        ] {
+            assert!(
+                matches!(db.execute(sql), Err(PrismDBError::ReadOnly(_))),
+                "{}",
+                sql
+            );
+        }
+        assert_eq!(balances(&db)?, before);
+        db.execute("COMMIT")?;
+
+        // Writes work again once the transaction ends
+        db.execute("INSERT INTO accounts VALUES (3, 5)")?;
+        assert_eq!(balances(&db)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_transaction_allows_reads() -> PrismDBResult<()> {
+        let mut db = database(ACCOUNTS)?;
+
+        db.execute("BEGIN TRANSACTION READ ONLY")?;
+        assert_eq!(
+            balances(&db)?,
+            vec![
+                vec![Value::Integer(1), Value::Integer(100)],
+                vec![Value::Integer(2), Value::Integer(250)],
+            ]
+        );
+        let count = db.query("SELECT COUNT(*) FROM accounts WHERE balance > 150")?.collect()?.rows;
+        assert_eq!(count, vec![vec![Value::BigInt(1)]]);
+        db.execute("ROLLBACK")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_statements() -> PrismDBResult<()> {
+        let mut db = database(ACCOUNTS)?;
+
+        db.execute("BEGIN")?;
+        assert!(matches!(db.execute("BEGIN"), Err(PrismDBError::Transaction(_))));
+        db.execute("UPDATE accounts SET balance = 101 WHERE id = 1")?;
+        db.execute("COMMIT")?;
+        assert_eq!(balances(&db)?[0], vec![Value::Integer(1), Value::Integer(101)]);
+
+        assert!(matches!(db.execute("COMMIT"), Err(PrismDBError::Transaction(_))));
+        assert!(matches!(db.execute("ROLLBACK"), Err(PrismDBError::Transaction(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_undoes_writes() -> PrismDBResult<()> {
+        let mut db = database(ACCOUNTS)?;
+        db.execute("CREATE TABLE audit (x INTEGER)")?;
+        let before = balances(&db)?;
+
+        db.execute("BEGIN")?;
+        db.execute("INSERT INTO accounts VALUES (3, 5)")?;
+        db.execute("UPDATE accounts SET balance = 0 WHERE id = 1")?;
+        db.execute("DELETE FROM accounts WHERE id = 2")?;
+        db.execute("INSERT INTO audit VALUES (1)")?;
+        assert_eq!(balances(&db)?.len(), 2);
+        db.execute("ROLLBACK")?;
+
+        assert_eq!(balances(&db)?, before);
+        let audit = db.query("SELECT COUNT(*) FROM audit")?.collect()?.rows;
+        assert_eq!(audit, vec![vec![Value::BigInt(0)]]);
+
+        // Writes made after the rollback stick
+        db.execute("INSERT INTO accounts VALUES (4, 7)")?;
+        assert_eq!(balances(&db)?.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_keeps_rows_other_connections_committed() -> PrismDBResult<()> {
+        let mut db = database(ACCOUNTS)?;
+        let mut other = db.connect();
+
+        db.execute("BEGIN")?;
+        db.execute("INSERT INTO accounts VALUES (3, 5)")?;
+        other.execute("INSERT INTO accounts VALUES (4, 20)")?;
+        db.execute("ROLLBACK")?;
+
+        assert_eq!(
+            balances(&db)?,
+            vec![
+                vec![Value::Integer(1), Value::Integer(100)],
+                vec![Value::Integer(2), Value::Integer(250)],
+                vec![Value::Integer(4), Value::Integer(20)],
+            ]
+        );
+        Ok(())
+    }
+}
+
+/// Tests for READ COMMITTED, REPEATABLE READ and SERIALIZABLE transactions
+#[cfg(test)]
+mod isolation_level_tests {
+    use crate::common::database;
+    use prism::{Database, PrismDBError, PrismDBResult, Value};
+
+    const ACCOUNTS: &[&str] = &[
+        "CREATE TABLE accounts (id INTEGER, balance INTEGER)",
+        "INSERT INTO accounts VALUES (1, 100), (2, 200)",
+    ];
+
+    fn balance(db: &Database, id: i32) -> PrismDBResult<Value> {
+        let rows = db
+            .query(&format!("SELECT balance FROM accounts WHERE id = {}", id))?
+            .collect()?
+            .rows;
+        assert_eq!(rows.len(), 1);
+        Ok(rows[0][0].clone())
+    }
+
+    fn count(db: &Database, sql: &str) -> PrismDBResult<Value> {
+        Ok(db.query(sql)?.collect()?.rows[0][0].clone())
+    }
+
+    fn is_serialization_failure(result: PrismDBResult<impl Sized>) -> bool {
+        matches!(result, Err(PrismDBError::Transaction(message)) if message.contains("could not serialize"))
+    }
+
+    #[test]
+    fn test_read_committed_sees_concurrent_commit() -> PrismDBResult<()> {
+        let db = database(ACCOUNTS)?;
+        let other = db.connect();
+
+        db.query("BEGIN TRANSACTION READ COMMITTED")?;
+        assert_eq!(balance(&db, 1)?, Value::Integer(100));
+
+        other.query("UPDATE accounts SET balance = 150 WHERE id = 1")?;
+        other.query("INSERT INTO accounts VALUES (3, 300)")?;
+
+        // Each statement sees the latest committed data
+        assert_eq!(balance(&db, 1)?, Value::Integer(150));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(3));
+        db.query("COMMIT")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_committed_skips_uncommitted_writes() -> PrismDBResult<()> {
+        let db = database(ACCOUNTS)?;
+        let other = db.connect();
+
+        other.query("BEGIN")?;
+        other.query("UPDATE accounts SET balance = 999 WHERE id = 1")?;
+        other.query("DELETE FROM accounts WHERE id = 2")?;
+        other.query("INSERT INTO accounts VALUES (3, 300)")?;
+
+        db.query("BEGIN TRANSACTION READ COMMITTED")?;
+        assert_eq!(balance(&db, 1)?, Value::Integer(100));
+        assert_eq!(balance(&db, 2)?, Value::Integer(200));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(2));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts WHERE balance = 999")?, Value::BigInt(0));
+
+        other.query("COMMIT")?;
+        assert_eq!(balance(&db, 1)?, Value::Integer(999));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(2));
+        db.query("COMMIT")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeatable_read_keeps_snapshot() -> PrismDBResult<()> {
+        let db = database(ACCOUNTS)?;
+        let other = db.connect();
+
+        db.query("BEGIN TRANSACTION REPEATABLE READ")?;
+        assert_eq!(balance(&db, 1)?, Value::Integer(100));
+
+        other.query("BEGIN")?;
+        other.query("UPDATE accounts SET balance = 150 WHERE id = 1")?;
+        other.query("INSERT INTO accounts VALUES (3, 300)")?;
+        other.query("COMMIT")?;
+
+        // Reads repeat the snapshot taken at BEGIN
+        assert_eq!(balance(&db, 1)?, Value::Integer(100));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(2));
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) FROM accounts WHERE balance > 120")?,
+            Value::BigInt(1)
+        );
+        db.query("COMMIT")?;
+
+        // A new transaction sees the commit
+        assert_eq!(balance(&db, 1)?, Value::Integer(150));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeatable_read_sees_own_writes() -> PrismDBResult<()> {
+        let db = database(ACCOUNTS)?;
+
+        db.query("BEGIN TRANSACTION REPEATABLE READ")?;
+        db.query("UPDATE accounts SET balance = 50 WHERE id = 2")?;
+        db.query("INSERT INTO accounts VALUES (3, 300)")?;
+        assert_eq!(balance(&db, 2)?, Value::Integer(50));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(3));
+        db.query("COMMIT")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_serializable_write_conflict_aborts() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE doctors (name VARCHAR, on_call BOOLEAN)")?;
+        db.execute("INSERT INTO doctors VALUES ('alice', true), ('bob', true)")?;
+        let other = db.connect();
+        let on_call = "SELECT COUNT(*) FROM doctors WHERE on_call = true";
+
+        // Both check that someone else is still on call, then go off call
+        db.query("BEGIN TRANSACTION SERIALIZABLE")?;
+        other.query("BEGIN TRANSACTION SERIALIZABLE")?;
+        assert_eq!(count(&db, on_call)?, Value::BigInt(2));
+        assert_eq!(count(&other, on_call)?, Value::BigInt(2));
+
+        db.query("UPDATE doctors SET on_call = false WHERE name = 'alice'")?;
+        assert!(is_serialization_failure(
+            other.query("UPDATE doctors SET on_call = false WHERE name = 'bob'")
+        ));
+
+        // The failed transaction rejects further statements until it is ended
+        assert!(other.query(on_call).is_err());
+        other.query("ROLLBACK")?;
+        db.query("COMMIT")?;
+
+        assert_eq!(count(&db, on_call)?, Value::BigInt(1));
+        assert_eq!(count(&other, on_call)?, Value::BigInt(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_serializable_read_write_conflict_fails_commit() -> PrismDBResult<()> {
+        let db = database(ACCOUNTS)?;
+        db.query("CREATE TABLE audit (total INTEGER)")?;
+        let other = db.connect();
+
+        // `db` reads accounts and writes audit; `other` then changes accounts
+        db.query("BEGIN TRANSACTION SERIALIZABLE")?;
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(2));
+        db.query("INSERT INTO audit VALUES (2)")?;
+
+        other.query("INSERT INTO accounts VALUES (3, 300)")?;
+
+        assert!(is_serialization_failure(db.query("COMMIT")));
+        assert!(db.query("ROLLBACK").is_err());
+        // The failed transaction's insert was undone
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM audit")?, Value::BigInt(0));
+
+        // REPEATABLE READ allows the same interleaving
+        db.query("BEGIN TRANSACTION REPEATABLE READ")?;
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM accounts")?, Value::BigInt(3));
+        db.query("INSERT INTO audit VALUES (3)")?;
+        other.query("INSERT INTO accounts VALUES (4, 400)")?;
+        db.query("COMMIT")?;
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM audit")?, Value::BigInt(1));
+        Ok(())
+    }
+}
+
+/// Tests for UPDATE and DELETE finding their rows through an index
+#[cfg(test)]
+mod index_dml_tests {
+    use crate::common::{database, insert_rows};
+    use prism::{Database, Metric, PrismDBResult, Value};
+
+    fn items_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE items (id INTEGER, name VARCHAR)"])?;
+        insert_rows(&mut db, "items", (0..100).map(|i| format!("({}, 'item{}')", i, i)))?;
+        db.execute("CREATE INDEX items_id ON items (id)")?;
+        Ok(db)
+    }
+
+    /// Rows the statement changed, and the rows its scans read
+    fn run(db: &mut Database, sql: &str) -> PrismDBResult<(i64, u64)> {
+        let before = db.metrics().get(Metric::RowsScanned);
+        let rows = db.execute(sql)?.collect()?.rows;
+        let scanned = db.metrics().get(Metric::RowsScanned) - before;
+        match rows[0][0] {
+            Value::BigInt(changed) => Ok((changed, scanned)),
+            ref other => panic!("expected a row count, got {:?}", other),
+        }
+    }
+
+    fn ids(db: &Database, sql: &str) -> PrismDBResult<Vec<i32>> {
+        let rows = db.query(sql)?.collect()?.rows;
+        Ok(rows
+            .iter()
+            .map(|row| match row[0] {
+                Value::Integer(id) => id,
+                ref other => panic!("Expected integer id, got {:?}", other),
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_indexed_delete_touches_one_row() -> PrismDBResult<()> {
+        let mut db = items_database()?;
+        let lookups = db.metrics().get(Metric::IndexLookups);
+
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE id = 42")?, (1, 1));
+        assert_eq!(db.metrics().get(Metric::IndexLookups), lookups + 1);
+
+        let expected: Vec<i32> = (0..100).filter(|&i| i != 42).collect();
+        assert_eq!(ids(&db, "SELECT id FROM items ORDER BY id")?, expected);
+
+        // The deleted row is gone from the index too
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE id = 42")?, (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_indexed_range_update() -> PrismDBResult<()> {
+        let mut db = items_database()?;
+
+        // Only the rows of the range are read; the other conjunct is checked on them
+        let sql = "UPDATE items SET name = 'changed' WHERE id >= 10 AND id < 15 AND name <> 'item12'";
+        assert_eq!(run(&mut db, sql)?, (4, 5));
+        assert_eq!(
+            ids(&db, "SELECT id FROM items WHERE name = 'changed' ORDER BY id")?,
+            [10, 11, 13, 14]
+        );
+
+        // Moving a row to a new key moves its index entry
+        assert_eq!(run(&mut db, "UPDATE items SET id = 1000 WHERE id = 5")?, (1, 1));
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE id = 5")?, (0, 0));
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE id = 1000")?, (1, 1));
+        assert_eq!(ids(&db, "SELECT id FROM items")?.len(), 99);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unindexed_conditions_scan_the_table() -> PrismDBResult<()> {
+        let mut db = items_database()?;
+
+        // Inserted rows are indexed as they arrive
+        db.execute("INSERT INTO items VALUES (200, 'late'), (200, 'later')")?;
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE id = 200")?, (2, 2));
+
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE name = 'item7'")?.1, 102);
+        db.execute("DROP INDEX items_id")?;
+        assert_eq!(run(&mut db, "DELETE FROM items WHERE id = 8")?, (1, 102));
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_index_errors() -> PrismDBResult<()> {
+        let mut db = items_database()?;
+        assert!(db.execute("CREATE INDEX items_id ON items (name)").is_err());
+        db.execute("CREATE INDEX IF NOT EXISTS items_id ON items (name)")?;
+        assert!(db.execute("CREATE INDEX items_missing ON items (missing)").is_err());
+        assert!(db.execute("CREATE INDEX items_both ON items (id, name)").is_err());
+        assert!(db.execute("ALTER TABLE items DROP COLUMN id").is_err());
+        assert!(db.execute("DROP INDEX missing").is_err());
+        db.execute("DROP INDEX IF EXISTS missing")?;
+        Ok(())
+    }
+}
+
+/// Tests for VACUUM reclaiming the storage of deleted rows
+#[cfg(test)]
+mod vacuum_tests {
+    use crate::common::{database, insert_rows, rows};
+    use prism::{Database, PrismDBResult, Value};
+
+    fn readings_database() -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE readings (id INTEGER, sensor VARCHAR, value DOUBLE)"])?;
+        let values = (0..1000).map(|i| format!("({}, 's{}', {}.5)", i, i % 4, i));
+        insert_rows(&mut db, "readings", values)?;
+        Ok(db)
+    }
+
+    /// Physical and live row counts of a table
+    fn row_counts(db: &Database, table_name: &str) -> PrismDBResult<(usize, usize)> {
+        let catalog = db.catalog();
+        let catalog = catalog.read().unwrap();
+        let table = catalog.get_table("main", table_name)?;
+        let data = table.read().unwrap().get_data();
+        let data = data.read().unwrap();
+        Ok((data.physical_row_count(), data.row_count()))
+    }
+
+    #[test]
+    fn test_vacuum_reclaims_deleted_rows() -> PrismDBResult<()> {
+        let mut db = readings_database()?;
+        db.execute("CREATE INDEX readings_id ON readings (id)")?;
+        db.execute("DELETE FROM readings WHERE id % 10 <> 0")?;
+        assert_eq!(row_counts(&db, "readings")?, (1000, 100));
+
+        let queries = [
+            "SELECT * FROM readings ORDER BY id",
+            "SELECT sensor, COUNT(*), SUM(value) FROM readings GROUP BY sensor ORDER BY sensor",
+            "SELECT value FROM readings WHERE id = 500",
+        ];
+        let before = queries.iter().map(|sql| rows(&db, sql)).collect::<PrismDBResult<Vec<_>>>()?;
+
+        db.execute("VACUUM readings")?;
+        assert_eq!(row_counts(&db, "readings")?, (100, 100));
+        let after = queries.iter().map(|sql| rows(&db, sql)).collect::<PrismDBResult<Vec<_>>>()?;
+        assert_eq!(after, before);
+
+        // The rebuilt index finds rows at their new positions
+        db.execute("UPDATE readings SET value = 0 WHERE id = 990")?;
+        assert_eq!(rows(&db, "SELECT value FROM readings WHERE id = 990")?, [[Value::Double(0.0)]]);
+        db.execute("INSERT INTO readings VALUES (1000, 's0', 1.0)")?;
+        assert_eq!(row_counts(&db, "readings")?, (101, 101));
+        Ok(())
+    }
+
+    #[test]
+    fn test_vacuum_every_table() -> PrismDBResult<()> {
+        let mut db = readings_database()?;
+        db.execute("CREATE TABLE other (x INTEGER)")?;
+        db.execute("INSERT INTO other VALUES (1), (2), (3)")?;
+        db.execute("DELETE FROM readings WHERE id >= 10")?;
+        db.execute("DELETE FROM other WHERE x = 2")?;
+
+        db.execute("VACUUM")?;
+        assert_eq!(row_counts(&db, "readings")?, (10, 10));
+        assert_eq!(row_counts(&db, "other")?, (2, 2));
+        assert!(db.execute("VACUUM missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_vacuum_leaves_open_transactions_alone() -> PrismDBResult<()> {
+        let mut db = readings_database()?;
+        let mut writer = db.connect();
+        let mut reader = db.connect();
+
+        reader.execute("BEGIN TRANSACTION REPEATABLE READ")?;
+        assert_eq!(rows(&reader, "SELECT COUNT(*) FROM readings")?, [[Value::BigInt(1000)]]);
+
+        writer.execute("BEGIN")?;
+        writer.execute("DELETE FROM readings WHERE id < 500")?;
+        assert!(writer.execute("VACUUM readings").is_err());
+
+        // Rows deleted by a transaction that is still open are kept
+        db.execute("VACUUM readings")?;
+        assert_eq!(row_counts(&db, "readings")?, (1000, 1000));
+        writer.execute("COMMIT")?;
+
+        // ...and so are rows an open snapshot still sees
+        db.execute("VACUUM readings")?;
+        assert_eq!(row_counts(&db, "readings")?, (1000, 500));
+        assert_eq!(rows(&reader, "SELECT COUNT(*) FROM readings")?, [[Value::BigInt(1000)]]);
+        reader.execute("COMMIT")?;
+        assert_eq!(rows(&reader, "SELECT COUNT(*) FROM readings")?, [[Value::BigInt(500)]]);
+
+        db.execute("VACUUM readings")?;
+        assert_eq!(row_counts(&db, "readings")?, (500, 500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_vacuum_threshold() -> PrismDBResult<()> {
+        let mut db = readings_database()?;
+        db.execute("SET auto_vacuum_threshold = 50")?;
+
+        db.execute("DELETE FROM readings WHERE id < 400")?;
+        assert_eq!(row_counts(&db, "readings")?, (1000, 600));
+
+        db.execute("DELETE FROM readings WHERE id < 600")?;
+        assert_eq!(row_counts(&db, "readings")?, (400, 400));
+        Ok(())
+    }
+}
+
+/// Tests for server-side cursors fetching a query's rows in pages
+#[cfg(test)]
+mod cursor_tests {
+    use crate::common::{database, insert_rows};
+    use prism::{Database, Metric, PrismDBResult, Value};
+
+    fn events_database(rows: usize) -> PrismDBResult<Database> {
+        let mut db = database(&["CREATE TABLE events (id INTEGER, kind VARCHAR)"])?;
+        insert_rows(&mut db, "events", (0..rows).map(|i| format!("({}, 'k{}')", i, i % 7)))?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_pages_concatenate_to_the_full_result() -> PrismDBResult<()> {
+        let db = events_database(5000)?;
+        let sql = "SELECT id * 2 AS doubled, kind FROM events WHERE id % 3 <> 1";
+
+        let mut cursor = db.open_cursor(sql, &[])?;
+        assert_eq!(cursor.columns[0].name, "doubled");
+        let mut pages = Vec::new();
+        loop {
+            let chunks = cursor.fetch(128)?;
+            if chunks.is_empty() {
+                break;
+            }
+            let mut page = Vec::new();
+            for chunk in &chunks {
+                page.extend(chunk.to_rows()?);
+            }
+            pages.push(page);
+        }
+
+        // Every page but the last is full
+        let (last, full) = pages.split_last().unwrap();
+        assert!(full.iter().all(|page| page.len() == 128));
+        assert!(!last.is_empty() && last.len() <= 128);
+
+        let rows: Vec<Vec<Value>> = pages.concat();
+        assert_eq!(rows, db.query(sql)?.collect()?.rows);
+        assert!(cursor.fetch(128)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_runs_the_query_only_as_far_as_needed() -> PrismDBResult<()> {
+        let db = events_database(20000)?;
+        let before = db.metrics().get(Metric::RowsScanned);
+
+        let mut cursor = db.open_cursor("SELECT id FROM events WHERE kind = 'k3'", &[])?;
+        let chunks = cursor.fetch(10)?;
+        assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 10);
+        assert_eq!(chunks[0].get_value(0, 0)?, Value::Integer(3));
+        let scanned = db.metrics().get(Metric::RowsScanned) - before;
+        assert!(scanned > 0 && scanned < 20000, "scanned {} rows", scanned);
+
+        cursor.close();
+        assert!(cursor.is_closed());
+        assert!(cursor.fetch(10).is_err());
+        assert!(db.metrics().get(Metric::RowsScanned) - before == scanned);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor_parameters() -> PrismDBResult<()> {
+        let db = events_database(100)?;
+        let mut cursor = db.open_cursor(
+            "SELECT id FROM events WHERE id < ? AND kind = ? ORDER BY id",
+            &[Value::Integer(20), Value::Varchar("k3".to_string())],
+        )?;
+        let rows = cursor.fetch(100)?[0].to_rows()?;
+        assert_eq!(rows, [[Value::Integer(3)], [Value::Integer(10)], [Value::Integer(17)]]);
+
+        // Every placeholder needs a value and every value a placeholder
+        assert!(db.open_cursor("SELECT id FROM events WHERE id < ?", &[]).is_err());
+        assert!(db.open_cursor("SELECT id FROM events", &[Value::Integer(1)]).is_err());
+        // Only queries have rows to page through
+        assert!(db.open_cursor("DELETE FROM events WHERE id = ?", &[Value::Integer(1)]).is_err());
+        assert_eq!(db.query("SELECT COUNT(*) FROM events")?.collect()?.rows[0][0], Value::BigInt(100));
+        Ok(())
+    }
+}
+
+/// Tests for the prism_transactions table function
+#[cfg(test)]
+mod transactions_table_tests {
+    use prism::{Database, PrismDBResult, Value};
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn test_open_transaction_on_another_thread_is_listed() -> PrismDBResult<()> {
+        let db = Database::new_in_memory()?;
+        assert!(db.query("SELECT * FROM prism_transactions()")?.collect()?.rows.is_empty());
+
+        let (opened_tx, opened_rx) = mpsc::channel();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let mut session = db.connect();
+        let handle = thread::spawn(move || -> PrismDBResult<()> {
+            session.execute("BEGIN TRANSACTION SERIALIZABLE")?;
+            opened_tx.send(()).unwrap();
+            done_rx.recv().unwrap();
+            session.execute("COMMIT")?;
+            Ok(())
+        });
+        opened_rx.recv().unwrap();
+
+        let result = db.query("SELECT * FROM prism_transactions()")?;
+        let names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["transaction_id", "state", "start_time", "isolation_level", "read_only"]);
+        let rows = result.collect()?.rows;
+        assert_eq!(rows.len(), 1);
+        assert!(matches!(&rows[0][0], Value::Varchar(id) if id.len() == 36), "{:?}", rows[0][0]);
+        assert_eq!(rows[0][1], Value::Varchar("active".to_string()));
+        assert!(matches!(rows[0][2], Value::Timestamp(_)), "{:?}", rows[0][2]);
+        assert_eq!(rows[0][3], Value::Varchar("SERIALIZABLE".to_string()));
+        assert_eq!(rows[0][4], Value::Boolean(false));
+
+        // The transaction leaves the table once it commits
+        done_tx.send(()).unwrap();
+        handle.join().unwrap()?;
+        assert!(db.query("SELECT * FROM prism_transactions()")?.collect()?.rows.is_empty());
+        assert!(db.query("SELECT * FROM prism_transactions(1)").is_err());
+        Ok(())
+    }
+}
+
+/// Tests that a lock poisoned by a panicking thread fails later statements
+/// with an error instead of panicking them too
+#[cfg(test)]
+mod lock_poisoning_tests {
+    use prism::{Database, PrismDBError, PrismDBResult};
+    use std::thread;
+
+    #[test]
+    fn test_poisoned_catalog_lock_returns_errors() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE t (id INTEGER)")?;
+        db.execute("INSERT INTO t VALUES (1), (2)")?;
+
+        let catalog = db.catalog();
+        let poisoner = thread::spawn(move || {
+            let _guard = catalog.write().unwrap();
+            panic!("operator panicked while holding the catalog lock");
+        });
+        assert!(poisoner.join().is_err());
+        assert!(db.catalog().is_poisoned());
+
+        for sql in ["SELECT * FROM t", "INSERT INTO t VALUES (3)", "CREATE TABLE u (id INTEGER)"] {
+            match db.query(sql) {
+                Err(PrismDBError::Internal(message)) => assert!(message.contains("lock"), "{}", message),
+                other => panic!("{} should fail with a poisoning error, got {:?}", sql, other.map(|r| r.row_count())),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_poisoned_schema_lock_returns_errors() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE t (id INTEGER)")?;
+
+        let schema = db.catalog().read().unwrap().get_schema("main")?;
+        let poisoner = thread::spawn(move || {
+            let _guard = schema.write().unwrap();
+            panic!("operator panicked while holding the schema lock");
+        });
+        assert!(poisoner.join().is_err());
+
+        // Looking the table up goes through the schema's lock
+        let result = db.query("SELECT * FROM t");
+        assert!(matches!(result, Err(PrismDBError::Internal(_))), "{:?}", result.map(|r| r.row_count()));
+        Ok(())
+    }
+}
+
+/// Tests that a statement failing partway leaves no partial changes behind
+#[cfg(test)]
+mod statement_atomicity_tests {
+    use prism::{Database, LogicalType, PrismDBResult, Value};
+
+    fn count(db: &Database, sql: &str) -> PrismDBResult<Option<Value>> {
+        Ok(db.query(sql)?.first_value())
+    }
+
+    #[test]
+    fn test_failed_insert_inserts_nothing() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE items (id INTEGER, name VARCHAR NOT NULL)")?;
+
+        // Row 50 of 100 violates NOT NULL
+        let values: Vec<String> = (1..=100)
+            .map(|i| match i {
+                50 => format!("({}, NULL)", i),
+                _ => format!("({}, 'item {}')", i, i),
+            })
+            .collect();
+        let result = db.execute(&format!("INSERT INTO items VALUES {}", values.join(", ")));
+        assert!(result.unwrap_err().to_string().contains("not-null"));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM items")?, Some(Value::BigInt(0)));
+
+        // Rows already in the table stay, and later statements work as usual
+        db.execute("INSERT INTO items VALUES (1, 'first')")?;
+        assert!(db.execute("INSERT INTO items VALUES (2, 'second'), (3, NULL)").is_err());
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM items")?, Some(Value::BigInt(1)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_insert_select_spanning_chunks_inserts_nothing() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        // A duplicate key at the end, chunks after the first rows were inserted
+        let rows = (0..5000)
+            .chain([3])
+            .map(|id| vec![Value::Integer(id), Value::Varchar("row".to_string())])
+            .collect();
+        db.create_table_from_rows(
+            "source",
+            vec![("id".to_string(), LogicalType::Integer), ("name".to_string(), LogicalType::Varchar)],
+            rows,
+        )?;
+        db.execute("CREATE TABLE target (id INTEGER PRIMARY KEY, name VARCHAR)")?;
+
+        assert!(db.execute("INSERT INTO target SELECT id, name FROM source").is_err());
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM target")?, Some(Value::BigInt(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_update_changes_nothing() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE items (id INTEGER, name VARCHAR NOT NULL)")?;
+        db.execute("INSERT INTO items VALUES (1, 'a'), (2, 'b'), (3, 'c')")?;
+
+        // The second row updated sets NULL after the first was already changed
+        let result = db.execute("UPDATE items SET name = CASE WHEN id = 2 THEN NULL ELSE 'z' END");
+        assert!(result.is_err());
+        let rows = db.query("SELECT name FROM items ORDER BY id")?.collect()?.rows;
+        assert_eq!(rows, [["a"], ["b"], ["c"]].map(|[name]| [Value::Varchar(name.to_string())]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_statement_keeps_earlier_transaction_writes() -> PrismDBResult<()> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE items (id INTEGER, name VARCHAR NOT NULL)")?;
+        db.execute("INSERT INTO items VALUES (1, 'a')")?;
+
+        db.execute("BEGIN")?;
+        db.execute("INSERT INTO items VALUES (2, 'b')")?;
+        assert!(db.execute("UPDATE items SET name = CASE WHEN id = 2 THEN NULL ELSE 'z' END").is_err());
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM items")?, Some(Value::BigInt(2)));
+        db.execute("ROLLBACK")?;
+
+        // Only the row committed before the transaction is left
+        let rows = db.query("SELECT id, name FROM items")?.collect()?.rows;
+        assert_eq!(rows, vec![vec![Value::Integer(1), Value::Varchar("a".to_string())]]);
+        Ok(())
+    }
+}
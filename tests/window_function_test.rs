@@ -0,0 +1,403 @@
+//! Tests for window functions computed by the WINDOW operator and QUALIFY
+
+use prism::{PrismDBResult, Value};
+
+mod common;
+use common::{database, text};
+
+const EMP: &[&str] = &[
+    "CREATE TABLE emp (name VARCHAR, dept VARCHAR, salary INTEGER)",
+    "INSERT INTO emp VALUES ('a', 'x', 100), ('b', 'x', 300), ('c', 'x', 200), \
+     ('d', 'y', 50), ('e', 'y', 70), ('f', 'z', 10), ('g', 'x', 200)",
+];
+
+#[test]
+fn test_qualify_on_row_number() -> PrismDBResult<()> {
+    let db = database(EMP)?;
+    let result = db.query(
+        "SELECT *, ROW_NUMBER() OVER (PARTITION BY dept ORDER BY salary) rn FROM emp \
+         QUALIFY rn <= 2 ORDER BY dept, rn",
+    )?;
+    assert_eq!(result.columns.len(), 4);
+    assert_eq!(result.columns[3].name, "rn");
+    assert_eq!(
+        result.collect()?.rows,
+        [
+            [text("a"), text("x"), Value::Integer(100), Value::BigInt(1)],
+            [text("c"), text("x"), Value::Integer(200), Value::BigInt(2)],
+            [text("d"), text("y"), Value::Integer(50), Value::BigInt(1)],
+            [text("e"), text("y"), Value::Integer(70), Value::BigInt(2)],
+            [text("f"), text("z"), Value::Integer(10), Value::BigInt(1)],
+        ]
+    );
+
+    // QUALIFY can also name the window function itself
+    assert_eq!(
+        db.query("SELECT name FROM emp QUALIFY ROW_NUMBER() OVER (ORDER BY salary DESC) = 1")?
+            .collect()?
+            .rows,
+        [[text("b")]]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_window_functions_in_select_list() -> PrismDBResult<()> {
+    let db = database(EMP)?;
+
+    // Ties share a rank; RANK leaves a gap after them, DENSE_RANK does not
+    assert_eq!(
+        db.query(
+            "SELECT name, RANK() OVER (PARTITION BY dept ORDER BY salary DESC) AS r, \
+             DENSE_RANK() OVER (PARTITION BY dept ORDER BY salary DESC) AS dr \
+             FROM emp WHERE dept = 'x' ORDER BY name",
+        )?
+        .collect()?
+        .rows,
+        [
+            [text("a"), Value::BigInt(4), Value::BigInt(3)],
+            [text("b"), Value::BigInt(1), Value::BigInt(1)],
+            [text("c"), Value::BigInt(2), Value::BigInt(2)],
+            [text("g"), Value::BigInt(2), Value::BigInt(2)],
+        ]
+    );
+
+    // Without ORDER BY the frame is the whole partition
+    assert_eq!(
+        db.query(
+            "SELECT name, SUM(salary) OVER (PARTITION BY dept) AS total, COUNT(*) OVER () AS n \
+             FROM emp WHERE salary < 100 ORDER BY name",
+        )?
+        .collect()?
+        .rows,
+        [
+            [text("d"), Value::Double(120.0), Value::BigInt(3)],
+            [text("e"), Value::Double(120.0), Value::BigInt(3)],
+            [text("f"), Value::Double(10.0), Value::BigInt(3)],
+        ]
+    );
+
+    assert_eq!(
+        db.query(
+            "SELECT name, salary, SUM(salary) OVER (ORDER BY salary ROWS BETWEEN 1 PRECEDING AND CURRENT ROW) s \
+             FROM emp WHERE salary < 150 ORDER BY salary",
+        )?
+        .collect()?
+        .rows,
+        [
+            [text("f"), Value::Integer(10), Value::Double(10.0)],
+            [text("d"), Value::Integer(50), Value::Double(60.0)],
+            [text("e"), Value::Integer(70), Value::Double(120.0)],
+            [text("a"), Value::Integer(100), Value::Double(170.0)],
+        ]
+    );
+
+    // Windows over grouped rows
+    assert_eq!(
+        db.query(
+            "SELECT dept, RANK() OVER (ORDER BY SUM(salary) DESC) AS r FROM emp GROUP BY dept ORDER BY r",
+        )?
+        .collect()?
+        .rows,
+        [
+            [text("x"), Value::BigInt(1)],
+            [text("y"), Value::BigInt(2)],
+            [text("z"), Value::BigInt(3)],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_filter_on_partition_column_runs_before_window() -> PrismDBResult<()> {
+    let db = database(EMP)?;
+    let sql = "SELECT * FROM (SELECT name, dept, ROW_NUMBER() OVER (PARTITION BY dept ORDER BY salary) AS rn \
+               FROM emp) t WHERE dept = 'y' AND rn = 1";
+    assert_eq!(db.query(sql)?.collect()?.rows, [[text("d"), text("y"), Value::BigInt(1)]]);
+
+    // The partition filter reaches the scan, the filter on the window result stays above it
+    let plan = db.query(&format!("EXPLAIN {}", sql))?.collect()?.rows[0][0].to_string();
+    let window = plan.find("WINDOW").expect(&plan);
+    assert!(plan[..window].contains("= 1"), "{}", plan);
+    assert!(plan[window..].contains("filters: emp.dept = 'y'"), "{}", plan);
+    Ok(())
+}
+
+const LEDGER: &[&str] = &[
+    "CREATE TABLE ledger (id INTEGER, day INTEGER, amount INTEGER)",
+    "INSERT INTO ledger VALUES (4, 2, 40), (1, 1, 10), (3, 2, 30), (2, 1, 20), (5, 3, 50)",
+];
+
+#[test]
+fn test_row_number_and_rank_with_ties() -> PrismDBResult<()> {
+    let db = database(LEDGER)?;
+    // Days 1 and 2 each have two rows, which tie on day
+    assert_eq!(
+        db.query(
+            "SELECT id, ROW_NUMBER() OVER (ORDER BY day, id) AS rn, RANK() OVER (ORDER BY day) AS r, \
+             DENSE_RANK() OVER (ORDER BY day) AS dr FROM ledger ORDER BY id",
+        )?
+        .collect()?
+        .rows,
+        [
+            [Value::Integer(1), Value::BigInt(1), Value::BigInt(1), Value::BigInt(1)],
+            [Value::Integer(2), Value::BigInt(2), Value::BigInt(1), Value::BigInt(1)],
+            [Value::Integer(3), Value::BigInt(3), Value::BigInt(3), Value::BigInt(2)],
+            [Value::Integer(4), Value::BigInt(4), Value::BigInt(3), Value::BigInt(2)],
+            [Value::Integer(5), Value::BigInt(5), Value::BigInt(5), Value::BigInt(3)],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_running_sum() -> PrismDBResult<()> {
+    let db = database(LEDGER)?;
+    // 10, 10+20, 30+30, 60+40, 100+50
+    assert_eq!(
+        db.query("SELECT id, SUM(amount) OVER (ORDER BY id) AS total FROM ledger ORDER BY id")?
+            .collect()?
+            .rows,
+        [
+            [Value::Integer(1), Value::Double(10.0)],
+            [Value::Integer(2), Value::Double(30.0)],
+            [Value::Integer(3), Value::Double(60.0)],
+            [Value::Integer(4), Value::Double(100.0)],
+            [Value::Integer(5), Value::Double(150.0)],
+        ]
+    );
+
+    // The default frame runs to the last peer, so rows of the same day share a total;
+    // a ROWS frame stops at the current row
+    assert_eq!(
+        db.query(
+            "SELECT id, SUM(amount) OVER (ORDER BY day) AS by_day, \
+             COUNT(*) OVER (ORDER BY day, id ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS n \
+             FROM ledger ORDER BY id",
+        )?
+        .collect()?
+        .rows,
+        [
+            [Value::Integer(1), Value::Double(30.0), Value::BigInt(1)],
+            [Value::Integer(2), Value::Double(30.0), Value::BigInt(2)],
+            [Value::Integer(3), Value::Double(100.0), Value::BigInt(3)],
+            [Value::Integer(4), Value::Double(100.0), Value::BigInt(4)],
+            [Value::Integer(5), Value::Double(150.0), Value::BigInt(5)],
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_frame_exclude_current_row() -> PrismDBResult<()> {
+    let db = database(LEDGER)?;
+
+    // A moving average of the neighbours: ids 1..5 have amounts 10..50
+    assert_eq!(
+        db.query(
+            "SELECT id, AVG(amount) OVER (ORDER BY id ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING \
+             EXCLUDE CURRENT ROW) AS neighbours, \
+             SUM(amount) OVER (ORDER BY id ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING \
+             EXCLUDE CURRENT ROW) AS others \
+             FROM ledger ORDER BY id",
+        )?
+        .collect()?
+        .rows,
+        [
+            [Value::Integer(1), Value::Double(20.0), Value::Double(140.0)],
+            [Value::Integer(2), Value::Double(20.0), Value::Double(130.0)],
+            [Value::Integer(3), Value::Double(30.0), Value::Double(120.0)],
+            [Value::Integer(4), Value::Double(40.0), Value::Double(110.0)],
+            [Value::Integer(5), Value::Double(40.0), Value::Double(100.0)],
+        ]
+    );
+
+    // A RANGE frame still reaches the last peer; only the row itself is left out
+    assert_eq!(
+        db.query(
+            "SELECT id, SUM(amount) OVER (ORDER BY day RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW \
+             EXCLUDE CURRENT ROW) FROM ledger ORDER BY id",
+        )?
+        .collect()?
+        .rows,
+        [
+            [Value::Integer(1), Value::Double(20.0)],
+            [Value::Integer(2), Value::Double(10.0)],
+            [Value::Integer(3), Value::Double(70.0)],
+            [Value::Integer(4), Value::Double(60.0)],
+            [Value::Integer(5), Value::Double(100.0)],
+        ]
+    );
+
+    // A frame left empty has no first value
+    let rows = db
+        .query(
+            "SELECT FIRST_VALUE(id) OVER (ORDER BY id ROWS BETWEEN CURRENT ROW AND CURRENT ROW \
+             EXCLUDE CURRENT ROW) FROM ledger",
+        )?
+        .collect()?
+        .rows;
+    assert!(rows.iter().all(|row| row[0] == Value::Null));
+    Ok(())
+}
+
+#[test]
+fn test_frame_exclude_group_and_ties() -> PrismDBResult<()> {
+    let db = database(LEDGER)?;
+
+    // Days 1, 2 and 3 hold amounts {10, 20}, {30, 40} and {50}, 150 in all
+    assert_eq!(
+        db.query(
+            "SELECT id, \
+             SUM(amount) OVER (ORDER BY day ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING \
+             EXCLUDE GROUP) AS other_days, \
+             SUM(amount) OVER (ORDER BY day ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING \
+             EXCLUDE TIES) AS without_ties, \
+             COUNT(*) OVER (ORDER BY day ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING \
+             EXCLUDE NO OTHERS) AS everyone \
+             FROM ledger ORDER BY id",
+        )?
+        .collect()?
+        .rows,
+        [
+            [Value::Integer(1), Value::Double(120.0), Value::Double(130.0), Value::BigInt(5)],
+            [Value::Integer(2), Value::Double(120.0), Value::Double(140.0), Value::BigInt(5)],
+            [Value::Integer(3), Value::Double(80.0), Value::Double(110.0), Value::BigInt(5)],
+            [Value::Integer(4), Value::Double(80.0), Value::Double(120.0), Value::BigInt(5)],
+            [Value::Integer(5), Value::Double(100.0), Value::Double(150.0), Value::BigInt(5)],
+        ]
+    );
+
+    // The largest amount of any earlier day
+    assert_eq!(
+        db.query(
+            "SELECT id, MAX(amount) OVER (ORDER BY day RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW \
+             EXCLUDE GROUP) FROM ledger ORDER BY id",
+        )?
+        .collect()?
+        .rows,
+        [
+            [Value::Integer(1), Value::Null],
+            [Value::Integer(2), Value::Null],
+            [Value::Integer(3), Value::Integer(20)],
+            [Value::Integer(4), Value::Integer(20)],
+            [Value::Integer(5), Value::Integer(40)],
+        ]
+    );
+
+    assert!(db
+        .query("SELECT SUM(amount) OVER (ORDER BY day ROWS UNBOUNDED PRECEDING EXCLUDE OTHERS) FROM ledger")
+        .is_err());
+    Ok(())
+}
+
+/// Tests for planning ROW_NUMBER pagination and ORDER BY ... LIMIT as a Top-N
+#[cfg(test)]
+mod row_number_top_n_tests {
+    use crate::common::{explain, rows};
+    use prism::{Database, PrismDBResult, Value};
+
+    /// Table holding ids 1..=5000 in scrambled order, with a score that repeats
+    fn scores_database() -> PrismDBResult<Database> {
+        let mut db = Database::new_in_memory()?;
+        db.execute("CREATE TABLE scores (id INTEGER, score INTEGER, team VARCHAR)")?;
+        let values: Vec<String> = (0..5000)
+            .map(|i| (i * 7919) % 5000 + 1)
+            .map(|id| {
+                let score = if id % 100 == 0 { "NULL".to_string() } else { (id % 1000).to_string() };
+                format!("({}, {}, 't{}')", id, score, id % 3)
+            })
+            .collect();
+        db.execute(&format!("INSERT INTO scores VALUES {}", values.join(", ")))?;
+        Ok(db)
+    }
+
+    #[test]
+    fn test_row_number_page_becomes_top_n() -> PrismDBResult<()> {
+        let db = scores_database()?;
+        let sql = "SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS rn FROM scores \
+               QUALIFY rn BETWEEN 21 AND 30 ORDER BY rn";
+
+        let plan = explain(&db, sql)?;
+        assert!(plan.contains("TOP_N (~30 rows)"), "{}", plan);
+        assert!(plan.contains("WINDOW (~30 rows)"), "{}", plan);
+
+        let page = rows(&db, sql)?;
+        let expected: Vec<Vec<Value>> = (21..=30).map(|i| vec![Value::Integer(i), Value::BigInt(i as i64)]).collect();
+        assert_eq!(page, expected);
+
+        // Other spellings of the upper bound
+        for sql in [
+            "SELECT id FROM scores QUALIFY ROW_NUMBER() OVER (ORDER BY id DESC) <= 3 ORDER BY id",
+            "SELECT id FROM scores QUALIFY ROW_NUMBER() OVER (ORDER BY id DESC) < 4 ORDER BY id",
+            "SELECT id FROM scores QUALIFY 3 >= ROW_NUMBER() OVER (ORDER BY id DESC) ORDER BY id",
+        ] {
+            assert!(explain(&db, sql)?.contains("TOP_N (~3 rows)"), "{}", sql);
+            assert_eq!(rows(&db, sql)?, [[Value::Integer(4998)], [Value::Integer(4999)], [Value::Integer(5000)]]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_number_page_matches_offset_limit() -> PrismDBResult<()> {
+        let db = scores_database()?;
+
+        // The score repeats and has NULLs, which sort last ascending and first descending
+        for (order, page) in [("score, id", (101, 125)), ("score DESC, id", (1, 60)), ("score DESC, id DESC", (4990, 5000))] {
+            let paged = rows(
+                &db,
+                &format!(
+                    "SELECT id, score FROM scores QUALIFY ROW_NUMBER() OVER (ORDER BY {}) BETWEEN {} AND {} ORDER BY {}",
+                    order, page.0, page.1, order
+                ),
+            )?;
+            let expected = rows(
+                &db,
+                &format!(
+                    "SELECT id, score FROM scores ORDER BY {} LIMIT {} OFFSET {}",
+                    order,
+                    page.1 - page.0 + 1,
+                    page.0 - 1
+                ),
+            )?;
+            assert_eq!(paged, expected, "{}", order);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_row_number_without_bound_is_not_rewritten() -> PrismDBResult<()> {
+        let db = scores_database()?;
+
+        // A partitioned window numbers each partition, so it needs every row
+        let sql = "SELECT id FROM scores QUALIFY ROW_NUMBER() OVER (PARTITION BY team ORDER BY id) <= 2 ORDER BY id";
+        assert!(!explain(&db, sql)?.contains("TOP_N"));
+        assert_eq!(rows(&db, sql)?, [1, 2, 3, 4, 5, 6].map(|id| vec![Value::Integer(id)]));
+
+        // Only a lower bound keeps all but the first rows
+        let sql = "SELECT COUNT(*) FROM (SELECT id FROM scores QUALIFY ROW_NUMBER() OVER (ORDER BY id) > 10) AS rest";
+        assert!(!explain(&db, sql)?.contains("TOP_N"));
+        assert_eq!(db.query(sql)?.first_value(), Some(Value::BigInt(4990)));
+
+        // Other window functions over the same rows need the whole input
+        let sql = "SELECT id, COUNT(*) OVER () AS total FROM scores \
+               QUALIFY ROW_NUMBER() OVER (ORDER BY id) <= 1";
+        assert!(!explain(&db, sql)?.contains("TOP_N"));
+        assert_eq!(rows(&db, sql)?, [[Value::Integer(1), Value::BigInt(5000)]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_order_by_limit_is_top_n() -> PrismDBResult<()> {
+        let db = scores_database()?;
+
+        let plan = explain(&db, "SELECT id FROM scores ORDER BY id DESC LIMIT 5 OFFSET 2")?;
+        assert!(plan.contains("TOP_N (~7 rows)"), "{}", plan);
+        assert!(!plan.contains("ORDER_BY"), "{}", plan);
+        let ids = rows(&db, "SELECT id FROM scores ORDER BY id DESC LIMIT 5 OFFSET 2")?;
+        assert_eq!(ids, [4998, 4997, 4996, 4995, 4994].map(|id| vec![Value::Integer(id)]));
+
+        assert!(rows(&db, "SELECT id FROM scores ORDER BY id LIMIT 0")?.is_empty());
+        Ok(())
+    }
+}